@@ -1,6 +1,10 @@
 use anyhow::{Context, Result};
 use cargo_metadata::camino::Utf8PathBuf;
 use clap::Parser;
+use sp1_helper::{
+    docker_volume, encoded_guest_rustflags, guest_cargo_args, resolve_docker_image, user_rustflags,
+    BUILD_TARGET,
+};
 use std::{
     fs,
     io::{BufRead, BufReader},
@@ -8,18 +12,68 @@ use std::{
     thread,
 };
 
-fn get_docker_image() -> String {
-    // Get the docker image name from the environment variable
-    std::env::var("SP1_DOCKER_IMAGE")
-        .unwrap_or_else(|_| "ghcr.io/succinctlabs/sp1:latest".to_string())
-}
-
 #[derive(Parser)]
 pub(crate) struct BuildArgs {
     #[clap(long, action, help = "Ignore Rust version check.")]
     pub(crate) docker: bool,
+    #[clap(
+        long,
+        action,
+        help = "The docker image to build in, including the registry and the tag or digest. \
+                Implies --docker. Defaults to SP1_DOCKER_IMAGE, or the latest SP1 image."
+    )]
+    pub(crate) docker_image: Option<String>,
     #[clap(long, action, help = "Ignore Rust version check.")]
     pub(crate) ignore_rust_version: bool,
+    #[clap(
+        long,
+        action,
+        help = "Enable the Zbb bit manipulation instructions (clz, ctz, cpop and rev8)."
+    )]
+    pub(crate) zbb: bool,
+    #[clap(long, action, help = "Build only the given binary of the program.")]
+    pub(crate) binary: Option<String>,
+    #[clap(
+        long,
+        action,
+        value_delimiter = ',',
+        help = "Comma separated list of features to activate."
+    )]
+    pub(crate) features: Vec<String>,
+    #[clap(long, action, help = "Do not activate the default features.")]
+    pub(crate) no_default_features: bool,
+    #[clap(
+        long,
+        action,
+        default_value = "riscv32im-succinct-zkvm-elf",
+        help = "The name of the ELF written to the elf directory."
+    )]
+    pub(crate) elf_name: String,
+    #[clap(
+        long,
+        action,
+        help = "The directory the ELF is written to, instead of the elf directory next to the \
+                target directory. Created if it doesn't exist."
+    )]
+    pub(crate) output_directory: Option<String>,
+    #[clap(
+        long,
+        action,
+        allow_hyphen_values = true,
+        help = "A rustc flag for the program, such as `-Copt-level=z`. Can be repeated. Appended to \
+                the flags set in CARGO_ENCODED_RUSTFLAGS or RUSTFLAGS."
+    )]
+    pub(crate) rustflags: Vec<String>,
+}
+
+impl BuildArgs {
+    /// The rustc flags added to those of the programs: the flags set by the user for cargo,
+    /// followed by the `--rustflags` ones.
+    fn extra_rustflags(&self) -> Vec<String> {
+        let mut rustflags = user_rustflags();
+        rustflags.extend(self.rustflags.iter().cloned());
+        rustflags
+    }
 }
 
 pub fn build_program(args: &BuildArgs) -> Result<Utf8PathBuf> {
@@ -28,9 +82,9 @@ pub fn build_program(args: &BuildArgs) -> Result<Utf8PathBuf> {
     let root_package = metadata.root_package();
     let root_package_name = root_package.as_ref().map(|p| &p.name);
 
-    let build_target = "riscv32im-succinct-zkvm-elf";
-    if args.docker {
-        let image = get_docker_image();
+    if args.docker || args.docker_image.is_some() {
+        let image = resolve_docker_image(args.docker_image.as_deref());
+        println!("Building in docker image {}", image);
 
         let docker_check = Command::new("docker")
             .args(["info"])
@@ -44,19 +98,47 @@ pub fn build_program(args: &BuildArgs) -> Result<Utf8PathBuf> {
             exit(1);
         }
 
-        let workspace_root_path = format!("{}:/root/program", metadata.workspace_root);
-        let mut child_args = vec![
-            "run",
-            "--rm",
-            "-v",
-            workspace_root_path.as_str(),
-            image.as_str(),
-            "prove",
-            "build",
-        ];
+        let workspace_root_path = docker_volume(metadata.workspace_root.as_str(), "/root/program");
+        let mut child_args = vec!["run", "--rm", "-v", workspace_root_path.as_str()];
+
+        // If the target directory is outside of the workspace (e.g. set through
+        // `CARGO_TARGET_DIR`), mount it as well so the artifacts end up where the host expects.
+        let target_dir_path = docker_volume(metadata.target_directory.as_str(), "/root/target");
+        if !metadata
+            .target_directory
+            .starts_with(&metadata.workspace_root)
+        {
+            child_args.extend([
+                "-v",
+                target_dir_path.as_str(),
+                "-e",
+                "CARGO_TARGET_DIR=/root/target",
+            ]);
+        }
+        // The build in the container appends the flags of its environment to those of the program.
+        let rustflags_env = format!(
+            "CARGO_ENCODED_RUSTFLAGS={}",
+            args.extra_rustflags().join("\x1f")
+        );
+        child_args.extend(["-e", rustflags_env.as_str()]);
+        child_args.extend([image.as_str(), "prove", "build"]);
         if args.ignore_rust_version {
             child_args.push("--ignore-rust-version");
         }
+        if args.zbb {
+            child_args.push("--zbb");
+        }
+        if let Some(binary) = &args.binary {
+            child_args.extend(["--binary", binary.as_str()]);
+        }
+        let features = args.features.join(",");
+        if !args.features.is_empty() {
+            child_args.extend(["--features", features.as_str()]);
+        }
+        if args.no_default_features {
+            child_args.push("--no-default-features");
+        }
+        child_args.extend(["--elf-name", args.elf_name.as_str()]);
 
         let mut child = Command::new("docker")
             .args(&child_args)
@@ -86,23 +168,17 @@ pub fn build_program(args: &BuildArgs) -> Result<Utf8PathBuf> {
             exit(result.code().unwrap_or(1))
         }
     } else {
-        let rust_flags = [
-            "-C",
-            "passes=loweratomic",
-            "-C",
-            "link-arg=-Ttext=0x00200800",
-            "-C",
-            "panic=abort",
-        ];
-
-        let mut cargo_args = vec!["build", "--release", "--target", build_target, "--locked"];
-        if args.ignore_rust_version {
-            cargo_args.push("--ignore-rust-version");
-        }
+        let rust_flags = encoded_guest_rustflags(args.zbb, &args.extra_rustflags());
+        let cargo_args = guest_cargo_args(
+            args.ignore_rust_version,
+            args.binary.as_deref(),
+            &args.features,
+            args.no_default_features,
+        );
 
         let result = Command::new("cargo")
             .env("RUSTUP_TOOLCHAIN", "succinct")
-            .env("CARGO_ENCODED_RUSTFLAGS", rust_flags.join("\x1f"))
+            .env("CARGO_ENCODED_RUSTFLAGS", rust_flags)
             .args(&cargo_args)
             .status()
             .context("Failed to run cargo command.")?;
@@ -115,12 +191,17 @@ pub fn build_program(args: &BuildArgs) -> Result<Utf8PathBuf> {
 
     let elf_path = metadata
         .target_directory
-        .join(build_target)
+        .join(BUILD_TARGET)
         .join("release")
-        .join(root_package_name.unwrap());
-    let elf_dir = metadata.target_directory.parent().unwrap().join("elf");
+        .join(args.binary.as_ref().or(root_package_name).unwrap());
+    // The docker build is not given the output directory, which may be outside of the mounted
+    // workspace: the ELF is copied there from the target directory here.
+    let elf_dir = match &args.output_directory {
+        Some(output_directory) => Utf8PathBuf::from(output_directory),
+        None => metadata.target_directory.parent().unwrap().join("elf"),
+    };
     fs::create_dir_all(&elf_dir)?;
-    let result_elf_path = elf_dir.join("riscv32im-succinct-zkvm-elf");
+    let result_elf_path = elf_dir.join(&args.elf_name);
     fs::copy(elf_path, &result_elf_path)?;
 
     Ok(result_elf_path)
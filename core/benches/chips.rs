@@ -0,0 +1,58 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use p3_baby_bear::BabyBear;
+use sp1_core::air::MachineAir;
+use sp1_core::runtime::{ExecutionRecord, Opcode, RecordBuilder, ShardingConfig};
+use sp1_core::stark::RiscvAir;
+use sp1_core::utils::BabyBearPoseidon2;
+
+/// Benchmarks the trace generation of the heaviest chips, on synthetic records from
+/// [RecordBuilder] with `1 << 16` events of the chip.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chips");
+    group.sample_size(10);
+    let num_events = 1 << 16;
+    let records = [
+        (
+            "CPU",
+            RecordBuilder::new(0)
+                .alu(Opcode::ADD, num_events / 2)
+                .memory(0x1000..0x10_0000, num_events / 2),
+        ),
+        (
+            "DivRem",
+            RecordBuilder::new(0)
+                .alu(Opcode::DIVU, num_events / 2)
+                .alu(Opcode::REM, num_events / 2),
+        ),
+        // A permutation takes 24 rows.
+        (
+            "KeccakPermute",
+            RecordBuilder::new(0).keccak(num_events / 24),
+        ),
+    ];
+
+    let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+    for (name, builder) in records {
+        let shard = machine
+            .shard(builder.build(), &ShardingConfig::default())
+            .remove(0);
+        let chip = machine
+            .chips()
+            .iter()
+            .find(|chip| chip.name() == name)
+            .unwrap();
+        group.bench_function(format!("generate_trace:{}", name), |b| {
+            b.iter(|| {
+                MachineAir::<BabyBear>::generate_trace(
+                    chip,
+                    black_box(&shard),
+                    &mut ExecutionRecord::default(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use p3_maybe_rayon::prelude::*;
+use sp1_core::runtime::{Program, Runtime};
+use sp1_core::utils::SP1CoreOpts;
+
+/// Benchmarks the two executions of proving, recording the events of the second one either
+/// sequentially from the start of the program or from the snapshots of the first one, in parallel.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("replay");
+    group.sample_size(10);
+    let opts = SP1CoreOpts {
+        shard_size: 1 << 14,
+        shard_batch_size: 0,
+        ..Default::default()
+    };
+    let programs = ["fibonacci", "sha2"];
+    for p in programs {
+        let elf_path = format!("../tests/{}/elf/riscv32im-succinct-zkvm-elf", p);
+        let program = Program::from_elf(&elf_path);
+        group.bench_function(format!("sequential:{}", p), |b| {
+            b.iter(|| {
+                let mut runtime = Runtime::new(black_box(program.clone()), opts);
+                runtime.run_untraced().unwrap();
+                let mut runtime = Runtime::new(program.clone(), opts);
+                runtime.execute_record().unwrap()
+            })
+        });
+        group.bench_function(format!("snapshots:{}", p), |b| {
+            b.iter(|| {
+                let mut runtime = Runtime::new(black_box(program.clone()), opts);
+                let snapshots = runtime.execute_snapshots().unwrap();
+                (0..snapshots.shards.len())
+                    .into_par_iter()
+                    .map(|index| {
+                        let mut runtime =
+                            Runtime::from_snapshot(program.clone(), &snapshots, index, opts);
+                        runtime.execute_record().unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sp1_core::io::SP1Stdin;
+use sp1_core::runtime::Program;
+use sp1_core::stark::RiscvAir;
+use sp1_core::utils::{prove, BabyBearPoseidon2, SP1CoreOpts};
+
+/// Compares the sequential and batched verification of a proof with many shards.
+///
+/// On a multi-core machine, `verify_batched` verifies the shards concurrently, and its speedup
+/// over `verify` grows with the number of shards up to the number of cores.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify");
+    group.sample_size(10);
+    let programs = ["fibonacci"];
+    for p in programs {
+        let elf_path = format!("../programs/demo/{}/elf/riscv32im-succinct-zkvm-elf", p);
+        let program = Program::from_elf(&elf_path);
+        let mut opts = SP1CoreOpts::default();
+        opts.shard_size = 1 << 14;
+        let (proof, _) = prove(
+            program.clone(),
+            &SP1Stdin::new(),
+            BabyBearPoseidon2::new(),
+            opts,
+        )
+        .unwrap();
+        let num_shards = proof.shard_proofs.len();
+
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let (_, vk) = machine.setup(&program);
+        group.bench_function(format!("verify:{}:{}", p, num_shards), |b| {
+            b.iter(|| {
+                let mut challenger = machine.config().challenger();
+                machine
+                    .verify(&vk, black_box(&proof), &mut challenger)
+                    .unwrap()
+            })
+        });
+        group.bench_function(format!("verify_batched:{}:{}", p, num_shards), |b| {
+            b.iter(|| {
+                let mut challenger = machine.config().challenger();
+                machine
+                    .verify_batched(&vk, black_box(&proof), &mut challenger)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -2,10 +2,26 @@ use p3_air::BaseAir;
 use p3_field::Field;
 use p3_matrix::dense::RowMajorMatrix;
 
-use crate::{runtime::Program, stark::MachineRecord};
+use crate::{
+    runtime::Program,
+    stark::{MachineRecord, SetupError},
+};
 
 pub use sp1_derive::MachineAir;
 
+/// The widest packing of field elements the prover evaluates constraints on, of 16 BabyBear
+/// elements with AVX-512.
+///
+/// The quotient domain of a chip, of `2^log_quotient_degree` times the height of its traces, must
+/// have at least this many points.
+pub const MAX_PACKING_WIDTH: usize = 16;
+
+/// The default of [MachineAir::min_trace_height].
+///
+/// A chip with interactions has constraints of degree at least 3, so a quotient domain at least
+/// twice the height of its traces.
+pub const DEFAULT_MIN_TRACE_HEIGHT: usize = MAX_PACKING_WIDTH / 2;
+
 /// An AIR that is part of a multi table AIR arithmetization.
 pub trait MachineAir<F: Field>: BaseAir<F> {
     /// The execution record containing events for producing the air trace.
@@ -31,15 +47,33 @@ pub trait MachineAir<F: Field>: BaseAir<F> {
     /// Whether this execution record contains events for this air.
     fn included(&self, shard: &Self::Record) -> bool;
 
+    /// The fewest rows of the traces of this air, a power of two to which shorter traces are
+    /// padded.
+    ///
+    /// Shifted left by the log quotient degree of the chip, it must be at least
+    /// [MAX_PACKING_WIDTH], which [crate::stark::Chip::new] checks.
+    fn min_trace_height(&self) -> usize {
+        DEFAULT_MIN_TRACE_HEIGHT
+    }
+
     /// The width of the preprocessed trace.
     fn preprocessed_width(&self) -> usize {
         0
     }
 
     /// Generate the preprocessed trace given a specific program.
+    ///
+    /// A chip with preprocessed columns may return `None` for a program it holds nothing of: it
+    /// is then left out of the keys of the program, and must not be included in its shards.
     fn generate_preprocessed_trace(&self, _program: &Self::Program) -> Option<RowMajorMatrix<F>> {
         None
     }
+
+    /// Checks that the air can hold its part of `program`, before its preprocessed trace is
+    /// generated by [crate::stark::StarkMachine::try_setup].
+    fn check_program(&self, _program: &Self::Program) -> Result<(), SetupError> {
+        Ok(())
+    }
 }
 
 pub trait MachineProgram<F>: Send + Sync {
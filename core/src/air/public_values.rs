@@ -24,6 +24,15 @@ pub struct PublicValues<W, T> {
     /// The hash of all the bytes that the guest program has written to public values.
     pub committed_value_digest: [W; PV_DIGEST_NUM_WORDS],
 
+    /// The hash of the input buffers that the guest program has read, see
+    /// [crate::io::SP1Stdin::digest]. Zero if the program was built without the `commit-input`
+    /// feature of `sp1-zkvm`.
+    pub input_digest: [W; PV_DIGEST_NUM_WORDS],
+
+    /// The hash of the config the guest program was run with, see
+    /// [crate::io::SP1Stdin::config_digest].
+    pub config_digest: [W; PV_DIGEST_NUM_WORDS],
+
     /// The hash of all deferred proofs that have been witnessed in the VM. It will be rebuilt in
     /// recursive verification as the proofs get verified. The hash itself is a rolling poseidon2
     /// hash of each proof+vkey hash and the previous hash which is initially zero.
@@ -40,6 +49,10 @@ pub struct PublicValues<W, T> {
 
     /// The shard number.
     pub shard: T,
+
+    /// The bitmap of the features the proof was generated with, the same in all the shards. See
+    /// [crate::features].
+    pub features: T,
 }
 
 impl PublicValues<u32, u32> {
@@ -50,6 +63,16 @@ impl PublicValues<u32, u32> {
             .committed_value_digest
             .iter()
             .flat_map(|w| Word::<F>::from(*w).into_iter())
+            .chain(
+                self.input_digest
+                    .iter()
+                    .flat_map(|w| Word::<F>::from(*w).into_iter()),
+            )
+            .chain(
+                self.config_digest
+                    .iter()
+                    .flat_map(|w| Word::<F>::from(*w).into_iter()),
+            )
             .chain(
                 self.deferred_proofs_digest
                     .iter()
@@ -60,6 +83,7 @@ impl PublicValues<u32, u32> {
             .chain(once(F::from_canonical_u32(self.next_pc)))
             .chain(once(F::from_canonical_u32(self.exit_code)))
             .chain(once(F::from_canonical_u32(self.shard)))
+            .chain(once(F::from_canonical_u32(self.features)))
             .collect_vec();
 
         assert!(
@@ -81,6 +105,10 @@ impl<T: Clone + Debug> PublicValues<Word<T>, T> {
 
         let committed_value_digest = array::from_fn(|_| Word::from_iter(&mut iter));
 
+        let input_digest = array::from_fn(|_| Word::from_iter(&mut iter));
+
+        let config_digest = array::from_fn(|_| Word::from_iter(&mut iter));
+
         let deferred_proofs_digest = iter
             .by_ref()
             .take(POSEIDON_NUM_WORDS)
@@ -89,24 +117,30 @@ impl<T: Clone + Debug> PublicValues<Word<T>, T> {
             .unwrap();
 
         // Collecting the remaining items into a tuple.  Note that it is only getting the first
-        // four items, as the rest would be padded values.
+        // five items, as the rest would be padded values.
         let remaining_items = iter.collect_vec();
-        if remaining_items.len() < 4 {
+        if remaining_items.len() < 5 {
             panic!("Invalid number of items in the serialized vector.");
         }
 
-        let [start_pc, next_pc, exit_code, shard] = match &remaining_items.as_slice()[0..4] {
-            [start_pc, next_pc, exit_code, shard] => [start_pc, next_pc, exit_code, shard],
-            _ => unreachable!(),
-        };
+        let [start_pc, next_pc, exit_code, shard, features] =
+            match &remaining_items.as_slice()[0..5] {
+                [start_pc, next_pc, exit_code, shard, features] => {
+                    [start_pc, next_pc, exit_code, shard, features]
+                }
+                _ => unreachable!(),
+            };
 
         Self {
             committed_value_digest,
+            input_digest,
+            config_digest,
             deferred_proofs_digest,
             start_pc: start_pc.to_owned(),
             next_pc: next_pc.to_owned(),
             exit_code: exit_code.to_owned(),
             shard: shard.to_owned(),
+            features: features.to_owned(),
         }
     }
 }
@@ -119,6 +153,22 @@ impl<F: PrimeField32> PublicValues<Word<F>, F> {
             .flat_map(|w| w.into_iter().map(|f| f.as_canonical_u32() as u8))
             .collect_vec()
     }
+
+    /// Returns the input digest as a vector of little-endian bytes.
+    pub fn input_digest_bytes(&self) -> Vec<u8> {
+        self.input_digest
+            .iter()
+            .flat_map(|w| w.into_iter().map(|f| f.as_canonical_u32() as u8))
+            .collect_vec()
+    }
+
+    /// Returns the config digest as a vector of little-endian bytes.
+    pub fn config_digest_bytes(&self) -> Vec<u8> {
+        self.config_digest
+            .iter()
+            .flat_map(|w| w.into_iter().map(|f| f.as_canonical_u32() as u8))
+            .collect_vec()
+    }
 }
 
 #[cfg(test)]
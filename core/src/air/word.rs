@@ -16,13 +16,50 @@ use super::SP1AirBuilder;
 pub const WORD_SIZE: usize = 4;
 
 /// A word is a 32-bit value represented in an AIR.
+///
+/// The limbs are the bytes of the value in little-endian order, so that `self[0]` is the least
+/// significant byte. The constructors and accessors below make that order explicit, and should be
+/// preferred to indexing the limbs directly.
 #[derive(
     AlignedBorrow, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
 )]
-#[repr(C)]
+#[repr(transparent)]
 pub struct Word<T>(pub [T; WORD_SIZE]);
 
 impl<T> Word<T> {
+    /// Creates a word from its bytes in little-endian order.
+    pub const fn from_le_bytes(bytes: [T; WORD_SIZE]) -> Self {
+        Word(bytes)
+    }
+
+    /// Creates a word from its bytes in big-endian order.
+    pub fn from_be_bytes(mut bytes: [T; WORD_SIZE]) -> Self {
+        bytes.reverse();
+        Word(bytes)
+    }
+
+    /// Returns the bytes of the word in little-endian order.
+    pub fn to_le_bytes(self) -> [T; WORD_SIZE] {
+        self.0
+    }
+
+    /// Returns the bytes of the word in big-endian order.
+    pub fn to_be_bytes(self) -> [T; WORD_SIZE] {
+        let mut bytes = self.0;
+        bytes.reverse();
+        bytes
+    }
+
+    /// Returns the least significant byte of the word.
+    pub fn least_significant_byte(&self) -> &T {
+        &self.0[0]
+    }
+
+    /// Returns the three most significant bytes of the word, in little-endian order.
+    pub fn upper_bytes(&self) -> &[T] {
+        &self.0[1..]
+    }
+
     /// Applies `f` to each element of the word.
     pub fn map<F, S>(self, f: F) -> Word<S>
     where
@@ -81,6 +118,80 @@ impl<V: Copy> Word<V> {
             .map(|(i, x)| base[i].clone() * *x)
             .sum()
     }
+
+    /// Returns the two 16-bit limbs of the word, least significant first.
+    pub fn to_u16_limbs<AB: AirBuilder<Var = V>>(&self) -> [AB::Expr; 2] {
+        let base = AB::Expr::from_canonical_u32(1 << 8);
+        let [b0, b1, b2, b3] = self.0;
+        [b0.into() + base.clone() * b1, b2.into() + base * b3]
+    }
+
+    /// Returns the byte at the offset selected by the one-hot `flags`, where `flags[i]` selects the
+    /// byte of significance `i`.
+    pub fn select_byte<AB: AirBuilder<Var = V>>(&self, flags: [AB::Expr; WORD_SIZE]) -> AB::Expr {
+        self.0
+            .iter()
+            .zip(flags)
+            .map(|(byte, flag)| flag * *byte)
+            .sum()
+    }
+
+    /// Returns the lower half of the word if `is_lower` is set, and the upper half if `is_upper`
+    /// is set, as two bytes in little-endian order.
+    pub fn select_half<AB: AirBuilder<Var = V>>(
+        &self,
+        is_lower: AB::Expr,
+        is_upper: AB::Expr,
+    ) -> [AB::Expr; 2] {
+        let [b0, b1, b2, b3] = self.0;
+        [
+            is_lower.clone() * b0 + is_upper.clone() * b2,
+            is_lower * b1 + is_upper * b3,
+        ]
+    }
+
+    /// Returns the word with the byte at the offset selected by the one-hot `flags` replaced by
+    /// `byte`.
+    pub fn with_byte<AB: AirBuilder<Var = V>>(
+        &self,
+        byte: AB::Expr,
+        flags: [AB::Expr; WORD_SIZE],
+    ) -> Word<AB::Expr> {
+        let mut flags = flags.into_iter();
+        self.map(|prev| {
+            let flag = flags.next().unwrap();
+            flag.clone() * byte.clone() + (AB::Expr::one() - flag) * prev
+        })
+    }
+
+    /// Returns the word with its lower half replaced by `half` if `is_lower` is set, and its upper
+    /// half if `is_upper` is set. The bytes of `half` are in little-endian order.
+    pub fn with_half<AB: AirBuilder<Var = V>>(
+        &self,
+        half: [AB::Expr; 2],
+        is_lower: AB::Expr,
+        is_upper: AB::Expr,
+    ) -> Word<AB::Expr> {
+        let flags = [is_lower.clone(), is_lower, is_upper.clone(), is_upper];
+        let mut bytes = half.iter().cycle().zip(flags);
+        self.map(|prev| {
+            let (byte, flag) = bytes.next().unwrap();
+            flag.clone() * byte.clone() + (AB::Expr::one() - flag) * prev
+        })
+    }
+
+    /// Returns the inner product of the one-hot `selectors` and `words`, that is the word selected
+    /// by `selectors`.
+    pub fn select<AB: AirBuilder<Var = V>>(selectors: &[V], words: &[Word<V>]) -> Word<AB::Expr> {
+        debug_assert_eq!(selectors.len(), words.len());
+        Word(core::array::from_fn(|i| {
+            selectors
+                .iter()
+                .zip(words)
+                .map(|(selector, word)| *selector * word.0[i])
+                .sum()
+        }))
+    }
 }
 
 impl<T> Index<usize> for Word<T> {
@@ -15,7 +15,7 @@ use crate::air::{SP1AirBuilder, Word};
 use crate::operations::AddOperation;
 use crate::runtime::{ExecutionRecord, Opcode, Program};
 use crate::stark::MachineRecord;
-use crate::utils::pad_to_power_of_two;
+use crate::utils::{pad_to_power_of_two, trace_chunk_size};
 
 /// The number of main trace columns for `AddSubChip`.
 pub const NUM_ADD_SUB_COLS: usize = size_of::<AddSubCols<u8>>();
@@ -74,10 +74,7 @@ impl<F: PrimeField> MachineAir<F> for AddSubChip {
         output: &mut ExecutionRecord,
     ) -> RowMajorMatrix<F> {
         // Generate the rows for the trace.
-        let chunk_size = std::cmp::max(
-            (input.add_events.len() + input.sub_events.len()) / num_cpus::get(),
-            1,
-        );
+        let chunk_size = trace_chunk_size(input.add_events.len() + input.sub_events.len());
         let merged_events = input
             .add_events
             .iter()
@@ -131,7 +128,10 @@ impl<F: PrimeField> MachineAir<F> for AddSubChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_ADD_SUB_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_ADD_SUB_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         // Write the nonces to the trace.
         for i in 0..trace.height() {
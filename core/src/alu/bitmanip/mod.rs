@@ -0,0 +1,371 @@
+//! Implementation of the bit manipulation instructions of the Zbb extension: CLZ, CTZ, CPOP and
+//! REV8.
+//!
+//! The operand `b` is decomposed into its 32 bits. CPOP is the sum of the bits, and REV8 reverses
+//! the bytes of `b`. For CLZ, the column `msb_seen[i]` is set if one of the bits `i..32` is set, so
+//! that the number of leading zeros is the number of unset flags. CTZ is the same with the flags
+//! `lsb_seen[i]`, set if one of the bits `0..=i` is set. The instructions read a single register,
+//! and the second operand `c` is zero.
+
+use core::borrow::{Borrow, BorrowMut};
+use core::mem::size_of;
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::AbstractField;
+use p3_field::PrimeField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use sp1_derive::AlignedBorrow;
+
+use crate::air::{MachineAir, SP1AirBuilder, Word, WORD_SIZE};
+use crate::runtime::{ExecutionRecord, Opcode, Program};
+use crate::utils::pad_to_power_of_two;
+
+/// The number of main trace columns for `BitManipChip`.
+pub const NUM_BITMANIP_COLS: usize = size_of::<BitManipCols<u8>>();
+
+/// The number of bits in a word.
+const WORD_BITS: usize = 8 * WORD_SIZE;
+
+/// A chip that implements the bit manipulation operations for the opcodes CLZ, CTZ, CPOP and REV8.
+#[derive(Default)]
+pub struct BitManipChip;
+
+/// The column layout for the chip.
+#[derive(AlignedBorrow, Default, Clone, Copy)]
+#[repr(C)]
+pub struct BitManipCols<T> {
+    /// The shard number, used for byte lookup table.
+    pub shard: T,
+
+    /// The channel number, used for byte lookup table.
+    pub channel: T,
+
+    /// The nonce of the operation.
+    pub nonce: T,
+
+    /// The output operand.
+    pub a: Word<T>,
+
+    /// The input operand.
+    pub b: Word<T>,
+
+    /// The second operand, which is zero.
+    pub c: Word<T>,
+
+    /// The little endian bits of `b`.
+    pub b_bits: [T; WORD_BITS],
+
+    /// `msb_seen[i]` is set if one of the bits `i..32` of `b` is set.
+    pub msb_seen: [T; WORD_BITS],
+
+    /// `lsb_seen[i]` is set if one of the bits `0..=i` of `b` is set.
+    pub lsb_seen: [T; WORD_BITS],
+
+    /// If the opcode is CLZ.
+    pub is_clz: T,
+
+    /// If the opcode is CTZ.
+    pub is_ctz: T,
+
+    /// If the opcode is CPOP.
+    pub is_cpop: T,
+
+    /// If the opcode is REV8.
+    pub is_rev8: T,
+}
+
+impl<F: PrimeField> MachineAir<F> for BitManipChip {
+    type Record = ExecutionRecord;
+
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "BitManip".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        _: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        // Generate the trace rows for each event.
+        let rows = input
+            .bitmanip_events
+            .iter()
+            .map(|event| {
+                let mut row = [F::zero(); NUM_BITMANIP_COLS];
+                let cols: &mut BitManipCols<F> = row.as_mut_slice().borrow_mut();
+
+                cols.shard = F::from_canonical_u32(event.shard);
+                cols.channel = F::from_canonical_u32(event.channel);
+                cols.a = Word::from(event.a);
+                cols.b = Word::from(event.b);
+                cols.c = Word::from(event.c);
+
+                for i in 0..WORD_BITS {
+                    cols.b_bits[i] = F::from_canonical_u32((event.b >> i) & 1);
+                    cols.msb_seen[i] = F::from_bool(event.b >> i != 0);
+                    cols.lsb_seen[i] = F::from_bool(event.b << (WORD_BITS - 1 - i) != 0);
+                }
+
+                cols.is_clz = F::from_bool(event.opcode == Opcode::CLZ);
+                cols.is_ctz = F::from_bool(event.opcode == Opcode::CTZ);
+                cols.is_cpop = F::from_bool(event.opcode == Opcode::CPOP);
+                cols.is_rev8 = F::from_bool(event.opcode == Opcode::REV8);
+
+                row
+            })
+            .collect::<Vec<_>>();
+
+        // Convert the trace to a row major matrix.
+        let mut trace = RowMajorMatrix::new(
+            rows.into_iter().flatten().collect::<Vec<_>>(),
+            NUM_BITMANIP_COLS,
+        );
+
+        // Pad the trace to a power of two.
+        pad_to_power_of_two::<NUM_BITMANIP_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
+
+        for i in 0..trace.height() {
+            let cols: &mut BitManipCols<F> =
+                trace.values[i * NUM_BITMANIP_COLS..(i + 1) * NUM_BITMANIP_COLS].borrow_mut();
+            cols.nonce = F::from_canonical_usize(i);
+        }
+
+        trace
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        !shard.bitmanip_events.is_empty()
+    }
+}
+
+impl<F> BaseAir<F> for BitManipChip {
+    fn width(&self) -> usize {
+        NUM_BITMANIP_COLS
+    }
+}
+
+impl<AB> Air<AB> for BitManipChip
+where
+    AB: SP1AirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &BitManipCols<AB::Var> = (*local).borrow();
+        let next = main.row_slice(1);
+        let next: &BitManipCols<AB::Var> = (*next).borrow();
+
+        // Constrain the incrementing nonce.
+        builder.when_first_row().assert_zero(local.nonce);
+        builder
+            .when_transition()
+            .assert_eq(local.nonce + AB::Expr::one(), next.nonce);
+
+        // Decompose `b` into its bits.
+        for bit in local.b_bits.iter() {
+            builder.assert_bool(*bit);
+        }
+        for i in 0..WORD_SIZE {
+            let byte = (0..8).fold(AB::Expr::zero(), |acc, j| {
+                acc + local.b_bits[8 * i + j] * AB::F::from_canonical_u32(1 << j)
+            });
+            builder.assert_eq(local.b[i], byte);
+        }
+
+        // Compute the flags of the set bits seen so far, from the most and the least significant
+        // bit. A flag is the OR of the previous flag and the bit, so that the flags are boolean.
+        let or = |x: AB::Expr, y: AB::Expr| x.clone() + y.clone() - x * y;
+        builder.assert_eq(local.msb_seen[WORD_BITS - 1], local.b_bits[WORD_BITS - 1]);
+        builder.assert_eq(local.lsb_seen[0], local.b_bits[0]);
+        for i in 0..WORD_BITS - 1 {
+            builder.assert_eq(
+                local.msb_seen[i],
+                or(local.msb_seen[i + 1].into(), local.b_bits[i].into()),
+            );
+            builder.assert_eq(
+                local.lsb_seen[i + 1],
+                or(local.lsb_seen[i].into(), local.b_bits[i + 1].into()),
+            );
+        }
+
+        // The number of leading (resp. trailing) zeros is the number of unset flags, and the
+        // population count is the number of set bits.
+        let word_bits = AB::Expr::from_canonical_usize(WORD_BITS);
+        let clz = local
+            .msb_seen
+            .iter()
+            .fold(word_bits.clone(), |acc, &seen| acc - seen);
+        let ctz = local
+            .lsb_seen
+            .iter()
+            .fold(word_bits, |acc, &seen| acc - seen);
+        let cpop = local
+            .b_bits
+            .iter()
+            .fold(AB::Expr::zero(), |acc, &bit| acc + bit);
+
+        // The counts are at most 32, and are written to the least significant byte.
+        let count = local.is_clz * clz + local.is_ctz * ctz + local.is_cpop * cpop;
+        let is_count = local.is_clz + local.is_ctz + local.is_cpop;
+        builder.when(is_count.clone()).assert_eq(local.a[0], count);
+        for i in 1..WORD_SIZE {
+            builder.when(is_count.clone()).assert_zero(local.a[i]);
+        }
+
+        // REV8 reverses the bytes of `b`.
+        for i in 0..WORD_SIZE {
+            builder
+                .when(local.is_rev8)
+                .assert_eq(local.a[i], local.b[WORD_SIZE - 1 - i]);
+        }
+
+        let is_real = is_count + local.is_rev8;
+
+        // The instructions have a single operand.
+        builder.when(is_real.clone()).assert_word_zero(local.c);
+
+        // Get the cpu opcode, which corresponds to the opcode being sent in the CPU table.
+        let cpu_opcode = local.is_clz * Opcode::CLZ.as_field::<AB::F>()
+            + local.is_ctz * Opcode::CTZ.as_field::<AB::F>()
+            + local.is_cpop * Opcode::CPOP.as_field::<AB::F>()
+            + local.is_rev8 * Opcode::REV8.as_field::<AB::F>();
+
+        // Receive the arguments.
+        builder.receive_alu(
+            cpu_opcode,
+            local.a,
+            local.b,
+            local.c,
+            local.shard,
+            local.channel,
+            local.nonce,
+            is_real.clone(),
+        );
+
+        builder.assert_bool(local.is_clz);
+        builder.assert_bool(local.is_ctz);
+        builder.assert_bool(local.is_cpop);
+        builder.assert_bool(local.is_rev8);
+        builder.assert_bool(is_real);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::dense::RowMajorMatrix;
+
+    use crate::air::MachineAir;
+    use crate::stark::StarkGenericConfig;
+    use crate::utils::{uni_stark_prove as prove, uni_stark_verify as verify};
+
+    use super::BitManipChip;
+    use crate::alu::AluEvent;
+    use crate::runtime::{ExecutionRecord, Instruction, Opcode, Program, Runtime};
+    use crate::utils::{BabyBearPoseidon2, SP1CoreOpts};
+
+    const OPCODES: [Opcode; 4] = [Opcode::CLZ, Opcode::CTZ, Opcode::CPOP, Opcode::REV8];
+
+    /// Operands around the byte boundaries: every value of each byte and its complement, and
+    /// every single bit with its neighbours.
+    fn byte_boundary_operands() -> Vec<u32> {
+        let mut operands = vec![0, u32::MAX];
+        for shift in [0, 8, 16, 24] {
+            for byte in 0..=u8::MAX as u32 {
+                operands.push(byte << shift);
+                operands.push(!(byte << shift));
+            }
+        }
+        for i in 0..32 {
+            let bit = 1u32 << i;
+            operands.extend([bit, bit.wrapping_sub(1), bit.wrapping_add(1), !bit]);
+        }
+        operands
+    }
+
+    /// The result of `opcode` on `b`, as computed by the standard library.
+    fn reference(opcode: Opcode, b: u32) -> u32 {
+        match opcode {
+            Opcode::CLZ => b.leading_zeros(),
+            Opcode::CTZ => b.trailing_zeros(),
+            Opcode::CPOP => b.count_ones(),
+            Opcode::REV8 => b.swap_bytes(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Executes each opcode on each operand, returning the emitted events.
+    fn execute(operands: &[u32]) -> Vec<AluEvent> {
+        let instructions = OPCODES
+            .into_iter()
+            .flat_map(|opcode| {
+                operands.iter().flat_map(move |&b| {
+                    [
+                        Instruction::new(Opcode::ADD, 10, 0, b, false, true),
+                        Instruction::new(opcode, 11, 10, 0, false, true),
+                    ]
+                })
+            })
+            .collect();
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        runtime.record.bitmanip_events.clone()
+    }
+
+    #[test]
+    fn generate_trace() {
+        let mut shard = ExecutionRecord::default();
+        shard.bitmanip_events = vec![AluEvent::new(0, 0, 0, Opcode::CLZ, 27, 19, 0)];
+        let chip = BitManipChip::default();
+        let trace: RowMajorMatrix<BabyBear> =
+            chip.generate_trace(&shard, &mut ExecutionRecord::default());
+        println!("{:?}", trace.values)
+    }
+
+    #[test]
+    fn differential_byte_boundaries() {
+        let operands = byte_boundary_operands();
+        let events = execute(&operands);
+        assert_eq!(events.len(), OPCODES.len() * operands.len());
+        for event in events.iter() {
+            assert_eq!(event.a, reference(event.opcode, event.b), "{:?}", event);
+        }
+
+        let config = BabyBearPoseidon2::new();
+        let mut challenger = config.challenger();
+
+        let mut shard = ExecutionRecord::default();
+        shard.bitmanip_events = events;
+        let chip = BitManipChip::default();
+        let trace: RowMajorMatrix<BabyBear> =
+            chip.generate_trace(&shard, &mut ExecutionRecord::default());
+        let proof = prove::<BabyBearPoseidon2, _>(&config, &chip, &mut challenger, trace);
+
+        let mut challenger = config.challenger();
+        verify(&config, &chip, &mut challenger, &proof).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn prove_babybear_wrong_result() {
+        let config = BabyBearPoseidon2::new();
+        let mut challenger = config.challenger();
+
+        let mut shard = ExecutionRecord::default();
+        shard.bitmanip_events = vec![AluEvent::new(0, 0, 0, Opcode::CTZ, 3, 0x10, 0)];
+        let chip = BitManipChip::default();
+        let trace: RowMajorMatrix<BabyBear> =
+            chip.generate_trace(&shard, &mut ExecutionRecord::default());
+        let proof = prove::<BabyBearPoseidon2, _>(&config, &chip, &mut challenger, trace);
+
+        let mut challenger = config.challenger();
+        verify(&config, &chip, &mut challenger, &proof).unwrap();
+    }
+}
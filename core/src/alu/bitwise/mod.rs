@@ -114,7 +114,10 @@ impl<F: PrimeField> MachineAir<F> for BitwiseChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_BITWISE_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_BITWISE_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         for i in 0..trace.height() {
             let cols: &mut BitwiseCols<F> =
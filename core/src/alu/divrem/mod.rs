@@ -515,7 +515,10 @@ impl<F: PrimeField> MachineAir<F> for DivRemChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_DIVREM_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_DIVREM_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         // Create the template for the padded rows. These are fake rows that don't fail on some
         // sanity checks.
@@ -221,7 +221,10 @@ impl<F: PrimeField32> MachineAir<F> for LtChip {
             RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_LT_COLS);
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_LT_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_LT_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         // Write the nonces to the trace.
         for i in 0..trace.height() {
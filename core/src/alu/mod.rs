@@ -1,19 +1,19 @@
 pub mod add_sub;
+pub mod bitmanip;
 pub mod bitwise;
 pub mod divrem;
 pub mod lt;
 pub mod mul;
-pub mod sll;
-pub mod sr;
+pub mod shift;
 
 pub use add_sub::*;
+pub use bitmanip::*;
 pub use bitwise::*;
 pub use divrem::*;
 pub use lt::*;
 pub use mul::*;
 use rand::Rng;
-pub use sll::*;
-pub use sr::*;
+pub use shift::*;
 
 use serde::{Deserialize, Serialize};
 
@@ -1,11 +1,12 @@
 //! Implementation to check that b * c = product.
 //!
-//! We first extend the operands to 64 bits. We sign-extend them if the op code is signed. Then we
-//! calculate the un-carried product and propagate the carry. Finally, we check that the appropriate
-//! bits of the product match the result.
+//! We first extend the operands to 64 bits. Each operand is sign-extended if the op code treats it
+//! as signed, which is `b` for `MULH` and `MULHSU` and `c` for `MULH` only, and zero-extended
+//! otherwise. Then we calculate the un-carried product and propagate the carry. Finally, we check
+//! that the appropriate bits of the product match the result.
 //!
-//! b_64 = sign_extend(b) if signed operation else b
-//! c_64 = sign_extend(c) if signed operation else c
+//! b_64 = sign_extend(b) if opcode in [MULH, MULHSU] else b
+//! c_64 = sign_extend(c) if opcode == MULH else c
 //!
 //! m = []
 //! # 64-bit integers have 8 limbs.
@@ -50,7 +51,7 @@ use crate::bytes::{ByteLookupEvent, ByteOpcode};
 use crate::disassembler::WORD_SIZE;
 use crate::runtime::{ExecutionRecord, Opcode, Program};
 use crate::stark::MachineRecord;
-use crate::utils::pad_to_power_of_two;
+use crate::utils::{pad_to_power_of_two, trace_chunk_size};
 
 /// The number of main trace columns for `MulChip`.
 pub const NUM_MUL_COLS: usize = size_of::<MulCols<u8>>();
@@ -140,8 +141,8 @@ impl<F: PrimeField> MachineAir<F> for MulChip {
         output: &mut ExecutionRecord,
     ) -> RowMajorMatrix<F> {
         let mul_events = input.mul_events.clone();
-        // Compute the chunk size based on the number of events and the number of CPUs.
-        let chunk_size = std::cmp::max(mul_events.len() / num_cpus::get(), 1);
+        // Compute the chunk size based on the number of events.
+        let chunk_size = trace_chunk_size(mul_events.len());
 
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let rows_and_records = mul_events
@@ -271,7 +272,10 @@ impl<F: PrimeField> MachineAir<F> for MulChip {
             RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_MUL_COLS);
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_MUL_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_MUL_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         // Write the nonces to the trace.
         for i in 0..trace.height() {
@@ -339,7 +343,8 @@ where
             (local.b_msb, local.c_msb)
         };
 
-        // Calculate whether to extend b and c's sign.
+        // Calculate whether to extend b and c's sign. `MULHSU` multiplies a signed `b` by an
+        // unsigned `c`, so `c` is only sign extended by `MULH`.
         let (b_sign_extend, c_sign_extend) = {
             // MULH or MULHSU
             let is_b_i32 = local.is_mulh + local.is_mulhsu - local.is_mulh * local.is_mulhsu;
@@ -477,15 +482,16 @@ mod tests {
     use crate::{
         air::MachineAir,
         stark::StarkGenericConfig,
-        utils::{uni_stark_prove as prove, uni_stark_verify as verify},
+        utils::{run_test_core, uni_stark_prove as prove, uni_stark_verify as verify},
     };
     use p3_baby_bear::BabyBear;
     use p3_matrix::dense::RowMajorMatrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use crate::{
         alu::AluEvent,
-        runtime::{ExecutionRecord, Opcode},
-        utils::BabyBearPoseidon2,
+        runtime::{ExecutionRecord, Instruction, Opcode, Program, Runtime},
+        utils::{BabyBearPoseidon2, SP1CoreOpts},
     };
 
     use super::MulChip;
@@ -591,4 +597,71 @@ mod tests {
         let mut challenger = config.challenger();
         verify(&config, &chip, &mut challenger, &proof).unwrap();
     }
+
+    /// The result of a multiplication opcode as defined by the RISC-V spec, computed over `i128` so
+    /// that it does not share the operand extensions of the runtime.
+    fn mul_reference(opcode: Opcode, b: u32, c: u32) -> u32 {
+        let signed = |x: u32| x as i32 as i128;
+        let unsigned = |x: u32| x as i128;
+        let product = match opcode {
+            Opcode::MUL => return b.wrapping_mul(c),
+            Opcode::MULH => signed(b) * signed(c),
+            Opcode::MULHU => unsigned(b) * unsigned(c),
+            Opcode::MULHSU => signed(b) * unsigned(c),
+            _ => unreachable!(),
+        };
+        (product >> 32) as u32
+    }
+
+    #[test]
+    fn prove_boundary_matrix() {
+        let mut rng = StdRng::seed_from_u64(0xdead_beef);
+        let operands = [
+            0,
+            1,
+            u32::MAX,
+            i32::MIN as u32,
+            i32::MAX as u32,
+            0x80000001,
+            rng.gen::<u32>(),
+        ];
+        let opcodes = [Opcode::MUL, Opcode::MULH, Opcode::MULHU, Opcode::MULHSU];
+
+        // Every combination of opcode and operands stores its result to its own word.
+        let mut cases = Vec::new();
+        let mut instructions = Vec::new();
+        for opcode in opcodes {
+            for b in operands {
+                for c in operands {
+                    let addr = 0x1000 + 4 * cases.len() as u32;
+                    instructions.extend([
+                        Instruction::new(Opcode::ADD, 10, 0, b, false, true),
+                        Instruction::new(Opcode::ADD, 11, 0, c, false, true),
+                        Instruction::new(opcode, 12, 10, 11, false, false),
+                        Instruction::new(Opcode::SW, 12, 0, addr, false, true),
+                    ]);
+                    cases.push((opcode, b, c, addr));
+                }
+            }
+        }
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        for (opcode, b, c, addr) in cases.iter().copied() {
+            assert_eq!(
+                runtime.word(addr),
+                mul_reference(opcode, b, c),
+                "{:?} {:#010x} {:#010x}",
+                opcode,
+                b,
+                c
+            );
+        }
+
+        // The whole matrix is proven in a single shard.
+        assert_eq!(runtime.record.mul_events.len(), cases.len());
+        let proof = run_test_core(runtime).unwrap();
+        assert_eq!(proof.shard_proofs.len(), 1);
+    }
 }
@@ -0,0 +1,722 @@
+//! Verifies left, logical right and arithmetic right shifts.
+//!
+//! This module implements a = b << c (SLL), a = b >>> c (SRL) and a = b >> c (SRA) with a single
+//! chip, as a combination of bit and byte shifts sharing the same columns. The operation only
+//! changes the value the operand is extended with and the direction the bytes are read in.
+//!
+//! The shift amount c is decomposed into two components:
+//!
+//! - num_bits_to_shift = c % 8: Represents the fine-grained bit-level shift.
+//! - num_bytes_to_shift = c // 8: Represents the coarser byte-level shift.
+//!
+//! Both shifts are verified on b extended to 64 bits. A right shift by r bits is the left shift by
+//! 8 - r bits followed by a right shift by one byte, so the bit shift is always a multiplication,
+//! and the byte shift reads the product one byte higher for right shifts. The logic looks as
+//! follows:
+//!
+//! c = take the least significant 5 bits of c
+//! num_bytes_to_shift = c // 8
+//! num_bits_to_shift = c % 8
+//!
+//! # Extend b to 64 bits. This is the only place where SRA differs from SRL.
+//! fill = 0xff if opcode == SRA and msb(b) == 1 else 0
+//! extended_b = b[0..WORD_SIZE] + [fill; WORD_SIZE]
+//!
+//! # "Bit shift"
+//! if opcode == SLL:
+//!     bit_shift_multiplier = pow(2, num_bits_to_shift)
+//! else:
+//!     bit_shift_multiplier = pow(2, 8 - num_bits_to_shift)
+//! bit_shift_result = bit_shift_multiplier * extended_b
+//!
+//! # "Byte shift"
+//! for i in range(WORD_SIZE):
+//!     if opcode == SLL:
+//!         if i < num_bytes_to_shift:
+//!             assert(a[i] == 0)
+//!         else:
+//!             assert(a[i] == bit_shift_result[i - num_bytes_to_shift])
+//!     else:
+//!         assert(a[i] == bit_shift_result[i + num_bytes_to_shift + 1])
+//!
+//! Notes:
+//!
+//! - Ideally, we would calculate b * pow(2, c), but pow(2, c) could overflow in F.
+//! - The sign fill is a single expression of the operation flags and of the most significant bit
+//!   of b, which is looked up for SRA rows only.
+
+mod utils;
+
+use core::borrow::{Borrow, BorrowMut};
+use core::mem::size_of;
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::AbstractField;
+use p3_field::PrimeField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use sp1_derive::AlignedBorrow;
+
+use crate::air::MachineAir;
+use crate::air::{SP1AirBuilder, Word};
+use crate::alu::shift::utils::{nb_bits_to_shift, nb_bytes_to_shift};
+use crate::bytes::event::ByteRecord;
+use crate::bytes::{ByteLookupEvent, ByteOpcode};
+use crate::disassembler::WORD_SIZE;
+use crate::runtime::{ExecutionRecord, Opcode, Program};
+use crate::utils::pad_to_power_of_two;
+
+/// The number of main trace columns for `ShiftChip`.
+pub const NUM_SHIFT_COLS: usize = size_of::<ShiftCols<u8>>();
+
+/// The number of bytes necessary to represent a 64-bit integer.
+const LONG_WORD_SIZE: usize = 2 * WORD_SIZE;
+
+/// The number of bits in a byte.
+pub const BYTE_SIZE: usize = 8;
+
+/// A chip that implements shift operations for the opcodes SLL, SRL and SRA.
+#[derive(Default)]
+pub struct ShiftChip;
+
+/// The column layout for the chip.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ShiftCols<T> {
+    /// The shard number, used for byte lookup table.
+    pub shard: T,
+
+    /// The channel number, used for byte lookup table.
+    pub channel: T,
+
+    /// The nonce of the operation.
+    pub nonce: T,
+
+    /// The output operand.
+    pub a: Word<T>,
+
+    /// The first input operand.
+    pub b: Word<T>,
+
+    /// The second input operand.
+    pub c: Word<T>,
+
+    /// The least significant byte of `c`. Used to verify `shift_by_n_bits` and `shift_by_n_bytes`.
+    pub c_least_sig_byte: [T; BYTE_SIZE],
+
+    /// A boolean array whose `i`th element indicates whether `num_bits_to_shift = i`.
+    pub shift_by_n_bits: [T; BYTE_SIZE],
+
+    /// A boolean array whose `i`th element indicates whether `num_bytes_to_shift = i`.
+    pub shift_by_n_bytes: [T; WORD_SIZE],
+
+    /// The most significant bit of `b`, which is the sign fill of SRA.
+    pub b_msb: T,
+
+    /// The number to multiply the extended `b` by: `2^num_bits_to_shift` for SLL, and
+    /// `2^(8 - num_bits_to_shift)` for SRL and SRA.
+    pub bit_shift_multiplier: T,
+
+    /// The result of multiplying the extended `b` by `bit_shift_multiplier`.
+    pub bit_shift_result: [T; LONG_WORD_SIZE],
+
+    /// The carry propagated when multiplying the extended `b` by `bit_shift_multiplier`.
+    pub bit_shift_result_carry: [T; LONG_WORD_SIZE],
+
+    /// If the opcode is SLL.
+    pub is_sll: T,
+
+    /// If the opcode is SRL.
+    pub is_srl: T,
+
+    /// If the opcode is SRA.
+    pub is_sra: T,
+
+    /// Selector to know whether this row is enabled.
+    pub is_real: T,
+}
+
+impl<F: PrimeField> MachineAir<F> for ShiftChip {
+    type Record = ExecutionRecord;
+
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "Shift".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        // Generate the trace rows for each event.
+        let mut rows: Vec<[F; NUM_SHIFT_COLS]> = Vec::new();
+        for event in input.shift_events.iter() {
+            assert!(matches!(
+                event.opcode,
+                Opcode::SLL | Opcode::SRL | Opcode::SRA
+            ));
+            let mut row = [F::zero(); NUM_SHIFT_COLS];
+            let cols: &mut ShiftCols<F> = row.as_mut_slice().borrow_mut();
+            let a = event.a.to_le_bytes();
+            let b = event.b.to_le_bytes();
+
+            // Initialize cols with basic operands and flags derived from the current event.
+            cols.shard = F::from_canonical_u32(event.shard);
+            cols.channel = F::from_canonical_u32(event.channel);
+            cols.a = Word::from(event.a);
+            cols.b = Word::from(event.b);
+            cols.c = Word::from(event.c);
+            cols.is_sll = F::from_bool(event.opcode == Opcode::SLL);
+            cols.is_srl = F::from_bool(event.opcode == Opcode::SRL);
+            cols.is_sra = F::from_bool(event.opcode == Opcode::SRA);
+            cols.is_real = F::one();
+            for i in 0..BYTE_SIZE {
+                cols.c_least_sig_byte[i] = F::from_canonical_u32((event.c >> i) & 1);
+            }
+
+            // The sign fill, whose most significant bit is only looked up for SRA.
+            let b_msb = (event.b >> 31) & 1;
+            cols.b_msb = F::from_canonical_u32(b_msb);
+            let fill = if event.opcode == Opcode::SRA {
+                let most_significant_byte = b[WORD_SIZE - 1];
+                output.add_byte_lookup_event(ByteLookupEvent {
+                    shard: event.shard,
+                    channel: event.channel,
+                    opcode: ByteOpcode::MSB,
+                    a1: b_msb,
+                    a2: 0,
+                    b: most_significant_byte as u32,
+                    c: 0,
+                });
+                (b_msb * 0xff) as u8
+            } else {
+                0
+            };
+            let mut extended_b = [fill; LONG_WORD_SIZE];
+            extended_b[..WORD_SIZE].copy_from_slice(&b);
+
+            // Variables for bit shifting.
+            let num_bits_to_shift = nb_bits_to_shift(event.c);
+            for i in 0..BYTE_SIZE {
+                cols.shift_by_n_bits[i] = F::from_bool(num_bits_to_shift == i);
+            }
+            let bit_shift_multiplier = if event.opcode == Opcode::SLL {
+                1u32 << num_bits_to_shift
+            } else {
+                1u32 << (BYTE_SIZE - num_bits_to_shift)
+            };
+            cols.bit_shift_multiplier = F::from_canonical_u32(bit_shift_multiplier);
+
+            let mut carry = 0u32;
+            let base = 1u32 << BYTE_SIZE;
+            let mut bit_shift_result = [0u8; LONG_WORD_SIZE];
+            let mut bit_shift_result_carry = [0u8; LONG_WORD_SIZE];
+            for i in 0..LONG_WORD_SIZE {
+                let v = extended_b[i] as u32 * bit_shift_multiplier + carry;
+                carry = v / base;
+                bit_shift_result[i] = (v % base) as u8;
+                bit_shift_result_carry[i] = carry as u8;
+            }
+            cols.bit_shift_result = bit_shift_result.map(F::from_canonical_u8);
+            cols.bit_shift_result_carry = bit_shift_result_carry.map(F::from_canonical_u8);
+
+            // Variables for byte shifting.
+            let num_bytes_to_shift = nb_bytes_to_shift(event.c);
+            for i in 0..WORD_SIZE {
+                cols.shift_by_n_bytes[i] = F::from_bool(num_bytes_to_shift == i);
+            }
+
+            // Range checks.
+            {
+                output.add_u8_range_checks(event.shard, event.channel, &bit_shift_result);
+                output.add_u8_range_checks(event.shard, event.channel, &bit_shift_result_carry);
+            }
+
+            // Sanity check.
+            for i in 0..WORD_SIZE {
+                let expected = if event.opcode == Opcode::SLL {
+                    if i < num_bytes_to_shift {
+                        0
+                    } else {
+                        bit_shift_result[i - num_bytes_to_shift]
+                    }
+                } else {
+                    bit_shift_result[i + num_bytes_to_shift + 1]
+                };
+                debug_assert_eq!(expected, a[i]);
+            }
+
+            rows.push(row);
+        }
+
+        // Convert the trace to a row major matrix.
+        let mut trace = RowMajorMatrix::new(
+            rows.into_iter().flatten().collect::<Vec<_>>(),
+            NUM_SHIFT_COLS,
+        );
+
+        // Pad the trace to a power of two.
+        pad_to_power_of_two::<NUM_SHIFT_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
+
+        // Create the template for the padded rows. These are fake rows that don't fail on some
+        // sanity checks.
+        let padded_row_template = {
+            let mut row = [F::zero(); NUM_SHIFT_COLS];
+            let cols: &mut ShiftCols<F> = row.as_mut_slice().borrow_mut();
+            // Shift 0 by 0 bits and 0 bytes. Without an operation flag, the multiplier is 0.
+            cols.shift_by_n_bits[0] = F::one();
+            cols.shift_by_n_bytes[0] = F::one();
+            row
+        };
+        debug_assert!(padded_row_template.len() == NUM_SHIFT_COLS);
+        for i in input.shift_events.len() * NUM_SHIFT_COLS..trace.values.len() {
+            trace.values[i] = padded_row_template[i % NUM_SHIFT_COLS];
+        }
+
+        // Write the nonces to the trace.
+        for i in 0..trace.height() {
+            let cols: &mut ShiftCols<F> =
+                trace.values[i * NUM_SHIFT_COLS..(i + 1) * NUM_SHIFT_COLS].borrow_mut();
+            cols.nonce = F::from_canonical_usize(i);
+        }
+
+        trace
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        !shard.shift_events.is_empty()
+    }
+}
+
+impl<F> BaseAir<F> for ShiftChip {
+    fn width(&self) -> usize {
+        NUM_SHIFT_COLS
+    }
+}
+
+impl<AB> Air<AB> for ShiftChip
+where
+    AB: SP1AirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &ShiftCols<AB::Var> = (*local).borrow();
+        let next = main.row_slice(1);
+        let next: &ShiftCols<AB::Var> = (*next).borrow();
+
+        let zero: AB::Expr = AB::F::zero().into();
+        let one: AB::Expr = AB::F::one().into();
+        let base: AB::Expr = AB::F::from_canonical_u32(1 << BYTE_SIZE).into();
+        let is_right = local.is_srl + local.is_sra;
+
+        // Constrain the incrementing nonce.
+        builder.when_first_row().assert_zero(local.nonce);
+        builder
+            .when_transition()
+            .assert_eq(local.nonce + AB::Expr::one(), next.nonce);
+
+        // Step 1: Decompose the shift amount into the number of bits and bytes to shift by.
+        {
+            // The sum of c_least_sig_byte[i] * 2^i must match c[0].
+            let mut c_byte_sum = zero.clone();
+            for i in 0..BYTE_SIZE {
+                let val: AB::Expr = AB::F::from_canonical_u32(1 << i).into();
+                c_byte_sum += val * local.c_least_sig_byte[i];
+            }
+            builder.assert_eq(c_byte_sum, local.c[0]);
+
+            // The 3-bit number represented by the 3 least significant bits of c equals the number
+            // of bits to shift.
+            let mut num_bits_to_shift = zero.clone();
+            for i in 0..3 {
+                num_bits_to_shift += local.c_least_sig_byte[i] * AB::F::from_canonical_u32(1 << i);
+            }
+            for i in 0..BYTE_SIZE {
+                builder
+                    .when(local.shift_by_n_bits[i])
+                    .assert_eq(num_bits_to_shift.clone(), AB::F::from_canonical_usize(i));
+            }
+
+            // The 2-bit number represented by the 3rd and 4th least significant bits of c is the
+            // number of bytes to shift.
+            let num_bytes_to_shift = local.c_least_sig_byte[3]
+                + local.c_least_sig_byte[4] * AB::F::from_canonical_u32(2);
+            for i in 0..WORD_SIZE {
+                builder
+                    .when(local.shift_by_n_bytes[i])
+                    .assert_eq(num_bytes_to_shift.clone(), AB::F::from_canonical_usize(i));
+            }
+        }
+
+        // Step 2: Multiply the extended b by the bit shift multiplier.
+        {
+            // The leading bytes of b are 0xff if b's MSB is 1 & opcode = SRA, 0 otherwise.
+            let fill = local.is_sra * local.b_msb * AB::Expr::from_canonical_u8(0xff);
+            let mut extended_b: Vec<AB::Expr> = vec![];
+            for i in 0..WORD_SIZE {
+                extended_b.push(local.b[i].into());
+            }
+            for _ in 0..WORD_SIZE {
+                extended_b.push(fill.clone());
+            }
+
+            // The multiplier is 2^num_bits_to_shift for SLL, and 2^(8 - num_bits_to_shift) for SRL
+            // and SRA.
+            let mut bit_shift_multiplier = zero.clone();
+            for i in 0..BYTE_SIZE {
+                let left = AB::Expr::from_canonical_u32(1 << i);
+                let right = AB::Expr::from_canonical_u32(1 << (BYTE_SIZE - i));
+                bit_shift_multiplier +=
+                    local.shift_by_n_bits[i] * (local.is_sll * left + is_right.clone() * right);
+            }
+            builder.assert_eq(local.bit_shift_multiplier, bit_shift_multiplier);
+
+            // Check bit_shift_result = extended_b * bit_shift_multiplier by using
+            // bit_shift_result_carry to carry-propagate.
+            for i in 0..LONG_WORD_SIZE {
+                let mut v = extended_b[i].clone() * local.bit_shift_multiplier
+                    - local.bit_shift_result_carry[i] * base.clone();
+                if i > 0 {
+                    v += local.bit_shift_result_carry[i - 1].into();
+                }
+                builder.assert_eq(local.bit_shift_result[i], v);
+            }
+        }
+
+        // Step 3: Byte shift bit_shift_result and compare it to a. The bytes are read towards the
+        // most significant ones for right shifts, skipping the byte the bit shift added.
+        for num_bytes_to_shift in 0..WORD_SIZE {
+            let mut shifting = builder.when(local.shift_by_n_bytes[num_bytes_to_shift]);
+            for i in 0..WORD_SIZE {
+                let mut expected =
+                    is_right.clone() * local.bit_shift_result[i + num_bytes_to_shift + 1];
+                if i >= num_bytes_to_shift {
+                    expected += local.is_sll * local.bit_shift_result[i - num_bytes_to_shift];
+                }
+                shifting.assert_eq(local.a[i], expected);
+            }
+        }
+
+        // Step 4: Misc checks such as range checks, bool checks and lookups.
+        for bit in local.c_least_sig_byte.iter() {
+            builder.assert_bool(*bit);
+        }
+        for shift in local.shift_by_n_bits.iter() {
+            builder.assert_bool(*shift);
+        }
+        builder.assert_eq(
+            local
+                .shift_by_n_bits
+                .iter()
+                .fold(zero.clone(), |acc, &x| acc + x),
+            one.clone(),
+        );
+        for shift in local.shift_by_n_bytes.iter() {
+            builder.assert_bool(*shift);
+        }
+        builder.assert_eq(
+            local
+                .shift_by_n_bytes
+                .iter()
+                .fold(zero.clone(), |acc, &x| acc + x),
+            one.clone(),
+        );
+
+        // Check that the MSB of the most significant byte of b matches b_msb on SRA rows, the only
+        // ones using it.
+        builder.assert_bool(local.b_msb);
+        builder.send_byte(
+            AB::F::from_canonical_u32(ByteOpcode::MSB as u32),
+            local.b_msb,
+            local.b[WORD_SIZE - 1],
+            zero.clone(),
+            local.shard,
+            local.channel,
+            local.is_sra,
+        );
+
+        // Range check.
+        {
+            builder.slice_range_check_u8(
+                &local.bit_shift_result,
+                local.shard,
+                local.channel,
+                local.is_real,
+            );
+            builder.slice_range_check_u8(
+                &local.bit_shift_result_carry,
+                local.shard,
+                local.channel,
+                local.is_real,
+            );
+        }
+
+        // Check that the operation flags are boolean.
+        builder.assert_bool(local.is_sll);
+        builder.assert_bool(local.is_srl);
+        builder.assert_bool(local.is_sra);
+        builder.assert_bool(local.is_real);
+
+        // Check that is_real is the sum of the three operation flags.
+        builder.assert_eq(local.is_sll + local.is_srl + local.is_sra, local.is_real);
+
+        // Receive the arguments.
+        builder.receive_alu(
+            local.is_sll * AB::F::from_canonical_u32(Opcode::SLL as u32)
+                + local.is_srl * AB::F::from_canonical_u32(Opcode::SRL as u32)
+                + local.is_sra * AB::F::from_canonical_u32(Opcode::SRA as u32),
+            local.a,
+            local.b,
+            local.c,
+            local.shard,
+            local.channel,
+            local.nonce,
+            local.is_real,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::borrow::{Borrow, BorrowMut};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use crate::{
+        air::MachineAir,
+        stark::StarkGenericConfig,
+        utils::{uni_stark_prove as prove, uni_stark_verify as verify},
+    };
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use p3_matrix::{dense::RowMajorMatrix, Matrix};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        alu::AluEvent,
+        runtime::{ExecutionRecord, Opcode},
+        utils::BabyBearPoseidon2,
+    };
+
+    use super::{ShiftChip, ShiftCols, LONG_WORD_SIZE, NUM_SHIFT_COLS, WORD_SIZE};
+
+    fn shift_event(opcode: Opcode, b: u32, c: u32) -> AluEvent {
+        let a = match opcode {
+            Opcode::SLL => b << (c & 31),
+            Opcode::SRL => b >> (c & 31),
+            Opcode::SRA => ((b as i32) >> (c & 31)) as u32,
+            _ => unreachable!(),
+        };
+        AluEvent::new(0, 0, 0, opcode, a, b, c)
+    }
+
+    fn generate(events: Vec<AluEvent>) -> RowMajorMatrix<BabyBear> {
+        let mut shard = ExecutionRecord::default();
+        shard.shift_events = events;
+        ShiftChip::default().generate_trace(&shard, &mut ExecutionRecord::default())
+    }
+
+    fn prove_and_verify(trace: RowMajorMatrix<BabyBear>) {
+        let config = BabyBearPoseidon2::new();
+        let chip = ShiftChip::default();
+        let mut challenger = config.challenger();
+        let proof = prove::<BabyBearPoseidon2, _>(&config, &chip, &mut challenger, trace);
+
+        let mut challenger = config.challenger();
+        verify(&config, &chip, &mut challenger, &proof).unwrap();
+    }
+
+    #[test]
+    fn generate_trace() {
+        let trace = generate(vec![
+            AluEvent::new(0, 0, 0, Opcode::SLL, 16, 8, 1),
+            AluEvent::new(0, 0, 0, Opcode::SRL, 6, 12, 1),
+        ]);
+        println!("{:?}", trace.values)
+    }
+
+    #[test]
+    fn prove_babybear() {
+        let shift_instructions: Vec<(Opcode, u32, u32, u32)> = vec![
+            (Opcode::SLL, 0x00000002, 0x00000001, 1),
+            (Opcode::SLL, 0x00000080, 0x00000001, 7),
+            (Opcode::SLL, 0x00004000, 0x00000001, 14),
+            (Opcode::SLL, 0x80000000, 0x00000001, 31),
+            (Opcode::SLL, 0xffffffff, 0xffffffff, 0),
+            (Opcode::SLL, 0xfffffffe, 0xffffffff, 1),
+            (Opcode::SLL, 0xffffff80, 0xffffffff, 7),
+            (Opcode::SLL, 0xffffc000, 0xffffffff, 14),
+            (Opcode::SLL, 0x80000000, 0xffffffff, 31),
+            (Opcode::SLL, 0x21212121, 0x21212121, 0),
+            (Opcode::SLL, 0x42424242, 0x21212121, 1),
+            (Opcode::SLL, 0x90909080, 0x21212121, 7),
+            (Opcode::SLL, 0x48484000, 0x21212121, 14),
+            (Opcode::SLL, 0x80000000, 0x21212121, 31),
+            (Opcode::SLL, 0x21212121, 0x21212121, 0xffffffe0),
+            (Opcode::SLL, 0x42424242, 0x21212121, 0xffffffe1),
+            (Opcode::SLL, 0x90909080, 0x21212121, 0xffffffe7),
+            (Opcode::SLL, 0x48484000, 0x21212121, 0xffffffee),
+            (Opcode::SLL, 0x00000000, 0x21212120, 0xffffffff),
+            (Opcode::SRL, 0xffff8000, 0xffff8000, 0),
+            (Opcode::SRL, 0x7fffc000, 0xffff8000, 1),
+            (Opcode::SRL, 0x01ffff00, 0xffff8000, 7),
+            (Opcode::SRL, 0x0003fffe, 0xffff8000, 14),
+            (Opcode::SRL, 0x0001ffff, 0xffff8001, 15),
+            (Opcode::SRL, 0xffffffff, 0xffffffff, 0),
+            (Opcode::SRL, 0x7fffffff, 0xffffffff, 1),
+            (Opcode::SRL, 0x01ffffff, 0xffffffff, 7),
+            (Opcode::SRL, 0x0003ffff, 0xffffffff, 14),
+            (Opcode::SRL, 0x00000001, 0xffffffff, 31),
+            (Opcode::SRL, 0x21212121, 0x21212121, 0),
+            (Opcode::SRL, 0x10909090, 0x21212121, 1),
+            (Opcode::SRL, 0x00424242, 0x21212121, 7),
+            (Opcode::SRL, 0x00008484, 0x21212121, 14),
+            (Opcode::SRL, 0x00000000, 0x21212121, 31),
+            (Opcode::SRL, 0x21212121, 0x21212121, 0xffffffe0),
+            (Opcode::SRL, 0x10909090, 0x21212121, 0xffffffe1),
+            (Opcode::SRL, 0x00424242, 0x21212121, 0xffffffe7),
+            (Opcode::SRL, 0x00008484, 0x21212121, 0xffffffee),
+            (Opcode::SRL, 0x00000000, 0x21212121, 0xffffffff),
+            (Opcode::SRA, 0x00000000, 0x00000000, 0),
+            (Opcode::SRA, 0xc0000000, 0x80000000, 1),
+            (Opcode::SRA, 0xff000000, 0x80000000, 7),
+            (Opcode::SRA, 0xfffe0000, 0x80000000, 14),
+            (Opcode::SRA, 0xffffffff, 0x80000001, 31),
+            (Opcode::SRA, 0x7fffffff, 0x7fffffff, 0),
+            (Opcode::SRA, 0x3fffffff, 0x7fffffff, 1),
+            (Opcode::SRA, 0x00ffffff, 0x7fffffff, 7),
+            (Opcode::SRA, 0x0001ffff, 0x7fffffff, 14),
+            (Opcode::SRA, 0x00000000, 0x7fffffff, 31),
+            (Opcode::SRA, 0x81818181, 0x81818181, 0),
+            (Opcode::SRA, 0xc0c0c0c0, 0x81818181, 1),
+            (Opcode::SRA, 0xff030303, 0x81818181, 7),
+            (Opcode::SRA, 0xfffe0606, 0x81818181, 14),
+            (Opcode::SRA, 0xffffffff, 0x81818181, 31),
+        ];
+        let shift_events = shift_instructions
+            .iter()
+            .map(|t| AluEvent::new(0, 0, 0, t.0, t.1, t.2, t.3))
+            .collect();
+        prove_and_verify(generate(shift_events));
+    }
+
+    #[test]
+    fn prove_babybear_random() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let shift_events = (0..256)
+            .map(|i| {
+                let opcode = [Opcode::SLL, Opcode::SRL, Opcode::SRA][i % 3];
+                shift_event(opcode, rng.gen(), rng.gen())
+            })
+            .collect();
+        prove_and_verify(generate(shift_events));
+    }
+
+    /// The bit shift columns of the rows of three events, computed by hand.
+    #[test]
+    fn golden_trace() {
+        let trace = generate(vec![
+            shift_event(Opcode::SLL, 0x21212121, 14),
+            shift_event(Opcode::SRL, 0x21212121, 14),
+            shift_event(Opcode::SRA, 0x81818181, 7),
+        ]);
+        let golden: [(u32, u32, [u8; LONG_WORD_SIZE], [u8; LONG_WORD_SIZE]); 3] = [
+            (
+                64,
+                0,
+                [0x40, 0x48, 0x48, 0x48, 0x08, 0, 0, 0],
+                [8, 8, 8, 8, 0, 0, 0, 0],
+            ),
+            (4, 0, [0x84, 0x84, 0x84, 0x84, 0, 0, 0, 0], [0; 8]),
+            (
+                2,
+                1,
+                [0x02, 0x03, 0x03, 0x03, 0xff, 0xff, 0xff, 0xff],
+                [1; 8],
+            ),
+        ];
+        for (row, (multiplier, b_msb, result, carry)) in golden.into_iter().enumerate() {
+            let row = trace.row_slice(row);
+            let cols: &ShiftCols<BabyBear> = (*row).borrow();
+            assert_eq!(
+                cols.bit_shift_multiplier,
+                BabyBear::from_canonical_u32(multiplier)
+            );
+            assert_eq!(cols.b_msb, BabyBear::from_canonical_u32(b_msb));
+            assert_eq!(
+                cols.bit_shift_result,
+                result.map(BabyBear::from_canonical_u8)
+            );
+            assert_eq!(
+                cols.bit_shift_result_carry,
+                carry.map(BabyBear::from_canonical_u8)
+            );
+        }
+
+        // The padding rows shift 0 without an operation.
+        let row = trace.row_slice(3);
+        let padding: &ShiftCols<BabyBear> = (*row).borrow();
+        assert_eq!(padding.is_real, BabyBear::zero());
+        assert_eq!(padding.bit_shift_multiplier, BabyBear::zero());
+        assert_eq!(padding.nonce, BabyBear::from_canonical_u32(3));
+    }
+
+    /// Generates the trace of `events`, with `tamper` applied to its first row, and returns
+    /// whether the chip's constraints accept it.
+    fn shift_trace_is_accepted(
+        events: Vec<AluEvent>,
+        tamper: impl FnOnce(&mut ShiftCols<BabyBear>),
+    ) -> bool {
+        let mut trace = generate(events);
+        tamper(trace.values[..NUM_SHIFT_COLS].borrow_mut());
+
+        // In debug builds, the prover panics on unsatisfied constraints.
+        catch_unwind(AssertUnwindSafe(|| prove_and_verify(trace))).is_ok()
+    }
+
+    /// Random mutations of the columns the sign fill of SRA flows into must be rejected.
+    #[test]
+    fn rejects_sign_fill_mutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..16 {
+            let b = rng.gen::<u32>() | 0x80000000;
+            let event = shift_event(Opcode::SRA, b, rng.gen());
+            assert!(shift_trace_is_accepted(vec![event], |_| {}));
+
+            let column = rng.gen_range(0..=2 * WORD_SIZE);
+            let delta = BabyBear::from_canonical_u32(rng.gen_range(1..256));
+            assert!(
+                !shift_trace_is_accepted(vec![event], |cols| {
+                    match column {
+                        0 => cols.b_msb = BabyBear::zero(),
+                        i if i <= WORD_SIZE => cols.bit_shift_result[WORD_SIZE + i - 1] += delta,
+                        i => cols.bit_shift_result_carry[i - 1] += delta,
+                    }
+                }),
+                "mutating column {} of {:?} was accepted",
+                column,
+                event
+            );
+        }
+    }
+
+    /// The former `ShiftLeft` and `ShiftRight` chips had 45 and 71 columns.
+    #[test]
+    fn trace_area() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let events: Vec<_> = (0..1024)
+            .map(|i| {
+                let opcode = [Opcode::SLL, Opcode::SRL, Opcode::SLL, Opcode::SRA][i % 4];
+                shift_event(opcode, rng.gen(), rng.gen())
+            })
+            .collect();
+        let trace = generate(events);
+        assert_eq!(trace.height() * trace.width(), 1024 * NUM_SHIFT_COLS);
+        assert!(trace.height() * trace.width() < 512 * 45 + 512 * 71);
+    }
+}
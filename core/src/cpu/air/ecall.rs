@@ -77,29 +77,100 @@ impl CpuChip {
             ecall_cols.is_hint_len.result
         };
 
+        // Compute whether this ecall is CONFIG_LEN.
+        let is_config_len = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::CONFIG_LEN.syscall_id()),
+                ecall_cols.is_config_len,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_config_len.result
+        };
+
+        // Compute whether this ecall is MEMORY_LIMIT.
+        let is_memory_limit = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::MEMORY_LIMIT.syscall_id()),
+                ecall_cols.is_memory_limit,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_memory_limit.result
+        };
+
+        // Compute whether this ecall is CYCLE_COUNT.
+        let is_cycle_count = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::CYCLE_COUNT.syscall_id()),
+                ecall_cols.is_cycle_count,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_cycle_count.result
+        };
+
+        // Compute whether this ecall is ENTROPY_LEN.
+        let is_entropy_len = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::ENTROPY_LEN.syscall_id()),
+                ecall_cols.is_entropy_len,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_entropy_len.result
+        };
+
+        // Compute whether this ecall is GAS_USED.
+        let is_gas_used = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::GAS_USED.syscall_id()),
+                ecall_cols.is_gas_used,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_gas_used.result
+        };
+
         // When syscall_id is ENTER_UNCONSTRAINED, the new value of op_a should be 0.
         let zero_word = Word::<AB::F>::from(0);
         builder
             .when(is_ecall_instruction.clone() * is_enter_unconstrained)
             .assert_word_eq(local.op_a_val(), zero_word);
 
-        // When the syscall is not one of ENTER_UNCONSTRAINED or HINT_LEN, op_a shouldn't change.
+        // When the syscall is not one of ENTER_UNCONSTRAINED, HINT_LEN, CONFIG_LEN, MEMORY_LIMIT,
+        // CYCLE_COUNT, ENTROPY_LEN or GAS_USED, op_a shouldn't change.
         builder
             .when(is_ecall_instruction.clone())
-            .when_not(is_enter_unconstrained + is_hint_len)
+            .when_not(
+                is_enter_unconstrained
+                    + is_hint_len
+                    + is_config_len
+                    + is_memory_limit
+                    + is_cycle_count
+                    + is_entropy_len
+                    + is_gas_used,
+            )
             .assert_word_eq(local.op_a_val(), local.op_a_access.prev_value);
     }
 
-    /// Constraints related to the COMMIT and COMMIT_DEFERRED_PROOFS instructions.
+    /// Constraints related to the COMMIT, COMMIT_DEFERRED_PROOFS, COMMIT_INPUT and COMMIT_CONFIG
+    /// instructions.
     pub(crate) fn eval_commit<AB: SP1AirBuilder>(
         &self,
         builder: &mut AB,
         local: &CpuCols<AB::Var>,
         commit_digest: [Word<AB::Expr>; PV_DIGEST_NUM_WORDS],
         deferred_proofs_digest: [AB::Expr; POSEIDON_NUM_WORDS],
+        input_digest: [Word<AB::Expr>; PV_DIGEST_NUM_WORDS],
+        config_digest: [Word<AB::Expr>; PV_DIGEST_NUM_WORDS],
     ) {
-        let (is_commit, is_commit_deferred_proofs) =
+        let (is_commit, is_commit_deferred_proofs, is_commit_input, is_commit_config) =
             self.get_is_commit_related_syscall(builder, local);
+        let is_commit_related = is_commit.clone()
+            + is_commit_deferred_proofs.clone()
+            + is_commit_input.clone()
+            + is_commit_config.clone();
 
         // Get the ecall specific columns.
         let ecall_columns = local.opcode_specific_columns.ecall();
@@ -111,38 +182,28 @@ impl CpuChip {
             builder.when(local.selectors.is_ecall).assert_bool(*bit);
             bitmap_sum += (*bit).into();
         }
-        // When the syscall is COMMIT or COMMIT_DEFERRED_PROOFS, there should be one set bit.
+        // When the syscall is COMMIT, COMMIT_DEFERRED_PROOFS, COMMIT_INPUT or COMMIT_CONFIG, there
+        // should be one set bit.
         builder
-            .when(
-                local.selectors.is_ecall * (is_commit.clone() + is_commit_deferred_proofs.clone()),
-            )
+            .when(local.selectors.is_ecall * is_commit_related.clone())
             .assert_one(bitmap_sum.clone());
         // When it's some other syscall, there should be no set bits.
         builder
-            .when(
-                local.selectors.is_ecall
-                    * (AB::Expr::one() - (is_commit.clone() + is_commit_deferred_proofs.clone())),
-            )
+            .when(local.selectors.is_ecall * (AB::Expr::one() - is_commit_related.clone()))
             .assert_zero(bitmap_sum);
 
         // Verify that word_idx corresponds to the set bit in index bitmap.
         for (i, bit) in ecall_columns.index_bitmap.iter().enumerate() {
             builder.when(*bit * local.selectors.is_ecall).assert_eq(
-                local.op_b_access.prev_value()[0],
+                *local.op_b_access.prev_value().least_significant_byte(),
                 AB::Expr::from_canonical_u32(i as u32),
             );
         }
         // Verify that the 3 upper bytes of the word_idx are 0.
-        for i in 0..3 {
+        for byte in local.op_b_access.prev_value().upper_bytes() {
             builder
-                .when(
-                    local.selectors.is_ecall
-                        * (is_commit.clone() + is_commit_deferred_proofs.clone()),
-                )
-                .assert_eq(
-                    local.op_b_access.prev_value()[i + 1],
-                    AB::Expr::from_canonical_u32(0),
-                );
+                .when(local.selectors.is_ecall * is_commit_related.clone())
+                .assert_zero(*byte);
         }
 
         // Retrieve the expected public values digest word to check against the one passed into the
@@ -169,6 +230,22 @@ impl CpuChip {
                 expected_deferred_proofs_digest_word,
                 digest_word.reduce::<AB>(),
             );
+
+        // Verify the input digest word.
+        let expected_input_digest_word =
+            builder.index_word_array(&input_digest, &ecall_columns.index_bitmap);
+
+        builder
+            .when(local.selectors.is_ecall * is_commit_input)
+            .assert_word_eq(expected_input_digest_word, *digest_word);
+
+        // Verify the config digest word.
+        let expected_config_digest_word =
+            builder.index_word_array(&config_digest, &ecall_columns.index_bitmap);
+
+        builder
+            .when(local.selectors.is_ecall * is_commit_config)
+            .assert_word_eq(expected_config_digest_word, *digest_word);
     }
 
     /// Constraint related to the halt and unimpl instruction.
@@ -193,6 +270,15 @@ impl CpuChip {
             local.op_b_access.value().reduce::<AB>(),
             public_values.exit_code.clone(),
         );
+
+        // The rows after the HALT ecall are flagged as halted, and must all be padding, so that
+        // the HALT row is the last real row of the execution.
+        builder.assert_bool(local.is_halted);
+        builder.when_first_row().assert_zero(local.is_halted);
+        builder
+            .when_transition()
+            .assert_eq(next.is_halted, local.is_halted + is_halt);
+        builder.when(local.is_halted).assert_zero(local.is_real);
     }
 
     /// Returns a boolean expression indicating whether the instruction is a HALT instruction.
@@ -223,12 +309,13 @@ impl CpuChip {
         is_halt * is_ecall_instruction
     }
 
-    /// Returns two boolean expression indicating whether the instruction is a COMMIT or COMMIT_DEFERRED_PROOFS instruction.
+    /// Returns four boolean expressions indicating whether the instruction is a COMMIT,
+    /// COMMIT_DEFERRED_PROOFS, COMMIT_INPUT or COMMIT_CONFIG instruction.
     pub(crate) fn get_is_commit_related_syscall<AB: SP1AirBuilder>(
         &self,
         builder: &mut AB,
         local: &CpuCols<AB::Var>,
-    ) -> (AB::Expr, AB::Expr) {
+    ) -> (AB::Expr, AB::Expr, AB::Expr, AB::Expr) {
         let ecall_cols = local.opcode_specific_columns.ecall();
 
         let is_ecall_instruction = self.is_ecall_instruction::<AB>(&local.selectors);
@@ -263,7 +350,34 @@ impl CpuChip {
             ecall_cols.is_commit_deferred_proofs.result
         };
 
-        (is_commit.into(), is_commit_deferred_proofs.into())
+        // Compute whether this ecall is COMMIT_INPUT.
+        let is_commit_input = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::COMMIT_INPUT.syscall_id()),
+                ecall_cols.is_commit_input,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_commit_input.result
+        };
+
+        // Compute whether this ecall is COMMIT_CONFIG.
+        let is_commit_config = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::COMMIT_CONFIG.syscall_id()),
+                ecall_cols.is_commit_config,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_commit_config.result
+        };
+
+        (
+            is_commit.into(),
+            is_commit_deferred_proofs.into(),
+            is_commit_input.into(),
+            is_commit_config.into(),
+        )
     }
 
     /// Returns the number of extra cycles from an ECALL instruction.
@@ -219,20 +219,17 @@ impl CpuChip {
             - memory_columns.offset_is_three;
 
         // Compute the expected stored value for a SB instruction.
-        let one = AB::Expr::one();
         let a_val = local.op_a_val();
         let mem_val = *memory_columns.memory_access.value();
         let prev_mem_val = *memory_columns.memory_access.prev_value();
-        let sb_expected_stored_value = Word([
-            a_val[0] * offset_is_zero.clone()
-                + (one.clone() - offset_is_zero.clone()) * prev_mem_val[0],
-            a_val[0] * memory_columns.offset_is_one
-                + (one.clone() - memory_columns.offset_is_one) * prev_mem_val[1],
-            a_val[0] * memory_columns.offset_is_two
-                + (one.clone() - memory_columns.offset_is_two) * prev_mem_val[2],
-            a_val[0] * memory_columns.offset_is_three
-                + (one.clone() - memory_columns.offset_is_three) * prev_mem_val[3],
-        ]);
+        let offset_flags = [
+            offset_is_zero.clone(),
+            memory_columns.offset_is_one.into(),
+            memory_columns.offset_is_two.into(),
+            memory_columns.offset_is_three.into(),
+        ];
+        let sb_expected_stored_value =
+            prev_mem_val.with_byte::<AB>((*a_val.least_significant_byte()).into(), offset_flags);
         builder
             .when(local.selectors.is_sb)
             .assert_word_eq(mem_val.map(|x| x.into()), sb_expected_stored_value);
@@ -250,13 +247,12 @@ impl CpuChip {
         // Compute the expected stored value for a SH instruction.
         let a_is_lower_half = offset_is_zero;
         let a_is_upper_half = memory_columns.offset_is_two;
-        let sh_expected_stored_value = Word([
-            a_val[0] * a_is_lower_half.clone()
-                + (one.clone() - a_is_lower_half.clone()) * prev_mem_val[0],
-            a_val[1] * a_is_lower_half.clone() + (one.clone() - a_is_lower_half) * prev_mem_val[1],
-            a_val[0] * a_is_upper_half + (one.clone() - a_is_upper_half) * prev_mem_val[2],
-            a_val[1] * a_is_upper_half + (one.clone() - a_is_upper_half) * prev_mem_val[3],
-        ]);
+        let [a_byte_0, a_byte_1, _, _] = a_val.to_le_bytes();
+        let sh_expected_stored_value = prev_mem_val.with_half::<AB>(
+            [a_byte_0.into(), a_byte_1.into()],
+            a_is_lower_half,
+            a_is_upper_half.into(),
+        );
         builder
             .when(local.selectors.is_sh)
             .assert_word_eq(mem_val.map(|x| x.into()), sh_expected_stored_value);
@@ -284,10 +280,12 @@ impl CpuChip {
             - memory_columns.offset_is_three;
 
         // Compute the byte value.
-        let mem_byte = mem_val[0] * offset_is_zero.clone()
-            + mem_val[1] * memory_columns.offset_is_one
-            + mem_val[2] * memory_columns.offset_is_two
-            + mem_val[3] * memory_columns.offset_is_three;
+        let mem_byte = mem_val.select_byte::<AB>([
+            offset_is_zero.clone(),
+            memory_columns.offset_is_one.into(),
+            memory_columns.offset_is_two.into(),
+            memory_columns.offset_is_three.into(),
+        ]);
         let byte_value = Word::extend_expr::<AB>(mem_byte.clone());
 
         // When the instruciton is LB or LBU, just use the lower byte.
@@ -307,12 +305,10 @@ impl CpuChip {
 
         let use_lower_half = offset_is_zero;
         let use_upper_half = memory_columns.offset_is_two;
-        let half_value = Word([
-            use_lower_half.clone() * mem_val[0] + use_upper_half * mem_val[2],
-            use_lower_half * mem_val[1] + use_upper_half * mem_val[3],
-            AB::Expr::zero(),
-            AB::Expr::zero(),
-        ]);
+        let [half_lower, half_upper] =
+            mem_val.select_half::<AB>(use_lower_half, use_upper_half.into());
+        let half_value =
+            Word::from_le_bytes([half_lower, half_upper, AB::Expr::zero(), AB::Expr::zero()]);
         builder
             .when(local.selectors.is_lh + local.selectors.is_lhu)
             .assert_word_eq(half_value, local.unsigned_mem_val.map(|x| x.into()));
@@ -103,12 +103,14 @@ where
         // ECALL instruction.
         self.eval_ecall(builder, local);
 
-        // COMMIT/COMMIT_DEFERRED_PROOFS ecall instruction.
+        // COMMIT/COMMIT_DEFERRED_PROOFS/COMMIT_INPUT/COMMIT_CONFIG ecall instruction.
         self.eval_commit(
             builder,
             local,
             public_values.committed_value_digest.clone(),
             public_values.deferred_proofs_digest.clone(),
+            public_values.input_digest.clone(),
+            public_values.config_digest.clone(),
         );
 
         // HALT ecall and UNIMPL instruction.
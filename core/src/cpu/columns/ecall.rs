@@ -14,6 +14,21 @@ pub struct EcallCols<T> {
     /// Whether the current ecall is HINT_LEN.
     pub is_hint_len: IsZeroOperation<T>,
 
+    /// Whether the current ecall is CONFIG_LEN.
+    pub is_config_len: IsZeroOperation<T>,
+
+    /// Whether the current ecall is MEMORY_LIMIT.
+    pub is_memory_limit: IsZeroOperation<T>,
+
+    /// Whether the current ecall is CYCLE_COUNT.
+    pub is_cycle_count: IsZeroOperation<T>,
+
+    /// Whether the current ecall is ENTROPY_LEN.
+    pub is_entropy_len: IsZeroOperation<T>,
+
+    /// Whether the current ecall is GAS_USED.
+    pub is_gas_used: IsZeroOperation<T>,
+
     /// Whether the current ecall is HALT.
     pub is_halt: IsZeroOperation<T>,
 
@@ -23,6 +38,12 @@ pub struct EcallCols<T> {
     /// Whether the current ecall is a COMMIT_DEFERRED_PROOFS.
     pub is_commit_deferred_proofs: IsZeroOperation<T>,
 
+    /// Whether the current ecall is a COMMIT_INPUT.
+    pub is_commit_input: IsZeroOperation<T>,
+
+    /// Whether the current ecall is a COMMIT_CONFIG.
+    pub is_commit_config: IsZeroOperation<T>,
+
     /// Field to store the word index passed into the COMMIT ecall.  index_bitmap[word index] should
     /// be set to 1 and everything else set to 0.
     pub index_bitmap: [T; PV_DIGEST_NUM_WORDS],
@@ -107,6 +107,9 @@ pub struct CpuCols<T: Copy> {
     /// This is true for all instructions that are not jumps, branches, and halt.  Those instructions
     /// may move the program counter to a non sequential instruction.
     pub is_sequential_instr: T,
+
+    /// Whether the HALT ecall was executed on an earlier row, in which case this row is padding.
+    pub is_halted: T,
 }
 
 impl<T: Copy> CpuCols<T> {
@@ -54,6 +54,9 @@ pub struct CpuEvent {
 
     pub alu_lookup_id: usize,
     pub syscall_lookup_id: usize,
+
+    // The ids of the operations the instruction depends on, zero for the instructions without
+    // such an operation.
     pub memory_add_lookup_id: usize,
     pub memory_sub_lookup_id: usize,
     pub branch_gt_lookup_id: usize,
@@ -1,9 +1,11 @@
 pub mod air;
 pub mod columns;
 pub mod event;
+pub mod store;
 pub mod trace;
 
 pub use event::*;
+pub use store::*;
 
 /// A chip that implements the CPU.
 #[derive(Default)]
@@ -0,0 +1,648 @@
+//! Compressed storage of the CPU events of an execution record.
+//!
+//! A [CpuEvent] takes a few hundred bytes, most of them redundant with the neighbouring events:
+//! the clock and the program counter move by small steps, a program runs few distinct
+//! instructions, and the memory records repeat the shard, the clock and the operands of the
+//! event. [CpuEventStore] keeps the events in blocks of [CPU_EVENT_BLOCK_LEN] events, each encoded
+//! as
+//!
+//! - a byte stream of varints, with the shard, the channel and the clock delta encoded from the
+//!   previous event, the program counter from the previous next program counter, the instruction
+//!   as its index in a palette of the instructions of the store, and the memory records relative
+//!   to the event,
+//! - the operands `a`, `b` and `c` and the ALU and syscall lookup ids in columns, since they don't
+//!   compress.
+//!
+//! The events of a block are decoded back on the fly, block by block, when they are read. Each
+//! block is decoded on its own, so the trace generation decodes the blocks in parallel, see
+//! [CpuEventStore::chunks]. The compression is lossless, and turned off with
+//! [crate::utils::SP1CoreOpts::compress_cpu_events].
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::mem::size_of;
+use std::ops::Range;
+
+use itertools::Either;
+use serde::{Deserialize, Serialize};
+
+use super::CpuEvent;
+use crate::runtime::{Instruction, MemoryReadRecord, MemoryRecordEnum, MemoryWriteRecord, Opcode};
+
+/// The number of events in a compressed block, and in a chunk of [CpuEventStore::chunks].
+pub const CPU_EVENT_BLOCK_LEN: usize = 1024;
+
+/// The number of per-operation lookup ids of a [CpuEvent], see [op_lookup_ids].
+const NUM_OP_LOOKUP_IDS: usize = 8;
+
+/// The key of an instruction in the palette.
+type InstructionKey = (Opcode, u32, u32, u32, bool, bool);
+
+/// The CPU events of an execution record, compressed unless created with
+/// `CpuEventStore::new(false)`.
+///
+/// The events are pushed and read by value. The serialized store only depends on the sequence of
+/// events and on whether it is compressed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CpuEventStore {
+    compressed: bool,
+    palette: Vec<Instruction>,
+    /// The index of each instruction of the palette, rebuilt after deserialization.
+    #[serde(skip)]
+    palette_index: HashMap<InstructionKey, u32>,
+    blocks: Vec<CpuEventBlock>,
+    /// The events after the last block, fewer than [CPU_EVENT_BLOCK_LEN] if compressed.
+    tail: Vec<CpuEvent>,
+}
+
+/// [CPU_EVENT_BLOCK_LEN] encoded events.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct CpuEventBlock {
+    stream: Vec<u8>,
+    a: Vec<u32>,
+    b: Vec<u32>,
+    c: Vec<u32>,
+    alu_lookup_ids: Vec<usize>,
+    syscall_lookup_ids: Vec<usize>,
+}
+
+/// A chunk of the events of a [CpuEventStore], decoded independently of the other chunks.
+#[derive(Clone, Copy)]
+pub struct CpuEventChunk<'a>(ChunkRepr<'a>);
+
+#[derive(Clone, Copy)]
+enum ChunkRepr<'a> {
+    Compressed {
+        palette: &'a [Instruction],
+        block: &'a CpuEventBlock,
+    },
+    Raw(&'a [CpuEvent]),
+}
+
+impl CpuEventStore {
+    /// An empty store, compressing the events if `compressed`.
+    pub fn new(compressed: bool) -> Self {
+        Self {
+            compressed,
+            palette: Vec::new(),
+            palette_index: HashMap::new(),
+            blocks: Vec::new(),
+            tail: Vec::new(),
+        }
+    }
+
+    /// Whether the events are compressed.
+    pub const fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// The number of events.
+    pub fn len(&self) -> usize {
+        self.blocks.len() * CPU_EVENT_BLOCK_LEN + self.tail.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds an event at the end of the store.
+    pub fn push(&mut self, event: CpuEvent) {
+        self.tail.push(event);
+        if self.compressed && self.tail.len() == CPU_EVENT_BLOCK_LEN {
+            if self.palette_index.len() != self.palette.len() {
+                self.palette_index = self
+                    .palette
+                    .iter()
+                    .zip(0..)
+                    .map(|(instruction, index)| (instruction_key(instruction), index))
+                    .collect();
+            }
+            let block = encode_block(&mut self.palette, &mut self.palette_index, &self.tail);
+            self.blocks.push(block);
+            self.tail.clear();
+        }
+    }
+
+    /// Moves the events of `other` to the end of the store, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        if self.is_empty() && self.compressed == other.compressed {
+            std::mem::swap(self, other);
+            return;
+        }
+        for event in other.iter() {
+            self.push(event);
+        }
+        *other = Self::new(other.compressed);
+    }
+
+    /// The events in order.
+    pub fn iter(&self) -> impl Iterator<Item = CpuEvent> + '_ {
+        self.chunks().into_iter().flat_map(|chunk| chunk.iter())
+    }
+
+    /// The event at `index`, which decodes the block containing it.
+    pub fn get(&self, index: usize) -> Option<CpuEvent> {
+        let block = index / CPU_EVENT_BLOCK_LEN;
+        if block < self.blocks.len() {
+            let events = decode_block(&self.palette, &self.blocks[block]);
+            return Some(events[index % CPU_EVENT_BLOCK_LEN]);
+        }
+        self.tail
+            .get(index - self.blocks.len() * CPU_EVENT_BLOCK_LEN)
+            .copied()
+    }
+
+    pub fn first(&self) -> Option<CpuEvent> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<CpuEvent> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// A store with the events in `range`, compressed like this one.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(range.start <= range.end && range.end <= self.len());
+        let mut slice = Self::new(self.compressed);
+        let first_chunk = range.start / CPU_EVENT_BLOCK_LEN;
+        self.chunks()
+            .into_iter()
+            .skip(first_chunk)
+            .flat_map(|chunk| chunk.iter())
+            .skip(range.start - first_chunk * CPU_EVENT_BLOCK_LEN)
+            .take(range.len())
+            .for_each(|event| slice.push(event));
+        slice
+    }
+
+    /// The events in chunks of [CPU_EVENT_BLOCK_LEN] events, the last one possibly shorter, for
+    /// decoding them in parallel.
+    pub fn chunks(&self) -> Vec<CpuEventChunk<'_>> {
+        let blocks = self.blocks.iter().map(|block| {
+            CpuEventChunk(ChunkRepr::Compressed {
+                palette: &self.palette,
+                block,
+            })
+        });
+        let tail = self
+            .tail
+            .chunks(CPU_EVENT_BLOCK_LEN)
+            .map(|events| CpuEventChunk(ChunkRepr::Raw(events)));
+        blocks.chain(tail).collect()
+    }
+
+    /// The number of bytes holding the events.
+    pub fn size_in_bytes(&self) -> usize {
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| {
+                block.stream.len()
+                    + CPU_EVENT_BLOCK_LEN * (3 * size_of::<u32>() + 2 * size_of::<usize>())
+            })
+            .sum::<usize>();
+        blocks
+            + self.palette.len() * size_of::<Instruction>()
+            + self.tail.len() * size_of::<CpuEvent>()
+    }
+}
+
+impl Default for CpuEventStore {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl Debug for CpuEventStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> CpuEventChunk<'a> {
+    /// The number of events of the chunk.
+    pub fn len(&self) -> usize {
+        match self.0 {
+            ChunkRepr::Compressed { block, .. } => block.a.len(),
+            ChunkRepr::Raw(events) => events.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The events of the chunk, decoded if compressed.
+    pub fn iter(&self) -> impl Iterator<Item = CpuEvent> + 'a {
+        match self.0 {
+            ChunkRepr::Compressed { palette, block } => {
+                Either::Left(decode_block(palette, block).into_iter())
+            }
+            ChunkRepr::Raw(events) => Either::Right(events.iter().copied()),
+        }
+    }
+}
+
+fn instruction_key(instruction: &Instruction) -> InstructionKey {
+    (
+        instruction.opcode,
+        instruction.op_a,
+        instruction.op_b,
+        instruction.op_c,
+        instruction.imm_b,
+        instruction.imm_c,
+    )
+}
+
+/// The lookup ids of the operations a CPU event depends on, zero when the opcode has no such
+/// operation.
+const fn op_lookup_ids(event: &CpuEvent) -> [usize; NUM_OP_LOOKUP_IDS] {
+    [
+        event.memory_add_lookup_id,
+        event.memory_sub_lookup_id,
+        event.branch_lt_lookup_id,
+        event.branch_gt_lookup_id,
+        event.branch_add_lookup_id,
+        event.jump_jal_lookup_id,
+        event.jump_jalr_lookup_id,
+        event.auipc_lookup_id,
+    ]
+}
+
+/// The memory records of an event, with the values they are encoded relative to.
+const fn memory_records(event: &CpuEvent) -> [(Option<MemoryRecordEnum>, u32); 4] {
+    [
+        (event.a_record, event.a),
+        (event.b_record, event.b),
+        (event.c_record, event.c),
+        (event.memory_record, 0),
+    ]
+}
+
+// The bits of the flags of an event.
+const RECORD_TAG_BITS: usize = 2;
+const MEMORY_FLAG: u64 = 1 << 8;
+const EXIT_CODE_FLAG: u64 = 1 << 9;
+const OP_LOOKUP_IDS_SHIFT: usize = 10;
+
+/// The state the next event of a block is encoded relative to.
+#[derive(Default)]
+struct Previous {
+    shard: u32,
+    channel: u32,
+    clk: u32,
+    next_pc: u32,
+}
+
+fn encode_block(
+    palette: &mut Vec<Instruction>,
+    palette_index: &mut HashMap<InstructionKey, u32>,
+    events: &[CpuEvent],
+) -> CpuEventBlock {
+    let mut block = CpuEventBlock {
+        stream: Vec::with_capacity(events.len() * 16),
+        a: Vec::with_capacity(events.len()),
+        b: Vec::with_capacity(events.len()),
+        c: Vec::with_capacity(events.len()),
+        alu_lookup_ids: Vec::with_capacity(events.len()),
+        syscall_lookup_ids: Vec::with_capacity(events.len()),
+    };
+    let mut previous = Previous::default();
+    for event in events {
+        block.a.push(event.a);
+        block.b.push(event.b);
+        block.c.push(event.c);
+        block.alu_lookup_ids.push(event.alu_lookup_id);
+        block.syscall_lookup_ids.push(event.syscall_lookup_id);
+
+        let out = &mut block.stream;
+        write_delta(out, event.shard, previous.shard);
+        write_delta(out, event.channel, previous.channel);
+        write_delta(out, event.clk, previous.clk);
+        write_delta(out, event.pc, previous.next_pc);
+        write_delta(out, event.next_pc, event.pc.wrapping_add(4));
+        let instruction = *palette_index
+            .entry(instruction_key(&event.instruction))
+            .or_insert_with(|| {
+                palette.push(event.instruction);
+                palette.len() as u32 - 1
+            });
+        write_varint(out, instruction as u64);
+
+        let records = memory_records(event);
+        let op_lookup_ids = op_lookup_ids(event);
+        let mut flags = 0u64;
+        for (i, (record, _)) in records.iter().enumerate() {
+            let tag = match record {
+                None => 0,
+                Some(MemoryRecordEnum::Read(_)) => 1,
+                Some(MemoryRecordEnum::Write(_)) => 2,
+            };
+            flags |= tag << (i * RECORD_TAG_BITS);
+        }
+        if event.memory.is_some() {
+            flags |= MEMORY_FLAG;
+        }
+        if event.exit_code != 0 {
+            flags |= EXIT_CODE_FLAG;
+        }
+        for (i, id) in op_lookup_ids.iter().enumerate() {
+            if *id != 0 {
+                flags |= 1 << (OP_LOOKUP_IDS_SHIFT + i);
+            }
+        }
+        write_varint(out, flags);
+
+        if let Some(memory) = event.memory {
+            write_varint(out, memory as u64);
+        }
+        if event.exit_code != 0 {
+            write_varint(out, event.exit_code as u64);
+        }
+        for (record, base) in records {
+            if let Some(record) = record {
+                write_record(out, event, &record, base);
+            }
+        }
+        for id in op_lookup_ids.into_iter().filter(|id| *id != 0) {
+            out.extend_from_slice(&(id as u64).to_le_bytes());
+        }
+
+        previous = Previous {
+            shard: event.shard,
+            channel: event.channel,
+            clk: event.clk,
+            next_pc: event.next_pc,
+        };
+    }
+    block
+}
+
+fn decode_block(palette: &[Instruction], block: &CpuEventBlock) -> Vec<CpuEvent> {
+    let mut reader = Reader {
+        bytes: &block.stream,
+        pos: 0,
+    };
+    let mut previous = Previous::default();
+    let events = (0..block.a.len())
+        .map(|i| {
+            let (a, b, c) = (block.a[i], block.b[i], block.c[i]);
+            let shard = reader.delta(previous.shard);
+            let channel = reader.delta(previous.channel);
+            let clk = reader.delta(previous.clk);
+            let pc = reader.delta(previous.next_pc);
+            let next_pc = reader.delta(pc.wrapping_add(4));
+            let instruction = palette[reader.varint() as usize];
+            let flags = reader.varint();
+            let memory = (flags & MEMORY_FLAG != 0).then(|| reader.varint() as u32);
+            let exit_code = if flags & EXIT_CODE_FLAG != 0 {
+                reader.varint() as u32
+            } else {
+                0
+            };
+            let mut records = [None; 4];
+            for (position, (record, base)) in records.iter_mut().zip([a, b, c, 0]).enumerate() {
+                let tag = (flags >> (position * RECORD_TAG_BITS)) & ((1 << RECORD_TAG_BITS) - 1);
+                if tag != 0 {
+                    *record = Some(reader.record(shard, clk, base, tag == 2));
+                }
+            }
+            let mut op_lookup_ids = [0; NUM_OP_LOOKUP_IDS];
+            for (position, id) in op_lookup_ids.iter_mut().enumerate() {
+                if flags & (1 << (OP_LOOKUP_IDS_SHIFT + position)) != 0 {
+                    *id = reader.u64() as usize;
+                }
+            }
+            let [a_record, b_record, c_record, memory_record] = records;
+
+            let event = CpuEvent {
+                shard,
+                channel,
+                clk,
+                pc,
+                next_pc,
+                instruction,
+                a,
+                a_record,
+                b,
+                b_record,
+                c,
+                c_record,
+                memory,
+                memory_record,
+                exit_code,
+                alu_lookup_id: block.alu_lookup_ids[i],
+                syscall_lookup_id: block.syscall_lookup_ids[i],
+                // In the order of [op_lookup_ids].
+                memory_add_lookup_id: op_lookup_ids[0],
+                memory_sub_lookup_id: op_lookup_ids[1],
+                branch_lt_lookup_id: op_lookup_ids[2],
+                branch_gt_lookup_id: op_lookup_ids[3],
+                branch_add_lookup_id: op_lookup_ids[4],
+                jump_jal_lookup_id: op_lookup_ids[5],
+                jump_jalr_lookup_id: op_lookup_ids[6],
+                auipc_lookup_id: op_lookup_ids[7],
+            };
+            previous = Previous {
+                shard,
+                channel,
+                clk,
+                next_pc,
+            };
+            event
+        })
+        .collect();
+    debug_assert_eq!(reader.pos, block.stream.len());
+    events
+}
+
+/// Writes a memory record of `event`, relative to the shard and the clock of the event, and to
+/// `base` for its value.
+fn write_record(out: &mut Vec<u8>, event: &CpuEvent, record: &MemoryRecordEnum, base: u32) {
+    let (value, shard, timestamp, prev_shard, prev_timestamp, prev_value) = match record {
+        MemoryRecordEnum::Read(record) => (
+            record.value,
+            record.shard,
+            record.timestamp,
+            record.prev_shard,
+            record.prev_timestamp,
+            None,
+        ),
+        MemoryRecordEnum::Write(record) => (
+            record.value,
+            record.shard,
+            record.timestamp,
+            record.prev_shard,
+            record.prev_timestamp,
+            Some(record.prev_value),
+        ),
+    };
+    write_delta(out, shard, event.shard);
+    write_delta(out, timestamp, event.clk);
+    write_delta(out, prev_shard, shard);
+    write_delta(out, prev_timestamp, timestamp);
+    write_varint(out, (value ^ base) as u64);
+    if let Some(prev_value) = prev_value {
+        write_varint(out, (prev_value ^ value) as u64);
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Writes the zigzag encoded difference between `value` and `base`.
+fn write_delta(out: &mut Vec<u8>, value: u32, base: u32) {
+    let delta = value.wrapping_sub(base) as i32;
+    write_varint(out, ((delta << 1) ^ (delta >> 31)) as u32 as u64);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> u64 {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.bytes[self.pos];
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    fn delta(&mut self, base: u32) -> u32 {
+        let zigzag = self.varint() as u32;
+        base.wrapping_add((zigzag >> 1) ^ (zigzag & 1).wrapping_neg())
+    }
+
+    fn u64(&mut self) -> u64 {
+        let bytes = self.bytes[self.pos..self.pos + 8].try_into().unwrap();
+        self.pos += 8;
+        u64::from_le_bytes(bytes)
+    }
+
+    fn record(&mut self, shard: u32, clk: u32, base: u32, write: bool) -> MemoryRecordEnum {
+        let shard = self.delta(shard);
+        let timestamp = self.delta(clk);
+        let prev_shard = self.delta(shard);
+        let prev_timestamp = self.delta(timestamp);
+        let value = self.varint() as u32 ^ base;
+        if write {
+            let prev_value = self.varint() as u32 ^ value;
+            MemoryWriteRecord::new(
+                value,
+                shard,
+                timestamp,
+                prev_value,
+                prev_shard,
+                prev_timestamp,
+            )
+            .into()
+        } else {
+            MemoryReadRecord::new(value, shard, timestamp, prev_shard, prev_timestamp).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::tests::fibonacci_program;
+    use crate::runtime::Runtime;
+    use crate::utils::{run_test_core, SP1CoreOpts};
+
+    fn run(compress_cpu_events: bool) -> Runtime<'static> {
+        let opts = SP1CoreOpts {
+            compress_cpu_events,
+            ..SP1CoreOpts::default()
+        };
+        let mut runtime = Runtime::new(fibonacci_program(), opts);
+        runtime.seed_lookup_ids([3; 32]);
+        runtime.run().unwrap();
+        runtime
+    }
+
+    fn same_events(a: impl Iterator<Item = CpuEvent>, b: impl Iterator<Item = CpuEvent>) -> bool {
+        let bytes = |events: Vec<CpuEvent>| bincode::serialize(&events).unwrap();
+        bytes(a.collect()) == bytes(b.collect())
+    }
+
+    #[test]
+    fn test_compressed_cpu_events() {
+        let compressed = run(true);
+        let uncompressed = run(false);
+        let events = &compressed.record.cpu_events;
+        let raw_events = &uncompressed.record.cpu_events;
+        assert!(events.is_compressed() && !raw_events.is_compressed());
+        assert!(events.len() > 4 * CPU_EVENT_BLOCK_LEN);
+        assert_eq!(events.len(), raw_events.len());
+        assert!(same_events(events.iter(), raw_events.iter()));
+        assert!(
+            3 * events.size_in_bytes() < raw_events.size_in_bytes(),
+            "{} bytes compressed, {} bytes uncompressed",
+            events.size_in_bytes(),
+            raw_events.size_in_bytes()
+        );
+
+        // Proving reads the same events either way.
+        let proof = |runtime| bincode::serialize(&run_test_core(runtime).unwrap()).unwrap();
+        assert_eq!(proof(compressed), proof(uncompressed));
+    }
+
+    #[test]
+    fn test_cpu_event_store_accessors() {
+        let runtime = run(true);
+        let events = &runtime.record.cpu_events;
+        let raw_events = events.iter().collect::<Vec<_>>();
+        let len = events.len();
+
+        for index in [0, 1, CPU_EVENT_BLOCK_LEN - 1, CPU_EVENT_BLOCK_LEN, len - 1] {
+            assert!(same_events(
+                events.get(index).into_iter(),
+                [raw_events[index]].into_iter()
+            ));
+        }
+        assert!(events.get(len).is_none());
+        assert!(same_events(
+            events.last().into_iter(),
+            raw_events.last().copied().into_iter()
+        ));
+
+        // Slicing and appending again gives the same store.
+        let split = CPU_EVENT_BLOCK_LEN + 100;
+        let mut store = events.slice(0..split);
+        let mut rest = events.slice(split..len);
+        assert!(same_events(
+            rest.iter(),
+            raw_events[split..].iter().copied()
+        ));
+        store.append(&mut rest);
+        assert!(rest.is_empty());
+        assert_eq!(
+            bincode::serialize(&store).unwrap(),
+            bincode::serialize(events).unwrap()
+        );
+
+        // A deserialized store keeps compressing with the same palette.
+        let mut store: CpuEventStore =
+            bincode::deserialize(&bincode::serialize(&events.slice(0..split)).unwrap()).unwrap();
+        raw_events[split..]
+            .iter()
+            .for_each(|event| store.push(*event));
+        assert_eq!(
+            bincode::serialize(&store).unwrap(),
+            bincode::serialize(events).unwrap()
+        );
+    }
+}
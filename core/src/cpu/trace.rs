@@ -1,12 +1,11 @@
 use std::array;
-use std::borrow::BorrowMut;
+use std::borrow::{Borrow, BorrowMut};
 use std::collections::HashMap;
 
 use p3_field::{PrimeField, PrimeField32};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_maybe_rayon::prelude::IntoParallelRefIterator;
 use p3_maybe_rayon::prelude::ParallelIterator;
-use p3_maybe_rayon::prelude::ParallelSlice;
 use tracing::instrument;
 
 use super::columns::{CPU_COL_MAP, NUM_CPU_COLS};
@@ -44,8 +43,17 @@ impl<F: PrimeField32> MachineAir<F> for CpuChip {
         // Generate the trace rows for each event.
         let mut rows_with_events = input
             .cpu_events
+            .chunks()
             .par_iter()
-            .map(|op: &CpuEvent| self.event_to_row::<F>(*op, &input.nonce_lookup))
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|op| self.event_to_row::<F>(op, &input.nonce_lookup))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
             .collect::<Vec<_>>();
 
         // No need to sort by the shard, since the cpu events are already partitioned by that.
@@ -78,7 +86,10 @@ impl<F: PrimeField32> MachineAir<F> for CpuChip {
         let mut trace = RowMajorMatrix::new(rows, NUM_CPU_COLS);
 
         // Pad the trace to a power of two.
-        Self::pad_to_power_of_two::<F>(&mut trace.values);
+        Self::pad_to_power_of_two::<F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         trace
     }
@@ -86,15 +97,15 @@ impl<F: PrimeField32> MachineAir<F> for CpuChip {
     #[instrument(name = "generate cpu dependencies", level = "debug", skip_all)]
     fn generate_dependencies(&self, input: &ExecutionRecord, output: &mut ExecutionRecord) {
         // Generate the trace rows for each event.
-        let chunk_size = std::cmp::max(input.cpu_events.len() / num_cpus::get(), 1);
         let events = input
             .cpu_events
-            .par_chunks(chunk_size)
-            .map(|ops: &[CpuEvent]| {
+            .chunks()
+            .par_iter()
+            .map(|chunk| {
                 let mut alu = HashMap::new();
                 let mut blu: Vec<_> = Vec::default();
-                ops.iter().for_each(|op| {
-                    let (_, alu_events, blu_events) = self.event_to_row::<F>(*op, &HashMap::new());
+                chunk.iter().for_each(|op| {
+                    let (_, alu_events, blu_events) = self.event_to_row::<F>(op, &HashMap::new());
                     alu_events.into_iter().for_each(|(key, value)| {
                         alu.entry(key).or_insert(Vec::default()).extend(value);
                     });
@@ -710,6 +721,31 @@ impl CpuChip {
                 syscall_id - F::from_canonical_u32(SyscallCode::HINT_LEN.syscall_id()),
             );
 
+            // Populate `is_config_len`.
+            ecall_cols.is_config_len.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::CONFIG_LEN.syscall_id()),
+            );
+
+            // Populate `is_memory_limit`.
+            ecall_cols.is_memory_limit.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::MEMORY_LIMIT.syscall_id()),
+            );
+
+            // Populate `is_cycle_count`.
+            ecall_cols.is_cycle_count.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::CYCLE_COUNT.syscall_id()),
+            );
+
+            // Populate `is_entropy_len`.
+            ecall_cols.is_entropy_len.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::ENTROPY_LEN.syscall_id()),
+            );
+
+            // Populate `is_gas_used`.
+            ecall_cols.is_gas_used.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::GAS_USED.syscall_id()),
+            );
+
             // Populate `is_halt`.
             ecall_cols.is_halt.populate_from_field_element(
                 syscall_id - F::from_canonical_u32(SyscallCode::HALT.syscall_id()),
@@ -728,10 +764,23 @@ impl CpuChip {
                         - F::from_canonical_u32(SyscallCode::COMMIT_DEFERRED_PROOFS.syscall_id()),
                 );
 
-            // If the syscall is `COMMIT` or `COMMIT_DEFERRED_PROOFS`, set the index bitmap and digest word.
+            // Populate `is_commit_input`.
+            ecall_cols.is_commit_input.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::COMMIT_INPUT.syscall_id()),
+            );
+
+            // Populate `is_commit_config`.
+            ecall_cols.is_commit_config.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::COMMIT_CONFIG.syscall_id()),
+            );
+
+            // If the syscall is `COMMIT`, `COMMIT_DEFERRED_PROOFS`, `COMMIT_INPUT` or
+            // `COMMIT_CONFIG`, set the index bitmap and digest word.
             if syscall_id == F::from_canonical_u32(SyscallCode::COMMIT.syscall_id())
                 || syscall_id
                     == F::from_canonical_u32(SyscallCode::COMMIT_DEFERRED_PROOFS.syscall_id())
+                || syscall_id == F::from_canonical_u32(SyscallCode::COMMIT_INPUT.syscall_id())
+                || syscall_id == F::from_canonical_u32(SyscallCode::COMMIT_CONFIG.syscall_id())
             {
                 let digest_idx = cols.op_b_access.value().to_u32() as usize;
                 ecall_cols.index_bitmap[digest_idx] = F::one();
@@ -751,13 +800,9 @@ impl CpuChip {
         is_halt
     }
 
-    fn pad_to_power_of_two<F: PrimeField>(values: &mut Vec<F>) {
+    fn pad_to_power_of_two<F: PrimeField>(values: &mut Vec<F>, min_height: usize) {
         let n_real_rows = values.len() / NUM_CPU_COLS;
-        let padded_nb_rows = if n_real_rows < 16 {
-            16
-        } else {
-            n_real_rows.next_power_of_two()
-        };
+        let padded_nb_rows = n_real_rows.next_power_of_two().max(min_height);
         values.resize(padded_nb_rows * NUM_CPU_COLS, F::zero());
 
         // Interpret values as a slice of arrays of length `NUM_CPU_COLS`
@@ -772,17 +817,32 @@ impl CpuChip {
             padded_row[CPU_COL_MAP.selectors.imm_b] = F::one();
             padded_row[CPU_COL_MAP.selectors.imm_c] = F::one();
         });
+
+        // Flag the rows following the HALT ecall as halted.
+        let halt_row = rows[..n_real_rows].iter().position(|row| {
+            let cols: &CpuCols<F> = row.as_slice().borrow();
+            cols.selectors.is_ecall == F::one()
+                && cols.opcode_specific_columns.ecall().is_halt.result == F::one()
+        });
+        if let Some(halt_row) = halt_row {
+            rows[halt_row + 1..]
+                .iter_mut()
+                .for_each(|row| row[CPU_COL_MAP.is_halted] = F::one());
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use p3_matrix::Matrix;
 
     use super::*;
 
-    use crate::runtime::{tests::simple_program, Runtime};
-    use crate::utils::{run_test, setup_logger, SP1CoreOpts};
+    use crate::runtime::{tests::simple_program, Instruction, Runtime};
+    use crate::stark::{unsatisfied_row, Challenge, RiscvAir};
+    use crate::utils::{run_test, setup_logger, BabyBearPoseidon2, SP1CoreOpts};
 
     // #[test]
     // fn generate_trace() {
@@ -825,7 +885,7 @@ mod tests {
         let chip = CpuChip::default();
         let trace: RowMajorMatrix<BabyBear> =
             chip.generate_trace(&runtime.record, &mut ExecutionRecord::default());
-        for cpu_event in runtime.record.cpu_events {
+        for cpu_event in runtime.record.cpu_events.iter() {
             println!("{:?}", cpu_event);
         }
         println!("{:?}", trace.values)
@@ -837,4 +897,94 @@ mod tests {
         let program = simple_program();
         run_test(program).unwrap();
     }
+
+    /// A program which halts before its last instruction.
+    fn halt_program() -> Program {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HALT as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 29, 0, 5, false, true),
+        ];
+        Program::new(instructions, 0x1000, 0x1000)
+    }
+
+    /// Checks the constraints of the CPU chip on the trace of [halt_program] after `tamper` is
+    /// called with the trace and the index of the HALT row, returning the first unsatisfied row.
+    fn tampered_halt_trace_unsatisfied_row(
+        tamper: impl FnOnce(&mut RowMajorMatrix<BabyBear>, usize),
+    ) -> Option<usize> {
+        let mut runtime = Runtime::new(halt_program(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let shard = machine
+            .shard(runtime.record, &SP1CoreOpts::default())
+            .remove(0);
+        let chip = machine
+            .chips()
+            .iter()
+            .find(|chip| chip.name() == "CPU")
+            .unwrap();
+        let mut trace = chip.generate_trace(&shard, &mut ExecutionRecord::default());
+
+        let first_halted_row = (0..trace.height())
+            .find(|&i| {
+                let row = trace.row_slice(i);
+                let cols: &CpuCols<BabyBear> = (*row).borrow();
+                cols.is_halted == BabyBear::one()
+            })
+            .unwrap();
+        tamper(&mut trace, first_halted_row - 1);
+
+        let perm_challenges = [
+            Challenge::<BabyBearPoseidon2>::from_canonical_u32(7),
+            Challenge::<BabyBearPoseidon2>::from_canonical_u32(11),
+        ];
+        let perm = chip.generate_permutation_trace(None, &trace, &perm_challenges);
+        unsatisfied_row(
+            chip,
+            None,
+            &trace,
+            &perm,
+            &perm_challenges,
+            shard.public_values(),
+        )
+    }
+
+    #[test]
+    fn test_halt_is_last_real_row() {
+        assert_eq!(tampered_halt_trace_unsatisfied_row(|_, _| {}), None);
+    }
+
+    #[test]
+    fn test_real_row_after_halt() {
+        let row = tampered_halt_trace_unsatisfied_row(|trace, halt_row| {
+            let cols: &mut CpuCols<BabyBear> = trace.row_mut(halt_row + 1).borrow_mut();
+            cols.is_real = BabyBear::one();
+        });
+        assert!(row.is_some());
+    }
+
+    #[test]
+    fn test_real_row_after_unflagged_halt() {
+        let row = tampered_halt_trace_unsatisfied_row(|trace, halt_row| {
+            for i in halt_row + 1..trace.height() {
+                let cols: &mut CpuCols<BabyBear> = trace.row_mut(i).borrow_mut();
+                cols.is_halted = BabyBear::zero();
+            }
+            let cols: &mut CpuCols<BabyBear> = trace.row_mut(halt_row + 1).borrow_mut();
+            cols.is_real = BabyBear::one();
+        });
+        assert!(row.is_some());
+    }
+
+    #[test]
+    fn test_unflagged_rows_after_halt() {
+        let row = tampered_halt_trace_unsatisfied_row(|trace, halt_row| {
+            let cols: &mut CpuCols<BabyBear> = trace.row_mut(halt_row + 1).borrow_mut();
+            cols.is_halted = BabyBear::zero();
+        });
+        assert!(row.is_some());
+    }
 }
@@ -1,17 +1,25 @@
 use std::cmp::min;
 use std::collections::BTreeMap;
 
-use elf::abi::{EM_RISCV, ET_EXEC, PF_X, PT_LOAD};
+use elf::abi::{EM_RISCV, ET_EXEC, PF_W, PF_X, PT_LOAD};
 use elf::endian::LittleEndian;
 use elf::file::Class;
 use elf::ElfBytes;
+use serde::{Deserialize, Serialize};
 
 /// The maximum size of the memory in bytes.
 pub const MAXIMUM_MEMORY_SIZE: u32 = u32::MAX;
 
+/// The maximum total size in bytes of the loadable segments of an ELF, including zero-filled
+/// memory such as `.bss`.
+pub const MAXIMUM_MEMORY_IMAGE_SIZE: u32 = 1 << 28;
+
 /// The size of a word in bytes.
 pub const WORD_SIZE: usize = 4;
 
+/// The registers are mapped to the first addresses of memory, so segments must start after them.
+const NUM_REGISTERS: u32 = 32;
+
 /// A RV32IM ELF file.
 #[derive(Debug, Clone)]
 pub struct Elf {
@@ -26,6 +34,31 @@ pub struct Elf {
 
     /// The initial memory image, useful for global constants.
     pub memory_image: BTreeMap<u32, u32>,
+
+    /// The loaded segments, ordered by address.
+    pub segments: Vec<MemorySegment>,
+}
+
+/// A `PT_LOAD` segment of an ELF, as loaded into memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemorySegment {
+    /// The address of the first byte of the segment.
+    pub vaddr: u32,
+    /// The number of bytes loaded from the file.
+    pub file_size: u32,
+    /// The number of bytes occupied in memory. Bytes past `file_size` are zero.
+    pub mem_size: u32,
+    /// Whether the segment may be written to. Read-only segments include `.text` and `.rodata`.
+    pub writable: bool,
+    /// Whether the segment contains instructions.
+    pub executable: bool,
+}
+
+impl MemorySegment {
+    /// The address one past the end of the segment.
+    pub const fn end(&self) -> u64 {
+        self.vaddr as u64 + self.mem_size as u64
+    }
 }
 
 impl Elf {
@@ -35,12 +68,14 @@ impl Elf {
         pc_start: u32,
         pc_base: u32,
         memory_image: BTreeMap<u32, u32>,
+        segments: Vec<MemorySegment>,
     ) -> Self {
         Self {
             instructions,
             pc_start,
             pc_base,
             memory_image,
+            segments,
         }
     }
 
@@ -79,11 +114,20 @@ impl Elf {
             panic!("too many program headers");
         }
 
+        // Only PT_LOAD segments are loaded into memory. They are processed in address order so
+        // that overlaps can be detected and executable segments are laid out contiguously.
+        let mut loaded = segments
+            .iter()
+            .filter(|x| x.p_type == PT_LOAD)
+            .collect::<Vec<_>>();
+        loaded.sort_by_key(|segment| segment.p_vaddr);
+
         let mut instructions: Vec<u32> = Vec::new();
         let mut base_address = u32::MAX;
+        let mut segment_map: Vec<MemorySegment> = Vec::new();
+        let mut image_size: u64 = 0;
 
-        // Only read segments that are executable instructions that are also PT_LOAD.
-        for segment in segments.iter().filter(|x| x.p_type == PT_LOAD) {
+        for segment in loaded {
             // Get the file size of the segment as an u32.
             let file_size: u32 = segment
                 .p_filesz
@@ -110,11 +154,48 @@ impl Elf {
             if vaddr % WORD_SIZE as u32 != 0 {
                 panic!("vaddr {vaddr:08x} is unaligned");
             }
+            if vaddr < NUM_REGISTERS {
+                panic!("segment at 0x{vaddr:08x} overlaps the registers");
+            }
+            if vaddr.checked_add(mem_size).is_none() {
+                panic!(
+                    "segment at 0x{vaddr:08x} with size 0x{mem_size:08x} exceeds the 32-bit address space"
+                );
+            }
 
-            // If the virtual address is less than the first memory address, then update the first
-            // memory address.
-            if (segment.p_flags & PF_X) != 0 && base_address > vaddr {
-                base_address = vaddr;
+            let memory_segment = MemorySegment {
+                vaddr,
+                file_size,
+                mem_size,
+                writable: (segment.p_flags & PF_W) != 0,
+                executable: (segment.p_flags & PF_X) != 0,
+            };
+            if let Some(previous) = segment_map.last() {
+                if previous.end() > vaddr as u64 {
+                    panic!(
+                        "segment at 0x{vaddr:08x} overlaps the segment at 0x{:08x}",
+                        previous.vaddr
+                    );
+                }
+            }
+
+            image_size += mem_size as u64;
+            if image_size > MAXIMUM_MEMORY_IMAGE_SIZE as u64 {
+                panic!(
+                    "the memory image exceeds the maximum size of 0x{MAXIMUM_MEMORY_IMAGE_SIZE:08x} bytes"
+                );
+            }
+
+            // The instructions are indexed from the base address, so executable segments cannot
+            // leave gaps between them.
+            if memory_segment.executable {
+                if base_address == u32::MAX {
+                    base_address = vaddr;
+                } else if base_address as u64 + (instructions.len() * WORD_SIZE) as u64
+                    != vaddr as u64
+                {
+                    panic!("executable segment at 0x{vaddr:08x} is not contiguous with the previous executable segment");
+                }
             }
 
             // Get the offset to the segment.
@@ -145,12 +226,170 @@ impl Elf {
                     word |= (*byte as u32) << (j * 8);
                 }
                 image.insert(addr, word);
-                if (segment.p_flags & PF_X) != 0 {
+                if memory_segment.executable {
                     instructions.push(word);
                 }
             }
+
+            segment_map.push(memory_segment);
         }
 
-        Elf::new(instructions, entry, base_address, image)
+        Elf::new(instructions, entry, base_address, image, segment_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elf::abi::PF_R;
+
+    use super::*;
+    use crate::runtime::{Program, Runtime};
+    use crate::utils::SP1CoreOpts;
+
+    const TEXT_ADDR: u32 = 0x0020_0800;
+    const RODATA_ADDR: u32 = 0xf000_0000;
+    const BSS_ADDR: u32 = 0x0030_0000;
+
+    /// Loads the word at `RODATA_ADDR`, stores it in `.bss` and commits it.
+    const TEXT: [u32; 12] = [
+        0xf000_0337, // lui t1, 0xf0000
+        0x0003_2583, // lw a1, 0(t1)
+        0x0030_03b7, // lui t2, 0x300
+        0x00b3_a023, // sw a1, 0(t2)
+        0x0030_0513, // li a0, 3
+        0x0003_8593, // mv a1, t2
+        0x0040_0613, // li a2, 4
+        0x0020_0293, // li t0, WRITE
+        0x0000_0073, // ecall
+        0x0000_0513, // li a0, 0
+        0x0000_0293, // li t0, HALT
+        0x0000_0073, // ecall
+    ];
+
+    struct Segment {
+        vaddr: u32,
+        flags: u32,
+        data: Vec<u8>,
+        mem_size: u32,
+    }
+
+    /// Builds a little-endian RV32 executable with one program header per segment.
+    fn build_elf(entry: u32, segments: &[Segment]) -> Vec<u8> {
+        const EHDR_SIZE: u32 = 52;
+        const PHDR_SIZE: u32 = 32;
+
+        let mut elf = Vec::new();
+        elf.extend_from_slice(&[0x7f, b'E', b'L', b'F', 1, 1, 1]);
+        elf.resize(16, 0);
+        elf.extend_from_slice(&ET_EXEC.to_le_bytes());
+        elf.extend_from_slice(&EM_RISCV.to_le_bytes());
+        elf.extend_from_slice(&1u32.to_le_bytes());
+        elf.extend_from_slice(&entry.to_le_bytes());
+        elf.extend_from_slice(&EHDR_SIZE.to_le_bytes());
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        elf.extend_from_slice(&(EHDR_SIZE as u16).to_le_bytes());
+        elf.extend_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        elf.extend_from_slice(&(segments.len() as u16).to_le_bytes());
+        elf.extend_from_slice(&[0; 6]); // e_shentsize, e_shnum, e_shstrndx
+
+        let mut offset = EHDR_SIZE + PHDR_SIZE * segments.len() as u32;
+        for segment in segments {
+            let file_size = segment.data.len() as u32;
+            for field in [
+                PT_LOAD,
+                offset,
+                segment.vaddr,
+                segment.vaddr,
+                file_size,
+                segment.mem_size,
+                segment.flags,
+                WORD_SIZE as u32,
+            ] {
+                elf.extend_from_slice(&field.to_le_bytes());
+            }
+            offset += file_size;
+        }
+        for segment in segments {
+            elf.extend_from_slice(&segment.data);
+        }
+        elf
+    }
+
+    fn fixture_segments() -> Vec<Segment> {
+        vec![
+            Segment {
+                vaddr: TEXT_ADDR,
+                flags: PF_R | PF_X,
+                data: TEXT.iter().flat_map(|word| word.to_le_bytes()).collect(),
+                mem_size: (TEXT.len() * WORD_SIZE) as u32,
+            },
+            // Listed out of order, the loader sorts segments by address.
+            Segment {
+                vaddr: RODATA_ADDR,
+                flags: PF_R,
+                data: 0xdead_beef_u32.to_le_bytes().to_vec(),
+                mem_size: 4,
+            },
+            Segment {
+                vaddr: BSS_ADDR,
+                flags: PF_R | PF_W,
+                data: Vec::new(),
+                mem_size: 16,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_load_segments() {
+        let elf = build_elf(TEXT_ADDR, &fixture_segments());
+        let program = Program::from(&elf);
+
+        let segments = program.segments();
+        assert_eq!(
+            segments.iter().map(|s| s.vaddr).collect::<Vec<_>>(),
+            vec![TEXT_ADDR, BSS_ADDR, RODATA_ADDR]
+        );
+        assert!(segments[0].executable && !segments[0].writable);
+        assert!(segments[1].writable && !segments[1].executable);
+        assert!(!segments[2].writable && !segments[2].executable);
+        assert_eq!(program.segment(RODATA_ADDR + 3), Some(&segments[2]));
+        assert_eq!(program.segment(RODATA_ADDR + 4), None);
+
+        assert_eq!(program.pc_base, TEXT_ADDR);
+        assert_eq!(program.instructions.len(), TEXT.len());
+        assert_eq!(program.memory_image[&RODATA_ADDR], 0xdead_beef);
+        assert_eq!(program.memory_image[&(BSS_ADDR + 12)], 0);
+
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert_eq!(
+            runtime.state.public_values_stream,
+            0xdead_beef_u32.to_le_bytes()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps the segment")]
+    fn test_overlapping_segments() {
+        let mut segments = fixture_segments();
+        segments[2].vaddr = TEXT_ADDR + 4;
+        Program::from(&build_elf(TEXT_ADDR, &segments));
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum size")]
+    fn test_memory_image_too_large() {
+        let mut segments = fixture_segments();
+        segments[2].mem_size = MAXIMUM_MEMORY_IMAGE_SIZE;
+        Program::from(&build_elf(TEXT_ADDR, &segments));
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the 32-bit address space")]
+    fn test_segment_past_address_space() {
+        let mut segments = fixture_segments();
+        segments[1].mem_size = 0x1000_0000;
+        Program::from(&build_elf(TEXT_ADDR, &segments));
     }
 }
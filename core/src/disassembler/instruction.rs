@@ -405,13 +405,69 @@ impl InstructionProcessor for InstructionTranspiler {
     }
 }
 
+/// Decodes the instructions of the Zbb extension supported by the zkVM, which `rrs_lib` doesn't
+/// know: CLZ, CTZ and CPOP, encoded as an I-type `OP-IMM` with `funct3 = 1`, and REV8, with
+/// `funct3 = 5`. They read `rs1` only, and their immediate selects the operation.
+fn decode_zbb(insn: u32) -> Option<Instruction> {
+    const OP_IMM: u32 = 0b0010011;
+    if insn & 0x7f != OP_IMM {
+        return None;
+    }
+    let funct3 = (insn >> 12) & 0x7;
+    let imm = insn >> 20;
+    let opcode = match (funct3, imm) {
+        (0b001, 0x600) => Opcode::CLZ,
+        (0b001, 0x601) => Opcode::CTZ,
+        (0b001, 0x602) => Opcode::CPOP,
+        (0b101, 0x698) => Opcode::REV8,
+        _ => return None,
+    };
+    let rd = (insn >> 7) & 0x1f;
+    let rs1 = (insn >> 15) & 0x1f;
+    Some(Instruction::new(opcode, rd, rs1, 0, false, true))
+}
+
 /// Transpile the instructions from the 32-bit encoded instructions.
 pub fn transpile(instructions_u32: &[u32]) -> Vec<Instruction> {
     let mut instructions = Vec::new();
     let mut transpiler = InstructionTranspiler;
     for instruction_u32 in instructions_u32 {
-        let instruction = process_instruction(&mut transpiler, *instruction_u32).unwrap();
+        let instruction = match decode_zbb(*instruction_u32) {
+            Some(instruction) => instruction,
+            None => process_instruction(&mut transpiler, *instruction_u32).unwrap_or_else(|| {
+                panic!(
+                    "unsupported instruction {:#010x}: of the Zbb extension, only clz, ctz, cpop \
+                     and rev8 are supported",
+                    instruction_u32
+                )
+            }),
+        };
         instructions.push(instruction);
     }
     instructions
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpile_zbb() {
+        // clz a0, a1; ctz a0, a1; cpop a0, a1; rev8 a0, a1; slli a0, a1, 1.
+        let instructions = transpile(&[0x60059513, 0x60159513, 0x60259513, 0x6985d513, 0x00159513]);
+        let opcodes = [
+            Opcode::CLZ,
+            Opcode::CTZ,
+            Opcode::CPOP,
+            Opcode::REV8,
+            Opcode::SLL,
+        ];
+        for (instruction, opcode) in instructions.iter().zip(opcodes) {
+            assert_eq!(instruction.opcode, opcode);
+            assert_eq!(instruction.op_a, Register::X10 as u32);
+            assert_eq!(instruction.op_b, Register::X11 as u32);
+        }
+        assert_eq!(instructions[0].op_c, 0);
+        assert_eq!(instructions[4].op_c, 1);
+    }
+}
@@ -6,7 +6,7 @@ pub use instruction::*;
 
 use std::{collections::BTreeMap, fs::File, io::Read};
 
-use crate::runtime::{Instruction, Program};
+use crate::runtime::{Instruction, MemoryLayout, Program};
 
 impl Program {
     /// Create a new program.
@@ -16,6 +16,8 @@ impl Program {
             pc_start,
             pc_base,
             memory_image: BTreeMap::new(),
+            segments: Vec::new(),
+            layout: None,
         }
     }
 
@@ -33,6 +35,8 @@ impl Program {
             pc_start: elf.pc_start,
             pc_base: elf.pc_base,
             memory_image: elf.memory_image,
+            layout: Some(MemoryLayout::new(&elf.segments)),
+            segments: elf.segments,
         }
     }
 
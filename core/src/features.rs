@@ -0,0 +1,72 @@
+//! The optional features a proof can be generated with.
+//!
+//! Some features change the layout or the meaning of the public values of a proof, which a
+//! verifier unaware of them would misinterpret. The features of a proof are a bitmap, committed
+//! to in the [crate::air::PublicValues::features] word of its shards and carried by the proofs of
+//! the SDK. Verifiers reject the bits they don't support, see [unsupported_features].
+
+use serde::Serialize;
+
+/// The program committed to the digest of the input buffers it read, in
+/// [crate::air::PublicValues::input_digest], which is zero without it.
+pub const INPUT_DIGEST_FEATURE: u32 = 1 << 0;
+
+/// The program committed to the digest of the config it was run with, in
+/// [crate::air::PublicValues::config_digest], which is zero without it.
+pub const CONFIG_DIGEST_FEATURE: u32 = 1 << 1;
+
+/// The bitmap of the features supported by this version of SP1.
+pub const SUPPORTED_FEATURES: u32 = INPUT_DIGEST_FEATURE | CONFIG_DIGEST_FEATURE;
+
+/// A feature supported by this version of SP1, see [supported_features].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FeatureInfo {
+    /// The bit of the feature in the bitmap.
+    pub bit: u32,
+    /// The name of the feature.
+    pub name: &'static str,
+    /// The first version of SP1 supporting the feature.
+    pub since: &'static str,
+}
+
+const FEATURES: [FeatureInfo; 2] = [
+    FeatureInfo {
+        bit: INPUT_DIGEST_FEATURE,
+        name: "input-digest",
+        since: "v1.0.8-testnet",
+    },
+    FeatureInfo {
+        bit: CONFIG_DIGEST_FEATURE,
+        name: "config-digest",
+        since: "v1.0.8-testnet",
+    },
+];
+
+/// The compatibility table of the features supported by this version of SP1, so that services
+/// can check the features of the work they submit before proving it.
+pub fn supported_features() -> &'static [FeatureInfo] {
+    &FEATURES
+}
+
+/// The bits of `features` not supported by this version of SP1.
+pub const fn unsupported_features(features: u32) -> u32 {
+    features & !SUPPORTED_FEATURES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_features() {
+        let bitmap = supported_features()
+            .iter()
+            .fold(0, |bitmap, feature| bitmap | feature.bit);
+        assert_eq!(bitmap, SUPPORTED_FEATURES);
+        assert_eq!(unsupported_features(SUPPORTED_FEATURES), 0);
+        assert_eq!(
+            unsupported_features(1 << 31 | INPUT_DIGEST_FEATURE),
+            1 << 31
+        );
+    }
+}
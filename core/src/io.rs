@@ -4,7 +4,378 @@ use crate::{
 };
 use k256::sha2::{Digest, Sha256};
 use num_bigint::BigUint;
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::CryptographicHasher;
+use rand::RngCore;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Domain separator prepended to the canonical config encoding before hashing.
+pub const CONFIG_DIGEST_DOMAIN: &[u8] = b"SP1_CONFIG_V1";
+
+/// Domain separator prepended to the entropy consumed by the guest before hashing, see
+/// [input_digest_with_entropy].
+pub const ENTROPY_DIGEST_DOMAIN: &[u8] = b"SP1_ENTROPY_V1";
+
+/// The config key holding the files written by [SP1Stdin::write_file_tree]. Must match
+/// `FILE_TREE_CONFIG_KEY` in `zkvm/entrypoint/src/fs.rs`.
+pub const FILE_TREE_CONFIG_KEY: &str = "sp1.fs";
+
+/// The largest encoding of the arguments written with [SP1Stdin::write_args]. Must match
+/// `MAX_ARGS_LEN` in `zkvm/entrypoint/src/args.rs`.
+pub const MAX_ARGS_LEN: usize = 1 << 16;
+
+/// Tag of a span committed by `sp1_zkvm::io::commit_span_cycles`, see [SpanCycles].
+pub const SPAN_CYCLES_TAG: &[u8; 8] = b"SP1SPAN\x01";
+
+/// The number of cycles of a named span of the program, committed to the public values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanCycles {
+    pub name: String,
+    pub cycles: u64,
+}
+
+impl SpanCycles {
+    /// The canonical encoding of the span, as committed by the guest:
+    ///
+    /// ```text
+    /// SPAN_CYCLES_TAG || name_len || name || cycles
+    /// ```
+    ///
+    /// where `name_len` is a little-endian `u32` and `cycles` a little-endian `u64`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SPAN_CYCLES_TAG.len() + 4 + self.name.len() + 8);
+        bytes.extend_from_slice(SPAN_CYCLES_TAG);
+        bytes.extend_from_slice(&(self.name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.name.as_bytes());
+        bytes.extend_from_slice(&self.cycles.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a span from exactly the bytes of its canonical encoding, returning `None` if they
+    /// are anything else.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let rest = bytes.strip_prefix(SPAN_CYCLES_TAG.as_slice())?;
+        let (name_len, rest) = rest.split_first_chunk::<4>()?;
+        let name_len = u32::from_le_bytes(*name_len) as usize;
+        if rest.len() != name_len.checked_add(8)? {
+            return None;
+        }
+        let (name, cycles) = rest.split_at(name_len);
+        Some(Self {
+            name: String::from_utf8(name.to_vec()).ok()?,
+            cycles: u64::from_le_bytes(cycles.try_into().unwrap()),
+        })
+    }
+}
+
+/// The file descriptor the chunks committed by `sp1_zkvm::io::commit_chunk` are written to.
+pub const FD_OUTPUT_CHUNKS: u32 = 6;
+
+/// The file descriptor the reports of the guest tests are written to, see
+/// [crate::utils::GuestTestRunner].
+pub const FD_TEST_REPORTS: u32 = 7;
+
+/// Tag of the public values of a chunked commitment, see [SP1PublicValues::output_chunks].
+pub const CHUNKED_COMMIT_TAG: &[u8; 8] = b"SP1CHNK\x01";
+
+/// The length of the public values of a chunked commitment:
+///
+/// ```text
+/// CHUNKED_COMMIT_TAG || chunk_size || num_chunks || chain
+/// ```
+///
+/// where `chunk_size` is a little-endian `u32` and `num_chunks` a little-endian `u64`.
+pub const CHUNKED_COMMIT_LEN: usize = 8 + 4 + 8 + 32;
+
+/// A chunk of the outputs of a program, checked against its chunked commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputChunk {
+    pub index: usize,
+    pub len: usize,
+    /// The SHA-256 digest of the chunk, folded into the committed hash chain.
+    pub digest: [u8; 32],
+}
+
+/// An error checking the chunks captured by the host against a chunked commitment.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ChunkedCommitError {
+    #[error("the public values are not a chunked commitment")]
+    NotChunked,
+    #[error("the public values mix a chunked commitment with other committed values")]
+    MixedCommits,
+    #[error("{captured} chunks were captured, but {committed} were committed")]
+    ChunkCount { committed: u64, captured: usize },
+    #[error("chunk {index} has {len} bytes, but the chunk size is {chunk_size}")]
+    ChunkSize {
+        index: usize,
+        len: usize,
+        chunk_size: u32,
+    },
+    #[error("the captured chunks do not match the committed hash chain")]
+    ChainMismatch,
+}
+
+/// The initial value of the hash chain of a chunked commitment with chunks of `chunk_size` bytes.
+fn chunk_chain_init(chunk_size: u32) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(CHUNKED_COMMIT_TAG);
+    hasher.update(chunk_size.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Folds the digest of a chunk into the hash chain of a chunked commitment.
+fn chunk_chain_step(chain: &[u8; 32], digest: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(chain);
+    hasher.update(digest);
+    hasher.finalize().into()
+}
+
+/// The file descriptor the state suspended by `sp1_zkvm::io::suspend_state` is written to.
+pub const FD_SEGMENT_STATE: u32 = 8;
+
+/// Tag of the state digests committed by a segment, see [SegmentDigests].
+pub const SEGMENT_DIGESTS_TAG: &[u8; 8] = b"SP1SEGM\x01";
+
+/// Domain separator prepended to the state of a segment before hashing, see
+/// [segment_state_digest].
+pub const SEGMENT_STATE_DOMAIN: &[u8] = b"SP1_SEGMENT_STATE_V1";
+
+/// The digest of the serialized state of a segment, `sha256(SEGMENT_STATE_DOMAIN || state)`.
+pub fn segment_state_digest(state: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SEGMENT_STATE_DOMAIN);
+    hasher.update(state);
+    hasher.finalize().into()
+}
+
+/// The digests of the state a segment of a computation resumed from and of the state it suspended,
+/// committed by `sp1_zkvm::io::suspend_state` as the last public values of the segment:
+///
+/// ```text
+/// SEGMENT_DIGESTS_TAG || input || output
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentDigests {
+    pub input: [u8; 32],
+    pub output: [u8; 32],
+}
+
+impl SegmentDigests {
+    /// The length of the encoding of the digests.
+    pub const LEN: usize = 8 + 32 + 32;
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(SEGMENT_DIGESTS_TAG);
+        bytes[8..40].copy_from_slice(&self.input);
+        bytes[40..].copy_from_slice(&self.output);
+        bytes
+    }
+
+    /// Decodes the digests from the end of the public values of a segment, returning `None` if
+    /// they do not end with them.
+    pub fn decode(public_values: &[u8]) -> Option<Self> {
+        let start = public_values.len().checked_sub(Self::LEN)?;
+        let bytes = public_values[start..].strip_prefix(SEGMENT_DIGESTS_TAG.as_slice())?;
+        Some(Self {
+            input: bytes[..32].try_into().unwrap(),
+            output: bytes[32..].try_into().unwrap(),
+        })
+    }
+}
+
+/// An error chaining the public values of the segments of a computation, see
+/// [verify_segment_chain].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SegmentChainError {
+    #[error("the segment chain is empty")]
+    Empty,
+    #[error("segment {0} does not commit its state digests")]
+    NotSegment(usize),
+    #[error("segment {0} does not resume from the state of segment {}", .0 - 1)]
+    Unlinked(usize),
+}
+
+/// Checks that the public values of consecutive segments link, each segment resuming from the
+/// state the previous one suspended, and returns the digests of the chain: the state the first
+/// segment resumed from and the state the last one suspended.
+///
+/// This only checks the public values: the proofs of the segments must be verified separately.
+/// `sp1_zkvm::precompiles::verify::verify_segment_chain` does both in an aggregation program.
+pub fn verify_segment_chain(
+    public_values: &[SP1PublicValues],
+) -> Result<SegmentDigests, SegmentChainError> {
+    let (first, rest) = public_values
+        .split_first()
+        .ok_or(SegmentChainError::Empty)?;
+    let mut chain = first
+        .segment_digests()
+        .ok_or(SegmentChainError::NotSegment(0))?;
+    for (index, public_values) in rest.iter().enumerate().map(|(i, pv)| (i + 1, pv)) {
+        let digests = public_values
+            .segment_digests()
+            .ok_or(SegmentChainError::NotSegment(index))?;
+        if digests.input != chain.output {
+            return Err(SegmentChainError::Unlinked(index));
+        }
+        chain.output = digests.output;
+    }
+    Ok(chain)
+}
+
+/// The file descriptor the entries committed by `sp1_zkvm::io::commit_kv` are written to.
+pub const FD_KV_ENTRIES: u32 = 9;
+
+/// Tag of a key-value commitment, see [KvCommitment].
+pub const KV_COMMIT_TAG: &[u8; 8] = b"SP1KVCM\x01";
+
+/// The length of the encoding of a [KvCommitment].
+pub const KV_COMMIT_LEN: usize = 8 + 8 + 32;
+
+/// The root of the key-value entries committed by `sp1_zkvm::io::commit_kv`, whose entries are
+/// captured by the host, see `SP1Prover::execute_with_kv_entries`.
+///
+/// Each entry is encoded as `key_len || key || value_len || value`, where the lengths are
+/// little-endian `u32`s, and the keys are strictly increasing in lexicographic order. The root
+/// starts at `keccak256(KV_COMMIT_TAG)`, into which each entry is folded as
+/// `root = keccak256(root || keccak256(entry))`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KvCommitment {
+    pub num_entries: u64,
+    pub root: [u8; 32],
+}
+
+/// An error checking the entries captured by the host against a key-value commitment.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum KvCommitError {
+    #[error("{captured} entries were captured, but {committed} were committed")]
+    EntryCount { committed: u64, captured: usize },
+    #[error("entry {0} is not a canonical key-value encoding")]
+    Malformed(usize),
+    #[error("the key of entry {0} is not greater than the key of the previous entry")]
+    KeyOrder(usize),
+    #[error("the captured entries do not match the committed root")]
+    RootMismatch,
+}
+
+impl KvCommitment {
+    /// The commitment of `entries`, as computed by the guest, with the keys in strictly increasing
+    /// order. Panics if they are not.
+    pub fn from_entries<K: AsRef<[u8]>, V: AsRef<[u8]>>(entries: &[(K, V)]) -> Self {
+        let mut root = keccak256(KV_COMMIT_TAG);
+        for (index, (key, value)) in entries.iter().enumerate() {
+            if index > 0 {
+                assert!(
+                    entries[index - 1].0.as_ref() < key.as_ref(),
+                    "the keys of a key-value commitment must be strictly increasing"
+                );
+            }
+            root = kv_root_step(&root, &encode_kv_entry(key.as_ref(), value.as_ref()));
+        }
+        Self {
+            num_entries: entries.len() as u64,
+            root,
+        }
+    }
+
+    /// The public values of the commitment, `KV_COMMIT_TAG || num_entries || root`, where
+    /// `num_entries` is a little-endian `u64`.
+    pub fn encode(&self) -> [u8; KV_COMMIT_LEN] {
+        let mut bytes = [0u8; KV_COMMIT_LEN];
+        bytes[..8].copy_from_slice(KV_COMMIT_TAG);
+        bytes[8..16].copy_from_slice(&self.num_entries.to_le_bytes());
+        bytes[16..].copy_from_slice(&self.root);
+        bytes
+    }
+
+    /// Decodes a commitment from exactly the bytes of its encoding, returning `None` if they are
+    /// anything else.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let rest = bytes.strip_prefix(KV_COMMIT_TAG.as_slice())?;
+        if rest.len() != 8 + 32 {
+            return None;
+        }
+        Some(Self {
+            num_entries: u64::from_le_bytes(rest[..8].try_into().unwrap()),
+            root: rest[8..].try_into().unwrap(),
+        })
+    }
+
+    /// Recomputes the root from the entries captured by the host, checking their encoding and the
+    /// order of their keys, and returns the keys and values if it matches the committed one.
+    ///
+    /// The entries of the commitments of a program are captured in the order they are committed,
+    /// so those of a commitment are the `num_entries` following the entries of the previous ones.
+    pub fn verify_entries(
+        &self,
+        entries: &[Vec<u8>],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, KvCommitError> {
+        if self.num_entries != entries.len() as u64 {
+            return Err(KvCommitError::EntryCount {
+                committed: self.num_entries,
+                captured: entries.len(),
+            });
+        }
+        let mut root = keccak256(KV_COMMIT_TAG);
+        let mut decoded: Vec<(Vec<u8>, Vec<u8>)> = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.iter().enumerate() {
+            let (key, value) = decode_kv_entry(entry).ok_or(KvCommitError::Malformed(index))?;
+            if decoded
+                .last()
+                .is_some_and(|(last_key, _)| last_key.as_slice() >= key)
+            {
+                return Err(KvCommitError::KeyOrder(index));
+            }
+            root = kv_root_step(&root, entry);
+            decoded.push((key.to_vec(), value.to_vec()));
+        }
+        if root != self.root {
+            return Err(KvCommitError::RootMismatch);
+        }
+        Ok(decoded)
+    }
+}
+
+/// The canonical encoding of an entry of a key-value commitment, see [KvCommitment].
+pub fn encode_kv_entry(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(4 + key.len() + 4 + value.len());
+    entry.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    entry.extend_from_slice(key);
+    entry.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    entry.extend_from_slice(value);
+    entry
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256Hash.hash_slice(data)
+}
+
+/// Splits the canonical encoding of an entry into its key and value, returning `None` if it is
+/// anything else.
+fn decode_kv_entry(entry: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (key_len, rest) = entry.split_first_chunk::<4>()?;
+    let key_len = u32::from_le_bytes(*key_len) as usize;
+    if rest.len() < key_len {
+        return None;
+    }
+    let (key, rest) = rest.split_at(key_len);
+    let (value_len, value) = rest.split_first_chunk::<4>()?;
+    if value.len() != u32::from_le_bytes(*value_len) as usize {
+        return None;
+    }
+    Some((key, value))
+}
+
+/// Folds the encoding of an entry into the root of a key-value commitment.
+fn kv_root_step(root: &[u8; 32], entry: &[u8]) -> [u8; 32] {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(root);
+    bytes[32..].copy_from_slice(&keccak256(entry));
+    keccak256(&bytes)
+}
 
 /// Standard input for the prover.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -17,6 +388,86 @@ pub struct SP1Stdin {
         ShardProof<BabyBearPoseidon2>,
         StarkVerifyingKey<BabyBearPoseidon2>,
     )>,
+    /// Key/value configuration exposed to the guest through `io::config`. Kept sorted by key so
+    /// that the encoding (and therefore the committed digest) is canonical.
+    #[serde(default)]
+    pub config: BTreeMap<String, Vec<u8>>,
+    /// Entropy pool served to the guest's `getrandom`, consumed in order. Stored with the rest of
+    /// the input so that a run can be replayed with the same randomness.
+    #[serde(default)]
+    pub entropy: Vec<u8>,
+}
+
+/// The number of input bytes shown in a [ReadError].
+const READ_ERROR_PREFIX_LEN: usize = 16;
+
+/// An error deserializing an input buffer, mirroring the `ReadError` of `sp1_zkvm::io::try_read`.
+///
+/// The read index is the index of the buffer in [SP1Stdin::buffer], which is also the number of
+/// `write` calls made before the one that wrote it. The message is formatted like the guest's, so
+/// that a guest panic can be parsed back with [ReadError::index_from_message].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("failed to deserialize `{type_name}` at read index {index}: {message} (consumed {consumed} of {available} bytes, input starts with 0x{prefix})")]
+pub struct ReadError {
+    pub index: usize,
+    pub type_name: String,
+    /// The number of bytes consumed before deserialization failed.
+    pub consumed: usize,
+    /// The length of the buffer.
+    pub available: usize,
+    /// The first bytes of the buffer, hex encoded.
+    pub prefix: String,
+    pub message: String,
+}
+
+impl ReadError {
+    /// Finds the read index of the last read error in a guest panic message.
+    pub fn index_from_message(message: &str) -> Option<usize> {
+        let (_, rest) = message.rsplit_once(" at read index ")?;
+        let digits = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+        digits.parse().ok()
+    }
+}
+
+/// Public values with a fixed layout, declared in the guest with `sp1_zkvm::sp1_public_values!`.
+///
+/// Implementations are generated from the declaration embedded in the program's ELF by
+/// `sp1_helper::generate_public_values_bindings`, so that the host reads the public values with
+/// the layout the guest commits them with.
+pub trait FromPublicValues: Sized {
+    /// The name of the declared struct.
+    const NAME: &'static str;
+    /// The number of bytes committed by the guest.
+    const LEN: usize;
+    /// The digest of the declaration the implementation was generated from.
+    const DECLARATION_DIGEST: u64;
+
+    /// Decodes the values from exactly [Self::LEN] bytes.
+    fn decode(bytes: &[u8]) -> Self;
+
+    /// Decodes the values from the public values, which must be exactly the declared values.
+    fn from_public_values(
+        public_values: &SP1PublicValues,
+    ) -> Result<Self, PublicValuesLengthError> {
+        let bytes = public_values.as_slice();
+        if bytes.len() != Self::LEN {
+            return Err(PublicValuesLengthError {
+                name: Self::NAME,
+                expected: Self::LEN,
+                actual: bytes.len(),
+            });
+        }
+        Ok(Self::decode(bytes))
+    }
+}
+
+/// Public values whose length differs from the declared layout of a [FromPublicValues] struct.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("the public values have {actual} bytes, but `{name}` is declared with {expected}")]
+pub struct PublicValuesLengthError {
+    pub name: &'static str,
+    pub expected: usize,
+    pub actual: usize,
 }
 
 /// Public values for the prover.
@@ -32,6 +483,8 @@ impl SP1Stdin {
             buffer: Vec::new(),
             ptr: 0,
             proofs: Vec::new(),
+            config: BTreeMap::new(),
+            entropy: Vec::new(),
         }
     }
 
@@ -41,15 +494,51 @@ impl SP1Stdin {
             buffer: vec![data.to_vec()],
             ptr: 0,
             proofs: Vec::new(),
+            config: BTreeMap::new(),
+            entropy: Vec::new(),
         }
     }
 
     /// Read a value from the buffer.
     pub fn read<T: DeserializeOwned>(&mut self) -> T {
-        let result: T =
-            bincode::deserialize(&self.buffer[self.ptr]).expect("failed to deserialize");
+        self.try_read().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Read a value from the buffer, returning a [ReadError] if it cannot be deserialized as a
+    /// `T`. The buffer is consumed either way.
+    pub fn try_read<T: DeserializeOwned>(&mut self) -> Result<T, ReadError> {
+        let index = self.ptr;
+        let bytes = &self.buffer[index];
         self.ptr += 1;
-        result
+        let mut cursor = std::io::Cursor::new(bytes.as_slice());
+        bincode::deserialize_from(&mut cursor).map_err(|err| ReadError {
+            index,
+            type_name: std::any::type_name::<T>().to_string(),
+            consumed: cursor.position() as usize,
+            available: bytes.len(),
+            prefix: hex::encode(&bytes[..bytes.len().min(READ_ERROR_PREFIX_LEN)]),
+            message: err.to_string(),
+        })
+    }
+
+    /// Describes the buffer the guest failed to deserialize, given the message of a guest panic
+    /// raised by `sp1_zkvm::io::read`. Returns `None` if the message is not a read error.
+    pub fn describe_read_error(&self, message: &str) -> Option<String> {
+        let index = ReadError::index_from_message(message)?;
+        let description = match self.buffer.get(index) {
+            Some(bytes) => format!(
+                "read index {} is the input written by write call #{} ({} bytes)",
+                index,
+                index,
+                bytes.len()
+            ),
+            None => format!(
+                "read index {} is past the {} inputs written to SP1Stdin",
+                index,
+                self.buffer.len()
+            ),
+        };
+        Some(description)
     }
 
     /// Read a slice of bytes from the buffer.
@@ -81,6 +570,220 @@ impl SP1Stdin {
     ) {
         self.proofs.push((proof, vk));
     }
+
+    /// Append bytes to the entropy pool served to the guest's `getrandom`.
+    ///
+    /// The guest panics if it requests more entropy than the pool holds, including when no entropy
+    /// is written at all. Guests built with the `insecure-rand` feature of sp1-zkvm fall back to a
+    /// fixed-seed generator instead when no entropy is written.
+    pub fn write_entropy(&mut self, seed: &[u8]) {
+        self.entropy.extend_from_slice(seed);
+    }
+
+    /// Append `len` bytes from the operating system's random number generator to the entropy pool.
+    /// The bytes are recorded in the input, so serializing it replays the same randomness.
+    pub fn write_random_entropy(&mut self, len: usize) {
+        let mut seed = vec![0u8; len];
+        rand::rngs::OsRng.fill_bytes(&mut seed);
+        self.write_entropy(&seed);
+    }
+
+    /// Write the arguments received by a program declared with `entrypoint!(main, args)`.
+    ///
+    /// The arguments are the first input buffer, so they are part of [SP1Stdin::digest]. Panics
+    /// if any other input has been written, or if their encoding is larger than [MAX_ARGS_LEN].
+    pub fn write_args<S: AsRef<str>>(&mut self, args: &[S]) {
+        self.write_args_with_env(args, &BTreeMap::new());
+    }
+
+    /// Write the arguments and the environment received by a program declared with
+    /// `entrypoint!(main, args, env)`, see [SP1Stdin::write_args].
+    ///
+    /// The layout is the number of arguments followed by each argument as `len || arg`, then the
+    /// number of variables followed by each variable in key order as
+    /// `key_len || key || value_len || value`, where all lengths are little-endian `u32`s.
+    pub fn write_args_with_env<S: AsRef<str>>(
+        &mut self,
+        args: &[S],
+        env: &BTreeMap<String, String>,
+    ) {
+        assert!(
+            self.buffer.is_empty(),
+            "the arguments must be written before any other input"
+        );
+        fn push(bytes: &mut Vec<u8>, data: &[u8]) {
+            bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+        let mut bytes = (args.len() as u32).to_le_bytes().to_vec();
+        for arg in args {
+            push(&mut bytes, arg.as_ref().as_bytes());
+        }
+        bytes.extend_from_slice(&(env.len() as u32).to_le_bytes());
+        for (key, value) in env {
+            push(&mut bytes, key.as_bytes());
+            push(&mut bytes, value.as_bytes());
+        }
+        assert!(
+            bytes.len() <= MAX_ARGS_LEN,
+            "the arguments have {} bytes, more than the limit of {}",
+            bytes.len(),
+            MAX_ARGS_LEN
+        );
+        self.buffer.push(bytes);
+    }
+
+    /// Write a config entry that the guest can look up with `io::config(key)`.
+    ///
+    /// Panics if `key` has already been written.
+    pub fn write_config(&mut self, key: &str, value: &[u8]) {
+        assert!(
+            !self.config.contains_key(key),
+            "duplicate config key: {}",
+            key
+        );
+        self.config.insert(key.to_string(), value.to_vec());
+    }
+
+    /// Write a read-only file tree that the guest can read with the functions of `sp1_zkvm::fs`.
+    ///
+    /// Paths are normalized with [normalize_file_path], so relative paths are relative to the
+    /// root. The files are stored in the config under [FILE_TREE_CONFIG_KEY], which means the
+    /// guest commits to their contents through the config digest.
+    ///
+    /// Panics if called more than once, if two files have the same normalized path, or if a file
+    /// is also the parent directory of another one.
+    pub fn write_file_tree<P: AsRef<str>, B: AsRef<[u8]>>(&mut self, files: &[(P, B)]) {
+        let mut tree = BTreeMap::new();
+        for (path, bytes) in files {
+            let path = normalize_file_path(path.as_ref());
+            assert!(path != "/", "the root of the file tree is a directory");
+            let previous = tree.insert(path.clone(), bytes.as_ref().to_vec());
+            assert!(previous.is_none(), "duplicate file: {}", path);
+        }
+        for path in tree.keys() {
+            let dir = format!("{}/", path);
+            let child = tree.range(dir.clone()..).next();
+            assert!(
+                !child.is_some_and(|(child, _)| child.starts_with(&dir)),
+                "{} is both a file and a directory",
+                path
+            );
+        }
+        let bytes = bincode::serialize(&tree).expect("serialization failed");
+        self.write_config(FILE_TREE_CONFIG_KEY, &bytes);
+    }
+
+    /// The canonical encoding of the config map, as read by the guest.
+    ///
+    /// The layout is the number of entries followed by each entry in key order, where every
+    /// entry is `key_len || key || value_len || value` and all lengths are little-endian `u32`s.
+    pub fn config_bytes(&self) -> Vec<u8> {
+        if self.config.is_empty() {
+            return Vec::new();
+        }
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.config.len() as u32).to_le_bytes());
+        for (key, value) in self.config.iter() {
+            bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(key.as_bytes());
+            bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(value);
+        }
+        bytes
+    }
+
+    /// The digest of the config map, committed by the guest on halt in
+    /// [crate::air::PublicValues::config_digest]. A program given no config commits the digest of
+    /// the empty config.
+    ///
+    /// ```text
+    /// sha256(CONFIG_DIGEST_DOMAIN || config_bytes)
+    /// ```
+    pub fn config_digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(CONFIG_DIGEST_DOMAIN);
+        hasher.update(self.config_bytes());
+        hasher.finalize().into()
+    }
+
+    /// The digest of the input buffers, as committed by a guest built with the `commit-input`
+    /// feature of `sp1-zkvm` that reads every buffer. See [input_digest].
+    pub fn digest(&self) -> [u8; 32] {
+        input_digest(&self.buffer)
+    }
+
+    /// The digest committed by a guest that only reads the first `num_buffers` input buffers.
+    pub fn digest_first(&self, num_buffers: usize) -> [u8; 32] {
+        input_digest(&self.buffer[..num_buffers.min(self.buffer.len())])
+    }
+
+    /// The digest committed by a guest that reads every buffer and consumes the first `consumed`
+    /// bytes of the entropy pool. See [input_digest_with_entropy].
+    pub fn digest_with_entropy(&self, consumed: usize) -> [u8; 32] {
+        input_digest_with_entropy(
+            &self.buffer,
+            &self.entropy[..consumed.min(self.entropy.len())],
+        )
+    }
+}
+
+/// Normalizes a path of the file tree written by [SP1Stdin::write_file_tree] to an absolute path
+/// without `.`, `..` or repeated separators, the same way the guest does.
+///
+/// The guest has no current directory, so relative paths are relative to the root, and `..` at
+/// the root stays at the root.
+pub fn normalize_file_path(path: &str) -> String {
+    let mut components = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            name => components.push(name),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
+
+/// Hashes input buffers the way the guest does as it reads them.
+///
+/// Each buffer is absorbed as its little-endian `u32` length followed by its bytes, so only the
+/// buffers the guest actually reads are covered, and splitting the same bytes into different
+/// buffers produces a different digest. Proofs and config entries are not part of the digest.
+///
+/// ```text
+/// sha256(len_0 || buffer_0 || len_1 || buffer_1 || ...)
+/// ```
+pub fn input_digest(buffers: &[Vec<u8>]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for buffer in buffers {
+        hasher.update((buffer.len() as u32).to_le_bytes());
+        hasher.update(buffer);
+    }
+    hasher.finalize().into()
+}
+
+/// Hashes input buffers together with the entropy consumed by the guest's `getrandom`.
+///
+/// A guest that consumed no entropy commits the plain [input_digest], otherwise
+///
+/// ```text
+/// sha256(input_digest(buffers) || sha256(ENTROPY_DIGEST_DOMAIN || entropy))
+/// ```
+pub fn input_digest_with_entropy(buffers: &[Vec<u8>], entropy: &[u8]) -> [u8; 32] {
+    let digest = input_digest(buffers);
+    if entropy.is_empty() {
+        return digest;
+    }
+    let mut entropy_hasher = Sha256::new();
+    entropy_hasher.update(ENTROPY_DIGEST_DOMAIN);
+    entropy_hasher.update(entropy);
+    let mut hasher = Sha256::new();
+    hasher.update(digest);
+    hasher.update(entropy_hasher.finalize());
+    hasher.finalize().into()
 }
 
 impl SP1PublicValues {
@@ -130,6 +833,111 @@ impl SP1PublicValues {
         self.buffer.write_slice(slice);
     }
 
+    /// Read a span committed with `sp1_zkvm::io::commit_span_cycles`.
+    ///
+    /// Panics if the next committed bytes are not a span.
+    pub fn read_span_cycles(&mut self) -> SpanCycles {
+        let mut tag = [0u8; 8];
+        self.buffer.read_slice(&mut tag);
+        assert_eq!(
+            &tag, SPAN_CYCLES_TAG,
+            "the next committed value is not a span"
+        );
+        let mut name_len = [0u8; 4];
+        self.buffer.read_slice(&mut name_len);
+        let mut name = vec![0u8; u32::from_le_bytes(name_len) as usize];
+        self.buffer.read_slice(&mut name);
+        let mut cycles = [0u8; 8];
+        self.buffer.read_slice(&mut cycles);
+        SpanCycles {
+            name: String::from_utf8(name).expect("span name is not utf-8"),
+            cycles: u64::from_le_bytes(cycles),
+        }
+    }
+
+    /// Read a key-value commitment committed with `sp1_zkvm::io::commit_kv`, whose entries are
+    /// checked with [KvCommitment::verify_entries].
+    ///
+    /// Panics if the next committed bytes are not a key-value commitment.
+    pub fn read_kv_commitment(&mut self) -> KvCommitment {
+        let mut bytes = [0u8; KV_COMMIT_LEN];
+        self.buffer.read_slice(&mut bytes);
+        KvCommitment::decode(&bytes)
+            .expect("the next committed value is not a key-value commitment")
+    }
+
+    /// Checks the chunks captured by the host while executing the program, see
+    /// `SP1Prover::execute_with_output_chunks`, against the chunked commitment of the public
+    /// values, and returns the digest of each of them.
+    ///
+    /// The public values of a program committing its outputs with `sp1_zkvm::io::commit_chunk` are
+    /// the chunk size, the number of chunks and a hash chain starting at
+    /// `sha256(CHUNKED_COMMIT_TAG || chunk_size)`, into which the digest of each chunk is folded as
+    /// `chain = sha256(chain || sha256(chunk))`. Every chunk has the chunk size, except the last
+    /// one, which may be shorter.
+    pub fn output_chunks(
+        &self,
+        chunks: &[Vec<u8>],
+    ) -> Result<Vec<OutputChunk>, ChunkedCommitError> {
+        let bytes = self.buffer.data.as_slice();
+        let Some(rest) = bytes.strip_prefix(CHUNKED_COMMIT_TAG.as_slice()) else {
+            if bytes
+                .windows(CHUNKED_COMMIT_TAG.len())
+                .any(|w| w == CHUNKED_COMMIT_TAG)
+            {
+                return Err(ChunkedCommitError::MixedCommits);
+            }
+            return Err(ChunkedCommitError::NotChunked);
+        };
+        if bytes.len() != CHUNKED_COMMIT_LEN {
+            return Err(ChunkedCommitError::MixedCommits);
+        }
+        let chunk_size = u32::from_le_bytes(rest[..4].try_into().unwrap());
+        let num_chunks = u64::from_le_bytes(rest[4..12].try_into().unwrap());
+        let committed_chain: [u8; 32] = rest[12..].try_into().unwrap();
+
+        if num_chunks != chunks.len() as u64 {
+            return Err(ChunkedCommitError::ChunkCount {
+                committed: num_chunks,
+                captured: chunks.len(),
+            });
+        }
+        let mut chain = chunk_chain_init(chunk_size);
+        let mut output_chunks = Vec::with_capacity(chunks.len());
+        for (index, chunk) in chunks.iter().enumerate() {
+            let is_last = index == chunks.len() - 1;
+            let valid_len = if is_last {
+                !chunk.is_empty() && chunk.len() <= chunk_size as usize
+            } else {
+                chunk.len() == chunk_size as usize
+            };
+            if !valid_len {
+                return Err(ChunkedCommitError::ChunkSize {
+                    index,
+                    len: chunk.len(),
+                    chunk_size,
+                });
+            }
+            let digest: [u8; 32] = Sha256::digest(chunk).into();
+            chain = chunk_chain_step(&chain, &digest);
+            output_chunks.push(OutputChunk {
+                index,
+                len: chunk.len(),
+                digest,
+            });
+        }
+        if chain != committed_chain {
+            return Err(ChunkedCommitError::ChainMismatch);
+        }
+        Ok(output_chunks)
+    }
+
+    /// The digests of the states committed by a segment with `sp1_zkvm::io::suspend_state`, or
+    /// `None` if the public values do not end with them.
+    pub fn segment_digests(&self) -> Option<SegmentDigests> {
+        SegmentDigests::decode(self.buffer.data.as_slice())
+    }
+
     /// Hash the public values, mask the top 3 bits and return a BigUint. Matches the implementation
     /// of `hashPublicValues` in the Solidity verifier.
     ///
@@ -199,6 +1007,47 @@ pub mod proof_serde {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_error() {
+        #[derive(Serialize)]
+        struct Written {
+            a: u32,
+            b: u32,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Expected {
+            a: u32,
+            b: u64,
+        }
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&1u32);
+        stdin.write(&"two");
+        stdin.write(&Written { a: 3, b: 4 });
+
+        assert_eq!(stdin.read::<u32>(), 1);
+        assert_eq!(stdin.read::<String>(), "two");
+        let err = stdin.try_read::<Expected>().unwrap_err();
+        assert_eq!(err.index, 2);
+        assert_eq!(err.available, 8);
+        assert_eq!(err.prefix, "0300000004000000");
+
+        let message = err.to_string();
+        assert!(message.contains("read index 2"), "{}", message);
+        assert!(message.contains("test_read_error::Expected"), "{}", message);
+
+        // The guest reports the same error in its panic message.
+        let panic_message = format!("panicked at src/main.rs:8:5:\n{}", message);
+        assert_eq!(ReadError::index_from_message(&panic_message), Some(2));
+        assert_eq!(
+            stdin.describe_read_error(&panic_message).unwrap(),
+            "read index 2 is the input written by write call #2 (8 bytes)"
+        );
+        assert_eq!(stdin.describe_read_error("assertion failed"), None);
+    }
+
     #[test]
     fn test_hash_public_values() {
         let test_hex = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
@@ -213,4 +1062,458 @@ mod tests {
 
         assert_eq!(hash, expected_hash_biguint);
     }
+
+    #[test]
+    fn test_input_digest() {
+        // A guest that never reads its input commits the digest of the empty stream.
+        assert_eq!(
+            hex::encode(SP1Stdin::new().digest()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write_slice(b"ab");
+        stdin.write_slice(b"c");
+        assert_eq!(stdin.digest_first(0), SP1Stdin::new().digest());
+        assert_eq!(stdin.digest_first(2), stdin.digest());
+        assert_eq!(stdin.digest_first(5), stdin.digest());
+
+        // The buffer boundaries are part of the digest.
+        let mut other = SP1Stdin::new();
+        other.write_slice(b"a");
+        other.write_slice(b"bc");
+        assert_ne!(stdin.digest(), other.digest());
+
+        // Config entries are not.
+        other.write_config("fork", b"cancun");
+        assert_eq!(other.digest(), input_digest(&other.buffer));
+    }
+
+    #[test]
+    fn test_write_args() {
+        assert_eq!(MAX_ARGS_LEN, sp1_zkvm::args::MAX_ARGS_LEN);
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write_args(&["tool", "--input", "données"]);
+        stdin.write(&1u32);
+        let (args, env) = sp1_zkvm::args::decode_args(&stdin.buffer[0]).unwrap();
+        assert_eq!(args, ["tool", "--input", "données"]);
+        assert!(env.is_empty());
+
+        // The arguments are part of the input digest.
+        let mut other = SP1Stdin::new();
+        other.write_args(&["tool"]);
+        other.write(&1u32);
+        assert_ne!(stdin.digest(), other.digest());
+
+        let mut stdin = SP1Stdin::new();
+        let env = BTreeMap::from([
+            ("PATH".to_string(), "/bin".to_string()),
+            ("HOME".to_string(), "/".to_string()),
+        ]);
+        stdin.write_args_with_env::<&str>(&[], &env);
+        assert_eq!(
+            sp1_zkvm::args::decode_args(&stdin.buffer[0]).unwrap(),
+            (vec![], env)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the arguments must be written before any other input")]
+    fn test_write_args_after_input() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&1u32);
+        stdin.write_args(&["tool"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than the limit of 65536")]
+    fn test_write_args_too_large() {
+        SP1Stdin::new().write_args(&["a".repeat(MAX_ARGS_LEN)]);
+    }
+
+    #[test]
+    fn test_config_bytes_sorted() {
+        let mut a = SP1Stdin::new();
+        a.write_config("fork", &[1]);
+        a.write_config("chain_id", &1u64.to_le_bytes());
+
+        let mut b = SP1Stdin::new();
+        b.write_config("chain_id", &1u64.to_le_bytes());
+        b.write_config("fork", &[1]);
+
+        assert_eq!(a.config_bytes(), b.config_bytes());
+        assert_eq!(a.config_digest(), b.config_digest());
+        assert_eq!(&a.config_bytes()[0..4], &2u32.to_le_bytes());
+        assert_eq!(&a.config_bytes()[8..16], b"chain_id");
+        assert_eq!(CONFIG_DIGEST_DOMAIN, sp1_zkvm::io::CONFIG_DIGEST_DOMAIN);
+    }
+
+    #[test]
+    fn test_file_tree() {
+        assert_eq!(
+            normalize_file_path("etc//app/./config.toml"),
+            "/etc/app/config.toml"
+        );
+        assert_eq!(normalize_file_path("/../etc/app/../hosts"), "/etc/hosts");
+        assert_eq!(normalize_file_path(""), "/");
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write_file_tree(&[
+            ("etc/app/config.toml", "name = \"app\"".as_bytes()),
+            ("/etc/hosts", &b"localhost"[..]),
+        ]);
+        assert_eq!(FILE_TREE_CONFIG_KEY, sp1_zkvm::fs::FILE_TREE_CONFIG_KEY);
+
+        // The guest reads the files from the config under their normalized paths.
+        let tree = sp1_zkvm::fs::FileTree::decode(&stdin.config[FILE_TREE_CONFIG_KEY]).unwrap();
+        assert_eq!(
+            tree.read("/etc/app/config.toml").unwrap(),
+            b"name = \"app\""
+        );
+        assert_eq!(tree.read("etc/hosts").unwrap(), b"localhost");
+        assert!(tree.metadata("/etc/app").unwrap().is_dir());
+    }
+
+    #[test]
+    #[should_panic(expected = "/etc/app is both a file and a directory")]
+    fn test_file_tree_conflict() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write_file_tree(&[("/etc/app", "a"), ("/etc/app/config.toml", "b")]);
+    }
+
+    #[test]
+    fn test_span_cycles_encoding() {
+        let span = SpanCycles {
+            name: "stf".to_string(),
+            cycles: 1234,
+        };
+        let bytes = span.encode();
+        assert_eq!(&bytes[..8], SPAN_CYCLES_TAG);
+        assert_eq!(SpanCycles::decode(&bytes), Some(span.clone()));
+        assert_eq!(SpanCycles::decode(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(SPAN_CYCLES_TAG, sp1_zkvm::io::SPAN_CYCLES_TAG);
+
+        // Spans are read in order with the other committed values.
+        let mut public_values = SP1PublicValues::new();
+        public_values.write(&7u32);
+        public_values.write_slice(&bytes);
+        assert_eq!(public_values.read::<u32>(), 7);
+        assert_eq!(public_values.read_span_cycles(), span);
+    }
+
+    /// The public values and the captured chunks of a program committing `chunks` in chunks of
+    /// `chunk_size` bytes.
+    fn chunked_commit(chunk_size: u32, chunks: &[&[u8]]) -> (SP1PublicValues, Vec<Vec<u8>>) {
+        let mut chain = sp1_zkvm::io::ChunkChain::new(chunk_size);
+        for chunk in chunks {
+            chain.push(chunk);
+        }
+        let public_values = SP1PublicValues::from(&chain.encode());
+        (public_values, chunks.iter().map(|c| c.to_vec()).collect())
+    }
+
+    #[test]
+    fn test_output_chunks() {
+        assert_eq!(CHUNKED_COMMIT_TAG, sp1_zkvm::io::CHUNKED_COMMIT_TAG);
+        assert_eq!(FD_OUTPUT_CHUNKS, sp1_zkvm::io::FD_OUTPUT_CHUNKS);
+        assert_eq!(FD_TEST_REPORTS, sp1_zkvm::io::FD_TEST_REPORTS);
+
+        // Full chunks, and a final partial chunk.
+        for chunks in [&[b"abcd", b"efgh"][..], &[b"abcd", b"efgh", b"ij"]] {
+            let chunks = chunks.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+            let (public_values, captured) = chunked_commit(4, &chunks);
+            assert_eq!(public_values.as_slice().len(), CHUNKED_COMMIT_LEN);
+            let output_chunks = public_values.output_chunks(&captured).unwrap();
+            assert_eq!(output_chunks.len(), chunks.len());
+            for (i, output_chunk) in output_chunks.iter().enumerate() {
+                assert_eq!(output_chunk.index, i);
+                assert_eq!(output_chunk.len, chunks[i].len());
+                assert_eq!(
+                    output_chunk.digest,
+                    <[u8; 32]>::from(Sha256::digest(chunks[i]))
+                );
+            }
+        }
+
+        // No chunks.
+        let (public_values, captured) = chunked_commit(4, &[]);
+        assert_eq!(public_values.output_chunks(&captured).unwrap(), vec![]);
+        assert_eq!(
+            public_values.output_chunks(&[b"abcd".to_vec()]),
+            Err(ChunkedCommitError::ChunkCount {
+                committed: 0,
+                captured: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_output_chunks_invalid() {
+        let (public_values, captured) = chunked_commit(4, &[b"abcd", b"ef"]);
+
+        let tampered = vec![b"abcd".to_vec(), b"eg".to_vec()];
+        assert_eq!(
+            public_values.output_chunks(&tampered),
+            Err(ChunkedCommitError::ChainMismatch)
+        );
+        let swapped = vec![b"ef".to_vec(), b"abcd".to_vec()];
+        assert_eq!(
+            public_values.output_chunks(&swapped),
+            Err(ChunkedCommitError::ChunkSize {
+                index: 0,
+                len: 2,
+                chunk_size: 4
+            })
+        );
+        assert_eq!(
+            public_values.output_chunks(&captured[..1]),
+            Err(ChunkedCommitError::ChunkCount {
+                committed: 2,
+                captured: 1
+            })
+        );
+
+        // The same chunks split differently do not match the chain.
+        let (public_values, _) = chunked_commit(3, &[b"abc", b"def"]);
+        let captured = vec![b"abcdef".to_vec()];
+        assert!(public_values.output_chunks(&captured).is_err());
+    }
+
+    #[test]
+    fn test_output_chunks_mixed_commits() {
+        let (chunked, captured) = chunked_commit(4, &[b"abcd"]);
+
+        // Values committed before or after the chunked commitment.
+        let mut before = SP1PublicValues::new();
+        before.write(&7u32);
+        before.write_slice(chunked.as_slice());
+        assert_eq!(
+            before.output_chunks(&captured),
+            Err(ChunkedCommitError::MixedCommits)
+        );
+        let mut after = chunked.clone();
+        after.write(&7u32);
+        assert_eq!(
+            after.output_chunks(&captured),
+            Err(ChunkedCommitError::MixedCommits)
+        );
+
+        // Public values without a chunked commitment.
+        let mut legacy = SP1PublicValues::new();
+        legacy.write(&7u32);
+        assert_eq!(
+            legacy.output_chunks(&[]),
+            Err(ChunkedCommitError::NotChunked)
+        );
+    }
+
+    /// The public values and the captured entries of a program committing `entries` with
+    /// `commit_kv`.
+    fn kv_commit(entries: &[(Vec<u8>, Vec<u8>)]) -> (SP1PublicValues, Vec<Vec<u8>>) {
+        let mut accumulator = sp1_zkvm::io::KvAccumulator::new();
+        let captured = entries
+            .iter()
+            .map(|(key, value)| accumulator.push(key, value))
+            .collect();
+        (SP1PublicValues::from(&accumulator.encode()), captured)
+    }
+
+    #[test]
+    fn test_kv_commitment() {
+        assert_eq!(KV_COMMIT_TAG, sp1_zkvm::io::KV_COMMIT_TAG);
+        assert_eq!(FD_KV_ENTRIES, sp1_zkvm::io::FD_KV_ENTRIES);
+
+        let large = (0..10_000u32)
+            .map(|i| {
+                (
+                    i.to_be_bytes().to_vec(),
+                    (i * 7).to_le_bytes().repeat(i as usize % 5),
+                )
+            })
+            .collect::<Vec<_>>();
+        for entries in [vec![], vec![(b"key".to_vec(), b"value".to_vec())], large] {
+            let (mut public_values, captured) = kv_commit(&entries);
+            assert_eq!(public_values.as_slice().len(), KV_COMMIT_LEN);
+            let commitment = public_values.read_kv_commitment();
+            assert_eq!(commitment, KvCommitment::from_entries(&entries));
+            assert_eq!(commitment.num_entries, entries.len() as u64);
+            assert_eq!(commitment.verify_entries(&captured).unwrap(), entries);
+            assert_eq!(
+                captured,
+                entries
+                    .iter()
+                    .map(|(key, value)| encode_kv_entry(key, value))
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        // The empty commitment is the hash of the tag, and an empty key is the smallest one.
+        let empty = KvCommitment::from_entries::<&[u8], &[u8]>(&[]);
+        assert_eq!(empty.root, keccak256(KV_COMMIT_TAG));
+        let entries = vec![(vec![], b"a".to_vec()), (vec![0], vec![])];
+        let (mut public_values, captured) = kv_commit(&entries);
+        let commitment = public_values.read_kv_commitment();
+        assert_eq!(commitment.verify_entries(&captured).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_kv_commitment_invalid() {
+        let entries = vec![
+            (b"a".to_vec(), b"1".to_vec()),
+            (b"b".to_vec(), b"2".to_vec()),
+        ];
+        let (mut public_values, captured) = kv_commit(&entries);
+        let commitment = public_values.read_kv_commitment();
+
+        let tampered = vec![captured[0].clone(), encode_kv_entry(b"b", b"3")];
+        assert_eq!(
+            commitment.verify_entries(&tampered),
+            Err(KvCommitError::RootMismatch)
+        );
+        let swapped = vec![captured[1].clone(), captured[0].clone()];
+        assert_eq!(
+            commitment.verify_entries(&swapped),
+            Err(KvCommitError::KeyOrder(1))
+        );
+        let duplicated = vec![captured[0].clone(), captured[0].clone()];
+        assert_eq!(
+            commitment.verify_entries(&duplicated),
+            Err(KvCommitError::KeyOrder(1))
+        );
+        assert_eq!(
+            commitment.verify_entries(&captured[..1]),
+            Err(KvCommitError::EntryCount {
+                committed: 2,
+                captured: 1
+            })
+        );
+        let mut truncated = captured.clone();
+        truncated[1].pop();
+        assert_eq!(
+            commitment.verify_entries(&truncated),
+            Err(KvCommitError::Malformed(1))
+        );
+
+        // Commitments are read in order with the other committed values.
+        let mut mixed = SP1PublicValues::new();
+        mixed.write(&7u32);
+        mixed.write_slice(&commitment.encode());
+        assert_eq!(mixed.read::<u32>(), 7);
+        assert_eq!(mixed.read_kv_commitment(), commitment);
+        assert_eq!(KvCommitment::decode(&commitment.encode()[1..]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn test_kv_commitment_out_of_order() {
+        kv_commit(&[
+            (b"b".to_vec(), b"2".to_vec()),
+            (b"a".to_vec(), b"1".to_vec()),
+        ]);
+    }
+
+    /// The public values of the segments of a counter program adding `step` to its state in each
+    /// segment and committing the new count before suspending it, like the guest does.
+    fn counter_segments(initial: u64, step: u64, num_segments: usize) -> Vec<SP1PublicValues> {
+        let mut state = bincode::serialize(&initial).unwrap();
+        (0..num_segments)
+            .map(|_| {
+                let count = bincode::deserialize::<u64>(&state).unwrap() + step;
+                let next = bincode::serialize(&count).unwrap();
+                let digests = sp1_zkvm::io::SegmentDigests {
+                    input: sp1_zkvm::io::segment_state_digest(&state),
+                    output: sp1_zkvm::io::segment_state_digest(&next),
+                };
+                state = next;
+
+                let mut public_values = SP1PublicValues::new();
+                public_values.write(&count);
+                public_values.write_slice(&digests.encode());
+                public_values
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_segment_chain() {
+        assert_eq!(SEGMENT_DIGESTS_TAG, sp1_zkvm::io::SEGMENT_DIGESTS_TAG);
+        assert_eq!(SEGMENT_STATE_DOMAIN, sp1_zkvm::io::SEGMENT_STATE_DOMAIN);
+        assert_eq!(FD_SEGMENT_STATE, sp1_zkvm::io::FD_SEGMENT_STATE);
+
+        let segments = counter_segments(0, 10, 3);
+        let mut counts = segments.clone();
+        assert_eq!(
+            counts
+                .iter_mut()
+                .map(|pv| pv.read::<u64>())
+                .collect::<Vec<_>>(),
+            [10, 20, 30]
+        );
+        // Only the first input and the last output are exposed.
+        let chain = verify_segment_chain(&segments).unwrap();
+        let state = |count: u64| segment_state_digest(&bincode::serialize(&count).unwrap());
+        assert_eq!(
+            chain,
+            SegmentDigests {
+                input: state(0),
+                output: state(30),
+            }
+        );
+        assert_eq!(
+            verify_segment_chain(&segments[1..2]).unwrap(),
+            segments[1].segment_digests().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_segment_chain_tampered() {
+        // A middle segment resuming from another state breaks the chain on both sides.
+        let mut segments = counter_segments(0, 10, 3);
+        segments[1] = counter_segments(5, 10, 1).remove(0);
+        assert_eq!(
+            verify_segment_chain(&segments),
+            Err(SegmentChainError::Unlinked(1))
+        );
+        let mut segments = counter_segments(0, 10, 3);
+        segments[1] = counter_segments(10, 11, 1).remove(0);
+        assert_eq!(
+            verify_segment_chain(&segments),
+            Err(SegmentChainError::Unlinked(2))
+        );
+
+        // Segments out of order, or without state digests.
+        let mut segments = counter_segments(0, 10, 3);
+        segments.swap(0, 1);
+        assert_eq!(
+            verify_segment_chain(&segments),
+            Err(SegmentChainError::Unlinked(1))
+        );
+        segments[2] = SP1PublicValues::from(&[7; 8]);
+        assert_eq!(
+            verify_segment_chain(&segments[1..]),
+            Err(SegmentChainError::NotSegment(1))
+        );
+        assert_eq!(verify_segment_chain(&[]), Err(SegmentChainError::Empty));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate config key")]
+    fn test_config_duplicate_key() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write_config("chain_id", &[1]);
+        stdin.write_config("chain_id", &[2]);
+    }
+
+    #[test]
+    fn test_digest_with_entropy() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&1u32);
+        stdin.write_entropy(&[7; 32]);
+        // A guest that consumed no entropy commits the plain input digest.
+        assert_eq!(stdin.digest_with_entropy(0), stdin.digest());
+        assert_ne!(stdin.digest_with_entropy(16), stdin.digest());
+        assert_ne!(stdin.digest_with_entropy(16), stdin.digest_with_entropy(32));
+        assert_eq!(stdin.digest_with_entropy(64), stdin.digest_with_entropy(32));
+    }
 }
@@ -1,11 +1,12 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
 
 use p3_baby_bear::BabyBear;
 use p3_field::{AbstractField, PrimeField32};
 use p3_field::{Field, PrimeField64};
 use p3_matrix::Matrix;
 
-use super::InteractionKind;
+use super::{Interaction, InteractionKind};
 use crate::air::MachineAir;
 use crate::stark::{MachineChip, StarkGenericConfig, StarkMachine, StarkProvingKey, Val};
 
@@ -45,18 +46,16 @@ fn field_to_int<F: PrimeField32>(x: F) -> i32 {
     }
 }
 
-pub fn debug_interactions<SC: StarkGenericConfig, A: MachineAir<Val<SC>>>(
+/// Evaluates the interactions of `chip` of the given kinds on each row of its trace for `record`,
+/// calling `f` with the row, the interaction, whether it is a send and its nonzero multiplicity
+/// and values.
+fn for_each_interaction<SC: StarkGenericConfig, A: MachineAir<Val<SC>>>(
     chip: &MachineChip<SC, A>,
     pkey: &StarkProvingKey<SC>,
     record: &A::Record,
-    interaction_kinds: Vec<InteractionKind>,
-) -> (
-    BTreeMap<String, Vec<InteractionData<Val<SC>>>>,
-    BTreeMap<String, Val<SC>>,
+    interaction_kinds: &[InteractionKind],
+    mut f: impl FnMut(usize, usize, &Interaction<Val<SC>>, bool, Val<SC>, Vec<Val<SC>>),
 ) {
-    let mut key_to_vec_data = BTreeMap::new();
-    let mut key_to_count = BTreeMap::new();
-
     let trace = chip.generate_trace(record, &mut A::Record::default());
     let mut pre_traces = pkey.traces.clone();
     let mut preprocessed_trace = pkey
@@ -94,31 +93,54 @@ pub fn debug_interactions<SC: StarkGenericConfig, A: MachineAir<Val<SC>>>(
                     let expr: Val<SC> = value.apply(preprocessed_row, main.row_mut(row));
                     values.push(expr);
                 }
-                let key = format!(
-                    "{} {}",
-                    &interaction.kind.to_string(),
-                    vec_to_string(values)
-                );
-                key_to_vec_data
-                    .entry(key.clone())
-                    .or_insert_with(Vec::new)
-                    .push(InteractionData {
-                        chip_name: chip.name(),
-                        kind: interaction.kind,
-                        row,
-                        interaction_number: m,
-                        is_send,
-                        multiplicity: multiplicity_eval,
-                    });
-                let current = key_to_count.entry(key.clone()).or_insert(Val::<SC>::zero());
-                if is_send {
-                    *current += multiplicity_eval;
-                } else {
-                    *current -= multiplicity_eval;
-                }
+                f(row, m, interaction, is_send, multiplicity_eval, values);
             }
         }
     }
+}
+
+pub fn debug_interactions<SC: StarkGenericConfig, A: MachineAir<Val<SC>>>(
+    chip: &MachineChip<SC, A>,
+    pkey: &StarkProvingKey<SC>,
+    record: &A::Record,
+    interaction_kinds: Vec<InteractionKind>,
+) -> (
+    BTreeMap<String, Vec<InteractionData<Val<SC>>>>,
+    BTreeMap<String, Val<SC>>,
+) {
+    let mut key_to_vec_data = BTreeMap::new();
+    let mut key_to_count = BTreeMap::new();
+
+    for_each_interaction::<SC, A>(
+        chip,
+        pkey,
+        record,
+        &interaction_kinds,
+        |row, m, interaction, is_send, multiplicity_eval, values| {
+            let key = format!(
+                "{} {}",
+                &interaction.kind.to_string(),
+                vec_to_string(values)
+            );
+            key_to_vec_data
+                .entry(key.clone())
+                .or_insert_with(Vec::new)
+                .push(InteractionData {
+                    chip_name: chip.name(),
+                    kind: interaction.kind,
+                    row,
+                    interaction_number: m,
+                    is_send,
+                    multiplicity: multiplicity_eval,
+                });
+            let current = key_to_count.entry(key.clone()).or_insert(Val::<SC>::zero());
+            if is_send {
+                *current += multiplicity_eval;
+            } else {
+                *current -= multiplicity_eval;
+            }
+        },
+    );
 
     (key_to_vec_data, key_to_count)
 }
@@ -204,11 +226,244 @@ where
     !any_nonzero
 }
 
+/// The default number of unmatched messages of each kind listed by an [InteractionReport].
+pub const DEFAULT_MAX_REPORTED_INTERACTIONS: usize = 16;
+
+/// The number of sends and receives of an unmatched message kept by an [InteractionReport].
+const MAX_OCCURRENCES: usize = 8;
+
+/// A row sending or receiving an interaction message.
+#[derive(Debug, Clone)]
+pub struct InteractionOccurrence<F> {
+    pub shard: usize,
+    pub chip_name: String,
+    pub row: usize,
+    pub is_send: bool,
+    pub multiplicity: F,
+}
+
+/// A message sent and received with different multiplicities.
+#[derive(Debug, Clone)]
+pub struct UnmatchedInteraction<F> {
+    pub kind: InteractionKind,
+    pub values: Vec<F>,
+    /// The multiplicity the message is sent with minus the one it is received with.
+    pub balance: F,
+    /// The first rows sending or receiving the message.
+    pub occurrences: Vec<InteractionOccurrence<F>>,
+}
+
+impl<F: PrimeField32> UnmatchedInteraction<F> {
+    /// The values of the message, named after the layout of the messages of its kind if known.
+    pub fn decoded_values(&self) -> Vec<(String, u32)> {
+        let names = value_names(self.kind, self.values.len());
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let name = names.map_or_else(|| format!("v{}", i), |names| names[i].to_string());
+                (name, value.as_canonical_u32())
+            })
+            .collect()
+    }
+}
+
+/// The names of the values of the messages of `kind` sent by the RISC-V machine, if they have
+/// `len` values.
+fn value_names(kind: InteractionKind, len: usize) -> Option<&'static [&'static str]> {
+    let names: &'static [&'static str] = match kind {
+        InteractionKind::Memory => &[
+            "shard", "clk", "addr", "value[0]", "value[1]", "value[2]", "value[3]",
+        ],
+        InteractionKind::Alu => &[
+            "opcode", "a[0]", "a[1]", "a[2]", "a[3]", "b[0]", "b[1]", "b[2]", "b[3]", "c[0]",
+            "c[1]", "c[2]", "c[3]", "shard", "channel", "nonce",
+        ],
+        InteractionKind::Byte => &["opcode", "a1", "a2", "b", "c", "shard", "channel"],
+        InteractionKind::Syscall => &[
+            "shard",
+            "channel",
+            "clk",
+            "nonce",
+            "syscall_id",
+            "arg1",
+            "arg2",
+        ],
+        _ => return None,
+    };
+    (names.len() == len).then_some(names)
+}
+
+/// The messages sent and received by the chips of a machine over a set of shards, and the ones
+/// whose sends and receives don't match, which make the cumulative sum nonzero.
+#[derive(Debug, Clone)]
+pub struct InteractionReport<F> {
+    /// The number of distinct messages.
+    pub num_messages: usize,
+    /// The unmatched messages of each kind, ordered by values and capped to the maximum number of
+    /// reported messages.
+    pub unmatched: BTreeMap<InteractionKind, Vec<UnmatchedInteraction<F>>>,
+    /// The number of unmatched messages of each kind, including the ones not reported.
+    pub num_unmatched: BTreeMap<InteractionKind, usize>,
+}
+
+impl<F> InteractionReport<F> {
+    /// Whether every message is received with the multiplicity it is sent with.
+    pub fn is_balanced(&self) -> bool {
+        self.num_unmatched.is_empty()
+    }
+
+    /// The total number of unmatched messages.
+    pub fn total_unmatched(&self) -> usize {
+        self.num_unmatched.values().sum()
+    }
+}
+
+impl<F: PrimeField32> Display for InteractionReport<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} distinct interaction messages, {} unmatched",
+            self.num_messages,
+            self.total_unmatched()
+        )?;
+        for (kind, unmatched) in self.unmatched.iter() {
+            let count = self.num_unmatched[kind];
+            writeln!(f, "{}: {} unmatched", kind, count)?;
+            for message in unmatched {
+                let values = message
+                    .decoded_values()
+                    .into_iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                writeln!(
+                    f,
+                    "  ({}) send-receive discrepancy {}",
+                    values,
+                    field_to_int(message.balance)
+                )?;
+                for occurrence in message.occurrences.iter() {
+                    writeln!(
+                        f,
+                        "    {} by {} at shard {} row {} with multiplicity {}",
+                        if occurrence.is_send {
+                            "sent"
+                        } else {
+                            "received"
+                        },
+                        occurrence.chip_name,
+                        occurrence.shard,
+                        occurrence.row,
+                        field_to_int(occurrence.multiplicity)
+                    )?;
+                }
+            }
+            if count > unmatched.len() {
+                writeln!(f, "  ... and {} more", count - unmatched.len())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Materializes the interaction messages of all chips over `shards` and pairs their sends and
+/// receives, reporting at most `max_reported` unmatched messages of each kind.
+pub fn interaction_report<SC, A>(
+    machine: &StarkMachine<SC, A>,
+    pkey: &StarkProvingKey<SC>,
+    shards: &[A::Record],
+    max_reported: usize,
+) -> InteractionReport<Val<SC>>
+where
+    SC: StarkGenericConfig,
+    SC::Val: PrimeField32,
+    A: MachineAir<SC::Val>,
+{
+    type Message<F> = (F, Vec<InteractionOccurrence<F>>);
+    let mut messages: HashMap<(InteractionKind, Vec<Val<SC>>), Message<Val<SC>>> = HashMap::new();
+
+    let kinds = InteractionKind::all_kinds();
+    for (shard_index, shard) in shards.iter().enumerate() {
+        for chip in machine.shard_chips(shard) {
+            for_each_interaction::<SC, A>(
+                chip,
+                pkey,
+                shard,
+                &kinds,
+                |row, _, interaction, is_send, multiplicity, values| {
+                    let (balance, occurrences) = messages
+                        .entry((interaction.kind, values))
+                        .or_insert_with(|| (Val::<SC>::zero(), Vec::new()));
+                    if is_send {
+                        *balance += multiplicity;
+                    } else {
+                        *balance -= multiplicity;
+                    }
+                    if occurrences.len() < MAX_OCCURRENCES {
+                        occurrences.push(InteractionOccurrence {
+                            shard: shard_index,
+                            chip_name: chip.name(),
+                            row,
+                            is_send,
+                            multiplicity,
+                        });
+                    }
+                },
+            );
+        }
+    }
+
+    let num_messages = messages.len();
+    let mut unmatched = messages
+        .into_iter()
+        .filter(|(_, (balance, _))| !balance.is_zero())
+        .map(
+            |((kind, values), (balance, occurrences))| UnmatchedInteraction {
+                kind,
+                values,
+                balance,
+                occurrences,
+            },
+        )
+        .collect::<Vec<_>>();
+    unmatched.sort_by_key(|message| {
+        (
+            message.kind,
+            message
+                .values
+                .iter()
+                .map(|value| value.as_canonical_u32())
+                .collect::<Vec<_>>(),
+        )
+    });
+
+    let mut report = InteractionReport {
+        num_messages,
+        unmatched: BTreeMap::new(),
+        num_unmatched: BTreeMap::new(),
+    };
+    for message in unmatched {
+        *report.num_unmatched.entry(message.kind).or_insert(0) += 1;
+        let reported = report
+            .unmatched
+            .entry(message.kind)
+            .or_insert_with(Vec::new);
+        if reported.len() < max_reported {
+            reported.push(message);
+        }
+    }
+    report
+}
+
 #[cfg(test)]
 mod test {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+
     use crate::{
         lookup::InteractionKind,
-        runtime::{Program, Runtime, ShardingConfig},
+        runtime::{tests::simple_program, Program, Runtime, ShardingConfig},
         stark::RiscvAir,
         utils::{setup_logger, tests::UINT256_MUL_ELF, BabyBearPoseidon2, SP1CoreOpts},
     };
@@ -229,4 +484,40 @@ mod test {
             debug_interactions_with_all_chips(&machine, &pk, &shards, InteractionKind::all_kinds());
         assert!(ok);
     }
+
+    #[test]
+    fn test_interaction_report_corrupted_memory() {
+        let program = simple_program();
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let (pk, _) = machine.setup(&program);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let mut shards = machine.shard(runtime.record, &ShardingConfig::default());
+        assert!(machine.debug_interactions(&pk, &shards).is_balanced());
+
+        // Corrupt the final value of a register written by the program.
+        let event = shards
+            .iter_mut()
+            .flat_map(|shard| shard.memory_finalize_events.iter_mut())
+            .find(|event| event.timestamp != 0)
+            .unwrap();
+        event.value ^= 1;
+        let addr = event.addr;
+
+        // The value sent by the finalize chip and the one received from the last access differ.
+        let report = machine.debug_interactions(&pk, &shards);
+        assert_eq!(
+            report.num_unmatched.clone().into_iter().collect::<Vec<_>>(),
+            vec![(InteractionKind::Memory, 2)]
+        );
+        let memory = &report.unmatched[&InteractionKind::Memory];
+        assert!(memory
+            .iter()
+            .all(|message| message.values[2] == BabyBear::from_canonical_u32(addr)));
+        assert!(memory.iter().any(|message| message
+            .occurrences
+            .iter()
+            .any(|occurrence| occurrence.is_send && occurrence.chip_name == "MemoryFinalize")));
+        assert!(report.to_string().contains(&format!("addr={}", addr)));
+    }
 }
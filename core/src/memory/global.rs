@@ -113,7 +113,10 @@ impl<F: PrimeField> MachineAir<F> for MemoryChip {
             NUM_MEMORY_INIT_COLS,
         );
 
-        pad_to_power_of_two::<NUM_MEMORY_INIT_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_MEMORY_INIT_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         trace
     }
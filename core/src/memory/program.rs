@@ -88,7 +88,10 @@ impl<F: PrimeField> MachineAir<F> for MemoryProgramChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_MEMORY_PROGRAM_PREPROCESSED_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_MEMORY_PROGRAM_PREPROCESSED_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         Some(trace)
     }
@@ -134,7 +137,10 @@ impl<F: PrimeField> MachineAir<F> for MemoryProgramChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_MEMORY_PROGRAM_MULT_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_MEMORY_PROGRAM_MULT_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         trace
     }
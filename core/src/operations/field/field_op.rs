@@ -3,6 +3,7 @@ use std::fmt::Debug;
 use num::{BigUint, Zero};
 use p3_air::AirBuilder;
 use p3_field::PrimeField32;
+use serde::{Deserialize, Serialize};
 use sp1_derive::AlignedBorrow;
 
 use super::params::{FieldParameters, Limbs};
@@ -14,7 +15,7 @@ use crate::bytes::event::ByteRecord;
 use typenum::Unsigned;
 
 /// Airthmetic operation for emulating modular arithmetic.
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum FieldOperation {
     Add,
     Mul,
@@ -272,7 +273,7 @@ mod tests {
 
     use super::{FieldOpCols, FieldOperation, Limbs};
 
-    use crate::air::MachineAir;
+    use crate::air::{MachineAir, MAX_PACKING_WIDTH};
 
     use crate::bytes::event::ByteRecord;
     use crate::operations::field::params::FieldParameters;
@@ -374,7 +375,7 @@ mod tests {
             );
 
             // Pad the trace to a power of two.
-            pad_to_power_of_two::<NUM_TEST_COLS, F>(&mut trace.values);
+            pad_to_power_of_two::<NUM_TEST_COLS, F>(&mut trace.values, MAX_PACKING_WIDTH);
 
             trace
         }
@@ -175,7 +175,7 @@ mod tests {
 
     use super::{FieldSqrtCols, Limbs};
 
-    use crate::air::MachineAir;
+    use crate::air::{MachineAir, MAX_PACKING_WIDTH};
 
     use crate::bytes::event::ByteRecord;
     use crate::operations::field::params::FieldParameters;
@@ -264,7 +264,7 @@ mod tests {
             );
 
             // Pad the trace to a power of two.
-            pad_to_power_of_two::<NUM_TEST_COLS, F>(&mut trace.values);
+            pad_to_power_of_two::<NUM_TEST_COLS, F>(&mut trace.values, MAX_PACKING_WIDTH);
 
             trace
         }
@@ -1,7 +1,9 @@
 use core::borrow::{Borrow, BorrowMut};
 use core::mem::size_of;
 use std::collections::HashMap;
+use std::ops::Range;
 
+use p3_air::AirBuilder;
 use p3_air::{Air, BaseAir, PairBuilder};
 use p3_field::PrimeField;
 use p3_matrix::dense::RowMajorMatrix;
@@ -13,6 +15,7 @@ use crate::air::SP1AirBuilder;
 use crate::cpu::columns::InstructionCols;
 use crate::cpu::columns::OpcodeSelectorCols;
 use crate::runtime::{ExecutionRecord, Program};
+use crate::stark::SetupError;
 use crate::utils::pad_to_power_of_two;
 
 /// The number of preprocessed program columns.
@@ -26,6 +29,8 @@ pub const NUM_PROGRAM_MULT_COLS: usize = size_of::<ProgramMultiplicityCols<u8>>(
 #[repr(C)]
 pub struct ProgramPreprocessedCols<T> {
     pub pc: T,
+    /// Whether the row holds an instruction of the program, as opposed to padding.
+    pub is_real: T,
     pub instruction: InstructionCols<T>,
     pub selectors: OpcodeSelectorCols<T>,
 }
@@ -38,13 +43,62 @@ pub struct ProgramMultiplicityCols<T> {
     pub multiplicity: T,
 }
 
-/// A chip that implements addition for the opcodes ADD and ADDI.
-#[derive(Default)]
-pub struct ProgramChip;
+/// The log2 of the number of instructions in a program bank, which is 4MB of text.
+pub const PROGRAM_BANK_LOG_HEIGHT: usize = 20;
+
+/// The number of program banks of the machine, so programs can have up to 16MB of text.
+pub const NUM_PROGRAM_BANKS: usize = 4;
+
+/// The number of banks of `2^log_bank_height` instructions holding a program of
+/// `num_instructions` instructions, at least one.
+pub fn num_program_banks(num_instructions: usize, log_bank_height: usize) -> usize {
+    num_instructions.div_ceil(1 << log_bank_height).max(1)
+}
+
+/// A chip holding a bank of the program ROM, which the CPU fetches its instructions from.
+///
+/// The program is split into banks of `2^log_bank_height` consecutive instructions, bank `i`
+/// holding the instructions whose index has `i` as its high bits, so that no preprocessed trace
+/// grows past the height of a bank. The machine has [NUM_PROGRAM_BANKS] banks, but a program only
+/// uses the [num_program_banks] first ones: the others have no preprocessed trace, so they are
+/// left out of its keys and of its shards. Since the program lookup is global, the CPU does not
+/// need to know which bank it fetches from.
+pub struct ProgramChip {
+    bank: usize,
+    log_bank_height: usize,
+}
+
+impl Default for ProgramChip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl ProgramChip {
+    /// The first bank, with banks of [PROGRAM_BANK_LOG_HEIGHT].
     pub const fn new() -> Self {
-        Self {}
+        Self::bank(0, PROGRAM_BANK_LOG_HEIGHT)
+    }
+
+    /// The bank `bank`, with banks of `2^log_bank_height` instructions.
+    pub const fn bank(bank: usize, log_bank_height: usize) -> Self {
+        Self {
+            bank,
+            log_bank_height,
+        }
+    }
+
+    /// The range of the indices of the instructions in the bank, for a program of `len`
+    /// instructions.
+    fn instruction_range(&self, len: usize) -> Range<usize> {
+        let start = (self.bank << self.log_bank_height).min(len);
+        let end = ((self.bank + 1) << self.log_bank_height).min(len);
+        start..end
+    }
+
+    /// Whether the bank is used by a program of `len` instructions, see [num_program_banks].
+    fn is_used(&self, len: usize) -> bool {
+        self.bank < num_program_banks(len, self.log_bank_height)
     }
 }
 
@@ -54,26 +108,48 @@ impl<F: PrimeField> MachineAir<F> for ProgramChip {
     type Program = Program;
 
     fn name(&self) -> String {
-        "Program".to_string()
+        // The first bank keeps the name of the single program chip.
+        match self.bank {
+            0 => "Program".to_string(),
+            bank => format!("ProgramBank{}", bank),
+        }
     }
 
     fn preprocessed_width(&self) -> usize {
         NUM_PROGRAM_PREPROCESSED_COLS
     }
 
+    fn check_program(&self, program: &Self::Program) -> Result<(), SetupError> {
+        let num_instructions = program.instructions.len();
+        let num_banks = num_program_banks(num_instructions, self.log_bank_height);
+        if num_banks > NUM_PROGRAM_BANKS {
+            return Err(SetupError::ProgramTooLarge {
+                num_instructions,
+                num_banks,
+                bank_size: 1 << self.log_bank_height,
+                max_banks: NUM_PROGRAM_BANKS,
+            });
+        }
+        Ok(())
+    }
+
     fn generate_preprocessed_trace(&self, program: &Self::Program) -> Option<RowMajorMatrix<F>> {
-        let rows = program
-            .instructions
-            .clone()
-            .into_iter()
-            .enumerate()
-            .map(|(i, instruction)| {
+        if !self.is_used(program.instructions.len()) {
+            return None;
+        }
+
+        let range = self.instruction_range(program.instructions.len());
+        let rows = program.instructions[range.clone()]
+            .iter()
+            .zip(range)
+            .map(|(instruction, i)| {
                 let pc = program.pc_base + (i as u32 * 4);
                 let mut row = [F::zero(); NUM_PROGRAM_PREPROCESSED_COLS];
                 let cols: &mut ProgramPreprocessedCols<F> = row.as_mut_slice().borrow_mut();
                 cols.pc = F::from_canonical_u32(pc);
-                cols.instruction.populate(instruction);
-                cols.selectors.populate(instruction);
+                cols.is_real = F::one();
+                cols.instruction.populate(*instruction);
+                cols.selectors.populate(*instruction);
 
                 row
             })
@@ -86,7 +162,10 @@ impl<F: PrimeField> MachineAir<F> for ProgramChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_PROGRAM_PREPROCESSED_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_PROGRAM_PREPROCESSED_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         Some(trace)
     }
@@ -113,13 +192,9 @@ impl<F: PrimeField> MachineAir<F> for ProgramChip {
                 .or_insert(1);
         });
 
-        let rows = input
-            .program
-            .instructions
-            .clone()
-            .into_iter()
-            .enumerate()
-            .map(|(i, _)| {
+        let rows = self
+            .instruction_range(input.program.instructions.len())
+            .map(|i| {
                 let pc = input.program.pc_base + (i as u32 * 4);
                 let mut row = [F::zero(); NUM_PROGRAM_MULT_COLS];
                 let cols: &mut ProgramMultiplicityCols<F> = row.as_mut_slice().borrow_mut();
@@ -137,13 +212,16 @@ impl<F: PrimeField> MachineAir<F> for ProgramChip {
         );
 
         // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_PROGRAM_MULT_COLS, F>(&mut trace.values);
+        pad_to_power_of_two::<NUM_PROGRAM_MULT_COLS, F>(
+            &mut trace.values,
+            <Self as MachineAir<F>>::min_trace_height(self),
+        );
 
         trace
     }
 
-    fn included(&self, _: &Self::Record) -> bool {
-        true
+    fn included(&self, shard: &Self::Record) -> bool {
+        self.is_used(shard.program.instructions.len())
     }
 }
 
@@ -166,6 +244,12 @@ where
         let mult_local = main.row_slice(0);
         let mult_local: &ProgramMultiplicityCols<AB::Var> = (*mult_local).borrow();
 
+        // The padding rows are not instructions of the program, so they can't be fetched by the
+        // CPU. Otherwise a zero instruction could be executed at pc 0.
+        builder
+            .when_not(prep_local.is_real)
+            .assert_zero(mult_local.multiplicity);
+
         // Contrain the interaction with CPU table
         builder.receive_program(
             prep_local.pc,
@@ -180,18 +264,33 @@ where
 #[cfg(test)]
 mod tests {
 
-    use std::{collections::BTreeMap, sync::Arc};
+    use core::borrow::Borrow;
+    use std::sync::Arc;
 
     use p3_baby_bear::BabyBear;
-
+    use p3_field::AbstractField;
     use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
 
     use crate::{
-        air::MachineAir,
-        program::ProgramChip,
-        runtime::{ExecutionRecord, Instruction, Opcode, Program},
+        air::{MachineAir, DEFAULT_MIN_TRACE_HEIGHT, SP1_PROOF_NUM_PV_ELTS},
+        program::{num_program_banks, ProgramChip, ProgramPreprocessedCols, NUM_PROGRAM_BANKS},
+        runtime::{ExecutionRecord, Instruction, Opcode, Program, Register, Runtime},
+        stark::{Chip, RiscvAir, SetupError, StarkMachine},
+        utils::{run_test_machine, setup_logger, BabyBearPoseidon2, SP1CoreOpts},
     };
 
+    /// The `(pc, is_real)` of the rows of a preprocessed program trace.
+    fn preprocessed_pcs(trace: &RowMajorMatrix<BabyBear>) -> Vec<(BabyBear, BabyBear)> {
+        (0..trace.height())
+            .map(|i| {
+                let row = trace.row_slice(i);
+                let cols: &ProgramPreprocessedCols<BabyBear> = (*row).borrow();
+                (cols.pc, cols.is_real)
+            })
+            .collect()
+    }
+
     #[test]
     fn generate_trace() {
         // main:
@@ -204,12 +303,7 @@ mod tests {
             Instruction::new(Opcode::ADD, 31, 30, 29, false, false),
         ];
         let shard = ExecutionRecord {
-            program: Arc::new(Program {
-                instructions,
-                pc_start: 0,
-                pc_base: 0,
-                memory_image: BTreeMap::new(),
-            }),
+            program: Arc::new(Program::new(instructions, 0, 0)),
             ..Default::default()
         };
         let chip = ProgramChip::new();
@@ -217,4 +311,157 @@ mod tests {
             chip.generate_trace(&shard, &mut ExecutionRecord::default());
         println!("{:?}", trace.values)
     }
+
+    #[test]
+    fn generate_preprocessed_trace() {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 29, 0, 5, false, true),
+            Instruction::new(Opcode::ADD, 30, 0, 37, false, true),
+            Instruction::new(Opcode::ADD, 31, 30, 29, false, false),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let chip = ProgramChip::new();
+        let trace: RowMajorMatrix<BabyBear> = chip.generate_preprocessed_trace(&program).unwrap();
+
+        // The padding rows have pc 0 like the first instruction, but are marked as not real.
+        assert_eq!(trace.height(), DEFAULT_MIN_TRACE_HEIGHT);
+        assert_eq!(
+            preprocessed_pcs(&trace)[..4],
+            [
+                (BabyBear::zero(), BabyBear::one()),
+                (BabyBear::from_canonical_u32(4), BabyBear::one()),
+                (BabyBear::from_canonical_u32(8), BabyBear::one()),
+                (BabyBear::zero(), BabyBear::zero()),
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_banked_preprocessed_traces() {
+        let instructions = vec![Instruction::new(Opcode::ADD, 29, 29, 1, false, true); 6];
+        let program = Program::new(instructions, 0x1000, 0x1000);
+
+        // Banks of 4 instructions, so the last two instructions are in the second bank, and the
+        // other banks are unused.
+        assert_eq!(num_program_banks(program.instructions.len(), 2), 2);
+        let banks = (0..NUM_PROGRAM_BANKS)
+            .map(|bank| {
+                let trace: Option<RowMajorMatrix<BabyBear>> =
+                    ProgramChip::bank(bank, 2).generate_preprocessed_trace(&program);
+                trace.map(|trace| {
+                    preprocessed_pcs(&trace)
+                        .into_iter()
+                        .filter(|(_, is_real)| *is_real == BabyBear::one())
+                        .map(|(pc, _)| pc)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let pcs = |pcs: &[u32]| {
+            pcs.iter()
+                .map(|pc| BabyBear::from_canonical_u32(*pc))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(banks[0], Some(pcs(&[0x1000, 0x1004, 0x1008, 0x100c])));
+        assert_eq!(banks[1], Some(pcs(&[0x1010, 0x1014])));
+        assert!(banks[2..].iter().all(Option::is_none));
+
+        // The unused banks are not included in the shards of the program either.
+        let shard = ExecutionRecord {
+            program: Arc::new(program),
+            ..Default::default()
+        };
+        let included = (0..NUM_PROGRAM_BANKS)
+            .map(|bank| MachineAir::<BabyBear>::included(&ProgramChip::bank(bank, 2), &shard))
+            .collect::<Vec<_>>();
+        assert_eq!(included, [true, true, false, false]);
+
+        let names = (0..NUM_PROGRAM_BANKS)
+            .map(|bank| MachineAir::<BabyBear>::name(&ProgramChip::bank(bank, 2)))
+            .collect::<Vec<_>>();
+        assert_eq!(names[..2], ["Program", "ProgramBank1"]);
+    }
+
+    #[test]
+    fn setup_too_many_banks() {
+        let instructions = vec![Instruction::new(Opcode::ADD, 29, 29, 1, false, true); 17];
+        let program = Program::new(instructions, 0, 0);
+        let err =
+            MachineAir::<BabyBear>::check_program(&ProgramChip::bank(0, 2), &program).unwrap_err();
+        assert!(matches!(
+            err,
+            SetupError::ProgramTooLarge {
+                num_instructions: 17,
+                num_banks: 5,
+                bank_size: 4,
+                max_banks: NUM_PROGRAM_BANKS,
+            }
+        ));
+
+        // Setting up the program fails instead of panicking.
+        let chips = RiscvAir::get_all_with_program_bank_height(2)
+            .into_iter()
+            .map(Chip::new)
+            .collect::<Vec<_>>();
+        let machine = StarkMachine::new(BabyBearPoseidon2::new(), chips, SP1_PROOF_NUM_PV_ELTS);
+        assert!(matches!(
+            machine.try_setup(&program),
+            Err(SetupError::ProgramTooLarge { .. })
+        ));
+    }
+
+    /// A program of 22 instructions jumping from the first bank of 16 instructions to the second
+    /// and back, adding 1 to `x29` 18 times.
+    fn banked_program() -> Program {
+        let add = Instruction::new(Opcode::ADD, 29, 29, 1, false, true);
+        let mut instructions = vec![Instruction::new(Opcode::JAL, 1, 76, 0, true, true)];
+        instructions.extend([add; 18]);
+        instructions.extend([
+            // Halt by jumping past the end on the second visit.
+            Instruction::new(Opcode::BNE, 31, 0, 12, false, true),
+            Instruction::new(Opcode::ADD, 31, 0, 1, false, true),
+            Instruction::new(Opcode::JALR, 0, 1, 0, false, true),
+        ]);
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_prove_program_banks() {
+        setup_logger();
+        let program = banked_program();
+        let mut runtime = Runtime::new(program.clone(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X29), 18);
+
+        let machine = || {
+            let chips = RiscvAir::get_all_with_program_bank_height(4)
+                .into_iter()
+                .map(Chip::new)
+                .collect::<Vec<_>>();
+            StarkMachine::new(BabyBearPoseidon2::new(), chips, SP1_PROOF_NUM_PV_ELTS)
+        };
+
+        // The keys only depend on the program.
+        let (pk, vk) = machine().setup(&program);
+        let (_, other_vk) = machine().setup(&program);
+        assert_eq!(vk.commit, other_vk.commit);
+        let shape = |vk: &crate::stark::StarkVerifyingKey<BabyBearPoseidon2>| {
+            vk.chip_information
+                .iter()
+                .map(|(name, _, dimensions)| (name.clone(), dimensions.height))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shape(&vk), shape(&other_vk));
+
+        // Only the two banks the program uses are in its keys.
+        let mut names = shape(&vk)
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| name == "Program" || name.starts_with("ProgramBank"))
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["Program", "ProgramBank1"]);
+
+        run_test_machine(runtime.record, machine(), pk, vk).unwrap();
+    }
 }
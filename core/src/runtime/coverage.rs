@@ -0,0 +1,167 @@
+use std::fmt::Write as _;
+
+use elf::{abi::STT_FUNC, endian::LittleEndian, ElfBytes};
+use serde::{Deserialize, Serialize};
+
+/// The instructions executed by a program, aggregated per function using the ELF symbol table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    /// The address of the first instruction.
+    pub pc_base: u32,
+    /// The number of times each instruction was executed, indexed by instruction index.
+    pub counts: Vec<u32>,
+    /// The functions of the program, ordered by address.
+    pub functions: Vec<FunctionCoverage>,
+}
+
+/// The coverage of a single function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionCoverage {
+    /// The demangled name of the function, without its hash.
+    pub name: String,
+    pub address: u32,
+    /// The number of instructions in the function.
+    pub instructions: usize,
+    /// The number of instructions in the function that were executed at least once.
+    pub instructions_executed: usize,
+    /// The number of times the first instruction was executed. This is the number of calls unless
+    /// the function branches back to its entry.
+    pub entry_count: u32,
+    /// The total number of instructions executed in the function.
+    pub cycles: u64,
+}
+
+impl CoverageReport {
+    /// Builds a report from the per-instruction execution counts of a program loaded from `elf`.
+    ///
+    /// Functions are taken from the symbol table, so a stripped ELF produces a report without any
+    /// functions.
+    pub fn new(elf: &[u8], pc_base: u32, counts: Vec<u32>) -> Self {
+        let mut functions = function_symbols(elf)
+            .into_iter()
+            .filter_map(|(name, address, size)| {
+                let start = (address.checked_sub(pc_base)? / 4) as usize;
+                let end = (start + size as usize / 4).min(counts.len());
+                let function_counts = counts.get(start..end)?;
+                Some(FunctionCoverage {
+                    name,
+                    address,
+                    instructions: function_counts.len(),
+                    instructions_executed: function_counts.iter().filter(|c| **c > 0).count(),
+                    entry_count: function_counts.first().copied().unwrap_or_default(),
+                    cycles: function_counts.iter().map(|c| *c as u64).sum(),
+                })
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|a, b| a.address.cmp(&b.address).then_with(|| a.name.cmp(&b.name)));
+        Self {
+            pc_base,
+            counts,
+            functions,
+        }
+    }
+
+    /// Returns the first function with the given demangled name.
+    pub fn function(&self, name: &str) -> Option<&FunctionCoverage> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Returns the functions that were never entered.
+    pub fn uncovered_functions(&self) -> impl Iterator<Item = &FunctionCoverage> {
+        self.functions
+            .iter()
+            .filter(|function| function.instructions_executed == 0)
+    }
+
+    /// Serializes the report to JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Formats the report as an lcov tracefile for the ELF at `elf_path`, using instruction
+    /// addresses as line numbers.
+    pub fn to_lcov(&self, elf_path: &str) -> String {
+        let mut lcov = String::new();
+        writeln!(lcov, "TN:").unwrap();
+        writeln!(lcov, "SF:{}", elf_path).unwrap();
+        for function in self.functions.iter() {
+            writeln!(lcov, "FN:{},{}", function.address, function.name).unwrap();
+        }
+        for function in self.functions.iter() {
+            writeln!(lcov, "FNDA:{},{}", function.entry_count, function.name).unwrap();
+        }
+        writeln!(lcov, "FNF:{}", self.functions.len()).unwrap();
+        let functions_hit = self.functions.iter().filter(|f| f.entry_count > 0).count();
+        writeln!(lcov, "FNH:{}", functions_hit).unwrap();
+        for (i, count) in self.counts.iter().enumerate() {
+            writeln!(lcov, "DA:{},{}", self.pc_base + 4 * i as u32, count).unwrap();
+        }
+        writeln!(lcov, "LF:{}", self.counts.len()).unwrap();
+        let lines_hit = self.counts.iter().filter(|c| **c > 0).count();
+        writeln!(lcov, "LH:{}", lines_hit).unwrap();
+        writeln!(lcov, "end_of_record").unwrap();
+        lcov
+    }
+}
+
+/// Returns the name, address and size of every function symbol in the ELF.
+pub(crate) fn function_symbols(elf: &[u8]) -> Vec<(String, u32, u32)> {
+    let Ok(elf) = ElfBytes::<LittleEndian>::minimal_parse(elf) else {
+        return Vec::new();
+    };
+    let Ok(Some((symbols, strings))) = elf.symbol_table() else {
+        return Vec::new();
+    };
+    symbols
+        .iter()
+        .filter(|symbol| symbol.st_symtype() == STT_FUNC && symbol.st_size > 0)
+        .filter_map(|symbol| {
+            let name = strings.get(symbol.st_name as usize).ok()?;
+            let name = format!("{:#}", rustc_demangle::demangle(name));
+            Some((name, symbol.st_value as u32, symbol.st_size as u32))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runtime::{Program, Runtime},
+        utils::{tests::FIBONACCI_ELF, SP1CoreOpts},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_fibonacci_coverage() {
+        let program = Program::from(FIBONACCI_ELF);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.enable_coverage();
+        runtime.run().unwrap();
+        let report = runtime.coverage_report(FIBONACCI_ELF).unwrap();
+
+        // The loop is folded into constants, so `main` runs once and commits twice.
+        let main = report.function("main").unwrap();
+        assert_eq!(main.entry_count, 1);
+        assert_eq!(main.instructions_executed, main.instructions);
+        let commit = report.function("sp1_precompiles::io::commit").unwrap();
+        assert_eq!(commit.entry_count, 2);
+
+        // The allocator's panic path is never taken.
+        let panic = report
+            .function("sp1_zkvm::syscalls::memory::sys_alloc_aligned::panic_cold_explicit")
+            .unwrap();
+        assert_eq!(panic.entry_count, 0);
+        assert_eq!(panic.cycles, 0);
+        assert!(report.uncovered_functions().any(|f| f.name == panic.name));
+
+        let total: u64 = report.counts.iter().map(|c| *c as u64).sum();
+        assert_eq!(total, runtime.state.global_clk);
+
+        let decoded: CoverageReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(decoded, report);
+        let lcov = report.to_lcov("fibonacci");
+        assert!(lcov.contains("FNDA:2,sp1_precompiles::io::commit\n"));
+        assert!(lcov.ends_with("end_of_record\n"));
+    }
+}
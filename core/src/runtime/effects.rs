@@ -0,0 +1,206 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::Hasher;
+
+use thiserror::Error;
+
+use super::{Register, Runtime, SyscallCode};
+
+/// A syscall made by the program, with a digest of the data it read or wrote.
+///
+/// The effects of an execution are recorded by [Runtime::enable_effect_log], so that the execution
+/// and proving passes of the same program can be checked to make the same syscalls with the same
+/// data, see [first_divergence].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Effect {
+    pub syscall: SyscallCode,
+    /// The first argument of the syscall, the file descriptor of a `WRITE`.
+    pub arg1: u32,
+    /// The number of bytes written by a `WRITE` or read by a `HINT_READ` or `ENTROPY_READ`, or the
+    /// value returned by any other syscall.
+    pub len: u32,
+    /// A digest of the arguments of the syscall, the value it returned and the bytes it read or
+    /// wrote.
+    pub digest: u64,
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.syscall {
+            SyscallCode::WRITE => write!(f, "WRITE fd={} len={}", self.arg1, self.len),
+            SyscallCode::HINT_READ | SyscallCode::HINT_LEN | SyscallCode::ENTROPY_READ => {
+                write!(f, "{:?} len={}", self.syscall, self.len)
+            }
+            syscall => write!(f, "{:?}", syscall),
+        }
+    }
+}
+
+/// The execution and proving passes of a program made different syscalls.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("first divergence at syscall #{index}: {}", describe_divergence(.expected, .actual))]
+pub struct NondeterminismError {
+    /// The index of the first syscall that differs, counted from the start of the execution.
+    pub index: usize,
+    /// The syscall made by the execution pass, `None` if it had already halted.
+    pub expected: Option<Effect>,
+    /// The syscall made by the proving pass, `None` if it had already halted.
+    pub actual: Option<Effect>,
+}
+
+fn describe_divergence(expected: &Option<Effect>, actual: &Option<Effect>) -> String {
+    let describe = |effect: &Option<Effect>| match effect {
+        Some(effect) => effect.to_string(),
+        None => "no more syscalls".to_string(),
+    };
+    let (expected, actual) = (describe(expected), describe(actual));
+    if expected == actual {
+        format!("expected {}, got {} with different data", expected, actual)
+    } else {
+        format!("expected {}, got {}", expected, actual)
+    }
+}
+
+/// Compares the effects of a pass against the expected ones, where `offset` is the index of the
+/// first effect of both slices in the whole execution.
+pub fn first_divergence(
+    expected: &[Effect],
+    actual: &[Effect],
+    offset: usize,
+) -> Result<(), NondeterminismError> {
+    let len = expected.len().max(actual.len());
+    match (0..len).find(|&i| expected.get(i) != actual.get(i)) {
+        Some(i) => Err(NondeterminismError {
+            index: offset + i,
+            expected: expected.get(i).copied(),
+            actual: actual.get(i).copied(),
+        }),
+        None => Ok(()),
+    }
+}
+
+impl<'a> Runtime<'a> {
+    /// Records the syscalls made by the program from now on, see [Effect].
+    pub fn enable_effect_log(&mut self) {
+        self.effects.get_or_insert_with(Vec::new);
+    }
+
+    /// Records a syscall that was called with `arg1` and `arg2` and returned `result`.
+    pub(crate) fn record_effect(
+        &mut self,
+        syscall: SyscallCode,
+        arg1: u32,
+        arg2: u32,
+        result: u32,
+    ) {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u32(syscall as u32);
+        hasher.write_u32(arg1);
+        hasher.write_u32(arg2);
+        hasher.write_u32(result);
+        let len = match syscall {
+            SyscallCode::WRITE => {
+                let len = self.register(Register::X12);
+                for i in 0..len {
+                    hasher.write_u8(self.byte(arg2 + i));
+                }
+                len
+            }
+            SyscallCode::HINT_READ => {
+                let read = &self.state.input_stream[self.state.input_stream_ptr - 1];
+                hasher.write(read);
+                read.len() as u32
+            }
+            SyscallCode::ENTROPY_READ => {
+                let end = self.state.entropy_stream_ptr;
+                hasher.write(&self.state.entropy_stream[end - arg2 as usize..end]);
+                arg2
+            }
+            _ => result,
+        };
+        let effect = Effect {
+            syscall,
+            arg1,
+            len,
+            digest: hasher.finish(),
+        };
+        if let Some(effects) = self.effects.as_mut() {
+            effects.push(effect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::runtime::{Instruction, Opcode, Program};
+    use crate::utils::SP1CoreOpts;
+
+    /// A program that calls the hook of fd 10, then reads back its output.
+    fn hook_program() -> Program {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::WRITE as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 10, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 0x100, false, true),
+            Instruction::new(Opcode::ADD, 12, 0, 4, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 11, 5, 0, false, false),
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::HINT_READ as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ADD, 10, 0, 0x200, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ];
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_nondeterministic_hook() {
+        // A hook whose output grows every time it is called, like one reading the host clock.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let run = || {
+            let calls = calls.clone();
+            let mut runtime = Runtime::new(hook_program(), SP1CoreOpts::default());
+            runtime.hook_registry.register(
+                10,
+                Box::new(move |_, _| {
+                    let len = 4 * (calls.fetch_add(1, Ordering::SeqCst) + 1);
+                    vec![vec![0; len]]
+                }),
+            );
+            runtime.enable_effect_log();
+            runtime.run().unwrap();
+            runtime.effects.unwrap()
+        };
+        let expected = run();
+        let actual = run();
+
+        assert_eq!(expected.len(), 3);
+        assert_eq!(first_divergence(&expected, &expected, 0), Ok(()));
+        let err = first_divergence(&expected, &actual, 0).unwrap_err();
+        assert_eq!(err.index, 1);
+        assert_eq!(
+            err.to_string(),
+            "first divergence at syscall #1: expected HINT_LEN len=4, got HINT_LEN len=8"
+        );
+
+        // The index is counted from the start of the execution, and missing syscalls are reported.
+        let err = first_divergence(&expected[..2], &expected[..1], 5).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "first divergence at syscall #6: expected HINT_LEN len=4, got no more syscalls"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeSet;
+
+use p3_baby_bear::BabyBear;
+use p3_field::PrimeField32;
+
+use super::{ExecutionRecord, SyscallCode};
+use crate::air::MachineAir;
+use crate::stark::{RiscvAir, StarkMachine};
+use crate::StarkGenericConfig;
+
+/// A selection of the chips whose events a [super::Runtime] records.
+///
+/// When proving with a machine made of a subset of the [RiscvAir] chips, the events of the chips
+/// left out are never read, so the runtime drops them at the end of every shard batch, see
+/// [super::Runtime::new_for_machine]. A syscall whose chip is excluded can't be constrained, so
+/// executing one fails with [super::ExecutionError::ExcludedSyscall].
+///
+/// The default filter records the events of every chip.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordFilter {
+    /// The names of the chips whose events are not recorded.
+    excluded: BTreeSet<String>,
+}
+
+impl RecordFilter {
+    /// A filter recording the events of every chip.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// A filter recording the events of the [RiscvAir] chips named in `chips` only.
+    pub fn from_chip_names<'a>(chips: impl IntoIterator<Item = &'a str>) -> Self {
+        let included = chips.into_iter().collect::<BTreeSet<_>>();
+        let excluded = RiscvAir::<BabyBear>::get_all()
+            .iter()
+            .map(|chip| chip.name())
+            .filter(|name| !included.contains(name.as_str()))
+            .collect();
+        Self { excluded }
+    }
+
+    /// A filter recording the events of the chips of `machine` only.
+    pub fn for_machine<SC: StarkGenericConfig>(
+        machine: &StarkMachine<SC, RiscvAir<SC::Val>>,
+    ) -> Self
+    where
+        SC::Val: PrimeField32,
+    {
+        let names = machine
+            .chips()
+            .iter()
+            .map(|chip| chip.name())
+            .collect::<Vec<_>>();
+        Self::from_chip_names(names.iter().map(String::as_str))
+    }
+
+    /// Whether the filter records the events of every chip.
+    pub fn is_all(&self) -> bool {
+        self.excluded.is_empty()
+    }
+
+    /// Whether the events of the chip named `chip` are recorded.
+    pub fn includes(&self, chip: &str) -> bool {
+        !self.excluded.contains(chip)
+    }
+
+    /// The names of the chips whose events are not recorded.
+    pub fn excluded(&self) -> impl Iterator<Item = &str> {
+        self.excluded.iter().map(String::as_str)
+    }
+
+    /// Whether the program may make the syscall `code`, which is the case unless its chip is
+    /// excluded.
+    pub fn allows_syscall(&self, code: SyscallCode) -> bool {
+        code.chip_name().map_or(true, |chip| self.includes(chip))
+    }
+
+    /// Drops the events of the excluded chips from `record`, releasing their memory.
+    pub fn prune(&self, record: &mut ExecutionRecord) {
+        if self.is_all() {
+            return;
+        }
+        for chip in self.excluded.iter() {
+            match chip.as_str() {
+                "AddSub" => {
+                    record.add_events = Vec::new();
+                    record.sub_events = Vec::new();
+                }
+                "Mul" => record.mul_events = Vec::new(),
+                "Bitwise" => record.bitwise_events = Vec::new(),
+                "Shift" => record.shift_events = Vec::new(),
+                "DivRem" => record.divrem_events = Vec::new(),
+                "Lt" => record.lt_events = Vec::new(),
+                "BitManip" => record.bitmanip_events = Vec::new(),
+                "MemoryInit" => record.memory_initialize_events = Vec::new(),
+                "MemoryFinalize" => record.memory_finalize_events = Vec::new(),
+                "ShaExtend" => record.sha_extend_events = Vec::new(),
+                "ShaCompress" => record.sha_compress_events = Vec::new(),
+                "KeccakPermute" => record.keccak_permute_events = Vec::new(),
+                "EdAddAssign" => record.ed_add_events = Vec::new(),
+                "EdDecompress" => record.ed_decompress_events = Vec::new(),
+                "Secp256k1AddAssign" => record.secp256k1_add_events = Vec::new(),
+                "Secp256k1DoubleAssign" => record.secp256k1_double_events = Vec::new(),
+                "Secp256r1AddAssign" => record.secp256r1_add_events = Vec::new(),
+                "Secp256r1DoubleAssign" => record.secp256r1_double_events = Vec::new(),
+                "Secp256k1Decompress" => record.k256_decompress_events = Vec::new(),
+                "Bn254AddAssign" => record.bn254_add_events = Vec::new(),
+                "Bn254DoubleAssign" => record.bn254_double_events = Vec::new(),
+                "Bls12381AddAssign" => record.bls12381_add_events = Vec::new(),
+                "Bls12381DoubleAssign" => record.bls12381_double_events = Vec::new(),
+                "Bls12381Decompress" => record.bls12381_decompress_events = Vec::new(),
+                "Uint256MulMod" => record.uint256_mul_events = Vec::new(),
+                "Bls12381FpOp" => record.bls12381_fp_op_events = Vec::new(),
+                // The other chips have no events of their own.
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use strum::IntoEnumIterator;
+
+    use crate::air::{MachineAir, SP1_PROOF_NUM_PV_ELTS};
+    use crate::runtime::{ExecutionError, Program, Runtime, SyscallCode};
+    use crate::stark::{Chip, RiscvAir, StarkMachine};
+    use crate::utils::tests::{FIBONACCI_ELF, SHA2_ELF};
+    use crate::utils::{run_test_machine, setup_logger, BabyBearPoseidon2, SP1CoreOpts};
+
+    use super::RecordFilter;
+
+    /// A machine with the CPU, ALU and memory chips, without any precompile.
+    fn reduced_machine() -> StarkMachine<BabyBearPoseidon2, RiscvAir<BabyBear>> {
+        let precompiles = SyscallCode::iter()
+            .filter_map(|code| code.chip_name())
+            .collect::<Vec<_>>();
+        let chips = RiscvAir::get_all()
+            .into_iter()
+            .filter(|chip| !precompiles.contains(&chip.name().as_str()))
+            .map(Chip::new)
+            .collect();
+        StarkMachine::new(BabyBearPoseidon2::new(), chips, SP1_PROOF_NUM_PV_ELTS)
+    }
+
+    #[test]
+    fn test_filter_for_machine() {
+        let machine = reduced_machine();
+        let filter = RecordFilter::for_machine(&machine);
+        assert!(filter.includes("CPU"));
+        assert!(filter.includes("AddSub"));
+        assert!(!filter.includes("ShaExtend"));
+        assert!(!filter.allows_syscall(SyscallCode::SHA_COMPRESS));
+        assert!(filter.allows_syscall(SyscallCode::WRITE));
+        assert!(RecordFilter::all().allows_syscall(SyscallCode::SHA_COMPRESS));
+
+        let full = RecordFilter::for_machine(&RiscvAir::machine(BabyBearPoseidon2::new()));
+        assert!(full.is_all());
+    }
+
+    #[test]
+    fn test_prove_reduced_machine() {
+        setup_logger();
+        let machine = reduced_machine();
+        let program = Program::from(FIBONACCI_ELF);
+        let mut runtime = Runtime::new_for_machine(program, SP1CoreOpts::default(), &machine);
+        runtime.run().unwrap();
+        let (pk, vk) = machine.setup(runtime.program.as_ref());
+        run_test_machine(runtime.record, machine, pk, vk).unwrap();
+    }
+
+    #[test]
+    fn test_prune_lowers_record_memory() {
+        let program = Program::from(SHA2_ELF);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let mut record = runtime.record;
+        assert!(!record.sha_compress_events.is_empty());
+        let full_size = bincode::serialized_size(&record).unwrap();
+
+        RecordFilter::for_machine(&reduced_machine()).prune(&mut record);
+        assert!(record.sha_extend_events.is_empty());
+        assert!(record.sha_compress_events.is_empty());
+        assert!(!record.cpu_events.is_empty());
+        assert!(bincode::serialized_size(&record).unwrap() < full_size);
+    }
+
+    #[test]
+    fn test_excluded_syscall() {
+        let program = Program::from(SHA2_ELF);
+        let mut runtime =
+            Runtime::new_for_machine(program, SP1CoreOpts::default(), &reduced_machine());
+        match runtime.run() {
+            Err(ExecutionError::ExcludedSyscall { syscall, chip }) => {
+                assert!(matches!(
+                    syscall,
+                    SyscallCode::SHA_EXTEND | SyscallCode::SHA_COMPRESS
+                ));
+                assert_eq!(Some(chip), syscall.chip_name());
+            }
+            result => panic!("expected an excluded syscall error, got {:?}", result.err()),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Cooperative pre-emption of executions, so that a thread can interleave several of them.
+
+use super::{ExecutionError, Runtime};
+
+/// The outcome of [Runtime::run_for].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    /// The fuel ran out before the program finished, and the next call resumes the execution.
+    Paused,
+    /// The program finished, and all of its events are in the record of the runtime.
+    Done,
+}
+
+impl<'a> Runtime<'a> {
+    /// Executes at most `fuel` cycles of the program, recording their events like [Runtime::run].
+    ///
+    /// All the state of the execution is in the runtime, so it can be paused at any cycle, in the
+    /// middle of a shard too: calling `run_for` until it returns [RunStatus::Done] records the same
+    /// events as [Runtime::run]. This lets a thread time-slice the executions of several runtimes.
+    /// Once the program finished, it returns [RunStatus::Done] without executing anything. After an
+    /// error, the execution can't be resumed.
+    pub fn run_for(&mut self, fuel: u64) -> Result<RunStatus, ExecutionError> {
+        if self.is_finished() {
+            return Ok(RunStatus::Done);
+        }
+        if fuel == 0 {
+            return Ok(RunStatus::Paused);
+        }
+        self.emit_events = true;
+        self.print_report = true;
+
+        // If it's the first cycle, initialize the program.
+        if self.state.global_clk == 0 {
+            self.snapshot_shard();
+            self.initialize();
+        }
+
+        let mut status = RunStatus::Paused;
+        let mut current_shard = self.state.current_shard;
+        for _ in 0..fuel {
+            if self.execute_cycle()? {
+                status = RunStatus::Done;
+                break;
+            }
+            if current_shard != self.state.current_shard {
+                current_shard = self.state.current_shard;
+                self.snapshot_shard();
+            }
+        }
+
+        if status == RunStatus::Done {
+            self.postprocess();
+        }
+        self.record_filter.prune(&mut self.record);
+
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{ExecutionRecord, Program};
+    use crate::utils::tests::{FIBONACCI_ELF, SSZ_WITHDRAWALS_ELF};
+    use crate::utils::SP1CoreOpts;
+
+    fn runtime(elf: &[u8]) -> Runtime<'static> {
+        let opts = SP1CoreOpts {
+            shard_size: 1 << 12,
+            ..Default::default()
+        };
+        let mut runtime = Runtime::new(Program::from(elf), opts);
+        runtime.seed_lookup_ids([7; 32]);
+        runtime
+    }
+
+    fn uninterrupted_record(elf: &[u8]) -> ExecutionRecord {
+        let mut runtime = runtime(elf);
+        runtime.run().unwrap();
+        runtime.record
+    }
+
+    fn assert_same_record(record: &ExecutionRecord, expected: &ExecutionRecord) {
+        assert!(
+            bincode::serialize(record).unwrap() == bincode::serialize(expected).unwrap(),
+            "the records differ"
+        );
+    }
+
+    #[test]
+    fn test_run_for_interleaved() {
+        let mut runtimes = [runtime(FIBONACCI_ELF), runtime(SSZ_WITHDRAWALS_ELF)];
+        let mut done = [false; 2];
+        let mut num_slices = [0; 2];
+        while done.contains(&false) {
+            for (i, runtime) in runtimes.iter_mut().enumerate() {
+                if !done[i] {
+                    num_slices[i] += 1;
+                    done[i] = runtime.run_for(10_000).unwrap() == RunStatus::Done;
+                }
+            }
+        }
+        assert!(
+            num_slices.iter().all(|&n| n > 1),
+            "the executions should pause"
+        );
+
+        // A finished execution stays finished.
+        assert_eq!(runtimes[0].run_for(10_000).unwrap(), RunStatus::Done);
+
+        assert_same_record(&runtimes[0].record, &uninterrupted_record(FIBONACCI_ELF));
+        assert_same_record(
+            &runtimes[1].record,
+            &uninterrupted_record(SSZ_WITHDRAWALS_ELF),
+        );
+    }
+
+    #[test]
+    fn test_run_for_zero_fuel() {
+        let mut runtime = runtime(FIBONACCI_ELF);
+        assert_eq!(runtime.run_for(0).unwrap(), RunStatus::Paused);
+        assert_eq!(runtime.state.global_clk, 0);
+        while runtime.run_for(1).unwrap() == RunStatus::Paused {}
+        assert_same_record(&runtime.record, &uninterrupted_record(FIBONACCI_ELF));
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::{Opcode, SyscallCode};
+
+/// The opcodes metered alike by a [GasSchedule], mostly by the chips their events take a row of.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, EnumIter,
+)]
+pub enum OpcodeClass {
+    AddSub,
+    Bitwise,
+    ShiftLeft,
+    ShiftRight,
+    Lt,
+    Mul,
+    DivRem,
+    BitManip,
+    Load,
+    Store,
+    Branch,
+    Jump,
+    Auipc,
+    System,
+}
+
+impl OpcodeClass {
+    pub const fn of(opcode: Opcode) -> Self {
+        match opcode {
+            Opcode::ADD | Opcode::SUB => Self::AddSub,
+            Opcode::XOR | Opcode::OR | Opcode::AND => Self::Bitwise,
+            Opcode::SLL => Self::ShiftLeft,
+            Opcode::SRL | Opcode::SRA => Self::ShiftRight,
+            Opcode::SLT | Opcode::SLTU => Self::Lt,
+            Opcode::MUL | Opcode::MULH | Opcode::MULHU | Opcode::MULHSU => Self::Mul,
+            Opcode::DIV | Opcode::DIVU | Opcode::REM | Opcode::REMU => Self::DivRem,
+            Opcode::CLZ | Opcode::CTZ | Opcode::CPOP | Opcode::REV8 => Self::BitManip,
+            Opcode::LB | Opcode::LH | Opcode::LW | Opcode::LBU | Opcode::LHU => Self::Load,
+            Opcode::SB | Opcode::SH | Opcode::SW => Self::Store,
+            Opcode::BEQ | Opcode::BNE | Opcode::BLT | Opcode::BGE | Opcode::BLTU | Opcode::BGEU => {
+                Self::Branch
+            }
+            Opcode::JAL | Opcode::JALR => Self::Jump,
+            Opcode::AUIPC => Self::Auipc,
+            Opcode::ECALL | Opcode::EBREAK | Opcode::UNIMP => Self::System,
+        }
+    }
+
+    /// The default weight, about the main trace cells of the instruction over 16: a CPU row, plus
+    /// the rows of the ALU chips its events go to.
+    const fn default_weight(&self) -> u64 {
+        match self {
+            Self::AddSub | Self::Bitwise | Self::BitManip | Self::Jump | Self::Auipc => 10,
+            Self::ShiftLeft | Self::ShiftRight | Self::Lt => 11,
+            Self::Load | Self::Store => 12,
+            Self::Mul | Self::Branch => 14,
+            // A division also checks its result with a multiplication, an addition and a
+            // comparison.
+            Self::DivRem => 30,
+            Self::System => 8,
+        }
+    }
+}
+
+/// The default weights of the syscalls with a precompile chip, about the main trace cells of an
+/// event over 16. The other syscalls only cost the weight of their `ecall`.
+const DEFAULT_SYSCALL_WEIGHTS: &[(SyscallCode, u64)] = &[
+    (SyscallCode::KECCAK_PERMUTE, 4000),
+    (SyscallCode::SHA_EXTEND, 300),
+    (SyscallCode::SHA_COMPRESS, 1000),
+    (SyscallCode::ED_ADD, 60),
+    (SyscallCode::ED_DECOMPRESS, 60),
+    (SyscallCode::SECP256K1_ADD, 70),
+    (SyscallCode::SECP256K1_DOUBLE, 70),
+    (SyscallCode::SECP256K1_DECOMPRESS, 60),
+    (SyscallCode::SECP256R1_ADD, 70),
+    (SyscallCode::SECP256R1_DOUBLE, 70),
+    (SyscallCode::BN254_ADD, 70),
+    (SyscallCode::BN254_DOUBLE, 70),
+    (SyscallCode::BLS12381_ADD, 100),
+    (SyscallCode::BLS12381_DOUBLE, 100),
+    (SyscallCode::BLS12381_DECOMPRESS, 90),
+    (SyscallCode::UINT256_MUL, 25),
+    // A KZG point evaluation of `sp1_precompiles::kzg` makes about 164k calls to these, 184,687
+    // at most, so its weight is up to 184,687 * (30 + the `ecall` weight) on top of the
+    // instructions moving the operands around.
+    (SyscallCode::BLS12381_FP_ADD, 30),
+    (SyscallCode::BLS12381_FP_SUB, 30),
+    (SyscallCode::BLS12381_FP_MUL, 30),
+    (SyscallCode::BLS12381_FP_DIV, 30),
+];
+
+/// The weights metering an execution, as a measure of its proving cost finer than its cycles.
+///
+/// Every instruction costs the weight of its [OpcodeClass], and every syscall the weight of its
+/// code on top of its `ecall`. The total is reported as [super::ExecutionReport::gas_used], along
+/// with the [GasSchedule::digest] of the schedule, and the program can read it with the
+/// `GAS_USED` syscall.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSchedule {
+    pub opcode_weights: BTreeMap<OpcodeClass, u64>,
+    pub syscall_weights: BTreeMap<SyscallCode, u64>,
+}
+
+impl Default for GasSchedule {
+    /// The weights calibrated to the relative proving cost of the instructions and precompiles.
+    fn default() -> Self {
+        Self {
+            opcode_weights: OpcodeClass::iter()
+                .map(|class| (class, class.default_weight()))
+                .collect(),
+            syscall_weights: DEFAULT_SYSCALL_WEIGHTS.iter().copied().collect(),
+        }
+    }
+}
+
+impl GasSchedule {
+    /// The default schedule with the weights of `json` instead, such as
+    /// `{"opcode_weights": {"DivRem": 40}, "syscall_weights": {"KECCAK_PERMUTE": 5000}}`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct Overrides {
+            #[serde(default)]
+            opcode_weights: BTreeMap<OpcodeClass, u64>,
+            #[serde(default)]
+            syscall_weights: BTreeMap<SyscallCode, u64>,
+        }
+
+        let overrides: Overrides = serde_json::from_str(json)?;
+        let mut schedule = Self::default();
+        schedule.opcode_weights.extend(overrides.opcode_weights);
+        schedule.syscall_weights.extend(overrides.syscall_weights);
+        Ok(schedule)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// The weight of an instruction of `opcode`.
+    pub fn opcode_weight(&self, opcode: Opcode) -> u64 {
+        self.opcode_weights
+            .get(&OpcodeClass::of(opcode))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The weight of a syscall of `code`, on top of the weight of its `ecall`.
+    pub fn syscall_weight(&self, code: SyscallCode) -> u64 {
+        self.syscall_weights.get(&code).copied().unwrap_or_default()
+    }
+
+    /// The blake3 digest of the schedule, identifying the weights an amount of gas was metered
+    /// with.
+    pub fn digest(&self) -> GasScheduleDigest {
+        GasScheduleDigest(*blake3::hash(&bincode::serialize(self).unwrap()).as_bytes())
+    }
+}
+
+/// The digest of a [GasSchedule], displayed in hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GasScheduleDigest(pub [u8; 32]);
+
+impl Display for GasScheduleDigest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// The length of the table of the weights of the opcodes, more than the largest opcode.
+const OPCODE_TABLE_LEN: usize = 64;
+
+/// The weights of a [GasSchedule] laid out for metering every instruction.
+#[derive(Debug, Clone)]
+pub(crate) struct GasMeter {
+    opcode_weights: [u64; OPCODE_TABLE_LEN],
+    syscall_weights: HashMap<SyscallCode, u64>,
+    pub(crate) digest: GasScheduleDigest,
+}
+
+impl GasMeter {
+    pub(crate) fn new(schedule: &GasSchedule) -> Self {
+        let mut opcode_weights = [0; OPCODE_TABLE_LEN];
+        for opcode in Opcode::iter() {
+            opcode_weights[opcode as usize] = schedule.opcode_weight(opcode);
+        }
+        Self {
+            opcode_weights,
+            syscall_weights: schedule.syscall_weights.clone().into_iter().collect(),
+            digest: schedule.digest(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn opcode(&self, opcode: Opcode) -> u64 {
+        self.opcode_weights[opcode as usize]
+    }
+
+    pub(crate) fn syscall(&self, code: SyscallCode) -> u64 {
+        self.syscall_weights.get(&code).copied().unwrap_or_default()
+    }
+}
+
+impl Default for GasMeter {
+    fn default() -> Self {
+        Self::new(&GasSchedule::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::runtime::{Instruction, Program, Runtime};
+    use crate::utils::{setup_logger, SP1CoreOpts};
+
+    const STATE_PTR: u32 = 0x1000;
+
+    /// A program of `permutations` keccak permutations, of three instructions each.
+    fn keccak_program(permutations: usize) -> Program {
+        let mut instructions = Vec::new();
+        for _ in 0..permutations {
+            instructions.extend([
+                Instruction::new(
+                    Opcode::ADD,
+                    5,
+                    0,
+                    SyscallCode::KECCAK_PERMUTE as u32,
+                    false,
+                    true,
+                ),
+                Instruction::new(Opcode::ADD, 10, 0, STATE_PTR, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        let mut program = Program::new(instructions, 0, 0);
+        program.memory_image = (0..50)
+            .map(|i| (STATE_PTR + 4 * i, 0))
+            .collect::<BTreeMap<_, _>>();
+        program
+    }
+
+    /// A program of `cycles` additions.
+    fn alu_program(cycles: usize) -> Program {
+        let instructions = (0..cycles)
+            .map(|_| Instruction::new(Opcode::ADD, 29, 29, 1, false, true))
+            .collect();
+        Program::new(instructions, 0, 0)
+    }
+
+    fn execute(program: Program, schedule: &GasSchedule) -> Runtime<'static> {
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.set_gas_schedule(schedule);
+        runtime.run().unwrap();
+        runtime
+    }
+
+    #[test]
+    fn test_gas_calibration() {
+        setup_logger();
+        let schedule = GasSchedule::default();
+        let keccak = execute(keccak_program(10), &schedule);
+        let alu = execute(alu_program(30), &schedule);
+        assert_eq!(
+            keccak.report.total_instruction_count(),
+            alu.report.total_instruction_count()
+        );
+
+        // The cycles cost the same, but the permutations cost far more than additions.
+        let add = schedule.opcode_weight(Opcode::ADD);
+        let ecall = schedule.opcode_weight(Opcode::ECALL);
+        let permute = schedule.syscall_weight(SyscallCode::KECCAK_PERMUTE);
+        assert_eq!(alu.report.gas_used, 30 * add);
+        assert_eq!(keccak.report.gas_used, 10 * (2 * add + ecall + permute));
+        assert!(keccak.report.gas_used > 100 * alu.report.gas_used);
+
+        // Every class of instructions costs at least an instruction of the CPU alone.
+        for class in OpcodeClass::iter() {
+            assert!(
+                schedule.opcode_weights[&class] >= schedule.opcode_weights[&OpcodeClass::System]
+            );
+        }
+        // Every precompile costs more than an addition.
+        for code in SyscallCode::iter().filter(|code| code.chip_name().is_some()) {
+            assert!(schedule.syscall_weight(code) > add, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_gas_determinism() {
+        let schedule = GasSchedule::default();
+        let first = execute(keccak_program(3), &schedule);
+        let second = execute(keccak_program(3), &schedule);
+        assert_eq!(first.report.gas_used, second.report.gas_used);
+        assert_eq!(first.state.gas_used, first.report.gas_used);
+        assert_eq!(
+            first.report.gas_schedule_digest,
+            Some(GasSchedule::default().digest())
+        );
+
+        // An override from JSON changes the gas and the digest of the schedule.
+        let json = r#"{"syscall_weights": {"KECCAK_PERMUTE": 5000}}"#;
+        let custom = GasSchedule::from_json(json).unwrap();
+        assert_eq!(custom.opcode_weights, schedule.opcode_weights);
+        assert_ne!(custom.digest(), schedule.digest());
+        let third = execute(keccak_program(3), &custom);
+        assert_eq!(
+            third.report.gas_used,
+            first.report.gas_used
+                + 3 * (5000 - schedule.syscall_weight(SyscallCode::KECCAK_PERMUTE))
+        );
+        assert_eq!(third.report.gas_schedule_digest, Some(custom.digest()));
+
+        // The schedule round-trips through JSON.
+        assert_eq!(
+            GasSchedule::from_json(&custom.to_json().unwrap()).unwrap(),
+            custom
+        );
+    }
+}
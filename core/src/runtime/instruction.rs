@@ -56,6 +56,10 @@ impl Instruction {
                 | Opcode::DIVU
                 | Opcode::REM
                 | Opcode::REMU
+                | Opcode::CLZ
+                | Opcode::CTZ
+                | Opcode::CPOP
+                | Opcode::REV8
         )
     }
 
@@ -1,5 +1,6 @@
 use std::io::Read;
 
+use crate::io::input_digest_with_entropy;
 use crate::stark::{ShardProof, StarkVerifyingKey};
 use crate::utils::BabyBearPoseidon2;
 
@@ -32,6 +33,16 @@ impl<'a> Runtime<'a> {
         }
     }
 
+    /// Set the canonical config encoding read by the `CONFIG_LEN` and `CONFIG_READ` syscalls.
+    pub fn write_config(&mut self, config: &[u8]) {
+        self.state.config_stream = config.to_vec();
+    }
+
+    /// Set the entropy pool read by the `ENTROPY_LEN` and `ENTROPY_READ` syscalls.
+    pub fn write_entropy(&mut self, entropy: &[u8]) {
+        self.state.entropy_stream = entropy.to_vec();
+    }
+
     pub fn write_proof(
         &mut self,
         proof: ShardProof<BabyBearPoseidon2>,
@@ -40,6 +51,16 @@ impl<'a> Runtime<'a> {
         self.state.proof_stream.push((proof, vk));
     }
 
+    /// The digest of the input buffers read and the entropy consumed so far, see
+    /// [crate::io::input_digest_with_entropy]. Once the program has halted, this is the digest a
+    /// guest built with the `commit-input` feature commits.
+    pub fn input_digest(&self) -> [u8; 32] {
+        input_digest_with_entropy(
+            &self.state.input_stream[..self.state.input_stream_ptr],
+            &self.state.entropy_stream[..self.state.entropy_stream_ptr],
+        )
+    }
+
     pub fn read_public_values<T: DeserializeOwned>(&mut self) -> T {
         let result = bincode::deserialize_from::<_, T>(self);
         result.unwrap()
@@ -0,0 +1,407 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::disassembler::{MemorySegment, WORD_SIZE};
+
+/// The address the stack of the guest grows down from, set by the entrypoint of `sp1-zkvm`.
+pub const STACK_TOP: u32 = 0x0020_0400;
+
+/// The number of bytes below [STACK_TOP] reserved for the stack.
+pub const STACK_MAX_SIZE: u32 = 0x0010_0000;
+
+/// The address the heap of the guest ends at, checked by the allocator of `sp1-zkvm`.
+pub const HEAP_END: u32 = 0x0C00_0000;
+
+/// The start of the memory reserved for the buffers of the syscalls.
+pub const SYSCALL_BUFFERS_START: u32 = HEAP_END;
+
+/// The end of the memory reserved for the buffers of the syscalls.
+pub const SYSCALL_BUFFERS_END: u32 = 0x0D00_0000;
+
+/// The start of the memory reserved for the data placed by the host, such as a config page or a
+/// witness region.
+pub const INPUT_REGION_START: u32 = SYSCALL_BUFFERS_END;
+
+/// The end of the memory reserved for the data placed by the host.
+pub const INPUT_REGION_END: u32 = 0x1000_0000;
+
+/// A range of guest addresses, from `start` included to `end` excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl MemoryRange {
+    pub const fn new(start: u32, end: u32) -> Self {
+        Self { start, end }
+    }
+
+    /// The number of bytes in the range.
+    pub const fn len(&self) -> u32 {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `addr` is in the range.
+    pub const fn contains(&self, addr: u32) -> bool {
+        self.start <= addr && addr < self.end
+    }
+
+    /// Whether the two ranges share an address.
+    pub const fn overlaps(&self, other: &MemoryRange) -> bool {
+        !self.is_empty() && !other.is_empty() && self.start < other.end && other.start < self.end
+    }
+}
+
+impl Display for MemoryRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "[{:#010x}, {:#010x})", self.start, self.end)
+    }
+}
+
+/// A region of a [MemoryLayout].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemoryRegion {
+    /// An executable segment of the program.
+    Text,
+    /// The bytes of a non-executable segment loaded from the ELF, including read-only data.
+    Data,
+    /// The zero-filled bytes of a non-executable segment.
+    Bss,
+    Stack,
+    Heap,
+    SyscallBuffers,
+    InputRegion,
+}
+
+impl Display for MemoryRegion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let name = match self {
+            MemoryRegion::Text => "text",
+            MemoryRegion::Data => "data",
+            MemoryRegion::Bss => "bss",
+            MemoryRegion::Stack => "stack",
+            MemoryRegion::Heap => "heap",
+            MemoryRegion::SyscallBuffers => "syscall buffers",
+            MemoryRegion::InputRegion => "input region",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MemoryLayoutError {
+    #[error("the {first} at {first_range} overlaps the {second} at {second_range}")]
+    Overlap {
+        first: MemoryRegion,
+        first_range: MemoryRange,
+        second: MemoryRegion,
+        second_range: MemoryRange,
+    },
+    #[error("the {name} at {range} collides with the {region} at {region_range}")]
+    PlacementCollision {
+        name: String,
+        range: MemoryRange,
+        region: MemoryRegion,
+        region_range: MemoryRange,
+    },
+    #[error("the {name} of {len} bytes at {start:#x} exceeds the 32-bit address space")]
+    PlacementOutOfBounds { name: String, start: u32, len: u32 },
+}
+
+/// Where the program, its stack, its heap and the reserved memory are in the guest address space.
+///
+/// The layout is fixed by the ELF and the constants of this module, which the entrypoint and the
+/// allocator of `sp1-zkvm` share, so that hosts can place data at addresses the guest never uses,
+/// see [MemoryLayout::check_placement].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLayout {
+    /// The executable segments, ordered by address.
+    pub text: Vec<MemoryRange>,
+    /// The bytes loaded from the non-executable segments, ordered by address.
+    pub data: Vec<MemoryRange>,
+    /// The zero-filled bytes of the non-executable segments, ordered by address.
+    pub bss: Vec<MemoryRange>,
+    /// The address the stack grows down from.
+    pub stack_top: u32,
+    /// The number of bytes reserved for the stack.
+    pub stack_max_size: u32,
+    /// The heap, from the end of the segments to [HEAP_END].
+    pub heap: MemoryRange,
+    /// The memory reserved for the buffers of the syscalls.
+    pub syscall_buffers: MemoryRange,
+    /// The memory reserved for the data placed by the host.
+    pub input_region: MemoryRange,
+}
+
+impl MemoryLayout {
+    /// Computes the layout of a program loaded from `segments`, ordered by address.
+    pub fn new(segments: &[MemorySegment]) -> Self {
+        let mut text = Vec::new();
+        let mut data = Vec::new();
+        let mut bss = Vec::new();
+        for segment in segments {
+            let end = segment.end() as u32;
+            if segment.executable {
+                text.push(MemoryRange::new(segment.vaddr, end));
+                continue;
+            }
+            let file_end = segment.vaddr + segment.file_size.min(segment.mem_size);
+            if file_end > segment.vaddr {
+                data.push(MemoryRange::new(segment.vaddr, file_end));
+            }
+            if end > file_end {
+                bss.push(MemoryRange::new(file_end, end));
+            }
+        }
+
+        // The allocator starts at the end of the last segment below the heap end, the `_end`
+        // symbol of the linker script.
+        let heap_start = segments
+            .iter()
+            .filter(|segment| segment.vaddr < HEAP_END)
+            .map(|segment| segment.end())
+            .max()
+            .map_or(STACK_TOP as u64, |end| {
+                end.next_multiple_of(WORD_SIZE as u64)
+            })
+            .min(HEAP_END as u64) as u32;
+
+        Self {
+            text,
+            data,
+            bss,
+            stack_top: STACK_TOP,
+            stack_max_size: STACK_MAX_SIZE,
+            heap: MemoryRange::new(heap_start, HEAP_END),
+            syscall_buffers: MemoryRange::new(SYSCALL_BUFFERS_START, SYSCALL_BUFFERS_END),
+            input_region: MemoryRange::new(INPUT_REGION_START, INPUT_REGION_END),
+        }
+    }
+
+    /// The memory reserved for the stack.
+    pub const fn stack(&self) -> MemoryRange {
+        MemoryRange::new(
+            self.stack_top.saturating_sub(self.stack_max_size),
+            self.stack_top,
+        )
+    }
+
+    /// The regions of the layout with their ranges: the segments in address order, then the
+    /// stack, the heap and the reserved memory.
+    pub fn regions(&self) -> Vec<(MemoryRegion, MemoryRange)> {
+        let mut segments = self
+            .text
+            .iter()
+            .map(|range| (MemoryRegion::Text, *range))
+            .chain(self.data.iter().map(|range| (MemoryRegion::Data, *range)))
+            .chain(self.bss.iter().map(|range| (MemoryRegion::Bss, *range)))
+            .collect::<Vec<_>>();
+        segments.sort_by_key(|(_, range)| range.start);
+        segments.extend([
+            (MemoryRegion::Stack, self.stack()),
+            (MemoryRegion::Heap, self.heap),
+            (MemoryRegion::SyscallBuffers, self.syscall_buffers),
+            (MemoryRegion::InputRegion, self.input_region),
+        ]);
+        segments
+    }
+
+    /// Checks that no two regions of the layout overlap, which the runtime does before executing
+    /// the program.
+    pub fn validate(&self) -> Result<(), MemoryLayoutError> {
+        let regions = self.regions();
+        for (i, (first, first_range)) in regions.iter().enumerate() {
+            for (second, second_range) in &regions[i + 1..] {
+                if first_range.overlaps(second_range) {
+                    return Err(MemoryLayoutError::Overlap {
+                        first: *first,
+                        first_range: *first_range,
+                        second: *second,
+                        second_range: *second_range,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the `len` bytes placed by the host at `start`, named `name` in the errors,
+    /// don't collide with the memory used by the guest, returning their range.
+    ///
+    /// Placements may be anywhere outside of the segments, the stack, the heap and the syscall
+    /// buffers, and the [MemoryLayout::input_region] is reserved for them.
+    pub fn check_placement(
+        &self,
+        name: &str,
+        start: u32,
+        len: u32,
+    ) -> Result<MemoryRange, MemoryLayoutError> {
+        let end =
+            start
+                .checked_add(len)
+                .ok_or_else(|| MemoryLayoutError::PlacementOutOfBounds {
+                    name: name.to_string(),
+                    start,
+                    len,
+                })?;
+        let range = MemoryRange::new(start, end);
+        let collision = self
+            .regions()
+            .into_iter()
+            .filter(|(region, _)| *region != MemoryRegion::InputRegion)
+            .find(|(_, region_range)| region_range.overlaps(&range));
+        match collision {
+            Some((region, region_range)) => Err(MemoryLayoutError::PlacementCollision {
+                name: name.to_string(),
+                range,
+                region,
+                region_range,
+            }),
+            None => Ok(range),
+        }
+    }
+}
+
+impl Display for MemoryLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (region, range) in self.regions() {
+            writeln!(f, "  {}: {}", region, range)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{ExecutionError, Program, Runtime};
+    use crate::utils::tests::{FIBONACCI_ELF, KECCAK_PERMUTE_ELF, SSZ_WITHDRAWALS_ELF};
+    use crate::utils::SP1CoreOpts;
+
+    #[test]
+    fn test_zkvm_layout() {
+        assert_eq!(STACK_TOP, sp1_zkvm::layout::STACK_TOP);
+        assert_eq!(STACK_MAX_SIZE, sp1_zkvm::layout::STACK_MAX_SIZE);
+        assert_eq!(HEAP_END, sp1_zkvm::layout::HEAP_END);
+        assert_eq!(SYSCALL_BUFFERS_END, sp1_zkvm::layout::SYSCALL_BUFFERS_END);
+        assert_eq!(INPUT_REGION_END, sp1_zkvm::layout::INPUT_REGION_END);
+    }
+
+    #[test]
+    fn test_elf_layouts() {
+        for elf in [FIBONACCI_ELF, KECCAK_PERMUTE_ELF, SSZ_WITHDRAWALS_ELF] {
+            let program = Program::from(elf);
+            let layout = program.layout().unwrap();
+            layout.validate().unwrap();
+
+            assert!(!layout.text.is_empty());
+            assert!(layout
+                .text
+                .iter()
+                .any(|text| text.contains(program.pc_start)));
+            assert_eq!(layout.text[0].start, program.pc_base);
+            let segments_end = program
+                .segments()
+                .iter()
+                .map(|segment| segment.end())
+                .max()
+                .unwrap();
+            assert_eq!(
+                layout.heap.start as u64,
+                segments_end.next_multiple_of(WORD_SIZE as u64)
+            );
+            assert_eq!(layout.heap.start % WORD_SIZE as u32, 0);
+            assert!(layout.stack().end <= layout.text[0].start);
+            assert_eq!(layout.heap.end, layout.syscall_buffers.start);
+            assert_eq!(layout.syscall_buffers.end, layout.input_region.start);
+
+            // Every byte of the memory image is in a segment of the layout.
+            for addr in program.memory_image.keys() {
+                assert!(layout.regions().iter().any(|(region, range)| {
+                    matches!(
+                        region,
+                        MemoryRegion::Text | MemoryRegion::Data | MemoryRegion::Bss
+                    ) && range.contains(*addr)
+                }));
+            }
+
+            let config = layout
+                .check_placement("config page", INPUT_REGION_START, 0x1000)
+                .unwrap();
+            assert_eq!(config.len(), 0x1000);
+        }
+    }
+
+    #[test]
+    fn test_placement_collision() {
+        let layout = Program::from(FIBONACCI_ELF).layout().unwrap().clone();
+
+        let err = layout
+            .check_placement("witness region", layout.heap.start + 0x100, 0x1000)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            MemoryLayoutError::PlacementCollision {
+                name: "witness region".to_string(),
+                range: MemoryRange::new(layout.heap.start + 0x100, layout.heap.start + 0x1100),
+                region: MemoryRegion::Heap,
+                region_range: layout.heap,
+            }
+        );
+
+        let err = layout
+            .check_placement("config page", STACK_TOP - 4, 8)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MemoryLayoutError::PlacementCollision {
+                region: MemoryRegion::Stack,
+                ..
+            }
+        ));
+        assert!(matches!(
+            layout.check_placement("config page", layout.text[0].start, 4),
+            Err(MemoryLayoutError::PlacementCollision {
+                region: MemoryRegion::Text,
+                ..
+            })
+        ));
+        assert!(matches!(
+            layout.check_placement("config page", u32::MAX - 4, 8),
+            Err(MemoryLayoutError::PlacementOutOfBounds { .. })
+        ));
+        layout
+            .check_placement("config page", INPUT_REGION_END, 0x1000)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_overlapping_layout() {
+        let mut program = Program::from(FIBONACCI_ELF);
+        let mut layout = program.layout().unwrap().clone();
+        layout.stack_max_size = STACK_TOP;
+        assert!(matches!(
+            layout.validate(),
+            Err(MemoryLayoutError::Overlap {
+                second: MemoryRegion::Stack,
+                ..
+            })
+        ));
+
+        program.layout = Some(layout);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        assert!(matches!(
+            runtime.run(),
+            Err(ExecutionError::InvalidMemoryLayout(
+                MemoryLayoutError::Overlap { .. }
+            ))
+        ));
+    }
+}
@@ -1,29 +1,49 @@
+mod coverage;
+mod effects;
+mod filter;
+mod fuel;
+mod gas;
 mod hooks;
 mod instruction;
 mod io;
+mod layout;
 mod memory;
 mod opcode;
+mod profiler;
 mod program;
 mod record;
+mod record_builder;
 mod register;
 mod report;
+mod snapshot;
 mod state;
 mod syscall;
 #[macro_use]
 mod utils;
 mod subproof;
+mod unconstrained_syscall;
 
+pub use coverage::*;
+pub use effects::*;
+pub use filter::*;
+pub use fuel::*;
+pub use gas::*;
 pub use hooks::*;
 pub use instruction::*;
+pub use layout::*;
 pub use memory::*;
 pub use opcode::*;
+pub use profiler::*;
 pub use program::*;
 pub use record::*;
+pub use record_builder::*;
 pub use register::*;
 pub use report::*;
+pub use snapshot::*;
 pub use state::*;
 pub use subproof::*;
 pub use syscall::*;
+pub use unconstrained_syscall::*;
 pub use utils::*;
 
 use std::collections::hash_map::Entry;
@@ -33,14 +53,23 @@ use std::io::BufWriter;
 use std::io::Write;
 use std::sync::Arc;
 
+use p3_field::PrimeField32;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use thiserror::Error;
 
 use crate::alu::create_alu_lookup_id;
 use crate::alu::create_alu_lookups;
 use crate::bytes::NUM_BYTE_LOOKUP_CHANNELS;
 use crate::memory::MemoryInitializeFinalizeEvent;
-use crate::utils::SP1CoreOpts;
-use crate::{alu::AluEvent, cpu::CpuEvent};
+use crate::stark::{RiscvAir, StarkMachine};
+use crate::syscall::SpanMeasurements;
+use crate::utils::{shard_seed, SP1CoreOpts};
+use crate::StarkGenericConfig;
+use crate::{
+    alu::AluEvent,
+    cpu::{CpuEvent, CpuEventStore},
+};
 
 /// An implementation of a runtime for the SP1 RISC-V zkVM.
 ///
@@ -67,12 +96,37 @@ pub struct Runtime<'a> {
 
     pub shard_batch_size: u32,
 
+    /// The clock from which the current shard ends at a shard boundary hint, see
+    /// [SP1CoreOpts::shard_boundary_hint_fraction].
+    pub shard_boundary_hint_clk: u32,
+
+    /// Whether the program hinted a shard boundary in the current cycle.
+    pub(crate) shard_boundary_hinted: bool,
+
     /// A counter for the number of cycles that have been executed in certain functions.
     pub cycle_tracker: HashMap<String, (u64, u32)>,
 
+    /// The handlers of the unconstrained syscalls made by the program, none by default.
+    pub unconstrained_syscalls: UnconstrainedSyscalls,
+
+    /// The number of unconstrained syscalls made by the program.
+    pub num_unconstrained_syscalls: u64,
+
+    /// The first address written by an unconstrained syscall after the program touched it, which
+    /// makes the execution unprovable.
+    pub unconstrained_syscall_overwrite: Option<u32>,
+
+    /// The cycle counts read by the program in the cycle-tracker spans, used to check the span
+    /// cycles it commits.
+    pub(crate) span_measurements: SpanMeasurements,
+
     /// A buffer for stdout and stderr IO.
     pub io_buf: HashMap<u32, String>,
 
+    /// The last [STDERR_CAPTURE_LEN] bytes written to stderr, reported in
+    /// [ExecutionError::GuestPanic] if the program halts with a non-zero exit code.
+    pub(crate) stderr: String,
+
     /// A buffer for writing trace events to a file.
     pub trace_buf: Option<BufWriter<File>>,
 
@@ -96,17 +150,51 @@ pub struct Runtime<'a> {
     /// Whether we should write to the report.
     pub print_report: bool,
 
+    /// The weights the execution is metered with, see [Runtime::set_gas_schedule].
+    pub(crate) gas: GasMeter,
+
     /// Verifier used to sanity check `verify_sp1_proof` during runtime.
     pub subproof_verifier: Arc<dyn SubproofVerifier + 'a>,
 
     /// Registry of hooks, to be invoked by writing to certain file descriptors.
     pub hook_registry: HookRegistry<'a>,
+
+    /// The number of times each instruction was executed, if coverage is enabled.
+    pub coverage: Option<Vec<u32>>,
+
+    /// The sampled call stacks of the program, if the profiler is enabled.
+    pub profiler: Option<Profiler>,
+
+    /// The syscalls made by the program, if the effect log is enabled.
+    pub effects: Option<Vec<Effect>>,
+
+    /// The guest memory limit in bytes, see [SP1CoreOpts::max_memory].
+    pub max_memory: u64,
+
+    /// The address of the first word touched past `max_memory` by the current instruction.
+    pub(crate) memory_limit_fault: Option<u32>,
+
+    /// The chips whose events are recorded, see [Runtime::new_for_machine].
+    pub record_filter: RecordFilter,
+
+    /// The seed of the lookup ids of the events and their generator for the current shard, if
+    /// seeded by [Runtime::seed_lookup_ids].
+    lookup_ids: Option<([u8; 32], StdRng)>,
+
+    /// The snapshots captured at the start of each shard, if enabled by
+    /// [Runtime::enable_snapshots].
+    pub(crate) snapshots: Option<SnapshotRecorder>,
 }
 
+/// The number of bytes of stderr output kept for [ExecutionError::GuestPanic].
+pub const STDERR_CAPTURE_LEN: usize = 1 << 12;
+
 #[derive(Error, Debug)]
 pub enum ExecutionError {
     #[error("execution failed with exit code {0}")]
     HaltWithNonZeroExitCode(u32),
+    #[error("execution failed with exit code {exit_code}: {message}")]
+    GuestPanic { exit_code: u32, message: String },
     #[error("invalid memory access for opcode {0} and address {1}")]
     InvalidMemoryAccess(Opcode, u32),
     #[error("unimplemented syscall {0}")]
@@ -115,6 +203,15 @@ pub enum ExecutionError {
     Breakpoint(),
     #[error("got unimplemented as opcode")]
     Unimplemented(),
+    #[error("memory footprint limit of {limit} bytes exceeded by a first access to address {attempted_addr:#x}")]
+    MemoryLimitExceeded { limit: u64, attempted_addr: u32 },
+    #[error("syscall {syscall} requires the {chip} chip, which is excluded from the machine")]
+    ExcludedSyscall {
+        syscall: SyscallCode,
+        chip: &'static str,
+    },
+    #[error("invalid memory layout: {0}")]
+    InvalidMemoryLayout(#[from] MemoryLayoutError),
 }
 
 impl<'a> Runtime<'a> {
@@ -126,6 +223,7 @@ impl<'a> Runtime<'a> {
         // Create a default record with the program.
         let record = ExecutionRecord {
             program: program.clone(),
+            cpu_events: CpuEventStore::new(opts.compress_cpu_events),
             ..Default::default()
         };
 
@@ -145,6 +243,9 @@ impl<'a> Runtime<'a> {
             .max()
             .unwrap_or(0);
 
+        let gas = GasMeter::default();
+        let layout = program.layout().cloned();
+
         Self {
             record,
             state: ExecutionState::new(program.pc_start),
@@ -152,21 +253,58 @@ impl<'a> Runtime<'a> {
             memory_accesses: MemoryAccessRecord::default(),
             shard_size: (opts.shard_size as u32) * 4,
             shard_batch_size: opts.shard_batch_size as u32,
+            shard_boundary_hint_clk: (opts.shard_size as f64 * opts.shard_boundary_hint_fraction)
+                as u32
+                * 4,
+            shard_boundary_hinted: false,
             cycle_tracker: HashMap::new(),
+            span_measurements: SpanMeasurements::default(),
             io_buf: HashMap::new(),
+            stderr: String::new(),
             trace_buf,
             unconstrained: false,
             unconstrained_state: ForkState::default(),
             syscall_map,
+            unconstrained_syscalls: UnconstrainedSyscalls::default(),
+            num_unconstrained_syscalls: 0,
+            unconstrained_syscall_overwrite: None,
             emit_events: true,
             max_syscall_cycles,
-            report: ExecutionReport::default(),
+            report: ExecutionReport {
+                gas_schedule_digest: Some(gas.digest),
+                layout,
+                ..Default::default()
+            },
             print_report: false,
+            gas,
             subproof_verifier: Arc::new(DefaultSubproofVerifier::new()),
             hook_registry: HookRegistry::default(),
+            coverage: None,
+            profiler: None,
+            effects: None,
+            max_memory: opts.max_memory,
+            memory_limit_fault: None,
+            record_filter: RecordFilter::all(),
+            lookup_ids: None,
+            snapshots: None,
         }
     }
 
+    /// Create a new runtime from a program, which only records the events of the chips of
+    /// `machine`, see [RecordFilter].
+    pub fn new_for_machine<SC: StarkGenericConfig>(
+        program: Program,
+        opts: SP1CoreOpts,
+        machine: &StarkMachine<SC, RiscvAir<SC::Val>>,
+    ) -> Self
+    where
+        SC::Val: PrimeField32,
+    {
+        let mut runtime = Self::new(program, opts);
+        runtime.record_filter = RecordFilter::for_machine(machine);
+        runtime
+    }
+
     /// Invokes the hook corresponding to the given file descriptor `fd` with the data `buf`,
     /// returning the resulting data.
     pub fn hook(&self, fd: u32, buf: &[u8]) -> Vec<Vec<u8>> {
@@ -182,13 +320,29 @@ impl<'a> Runtime<'a> {
     pub fn recover(program: Program, state: ExecutionState, opts: SP1CoreOpts) -> Self {
         let mut runtime = Self::new(program, opts);
         runtime.state = state;
-        let index: u32 = (runtime.state.global_clk / (runtime.shard_size / 4) as u64)
-            .try_into()
-            .unwrap();
-        runtime.record.index = index + 1;
+        // Shard boundary hints make the shards shorter, so the index is not a function of the clock.
+        runtime.record.index = runtime.state.current_shard;
         runtime
     }
 
+    /// Meters the execution with `schedule` instead of the default [GasSchedule].
+    pub fn set_gas_schedule(&mut self, schedule: &GasSchedule) {
+        self.gas = GasMeter::new(schedule);
+        self.report.gas_schedule_digest = Some(self.gas.digest);
+    }
+
+    /// Start counting how many times each instruction is executed.
+    pub fn enable_coverage(&mut self) {
+        self.coverage = Some(vec![0; self.program.instructions.len()]);
+    }
+
+    /// Builds a [CoverageReport] from the counts collected so far, using the symbol table of `elf`
+    /// (the ELF the program was loaded from). Returns `None` if coverage is not enabled.
+    pub fn coverage_report(&self, elf: &[u8]) -> Option<CoverageReport> {
+        let counts = self.coverage.clone()?;
+        Some(CoverageReport::new(elf, self.program.pc_base, counts))
+    }
+
     /// Get the current values of the registers.
     pub fn registers(&self) -> [u32; 32] {
         let mut registers = [0; 32];
@@ -211,6 +365,12 @@ impl<'a> Runtime<'a> {
         }
     }
 
+    /// Get the memory footprint in bytes: the number of distinct words touched so far, registers
+    /// included, times 4.
+    pub fn memory_footprint(&self) -> u64 {
+        self.state.memory.len() as u64 * 4
+    }
+
     /// Get the current value of a word.
     pub fn word(&self, addr: u32) -> u32 {
         match self.state.memory.get(&addr) {
@@ -243,7 +403,10 @@ impl<'a> Runtime<'a> {
 
     /// Read a word from memory and create an access record.
     pub fn mr(&mut self, addr: u32, shard: u32, timestamp: u32) -> MemoryReadRecord {
+        self.touch(addr);
+
         // Get the memory record entry.
+        let memory_full = self.memory_footprint() >= self.max_memory;
         let entry = self.state.memory.entry(addr);
 
         // If we're in unconstrained mode, we don't want to modify state, so we'll save the
@@ -263,6 +426,10 @@ impl<'a> Runtime<'a> {
         let record: &mut MemoryRecord = match entry {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
+                if memory_full {
+                    self.memory_limit_fault.get_or_insert(addr);
+                }
+
                 // If addr has a specific value to be initialized with, use that, otherwise 0.
                 let value = self.state.uninitialized_memory.get(&addr).unwrap_or(&0);
                 entry.insert(MemoryRecord {
@@ -284,7 +451,10 @@ impl<'a> Runtime<'a> {
 
     /// Write a word to memory and create an access record.
     pub fn mw(&mut self, addr: u32, value: u32, shard: u32, timestamp: u32) -> MemoryWriteRecord {
+        self.touch(addr);
+
         // Get the memory record entry.
+        let memory_full = self.memory_footprint() >= self.max_memory;
         let entry = self.state.memory.entry(addr);
 
         // If we're in unconstrained mode, we don't want to modify state, so we'll save the
@@ -304,6 +474,10 @@ impl<'a> Runtime<'a> {
         let record: &mut MemoryRecord = match entry {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
+                if memory_full {
+                    self.memory_limit_fault.get_or_insert(addr);
+                }
+
                 // If addr has a specific value to be initialized with, use that, otherwise 0.
                 let value = self.state.uninitialized_memory.get(&addr).unwrap_or(&0);
 
@@ -419,6 +593,19 @@ impl<'a> Runtime<'a> {
         lookup_id: usize,
         syscall_lookup_id: usize,
     ) {
+        // The ids of the operations the instruction doesn't depend on are left to zero, so that
+        // they compress, see [crate::cpu::CpuEventStore].
+        let opcode = instruction.opcode;
+        let mut lookup_id_if = |used: bool| if used { self.lookup_id() } else { 0 };
+        let memory_add_lookup_id = lookup_id_if(instruction.is_memory_instruction());
+        let memory_sub_lookup_id = lookup_id_if(matches!(opcode, Opcode::LB | Opcode::LH));
+        let branch_lt_lookup_id = lookup_id_if(instruction.is_branch_instruction());
+        let branch_gt_lookup_id = lookup_id_if(instruction.is_branch_instruction());
+        let branch_add_lookup_id = lookup_id_if(instruction.is_branch_instruction());
+        let jump_jal_lookup_id = lookup_id_if(opcode == Opcode::JAL);
+        let jump_jalr_lookup_id = lookup_id_if(opcode == Opcode::JALR);
+        let auipc_lookup_id = lookup_id_if(opcode == Opcode::AUIPC);
+
         let cpu_event = CpuEvent {
             shard,
             channel,
@@ -437,19 +624,47 @@ impl<'a> Runtime<'a> {
             exit_code,
             alu_lookup_id: lookup_id,
             syscall_lookup_id,
-            memory_add_lookup_id: create_alu_lookup_id(),
-            memory_sub_lookup_id: create_alu_lookup_id(),
-            branch_lt_lookup_id: create_alu_lookup_id(),
-            branch_gt_lookup_id: create_alu_lookup_id(),
-            branch_add_lookup_id: create_alu_lookup_id(),
-            jump_jal_lookup_id: create_alu_lookup_id(),
-            jump_jalr_lookup_id: create_alu_lookup_id(),
-            auipc_lookup_id: create_alu_lookup_id(),
+            memory_add_lookup_id,
+            memory_sub_lookup_id,
+            branch_lt_lookup_id,
+            branch_gt_lookup_id,
+            branch_add_lookup_id,
+            jump_jal_lookup_id,
+            jump_jalr_lookup_id,
+            auipc_lookup_id,
         };
 
         self.record.cpu_events.push(cpu_event);
     }
 
+    /// Draws the ids of the events from a generator seeded by `seed` instead of the thread's random
+    /// number generator, so that executing the same program again records the same events.
+    ///
+    /// The ids only pair the events of the CPU with the rows of the chips proving them, so they
+    /// don't change the proof, but a seeded execution gives byte-identical records. The generator
+    /// is seeded again at the start of each shard with [shard_seed], so that the ids of a shard
+    /// don't depend on where the execution started.
+    pub fn seed_lookup_ids(&mut self, seed: [u8; 32]) {
+        let rng = StdRng::from_seed(shard_seed(seed, self.state.current_shard));
+        self.lookup_ids = Some((seed, rng));
+    }
+
+    /// A new lookup id for an event.
+    fn lookup_id(&mut self) -> usize {
+        match self.lookup_ids.as_mut() {
+            Some((_, rng)) => rng.gen(),
+            None => create_alu_lookup_id(),
+        }
+    }
+
+    /// New lookup ids for the operations an ALU event depends on.
+    fn sub_lookup_ids(&mut self) -> [usize; 6] {
+        match self.lookup_ids.as_mut() {
+            Some((_, rng)) => rng.gen(),
+            None => create_alu_lookups(),
+        }
+    }
+
     /// Emit an ALU event.
     fn emit_alu(&mut self, clk: u32, opcode: Opcode, a: u32, b: u32, c: u32, lookup_id: usize) {
         let event = AluEvent {
@@ -461,7 +676,7 @@ impl<'a> Runtime<'a> {
             a,
             b,
             c,
-            sub_lookups: create_alu_lookups(),
+            sub_lookups: self.sub_lookup_ids(),
         };
         match opcode {
             Opcode::ADD => {
@@ -473,11 +688,8 @@ impl<'a> Runtime<'a> {
             Opcode::XOR | Opcode::OR | Opcode::AND => {
                 self.record.bitwise_events.push(event);
             }
-            Opcode::SLL => {
-                self.record.shift_left_events.push(event);
-            }
-            Opcode::SRL | Opcode::SRA => {
-                self.record.shift_right_events.push(event);
+            Opcode::SLL | Opcode::SRL | Opcode::SRA => {
+                self.record.shift_events.push(event);
             }
             Opcode::SLT | Opcode::SLTU => {
                 self.record.lt_events.push(event);
@@ -488,6 +700,9 @@ impl<'a> Runtime<'a> {
             Opcode::DIVU | Opcode::REMU | Opcode::DIV | Opcode::REM => {
                 self.record.divrem_events.push(event);
             }
+            Opcode::CLZ | Opcode::CTZ | Opcode::CPOP | Opcode::REV8 => {
+                self.record.bitmanip_events.push(event);
+            }
             _ => {}
         }
     }
@@ -579,15 +794,15 @@ impl<'a> Runtime<'a> {
         let mut memory_store_value: Option<u32> = None;
         self.memory_accesses = MemoryAccessRecord::default();
 
-        let lookup_id = create_alu_lookup_id();
-        let syscall_lookup_id = create_alu_lookup_id();
+        let lookup_id = self.lookup_id();
+        let syscall_lookup_id = self.lookup_id();
 
-        if self.print_report && !self.unconstrained {
-            self.report
-                .opcode_counts
-                .entry(instruction.opcode)
-                .and_modify(|c| *c += 1)
-                .or_insert(1);
+        if !self.unconstrained {
+            let gas = self.gas.opcode(instruction.opcode);
+            self.state.gas_used += gas;
+            if self.print_report {
+                self.report.record_opcode(instruction.opcode, gas);
+            }
         }
 
         match instruction.opcode {
@@ -795,6 +1010,16 @@ impl<'a> Runtime<'a> {
             }
 
             // System instructions.
+            Opcode::ECALL if UNCONSTRAINED_SYSCALL_IDS.contains(&self.register(Register::X5)) => {
+                let t0 = Register::X5;
+                let syscall_id = self.register(t0);
+                c = self.rr(Register::X11, MemoryAccessPosition::C);
+                b = self.rr(Register::X10, MemoryAccessPosition::B);
+                self.execute_unconstrained_syscall(syscall_id, b, c)?;
+                // Like the syscalls without a chip, t0 keeps the syscall id.
+                a = syscall_id;
+                self.rw(t0, a);
+            }
             Opcode::ECALL => {
                 // We peek at register x5 to get the syscall id. The reason we don't `self.rr` this
                 // register is that we write to it later.
@@ -804,12 +1029,20 @@ impl<'a> Runtime<'a> {
                 b = self.rr(Register::X10, MemoryAccessPosition::B);
                 let syscall = SyscallCode::from_u32(syscall_id);
 
-                if self.print_report && !self.unconstrained {
-                    self.report
-                        .syscall_counts
-                        .entry(syscall)
-                        .and_modify(|c| *c += 1)
-                        .or_insert(1);
+                // The syscalls whose chip is not in the machine being proved can't be constrained.
+                if !self.unconstrained && !self.record_filter.allows_syscall(syscall) {
+                    return Err(ExecutionError::ExcludedSyscall {
+                        syscall,
+                        chip: syscall.chip_name().unwrap(),
+                    });
+                }
+
+                if !self.unconstrained {
+                    let gas = self.gas.syscall(syscall);
+                    self.state.gas_used += gas;
+                    if self.print_report {
+                        self.report.record_syscall(syscall, gas);
+                    }
                 }
 
                 let syscall_impl = self.get_syscall(syscall).cloned();
@@ -826,11 +1059,18 @@ impl<'a> Runtime<'a> {
                             a = syscall_id;
                         }
 
-                        // If the syscall is `HALT` and the exit code is non-zero, return an error.
+                        // If the syscall is `HALT` and the exit code is non-zero, return an error
+                        // with the panic message the program wrote to stderr, if any.
                         if syscall == SyscallCode::HALT && precompile_rt.exit_code != 0 {
-                            return Err(ExecutionError::HaltWithNonZeroExitCode(
-                                precompile_rt.exit_code,
-                            ));
+                            let exit_code = precompile_rt.exit_code;
+                            let message = precompile_rt.rt.stderr.trim_end();
+                            if message.is_empty() {
+                                return Err(ExecutionError::HaltWithNonZeroExitCode(exit_code));
+                            }
+                            return Err(ExecutionError::GuestPanic {
+                                exit_code,
+                                message: message.to_string(),
+                            });
                         }
 
                         (
@@ -847,6 +1087,9 @@ impl<'a> Runtime<'a> {
                 pc = self.state.pc;
 
                 self.rw(t0, a);
+                if self.effects.is_some() {
+                    self.record_effect(syscall, b, c, a);
+                }
                 next_pc = precompile_next_pc;
                 self.state.clk += precompile_cycles;
                 exit_code = returned_exit_code;
@@ -913,6 +1156,28 @@ impl<'a> Runtime<'a> {
                 self.alu_rw(instruction, rd, a, b, c, lookup_id);
             }
 
+            // Bit manipulation instructions, which only read `rs1`.
+            Opcode::CLZ => {
+                (rd, b, c) = self.alu_rr(instruction);
+                a = b.leading_zeros();
+                self.alu_rw(instruction, rd, a, b, c, lookup_id);
+            }
+            Opcode::CTZ => {
+                (rd, b, c) = self.alu_rr(instruction);
+                a = b.trailing_zeros();
+                self.alu_rw(instruction, rd, a, b, c, lookup_id);
+            }
+            Opcode::CPOP => {
+                (rd, b, c) = self.alu_rr(instruction);
+                a = b.count_ones();
+                self.alu_rw(instruction, rd, a, b, c, lookup_id);
+            }
+            Opcode::REV8 => {
+                (rd, b, c) = self.alu_rr(instruction);
+                a = b.swap_bytes();
+                self.alu_rw(instruction, rd, a, b, c, lookup_id);
+            }
+
             // See https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#instruction-aliases
             Opcode::UNIMP => {
                 return Err(ExecutionError::Unimplemented());
@@ -960,25 +1225,57 @@ impl<'a> Runtime<'a> {
         // Fetch the instruction at the current program counter.
         let instruction = self.fetch();
 
+        if let Some(coverage) = self.coverage.as_mut() {
+            let idx = ((self.state.pc - self.program.pc_base) / 4) as usize;
+            coverage[idx] = coverage[idx].saturating_add(1);
+        }
+
+        if self.profiler.is_some() {
+            self.sample_call_stack();
+        }
+
         // Log the current state of the runtime.
         self.log(&instruction);
 
         // Execute the instruction.
         self.execute_instruction(instruction)?;
 
+        // Stop before the memory maps grow any further if the instruction touched more memory than
+        // the limit allows.
+        if let Some(attempted_addr) = self.memory_limit_fault.take() {
+            return Err(ExecutionError::MemoryLimitExceeded {
+                limit: self.max_memory,
+                attempted_addr,
+            });
+        }
+
         // Increment the clock.
         self.state.global_clk += 1;
 
-        // If there's not enough cycles left for another instruction, move to the next shard.
-        // We multiply by 4 because clk is incremented by 4 for each normal instruction.
-        if !self.unconstrained && self.max_syscall_cycles + self.state.clk >= self.shard_size {
+        // If there's not enough cycles left for another instruction, or the program hinted a shard
+        // boundary late enough in the shard, move to the next shard. We multiply by 4 because clk
+        // is incremented by 4 for each normal instruction.
+        let hinted = std::mem::take(&mut self.shard_boundary_hinted)
+            && self.state.clk >= self.shard_boundary_hint_clk;
+        if !self.unconstrained
+            && (hinted || self.max_syscall_cycles + self.state.clk >= self.shard_size)
+        {
             self.state.current_shard += 1;
             self.state.clk = 0;
             self.state.channel = 0;
+            if let Some((seed, _)) = self.lookup_ids {
+                self.seed_lookup_ids(seed);
+            }
         }
 
-        Ok(self.state.pc.wrapping_sub(self.program.pc_base)
-            >= (self.program.instructions.len() * 4) as u32)
+        Ok(self.is_finished())
+    }
+
+    /// Whether the program counter left the program, which it does when the program halts.
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.state.pc.wrapping_sub(self.program.pc_base)
+            >= (self.program.instructions.len() * 4) as u32
     }
 
     /// Execute up to `self.shard_batch_size` cycles, returning the events emitted and whether the program ended.
@@ -986,7 +1283,9 @@ impl<'a> Runtime<'a> {
         self.emit_events = true;
         self.print_report = true;
         let done = self.execute()?;
-        Ok((std::mem::take(&mut self.record), done))
+        let record = std::mem::take(&mut self.record);
+        self.record.cpu_events = CpuEventStore::new(record.cpu_events.is_compressed());
+        Ok((record, done))
     }
 
     /// Execute up to `self.shard_batch_size` cycles, returning a copy of the prestate and whether the program ended.
@@ -1004,6 +1303,9 @@ impl<'a> Runtime<'a> {
 
         tracing::debug!("loading memory image");
         for (addr, value) in self.program.memory_image.iter() {
+            if let Some(recorder) = self.snapshots.as_mut() {
+                recorder.touch(*addr);
+            }
             self.state.memory.insert(
                 *addr,
                 MemoryRecord {
@@ -1038,6 +1340,10 @@ impl<'a> Runtime<'a> {
     fn execute(&mut self) -> Result<bool, ExecutionError> {
         // If it's the first cycle, initialize the program.
         if self.state.global_clk == 0 {
+            if let Some(layout) = self.program.layout() {
+                layout.validate()?;
+            }
+            self.snapshot_shard();
             self.initialize();
         }
 
@@ -1051,9 +1357,10 @@ impl<'a> Runtime<'a> {
                 break;
             }
 
-            if self.shard_batch_size > 0 && current_shard != self.state.current_shard {
+            if current_shard != self.state.current_shard {
                 num_shards_executed += 1;
                 current_shard = self.state.current_shard;
+                self.snapshot_shard();
                 if num_shards_executed == self.shard_batch_size {
                     break;
                 }
@@ -1064,6 +1371,11 @@ impl<'a> Runtime<'a> {
             self.postprocess();
         }
 
+        // Drop the events of the chips left out of the machine at the end of the shard batch.
+        if self.emit_events {
+            self.record_filter.prune(&mut self.record);
+        }
+
         Ok(done)
     }
 
@@ -1121,7 +1433,10 @@ impl<'a> Runtime<'a> {
             MemoryInitializeFinalizeEvent::initialize(0, 0, addr_0_record.is_some());
         memory_initialize_events.push(addr_0_initialize_event);
 
-        for addr in self.state.memory.keys() {
+        // The events are in address order, so that they don't depend on the history of the map.
+        let mut addrs = self.state.memory.keys().copied().collect::<Vec<_>>();
+        addrs.sort_unstable();
+        for addr in addrs.iter() {
             if addr == &0 {
                 // Handled above.
                 continue;
@@ -1207,6 +1522,19 @@ pub mod tests {
         runtime.run().unwrap();
     }
 
+    #[test]
+    fn test_panic_message() {
+        let program = panic_program();
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        match runtime.run() {
+            Err(ExecutionError::GuestPanic { exit_code, message }) => {
+                assert_eq!(exit_code, 1);
+                assert!(!message.is_empty());
+            }
+            result => panic!("expected a guest panic, got {:?}", result.err()),
+        }
+    }
+
     #[test]
     fn test_add() {
         // main:
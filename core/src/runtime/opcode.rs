@@ -2,9 +2,12 @@ use std::fmt::Display;
 
 use p3_field::Field;
 use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 
 /// An opcode specifies which operation to execute.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord, EnumIter,
+)]
 #[allow(non_camel_case_types)]
 pub enum Opcode {
     // Arithmetic instructions.
@@ -60,6 +63,12 @@ pub enum Opcode {
 
     // Miscellaneaous instructions.
     UNIMP = 39,
+
+    // Bit manipulation instructions (Zbb).
+    CLZ = 40,
+    CTZ = 41,
+    CPOP = 42,
+    REV8 = 43,
 }
 
 impl Display for Opcode {
@@ -109,6 +118,10 @@ impl Opcode {
             Opcode::REM => "rem",
             Opcode::REMU => "remu",
             Opcode::UNIMP => "unimp",
+            Opcode::CLZ => "clz",
+            Opcode::CTZ => "ctz",
+            Opcode::CPOP => "cpop",
+            Opcode::REV8 => "rev8",
         }
     }
 }
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use super::coverage::function_symbols;
+use super::{Register, Runtime};
+
+/// The largest number of frames walked per sample, which bounds the cost of a corrupted chain of
+/// frame pointers.
+const MAX_STACK_DEPTH: usize = 256;
+
+/// A sampling profiler recording the call stack of the program every `interval` cycles.
+///
+/// The call stack is recovered by walking the frame pointers of the program, which the compiler
+/// only maintains if the program is built with `-C force-frame-pointers=yes` in its rustflags.
+/// Without them, each sample only attributes its cycle to the function being executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profiler {
+    /// The number of cycles between two samples.
+    pub interval: u64,
+    /// The number of samples of each call stack, given as the pc followed by the call sites of the
+    /// frames of its callers, from the innermost to the outermost.
+    pub samples: HashMap<Vec<u32>, u64>,
+}
+
+/// The options of the [Profiler] of an execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfilerOpts {
+    /// The number of cycles between two samples.
+    pub interval: u64,
+    /// The file the folded call stacks are written to, see [Profiler::write_folded].
+    pub output: PathBuf,
+}
+
+impl Profiler {
+    pub fn new(interval: u64) -> Self {
+        assert!(interval > 0, "the sampling interval must be positive");
+        Self {
+            interval,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// The total number of samples taken.
+    pub fn num_samples(&self) -> u64 {
+        self.samples.values().sum()
+    }
+
+    /// Writes the samples in the folded format read by inferno and `flamegraph.pl`: one line per
+    /// call stack, with its functions from the outermost to the innermost separated by `;`,
+    /// followed by its number of samples.
+    ///
+    /// Addresses are symbolized with the symbol table of `elf`, the ELF the program was loaded
+    /// from. Addresses outside of any function are written in hexadecimal.
+    pub fn write_folded(&self, elf: &[u8], mut writer: impl Write) -> io::Result<()> {
+        let mut symbols = function_symbols(elf);
+        symbols.sort_by_key(|(_, address, _)| *address);
+        let symbolize = |pc: u32| {
+            let i = symbols.partition_point(|(_, address, _)| *address <= pc);
+            match i.checked_sub(1).map(|i| &symbols[i]) {
+                // Generic arguments such as `[u8; 32]` would otherwise be split into two frames.
+                Some((name, address, size)) if pc - address < *size => name.replace(';', ","),
+                _ => format!("{:#x}", pc),
+            }
+        };
+
+        // Stacks symbolized to the same functions are merged, and sorted for a stable output.
+        let mut folded = BTreeMap::<String, u64>::new();
+        for (stack, count) in self.samples.iter() {
+            let frames = stack
+                .iter()
+                .rev()
+                .map(|pc| symbolize(*pc))
+                .collect::<Vec<_>>();
+            *folded.entry(frames.join(";")).or_default() += count;
+        }
+        for (frames, count) in folded {
+            writeln!(writer, "{} {}", frames, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Runtime<'a> {
+    /// Samples the call stack of the program every `interval` cycles from now on, see [Profiler].
+    pub fn enable_profiler(&mut self, interval: u64) {
+        self.profiler = Some(Profiler::new(interval));
+    }
+
+    /// Records the call stack of the program if a sample is due at the current cycle.
+    pub(crate) fn sample_call_stack(&mut self) {
+        let Some(profiler) = self.profiler.as_ref() else {
+            return;
+        };
+        if self.state.global_clk % profiler.interval != 0 {
+            return;
+        }
+        let stack = self.call_stack();
+        let profiler = self.profiler.as_mut().unwrap();
+        *profiler.samples.entry(stack).or_default() += 1;
+    }
+
+    /// Returns the pc followed by the call sites found by walking the frame pointers.
+    ///
+    /// With frame pointers, `s0` points to the top of the frame of the current function, below
+    /// which its prologue saved the return address and the frame pointer of its caller. The walk
+    /// stops at the first return address outside of the program, which includes the null frame
+    /// pointer the program starts with, or at a frame pointer that does not grow.
+    pub fn call_stack(&self) -> Vec<u32> {
+        let pc_base = self.program.pc_base;
+        let pc_end = pc_base + 4 * self.program.instructions.len() as u32;
+        let mut stack = vec![self.state.pc];
+        let mut fp = self.register(Register::X8);
+        while stack.len() < MAX_STACK_DEPTH && fp % 4 == 0 && fp >= 8 {
+            let ra = self.word(fp - 4);
+            if ra <= pc_base || ra > pc_end {
+                break;
+            }
+            // The return address is the instruction after the call, which may be in another
+            // function if the call never returns.
+            stack.push(ra - 4);
+
+            // The stack grows downwards, so the frames of the callers are at higher addresses.
+            let caller_fp = self.word(fp - 8);
+            if caller_fp <= fp {
+                break;
+            }
+            fp = caller_fp;
+        }
+        stack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runtime::{Program, Runtime},
+        utils::{tests::FIBONACCI_IO_ELF, SP1CoreOpts},
+    };
+
+    #[test]
+    fn test_fibonacci_profile() {
+        let program = Program::from(FIBONACCI_IO_ELF);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.write_stdin(&100_000u32);
+        runtime.enable_profiler(7);
+        runtime.run().unwrap();
+        let profiler = runtime.profiler.as_ref().unwrap();
+        assert_eq!(profiler.num_samples(), runtime.state.global_clk.div_ceil(7));
+
+        let mut folded = Vec::new();
+        profiler
+            .write_folded(FIBONACCI_IO_ELF, &mut folded)
+            .unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+
+        // Every line is a call stack followed by a count, and the counts add up to the samples.
+        let mut total = 0;
+        let mut in_main = 0;
+        for line in folded.lines() {
+            let (frames, count) = line.rsplit_once(' ').unwrap();
+            let count = count.parse::<u64>().unwrap();
+            total += count;
+            if frames.split(';').last() == Some("main") {
+                in_main += count;
+            }
+        }
+        assert_eq!(total, profiler.num_samples());
+
+        // The fibonacci loop is inlined in `main`, where nearly all the cycles are spent.
+        assert!(in_main * 10 > total * 9, "{} of {} samples", in_main, total);
+    }
+}
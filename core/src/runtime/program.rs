@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-use super::Instruction;
+use super::{Instruction, MemoryLayout};
+use crate::disassembler::MemorySegment;
 
 /// A program that can be executed by the VM.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -17,4 +18,33 @@ pub struct Program {
 
     /// The initial memory image, useful for global constants.
     pub memory_image: BTreeMap<u32, u32>,
+
+    /// The segments the program was loaded from, ordered by address.
+    #[serde(default)]
+    pub(crate) segments: Vec<MemorySegment>,
+
+    /// The memory layout of the program, if it was loaded from an ELF.
+    #[serde(default)]
+    pub(crate) layout: Option<MemoryLayout>,
+}
+
+impl Program {
+    /// Returns the segments the program was loaded from, ordered by address. This is empty for
+    /// programs that were not loaded from an ELF.
+    pub fn segments(&self) -> &[MemorySegment] {
+        &self.segments
+    }
+
+    /// Returns the segment containing `addr`, if any.
+    pub fn segment(&self, addr: u32) -> Option<&MemorySegment> {
+        self.segments
+            .iter()
+            .find(|segment| segment.vaddr <= addr && (addr as u64) < segment.end())
+    }
+
+    /// Returns the memory layout of the program, or `None` for programs that were not loaded from
+    /// an ELF. It is validated when the program starts executing.
+    pub fn layout(&self) -> Option<&MemoryLayout> {
+        self.layout.as_ref()
+    }
 }
@@ -8,15 +8,17 @@ use p3_field::AbstractField;
 use serde::{Deserialize, Serialize};
 
 use super::program::Program;
-use super::Opcode;
+use super::report::PRECOMPILE_ROWS;
+use super::{ExecutionReport, Opcode};
 use crate::air::PublicValues;
 use crate::alu::AluEvent;
 use crate::bytes::event::ByteRecord;
 use crate::bytes::ByteLookupEvent;
-use crate::cpu::CpuEvent;
+use crate::cpu::CpuEventStore;
 use crate::runtime::MemoryInitializeFinalizeEvent;
 use crate::runtime::MemoryRecordEnum;
 use crate::stark::MachineRecord;
+use crate::syscall::precompiles::bls12381_fp::Bls12381FpOpEvent;
 use crate::syscall::precompiles::edwards::EdDecompressEvent;
 use crate::syscall::precompiles::keccak256::KeccakPermuteEvent;
 use crate::syscall::precompiles::sha256::{ShaCompressEvent, ShaExtendEvent};
@@ -36,7 +38,7 @@ pub struct ExecutionRecord {
     pub program: Arc<Program>,
 
     /// A trace of the CPU events which get emitted during execution.
-    pub cpu_events: Vec<CpuEvent>,
+    pub cpu_events: CpuEventStore,
 
     /// A trace of the ADD, and ADDI events.
     pub add_events: Vec<AluEvent>,
@@ -50,11 +52,8 @@ pub struct ExecutionRecord {
     /// A trace of the XOR, XORI, OR, ORI, AND, and ANDI events.
     pub bitwise_events: Vec<AluEvent>,
 
-    /// A trace of the SLL and SLLI events.
-    pub shift_left_events: Vec<AluEvent>,
-
-    /// A trace of the SRL, SRLI, SRA, and SRAI events.
-    pub shift_right_events: Vec<AluEvent>,
+    /// A trace of the SLL, SLLI, SRL, SRLI, SRA, and SRAI events.
+    pub shift_events: Vec<AluEvent>,
 
     /// A trace of the DIV, DIVU, REM, and REMU events.
     pub divrem_events: Vec<AluEvent>,
@@ -62,6 +61,9 @@ pub struct ExecutionRecord {
     /// A trace of the SLT, SLTI, SLTU, and SLTIU events.
     pub lt_events: Vec<AluEvent>,
 
+    /// A trace of the CLZ, CTZ, CPOP, and REV8 events.
+    pub bitmanip_events: Vec<AluEvent>,
+
     /// All byte lookups that are needed. The layout is shard -> (event -> count). Byte lookups are
     /// sharded to prevent the multiplicities from overflowing.
     pub byte_lookups: BTreeMap<u32, BTreeMap<ByteLookupEvent, usize>>,
@@ -80,6 +82,10 @@ pub struct ExecutionRecord {
 
     pub secp256k1_double_events: Vec<ECDoubleEvent>,
 
+    pub secp256r1_add_events: Vec<ECAddEvent>,
+
+    pub secp256r1_double_events: Vec<ECDoubleEvent>,
+
     pub bn254_add_events: Vec<ECAddEvent>,
 
     pub bn254_double_events: Vec<ECDoubleEvent>,
@@ -98,6 +104,8 @@ pub struct ExecutionRecord {
 
     pub bls12381_decompress_events: Vec<ECDecompressEvent>,
 
+    pub bls12381_fp_op_events: Vec<Bls12381FpOpEvent>,
+
     /// The public values.
     pub public_values: PublicValues<u32, u32>,
 
@@ -110,14 +118,16 @@ pub struct ShardingConfig {
     pub mul_len: usize,
     pub sub_len: usize,
     pub bitwise_len: usize,
-    pub shift_left_len: usize,
-    pub shift_right_len: usize,
+    pub shift_len: usize,
     pub divrem_len: usize,
     pub lt_len: usize,
+    pub bitmanip_len: usize,
     pub field_len: usize,
     pub keccak_len: usize,
     pub secp256k1_add_len: usize,
     pub secp256k1_double_len: usize,
+    pub secp256r1_add_len: usize,
+    pub secp256r1_double_len: usize,
     pub bn254_add_len: usize,
     pub bn254_double_len: usize,
     pub bls12381_add_len: usize,
@@ -125,29 +135,35 @@ pub struct ShardingConfig {
     pub uint256_mul_len: usize,
 }
 
-impl ShardingConfig {
-    pub const fn shard_size(&self) -> usize {
-        self.shard_size
-    }
-}
+/// The maximum number of rows of a chip trace that [ShardingConfig::auto] aims for.
+pub const AUTO_SHARD_MAX_ROWS: usize = 1 << 22;
 
-impl Default for ShardingConfig {
-    fn default() -> Self {
-        let shard_size = SP1CoreOpts::default().shard_size;
+/// The smallest shard size chosen by [ShardingConfig::auto].
+pub const AUTO_SHARD_MIN_SIZE: usize = 1 << 12;
+
+/// The fraction of [AUTO_SHARD_MAX_ROWS] kept free in the projected traces of [ShardingConfig::auto],
+/// as the densities of a report are estimates.
+const AUTO_SHARD_MARGIN: f64 = 0.125;
+
+impl ShardingConfig {
+    /// The sharding of shards of `shard_size` cycles.
+    pub fn new(shard_size: usize) -> Self {
         Self {
             shard_size,
             add_len: shard_size,
             sub_len: shard_size,
             bitwise_len: shard_size,
-            shift_left_len: shard_size,
+            shift_len: shard_size,
             divrem_len: shard_size,
             lt_len: shard_size,
+            bitmanip_len: shard_size,
             mul_len: shard_size,
-            shift_right_len: shard_size,
             field_len: shard_size * 4,
             keccak_len: shard_size,
             secp256k1_add_len: shard_size,
             secp256k1_double_len: shard_size,
+            secp256r1_add_len: shard_size,
+            secp256r1_double_len: shard_size,
             bn254_add_len: shard_size,
             bn254_double_len: shard_size,
             bls12381_add_len: shard_size,
@@ -155,6 +171,63 @@ impl Default for ShardingConfig {
             uint256_mul_len: shard_size,
         }
     }
+
+    /// The sharding with the largest shard size keeping every chip under [AUTO_SHARD_MAX_ROWS]
+    /// rows for a program with the execution `report`, see [ShardingConfig::auto_with_max_rows].
+    pub fn auto(report: &ExecutionReport) -> Self {
+        Self::new(Self::auto_shard_size(report, AUTO_SHARD_MAX_ROWS))
+    }
+
+    /// The sharding with the largest shard size keeping every chip under `max_rows` rows for a
+    /// program with the execution `report`, see [ShardingConfig::auto_shard_size].
+    pub fn auto_with_max_rows(report: &ExecutionReport, max_rows: usize) -> Self {
+        Self::new(Self::auto_shard_size(report, max_rows))
+    }
+
+    /// The largest power of two shard size such that the projected rows of every chip stay under
+    /// `max_rows`.
+    ///
+    /// The CPU chip has one row per cycle, which also bounds the events an instruction emits to
+    /// its own ALU chip. The rows of the precompile chips are projected from the peak syscall
+    /// counts of the report, so that a program whose precompile density varies over time is
+    /// sharded for its densest window, and are kept under `max_rows` with a safety margin.
+    pub fn auto_shard_size(report: &ExecutionReport, max_rows: usize) -> usize {
+        let window_cycles = report.peak_window_cycles();
+        let mut shard_size = max_rows as f64;
+        if window_cycles > 0 {
+            for (chip, syscall, rows_per_event) in PRECOMPILE_ROWS.iter().copied() {
+                let events = report
+                    .peak_syscall_counts
+                    .get(&syscall)
+                    .copied()
+                    .unwrap_or_default();
+                if events == 0 {
+                    continue;
+                }
+                let density = (events * rows_per_event) as f64 / window_cycles as f64;
+                let chip_shard_size = max_rows as f64 * (1.0 - AUTO_SHARD_MARGIN) / density;
+                if chip_shard_size < shard_size {
+                    tracing::debug!("auto sharding: {} has {:.3} rows per cycle", chip, density);
+                    shard_size = chip_shard_size;
+                }
+            }
+        }
+
+        // Round down to a power of two, as traces are padded to one anyway.
+        let shard_size = (shard_size as usize).max(1);
+        let shard_size = 1usize << shard_size.ilog2();
+        shard_size.clamp(AUTO_SHARD_MIN_SIZE.min(max_rows), max_rows)
+    }
+
+    pub const fn shard_size(&self) -> usize {
+        self.shard_size
+    }
+}
+
+impl Default for ShardingConfig {
+    fn default() -> Self {
+        Self::new(SP1CoreOpts::default().shard_size)
+    }
 }
 
 impl MachineRecord for ExecutionRecord {
@@ -175,16 +248,10 @@ impl MachineRecord for ExecutionRecord {
         stats.insert("mul_events".to_string(), self.mul_events.len());
         stats.insert("sub_events".to_string(), self.sub_events.len());
         stats.insert("bitwise_events".to_string(), self.bitwise_events.len());
-        stats.insert(
-            "shift_left_events".to_string(),
-            self.shift_left_events.len(),
-        );
-        stats.insert(
-            "shift_right_events".to_string(),
-            self.shift_right_events.len(),
-        );
+        stats.insert("shift_events".to_string(), self.shift_events.len());
         stats.insert("divrem_events".to_string(), self.divrem_events.len());
         stats.insert("lt_events".to_string(), self.lt_events.len());
+        stats.insert("bitmanip_events".to_string(), self.bitmanip_events.len());
         stats.insert(
             "sha_extend_events".to_string(),
             self.sha_extend_events.len(),
@@ -210,6 +277,14 @@ impl MachineRecord for ExecutionRecord {
             "secp256k1_double_events".to_string(),
             self.secp256k1_double_events.len(),
         );
+        stats.insert(
+            "secp256r1_add_events".to_string(),
+            self.secp256r1_add_events.len(),
+        );
+        stats.insert(
+            "secp256r1_double_events".to_string(),
+            self.secp256r1_double_events.len(),
+        );
         stats.insert("bn254_add_events".to_string(), self.bn254_add_events.len());
         stats.insert(
             "bn254_double_events".to_string(),
@@ -236,6 +311,11 @@ impl MachineRecord for ExecutionRecord {
             "bls12381_decompress_events".to_string(),
             self.bls12381_decompress_events.len(),
         );
+
+        stats.insert(
+            "bls12381_fp_op_events".to_string(),
+            self.bls12381_fp_op_events.len(),
+        );
         stats
     }
 
@@ -245,11 +325,10 @@ impl MachineRecord for ExecutionRecord {
         self.sub_events.append(&mut other.sub_events);
         self.mul_events.append(&mut other.mul_events);
         self.bitwise_events.append(&mut other.bitwise_events);
-        self.shift_left_events.append(&mut other.shift_left_events);
-        self.shift_right_events
-            .append(&mut other.shift_right_events);
+        self.shift_events.append(&mut other.shift_events);
         self.divrem_events.append(&mut other.divrem_events);
         self.lt_events.append(&mut other.lt_events);
+        self.bitmanip_events.append(&mut other.bitmanip_events);
         self.sha_extend_events.append(&mut other.sha_extend_events);
         self.sha_compress_events
             .append(&mut other.sha_compress_events);
@@ -262,6 +341,10 @@ impl MachineRecord for ExecutionRecord {
             .append(&mut other.secp256k1_add_events);
         self.secp256k1_double_events
             .append(&mut other.secp256k1_double_events);
+        self.secp256r1_add_events
+            .append(&mut other.secp256r1_add_events);
+        self.secp256r1_double_events
+            .append(&mut other.secp256r1_double_events);
         self.bn254_add_events.append(&mut other.bn254_add_events);
         self.bn254_double_events
             .append(&mut other.bn254_double_events);
@@ -275,6 +358,8 @@ impl MachineRecord for ExecutionRecord {
             .append(&mut other.uint256_mul_events);
         self.bls12381_decompress_events
             .append(&mut other.bls12381_decompress_events);
+        self.bls12381_fp_op_events
+            .append(&mut other.bls12381_fp_op_events);
 
         // Merge the byte lookups.
         for (shard, events_map) in std::mem::take(&mut other.byte_lookups).into_iter() {
@@ -307,7 +392,7 @@ impl MachineRecord for ExecutionRecord {
 
         // Iterate throught he CPU events and fill in the shards.
         let mut start_idx = 0;
-        let mut current_shard = self.cpu_events[0].shard;
+        let mut current_shard = self.cpu_events.first().unwrap().shard;
         for (i, cpu_event) in self.cpu_events.iter().enumerate() {
             let at_last_event = i == num_cpu_events - 1;
             if cpu_event.shard != current_shard || at_last_event {
@@ -316,7 +401,7 @@ impl MachineRecord for ExecutionRecord {
                 // Fill in the shard.
                 let mut shard = ExecutionRecord::default();
                 shard.index = current_shard;
-                shard.cpu_events = self.cpu_events[start_idx..last_idx].to_vec();
+                shard.cpu_events = self.cpu_events.slice(start_idx..last_idx);
                 shard.program = self.program.clone();
 
                 // Byte lookups are already sharded, so put this shard's lookups in.
@@ -334,10 +419,13 @@ impl MachineRecord for ExecutionRecord {
                 // for all of the shards.
                 shard.public_values.committed_value_digest =
                     self.public_values.committed_value_digest;
+                shard.public_values.input_digest = self.public_values.input_digest;
+                shard.public_values.config_digest = self.public_values.config_digest;
+                shard.public_values.features = self.public_values.features;
                 shard.public_values.deferred_proofs_digest =
                     self.public_values.deferred_proofs_digest;
                 shard.public_values.shard = current_shard;
-                shard.public_values.start_pc = shard.cpu_events[0].pc;
+                shard.public_values.start_pc = shard.cpu_events.first().unwrap().pc;
                 shard.public_values.next_pc = last_shard_cpu_event.next_pc;
                 shard.public_values.exit_code = last_shard_cpu_event.exit_code;
                 shards.push(shard);
@@ -394,26 +482,13 @@ impl MachineRecord for ExecutionRecord {
             }
         }
 
-        // Shard the shift left events.
-        for (shift_left_chunk, shard) in take(&mut self.shift_left_events)
-            .chunks_mut(config.shift_left_len)
+        // Shard the shift events.
+        for (shift_chunk, shard) in take(&mut self.shift_events)
+            .chunks_mut(config.shift_len)
             .zip(shards.iter_mut())
         {
-            shard.shift_left_events.extend_from_slice(shift_left_chunk);
-            for (i, event) in shift_left_chunk.iter().enumerate() {
-                self.nonce_lookup.insert(event.lookup_id, i as u32);
-            }
-        }
-
-        // Shard the shift right events.
-        for (shift_right_chunk, shard) in take(&mut self.shift_right_events)
-            .chunks_mut(config.shift_right_len)
-            .zip(shards.iter_mut())
-        {
-            shard
-                .shift_right_events
-                .extend_from_slice(shift_right_chunk);
-            for (i, event) in shift_right_chunk.iter().enumerate() {
+            shard.shift_events.extend_from_slice(shift_chunk);
+            for (i, event) in shift_chunk.iter().enumerate() {
                 self.nonce_lookup.insert(event.lookup_id, i as u32);
             }
         }
@@ -440,6 +515,17 @@ impl MachineRecord for ExecutionRecord {
             }
         }
 
+        // Shard the bit manipulation events.
+        for (bitmanip_chunk, shard) in take(&mut self.bitmanip_events)
+            .chunks_mut(config.bitmanip_len)
+            .zip(shards.iter_mut())
+        {
+            shard.bitmanip_events.extend_from_slice(bitmanip_chunk);
+            for (i, event) in bitmanip_chunk.iter().enumerate() {
+                self.nonce_lookup.insert(event.lookup_id, i as u32);
+            }
+        }
+
         // Keccak-256 permute events.
         for (keccak_chunk, shard) in take(&mut self.keccak_permute_events)
             .chunks_mut(config.keccak_len)
@@ -477,6 +563,32 @@ impl MachineRecord for ExecutionRecord {
             }
         }
 
+        // secp256r1 curve add events.
+        for (secp256r1_add_chunk, shard) in take(&mut self.secp256r1_add_events)
+            .chunks_mut(config.secp256r1_add_len)
+            .zip(shards.iter_mut())
+        {
+            shard
+                .secp256r1_add_events
+                .extend_from_slice(secp256r1_add_chunk);
+            for (i, event) in secp256r1_add_chunk.iter().enumerate() {
+                self.nonce_lookup.insert(event.lookup_id, i as u32);
+            }
+        }
+
+        // secp256r1 curve double events.
+        for (secp256r1_double_chunk, shard) in take(&mut self.secp256r1_double_events)
+            .chunks_mut(config.secp256r1_double_len)
+            .zip(shards.iter_mut())
+        {
+            shard
+                .secp256r1_double_events
+                .extend_from_slice(secp256r1_double_chunk);
+            for (i, event) in secp256r1_double_chunk.iter().enumerate() {
+                self.nonce_lookup.insert(event.lookup_id, i as u32);
+            }
+        }
+
         // bn254 curve add events.
         for (bn254_add_chunk, shard) in take(&mut self.bn254_add_events)
             .chunks_mut(config.bn254_add_len)
@@ -572,6 +684,12 @@ impl MachineRecord for ExecutionRecord {
             self.nonce_lookup.insert(event.lookup_id, i as u32);
         }
 
+        // Bls12-381 base field arithmetic events.
+        first.bls12381_fp_op_events = std::mem::take(&mut self.bls12381_fp_op_events);
+        for (i, event) in first.bls12381_fp_op_events.iter().enumerate() {
+            self.nonce_lookup.insert(event.lookup_id, i as u32);
+        }
+
         // Put MemoryInit / MemoryFinalize events in the last shard.
         let last = shards.last_mut().unwrap();
         last.memory_initialize_events
@@ -611,6 +729,34 @@ impl ExecutionRecord {
         self.lt_events.push(lt_event);
     }
 
+    /// The ALU events, of all opcodes.
+    pub fn alu_events(&self) -> impl Iterator<Item = &AluEvent> {
+        self.add_events
+            .iter()
+            .chain(&self.sub_events)
+            .chain(&self.mul_events)
+            .chain(&self.bitwise_events)
+            .chain(&self.shift_events)
+            .chain(&self.divrem_events)
+            .chain(&self.lt_events)
+            .chain(&self.bitmanip_events)
+    }
+
+    /// The ALU events of `opcode`.
+    pub fn alu_events_of(&self, opcode: Opcode) -> impl Iterator<Item = &AluEvent> {
+        self.alu_events()
+            .filter(move |event| event.opcode == opcode)
+    }
+
+    /// The loads and stores of the CPU, with the address they access.
+    pub fn memory_accesses(&self) -> impl Iterator<Item = (u32, MemoryRecordEnum)> + '_ {
+        self.cpu_events.iter().filter_map(|event| {
+            event
+                .memory_record
+                .map(|record| (event.b.wrapping_add(event.c), record))
+        })
+    }
+
     pub fn add_alu_events(&mut self, alu_events: HashMap<Opcode, Vec<AluEvent>>) {
         let keys = alu_events.keys().sorted();
         for opcode in keys {
@@ -627,17 +773,15 @@ impl ExecutionRecord {
                 Opcode::XOR | Opcode::OR | Opcode::AND => {
                     self.bitwise_events.extend_from_slice(&alu_events[opcode]);
                 }
-                Opcode::SLL => {
-                    self.shift_left_events
-                        .extend_from_slice(&alu_events[opcode]);
-                }
-                Opcode::SRL | Opcode::SRA => {
-                    self.shift_right_events
-                        .extend_from_slice(&alu_events[opcode]);
+                Opcode::SLL | Opcode::SRL | Opcode::SRA => {
+                    self.shift_events.extend_from_slice(&alu_events[opcode]);
                 }
                 Opcode::SLT | Opcode::SLTU => {
                     self.lt_events.extend_from_slice(&alu_events[opcode]);
                 }
+                Opcode::CLZ | Opcode::CTZ | Opcode::CPOP | Opcode::REV8 => {
+                    self.bitmanip_events.extend_from_slice(&alu_events[opcode]);
+                }
                 _ => {
                     panic!("Invalid opcode: {:?}", opcode);
                 }
@@ -664,3 +808,86 @@ pub struct MemoryAccessRecord {
     pub c: Option<MemoryRecordEnum>,
     pub memory: Option<MemoryRecordEnum>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::SyscallCode;
+
+    /// A report of `windows` full windows of instructions, where the cycles for which `is_keccak`
+    /// holds make a keccak permute and the others are ADD instructions.
+    fn synthetic_report(windows: u64, is_keccak: impl Fn(u64) -> bool) -> ExecutionReport {
+        let mut report = ExecutionReport::default();
+        for cycle in 0..windows * REPORT_WINDOW_CYCLES {
+            if is_keccak(cycle) {
+                report.record_opcode(Opcode::ECALL, 0);
+                report.record_syscall(SyscallCode::KECCAK_PERMUTE, 0);
+            } else {
+                report.record_opcode(Opcode::ADD, 0);
+            }
+        }
+        report
+    }
+
+    #[test]
+    fn test_auto_shard_size_keccak_density() {
+        let cpu_only = synthetic_report(4, |_| false);
+        assert_eq!(
+            ShardingConfig::auto(&cpu_only).shard_size(),
+            AUTO_SHARD_MAX_ROWS
+        );
+
+        // A keccak permute every 16 cycles takes 1.5 rows per cycle, so the keccak trace of a
+        // shard as large as the CPU-only one would be too tall.
+        let keccak = synthetic_report(4, |cycle| cycle % 16 == 0);
+        assert_eq!(
+            ShardingConfig::auto(&keccak).shard_size(),
+            AUTO_SHARD_MAX_ROWS / 2
+        );
+
+        // A keccak permute every 4 cycles takes 6 rows per cycle.
+        let keccak = synthetic_report(4, |cycle| cycle % 4 == 0);
+        assert_eq!(
+            ShardingConfig::auto(&keccak).shard_size(),
+            AUTO_SHARD_MAX_ROWS / 8
+        );
+        assert_eq!(
+            ShardingConfig::auto_with_max_rows(&keccak, 1 << 14).shard_size(),
+            AUTO_SHARD_MIN_SIZE
+        );
+    }
+
+    #[test]
+    fn test_auto_shard_size_uses_peak_window() {
+        // The same number of keccak permutes, spread over 16 windows or all in the eighth one.
+        let spread = synthetic_report(16, |cycle| cycle % 256 == 0);
+        let burst = synthetic_report(16, |cycle| {
+            cycle / REPORT_WINDOW_CYCLES == 7 && cycle % 16 == 0
+        });
+        assert_eq!(
+            spread.syscall_counts[&SyscallCode::KECCAK_PERMUTE],
+            burst.syscall_counts[&SyscallCode::KECCAK_PERMUTE]
+        );
+        assert_eq!(
+            burst.peak_syscall_counts[&SyscallCode::KECCAK_PERMUTE],
+            REPORT_WINDOW_CYCLES / 16
+        );
+        assert_eq!(
+            ShardingConfig::auto(&spread).shard_size(),
+            AUTO_SHARD_MAX_ROWS
+        );
+        assert_eq!(
+            ShardingConfig::auto(&burst).shard_size(),
+            AUTO_SHARD_MAX_ROWS / 2
+        );
+
+        // The peaks of reports of consecutive checkpoints are kept when they are added up.
+        let mut aggregate = synthetic_report(2, |_| false);
+        aggregate += burst.clone();
+        assert_eq!(aggregate.peak_syscall_counts, burst.peak_syscall_counts);
+        assert_eq!(
+            ShardingConfig::auto(&aggregate).shard_size(),
+            AUTO_SHARD_MAX_ROWS / 2
+        );
+    }
+}
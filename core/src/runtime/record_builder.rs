@@ -0,0 +1,178 @@
+use std::ops::Range;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{ExecutionRecord, Instruction, Opcode, Program, Runtime, SyscallCode};
+use crate::utils::SP1CoreOpts;
+
+/// The address of the state permuted by the keccak invocations of a [RecordBuilder].
+const KECCAK_STATE_PTR: u32 = 0x0010_0000;
+
+/// The register the values of the ALU operations and memory loads are written to.
+const RESULT_REGISTER: u32 = 31;
+
+/// The register stored by the memory stores.
+const STORE_REGISTER: u32 = 29;
+
+/// A builder of synthetic execution records, with the given numbers of events of each kind, for
+/// benchmarking chips on realistic event distributions.
+///
+/// The builder assembles a program making the requested operations, in the order they were added,
+/// with random operands, and executes it. The derived bookkeeping of the record, such as the CPU
+/// events, the lookup ids and the shard indices, is thus consistent by construction, and the events
+/// of the dependencies of the chips, such as the byte lookups, are added by
+/// [crate::stark::StarkMachine::shard]. The record has no public values.
+///
+/// ```ignore
+/// let builder = RecordBuilder::new(0)
+///     .alu(Opcode::ADD, 1000)
+///     .memory(0x1000..0x2000, 500)
+///     .keccak(10);
+/// let record = builder.build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordBuilder {
+    rng: StdRng,
+    instructions: Vec<Instruction>,
+}
+
+impl RecordBuilder {
+    /// Creates a builder whose random operands are drawn from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Adds `n` ALU operations of `opcode` on random operands.
+    pub fn alu(mut self, opcode: Opcode, n: usize) -> Self {
+        for _ in 0..n {
+            let (b, c) = (self.rng.gen(), self.rng.gen());
+            let instruction = Instruction::new(opcode, RESULT_REGISTER, b, c, true, true);
+            assert!(
+                instruction.is_alu_instruction(),
+                "{:?} is not an ALU opcode",
+                opcode
+            );
+            self.instructions.push(instruction);
+        }
+        self
+    }
+
+    /// Adds `n` memory accesses at random word addresses in `range`, alternating stores and loads.
+    /// The range must not overlap the registers, which are the first 32 addresses.
+    pub fn memory(mut self, range: Range<u32>, n: usize) -> Self {
+        assert!(range.start >= 32, "the range overlaps the registers");
+        for i in 0..n {
+            let addr = self.rng.gen_range(range.clone()) & !3;
+            let instruction = if i % 2 == 0 {
+                Instruction::new(Opcode::SW, STORE_REGISTER, 0, addr, false, true)
+            } else {
+                Instruction::new(Opcode::LW, RESULT_REGISTER, 0, addr, false, true)
+            };
+            self.instructions.push(instruction);
+        }
+        self
+    }
+
+    /// Adds `n` invocations of the keccak permutation, all on the same state.
+    pub fn keccak(mut self, n: usize) -> Self {
+        for _ in 0..n {
+            self.instructions.extend([
+                Instruction::new(
+                    Opcode::ADD,
+                    5,
+                    0,
+                    SyscallCode::KECCAK_PERMUTE as u32,
+                    false,
+                    true,
+                ),
+                Instruction::new(Opcode::ADD, 10, 0, KECCAK_STATE_PTR, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        self
+    }
+
+    /// The program making the operations added so far.
+    pub fn program(&self) -> Program {
+        Program::new(self.instructions.clone(), 0, 0)
+    }
+
+    /// Executes the program and returns its record, which fits in a single shard unless it has
+    /// more cycles than the default shard size.
+    pub fn build(&self) -> ExecutionRecord {
+        let mut runtime = Runtime::new(self.program(), SP1CoreOpts::default());
+        runtime.run().expect("the synthetic program failed");
+        runtime.record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stark::{MachineRecord, RiscvAir},
+        utils::{run_test_machine, setup_logger, BabyBearPoseidon2},
+    };
+
+    #[test]
+    fn test_record_builder_events() {
+        let record = RecordBuilder::new(0)
+            .alu(Opcode::DIVU, 10)
+            .alu(Opcode::XOR, 5)
+            .memory(0x1000..0x2000, 6)
+            .keccak(2)
+            .build();
+
+        assert_eq!(record.alu_events_of(Opcode::DIVU).count(), 10);
+        assert_eq!(record.alu_events_of(Opcode::XOR).count(), 5);
+        assert_eq!(record.memory_accesses().count(), 6);
+        assert!(record
+            .memory_accesses()
+            .all(|(addr, _)| (0x1000..0x2000).contains(&addr) && addr % 4 == 0));
+        assert_eq!(record.keccak_permute_events.len(), 2);
+        assert_eq!(record.cpu_events.len(), 10 + 5 + 6 + 2 * 3);
+
+        // The builder is deterministic.
+        let other = RecordBuilder::new(0)
+            .alu(Opcode::DIVU, 10)
+            .alu(Opcode::XOR, 5)
+            .memory(0x1000..0x2000, 6)
+            .keccak(2)
+            .build();
+        assert_eq!(record.stats(), other.stats());
+        let operands = |record: &ExecutionRecord| {
+            record
+                .alu_events()
+                .map(|event| (event.b, event.c))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(operands(&record), operands(&other));
+    }
+
+    #[test]
+    fn test_record_builder_proves_in_one_shard() {
+        setup_logger();
+        let builder = RecordBuilder::new(1)
+            .alu(Opcode::ADD, 20)
+            .alu(Opcode::MULHSU, 20)
+            .alu(Opcode::REM, 20)
+            .alu(Opcode::SRA, 20)
+            .alu(Opcode::SLTU, 20)
+            .memory(0x2000..0x3000, 40)
+            .keccak(1);
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let (pk, vk) = machine.setup(&builder.program());
+        let proof = run_test_machine(builder.build(), machine, pk, vk).unwrap();
+        assert_eq!(proof.shard_proofs.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not an ALU opcode")]
+    fn test_record_builder_rejects_non_alu_opcode() {
+        let _ = RecordBuilder::new(0).alu(Opcode::LW, 1);
+    }
+}
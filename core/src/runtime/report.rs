@@ -3,16 +3,123 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::hash::Hash;
 use std::ops::{Add, AddAssign};
+use std::path::PathBuf;
 
 use super::*;
 
+/// The number of instructions in the windows over which the peak counts of an [ExecutionReport]
+/// are measured.
+pub const REPORT_WINDOW_CYCLES: u64 = 1 << 16;
+
+/// The precompile chips, with the syscall emitting their events and the rows each event takes.
+pub(crate) const PRECOMPILE_ROWS: &[(&str, SyscallCode, u64)] = &[
+    ("KeccakPermute", SyscallCode::KECCAK_PERMUTE, 24),
+    ("ShaExtend", SyscallCode::SHA_EXTEND, 48),
+    ("ShaCompress", SyscallCode::SHA_COMPRESS, 80),
+    ("EdAddAssign", SyscallCode::ED_ADD, 1),
+    ("EdDecompress", SyscallCode::ED_DECOMPRESS, 1),
+    ("Secp256k1AddAssign", SyscallCode::SECP256K1_ADD, 1),
+    ("Secp256k1DoubleAssign", SyscallCode::SECP256K1_DOUBLE, 1),
+    ("Secp256k1Decompress", SyscallCode::SECP256K1_DECOMPRESS, 1),
+    ("Secp256r1AddAssign", SyscallCode::SECP256R1_ADD, 1),
+    ("Secp256r1DoubleAssign", SyscallCode::SECP256R1_DOUBLE, 1),
+    ("Bn254AddAssign", SyscallCode::BN254_ADD, 1),
+    ("Bn254DoubleAssign", SyscallCode::BN254_DOUBLE, 1),
+    ("Bls12381AddAssign", SyscallCode::BLS12381_ADD, 1),
+    ("Bls12381DoubleAssign", SyscallCode::BLS12381_DOUBLE, 1),
+    ("Bls12381Decompress", SyscallCode::BLS12381_DECOMPRESS, 1),
+    ("Uint256MulMod", SyscallCode::UINT256_MUL, 1),
+    // A KZG point evaluation of `sp1_precompiles::kzg` takes about 164k of these rows, 184,687 at
+    // most for the heaviest evaluation point and claimed value, and as many `ecall` cycles.
+    ("Bls12381FpOp", SyscallCode::BLS12381_FP_ADD, 1),
+    ("Bls12381FpOp", SyscallCode::BLS12381_FP_SUB, 1),
+    ("Bls12381FpOp", SyscallCode::BLS12381_FP_MUL, 1),
+    ("Bls12381FpOp", SyscallCode::BLS12381_FP_DIV, 1),
+];
+
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct ExecutionReport {
     pub opcode_counts: HashMap<Opcode, u64>,
     pub syscall_counts: HashMap<SyscallCode, u64>,
+    /// The largest number of times each opcode was executed within a window of
+    /// [REPORT_WINDOW_CYCLES] consecutive instructions.
+    pub peak_opcode_counts: HashMap<Opcode, u64>,
+    /// The largest number of times each syscall was made within a window of
+    /// [REPORT_WINDOW_CYCLES] consecutive instructions.
+    pub peak_syscall_counts: HashMap<SyscallCode, u64>,
+    /// The cycles spent in each `cycle-tracker` span, summed over the times it was entered.
+    pub span_cycles: HashMap<String, u64>,
+    /// The file the sampled call stacks were written to, if the execution was profiled.
+    pub profile_path: Option<PathBuf>,
+    /// The gas used by the execution, see [GasSchedule].
+    pub gas_used: u64,
+    /// The digest of the [GasSchedule] the gas was metered with.
+    pub gas_schedule_digest: Option<GasScheduleDigest>,
+    /// The memory layout of the program, see [Program::layout].
+    pub layout: Option<MemoryLayout>,
+    window: ReportWindow,
+}
+
+/// The counts of the current window of an [ExecutionReport].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct ReportWindow {
+    cycles: u64,
+    opcode_counts: HashMap<Opcode, u64>,
+    syscall_counts: HashMap<SyscallCode, u64>,
 }
 
 impl ExecutionReport {
+    /// Records the execution of an instruction using `gas`, starting a new window if the current
+    /// one is full.
+    pub(crate) fn record_opcode(&mut self, opcode: Opcode, gas: u64) {
+        if self.window.cycles == REPORT_WINDOW_CYCLES {
+            self.window = ReportWindow::default();
+        }
+        self.window.cycles += 1;
+        self.gas_used += gas;
+        *self.opcode_counts.entry(opcode).or_default() += 1;
+        record_peak(
+            &mut self.window.opcode_counts,
+            &mut self.peak_opcode_counts,
+            opcode,
+        );
+    }
+
+    /// Records a syscall made by the last recorded instruction, using `gas` on top of it.
+    pub(crate) fn record_syscall(&mut self, syscall: SyscallCode, gas: u64) {
+        self.gas_used += gas;
+        *self.syscall_counts.entry(syscall).or_default() += 1;
+        record_peak(
+            &mut self.window.syscall_counts,
+            &mut self.peak_syscall_counts,
+            syscall,
+        );
+    }
+
+    /// The bitmap of the features a proof of the execution is generated with, see
+    /// [crate::features].
+    pub fn features(&self) -> u32 {
+        let called = |syscall| {
+            self.syscall_counts
+                .get(&syscall)
+                .is_some_and(|count| *count > 0)
+        };
+        let mut features = 0;
+        if called(SyscallCode::COMMIT_INPUT) {
+            features |= crate::features::INPUT_DIGEST_FEATURE;
+        }
+        if called(SyscallCode::COMMIT_CONFIG) {
+            features |= crate::features::CONFIG_DIGEST_FEATURE;
+        }
+        features
+    }
+
+    /// The number of instructions the peak counts were measured over: a full window, or the whole
+    /// execution if it is shorter than a window.
+    pub fn peak_window_cycles(&self) -> u64 {
+        self.total_instruction_count().min(REPORT_WINDOW_CYCLES)
+    }
+
     /// Compute the total number of instructions run during the execution.
     pub fn total_instruction_count(&self) -> u64 {
         self.opcode_counts.values().sum()
@@ -23,6 +130,20 @@ impl ExecutionReport {
         self.syscall_counts.values().sum()
     }
 
+    /// Compute the number of rows taken by the precompile events in the precompile traces.
+    pub fn total_precompile_rows(&self) -> u64 {
+        PRECOMPILE_ROWS
+            .iter()
+            .map(|(_, syscall, rows_per_event)| {
+                self.syscall_counts
+                    .get(syscall)
+                    .copied()
+                    .unwrap_or_default()
+                    * rows_per_event
+            })
+            .sum()
+    }
+
     /// Returns sorted and formatted rows of a table of counts (e.g. `opcode_counts`).
     ///
     /// The table is sorted first by count (descending) and then by label (ascending).
@@ -57,6 +178,18 @@ impl ExecutionReport {
     }
 }
 
+/// Increments the count of `key` in the current window and raises its peak count accordingly.
+fn record_peak<K: Eq + Hash + Copy>(
+    window: &mut HashMap<K, u64>,
+    peaks: &mut HashMap<K, u64>,
+    key: K,
+) {
+    let count = window.entry(key).or_default();
+    *count += 1;
+    let peak = peaks.entry(key).or_default();
+    *peak = (*peak).max(*count);
+}
+
 /// Combines two `HashMap`s together. If a key is in both maps, the values are added together.
 fn hashmap_add_assign<K, V>(lhs: &mut HashMap<K, V>, rhs: HashMap<K, V>)
 where
@@ -72,10 +205,39 @@ where
     }
 }
 
+/// Combines two `HashMap`s together, keeping the largest value of keys in both maps.
+fn hashmap_max_assign<K, V>(lhs: &mut HashMap<K, V>, rhs: HashMap<K, V>)
+where
+    K: Eq + Hash,
+    V: Ord + Default,
+{
+    for (k, v) in rhs.into_iter() {
+        let e = lhs.entry(k).or_default();
+        if v > *e {
+            *e = v;
+        }
+    }
+}
+
 impl AddAssign for ExecutionReport {
+    /// Adds the counts of `rhs`. The peak counts are the largest of both reports, as the windows
+    /// of two reports are never merged.
     fn add_assign(&mut self, rhs: Self) {
         hashmap_add_assign(&mut self.opcode_counts, rhs.opcode_counts);
         hashmap_add_assign(&mut self.syscall_counts, rhs.syscall_counts);
+        hashmap_max_assign(&mut self.peak_opcode_counts, rhs.peak_opcode_counts);
+        hashmap_max_assign(&mut self.peak_syscall_counts, rhs.peak_syscall_counts);
+        hashmap_add_assign(&mut self.span_cycles, rhs.span_cycles);
+        if self.profile_path.is_none() {
+            self.profile_path = rhs.profile_path;
+        }
+        self.gas_used += rhs.gas_used;
+        if self.gas_schedule_digest.is_none() {
+            self.gas_schedule_digest = rhs.gas_schedule_digest;
+        }
+        if self.layout.is_none() {
+            self.layout = rhs.layout;
+        }
     }
 }
 
@@ -108,6 +270,21 @@ impl Display for ExecutionReport {
             writeln!(f, "  {line}")?;
         }
 
+        write!(f, "gas used: {}", self.gas_used)?;
+        if let Some(digest) = &self.gas_schedule_digest {
+            write!(f, " (schedule {})", digest)?;
+        }
+        writeln!(f)?;
+
+        if let Some(path) = &self.profile_path {
+            writeln!(f, "profile: {}", path.display())?;
+        }
+
+        if let Some(layout) = &self.layout {
+            writeln!(f, "memory layout:")?;
+            write!(f, "{}", layout)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,439 @@
+//! Copy-on-write snapshots of an execution at the start of each of its shards.
+//!
+//! Proving executes a program twice: once to find its shards and its public values, and once more
+//! to record the events of each shard. With snapshots, the first execution captures the state at
+//! the start of each shard, and the second one executes the shards from their snapshots, in
+//! parallel, with [Runtime::from_snapshot].
+//!
+//! The memory of a snapshot is split into pages of [PAGE_SIZE] bytes, shared with the previous
+//! snapshot unless the shard in between touched them. Reading a word touches its page too, since
+//! the record of the word keeps the time of its last access. The input streams only grow during an
+//! execution, so the snapshots share their final contents and only keep the length of the input
+//! stream, which hooks append to. The other cursors, the clocks and the registers are copied.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use nohash_hasher::BuildNoHashHasher;
+
+use super::{ExecutionError, ExecutionState, MemoryRecord, Program, Runtime};
+use crate::stark::{ShardProof, StarkVerifyingKey};
+use crate::utils::{BabyBearPoseidon2, SP1CoreOpts};
+
+/// The base 2 logarithm of [PAGE_SIZE].
+pub const LOG_PAGE_SIZE: u32 = 12;
+
+/// The number of bytes of memory in a page of a snapshot.
+pub const PAGE_SIZE: u32 = 1 << LOG_PAGE_SIZE;
+
+/// The number of pages of the address space.
+const NUM_PAGES: usize = 1 << (32 - LOG_PAGE_SIZE);
+
+/// The pages touched since the last snapshot, one bit per page.
+#[derive(Debug, Clone)]
+struct DirtyPages(Vec<u64>);
+
+impl DirtyPages {
+    fn new() -> Self {
+        Self(vec![0; NUM_PAGES / 64])
+    }
+
+    #[inline]
+    fn mark(&mut self, addr: u32) {
+        let page = (addr >> LOG_PAGE_SIZE) as usize;
+        self.0[page / 64] |= 1 << (page % 64);
+    }
+
+    /// Clears the touched pages, returning them in increasing order.
+    fn drain(&mut self) -> Vec<u32> {
+        let mut pages = Vec::new();
+        for (i, bits) in self.0.iter_mut().enumerate() {
+            let mut bits = std::mem::take(bits);
+            while bits != 0 {
+                pages.push((i * 64) as u32 + bits.trailing_zeros());
+                bits &= bits - 1;
+            }
+        }
+        pages
+    }
+}
+
+/// The addresses of the words of a page. The registers are the words at the addresses 0 to 31,
+/// and the other words are aligned.
+fn page_addrs(page: u32) -> impl Iterator<Item = u32> {
+    let start = page << LOG_PAGE_SIZE;
+    let registers = if page == 0 { 0..32 } else { 0..0 };
+    registers.chain(
+        (0..PAGE_SIZE / 4)
+            .map(move |i| start + 4 * i)
+            .filter(|addr| *addr >= 32),
+    )
+}
+
+/// A map from addresses to values split into pages, shared between the snapshots that didn't
+/// touch them.
+#[derive(Debug, Clone)]
+pub struct PagedMemory<V> {
+    pages: BTreeMap<u32, Arc<Vec<(u32, V)>>>,
+}
+
+impl<V> Default for PagedMemory<V> {
+    fn default() -> Self {
+        Self {
+            pages: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V: Copy> PagedMemory<V> {
+    /// A copy of `self` with the `pages` taken from `memory`, sharing the other pages.
+    fn update(&self, memory: &HashMap<u32, V, BuildNoHashHasher<u32>>, pages: &[u32]) -> Self {
+        let mut next = self.clone();
+        for page in pages.iter().copied() {
+            let words = page_addrs(page)
+                .filter_map(|addr| memory.get(&addr).map(|value| (addr, *value)))
+                .collect::<Vec<_>>();
+            if words.is_empty() {
+                next.pages.remove(&page);
+            } else {
+                next.pages.insert(page, Arc::new(words));
+            }
+        }
+        next
+    }
+
+    /// The map of all the words of the pages.
+    fn to_map(&self) -> HashMap<u32, V, BuildNoHashHasher<u32>> {
+        let len = self.pages.values().map(|words| words.len()).sum();
+        let mut map = HashMap::with_capacity_and_hasher(len, BuildNoHashHasher::default());
+        for words in self.pages.values() {
+            map.extend(words.iter().copied());
+        }
+        map
+    }
+
+    /// The number of pages holding at least a word.
+    pub fn num_pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The number of pages of `self` that are the same allocation as in `other`.
+    pub fn num_shared_pages(&self, other: &Self) -> usize {
+        self.pages
+            .iter()
+            .filter(|(page, words)| {
+                other
+                    .pages
+                    .get(page)
+                    .is_some_and(|other| Arc::ptr_eq(words, other))
+            })
+            .count()
+    }
+}
+
+/// The state of an execution at the start of a shard, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct ShardSnapshot {
+    /// The state, without its memory and its input streams.
+    state: ExecutionState,
+    pub memory: PagedMemory<MemoryRecord>,
+    pub uninitialized_memory: PagedMemory<u32>,
+    /// The number of buffers of the input stream.
+    input_stream_len: usize,
+    /// The number of syscalls in the effect log of the execution, if it is enabled.
+    pub effects_start: usize,
+}
+
+impl ShardSnapshot {
+    /// The index of the shard.
+    pub fn shard(&self) -> u32 {
+        self.state.current_shard
+    }
+}
+
+/// The input streams of an execution, at its end.
+#[derive(Debug, Clone, Default)]
+struct Streams {
+    input_stream: Vec<Vec<u8>>,
+    config_stream: Vec<u8>,
+    entropy_stream: Vec<u8>,
+    proof_stream: Vec<(
+        ShardProof<BabyBearPoseidon2>,
+        StarkVerifyingKey<BabyBearPoseidon2>,
+    )>,
+}
+
+/// The snapshots of the shards of an execution, see [Runtime::enable_snapshots].
+#[derive(Debug, Clone, Default)]
+pub struct Snapshots {
+    /// The snapshots, one per shard in order.
+    pub shards: Vec<ShardSnapshot>,
+    streams: Arc<Streams>,
+}
+
+impl Snapshots {
+    /// The state of the execution at the start of the `index`-th shard.
+    pub fn state(&self, index: usize) -> ExecutionState {
+        let snapshot = &self.shards[index];
+        ExecutionState {
+            memory: snapshot.memory.to_map(),
+            uninitialized_memory: snapshot.uninitialized_memory.to_map(),
+            input_stream: self.streams.input_stream[..snapshot.input_stream_len].to_vec(),
+            config_stream: self.streams.config_stream.clone(),
+            entropy_stream: self.streams.entropy_stream.clone(),
+            proof_stream: self.streams.proof_stream.clone(),
+            ..snapshot.state.clone()
+        }
+    }
+
+    /// The range of the effect log of the execution made by the `index`-th shard, given the number
+    /// of effects of the whole execution.
+    pub fn effects_range(&self, index: usize, num_effects: usize) -> std::ops::Range<usize> {
+        let end = self
+            .shards
+            .get(index + 1)
+            .map_or(num_effects, |next| next.effects_start);
+        self.shards[index].effects_start..end
+    }
+}
+
+/// Captures the snapshots of an execution.
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotRecorder {
+    dirty: DirtyPages,
+    dirty_uninitialized: DirtyPages,
+    memory: PagedMemory<MemoryRecord>,
+    uninitialized_memory: PagedMemory<u32>,
+    shards: Vec<ShardSnapshot>,
+}
+
+impl SnapshotRecorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            dirty: DirtyPages::new(),
+            dirty_uninitialized: DirtyPages::new(),
+            memory: PagedMemory::default(),
+            uninitialized_memory: PagedMemory::default(),
+            shards: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn touch(&mut self, addr: u32) {
+        self.dirty.mark(addr);
+    }
+
+    #[inline]
+    pub(crate) fn touch_uninitialized(&mut self, addr: u32) {
+        self.dirty_uninitialized.mark(addr);
+    }
+
+    /// Captures the snapshot of `state`, at the start of a shard.
+    pub(crate) fn capture(&mut self, state: &ExecutionState, effects_start: usize) {
+        self.memory = self.memory.update(&state.memory, &self.dirty.drain());
+        self.uninitialized_memory = self.uninitialized_memory.update(
+            &state.uninitialized_memory,
+            &self.dirty_uninitialized.drain(),
+        );
+        self.shards.push(ShardSnapshot {
+            state: ExecutionState {
+                global_clk: state.global_clk,
+                current_shard: state.current_shard,
+                clk: state.clk,
+                channel: state.channel,
+                pc: state.pc,
+                memory: HashMap::default(),
+                uninitialized_memory: HashMap::default(),
+                input_stream: Vec::new(),
+                input_stream_ptr: state.input_stream_ptr,
+                config_stream: Vec::new(),
+                entropy_stream: Vec::new(),
+                entropy_stream_ptr: state.entropy_stream_ptr,
+                proof_stream: Vec::new(),
+                proof_stream_ptr: state.proof_stream_ptr,
+                public_values_stream: state.public_values_stream.clone(),
+                public_values_stream_ptr: state.public_values_stream_ptr,
+                output_chunks: state.output_chunks.clone(),
+                kv_entries: state.kv_entries.clone(),
+                segment_state: state.segment_state.clone(),
+                gas_used: state.gas_used,
+            },
+            memory: self.memory.clone(),
+            uninitialized_memory: self.uninitialized_memory.clone(),
+            input_stream_len: state.input_stream.len(),
+            effects_start,
+        });
+    }
+
+    /// The snapshots captured, sharing the streams of the final `state`.
+    pub(crate) fn finish(self, state: &ExecutionState) -> Snapshots {
+        Snapshots {
+            shards: self.shards,
+            streams: Arc::new(Streams {
+                input_stream: state.input_stream.clone(),
+                config_stream: state.config_stream.clone(),
+                entropy_stream: state.entropy_stream.clone(),
+                proof_stream: state.proof_stream.clone(),
+            }),
+        }
+    }
+}
+
+impl<'a> Runtime<'a> {
+    /// Captures a [ShardSnapshot] at the start of each shard of the execution, taken with
+    /// [Runtime::take_snapshots]. Must be called before the execution starts.
+    pub fn enable_snapshots(&mut self) {
+        assert_eq!(
+            self.state.global_clk, 0,
+            "snapshots must be enabled before the execution starts"
+        );
+        self.snapshots = Some(SnapshotRecorder::new());
+    }
+
+    /// The snapshots captured since [Runtime::enable_snapshots], which stops capturing them.
+    pub fn take_snapshots(&mut self) -> Snapshots {
+        self.snapshots
+            .take()
+            .expect("snapshots are not enabled")
+            .finish(&self.state)
+    }
+
+    /// Executes the program without recording events, returning the snapshots of its shards.
+    pub fn execute_snapshots(&mut self) -> Result<Snapshots, ExecutionError> {
+        self.enable_snapshots();
+        self.emit_events = false;
+        self.print_report = false;
+        while !self.execute()? {}
+        Ok(self.take_snapshots())
+    }
+
+    /// A runtime executing the `index`-th shard of `snapshots`, whose [Runtime::execute_record]
+    /// records the events of that shard only.
+    pub fn from_snapshot(
+        program: Program,
+        snapshots: &Snapshots,
+        index: usize,
+        opts: SP1CoreOpts,
+    ) -> Self {
+        let opts = SP1CoreOpts {
+            shard_batch_size: 1,
+            ..opts
+        };
+        Self::recover(program, snapshots.state(index), opts)
+    }
+
+    /// Captures the snapshot of the shard starting now, if snapshots are enabled.
+    pub(crate) fn snapshot_shard(&mut self) {
+        let effects_start = self.effects.as_ref().map_or(0, Vec::len);
+        if let Some(recorder) = self.snapshots.as_mut() {
+            recorder.capture(&self.state, effects_start);
+        }
+    }
+
+    /// Marks the word at `addr` as touched since the last snapshot.
+    #[inline]
+    pub(crate) fn touch(&mut self, addr: u32) {
+        if let Some(recorder) = self.snapshots.as_mut() {
+            recorder.touch(addr);
+        }
+    }
+
+    /// Marks the initial value of the word at `addr` as set since the last snapshot.
+    #[inline]
+    pub(crate) fn touch_uninitialized(&mut self, addr: u32) {
+        if let Some(recorder) = self.snapshots.as_mut() {
+            recorder.touch_uninitialized(addr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_maybe_rayon::prelude::*;
+
+    use super::*;
+    use crate::runtime::ExecutionRecord;
+    use crate::stark::MachineRecord;
+    use crate::utils::tests::{FIBONACCI_ELF, SHA2_ELF};
+
+    fn opts() -> SP1CoreOpts {
+        SP1CoreOpts {
+            shard_size: 1 << 12,
+            shard_batch_size: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Records the shards of `program` by executing it once with a single runtime, the second pass
+    /// of proving without snapshots.
+    fn sequential_record(program: &Program) -> ExecutionRecord {
+        let mut runtime = Runtime::new(program.clone(), opts());
+        runtime.seed_lookup_ids([7; 32]);
+        runtime.execute_record().unwrap().0
+    }
+
+    /// Records the shards of `program` from their snapshots, in parallel.
+    fn snapshot_records(program: &Program) -> (Snapshots, Vec<ExecutionRecord>) {
+        let mut runtime = Runtime::new(program.clone(), opts());
+        runtime.enable_snapshots();
+        runtime.run_untraced().unwrap();
+        let snapshots = runtime.take_snapshots();
+        let records = (0..snapshots.shards.len())
+            .into_par_iter()
+            .map(|index| {
+                let mut runtime =
+                    Runtime::from_snapshot(program.clone(), &snapshots, index, opts());
+                runtime.seed_lookup_ids([7; 32]);
+                runtime.execute_record().unwrap().0
+            })
+            .collect();
+        (snapshots, records)
+    }
+
+    fn assert_same_records(elf: &[u8]) {
+        let program = Program::from(elf);
+        let expected = sequential_record(&program);
+        let (snapshots, records) = snapshot_records(&program);
+        assert!(records.len() > 1, "the program should span several shards");
+        for (snapshot, record) in snapshots.shards.iter().zip(records.iter()) {
+            assert_eq!(snapshot.shard(), record.index);
+            assert_eq!(
+                record.cpu_events.first().map(|event| event.shard),
+                Some(record.index)
+            );
+        }
+
+        // The public values are only complete at the end of the execution, and are set by the
+        // prover, so they are left out.
+        let mut records = records.into_iter();
+        let mut record = records.next().unwrap();
+        for mut next in records {
+            record.append(&mut next);
+        }
+        record.public_values = expected.public_values;
+        assert!(
+            bincode::serialize(&record).unwrap() == bincode::serialize(&expected).unwrap(),
+            "the records differ"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_records_fibonacci() {
+        assert_same_records(FIBONACCI_ELF);
+    }
+
+    #[test]
+    fn test_snapshot_records_sha2() {
+        assert_same_records(SHA2_ELF);
+    }
+
+    #[test]
+    fn test_snapshots_share_pages() {
+        let program = Program::from(FIBONACCI_ELF);
+        let (snapshots, _) = snapshot_records(&program);
+        let (first, last) = (&snapshots.shards[1], snapshots.shards.last().unwrap());
+        // The program image is loaded in the first shard and mostly left alone after it.
+        assert!(last.memory.num_shared_pages(&first.memory) > 0);
+        assert!(last.memory.num_pages() > 1);
+    }
+}
@@ -46,6 +46,15 @@ pub struct ExecutionState {
     /// A ptr to the current position in the input stream incremented by HINT_READ opcode.
     pub input_stream_ptr: usize,
 
+    /// The canonical encoding of the config map, read by the CONFIG_READ opcode.
+    pub config_stream: Vec<u8>,
+
+    /// The entropy pool provided by the host, consumed in order by the ENTROPY_READ opcode.
+    pub entropy_stream: Vec<u8>,
+
+    /// A ptr to the first unconsumed byte of the entropy pool.
+    pub entropy_stream_ptr: usize,
+
     /// A stream of proofs inputted to the program.
     pub proof_stream: Vec<(
         ShardProof<BabyBearPoseidon2>,
@@ -60,6 +69,21 @@ pub struct ExecutionState {
 
     /// A ptr to the current position in the public values stream, incremented when reading from public_values_stream.
     pub public_values_stream_ptr: usize,
+
+    /// The chunks committed by the program with `sp1_zkvm::io::commit_chunk`, see
+    /// [crate::io::SP1PublicValues::output_chunks].
+    pub output_chunks: Vec<Vec<u8>>,
+
+    /// The encoded entries committed by the program with `sp1_zkvm::io::commit_kv`, see
+    /// [crate::io::KvCommitment::verify_entries].
+    pub kv_entries: Vec<Vec<u8>>,
+
+    /// The state suspended by the program with `sp1_zkvm::io::suspend_state`, for the next segment
+    /// of the computation to resume from.
+    pub segment_state: Option<Vec<u8>>,
+
+    /// The gas used so far, metered with the [super::GasSchedule] of the runtime.
+    pub gas_used: u64,
 }
 
 impl ExecutionState {
@@ -75,10 +99,17 @@ impl ExecutionState {
             uninitialized_memory: HashMap::default(),
             input_stream: Vec::new(),
             input_stream_ptr: 0,
+            config_stream: Vec::new(),
+            entropy_stream: Vec::new(),
+            entropy_stream_ptr: 0,
             public_values_stream: Vec::new(),
             public_values_stream_ptr: 0,
+            output_chunks: Vec::new(),
+            kv_entries: Vec::new(),
+            segment_state: None,
             proof_stream: Vec::new(),
             proof_stream_ptr: 0,
+            gas_used: 0,
         }
     }
 }
@@ -2,9 +2,12 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
+use crate::operations::field::field_op::FieldOperation;
 use crate::runtime::{Register, Runtime};
+use crate::syscall::precompiles::bls12381_fp::Bls12381FpOpSyscall;
 use crate::syscall::precompiles::edwards::EdAddAssignChip;
 use crate::syscall::precompiles::edwards::EdDecompressChip;
 use crate::syscall::precompiles::keccak256::KeccakPermuteChip;
@@ -14,12 +17,15 @@ use crate::syscall::precompiles::weierstrass::WeierstrassAddAssignChip;
 use crate::syscall::precompiles::weierstrass::WeierstrassDecompressChip;
 use crate::syscall::precompiles::weierstrass::WeierstrassDoubleAssignChip;
 use crate::syscall::{
-    SyscallCommit, SyscallCommitDeferred, SyscallEnterUnconstrained, SyscallExitUnconstrained,
-    SyscallHalt, SyscallHintLen, SyscallHintRead, SyscallVerifySP1Proof, SyscallWrite,
+    SyscallCommit, SyscallCommitConfig, SyscallCommitDeferred, SyscallCommitInput,
+    SyscallConfigLen, SyscallConfigRead, SyscallCycleCount, SyscallEnterUnconstrained,
+    SyscallEntropyLen, SyscallEntropyRead, SyscallExitUnconstrained, SyscallGasUsed, SyscallHalt,
+    SyscallHintLen, SyscallHintRead, SyscallHintShardBoundary, SyscallMemoryLimit,
+    SyscallVerifySP1Proof, SyscallWrite,
 };
 use crate::utils::ec::edwards::ed25519::{Ed25519, Ed25519Parameters};
 use crate::utils::ec::weierstrass::bls12_381::Bls12381;
-use crate::utils::ec::weierstrass::{bn254::Bn254, secp256k1::Secp256k1};
+use crate::utils::ec::weierstrass::{bn254::Bn254, secp256k1::Secp256k1, secp256r1::Secp256r1};
 use crate::{runtime::ExecutionRecord, runtime::MemoryReadRecord, runtime::MemoryWriteRecord};
 
 /// A system call is invoked by the the `ecall` instruction with a specific value in register t0.
@@ -28,7 +34,9 @@ use crate::{runtime::ExecutionRecord, runtime::MemoryReadRecord, runtime::Memory
 /// - The second byte is 0/1 depending on whether the syscall has a separate table. This is used
 /// in the CPU table to determine whether to lookup the syscall using the syscall interaction.
 /// - The third byte is the number of additional cycles the syscall uses.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, Ord, PartialOrd)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, Ord, PartialOrd, Serialize, Deserialize,
+)]
 #[allow(non_camel_case_types)]
 pub enum SyscallCode {
     /// Halts the program.
@@ -76,6 +84,12 @@ pub enum SyscallCode {
     /// Executes the `COMMIT` precompile.
     COMMIT = 0x00_00_00_10,
 
+    /// Executes the `COMMIT_INPUT` precompile.
+    COMMIT_INPUT = 0x00_00_00_11,
+
+    /// Executes the `COMMIT_CONFIG` precompile.
+    COMMIT_CONFIG = 0x00_00_00_12,
+
     /// Executes the `COMMIT_DEFERRED_PROOFS` precompile.
     COMMIT_DEFERRED_PROOFS = 0x00_00_00_1A,
 
@@ -91,6 +105,30 @@ pub enum SyscallCode {
     /// Executes the `HINT_READ` precompile.
     HINT_READ = 0x00_00_00_F1,
 
+    /// Executes the `CONFIG_LEN` precompile.
+    CONFIG_LEN = 0x00_00_00_F2,
+
+    /// Executes the `CONFIG_READ` precompile.
+    CONFIG_READ = 0x00_00_00_F3,
+
+    /// Executes the `MEMORY_LIMIT` precompile.
+    MEMORY_LIMIT = 0x00_00_00_F4,
+
+    /// Executes the `CYCLE_COUNT` precompile.
+    CYCLE_COUNT = 0x00_00_00_F5,
+
+    /// Executes the `HINT_SHARD_BOUNDARY` precompile.
+    HINT_SHARD_BOUNDARY = 0x00_00_00_F6,
+
+    /// Executes the `ENTROPY_LEN` precompile.
+    ENTROPY_LEN = 0x00_00_00_F7,
+
+    /// Executes the `ENTROPY_READ` precompile.
+    ENTROPY_READ = 0x00_00_00_F8,
+
+    /// Executes the `GAS_USED` precompile.
+    GAS_USED = 0x00_00_00_F9,
+
     /// Executes the `UINT256_MUL` precompile.
     UINT256_MUL = 0x00_00_01_1D,
 
@@ -99,6 +137,24 @@ pub enum SyscallCode {
 
     /// Executes the `BLS12381_DOUBLE` precompile.
     BLS12381_DOUBLE = 0x00_00_01_1F,
+
+    /// Executes the `BLS12381_FP_ADD` precompile.
+    BLS12381_FP_ADD = 0x00_00_01_20,
+
+    /// Executes the `BLS12381_FP_SUB` precompile.
+    BLS12381_FP_SUB = 0x00_00_01_21,
+
+    /// Executes the `BLS12381_FP_MUL` precompile.
+    BLS12381_FP_MUL = 0x00_00_01_22,
+
+    /// Executes the `SECP256R1_ADD` precompile.
+    SECP256R1_ADD = 0x00_01_01_23,
+
+    /// Executes the `SECP256R1_DOUBLE` precompile.
+    SECP256R1_DOUBLE = 0x00_00_01_24,
+
+    /// Executes the `BLS12381_FP_DIV` precompile.
+    BLS12381_FP_DIV = 0x00_00_01_25,
 }
 
 impl SyscallCode {
@@ -122,12 +178,28 @@ impl SyscallCode {
             0x00_01_01_1E => SyscallCode::BLS12381_ADD,
             0x00_00_01_1F => SyscallCode::BLS12381_DOUBLE,
             0x00_00_00_10 => SyscallCode::COMMIT,
+            0x00_00_00_11 => SyscallCode::COMMIT_INPUT,
+            0x00_00_00_12 => SyscallCode::COMMIT_CONFIG,
             0x00_00_00_1A => SyscallCode::COMMIT_DEFERRED_PROOFS,
             0x00_00_00_1B => SyscallCode::VERIFY_SP1_PROOF,
             0x00_00_00_F0 => SyscallCode::HINT_LEN,
             0x00_00_00_F1 => SyscallCode::HINT_READ,
+            0x00_00_00_F2 => SyscallCode::CONFIG_LEN,
+            0x00_00_00_F3 => SyscallCode::CONFIG_READ,
+            0x00_00_00_F4 => SyscallCode::MEMORY_LIMIT,
+            0x00_00_00_F5 => SyscallCode::CYCLE_COUNT,
+            0x00_00_00_F6 => SyscallCode::HINT_SHARD_BOUNDARY,
+            0x00_00_00_F7 => SyscallCode::ENTROPY_LEN,
+            0x00_00_00_F8 => SyscallCode::ENTROPY_READ,
+            0x00_00_00_F9 => SyscallCode::GAS_USED,
             0x00_00_01_1D => SyscallCode::UINT256_MUL,
             0x00_00_01_1C => SyscallCode::BLS12381_DECOMPRESS,
+            0x00_00_01_20 => SyscallCode::BLS12381_FP_ADD,
+            0x00_00_01_21 => SyscallCode::BLS12381_FP_SUB,
+            0x00_00_01_22 => SyscallCode::BLS12381_FP_MUL,
+            0x00_01_01_23 => SyscallCode::SECP256R1_ADD,
+            0x00_00_01_24 => SyscallCode::SECP256R1_DOUBLE,
+            0x00_00_01_25 => SyscallCode::BLS12381_FP_DIV,
             _ => panic!("invalid syscall number: {}", value),
         }
     }
@@ -143,6 +215,33 @@ impl SyscallCode {
     pub fn num_cycles(&self) -> u32 {
         (*self as u32).to_le_bytes()[2].into()
     }
+
+    /// The name of the chip constraining the syscall, or `None` if the syscall has no chip.
+    pub fn chip_name(&self) -> Option<&'static str> {
+        match self {
+            SyscallCode::SHA_EXTEND => Some("ShaExtend"),
+            SyscallCode::SHA_COMPRESS => Some("ShaCompress"),
+            SyscallCode::ED_ADD => Some("EdAddAssign"),
+            SyscallCode::ED_DECOMPRESS => Some("EdDecompress"),
+            SyscallCode::KECCAK_PERMUTE => Some("KeccakPermute"),
+            SyscallCode::SECP256K1_ADD => Some("Secp256k1AddAssign"),
+            SyscallCode::SECP256K1_DOUBLE => Some("Secp256k1DoubleAssign"),
+            SyscallCode::SECP256K1_DECOMPRESS => Some("Secp256k1Decompress"),
+            SyscallCode::SECP256R1_ADD => Some("Secp256r1AddAssign"),
+            SyscallCode::SECP256R1_DOUBLE => Some("Secp256r1DoubleAssign"),
+            SyscallCode::BN254_ADD => Some("Bn254AddAssign"),
+            SyscallCode::BN254_DOUBLE => Some("Bn254DoubleAssign"),
+            SyscallCode::BLS12381_ADD => Some("Bls12381AddAssign"),
+            SyscallCode::BLS12381_DOUBLE => Some("Bls12381DoubleAssign"),
+            SyscallCode::BLS12381_DECOMPRESS => Some("Bls12381Decompress"),
+            SyscallCode::UINT256_MUL => Some("Uint256MulMod"),
+            SyscallCode::BLS12381_FP_ADD
+            | SyscallCode::BLS12381_FP_SUB
+            | SyscallCode::BLS12381_FP_MUL
+            | SyscallCode::BLS12381_FP_DIV => Some("Bls12381FpOp"),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for SyscallCode {
@@ -293,6 +392,14 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
         SyscallCode::SECP256K1_DECOMPRESS,
         Arc::new(WeierstrassDecompressChip::<Secp256k1>::new()),
     );
+    syscall_map.insert(
+        SyscallCode::SECP256R1_ADD,
+        Arc::new(WeierstrassAddAssignChip::<Secp256r1>::new()),
+    );
+    syscall_map.insert(
+        SyscallCode::SECP256R1_DOUBLE,
+        Arc::new(WeierstrassDoubleAssignChip::<Secp256r1>::new()),
+    );
     syscall_map.insert(
         SyscallCode::BN254_ADD,
         Arc::new(WeierstrassAddAssignChip::<Bn254>::new()),
@@ -320,6 +427,14 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
     );
     syscall_map.insert(SyscallCode::WRITE, Arc::new(SyscallWrite::new()));
     syscall_map.insert(SyscallCode::COMMIT, Arc::new(SyscallCommit::new()));
+    syscall_map.insert(
+        SyscallCode::COMMIT_INPUT,
+        Arc::new(SyscallCommitInput::new()),
+    );
+    syscall_map.insert(
+        SyscallCode::COMMIT_CONFIG,
+        Arc::new(SyscallCommitConfig::new()),
+    );
     syscall_map.insert(
         SyscallCode::COMMIT_DEFERRED_PROOFS,
         Arc::new(SyscallCommitDeferred::new()),
@@ -330,11 +445,44 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
     );
     syscall_map.insert(SyscallCode::HINT_LEN, Arc::new(SyscallHintLen::new()));
     syscall_map.insert(SyscallCode::HINT_READ, Arc::new(SyscallHintRead::new()));
+    syscall_map.insert(SyscallCode::CONFIG_LEN, Arc::new(SyscallConfigLen::new()));
+    syscall_map.insert(SyscallCode::CONFIG_READ, Arc::new(SyscallConfigRead::new()));
+    syscall_map.insert(
+        SyscallCode::MEMORY_LIMIT,
+        Arc::new(SyscallMemoryLimit::new()),
+    );
+    syscall_map.insert(SyscallCode::CYCLE_COUNT, Arc::new(SyscallCycleCount::new()));
+    syscall_map.insert(
+        SyscallCode::HINT_SHARD_BOUNDARY,
+        Arc::new(SyscallHintShardBoundary::new()),
+    );
+    syscall_map.insert(SyscallCode::ENTROPY_LEN, Arc::new(SyscallEntropyLen::new()));
+    syscall_map.insert(
+        SyscallCode::ENTROPY_READ,
+        Arc::new(SyscallEntropyRead::new()),
+    );
+    syscall_map.insert(SyscallCode::GAS_USED, Arc::new(SyscallGasUsed::new()));
     syscall_map.insert(
         SyscallCode::BLS12381_DECOMPRESS,
         Arc::new(WeierstrassDecompressChip::<Bls12381>::new()),
     );
     syscall_map.insert(SyscallCode::UINT256_MUL, Arc::new(Uint256MulChip::new()));
+    syscall_map.insert(
+        SyscallCode::BLS12381_FP_ADD,
+        Arc::new(Bls12381FpOpSyscall::new(FieldOperation::Add)),
+    );
+    syscall_map.insert(
+        SyscallCode::BLS12381_FP_SUB,
+        Arc::new(Bls12381FpOpSyscall::new(FieldOperation::Sub)),
+    );
+    syscall_map.insert(
+        SyscallCode::BLS12381_FP_MUL,
+        Arc::new(Bls12381FpOpSyscall::new(FieldOperation::Mul)),
+    );
+    syscall_map.insert(
+        SyscallCode::BLS12381_FP_DIV,
+        Arc::new(Bls12381FpOpSyscall::new(FieldOperation::Div)),
+    );
 
     syscall_map
 }
@@ -342,6 +490,10 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
 #[cfg(test)]
 mod tests {
     use super::{default_syscall_map, SyscallCode};
+    use crate::air::MachineAir;
+    use crate::runtime::UNCONSTRAINED_SYSCALL_IDS;
+    use crate::stark::RiscvAir;
+    use p3_baby_bear::BabyBear;
     use strum::IntoEnumIterator;
 
     #[test]
@@ -360,6 +512,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_syscall_chip_names() {
+        let chips = RiscvAir::<BabyBear>::get_all()
+            .iter()
+            .map(|chip| chip.name())
+            .collect::<Vec<_>>();
+        for code in SyscallCode::iter() {
+            if let Some(name) = code.chip_name() {
+                assert!(chips.iter().any(|chip| chip == name), "{}", name);
+            }
+        }
+    }
+
     #[test]
     fn test_encoding_roundtrip() {
         for (syscall_code, _) in default_syscall_map().iter() {
@@ -406,6 +571,12 @@ mod tests {
                 SyscallCode::SECP256K1_DECOMPRESS => {
                     assert_eq!(code as u32, sp1_zkvm::syscalls::SECP256K1_DECOMPRESS)
                 }
+                SyscallCode::SECP256R1_ADD => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::SECP256R1_ADD)
+                }
+                SyscallCode::SECP256R1_DOUBLE => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::SECP256R1_DOUBLE)
+                }
                 SyscallCode::BN254_ADD => assert_eq!(code as u32, sp1_zkvm::syscalls::BN254_ADD),
                 SyscallCode::BN254_DOUBLE => {
                     assert_eq!(code as u32, sp1_zkvm::syscalls::BN254_DOUBLE)
@@ -413,7 +584,25 @@ mod tests {
                 SyscallCode::UINT256_MUL => {
                     assert_eq!(code as u32, sp1_zkvm::syscalls::UINT256_MUL)
                 }
+                SyscallCode::BLS12381_FP_ADD => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::BLS12381_FP_ADD)
+                }
+                SyscallCode::BLS12381_FP_SUB => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::BLS12381_FP_SUB)
+                }
+                SyscallCode::BLS12381_FP_MUL => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::BLS12381_FP_MUL)
+                }
+                SyscallCode::BLS12381_FP_DIV => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::BLS12381_FP_DIV)
+                }
                 SyscallCode::COMMIT => assert_eq!(code as u32, sp1_zkvm::syscalls::COMMIT),
+                SyscallCode::COMMIT_INPUT => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::COMMIT_INPUT)
+                }
+                SyscallCode::COMMIT_CONFIG => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::COMMIT_CONFIG)
+                }
                 SyscallCode::COMMIT_DEFERRED_PROOFS => {
                     assert_eq!(code as u32, sp1_zkvm::syscalls::COMMIT_DEFERRED_PROOFS)
                 }
@@ -422,10 +611,46 @@ mod tests {
                 }
                 SyscallCode::HINT_LEN => assert_eq!(code as u32, sp1_zkvm::syscalls::HINT_LEN),
                 SyscallCode::HINT_READ => assert_eq!(code as u32, sp1_zkvm::syscalls::HINT_READ),
+                SyscallCode::CONFIG_LEN => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::CONFIG_LEN)
+                }
+                SyscallCode::CONFIG_READ => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::CONFIG_READ)
+                }
+                SyscallCode::MEMORY_LIMIT => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::MEMORY_LIMIT)
+                }
+                SyscallCode::CYCLE_COUNT => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::CYCLE_COUNT)
+                }
+                SyscallCode::HINT_SHARD_BOUNDARY => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::HINT_SHARD_BOUNDARY)
+                }
+                SyscallCode::ENTROPY_LEN => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::ENTROPY_LEN)
+                }
+                SyscallCode::ENTROPY_READ => {
+                    assert_eq!(code as u32, sp1_zkvm::syscalls::ENTROPY_READ)
+                }
+                SyscallCode::GAS_USED => assert_eq!(code as u32, sp1_zkvm::syscalls::GAS_USED),
                 SyscallCode::BLS12381_DECOMPRESS => {
                     assert_eq!(code as u32, sp1_zkvm::syscalls::BLS12381_DECOMPRESS)
                 }
             }
         }
     }
+
+    #[test]
+    /// Check that the ids reserved for unconstrained syscalls match the zkVM crate's, and are not
+    /// used by any other syscall.
+    fn test_unconstrained_syscall_ids() {
+        assert_eq!(
+            UNCONSTRAINED_SYSCALL_IDS,
+            sp1_zkvm::syscalls::UNCONSTRAINED_SYSCALL_START
+                ..=sp1_zkvm::syscalls::UNCONSTRAINED_SYSCALL_END
+        );
+        for code in SyscallCode::iter() {
+            assert!(!UNCONSTRAINED_SYSCALL_IDS.contains(&(code as u32)));
+        }
+    }
 }
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use super::{ExecutionError, Runtime};
+
+/// The syscall ids reserved for unconstrained syscalls, see [UnconstrainedSyscalls::register].
+///
+/// Like the syscalls without a chip, they keep their id in `t0` and take no extra cycles, so the
+/// CPU constraints of the `ECALL` hold. Their effects on memory are not constrained at all.
+pub const UNCONSTRAINED_SYSCALL_IDS: RangeInclusive<u32> = 0x00_00_00_C0..=0x00_00_00_DF;
+
+/// A handler of an unconstrained syscall, called with the arguments in `a0` and `a1`.
+pub trait UnconstrainedSyscall:
+    Fn(&mut UnconstrainedSyscallContext, u32, u32) + Send + Sync
+{
+}
+
+impl<F: Fn(&mut UnconstrainedSyscallContext, u32, u32) + Send + Sync> UnconstrainedSyscall for F {}
+
+/// The handlers of the unconstrained syscalls of a runtime, see [Runtime::unconstrained_syscalls].
+///
+/// Unconstrained syscalls let a guest prototype the API of a precompile, and measure the cycles it
+/// saves, before its chip is written. Nothing they do is proven: proving an execution that makes
+/// one fails with [crate::utils::SP1CoreProverError::UnconstrainedSyscalls], unless the
+/// [crate::utils::SP1CoreOpts::allow_unconstrained] dev flag is set, in which case the proof is
+/// insecure.
+#[derive(Clone, Default)]
+pub struct UnconstrainedSyscalls {
+    handlers: BTreeMap<u32, Arc<dyn UnconstrainedSyscall>>,
+}
+
+impl UnconstrainedSyscalls {
+    /// Creates a registry without handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the handler of the unconstrained syscall `id`, which must be in
+    /// [UNCONSTRAINED_SYSCALL_IDS], replacing the previous one if any.
+    pub fn register(
+        &mut self,
+        id: u32,
+        handler: impl Fn(&mut UnconstrainedSyscallContext, u32, u32) + Send + Sync + 'static,
+    ) {
+        assert!(
+            UNCONSTRAINED_SYSCALL_IDS.contains(&id),
+            "syscall id {:#x} is not reserved for unconstrained syscalls",
+            id
+        );
+        self.handlers.insert(id, Arc::new(handler));
+    }
+
+    /// The handler of the unconstrained syscall `id`, if one is registered.
+    pub(crate) fn get(&self, id: u32) -> Option<Arc<dyn UnconstrainedSyscall>> {
+        self.handlers.get(&id).cloned()
+    }
+}
+
+impl fmt::Debug for UnconstrainedSyscalls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.handlers.keys().map(|id| format!("{:#x}", id)))
+            .finish()
+    }
+}
+
+/// The guest memory, as seen by the handler of an unconstrained syscall.
+pub struct UnconstrainedSyscallContext<'a, 'b> {
+    rt: &'a mut Runtime<'b>,
+}
+
+impl<'a, 'b> UnconstrainedSyscallContext<'a, 'b> {
+    /// Reads the word at the aligned address `addr`.
+    pub fn read_word(&self, addr: u32) -> u32 {
+        assert_eq!(addr % 4, 0, "address {:#x} is not aligned", addr);
+        match self.rt.state.memory.get(&addr) {
+            Some(record) => record.value,
+            None => self
+                .rt
+                .state
+                .uninitialized_memory
+                .get(&addr)
+                .copied()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Reads `len` words starting at the aligned address `addr`.
+    pub fn read_words(&self, addr: u32, len: usize) -> Vec<u32> {
+        (0..len as u32)
+            .map(|i| self.read_word(addr + 4 * i))
+            .collect()
+    }
+
+    /// Writes `value` to the aligned address `addr`.
+    ///
+    /// An address the program has not touched yet is given `value` as its initial value, the same
+    /// way hints are, which can be proven. Overwriting a touched address cannot be proven without a
+    /// chip, so an execution doing it can only be executed.
+    pub fn write_word(&mut self, addr: u32, value: u32) {
+        assert_eq!(addr % 4, 0, "address {:#x} is not aligned", addr);
+        assert!(
+            !self.rt.unconstrained,
+            "unconstrained syscalls cannot write memory in an unconstrained block"
+        );
+        self.rt.touch(addr);
+        match self.rt.state.memory.get_mut(&addr) {
+            Some(record) => {
+                record.value = value;
+                self.rt.unconstrained_syscall_overwrite.get_or_insert(addr);
+            }
+            None => {
+                self.rt.touch_uninitialized(addr);
+                self.rt.state.uninitialized_memory.insert(addr, value);
+            }
+        }
+    }
+
+    /// Writes `values` starting at the aligned address `addr`.
+    pub fn write_words(&mut self, addr: u32, values: &[u32]) {
+        for (i, value) in values.iter().enumerate() {
+            self.write_word(addr + 4 * i as u32, *value);
+        }
+    }
+}
+
+impl<'a> Runtime<'a> {
+    /// Executes the unconstrained syscall `syscall_id` with the handler registered for it.
+    pub(crate) fn execute_unconstrained_syscall(
+        &mut self,
+        syscall_id: u32,
+        arg1: u32,
+        arg2: u32,
+    ) -> Result<(), ExecutionError> {
+        let Some(handler) = self.unconstrained_syscalls.get(syscall_id) else {
+            return Err(ExecutionError::UnsupportedSyscall(syscall_id));
+        };
+        if self.num_unconstrained_syscalls == 0 {
+            tracing::warn!(
+                "executing unconstrained syscall {:#x}, whose effects are not proven",
+                syscall_id
+            );
+        }
+        self.num_unconstrained_syscalls += 1;
+        handler(&mut UnconstrainedSyscallContext { rt: self }, arg1, arg2);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        io::SP1Stdin,
+        runtime::{Instruction, NoOpSubproofVerifier, Opcode, Program, Register},
+        stark::{MachineProof, RiscvAir},
+        utils::{prove_with_subproof_verifier, BabyBearPoseidon2, SP1CoreOpts, SP1CoreProverError},
+    };
+
+    /// Adds the word at `a1` to the word at `a0`, which the program has already written.
+    const ADD_ASSIGN: u32 = 0x00_00_00_C0;
+    /// Writes the square of `a1` to the untouched word at `a0`.
+    const SQUARE: u32 = 0x00_00_00_C1;
+
+    fn handlers() -> UnconstrainedSyscalls {
+        let mut syscalls = UnconstrainedSyscalls::new();
+        syscalls.register(ADD_ASSIGN, |ctx, x, y| {
+            let sum = ctx.read_word(x).wrapping_add(ctx.read_word(y));
+            ctx.write_word(x, sum);
+        });
+        syscalls.register(SQUARE, |ctx, ptr, x| {
+            ctx.write_word(ptr, x.wrapping_mul(x));
+        });
+        syscalls
+    }
+
+    /// Proves `program` with the handlers of [handlers].
+    fn prove_with_handlers(
+        program: Program,
+        opts: SP1CoreOpts,
+    ) -> Result<(MachineProof<BabyBearPoseidon2>, Vec<u8>, bool), SP1CoreProverError> {
+        prove_with_subproof_verifier::<_, NoOpSubproofVerifier>(
+            program,
+            &SP1Stdin::new(),
+            BabyBearPoseidon2::new(),
+            opts,
+            None,
+            &handlers(),
+        )
+    }
+
+    /// The instructions making the unconstrained syscall `id` with `a0 = arg1` and `a1 = arg2`.
+    fn ecall(id: u32, arg1: u32, arg2: u32) -> Vec<Instruction> {
+        vec![
+            Instruction::new(Opcode::ADD, 5, 0, id, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, arg1, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, arg2, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]
+    }
+
+    /// A program adding 5 to 37 in memory, then loading the result into `x20`.
+    fn add_assign_program() -> Program {
+        let mut instructions = vec![
+            Instruction::new(Opcode::ADD, 29, 0, 37, false, true),
+            Instruction::new(Opcode::SW, 29, 0, 0x1000, false, true),
+            Instruction::new(Opcode::ADD, 29, 0, 5, false, true),
+            Instruction::new(Opcode::SW, 29, 0, 0x1004, false, true),
+        ];
+        instructions.extend(ecall(ADD_ASSIGN, 0x1000, 0x1004));
+        instructions.push(Instruction::new(Opcode::LW, 20, 0, 0x1000, false, true));
+        Program::new(instructions, 0, 0)
+    }
+
+    /// A program squaring 12 into the untouched word at `0x2000`, then loading it into `x20`.
+    fn square_program() -> Program {
+        let mut instructions = ecall(SQUARE, 0x2000, 12);
+        instructions.push(Instruction::new(Opcode::LW, 20, 0, 0x2000, false, true));
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_unconstrained_syscall_writes_memory() {
+        let mut runtime = Runtime::new(add_assign_program(), SP1CoreOpts::default());
+        runtime.unconstrained_syscalls = handlers();
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X20), 42);
+        assert_eq!(runtime.register(Register::X5), ADD_ASSIGN);
+        assert_eq!(runtime.num_unconstrained_syscalls, 1);
+        assert_eq!(runtime.unconstrained_syscall_overwrite, Some(0x1000));
+
+        let mut runtime = Runtime::new(square_program(), SP1CoreOpts::default());
+        runtime.unconstrained_syscalls = handlers();
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X20), 144);
+        assert_eq!(runtime.unconstrained_syscall_overwrite, None);
+    }
+
+    #[test]
+    fn test_unregistered_unconstrained_syscall() {
+        let mut runtime = Runtime::new(
+            Program::new(ecall(0x00_00_00_DF, 0, 0), 0, 0),
+            SP1CoreOpts::default(),
+        );
+        runtime.unconstrained_syscalls = handlers();
+        assert!(matches!(
+            runtime.run(),
+            Err(ExecutionError::UnsupportedSyscall(0x00_00_00_DF))
+        ));
+
+        // The handlers are only known to the runtimes they are given to.
+        let mut runtime = Runtime::new(square_program(), SP1CoreOpts::default());
+        assert!(matches!(
+            runtime.run(),
+            Err(ExecutionError::UnsupportedSyscall(SQUARE))
+        ));
+    }
+
+    #[test]
+    fn test_prove_refuses_unconstrained_syscalls() {
+        let result = prove_with_handlers(square_program(), SP1CoreOpts::default());
+        assert!(matches!(
+            result,
+            Err(SP1CoreProverError::UnconstrainedSyscalls(1))
+        ));
+
+        // Overwriting memory cannot be proven, even with the dev flag.
+        let opts = SP1CoreOpts {
+            allow_unconstrained: true,
+            ..SP1CoreOpts::default()
+        };
+        let result = prove_with_handlers(add_assign_program(), opts);
+        assert!(matches!(
+            result,
+            Err(SP1CoreProverError::UnprovableUnconstrainedSyscall(0x1000))
+        ));
+    }
+
+    #[test]
+    fn test_prove_allow_unconstrained() {
+        let opts = SP1CoreOpts {
+            allow_unconstrained: true,
+            ..SP1CoreOpts::default()
+        };
+        let (proof, _, insecure) = prove_with_handlers(square_program(), opts).unwrap();
+        assert!(insecure);
+
+        // The proof verifies, but says nothing about the square.
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let (_, vk) = machine.setup(&square_program());
+        let mut challenger = machine.config().challenger();
+        machine.verify(&vk, &proof, &mut challenger).unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use super::StarkMachine;
 pub use crate::air::SP1AirBuilder;
 use crate::air::{MachineAir, SP1_PROOF_NUM_PV_ELTS};
 use crate::memory::{MemoryChipType, MemoryProgramChip};
+use crate::program::{NUM_PROGRAM_BANKS, PROGRAM_BANK_LOG_HEIGHT};
 use crate::stark::Chip;
 use crate::StarkGenericConfig;
 use p3_field::PrimeField32;
@@ -11,16 +12,17 @@ use tracing::instrument;
 /// A module for importing all the different RISC-V chips.
 pub(crate) mod riscv_chips {
     pub use crate::alu::AddSubChip;
+    pub use crate::alu::BitManipChip;
     pub use crate::alu::BitwiseChip;
     pub use crate::alu::DivRemChip;
     pub use crate::alu::LtChip;
     pub use crate::alu::MulChip;
-    pub use crate::alu::ShiftLeft;
-    pub use crate::alu::ShiftRightChip;
+    pub use crate::alu::ShiftChip;
     pub use crate::bytes::ByteChip;
     pub use crate::cpu::CpuChip;
     pub use crate::memory::MemoryChip;
     pub use crate::program::ProgramChip;
+    pub use crate::syscall::precompiles::bls12381_fp::Bls12381FpOpChip;
     pub use crate::syscall::precompiles::edwards::EdAddAssignChip;
     pub use crate::syscall::precompiles::edwards::EdDecompressChip;
     pub use crate::syscall::precompiles::keccak256::KeccakPermuteChip;
@@ -35,6 +37,7 @@ pub(crate) mod riscv_chips {
     pub use crate::utils::ec::weierstrass::bls12_381::Bls12381Parameters;
     pub use crate::utils::ec::weierstrass::bn254::Bn254Parameters;
     pub use crate::utils::ec::weierstrass::secp256k1::Secp256k1Parameters;
+    pub use crate::utils::ec::weierstrass::secp256r1::Secp256r1Parameters;
     pub use crate::utils::ec::weierstrass::SwCurve;
 }
 
@@ -45,7 +48,8 @@ pub(crate) mod riscv_chips {
 /// different AIR variants have a joint lookup argument.
 #[derive(MachineAir)]
 pub enum RiscvAir<F: PrimeField32> {
-    /// An AIR that containts a preprocessed program table and a lookup for the instructions.
+    /// An AIR that containts a bank of the preprocessed program table and a lookup for the
+    /// instructions.
     Program(ProgramChip),
     /// An AIR for the RISC-V CPU. Each row represents a cpu cycle.
     Cpu(CpuChip),
@@ -59,10 +63,10 @@ pub enum RiscvAir<F: PrimeField32> {
     DivRem(DivRemChip),
     /// An AIR for RISC-V Lt instruction.
     Lt(LtChip),
-    /// An AIR for RISC-V SLL instruction.
-    ShiftLeft(ShiftLeft),
-    /// An AIR for RISC-V SRL and SRA instruction.
-    ShiftRight(ShiftRightChip),
+    /// An AIR for RISC-V SLL, SRL and SRA instructions.
+    Shift(ShiftChip),
+    /// An AIR for the RISC-V Zbb CLZ, CTZ, CPOP and REV8 instructions.
+    BitManip(BitManipChip),
     /// A lookup table for byte operations.
     ByteLookup(ByteChip<F>),
     /// A table for initializing the memory state.
@@ -87,6 +91,10 @@ pub enum RiscvAir<F: PrimeField32> {
     Secp256k1Double(WeierstrassDoubleAssignChip<SwCurve<Secp256k1Parameters>>),
     /// A precompile for the Keccak permutation.
     KeccakP(KeccakPermuteChip),
+    /// A precompile for addition on the Elliptic curve secp256r1.
+    Secp256r1Add(WeierstrassAddAssignChip<SwCurve<Secp256r1Parameters>>),
+    /// A precompile for doubling a point on the Elliptic curve secp256r1.
+    Secp256r1Double(WeierstrassDoubleAssignChip<SwCurve<Secp256r1Parameters>>),
     /// A precompile for addition on the Elliptic curve bn254.
     Bn254Add(WeierstrassAddAssignChip<SwCurve<Bn254Parameters>>),
     /// A precompile for doubling a point on the Elliptic curve bn254.
@@ -99,6 +107,8 @@ pub enum RiscvAir<F: PrimeField32> {
     Uint256Mul(Uint256MulChip),
     /// A precompile for decompressing a point on the BLS12-381 curve.
     Bls12381Decompress(WeierstrassDecompressChip<SwCurve<Bls12381Parameters>>),
+    /// A precompile for arithmetic in the base field of the BLS12-381 curve.
+    Bls12381FpOp(Bls12381FpOpChip),
 }
 
 impl<F: PrimeField32> RiscvAir<F> {
@@ -113,13 +123,20 @@ impl<F: PrimeField32> RiscvAir<F> {
 
     /// Get all the different RISC-V AIRs.
     pub fn get_all() -> Vec<Self> {
+        Self::get_all_with_program_bank_height(PROGRAM_BANK_LOG_HEIGHT)
+    }
+
+    /// Get all the different RISC-V AIRs, with program banks of `2^log_bank_height` instructions.
+    pub fn get_all_with_program_bank_height(log_bank_height: usize) -> Vec<Self> {
         // The order of the chips is important, as it is used to determine the order of trace
         // generation. In the future, we will detect that order automatically.
         let mut chips = vec![];
         let cpu = CpuChip::default();
         chips.push(RiscvAir::Cpu(cpu));
-        let program = ProgramChip::default();
-        chips.push(RiscvAir::Program(program));
+        for bank in 0..NUM_PROGRAM_BANKS {
+            let program = ProgramChip::bank(bank, log_bank_height);
+            chips.push(RiscvAir::Program(program));
+        }
         let sha_extend = ShaExtendChip::default();
         chips.push(RiscvAir::Sha256Extend(sha_extend));
         let sha_compress = ShaCompressChip::default();
@@ -137,6 +154,11 @@ impl<F: PrimeField32> RiscvAir<F> {
         chips.push(RiscvAir::Secp256k1Double(secp256k1_double_assign));
         let keccak_permute = KeccakPermuteChip::new();
         chips.push(RiscvAir::KeccakP(keccak_permute));
+        let secp256r1_add_assign = WeierstrassAddAssignChip::<SwCurve<Secp256r1Parameters>>::new();
+        chips.push(RiscvAir::Secp256r1Add(secp256r1_add_assign));
+        let secp256r1_double_assign =
+            WeierstrassDoubleAssignChip::<SwCurve<Secp256r1Parameters>>::new();
+        chips.push(RiscvAir::Secp256r1Double(secp256r1_double_assign));
         let bn254_add_assign = WeierstrassAddAssignChip::<SwCurve<Bn254Parameters>>::new();
         chips.push(RiscvAir::Bn254Add(bn254_add_assign));
         let bn254_double_assign = WeierstrassDoubleAssignChip::<SwCurve<Bn254Parameters>>::new();
@@ -149,6 +171,8 @@ impl<F: PrimeField32> RiscvAir<F> {
         chips.push(RiscvAir::Uint256Mul(uint256_mul));
         let bls12381_decompress = WeierstrassDecompressChip::<SwCurve<Bls12381Parameters>>::new();
         chips.push(RiscvAir::Bls12381Decompress(bls12381_decompress));
+        let bls12381_fp_op = Bls12381FpOpChip::new();
+        chips.push(RiscvAir::Bls12381FpOp(bls12381_fp_op));
         let div_rem = DivRemChip::default();
         chips.push(RiscvAir::DivRem(div_rem));
         let add = AddSubChip::default();
@@ -157,12 +181,12 @@ impl<F: PrimeField32> RiscvAir<F> {
         chips.push(RiscvAir::Bitwise(bitwise));
         let mul = MulChip::default();
         chips.push(RiscvAir::Mul(mul));
-        let shift_right = ShiftRightChip::default();
-        chips.push(RiscvAir::ShiftRight(shift_right));
-        let shift_left = ShiftLeft::default();
-        chips.push(RiscvAir::ShiftLeft(shift_left));
+        let shift = ShiftChip::default();
+        chips.push(RiscvAir::Shift(shift));
         let lt = LtChip::default();
         chips.push(RiscvAir::Lt(lt));
+        let bitmanip = BitManipChip::default();
+        chips.push(RiscvAir::BitManip(bitmanip));
         let memory_init = MemoryChip::new(MemoryChipType::Initialize);
         chips.push(RiscvAir::MemoryInit(memory_init));
         let memory_finalize = MemoryChip::new(MemoryChipType::Finalize);
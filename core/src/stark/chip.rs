@@ -7,12 +7,12 @@ use p3_uni_stark::{get_max_constraint_degree, SymbolicAirBuilder};
 use p3_util::log2_ceil_usize;
 
 use crate::{
-    air::{MachineAir, MultiTableAirBuilder, SP1AirBuilder},
+    air::{MachineAir, MultiTableAirBuilder, SP1AirBuilder, MAX_PACKING_WIDTH},
     lookup::{Interaction, InteractionBuilder, InteractionKind},
 };
 
 use super::{
-    eval_permutation_constraints, generate_permutation_trace, permutation_trace_width,
+    eval_permutation_constraints, generate_permutation_trace, permutation_trace_width, SetupError,
     PROOF_MAX_NUM_PVS,
 };
 
@@ -87,6 +87,18 @@ where
         }
         let log_quotient_degree = log2_ceil_usize(max_constraint_degree - 1);
 
+        let min_trace_height = air.min_trace_height();
+        assert!(
+            min_trace_height.is_power_of_two()
+                && min_trace_height << log_quotient_degree >= MAX_PACKING_WIDTH,
+            "chip {} has a minimum trace height of {} and a log quotient degree of {}, \
+             its quotient domain could be narrower than a packing of {} elements",
+            air.name(),
+            min_trace_height,
+            log_quotient_degree,
+            MAX_PACKING_WIDTH
+        );
+
         Self {
             air,
             sends,
@@ -184,6 +196,10 @@ where
         <A as MachineAir<F>>::generate_preprocessed_trace(&self.air, program)
     }
 
+    fn check_program(&self, program: &A::Program) -> Result<(), SetupError> {
+        <A as MachineAir<F>>::check_program(&self.air, program)
+    }
+
     fn generate_trace(&self, input: &A::Record, output: &mut A::Record) -> RowMajorMatrix<F> {
         self.air.generate_trace(input, output)
     }
@@ -195,6 +211,10 @@ where
     fn included(&self, shard: &Self::Record) -> bool {
         self.air.included(shard)
     }
+
+    fn min_trace_height(&self) -> usize {
+        <A as MachineAir<F>>::min_trace_height(&self.air)
+    }
 }
 
 // Implement AIR directly on Chip, evaluating both execution and permutation constraints.
@@ -29,17 +29,48 @@ pub fn debug_constraints<SC, A>(
     SC: StarkGenericConfig,
     Val<SC>: PrimeField32,
     A: MachineAir<Val<SC>> + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+{
+    if let Some(i) = unsatisfied_row(
+        chip,
+        preprocessed,
+        main,
+        perm,
+        perm_challenges,
+        public_values,
+    ) {
+        let i_next = (i + 1) % main.height();
+        eprintln!("local: {:?}", &*main.row_slice(i));
+        eprintln!("next:  {:?}", &*main.row_slice(i_next));
+        eprintln!("failed at row {} of chip {}", i, chip.name());
+        exit(1);
+    }
+}
+
+/// Returns the first row of the trace on which the constraints of the given AIR, including the
+/// permutation trace, are not satisfied.
+pub fn unsatisfied_row<SC, A>(
+    chip: &MachineChip<SC, A>,
+    preprocessed: Option<&RowMajorMatrix<Val<SC>>>,
+    main: &RowMajorMatrix<Val<SC>>,
+    perm: &RowMajorMatrix<SC::Challenge>,
+    perm_challenges: &[SC::Challenge],
+    public_values: Vec<Val<SC>>,
+) -> Option<usize>
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PrimeField32,
+    A: MachineAir<Val<SC>> + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
 {
     assert_eq!(main.height(), perm.height());
     let height = main.height();
     if height == 0 {
-        return;
+        return None;
     }
 
     let cumulative_sum = perm.row_slice(perm.height() - 1).last().copied().unwrap();
 
     // Check that constraints are satisfied.
-    (0..height).for_each(|i| {
+    (0..height).find(|&i| {
         let i_next = (i + 1) % height;
 
         let main_local = main.row_slice(i);
@@ -96,13 +127,8 @@ pub fn debug_constraints<SC, A>(
         let result = catch_unwind_silent(AssertUnwindSafe(|| {
             chip.eval(&mut builder);
         }));
-        if result.is_err() {
-            eprintln!("local: {:?}", main_local);
-            eprintln!("next:  {:?}", main_next);
-            eprintln!("failed at row {} of chip {}", i, chip.name());
-            exit(1);
-        }
-    });
+        result.is_err()
+    })
 }
 
 fn catch_unwind_silent<F: FnOnce() -> R + panic::UnwindSafe, R>(f: F) -> std::thread::Result<R> {
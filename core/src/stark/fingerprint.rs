@@ -0,0 +1,159 @@
+//! The fingerprint of the shape of a machine: its chips in order, with their widths and
+//! interactions.
+//!
+//! The recursion programs verifying the proofs of a machine are compiled for its chips. A program
+//! verifying the proofs of a machine of another shape fails deep in the proof of the recursion, on
+//! a constraint, so the fingerprint is recorded in the verifying keys and in the recursion programs
+//! and compared before proving, see [MachineFingerprint::first_difference].
+
+use std::fmt::{Display, Formatter};
+
+use p3_air::BaseAir;
+use p3_field::Field;
+use serde::{Deserialize, Serialize};
+
+use super::Chip;
+use crate::air::MachineAir;
+use crate::lookup::Interaction;
+
+/// The digest of a [MachineFingerprint], for pinning the shape of a release.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FingerprintDigest(pub [u8; 32]);
+
+impl Display for FingerprintDigest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// The signature of an interaction: its kind and its number of values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InteractionShape {
+    pub kind: usize,
+    pub num_values: usize,
+}
+
+impl<F: Field> From<&Interaction<F>> for InteractionShape {
+    fn from(interaction: &Interaction<F>) -> Self {
+        Self {
+            kind: interaction.argument_index(),
+            num_values: interaction.values.len(),
+        }
+    }
+}
+
+/// The shape of a chip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChipShape {
+    pub name: String,
+    pub preprocessed_width: usize,
+    pub width: usize,
+    pub sends: Vec<InteractionShape>,
+    pub receives: Vec<InteractionShape>,
+}
+
+impl ChipShape {
+    pub fn new<F: Field, A: MachineAir<F>>(chip: &Chip<F, A>) -> Self {
+        Self {
+            name: chip.name(),
+            preprocessed_width: chip.preprocessed_width(),
+            width: chip.width(),
+            sends: chip.sends().iter().map(InteractionShape::from).collect(),
+            receives: chip.receives().iter().map(InteractionShape::from).collect(),
+        }
+    }
+}
+
+/// The fingerprint of the shape of a machine: the shapes of its chips, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineFingerprint {
+    pub chips: Vec<ChipShape>,
+}
+
+impl MachineFingerprint {
+    /// The digest of the fingerprint.
+    pub fn digest(&self) -> FingerprintDigest {
+        let bytes = bincode::serialize(self).expect("failed to serialize the shape");
+        FingerprintDigest(*blake3::hash(&bytes).as_bytes())
+    }
+
+    /// The name of the first chip differing between this fingerprint and the `expected` one, or
+    /// `None` if they are the same.
+    ///
+    /// A chip of `expected` missing from this machine is named, and otherwise the chip of this
+    /// machine at the position of the first difference.
+    pub fn first_difference(&self, expected: &MachineFingerprint) -> Option<String> {
+        let position = self
+            .chips
+            .iter()
+            .zip(expected.chips.iter())
+            .position(|(chip, expected)| chip != expected)
+            .unwrap_or(self.chips.len().min(expected.chips.len()));
+        match (self.chips.get(position), expected.chips.get(position)) {
+            (None, None) => None,
+            (Some(chip), None) => Some(chip.name.clone()),
+            (None, Some(expected)) => Some(expected.name.clone()),
+            (Some(chip), Some(expected)) => {
+                if self.chips.iter().any(|chip| chip.name == expected.name) {
+                    Some(chip.name.clone())
+                } else {
+                    Some(expected.name.clone())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+
+    use super::*;
+    use crate::stark::RiscvAir;
+    use crate::utils::BabyBearPoseidon2;
+
+    fn fingerprint_without(name: &str) -> MachineFingerprint {
+        let chips = RiscvAir::<BabyBear>::get_all()
+            .into_iter()
+            .filter(|air| air.name() != name)
+            .map(Chip::new)
+            .collect::<Vec<_>>();
+        MachineFingerprint {
+            chips: chips.iter().map(ChipShape::new).collect(),
+        }
+    }
+
+    #[test]
+    fn test_machine_fingerprint() {
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let fingerprint = machine.fingerprint();
+        assert_eq!(fingerprint.chips.len(), machine.chips().len());
+        assert_eq!(fingerprint.first_difference(&fingerprint), None);
+        assert_eq!(
+            fingerprint.digest(),
+            RiscvAir::machine(BabyBearPoseidon2::new())
+                .fingerprint()
+                .digest()
+        );
+
+        // A disabled chip is named, whichever side it is missing from.
+        let disabled = fingerprint_without("KeccakPermute");
+        assert_ne!(disabled.digest(), fingerprint.digest());
+        assert_eq!(
+            disabled.first_difference(&fingerprint),
+            Some("KeccakPermute".to_string())
+        );
+        assert_eq!(
+            fingerprint.first_difference(&disabled),
+            Some("KeccakPermute".to_string())
+        );
+
+        // So is a chip whose width changed.
+        let mut wider = fingerprint.clone();
+        wider.chips[3].width += 1;
+        assert_eq!(
+            wider.first_difference(&fingerprint),
+            Some(fingerprint.chips[3].name.clone())
+        );
+    }
+}
@@ -16,15 +16,17 @@ use serde::Serialize;
 use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use thiserror::Error;
 use tracing::instrument;
 
 use super::debug_constraints;
 use super::Dom;
 use crate::air::MachineAir;
 use crate::air::MachineProgram;
-use crate::lookup::debug_interactions_with_all_chips;
+use crate::lookup::interaction_report;
 use crate::lookup::InteractionBuilder;
-use crate::lookup::InteractionKind;
+use crate::lookup::InteractionReport;
+use crate::lookup::DEFAULT_MAX_REPORTED_INTERACTIONS;
 use crate::stark::record::MachineRecord;
 use crate::stark::DebugConstraintBuilder;
 use crate::stark::ProverConstraintFolder;
@@ -33,8 +35,11 @@ use crate::stark::VerifierConstraintFolder;
 use crate::utils::SP1CoreOpts;
 
 use super::Chip;
+use super::ChipShape;
 use super::Com;
+use super::MachineFingerprint;
 use super::MachineProof;
+use super::OpeningProof;
 use super::PcsProverData;
 use super::Prover;
 use super::StarkGenericConfig;
@@ -73,6 +78,7 @@ pub struct StarkProvingKey<SC: StarkGenericConfig> {
     pub pc_start: Val<SC>,
     pub traces: Vec<RowMajorMatrix<Val<SC>>>,
     pub data: PcsProverData<SC>,
+    #[serde(serialize_with = "crate::utils::serialize_sorted")]
     pub chip_ordering: HashMap<String, usize>,
 }
 
@@ -90,7 +96,10 @@ pub struct StarkVerifyingKey<SC: StarkGenericConfig> {
     pub commit: Com<SC>,
     pub pc_start: Val<SC>,
     pub chip_information: Vec<(String, Dom<SC>, Dimensions)>,
+    #[serde(serialize_with = "crate::utils::serialize_sorted")]
     pub chip_ordering: HashMap<String, usize>,
+    /// The fingerprint of the machine of the key.
+    pub fingerprint: MachineFingerprint,
 }
 
 impl<SC: StarkGenericConfig> StarkVerifyingKey<SC> {
@@ -106,6 +115,21 @@ impl<SC: StarkGenericConfig> Debug for StarkVerifyingKey<SC> {
     }
 }
 
+/// An error setting up a program on a machine, see [StarkMachine::try_setup].
+#[derive(Debug, Error)]
+pub enum SetupError {
+    #[error(
+        "the program has {num_instructions} instructions, which need {num_banks} program banks of \
+         {bank_size} instructions, but the machine only has {max_banks}"
+    )]
+    ProgramTooLarge {
+        num_instructions: usize,
+        num_banks: usize,
+        bank_size: usize,
+        max_banks: usize,
+    },
+}
+
 impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
     /// Get an array containing a `ChipRef` for all the chips of this RISC-V STARK machine.
     pub fn chips(&self) -> &[MachineChip<SC, A>] {
@@ -126,6 +150,13 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
             .collect()
     }
 
+    /// The fingerprint of the shape of the machine, recorded in its verifying keys.
+    pub fn fingerprint(&self) -> MachineFingerprint {
+        MachineFingerprint {
+            chips: self.chips.iter().map(ChipShape::new).collect(),
+        }
+    }
+
     pub fn shard_chips<'a, 'b>(
         &'a self,
         shard: &'b A::Record,
@@ -160,22 +191,41 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
     ///
     /// Given a program, this function generates the proving and verifying keys. The keys correspond
     /// to the program code and other preprocessed colunms such as lookup tables.
-    #[instrument("setup machine", level = "debug", skip_all)]
+    ///
+    /// Panics if the machine cannot hold the program, see [StarkMachine::try_setup].
     pub fn setup(&self, program: &A::Program) -> (StarkProvingKey<SC>, StarkVerifyingKey<SC>) {
+        self.try_setup(program)
+            .unwrap_or_else(|err| panic!("failed to set up the program: {}", err))
+    }
+
+    /// The setup preprocessing phase, like [StarkMachine::setup], failing if a chip cannot hold its
+    /// part of the program, see [MachineAir::check_program].
+    #[instrument("setup machine", level = "debug", skip_all)]
+    pub fn try_setup(
+        &self,
+        program: &A::Program,
+    ) -> Result<(StarkProvingKey<SC>, StarkVerifyingKey<SC>), SetupError> {
+        for chip in self.chips() {
+            chip.check_program(program)?;
+        }
+
         let mut named_preprocessed_traces = tracing::debug_span!("generate preprocessed traces")
             .in_scope(|| {
                 self.chips()
                     .iter()
                     .map(|chip| {
                         let prep_trace = chip.generate_preprocessed_trace(program);
-                        // Assert that the chip width data is correct.
-                        let expected_width = prep_trace.as_ref().map(|t| t.width()).unwrap_or(0);
-                        assert_eq!(
-                            expected_width,
-                            chip.preprocessed_width(),
-                            "Incorrect number of preprocessed columns for chip {}",
-                            chip.name()
-                        );
+                        // Assert that the chip width data is correct. A chip holding nothing of
+                        // the program has no preprocessed trace, see
+                        // [MachineAir::generate_preprocessed_trace].
+                        if let Some(trace) = prep_trace.as_ref() {
+                            assert_eq!(
+                                trace.width(),
+                                chip.preprocessed_width(),
+                                "Incorrect number of preprocessed columns for chip {}",
+                                chip.name()
+                            );
+                        }
 
                         (chip.name(), prep_trace)
                     })
@@ -222,7 +272,7 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
 
         let pc_start = program.pc_start();
 
-        (
+        Ok((
             StarkProvingKey {
                 commit: commit.clone(),
                 pc_start,
@@ -235,8 +285,9 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
                 pc_start,
                 chip_information,
                 chip_ordering,
+                fingerprint: self.fingerprint(),
             },
-        )
+        ))
     }
 
     pub fn shard(
@@ -305,14 +356,7 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
         SC::Challenger: Clone,
         A: for<'a> Air<VerifierConstraintFolder<'a, SC>>,
     {
-        // Observe the preprocessed commitment.
-        vk.observe_into(challenger);
-        tracing::debug_span!("observe challenges for all shards").in_scope(|| {
-            proof.shard_proofs.iter().for_each(|proof| {
-                challenger.observe(proof.commitment.main_commit.clone());
-                challenger.observe_slice(&proof.public_values[0..self.num_pv_elts()]);
-            });
-        });
+        self.observe_shards(vk, proof, challenger);
 
         // Verify the shard proofs.
         if proof.shard_proofs.is_empty() {
@@ -321,25 +365,101 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
 
         tracing::debug_span!("verify shard proofs").in_scope(|| {
             for (i, shard_proof) in proof.shard_proofs.iter().enumerate() {
-                tracing::debug_span!("verifying shard", segment = i).in_scope(|| {
-                    let chips = self
-                        .shard_chips_ordered(&shard_proof.chip_ordering)
-                        .collect::<Vec<_>>();
-                    Verifier::verify_shard(
-                        &self.config,
-                        vk,
-                        &chips,
-                        &mut challenger.clone(),
-                        shard_proof,
-                    )
-                    .map_err(MachineVerificationError::InvalidSegmentProof)
-                })?;
+                tracing::debug_span!("verifying shard", segment = i)
+                    .in_scope(|| self.verify_shard(vk, challenger, shard_proof))?;
             }
 
             Ok(())
         })?;
 
-        // Verify the cumulative sum is 0.
+        self.verify_cumulative_sum(proof)
+    }
+
+    /// Verify a proof like [StarkMachine::verify], verifying its shards in parallel.
+    ///
+    /// Once the commitments of every shard are observed, each shard is verified against its own
+    /// copy of the challenger, so the opening proofs of all the shards, and the Merkle paths they
+    /// open, are checked at the same time. A proof is accepted or rejected exactly as with
+    /// [StarkMachine::verify], and an invalid proof reports the error of its first invalid shard.
+    #[instrument("verify_batched", level = "info", skip_all)]
+    pub fn verify_batched(
+        &self,
+        vk: &StarkVerifyingKey<SC>,
+        proof: &MachineProof<SC>,
+        challenger: &mut SC::Challenger,
+    ) -> Result<(), MachineVerificationError<SC>>
+    where
+        SC::Challenger: Clone + Sync,
+        A: for<'a> Air<VerifierConstraintFolder<'a, SC>> + Sync,
+        Com<SC>: Send + Sync,
+        OpeningProof<SC>: Send + Sync,
+        MachineVerificationError<SC>: Send,
+    {
+        self.observe_shards(vk, proof, challenger);
+
+        if proof.shard_proofs.is_empty() {
+            return Err(MachineVerificationError::EmptyProof);
+        }
+
+        let challenger = &*challenger;
+        tracing::debug_span!("verify shard proofs").in_scope(|| {
+            proof
+                .shard_proofs
+                .par_iter()
+                .map(|shard_proof| self.verify_shard(vk, challenger, shard_proof))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .collect::<Result<(), _>>()
+        })?;
+
+        self.verify_cumulative_sum(proof)
+    }
+
+    /// Observes the preprocessed commitment, then the commitment and public values of each shard.
+    fn observe_shards(
+        &self,
+        vk: &StarkVerifyingKey<SC>,
+        proof: &MachineProof<SC>,
+        challenger: &mut SC::Challenger,
+    ) {
+        vk.observe_into(challenger);
+        tracing::debug_span!("observe challenges for all shards").in_scope(|| {
+            proof.shard_proofs.iter().for_each(|proof| {
+                challenger.observe(proof.commitment.main_commit.clone());
+                challenger.observe_slice(&proof.public_values[0..self.num_pv_elts()]);
+            });
+        });
+    }
+
+    /// Verifies a shard proof against a copy of the challenger that observed every shard.
+    fn verify_shard(
+        &self,
+        vk: &StarkVerifyingKey<SC>,
+        challenger: &SC::Challenger,
+        shard_proof: &ShardProof<SC>,
+    ) -> Result<(), MachineVerificationError<SC>>
+    where
+        SC::Challenger: Clone,
+        A: for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+    {
+        let chips = self
+            .shard_chips_ordered(&shard_proof.chip_ordering)
+            .collect::<Vec<_>>();
+        Verifier::verify_shard(
+            &self.config,
+            vk,
+            &chips,
+            &mut challenger.clone(),
+            shard_proof,
+        )
+        .map_err(MachineVerificationError::InvalidSegmentProof)
+    }
+
+    /// Verifies that the cumulative sums of the shards add up to 0.
+    fn verify_cumulative_sum(
+        &self,
+        proof: &MachineProof<SC>,
+    ) -> Result<(), MachineVerificationError<SC>> {
         tracing::debug_span!("verify cumulative sum is 0").in_scope(|| {
             let mut sum = SC::Challenge::zero();
             for proof in proof.shard_proofs.iter() {
@@ -454,15 +574,23 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
 
         // If the cumulative sum is not zero, debug the interactions.
         if !cumulative_sum.is_zero() {
-            debug_interactions_with_all_chips::<SC, A>(
-                self,
-                pk,
-                &shards,
-                InteractionKind::all_kinds(),
-            );
-            panic!("Cumulative sum is not zero");
+            let report = self.debug_interactions(pk, &shards);
+            panic!("Cumulative sum is not zero: {}", report);
         }
     }
+
+    /// Pairs the interaction messages sent and received by the chips over `shards`, reporting the
+    /// unmatched ones with the chips and rows sending or receiving them.
+    pub fn debug_interactions(
+        &self,
+        pk: &StarkProvingKey<SC>,
+        shards: &[A::Record],
+    ) -> InteractionReport<Val<SC>>
+    where
+        SC::Val: PrimeField32,
+    {
+        interaction_report(self, pk, shards, DEFAULT_MAX_REPORTED_INTERACTIONS)
+    }
 }
 
 pub enum MachineVerificationError<SC: StarkGenericConfig> {
@@ -475,6 +603,7 @@ pub enum MachineVerificationError<SC: StarkGenericConfig> {
     InvalidPublicValues(&'static str),
     TooManyShards,
     InvalidChipOccurence(String),
+    UnsupportedFeatures { bits: u32 },
 }
 
 impl<SC: StarkGenericConfig> Debug for MachineVerificationError<SC> {
@@ -507,6 +636,9 @@ impl<SC: StarkGenericConfig> Debug for MachineVerificationError<SC> {
             MachineVerificationError::InvalidChipOccurence(s) => {
                 write!(f, "Invalid chip occurence: {}", s)
             }
+            MachineVerificationError::UnsupportedFeatures { bits } => {
+                write!(f, "Unsupported features: {:#x}", bits)
+            }
         }
     }
 }
@@ -523,6 +655,11 @@ impl<SC: StarkGenericConfig> std::error::Error for MachineVerificationError<SC>
 #[allow(non_snake_case)]
 pub mod tests {
 
+    use p3_air::BaseAir;
+    use p3_field::AbstractField;
+
+    use crate::air::MachineAir;
+    use crate::air::MAX_PACKING_WIDTH;
     use crate::io::SP1Stdin;
     use crate::runtime::tests::fibonacci_program;
     use crate::runtime::tests::simple_memory_program;
@@ -531,6 +668,7 @@ pub mod tests {
     use crate::runtime::Instruction;
     use crate::runtime::Opcode;
     use crate::runtime::Program;
+    use crate::stark::MachineProof;
     use crate::stark::RiscvAir;
     use crate::stark::StarkProvingKey;
     use crate::stark::StarkVerifyingKey;
@@ -548,6 +686,31 @@ pub mod tests {
         run_test(program).unwrap();
     }
 
+    #[test]
+    fn test_tiny_program_trace_heights() {
+        setup_logger();
+        let program = simple_program();
+        let proof = run_test(program).unwrap();
+
+        // The traces of a tiny program are padded to the floor of their chip only, which is below
+        // the former global floor of 16 rows.
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let shard_proof = &proof.shard_proofs[0];
+        let mut area = 0;
+        let mut area_at_former_floor = 0;
+        for (chip, values) in machine
+            .shard_chips_ordered(&shard_proof.chip_ordering)
+            .zip(shard_proof.opened_values.chips.iter())
+        {
+            let height = 1 << values.log_degree;
+            assert!(height >= chip.min_trace_height());
+            let width = chip.preprocessed_width() + chip.width();
+            area += width * height;
+            area_at_former_floor += width * height.max(MAX_PACKING_WIDTH);
+        }
+        assert!(area < area_at_former_floor);
+    }
+
     #[test]
     fn test_shift_prove() {
         utils::setup_logger();
@@ -694,6 +857,42 @@ pub mod tests {
         prove(program, &stdin, BabyBearPoseidon2::new(), opts).unwrap();
     }
 
+    #[test]
+    fn test_fibonacci_verify_batched() {
+        setup_logger();
+
+        let program = fibonacci_program();
+        let mut opts = SP1CoreOpts::default();
+        opts.shard_size = 1024;
+        let (mut proof, _) = prove(
+            program.clone(),
+            &SP1Stdin::new(),
+            BabyBearPoseidon2::new(),
+            opts,
+        )
+        .unwrap();
+        assert!(proof.shard_proofs.len() > 2);
+
+        let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+        let (_, vk) = machine.setup(&program);
+        let verify = |proof: &MachineProof<BabyBearPoseidon2>| {
+            let mut challenger = machine.config().challenger();
+            machine.verify(&vk, proof, &mut challenger)
+        };
+        let verify_batched = |proof: &MachineProof<BabyBearPoseidon2>| {
+            let mut challenger = machine.config().challenger();
+            machine.verify_batched(&vk, proof, &mut challenger)
+        };
+        verify(&proof).unwrap();
+        verify_batched(&proof).unwrap();
+
+        // Both paths reject a tampered shard with the same error.
+        proof.shard_proofs[1].opened_values.chips[0].main.local[0] += AbstractField::one();
+        let sequential = verify(&proof).unwrap_err();
+        let batched = verify_batched(&proof).unwrap_err();
+        assert_eq!(format!("{:?}", sequential), format!("{:?}", batched));
+    }
+
     #[test]
     fn test_fibonacci_prove_batch() {
         setup_logger();
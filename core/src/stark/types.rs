@@ -24,6 +24,7 @@ pub struct ShardMainData<SC: StarkGenericConfig> {
     pub traces: Vec<RowMajorMatrix<Val<SC>>>,
     pub main_commit: Com<SC>,
     pub main_data: PcsProverData<SC>,
+    #[serde(serialize_with = "crate::utils::serialize_sorted")]
     pub chip_ordering: HashMap<String, usize>,
     pub index: usize,
     pub public_values: Vec<SC::Val>,
@@ -124,7 +125,7 @@ pub struct ShardOpenedValues<T: Serialize> {
 /// The maximum number of elements that can be stored in the public values vec.  Both SP1 and recursive
 /// proofs need to pad their public_values vec to this length.  This is required since the recursion
 /// verification program expects the public values vec to be fixed length.
-pub const PROOF_MAX_NUM_PVS: usize = 241;
+pub const PROOF_MAX_NUM_PVS: usize = 314;
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
@@ -132,6 +133,7 @@ pub struct ShardProof<SC: StarkGenericConfig> {
     pub commitment: ShardCommitment<Com<SC>>,
     pub opened_values: ShardOpenedValues<Challenge<SC>>,
     pub opening_proof: OpeningProof<SC>,
+    #[serde(serialize_with = "crate::utils::serialize_sorted")]
     pub chip_ordering: HashMap<String, usize>,
     pub public_values: Vec<Val<SC>>,
 }
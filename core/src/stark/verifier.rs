@@ -53,6 +53,19 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> Verifier<SC, A> {
 
         assert_eq!(chips.len(), opened_values.chips.len());
 
+        // The preprocessed chips of the shard are exactly the ones with a preprocessed trace in the
+        // key: the preprocessed openings of any other chip would not be checked against the key.
+        for chip in chips {
+            if chip.preprocessed_width() > 0 && !vk.chip_ordering.contains_key(&chip.name()) {
+                return Err(VerificationError::PreprocessedChipMismatch(chip.name()));
+            }
+        }
+        for (name, _, _) in vk.chip_information.iter() {
+            if !chip_ordering.contains_key(name) {
+                return Err(VerificationError::PreprocessedChipMismatch(name.clone()));
+            }
+        }
+
         let log_degrees = opened_values
             .chips
             .iter()
@@ -417,6 +430,9 @@ pub enum VerificationError<SC: StarkGenericConfig> {
     /// The shape of the opening arguments is invalid.
     OpeningShapeError(String, OpeningShapeError),
     MissingCpuChip,
+    /// A chip of the shard has preprocessed columns without a preprocessed trace in the key, or a
+    /// chip with a preprocessed trace in the key is missing from the shard.
+    PreprocessedChipMismatch(String),
 }
 
 impl Debug for OpeningShapeError {
@@ -483,6 +499,9 @@ impl<SC: StarkGenericConfig> Debug for VerificationError<SC> {
             VerificationError::MissingCpuChip => {
                 write!(f, "Missing CPU chip")
             }
+            VerificationError::PreprocessedChipMismatch(chip) => {
+                write!(f, "Preprocessed chip mismatch on chip {}", chip)
+            }
         }
     }
 }
@@ -502,6 +521,13 @@ impl<SC: StarkGenericConfig> Display for VerificationError<SC> {
             VerificationError::MissingCpuChip => {
                 write!(f, "Missing CPU chip in shard")
             }
+            VerificationError::PreprocessedChipMismatch(chip) => {
+                write!(
+                    f,
+                    "Chip {} is in the shard or the key, but not in both, as a preprocessed chip",
+                    chip
+                )
+            }
         }
     }
 }
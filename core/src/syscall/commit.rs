@@ -1,3 +1,4 @@
+use crate::features::{CONFIG_DIGEST_FEATURE, INPUT_DIGEST_FEATURE};
 use crate::runtime::{Syscall, SyscallContext};
 
 /// A syscall that commits a word of the public values digest.
@@ -43,3 +44,111 @@ impl Syscall for SyscallCommitDeferred {
         None
     }
 }
+
+/// Commit to one word within the digest of the input buffers read by the program. Takes in an
+/// index and a word.
+pub struct SyscallCommitInput;
+
+impl SyscallCommitInput {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallCommitInput {
+    fn execute(&self, ctx: &mut SyscallContext, word_idx: u32, word: u32) -> Option<u32> {
+        let rt = &mut ctx.rt;
+
+        rt.record.public_values.input_digest[word_idx as usize] = word;
+        rt.record.public_values.features |= INPUT_DIGEST_FEATURE;
+
+        None
+    }
+}
+
+/// Commit to one word within the digest of the config the program was run with. Takes in an index
+/// and a word.
+pub struct SyscallCommitConfig;
+
+impl SyscallCommitConfig {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallCommitConfig {
+    fn execute(&self, ctx: &mut SyscallContext, word_idx: u32, word: u32) -> Option<u32> {
+        let rt = &mut ctx.rt;
+
+        rt.record.public_values.config_digest[word_idx as usize] = word;
+        rt.record.public_values.features |= CONFIG_DIGEST_FEATURE;
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        features::{CONFIG_DIGEST_FEATURE, INPUT_DIGEST_FEATURE},
+        io::SP1Stdin,
+        runtime::{Instruction, Opcode, Program, Runtime, SyscallCode},
+        utils::{run_test, SP1CoreOpts},
+    };
+
+    /// A program that commits `digest` with the COMMIT_INPUT or COMMIT_CONFIG `syscall`, as a
+    /// guest does on halt.
+    fn commit_digest_program(syscall: SyscallCode, digest: [u8; 32]) -> Program {
+        let mut instructions = Vec::new();
+        for (i, word) in digest.chunks_exact(4).enumerate() {
+            let word = u32::from_le_bytes(word.try_into().unwrap());
+            instructions.extend([
+                Instruction::new(Opcode::ADD, 10, 0, i as u32, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, word, false, true),
+                Instruction::new(Opcode::ADD, 5, 0, syscall as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_commit_input() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&42u32);
+        let program = commit_digest_program(SyscallCode::COMMIT_INPUT, stdin.digest());
+
+        let mut runtime = Runtime::new(program.clone(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let digest_words = runtime.record.public_values.input_digest;
+        let digest_bytes = digest_words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(digest_bytes, stdin.digest());
+        assert_eq!(runtime.record.public_values.features, INPUT_DIGEST_FEATURE);
+
+        run_test(program).unwrap();
+    }
+
+    #[test]
+    fn test_commit_config() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write_config("chain_id", &1u64.to_le_bytes());
+        let program = commit_digest_program(SyscallCode::COMMIT_CONFIG, stdin.config_digest());
+
+        let mut runtime = Runtime::new(program.clone(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let public_values = runtime.record.public_values;
+        let digest_bytes = public_values
+            .config_digest
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(digest_bytes, stdin.config_digest());
+        assert_eq!(public_values.input_digest, [0; 8]);
+        assert_eq!(public_values.features, CONFIG_DIGEST_FEATURE);
+
+        run_test(program).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use crate::runtime::{Syscall, SyscallContext};
+
+/// SyscallConfigLen returns the length of the encoded config map.
+pub struct SyscallConfigLen;
+
+impl SyscallConfigLen {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallConfigLen {
+    fn execute(&self, ctx: &mut SyscallContext, _arg1: u32, _arg2: u32) -> Option<u32> {
+        Some(ctx.rt.state.config_stream.len() as u32)
+    }
+}
+
+/// SyscallConfigRead writes the encoded config map into uninitialized memory at `ptr`.
+pub struct SyscallConfigRead;
+
+impl SyscallConfigRead {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallConfigRead {
+    fn execute(&self, ctx: &mut SyscallContext, ptr: u32, len: u32) -> Option<u32> {
+        assert!(
+            !ctx.rt.unconstrained,
+            "config read should not be used in a unconstrained block"
+        );
+        let config = &ctx.rt.state.config_stream;
+        assert_eq!(config.len() as u32, len, "config read length mismatch");
+        assert_eq!(ptr % 4, 0, "config read address not aligned to 4 bytes");
+        let words = config
+            .chunks(4)
+            .map(|chunk| {
+                let mut bytes = [0u8; 4];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(bytes)
+            })
+            .collect::<Vec<_>>();
+        for (i, word) in words.into_iter().enumerate() {
+            ctx.rt.touch_uninitialized(ptr + 4 * i as u32);
+            ctx.rt
+                .state
+                .uninitialized_memory
+                .entry(ptr + 4 * i as u32)
+                .and_modify(|_| panic!("config read address is initialized already"))
+                .or_insert(word);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        io::SP1Stdin,
+        runtime::{Instruction, Opcode, Program, Register, Runtime, SyscallCode},
+        utils::SP1CoreOpts,
+    };
+
+    #[test]
+    fn test_config_syscalls() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write_config("chain_id", &1u64.to_le_bytes());
+        stdin.write_config("fork", b"cancun");
+        let config = stdin.config_bytes();
+
+        let ptr = 0x1000;
+        let instructions = vec![
+            // CONFIG_LEN
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::CONFIG_LEN as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 11, 0, 5, false, false),
+            // CONFIG_READ(ptr, len)
+            Instruction::new(Opcode::ADD, 10, 0, ptr, false, true),
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::CONFIG_READ as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            // Load the first word (the entry count).
+            Instruction::new(Opcode::LW, 12, 10, 0, false, true),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.write_config(&config);
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.register(Register::X11), config.len() as u32);
+        assert_eq!(runtime.register(Register::X12), 2);
+    }
+}
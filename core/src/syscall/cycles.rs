@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use crate::io::SpanCycles;
+use crate::runtime::{Syscall, SyscallContext};
+
+/// SyscallCycleCount returns the number of cycles executed so far, modulo 2^32.
+pub struct SyscallCycleCount;
+
+impl SyscallCycleCount {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallCycleCount {
+    fn execute(&self, ctx: &mut SyscallContext, _arg1: u32, _arg2: u32) -> Option<u32> {
+        let rt = &mut ctx.rt;
+        let clk = rt.state.global_clk;
+        if !rt.unconstrained {
+            rt.span_measurements
+                .record_cycle_count(rt.cycle_tracker.keys(), clk);
+        }
+        Some(clk as u32)
+    }
+}
+
+/// The cycle counts read by the program inside its cycle-tracker spans.
+///
+/// A span committed with `sp1_zkvm::io::commit_span_cycles` is expected to be the difference of the
+/// first and last cycle counts read between its `cycle-tracker-start` and `cycle-tracker-end`
+/// markers. The runtime checks the committed value against that difference, and only warns if they
+/// differ, since the cycle count is not constrained and the committed value is what gets proven.
+#[derive(Debug, Clone, Default)]
+pub struct SpanMeasurements {
+    /// The first and last cycle counts read in each open span.
+    open: HashMap<String, (u64, u64)>,
+    /// The cycles between the first and last cycle counts read in the last run of each span.
+    closed: HashMap<String, u64>,
+    /// The committed spans that differ from the runtime's measurement.
+    pub mismatches: Vec<SpanCyclesMismatch>,
+}
+
+/// A committed span whose cycles differ from the runtime's measurement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanCyclesMismatch {
+    pub name: String,
+    pub committed: u64,
+    pub measured: u64,
+}
+
+impl SpanMeasurements {
+    /// Records a cycle count read at `clk` in each of the `open` spans.
+    pub(crate) fn record_cycle_count<'a>(
+        &mut self,
+        open: impl Iterator<Item = &'a String>,
+        clk: u64,
+    ) {
+        for name in open {
+            self.open
+                .entry(name.clone())
+                .and_modify(|(_, last)| *last = clk)
+                .or_insert((clk, clk));
+        }
+    }
+
+    /// Closes the span `name`, keeping its measurement if the program read the cycle count in it.
+    pub(crate) fn close(&mut self, name: &str) {
+        if let Some((first, last)) = self.open.remove(name) {
+            self.closed.insert(name.to_string(), last - first);
+        }
+    }
+
+    /// Checks a span committed by the program against the measurement of the span of that name.
+    pub(crate) fn check(&mut self, span: &SpanCycles) {
+        let Some(&measured) = self.closed.get(&span.name) else {
+            log::debug!("committed span {} was not measured", span.name);
+            return;
+        };
+        // The program measures its spans with cycle counts modulo 2^32.
+        if span.cycles != measured && span.cycles != measured % (1 << 32) {
+            log::warn!(
+                "committed span {} has {} cycles, but the runtime measured {}",
+                span.name,
+                span.cycles,
+                measured
+            );
+            self.mismatches.push(SpanCyclesMismatch {
+                name: span.name.clone(),
+                committed: span.cycles,
+                measured,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::SpanCyclesMismatch;
+    use crate::{
+        io::{SP1PublicValues, SpanCycles},
+        runtime::{Instruction, Opcode, Program, Runtime, SyscallCode},
+        utils::{run_test_core, setup_logger, SP1CoreOpts},
+    };
+
+    const START: u32 = 0x1000;
+    const END: u32 = 0x1100;
+    const SPAN: u32 = 0x1200;
+
+    /// Writes `bytes` at `addr` in the memory image.
+    fn store(image: &mut BTreeMap<u32, u32>, addr: u32, bytes: &[u8]) {
+        for (i, chunk) in bytes.chunks(4).enumerate() {
+            let mut word = [0u8; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            image.insert(addr + 4 * i as u32, u32::from_le_bytes(word));
+        }
+    }
+
+    /// The instructions writing the `len` bytes at `addr` to `fd`.
+    fn write(fd: u32, addr: u32, len: u32) -> Vec<Instruction> {
+        vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::WRITE as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, fd, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, addr, false, true),
+            Instruction::new(Opcode::ADD, 12, 0, len, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]
+    }
+
+    /// The instructions reading the cycle count into `rd`.
+    fn cycle_count(rd: u32) -> Vec<Instruction> {
+        vec![
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::CYCLE_COUNT as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, rd, 5, 0, false, false),
+        ]
+    }
+
+    /// A program measuring a span named "span" with the cycle count, and committing the measured
+    /// cycles plus `error`, as `sp1_zkvm::io::commit_span_cycles` would.
+    fn span_program(error: u32) -> Program {
+        let start = b"cycle-tracker-start: span";
+        let end = b"cycle-tracker-end: span";
+        let span = SpanCycles {
+            name: "span".to_string(),
+            cycles: 0,
+        }
+        .encode();
+        let mut image = BTreeMap::new();
+        store(&mut image, START, start);
+        store(&mut image, END, end);
+        store(&mut image, SPAN, &span);
+
+        let mut instructions = write(1, START, start.len() as u32);
+        instructions.extend(cycle_count(20));
+        instructions.extend((0..3).map(|_| Instruction::new(Opcode::ADD, 7, 7, 1, false, true)));
+        instructions.extend(cycle_count(21));
+        instructions.extend(write(1, END, end.len() as u32));
+        // The cycles are the last 8 bytes of the encoding, and fit in their low word.
+        let cycles_addr = SPAN + span.len() as u32 - 8;
+        instructions.extend([
+            Instruction::new(Opcode::SUB, 22, 21, 20, false, false),
+            Instruction::new(Opcode::ADD, 22, 22, error, false, true),
+            Instruction::new(Opcode::SW, 22, 0, cycles_addr, false, true),
+        ]);
+        instructions.extend(write(3, SPAN, span.len() as u32));
+
+        let mut program = Program::new(instructions, 0, 0);
+        program.memory_image = image;
+        program
+    }
+
+    #[test]
+    fn test_commit_span_cycles() {
+        setup_logger();
+        let mut runtime = Runtime::new(span_program(0), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert!(runtime.span_measurements.mismatches.is_empty());
+
+        // The second read is six instructions after the first one.
+        let mut public_values = SP1PublicValues::from(&runtime.state.public_values_stream);
+        let span = public_values.read_span_cycles();
+        assert_eq!(span.name, "span");
+        assert_eq!(span.cycles, 6);
+
+        run_test_core(runtime).unwrap();
+    }
+
+    #[test]
+    fn test_commit_span_cycles_mismatch() {
+        let mut runtime = Runtime::new(span_program(1), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        // The committed value is kept, and the mismatch is only reported.
+        let mut public_values = SP1PublicValues::from(&runtime.state.public_values_stream);
+        assert_eq!(public_values.read_span_cycles().cycles, 7);
+        assert_eq!(
+            runtime.span_measurements.mismatches,
+            vec![SpanCyclesMismatch {
+                name: "span".to_string(),
+                committed: 7,
+                measured: 6,
+            }]
+        );
+    }
+}
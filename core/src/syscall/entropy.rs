@@ -0,0 +1,194 @@
+use crate::runtime::{Syscall, SyscallContext};
+
+/// SyscallEntropyLen returns the number of bytes left in the entropy pool.
+pub struct SyscallEntropyLen;
+
+impl SyscallEntropyLen {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallEntropyLen {
+    fn execute(&self, ctx: &mut SyscallContext, _arg1: u32, _arg2: u32) -> Option<u32> {
+        let state = &ctx.rt.state;
+        Some((state.entropy_stream.len() - state.entropy_stream_ptr) as u32)
+    }
+}
+
+/// SyscallEntropyRead writes the next `len` bytes of the entropy pool into uninitialized memory at
+/// `ptr` and marks them as consumed.
+pub struct SyscallEntropyRead;
+
+impl SyscallEntropyRead {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallEntropyRead {
+    fn execute(&self, ctx: &mut SyscallContext, ptr: u32, len: u32) -> Option<u32> {
+        assert!(
+            !ctx.rt.unconstrained,
+            "entropy read should not be used in a unconstrained block"
+        );
+        assert_eq!(ptr % 4, 0, "entropy read address not aligned to 4 bytes");
+        let start = ctx.rt.state.entropy_stream_ptr;
+        let remaining = ctx.rt.state.entropy_stream.len() - start;
+        if len as usize > remaining {
+            panic!(
+                "entropy exhausted: requested {} bytes, {} remaining",
+                len, remaining
+            );
+        }
+        let end = start + len as usize;
+        let words = ctx.rt.state.entropy_stream[start..end]
+            .chunks(4)
+            .map(|chunk| {
+                let mut bytes = [0u8; 4];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(bytes)
+            })
+            .collect::<Vec<_>>();
+        for (i, word) in words.into_iter().enumerate() {
+            ctx.rt.touch_uninitialized(ptr + 4 * i as u32);
+            ctx.rt
+                .state
+                .uninitialized_memory
+                .entry(ptr + 4 * i as u32)
+                .and_modify(|_| panic!("entropy read address is initialized already"))
+                .or_insert(word);
+        }
+        ctx.rt.state.entropy_stream_ptr = end;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+
+    use crate::{
+        air::{PublicValues, Word},
+        io::SP1Stdin,
+        runtime::{Instruction, Opcode, Program, Register, Runtime, SyscallCode},
+        utils::{prove, setup_logger, BabyBearPoseidon2, SP1CoreOpts},
+    };
+
+    const UUID_PTR: u32 = 0x1000;
+
+    /// Sets the `(and, or)` masks of a byte of the UUID at `UUID_PTR`.
+    fn mask_byte(offset: u32, and: u32, or: u32) -> Vec<Instruction> {
+        vec![
+            Instruction::new(Opcode::LBU, 12, 14, offset, false, true),
+            Instruction::new(Opcode::AND, 12, 12, and, false, true),
+            Instruction::new(Opcode::OR, 12, 12, or, false, true),
+            Instruction::new(Opcode::SB, 12, 14, offset, false, true),
+        ]
+    }
+
+    /// A program generating a v4 UUID from 16 bytes of entropy and committing it as the first four
+    /// words of the public values digest. The entropy left before and after the read is kept in
+    /// x20 and x21.
+    fn uuid_program() -> Program {
+        let entropy_len = |rd| {
+            vec![
+                Instruction::new(
+                    Opcode::ADD,
+                    5,
+                    0,
+                    SyscallCode::ENTROPY_LEN as u32,
+                    false,
+                    true,
+                ),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                Instruction::new(Opcode::ADD, rd, 0, 5, false, false),
+            ]
+        };
+        let mut instructions = entropy_len(20);
+        instructions.extend([
+            Instruction::new(Opcode::ADD, 10, 0, UUID_PTR, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 16, false, true),
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::ENTROPY_READ as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 14, 0, UUID_PTR, false, true),
+        ]);
+        // Version 4 in the high nibble of byte 6, variant 0b10 in the high bits of byte 8.
+        instructions.extend(mask_byte(6, 0x0f, 0x40));
+        instructions.extend(mask_byte(8, 0x3f, 0x80));
+        for i in 0..4 {
+            instructions.extend([
+                Instruction::new(Opcode::LW, 11, 14, 4 * i, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, i, false, true),
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::COMMIT as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        instructions.extend(entropy_len(21));
+        Program::new(instructions, 0, 0)
+    }
+
+    fn uuid_from_words(words: &[u32]) -> [u8; 16] {
+        let mut uuid = [0u8; 16];
+        for (chunk, word) in uuid.chunks_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        uuid
+    }
+
+    #[test]
+    fn test_entropy_uuid() {
+        setup_logger();
+        let seed = (0..32u8).collect::<Vec<_>>();
+        let mut stdin = SP1Stdin::new();
+        stdin.write_entropy(&seed);
+
+        let mut runtime = Runtime::new(uuid_program(), SP1CoreOpts::default());
+        runtime.write_entropy(&stdin.entropy);
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X20), 32);
+        assert_eq!(runtime.register(Register::X21), 16);
+
+        let uuid = uuid_from_words(&runtime.record.public_values.committed_value_digest[..4]);
+        let mut expected = [0u8; 16];
+        expected.copy_from_slice(&seed[..16]);
+        expected[6] = (expected[6] & 0x0f) | 0x40;
+        expected[8] = (expected[8] & 0x3f) | 0x80;
+        assert_eq!(uuid, expected);
+        assert_eq!(runtime.input_digest(), stdin.digest_with_entropy(16));
+
+        // The proving pass is checked to read the same entropy as the execution pass, and proves
+        // the same UUID.
+        let (proof, _) = prove(
+            uuid_program(),
+            &stdin,
+            BabyBearPoseidon2::new(),
+            SP1CoreOpts::default(),
+        )
+        .unwrap();
+        let public_values = PublicValues::<Word<BabyBear>, BabyBear>::from_vec(
+            proof.shard_proofs[0].public_values.clone(),
+        );
+        let proven = public_values
+            .committed_value_digest
+            .iter()
+            .map(Word::to_u32)
+            .collect::<Vec<_>>();
+        assert_eq!(uuid_from_words(&proven[..4]), uuid);
+    }
+
+    #[test]
+    #[should_panic(expected = "entropy exhausted: requested 16 bytes, 8 remaining")]
+    fn test_entropy_exhausted() {
+        let mut runtime = Runtime::new(uuid_program(), SP1CoreOpts::default());
+        runtime.write_entropy(&[0; 8]);
+        runtime.run().unwrap();
+    }
+}
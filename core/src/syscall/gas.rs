@@ -0,0 +1,61 @@
+use crate::runtime::{Syscall, SyscallContext};
+
+/// SyscallGasUsed returns the low word of the gas used so far if `arg1` is 0, and its high word
+/// otherwise.
+pub struct SyscallGasUsed;
+
+impl SyscallGasUsed {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallGasUsed {
+    fn execute(&self, ctx: &mut SyscallContext, half: u32, _arg2: u32) -> Option<u32> {
+        let gas_used = ctx.rt.state.gas_used;
+        if half == 0 {
+            Some(gas_used as u32)
+        } else {
+            Some((gas_used >> 32) as u32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::{
+        GasSchedule, Instruction, Opcode, Program, Register, Runtime, SyscallCode,
+    };
+    use crate::utils::{run_test_core, setup_logger, SP1CoreOpts};
+
+    /// The instructions reading the `half` word of the gas used into `rd`.
+    fn gas_used(rd: u32, half: u32) -> Vec<Instruction> {
+        vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::GAS_USED as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, half, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, rd, 5, 0, false, false),
+        ]
+    }
+
+    #[test]
+    fn test_gas_used_syscall() {
+        setup_logger();
+        let mut instructions = gas_used(20, 0);
+        instructions.extend(gas_used(21, 1));
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        // The gas read includes the `ecall` reading it.
+        let schedule = GasSchedule::default();
+        let add = schedule.opcode_weight(Opcode::ADD);
+        let ecall = schedule.opcode_weight(Opcode::ECALL);
+        assert_eq!(runtime.register(Register::X20) as u64, 2 * add + ecall);
+        assert_eq!(runtime.register(Register::X21), 0);
+        assert_eq!(runtime.state.gas_used, 6 * add + 2 * ecall);
+        assert_eq!(runtime.report.gas_used, runtime.state.gas_used);
+
+        run_test_core(runtime).unwrap();
+    }
+}
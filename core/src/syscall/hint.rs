@@ -57,6 +57,7 @@ impl Syscall for SyscallHintRead {
 
             // Save the data into runtime state so the runtime will use the desired data instead of
             // 0 when first reading/writing from this address.
+            ctx.rt.touch_uninitialized(ptr + i);
             ctx.rt
                 .state
                 .uninitialized_memory
@@ -74,7 +75,7 @@ mod tests {
 
     use crate::{
         io::SP1Stdin,
-        runtime::Program,
+        runtime::{Program, Runtime},
         utils::{prove, setup_logger, BabyBearPoseidon2, SP1CoreOpts},
     };
 
@@ -98,4 +99,22 @@ mod tests {
         let config = BabyBearPoseidon2::new();
         prove(program, &stdin, config, SP1CoreOpts::default()).unwrap();
     }
+
+    #[test]
+    fn test_input_digest_covers_read_buffers() {
+        let data = vec![7u8; 100];
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&data);
+        stdin.write_vec(data);
+        // The program only reads the first two buffers.
+        stdin.write_vec(vec![1, 2, 3]);
+
+        let mut runtime = Runtime::new(Program::from(HINT_IO_ELF), SP1CoreOpts::default());
+        runtime.write_vecs(&stdin.buffer);
+        assert_eq!(runtime.input_digest(), SP1Stdin::new().digest());
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.input_digest(), stdin.digest_first(2));
+        assert_ne!(runtime.input_digest(), stdin.digest());
+    }
 }
@@ -0,0 +1,117 @@
+use crate::runtime::{Syscall, SyscallContext};
+
+/// SyscallMemoryLimit returns the guest memory footprint limit in bytes, saturated to `u32::MAX`.
+pub struct SyscallMemoryLimit;
+
+impl SyscallMemoryLimit {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallMemoryLimit {
+    fn execute(&self, ctx: &mut SyscallContext, _arg1: u32, _arg2: u32) -> Option<u32> {
+        Some(ctx.rt.max_memory.min(u32::MAX as u64) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runtime::{ExecutionError, Instruction, Opcode, Program, Register, Runtime, SyscallCode},
+        utils::SP1CoreOpts,
+    };
+
+    const LIMIT: u64 = 16 << 20;
+
+    #[test]
+    fn test_memory_limit_syscall() {
+        let instructions = vec![
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::MEMORY_LIMIT as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 11, 0, 5, false, false),
+        ];
+        let program = Program::new(instructions.clone(), 0, 0);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X11), u32::MAX);
+
+        let program = Program::new(instructions, 0, 0);
+        let opts = SP1CoreOpts {
+            max_memory: LIMIT,
+            ..SP1CoreOpts::default()
+        };
+        let mut runtime = Runtime::new(program, opts);
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X11), LIMIT as u32);
+    }
+
+    #[test]
+    fn test_greedy_allocation_hits_memory_limit() {
+        // Touch a new word on every iteration, forever:
+        //     addi x10, x0, 0x1000
+        // loop:
+        //     sw x0, 0(x10)
+        //     addi x10, x10, 4
+        //     jal x0, loop
+        const LIMIT: u64 = 1 << 20;
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 10, 0, 0x1000, false, true),
+            Instruction::new(Opcode::SW, 0, 10, 0, false, true),
+            Instruction::new(Opcode::ADD, 10, 10, 4, false, true),
+            Instruction::new(Opcode::JAL, 0, -8i32 as u32, 0, true, true),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let opts = SP1CoreOpts {
+            max_memory: LIMIT,
+            ..SP1CoreOpts::default()
+        };
+        let mut runtime = Runtime::new(program, opts);
+
+        let attempted_addr = match runtime.run() {
+            Err(ExecutionError::MemoryLimitExceeded {
+                limit,
+                attempted_addr,
+            }) => {
+                assert_eq!(limit, LIMIT);
+                attempted_addr
+            }
+            result => panic!("expected a memory limit error, got {:?}", result),
+        };
+
+        // Execution stopped at the first word past the limit.
+        assert_eq!(runtime.memory_footprint(), LIMIT + 4);
+        assert_eq!(runtime.register(Register::X10), attempted_addr);
+        assert!(runtime.record.cpu_events.len() as u64 <= 3 * LIMIT / 4 + 1);
+    }
+
+    #[test]
+    fn test_sparse_accesses_within_memory_limit() {
+        // Words far apart in the address space only count for the memory they touch:
+        //     li x10, 0x70000000
+        //     sw x0, 0(x10)
+        //     li x10, 0x7ff00000
+        //     sw x0, 0(x10)
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 10, 0, 0x7000_0000, false, true),
+            Instruction::new(Opcode::SW, 0, 10, 0, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x7ff0_0000, false, true),
+            Instruction::new(Opcode::SW, 0, 10, 0, false, true),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let opts = SP1CoreOpts {
+            max_memory: LIMIT,
+            ..SP1CoreOpts::default()
+        };
+        let mut runtime = Runtime::new(program, opts);
+        runtime.run().unwrap();
+        assert!(runtime.memory_footprint() < LIMIT);
+    }
+}
@@ -1,14 +1,26 @@
 mod commit;
+mod config;
+mod cycles;
+mod entropy;
+mod gas;
 mod halt;
 mod hint;
+mod memory;
 pub mod precompiles;
+mod shard;
 mod unconstrained;
 mod verify;
 mod write;
 
 pub use commit::*;
+pub use config::*;
+pub use cycles::*;
+pub use entropy::*;
+pub use gas::*;
 pub use halt::*;
 pub use hint::*;
+pub use memory::*;
+pub use shard::*;
 pub use unconstrained::*;
 pub use verify::*;
 pub use write::*;
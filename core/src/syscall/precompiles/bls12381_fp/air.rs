@@ -0,0 +1,458 @@
+use crate::air::{MachineAir, Polynomial, SP1AirBuilder};
+use crate::bytes::event::ByteRecord;
+use crate::memory::{value_as_limbs, MemoryReadCols, MemoryWriteCols};
+use crate::operations::field::field_op::{FieldOpCols, FieldOperation};
+use crate::operations::field::params::{FieldParameters, NumWords};
+use crate::operations::field::params::{Limbs, NumLimbs};
+use crate::runtime::{ExecutionRecord, Program, Syscall, SyscallCode};
+use crate::runtime::{MemoryReadRecord, MemoryWriteRecord};
+use crate::syscall::precompiles::SyscallContext;
+use crate::utils::ec::weierstrass::bls12_381::Bls12381BaseField;
+use crate::utils::{
+    bytes_to_words_le_vec, limbs_from_access, limbs_from_prev_access, pad_rows,
+    words_to_bytes_le_vec,
+};
+use generic_array::GenericArray;
+use num::BigUint;
+use num::Zero;
+use p3_air::AirBuilder;
+use p3_air::{Air, BaseAir};
+use p3_field::AbstractField;
+use p3_field::PrimeField32;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use serde::{Deserialize, Serialize};
+use sp1_derive::AlignedBorrow;
+use std::borrow::{Borrow, BorrowMut};
+use std::mem::size_of;
+use typenum::Unsigned;
+
+/// The number of columns in the Bls12381FpOpCols.
+const NUM_COLS: usize = size_of::<Bls12381FpOpCols<u8>>();
+
+type WordsFieldElement = <Bls12381BaseField as NumWords>::WordsFieldElement;
+const WORDS_FIELD_ELEMENT: usize = WordsFieldElement::USIZE;
+
+/// An addition, subtraction, multiplication or division of two elements of the BLS12-381 base
+/// field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bls12381FpOpEvent {
+    pub lookup_id: usize,
+    pub shard: u32,
+    pub channel: u32,
+    pub clk: u32,
+    pub op: FieldOperation,
+    pub x_ptr: u32,
+    pub x: Vec<u32>,
+    pub y_ptr: u32,
+    pub y: Vec<u32>,
+    pub x_memory_records: Vec<MemoryWriteRecord>,
+    pub y_memory_records: Vec<MemoryReadRecord>,
+}
+
+/// A chip computing `x op y mod p` for the 381-bit modulus `p` of the BLS12-381 base field, where
+/// `op` is an addition, a subtraction, a multiplication or a division.
+///
+/// The operations are evaluated on every row, and the selectors pick the one written back to `x`.
+/// A division is the multiplication of `x` by the inverse of `y`, which is hinted and checked by
+/// `inverse * y = 1`. Each syscall takes a single row and no extra cycles. Emulated field
+/// operations are the building block of the BLS12-381 pairing, which is too expensive to compute
+/// with RISC-V instructions.
+#[derive(Default)]
+pub struct Bls12381FpOpChip;
+
+impl Bls12381FpOpChip {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+/// The syscall of one of the operations of the [Bls12381FpOpChip].
+pub struct Bls12381FpOpSyscall {
+    op: FieldOperation,
+}
+
+impl Bls12381FpOpSyscall {
+    pub const fn new(op: FieldOperation) -> Self {
+        Self { op }
+    }
+}
+
+/// A set of columns for the Bls12381FpOp operation.
+#[derive(Debug, Clone, AlignedBorrow)]
+#[repr(C)]
+pub struct Bls12381FpOpCols<T> {
+    /// The shard number of the syscall.
+    pub shard: T,
+
+    /// The byte lookup channel.
+    pub channel: T,
+
+    /// The clock cycle of the syscall.
+    pub clk: T,
+
+    /// The nonce of the operation.
+    pub nonce: T,
+
+    /// The pointer to the first input, which is overwritten with the result.
+    pub x_ptr: T,
+
+    /// The pointer to the second input.
+    pub y_ptr: T,
+
+    // Memory columns.
+    // x_memory is written to with the result, which is why it is of type MemoryWriteCols.
+    pub x_memory: GenericArray<MemoryWriteCols<T>, WordsFieldElement>,
+    pub y_memory: GenericArray<MemoryReadCols<T>, WordsFieldElement>,
+
+    /// Which operation the row computes.
+    pub is_add: T,
+    pub is_sub: T,
+    pub is_mul: T,
+    pub is_div: T,
+    pub is_real: T,
+
+    // The results of the operations.
+    pub add: FieldOpCols<T, Bls12381BaseField>,
+    pub sub: FieldOpCols<T, Bls12381BaseField>,
+    pub mul: FieldOpCols<T, Bls12381BaseField>,
+
+    /// The inverse of y on a division row, and zero otherwise.
+    pub inverse: FieldOpCols<T, Bls12381BaseField>,
+
+    /// The product of x and [Self::inverse], the result of a division.
+    pub div: FieldOpCols<T, Bls12381BaseField>,
+}
+
+impl<F: PrimeField32> MachineAir<F> for Bls12381FpOpChip {
+    type Record = ExecutionRecord;
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "Bls12381FpOp".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let mut new_byte_lookup_events = Vec::new();
+        let mut rows = input
+            .bls12381_fp_op_events
+            .iter()
+            .map(|event| {
+                let mut row: [F; NUM_COLS] = [F::zero(); NUM_COLS];
+                let cols: &mut Bls12381FpOpCols<F> = row.as_mut_slice().borrow_mut();
+
+                // Decode the field elements.
+                let x = BigUint::from_bytes_le(&words_to_bytes_le_vec(&event.x));
+                let y = BigUint::from_bytes_le(&words_to_bytes_le_vec(&event.y));
+
+                // Assign basic values to the columns.
+                cols.shard = F::from_canonical_u32(event.shard);
+                cols.channel = F::from_canonical_u32(event.channel);
+                cols.clk = F::from_canonical_u32(event.clk);
+                cols.x_ptr = F::from_canonical_u32(event.x_ptr);
+                cols.y_ptr = F::from_canonical_u32(event.y_ptr);
+                cols.is_add = F::from_bool(event.op == FieldOperation::Add);
+                cols.is_sub = F::from_bool(event.op == FieldOperation::Sub);
+                cols.is_mul = F::from_bool(event.op == FieldOperation::Mul);
+                cols.is_div = F::from_bool(event.op == FieldOperation::Div);
+                cols.is_real = F::one();
+
+                // Populate memory columns.
+                for i in 0..WORDS_FIELD_ELEMENT {
+                    cols.x_memory[i].populate(
+                        event.channel,
+                        event.x_memory_records[i],
+                        &mut new_byte_lookup_events,
+                    );
+                    cols.y_memory[i].populate(
+                        event.channel,
+                        event.y_memory_records[i],
+                        &mut new_byte_lookup_events,
+                    );
+                }
+
+                // Populate the operations, only the selected one is written back. The byte range
+                // checks are only sent for the selected operation.
+                let mut ignored_events = Vec::new();
+                for (op_cols, op) in [
+                    (&mut cols.add, FieldOperation::Add),
+                    (&mut cols.sub, FieldOperation::Sub),
+                    (&mut cols.mul, FieldOperation::Mul),
+                ] {
+                    let blu_events = if op == event.op {
+                        &mut new_byte_lookup_events
+                    } else {
+                        &mut ignored_events
+                    };
+                    op_cols.populate(blu_events, event.shard, event.channel, &x, &y, op);
+                }
+
+                // The inverse of y is `1 / y` on a division row, and `0 / y` otherwise.
+                let is_div = event.op == FieldOperation::Div;
+                let blu_events = if is_div {
+                    &mut new_byte_lookup_events
+                } else {
+                    &mut ignored_events
+                };
+                let inverse = cols.inverse.populate(
+                    blu_events,
+                    event.shard,
+                    event.channel,
+                    &BigUint::from(is_div as u32),
+                    &y,
+                    FieldOperation::Div,
+                );
+                cols.div.populate(
+                    blu_events,
+                    event.shard,
+                    event.channel,
+                    &x,
+                    &inverse,
+                    FieldOperation::Mul,
+                );
+
+                row
+            })
+            .collect::<Vec<_>>();
+        output.add_byte_lookup_events(new_byte_lookup_events);
+
+        pad_rows(
+            &mut rows,
+            <Self as MachineAir<F>>::min_trace_height(self),
+            || {
+                let mut row: [F; NUM_COLS] = [F::zero(); NUM_COLS];
+                let cols: &mut Bls12381FpOpCols<F> = row.as_mut_slice().borrow_mut();
+                let zero = BigUint::zero();
+                for (op_cols, op) in [
+                    (&mut cols.add, FieldOperation::Add),
+                    (&mut cols.sub, FieldOperation::Sub),
+                    (&mut cols.mul, FieldOperation::Mul),
+                    (&mut cols.inverse, FieldOperation::Div),
+                    (&mut cols.div, FieldOperation::Mul),
+                ] {
+                    op_cols.populate(&mut vec![], 0, 0, &zero, &zero, op);
+                }
+                row
+            },
+        );
+
+        // Convert the trace to a row major matrix.
+        let mut trace =
+            RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_COLS);
+
+        // Write the nonces to the trace.
+        for i in 0..trace.height() {
+            let cols: &mut Bls12381FpOpCols<F> =
+                trace.values[i * NUM_COLS..(i + 1) * NUM_COLS].borrow_mut();
+            cols.nonce = F::from_canonical_usize(i);
+        }
+
+        trace
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        !shard.bls12381_fp_op_events.is_empty()
+    }
+}
+
+impl Syscall for Bls12381FpOpSyscall {
+    fn num_extra_cycles(&self) -> u32 {
+        0
+    }
+
+    fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
+        let op = self.op;
+        let x_ptr = arg1;
+        let y_ptr = arg2;
+        assert!(x_ptr % 4 == 0, "x_ptr must be word aligned");
+        assert!(y_ptr % 4 == 0, "y_ptr must be word aligned");
+
+        // First read the words for the x value. We can read a slice_unsafe here because we write
+        // the computed result to x later.
+        let x = rt.slice_unsafe(x_ptr, WORDS_FIELD_ELEMENT);
+
+        // Read the y value.
+        let (y_memory_records, y) = rt.mr_slice(y_ptr, WORDS_FIELD_ELEMENT);
+
+        // The operands must be reduced, which the constraints of a subtraction rely on.
+        let modulus = Bls12381BaseField::modulus();
+        let fp_x = BigUint::from_bytes_le(&words_to_bytes_le_vec(&x));
+        let fp_y = BigUint::from_bytes_le(&words_to_bytes_le_vec(&y));
+        assert!(
+            fp_x < modulus && fp_y < modulus,
+            "BLS12-381 Fp operands must be reduced"
+        );
+
+        let result = match op {
+            FieldOperation::Add => (fp_x + fp_y) % &modulus,
+            FieldOperation::Sub => (&modulus + fp_x - fp_y) % &modulus,
+            FieldOperation::Mul => (fp_x * fp_y) % &modulus,
+            FieldOperation::Div => {
+                assert!(!fp_y.is_zero(), "BLS12-381 Fp division by zero");
+                (fp_x * fp_y.modpow(&(&modulus - 2u32), &modulus)) % &modulus
+            }
+        };
+        let mut result_bytes = result.to_bytes_le();
+        result_bytes.resize(WORDS_FIELD_ELEMENT * 4, 0u8);
+        let result = bytes_to_words_le_vec(&result_bytes);
+
+        // Write the result to x and keep track of the memory records.
+        let x_memory_records = rt.mw_slice(x_ptr, &result);
+
+        let lookup_id = rt.syscall_lookup_id;
+        let shard = rt.current_shard();
+        let channel = rt.current_channel();
+        let clk = rt.clk;
+        rt.record_mut()
+            .bls12381_fp_op_events
+            .push(Bls12381FpOpEvent {
+                lookup_id,
+                shard,
+                channel,
+                clk,
+                op,
+                x_ptr,
+                x,
+                y_ptr,
+                y,
+                x_memory_records,
+                y_memory_records,
+            });
+
+        None
+    }
+}
+
+impl<F> BaseAir<F> for Bls12381FpOpChip {
+    fn width(&self) -> usize {
+        NUM_COLS
+    }
+}
+
+impl<AB> Air<AB> for Bls12381FpOpChip
+where
+    AB: SP1AirBuilder,
+    Limbs<AB::Var, <Bls12381BaseField as NumLimbs>::Limbs>: Copy,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &Bls12381FpOpCols<AB::Var> = (*local).borrow();
+        let next = main.row_slice(1);
+        let next: &Bls12381FpOpCols<AB::Var> = (*next).borrow();
+
+        // Constrain the incrementing nonce.
+        builder.when_first_row().assert_zero(local.nonce);
+        builder
+            .when_transition()
+            .assert_eq(local.nonce + AB::Expr::one(), next.nonce);
+
+        // At most one operation is selected, and the row is real if one is.
+        builder.assert_bool(local.is_add);
+        builder.assert_bool(local.is_sub);
+        builder.assert_bool(local.is_mul);
+        builder.assert_bool(local.is_div);
+        builder.assert_bool(local.is_real);
+        builder.assert_eq(
+            local.is_add + local.is_sub + local.is_mul + local.is_div,
+            local.is_real,
+        );
+
+        // The value of x is stored in the "prev_value" of the x_memory, since we write to it later.
+        let x_limbs = limbs_from_prev_access(&local.x_memory);
+        let y_limbs = limbs_from_access(&local.y_memory);
+
+        // Evaluate the operations on x and y. Each one is satisfied on every row, so their byte
+        // range checks are only sent for the selected operation.
+        for (op_cols, op, is_op) in [
+            (&local.add, FieldOperation::Add, local.is_add),
+            (&local.sub, FieldOperation::Sub, local.is_sub),
+            (&local.mul, FieldOperation::Mul, local.is_mul),
+        ] {
+            op_cols.eval(
+                builder,
+                &x_limbs,
+                &y_limbs,
+                op,
+                local.shard,
+                local.channel,
+                is_op,
+            );
+        }
+
+        // Check that `inverse * y = is_div`, so that y is invertible on a division row, and divide
+        // by multiplying x with the inverse.
+        let is_div = Polynomial::from_coefficients(&[local.is_div.into()]);
+        local.inverse.eval(
+            builder,
+            &is_div,
+            &y_limbs,
+            FieldOperation::Div,
+            local.shard,
+            local.channel,
+            local.is_div,
+        );
+        local.div.eval(
+            builder,
+            &x_limbs,
+            &local.inverse.result,
+            FieldOperation::Mul,
+            local.shard,
+            local.channel,
+            local.is_div,
+        );
+
+        // Assert that the result of the selected operation is written to x_memory.
+        for (op_cols, is_op) in [
+            (&local.add, local.is_add),
+            (&local.sub, local.is_sub),
+            (&local.mul, local.is_mul),
+            (&local.div, local.is_div),
+        ] {
+            builder
+                .when(is_op)
+                .assert_all_eq(op_cols.result, value_as_limbs(&local.x_memory));
+        }
+
+        // Read and write x.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.channel,
+            local.clk.into(),
+            local.x_ptr,
+            &local.x_memory,
+            local.is_real,
+        );
+
+        // Read y.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.channel,
+            local.clk.into(),
+            local.y_ptr,
+            &local.y_memory,
+            local.is_real,
+        );
+
+        // Receive the arguments, with the syscall of the selected operation.
+        let syscall_id = local.is_add
+            * AB::F::from_canonical_u32(SyscallCode::BLS12381_FP_ADD.syscall_id())
+            + local.is_sub * AB::F::from_canonical_u32(SyscallCode::BLS12381_FP_SUB.syscall_id())
+            + local.is_mul * AB::F::from_canonical_u32(SyscallCode::BLS12381_FP_MUL.syscall_id())
+            + local.is_div * AB::F::from_canonical_u32(SyscallCode::BLS12381_FP_DIV.syscall_id());
+        builder.receive_syscall(
+            local.shard,
+            local.channel,
+            local.clk,
+            local.nonce,
+            syscall_id,
+            local.x_ptr,
+            local.y_ptr,
+            local.is_real,
+        );
+    }
+}
@@ -0,0 +1,121 @@
+mod air;
+
+pub use air::*;
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use crate::operations::field::params::FieldParameters;
+    use crate::runtime::{Instruction, Opcode, Program, Runtime, SyscallCode};
+    use crate::utils::ec::weierstrass::bls12_381::Bls12381BaseField;
+    use crate::utils::{self, bytes_to_words_le_vec, run_test, words_to_bytes_le_vec, SP1CoreOpts};
+
+    const X_PTR: u32 = 100;
+    const Y_PTR: u32 = 200;
+
+    /// A program storing `x` and `y` in memory, then applying each syscall of `ops` to them.
+    fn fp_op_program(x: &BigUint, y: &BigUint, ops: &[SyscallCode]) -> Program {
+        let mut instructions = vec![];
+        for (ptr, value) in [(X_PTR, x), (Y_PTR, y)] {
+            let mut bytes = value.to_bytes_le();
+            bytes.resize(48, 0);
+            for (i, word) in bytes_to_words_le_vec(&bytes).into_iter().enumerate() {
+                instructions.extend(vec![
+                    Instruction::new(Opcode::ADD, 29, 0, word, false, true),
+                    Instruction::new(Opcode::ADD, 30, 0, ptr + i as u32 * 4, false, true),
+                    Instruction::new(Opcode::SW, 29, 30, 0, false, true),
+                ]);
+            }
+        }
+        for op in ops {
+            instructions.extend(vec![
+                Instruction::new(Opcode::ADD, 5, 0, *op as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, X_PTR, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, Y_PTR, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_bls12381_fp_ops() {
+        let p = Bls12381BaseField::modulus();
+        let x = &p - 1u32;
+        let y = BigUint::from(5u32);
+        let ops = [
+            SyscallCode::BLS12381_FP_ADD,
+            SyscallCode::BLS12381_FP_SUB,
+            SyscallCode::BLS12381_FP_MUL,
+            SyscallCode::BLS12381_FP_DIV,
+        ];
+
+        // Check the value of x after each operation.
+        let expected = [BigUint::from(4u32), &p - 1u32, &p - 5u32, &p - 1u32];
+        for (i, expected) in expected.iter().enumerate() {
+            let mut runtime =
+                Runtime::new(fp_op_program(&x, &y, &ops[..i + 1]), SP1CoreOpts::default());
+            runtime.run().unwrap();
+            let words = (0..12)
+                .map(|j| runtime.word(X_PTR + j * 4))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                &BigUint::from_bytes_le(&words_to_bytes_le_vec(&words)),
+                expected
+            );
+        }
+
+        utils::setup_logger();
+        run_test(fp_op_program(&x, &y, &ops)).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "BLS12-381 Fp operands must be reduced")]
+    fn test_bls12381_fp_unreduced_operand() {
+        let p = Bls12381BaseField::modulus();
+        let program = fp_op_program(&p, &BigUint::from(1u32), &[SyscallCode::BLS12381_FP_ADD]);
+        Runtime::new(program, SP1CoreOpts::default()).run().unwrap();
+    }
+
+    #[test]
+    fn test_bls12381_fp_div() {
+        // Divide a few elements by each other, including by 1 and p - 1.
+        let p = Bls12381BaseField::modulus();
+        let values = [
+            BigUint::from(1u32),
+            BigUint::from(2u32),
+            &p - 1u32,
+            BigUint::from_bytes_le(&[0xab; 40]),
+        ];
+        for x in &values {
+            for y in &values {
+                let program = fp_op_program(x, y, &[SyscallCode::BLS12381_FP_DIV]);
+                let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+                runtime.run().unwrap();
+                let words = (0..12)
+                    .map(|j| runtime.word(X_PTR + j * 4))
+                    .collect::<Vec<_>>();
+                let result = BigUint::from_bytes_le(&words_to_bytes_le_vec(&words));
+                assert_eq!((result * y) % &p, *x);
+            }
+        }
+
+        // Dividing zero is allowed.
+        let x = BigUint::from(0u32);
+        let y = &p - 2u32;
+        utils::setup_logger();
+        run_test(fp_op_program(&x, &y, &[SyscallCode::BLS12381_FP_DIV])).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "BLS12-381 Fp division by zero")]
+    fn test_bls12381_fp_div_by_zero() {
+        let program = fp_op_program(
+            &BigUint::from(1u32),
+            &BigUint::from(0u32),
+            &[SyscallCode::BLS12381_FP_DIV],
+        );
+        Runtime::new(program, SP1CoreOpts::default()).run().unwrap();
+    }
+}
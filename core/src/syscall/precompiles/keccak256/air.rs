@@ -97,17 +97,13 @@ where
         // Verify that local.a values are equal to the memory values in the 0 and 23rd rows of each cycle
         // Memory values are 32 bit values (encoded as 4 8-bit columns).
         // local.a values are 64 bit values (encoded as 4 16-bit columns).
-        let expr_2_pow_8 = AB::Expr::from_canonical_u32(2u32.pow(8));
         for i in 0..STATE_SIZE as u32 {
             // Interpret u32 memory words as u16 limbs
             let least_sig_word = local.state_mem[(i * 2) as usize].value();
             let most_sig_word = local.state_mem[(i * 2 + 1) as usize].value();
-            let memory_limbs = [
-                least_sig_word[0] + least_sig_word[1] * expr_2_pow_8.clone(),
-                least_sig_word[2] + least_sig_word[3] * expr_2_pow_8.clone(),
-                most_sig_word[0] + most_sig_word[1] * expr_2_pow_8.clone(),
-                most_sig_word[2] + most_sig_word[3] * expr_2_pow_8.clone(),
-            ];
+            let [limb_0, limb_1] = least_sig_word.to_u16_limbs::<AB>();
+            let [limb_2, limb_3] = most_sig_word.to_u16_limbs::<AB>();
+            let memory_limbs = [limb_0, limb_1, limb_2, limb_3];
 
             let y_idx = i / 5;
             let x_idx = i % 5;
@@ -40,8 +40,22 @@ impl KeccakPermuteChip {
 
 #[cfg(test)]
 pub mod permute_tests {
-    use crate::runtime::SyscallCode;
-    use crate::utils::{run_test, SP1CoreOpts};
+    use core::borrow::BorrowMut;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::dense::RowMajorMatrix;
+
+    use super::columns::{KeccakMemCols, NUM_KECCAK_MEM_COLS};
+    use super::KeccakPermuteChip;
+    use crate::air::{MachineAir, Word};
+    use crate::memory::MemoryCols;
+    use crate::runtime::{ExecutionRecord, SyscallCode};
+    use crate::stark::StarkGenericConfig;
+    use crate::utils::{
+        run_test, uni_stark_prove as prove, uni_stark_verify as verify, BabyBearPoseidon2,
+        SP1CoreOpts,
+    };
     use crate::{
         runtime::{Instruction, Opcode, Program, Runtime},
         utils::{self, tests::KECCAK_PERMUTE_ELF},
@@ -94,4 +108,39 @@ pub mod permute_tests {
         let program = Program::from(KECCAK_PERMUTE_ELF);
         run_test(program).unwrap();
     }
+
+    /// Generates the trace of the keccak chip for [keccak_permute_program], with `tamper` applied
+    /// to its first row, and returns whether the chip's constraints accept it.
+    fn keccak_trace_is_accepted(tamper: impl FnOnce(&mut KeccakMemCols<BabyBear>)) -> bool {
+        let mut runtime = Runtime::new(keccak_permute_program(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let chip = KeccakPermuteChip::new();
+        let mut trace: RowMajorMatrix<BabyBear> =
+            chip.generate_trace(&runtime.record, &mut ExecutionRecord::default());
+        tamper(trace.values[..NUM_KECCAK_MEM_COLS].borrow_mut());
+
+        // In debug builds, the prover panics on unsatisfied constraints.
+        let config = BabyBearPoseidon2::new();
+        catch_unwind(AssertUnwindSafe(|| {
+            let proof = prove(&config, &chip, &mut config.challenger(), trace);
+            verify(&config, &chip, &mut config.challenger(), &proof)
+        }))
+        .map_or(false, |result| result.is_ok())
+    }
+
+    #[test]
+    fn test_keccak_permute_rejects_swapped_word_order() {
+        utils::setup_logger();
+        assert!(keccak_trace_is_accepted(|_| {}));
+
+        // Reading the first state word with its bytes in big-endian order must be caught by the
+        // constraints tying the memory words to the keccak state limbs.
+        assert!(!keccak_trace_is_accepted(|cols| {
+            let value = *cols.state_mem[0].value();
+            assert_ne!(value.to_le_bytes(), value.to_be_bytes());
+            let swapped = Word::from_be_bytes(value.to_le_bytes());
+            *cols.state_mem[0].prev_value_mut() = swapped;
+            *cols.state_mem[0].value_mut() = swapped;
+        }));
+    }
 }
@@ -7,7 +7,7 @@ use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::{ParallelIterator, ParallelSlice};
 
 use crate::bytes::event::ByteRecord;
-use crate::{runtime::Program, stark::MachineRecord};
+use crate::{runtime::Program, stark::MachineRecord, utils::trace_chunk_size};
 
 use crate::{air::MachineAir, runtime::ExecutionRecord};
 
@@ -30,7 +30,7 @@ impl<F: PrimeField32> MachineAir<F> for KeccakPermuteChip {
         output: &mut ExecutionRecord,
     ) -> RowMajorMatrix<F> {
         let num_events = input.keccak_permute_events.len();
-        let chunk_size = std::cmp::max(num_events / num_cpus::get(), 1);
+        let chunk_size = trace_chunk_size(num_events);
 
         // Use par_chunks to generate the trace in parallel.
         let rows_and_records = (0..num_events)
@@ -131,10 +131,9 @@ impl<F: PrimeField32> MachineAir<F> for KeccakPermuteChip {
         }
 
         let nb_rows = rows.len();
-        let mut padded_nb_rows = nb_rows.next_power_of_two();
-        if padded_nb_rows == 2 || padded_nb_rows == 1 {
-            padded_nb_rows = 4;
-        }
+        let padded_nb_rows = nb_rows
+            .next_power_of_two()
+            .max(<Self as MachineAir<F>>::min_trace_height(self));
         if padded_nb_rows > nb_rows {
             let dummy_keccak_rows = generate_trace_rows::<F>(vec![[0; STATE_SIZE]]);
             let mut dummy_rows = Vec::new();
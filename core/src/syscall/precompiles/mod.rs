@@ -1,3 +1,4 @@
+pub mod bls12381_fp;
 pub mod edwards;
 pub mod keccak256;
 pub mod sha256;
@@ -602,13 +602,7 @@ impl ShaCompressChip {
         let add_operands = [
             local.a, local.b, local.c, local.d, local.e, local.f, local.g, local.h,
         ];
-        let zero = AB::Expr::zero();
-        let mut filtered_operand = Word([zero.clone(), zero.clone(), zero.clone(), zero]);
-        for (i, operand) in local.octet.iter().zip(add_operands.iter()) {
-            for j in 0..4 {
-                filtered_operand.0[j] += *i * operand.0[j];
-            }
-        }
+        let filtered_operand = Word::select::<AB>(&local.octet, &add_operands);
 
         builder
             .when(local.is_finalize)
@@ -254,7 +254,11 @@ impl<F: PrimeField32> MachineAir<F> for ShaCompressChip {
 
         let num_real_rows = rows.len();
 
-        pad_rows(&mut rows, || [F::zero(); NUM_SHA_COMPRESS_COLS]);
+        pad_rows(
+            &mut rows,
+            <Self as MachineAir<F>>::min_trace_height(self),
+            || [F::zero(); NUM_SHA_COMPRESS_COLS],
+        );
 
         // Set the octet_num and octect columns for the padded rows.
         let mut octet_num = 0;
@@ -144,10 +144,9 @@ impl<F: PrimeField32> MachineAir<F> for ShaExtendChip {
         output.add_byte_lookup_events(new_byte_lookup_events);
 
         let nb_rows = rows.len();
-        let mut padded_nb_rows = nb_rows.next_power_of_two();
-        if padded_nb_rows == 2 || padded_nb_rows == 1 {
-            padded_nb_rows = 4;
-        }
+        let padded_nb_rows = nb_rows
+            .next_power_of_two()
+            .max(<Self as MachineAir<F>>::min_trace_height(self));
         for i in nb_rows..padded_nb_rows {
             let mut row = [F::zero(); NUM_SHA_EXTEND_COLS];
             let cols: &mut ShaExtendCols<F> = row.as_mut_slice().borrow_mut();
@@ -193,17 +193,21 @@ impl<F: PrimeField32> MachineAir<F> for Uint256MulChip {
             output.append(&mut record);
         }
 
-        pad_rows(&mut rows, || {
-            let mut row: [F; NUM_COLS] = [F::zero(); NUM_COLS];
-            let cols: &mut Uint256MulCols<F> = row.as_mut_slice().borrow_mut();
-
-            let x = BigUint::zero();
-            let y = BigUint::zero();
-            cols.output
-                .populate(&mut vec![], 0, 0, &x, &y, FieldOperation::Mul);
-
-            row
-        });
+        pad_rows(
+            &mut rows,
+            <Self as MachineAir<F>>::min_trace_height(self),
+            || {
+                let mut row: [F; NUM_COLS] = [F::zero(); NUM_COLS];
+                let cols: &mut Uint256MulCols<F> = row.as_mut_slice().borrow_mut();
+
+                let x = BigUint::zero();
+                let y = BigUint::zero();
+                cols.output
+                    .populate(&mut vec![], 0, 0, &x, &y, FieldOperation::Mul);
+
+                row
+            },
+        );
 
         // Convert the trace to a row major matrix.
         let mut trace =
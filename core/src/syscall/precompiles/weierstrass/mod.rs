@@ -5,3 +5,142 @@ mod weierstrass_double;
 pub use weierstrass_add::*;
 pub use weierstrass_decompress::*;
 pub use weierstrass_double::*;
+
+use crate::runtime::{ExecutionRecord, SyscallCode};
+use crate::syscall::precompiles::{ECAddEvent, ECDoubleEvent};
+use crate::utils::ec::weierstrass::bls12_381::Bls12381;
+use crate::utils::ec::weierstrass::bn254::Bn254;
+use crate::utils::ec::weierstrass::secp256k1::Secp256k1;
+use crate::utils::ec::weierstrass::secp256r1::Secp256r1;
+use crate::utils::ec::weierstrass::WeierstrassParameters;
+use crate::utils::ec::EllipticCurve;
+
+/// A short Weierstrass curve with add and double precompiles, constrained by the generic
+/// [WeierstrassAddAssignChip] and [WeierstrassDoubleAssignChip].
+///
+/// The arithmetic of the chips only depends on the modulus and the limb count of the base field
+/// and on the coefficients `a` and `b`, given by [WeierstrassParameters]. This gives the rest: the
+/// syscalls of the precompiles and where their events are recorded. The names of the chips are
+/// the name of the curve followed by `AddAssign` or `DoubleAssign`.
+pub trait CurveParams: EllipticCurve + WeierstrassParameters {
+    /// The syscall adding two points.
+    const ADD_SYSCALL: SyscallCode;
+
+    /// The syscall doubling a point.
+    const DOUBLE_SYSCALL: SyscallCode;
+
+    /// The add events of a record.
+    fn add_events(record: &ExecutionRecord) -> &Vec<ECAddEvent>;
+
+    fn add_events_mut(record: &mut ExecutionRecord) -> &mut Vec<ECAddEvent>;
+
+    /// The double events of a record.
+    fn double_events(record: &ExecutionRecord) -> &Vec<ECDoubleEvent>;
+
+    fn double_events_mut(record: &mut ExecutionRecord) -> &mut Vec<ECDoubleEvent>;
+}
+
+macro_rules! impl_curve_params {
+    ($curve:ty, $add:ident, $double:ident, $add_events:ident, $double_events:ident) => {
+        impl CurveParams for $curve {
+            const ADD_SYSCALL: SyscallCode = SyscallCode::$add;
+            const DOUBLE_SYSCALL: SyscallCode = SyscallCode::$double;
+
+            fn add_events(record: &ExecutionRecord) -> &Vec<ECAddEvent> {
+                &record.$add_events
+            }
+
+            fn add_events_mut(record: &mut ExecutionRecord) -> &mut Vec<ECAddEvent> {
+                &mut record.$add_events
+            }
+
+            fn double_events(record: &ExecutionRecord) -> &Vec<ECDoubleEvent> {
+                &record.$double_events
+            }
+
+            fn double_events_mut(record: &mut ExecutionRecord) -> &mut Vec<ECDoubleEvent> {
+                &mut record.$double_events
+            }
+        }
+    };
+}
+
+impl_curve_params!(
+    Secp256k1,
+    SECP256K1_ADD,
+    SECP256K1_DOUBLE,
+    secp256k1_add_events,
+    secp256k1_double_events
+);
+impl_curve_params!(
+    Secp256r1,
+    SECP256R1_ADD,
+    SECP256R1_DOUBLE,
+    secp256r1_add_events,
+    secp256r1_double_events
+);
+impl_curve_params!(
+    Bn254,
+    BN254_ADD,
+    BN254_DOUBLE,
+    bn254_add_events,
+    bn254_double_events
+);
+impl_curve_params!(
+    Bls12381,
+    BLS12381_ADD,
+    BLS12381_DOUBLE,
+    bls12381_add_events,
+    bls12381_double_events
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::{Instruction, Opcode, Program, Runtime, SyscallCode};
+    use crate::utils::ec::weierstrass::secp256r1::Secp256r1;
+    use crate::utils::ec::AffinePoint;
+    use crate::utils::{run_test, setup_logger, SP1CoreOpts};
+
+    const P_PTR: u32 = 0x1000;
+    const Q_PTR: u32 = 0x2000;
+
+    fn ecall(code: SyscallCode, arg1: u32, arg2: u32) -> Vec<Instruction> {
+        vec![
+            Instruction::new(Opcode::ADD, 5, 0, code as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, arg1, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, arg2, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]
+    }
+
+    /// A program setting `p` to `2 * (p + q)` with the P-256 precompiles.
+    fn secp256r1_program(p: &AffinePoint<Secp256r1>, q: &AffinePoint<Secp256r1>) -> Program {
+        let mut instructions = ecall(SyscallCode::SECP256R1_ADD, P_PTR, Q_PTR);
+        instructions.extend(ecall(SyscallCode::SECP256R1_DOUBLE, P_PTR, 0));
+        let mut program = Program::new(instructions, 0, 0);
+        for (ptr, point) in [(P_PTR, p), (Q_PTR, q)] {
+            for (i, word) in point.to_words_le().into_iter().enumerate() {
+                program.memory_image.insert(ptr + 4 * i as u32, word);
+            }
+        }
+        program
+    }
+
+    #[test]
+    fn test_secp256r1_add_double() {
+        setup_logger();
+        let g = Secp256r1::generator();
+        let (p, q) = (g.sw_double(), g);
+        let program = secp256r1_program(&p, &q);
+
+        let mut runtime = Runtime::new(program.clone(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let words = (0..16)
+            .map(|i| runtime.word(P_PTR + 4 * i))
+            .collect::<Vec<_>>();
+        let expected = p.sw_add(&q).sw_double();
+        assert_eq!(AffinePoint::<Secp256r1>::from_words_le(&words), expected);
+
+        run_test(program).unwrap();
+    }
+}
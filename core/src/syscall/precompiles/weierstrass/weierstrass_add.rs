@@ -16,6 +16,7 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use sp1_derive::AlignedBorrow;
 
+use super::CurveParams;
 use crate::air::MachineAir;
 use crate::air::SP1AirBuilder;
 use crate::bytes::event::ByteRecord;
@@ -29,12 +30,9 @@ use crate::operations::field::params::{FieldParameters, Limbs, NumLimbs, NumWord
 use crate::runtime::ExecutionRecord;
 use crate::runtime::Program;
 use crate::runtime::Syscall;
-use crate::runtime::SyscallCode;
 use crate::syscall::precompiles::create_ec_add_event;
 use crate::syscall::precompiles::SyscallContext;
-use crate::utils::ec::weierstrass::WeierstrassParameters;
 use crate::utils::ec::AffinePoint;
-use crate::utils::ec::CurveType;
 use crate::utils::ec::EllipticCurve;
 use crate::utils::{limbs_from_prev_access, pad_rows};
 
@@ -74,15 +72,10 @@ pub struct WeierstrassAddAssignChip<E> {
     _marker: PhantomData<E>,
 }
 
-impl<E: EllipticCurve> Syscall for WeierstrassAddAssignChip<E> {
+impl<E: CurveParams> Syscall for WeierstrassAddAssignChip<E> {
     fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
         let event = create_ec_add_event::<E>(rt, arg1, arg2);
-        match E::CURVE_TYPE {
-            CurveType::Secp256k1 => rt.record_mut().secp256k1_add_events.push(event),
-            CurveType::Bn254 => rt.record_mut().bn254_add_events.push(event),
-            CurveType::Bls12381 => rt.record_mut().bls12381_add_events.push(event),
-            _ => panic!("Unsupported curve"),
-        }
+        E::add_events_mut(rt.record_mut()).push(event);
         None
     }
 
@@ -200,19 +193,12 @@ impl<E: EllipticCurve> WeierstrassAddAssignChip<E> {
     }
 }
 
-impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
-    for WeierstrassAddAssignChip<E>
-{
+impl<F: PrimeField32, E: CurveParams> MachineAir<F> for WeierstrassAddAssignChip<E> {
     type Record = ExecutionRecord;
     type Program = Program;
 
     fn name(&self) -> String {
-        match E::CURVE_TYPE {
-            CurveType::Secp256k1 => "Secp256k1AddAssign".to_string(),
-            CurveType::Bn254 => "Bn254AddAssign".to_string(),
-            CurveType::Bls12381 => "Bls12381AddAssign".to_string(),
-            _ => panic!("Unsupported curve"),
-        }
+        format!("{}AddAssign", E::CURVE_TYPE)
     }
 
     fn generate_trace(
@@ -220,12 +206,7 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
         input: &ExecutionRecord,
         output: &mut ExecutionRecord,
     ) -> RowMajorMatrix<F> {
-        let events = match E::CURVE_TYPE {
-            CurveType::Secp256k1 => &input.secp256k1_add_events,
-            CurveType::Bn254 => &input.bn254_add_events,
-            CurveType::Bls12381 => &input.bls12381_add_events,
-            _ => panic!("Unsupported curve"),
-        };
+        let events = E::add_events(input);
 
         let mut rows = Vec::new();
 
@@ -284,23 +265,27 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
         }
         output.add_byte_lookup_events(new_byte_lookup_events);
 
-        pad_rows(&mut rows, || {
-            let mut row = vec![F::zero(); num_weierstrass_add_cols::<E::BaseField>()];
-            let cols: &mut WeierstrassAddAssignCols<F, E::BaseField> =
-                row.as_mut_slice().borrow_mut();
-            let zero = BigUint::zero();
-            Self::populate_field_ops(
-                &mut vec![],
-                0,
-                0,
-                cols,
-                zero.clone(),
-                zero.clone(),
-                zero.clone(),
-                zero,
-            );
-            row
-        });
+        pad_rows(
+            &mut rows,
+            <Self as MachineAir<F>>::min_trace_height(self),
+            || {
+                let mut row = vec![F::zero(); num_weierstrass_add_cols::<E::BaseField>()];
+                let cols: &mut WeierstrassAddAssignCols<F, E::BaseField> =
+                    row.as_mut_slice().borrow_mut();
+                let zero = BigUint::zero();
+                Self::populate_field_ops(
+                    &mut vec![],
+                    0,
+                    0,
+                    cols,
+                    zero.clone(),
+                    zero.clone(),
+                    zero.clone(),
+                    zero,
+                );
+                row
+            },
+        );
 
         // Convert the trace to a row major matrix.
         let mut trace = RowMajorMatrix::new(
@@ -321,12 +306,7 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
     }
 
     fn included(&self, shard: &Self::Record) -> bool {
-        match E::CURVE_TYPE {
-            CurveType::Secp256k1 => !shard.secp256k1_add_events.is_empty(),
-            CurveType::Bn254 => !shard.bn254_add_events.is_empty(),
-            CurveType::Bls12381 => !shard.bls12381_add_events.is_empty(),
-            _ => panic!("Unsupported curve"),
-        }
+        !E::add_events(shard).is_empty()
     }
 }
 
@@ -336,7 +316,7 @@ impl<F, E: EllipticCurve> BaseAir<F> for WeierstrassAddAssignChip<E> {
     }
 }
 
-impl<AB, E: EllipticCurve> Air<AB> for WeierstrassAddAssignChip<E>
+impl<AB, E: CurveParams> Air<AB> for WeierstrassAddAssignChip<E>
 where
     AB: SP1AirBuilder,
     Limbs<AB::Var, <E::BaseField as NumLimbs>::Limbs>: Copy,
@@ -495,16 +475,7 @@ where
         );
 
         // Fetch the syscall id for the curve type.
-        let syscall_id_felt = match E::CURVE_TYPE {
-            CurveType::Secp256k1 => {
-                AB::F::from_canonical_u32(SyscallCode::SECP256K1_ADD.syscall_id())
-            }
-            CurveType::Bn254 => AB::F::from_canonical_u32(SyscallCode::BN254_ADD.syscall_id()),
-            CurveType::Bls12381 => {
-                AB::F::from_canonical_u32(SyscallCode::BLS12381_ADD.syscall_id())
-            }
-            _ => panic!("Unsupported curve"),
-        };
+        let syscall_id_felt = AB::F::from_canonical_u32(E::ADD_SYSCALL.syscall_id());
 
         builder.receive_syscall(
             local.shard,
@@ -206,22 +206,26 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
         }
         output.add_byte_lookup_events(new_byte_lookup_events);
 
-        pad_rows(&mut rows, || {
-            let mut row = vec![F::zero(); num_weierstrass_decompress_cols::<E::BaseField>()];
-            let cols: &mut WeierstrassDecompressCols<F, E::BaseField> =
-                row.as_mut_slice().borrow_mut();
-
-            // take X of the generator as a dummy value to make sure Y^2 = X^3 + b holds
-            let dummy_value = E::generator().0;
-            let dummy_bytes = dummy_value.to_bytes_le();
-            let words = bytes_to_words_le_vec(&dummy_bytes);
-            for i in 0..cols.x_access.len() {
-                cols.x_access[i].access.value = words[i].into();
-            }
-
-            Self::populate_field_ops(&mut vec![], 0, 0, cols, dummy_value);
-            row
-        });
+        pad_rows(
+            &mut rows,
+            <Self as MachineAir<F>>::min_trace_height(self),
+            || {
+                let mut row = vec![F::zero(); num_weierstrass_decompress_cols::<E::BaseField>()];
+                let cols: &mut WeierstrassDecompressCols<F, E::BaseField> =
+                    row.as_mut_slice().borrow_mut();
+
+                // take X of the generator as a dummy value to make sure Y^2 = X^3 + b holds
+                let dummy_value = E::generator().0;
+                let dummy_bytes = dummy_value.to_bytes_le();
+                let words = bytes_to_words_le_vec(&dummy_bytes);
+                for i in 0..cols.x_access.len() {
+                    cols.x_access[i].access.value = words[i].into();
+                }
+
+                Self::populate_field_ops(&mut vec![], 0, 0, cols, dummy_value);
+                row
+            },
+        );
 
         let mut trace = RowMajorMatrix::new(
             rows.into_iter().flatten().collect::<Vec<_>>(),
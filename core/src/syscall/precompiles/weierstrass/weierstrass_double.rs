@@ -16,6 +16,7 @@ use p3_maybe_rayon::prelude::ParallelIterator;
 use p3_maybe_rayon::prelude::ParallelSlice;
 use sp1_derive::AlignedBorrow;
 
+use super::CurveParams;
 use crate::air::MachineAir;
 use crate::air::SP1AirBuilder;
 use crate::bytes::event::ByteRecord;
@@ -29,15 +30,13 @@ use crate::operations::field::params::{Limbs, NumLimbs};
 use crate::runtime::ExecutionRecord;
 use crate::runtime::Program;
 use crate::runtime::Syscall;
-use crate::runtime::SyscallCode;
 use crate::stark::MachineRecord;
 use crate::syscall::precompiles::create_ec_double_event;
 use crate::syscall::precompiles::SyscallContext;
 use crate::utils::ec::weierstrass::WeierstrassParameters;
 use crate::utils::ec::AffinePoint;
-use crate::utils::ec::CurveType;
 use crate::utils::ec::EllipticCurve;
-use crate::utils::{limbs_from_prev_access, pad_rows};
+use crate::utils::{limbs_from_prev_access, pad_rows, trace_chunk_size};
 
 pub const fn num_weierstrass_double_cols<P: FieldParameters + NumWords>() -> usize {
     size_of::<WeierstrassDoubleAssignCols<u8, P>>()
@@ -75,15 +74,10 @@ pub struct WeierstrassDoubleAssignChip<E> {
     _marker: PhantomData<E>,
 }
 
-impl<E: EllipticCurve + WeierstrassParameters> Syscall for WeierstrassDoubleAssignChip<E> {
+impl<E: CurveParams> Syscall for WeierstrassDoubleAssignChip<E> {
     fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
         let event = create_ec_double_event::<E>(rt, arg1, arg2);
-        match E::CURVE_TYPE {
-            CurveType::Secp256k1 => rt.record_mut().secp256k1_double_events.push(event),
-            CurveType::Bn254 => rt.record_mut().bn254_double_events.push(event),
-            CurveType::Bls12381 => rt.record_mut().bls12381_double_events.push(event),
-            _ => panic!("Unsupported curve"),
-        }
+        E::double_events_mut(rt.record_mut()).push(event);
         None
     }
 
@@ -219,19 +213,12 @@ impl<E: EllipticCurve + WeierstrassParameters> WeierstrassDoubleAssignChip<E> {
     }
 }
 
-impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
-    for WeierstrassDoubleAssignChip<E>
-{
+impl<F: PrimeField32, E: CurveParams> MachineAir<F> for WeierstrassDoubleAssignChip<E> {
     type Record = ExecutionRecord;
     type Program = Program;
 
     fn name(&self) -> String {
-        match E::CURVE_TYPE {
-            CurveType::Secp256k1 => "Secp256k1DoubleAssign".to_string(),
-            CurveType::Bn254 => "Bn254DoubleAssign".to_string(),
-            CurveType::Bls12381 => "Bls12381DoubleAssign".to_string(),
-            _ => panic!("Unsupported curve"),
-        }
+        format!("{}DoubleAssign", E::CURVE_TYPE)
     }
 
     fn generate_trace(
@@ -240,14 +227,9 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
         output: &mut ExecutionRecord,
     ) -> RowMajorMatrix<F> {
         // collects the events based on the curve type.
-        let events = match E::CURVE_TYPE {
-            CurveType::Secp256k1 => &input.secp256k1_double_events,
-            CurveType::Bn254 => &input.bn254_double_events,
-            CurveType::Bls12381 => &input.bls12381_double_events,
-            _ => panic!("Unsupported curve"),
-        };
+        let events = E::double_events(input);
 
-        let chunk_size = std::cmp::max(events.len() / num_cpus::get(), 1);
+        let chunk_size = trace_chunk_size(events.len());
 
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let rows_and_records = events
@@ -308,14 +290,18 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
             output.append(&mut row_and_record.1);
         }
 
-        pad_rows(&mut rows, || {
-            let mut row = vec![F::zero(); num_weierstrass_double_cols::<E::BaseField>()];
-            let cols: &mut WeierstrassDoubleAssignCols<F, E::BaseField> =
-                row.as_mut_slice().borrow_mut();
-            let zero = BigUint::zero();
-            Self::populate_field_ops(&mut vec![], 0, 0, cols, zero.clone(), zero.clone());
-            row
-        });
+        pad_rows(
+            &mut rows,
+            <Self as MachineAir<F>>::min_trace_height(self),
+            || {
+                let mut row = vec![F::zero(); num_weierstrass_double_cols::<E::BaseField>()];
+                let cols: &mut WeierstrassDoubleAssignCols<F, E::BaseField> =
+                    row.as_mut_slice().borrow_mut();
+                let zero = BigUint::zero();
+                Self::populate_field_ops(&mut vec![], 0, 0, cols, zero.clone(), zero.clone());
+                row
+            },
+        );
 
         // Convert the trace to a row major matrix.
         let mut trace = RowMajorMatrix::new(
@@ -336,12 +322,7 @@ impl<F: PrimeField32, E: EllipticCurve + WeierstrassParameters> MachineAir<F>
     }
 
     fn included(&self, shard: &Self::Record) -> bool {
-        match E::CURVE_TYPE {
-            CurveType::Secp256k1 => !shard.secp256k1_double_events.is_empty(),
-            CurveType::Bn254 => !shard.bn254_double_events.is_empty(),
-            CurveType::Bls12381 => !shard.bls12381_double_events.is_empty(),
-            _ => panic!("Unsupported curve"),
-        }
+        !E::double_events(shard).is_empty()
     }
 }
 
@@ -351,7 +332,7 @@ impl<F, E: EllipticCurve + WeierstrassParameters> BaseAir<F> for WeierstrassDoub
     }
 }
 
-impl<AB, E: EllipticCurve + WeierstrassParameters> Air<AB> for WeierstrassDoubleAssignChip<E>
+impl<AB, E: CurveParams> Air<AB> for WeierstrassDoubleAssignChip<E>
 where
     AB: SP1AirBuilder,
     Limbs<AB::Var, <E::BaseField as NumLimbs>::Limbs>: Copy,
@@ -520,16 +501,7 @@ where
         );
 
         // Fetch the syscall id for the curve type.
-        let syscall_id_felt = match E::CURVE_TYPE {
-            CurveType::Secp256k1 => {
-                AB::F::from_canonical_u32(SyscallCode::SECP256K1_DOUBLE.syscall_id())
-            }
-            CurveType::Bn254 => AB::F::from_canonical_u32(SyscallCode::BN254_DOUBLE.syscall_id()),
-            CurveType::Bls12381 => {
-                AB::F::from_canonical_u32(SyscallCode::BLS12381_DOUBLE.syscall_id())
-            }
-            _ => panic!("Unsupported curve"),
-        };
+        let syscall_id_felt = AB::F::from_canonical_u32(E::DOUBLE_SYSCALL.syscall_id());
 
         builder.receive_syscall(
             local.shard,
@@ -0,0 +1,113 @@
+use crate::runtime::{Syscall, SyscallContext};
+
+/// SyscallHintShardBoundary marks the current cycle as a preferred place to end the shard.
+///
+/// The hint only moves the shard boundaries, which the constraints allow anywhere, so it has no
+/// effect on the registers or the memory besides those of the `ECALL` itself.
+pub struct SyscallHintShardBoundary;
+
+impl SyscallHintShardBoundary {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Syscall for SyscallHintShardBoundary {
+    fn execute(&self, ctx: &mut SyscallContext, _arg1: u32, _arg2: u32) -> Option<u32> {
+        let rt = &mut ctx.rt;
+        if !rt.unconstrained {
+            rt.shard_boundary_hinted = true;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runtime::{Instruction, Opcode, Program, Runtime, SyscallCode},
+        utils::{run_test_core, setup_logger, SP1CoreOpts},
+    };
+
+    /// The number of iterations of the inner loop between two hints.
+    const INNER_ITERATIONS: u32 = 4997;
+
+    /// A program hinting a shard boundary after every 10k cycles, `num_hints` times.
+    fn hinting_program(num_hints: u32) -> Program {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 6, 0, num_hints, false, true),
+            // The outer loop, at pc 4.
+            Instruction::new(Opcode::ADD, 7, 0, INNER_ITERATIONS, false, true),
+            // The inner loop, at pc 8.
+            Instruction::new(Opcode::SUB, 7, 7, 1, false, true),
+            Instruction::new(Opcode::BNE, 7, 0, -4i32 as u32, false, true),
+            Instruction::new(
+                Opcode::ADD,
+                5,
+                0,
+                SyscallCode::HINT_SHARD_BOUNDARY as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::SUB, 6, 6, 1, false, true),
+            Instruction::new(Opcode::BNE, 6, 0, -24i32 as u32, false, true),
+        ];
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_hint_shard_boundary() {
+        setup_logger();
+        let opts = SP1CoreOpts {
+            shard_size: 100_000,
+            ..SP1CoreOpts::default()
+        };
+        let mut runtime = Runtime::new(hinting_program(12), opts);
+        runtime.run().unwrap();
+
+        let events = runtime.record.cpu_events.iter().collect::<Vec<_>>();
+        let is_hint = |i: usize| {
+            events[i].instruction.opcode == Opcode::ECALL
+                && events[i].a == SyscallCode::HINT_SHARD_BOUNDARY as u32
+        };
+        let hints = (0..events.len())
+            .filter(|&i| is_hint(i))
+            .collect::<Vec<_>>();
+        assert_eq!(hints.len(), 12);
+        assert_eq!(hints[1] - hints[0], 2 * INNER_ITERATIONS as usize + 5);
+
+        // Every shard but the last one ends on a hint, once it is half of the shard size long.
+        let boundaries = (1..events.len())
+            .filter(|&i| events[i].shard != events[i - 1].shard)
+            .collect::<Vec<_>>();
+        assert!(!boundaries.is_empty());
+        let mut start = 0;
+        for &boundary in boundaries.iter() {
+            assert!(is_hint(boundary - 1));
+            assert!(boundary - start >= 50_000);
+            assert!(boundary - start < 60_000);
+            assert_eq!(events[boundary].clk, 0);
+            start = boundary;
+        }
+
+        // The hints do not change the execution.
+        let mut unhinted = Runtime::new(
+            hinting_program(12),
+            SP1CoreOpts {
+                shard_boundary_hint_fraction: 1.0,
+                ..opts
+            },
+        );
+        unhinted.run().unwrap();
+        assert_eq!(unhinted.state.global_clk, runtime.state.global_clk);
+        assert_eq!(unhinted.registers(), runtime.registers());
+        let unhinted_events = unhinted.record.cpu_events.iter().collect::<Vec<_>>();
+        let first_boundary = (1..unhinted_events.len())
+            .find(|&i| unhinted_events[i].shard != unhinted_events[i - 1].shard)
+            .unwrap();
+        assert!(first_boundary > 99_000);
+
+        run_test_core(runtime).unwrap();
+    }
+}
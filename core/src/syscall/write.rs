@@ -1,5 +1,6 @@
 use crate::{
-    runtime::{Register, Syscall, SyscallContext},
+    io::{SpanCycles, FD_KV_ENTRIES, FD_OUTPUT_CHUNKS, FD_SEGMENT_STATE},
+    runtime::{Register, Syscall, SyscallContext, STDERR_CAPTURE_LEN},
     utils::num_to_comma_separated,
 };
 
@@ -45,6 +46,13 @@ impl Syscall for SyscallWrite {
                     .trim_end()
                     .trim_start();
                 let (start, depth) = rt.cycle_tracker.remove(fn_name).unwrap_or((0, 0));
+                rt.span_measurements.close(fn_name);
+                if rt.print_report {
+                    *rt.report
+                        .span_cycles
+                        .entry(fn_name.to_string())
+                        .or_default() += rt.state.global_clk - start;
+                }
                 // Leftpad by 2 spaces for each depth.
                 let padding = (0..depth).map(|_| "│ ").collect::<String>();
                 log::info!(
@@ -62,6 +70,7 @@ impl Syscall for SyscallWrite {
             }
         } else if fd == 2 {
             let s = core::str::from_utf8(slice).unwrap();
+            capture_stderr(&mut rt.stderr, s);
             let flush_s = update_io_buf(ctx, fd, s);
             if !flush_s.is_empty() {
                 flush_s
@@ -70,8 +79,17 @@ impl Syscall for SyscallWrite {
             }
         } else if fd == 3 {
             rt.state.public_values_stream.extend_from_slice(slice);
+            if let Some(span) = SpanCycles::decode(slice) {
+                rt.span_measurements.check(&span);
+            }
         } else if fd == 4 {
             rt.state.input_stream.push(slice.to_vec());
+        } else if fd == FD_OUTPUT_CHUNKS {
+            rt.state.output_chunks.push(slice.to_vec());
+        } else if fd == FD_KV_ENTRIES {
+            rt.state.kv_entries.push(slice.to_vec());
+        } else if fd == FD_SEGMENT_STATE {
+            rt.state.segment_state = Some(slice.to_vec());
         } else if let Some(hook) = rt.hook_registry.table.get(&fd) {
             rt.state.input_stream.extend(hook(rt.hook_env(), slice));
         } else {
@@ -81,6 +99,18 @@ impl Syscall for SyscallWrite {
     }
 }
 
+/// Appends `s` to the captured stderr output, keeping only the last [STDERR_CAPTURE_LEN] bytes.
+fn capture_stderr(stderr: &mut String, s: &str) {
+    stderr.push_str(s);
+    if stderr.len() > STDERR_CAPTURE_LEN {
+        let mut start = stderr.len() - STDERR_CAPTURE_LEN;
+        while !stderr.is_char_boundary(start) {
+            start += 1;
+        }
+        stderr.drain(..start);
+    }
+}
+
 pub fn update_io_buf(ctx: &mut SyscallContext, fd: u32, s: &str) -> Vec<String> {
     let rt = &mut ctx.rt;
     let entry = rt.io_buf.entry(fd).or_default();
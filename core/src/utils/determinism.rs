@@ -0,0 +1,257 @@
+//! Reproducible proofs, for proving the same program on the same input again and getting the same
+//! bytes.
+//!
+//! The prover-side sources of nondeterminism and how they are fixed:
+//!
+//! - The proof of work of FRI is searched by every thread at once, keeping the first witness
+//!   found. [FirstWitnessChallenger] keeps the smallest witness instead, for all the proofs.
+//! - The chip orderings of the keys and of the shard proofs are hash maps, serialized in an order
+//!   that changes from one process to the next. [serialize_sorted] serializes them by chip name.
+//! - The lookup ids pairing the events of the CPU with the rows of the ALU chips are random. They
+//!   don't change the proof, but with [crate::utils::SP1CoreOpts::deterministic] they are drawn
+//!   from [determinism_seed], reseeded at each shard with [shard_seed], so the execution records
+//!   are reproducible too, whichever shard the execution starts from.
+//!
+//! - The trace rows of some chips are generated in parallel over chunks of events, each with its
+//!   own record of dependency events, appended in order. The chunks are cut by
+//!   [trace_chunk_size], from the number of events only, so that neither the machine nor the size
+//!   of the rayon thread pool changes the traces or the records.
+//!
+//! The traces, the commitments and the rest of the transcript are deterministic functions of the
+//! program, the input and the options. Up to the wrapped proof, proving is then reproducible. The
+//! PLONK proofs of the wrapped proofs are blinded by gnark with fresh randomness, and are not.
+
+use std::collections::HashMap;
+
+use p3_challenger::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+use p3_field::{Field, PrimeField64};
+use p3_maybe_rayon::prelude::*;
+use serde::{Serialize, Serializer};
+
+use crate::io::SP1Stdin;
+use crate::runtime::Program;
+use crate::utils::SP1CoreOpts;
+
+/// The seed of the lookup ids of an execution of `program` on `stdin` with `opts`.
+pub fn determinism_seed(program: &Program, stdin: &SP1Stdin, opts: &SP1CoreOpts) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&bincode::serialize(program).expect("failed to serialize the program"));
+    hasher.update(&bincode::serialize(stdin).expect("failed to serialize the input"));
+    for value in [
+        opts.shard_size as u64,
+        opts.shard_batch_size as u64,
+        opts.shard_chunking_multiplier as u64,
+        opts.reconstruct_commitments as u64,
+        opts.max_memory,
+        opts.allow_unconstrained as u64,
+        opts.shard_boundary_hint_fraction.to_bits(),
+    ] {
+        hasher.update(&value.to_le_bytes());
+    }
+    *hasher.finalize().as_bytes()
+}
+
+/// The seed of the lookup ids of the shard `shard` of an execution seeded by `seed`.
+pub fn shard_seed(seed: [u8; 32], shard: u32) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&seed);
+    hasher.update(&shard.to_le_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+/// The number of chunks of events whose trace rows are generated in parallel.
+const TRACE_CHUNKS: usize = 64;
+
+/// The size of the chunks of `num_events` events whose trace rows are generated in parallel.
+pub fn trace_chunk_size(num_events: usize) -> usize {
+    std::cmp::max(num_events / TRACE_CHUNKS, 1)
+}
+
+/// Serializes `map` as a map ordered by key, which deserializes into the same hash map.
+pub fn serialize_sorted<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    serializer.collect_map(entries)
+}
+
+/// A challenger whose proof of work witness is the smallest valid one.
+///
+/// The witness of the wrapped challenger is the first one found by any thread. The search still
+/// runs on every thread, and stops once the witnesses below the smallest one found are checked.
+#[derive(Clone)]
+pub struct FirstWitnessChallenger<C>(pub C);
+
+impl<C> FirstWitnessChallenger<C> {
+    pub const fn new(challenger: C) -> Self {
+        Self(challenger)
+    }
+}
+
+impl<C> core::ops::Deref for FirstWitnessChallenger<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.0
+    }
+}
+
+impl<C> core::ops::DerefMut for FirstWitnessChallenger<C> {
+    fn deref_mut(&mut self) -> &mut C {
+        &mut self.0
+    }
+}
+
+impl<T, C: CanObserve<T>> CanObserve<T> for FirstWitnessChallenger<C> {
+    fn observe(&mut self, value: T) {
+        self.0.observe(value);
+    }
+}
+
+impl<T, C: CanSample<T>> CanSample<T> for FirstWitnessChallenger<C> {
+    fn sample(&mut self) -> T {
+        self.0.sample()
+    }
+}
+
+impl<T, C: CanSampleBits<T>> CanSampleBits<T> for FirstWitnessChallenger<C> {
+    fn sample_bits(&mut self, bits: usize) -> T {
+        self.0.sample_bits(bits)
+    }
+}
+
+impl<F: Field, C: FieldChallenger<F>> FieldChallenger<F> for FirstWitnessChallenger<C> {}
+
+impl<C> GrindingChallenger for FirstWitnessChallenger<C>
+where
+    C: GrindingChallenger,
+    C::Witness: PrimeField64,
+{
+    type Witness = C::Witness;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = (0..C::Witness::ORDER_U64)
+            .into_par_iter()
+            .map(C::Witness::from_canonical_u64)
+            .find_first(|witness| self.0.clone().check_witness(bits, *witness))
+            .expect("failed to find witness");
+        assert!(self.0.check_witness(bits, witness));
+        witness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::AbstractField;
+
+    use super::*;
+    use crate::air::MachineAir;
+    use crate::runtime::{ExecutionRecord, Runtime};
+    use crate::stark::{RiscvAir, StarkGenericConfig};
+    use crate::utils::tests::{
+        ED_ADD_ELF, FIBONACCI_ELF, KECCAK_PERMUTE_ELF, SECP256K1_DOUBLE_ELF,
+    };
+    use crate::utils::BabyBearPoseidon2;
+
+    /// The traces of the chips of each shard of `record`, sharded and generated in a thread pool of
+    /// `num_threads` threads.
+    fn traces_with_threads(
+        record: &ExecutionRecord,
+        num_threads: usize,
+    ) -> Vec<Vec<(String, Vec<p3_baby_bear::BabyBear>)>> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let machine = RiscvAir::machine(BabyBearPoseidon2::new());
+            let shards = machine.shard(record.clone(), &SP1CoreOpts::default());
+            shards
+                .iter()
+                .map(|shard| {
+                    machine
+                        .chips()
+                        .iter()
+                        .filter(|chip| chip.included(shard))
+                        .map(|chip| {
+                            let trace = chip.generate_trace(shard, &mut ExecutionRecord::default());
+                            (chip.name(), trace.values)
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_traces_independent_of_thread_count() {
+        let max_threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+        for elf in [
+            FIBONACCI_ELF,
+            KECCAK_PERMUTE_ELF,
+            ED_ADD_ELF,
+            SECP256K1_DOUBLE_ELF,
+        ] {
+            let mut runtime = Runtime::new(Program::from(elf), SP1CoreOpts::default());
+            runtime.run().unwrap();
+
+            // Five generations, under different thread counts.
+            let expected = traces_with_threads(&runtime.record, 1);
+            for num_threads in [2, max_threads, 1, 2, max_threads] {
+                let traces = traces_with_threads(&runtime.record, num_threads);
+                for (shard, (traces, expected)) in traces.iter().zip(&expected).enumerate() {
+                    for ((name, values), (_, expected_values)) in traces.iter().zip(expected) {
+                        assert!(
+                            values == expected_values,
+                            "the {} trace of shard {} changed with {} threads",
+                            name,
+                            shard,
+                            num_threads
+                        );
+                    }
+                    assert_eq!(traces.len(), expected.len());
+                }
+                assert_eq!(traces.len(), expected.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_witness() {
+        let config = BabyBearPoseidon2::new();
+        let mut challenger = config.challenger();
+        challenger.observe(p3_baby_bear::BabyBear::from_canonical_u32(7));
+
+        let witness = challenger.clone().grind(8);
+        for smaller in 0..witness.as_canonical_u64() {
+            let smaller = p3_baby_bear::BabyBear::from_canonical_u64(smaller);
+            assert!(!challenger.clone().check_witness(8, smaller));
+        }
+        assert_eq!(challenger.clone().grind(8), witness);
+    }
+
+    #[test]
+    fn test_serialize_sorted() {
+        #[derive(Serialize)]
+        struct Ordering(#[serde(serialize_with = "serialize_sorted")] HashMap<String, usize>);
+
+        let names = (0..32).map(|i| format!("chip{}", i)).collect::<Vec<_>>();
+        let a = names.iter().cloned().zip(0..).collect::<HashMap<_, _>>();
+        let b = names
+            .iter()
+            .cloned()
+            .zip(0..)
+            .rev()
+            .collect::<HashMap<_, _>>();
+        let bytes = bincode::serialize(&Ordering(a.clone())).unwrap();
+        assert_eq!(bytes, bincode::serialize(&Ordering(b)).unwrap());
+        assert_eq!(
+            bincode::deserialize::<HashMap<String, usize>>(&bytes).unwrap(),
+            a
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Modulo defining the Secp256r1 curve, also known as P-256, and its base field. The constants are
+//! all taken from https://neuromancer.sk/std/secg/secp256r1.
+
+use std::str::FromStr;
+
+use generic_array::GenericArray;
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+use typenum::{U32, U62};
+
+use super::{SwCurve, WeierstrassParameters};
+use crate::operations::field::params::FieldParameters;
+use crate::operations::field::params::NumLimbs;
+use crate::utils::ec::CurveType;
+use crate::utils::ec::EllipticCurveParameters;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Secp256r1 curve parameter
+pub struct Secp256r1Parameters;
+
+pub type Secp256r1 = SwCurve<Secp256r1Parameters>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Secp256r1 base field parameter
+pub struct Secp256r1BaseField;
+
+impl FieldParameters for Secp256r1BaseField {
+    const MODULUS: &'static [u8] = &[
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0xff, 0xff,
+        0xff, 0xff,
+    ];
+
+    /// A rough witness-offset estimate given the size of the limbs and the size of the field.
+    const WITNESS_OFFSET: usize = 1usize << 14;
+
+    fn modulus() -> BigUint {
+        BigUint::from_bytes_le(Self::MODULUS)
+    }
+}
+
+impl NumLimbs for Secp256r1BaseField {
+    type Limbs = U32;
+    type Witness = U62;
+}
+
+impl EllipticCurveParameters for Secp256r1Parameters {
+    type BaseField = Secp256r1BaseField;
+    const CURVE_TYPE: CurveType = CurveType::Secp256r1;
+}
+
+impl WeierstrassParameters for Secp256r1Parameters {
+    /// The coefficient `a` is `p - 3`.
+    const A: GenericArray<u8, U32> = GenericArray::from_array([
+        0xfc, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0xff, 0xff,
+        0xff, 0xff,
+    ]);
+
+    const B: GenericArray<u8, U32> = GenericArray::from_array([
+        0x4b, 0x60, 0xd2, 0x27, 0x3e, 0x3c, 0xce, 0x3b, 0xf6, 0xb0, 0x53, 0xcc, 0xb0, 0x06, 0x1d,
+        0x65, 0xbc, 0x86, 0x98, 0x76, 0x55, 0xbd, 0xeb, 0xb3, 0xe7, 0x93, 0x3a, 0xaa, 0xd8, 0x35,
+        0xc6, 0x5a,
+    ]);
+
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str(
+            "48439561293906451759052585252797914202762949526041747995844080717082404635286",
+        )
+        .unwrap();
+        let y = BigUint::from_str(
+            "36134250956749795798585127919587881956611106672985015071877198253568414405109",
+        )
+        .unwrap();
+        (x, y)
+    }
+
+    fn prime_group_order() -> num::BigUint {
+        BigUint::from_slice(&[
+            0xFC632551, 0xF3B9CAC2, 0xA7179E84, 0xBCE6FAAD, 0xFFFFFFFF, 0xFFFFFFFF, 0x00000000,
+            0xFFFFFFFF,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::Num;
+    use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+    use p256::elliptic_curve::PrimeField;
+    use p256::{EncodedPoint, ProjectivePoint, Scalar};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::utils::ec::utils::biguint_from_limbs;
+    use crate::utils::ec::AffinePoint;
+
+    fn to_p256(point: &AffinePoint<Secp256r1>) -> ProjectivePoint {
+        let mut x = point.x.to_bytes_be();
+        let mut y = point.y.to_bytes_be();
+        x.splice(0..0, vec![0; 32 - x.len()]);
+        y.splice(0..0, vec![0; 32 - y.len()]);
+        let encoded = EncodedPoint::from_affine_coordinates(x[..].into(), y[..].into(), false);
+        ProjectivePoint::from_encoded_point(&encoded).unwrap()
+    }
+
+    fn from_p256(point: &ProjectivePoint) -> AffinePoint<Secp256r1> {
+        let encoded = point.to_affine().to_encoded_point(false);
+        AffinePoint::new(
+            BigUint::from_bytes_be(encoded.x().unwrap()),
+            BigUint::from_bytes_be(encoded.y().unwrap()),
+        )
+    }
+
+    fn to_scalar(n: &BigUint) -> Scalar {
+        let mut bytes = n.to_bytes_be();
+        bytes.splice(0..0, vec![0; 32 - bytes.len()]);
+        Scalar::from_repr(*p256::FieldBytes::from_slice(&bytes)).unwrap()
+    }
+
+    #[test]
+    fn test_secp256r1_parameters() {
+        assert_eq!(
+            biguint_from_limbs(Secp256r1BaseField::MODULUS),
+            Secp256r1BaseField::modulus()
+        );
+        assert_eq!(
+            Secp256r1::a_int() + 3u32,
+            Secp256r1BaseField::modulus(),
+            "a must be -3"
+        );
+        assert_eq!(to_p256(&Secp256r1::generator()), ProjectivePoint::GENERATOR);
+        assert_eq!(
+            Secp256r1Parameters::prime_group_order(),
+            BigUint::from_str_radix(
+                "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551",
+                16
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_secp256r1_against_p256() {
+        let mut rng = thread_rng();
+        let order = Secp256r1Parameters::prime_group_order();
+        let generator = Secp256r1::generator();
+        for _ in 0..10 {
+            let a = rng.gen_biguint(256) % &order;
+            let b = rng.gen_biguint(256) % &order;
+            let p = generator.sw_scalar_mul(&a);
+            let q = generator.sw_scalar_mul(&b);
+            let (expected_p, expected_q) = (
+                ProjectivePoint::GENERATOR * to_scalar(&a),
+                ProjectivePoint::GENERATOR * to_scalar(&b),
+            );
+            assert_eq!(to_p256(&p), expected_p);
+            assert_eq!(p.sw_add(&q), from_p256(&(expected_p + expected_q)));
+            assert_eq!(p.sw_double(), from_p256(&expected_p.double()));
+        }
+    }
+}
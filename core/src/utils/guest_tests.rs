@@ -0,0 +1,434 @@
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::io::FD_TEST_REPORTS;
+use crate::runtime::{ExecutionError, Program, Runtime};
+use crate::utils::{num_to_comma_separated, SP1CoreOpts};
+
+/// What the runner asks a test program to do. Must match `TestPlan` in
+/// `zkvm/entrypoint/src/testing.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuestTestPlan {
+    /// Report the names of all the tests.
+    List,
+    /// Run the tests with these names, in order.
+    Run(Vec<String>),
+}
+
+/// What a test program tells the runner. Must match `TestReport` in
+/// `zkvm/entrypoint/src/testing.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuestTestReport {
+    Listed {
+        names: Vec<String>,
+    },
+    Started {
+        name: String,
+    },
+    Passed {
+        name: String,
+        cycles: u64,
+    },
+    Failed {
+        name: String,
+        message: String,
+        cycles: u64,
+    },
+}
+
+/// The outcome of a test run in the zkVM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestTestOutcome {
+    pub name: String,
+    /// The panic message of the test, if it failed.
+    pub failure: Option<String>,
+    /// The number of cycles the test ran for, up to the panic if it failed.
+    pub cycles: u64,
+}
+
+impl GuestTestOutcome {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum GuestTestError {
+    #[error("the test program failed before running any test: {0}")]
+    Execution(#[from] ExecutionError),
+    #[error("invalid test report: {0}")]
+    InvalidReport(#[from] bincode::Error),
+    #[error("the test program did not list its tests")]
+    NotListed,
+    #[error("the test program did not run test {0}")]
+    NotRun(String),
+}
+
+/// Runs the tests of a program generated by `#[sp1_derive::sp1_test]` in the runtime, without
+/// proving them.
+///
+/// A failing test halts the program, so the program is run again with the tests left after it
+/// until all of them have run.
+pub struct GuestTestRunner {
+    program: Program,
+    opts: SP1CoreOpts,
+    filter: Option<String>,
+    exact: bool,
+}
+
+impl GuestTestRunner {
+    pub fn new(elf: &[u8]) -> Self {
+        Self {
+            program: Program::from(elf),
+            opts: SP1CoreOpts::default(),
+            filter: None,
+            exact: false,
+        }
+    }
+
+    /// Only run the tests whose name contains `filter`, or is `filter` if [Self::exact] is set.
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    pub fn opts(mut self, opts: SP1CoreOpts) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    fn selected(&self, name: &str) -> bool {
+        match &self.filter {
+            None => true,
+            Some(filter) if self.exact => name == filter,
+            Some(filter) => name.contains(filter.as_str()),
+        }
+    }
+
+    /// The names of the tests of the program, in order, and whether each of them is selected by
+    /// the filter.
+    pub fn list(&self) -> Result<Vec<(String, bool)>, GuestTestError> {
+        list_tests(|plan| self.execute(plan)).map(|names| {
+            names
+                .into_iter()
+                .map(|name| {
+                    let selected = self.selected(&name);
+                    (name, selected)
+                })
+                .collect()
+        })
+    }
+
+    /// Runs the selected tests, returning their outcomes in order.
+    pub fn run(&self) -> Result<Vec<GuestTestOutcome>, GuestTestError> {
+        let names = self
+            .list()?
+            .into_iter()
+            .filter_map(|(name, selected)| selected.then_some(name))
+            .collect();
+        run_tests(names, |plan| self.execute(plan))
+    }
+
+    /// Executes the program with `plan`, returning the reports it wrote until it halted.
+    fn execute(&self, plan: &GuestTestPlan) -> (Vec<Vec<u8>>, Result<(), ExecutionError>) {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = Runtime::new(self.program.clone(), self.opts);
+        runtime.write_stdin(plan);
+        let captured = reports.clone();
+        runtime.hook_registry.register(
+            FD_TEST_REPORTS,
+            Box::new(move |_, buf| {
+                captured.lock().unwrap().push(buf.to_vec());
+                vec![]
+            }),
+        );
+        let result = runtime.run_untraced();
+        drop(runtime);
+        let reports = Arc::try_unwrap(reports).unwrap().into_inner().unwrap();
+        (reports, result)
+    }
+}
+
+fn decode(reports: &[Vec<u8>]) -> Result<Vec<GuestTestReport>, GuestTestError> {
+    reports
+        .iter()
+        .map(|report| bincode::deserialize(report).map_err(GuestTestError::from))
+        .collect()
+}
+
+fn list_tests(
+    mut execute: impl FnMut(&GuestTestPlan) -> (Vec<Vec<u8>>, Result<(), ExecutionError>),
+) -> Result<Vec<String>, GuestTestError> {
+    let (reports, result) = execute(&GuestTestPlan::List);
+    result?;
+    match decode(&reports)?.into_iter().next() {
+        Some(GuestTestReport::Listed { names }) => Ok(names),
+        _ => Err(GuestTestError::NotListed),
+    }
+}
+
+fn run_tests(
+    mut pending: Vec<String>,
+    mut execute: impl FnMut(&GuestTestPlan) -> (Vec<Vec<u8>>, Result<(), ExecutionError>),
+) -> Result<Vec<GuestTestOutcome>, GuestTestError> {
+    let mut outcomes = Vec::new();
+    while !pending.is_empty() {
+        let (reports, result) = execute(&GuestTestPlan::Run(pending.clone()));
+        let mut started = None;
+        let mut done = 0;
+        for report in decode(&reports)? {
+            match report {
+                GuestTestReport::Started { name } => started = Some(name),
+                GuestTestReport::Passed { name, cycles } => {
+                    started = None;
+                    done += 1;
+                    outcomes.push(GuestTestOutcome {
+                        name,
+                        failure: None,
+                        cycles,
+                    });
+                }
+                GuestTestReport::Failed {
+                    name,
+                    message,
+                    cycles,
+                } => {
+                    started = None;
+                    done += 1;
+                    outcomes.push(GuestTestOutcome {
+                        name,
+                        failure: Some(message),
+                        cycles,
+                    });
+                }
+                GuestTestReport::Listed { .. } => {}
+            }
+        }
+
+        // A test that started without reporting its failure, for example because it ran out of
+        // cycles, failed with the error of the execution.
+        match (started, result) {
+            (Some(name), Err(err)) => {
+                done += 1;
+                outcomes.push(GuestTestOutcome {
+                    name,
+                    failure: Some(err.to_string()),
+                    cycles: 0,
+                });
+            }
+            (None, Err(err)) if done == 0 => return Err(err.into()),
+            _ => {}
+        }
+        if done == 0 {
+            return Err(GuestTestError::NotRun(pending.swap_remove(0)));
+        }
+        // The program halts after the first failing test, and every test it did not get to runs
+        // again.
+        pending.drain(..done.min(pending.len()));
+    }
+    Ok(outcomes)
+}
+
+/// The main function of a `cargo test` target with `harness = false` running the tests of `elf`.
+///
+/// Like the default harness, the first free argument filters the tests by name, `--exact` only
+/// runs the test with exactly that name, and `--list` lists the tests. Other flags are ignored.
+pub fn guest_test_main(elf: &[u8]) {
+    let mut runner = GuestTestRunner::new(elf);
+    let mut list = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--list" => list = true,
+            "--exact" => runner = runner.exact(true),
+            flag if flag.starts_with('-') => {}
+            filter => runner = runner.filter(filter),
+        }
+    }
+
+    if list {
+        let tests = runner.list().unwrap();
+        for (name, _) in tests.iter().filter(|(_, selected)| *selected) {
+            println!("{}: test", name);
+        }
+        return;
+    }
+
+    let total = runner.list().unwrap().len();
+    let outcomes = runner.run().unwrap();
+    println!("\nrunning {} tests", outcomes.len());
+    for outcome in outcomes.iter() {
+        println!(
+            "test {} ... {} ({} cycles)",
+            outcome.name,
+            if outcome.passed() { "ok" } else { "FAILED" },
+            num_to_comma_separated(outcome.cycles)
+        );
+    }
+
+    let failures = outcomes
+        .iter()
+        .filter(|outcome| !outcome.passed())
+        .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        println!("\nfailures:\n");
+        for outcome in failures.iter() {
+            println!(
+                "---- {} ----\n{}\n",
+                outcome.name,
+                outcome.failure.as_ref().unwrap()
+            );
+        }
+    }
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} filtered out\n",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        outcomes.len() - failures.len(),
+        failures.len(),
+        total - outcomes.len()
+    );
+    if !failures.is_empty() {
+        std::process::exit(101);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Guest = &'static [(&'static str, Option<&'static str>)];
+
+    /// Executes a fake test program of `tests`, failing the tests with a message like a program
+    /// generated by `#[sp1_test]` would.
+    fn fake_execute(
+        tests: Guest,
+        calls: &mut usize,
+    ) -> impl FnMut(&GuestTestPlan) -> (Vec<Vec<u8>>, Result<(), ExecutionError>) + '_ {
+        move |plan| {
+            *calls += 1;
+            let mut reports = Vec::new();
+            let mut write = |report: sp1_zkvm::testing::TestReport| {
+                reports.push(bincode::serialize(&report).unwrap())
+            };
+            let names = match plan {
+                GuestTestPlan::List => {
+                    write(sp1_zkvm::testing::TestReport::Listed {
+                        names: tests.iter().map(|(name, _)| *name).collect(),
+                    });
+                    return (reports, Ok(()));
+                }
+                GuestTestPlan::Run(names) => names,
+            };
+            for name in names {
+                let &(name, failure) = tests.iter().find(|(test, _)| test == name).unwrap();
+                write(sp1_zkvm::testing::TestReport::Started { name });
+                match failure {
+                    None => write(sp1_zkvm::testing::TestReport::Passed { name, cycles: 10 }),
+                    Some(message) => {
+                        write(sp1_zkvm::testing::TestReport::Failed {
+                            name,
+                            message: message.to_string(),
+                            cycles: 5,
+                        });
+                        let err = ExecutionError::GuestPanic {
+                            exit_code: 1,
+                            message: message.to_string(),
+                        };
+                        return (reports, Err(err));
+                    }
+                }
+            }
+            (reports, Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_guest_test_plan_matches() {
+        assert_eq!(FD_TEST_REPORTS, sp1_zkvm::io::FD_TEST_REPORTS);
+        let plan = GuestTestPlan::Run(vec!["a".to_string()]);
+        let guest: sp1_zkvm::testing::TestPlan =
+            bincode::deserialize(&bincode::serialize(&plan).unwrap()).unwrap();
+        assert_eq!(
+            guest,
+            sp1_zkvm::testing::TestPlan::Run(vec!["a".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_run_guest_tests() {
+        let tests: Guest = &[
+            ("addition", None),
+            ("overflow", Some("attempt to add with overflow")),
+            ("sha256", None),
+        ];
+        let mut calls = 0;
+        let names = list_tests(fake_execute(tests, &mut calls)).unwrap();
+        assert_eq!(names, ["addition", "overflow", "sha256"]);
+
+        let outcomes = run_tests(names, fake_execute(tests, &mut calls)).unwrap();
+        let summary = outcomes
+            .iter()
+            .map(|outcome| {
+                (
+                    outcome.name.as_str(),
+                    outcome.failure.as_deref(),
+                    outcome.cycles,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("addition", None, 10),
+                ("overflow", Some("attempt to add with overflow"), 5),
+                ("sha256", None, 10),
+            ]
+        );
+        // The list, then a run halted by the failing test, then a run of the last test.
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_run_guest_tests_unreported_failure() {
+        // A test killed by the runtime before it could report its failure.
+        let execute = |plan: &GuestTestPlan| {
+            let GuestTestPlan::Run(names) = plan else {
+                unreachable!()
+            };
+            let started = GuestTestReport::Started {
+                name: names[0].clone(),
+            };
+            let err = ExecutionError::HaltWithNonZeroExitCode(1);
+            (vec![bincode::serialize(&started).unwrap()], Err(err))
+        };
+        let outcomes = run_tests(vec!["a".to_string(), "b".to_string()], execute).unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|outcome| !outcome.passed()));
+        assert_eq!(
+            outcomes[0].failure.as_deref(),
+            Some("execution failed with exit code 1")
+        );
+    }
+
+    #[test]
+    fn test_guest_test_filter() {
+        let runner = GuestTestRunner {
+            program: Program::default(),
+            opts: SP1CoreOpts::default(),
+            filter: Some("add".to_string()),
+            exact: false,
+        };
+        assert!(runner.selected("addition"));
+        assert!(runner.selected("add"));
+        assert!(!runner.selected("sha256"));
+        let runner = runner.exact(true);
+        assert!(!runner.selected("addition"));
+        assert!(runner.selected("add"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Heap usage of the prover.
+//!
+//! With the `memory-stats` feature, the process allocator is wrapped by a [TrackingAllocator]
+//! counting the bytes currently allocated and their high-water mark since the last call to
+//! [reset_peak]. Without it, nothing is tracked: [peak] returns `None` and memory budgets are never
+//! exceeded.
+
+/// The peak heap usage allowed for each proving stage, in bytes. `None` leaves a stage unbounded.
+///
+/// Budgets are checked at the points where a stage can stop cleanly, between checkpoints and
+/// shards of the core stage and between layers of the compress stage, so the peak may overshoot
+/// the budget by up to the memory used between two checks. They require the `memory-stats`
+/// feature and are ignored without it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBudgets {
+    pub core: Option<u64>,
+    pub compress: Option<u64>,
+    pub shrink: Option<u64>,
+    pub wrap: Option<u64>,
+}
+
+#[cfg(feature = "memory-stats")]
+mod tracking {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static CURRENT: AtomicU64 = AtomicU64::new(0);
+    static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    /// A wrapper around the system allocator tracking the heap usage of the process.
+    pub struct TrackingAllocator;
+
+    impl TrackingAllocator {
+        fn record_alloc(size: usize) {
+            let current = CURRENT.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+
+        fn record_dealloc(size: usize) {
+            CURRENT.fetch_sub(size as u64, Ordering::Relaxed);
+        }
+    }
+
+    unsafe impl GlobalAlloc for TrackingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                Self::record_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                Self::record_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            Self::record_dealloc(layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                Self::record_dealloc(layout.size());
+                Self::record_alloc(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+    pub fn current() -> u64 {
+        CURRENT.load(Ordering::Relaxed)
+    }
+
+    pub fn peak() -> u64 {
+        PEAK.load(Ordering::Relaxed)
+    }
+
+    pub fn reset_peak() {
+        PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "memory-stats")]
+pub use tracking::TrackingAllocator;
+
+/// The bytes currently allocated, if tracked.
+pub fn current() -> Option<u64> {
+    #[cfg(feature = "memory-stats")]
+    return Some(tracking::current());
+    #[cfg(not(feature = "memory-stats"))]
+    None
+}
+
+/// The largest number of bytes allocated at once since the last [reset_peak], if tracked.
+pub fn peak() -> Option<u64> {
+    #[cfg(feature = "memory-stats")]
+    return Some(tracking::peak());
+    #[cfg(not(feature = "memory-stats"))]
+    None
+}
+
+/// Starts a new high-water mark from the bytes currently allocated. Called when a stage starts.
+pub fn reset_peak() {
+    #[cfg(feature = "memory-stats")]
+    tracking::reset_peak();
+}
+
+/// Returns the peak and the budget if the peak since the last [reset_peak] exceeds `budget`.
+pub fn exceeded_budget(budget: Option<u64>) -> Option<(u64, u64)> {
+    let (peak, budget) = (peak()?, budget?);
+    (peak > budget).then_some((peak, budget))
+}
+
+#[cfg(all(test, feature = "memory-stats"))]
+mod tests {
+    use super::*;
+    use crate::io::SP1Stdin;
+    use crate::runtime::{Instruction, Opcode, Program};
+    use crate::utils::{prove, setup_logger, BabyBearPoseidon2, SP1CoreOpts, SP1CoreProverError};
+
+    /// A loop of 1401 cycles, proven in two shards of 1024 cycles.
+    fn two_shard_program() -> Program {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 6, 0, 700, false, true),
+            Instruction::new(Opcode::SUB, 6, 6, 1, false, true),
+            Instruction::new(Opcode::BNE, 6, 0, -4i32 as u32, false, true),
+        ];
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_peak_tracks_allocations() {
+        // Other tests may reset the peak concurrently, but not below the live buffer.
+        let buffer = vec![1u8; 64 << 20];
+        assert!(current().unwrap() >= 64 << 20);
+        assert!(peak().unwrap() >= 64 << 20);
+        assert!(exceeded_budget(Some(32 << 20)).is_some());
+        assert_eq!(exceeded_budget(None), None);
+        drop(buffer);
+    }
+
+    #[test]
+    fn test_core_memory_budget_exceeded() {
+        setup_logger();
+        let mut opts = SP1CoreOpts {
+            shard_size: 1024,
+            shard_batch_size: 1,
+            ..SP1CoreOpts::default()
+        };
+        let (proof, _) = prove(
+            two_shard_program(),
+            &SP1Stdin::new(),
+            BabyBearPoseidon2::new(),
+            opts,
+        )
+        .unwrap();
+        assert_eq!(proof.shard_proofs.len(), 2);
+
+        opts.memory_budgets.core = Some(1 << 10);
+        let err = prove(
+            two_shard_program(),
+            &SP1Stdin::new(),
+            BabyBearPoseidon2::new(),
+            opts,
+        )
+        .unwrap_err();
+        match err {
+            SP1CoreProverError::MemoryBudgetExceeded {
+                stage,
+                peak,
+                budget,
+            } => {
+                assert_eq!(stage, "core");
+                assert_eq!(budget, 1 << 10);
+                assert!(peak > budget);
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+}
@@ -1,7 +1,10 @@
 mod buffer;
 mod config;
+mod determinism;
 pub mod ec;
+mod guest_tests;
 mod logger;
+pub mod memory;
 mod options;
 #[cfg(any(test, feature = "programs"))]
 mod programs;
@@ -10,6 +13,8 @@ mod tracer;
 
 pub use buffer::*;
 pub use config::*;
+pub use determinism::*;
+pub use guest_tests::*;
 pub use logger::*;
 pub use options::*;
 pub use prove::*;
@@ -31,13 +36,16 @@ pub const fn indices_arr<const N: usize>() -> [usize; N] {
     indices_arr
 }
 
-pub fn pad_to_power_of_two<const N: usize, T: Clone + Default>(values: &mut Vec<T>) {
+/// Pads the values of a trace of `N` columns with default rows to a power of two of at least
+/// `min_height` rows, usually the [crate::air::MachineAir::min_trace_height] of the chip.
+pub fn pad_to_power_of_two<const N: usize, T: Clone + Default>(
+    values: &mut Vec<T>,
+    min_height: usize,
+) {
     debug_assert!(values.len() % N == 0);
-    let mut n_real_rows = values.len() / N;
-    if n_real_rows < 16 {
-        n_real_rows = 16;
-    }
-    values.resize(n_real_rows.next_power_of_two() * N, T::default());
+    let n_real_rows = values.len() / N;
+    let padded_nb_rows = n_real_rows.next_power_of_two().max(min_height);
+    values.resize(padded_nb_rows * N, T::default());
 }
 
 pub fn limbs_from_prev_access<T: Copy, N: ArrayLength, M: MemoryCols<T>>(
@@ -66,12 +74,11 @@ pub fn limbs_from_access<T: Copy, N: ArrayLength, M: MemoryCols<T>>(cols: &[M])
     Limbs(sized)
 }
 
-pub fn pad_rows<T: Clone>(rows: &mut Vec<T>, row_fn: impl Fn() -> T) {
+/// Pads `rows` with rows of `row_fn` to a power of two of at least `min_height` rows, like
+/// [pad_to_power_of_two].
+pub fn pad_rows<T: Clone>(rows: &mut Vec<T>, min_height: usize, row_fn: impl Fn() -> T) {
     let nb_rows = rows.len();
-    let mut padded_nb_rows = nb_rows.next_power_of_two();
-    if padded_nb_rows < 16 {
-        padded_nb_rows = 16;
-    }
+    let padded_nb_rows = nb_rows.next_power_of_two().max(min_height);
     if padded_nb_rows == nb_rows {
         return;
     }
@@ -1,7 +1,12 @@
 use std::env;
 
+use super::memory::MemoryBudgets;
+
 const DEFAULT_SHARD_SIZE: usize = 1 << 22;
 const DEFAULT_SHARD_BATCH_SIZE: usize = 16;
+/// The default guest memory limit, the full 32-bit address space.
+pub const DEFAULT_MAX_MEMORY: u64 = 1 << 32;
+const DEFAULT_SHARD_BOUNDARY_HINT_FRACTION: f64 = 0.5;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SP1CoreOpts {
@@ -9,6 +14,31 @@ pub struct SP1CoreOpts {
     pub shard_batch_size: usize,
     pub shard_chunking_multiplier: usize,
     pub reconstruct_commitments: bool,
+    /// The guest memory limit in bytes. It bounds the memory footprint, the number of distinct
+    /// words touched times 4, not the addresses: touching a word past the limit stops execution
+    /// with [crate::runtime::ExecutionError::MemoryLimitExceeded].
+    pub max_memory: u64,
+    /// Whether executions making unconstrained syscalls can be proven, see
+    /// [crate::runtime::UnconstrainedSyscalls]. Such proofs are insecure, so this is only
+    /// meant for prototyping.
+    pub allow_unconstrained: bool,
+    /// The fraction of `shard_size` from which a shard ends at the next shard boundary hint of the
+    /// program, see `sp1_zkvm::io::hint_shard_boundary`. Hints in shards shorter than that are
+    /// ignored, and a fraction of 1 or more disables them.
+    pub shard_boundary_hint_fraction: f64,
+    /// The peak heap usage allowed for each proving stage, see [MemoryBudgets].
+    pub memory_budgets: MemoryBudgets,
+    /// Whether the records are reproducible: the lookup ids of the events are drawn from a seed
+    /// derived from the program, the input and these options, see [crate::utils::determinism_seed].
+    pub deterministic: bool,
+    /// Whether proving records the shards from the snapshots captured by the first execution, in
+    /// parallel, instead of executing each batch of shards again from a checkpoint saved to disk,
+    /// see [crate::runtime::Snapshots]. The snapshots are kept in memory.
+    pub replay_from_snapshots: bool,
+    /// Whether the CPU events of the execution records are compressed, see
+    /// [crate::cpu::CpuEventStore]. They take several times less memory, and are decoded again
+    /// when generating the traces.
+    pub compress_cpu_events: bool,
 }
 
 impl Default for SP1CoreOpts {
@@ -24,6 +54,18 @@ impl Default for SP1CoreOpts {
             ),
             shard_chunking_multiplier: 1,
             reconstruct_commitments: true,
+            max_memory: env::var("MAX_MEMORY").map_or_else(
+                |_| DEFAULT_MAX_MEMORY,
+                |s| s.parse::<u64>().unwrap_or(DEFAULT_MAX_MEMORY),
+            ),
+            allow_unconstrained: env::var("SP1_ALLOW_UNCONSTRAINED")
+                .map_or(false, |s| s.parse::<bool>().unwrap_or(false)),
+            shard_boundary_hint_fraction: DEFAULT_SHARD_BOUNDARY_HINT_FRACTION,
+            memory_budgets: MemoryBudgets::default(),
+            deterministic: false,
+            replay_from_snapshots: true,
+            compress_cpu_events: env::var("SP1_COMPRESS_CPU_EVENTS")
+                .map_or(true, |s| s.parse::<bool>().unwrap_or(true)),
         }
     }
 }
@@ -7,6 +7,7 @@ use web_time::Instant;
 pub use baby_bear_blake3::BabyBearBlake3;
 use p3_challenger::CanObserve;
 use p3_field::PrimeField32;
+use p3_maybe_rayon::prelude::*;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use size::Size;
@@ -16,7 +17,8 @@ use crate::air::MachineAir;
 use crate::io::{SP1PublicValues, SP1Stdin};
 use crate::lookup::InteractionBuilder;
 use crate::runtime::{
-    DefaultSubproofVerifier, ExecutionError, NoOpSubproofVerifier, SubproofVerifier,
+    first_divergence, DefaultSubproofVerifier, Effect, ExecutionError, NoOpSubproofVerifier,
+    NondeterminismError, Snapshots, SubproofVerifier, UnconstrainedSyscalls,
 };
 use crate::runtime::{ExecutionRecord, ExecutionReport, ShardingConfig};
 use crate::stark::DebugConstraintBuilder;
@@ -27,7 +29,7 @@ use crate::stark::Val;
 use crate::stark::VerifierConstraintFolder;
 use crate::stark::{Com, PcsProverData, RiscvAir, ShardProof, StarkProvingKey, UniConfig};
 use crate::stark::{MachineRecord, StarkMachine};
-use crate::utils::SP1CoreOpts;
+use crate::utils::{determinism_seed, memory, SP1CoreOpts};
 use crate::{
     runtime::{Program, Runtime},
     stark::StarkGenericConfig,
@@ -44,6 +46,56 @@ pub enum SP1CoreProverError {
     IoError(io::Error),
     #[error("serialization error: {0}")]
     SerializationError(bincode::Error),
+    #[error("the proving pass diverged from the execution pass: {0}")]
+    Nondeterminism(NondeterminismError),
+    #[error(
+        "the program made {0} unconstrained syscalls, which are only proven with `allow_unconstrained`"
+    )]
+    UnconstrainedSyscalls(u64),
+    #[error("an unconstrained syscall overwrote the memory at {0:#x}, which cannot be proven")]
+    UnprovableUnconstrainedSyscall(u32),
+    #[error("the {stage} stage used {peak} bytes of memory, more than its budget of {budget}")]
+    MemoryBudgetExceeded {
+        stage: String,
+        peak: u64,
+        budget: u64,
+    },
+}
+
+/// Checks the peak memory of the core stage against its budget, see [crate::utils::MemoryBudgets].
+fn check_memory_budget(opts: SP1CoreOpts) -> Result<(), SP1CoreProverError> {
+    match memory::exceeded_budget(opts.memory_budgets.core) {
+        Some((peak, budget)) => Err(SP1CoreProverError::MemoryBudgetExceeded {
+            stage: "core".to_string(),
+            peak,
+            budget,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the unconstrained syscalls made by an execution, if any, can be proven with `opts`,
+/// returning whether the proof is insecure.
+fn check_unconstrained_syscalls(
+    runtime: &Runtime,
+    opts: SP1CoreOpts,
+) -> Result<bool, SP1CoreProverError> {
+    if runtime.num_unconstrained_syscalls == 0 {
+        return Ok(false);
+    }
+    if !opts.allow_unconstrained {
+        return Err(SP1CoreProverError::UnconstrainedSyscalls(
+            runtime.num_unconstrained_syscalls,
+        ));
+    }
+    if let Some(addr) = runtime.unconstrained_syscall_overwrite {
+        return Err(SP1CoreProverError::UnprovableUnconstrainedSyscall(addr));
+    }
+    tracing::warn!(
+        "proving an execution with {} unconstrained syscalls, the proof is insecure",
+        runtime.num_unconstrained_syscalls
+    );
+    Ok(true)
 }
 
 pub fn prove_simple<SC: StarkGenericConfig>(
@@ -61,12 +113,29 @@ where
     // Setup the machine.
     let machine = RiscvAir::machine(config);
     let (pk, _) = machine.setup(runtime.program.as_ref());
+    prove_simple_with_setup(&machine, &pk, runtime)
+}
 
+/// Proves an executed runtime like [prove_simple], with the proving key of a previous setup of
+/// its program on `machine`.
+pub fn prove_simple_with_setup<SC: StarkGenericConfig>(
+    machine: &StarkMachine<SC, RiscvAir<SC::Val>>,
+    pk: &StarkProvingKey<SC>,
+    runtime: Runtime,
+) -> Result<MachineProof<SC>, SP1CoreProverError>
+where
+    SC::Challenger: Clone,
+    OpeningProof<SC>: Send + Sync,
+    Com<SC>: Send + Sync,
+    PcsProverData<SC>: Send + Sync,
+    ShardMainData<SC>: Serialize + DeserializeOwned,
+    <SC as StarkGenericConfig>::Val: PrimeField32,
+{
     // Prove the program.
     let mut challenger = machine.config().challenger();
     let proving_start = Instant::now();
     let proof = machine.prove::<LocalProver<_, _>>(
-        &pk,
+        pk,
         runtime.record,
         &mut challenger,
         SP1CoreOpts::default(),
@@ -100,16 +169,67 @@ where
     ShardMainData<SC>: Serialize + DeserializeOwned,
     <SC as StarkGenericConfig>::Val: PrimeField32,
 {
-    prove_with_subproof_verifier::<SC, DefaultSubproofVerifier>(program, stdin, config, opts, None)
+    let (proof, public_values, _) = prove_with_subproof_verifier::<SC, DefaultSubproofVerifier>(
+        program,
+        stdin,
+        config,
+        opts,
+        None,
+        &UnconstrainedSyscalls::default(),
+    )?;
+    Ok((proof, public_values))
 }
 
+/// Proves the execution of a program like [prove], verifying its subproofs with
+/// `subproof_verifier` and handling its unconstrained syscalls with `unconstrained_syscalls`.
+///
+/// Also returns whether the proof is insecure, because the program made unconstrained syscalls
+/// allowed by [SP1CoreOpts::allow_unconstrained].
+
 pub fn prove_with_subproof_verifier<SC: StarkGenericConfig + Send + Sync, V: SubproofVerifier>(
     program: Program,
     stdin: &SP1Stdin,
     config: SC,
     opts: SP1CoreOpts,
     subproof_verifier: Option<Arc<V>>,
-) -> Result<(MachineProof<SC>, Vec<u8>), SP1CoreProverError>
+    unconstrained_syscalls: &UnconstrainedSyscalls,
+) -> Result<(MachineProof<SC>, Vec<u8>, bool), SP1CoreProverError>
+where
+    SC::Challenger: Clone,
+    OpeningProof<SC>: Send + Sync,
+    Com<SC>: Send + Sync,
+    PcsProverData<SC>: Send + Sync,
+    ShardMainData<SC>: Serialize + DeserializeOwned,
+    <SC as StarkGenericConfig>::Val: PrimeField32,
+{
+    // Setup the machine.
+    let machine = RiscvAir::machine(config);
+    let (pk, vk) = machine.setup(&program);
+    prove_with_setup(
+        program,
+        &machine,
+        &pk,
+        &vk,
+        stdin,
+        opts,
+        subproof_verifier,
+        unconstrained_syscalls,
+    )
+}
+
+/// Proves the execution of a program like [prove_with_subproof_verifier], with the keys of a
+/// previous setup of the program on `machine`, so that proving the same program again only pays
+/// for the execution-dependent work.
+pub fn prove_with_setup<SC: StarkGenericConfig + Send + Sync, V: SubproofVerifier>(
+    program: Program,
+    machine: &StarkMachine<SC, RiscvAir<SC::Val>>,
+    pk: &StarkProvingKey<SC>,
+    vk: &StarkVerifyingKey<SC>,
+    stdin: &SP1Stdin,
+    opts: SP1CoreOpts,
+    subproof_verifier: Option<Arc<V>>,
+    unconstrained_syscalls: &UnconstrainedSyscalls,
+) -> Result<(MachineProof<SC>, Vec<u8>, bool), SP1CoreProverError>
 where
     SC::Challenger: Clone,
     OpeningProof<SC>: Send + Sync,
@@ -119,10 +239,20 @@ where
     <SC as StarkGenericConfig>::Val: PrimeField32,
 {
     let proving_start = Instant::now();
+    memory::reset_peak();
 
-    // Execute the program.
+    // Execute the program, drawing the lookup ids from a seed if the proof must be reproducible.
+    let seed = opts
+        .deterministic
+        .then(|| determinism_seed(&program, stdin, &opts));
     let mut runtime = Runtime::new(program.clone(), opts);
+    if let Some(seed) = seed {
+        runtime.seed_lookup_ids(seed);
+    }
+    runtime.unconstrained_syscalls = unconstrained_syscalls.clone();
     runtime.write_vecs(&stdin.buffer);
+    runtime.write_config(&stdin.config_bytes());
+    runtime.write_entropy(&stdin.entropy);
     for proof in stdin.proofs.iter() {
         runtime.write_proof(proof.0.clone(), proof.1.clone());
     }
@@ -130,67 +260,78 @@ where
         runtime.subproof_verifier = deferred_fn;
     }
 
-    // Setup the machine.
-    let machine = RiscvAir::machine(config);
-    let (pk, vk) = machine.setup(runtime.program.as_ref());
-
     // If we don't need to batch, we can just run the program normally and prove it.
     if opts.shard_batch_size == 0 {
         // Execute the runtime and collect all the events..
         runtime.run().map_err(SP1CoreProverError::ExecutionError)?;
+        let insecure = check_unconstrained_syscalls(&runtime, opts)?;
 
         // If debugging is enabled, we will also debug the constraints.
         #[cfg(feature = "debug")]
         {
             let mut challenger = machine.config().challenger();
-            machine.debug_constraints(&pk, runtime.record.clone(), &mut challenger);
+            machine.debug_constraints(pk, runtime.record.clone(), &mut challenger);
         }
 
         // Generate the proof and return the proof and public values.
         let public_values = std::mem::take(&mut runtime.state.public_values_stream);
-        let proof = prove_simple(machine.config().clone(), runtime)?;
-        return Ok((proof, public_values));
+        let proof = prove_simple_with_setup(machine, pk, runtime)?;
+        return Ok((proof, public_values, insecure));
     }
 
-    // Execute the program, saving checkpoints at the start of every `shard_batch_size` cycle range.
-    let mut checkpoints = Vec::new();
-    let (public_values_stream, public_values) = loop {
-        // Execute the runtime until we reach a checkpoint.
-        let (checkpoint, done) = tracing::info_span!("collect_checkpoints")
-            .in_scope(|| runtime.execute_state())
+    // Execute the program, capturing a snapshot at the start of every shard, or saving checkpoints
+    // at the start of every `shard_batch_size` cycle range. The syscalls made from each are recorded
+    // to check that tracing them again makes the same ones.
+    runtime.enable_effect_log();
+    let checkpoints = if opts.replay_from_snapshots {
+        let snapshots = tracing::info_span!("collect_snapshots")
+            .in_scope(|| runtime.execute_snapshots())
             .map_err(SP1CoreProverError::ExecutionError)?;
-
-        // Save the checkpoint to a temp file.
-        let mut tempfile = tempfile::tempfile().map_err(SP1CoreProverError::IoError)?;
-        let mut writer = std::io::BufWriter::new(&mut tempfile);
-        bincode::serialize_into(&mut writer, &checkpoint)
-            .map_err(SP1CoreProverError::SerializationError)?;
-        writer.flush().map_err(SP1CoreProverError::IoError)?;
-        drop(writer);
-        tempfile
-            .seek(std::io::SeekFrom::Start(0))
-            .map_err(SP1CoreProverError::IoError)?;
-        checkpoints.push(tempfile);
-
-        // If we've reached the final checkpoint, break out of the loop.
-        if done {
-            break (
-                std::mem::take(&mut runtime.state.public_values_stream),
-                runtime.record.public_values,
-            );
+        Checkpoints::Snapshots(snapshots)
+    } else {
+        let mut checkpoints = Vec::new();
+        loop {
+            let effects_start = runtime.effects.as_ref().map_or(0, Vec::len);
+            // Execute the runtime until we reach a checkpoint.
+            let (checkpoint, done) = tracing::info_span!("collect_checkpoints")
+                .in_scope(|| runtime.execute_state())
+                .map_err(SP1CoreProverError::ExecutionError)?;
+
+            // Save the checkpoint to a temp file.
+            let mut tempfile = tempfile::tempfile().map_err(SP1CoreProverError::IoError)?;
+            let mut writer = std::io::BufWriter::new(&mut tempfile);
+            bincode::serialize_into(&mut writer, &checkpoint)
+                .map_err(SP1CoreProverError::SerializationError)?;
+            writer.flush().map_err(SP1CoreProverError::IoError)?;
+            drop(writer);
+            tempfile
+                .seek(std::io::SeekFrom::Start(0))
+                .map_err(SP1CoreProverError::IoError)?;
+            let effects_end = runtime.effects.as_ref().map_or(0, Vec::len);
+            checkpoints.push((tempfile, effects_start..effects_end));
+
+            // If we've reached the final checkpoint, break out of the loop.
+            if done {
+                break Checkpoints::Files(checkpoints);
+            }
         }
     };
+    let public_values_stream = std::mem::take(&mut runtime.state.public_values_stream);
+    let public_values = runtime.record.public_values;
+    let insecure = check_unconstrained_syscalls(&runtime, opts)?;
 
     // For each checkpoint, generate events, shard them, commit shards, and observe in challenger.
-    let sharding_config = ShardingConfig::default();
+    let sharding_config = ShardingConfig::new(opts.shard_size);
     let mut shard_main_datas = Vec::new();
     let mut challenger = machine.config().challenger();
     vk.observe_into(&mut challenger);
-    for (num, checkpoint_file) in checkpoints.iter_mut().enumerate() {
-        let (mut record, _) = tracing::info_span!("commit_checkpoint", num)
-            .in_scope(|| trace_checkpoint(program.clone(), checkpoint_file, opts));
+    let effects = runtime.effects.as_deref().unwrap();
+    let num_checkpoints = checkpoints.len(opts.shard_batch_size);
+    for num in 0..num_checkpoints {
+        let (mut record, _) = tracing::info_span!("commit_checkpoint", num).in_scope(|| {
+            checkpoints.trace(&program, num, opts, seed, unconstrained_syscalls, effects)
+        })?;
         record.public_values = public_values;
-        reset_seek(&mut *checkpoint_file);
 
         // Shard the record into shards.
         let checkpoint_shards =
@@ -198,7 +339,7 @@ where
 
         // Commit to each shard.
         let (commitments, commit_data) = tracing::info_span!("commit")
-            .in_scope(|| LocalProver::commit_shards(&machine, &checkpoint_shards, opts));
+            .in_scope(|| LocalProver::commit_shards(machine, &checkpoint_shards, opts));
         shard_main_datas.push(commit_data);
 
         // Observe the commitments.
@@ -206,42 +347,41 @@ where
             challenger.observe(commitment);
             challenger.observe_slice(&shard.public_values::<SC::Val>()[0..machine.num_pv_elts()]);
         }
+        check_memory_budget(opts)?;
     }
 
     // For each checkpoint, generate events and shard again, then prove the shards.
     let mut shard_proofs = Vec::<ShardProof<SC>>::new();
     let mut report_aggregate = ExecutionReport::default();
-    for (num, mut checkpoint_file) in checkpoints.into_iter().enumerate() {
+    for num in 0..num_checkpoints {
         let checkpoint_shards = {
-            let (mut events, report) = tracing::info_span!("prove_checkpoint", num)
-                .in_scope(|| trace_checkpoint(program.clone(), &checkpoint_file, opts));
+            let (mut events, report) =
+                tracing::info_span!("prove_checkpoint", num).in_scope(|| {
+                    checkpoints.trace(&program, num, opts, seed, unconstrained_syscalls, effects)
+                })?;
             report_aggregate += report;
             events.public_values = public_values;
-            reset_seek(&mut checkpoint_file);
             tracing::debug_span!("shard").in_scope(|| machine.shard(events, &sharding_config))
         };
-        let mut checkpoint_proofs = checkpoint_shards
-            .into_iter()
-            .map(|shard| {
-                let config = machine.config();
-                let shard_data =
-                    LocalProver::commit_main(config, &machine, &shard, shard.index() as usize);
-
-                let chip_ordering = shard_data.chip_ordering.clone();
-                let ordered_chips = machine
-                    .shard_chips_ordered(&chip_ordering)
-                    .collect::<Vec<_>>()
-                    .to_vec();
-                LocalProver::prove_shard(
-                    config,
-                    &pk,
-                    &ordered_chips,
-                    shard_data,
-                    &mut challenger.clone(),
-                )
-            })
-            .collect::<Vec<_>>();
-        shard_proofs.append(&mut checkpoint_proofs);
+        for shard in checkpoint_shards {
+            let config = machine.config();
+            let shard_data =
+                LocalProver::commit_main(config, machine, &shard, shard.index() as usize);
+
+            let chip_ordering = shard_data.chip_ordering.clone();
+            let ordered_chips = machine
+                .shard_chips_ordered(&chip_ordering)
+                .collect::<Vec<_>>()
+                .to_vec();
+            shard_proofs.push(LocalProver::prove_shard(
+                config,
+                pk,
+                &ordered_chips,
+                shard_data,
+                &mut challenger.clone(),
+            ));
+            check_memory_budget(opts)?;
+        }
     }
     // Log some of the `ExecutionReport` information.
     tracing::info!(
@@ -272,7 +412,7 @@ where
         bincode::serialize(&proof).unwrap().len(),
     );
 
-    Ok((proof, public_values_stream))
+    Ok((proof, public_values_stream, insecure))
 }
 
 /// Runs a program and returns the public values stream.
@@ -283,6 +423,8 @@ pub fn run_test_io(
     let runtime = tracing::info_span!("runtime.run(...)").in_scope(|| {
         let mut runtime = Runtime::new(program, SP1CoreOpts::default());
         runtime.write_vecs(&inputs.buffer);
+        runtime.write_config(&inputs.config_bytes());
+        runtime.write_entropy(&inputs.entropy);
         runtime.run().unwrap();
         runtime
     });
@@ -371,27 +513,145 @@ where
     Ok(proof)
 }
 
+/// Traces the execution from a checkpoint, checking that it makes the syscalls `effects.1` of the
+/// effects `effects.0` recorded by the execution pass. The lookup ids are drawn from `seed`, if any.
 fn trace_checkpoint(
     program: Program,
     file: &File,
     opts: SP1CoreOpts,
-) -> (ExecutionRecord, ExecutionReport) {
+    seed: Option<[u8; 32]>,
+    unconstrained_syscalls: &UnconstrainedSyscalls,
+    effects: (&[Effect], std::ops::Range<usize>),
+) -> Result<(ExecutionRecord, ExecutionReport), SP1CoreProverError> {
     let mut reader = std::io::BufReader::new(file);
     let state = bincode::deserialize_from(&mut reader).expect("failed to deserialize state");
     let mut runtime = Runtime::recover(program.clone(), state, opts);
+    if let Some(seed) = seed {
+        runtime.seed_lookup_ids(seed);
+    }
+    runtime.unconstrained_syscalls = unconstrained_syscalls.clone();
     // We already passed the deferred proof verifier when creating checkpoints, so the proofs were
     // already verified. So here we use a noop verifier to not print any warnings.
     runtime.subproof_verifier = Arc::new(NoOpSubproofVerifier);
+    runtime.enable_effect_log();
     let (events, _) =
         tracing::debug_span!("runtime.trace").in_scope(|| runtime.execute_record().unwrap());
-    (events, runtime.report)
+    let (expected, range) = effects;
+    first_divergence(
+        &expected[range.clone()],
+        runtime.effects.as_deref().unwrap(),
+        range.start,
+    )
+    .map_err(SP1CoreProverError::Nondeterminism)?;
+    Ok((events, runtime.report))
 }
 
-fn reset_seek(file: &mut File) {
+fn reset_seek(mut file: &File) {
     file.seek(std::io::SeekFrom::Start(0))
         .expect("failed to seek to start of tempfile");
 }
 
+/// The starting points of the second execution of [prove_with_subproof_verifier], which records
+/// the events.
+enum Checkpoints {
+    /// The states at the start of each batch of shards, saved to disk, with the ranges of the
+    /// effect log made by each batch.
+    Files(Vec<(File, std::ops::Range<usize>)>),
+    /// The snapshots at the start of each shard, replayed in parallel a batch at a time.
+    Snapshots(Snapshots),
+}
+
+impl Checkpoints {
+    /// The number of batches of shards.
+    fn len(&self, shard_batch_size: usize) -> usize {
+        match self {
+            Self::Files(files) => files.len(),
+            Self::Snapshots(snapshots) => snapshots.shards.len().div_ceil(shard_batch_size),
+        }
+    }
+
+    /// Records the events of the `num`-th batch of shards.
+    fn trace(
+        &self,
+        program: &Program,
+        num: usize,
+        opts: SP1CoreOpts,
+        seed: Option<[u8; 32]>,
+        unconstrained_syscalls: &UnconstrainedSyscalls,
+        effects: &[Effect],
+    ) -> Result<(ExecutionRecord, ExecutionReport), SP1CoreProverError> {
+        match self {
+            Self::Files(files) => {
+                let (file, range) = &files[num];
+                let traced = trace_checkpoint(
+                    program.clone(),
+                    file,
+                    opts,
+                    seed,
+                    unconstrained_syscalls,
+                    (effects, range.clone()),
+                );
+                reset_seek(file);
+                traced
+            }
+            Self::Snapshots(snapshots) => {
+                let start = num * opts.shard_batch_size;
+                let end = (start + opts.shard_batch_size).min(snapshots.shards.len());
+                let traces = (start..end)
+                    .into_par_iter()
+                    .map(|index| {
+                        trace_snapshot(
+                            program.clone(),
+                            snapshots,
+                            index,
+                            opts,
+                            seed,
+                            unconstrained_syscalls,
+                            effects,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut traces = traces.into_iter();
+                let (mut record, mut report) = traces.next().expect("no shards to trace");
+                for (mut next, next_report) in traces {
+                    record.append(&mut next);
+                    report += next_report;
+                }
+                Ok((record, report))
+            }
+        }
+    }
+}
+
+/// Records the events of the `index`-th shard of `snapshots`, like [trace_checkpoint].
+fn trace_snapshot(
+    program: Program,
+    snapshots: &Snapshots,
+    index: usize,
+    opts: SP1CoreOpts,
+    seed: Option<[u8; 32]>,
+    unconstrained_syscalls: &UnconstrainedSyscalls,
+    effects: &[Effect],
+) -> Result<(ExecutionRecord, ExecutionReport), SP1CoreProverError> {
+    let mut runtime = Runtime::from_snapshot(program, snapshots, index, opts);
+    if let Some(seed) = seed {
+        runtime.seed_lookup_ids(seed);
+    }
+    runtime.unconstrained_syscalls = unconstrained_syscalls.clone();
+    runtime.subproof_verifier = Arc::new(NoOpSubproofVerifier);
+    runtime.enable_effect_log();
+    let (events, _) =
+        tracing::debug_span!("runtime.trace").in_scope(|| runtime.execute_record().unwrap());
+    let range = snapshots.effects_range(index, effects.len());
+    first_divergence(
+        &effects[range.clone()],
+        runtime.effects.as_deref().unwrap(),
+        range.start,
+    )
+    .map_err(SP1CoreProverError::Nondeterminism)?;
+    Ok((events, runtime.report))
+}
+
 #[cfg(debug_assertions)]
 #[cfg(not(doctest))]
 pub fn uni_stark_prove<SC, A>(
@@ -478,6 +738,7 @@ pub mod baby_bear_poseidon2 {
     use sp1_primitives::RC_16_30;
 
     use crate::stark::StarkGenericConfig;
+    use crate::utils::FirstWitnessChallenger;
 
     pub type Val = BabyBear;
     pub type Challenge = BinomialExtensionField<Val, 4>;
@@ -494,7 +755,7 @@ pub mod baby_bear_poseidon2 {
     >;
     pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
     pub type Dft = Radix2DitParallel;
-    pub type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    pub type Challenger = FirstWitnessChallenger<DuplexChallenger<Val, Perm, 16, 8>>;
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
     pub fn my_perm() -> Perm {
@@ -640,7 +901,7 @@ pub mod baby_bear_poseidon2 {
         }
 
         fn challenger(&self) -> Self::Challenger {
-            Challenger::new(self.perm.clone())
+            FirstWitnessChallenger(DuplexChallenger::new(self.perm.clone()))
         }
     }
 }
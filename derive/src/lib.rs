@@ -165,6 +165,13 @@ pub fn machine_air_derive(input: TokenStream) -> TokenStream {
                 }
             });
 
+            let check_program_arms = variants.iter().map(|(variant_name, field)| {
+                let field_ty = &field.ty;
+                quote! {
+                    #name::#variant_name(x) => <#field_ty as #sp1_core_path::air::MachineAir<F>>::check_program(x, program)
+                }
+            });
+
             let generate_trace_arms = variants.iter().map(|(variant_name, field)| {
                 let field_ty = &field.ty;
                 quote! {
@@ -186,6 +193,13 @@ pub fn machine_air_derive(input: TokenStream) -> TokenStream {
                 }
             });
 
+            let min_trace_height_arms = variants.iter().map(|(variant_name, field)| {
+                let field_ty = &field.ty;
+                quote! {
+                    #name::#variant_name(x) => <#field_ty as #sp1_core_path::air::MachineAir<F>>::min_trace_height(x)
+                }
+            });
+
             let machine_air = quote! {
                 impl #impl_generics #sp1_core_path::air::MachineAir<F> for #name #ty_generics #where_clause {
                     type Record = #execution_record_path;
@@ -213,6 +227,15 @@ pub fn machine_air_derive(input: TokenStream) -> TokenStream {
                         }
                     }
 
+                    fn check_program(
+                        &self,
+                        program: &#program_path,
+                    ) -> Result<(), #sp1_core_path::stark::SetupError> {
+                        match self {
+                            #(#check_program_arms,)*
+                        }
+                    }
+
                     fn generate_trace(
                         &self,
                         input: &#execution_record_path,
@@ -238,6 +261,12 @@ pub fn machine_air_derive(input: TokenStream) -> TokenStream {
                             #(#included_arms,)*
                         }
                     }
+
+                    fn min_trace_height(&self) -> usize {
+                        match self {
+                            #(#min_trace_height_arms,)*
+                        }
+                    }
                 }
             };
 
@@ -303,6 +332,67 @@ pub fn cycle_tracker(_attr: TokenStream, item: TokenStream) -> TokenStream {
     result.into()
 }
 
+/// Turns an inline module of tests into a program running them in the zkVM.
+///
+/// Every function of the module marked with `#[test]` is collected, in order, and the module gets
+/// the entrypoint of the program, which runs the tests asked for by the host with
+/// `sp1_zkvm::testing::run_tests`. The program is run on the host with
+/// `sp1_core::utils::GuestTestRunner`.
+///
+/// ```ignore
+/// #![no_main]
+///
+/// #[sp1_derive::sp1_test]
+/// mod tests {
+///     #[test]
+///     fn addition() {
+///         assert_eq!(1 + 1, 2);
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn sp1_test(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(item as syn::ItemMod);
+    let Some((_, items)) = input.content.as_mut() else {
+        return syn::Error::new_spanned(&input, "#[sp1_test] requires an inline module")
+            .to_compile_error()
+            .into();
+    };
+
+    let mut tests = Vec::new();
+    for item in items.iter_mut() {
+        if let syn::Item::Fn(item_fn) = item {
+            let len = item_fn.attrs.len();
+            item_fn.attrs.retain(|attr| !attr.path.is_ident("test"));
+            if item_fn.attrs.len() != len {
+                tests.push(item_fn.sig.ident.clone());
+            }
+        }
+    }
+
+    let cases = tests.iter().map(|name| {
+        quote! {
+            ::sp1_zkvm::testing::TestCase {
+                name: stringify!(#name),
+                test: #name,
+            }
+        }
+    });
+    items.push(parse_quote! {
+        const SP1_TESTS: &[::sp1_zkvm::testing::TestCase] = &[#(#cases),*];
+    });
+    items.push(parse_quote! {
+        fn sp1_test_main() {
+            ::sp1_zkvm::testing::run_tests(SP1_TESTS);
+        }
+    });
+    items.push(parse_quote! {
+        ::sp1_zkvm::entrypoint!(sp1_test_main);
+    });
+
+    quote!(#input).into()
+}
+
 fn find_sp1_core_path(attrs: &[syn::Attribute]) -> syn::Ident {
     for attr in attrs {
         if attr.path.is_ident("sp1_core_path") {
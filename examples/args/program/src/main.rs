@@ -0,0 +1,15 @@
+//! A program invoked like a command line tool, echoing its arguments and environment.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main, args, env);
+
+use std::collections::BTreeMap;
+
+pub fn main(args: Vec<String>, env: BTreeMap<String, String>) {
+    // The flags a command line parser would see, after the name of the program.
+    let verbose = args.iter().skip(1).any(|arg| arg == "--verbose");
+
+    sp1_zkvm::io::commit(&args);
+    sp1_zkvm::io::commit(&env);
+    sp1_zkvm::io::commit(&verbose);
+}
@@ -0,0 +1,3 @@
+fn main() {
+    sp1_helper::build_program(&format!("{}/../program", env!("CARGO_MANIFEST_DIR")));
+}
@@ -0,0 +1,47 @@
+//! A script running a program with command line arguments and environment variables.
+
+use std::collections::BTreeMap;
+
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+
+/// The ELF we want to execute inside the zkVM.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let args = ["echo", "--verbose", "héllo wörld", ""];
+    let env = BTreeMap::from([
+        ("HOME".to_string(), "/root".to_string()),
+        ("LANG".to_string(), "C.UTF-8".to_string()),
+    ]);
+    let mut stdin = SP1Stdin::new();
+    stdin.write_args_with_env(&args, &env);
+
+    // Generate the proof for the given program and input.
+    let client = ProverClient::new();
+    let (pk, vk) = client.setup(ELF);
+    let mut proof = client.prove(&pk, stdin.clone()).expect("proving failed");
+
+    // The program echoes its arguments and environment.
+    assert_eq!(proof.public_values.read::<Vec<String>>(), args);
+    assert_eq!(proof.public_values.read::<BTreeMap<String, String>>(), env);
+    assert!(proof.public_values.read::<bool>());
+
+    // The arguments are committed as part of the input digest.
+    assert_eq!(proof.input_digest(), stdin.digest());
+
+    // Verify proof.
+    client.verify(&proof, &vk).expect("verification failed");
+
+    // A program without arguments nor environment.
+    let mut stdin = SP1Stdin::new();
+    stdin.write_args::<&str>(&[]);
+    let (mut public_values, _) = client.execute(ELF, stdin).expect("execution failed");
+    assert!(public_values.read::<Vec<String>>().is_empty());
+    assert!(public_values.read::<BTreeMap<String, String>>().is_empty());
+    assert!(!public_values.read::<bool>());
+
+    println!("successfully generated and verified proof for the program!")
+}
@@ -0,0 +1,9 @@
+//! Commits the `n`-th Fibonacci number.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    let n = sp1_zkvm::io::read::<u32>();
+    sp1_zkvm::io::commit(&n);
+    sp1_zkvm::io::commit(&bundle_program::sequence(0, 1, n));
+}
@@ -0,0 +1,17 @@
+//! The sequences computed by the binaries of the program, which are built with different features.
+
+/// The `n`-th term of the sequence starting with `a` and `b`, where each term is the sum of the
+/// two before it. With the `wrapping` feature the sums wrap around, and otherwise they panic on
+/// overflow.
+pub fn sequence(a: u64, b: u64, n: u32) -> u64 {
+    let (mut a, mut b) = (a, b);
+    for _ in 0..n {
+        let sum = if cfg!(feature = "wrapping") {
+            a.wrapping_add(b)
+        } else {
+            a.checked_add(b).expect("overflow")
+        };
+        (a, b) = (b, sum);
+    }
+    a
+}
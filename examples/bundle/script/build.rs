@@ -0,0 +1,14 @@
+use sp1_helper::{build_program_with_args, BinaryTarget, BuildArgs};
+
+fn main() {
+    build_program_with_args(
+        "../program",
+        BuildArgs {
+            binaries: vec![
+                BinaryTarget::new("fibonacci"),
+                BinaryTarget::new("lucas").with_features(&["wrapping"]),
+            ],
+            ..Default::default()
+        },
+    );
+}
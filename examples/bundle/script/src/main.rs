@@ -0,0 +1,34 @@
+//! A script proving the two binaries of a program through a bundle.
+use sp1_sdk::{utils, ProgramBundle, ProverClient, SP1Stdin};
+
+const FIBONACCI_ELF: &[u8] = include_bytes!(env!("SP1_ELF_FIBONACCI"));
+const LUCAS_ELF: &[u8] = include_bytes!(env!("SP1_ELF_LUCAS"));
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let client = ProverClient::new();
+    let bundle = ProgramBundle::new(&client)
+        .with_program("fibonacci", FIBONACCI_ELF)
+        .unwrap()
+        .with_program("lucas", LUCAS_ELF)
+        .unwrap();
+    let digest = bundle
+        .digest()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    println!("bundle digest: 0x{}", digest);
+
+    for name in ["fibonacci", "lucas"] {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&20u32);
+        let mut proof = bundle.prove(name, stdin).expect("proving failed");
+        bundle.verify(name, &proof).expect("verification failed");
+
+        let n = proof.public_values.read::<u32>();
+        let value = proof.public_values.read::<u64>();
+        println!("{}({}) = {}", name, n, value);
+    }
+}
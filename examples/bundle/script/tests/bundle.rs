@@ -0,0 +1,64 @@
+//! Proves the two binaries of the program, built with different features, through a bundle.
+
+use sp1_sdk::{verify_bundle_membership, HashableKey, ProgramBundle, ProverClient, SP1Stdin};
+
+const FIBONACCI_ELF: &[u8] = include_bytes!(env!("SP1_ELF_FIBONACCI"));
+const LUCAS_ELF: &[u8] = include_bytes!(env!("SP1_ELF_LUCAS"));
+
+#[test]
+fn test_prove_bundle() {
+    let client = ProverClient::new();
+    let bundle = ProgramBundle::new(&client)
+        .with_program("fibonacci", FIBONACCI_ELF)
+        .unwrap()
+        .with_program("lucas", LUCAS_ELF)
+        .unwrap();
+
+    for (name, expected) in [("fibonacci", 6765u64), ("lucas", 15127)] {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&20u32);
+        let mut proof = bundle.prove(name, stdin).unwrap();
+        bundle.verify(name, &proof).unwrap();
+        assert_eq!(proof.public_values.read::<u32>(), 20);
+        assert_eq!(proof.public_values.read::<u64>(), expected);
+
+        let leaf = bundle.vk(name).unwrap().hash_bytes();
+        let merkle_proof = bundle.merkle_proof(name).unwrap();
+        assert!(verify_bundle_membership(
+            bundle.digest(),
+            leaf,
+            &merkle_proof
+        ));
+    }
+
+    // A proof of one binary doesn't verify as the other.
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&5u32);
+    let proof = bundle.prove("fibonacci", stdin).unwrap();
+    assert!(bundle.verify("lucas", &proof).is_err());
+}
+
+#[test]
+fn test_bundle_digest() {
+    let client = ProverClient::new();
+    let digest = |programs: &[(&str, &[u8])]| {
+        programs
+            .iter()
+            .fold(ProgramBundle::new(&client), |bundle, (name, elf)| {
+                bundle.with_program(*name, elf).unwrap()
+            })
+            .digest()
+    };
+
+    // The digest is stable, and doesn't depend on the order of the programs.
+    let bundle = [("fibonacci", FIBONACCI_ELF), ("lucas", LUCAS_ELF)];
+    let digest_of_bundle = digest(&bundle);
+    assert_eq!(digest(&bundle), digest_of_bundle);
+    assert_eq!(
+        digest(&[("lucas", LUCAS_ELF), ("fibonacci", FIBONACCI_ELF)]),
+        digest_of_bundle
+    );
+
+    // It commits to every program.
+    assert_ne!(digest(&[("fibonacci", FIBONACCI_ELF)]), digest_of_bundle);
+}
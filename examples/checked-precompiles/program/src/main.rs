@@ -0,0 +1,344 @@
+#![no_main]
+
+//! Tests of the checked precompile wrappers: each wrapper is checked against the raw syscall, and
+//! misused on purpose in a `*_misuse` test which must panic before the `ecall`.
+
+#[sp1_derive::sp1_test]
+mod tests {
+    use sp1_zkvm::precompiles::bls12381::Bls12381;
+    use sp1_zkvm::precompiles::bn254::Bn254;
+    use sp1_zkvm::precompiles::checked::{self, Aligned};
+    use sp1_zkvm::precompiles::secp256k1::Secp256k1Operations;
+    use sp1_zkvm::precompiles::utils::CurveOperations;
+    use sp1_zkvm::syscalls;
+
+    /// The little endian words of `bytes`.
+    fn words<const N: usize>(bytes: &[u8]) -> [u32; N] {
+        let mut words = [0; N];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        words
+    }
+
+    /// The 256 bits integer `n`, as little endian words.
+    fn uint256(n: u32) -> [u32; 8] {
+        let mut words = [0; 8];
+        words[0] = n;
+        words
+    }
+
+    const SECP256K1_G: [u32; 16] = <Secp256k1Operations as CurveOperations<16>>::GENERATOR;
+    const BN254_G: [u32; 16] = <Bn254 as CurveOperations<16>>::GENERATOR;
+    const BLS12381_G: [u32; 24] = <Bls12381 as CurveOperations<24>>::GENERATOR;
+
+    /// The big endian X coordinate of the secp256k1 generator.
+    const SECP256K1_G_X: [u8; 32] = [
+        0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b,
+        0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8,
+        0x17, 0x98,
+    ];
+
+    /// The compressed encoding of the BLS12-381 G1 generator.
+    const BLS12381_G_COMPRESSED: [u8; 48] = [
+        0x97, 0xf1, 0xd3, 0xa7, 0x31, 0x97, 0xd7, 0x94, 0x26, 0x95, 0x63, 0x8c, 0x4f, 0xa9, 0xac,
+        0x0f, 0xc3, 0x68, 0x8c, 0x4f, 0x97, 0x74, 0xb9, 0x05, 0xa1, 0x4e, 0x3a, 0x3f, 0x17, 0x1b,
+        0xac, 0x58, 0x6c, 0x55, 0xe8, 0x3f, 0xf9, 0x7a, 0x1a, 0xef, 0xfb, 0x3a, 0xf0, 0x0a, 0xdb,
+        0x22, 0xc6, 0xbb,
+    ];
+
+    /// The compressed encoding of an ed25519 point, see `tests/ed-decompress`.
+    const ED_COMPRESSED: [u8; 32] = [
+        0xec, 0x17, 0x2b, 0x93, 0xad, 0x5e, 0x56, 0x3b, 0xf4, 0x93, 0x2c, 0x70, 0xe1, 0x24, 0x50,
+        0x34, 0xc3, 0x54, 0x67, 0xef, 0x2e, 0xfd, 0x4d, 0x64, 0xeb, 0xf8, 0x19, 0x68, 0x34, 0x67,
+        0xe2, 0xbf,
+    ];
+
+    /// Two ed25519 points and their sum, see `tests/ed-add`.
+    const ED_A: [u8; 64] = [
+        195, 166, 157, 207, 218, 220, 175, 197, 111, 177, 123, 23, 73, 72, 114, 103, 28, 246, 66,
+        207, 66, 146, 187, 234, 136, 238, 133, 145, 47, 196, 216, 199, 79, 31, 224, 30, 179, 122,
+        51, 84, 116, 12, 4, 189, 198, 198, 190, 22, 71, 201, 143, 249, 92, 56, 147, 133, 92, 187,
+        130, 33, 152, 19, 171, 73,
+    ];
+    const ED_B: [u8; 64] = [
+        197, 189, 200, 77, 201, 212, 57, 105, 191, 133, 123, 170, 167, 50, 114, 38, 37, 102, 188,
+        29, 215, 227, 157, 142, 252, 31, 129, 67, 24, 255, 114, 136, 115, 94, 94, 55, 43, 200, 117,
+        224, 139, 251, 238, 45, 80, 154, 70, 213, 219, 78, 201, 108, 73, 203, 72, 45, 167, 131,
+        199, 47, 82, 134, 53, 62,
+    ];
+    const ED_A_PLUS_B: [u8; 64] = [
+        49, 144, 129, 197, 86, 163, 62, 48, 222, 208, 213, 200, 219, 90, 163, 54, 211, 248, 178,
+        224, 238, 167, 235, 219, 251, 247, 189, 239, 194, 16, 16, 80, 19, 106, 20, 198, 72, 56,
+        103, 111, 68, 201, 29, 107, 75, 208, 193, 232, 181, 186, 175, 22, 213, 187, 253, 125, 44,
+        80, 222, 209, 159, 125, 202, 37,
+    ];
+
+    #[test]
+    fn sha256_extend() {
+        let mut w = [0u32; 64];
+        for (i, word) in w.iter_mut().take(16).enumerate() {
+            *word = i as u32 * 0x0101_0101;
+        }
+        let mut expected = w;
+        syscalls::syscall_sha256_extend(expected.as_mut_ptr());
+        checked::sha256_extend(&mut w);
+        assert_eq!(w, expected);
+    }
+
+    #[test]
+    fn sha256_extend_misuse() {
+        checked::sha256_extend(&mut [0u32; 63]);
+    }
+
+    #[test]
+    fn sha256_compress() {
+        let mut w = [0x6162_6380u32; 64];
+        let mut state = [0x6a09_e667u32; 8];
+        let mut expected = state;
+        syscalls::syscall_sha256_compress(w.as_mut_ptr(), expected.as_mut_ptr());
+        checked::sha256_compress(&w, &mut state);
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn sha256_compress_misuse() {
+        checked::sha256_compress(&[0u32; 64], &mut [0u32; 7]);
+    }
+
+    #[test]
+    fn keccak_permute() {
+        let mut state = [0u64; 25];
+        checked::keccak_permute(&mut state);
+        assert_eq!(state[0], 0xf125_8f79_40e1_dde7);
+    }
+
+    #[test]
+    fn keccak_permute_misuse() {
+        checked::keccak_permute(&mut [0u64; 24]);
+    }
+
+    #[test]
+    fn ed_add() {
+        let mut p: [u32; 16] = words(&ED_A);
+        checked::ed_add(&mut p, &words::<16>(&ED_B));
+        assert_eq!(p, words::<16>(&ED_A_PLUS_B));
+    }
+
+    #[test]
+    fn ed_add_misuse() {
+        let mut p: [u32; 16] = words(&ED_A);
+        checked::ed_add(&mut p, &words::<16>(&ED_B)[..15]);
+    }
+
+    #[test]
+    fn ed_decompress() {
+        let mut point = Aligned([0u8; 64]);
+        point.0[32..].copy_from_slice(&ED_COMPRESSED);
+        let mut expected = point;
+        syscalls::syscall_ed_decompress(&mut expected.0);
+        checked::ed_decompress(&mut point.0);
+        assert_eq!(point, expected);
+    }
+
+    #[test]
+    fn ed_decompress_misuse() {
+        let mut buffer = Aligned([0u8; 65]);
+        buffer.0[33..].copy_from_slice(&ED_COMPRESSED);
+        checked::ed_decompress(&mut buffer.0[1..]);
+    }
+
+    #[test]
+    fn secp256k1_add() {
+        let mut two_g = SECP256K1_G;
+        checked::secp256k1_double(&mut two_g);
+        let mut expected = two_g;
+        syscalls::syscall_secp256k1_add(expected.as_mut_ptr(), SECP256K1_G.as_ptr() as *mut u32);
+        let mut three_g = two_g;
+        checked::secp256k1_add(&mut three_g, &SECP256K1_G);
+        assert_eq!(three_g, expected);
+    }
+
+    #[test]
+    fn secp256k1_add_misuse() {
+        let mut p = [0u32; 17];
+        checked::secp256k1_add(&mut p, &SECP256K1_G);
+    }
+
+    #[test]
+    fn secp256k1_double() {
+        let mut expected = SECP256K1_G;
+        syscalls::syscall_secp256k1_double(expected.as_mut_ptr());
+        let mut p = SECP256K1_G;
+        checked::secp256k1_double(&mut p);
+        assert_eq!(p, expected);
+    }
+
+    #[test]
+    fn secp256k1_double_misuse() {
+        checked::secp256k1_double(&mut [1u32; 15]);
+    }
+
+    #[test]
+    fn secp256k1_decompress() {
+        let mut point = Aligned([0u8; 64]);
+        point.0[..32].copy_from_slice(&SECP256K1_G_X);
+        let mut expected = point;
+        syscalls::syscall_secp256k1_decompress(&mut expected.0, false);
+        checked::secp256k1_decompress(&mut point.0, false);
+        assert_eq!(point, expected);
+        // The generator has an even Y coordinate ending with 0xb8.
+        assert_eq!(point.0[63], 0xb8);
+    }
+
+    #[test]
+    fn secp256k1_decompress_misuse() {
+        let mut buffer = Aligned([0u8; 66]);
+        buffer.0[2..34].copy_from_slice(&SECP256K1_G_X);
+        checked::secp256k1_decompress(&mut buffer.0[2..], false);
+    }
+
+    #[test]
+    fn bn254_add() {
+        let mut two_g = BN254_G;
+        checked::bn254_double(&mut two_g);
+        let mut expected = two_g;
+        syscalls::syscall_bn254_add(expected.as_mut_ptr(), BN254_G.as_ptr());
+        let mut three_g = two_g;
+        checked::bn254_add(&mut three_g, &BN254_G);
+        assert_eq!(three_g, expected);
+    }
+
+    #[test]
+    fn bn254_add_misuse() {
+        let mut p = BN254_G;
+        checked::bn254_add(&mut p, &BN254_G[..8]);
+    }
+
+    #[test]
+    fn bn254_double() {
+        let mut expected = BN254_G;
+        syscalls::syscall_bn254_double(expected.as_mut_ptr());
+        let mut p = BN254_G;
+        checked::bn254_double(&mut p);
+        assert_eq!(p, expected);
+    }
+
+    #[test]
+    fn bn254_double_misuse() {
+        checked::bn254_double(&mut [1u32; 8]);
+    }
+
+    #[test]
+    fn bls12381_add() {
+        let mut two_g = BLS12381_G;
+        checked::bls12381_double(&mut two_g);
+        let mut expected = two_g;
+        syscalls::syscall_bls12381_add(expected.as_mut_ptr(), BLS12381_G.as_ptr());
+        let mut three_g = two_g;
+        checked::bls12381_add(&mut three_g, &BLS12381_G);
+        assert_eq!(three_g, expected);
+    }
+
+    #[test]
+    fn bls12381_add_misuse() {
+        // A point of a 256 bits curve.
+        let mut p = BLS12381_G;
+        checked::bls12381_add(&mut p, &BN254_G);
+    }
+
+    #[test]
+    fn bls12381_double() {
+        let mut expected = BLS12381_G;
+        syscalls::syscall_bls12381_double(expected.as_mut_ptr());
+        let mut p = BLS12381_G;
+        checked::bls12381_double(&mut p);
+        assert_eq!(p, expected);
+    }
+
+    #[test]
+    fn bls12381_double_misuse() {
+        let mut p = BN254_G;
+        checked::bls12381_double(&mut p);
+    }
+
+    #[test]
+    fn bls12381_decompress() {
+        let mut point = Aligned([0u8; 96]);
+        point.0[..48].copy_from_slice(&BLS12381_G_COMPRESSED);
+        let is_odd = (point.0[0] & 0b_0010_0000) >> 5 == 0;
+        point.0[0] &= 0b_0001_1111;
+        let mut expected = point;
+        syscalls::syscall_bls12381_decompress(&mut expected.0, is_odd);
+        checked::bls12381_decompress(&mut point.0, is_odd);
+        assert_eq!(point, expected);
+    }
+
+    #[test]
+    fn bls12381_decompress_misuse() {
+        let mut point = Aligned([0u8; 64]);
+        checked::bls12381_decompress(&mut point.0, false);
+    }
+
+    #[test]
+    fn bls12381_fp_add() {
+        let (mut x, mut y) = ([0u32; 12], [0u32; 12]);
+        x[0] = 2;
+        y[0] = 3;
+        checked::bls12381_fp_add(&mut x, &y);
+        assert_eq!(x[0], 5);
+        assert!(x[1..].iter().all(|&word| word == 0));
+    }
+
+    #[test]
+    fn bls12381_fp_add_misuse() {
+        checked::bls12381_fp_add(&mut [0u32; 12], &[0u32; 8]);
+    }
+
+    #[test]
+    fn bls12381_fp_sub() {
+        let (mut x, mut y) = ([0u32; 12], [0u32; 12]);
+        x[0] = 3;
+        y[0] = 2;
+        checked::bls12381_fp_sub(&mut x, &y);
+        assert_eq!(x[0], 1);
+        assert!(x[1..].iter().all(|&word| word == 0));
+    }
+
+    #[test]
+    fn bls12381_fp_sub_misuse() {
+        checked::bls12381_fp_sub(&mut [0u32; 11], &[0u32; 12]);
+    }
+
+    #[test]
+    fn bls12381_fp_mul() {
+        let (mut x, mut y) = ([0u32; 12], [0u32; 12]);
+        x[0] = 2;
+        y[0] = 3;
+        checked::bls12381_fp_mul(&mut x, &y);
+        assert_eq!(x[0], 6);
+        assert!(x[1..].iter().all(|&word| word == 0));
+    }
+
+    #[test]
+    fn bls12381_fp_mul_misuse() {
+        checked::bls12381_fp_mul(&mut [0u32; 12], &[0u32; 13]);
+    }
+
+    #[test]
+    fn uint256_mulmod() {
+        let mut x = uint256(7);
+        checked::uint256_mulmod(&mut x, &uint256(6), &uint256(5));
+        assert_eq!(x, uint256(2));
+
+        // A zero modulus stands for 2^256.
+        let mut x = uint256(7);
+        checked::uint256_mulmod(&mut x, &uint256(6), &uint256(0));
+        assert_eq!(x, uint256(42));
+    }
+
+    #[test]
+    fn uint256_mulmod_misuse() {
+        checked::uint256_mulmod(&mut uint256(7), &uint256(6), &[5, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,22 @@
+use sp1_sdk::{utils, GuestTestRunner};
+
+/// The ELF of the tests of the checked precompile wrappers.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    // Run the tests without proving them.
+    let outcomes = GuestTestRunner::new(ELF)
+        .run()
+        .expect("failed to run the tests");
+
+    // The `_misuse` tests fail on purpose, with the message of the wrapper.
+    for outcome in outcomes.iter() {
+        match &outcome.failure {
+            None => println!("{} passed in {} cycles", outcome.name, outcome.cycles),
+            Some(message) => println!("{} failed: {}", outcome.name, message),
+        }
+    }
+}
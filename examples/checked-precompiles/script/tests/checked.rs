@@ -0,0 +1,112 @@
+//! Runs the tests of the program in the zkVM: every wrapper must run its precompile, and every
+//! misuse must panic with the message of the wrapper before the `ecall`.
+
+use sp1_sdk::GuestTestRunner;
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+/// The message each misuse test must fail with.
+const MISUSES: &[(&str, &str)] = &[
+    (
+        "sha256_extend_misuse",
+        "sha256_extend: `w` must have 64 elements, got 63",
+    ),
+    (
+        "sha256_compress_misuse",
+        "sha256_compress: `state` must have 8 elements, got 7",
+    ),
+    (
+        "keccak_permute_misuse",
+        "keccak_permute: `state` must have 25 elements, got 24",
+    ),
+    ("ed_add_misuse", "ed_add: `q` must have 16 elements, got 15"),
+    (
+        "ed_decompress_misuse",
+        "ed_decompress: `point` must be aligned to 4 bytes",
+    ),
+    (
+        "secp256k1_add_misuse",
+        "secp256k1_add: `p` must have 16 elements, got 17",
+    ),
+    (
+        "secp256k1_double_misuse",
+        "secp256k1_double: `p` must have 16 elements, got 15",
+    ),
+    (
+        "secp256k1_decompress_misuse",
+        "secp256k1_decompress: `point` must be aligned to 4 bytes",
+    ),
+    (
+        "bn254_add_misuse",
+        "bn254_add: `q` must have 16 elements, got 8",
+    ),
+    (
+        "bn254_double_misuse",
+        "bn254_double: `p` must have 16 elements, got 8",
+    ),
+    (
+        "bls12381_add_misuse",
+        "bls12381_add: `q` must have 24 elements, got 16",
+    ),
+    (
+        "bls12381_double_misuse",
+        "bls12381_double: `p` must have 24 elements, got 16",
+    ),
+    (
+        "bls12381_decompress_misuse",
+        "bls12381_decompress: `point` must have 96 elements, got 64",
+    ),
+    (
+        "bls12381_fp_add_misuse",
+        "bls12381_fp_add: `y` must have 12 elements, got 8",
+    ),
+    (
+        "bls12381_fp_sub_misuse",
+        "bls12381_fp_sub: `x` must have 12 elements, got 11",
+    ),
+    (
+        "bls12381_fp_mul_misuse",
+        "bls12381_fp_mul: `y` must have 12 elements, got 13",
+    ),
+    (
+        "uint256_mulmod_misuse",
+        "uint256_mulmod: `modulus` must have 8 elements, got 4",
+    ),
+];
+
+#[test]
+fn test_checked_wrappers() {
+    let outcomes = GuestTestRunner::new(ELF).run().unwrap();
+
+    for outcome in outcomes.iter().filter(|o| !o.name.ends_with("_misuse")) {
+        assert!(
+            outcome.passed(),
+            "{} failed: {:?}",
+            outcome.name,
+            outcome.failure
+        );
+    }
+
+    let misuses = outcomes
+        .iter()
+        .filter(|o| o.name.ends_with("_misuse"))
+        .collect::<Vec<_>>();
+    assert_eq!(misuses.len(), MISUSES.len());
+    for (name, expected) in MISUSES {
+        let outcome = misuses
+            .iter()
+            .find(|o| o.name == *name)
+            .unwrap_or_else(|| panic!("missing test {}", name));
+        let message = outcome
+            .failure
+            .as_ref()
+            .unwrap_or_else(|| panic!("{} passed", name));
+        assert!(
+            message.contains(expected),
+            "{} failed with {:?}, expected {:?}",
+            name,
+            message,
+            expected
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! A program loading its configuration from TOML files, as a crate written for a regular
+//! filesystem would.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use std::io::ErrorKind;
+
+use serde::Deserialize;
+use sp1_zkvm::fs;
+
+#[derive(Deserialize)]
+struct Config {
+    name: String,
+    max_retries: u32,
+}
+
+#[derive(Deserialize)]
+struct Rule {
+    limit: u64,
+}
+
+/// Loads a TOML file the way a config crate would, treating a missing file as absent.
+fn load<T: serde::de::DeserializeOwned>(path: &str) -> Option<T> {
+    match fs::read_to_string(path) {
+        Ok(text) => Some(toml::from_str(&text).expect("invalid config")),
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(err) => panic!("failed to read {}: {}", path, err),
+    }
+}
+
+pub fn main() {
+    let config: Config = load("/etc/app/config.toml").expect("missing config");
+
+    // An optional override that the host did not provide.
+    let overrides: Option<Config> = load("etc/app/local.toml");
+    assert!(overrides.is_none());
+
+    // Every file of the rules directory is a rule.
+    let mut total_limit = 0;
+    for entry in fs::read_dir("/etc/app/rules").unwrap() {
+        let path = entry.unwrap().path();
+        let rule: Rule = load(path.to_str().unwrap()).unwrap();
+        total_limit += rule.limit;
+    }
+
+    sp1_zkvm::io::commit(&config.name);
+    sp1_zkvm::io::commit(&config.max_retries);
+    sp1_zkvm::io::commit(&total_limit);
+}
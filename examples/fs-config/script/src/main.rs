@@ -0,0 +1,43 @@
+//! A script proving a program that reads its configuration from files.
+
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+
+/// The ELF we want to execute inside the zkVM.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    // The files the program reads, as they would be laid out on disk.
+    let mut stdin = SP1Stdin::new();
+    stdin.write_file_tree(&[
+        (
+            "/etc/app/config.toml",
+            "name = \"relay\"\nmax_retries = 3\n",
+        ),
+        ("/etc/app/rules/burst.toml", "limit = 100\n"),
+        ("/etc/app/rules/daily.toml", "limit = 5000\n"),
+    ]);
+
+    let config_digest = stdin.config_digest();
+
+    // Generate the proof for the given program and input.
+    let client = ProverClient::new();
+    let (pk, vk) = client.setup(ELF);
+    let mut proof = client.prove(&pk, stdin).expect("proving failed");
+
+    // The file tree is part of the config, whose digest the program commits to.
+    assert_eq!(proof.config_digest(), config_digest);
+    let name = proof.public_values.read::<String>();
+    let max_retries = proof.public_values.read::<u32>();
+    let total_limit = proof.public_values.read::<u64>();
+    assert_eq!(name, "relay");
+    assert_eq!(max_retries, 3);
+    assert_eq!(total_limit, 5100);
+
+    // Verify proof.
+    client.verify(&proof, &vk).expect("verification failed");
+
+    println!("successfully generated and verified proof for the program!")
+}
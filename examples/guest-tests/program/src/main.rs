@@ -0,0 +1,29 @@
+#![no_main]
+
+#[sp1_derive::sp1_test]
+mod tests {
+    fn fibonacci(n: u32) -> u32 {
+        let (mut a, mut b) = (0u32, 1u32);
+        for _ in 0..n {
+            (a, b) = (b, a.wrapping_add(b));
+        }
+        a
+    }
+
+    #[test]
+    fn addition() {
+        assert_eq!(2 + 2, 4);
+    }
+
+    #[test]
+    fn fibonacci_sequence() {
+        assert_eq!(fibonacci(10), 55);
+        assert_eq!(fibonacci(20), 6765);
+    }
+
+    // Fails on purpose, to show how failures are reported.
+    #[test]
+    fn fibonacci_off_by_one() {
+        assert_eq!(fibonacci(10), 89, "fibonacci(10) is not fibonacci(11)");
+    }
+}
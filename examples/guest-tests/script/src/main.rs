@@ -0,0 +1,27 @@
+use sp1_sdk::{utils, GuestTestRunner};
+
+/// The ELF of the tests we want to run inside the zkVM.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    // Run the tests whose name contains `fibonacci`, without proving them.
+    let outcomes = GuestTestRunner::new(ELF)
+        .filter("fibonacci")
+        .run()
+        .expect("failed to run the tests");
+
+    for outcome in outcomes.iter() {
+        match &outcome.failure {
+            None => println!("{} passed in {} cycles", outcome.name, outcome.cycles),
+            Some(message) => println!("{} failed: {}", outcome.name, message),
+        }
+    }
+
+    // One of the tests fails on purpose.
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes[0].passed());
+    assert!(!outcomes[1].passed());
+}
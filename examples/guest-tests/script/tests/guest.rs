@@ -0,0 +1,13 @@
+//! Runs the tests of the program in the zkVM, like `cargo test` runs the tests of a crate:
+//!
+//! ```sh
+//! cargo test                          # runs all the tests
+//! cargo test -- fibonacci             # runs the tests whose name contains `fibonacci`
+//! cargo test -- --exact addition      # runs the `addition` test alone
+//! ```
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    sp1_sdk::guest_test_main(ELF);
+}
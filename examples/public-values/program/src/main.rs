@@ -0,0 +1,31 @@
+//! A program committing typed public values, whose layout the host reads from the ELF.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+sp1_zkvm::sp1_public_values! {
+    pub struct FibonacciOutputs {
+        n: u32,
+        fib: u64,
+        is_even: bool,
+    }
+}
+
+pub fn main() {
+    let n = sp1_zkvm::io::read::<u32>();
+
+    let mut a = 0u64;
+    let mut b = 1u64;
+    for _ in 0..n {
+        let c = (a + b) % 7919;
+        a = b;
+        b = c;
+    }
+
+    FibonacciOutputs {
+        n,
+        fib: a,
+        is_even: a % 2 == 0,
+    }
+    .commit();
+}
@@ -0,0 +1,9 @@
+fn main() {
+    let program = format!("{}/../program", env!("CARGO_MANIFEST_DIR"));
+    sp1_helper::build_program(&program);
+    sp1_helper::generate_public_values_bindings(format!(
+        "{}/elf/riscv32im-succinct-zkvm-elf",
+        program
+    ))
+    .expect("failed to generate the public values bindings");
+}
@@ -0,0 +1,63 @@
+//! A script reading the public values of a program with bindings generated from its ELF.
+
+use sp1_sdk::{utils, FromPublicValues, ProverClient, SP1Stdin};
+
+// The `FibonacciOutputs` struct declared by the program.
+include!(concat!(env!("OUT_DIR"), "/public_values.rs"));
+
+/// The ELF we want to execute inside the zkVM.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&20u32);
+
+    // Generate the proof for the given program and input.
+    let client = ProverClient::new();
+    let (pk, vk) = client.setup(ELF);
+    let proof = client.prove(&pk, stdin).expect("proving failed");
+
+    // The public values are parsed with the layout the program committed them with.
+    let outputs = FibonacciOutputs::from_public_values(&proof.public_values)
+        .expect("unexpected public values");
+    println!("fib({}) = {}", outputs.n, outputs.fib);
+
+    // Verify proof.
+    client.verify(&proof, &vk).expect("verification failed");
+
+    println!("successfully generated and verified proof for the program!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_public_values() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&20u32);
+        let client = ProverClient::new();
+        let (public_values, _) = client.execute(ELF, stdin).unwrap();
+
+        let outputs = FibonacciOutputs::from_public_values(&public_values).unwrap();
+        assert_eq!(
+            outputs,
+            FibonacciOutputs {
+                n: 20,
+                fib: 6765,
+                is_even: false,
+            }
+        );
+        assert_eq!(FibonacciOutputs::LEN, 13);
+
+        // Public values with another layout are rejected.
+        let mut other = public_values.to_vec();
+        other.push(0);
+        let err = FibonacciOutputs::from_public_values(&sp1_sdk::SP1PublicValues::from(&other))
+            .unwrap_err();
+        assert_eq!((err.expected, err.actual), (13, 14));
+    }
+}
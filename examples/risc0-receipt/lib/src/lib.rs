@@ -0,0 +1,96 @@
+//! Verification of RISC Zero receipts inside an SP1 program, so that both zkVMs can attest the
+//! same claim.
+//!
+//! The receipt is deserialized and verified with the verifier of `risc0-zkvm`, built without its
+//! prover: the FRI verification of each seal, and the checks that the control ids of the circuits
+//! that produced them are among the allowed ones, whose Merkle root is the control root of the
+//! RISC Zero release. Its SHA-256 hashing runs on the SHA-256 precompiles of SP1 when the program
+//! patches `sha2`, as the `risc0-receipt` program does. The Poseidon2 permutation of RISC Zero is
+//! over a state of 24 BabyBear elements, not the 16 of the SP1 precompile, and runs in software.
+//!
+//! # Cycle budget
+//!
+//! The cost of a verification depends on the kind of the receipt, not on the program it proves:
+//!
+//! - a succinct receipt has a single seal, proven by the recursion circuit with Poseidon2, and
+//!   costs the same for every program: prefer it;
+//! - a composite receipt has a seal per segment, so its cost grows linearly with the number of
+//!   segments of the execution.
+//!
+//! The `risc0-receipt` script prints the cycles of the `verify` span of a receipt, which is the
+//! budget to plan for.
+
+use risc0_zkvm::{InnerReceipt, Receipt};
+use sha2::{Digest, Sha256};
+
+/// A receipt whose seal verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedReceipt {
+    /// The image id of the program the receipt proves.
+    pub image_id: [u32; 8],
+    pub journal: Vec<u8>,
+    /// The SHA-256 digest of the journal, which is what RISC Zero commits to.
+    pub journal_digest: [u8; 32],
+}
+
+/// Why a receipt was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptError {
+    /// The bytes are not a bincode serialized receipt.
+    Decode(String),
+    /// The receipt is neither composite nor succinct.
+    UnsupportedKind,
+    /// The seal doesn't verify against the image id.
+    Verification(String),
+}
+
+impl core::fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode(message) => write!(f, "failed to decode the receipt: {}", message),
+            Self::UnsupportedKind => {
+                write!(f, "only composite and succinct receipts are supported")
+            }
+            Self::Verification(message) => write!(f, "invalid receipt: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ReceiptError {}
+
+/// Verifies a receipt serialized with bincode, as `sp1_sdk::Risc0Receipt` writes it, proving an
+/// execution of the program of `image_id`.
+///
+/// Groth16 receipts, whose verification needs a pairing, and fake receipts are rejected.
+pub fn verify_receipt(bytes: &[u8], image_id: [u32; 8]) -> Result<VerifiedReceipt, ReceiptError> {
+    let receipt: Receipt =
+        bincode::deserialize(bytes).map_err(|err| ReceiptError::Decode(err.to_string()))?;
+    if !matches!(
+        receipt.inner,
+        InnerReceipt::Composite(_) | InnerReceipt::Succinct(_)
+    ) {
+        return Err(ReceiptError::UnsupportedKind);
+    }
+    receipt
+        .verify(image_id)
+        .map_err(|err| ReceiptError::Verification(err.to_string()))?;
+
+    let journal = receipt.journal.bytes;
+    let journal_digest = Sha256::digest(&journal).into();
+    Ok(VerifiedReceipt {
+        image_id,
+        journal,
+        journal_digest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_malformed_receipt() {
+        let err = verify_receipt(&[1, 2, 3], [0; 8]).unwrap_err();
+        assert!(matches!(err, ReceiptError::Decode(_)));
+    }
+}
@@ -0,0 +1,18 @@
+//! A program verifying a RISC Zero receipt, committing the image id of the program it proves and
+//! the digest of its journal.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use risc0_receipt_verifier::verify_receipt;
+
+pub fn main() {
+    let image_id = sp1_zkvm::io::read::<[u32; 8]>();
+    let receipt = sp1_zkvm::io::read_vec();
+
+    println!("cycle-tracker-start: verify");
+    let verified = verify_receipt(&receipt, image_id).unwrap_or_else(|err| panic!("{}", err));
+    println!("cycle-tracker-end: verify");
+
+    sp1_zkvm::io::commit(&verified.image_id);
+    sp1_zkvm::io::commit(&verified.journal_digest);
+}
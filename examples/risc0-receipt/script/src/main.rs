@@ -0,0 +1,43 @@
+//! A script verifying a RISC Zero receipt in SP1.
+//!
+//! Usage: `cargo run --release -- <receipt file> <image id in hex>`, with a receipt serialized with
+//! bincode. The cycles of the verification are logged as the `verify` span.
+use sp1_sdk::{utils, ProverClient, Risc0Receipt, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let args = std::env::args().collect::<Vec<_>>();
+    let [_, receipt_path, image_id] = args.as_slice() else {
+        panic!("usage: risc0-receipt-script <receipt file> <image id in hex>");
+    };
+    let image_id = Risc0Receipt::parse_image_id(image_id).expect("invalid image id");
+    let receipt = Risc0Receipt::read(receipt_path, image_id).expect("failed to read the receipt");
+
+    let mut stdin = SP1Stdin::new();
+    receipt.write_to(&mut stdin);
+
+    // Execute the program to report the cycles of the verification.
+    let client = ProverClient::new();
+    let (_, report) = client
+        .execute(ELF, stdin.clone())
+        .expect("the receipt is invalid");
+    println!("verified in {} cycles", report.total_instruction_count());
+
+    // Generate and verify the proof.
+    let (pk, vk) = client.setup(ELF);
+    let mut proof = client.prove(&pk, stdin).expect("proving failed");
+    client.verify(&proof, &vk).expect("verification failed");
+
+    let committed_image_id = proof.public_values.read::<[u32; 8]>();
+    let journal_digest = proof.public_values.read::<[u8; 32]>();
+    assert_eq!(committed_image_id, image_id);
+    let journal_digest = journal_digest
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    println!("journal digest: 0x{}", journal_digest);
+}
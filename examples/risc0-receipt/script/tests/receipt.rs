@@ -0,0 +1,56 @@
+//! Verifies the fixture RISC Zero receipt in the zkVM, and rejects it once its seal is corrupted.
+
+use std::{fs, path::PathBuf};
+
+use risc0_zkvm::{InnerReceipt, Receipt};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{ProverClient, Risc0Receipt, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+/// The receipt of `fixtures/receipt.bin`, proving the program of `fixtures/image_id.txt`, see
+/// `fixtures/README.md`.
+fn fixture() -> Risc0Receipt {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    let image_id = fs::read_to_string(dir.join("image_id.txt")).expect("missing fixture image id");
+    let image_id = Risc0Receipt::parse_image_id(&image_id).unwrap();
+    Risc0Receipt::read(dir.join("receipt.bin"), image_id).unwrap()
+}
+
+fn stdin(receipt: &Risc0Receipt) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    receipt.write_to(&mut stdin);
+    stdin
+}
+
+#[test]
+fn test_verify_risc0_receipt() {
+    let receipt = fixture();
+    let (mut public_values, _) = ProverClient::new().execute(ELF, stdin(&receipt)).unwrap();
+
+    let journal = bincode::deserialize::<Receipt>(&receipt.receipt)
+        .unwrap()
+        .journal
+        .bytes;
+    assert_eq!(public_values.read::<[u32; 8]>(), receipt.image_id);
+    assert_eq!(
+        public_values.read::<[u8; 32]>(),
+        <[u8; 32]>::from(Sha256::digest(journal))
+    );
+}
+
+#[test]
+fn test_reject_corrupted_seal() {
+    let mut receipt = fixture();
+    let mut decoded = bincode::deserialize::<Receipt>(&receipt.receipt).unwrap();
+    let seal = match &mut decoded.inner {
+        InnerReceipt::Composite(composite) => &mut composite.segments[0].seal,
+        InnerReceipt::Succinct(succinct) => &mut succinct.seal,
+        _ => panic!("the fixture is neither composite nor succinct"),
+    };
+    let middle = seal.len() / 2;
+    seal[middle] ^= 1;
+    receipt.receipt = bincode::serialize(&decoded).unwrap();
+
+    assert!(ProverClient::new().execute(ELF, stdin(&receipt)).is_err());
+}
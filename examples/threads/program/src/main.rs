@@ -0,0 +1,49 @@
+//! A program using crates built for threads: a lazy static, a channel drained by a spawned
+//! worker and a mutex, along with the deterministic clock of the zkVM.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use crossbeam_channel::bounded;
+use once_cell::sync::Lazy;
+use parking_lot::{const_mutex, Mutex};
+use sp1_zkvm::thread;
+use sp1_zkvm::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// The capacity of the channel, and the most values the program sums.
+const CAPACITY: usize = 16;
+
+/// A table initialized on first use.
+static SQUARES: Lazy<Vec<u64>> = Lazy::new(|| (0..CAPACITY as u64).map(|i| i * i).collect());
+
+/// The sum of the values received by the worker.
+static TOTAL: Mutex<u64> = const_mutex(0);
+
+pub fn main() {
+    let n = sp1_zkvm::io::read::<usize>();
+    assert!(
+        n <= CAPACITY,
+        "at most {} values fit in the channel",
+        CAPACITY
+    );
+    let start = Instant::now();
+
+    // Fill the channel before spawning the worker: it runs inline, and must not wait.
+    let (sender, receiver) = bounded(CAPACITY);
+    for square in SQUARES.iter().take(n) {
+        sender.send(*square).unwrap();
+    }
+    drop(sender);
+    let worker = thread::spawn(move || {
+        for value in receiver {
+            *TOTAL.lock() += value;
+        }
+    });
+    worker.join().unwrap();
+
+    let elapsed = start.elapsed();
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+    sp1_zkvm::io::commit(&*TOTAL.lock());
+    sp1_zkvm::io::commit(&(elapsed.as_nanos() as u64));
+    sp1_zkvm::io::commit(&(since_epoch.as_nanos() as u64));
+}
@@ -0,0 +1,22 @@
+//! A script proving a program using crates built for threads, single-threaded in the zkVM.
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&10usize);
+
+    // Generate and verify the proof.
+    let client = ProverClient::new();
+    let (pk, vk) = client.setup(ELF);
+    let mut proof = client.prove(&pk, stdin).expect("proving failed");
+    client.verify(&proof, &vk).expect("verification failed");
+
+    let total = proof.public_values.read::<u64>();
+    let elapsed = proof.public_values.read::<u64>();
+    println!("total: {}, elapsed: {}ns", total, elapsed);
+}
@@ -0,0 +1,41 @@
+//! Runs a program using once_cell, crossbeam-channel and parking_lot in the zkVM, with the
+//! atomics, threads and clock shims of sp1-zkvm.
+
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+/// The nanoseconds of a tick of the clock, see `sp1_zkvm::time::TICK`.
+const TICK_NANOS: u64 = 1000;
+
+fn stdin(n: usize) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&n);
+    stdin
+}
+
+#[test]
+fn test_threads_execute() {
+    let client = ProverClient::new();
+    let (mut public_values, _) = client.execute(ELF, stdin(10)).unwrap();
+
+    // The sum of the squares below 10.
+    assert_eq!(public_values.read::<u64>(), 285);
+
+    // The clock advances by a tick at every read: reading it is deterministic.
+    assert_eq!(public_values.read::<u64>(), TICK_NANOS);
+    assert_eq!(public_values.read::<u64>(), 2 * TICK_NANOS);
+
+    let (again, _) = client.execute(ELF, stdin(10)).unwrap();
+    let (first, _) = client.execute(ELF, stdin(10)).unwrap();
+    assert_eq!(first.as_slice(), again.as_slice());
+}
+
+#[test]
+fn test_threads_prove() {
+    let client = ProverClient::new();
+    let (pk, vk) = client.setup(ELF);
+    let mut proof = client.prove(&pk, stdin(16)).unwrap();
+    client.verify(&proof, &vk).unwrap();
+    assert_eq!(proof.public_values.read::<u64>(), 1240);
+}
@@ -0,0 +1,34 @@
+//! A program verifying a WebAuthn assertion: the P-256 signature by a passkey of its authenticator
+//! data followed by the SHA-256 hash of the client data JSON.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use p256::ecdsa::Signature;
+use sha2::{Digest, Sha256};
+use sp1_zkvm::precompiles::secp256r1::verify_signature;
+
+pub fn main() {
+    let authenticator_data = sp1_zkvm::io::read::<Vec<u8>>();
+    let client_data_json = sp1_zkvm::io::read::<Vec<u8>>();
+    let signature = sp1_zkvm::io::read::<Vec<u8>>();
+    let pubkey = sp1_zkvm::io::read::<Vec<u8>>();
+
+    // The signed message is the authenticator data followed by the hash of the client data.
+    let client_data_hash = Sha256::digest(&client_data_json);
+    let msg_hash: [u8; 32] = Sha256::new()
+        .chain_update(&authenticator_data)
+        .chain_update(client_data_hash)
+        .finalize()
+        .into();
+
+    let valid = match (
+        Signature::from_slice(&signature),
+        <[u8; 65]>::try_from(pubkey.as_slice()),
+    ) {
+        (Ok(signature), Ok(pubkey)) => verify_signature(&pubkey, &msg_hash, &signature),
+        _ => false,
+    };
+
+    sp1_zkvm::io::commit(&client_data_hash.to_vec());
+    sp1_zkvm::io::commit(&valid);
+}
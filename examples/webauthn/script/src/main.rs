@@ -0,0 +1,42 @@
+//! A script proving the verification of a WebAuthn assertion signed by a fresh P-256 key.
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    // Sign an assertion the way an authenticator does: the authenticator data followed by the
+    // hash of the client data JSON.
+    let signing_key = SigningKey::random(&mut rand::thread_rng());
+    let authenticator_data = [[0x49; 32].as_slice(), &[0x05, 0, 0, 0, 1]].concat();
+    let client_data_json =
+        br#"{"type":"webauthn.get","challenge":"c3AxIHdlYmF1dGhu","origin":"https://example.com"}"#;
+    let message = [
+        authenticator_data.as_slice(),
+        &Sha256::digest(client_data_json),
+    ]
+    .concat();
+    let signature: Signature = signing_key.sign(&message);
+    let pubkey = signing_key.verifying_key().to_encoded_point(false);
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&authenticator_data);
+    stdin.write(&client_data_json.to_vec());
+    stdin.write(&signature.to_bytes().to_vec());
+    stdin.write(&pubkey.as_bytes().to_vec());
+
+    // Generate and verify the proof.
+    let client = ProverClient::new();
+    let (pk, vk) = client.setup(ELF);
+    let mut proof = client.prove(&pk, stdin).expect("proving failed");
+    client.verify(&proof, &vk).expect("verification failed");
+
+    let _client_data_hash = proof.public_values.read::<Vec<u8>>();
+    let valid = proof.public_values.read::<bool>();
+    println!("valid assertion: {}", valid);
+}
@@ -0,0 +1,89 @@
+//! Verifies WebAuthn assertions in the zkVM with the secp256r1 precompiles, checking the outcome
+//! against the p256 crate.
+
+use p256::ecdsa::signature::{Signer, Verifier};
+use p256::ecdsa::{Signature, SigningKey};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+const CLIENT_DATA_JSON: &[u8] =
+    br#"{"type":"webauthn.get","challenge":"c3AxIHdlYmF1dGhu","origin":"https://example.com"}"#;
+
+/// The authenticator data of an assertion: the hash of the relying party id, the flags (user
+/// present and verified) and the signature counter.
+fn authenticator_data() -> Vec<u8> {
+    [[0x49; 32].as_slice(), &[0x05, 0, 0, 0, 1]].concat()
+}
+
+/// The message signed by an authenticator.
+fn message(authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    [authenticator_data, &Sha256::digest(client_data_json)].concat()
+}
+
+/// Runs the program on an assertion, returning whether it accepted it.
+fn verify(
+    authenticator_data: &[u8],
+    client_data_json: &[u8],
+    signature: &Signature,
+    signing_key: &SigningKey,
+) -> bool {
+    let pubkey = signing_key.verifying_key().to_encoded_point(false);
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&authenticator_data.to_vec());
+    stdin.write(&client_data_json.to_vec());
+    stdin.write(&signature.to_bytes().to_vec());
+    stdin.write(&pubkey.as_bytes().to_vec());
+
+    let (mut public_values, _) = ProverClient::new().execute(ELF, stdin).unwrap();
+    let client_data_hash = public_values.read::<Vec<u8>>();
+    assert_eq!(client_data_hash, Sha256::digest(client_data_json).to_vec());
+    public_values.read::<bool>()
+}
+
+#[test]
+fn test_webauthn_assertion() {
+    let signing_key = SigningKey::random(&mut rand::thread_rng());
+    let authenticator_data = authenticator_data();
+    let message = message(&authenticator_data, CLIENT_DATA_JSON);
+    let signature: Signature = signing_key.sign(&message);
+    assert!(signing_key
+        .verifying_key()
+        .verify(&message, &signature)
+        .is_ok());
+
+    assert!(verify(
+        &authenticator_data,
+        CLIENT_DATA_JSON,
+        &signature,
+        &signing_key
+    ));
+}
+
+#[test]
+fn test_webauthn_tampered_assertion() {
+    let signing_key = SigningKey::random(&mut rand::thread_rng());
+    let authenticator_data = authenticator_data();
+    let signature: Signature = signing_key.sign(&message(&authenticator_data, CLIENT_DATA_JSON));
+
+    // Another challenge in the client data.
+    let tampered = String::from_utf8(CLIENT_DATA_JSON.to_vec())
+        .unwrap()
+        .replace("c3AxIHdlYmF1dGhu", "dGFtcGVyZWQgY2hh");
+    assert!(!verify(
+        &authenticator_data,
+        tampered.as_bytes(),
+        &signature,
+        &signing_key
+    ));
+
+    // Another key.
+    let other_key = SigningKey::random(&mut rand::thread_rng());
+    assert!(!verify(
+        &authenticator_data,
+        CLIENT_DATA_JSON,
+        &signature,
+        &other_key
+    ));
+}
@@ -0,0 +1,51 @@
+//! A proof of work: finds the first nonce for which the Keccak-256 digest of the data and the nonce
+//! starts with `difficulty` zero bits. Built with the Zbb instructions, counting the zero bits and
+//! reading the digest in big endian take a single instruction each.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use tiny_keccak::{Hasher, Keccak};
+
+/// The number of leading zero bits of `digest`, read as a big endian number.
+fn leading_zeros(digest: &[u8; 32]) -> u32 {
+    let mut zeros = 0;
+    for word in digest.chunks_exact(4) {
+        let word = u32::from_le_bytes(word.try_into().unwrap()).swap_bytes();
+        zeros += word.leading_zeros();
+        if word != 0 {
+            break;
+        }
+    }
+    zeros
+}
+
+pub fn main() {
+    let difficulty = sp1_zkvm::io::read::<u32>();
+    let data = sp1_zkvm::io::read::<Vec<u8>>();
+
+    let mut digest = [0u8; 32];
+    let mut set_bits = 0u32;
+    let mut nonce = 0u64;
+    loop {
+        let mut hasher = Keccak::v256();
+        hasher.update(&data);
+        hasher.update(&nonce.to_le_bytes());
+        hasher.finalize(&mut digest);
+
+        // The number of set bits of the digests tried, to check that the work was done.
+        set_bits += digest
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()).count_ones())
+            .sum::<u32>();
+
+        if leading_zeros(&digest) >= difficulty {
+            break;
+        }
+        nonce += 1;
+    }
+
+    sp1_zkvm::io::commit(&nonce);
+    sp1_zkvm::io::commit(&digest);
+    sp1_zkvm::io::commit(&set_bits);
+}
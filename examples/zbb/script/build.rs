@@ -0,0 +1,12 @@
+use sp1_helper::{build_program, build_program_with_args, BuildArgs};
+
+fn main() {
+    build_program("../program");
+    build_program_with_args(
+        "../program-zbb",
+        BuildArgs {
+            zbb: true,
+            ..Default::default()
+        },
+    );
+}
@@ -0,0 +1,38 @@
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+
+/// The ELF of the proof of work.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+/// The ELF of the proof of work, built with the Zbb instructions.
+const ZBB_ELF: &[u8] = include_bytes!("../../program-zbb/elf/riscv32im-succinct-zkvm-elf");
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&12u32);
+    stdin.write(&b"hello zbb".to_vec());
+
+    // Execute both builds of the program, which must agree.
+    let client = ProverClient::new();
+    let (public_values, report) = client
+        .execute(ELF, stdin.clone())
+        .expect("execution failed");
+    let (zbb_public_values, zbb_report) = client
+        .execute(ZBB_ELF, stdin.clone())
+        .expect("execution failed");
+    assert_eq!(public_values.as_slice(), zbb_public_values.as_slice());
+    println!(
+        "{} cycles without Zbb, {} cycles with Zbb",
+        report.total_instruction_count(),
+        zbb_report.total_instruction_count()
+    );
+
+    // Prove the build with the Zbb instructions.
+    let (pk, vk) = client.setup(ZBB_ELF);
+    let proof = client.prove(&pk, stdin).expect("proving failed");
+    client.verify(&proof, &vk).expect("verification failed");
+
+    println!("successfully generated and verified proof for the program!")
+}
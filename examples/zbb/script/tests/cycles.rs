@@ -0,0 +1,28 @@
+//! Compares the cycles of the proof of work built with and without the Zbb instructions.
+
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+const ZBB_ELF: &[u8] = include_bytes!("../../program-zbb/elf/riscv32im-succinct-zkvm-elf");
+
+#[test]
+fn test_zbb_cycles() {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&8u32);
+    stdin.write(&b"hello zbb".to_vec());
+
+    let client = ProverClient::new();
+    let (public_values, report) = client.execute(ELF, stdin.clone()).unwrap();
+    let (zbb_public_values, zbb_report) = client.execute(ZBB_ELF, stdin).unwrap();
+
+    // Both builds find the same nonce, and the Zbb build in fewer cycles.
+    assert_eq!(public_values.as_slice(), zbb_public_values.as_slice());
+    let cycles = report.total_instruction_count();
+    let zbb_cycles = zbb_report.total_instruction_count();
+    println!(
+        "{} cycles without Zbb, {} cycles with Zbb",
+        cycles, zbb_cycles
+    );
+    assert!(zbb_cycles < cycles);
+}
@@ -0,0 +1,169 @@
+//! A content-addressed cache of the builds of a program, see [crate::BuildArgs::skip_unchanged].
+//!
+//! The digest of a build covers the files of the program and of its local dependencies, its
+//! `Cargo.lock` and the build arguments. It is stored with the digest of the built ELF under
+//! `sp1-helper-cache` in the target directory of the program, and the next build with the same
+//! digest is skipped if the ELF is still there. A missing or corrupted entry only means the program
+//! is built again.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use cargo_metadata::Metadata;
+use sha2::{Digest, Sha256};
+
+use crate::{resolve_docker_image, verify::hex, BinaryTarget, BuildArgs};
+
+/// The name of the cache directory, in the target directory of the program.
+const CACHE_DIR: &str = "sp1-helper-cache";
+
+/// The cache of the builds of a program.
+pub(crate) struct BuildCache {
+    /// The directory of the cache entries.
+    dir: PathBuf,
+    /// The digest of the sources of the program and of its local dependencies, and of its lock
+    /// file.
+    source_digest: String,
+}
+
+impl BuildCache {
+    /// The cache of the program described by `metadata`, whose ELFs are written to `elf_dir`.
+    pub(crate) fn new(metadata: &Metadata, elf_dir: &Path) -> io::Result<Self> {
+        let target_dir = metadata.target_directory.as_std_path();
+        // The ELFs and build outputs are not sources, and would change the digest of every build.
+        let skipped = [target_dir, elf_dir];
+
+        let mut files = Vec::new();
+        for package in metadata.packages.iter().filter(|p| p.source.is_none()) {
+            if let Some(package_dir) = package.manifest_path.parent() {
+                source_files(package_dir.as_std_path(), &skipped, &mut files)?;
+            }
+        }
+        let lock_file = metadata.workspace_root.as_std_path().join("Cargo.lock");
+        if lock_file.is_file() {
+            files.push(lock_file);
+        }
+        files.sort();
+        files.dedup();
+
+        let mut hasher = Sha256::new();
+        for file in files {
+            let contents = fs::read(&file)?;
+            hasher.update(file.to_string_lossy().as_bytes());
+            hasher.update((contents.len() as u64).to_le_bytes());
+            hasher.update(contents);
+        }
+        Ok(Self {
+            dir: target_dir.join(CACHE_DIR),
+            source_digest: hex(&hasher.finalize()),
+        })
+    }
+
+    /// Whether the last build of `binary` of `package_name` with `args` had the same digest, and
+    /// its ELF is still at `elf_path`.
+    pub(crate) fn is_fresh(
+        &self,
+        package_name: &str,
+        args: &BuildArgs,
+        binary: Option<&BinaryTarget>,
+        elf_path: &Path,
+    ) -> bool {
+        let Ok(entry) = fs::read_to_string(self.entry_path(package_name, binary)) else {
+            return false;
+        };
+        let (Some(build_digest), Some(elf_digest)) = (
+            json_string_field(&entry, "source_hash"),
+            json_string_field(&entry, "elf_digest"),
+        ) else {
+            return false;
+        };
+        build_digest == self.build_digest(args, binary)
+            && fs::read(elf_path).is_ok_and(|elf| hex(&Sha256::digest(elf)) == elf_digest)
+    }
+
+    /// Records the build of `binary` of `package_name` with `args` to the ELF at `elf_path`.
+    pub(crate) fn record(
+        &self,
+        package_name: &str,
+        args: &BuildArgs,
+        binary: Option<&BinaryTarget>,
+        elf_path: &Path,
+    ) -> io::Result<()> {
+        let elf_digest = hex(&Sha256::digest(fs::read(elf_path)?));
+        let entry = format!(
+            "{{\n  \"source_hash\": \"{}\",\n  \"elf_digest\": \"{}\"\n}}\n",
+            self.build_digest(args, binary),
+            elf_digest
+        );
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(package_name, binary), entry)
+    }
+
+    /// The digest of a build: the digest of the sources with the arguments of the build.
+    fn build_digest(&self, args: &BuildArgs, binary: Option<&BinaryTarget>) -> String {
+        let image = args
+            .uses_docker()
+            .then(|| resolve_docker_image(args.docker_image.as_deref()));
+        let mut hasher = Sha256::new();
+        hasher.update(self.source_digest.as_bytes());
+        hasher.update(format!("{:?}{:?}{:?}", args, binary, image).as_bytes());
+        hex(&hasher.finalize())
+    }
+
+    /// The path of the cache entry of `binary` of `package_name`, or of the package itself.
+    fn entry_path(&self, package_name: &str, binary: Option<&BinaryTarget>) -> PathBuf {
+        match binary {
+            Some(binary) => self
+                .dir
+                .join(format!("{}-{}.json", package_name, binary.name)),
+            None => self.dir.join(format!("{}.json", package_name)),
+        }
+    }
+}
+
+/// Adds the files under `dir` to `files`, except those under `skipped`, in `target` directories or
+/// hidden.
+fn source_files(dir: &Path, skipped: &[&Path], files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') || skipped.iter().any(|skipped| path.starts_with(skipped)) {
+            continue;
+        }
+        if path.is_dir() {
+            if name != "target" {
+                source_files(&path, skipped, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The string value of the field `key` of the flat JSON object `json`, as written by
+/// [BuildCache::record].
+fn json_string_field<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let start = json.find(&format!("\"{}\": \"", key))? + key.len() + 5;
+    let len = json[start..].find('"')?;
+    Some(&json[start..start + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string_field() {
+        let entry = "{\n  \"source_hash\": \"ab01\",\n  \"elf_digest\": \"cd23\"\n}\n";
+        assert_eq!(json_string_field(entry, "source_hash"), Some("ab01"));
+        assert_eq!(json_string_field(entry, "elf_digest"), Some("cd23"));
+        assert_eq!(json_string_field(entry, "other"), None);
+        assert_eq!(
+            json_string_field("{\"source_hash\": \"ab", "source_hash"),
+            None
+        );
+    }
+}
@@ -1,89 +1,1118 @@
+mod cache;
+mod path;
+mod public_values;
+mod unmanaged;
+mod validate;
+mod verify;
+
+use cache::BuildCache;
 use chrono::Local;
 use std::{
+    collections::{BTreeMap, VecDeque},
     io::{BufRead, BufReader},
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
     thread,
+    time::{Duration, Instant},
 };
+use thiserror::Error;
+
+pub use path::*;
+pub use public_values::*;
+pub use unmanaged::*;
+pub use validate::*;
+pub use verify::*;
+
+/// Arguments for building a program. These mirror the flags of `cargo prove build`.
+#[derive(Clone, Debug, Default)]
+pub struct BuildArgs {
+    /// Build the program in the SP1 docker image for a reproducible build.
+    pub docker: bool,
+    /// The docker image to build the program in, a full reference including the registry and the
+    /// tag or digest, like `ghcr.io/succinctlabs/sp1@sha256:<digest>`. Implies
+    /// [BuildArgs::docker]. If unset, the image is read from `SP1_DOCKER_IMAGE`, or is
+    /// [DEFAULT_DOCKER_IMAGE], see [resolve_docker_image].
+    pub docker_image: Option<String>,
+    /// Ignore the Rust version check.
+    pub ignore_rust_version: bool,
+    /// Enable the Zbb bit manipulation instructions (CLZ, CTZ, CPOP and REV8) in the program.
+    pub zbb: bool,
+    /// The binaries of the program to build, each with its own features, to the
+    /// [binary_elf_name] of the `elf` directory of the program. The path of each ELF is set in the
+    /// [elf_env_var] environment variable of the crate whose build script builds them, to embed it
+    /// with `include_bytes!(env!("SP1_ELF_<NAME>"))`. If empty, the only binary of the program is
+    /// built to `elf/riscv32im-succinct-zkvm-elf`.
+    pub binaries: Vec<BinaryTarget>,
+    /// The name of the ELF of the program, `riscv32im-succinct-zkvm-elf` if unset. Ignored for
+    /// [BuildArgs::binaries], whose ELFs are named with [binary_elf_name].
+    pub elf_name: Option<String>,
+    /// The directory the ELFs are written to, relative to the program directory unless absolute.
+    /// Created if it doesn't exist. If unset, the ELFs are written to the `elf` directory next to
+    /// the target directory of the program.
+    pub output_directory: Option<String>,
+    /// Additional rustc flags for the program, such as `-Copt-level=z`, passed after the flags of
+    /// [guest_rustflags]. They are appended to the flags set by the user in
+    /// `CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS`, like `cargo prove build --rustflags` does.
+    pub rustflags: Vec<String>,
+    /// Skip `cargo prove build` if the sources of the program and of its local dependencies, its
+    /// `Cargo.lock` and these arguments are the same as for the last build, and its ELF is still
+    /// there. The digests of the builds are kept in `sp1-helper-cache` in the target directory of
+    /// the program. Useful in docker mode, where cargo rebuilds the program every time.
+    pub skip_unchanged: bool,
+    /// Don't check that the built ELFs are 32-bit little-endian RISC-V ELFs with an entry point in
+    /// the zkVM program memory, see [validate_elf].
+    pub skip_elf_validation: bool,
+}
+
+impl BuildArgs {
+    /// Whether the program is built in docker, set by [BuildArgs::docker] or
+    /// [BuildArgs::docker_image].
+    pub fn uses_docker(&self) -> bool {
+        self.docker || self.docker_image.is_some()
+    }
+}
+
+/// The docker image `cargo prove build --docker` builds the programs in by default.
+pub const DEFAULT_DOCKER_IMAGE: &str = "ghcr.io/succinctlabs/sp1:latest";
+
+/// The docker image a docker build runs in: `image` if set, else the image in the
+/// `SP1_DOCKER_IMAGE` environment variable, else [DEFAULT_DOCKER_IMAGE].
+pub fn resolve_docker_image(image: Option<&str>) -> String {
+    match image {
+        Some(image) => image.to_string(),
+        None => {
+            std::env::var("SP1_DOCKER_IMAGE").unwrap_or_else(|_| DEFAULT_DOCKER_IMAGE.to_string())
+        }
+    }
+}
+
+/// A binary of a program with several, see [BuildArgs::binaries].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BinaryTarget {
+    /// The name of the binary target of the program crate.
+    pub name: String,
+    /// The features of the program crate to enable for this binary.
+    pub features: Vec<String>,
+    /// Disable the default features of the program crate for this binary.
+    pub no_default_features: bool,
+}
+
+impl BinaryTarget {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_features(mut self, features: &[&str]) -> Self {
+        self.features = features.iter().map(|feature| feature.to_string()).collect();
+        self
+    }
+
+    pub fn without_default_features(mut self) -> Self {
+        self.no_default_features = true;
+        self
+    }
+}
+
+/// The file name of the ELF of the binary `name`, in the `elf` directory of the program.
+pub fn binary_elf_name(name: &str) -> String {
+    format!("riscv32im-succinct-zkvm-elf-{}", name)
+}
+
+/// The environment variable holding the path of the ELF of the binary or package `name`: `SP1_ELF_`
+/// followed by the name in upper case, with the characters other than ASCII letters and digits,
+/// like `-`, replaced by `_`. The ELF of the package `fibonacci-program` is in
+/// `SP1_ELF_FIBONACCI_PROGRAM`.
+pub fn elf_env_var(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("SP1_ELF_{}", name)
+}
 
 fn current_datetime() -> String {
     let now = Local::now();
     now.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
-pub fn build_program(path: &str) {
-    println!("path: {:?}", path);
-    let program_dir = std::path::Path::new(path);
-
-    // Tell cargo to rerun the script only if program/{src, Cargo.toml, Cargo.lock} changes
-    // Ref: https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rerun-if-changed
-    let dirs = vec![
+/// The paths watched by [print_rerun_if_changed].
+fn rerun_if_changed_paths(program_dir: &Path) -> Vec<PathBuf> {
+    vec![
         program_dir.join("src"),
         program_dir.join("Cargo.toml"),
         program_dir.join("Cargo.lock"),
-    ];
-    for dir in dirs {
+    ]
+}
+
+/// Tells cargo to rerun the build script only if program/{src, Cargo.toml, Cargo.lock} changes.
+/// Ref: https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rerun-if-changed
+fn print_rerun_if_changed(program_dir: &Path) {
+    for dir in rerun_if_changed_paths(program_dir) {
         println!("cargo::rerun-if-changed={}", dir.display());
     }
+}
+
+/// The directory the ELFs of the program are written to, see [BuildArgs::output_directory], or
+/// `default_dir` if it is unset.
+///
+/// Fails if the directory is watched by [print_rerun_if_changed], since writing the ELFs there
+/// would rerun the build script after every build.
+fn elf_directory(
+    program_dir: &Path,
+    args: &BuildArgs,
+    default_dir: PathBuf,
+) -> Result<PathBuf, BuildError> {
+    let Some(output_directory) = &args.output_directory else {
+        return Ok(default_dir);
+    };
+    let elf_dir = program_dir.join(output_directory);
+    if let Some(watched) = rerun_if_changed_paths(program_dir)
+        .into_iter()
+        .find(|watched| elf_dir.starts_with(watched))
+    {
+        return Err(BuildError::OutputDirectoryWatched {
+            output_directory: elf_dir,
+            watched,
+        });
+    }
+    Ok(elf_dir)
+}
+
+/// The file name of the ELF of `binary` if any, or of the program.
+fn elf_file_name(args: &BuildArgs, binary: Option<&BinaryTarget>) -> String {
+    match binary {
+        Some(binary) => binary_elf_name(&binary.name),
+        None => args
+            .elf_name
+            .clone()
+            .unwrap_or_else(|| BUILD_TARGET.to_string()),
+    }
+}
+
+/// The output of a successful [try_build_program_with_args].
+#[derive(Clone, Debug)]
+pub struct BuildOutput {
+    /// The name of the program package.
+    pub package_name: String,
+    /// The paths of the built ELFs: the ELF of the program, or those of [BuildArgs::binaries] in
+    /// order.
+    pub elf_paths: Vec<PathBuf>,
+    /// The time it took to build the program.
+    pub duration: Duration,
+    /// The docker image the program was built in, if built in docker.
+    pub docker_image: Option<String>,
+}
 
+impl BuildOutput {
+    /// The path of the ELF of the program, or of the first of [BuildArgs::binaries].
+    pub fn elf_path(&self) -> &Path {
+        &self.elf_paths[0]
+    }
+}
+
+/// The number of trailing lines of the stderr of a failed build kept in
+/// [BuildError::CompilationFailed].
+pub const BUILD_STDERR_TAIL_LINES: usize = 50;
+
+/// An error building a program with [try_build_program_with_args].
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("`cargo prove` is not installed, see https://succinctlabs.github.io/sp1/getting-started/install.html")]
+    CargoProveNotInstalled,
+    #[error("failed to read the metadata of the program: {0}")]
+    Metadata(#[from] cargo_metadata::Error),
+    #[error(
+        "the output directory {} is inside {}, which is watched for changes: writing the ELF \
+         there would rebuild the program forever",
+        .output_directory.display(),
+        .watched.display()
+    )]
+    OutputDirectoryWatched {
+        output_directory: PathBuf,
+        watched: PathBuf,
+    },
+    #[error("failed to build {target} ({status}):\n{stderr}")]
+    CompilationFailed {
+        /// The program, or the binary of the program, which failed to build.
+        target: String,
+        status: ExitStatus,
+        /// The last [BUILD_STDERR_TAIL_LINES] lines of the stderr of the build.
+        stderr: String,
+    },
+    #[error(
+        "`cargo prove build` succeeded, but the ELF was not written to {}. Check that the \
+         installed `cargo prove` supports `--output-directory`.",
+        .0.display()
+    )]
+    ElfNotFound(PathBuf),
+    #[error("{} is not a zkVM program: {error}", .path.display())]
+    InvalidElf {
+        path: PathBuf,
+        #[source]
+        error: ElfValidationError,
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub fn build_program(path: &str) {
+    build_program_with_args(path, BuildArgs::default())
+}
+
+/// Builds the program at `path` from a build script, see [try_build_program_with_args]. Panics if
+/// the build fails.
+pub fn build_program_with_args(path: &str, args: BuildArgs) {
+    let program_dir = start_build_script(path);
+    if is_clippy_driver() {
+        skip_build_script(&program_dir, &args);
+        return;
+    }
+
+    let output =
+        try_build_program_with_args(path, args.clone()).unwrap_or_else(|err| panic!("{}", err));
+    finish_build_script(&output, &args);
+}
+
+/// Builds the programs at the given paths from a build script, running up to
+/// [std::thread::available_parallelism] builds at once. See [build_programs_with_parallelism].
+pub fn build_programs_with_args(programs: &[(&str, BuildArgs)]) {
+    let parallelism = thread::available_parallelism().map_or(1, |parallelism| parallelism.get());
+    build_programs_with_parallelism(programs, parallelism)
+}
+
+/// Builds the programs at the given paths from a build script, running up to `parallelism` builds
+/// at once.
+///
+/// The output of each concurrent `cargo prove build` is prefixed with `[sp1:<package>]`. The
+/// rerun directives of all the programs are printed before building them, and every program is
+/// built even if another one fails, before panicking with the errors of all the failed builds.
+/// With a `parallelism` of 1, the programs are built one after the other, the same way.
+///
+/// Under clippy, [build_program_with_args] is called on each of the programs, which skips them.
+pub fn build_programs_with_parallelism(programs: &[(&str, BuildArgs)], parallelism: usize) {
+    if is_clippy_driver() {
+        for (path, args) in programs {
+            build_program_with_args(path, args.clone());
+        }
+        return;
+    }
+
+    for (path, _) in programs {
+        start_build_script(path);
+    }
+    let labeled = parallelism > 1;
+    let outputs = run_concurrently(programs, parallelism, |(path, args)| {
+        try_build_program_labeled(path, args.clone(), labeled)
+    });
+    let mut errors = Vec::new();
+    for ((_, args), output) in programs.iter().zip(outputs) {
+        match output {
+            Ok(output) => finish_build_script(&output, args),
+            Err(err) => errors.push(err),
+        }
+    }
+    if !errors.is_empty() {
+        let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+        panic!("{}", errors.join("\n\n"));
+    }
+}
+
+/// Calls `f` on each of `items` from up to `parallelism` threads, returning the results in the
+/// order of the items.
+fn run_concurrently<T: Sync, R: Send>(
+    items: &[T],
+    parallelism: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..parallelism.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+/// Whether the build script runs under `cargo clippy`, whose `RUSTC_WORKSPACE_WRAPPER` is
+/// clippy-driver. The programs are not built then, because it breaks rust-analyzer's
+/// `cargo clippy` feature.
+fn is_clippy_driver() -> bool {
+    std::env::var("RUSTC_WORKSPACE_WRAPPER")
+        .map(|val| val.contains("clippy-driver"))
+        .unwrap_or(false)
+}
+
+/// Prints the rerun directives of the program at `path` before building it, returning the program
+/// directory.
+fn start_build_script(path: &str) -> PathBuf {
+    println!("path: {:?}", path);
+    let program_dir = normalize_path(Path::new(path));
+    print_rerun_if_changed(&program_dir);
+    program_dir
+}
+
+/// Skips the build of the program under clippy, see [is_clippy_driver].
+fn skip_build_script(program_dir: &Path, args: &BuildArgs) {
+    println!("cargo:warning=Skipping build due to clippy invocation.");
+    // The ELFs of a previous build, if any, can still be embedded.
+    if let Ok((package_name, elf_paths)) = resolve_elf_paths(program_dir, args) {
+        print_elf_env_vars(&package_name, args, &elf_paths);
+    }
+}
+
+/// Prints the directives of a built program.
+fn finish_build_script(output: &BuildOutput, args: &BuildArgs) {
     // Print a message so the user knows that their program was built. Cargo caches warnings emitted
     // from build scripts, so we'll print the date/time when the program was built.
-    let metadata_file = program_dir.join("Cargo.toml");
-    let mut metadata_cmd = cargo_metadata::MetadataCommand::new();
-    let metadata = metadata_cmd.manifest_path(metadata_file).exec().unwrap();
-    let root_package = metadata.root_package();
-    let root_package_name = root_package
-        .as_ref()
-        .map(|p| p.name.as_str())
-        .unwrap_or("Program");
     println!(
         "cargo:warning={} built at {}",
-        root_package_name,
+        output.package_name,
         current_datetime()
     );
+    if let Some(image) = &output.docker_image {
+        println!(
+            "cargo:warning={} built with docker image {}",
+            output.package_name, image
+        );
+    }
+    print_elf_env_vars(&output.package_name, args, &output.elf_paths);
+}
 
-    let status = execute_build_cmd(&program_dir)
-        .unwrap_or_else(|_| panic!("Failed to build `{}`.", root_package_name));
-    if !status.success() {
-        panic!("Failed to build `{}`.", root_package_name);
+/// Sets the [elf_env_var] of each of [BuildArgs::binaries] to the path of its ELF, or that of the
+/// package if there are none.
+///
+/// Panics if a variable was already set to another path by this build script, which happens when
+/// it builds two programs with the same package name, or two binaries with the same name.
+fn print_elf_env_vars(package_name: &str, args: &BuildArgs, elf_paths: &[PathBuf]) {
+    static SET_VARS: Mutex<BTreeMap<String, PathBuf>> = Mutex::new(BTreeMap::new());
+
+    let names = if args.binaries.is_empty() {
+        vec![package_name]
+    } else {
+        args.binaries
+            .iter()
+            .map(|binary| binary.name.as_str())
+            .collect()
+    };
+    let mut set_vars = SET_VARS.lock().unwrap();
+    for (name, elf_path) in names.into_iter().zip(elf_paths.iter()) {
+        let var = elf_env_var(name);
+        if let Some(set_path) = set_vars.get(&var).filter(|set_path| *set_path != elf_path) {
+            panic!(
+                "{} is set to both {} and {}: the built programs or binaries must have distinct \
+                 names.",
+                var,
+                set_path.display(),
+                elf_path.display()
+            );
+        }
+        println!("cargo:rustc-env={}={}", var, elf_path.display());
+        set_vars.insert(var, elf_path.clone());
+    }
+}
+
+/// Returns the path of the ELF [build_program_with_args] builds for the program at `program_dir`
+/// with `args`, or of the first of [BuildArgs::binaries] if any, without building it.
+///
+/// The ELF is named after [BuildArgs::elf_name] or [binary_elf_name], in
+/// [BuildArgs::output_directory] or the `elf` directory next to the target directory of the
+/// program, which is resolved with `cargo metadata`.
+///
+/// Panics if the metadata of the program can't be read.
+pub fn get_elf_path(program_dir: &str, args: &BuildArgs) -> PathBuf {
+    let program_dir = normalize_path(Path::new(program_dir));
+    let (_, mut elf_paths) =
+        resolve_elf_paths(&program_dir, args).unwrap_or_else(|err| panic!("{}", err));
+    elf_paths.swap_remove(0)
+}
+
+/// Returns the name of the program package and the paths of the ELFs built for it with `args`:
+/// the ELF of the program, or those of [BuildArgs::binaries] in order.
+fn resolve_elf_paths(
+    program_dir: &Path,
+    args: &BuildArgs,
+) -> Result<(String, Vec<PathBuf>), BuildError> {
+    elf_paths_of(program_dir, args, &program_metadata(program_dir)?)
+}
+
+/// The metadata of the program at `program_dir`.
+fn program_metadata(program_dir: &Path) -> Result<cargo_metadata::Metadata, BuildError> {
+    let metadata_file = program_dir.join("Cargo.toml");
+    let mut metadata_cmd = cargo_metadata::MetadataCommand::new();
+    Ok(metadata_cmd.manifest_path(metadata_file).exec()?)
+}
+
+/// Returns the name of the program package and the paths of its ELFs like [resolve_elf_paths],
+/// from the `metadata` of the program.
+fn elf_paths_of(
+    program_dir: &Path,
+    args: &BuildArgs,
+    metadata: &cargo_metadata::Metadata,
+) -> Result<(String, Vec<PathBuf>), BuildError> {
+    let package_name = metadata
+        .root_package()
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| "Program".to_string());
+
+    // `cargo prove build` copies the ELFs into `elf/` next to the target directory by default.
+    let elf_dir = elf_directory(
+        program_dir,
+        args,
+        metadata
+            .target_directory
+            .parent()
+            .unwrap()
+            .join("elf")
+            .into(),
+    )?;
+    let elf_paths = if args.binaries.is_empty() {
+        vec![elf_dir.join(elf_file_name(args, None))]
+    } else {
+        args.binaries
+            .iter()
+            .map(|binary| elf_dir.join(elf_file_name(args, Some(binary))))
+            .collect()
+    };
+    Ok((package_name, elf_paths))
+}
+
+/// Builds the program at `path` with `cargo prove build`, returning the paths of its ELFs.
+///
+/// Unlike [build_program_with_args], this doesn't panic and doesn't print the build script
+/// directives, so it can be called outside of a build script.
+pub fn try_build_program_with_args(path: &str, args: BuildArgs) -> Result<BuildOutput, BuildError> {
+    try_build_program_labeled(path, args, false)
+}
+
+/// Builds the program like [try_build_program_with_args], prefixing the output of the build with
+/// `[sp1:<package>]` if `labeled`, or [OUTPUT_PREFIX] otherwise.
+fn try_build_program_labeled(
+    path: &str,
+    args: BuildArgs,
+    labeled: bool,
+) -> Result<BuildOutput, BuildError> {
+    let start = Instant::now();
+    let program_dir = normalize_path(Path::new(path));
+
+    let metadata = program_metadata(&program_dir)?;
+    let (package_name, elf_paths) = elf_paths_of(&program_dir, &args, &metadata)?;
+    let prefix = if labeled {
+        format!("[sp1:{}]", package_name)
+    } else {
+        OUTPUT_PREFIX.to_string()
+    };
+    let cache = args
+        .skip_unchanged
+        .then(|| {
+            let elf_dir = elf_paths[0].parent().unwrap_or(&program_dir);
+            BuildCache::new(&metadata, elf_dir)
+                .map_err(|err| {
+                    println!("{} failed to hash the sources, rebuilding: {}", prefix, err);
+                })
+                .ok()
+        })
+        .flatten();
+    let build = Build {
+        program_dir: &program_dir,
+        args: &args,
+        package_name: &package_name,
+        prefix: &prefix,
+        cache: cache.as_ref(),
+    };
+    if args.binaries.is_empty() {
+        execute_build_cmd(&build, None, &elf_paths[0])?;
+    } else {
+        for (binary, elf_path) in args.binaries.iter().zip(elf_paths.iter()) {
+            execute_build_cmd(&build, Some(binary), elf_path)?;
+        }
     }
+
+    Ok(BuildOutput {
+        package_name,
+        elf_paths,
+        duration: start.elapsed(),
+        docker_image: args
+            .uses_docker()
+            .then(|| resolve_docker_image(args.docker_image.as_deref())),
+    })
 }
 
-/// Executes the `cargo prove build` command in the program directory
+/// A build of a program by [execute_build_cmd].
+struct Build<'a> {
+    program_dir: &'a Path,
+    args: &'a BuildArgs,
+    package_name: &'a str,
+    /// The prefix of the output of the build.
+    prefix: &'a str,
+    /// The cache of the builds of the program, if [BuildArgs::skip_unchanged] is set.
+    cache: Option<&'a BuildCache>,
+}
+
+/// Executes the `cargo prove build` command in the program directory, for `binary` if any, with
+/// its output prefixed with the prefix of the build.
+///
+/// The directory of `elf_path` is created beforehand, and the build fails if it succeeded without
+/// writing a valid ELF to `elf_path`, see [validate_elf]. With a cache, the command is skipped if
+/// the program didn't change since it was built to `elf_path`; a cache that can't be read or
+/// written is ignored.
 fn execute_build_cmd(
-    program_dir: &impl AsRef<std::path::Path>,
-) -> Result<std::process::ExitStatus, std::io::Error> {
-    // Check if RUSTC_WORKSPACE_WRAPPER is set to clippy-driver (i.e. if `cargo clippy` is the current
-    // compiler). If so, don't execute `cargo prove build` because it breaks rust-analyzer's `cargo clippy` feature.
-    let is_clippy_driver = std::env::var("RUSTC_WORKSPACE_WRAPPER")
-        .map(|val| val.contains("clippy-driver"))
-        .unwrap_or(false);
-    if is_clippy_driver {
-        println!("cargo:warning=Skipping build due to clippy invocation.");
-        return Ok(std::process::ExitStatus::default());
+    build: &Build,
+    binary: Option<&BinaryTarget>,
+    elf_path: &Path,
+) -> Result<(), BuildError> {
+    let Build {
+        program_dir,
+        args,
+        package_name,
+        prefix,
+        cache,
+    } = *build;
+    let target = match binary {
+        Some(binary) => format!("`{}` of `{}`", binary.name, package_name),
+        None => format!("`{}`", package_name),
+    };
+    if let Some(cache) = cache {
+        if cache.is_fresh(package_name, args, binary, elf_path) {
+            println!("{} {} is unchanged, skipping its build", prefix, target);
+            return Ok(());
+        }
+    }
+
+    if let Some(elf_dir) = elf_path.parent() {
+        std::fs::create_dir_all(elf_dir)?;
+    }
+    let mut cmd = cargo_prove_build_cmd(&program_dir, args);
+    match binary {
+        Some(binary) => add_binary_args(&mut cmd, binary),
+        None => {
+            if let Some(elf_name) = &args.elf_name {
+                cmd.args(["--elf-name", elf_name]);
+            }
+        }
+    }
+    let (status, stderr) = match run_with_output_prefix(&mut cmd, prefix) {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(BuildError::CargoProveNotInstalled)
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if !status.success() {
+        if stderr.contains("no such command: `prove`") {
+            return Err(BuildError::CargoProveNotInstalled);
+        }
+        return Err(BuildError::CompilationFailed {
+            target,
+            status,
+            stderr,
+        });
+    }
+    if !elf_path.is_file() {
+        return Err(BuildError::ElfNotFound(elf_path.to_path_buf()));
+    }
+    if !args.skip_elf_validation {
+        validate_elf(&std::fs::read(elf_path)?).map_err(|error| BuildError::InvalidElf {
+            path: elf_path.to_path_buf(),
+            error,
+        })?;
     }
+    if let Some(cache) = cache {
+        if let Err(err) = cache.record(package_name, args, binary, elf_path) {
+            println!(
+                "{} failed to cache the build of {}: {}",
+                prefix, target, err
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Adds the `cargo prove build` flags building `binary` to its [binary_elf_name] to `cmd`.
+fn add_binary_args(cmd: &mut Command, binary: &BinaryTarget) {
+    cmd.args(["--binary", &binary.name]);
+    if !binary.features.is_empty() {
+        cmd.args(["--features", &binary.features.join(",")]);
+    }
+    if binary.no_default_features {
+        cmd.arg("--no-default-features");
+    }
+    cmd.args(["--elf-name", &binary_elf_name(&binary.name)]);
+}
+
+/// Adds the `cargo prove build` flags corresponding to `args` to `cmd`.
+fn add_cargo_prove_build_args(cmd: &mut Command, args: &BuildArgs) {
+    if args.uses_docker() {
+        cmd.arg("--docker");
+    }
+    if let Some(image) = &args.docker_image {
+        cmd.args(["--docker-image", image]);
+    }
+    if args.ignore_rust_version {
+        cmd.arg("--ignore-rust-version");
+    }
+    if args.zbb {
+        cmd.arg("--zbb");
+    }
+    // The command runs in the program directory, so a relative output directory is resolved
+    // against it.
+    if let Some(output_directory) = &args.output_directory {
+        cmd.args(["--output-directory", output_directory]);
+    }
+}
+
+/// The value of `CARGO_ENCODED_RUSTFLAGS` for `cargo prove build`: the `user` flags followed by
+/// the flags of `args`, as `cargo prove build --rustflags` builds them, or `None` if there are
+/// none.
+fn encoded_rustflags(user: Vec<String>, args: &BuildArgs) -> Option<String> {
+    let mut rustflags = user;
+    rustflags.extend(args.rustflags.iter().cloned());
+    (!rustflags.is_empty()).then(|| rustflags.join("\x1f"))
+}
 
+/// Returns the `cargo prove build` command for the program directory.
+fn cargo_prove_build_cmd(program_dir: &impl AsRef<Path>, args: &BuildArgs) -> Command {
     let mut cmd = Command::new("cargo");
     cmd.current_dir(program_dir)
         .args(["prove", "build"])
-        .env_remove("RUSTC")
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        .env_remove("RUSTC");
+    // The flags set by the user are kept, and only replaced to append those of `args`.
+    if let Some(rustflags) = encoded_rustflags(user_rustflags(), args) {
+        cmd.env("CARGO_ENCODED_RUSTFLAGS", rustflags);
+    }
+    add_cargo_prove_build_args(&mut cmd, args);
+    cmd
+}
+
+/// The prefix of the output of the builds.
+const OUTPUT_PREFIX: &str = "[sp1]";
+
+/// Runs `cmd`, piping its stdout and stderr to the parent process with an [OUTPUT_PREFIX]. Returns
+/// its exit status with the last [BUILD_STDERR_TAIL_LINES] lines of its stderr.
+fn run_with_prefixed_output(cmd: &mut Command) -> Result<(ExitStatus, String), std::io::Error> {
+    run_with_output_prefix(cmd, OUTPUT_PREFIX)
+}
+
+/// Runs `cmd` like [run_with_prefixed_output], with the given `prefix`.
+fn run_with_output_prefix(
+    cmd: &mut Command,
+    prefix: &str,
+) -> Result<(ExitStatus, String), std::io::Error> {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
     let mut child = cmd.spawn()?;
 
     let stdout = BufReader::new(child.stdout.take().unwrap());
     let stderr = BufReader::new(child.stderr.take().unwrap());
 
-    // Pipe stdout and stderr to the parent process with [sp1] prefix
+    // Pipe stdout and stderr to the parent process with the prefix.
+    let stdout_prefix = prefix.to_string();
     let stdout_handle = thread::spawn(move || {
         stdout.lines().for_each(|line| {
-            println!("[sp1] {}", line.unwrap());
+            println!("{} {}", stdout_prefix, line.unwrap());
         });
     });
-    stderr.lines().for_each(|line| {
-        eprintln!("[sp1] {}", line.unwrap());
-    });
+    let mut stderr_tail = VecDeque::with_capacity(BUILD_STDERR_TAIL_LINES);
+    for line in stderr.lines() {
+        let line = line?;
+        eprintln!("{} {}", prefix, line);
+        if stderr_tail.len() == BUILD_STDERR_TAIL_LINES {
+            stderr_tail.pop_front();
+        }
+        stderr_tail.push_back(line);
+    }
 
     stdout_handle.join().unwrap();
 
-    child.wait()
+    let status = child.wait()?;
+    Ok((status, Vec::from(stderr_tail).join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elf_env_var() {
+        assert_eq!(elf_env_var("verifier"), "SP1_ELF_VERIFIER");
+        assert_eq!(elf_env_var("light-client_v2"), "SP1_ELF_LIGHT_CLIENT_V2");
+        assert_eq!(
+            binary_elf_name("verifier"),
+            "riscv32im-succinct-zkvm-elf-verifier"
+        );
+    }
+
+    #[test]
+    fn test_binary_build_args() {
+        let binary = BinaryTarget::new("verifier")
+            .with_features(&["fast", "std"])
+            .without_default_features();
+        let mut cmd = cargo_prove_build_cmd(&".", &BuildArgs::default());
+        add_binary_args(&mut cmd, &binary);
+        let args = cmd
+            .get_args()
+            .map(|arg| arg.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            args,
+            vec![
+                "prove",
+                "build",
+                "--binary",
+                "verifier",
+                "--features",
+                "fast,std",
+                "--no-default-features",
+                "--elf-name",
+                "riscv32im-succinct-zkvm-elf-verifier",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_docker_image_args() {
+        let image = "registry.example.com/sp1@sha256:0123";
+        let args = BuildArgs {
+            docker_image: Some(image.to_string()),
+            ..Default::default()
+        };
+        assert!(args.uses_docker());
+        assert_eq!(resolve_docker_image(args.docker_image.as_deref()), image);
+
+        // Setting the image implies a docker build.
+        let cmd = cargo_prove_build_cmd(&".", &args);
+        let cmd_args = cmd
+            .get_args()
+            .map(|arg| arg.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cmd_args,
+            vec!["prove", "build", "--docker", "--docker-image", image]
+        );
+        assert!(!BuildArgs::default().uses_docker());
+    }
+
+    #[test]
+    fn test_rustflags() {
+        let args = BuildArgs {
+            rustflags: vec![
+                "-Copt-level=z".to_string(),
+                "-Clink-arg=--emit-relocs".to_string(),
+            ],
+            ..Default::default()
+        };
+        let cmd = cargo_prove_build_cmd(&".", &args);
+        let rustflags = cmd
+            .get_envs()
+            .find(|(key, _)| *key == "CARGO_ENCODED_RUSTFLAGS")
+            .and_then(|(_, value)| value)
+            .unwrap();
+        let mut expected = user_rustflags();
+        expected.extend([
+            "-Copt-level=z".to_string(),
+            "-Clink-arg=--emit-relocs".to_string(),
+        ]);
+        assert_eq!(rustflags.to_str().unwrap(), expected.join("\x1f"));
+        assert_eq!(
+            encoded_rustflags(vec![], &args).unwrap(),
+            "-Copt-level=z\x1f-Clink-arg=--emit-relocs"
+        );
+        assert_eq!(
+            encoded_guest_rustflags(false, &args.rustflags)
+                .split('\x1f')
+                .collect::<Vec<_>>(),
+            vec![
+                "-C",
+                "passes=loweratomic",
+                "-C",
+                "link-arg=-Ttext=0x00200800",
+                "-C",
+                "panic=abort",
+                "-Copt-level=z",
+                "-Clink-arg=--emit-relocs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_user_rustflags_kept() {
+        let user = vec!["-Ctarget-cpu=generic".to_string(), "--cfg=ci".to_string()];
+        let args = BuildArgs {
+            rustflags: vec!["-Copt-level=z".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            encoded_rustflags(user.clone(), &args).unwrap(),
+            "-Ctarget-cpu=generic\x1f--cfg=ci\x1f-Copt-level=z"
+        );
+
+        // Without `--rustflags`, the flags of the user are passed through unchanged, and nothing
+        // is set if there are none.
+        assert_eq!(
+            encoded_rustflags(user, &BuildArgs::default()).unwrap(),
+            "-Ctarget-cpu=generic\x1f--cfg=ci"
+        );
+        assert_eq!(encoded_rustflags(vec![], &BuildArgs::default()), None);
+    }
+
+    #[test]
+    fn test_output_directory() {
+        let args = BuildArgs {
+            elf_name: Some("my.elf".to_string()),
+            output_directory: Some("../artifacts".to_string()),
+            ..Default::default()
+        };
+        let cmd = cargo_prove_build_cmd(&".", &args);
+        let cmd_args = cmd
+            .get_args()
+            .map(|arg| arg.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cmd_args,
+            vec!["prove", "build", "--output-directory", "../artifacts"]
+        );
+
+        // Relative directories are resolved against the program directory.
+        let program_dir = std::path::Path::new("/workspace/program");
+        let default_dir = std::path::PathBuf::from("/workspace/elf");
+        let elf_dir = elf_directory(program_dir, &args, default_dir.clone()).unwrap();
+        assert_eq!(
+            elf_dir.join(elf_file_name(&args, None)),
+            std::path::Path::new("/workspace/program/../artifacts/my.elf")
+        );
+        let binary = BinaryTarget::new("verifier");
+        assert_eq!(
+            elf_dir.join(elf_file_name(&args, Some(&binary))),
+            std::path::Path::new(
+                "/workspace/program/../artifacts/riscv32im-succinct-zkvm-elf-verifier"
+            )
+        );
+        assert_eq!(
+            elf_directory(program_dir, &BuildArgs::default(), default_dir.clone()).unwrap(),
+            default_dir
+        );
+        assert_eq!(
+            elf_file_name(&BuildArgs::default(), None),
+            "riscv32im-succinct-zkvm-elf"
+        );
+
+        let absolute = BuildArgs {
+            output_directory: Some("/artifacts".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            elf_directory(program_dir, &absolute, default_dir).unwrap(),
+            std::path::Path::new("/artifacts")
+        );
+    }
+
+    #[test]
+    fn test_output_directory_watched() {
+        let args = BuildArgs {
+            output_directory: Some("src/elf".to_string()),
+            ..Default::default()
+        };
+        let err = elf_directory(
+            std::path::Path::new("/workspace/program"),
+            &args,
+            "/workspace/elf".into(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, BuildError::OutputDirectoryWatched { .. }));
+        assert!(err.to_string().contains("which is watched for changes"));
+    }
+
+    #[test]
+    fn test_metadata_error() {
+        let err =
+            try_build_program_with_args("/nonexistent/program", BuildArgs::default()).unwrap_err();
+        assert!(matches!(err, BuildError::Metadata(_)));
+    }
+
+    #[test]
+    fn test_sequential_build_reports_every_error() {
+        let programs = [
+            ("/nonexistent/first", BuildArgs::default()),
+            ("/nonexistent/second", BuildArgs::default()),
+        ];
+        let panic =
+            std::panic::catch_unwind(|| build_programs_with_parallelism(&programs, 1)).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("/nonexistent/first"));
+        assert!(message.contains("/nonexistent/second"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_stderr_tail() {
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "for i in $(seq 1 100); do echo \"line $i\" >&2; done; exit 3",
+        ]);
+        let (status, stderr) = run_with_prefixed_output(&mut cmd).unwrap();
+        assert_eq!(status.code(), Some(3));
+        let lines = stderr.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), BUILD_STDERR_TAIL_LINES);
+        assert_eq!(lines[0], "line 51");
+        assert_eq!(lines[BUILD_STDERR_TAIL_LINES - 1], "line 100");
+    }
+
+    #[test]
+    fn test_run_concurrently() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let items = (0..16).collect::<Vec<_>>();
+        let results = run_concurrently(&items, 3, |item| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            running.fetch_sub(1, Ordering::SeqCst);
+            item * 2
+        });
+        assert_eq!(results, (0..16).map(|item| item * 2).collect::<Vec<_>>());
+        assert!(max_running.load(Ordering::SeqCst) <= 3);
+
+        assert!(run_concurrently(&[] as &[u32], 4, |item| *item).is_empty());
+        assert_eq!(run_concurrently(&[1, 2], 0, |item| item + 1), vec![2, 3]);
+    }
+
+    /// Creates a program crate named `name` in a temporary directory.
+    fn program_crate(name: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+                name
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_get_elf_path() {
+        let program = program_crate("fibonacci-program");
+        let program_dir = program.path().to_str().unwrap();
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(program.path().join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .unwrap();
+        let elf_dir = metadata.target_directory.parent().unwrap().join("elf");
+
+        assert_eq!(
+            get_elf_path(program_dir, &BuildArgs::default()),
+            elf_dir.join("riscv32im-succinct-zkvm-elf")
+        );
+        let args = BuildArgs {
+            elf_name: Some("my.elf".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(get_elf_path(program_dir, &args), elf_dir.join("my.elf"));
+        let args = BuildArgs {
+            binaries: vec![BinaryTarget::new("verifier")],
+            output_directory: Some("artifacts".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_elf_path(program_dir, &args),
+            program
+                .path()
+                .join("artifacts/riscv32im-succinct-zkvm-elf-verifier")
+        );
+        assert_eq!(
+            elf_env_var("fibonacci-program"),
+            "SP1_ELF_FIBONACCI_PROGRAM"
+        );
+    }
+
+    #[test]
+    fn test_skip_unchanged() {
+        let program = program_crate("cached-program");
+        let args = BuildArgs {
+            skip_unchanged: true,
+            ..Default::default()
+        };
+        let metadata = program_metadata(program.path()).unwrap();
+        let (package_name, elf_paths) = elf_paths_of(program.path(), &args, &metadata).unwrap();
+        let elf_path = &elf_paths[0];
+        let cache = || BuildCache::new(&metadata, elf_path.parent().unwrap()).unwrap();
+        let is_fresh = |args: &BuildArgs| cache().is_fresh(&package_name, args, None, elf_path);
+
+        // Simulate a build, which writes the ELF inside the program directory.
+        assert!(!is_fresh(&args));
+        std::fs::create_dir_all(elf_path.parent().unwrap()).unwrap();
+        std::fs::write(elf_path, b"elf").unwrap();
+        cache()
+            .record(&package_name, &args, None, elf_path)
+            .unwrap();
+        assert!(is_fresh(&args));
+
+        // Other arguments, another ELF or other sources are built again.
+        let other_args = BuildArgs {
+            zbb: true,
+            ..args.clone()
+        };
+        assert!(!is_fresh(&other_args));
+        std::fs::write(elf_path, b"other elf").unwrap();
+        assert!(!is_fresh(&args));
+        std::fs::write(elf_path, b"elf").unwrap();
+        assert!(is_fresh(&args));
+        std::fs::write(program.path().join("src/main.rs"), "fn main() { }\n").unwrap();
+        assert!(!is_fresh(&args));
+        cache()
+            .record(&package_name, &args, None, elf_path)
+            .unwrap();
+        assert!(is_fresh(&args));
+
+        // A corrupted entry or a missing ELF is built again.
+        let entry = metadata
+            .target_directory
+            .join("sp1-helper-cache/cached-program.json");
+        let contents = std::fs::read_to_string(&entry).unwrap();
+        std::fs::write(&entry, &contents[..10]).unwrap();
+        assert!(!is_fresh(&args));
+        std::fs::write(&entry, contents).unwrap();
+        assert!(is_fresh(&args));
+        std::fs::remove_file(elf_path).unwrap();
+        assert!(!is_fresh(&args));
+    }
+
+    #[test]
+    #[should_panic(expected = "SP1_ELF_COLLIDING_PROGRAM is set to both")]
+    fn test_elf_env_var_collision() {
+        let args = BuildArgs::default();
+        print_elf_env_vars("colliding-program", &args, &["/a/elf".into()]);
+        // Setting the same path again is fine.
+        print_elf_env_vars("colliding-program", &args, &["/a/elf".into()]);
+        print_elf_env_vars("colliding_program", &args, &["/b/elf".into()]);
+    }
 }
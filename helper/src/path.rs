@@ -0,0 +1,134 @@
+//! Host path handling for Unix and Windows paths.
+//!
+//! The helpers work on both path syntaxes regardless of the host platform, so that the Windows
+//! handling is exercised by the tests on any platform.
+
+use std::path::{Path, PathBuf};
+
+/// Strips the verbatim prefix that `std::fs::canonicalize` adds to paths on Windows, which many
+/// tools don't accept: `\\?\C:\program` becomes `C:\program` and `\\?\UNC\server\share` becomes
+/// `\\server\share`. Other paths are returned unchanged.
+pub fn strip_verbatim_prefix(path: &str) -> String {
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else if let Some(stripped) = path.strip_prefix(r"\\?\") {
+        stripped.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Returns `path` without its verbatim prefix, see [strip_verbatim_prefix].
+pub fn normalize_path(path: &Path) -> PathBuf {
+    match path.to_str() {
+        Some(path) => PathBuf::from(strip_verbatim_prefix(path)),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Splits a path starting with a drive letter, like `C:\program` or `C:/program`, into the
+/// lowercase drive letter and the rest of the path.
+fn split_drive(path: &str) -> Option<(char, &str)> {
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    let rest = chars.as_str().strip_prefix(':')?;
+    if !(rest.is_empty() || rest.starts_with('\\') || rest.starts_with('/')) {
+        return None;
+    }
+    Some((drive.to_ascii_lowercase(), rest))
+}
+
+/// Formats `path` for a TOML string, such as a path dependency written to a manifest. Windows
+/// separators are replaced with forward slashes so that they are not read as escapes.
+pub fn toml_path(path: &str) -> String {
+    strip_verbatim_prefix(path).replace('\\', "/")
+}
+
+/// Translates a Windows path to the form Docker Desktop accepts as the source of a bind mount:
+/// `C:\Users\me\program` becomes `/c/Users/me/program`. The colon after a drive letter would
+/// otherwise be read as the separator between the source and the destination of the mount.
+/// Paths without a drive letter are returned unchanged.
+pub fn windows_docker_path(path: &str) -> String {
+    let path = strip_verbatim_prefix(path);
+    match split_drive(&path) {
+        Some((drive, rest)) => format!("/{}{}", drive, rest.replace('\\', "/")),
+        None => path,
+    }
+}
+
+/// Returns the `-v` argument of `docker run` mounting `host_path` at `container_path`. Host paths
+/// are only translated with [windows_docker_path] on Windows.
+pub fn docker_volume(host_path: &str, container_path: &str) -> String {
+    let host_path = if cfg!(windows) {
+        windows_docker_path(host_path)
+    } else {
+        host_path.to_string()
+    };
+    format!("{}:{}", host_path, container_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_verbatim_prefix() {
+        assert_eq!(
+            strip_verbatim_prefix(r"\\?\C:\Users\me\program"),
+            r"C:\Users\me\program"
+        );
+        assert_eq!(
+            strip_verbatim_prefix(r"\\?\UNC\server\share\program"),
+            r"\\server\share\program"
+        );
+        assert_eq!(strip_verbatim_prefix(r"C:\program"), r"C:\program");
+        assert_eq!(
+            strip_verbatim_prefix("/home/me/program"),
+            "/home/me/program"
+        );
+        assert_eq!(
+            normalize_path(Path::new(r"\\?\D:\a\b")),
+            PathBuf::from(r"D:\a\b")
+        );
+    }
+
+    #[test]
+    fn test_toml_path() {
+        assert_eq!(
+            toml_path(r"\\?\C:\Users\me\sp1\zkvm\entrypoint"),
+            "C:/Users/me/sp1/zkvm/entrypoint"
+        );
+        assert_eq!(toml_path("/home/me/sp1"), "/home/me/sp1");
+    }
+
+    #[test]
+    fn test_windows_docker_path() {
+        assert_eq!(
+            windows_docker_path(r"C:\Users\me\program"),
+            "/c/Users/me/program"
+        );
+        assert_eq!(windows_docker_path(r"\\?\D:\work"), "/d/work");
+        assert_eq!(windows_docker_path("E:/target"), "/e/target");
+        assert_eq!(windows_docker_path("C:"), "/c");
+        assert_eq!(windows_docker_path("/home/me/program"), "/home/me/program");
+        assert_eq!(windows_docker_path("relative:path"), "relative:path");
+        assert_eq!(
+            windows_docker_path(r"\\server\share\program"),
+            r"\\server\share\program"
+        );
+    }
+
+    #[test]
+    fn test_docker_volume() {
+        let volume = docker_volume(r"C:\Users\me\program", "/root/program");
+        if cfg!(windows) {
+            assert_eq!(volume, "/c/Users/me/program:/root/program");
+        } else {
+            assert_eq!(volume, r"C:\Users\me\program:/root/program");
+        }
+        assert_eq!(
+            docker_volume("/home/me/program", "/root/program"),
+            "/home/me/program:/root/program"
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Host bindings for the public values declared with `sp1_zkvm::sp1_public_values!`.
+//!
+//! The guest embeds its declaration in the [PUBLIC_VALUES_SECTION] section of its ELF, prefixed
+//! with its digest. [generate_public_values_bindings] reads it back and generates the matching
+//! struct, with an implementation of `sp1_sdk::FromPublicValues`, for the host to include.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use elf::{endian::LittleEndian, ElfBytes};
+
+/// The ELF section holding the public values declaration. Must match `PUBLIC_VALUES_SECTION` in
+/// `zkvm/entrypoint/src/public_values.rs`.
+pub const PUBLIC_VALUES_SECTION: &str = ".sp1_public_values";
+
+/// The first line of a declaration, which versions its format.
+const DECLARATION_HEADER: &str = "sp1-public-values v1";
+
+/// The name of the file written to `OUT_DIR` by [generate_public_values_bindings].
+pub const PUBLIC_VALUES_BINDINGS_FILE: &str = "public_values.rs";
+
+/// The type of a declared public value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicValueType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    /// A `[u8; N]` byte array.
+    Bytes(usize),
+}
+
+impl PublicValueType {
+    /// Parses a type as written in the declaration, which is how `stringify!` formats it.
+    pub fn parse(ty: &str) -> Option<Self> {
+        let ty = match ty {
+            "bool" => Self::Bool,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            _ => {
+                let len = ty.strip_prefix("[u8; ")?.strip_suffix(']')?;
+                // Only the canonical decimal form, so that the declaration round-trips.
+                if len.is_empty() || !len.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                if len.len() > 1 && len.starts_with('0') {
+                    return None;
+                }
+                Self::Bytes(len.parse().ok()?)
+            }
+        };
+        Some(ty)
+    }
+
+    /// The type as written in Rust, and in the declaration.
+    pub fn rust_type(&self) -> String {
+        match self {
+            Self::Bool => "bool".to_string(),
+            Self::U8 => "u8".to_string(),
+            Self::U16 => "u16".to_string(),
+            Self::U32 => "u32".to_string(),
+            Self::U64 => "u64".to_string(),
+            Self::I8 => "i8".to_string(),
+            Self::I16 => "i16".to_string(),
+            Self::I32 => "i32".to_string(),
+            Self::I64 => "i64".to_string(),
+            Self::Bytes(len) => format!("[u8; {}]", len),
+        }
+    }
+
+    /// The number of bytes the value is committed as.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Bool | Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 => 4,
+            Self::U64 | Self::I64 => 8,
+            Self::Bytes(len) => *len,
+        }
+    }
+
+    /// The expression decoding the value from `bytes[start..start + size]`.
+    fn decode_expr(&self, start: usize) -> String {
+        let end = start + self.size();
+        match self {
+            Self::Bool => format!("bytes[{}] != 0", start),
+            Self::Bytes(_) => format!("bytes[{}..{}].try_into().unwrap()", start, end),
+            _ => format!(
+                "{}::from_le_bytes(bytes[{}..{}].try_into().unwrap())",
+                self.rust_type(),
+                start,
+                end
+            ),
+        }
+    }
+}
+
+/// A declared public value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicValueField {
+    pub name: String,
+    pub ty: PublicValueType,
+}
+
+/// The public values declared by a program, in commit order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicValuesDeclaration {
+    /// The name of the declared struct.
+    pub name: String,
+    pub fields: Vec<PublicValueField>,
+}
+
+/// The 64-bit FNV-1a digest of a declaration. Must match `declaration_digest` in
+/// `zkvm/entrypoint/src/public_values.rs`.
+pub const fn declaration_digest(declaration: &[u8]) -> u64 {
+    let mut digest = 0xcbf2_9ce4_8422_2325u64;
+    let mut i = 0;
+    while i < declaration.len() {
+        digest ^= declaration[i] as u64;
+        digest = digest.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    digest
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl PublicValuesDeclaration {
+    /// Parses a declaration in the format written by `sp1_public_values!`.
+    pub fn parse(declaration: &str) -> Result<Self> {
+        let mut lines = declaration.lines();
+        match lines.next() {
+            Some(DECLARATION_HEADER) => {}
+            Some(header) => bail!(
+                "unsupported public values declaration `{}`, expected `{}`: update sp1-helper",
+                header,
+                DECLARATION_HEADER
+            ),
+            None => bail!("empty public values declaration"),
+        }
+        let name = lines
+            .next()
+            .and_then(|line| line.strip_prefix("struct "))
+            .filter(|name| is_identifier(name))
+            .ok_or_else(|| anyhow!("public values declaration has no struct name"))?;
+        let fields = lines
+            .map(|line| {
+                let (name, ty) = line
+                    .split_once(' ')
+                    .filter(|(name, _)| is_identifier(name))
+                    .ok_or_else(|| anyhow!("invalid public value `{}`", line))?;
+                let ty = PublicValueType::parse(ty).ok_or_else(|| {
+                    anyhow!(
+                        "public value `{}` has type `{}`, which is not one of bool, u8, u16, u32, \
+                         u64, i8, i16, i32, i64 or [u8; N]",
+                        name,
+                        ty
+                    )
+                })?;
+                Ok(PublicValueField {
+                    name: name.to_string(),
+                    ty,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            fields,
+        })
+    }
+
+    /// The declaration in the format written by `sp1_public_values!`.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\nstruct {}\n", DECLARATION_HEADER, self.name);
+        for field in self.fields.iter() {
+            writeln!(text, "{} {}", field.name, field.ty.rust_type()).unwrap();
+        }
+        text
+    }
+
+    /// The digest of the declaration, as embedded by the guest.
+    pub fn digest(&self) -> u64 {
+        declaration_digest(self.to_text().as_bytes())
+    }
+
+    /// The number of bytes committed by the guest.
+    pub fn len(&self) -> usize {
+        self.fields.iter().map(|field| field.ty.size()).sum()
+    }
+
+    /// Whether no value is declared.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Reads the declaration embedded in an ELF, checking it against its embedded digest.
+    pub fn from_elf(elf: &[u8]) -> Result<Self> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(elf)?;
+        let header = elf
+            .section_header_by_name(PUBLIC_VALUES_SECTION)?
+            .ok_or_else(|| {
+                anyhow!(
+                    "the program has no {} section: declare its public values with \
+                     `sp1_zkvm::sp1_public_values!` and commit them with their `commit` method",
+                    PUBLIC_VALUES_SECTION
+                )
+            })?;
+        let (section, _) = elf.section_data(&header)?;
+        Self::from_section(section)
+    }
+
+    /// Reads the declaration from the contents of the [PUBLIC_VALUES_SECTION].
+    pub fn from_section(section: &[u8]) -> Result<Self> {
+        let (digest, declaration) = section
+            .split_first_chunk::<8>()
+            .ok_or_else(|| anyhow!("the public values declaration is truncated"))?;
+        let digest = u64::from_le_bytes(*digest);
+        if declaration_digest(declaration) != digest {
+            bail!("the public values declaration does not match its embedded digest");
+        }
+        let declaration = std::str::from_utf8(declaration)
+            .context("the public values declaration is not utf-8")?;
+        let parsed = Self::parse(declaration)?;
+        // The bindings are generated from the parsed declaration, so it must be the embedded one.
+        if parsed.digest() != digest {
+            bail!(
+                "the bindings generated from the public values declaration of `{}` would not \
+                 match its embedded digest {:#018x}: update sp1-helper",
+                parsed.name,
+                digest
+            );
+        }
+        Ok(parsed)
+    }
+
+    /// Generates the host bindings: the declared struct, and its implementation of
+    /// `sp1_sdk::FromPublicValues`.
+    ///
+    /// The bindings recompute the digest of the declaration they are generated from at compile
+    /// time, so bindings edited or generated from another declaration fail to compile.
+    pub fn generate_bindings(&self) -> String {
+        let mut code = String::new();
+        writeln!(
+            code,
+            "// Generated by sp1-helper from the public values declaration of the program. \
+             Do not edit."
+        )
+        .unwrap();
+        writeln!(code).unwrap();
+        writeln!(
+            code,
+            "/// The public values of the program, declared with `sp1_public_values!`."
+        )
+        .unwrap();
+        writeln!(code, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
+        writeln!(code, "pub struct {} {{", self.name).unwrap();
+        for field in self.fields.iter() {
+            writeln!(code, "    pub {}: {},", field.name, field.ty.rust_type()).unwrap();
+        }
+        writeln!(code, "}}").unwrap();
+        writeln!(code).unwrap();
+
+        writeln!(
+            code,
+            "impl ::sp1_sdk::FromPublicValues for {} {{",
+            self.name
+        )
+        .unwrap();
+        writeln!(code, "    const NAME: &'static str = {:?};", self.name).unwrap();
+        writeln!(code, "    const LEN: usize = {};", self.len()).unwrap();
+        writeln!(
+            code,
+            "    const DECLARATION_DIGEST: u64 = {:#018x};",
+            self.digest()
+        )
+        .unwrap();
+        writeln!(code).unwrap();
+        writeln!(code, "    fn decode(bytes: &[u8]) -> Self {{").unwrap();
+        writeln!(code, "        assert_eq!(bytes.len(), Self::LEN);").unwrap();
+        writeln!(code, "        Self {{").unwrap();
+        let mut start = 0;
+        for field in self.fields.iter() {
+            writeln!(
+                code,
+                "            {}: {},",
+                field.name,
+                field.ty.decode_expr(start)
+            )
+            .unwrap();
+            start += field.ty.size();
+        }
+        writeln!(code, "        }}").unwrap();
+        writeln!(code, "    }}").unwrap();
+        writeln!(code, "}}").unwrap();
+        writeln!(code).unwrap();
+
+        writeln!(code, "const _: () = {{").unwrap();
+        writeln!(code, "    const DECLARATION: &str = {:?};", self.to_text()).unwrap();
+        writeln!(code, "    const fn digest(declaration: &[u8]) -> u64 {{").unwrap();
+        writeln!(code, "        let mut digest = 0xcbf2_9ce4_8422_2325u64;").unwrap();
+        writeln!(code, "        let mut i = 0;").unwrap();
+        writeln!(code, "        while i < declaration.len() {{").unwrap();
+        writeln!(code, "            digest ^= declaration[i] as u64;").unwrap();
+        writeln!(
+            code,
+            "            digest = digest.wrapping_mul(0x0000_0100_0000_01b3);"
+        )
+        .unwrap();
+        writeln!(code, "            i += 1;").unwrap();
+        writeln!(code, "        }}").unwrap();
+        writeln!(code, "        digest").unwrap();
+        writeln!(code, "    }}").unwrap();
+        writeln!(code, "    assert!(").unwrap();
+        writeln!(
+            code,
+            "        digest(DECLARATION.as_bytes()) == <{} as ::sp1_sdk::FromPublicValues>::DECLARATION_DIGEST,",
+            self.name
+        )
+        .unwrap();
+        writeln!(
+            code,
+            "        \"the public values bindings do not match the declaration embedded in the \
+             program: rebuild the host to regenerate them\""
+        )
+        .unwrap();
+        writeln!(code, "    );").unwrap();
+        writeln!(code, "}};").unwrap();
+        code
+    }
+}
+
+/// Generates the host bindings of the public values declared by the program at `elf_path`, see
+/// [PublicValuesDeclaration::generate_bindings], into [PUBLIC_VALUES_BINDINGS_FILE] in `OUT_DIR`.
+///
+/// Meant to be called from the build script of the host, after the program is built:
+///
+/// ```ignore
+/// sp1_helper::build_program("../program");
+/// sp1_helper::generate_public_values_bindings("../program/elf/riscv32im-succinct-zkvm-elf")
+///     .unwrap();
+/// ```
+///
+/// and included with `include!(concat!(env!("OUT_DIR"), "/public_values.rs"))`.
+pub fn generate_public_values_bindings(elf_path: impl AsRef<Path>) -> Result<PathBuf> {
+    let elf_path = elf_path.as_ref();
+    println!("cargo::rerun-if-changed={}", elf_path.display());
+    let out_dir =
+        std::env::var("OUT_DIR").context("OUT_DIR is not set, call from a build script")?;
+    let elf =
+        fs::read(elf_path).with_context(|| format!("failed to read ELF at {:?}", elf_path))?;
+    let declaration = PublicValuesDeclaration::from_elf(&elf)
+        .with_context(|| format!("invalid public values declaration in {:?}", elf_path))?;
+    let path = Path::new(&out_dir).join(PUBLIC_VALUES_BINDINGS_FILE);
+    fs::write(&path, declaration.generate_bindings())
+        .with_context(|| format!("failed to write {:?}", path))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+
+    /// The declaration written by `sp1_public_values!` for
+    /// `struct Outputs { n: u32, fib: u64, digest: [u8; 32] }`.
+    const DECLARATION: &str =
+        "sp1-public-values v1\nstruct Outputs\nn u32\nfib u64\ndigest [u8; 32]\n";
+
+    fn section(declaration: &str) -> Vec<u8> {
+        let mut section = declaration_digest(declaration.as_bytes())
+            .to_le_bytes()
+            .to_vec();
+        section.extend_from_slice(declaration.as_bytes());
+        section
+    }
+
+    #[test]
+    fn test_parse_declaration() {
+        let declaration = PublicValuesDeclaration::from_section(&section(DECLARATION)).unwrap();
+        assert_eq!(declaration.name, "Outputs");
+        assert_eq!(
+            declaration.fields,
+            vec![
+                PublicValueField {
+                    name: "n".to_string(),
+                    ty: PublicValueType::U32
+                },
+                PublicValueField {
+                    name: "fib".to_string(),
+                    ty: PublicValueType::U64
+                },
+                PublicValueField {
+                    name: "digest".to_string(),
+                    ty: PublicValueType::Bytes(32)
+                },
+            ]
+        );
+        assert_eq!(declaration.len(), 44);
+        assert_eq!(declaration.to_text(), DECLARATION);
+    }
+
+    #[test]
+    fn test_invalid_declaration() {
+        // The digest covers the declaration.
+        let mut corrupted = section(DECLARATION);
+        *corrupted.last_mut().unwrap() = b' ';
+        assert!(PublicValuesDeclaration::from_section(&corrupted).is_err());
+
+        let unsupported = "sp1-public-values v1\nstruct Outputs\nname String\n";
+        let err = PublicValuesDeclaration::from_section(&section(unsupported)).unwrap_err();
+        assert!(err.to_string().contains("`String`"), "{}", err);
+
+        // A declaration the bindings would not reproduce is rejected.
+        let padded = "sp1-public-values v1\nstruct Outputs\nn [u8; 04]\n";
+        assert!(PublicValuesDeclaration::from_section(&section(padded)).is_err());
+
+        let err = PublicValuesDeclaration::from_elf(FIBONACCI_ELF).unwrap_err();
+        assert!(err.to_string().contains(PUBLIC_VALUES_SECTION), "{}", err);
+    }
+
+    #[test]
+    fn test_generate_bindings() {
+        let declaration = PublicValuesDeclaration::parse(DECLARATION).unwrap();
+        let code = declaration.generate_bindings();
+        assert!(code.contains("pub struct Outputs {"));
+        assert!(code.contains("    pub digest: [u8; 32],"));
+        assert!(code.contains("const LEN: usize = 44;"));
+        assert!(code.contains("n: u32::from_le_bytes(bytes[0..4].try_into().unwrap()),"));
+        assert!(code.contains("fib: u64::from_le_bytes(bytes[4..12].try_into().unwrap()),"));
+        assert!(code.contains("digest: bytes[12..44].try_into().unwrap(),"));
+        assert!(code.contains(&format!(
+            "const DECLARATION_DIGEST: u64 = {:#018x};",
+            declaration_digest(DECLARATION.as_bytes())
+        )));
+    }
+}
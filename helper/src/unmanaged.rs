@@ -0,0 +1,335 @@
+//! Building programs with plain `cargo build`, without the `cargo prove` subcommand.
+//!
+//! Some environments, like Nix sandboxes or bazel builds, can't run `cargo prove`. The build it
+//! makes is a `cargo build` of the [BUILD_TARGET] with the `succinct` toolchain and the
+//! [guest_rustflags], followed by a copy of the ELF to the `elf` directory of the program.
+//! [build_program_unmanaged] makes the same build itself, so both produce the same ELF.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    elf_directory, elf_file_name, normalize_path, print_elf_env_vars, print_rerun_if_changed,
+    run_with_prefixed_output, validate_elf, BinaryTarget, BuildArgs,
+};
+
+/// The target the programs are built for, built into the `succinct` toolchain.
+pub const BUILD_TARGET: &str = "riscv32im-succinct-zkvm-elf";
+
+/// The rustup toolchain providing the [BUILD_TARGET].
+pub const SUCCINCT_TOOLCHAIN: &str = "succinct";
+
+/// The rustc flags of the programs, passed through `CARGO_ENCODED_RUSTFLAGS`.
+pub fn guest_rustflags(zbb: bool) -> Vec<&'static str> {
+    let mut flags = vec![
+        "-C",
+        "passes=loweratomic",
+        "-C",
+        "link-arg=-Ttext=0x00200800",
+        "-C",
+        "panic=abort",
+    ];
+    if zbb {
+        flags.extend(["-C", "target-feature=+zbb"]);
+    }
+    flags
+}
+
+/// The rustc flags set by the user for cargo, in `CARGO_ENCODED_RUSTFLAGS` or else in `RUSTFLAGS`,
+/// read as cargo reads them.
+pub fn user_rustflags() -> Vec<String> {
+    if let Ok(encoded) = std::env::var("CARGO_ENCODED_RUSTFLAGS") {
+        return encoded
+            .split('\x1f')
+            .filter(|flag| !flag.is_empty())
+            .map(String::from)
+            .collect();
+    }
+    std::env::var("RUSTFLAGS")
+        .map(|flags| flags.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// The value of `CARGO_ENCODED_RUSTFLAGS` building the programs: the [guest_rustflags] followed by
+/// `extra`, which therefore take precedence over them.
+pub fn encoded_guest_rustflags(zbb: bool, extra: &[String]) -> String {
+    guest_rustflags(zbb)
+        .into_iter()
+        .chain(extra.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join("\x1f")
+}
+
+/// The `cargo build` arguments building the program, or only `binary` of it, with the given
+/// features.
+pub fn guest_cargo_args(
+    ignore_rust_version: bool,
+    binary: Option<&str>,
+    features: &[String],
+    no_default_features: bool,
+) -> Vec<String> {
+    let mut args = ["build", "--release", "--target", BUILD_TARGET, "--locked"]
+        .map(String::from)
+        .to_vec();
+    if ignore_rust_version {
+        args.push("--ignore-rust-version".to_string());
+    }
+    if let Some(binary) = binary {
+        args.extend(["--bin".to_string(), binary.to_string()]);
+    }
+    if !features.is_empty() {
+        args.extend(["--features".to_string(), features.join(",")]);
+    }
+    if no_default_features {
+        args.push("--no-default-features".to_string());
+    }
+    args
+}
+
+/// Builds the program like [crate::build_program_with_args], with `cargo build` instead of
+/// `cargo prove build`, and returns the paths of the ELFs.
+///
+/// The `succinct` toolchain must be installed, see `cargo prove install-toolchain`. Docker builds
+/// are not supported, since they run `cargo prove` in the image.
+pub fn build_program_unmanaged(path: &str, args: BuildArgs) -> Result<Vec<PathBuf>> {
+    if args.uses_docker() {
+        bail!("docker builds are not supported by `build_program_unmanaged`");
+    }
+    let program_dir = normalize_path(Path::new(path));
+    print_rerun_if_changed(&program_dir);
+
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .manifest_path(program_dir.join("Cargo.toml"))
+        .exec()
+        .with_context(|| format!("failed to read the metadata of `{}`", program_dir.display()))?;
+    let package_name = metadata
+        .root_package()
+        .map(|package| package.name.clone())
+        .ok_or_else(|| anyhow!("`{}` is not a package", program_dir.display()))?;
+    let target_dir = metadata.target_directory.as_std_path();
+
+    let elf_paths = if args.binaries.is_empty() {
+        vec![build_elf_unmanaged(
+            &program_dir,
+            target_dir,
+            &package_name,
+            &args,
+            None,
+        )?]
+    } else {
+        args.binaries
+            .iter()
+            .map(|binary| {
+                build_elf_unmanaged(&program_dir, target_dir, &package_name, &args, Some(binary))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    print_elf_env_vars(&package_name, &args, &elf_paths);
+    Ok(elf_paths)
+}
+
+/// Builds the program, or only `binary` of it, into `target_dir` and copies the ELF to the `elf`
+/// directory next to it, or to the [BuildArgs::output_directory], as `cargo prove build` does.
+/// Returns the path of the copy.
+fn build_elf_unmanaged(
+    program_dir: &Path,
+    target_dir: &Path,
+    package_name: &str,
+    args: &BuildArgs,
+    binary: Option<&BinaryTarget>,
+) -> Result<PathBuf> {
+    let default_dir = target_dir
+        .parent()
+        .ok_or_else(|| anyhow!("the target directory has no parent"))?
+        .join("elf");
+    let elf_dir = elf_directory(program_dir, args, default_dir)?;
+    let elf_path = elf_dir.join(elf_file_name(args, binary));
+
+    // See `execute_build_cmd`.
+    let is_clippy_driver = std::env::var("RUSTC_WORKSPACE_WRAPPER")
+        .map(|val| val.contains("clippy-driver"))
+        .unwrap_or(false);
+    if is_clippy_driver {
+        println!("cargo:warning=Skipping build due to clippy invocation.");
+        return Ok(elf_path);
+    }
+
+    check_toolchain()?;
+    let mut cmd = unmanaged_build_cmd(program_dir, target_dir, args, binary);
+    let (status, _) = run_with_prefixed_output(&mut cmd).context("failed to run cargo build")?;
+    if !status.success() {
+        bail!("failed to build `{}`", package_name);
+    }
+
+    let built = target_dir
+        .join(BUILD_TARGET)
+        .join("release")
+        .join(binary.map_or(package_name, |binary| binary.name.as_str()));
+    if !args.skip_elf_validation {
+        let elf =
+            fs::read(&built).with_context(|| format!("failed to read {}", built.display()))?;
+        validate_elf(&elf).with_context(|| format!("{} is not a zkVM program", built.display()))?;
+    }
+    fs::create_dir_all(&elf_dir)
+        .with_context(|| format!("failed to create {}", elf_dir.display()))?;
+    fs::copy(&built, &elf_path).with_context(|| {
+        format!(
+            "failed to copy {} to {}",
+            built.display(),
+            elf_path.display()
+        )
+    })?;
+    Ok(elf_path)
+}
+
+/// The `cargo build` command building the program, or only `binary` of it, into `target_dir`.
+fn unmanaged_build_cmd(
+    program_dir: &Path,
+    target_dir: &Path,
+    args: &BuildArgs,
+    binary: Option<&BinaryTarget>,
+) -> Command {
+    let features = binary.map_or(&[][..], |binary| binary.features.as_slice());
+    let mut cmd = Command::new("cargo");
+    cmd.current_dir(program_dir)
+        .env_remove("RUSTC")
+        .env("RUSTUP_TOOLCHAIN", SUCCINCT_TOOLCHAIN)
+        .env(
+            "CARGO_ENCODED_RUSTFLAGS",
+            encoded_guest_rustflags(args.zbb, &args.rustflags),
+        )
+        .env("CARGO_TARGET_DIR", target_dir)
+        .args(guest_cargo_args(
+            args.ignore_rust_version,
+            binary.map(|binary| binary.name.as_str()),
+            features,
+            binary.is_some_and(|binary| binary.no_default_features),
+        ));
+    cmd
+}
+
+/// Checks that the `succinct` toolchain is installed and provides the [BUILD_TARGET].
+fn check_toolchain() -> Result<()> {
+    let output = Command::new("rustc")
+        .env_remove("RUSTC")
+        .env("RUSTUP_TOOLCHAIN", SUCCINCT_TOOLCHAIN)
+        .args(["--print", "target-list"])
+        .output();
+    let installed = output.is_ok_and(|output| {
+        output.status.success()
+            && String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|target| target == BUILD_TARGET)
+    });
+    if !installed {
+        bail!(
+            "the `{}` toolchain providing the `{}` target is not installed, install it with \
+             `cargo prove install-toolchain`",
+            SUCCINCT_TOOLCHAIN,
+            BUILD_TARGET
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::cargo_prove_build_cmd;
+
+    #[test]
+    fn test_unmanaged_build_cmd() {
+        let binary = BinaryTarget::new("verifier").with_features(&["fast"]);
+        let args = BuildArgs {
+            zbb: true,
+            rustflags: vec!["-Copt-level=z".to_string()],
+            ..Default::default()
+        };
+        let cmd = unmanaged_build_cmd(Path::new("."), Path::new("target"), &args, Some(&binary));
+        let cmd_args = cmd
+            .get_args()
+            .map(|arg| arg.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cmd_args,
+            vec![
+                "build",
+                "--release",
+                "--target",
+                "riscv32im-succinct-zkvm-elf",
+                "--locked",
+                "--bin",
+                "verifier",
+                "--features",
+                "fast",
+            ]
+        );
+        let rustflags = cmd
+            .get_envs()
+            .find(|(key, _)| *key == "CARGO_ENCODED_RUSTFLAGS")
+            .and_then(|(_, value)| value)
+            .unwrap();
+        let rustflags = rustflags
+            .to_str()
+            .unwrap()
+            .split('\x1f')
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rustflags[rustflags.len() - 3..],
+            ["-C", "target-feature=+zbb", "-Copt-level=z"]
+        );
+    }
+
+    #[test]
+    fn test_unmanaged_docker_unsupported() {
+        let args = BuildArgs {
+            docker: true,
+            ..Default::default()
+        };
+        assert!(build_program_unmanaged(".", args).is_err());
+    }
+
+    /// Requires the `cargo prove` toolchain.
+    #[test]
+    #[ignore]
+    fn test_unmanaged_build_matches_cargo_prove() {
+        let program_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/fibonacci/program");
+        let args = BuildArgs::default();
+
+        // Build into temporary target directories, so that the committed ELF is left untouched.
+        let cargo_prove_dir = tempfile::tempdir().unwrap();
+        let mut cmd = cargo_prove_build_cmd(&program_dir, &args);
+        cmd.env("CARGO_TARGET_DIR", cargo_prove_dir.path().join("target"));
+        assert!(run_with_prefixed_output(&mut cmd).unwrap().0.success());
+        let cargo_prove_elf =
+            fs::read(cargo_prove_dir.path().join("elf").join(BUILD_TARGET)).unwrap();
+
+        let unmanaged_dir = tempfile::tempdir().unwrap();
+        let elf_path = build_elf_unmanaged(
+            &program_dir,
+            &unmanaged_dir.path().join("target"),
+            "fibonacci-program",
+            &args,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            elf_path,
+            unmanaged_dir.path().join("elf").join(BUILD_TARGET)
+        );
+        let unmanaged_elf = fs::read(elf_path).unwrap();
+
+        assert_eq!(
+            Sha256::digest(&unmanaged_elf),
+            Sha256::digest(&cargo_prove_elf)
+        );
+    }
+}
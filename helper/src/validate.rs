@@ -0,0 +1,154 @@
+//! Validation of the ELF header of built programs, see [crate::BuildArgs::skip_elf_validation].
+
+use thiserror::Error;
+
+/// The `e_machine` of RISC-V ELFs.
+pub const EM_RISCV: u16 = 0xf3;
+
+/// The start of the memory the program of the zkVM is loaded in, above the stack of the guest.
+pub const ZKVM_PROGRAM_START: u32 = 0x0020_0400;
+
+/// The end of the memory the program of the zkVM is loaded in, where the heap of the guest ends.
+pub const ZKVM_PROGRAM_END: u32 = 0x0C00_0000;
+
+/// The length of the header of a 32-bit ELF.
+const ELF32_HEADER_LEN: usize = 52;
+
+/// A property of an ELF header which makes it unfit for the zkVM.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ElfValidationError {
+    #[error("the file is not an ELF: its magic bytes are {0:02x?}")]
+    NotElf(Vec<u8>),
+    #[error(
+        "the ELF header is truncated: the file has {0} bytes, expected at least {ELF32_HEADER_LEN}"
+    )]
+    Truncated(usize),
+    #[error("the ELF class is {0} ({}), expected 32-bit (1)", class_name(*.0))]
+    Class(u8),
+    #[error("the ELF data encoding is {0} ({}), expected little-endian (1)", endianness_name(*.0))]
+    Endianness(u8),
+    #[error("the machine type is {0:#x}, expected EM_RISCV ({EM_RISCV:#x})")]
+    Machine(u16),
+    #[error(
+        "the entry point is {0:#010x}, expected it in the zkVM program memory \
+         [{ZKVM_PROGRAM_START:#010x}, {ZKVM_PROGRAM_END:#010x})"
+    )]
+    EntryPoint(u32),
+}
+
+fn class_name(class: u8) -> &'static str {
+    match class {
+        1 => "32-bit",
+        2 => "64-bit",
+        _ => "invalid",
+    }
+}
+
+fn endianness_name(data: u8) -> &'static str {
+    match data {
+        1 => "little-endian",
+        2 => "big-endian",
+        _ => "invalid",
+    }
+}
+
+/// Checks that `elf` starts with the header of a zkVM program: a 32-bit little-endian RISC-V ELF
+/// whose entry point is in the zkVM program memory.
+pub fn validate_elf(elf: &[u8]) -> Result<(), ElfValidationError> {
+    if elf.len() < 4 || elf[..4] != *b"\x7fELF" {
+        return Err(ElfValidationError::NotElf(elf[..elf.len().min(4)].to_vec()));
+    }
+    if elf.len() < 6 {
+        return Err(ElfValidationError::Truncated(elf.len()));
+    }
+    if elf[4] != 1 {
+        return Err(ElfValidationError::Class(elf[4]));
+    }
+    if elf[5] != 1 {
+        return Err(ElfValidationError::Endianness(elf[5]));
+    }
+    if elf.len() < ELF32_HEADER_LEN {
+        return Err(ElfValidationError::Truncated(elf.len()));
+    }
+    let machine = u16::from_le_bytes([elf[18], elf[19]]);
+    if machine != EM_RISCV {
+        return Err(ElfValidationError::Machine(machine));
+    }
+    let entry = u32::from_le_bytes(elf[24..28].try_into().unwrap());
+    if !(ZKVM_PROGRAM_START..ZKVM_PROGRAM_END).contains(&entry) {
+        return Err(ElfValidationError::EntryPoint(entry));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The header of an ELF of class `class` for `machine`, with the entry point `entry`.
+    fn header(class: u8, machine: u16, entry: u64) -> Vec<u8> {
+        let mut header = vec![0x7f, b'E', b'L', b'F', class, 1, 1];
+        header.resize(16, 0);
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&machine.to_le_bytes());
+        header.extend_from_slice(&1u32.to_le_bytes());
+        if class == 1 {
+            header.extend_from_slice(&(entry as u32).to_le_bytes());
+            header.resize(ELF32_HEADER_LEN, 0);
+        } else {
+            header.extend_from_slice(&entry.to_le_bytes());
+            header.resize(64, 0);
+        }
+        header
+    }
+
+    #[test]
+    fn test_validate_elf() {
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        assert_eq!(validate_elf(elf), Ok(()));
+        assert_eq!(validate_elf(&header(1, EM_RISCV, 0x0020_0800)), Ok(()));
+
+        // A host binary.
+        let x86_64 = header(2, 0x3e, 0x40_1000);
+        assert_eq!(validate_elf(&x86_64), Err(ElfValidationError::Class(2)));
+        let x86 = header(1, 0x03, 0x0804_8000);
+        assert_eq!(validate_elf(&x86), Err(ElfValidationError::Machine(0x03)));
+
+        let mut big_endian = header(1, EM_RISCV, 0x0020_0800);
+        big_endian[5] = 2;
+        assert_eq!(
+            validate_elf(&big_endian),
+            Err(ElfValidationError::Endianness(2))
+        );
+        assert_eq!(
+            validate_elf(&header(1, EM_RISCV, 0x1000)),
+            Err(ElfValidationError::EntryPoint(0x1000))
+        );
+    }
+
+    #[test]
+    fn test_validate_truncated_elf() {
+        let elf = header(1, EM_RISCV, 0x0020_0800);
+        assert_eq!(
+            validate_elf(&elf[..20]),
+            Err(ElfValidationError::Truncated(20))
+        );
+        assert_eq!(
+            validate_elf(&elf[..5]),
+            Err(ElfValidationError::Truncated(5))
+        );
+        assert_eq!(
+            validate_elf(&elf[..2]),
+            Err(ElfValidationError::NotElf(vec![0x7f, b'E']))
+        );
+        assert_eq!(
+            validate_elf(b"#!/bin/sh\n"),
+            Err(ElfValidationError::NotElf(b"#!/b".to_vec()))
+        );
+        assert_eq!(
+            ElfValidationError::Machine(0x3e).to_string(),
+            "the machine type is 0x3e, expected EM_RISCV (0xf3)"
+        );
+    }
+}
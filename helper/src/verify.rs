@@ -0,0 +1,253 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use elf::{endian::LittleEndian, ElfBytes};
+use sha2::{Digest, Sha256};
+
+use crate::{cargo_prove_build_cmd, run_with_prefixed_output, BuildArgs};
+
+/// The result of comparing a committed ELF against a fresh build of the program source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The sha256 digest of the committed ELF.
+    pub committed_digest: [u8; 32],
+    /// The sha256 digest of the ELF built from the current source.
+    pub rebuilt_digest: [u8; 32],
+    /// The sections whose size or contents differ between the two ELFs, ordered by name.
+    pub section_diffs: Vec<SectionDiff>,
+}
+
+/// A section that differs between the committed and the rebuilt ELF.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionDiff {
+    pub name: String,
+    /// The size of the section in the committed ELF, or `None` if it is missing.
+    pub committed_size: Option<u64>,
+    /// The size of the section in the rebuilt ELF, or `None` if it is missing.
+    pub rebuilt_size: Option<u64>,
+}
+
+impl VerifyReport {
+    /// Whether the committed ELF is byte-for-byte identical to the rebuilt one.
+    pub fn matches(&self) -> bool {
+        self.committed_digest == self.rebuilt_digest
+    }
+}
+
+impl SectionDiff {
+    /// The change in size from the committed to the rebuilt section, treating a missing section
+    /// as empty.
+    pub fn size_delta(&self) -> i64 {
+        self.rebuilt_size.unwrap_or(0) as i64 - self.committed_size.unwrap_or(0) as i64
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.matches() {
+            return write!(
+                f,
+                "ELF matches source (sha256 {})",
+                hex(&self.rebuilt_digest)
+            );
+        }
+        writeln!(
+            f,
+            "ELF does not match source: committed sha256 {}, rebuilt sha256 {}",
+            hex(&self.committed_digest),
+            hex(&self.rebuilt_digest)
+        )?;
+        for diff in self.section_diffs.iter() {
+            let size = |size: Option<u64>| size.map_or("missing".to_string(), |s| s.to_string());
+            writeln!(
+                f,
+                "  {}: {} -> {} ({:+} bytes)",
+                diff.name,
+                size(diff.committed_size),
+                size(diff.rebuilt_size),
+                diff.size_delta()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether the ELF at `elf_path` corresponds to the program source at `path`.
+///
+/// The program is rebuilt into a temporary target directory, so neither the committed ELF nor
+/// the program's normal target directory are modified.
+pub fn verify_program(path: &str, elf_path: &str, args: BuildArgs) -> Result<VerifyReport> {
+    let committed = fs::read(elf_path)
+        .with_context(|| format!("failed to read committed ELF at {}", elf_path))?;
+    let rebuilt = build_to_temp_dir(Path::new(path), &args)?;
+    compare_elfs(&committed, &rebuilt)
+}
+
+/// Builds the program with the target directory redirected to a temporary directory and returns
+/// the ELF bytes.
+fn build_to_temp_dir(program_dir: &Path, args: &BuildArgs) -> Result<Vec<u8>> {
+    let temp_dir = tempfile::tempdir().context("failed to create temporary directory")?;
+    let target_dir = temp_dir.path().join("target");
+
+    // `cargo prove build` copies the ELF into `elf/` next to the target directory, so this also
+    // keeps the committed ELF untouched.
+    let mut cmd = cargo_prove_build_cmd(&program_dir, args);
+    cmd.env("CARGO_TARGET_DIR", &target_dir);
+    let (status, _) =
+        run_with_prefixed_output(&mut cmd).context("failed to run cargo prove build")?;
+    if !status.success() {
+        return Err(anyhow!("failed to build `{}`", program_dir.display()));
+    }
+
+    let elf_path: PathBuf = temp_dir
+        .path()
+        .join("elf")
+        .join("riscv32im-succinct-zkvm-elf");
+    fs::read(&elf_path).with_context(|| format!("failed to read built ELF at {:?}", elf_path))
+}
+
+/// Compares two ELFs by digest and, if they differ, by section.
+pub fn compare_elfs(committed: &[u8], rebuilt: &[u8]) -> Result<VerifyReport> {
+    let committed_digest: [u8; 32] = Sha256::digest(committed).into();
+    let rebuilt_digest: [u8; 32] = Sha256::digest(rebuilt).into();
+    let section_diffs = if committed_digest == rebuilt_digest {
+        Vec::new()
+    } else {
+        let committed_sections = sections(committed).context("failed to parse committed ELF")?;
+        let rebuilt_sections = sections(rebuilt).context("failed to parse rebuilt ELF")?;
+        let names = committed_sections
+            .keys()
+            .chain(rebuilt_sections.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let committed = committed_sections.get(&name);
+                let rebuilt = rebuilt_sections.get(&name);
+                (committed != rebuilt).then(|| SectionDiff {
+                    committed_size: committed.map(|(size, _)| *size),
+                    rebuilt_size: rebuilt.map(|(size, _)| *size),
+                    name,
+                })
+            })
+            .collect()
+    };
+    Ok(VerifyReport {
+        committed_digest,
+        rebuilt_digest,
+        section_diffs,
+    })
+}
+
+/// Returns the size and content digest of every named section in the ELF.
+fn sections(elf: &[u8]) -> Result<BTreeMap<String, (u64, [u8; 32])>> {
+    let elf = ElfBytes::<LittleEndian>::minimal_parse(elf)?;
+    let (headers, strtab) = elf.section_headers_with_strtab()?;
+    let (headers, strtab) = headers
+        .zip(strtab)
+        .ok_or_else(|| anyhow!("ELF has no section headers"))?;
+    let mut sections = BTreeMap::new();
+    for header in headers.iter() {
+        let name = strtab.get(header.sh_name as usize)?;
+        if name.is_empty() {
+            continue;
+        }
+        // Sections like `.bss` occupy no space in the file, so only their size is compared.
+        let (data, _) = elf.section_data(&header)?;
+        let digest: [u8; 32] = Sha256::digest(data).into();
+        sections.insert(name.to_string(), (header.sh_size, digest));
+    }
+    Ok(sections)
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toml_path;
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+    const SHA2_ELF: &[u8] = include_bytes!("../../tests/sha2/elf/riscv32im-succinct-zkvm-elf");
+
+    #[test]
+    fn test_compare_identical_elfs() {
+        let report = compare_elfs(FIBONACCI_ELF, FIBONACCI_ELF).unwrap();
+        assert!(report.matches());
+        assert!(report.section_diffs.is_empty());
+    }
+
+    #[test]
+    fn test_compare_different_elfs() {
+        let report = compare_elfs(FIBONACCI_ELF, SHA2_ELF).unwrap();
+        assert!(!report.matches());
+        let text = report
+            .section_diffs
+            .iter()
+            .find(|diff| diff.name == ".text")
+            .expect(".text should differ");
+        assert!(text.committed_size.is_some() && text.rebuilt_size.is_some());
+        assert!(report.to_string().contains(".text"));
+    }
+
+    /// Requires the `cargo prove` toolchain.
+    #[test]
+    #[ignore]
+    fn test_verify_program() {
+        // Copy the fibonacci test program so that its source can be modified.
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fibonacci");
+        let entrypoint = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../zkvm/entrypoint")
+            .canonicalize()
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::copy(fixture.join("Cargo.lock"), dir.path().join("Cargo.lock")).unwrap();
+        let manifest = fs::read_to_string(fixture.join("Cargo.toml"))
+            .unwrap()
+            .replace(
+                "../../zkvm/entrypoint",
+                &toml_path(entrypoint.to_str().unwrap()),
+            );
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        let main = fs::read_to_string(fixture.join("src/main.rs")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), &main).unwrap();
+
+        // Use a fresh build as the committed ELF.
+        let elf_path = dir.path().join("riscv32im-succinct-zkvm-elf");
+        let elf = build_to_temp_dir(dir.path(), &BuildArgs::default()).unwrap();
+        fs::write(&elf_path, elf).unwrap();
+
+        let path = dir.path().to_str().unwrap();
+        let report =
+            verify_program(path, elf_path.to_str().unwrap(), BuildArgs::default()).unwrap();
+        assert!(report.matches(), "{}", report);
+        assert!(!dir.path().join("target").exists());
+        assert!(!dir.path().join("elf").exists());
+
+        // Perturb the source so that more code is generated.
+        let main = main.replace(
+            "    sp1_zkvm::io::commit(&b);",
+            "    sp1_zkvm::io::commit(&b);\n    sp1_zkvm::io::commit(&format!(\"{} {}\", a, b));",
+        );
+        fs::write(dir.path().join("src/main.rs"), main).unwrap();
+
+        let report =
+            verify_program(path, elf_path.to_str().unwrap(), BuildArgs::default()).unwrap();
+        assert!(!report.matches());
+        let text = report
+            .section_diffs
+            .iter()
+            .find(|diff| diff.name == ".text")
+            .expect(".text should differ");
+        assert!(text.size_delta() > 0, "{}", report);
+    }
+}
@@ -2,17 +2,40 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use sp1_core::utils::setup_logger;
-use sp1_prover::build::build_plonk_bn254_artifacts_with_dummy;
+use sp1_prover::build::{
+    build_plonk_bn254_artifacts_with_dummy, build_plonk_bn254_artifacts_with_dummy_and_opts,
+};
+use sp1_recursion_gnark_ffi::BuildOpts;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
     #[clap(short, long)]
     build_dir: PathBuf,
+    /// Build INSECURE development artifacts, with a setup generated locally.
+    #[clap(long)]
+    dev: bool,
+    /// The hex encoded 32 bytes seed of the development setup, for reproducible artifacts.
+    #[clap(long, requires = "dev")]
+    dev_seed: Option<String>,
 }
 
 pub fn main() {
     setup_logger();
     let args = Args::parse();
-    build_plonk_bn254_artifacts_with_dummy(args.build_dir);
+    if !args.dev {
+        build_plonk_bn254_artifacts_with_dummy(args.build_dir);
+        return;
+    }
+    let dev_seed = args.dev_seed.map(|seed| {
+        hex::decode(seed)
+            .expect("invalid dev seed")
+            .try_into()
+            .expect("the dev seed must be 32 bytes")
+    });
+    let opts = BuildOpts {
+        dev: true,
+        dev_seed,
+    };
+    build_plonk_bn254_artifacts_with_dummy_and_opts(args.build_dir, opts);
 }
@@ -60,11 +60,14 @@ pub fn main() {
         .try_into()
         .unwrap();
     let committed_values_digest = babybear_bytes_to_bn254(&committed_values_digest_bytes);
+    let input_digest_bytes: [BabyBear; 32] = words_to_bytes(&pv.input_digest).try_into().unwrap();
+    let input_digest = babybear_bytes_to_bn254(&input_digest_bytes);
 
     let mut witness = Witness::default();
     wrapped_proof.proof.write(&mut witness);
     witness.write_commited_values_digest(committed_values_digest);
     witness.write_vkey_hash(vkey_hash);
+    witness.write_input_digest(input_digest);
 
     tracing::info!("sanity check gnark test");
     PlonkBn254Prover::test(constraints.clone(), witness.clone());
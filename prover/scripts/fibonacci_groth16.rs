@@ -57,6 +57,8 @@ fn main() {
             buffer: vec![bincode::serialize::<u32>(&iterations).unwrap()],
             ptr: 0,
             proofs: vec![],
+            config: Default::default(),
+            entropy: vec![],
         };
         let leaf_proving_start = Instant::now();
         let proof = prover.prove_core(&pk, &stdin).unwrap();
@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use sp1_core::io::SP1Stdin;
+use sp1_core::utils::setup_logger;
+use sp1_prover::fixtures::{Fixtures, FIXTURES_DIR};
+use sp1_prover::SP1Prover;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// The directory the fixtures are written to, by default the committed fibonacci fixtures.
+    #[clap(short, long)]
+    out_dir: Option<PathBuf>,
+    /// The log2 of the shard size, small enough for the core proof to have several shards.
+    #[clap(long, default_value_t = 12)]
+    log_shard_size: usize,
+}
+
+pub fn main() {
+    setup_logger();
+    let args = Args::parse();
+    let out_dir = args
+        .out_dir
+        .unwrap_or_else(|| PathBuf::from(FIXTURES_DIR).join("fibonacci"));
+
+    let elf = include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+    let mut prover = SP1Prover::new();
+    prover.core_opts.shard_size = 1 << args.log_shard_size;
+
+    let manifest = Fixtures::generate(&prover, elf, &SP1Stdin::new(), &out_dir)
+        .expect("failed to generate the fixtures");
+    println!("{}", serde_json::to_string_pretty(&manifest).unwrap());
+}
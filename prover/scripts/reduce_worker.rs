@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use sp1_core::utils::setup_logger;
+use sp1_prover::run_reduce_job;
+
+/// Generates the witness of a reduce node, see [sp1_prover::ReduceJobOpts].
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// The job to run.
+    job: PathBuf,
+    /// The file the output of the job is written to.
+    output: PathBuf,
+}
+
+pub fn main() {
+    setup_logger();
+    let args = Args::parse();
+    let output = run_reduce_job(&args.job).expect("failed to run the reduce job");
+    output
+        .save(&args.output)
+        .expect("failed to write the output of the reduce job");
+}
@@ -2,15 +2,22 @@ use std::borrow::Borrow;
 use std::path::PathBuf;
 
 use p3_baby_bear::BabyBear;
+use p3_field::PrimeField32;
 use sp1_core::stark::StarkVerifyingKey;
 use sp1_core::{io::SP1Stdin, stark::ShardProof};
-pub use sp1_recursion_circuit::stark::build_wrap_circuit;
+use sp1_recursion_circuit::ecdsa::{
+    secp256k1_ecdsa_num_constraints, write_secp256k1_ecdsa_witness,
+};
+pub use sp1_recursion_circuit::ecdsa::{EcdsaSignature, Secp256k1Point};
+pub use sp1_recursion_circuit::stark::{
+    build_wrap_circuit, build_wrap_circuit_into, build_wrap_circuit_with_opts, WrapCircuitOpts,
+};
 pub use sp1_recursion_circuit::witness::Witnessable;
 pub use sp1_recursion_compiler::ir::Witness;
 use sp1_recursion_compiler::{config::OuterConfig, constraints::Constraint};
 use sp1_recursion_core::air::RecursionPublicValues;
 pub use sp1_recursion_core::stark::utils::sp1_dev_mode;
-use sp1_recursion_gnark_ffi::PlonkBn254Prover;
+use sp1_recursion_gnark_ffi::{BuildOpts, PlonkBn254Prover};
 
 use crate::install::install_plonk_bn254_artifacts;
 use crate::utils::{babybear_bytes_to_bn254, babybears_to_bn254, words_to_bytes};
@@ -33,6 +40,8 @@ pub fn try_install_plonk_bn254_artifacts() -> PathBuf {
         );
         install_plonk_bn254_artifacts(build_dir.clone());
     }
+    PlonkBn254Prover::check_production_artifacts(&build_dir)
+        .expect("refusing to prove with development plonk bn254 artifacts");
     build_dir
 }
 
@@ -75,9 +84,95 @@ pub fn build_plonk_bn254_artifacts(
     build_dir: impl Into<PathBuf>,
 ) {
     let build_dir = build_dir.into();
+    let opts = BuildOpts {
+        dev: build_dir.to_str().unwrap().contains("dev"),
+        dev_seed: None,
+    };
+    build_plonk_bn254_artifacts_streamed(
+        template_vk,
+        template_proof,
+        build_dir,
+        &WrapCircuitOpts::default(),
+        None,
+        opts,
+    );
+}
+
+/// Build the plonk bn254 artifacts to the given directory with the given options, for example with
+/// a seeded development setup to get reproducible test fixtures.
+pub fn build_plonk_bn254_artifacts_with_opts(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    build_dir: impl Into<PathBuf>,
+    opts: BuildOpts,
+) {
+    build_plonk_bn254_artifacts_streamed(
+        template_vk,
+        template_proof,
+        build_dir.into(),
+        &WrapCircuitOpts::default(),
+        None,
+        opts,
+    );
+}
+
+/// Build the plonk bn254 artifacts of a circuit also verifying a signature by `operator_key` over
+/// the committed values digest, see [WrapCircuitOpts::operator_key]. The template witness needs
+/// `template_signature`, the signature of the committed values digest of the template proof.
+pub fn build_plonk_bn254_artifacts_with_operator(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    build_dir: impl Into<PathBuf>,
+    operator_key: &Secp256k1Point,
+    template_signature: &EcdsaSignature,
+) {
+    let build_dir = build_dir.into();
+    tracing::info!(
+        "verifying operator signatures adds {} constraints",
+        secp256k1_ecdsa_num_constraints()
+    );
+    let wrap_opts = WrapCircuitOpts {
+        operator_key: Some(operator_key.clone()),
+    };
+    let opts = BuildOpts {
+        dev: build_dir.to_str().unwrap().contains("dev"),
+        dev_seed: None,
+    };
+    build_plonk_bn254_artifacts_streamed(
+        template_vk,
+        template_proof,
+        build_dir,
+        &wrap_opts,
+        Some(template_signature),
+        opts,
+    );
+}
+
+/// Builds the plonk bn254 artifacts, streaming the constraints of the circuit to the build
+/// directory as they are compiled instead of holding them in memory, see
+/// [PlonkBn254Prover::constraints_writer].
+fn build_plonk_bn254_artifacts_streamed(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    build_dir: PathBuf,
+    wrap_opts: &WrapCircuitOpts,
+    template_signature: Option<&EcdsaSignature>,
+    opts: BuildOpts,
+) {
     std::fs::create_dir_all(&build_dir).expect("failed to create build directory");
-    let (constraints, witness) = build_constraints_and_witness(template_vk, template_proof);
-    PlonkBn254Prover::build(constraints, witness, build_dir);
+
+    tracing::info!("building verifier constraints");
+    let mut writer = PlonkBn254Prover::constraints_writer(&build_dir)
+        .expect("failed to create the constraints file");
+    tracing::info_span!("wrap circuit").in_scope(|| {
+        build_wrap_circuit_into(template_vk, template_proof.clone(), wrap_opts, &mut writer)
+    });
+    tracing::info!("wrote {} constraints", writer.len());
+    writer.finish().expect("failed to write the constraints");
+
+    let witness = build_template_witness(template_proof, wrap_opts, template_signature);
+    PlonkBn254Prover::build_with_written_constraints(witness, build_dir, opts)
+        .expect("failed to build plonk bn254 artifacts");
 }
 
 /// Builds the plonk bn254 artifacts to the given directory.
@@ -89,29 +184,72 @@ pub fn build_plonk_bn254_artifacts_with_dummy(build_dir: impl Into<PathBuf>) {
     crate::build::build_plonk_bn254_artifacts(&wrap_vk, &wrapped_proof, build_dir.into());
 }
 
+/// Builds the plonk bn254 artifacts to the given directory with the given options, see
+/// [build_plonk_bn254_artifacts_with_dummy].
+pub fn build_plonk_bn254_artifacts_with_dummy_and_opts(
+    build_dir: impl Into<PathBuf>,
+    opts: BuildOpts,
+) {
+    let (wrap_vk, wrapped_proof) = dummy_proof();
+    build_plonk_bn254_artifacts_with_opts(&wrap_vk, &wrapped_proof, build_dir, opts);
+}
+
 /// Build the verifier constraints and template witness for the circuit.
 pub fn build_constraints_and_witness(
     template_vk: &StarkVerifyingKey<OuterSC>,
     template_proof: &ShardProof<OuterSC>,
+) -> (Vec<Constraint>, Witness<OuterConfig>) {
+    build_constraints_and_witness_with_opts(
+        template_vk,
+        template_proof,
+        &WrapCircuitOpts::default(),
+        None,
+    )
+}
+
+/// Build the verifier constraints and template witness for the circuit with the given options.
+/// `template_signature` is required if the circuit verifies an operator signature.
+pub fn build_constraints_and_witness_with_opts(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    opts: &WrapCircuitOpts,
+    template_signature: Option<&EcdsaSignature>,
 ) -> (Vec<Constraint>, Witness<OuterConfig>) {
     tracing::info!("building verifier constraints");
     let constraints = tracing::info_span!("wrap circuit")
-        .in_scope(|| build_wrap_circuit(template_vk, template_proof.clone()));
+        .in_scope(|| build_wrap_circuit_with_opts(template_vk, template_proof.clone(), opts));
+    let witness = build_template_witness(template_proof, opts, template_signature);
+    (constraints, witness)
+}
 
+/// Build the template witness of the circuit with the given options, see
+/// [build_constraints_and_witness_with_opts].
+pub fn build_template_witness(
+    template_proof: &ShardProof<OuterSC>,
+    opts: &WrapCircuitOpts,
+    template_signature: Option<&EcdsaSignature>,
+) -> Witness<OuterConfig> {
     let pv: &RecursionPublicValues<BabyBear> = template_proof.public_values.as_slice().borrow();
     let vkey_hash = babybears_to_bn254(&pv.sp1_vk_digest);
     let committed_values_digest_bytes: [BabyBear; 32] = words_to_bytes(&pv.committed_value_digest)
         .try_into()
         .unwrap();
     let committed_values_digest = babybear_bytes_to_bn254(&committed_values_digest_bytes);
+    let input_digest_bytes: [BabyBear; 32] = words_to_bytes(&pv.input_digest).try_into().unwrap();
+    let input_digest = babybear_bytes_to_bn254(&input_digest_bytes);
 
     tracing::info!("building template witness");
     let mut witness = Witness::default();
     template_proof.write(&mut witness);
     witness.write_commited_values_digest(committed_values_digest);
     witness.write_vkey_hash(vkey_hash);
-
-    (constraints, witness)
+    witness.write_input_digest(input_digest);
+    if let Some(operator_key) = &opts.operator_key {
+        let digest = committed_values_digest_bytes.map(|byte| byte.as_canonical_u32() as u8);
+        let signature = template_signature.expect("the template proof must be signed");
+        write_secp256k1_ecdsa_witness(&mut witness, operator_key, &digest, signature);
+    }
+    witness
 }
 
 /// Generate a dummy proof that we can use to build the circuit. We need this to know the shape of
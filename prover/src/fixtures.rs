@@ -0,0 +1,393 @@
+//! Golden proof fixtures, checking that the proofs made by an earlier version of the prover are
+//! still verified by the current one.
+//!
+//! A fixture directory holds a proof of the fibonacci program at every stage of the pipeline (the
+//! core shard proofs, the compressed proof and the proof wrapped over BN254), the verifying key of
+//! the program and a `manifest.json` listing the files with their SHA-256 digests. The manifest
+//! records the [FIXTURE_FORMAT_VERSION] the fixtures were generated with: a change which makes the
+//! existing proofs unverifiable, such as a new recursion circuit, must bump it, so that the old
+//! fixtures fail to load with [FixtureError::Incompatible] instead of a verification error.
+//!
+//! The fixtures are generated with the `generate_fixtures` binary, see `prover/fixtures/README.md`.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp1_core::io::SP1Stdin;
+use thiserror::Error;
+
+use crate::{
+    InnerSC, OuterSC, SP1CoreProof, SP1Prover, SP1ReduceProof, SP1VerifyingKey, SP1_CIRCUIT_VERSION,
+};
+
+/// The version of the fixture format, bumped on every change making the existing proofs
+/// unverifiable.
+pub const FIXTURE_FORMAT_VERSION: u32 = 1;
+
+/// The directory of the committed fixtures.
+pub const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+
+/// The name of the manifest of a fixture directory.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The name of the verifying key file of a fixture directory.
+const VK_FILE: &str = "vk.bin";
+
+/// A stage of the proof pipeline with a fixture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureStage {
+    /// The shard proofs of the program.
+    Core,
+    /// The shard proofs compressed into a single proof.
+    Compressed,
+    /// The compressed proof wrapped into a proof over BN254.
+    Wrapped,
+}
+
+impl FixtureStage {
+    /// All the stages, in the order of the pipeline.
+    pub const ALL: [FixtureStage; 3] = [
+        FixtureStage::Core,
+        FixtureStage::Compressed,
+        FixtureStage::Wrapped,
+    ];
+
+    /// The name of the proof file of the stage.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            FixtureStage::Core => "core_proof.bin",
+            FixtureStage::Compressed => "compressed_proof.bin",
+            FixtureStage::Wrapped => "wrapped_proof.bin",
+        }
+    }
+}
+
+impl fmt::Display for FixtureStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureStage::Core => write!(f, "core"),
+            FixtureStage::Compressed => write!(f, "compressed"),
+            FixtureStage::Wrapped => write!(f, "wrapped"),
+        }
+    }
+}
+
+/// An error generating, loading or verifying fixtures.
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error(
+        "the fixtures have format version {found}, which is incompatible with version {expected}"
+    )]
+    Incompatible { found: u32, expected: u32 },
+    #[error("{0} is missing from the fixtures")]
+    Missing(PathBuf),
+    #[error("{0} does not match the digest in the manifest")]
+    Checksum(PathBuf),
+    #[error("failed to decode {path}: {source}")]
+    Decode {
+        path: PathBuf,
+        source: bincode::Error,
+    },
+    #[error("invalid manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+    #[error("failed to prove the {stage} stage: {reason}")]
+    Proving { stage: FixtureStage, reason: String },
+    #[error("the {stage} proof failed to verify: {reason}")]
+    Verification { stage: FixtureStage, reason: String },
+    #[error("the public values of the core proof do not match the manifest")]
+    PublicValues,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The description of a fixture directory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FixtureManifest {
+    /// The [FIXTURE_FORMAT_VERSION] the fixtures were generated with.
+    pub format_version: u32,
+    /// The [SP1_CIRCUIT_VERSION] the fixtures were generated with, for information.
+    pub sp1_version: String,
+    /// The hex encoded public values committed by the program.
+    pub public_values: String,
+    /// The hex encoded SHA-256 digests of the files, by name.
+    pub files: BTreeMap<String, String>,
+}
+
+impl FixtureManifest {
+    /// Reads the manifest of the fixtures in `dir`, without checking its format version.
+    pub fn read(dir: &Path) -> Result<Self, FixtureError> {
+        let path = dir.join(MANIFEST_FILE);
+        let file = fs::File::open(&path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => FixtureError::Missing(path.clone()),
+            _ => err.into(),
+        })?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Whether the fixtures can be verified by the current version of the prover.
+    pub fn is_compatible(&self) -> bool {
+        self.format_version == FIXTURE_FORMAT_VERSION
+    }
+}
+
+/// The proofs of a fixture directory, see the [module documentation](self).
+pub struct Fixtures {
+    pub manifest: FixtureManifest,
+    pub vk: SP1VerifyingKey,
+    pub core: SP1CoreProof,
+    pub compressed: SP1ReduceProof<InnerSC>,
+    pub wrapped: SP1ReduceProof<OuterSC>,
+}
+
+impl Fixtures {
+    /// Proves the execution of `elf` on `stdin` at every stage and writes the fixtures to `dir`,
+    /// returning their manifest.
+    pub fn generate(
+        prover: &SP1Prover,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        dir: &Path,
+    ) -> Result<FixtureManifest, FixtureError> {
+        let proving = |stage: FixtureStage, err: &dyn fmt::Display| FixtureError::Proving {
+            stage,
+            reason: err.to_string(),
+        };
+
+        tracing::info!("proving the core stage");
+        let (pk, vk) = prover.setup(elf);
+        let core = prover
+            .prove_core(&pk, stdin)
+            .map_err(|err| proving(FixtureStage::Core, &err))?;
+
+        tracing::info!("proving the compressed stage");
+        let compressed = prover
+            .compress(&vk, core.clone(), vec![])
+            .map_err(|err| proving(FixtureStage::Compressed, &err))?;
+
+        tracing::info!("proving the wrapped stage");
+        let wrapped = prover
+            .shrink(compressed.clone())
+            .and_then(|shrink| prover.wrap_bn254(shrink))
+            .map_err(|err| proving(FixtureStage::Wrapped, &err))?;
+
+        let fixtures = Fixtures {
+            manifest: FixtureManifest {
+                format_version: FIXTURE_FORMAT_VERSION,
+                sp1_version: SP1_CIRCUIT_VERSION.to_string(),
+                public_values: hex::encode(core.public_values.as_slice()),
+                files: BTreeMap::new(),
+            },
+            vk,
+            core,
+            compressed,
+            wrapped,
+        };
+        fixtures.write(dir)
+    }
+
+    /// Writes the fixtures to `dir`, returning their manifest with the digests of the files.
+    pub fn write(&self, dir: &Path) -> Result<FixtureManifest, FixtureError> {
+        fs::create_dir_all(dir)?;
+        let mut manifest = self.manifest.clone();
+        manifest.files.clear();
+        let mut write = |name: &str, bytes: Vec<u8>| -> Result<(), FixtureError> {
+            fs::write(dir.join(name), &bytes)?;
+            manifest
+                .files
+                .insert(name.to_string(), hex::encode(Sha256::digest(&bytes)));
+            Ok(())
+        };
+        write(VK_FILE, encode(&self.vk))?;
+        write(FixtureStage::Core.file_name(), encode(&self.core))?;
+        write(
+            FixtureStage::Compressed.file_name(),
+            encode(&self.compressed),
+        )?;
+        write(FixtureStage::Wrapped.file_name(), encode(&self.wrapped))?;
+
+        let file = fs::File::create(dir.join(MANIFEST_FILE))?;
+        serde_json::to_writer_pretty(file, &manifest)?;
+        Ok(manifest)
+    }
+
+    /// Loads the fixtures in `dir`.
+    ///
+    /// Fails with [FixtureError::Incompatible] before reading any proof if the fixtures were
+    /// generated with another [FIXTURE_FORMAT_VERSION], and with [FixtureError::Checksum] if a file
+    /// does not match its digest in the manifest.
+    pub fn load(dir: &Path) -> Result<Self, FixtureError> {
+        let manifest = FixtureManifest::read(dir)?;
+        if !manifest.is_compatible() {
+            return Err(FixtureError::Incompatible {
+                found: manifest.format_version,
+                expected: FIXTURE_FORMAT_VERSION,
+            });
+        }
+
+        // Check all the files before decoding any, so that a missing or corrupted file is reported
+        // as such rather than as a malformed proof.
+        let vk = read(dir, &manifest, VK_FILE)?;
+        let core = read(dir, &manifest, FixtureStage::Core.file_name())?;
+        let compressed = read(dir, &manifest, FixtureStage::Compressed.file_name())?;
+        let wrapped = read(dir, &manifest, FixtureStage::Wrapped.file_name())?;
+        Ok(Self {
+            manifest,
+            vk: decode(dir, VK_FILE, &vk)?,
+            core: decode(dir, FixtureStage::Core.file_name(), &core)?,
+            compressed: decode(dir, FixtureStage::Compressed.file_name(), &compressed)?,
+            wrapped: decode(dir, FixtureStage::Wrapped.file_name(), &wrapped)?,
+        })
+    }
+
+    /// Verifies the proofs of every stage with `prover`.
+    pub fn verify(&self, prover: &SP1Prover) -> Result<(), FixtureError> {
+        if hex::encode(self.core.public_values.as_slice()) != self.manifest.public_values {
+            return Err(FixtureError::PublicValues);
+        }
+        for stage in FixtureStage::ALL {
+            let result = match stage {
+                FixtureStage::Core => prover
+                    .verify(&self.core.proof, &self.vk)
+                    .map_err(|err| err.to_string()),
+                FixtureStage::Compressed => prover
+                    .verify_compressed(&self.compressed, &self.vk)
+                    .map_err(|err| err.to_string()),
+                FixtureStage::Wrapped => prover
+                    .verify_wrap_bn254(&self.wrapped, &self.vk)
+                    .map_err(|err| err.to_string()),
+            };
+            result.map_err(|reason| FixtureError::Verification { stage, reason })?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes a fixture file.
+fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    bincode::serialize(value).expect("failed to encode the fixture")
+}
+
+/// Reads the fixture file `name` of `dir`, checking it against its digest in `manifest`.
+fn read(dir: &Path, manifest: &FixtureManifest, name: &str) -> Result<Vec<u8>, FixtureError> {
+    let path = dir.join(name);
+    let digest = manifest
+        .files
+        .get(name)
+        .ok_or_else(|| FixtureError::Missing(path.clone()))?;
+    let bytes = fs::read(&path).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => FixtureError::Missing(path.clone()),
+        _ => err.into(),
+    })?;
+    if hex::encode(Sha256::digest(&bytes)) != *digest {
+        return Err(FixtureError::Checksum(path));
+    }
+    Ok(bytes)
+}
+
+/// Decodes the fixture file `name` of `dir`.
+fn decode<T: DeserializeOwned>(dir: &Path, name: &str, bytes: &[u8]) -> Result<T, FixtureError> {
+    bincode::deserialize(bytes).map_err(|source| FixtureError::Decode {
+        path: dir.join(name),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serial_test::serial;
+    use sp1_core::utils::setup_logger;
+
+    /// The fixtures of the fibonacci program.
+    fn fibonacci_fixtures_dir() -> PathBuf {
+        Path::new(FIXTURES_DIR).join("fibonacci")
+    }
+
+    /// Writes a manifest listing `files` to `dir`.
+    fn write_manifest(dir: &Path, format_version: u32, files: &[(&str, &[u8])]) {
+        let manifest = FixtureManifest {
+            format_version,
+            sp1_version: SP1_CIRCUIT_VERSION.to_string(),
+            public_values: String::new(),
+            files: files
+                .iter()
+                .map(|(name, bytes)| (name.to_string(), hex::encode(Sha256::digest(bytes))))
+                .collect(),
+        };
+        let file = fs::File::create(dir.join(MANIFEST_FILE)).unwrap();
+        serde_json::to_writer(file, &manifest).unwrap();
+    }
+
+    /// Verifies the committed fixtures, or checks that they are reported as incompatible if they
+    /// were generated with an earlier format version.
+    #[test]
+    #[serial]
+    #[ignore = "requires the fixtures generated by the `generate_fixtures` binary"]
+    fn test_golden_fixtures() {
+        setup_logger();
+        let dir = fibonacci_fixtures_dir();
+        let manifest = FixtureManifest::read(&dir).unwrap();
+        if !manifest.is_compatible() {
+            assert!(manifest.format_version < FIXTURE_FORMAT_VERSION);
+            match Fixtures::load(&dir) {
+                Err(FixtureError::Incompatible { found, expected }) => {
+                    assert_eq!(found, manifest.format_version);
+                    assert_eq!(expected, FIXTURE_FORMAT_VERSION);
+                }
+                Err(err) => panic!("expected an incompatibility error, got {}", err),
+                Ok(_) => panic!("expected an incompatibility error"),
+            }
+            return;
+        }
+
+        let fixtures = Fixtures::load(&dir).unwrap();
+        fixtures.verify(&SP1Prover::new()).unwrap();
+    }
+
+    #[test]
+    fn test_incompatible_format_version() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(dir.path(), FIXTURE_FORMAT_VERSION + 1, &[]);
+        match Fixtures::load(dir.path()) {
+            Err(FixtureError::Incompatible { found, expected }) => {
+                assert_eq!(found, FIXTURE_FORMAT_VERSION + 1);
+                assert_eq!(expected, FIXTURE_FORMAT_VERSION);
+            }
+            Err(err) => panic!("expected an incompatibility error, got {}", err),
+            Ok(_) => panic!("expected an incompatibility error"),
+        }
+    }
+
+    #[test]
+    fn test_corrupted_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(dir.path(), FIXTURE_FORMAT_VERSION, &[(VK_FILE, b"vk")]);
+        fs::write(dir.path().join(VK_FILE), b"corrupted").unwrap();
+        assert!(matches!(
+            Fixtures::load(dir.path()),
+            Err(FixtureError::Checksum(path)) if path.ends_with(VK_FILE)
+        ));
+
+        // A file left out of the manifest is reported as missing.
+        fs::write(dir.path().join(VK_FILE), b"vk").unwrap();
+        assert!(matches!(
+            Fixtures::load(dir.path()),
+            Err(FixtureError::Missing(path)) if path.ends_with(FixtureStage::Core.file_name())
+        ));
+    }
+
+    #[test]
+    fn test_missing_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            Fixtures::load(dir.path()),
+            Err(FixtureError::Missing(path)) if path.ends_with(MANIFEST_FILE)
+        ));
+    }
+}
@@ -11,26 +11,37 @@
 #![allow(clippy::new_without_default)]
 
 pub mod build;
+pub mod fixtures;
 pub mod install;
+pub mod plan;
+pub mod reduce_job;
 pub mod types;
 pub mod utils;
 pub mod verify;
 
 use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use p3_baby_bear::BabyBear;
 use p3_challenger::CanObserve;
 use p3_field::{AbstractField, PrimeField};
+pub use plan::*;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rayon::prelude::*;
+pub use reduce_job::*;
 use sp1_core::air::{PublicValues, Word};
 pub use sp1_core::io::{SP1PublicValues, SP1Stdin};
-use sp1_core::runtime::{ExecutionError, ExecutionReport, Runtime};
-use sp1_core::stark::{Challenge, StarkProvingKey};
+use sp1_core::runtime::{
+    CoverageReport, ExecutionError, ExecutionReport, GasSchedule, Profiler, Runtime,
+    ShardingConfig, UnconstrainedSyscalls,
+};
+use sp1_core::stark::{Challenge, FingerprintDigest, StarkProvingKey};
 use sp1_core::stark::{Challenger, MachineVerificationError};
-use sp1_core::utils::{SP1CoreOpts, DIGEST_SIZE};
+use sp1_core::utils::{memory, SP1CoreOpts, DIGEST_SIZE};
 use sp1_core::{
     runtime::Program,
     stark::{
@@ -39,6 +50,8 @@ use sp1_core::{
     utils::{BabyBearPoseidon2, SP1CoreProverError},
 };
 use sp1_primitives::hash_deferred_proof;
+use sp1_recursion_circuit::ecdsa::write_secp256k1_ecdsa_witness;
+pub use sp1_recursion_circuit::ecdsa::{EcdsaSignature, Secp256k1Point};
 use sp1_recursion_circuit::witness::Witnessable;
 use sp1_recursion_compiler::config::InnerConfig;
 use sp1_recursion_compiler::ir::Witness;
@@ -76,6 +89,14 @@ const REDUCE_DEGREE: usize = 3;
 const COMPRESS_DEGREE: usize = 9;
 const WRAP_DEGREE: usize = 9;
 
+/// The number of proofs verified by each leaf of the default reduce tree built by
+/// [SP1Prover::compress].
+pub const REDUCE_BATCH_SIZE: usize = 2;
+
+/// The largest number of proofs verified by a reduce node of the default reduce tree, see
+/// [ReducePlan::default_for].
+pub const MAX_REDUCE_ARITY: usize = 3;
+
 pub type ReduceAir<F> = RecursionAir<F, REDUCE_DEGREE>;
 pub type CompressAir<F> = RecursionAir<F, COMPRESS_DEGREE>;
 pub type WrapAir<F> = RecursionAir<F, WRAP_DEGREE>;
@@ -144,6 +165,20 @@ pub struct SP1Prover {
 
     /// The options for the recursion prover.
     pub recursion_opts: SP1CoreOpts,
+
+    /// The handlers of the unconstrained syscalls made by the programs proven by this prover, see
+    /// [SP1CoreOpts::allow_unconstrained].
+    pub unconstrained_syscalls: UnconstrainedSyscalls,
+
+    /// The worker processes generating the witnesses of the reduce nodes, which are generated in
+    /// this process if `None`.
+    pub reduce_jobs: Option<ReduceJobOpts>,
+
+    /// The programs proven by this prover, decoded once, by digest of their verifying key.
+    programs: Mutex<HashMap<[u8; 32], Arc<Program>>>,
+
+    /// The number of programs set up or decoded, see [SP1Prover::num_program_setups].
+    program_setups: AtomicUsize,
 }
 
 impl SP1Prover {
@@ -203,12 +238,46 @@ impl SP1Prover {
             wrap_machine,
             core_opts: SP1CoreOpts::default(),
             recursion_opts: SP1CoreOpts::recursion(),
+            unconstrained_syscalls: UnconstrainedSyscalls::default(),
+            reduce_jobs: None,
+            programs: Mutex::default(),
+            program_setups: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of times this prover did program-dependent work: setting up the keys of a
+    /// program, or decoding the ELF of a proving key it had not proven yet.
+    pub fn num_program_setups(&self) -> usize {
+        self.program_setups.load(Ordering::Relaxed)
+    }
+
+    /// The program of `pk`, decoded the first time it is proven by this prover.
+    pub fn program(&self, pk: &SP1ProvingKey) -> Arc<Program> {
+        let digest = pk.vk.hash_bytes();
+        if let Some(program) = self.programs.lock().unwrap().get(&digest) {
+            return program.clone();
         }
+        self.program_setups.fetch_add(1, Ordering::Relaxed);
+        let program = Arc::new(Program::from(&pk.elf));
+        self.programs
+            .lock()
+            .unwrap()
+            .entry(digest)
+            .or_insert(program)
+            .clone()
+    }
+
+    /// The digest of the shape of the core machine, recorded in the verifying keys and in the
+    /// recursion program, for release tooling to pin: the keys and the recursion programs change
+    /// with it.
+    pub fn core_shape_digest() -> FingerprintDigest {
+        RiscvAir::machine(CoreSC::default()).fingerprint().digest()
     }
 
     /// Creates a proving key and a verifying key for a given RISC-V ELF.
     #[instrument(name = "setup", level = "debug", skip_all)]
     pub fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
+        self.program_setups.fetch_add(1, Ordering::Relaxed);
         let program = Program::from(elf);
         let (pk, vk) = self.core_machine.setup(&program);
         let vk = SP1VerifyingKey { vk };
@@ -226,18 +295,193 @@ impl SP1Prover {
         elf: &[u8],
         stdin: &SP1Stdin,
     ) -> Result<(SP1PublicValues, ExecutionReport), ExecutionError> {
-        let program = Program::from(elf);
-        let opts = SP1CoreOpts::default();
+        Self::execute_with_opts(elf, stdin, SP1CoreOpts::default())
+    }
+
+    /// Executes a program like [SP1Prover::execute] with the given options, such as a lower
+    /// [SP1CoreOpts::max_memory].
+    pub fn execute_with_opts(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+    ) -> Result<(SP1PublicValues, ExecutionReport), ExecutionError> {
+        let runtime = Self::execute_runtime(elf, stdin, opts, |_| {})?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            runtime.report,
+        ))
+    }
+
+    /// Executes a program like [SP1Prover::execute_with_opts], handling its unconstrained syscalls
+    /// with `syscalls`.
+    pub fn execute_with_unconstrained_syscalls(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+        syscalls: &UnconstrainedSyscalls,
+    ) -> Result<(SP1PublicValues, ExecutionReport), ExecutionError> {
+        let runtime = Self::execute_runtime(elf, stdin, opts, |runtime| {
+            runtime.unconstrained_syscalls = syscalls.clone()
+        })?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            runtime.report,
+        ))
+    }
+
+    /// Executes a program like [SP1Prover::execute_with_opts], metering its gas with `schedule`
+    /// instead of the default [GasSchedule].
+    pub fn execute_with_gas_schedule(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+        schedule: &GasSchedule,
+    ) -> Result<(SP1PublicValues, ExecutionReport), ExecutionError> {
+        let runtime = Self::execute_runtime(elf, stdin, opts, |runtime| {
+            runtime.set_gas_schedule(schedule)
+        })?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            runtime.report,
+        ))
+    }
+
+    /// Executes a program like [SP1Prover::execute], also recording which instructions were
+    /// executed.
+    pub fn execute_with_coverage(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport, CoverageReport), ExecutionError> {
+        let runtime = Self::execute_runtime(elf, stdin, SP1CoreOpts::default(), |runtime| {
+            runtime.enable_coverage()
+        })?;
+        let coverage = runtime.coverage_report(elf).unwrap();
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            runtime.report,
+            coverage,
+        ))
+    }
+
+    /// Executes a program like [SP1Prover::execute_with_opts], also sampling its call stack every
+    /// `interval` cycles.
+    pub fn execute_with_profiler(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+        interval: u64,
+    ) -> Result<(SP1PublicValues, ExecutionReport, Profiler), ExecutionError> {
+        let mut runtime = Self::execute_runtime(elf, stdin, opts, |runtime| {
+            runtime.enable_profiler(interval)
+        })?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            runtime.report,
+            runtime.profiler.take().unwrap(),
+        ))
+    }
+
+    /// Executes a program like [SP1Prover::execute_with_opts], also returning the chunks it
+    /// committed with `sp1_zkvm::io::commit_chunk`, to be checked with
+    /// [SP1PublicValues::output_chunks].
+    pub fn execute_with_output_chunks(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+    ) -> Result<(SP1PublicValues, Vec<Vec<u8>>, ExecutionReport), ExecutionError> {
+        let mut runtime = Self::execute_runtime(elf, stdin, opts, |_| {})?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            std::mem::take(&mut runtime.state.output_chunks),
+            runtime.report,
+        ))
+    }
+
+    /// Executes a program like [SP1Prover::execute_with_opts], also returning the entries it
+    /// committed with `sp1_zkvm::io::commit_kv`, to be checked with
+    /// [sp1_core::io::KvCommitment::verify_entries].
+    pub fn execute_with_kv_entries(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+    ) -> Result<(SP1PublicValues, Vec<Vec<u8>>, ExecutionReport), ExecutionError> {
+        let mut runtime = Self::execute_runtime(elf, stdin, opts, |_| {})?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            std::mem::take(&mut runtime.state.kv_entries),
+            runtime.report,
+        ))
+    }
+
+    /// Executes a segment of a computation like [SP1Prover::execute_with_opts], also returning the
+    /// state it suspended with `sp1_zkvm::io::suspend_state`, if it did, for the next segment to
+    /// resume from.
+    pub fn execute_segment(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+    ) -> Result<(SP1PublicValues, Option<Vec<u8>>, ExecutionReport), ExecutionError> {
+        let mut runtime = Self::execute_runtime(elf, stdin, opts, |_| {})?;
+        Ok((
+            SP1PublicValues::from(&runtime.state.public_values_stream),
+            runtime.state.segment_state.take(),
+            runtime.report,
+        ))
+    }
+
+    fn execute_runtime<'a>(
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+        configure: impl FnOnce(&mut Runtime<'a>),
+    ) -> Result<Runtime<'a>, ExecutionError> {
+        Self::execute_program_runtime(Program::from(elf), stdin, opts, configure)
+    }
+
+    fn execute_program_runtime<'a>(
+        program: Program,
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+        configure: impl FnOnce(&mut Runtime<'a>),
+    ) -> Result<Runtime<'a>, ExecutionError> {
         let mut runtime = Runtime::new(program, opts);
+        configure(&mut runtime);
         runtime.write_vecs(&stdin.buffer);
+        runtime.write_config(&stdin.config_bytes());
+        runtime.write_entropy(&stdin.entropy);
         for (proof, vkey) in stdin.proofs.iter() {
             runtime.write_proof(proof.clone(), vkey.clone());
         }
         runtime.run_untraced()?;
-        Ok((
-            SP1PublicValues::from(&runtime.state.public_values_stream),
-            runtime.report,
-        ))
+        Ok(runtime)
+    }
+
+    /// The core options with the shard size picked by [ShardingConfig::auto] for the execution
+    /// of `elf` on `stdin`, which is executed once to measure its precompile densities.
+    #[instrument(name = "auto_sharded_opts", level = "info", skip_all)]
+    pub fn auto_sharded_opts(
+        &self,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<SP1CoreOpts, ExecutionError> {
+        self.auto_sharded_opts_for_program(Program::from(elf), stdin)
+    }
+
+    /// The core options with the shard size picked like [SP1Prover::auto_sharded_opts], for an
+    /// already decoded program such as [SP1Prover::program].
+    pub fn auto_sharded_opts_for_program(
+        &self,
+        program: Program,
+        stdin: &SP1Stdin,
+    ) -> Result<SP1CoreOpts, ExecutionError> {
+        let runtime = Self::execute_program_runtime(program, stdin, self.core_opts, |runtime| {
+            runtime.unconstrained_syscalls = self.unconstrained_syscalls.clone()
+        })?;
+        let report = runtime.report;
+        let mut opts = self.core_opts;
+        opts.shard_size = ShardingConfig::auto(&report).shard_size();
+        tracing::info!("auto sharding: shard size {}", opts.shard_size);
+        Ok(opts)
     }
 
     /// Generate shard proofs which split up and prove the valid execution of a RISC-V program with
@@ -248,20 +492,35 @@ impl SP1Prover {
         pk: &SP1ProvingKey,
         stdin: &SP1Stdin,
     ) -> Result<SP1CoreProof, SP1CoreProverError> {
-        let config = CoreSC::default();
-        let program = Program::from(&pk.elf);
-        let (proof, public_values_stream) = sp1_core::utils::prove_with_subproof_verifier(
+        self.prove_core_with_opts(pk, stdin, self.core_opts)
+    }
+
+    /// Generates shard proofs like [SP1Prover::prove_core] with the given options, such as the
+    /// ones returned by [SP1Prover::auto_sharded_opts].
+    pub fn prove_core_with_opts(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        opts: SP1CoreOpts,
+    ) -> Result<SP1CoreProof, SP1CoreProverError> {
+        // The program and its keys are reused: only the execution-dependent work is done here.
+        let program = self.program(pk).as_ref().clone();
+        let (proof, public_values_stream, insecure) = sp1_core::utils::prove_with_setup(
             program,
+            &self.core_machine,
+            &pk.pk,
+            &pk.vk.vk,
             stdin,
-            config,
-            self.core_opts,
+            opts,
             Some(Arc::new(self)),
+            &self.unconstrained_syscalls,
         )?;
         let public_values = SP1PublicValues::from(&public_values_stream);
         Ok(SP1CoreProof {
             proof: SP1CoreProofData(proof.shard_proofs),
             stdin: stdin.clone(),
             public_values,
+            insecure,
         })
     }
 
@@ -270,7 +529,7 @@ impl SP1Prover {
         vk: &'a StarkVerifyingKey<CoreSC>,
         leaf_challenger: &'a Challenger<CoreSC>,
         shard_proofs: &[ShardProof<CoreSC>],
-        batch_size: usize,
+        batches: &[Range<usize>],
         is_complete: bool,
     ) -> Vec<SP1RecursionMemoryLayout<'a, CoreSC, RiscvAir<BabyBear>>> {
         let mut core_inputs = Vec::new();
@@ -278,7 +537,8 @@ impl SP1Prover {
         vk.observe_into(&mut reconstruct_challenger);
 
         // Prepare the inputs for the recursion programs.
-        for batch in shard_proofs.chunks(batch_size) {
+        for batch in batches {
+            let batch = &shard_proofs[batch.clone()];
             let proofs = batch.to_vec();
 
             core_inputs.push(SP1RecursionMemoryLayout {
@@ -320,14 +580,15 @@ impl SP1Prover {
         leaf_challenger: &'a Challenger<InnerSC>,
         last_proof_pv: &PublicValues<Word<BabyBear>, BabyBear>,
         deferred_proofs: &[ShardProof<InnerSC>],
-        batch_size: usize,
+        batches: &[Range<usize>],
         total_core_shards: usize,
     ) -> Vec<SP1DeferredMemoryLayout<'a, InnerSC, RecursionAir<BabyBear, 3>>> {
         // Prepare the inputs for the deferred proofs recursive verification.
         let mut deferred_digest = [Val::<InnerSC>::zero(); DIGEST_SIZE];
         let mut deferred_inputs = Vec::new();
 
-        for batch in deferred_proofs.chunks(batch_size) {
+        for batch in batches {
+            let batch = &deferred_proofs[batch.clone()];
             let proofs = batch.to_vec();
 
             deferred_inputs.push(SP1DeferredMemoryLayout {
@@ -342,8 +603,11 @@ impl SP1Prover {
                 end_shard: last_proof_pv.shard + BabyBear::one(),
                 leaf_challenger: leaf_challenger.clone(),
                 committed_value_digest: last_proof_pv.committed_value_digest.to_vec(),
+                input_digest: last_proof_pv.input_digest.to_vec(),
+                config_digest: last_proof_pv.config_digest.to_vec(),
                 deferred_proofs_digest: last_proof_pv.deferred_proofs_digest.to_vec(),
                 total_core_shards,
+                features: last_proof_pv.features,
             });
 
             deferred_digest = Self::hash_deferred_proofs(deferred_digest, batch);
@@ -359,7 +623,7 @@ impl SP1Prover {
         leaf_challenger: &'a Challenger<InnerSC>,
         shard_proofs: &[ShardProof<InnerSC>],
         deferred_proofs: &[ShardProof<InnerSC>],
-        batch_size: usize,
+        plan: &ReducePlan,
     ) -> (
         Vec<SP1RecursionMemoryLayout<'a, InnerSC, RiscvAir<BabyBear>>>,
         Vec<SP1DeferredMemoryLayout<'a, InnerSC, RecursionAir<BabyBear, 3>>>,
@@ -369,7 +633,7 @@ impl SP1Prover {
             &vk.vk,
             leaf_challenger,
             shard_proofs,
-            batch_size,
+            &plan.core_batches().collect::<Vec<_>>(),
             is_complete,
         );
         let last_proof_pv =
@@ -379,121 +643,125 @@ impl SP1Prover {
             leaf_challenger,
             &last_proof_pv,
             deferred_proofs,
-            batch_size,
+            &plan.deferred_batches().collect::<Vec<_>>(),
             shard_proofs.len(),
         );
         (core_inputs, deferred_inputs)
     }
 
     /// Reduce shards proofs to a single shard proof using the recursion prover.
-    #[instrument(name = "compress", level = "info", skip_all)]
     pub fn compress(
         &self,
         vk: &SP1VerifyingKey,
         proof: SP1CoreProof,
         deferred_proofs: Vec<ShardProof<InnerSC>>,
     ) -> Result<SP1ReduceProof<InnerSC>, SP1RecursionProverError> {
-        // Set the batch size for the reduction tree.
-        let batch_size = 2;
+        self.compress_with_plan(vk, proof, deferred_proofs, None)
+            .map(|(proof, _)| proof)
+    }
 
+    /// Reduce shards proofs to a single shard proof following `plan`, or the default
+    /// [ReducePlan] for the number of proofs if `plan` is `None`.
+    ///
+    /// Returns the reduced proof along with the plan it was built with. The digest of the plan is
+    /// committed to in the public values of the proof, see [SP1Prover::verify_reduce_plan].
+    pub fn compress_with_plan(
+        &self,
+        vk: &SP1VerifyingKey,
+        proof: SP1CoreProof,
+        deferred_proofs: Vec<ShardProof<InnerSC>>,
+        plan: Option<ReducePlan>,
+    ) -> Result<(SP1ReduceProof<InnerSC>, ReducePlan), SP1RecursionProverError> {
+        self.compress_with_report(vk, proof, deferred_proofs, plan)
+            .map(|(proof, plan, _)| (proof, plan))
+    }
+
+    /// Like [SP1Prover::compress_with_plan], also returning the time spent generating the witness
+    /// of every node of the plan, in plan order.
+    #[instrument(name = "compress", level = "info", skip_all)]
+    pub fn compress_with_report(
+        &self,
+        vk: &SP1VerifyingKey,
+        proof: SP1CoreProof,
+        deferred_proofs: Vec<ShardProof<InnerSC>>,
+        plan: Option<ReducePlan>,
+    ) -> Result<(SP1ReduceProof<InnerSC>, ReducePlan, Vec<NodeReport>), SP1RecursionProverError>
+    {
+        // A core machine of another shape fails on a constraint of the recursion instead.
+        SP1RecursionProverError::check_shape(
+            &vk.vk.fingerprint,
+            self.recursion_program.verified_fingerprint.as_ref(),
+        )?;
         let shard_proofs = &proof.proof.0;
+        let plan = plan
+            .unwrap_or_else(|| ReducePlan::default_for(shard_proofs.len(), deferred_proofs.len()));
+        plan.validate(shard_proofs.len(), deferred_proofs.len())?;
         let total_core_shards = shard_proofs.len();
-        // Get the leaf challenger.
-        let mut leaf_challenger = self.core_machine.config().challenger();
-        vk.vk.observe_into(&mut leaf_challenger);
-        shard_proofs.iter().for_each(|proof| {
-            leaf_challenger.observe(proof.commitment.main_commit);
-            leaf_challenger.observe_slice(&proof.public_values[0..self.core_machine.num_pv_elts()]);
-        });
-
-        // Run the recursion and reduce programs.
-        let (core_inputs, deferred_inputs) = self.get_first_layer_inputs(
-            vk,
-            &leaf_challenger,
-            shard_proofs,
-            &deferred_proofs,
-            batch_size,
-        );
-
-        let mut reduce_proofs = Vec::new();
-        let opts = self.recursion_opts;
-        let shard_batch_size = opts.shard_batch_size;
-        for inputs in core_inputs.chunks(shard_batch_size) {
-            let proofs = inputs
-                .into_par_iter()
-                .map(|input| {
-                    let proof =
-                        self.compress_machine_proof(input, &self.recursion_program, &self.rec_pk);
-                    (proof, ReduceProgramType::Core)
-                })
-                .collect::<Vec<_>>();
-            reduce_proofs.extend(proofs);
-        }
+        let budget = self.recursion_opts.memory_budgets.compress;
+        memory::reset_peak();
 
-        // Run the deferred proofs programs.
-        for inputs in deferred_inputs.chunks(shard_batch_size) {
-            let proofs = inputs
-                .into_par_iter()
-                .map(|input| {
-                    let proof = self.compress_machine_proof(
-                        input,
-                        &self.deferred_program,
-                        &self.deferred_pk,
-                    );
-                    (proof, ReduceProgramType::Deferred)
-                })
-                .collect::<Vec<_>>();
-            reduce_proofs.extend(proofs);
-        }
+        // Run the recursion and deferred proofs programs.
+        let leaf_jobs = self.first_layer_jobs(vk, shard_proofs, &deferred_proofs, &plan);
+        let mut node_reports = Vec::new();
+        let mut reduce_proofs = self.prove_reduce_jobs(&leaf_jobs, &mut node_reports)?;
+        SP1RecursionProverError::check_memory_budget("compress", budget)?;
 
-        // Iterate over the recursive proof batches until there is one proof remaining.
-        let mut is_complete;
-        loop {
+        // Reduce the proofs of each layer of the plan until there is one proof remaining.
+        for (layer, nodes) in plan.reduce_layers().iter().enumerate() {
             tracing::debug!("Recursive proof layer size: {}", reduce_proofs.len());
-            is_complete = reduce_proofs.len() <= batch_size;
-
-            let compress_inputs = reduce_proofs.chunks(batch_size).collect::<Vec<_>>();
-            let batched_compress_inputs =
-                compress_inputs.chunks(shard_batch_size).collect::<Vec<_>>();
-            reduce_proofs = batched_compress_inputs
-                .into_iter()
-                .flat_map(|batches| {
-                    batches
-                        .par_iter()
-                        .map(|batch| {
-                            let (shard_proofs, kinds) =
-                                batch.iter().cloned().unzip::<_, _, Vec<_>, Vec<_>>();
-
-                            let input = SP1ReduceMemoryLayout {
-                                compress_vk: &self.compress_vk,
-                                recursive_machine: &self.compress_machine,
-                                shard_proofs,
-                                kinds,
-                                is_complete,
-                                total_core_shards,
-                            };
-
-                            let proof = self.compress_machine_proof(
-                                input,
-                                &self.compress_program,
-                                &self.compress_pk,
-                            );
-                            (proof, ReduceProgramType::Reduce)
-                        })
-                        .collect::<Vec<_>>()
+            let is_complete = nodes.len() == 1;
+
+            let jobs = nodes
+                .par_iter()
+                .enumerate()
+                .map(|(index, node)| {
+                    let (shard_proofs, kinds) = reduce_proofs[node.inputs.clone()]
+                        .iter()
+                        .cloned()
+                        .unzip::<_, _, Vec<_>, Vec<_>>();
+                    let input = SP1ReduceMemoryLayout {
+                        compress_vk: &self.compress_vk,
+                        recursive_machine: &self.compress_machine,
+                        shard_proofs,
+                        kinds,
+                        is_complete,
+                        total_core_shards,
+                    };
+                    self.reduce_job(layer + 1, index, ReduceProgramType::Reduce, input)
                 })
                 .collect::<Vec<_>>();
-
-            if reduce_proofs.len() == 1 {
-                break;
-            }
+            reduce_proofs = self.prove_reduce_jobs(&jobs, &mut node_reports)?;
+            SP1RecursionProverError::check_memory_budget("compress", budget)?;
         }
         debug_assert_eq!(reduce_proofs.len(), 1);
         let reduce_proof = reduce_proofs.pop().unwrap();
 
-        Ok(SP1ReduceProof {
-            proof: reduce_proof.0,
-        })
+        Ok((
+            SP1ReduceProof {
+                proof: reduce_proof.0,
+            },
+            plan,
+            node_reports,
+        ))
+    }
+
+    /// Generates the witnesses of the nodes of a layer and proves them, in batches of
+    /// `shard_batch_size` nodes.
+    fn prove_reduce_jobs(
+        &self,
+        jobs: &[ReduceJob],
+        node_reports: &mut Vec<NodeReport>,
+    ) -> Result<Vec<(ShardProof<InnerSC>, ReduceProgramType)>, SP1RecursionProverError> {
+        let mut proofs = Vec::with_capacity(jobs.len());
+        for batch in jobs.chunks(self.recursion_opts.shard_batch_size) {
+            let (outputs, reports) = self.run_reduce_jobs(batch)?;
+            node_reports.extend(reports);
+            proofs.par_extend(outputs.into_par_iter().map(|output| {
+                let kind = output.kind;
+                (self.prove_reduce_node(output), kind)
+            }));
+        }
+        Ok(proofs)
     }
 
     pub fn compress_machine_proof(
@@ -529,6 +797,9 @@ impl SP1Prover {
         &self,
         reduced_proof: SP1ReduceProof<InnerSC>,
     ) -> Result<SP1ReduceProof<InnerSC>, SP1RecursionProverError> {
+        let budget = self.recursion_opts.memory_budgets.shrink;
+        memory::reset_peak();
+
         // Make the compress proof.
         let input = SP1RootMemoryLayout {
             machine: &self.compress_machine,
@@ -549,6 +820,7 @@ impl SP1Prover {
         runtime.run();
         runtime.print_stats();
         tracing::debug!("Compress program executed successfully");
+        SP1RecursionProverError::check_memory_budget("shrink", budget)?;
 
         // Prove the compress program.
         let opts = self.recursion_opts;
@@ -559,6 +831,7 @@ impl SP1Prover {
             &mut compress_challenger,
             opts,
         );
+        SP1RecursionProverError::check_memory_budget("shrink", budget)?;
 
         Ok(SP1ReduceProof {
             proof: compress_proof.shard_proofs.pop().unwrap(),
@@ -571,6 +844,9 @@ impl SP1Prover {
         &self,
         compressed_proof: SP1ReduceProof<InnerSC>,
     ) -> Result<SP1ReduceProof<OuterSC>, SP1RecursionProverError> {
+        let budget = self.recursion_opts.memory_budgets.wrap;
+        memory::reset_peak();
+
         let input = SP1RootMemoryLayout {
             machine: &self.shrink_machine,
             proof: compressed_proof.proof,
@@ -590,6 +866,7 @@ impl SP1Prover {
         runtime.run();
         runtime.print_stats();
         tracing::debug!("Wrap program executed successfully");
+        SP1RecursionProverError::check_memory_budget("wrap", budget)?;
 
         // Prove the wrap program.
         let opts = self.recursion_opts;
@@ -603,6 +880,7 @@ impl SP1Prover {
         );
         let elapsed = time.elapsed();
         tracing::debug!("Wrap proving time: {:?}", elapsed);
+        SP1RecursionProverError::check_memory_budget("wrap", budget)?;
         let mut wrap_challenger = self.wrap_machine.config().challenger();
         let result = self
             .wrap_machine
@@ -627,14 +905,43 @@ impl SP1Prover {
         &self,
         proof: SP1ReduceProof<OuterSC>,
         build_dir: &Path,
+    ) -> PlonkBn254Proof {
+        self.wrap_plonk_bn254_inner(proof, build_dir, None)
+    }
+
+    /// Wrap the STARK proven over a SNARK-friendly field into a PLONK proof with a circuit also
+    /// verifying the signature of the operator over the committed values digest, see
+    /// [build::build_plonk_bn254_artifacts_with_operator].
+    #[instrument(name = "wrap_plonk_bn254", level = "info", skip_all)]
+    pub fn wrap_plonk_bn254_with_signature(
+        &self,
+        proof: SP1ReduceProof<OuterSC>,
+        build_dir: &Path,
+        operator_key: &Secp256k1Point,
+        signature: &EcdsaSignature,
+    ) -> PlonkBn254Proof {
+        self.wrap_plonk_bn254_inner(proof, build_dir, Some((operator_key, signature)))
+    }
+
+    fn wrap_plonk_bn254_inner(
+        &self,
+        proof: SP1ReduceProof<OuterSC>,
+        build_dir: &Path,
+        operator: Option<(&Secp256k1Point, &EcdsaSignature)>,
     ) -> PlonkBn254Proof {
         let vkey_digest = proof.sp1_vkey_digest_bn254();
         let commited_values_digest = proof.sp1_commited_values_digest_bn254();
+        let input_digest = proof.sp1_input_digest_bn254();
 
         let mut witness = Witness::default();
         proof.proof.write(&mut witness);
         witness.write_commited_values_digest(commited_values_digest);
         witness.write_vkey_hash(vkey_digest);
+        witness.write_input_digest(input_digest);
+        if let Some((operator_key, signature)) = operator {
+            let digest = proof.sp1_commited_values_digest_bytes();
+            write_secp256k1_ecdsa_witness(&mut witness, operator_key, &digest, signature);
+        }
 
         let prover = PlonkBn254Prover::new();
         let proof = prover.prove(witness, build_dir.to_path_buf());
@@ -644,6 +951,7 @@ impl SP1Prover {
             &proof,
             &vkey_digest.as_canonical_biguint(),
             &commited_values_digest.as_canonical_biguint(),
+            &input_digest.as_canonical_biguint(),
             build_dir,
         );
 
@@ -674,14 +982,18 @@ mod tests {
 
     use std::fs::File;
     use std::io::{Read, Write};
+    use std::str::FromStr;
 
     use super::*;
 
     use anyhow::Result;
     use build::try_build_plonk_bn254_artifacts_dev;
+    use num_bigint::BigUint;
     use p3_field::PrimeField32;
     use serial_test::serial;
+    use sp1_core::air::{MachineAir, SP1_PROOF_NUM_PV_ELTS};
     use sp1_core::io::SP1Stdin;
+    use sp1_core::stark::Chip;
     use sp1_core::utils::setup_logger;
 
     /// Tests an end-to-end workflow of proving a program across the entire proof generation
@@ -707,15 +1019,30 @@ mod tests {
         let stdin = SP1Stdin::new();
         let core_proof = prover.prove_core(&pk, &stdin)?;
         let public_values = core_proof.public_values.clone();
+        let last_shard_pv = core_proof.proof.0.last().unwrap().public_values.clone();
+        let last_shard_pv = PublicValues::from_vec(last_shard_pv);
+        let input_digest = last_shard_pv.input_digest_bytes();
+        let config_digest = last_shard_pv.config_digest_bytes();
 
         tracing::info!("verify core");
         prover.verify(&core_proof.proof, &vk)?;
 
         tracing::info!("compress");
-        let compressed_proof = prover.compress(&vk, core_proof, vec![])?;
+        let num_shards = core_proof.proof.0.len();
+        let (compressed_proof, plan) = prover.compress_with_plan(&vk, core_proof, vec![], None)?;
+        assert_eq!(plan, ReducePlan::default_for(num_shards, 0));
 
         tracing::info!("verify compressed");
         prover.verify_compressed(&compressed_proof, &vk)?;
+        prover.verify_reduce_plan(&compressed_proof, &plan)?;
+        assert_eq!(
+            compressed_proof.sp1_input_digest_bytes().to_vec(),
+            input_digest
+        );
+        assert_eq!(
+            compressed_proof.sp1_config_digest_bytes().to_vec(),
+            config_digest
+        );
 
         tracing::info!("shrink");
         let shrink_proof = prover.shrink(compressed_proof)?;
@@ -739,7 +1066,19 @@ mod tests {
         let wrapped_bn254_proof = bincode::deserialize(&bytes).unwrap();
 
         tracing::info!("verify wrap bn254");
-        prover.verify_wrap_bn254(&wrapped_bn254_proof, &vk).unwrap();
+        prover
+            .verify_wrap_bn254_with_plan(&wrapped_bn254_proof, &vk, &plan)
+            .unwrap();
+
+        tracing::info!("checking reduce plan");
+        let mut wrong_plan = plan.clone();
+        wrong_plan.layers.push(vec![ReduceNode {
+            kind: ReduceProgramType::Reduce,
+            inputs: 0..1,
+        }]);
+        assert!(prover
+            .verify_wrap_bn254_with_plan(&wrapped_bn254_proof, &vk, &wrong_plan)
+            .is_err());
 
         tracing::info!("checking vkey hash babybear");
         let vk_digest_babybear = wrapped_bn254_proof.sp1_vkey_digest_babybear();
@@ -749,6 +1088,17 @@ mod tests {
         let vk_digest_bn254 = wrapped_bn254_proof.sp1_vkey_digest_bn254();
         assert_eq!(vk_digest_bn254, vk.hash_bn254());
 
+        tracing::info!("checking input and config digests");
+        assert_eq!(
+            wrapped_bn254_proof.sp1_input_digest_bytes().to_vec(),
+            input_digest
+        );
+        assert_eq!(
+            wrapped_bn254_proof.sp1_config_digest_bytes().to_vec(),
+            config_digest
+        );
+        let wrapped_input_digest = wrapped_bn254_proof.sp1_input_digest_bn254();
+
         tracing::info!("generate plonk bn254 proof");
         let artifacts_dir =
             try_build_plonk_bn254_artifacts_dev(&prover.wrap_vk, &wrapped_bn254_proof.proof);
@@ -757,6 +1107,226 @@ mod tests {
 
         prover.verify_plonk_bn254(&plonk_bn254_proof, &vk, &public_values, &artifacts_dir)?;
 
+        tracing::info!("batch verify plonk bn254 proofs");
+        let entry = (&plonk_bn254_proof, &vk, &public_values);
+        prover.verify_plonk_bn254_batch(&[entry, entry, entry], &artifacts_dir)?;
+
+        // A proof checked against the wrong committed values digest is named in the error.
+        let vkey_hash = BigUint::from_str(&plonk_bn254_proof.public_inputs[0])?;
+        let digest = BigUint::from_str(&plonk_bn254_proof.public_inputs[1])?;
+        let input_digest = BigUint::from_str(&plonk_bn254_proof.public_inputs[2])?;
+        assert_eq!(
+            input_digest,
+            wrapped_input_digest.as_canonical_biguint(),
+            "the wrapper exposes the input digest of the program"
+        );
+        let batch = [
+            (
+                plonk_bn254_proof.clone(),
+                vkey_hash.clone(),
+                digest.clone(),
+                input_digest.clone(),
+            ),
+            (
+                plonk_bn254_proof.clone(),
+                vkey_hash.clone(),
+                digest.clone() + 1u32,
+                input_digest.clone(),
+            ),
+        ];
+        let err = PlonkBn254Prover::new()
+            .batch_verify(&batch, &artifacts_dir)
+            .unwrap_err();
+        assert_eq!(err.index, 1);
+
+        // So is a proof checked against the wrong input digest.
+        let batch = [(
+            plonk_bn254_proof.clone(),
+            vkey_hash,
+            digest,
+            input_digest + 1u32,
+        )];
+        let err = PlonkBn254Prover::new()
+            .batch_verify(&batch, &artifacts_dir)
+            .unwrap_err();
+        assert_eq!(err.index, 0);
+
+        Ok(())
+    }
+
+    /// Tests compressing shard proofs with an explicit reduce plan of a different shape than the
+    /// default one.
+    #[test]
+    #[serial]
+    fn test_compress_with_plan() -> Result<()> {
+        setup_logger();
+        let elf = include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+
+        let mut prover = SP1Prover::new();
+        prover.core_opts.shard_size = 1 << 12;
+        let (pk, vk) = prover.setup(elf);
+        let core_proof = prover.prove_core(&pk, &SP1Stdin::new())?;
+        let num_shards = core_proof.proof.0.len();
+
+        // Verify all the shards in a single leaf, reduced by a single node.
+        let plan = ReducePlan {
+            layers: vec![
+                vec![ReduceNode {
+                    kind: ReduceProgramType::Core,
+                    inputs: 0..num_shards,
+                }],
+                vec![ReduceNode {
+                    kind: ReduceProgramType::Reduce,
+                    inputs: 0..1,
+                }],
+            ],
+        };
+        let (compressed_proof, _) =
+            prover.compress_with_plan(&vk, core_proof.clone(), vec![], Some(plan.clone()))?;
+        prover.verify_compressed(&compressed_proof, &vk)?;
+        prover.verify_reduce_plan(&compressed_proof, &plan)?;
+
+        let default_plan = ReducePlan::default_for(num_shards, 0);
+        assert!(prover
+            .verify_reduce_plan(&compressed_proof, &default_plan)
+            .is_err());
+
+        // A plan that skips a shard is rejected before proving.
+        let mut invalid_plan = plan;
+        invalid_plan.layers[0][0].inputs = 1..num_shards;
+        assert!(prover
+            .compress_with_plan(&vk, core_proof, vec![], Some(invalid_plan))
+            .is_err());
+
+        Ok(())
+    }
+
+    /// Tests that compressing the proof of a core machine of another shape than the one the
+    /// recursion program is built for fails before proving, naming the chip.
+    #[test]
+    #[serial]
+    fn test_compress_shape_mismatch() -> Result<()> {
+        setup_logger();
+        let elf = include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+
+        let mut prover = SP1Prover::new();
+        prover.core_opts.shard_size = 1 << 12;
+        let expected = SP1Prover::core_shape_digest();
+        assert_eq!(
+            prover.recursion_program.verified_fingerprint,
+            Some(prover.core_machine.fingerprint())
+        );
+
+        // A core machine with the keccak chip disabled.
+        let chips = RiscvAir::get_all()
+            .into_iter()
+            .filter(|air| air.name() != "KeccakPermute")
+            .map(Chip::new)
+            .collect::<Vec<_>>();
+        prover.core_machine = StarkMachine::new(CoreSC::default(), chips, SP1_PROOF_NUM_PV_ELTS);
+        let (pk, vk) = prover.setup(elf);
+        assert_ne!(vk.vk.fingerprint.digest(), expected);
+
+        let core_proof = prover.prove_core(&pk, &SP1Stdin::new())?;
+        match prover.compress(&vk, core_proof, vec![]) {
+            Err(SP1RecursionProverError::ShapeMismatch {
+                core_shape,
+                verifier_expected,
+                chip,
+            }) => {
+                assert_eq!(core_shape, vk.vk.fingerprint.digest());
+                assert_eq!(verifier_expected, expected);
+                assert_eq!(chip, "KeccakPermute");
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("compressed the proof of a machine of another shape"),
+        }
+
+        Ok(())
+    }
+
+    /// Tests that with deterministic proving, proving the same program on the same input twice
+    /// gives the same core, compressed and wrapped proofs, byte for byte.
+    #[test]
+    #[serial]
+    fn test_deterministic_proofs() -> Result<()> {
+        setup_logger();
+        let elf = include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+
+        let mut prover = SP1Prover::new();
+        prover.core_opts.shard_size = 1 << 12;
+        prover.core_opts.deterministic = true;
+        let (pk, vk) = prover.setup(elf);
+
+        let prove = || -> Result<[Vec<u8>; 3]> {
+            let core_proof = prover.prove_core(&pk, &SP1Stdin::new())?;
+            let core_bytes = bincode::serialize(&core_proof)?;
+            let compressed_proof = prover.compress(&vk, core_proof, vec![])?;
+            let compressed_bytes = bincode::serialize(&compressed_proof)?;
+            let shrink_proof = prover.shrink(compressed_proof)?;
+            let wrapped_proof = prover.wrap_bn254(shrink_proof)?;
+            let wrapped_bytes = bincode::serialize(&wrapped_proof)?;
+            Ok([core_bytes, compressed_bytes, wrapped_bytes])
+        };
+
+        let [core, compressed, wrapped] = prove()?;
+        let [core_again, compressed_again, wrapped_again] = prove()?;
+        assert!(core == core_again, "the core proofs differ");
+        assert!(
+            compressed == compressed_again,
+            "the compressed proofs differ"
+        );
+        assert!(wrapped == wrapped_again, "the wrapped proofs differ");
+
+        Ok(())
+    }
+
+    /// Tests compressing, shrinking and wrapping at a number of shards for which a fixed arity of
+    /// two needs an extra layer, so that the default plan has reduce nodes of arity three.
+    #[test]
+    #[serial]
+    fn test_compress_adaptive_arity() -> Result<()> {
+        setup_logger();
+        let elf = include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+        let (_, report) = SP1Prover::execute(elf, &SP1Stdin::new())?;
+        let cycles = report.total_instruction_count() as usize;
+
+        // The largest shard size whose default plan is smaller than the fixed arity one.
+        let shard_size = (10..20)
+            .map(|log_size| 1 << log_size)
+            .rev()
+            .find(|shard_size| {
+                let num_shards = cycles.div_ceil(*shard_size);
+                let num_nodes = |plan: &ReducePlan| plan.layers.iter().map(Vec::len).sum::<usize>();
+                num_nodes(&ReducePlan::default_for(num_shards, 0))
+                    < num_nodes(&ReducePlan::new(num_shards, 0, REDUCE_BATCH_SIZE))
+            })
+            .expect("no shard size needs an extra layer");
+
+        let mut prover = SP1Prover::new();
+        prover.core_opts.shard_size = shard_size;
+        let (pk, vk) = prover.setup(elf);
+        let core_proof = prover.prove_core(&pk, &SP1Stdin::new())?;
+        let num_shards = core_proof.proof.0.len();
+
+        let (compressed_proof, plan) = prover.compress_with_plan(&vk, core_proof, vec![], None)?;
+        assert_eq!(plan, ReducePlan::default_for(num_shards, 0));
+        assert!(plan
+            .reduce_layers()
+            .iter()
+            .flatten()
+            .any(|node| node.arity() == MAX_REDUCE_ARITY));
+        prover.verify_compressed(&compressed_proof, &vk)?;
+        prover.verify_reduce_plan(&compressed_proof, &plan)?;
+
+        let shrink_proof = prover.shrink(compressed_proof)?;
+        prover.verify_shrink(&shrink_proof, &vk)?;
+
+        let wrapped_bn254_proof = prover.wrap_bn254(shrink_proof)?;
+        prover
+            .verify_wrap_bn254_with_plan(&wrapped_bn254_proof, &vk, &plan)
+            .unwrap();
+
         Ok(())
     }
 
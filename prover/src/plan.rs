@@ -0,0 +1,523 @@
+//! The topology of the reduce tree built by [crate::SP1Prover::compress].
+//!
+//! A [ReducePlan] lists the layers of the tree, from the leaves verifying core shard proofs and
+//! deferred proofs to the single root reduce node. The recursion programs commit to a digest of
+//! the subtree below every proof in its public values, so the digest of the plan can be checked
+//! against the public values of the compressed, shrunk and wrapped proofs.
+
+use std::ops::Range;
+
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use serde::{Deserialize, Serialize};
+use sp1_core::utils::DIGEST_SIZE;
+use sp1_primitives::poseidon2_hash;
+use thiserror::Error;
+
+use crate::{ReduceProgramType, MAX_REDUCE_ARITY, REDUCE_BATCH_SIZE};
+
+/// A node of a [ReducePlan].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReduceNode {
+    pub kind: ReduceProgramType,
+    /// The inputs verified by the node: core shard proofs for a [ReduceProgramType::Core] leaf,
+    /// deferred proofs for a [ReduceProgramType::Deferred] leaf, and nodes of the previous layer
+    /// for a [ReduceProgramType::Reduce] node.
+    pub inputs: Range<usize>,
+}
+
+impl ReduceNode {
+    /// The number of inputs verified by the node.
+    pub fn arity(&self) -> usize {
+        self.inputs.len()
+    }
+}
+
+/// The layers of a reduce tree, starting with the leaves and ending with the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReducePlan {
+    pub layers: Vec<Vec<ReduceNode>>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ReducePlanError {
+    #[error("the plan has no reduce layer")]
+    MissingReduceLayer,
+    #[error("the last layer has {0} nodes instead of one")]
+    MissingRoot(usize),
+    #[error("node {node} of layer {layer} has no inputs")]
+    EmptyNode { layer: usize, node: usize },
+    #[error("node {node} of layer {layer} has an unexpected kind {kind:?}")]
+    UnexpectedKind {
+        layer: usize,
+        node: usize,
+        kind: ReduceProgramType,
+    },
+    #[error("the {kind:?} nodes of layer {layer} do not cover their {expected} inputs in order")]
+    InvalidCoverage {
+        layer: usize,
+        kind: ReduceProgramType,
+        expected: usize,
+    },
+}
+
+/// The approximate cost of proving a reduce node verifying `arity` proofs, in units of the cost
+/// of verifying one proof. On top of its inputs, every node pays about one verification worth of
+/// fixed work for its challenger, its public values and the digest of its subtree.
+pub fn reduce_node_cost(arity: usize) -> u64 {
+    arity as u64 + 1
+}
+
+impl ReducePlan {
+    /// A plan with fixed arity for `num_shards` core shard proofs and `num_deferred` deferred
+    /// proofs.
+    ///
+    /// The leaves verify consecutive batches of `batch_size` core shard proofs followed by batches
+    /// of deferred proofs, and every reduce layer verifies consecutive batches of `batch_size`
+    /// nodes of the previous layer until a single node remains.
+    pub fn new(num_shards: usize, num_deferred: usize, batch_size: usize) -> Self {
+        assert!(batch_size > 1, "the batch size must be at least 2");
+        let mut layers = vec![Self::leaf_layer(num_shards, num_deferred, batch_size)];
+        loop {
+            let layer = Self::batches(layers.last().unwrap().len(), batch_size)
+                .map(|inputs| ReduceNode {
+                    kind: ReduceProgramType::Reduce,
+                    inputs,
+                })
+                .collect::<Vec<_>>();
+            let is_root = layer.len() == 1;
+            layers.push(layer);
+            if is_root {
+                break;
+            }
+        }
+        Self { layers }
+    }
+
+    /// The plan used by [crate::SP1Prover::compress] for `num_shards` core shard proofs and
+    /// `num_deferred` deferred proofs: the leaves verify batches of [REDUCE_BATCH_SIZE] proofs
+    /// and the reduce layers are chosen by [ReducePlan::optimal] with nodes of arity up to
+    /// [MAX_REDUCE_ARITY].
+    pub fn default_for(num_shards: usize, num_deferred: usize) -> Self {
+        Self::optimal(
+            num_shards,
+            num_deferred,
+            REDUCE_BATCH_SIZE,
+            MAX_REDUCE_ARITY,
+            reduce_node_cost,
+        )
+    }
+
+    /// The plan with the same leaves as [ReducePlan::new] whose reduce layers minimize the total
+    /// cost of their nodes, where `cost` gives the cost of a node of each arity.
+    ///
+    /// The arity is chosen per node between 2 and `max_arity`, so that a number of leaves just
+    /// over a power of the arity does not need a whole extra layer of mostly-empty nodes.
+    pub fn optimal(
+        num_shards: usize,
+        num_deferred: usize,
+        batch_size: usize,
+        max_arity: usize,
+        cost: impl Fn(usize) -> u64,
+    ) -> Self {
+        assert!(batch_size > 1, "the batch size must be at least 2");
+        assert!(max_arity > 1, "the maximum arity must be at least 2");
+        let leaves = Self::leaf_layer(num_shards, num_deferred, batch_size);
+        let next_layer_len = Self::optimal_layer_lens(leaves.len(), max_arity, &cost);
+
+        let mut layers = vec![leaves];
+        loop {
+            let len = layers.last().unwrap().len();
+            let arities = if len <= 1 {
+                vec![len]
+            } else {
+                Self::optimal_split(len, next_layer_len[len], max_arity, &cost)
+            };
+            let mut start = 0;
+            let layer = arities
+                .into_iter()
+                .map(|arity| {
+                    start += arity;
+                    ReduceNode {
+                        kind: ReduceProgramType::Reduce,
+                        inputs: start - arity..start,
+                    }
+                })
+                .collect::<Vec<_>>();
+            let is_root = layer.len() == 1;
+            layers.push(layer);
+            if is_root {
+                break;
+            }
+        }
+        Self { layers }
+    }
+
+    /// For every number of nodes `n` up to `max_len`, the length of the layer following them in
+    /// the cheapest tree reducing `n` nodes to one.
+    ///
+    /// The cheapest tree over `n > 1` nodes splits them into `m` nodes of arity between 2 and
+    /// `max_arity`, followed by the cheapest tree over `m` nodes. The splits into `m` nodes are
+    /// computed for increasing `m`, which only needs the cost of trees over fewer nodes.
+    fn optimal_layer_lens(
+        max_len: usize,
+        max_arity: usize,
+        cost: impl Fn(usize) -> u64,
+    ) -> Vec<usize> {
+        let mut tree_cost = vec![u64::MAX; max_len + 1];
+        let mut next_layer_len = vec![0; max_len + 1];
+        if let Some(single) = tree_cost.get_mut(1) {
+            *single = 0;
+        }
+
+        // The cheapest split of each number of nodes into `m` nodes, `u64::MAX` if there is none.
+        let mut split_cost = vec![u64::MAX; max_len + 1];
+        split_cost[0] = 0;
+        for m in 1..=max_len / 2 {
+            split_cost = (0..=max_len)
+                .map(|n| {
+                    (2..=max_arity.min(n))
+                        .filter(|arity| split_cost[n - arity] != u64::MAX)
+                        .map(|arity| split_cost[n - arity] + cost(arity))
+                        .min()
+                        .unwrap_or(u64::MAX)
+                })
+                .collect();
+            for n in 2 * m..=max_len {
+                let total = split_cost[n].saturating_add(tree_cost[m]);
+                if total < tree_cost[n] {
+                    tree_cost[n] = total;
+                    next_layer_len[n] = m;
+                }
+            }
+        }
+        next_layer_len
+    }
+
+    /// The arities of the cheapest split of `len` nodes into `num_nodes` nodes, largest first.
+    fn optimal_split(
+        len: usize,
+        num_nodes: usize,
+        max_arity: usize,
+        cost: impl Fn(usize) -> u64,
+    ) -> Vec<usize> {
+        // `splits[m][n]` is the cheapest split of `n` nodes into `m` nodes and its last arity.
+        let mut splits = vec![vec![(u64::MAX, 0); len + 1]; num_nodes + 1];
+        splits[0][0] = (0, 0);
+        for m in 1..=num_nodes {
+            for n in 0..=len {
+                splits[m][n] = (2..=max_arity.min(n))
+                    .filter(|arity| splits[m - 1][n - arity].0 != u64::MAX)
+                    .map(|arity| (splits[m - 1][n - arity].0 + cost(arity), arity))
+                    .min()
+                    .unwrap_or((u64::MAX, 0));
+            }
+        }
+
+        let mut arities = Vec::with_capacity(num_nodes);
+        let mut n = len;
+        for m in (1..=num_nodes).rev() {
+            let arity = splits[m][n].1;
+            arities.push(arity);
+            n -= arity;
+        }
+        arities.sort_unstable_by(|a, b| b.cmp(a));
+        arities
+    }
+
+    /// The leaves verifying consecutive batches of `batch_size` core shard proofs followed by
+    /// batches of deferred proofs.
+    fn leaf_layer(num_shards: usize, num_deferred: usize, batch_size: usize) -> Vec<ReduceNode> {
+        let mut leaves = Self::batches(num_shards, batch_size)
+            .map(|inputs| ReduceNode {
+                kind: ReduceProgramType::Core,
+                inputs,
+            })
+            .collect::<Vec<_>>();
+        leaves.extend(
+            Self::batches(num_deferred, batch_size).map(|inputs| ReduceNode {
+                kind: ReduceProgramType::Deferred,
+                inputs,
+            }),
+        );
+        leaves
+    }
+
+    /// Consecutive ranges of at most `batch_size` elements covering `0..len`.
+    fn batches(len: usize, batch_size: usize) -> impl Iterator<Item = Range<usize>> {
+        (0..len)
+            .step_by(batch_size)
+            .map(move |start| start..(start + batch_size).min(len))
+    }
+
+    /// The leaves of the plan.
+    pub fn leaves(&self) -> &[ReduceNode] {
+        self.layers.first().map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The ranges of core shard proofs verified by the leaves, in order.
+    pub fn core_batches(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.leaves_of_kind(ReduceProgramType::Core)
+    }
+
+    /// The ranges of deferred proofs verified by the leaves, in order.
+    pub fn deferred_batches(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.leaves_of_kind(ReduceProgramType::Deferred)
+    }
+
+    fn leaves_of_kind(&self, kind: ReduceProgramType) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.leaves()
+            .iter()
+            .filter(move |node| node.kind == kind)
+            .map(|node| node.inputs.clone())
+    }
+
+    /// The reduce layers of the plan, ending with the root.
+    pub fn reduce_layers(&self) -> &[Vec<ReduceNode>] {
+        self.layers.get(1..).unwrap_or_default()
+    }
+
+    /// Checks that the plan is a tree over `num_shards` core shard proofs and `num_deferred`
+    /// deferred proofs: the core leaves followed by the deferred leaves cover each proof exactly
+    /// once and in order, every reduce layer covers the nodes of the previous layer the same way,
+    /// and the last layer is a single reduce node.
+    pub fn validate(&self, num_shards: usize, num_deferred: usize) -> Result<(), ReducePlanError> {
+        if self.layers.len() < 2 {
+            return Err(ReducePlanError::MissingReduceLayer);
+        }
+        let root_layer_len = self.layers.last().unwrap().len();
+        if root_layer_len != 1 {
+            return Err(ReducePlanError::MissingRoot(root_layer_len));
+        }
+
+        for (layer, nodes) in self.layers.iter().enumerate() {
+            let kinds: &[(ReduceProgramType, usize)] = if layer == 0 {
+                &[
+                    (ReduceProgramType::Core, num_shards),
+                    (ReduceProgramType::Deferred, num_deferred),
+                ]
+            } else {
+                &[(ReduceProgramType::Reduce, self.layers[layer - 1].len())]
+            };
+
+            let mut nodes = nodes.iter().enumerate().peekable();
+            for (kind, expected) in kinds.iter().copied() {
+                let mut covered = 0;
+                while let Some((node, current)) = nodes.next_if(|(_, node)| node.kind == kind) {
+                    if current.inputs.is_empty() {
+                        return Err(ReducePlanError::EmptyNode { layer, node });
+                    }
+                    if current.inputs.start != covered {
+                        return Err(ReducePlanError::InvalidCoverage {
+                            layer,
+                            kind,
+                            expected,
+                        });
+                    }
+                    covered = current.inputs.end;
+                }
+                if covered != expected {
+                    return Err(ReducePlanError::InvalidCoverage {
+                        layer,
+                        kind,
+                        expected,
+                    });
+                }
+            }
+            if let Some((node, current)) = nodes.next() {
+                return Err(ReducePlanError::UnexpectedKind {
+                    layer,
+                    node,
+                    kind: current.kind,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The digest of the plan, as committed to in the `reduce_plan_digest` public value of the
+    /// proof verified by the root.
+    ///
+    /// The digest of a leaf is `poseidon2(kind || arity)` and the digest of a reduce node is
+    /// `poseidon2(kind || arity || digests of its inputs)`.
+    pub fn digest(&self) -> [BabyBear; DIGEST_SIZE] {
+        let mut digests: Vec<[BabyBear; DIGEST_SIZE]> = Vec::new();
+        for (layer, nodes) in self.layers.iter().enumerate() {
+            digests = nodes
+                .iter()
+                .map(|node| {
+                    let mut inputs = vec![
+                        BabyBear::from_canonical_u32(node.kind as u32),
+                        BabyBear::from_canonical_usize(node.arity()),
+                    ];
+                    if layer > 0 {
+                        inputs.extend(digests[node.inputs.clone()].iter().flatten());
+                    }
+                    poseidon2_hash(inputs)
+                })
+                .collect();
+        }
+        digests.pop().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_plan_covers_shards_once() {
+        for num_shards in 1..=9 {
+            for num_deferred in [0, 1, 3] {
+                let plan = ReducePlan::new(num_shards, num_deferred, 2);
+                plan.validate(num_shards, num_deferred).unwrap();
+
+                let mut shards = plan.core_batches().flatten().collect::<Vec<_>>();
+                shards.sort();
+                assert_eq!(shards, (0..num_shards).collect::<Vec<_>>());
+                let deferred = plan.deferred_batches().flatten().collect::<Vec<_>>();
+                assert_eq!(deferred, (0..num_deferred).collect::<Vec<_>>());
+
+                for (layer, nodes) in plan.reduce_layers().iter().enumerate() {
+                    let mut inputs = nodes
+                        .iter()
+                        .flat_map(|node| node.inputs.clone())
+                        .collect::<Vec<_>>();
+                    inputs.sort();
+                    assert_eq!(inputs, (0..plan.layers[layer].len()).collect::<Vec<_>>());
+                    assert!(nodes.iter().all(|node| node.arity() <= 2));
+                }
+            }
+        }
+    }
+
+    /// The cost of the cheapest tree reducing `len` nodes to one, enumerating the number of nodes
+    /// of each arity in the next layer.
+    fn brute_force_cost(len: usize, cost: &impl Fn(usize) -> u64) -> u64 {
+        if len == 1 {
+            return 0;
+        }
+        let mut best = u64::MAX;
+        for threes in 0..=len / 3 {
+            let rest = len - 3 * threes;
+            if rest % 2 != 0 {
+                continue;
+            }
+            let twos = rest / 2;
+            let next = twos + threes;
+            let layer_cost = twos as u64 * cost(2) + threes as u64 * cost(3);
+            best = best.min(layer_cost + brute_force_cost(next, cost));
+        }
+        best
+    }
+
+    fn plan_cost(plan: &ReducePlan, cost: &impl Fn(usize) -> u64) -> u64 {
+        plan.reduce_layers()
+            .iter()
+            .flatten()
+            .map(|node| cost(node.arity()))
+            .sum()
+    }
+
+    #[test]
+    fn test_optimal_plan_matches_brute_force() {
+        let costs: [&dyn Fn(usize) -> u64; 3] =
+            [&reduce_node_cost, &|arity| arity as u64, &|arity| {
+                if arity == 2 {
+                    1
+                } else {
+                    3
+                }
+            }];
+        for cost in costs {
+            for num_shards in 1..=64 {
+                let plan = ReducePlan::optimal(num_shards, 0, 2, 3, cost);
+                plan.validate(num_shards, 0).unwrap();
+                assert_eq!(plan.leaves(), ReducePlan::new(num_shards, 0, 2).leaves());
+                assert!(plan
+                    .reduce_layers()
+                    .iter()
+                    .flatten()
+                    .all(|node| (2..=3).contains(&node.arity()) || plan.leaves().len() == 1));
+
+                let num_leaves = plan.leaves().len();
+                let expected = if num_leaves == 1 {
+                    cost(1)
+                } else {
+                    brute_force_cost(num_leaves, &cost)
+                };
+                assert_eq!(plan_cost(&plan, &cost), expected, "{} shards", num_shards);
+            }
+        }
+
+        // Five leaves take three layers and six nodes with a fixed arity of two, but only two
+        // layers and three nodes with a mix of arities.
+        let fixed = ReducePlan::new(10, 0, 2);
+        let adaptive = ReducePlan::default_for(10, 0);
+        assert_eq!(fixed.reduce_layers().len(), 3);
+        assert_eq!(fixed.reduce_layers().iter().map(Vec::len).sum::<usize>(), 6);
+        assert_eq!(adaptive.reduce_layers().len(), 2);
+        assert_eq!(
+            adaptive.reduce_layers().iter().map(Vec::len).sum::<usize>(),
+            3
+        );
+        adaptive.validate(10, 0).unwrap();
+    }
+
+    #[test]
+    fn test_digest_changes_with_plan() {
+        let plan = ReducePlan::new(4, 0, 2);
+        assert_eq!(plan.digest(), ReducePlan::new(4, 0, 2).digest());
+
+        // The same shards reduced with a single node of arity four.
+        let wide = ReducePlan::new(4, 0, 4);
+        wide.validate(4, 0).unwrap();
+        assert_ne!(plan.digest(), wide.digest());
+
+        // The same shards with different leaf batches.
+        let mut uneven = plan.clone();
+        uneven.layers[0][0].inputs = 0..3;
+        uneven.layers[0][1].inputs = 3..4;
+        uneven.validate(4, 0).unwrap();
+        assert_ne!(plan.digest(), uneven.digest());
+
+        assert_ne!(plan.digest(), ReducePlan::new(5, 0, 2).digest());
+        assert_ne!(plan.digest(), ReducePlan::new(4, 1, 2).digest());
+    }
+
+    #[test]
+    fn test_invalid_plans() {
+        let plan = ReducePlan::new(5, 2, 2);
+        assert_eq!(
+            plan.validate(6, 2),
+            Err(ReducePlanError::InvalidCoverage {
+                layer: 0,
+                kind: ReduceProgramType::Core,
+                expected: 6,
+            })
+        );
+
+        let mut overlapping = plan.clone();
+        overlapping.layers[0][1].inputs = 1..4;
+        assert!(matches!(
+            overlapping.validate(5, 2),
+            Err(ReducePlanError::InvalidCoverage { layer: 0, .. })
+        ));
+
+        let mut misordered = plan.clone();
+        misordered.layers[0].swap(0, 3);
+        assert!(misordered.validate(5, 2).is_err());
+
+        let mut no_root = plan.clone();
+        no_root.layers.pop();
+        assert_eq!(no_root.validate(5, 2), Err(ReducePlanError::MissingRoot(2)));
+
+        let leaves_only = ReducePlan {
+            layers: vec![plan.layers[0].clone()],
+        };
+        assert_eq!(
+            leaves_only.validate(5, 2),
+            Err(ReducePlanError::MissingReduceLayer)
+        );
+    }
+}
@@ -0,0 +1,550 @@
+//! Independently schedulable execution of the nodes of a reduce tree.
+//!
+//! Generating the witness of a reduce node, by running its recursion program on the node's inputs,
+//! only depends on the program and on the hint stream of the inputs. A [ReduceJob] holds both, the
+//! program by reference to the digest of its verifying key, so that the witness of each node can
+//! be generated in a separate process with [run_reduce_job]. When [SP1Prover::reduce_jobs] is set,
+//! [SP1Prover::run_reduce_jobs] fans the jobs of a layer out to a pool of worker processes,
+//! retries the failed ones and returns their outputs in plan order. The records are then proven in
+//! the coordinating process.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use p3_baby_bear::BabyBear;
+use p3_challenger::CanObserve;
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp1_core::{
+    stark::{Challenge, LocalProver, ShardProof, StarkGenericConfig, StarkProvingKey, Val},
+    utils::DIGEST_SIZE,
+};
+use sp1_recursion_compiler::config::InnerConfig;
+use sp1_recursion_core::{
+    air::Block,
+    runtime::{ExecutionRecord, RecursionProgram, Runtime as RecursionRuntime},
+};
+use sp1_recursion_program::hints::Hintable;
+use thiserror::Error;
+
+use crate::{
+    CoreSC, HashableKey, InnerSC, ReducePlan, ReduceProgramType, SP1Prover, SP1VerifyingKey,
+};
+
+/// The inputs of a reduce node, self-contained so that they can be executed by another process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReduceJob {
+    /// The layer of the node in the [ReducePlan], starting with 0 for the leaves.
+    pub layer: usize,
+    /// The index of the node in its layer.
+    pub index: usize,
+    pub kind: ReduceProgramType,
+    /// The digest of the verifying key of the program run by the node, which commits to the
+    /// program.
+    pub program_digest: [BabyBear; DIGEST_SIZE],
+    /// The digests of the recursion, deferred and compress verifying keys of the prover that made
+    /// the job. A worker built with different recursion programs refuses the job.
+    pub vk_digests: [[BabyBear; DIGEST_SIZE]; 3],
+    /// The hint stream read by the program.
+    pub witness_stream: Vec<Vec<Block<BabyBear>>>,
+}
+
+/// The witness generated for a reduce node: the execution record of its recursion program.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeWitnessOutput {
+    pub layer: usize,
+    pub index: usize,
+    pub kind: ReduceProgramType,
+    /// The record of the program, whose program is set again by [SP1Prover::prove_reduce_node].
+    pub record: ExecutionRecord<BabyBear>,
+}
+
+/// The time spent generating the witness of a reduce node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeReport {
+    pub layer: usize,
+    pub index: usize,
+    pub kind: ReduceProgramType,
+    pub attempts: u32,
+    /// The total time spent on the node, including the failed attempts.
+    pub elapsed: Duration,
+    /// Whether the witness was generated by a worker process.
+    pub out_of_process: bool,
+}
+
+/// How [SP1Prover::run_reduce_jobs] runs jobs in worker processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReduceJobOpts {
+    /// The worker executable, such as the `reduce_worker` binary of this crate. It is called with
+    /// `worker_args` followed by the path of the job and the path its output must be written to.
+    pub worker: PathBuf,
+    pub worker_args: Vec<String>,
+    /// The maximum number of worker processes running at once. At most `shard_batch_size` jobs of
+    /// the recursion options are in flight, since their records are held in memory until proven.
+    pub num_processes: usize,
+    /// The number of times a failed job is retried before giving up.
+    pub max_retries: u32,
+    /// The directory the jobs and their outputs are written to, a temporary directory if `None`.
+    pub job_dir: Option<PathBuf>,
+}
+
+impl ReduceJobOpts {
+    /// Options running jobs with `worker` on every available core, retrying them twice.
+    pub fn new(worker: impl Into<PathBuf>) -> Self {
+        Self {
+            worker: worker.into(),
+            worker_args: Vec::new(),
+            num_processes: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_retries: 2,
+            job_dir: None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReduceJobError {
+    #[error("failed to access a reduce job file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to encode a reduce job file: {0}")]
+    Encoding(#[from] bincode::Error),
+    #[error("node {index} of layer {layer} expects a different {kind:?} program")]
+    ProgramMismatch {
+        layer: usize,
+        index: usize,
+        kind: ReduceProgramType,
+    },
+    #[error("node {index} of layer {layer} was made by a prover with different recursion keys")]
+    KeysMismatch { layer: usize, index: usize },
+    #[error("node {index} of layer {layer} failed after {attempts} attempts: {error}")]
+    WorkerFailed {
+        layer: usize,
+        index: usize,
+        attempts: u32,
+        error: String,
+    },
+}
+
+fn save<T: Serialize>(value: &T, path: &Path) -> Result<(), ReduceJobError> {
+    fs::write(path, bincode::serialize(value)?)?;
+    Ok(())
+}
+
+fn load<T: DeserializeOwned>(path: &Path) -> Result<T, ReduceJobError> {
+    Ok(bincode::deserialize(&fs::read(path)?)?)
+}
+
+impl ReduceJob {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReduceJobError> {
+        save(self, path.as_ref())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReduceJobError> {
+        load(path.as_ref())
+    }
+}
+
+impl NodeWitnessOutput {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReduceJobError> {
+        save(self, path.as_ref())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReduceJobError> {
+        load(path.as_ref())
+    }
+}
+
+/// Generates the witness of the job at `path`, in a prover initialized once per process.
+///
+/// This is the entry point of the worker processes of [SP1Prover::run_reduce_jobs].
+pub fn run_reduce_job(path: impl AsRef<Path>) -> Result<NodeWitnessOutput, ReduceJobError> {
+    static PROVER: OnceLock<SP1Prover> = OnceLock::new();
+    let job = ReduceJob::load(path)?;
+    PROVER.get_or_init(SP1Prover::new).execute_reduce_job(&job)
+}
+
+impl SP1Prover {
+    /// The program run by the nodes of `kind`, with its proving key.
+    fn reduce_program(
+        &self,
+        kind: ReduceProgramType,
+    ) -> (&RecursionProgram<BabyBear>, &StarkProvingKey<InnerSC>) {
+        match kind {
+            ReduceProgramType::Core => (&self.recursion_program, &self.rec_pk),
+            ReduceProgramType::Deferred => (&self.deferred_program, &self.deferred_pk),
+            ReduceProgramType::Reduce => (&self.compress_program, &self.compress_pk),
+        }
+    }
+
+    fn reduce_program_digest(&self, kind: ReduceProgramType) -> [BabyBear; DIGEST_SIZE] {
+        match kind {
+            ReduceProgramType::Core => self.rec_vk.hash_babybear(),
+            ReduceProgramType::Deferred => self.deferred_vk.hash_babybear(),
+            ReduceProgramType::Reduce => self.compress_vk.hash_babybear(),
+        }
+    }
+
+    fn reduce_vk_digests(&self) -> [[BabyBear; DIGEST_SIZE]; 3] {
+        [
+            self.rec_vk.hash_babybear(),
+            self.deferred_vk.hash_babybear(),
+            self.compress_vk.hash_babybear(),
+        ]
+    }
+
+    /// The job of node `index` of `layer`, running the program of `kind` on `input`.
+    pub fn reduce_job(
+        &self,
+        layer: usize,
+        index: usize,
+        kind: ReduceProgramType,
+        input: impl Hintable<InnerConfig>,
+    ) -> ReduceJob {
+        self.reduce_job_with_stream(layer, index, kind, input.write())
+    }
+
+    fn reduce_job_with_stream(
+        &self,
+        layer: usize,
+        index: usize,
+        kind: ReduceProgramType,
+        witness_stream: Vec<Vec<Block<BabyBear>>>,
+    ) -> ReduceJob {
+        ReduceJob {
+            layer,
+            index,
+            kind,
+            program_digest: self.reduce_program_digest(kind),
+            vk_digests: self.reduce_vk_digests(),
+            witness_stream,
+        }
+    }
+
+    /// The jobs of the leaves of `plan`, verifying the core shard proofs of `vk` followed by the
+    /// deferred proofs.
+    pub fn first_layer_jobs(
+        &self,
+        vk: &SP1VerifyingKey,
+        shard_proofs: &[ShardProof<CoreSC>],
+        deferred_proofs: &[ShardProof<InnerSC>],
+        plan: &ReducePlan,
+    ) -> Vec<ReduceJob> {
+        let mut leaf_challenger = self.core_machine.config().challenger();
+        vk.vk.observe_into(&mut leaf_challenger);
+        shard_proofs.iter().for_each(|proof| {
+            leaf_challenger.observe(proof.commitment.main_commit);
+            leaf_challenger.observe_slice(&proof.public_values[0..self.core_machine.num_pv_elts()]);
+        });
+
+        let (core_inputs, deferred_inputs) =
+            self.get_first_layer_inputs(vk, &leaf_challenger, shard_proofs, deferred_proofs, plan);
+        let core_jobs = core_inputs
+            .iter()
+            .map(|input| (ReduceProgramType::Core, input.write()));
+        let deferred_jobs = deferred_inputs
+            .iter()
+            .map(|input| (ReduceProgramType::Deferred, input.write()));
+        core_jobs
+            .chain(deferred_jobs)
+            .enumerate()
+            .map(|(index, (kind, witness_stream))| {
+                self.reduce_job_with_stream(0, index, kind, witness_stream)
+            })
+            .collect()
+    }
+
+    /// Generates the witness of `job` in this process.
+    pub fn execute_reduce_job(&self, job: &ReduceJob) -> Result<NodeWitnessOutput, ReduceJobError> {
+        if job.vk_digests != self.reduce_vk_digests() {
+            return Err(ReduceJobError::KeysMismatch {
+                layer: job.layer,
+                index: job.index,
+            });
+        }
+        if job.program_digest != self.reduce_program_digest(job.kind) {
+            return Err(ReduceJobError::ProgramMismatch {
+                layer: job.layer,
+                index: job.index,
+                kind: job.kind,
+            });
+        }
+
+        let (program, _) = self.reduce_program(job.kind);
+        let mut runtime = RecursionRuntime::<Val<InnerSC>, Challenge<InnerSC>, _>::new(
+            program,
+            self.compress_machine.config().perm.clone(),
+        );
+        runtime.witness_stream = job.witness_stream.clone().into();
+        runtime.run();
+        runtime.print_stats();
+
+        Ok(NodeWitnessOutput {
+            layer: job.layer,
+            index: job.index,
+            kind: job.kind,
+            record: runtime.record,
+        })
+    }
+
+    /// Proves the witness of a reduce node.
+    pub fn prove_reduce_node(&self, output: NodeWitnessOutput) -> ShardProof<InnerSC> {
+        let (program, pk) = self.reduce_program(output.kind);
+        let mut record = output.record;
+        record.program = Arc::new(program.clone());
+
+        let mut challenger = self.compress_machine.config().challenger();
+        self.compress_machine
+            .prove::<LocalProver<_, _>>(pk, record, &mut challenger, self.recursion_opts)
+            .shard_proofs
+            .pop()
+            .unwrap()
+    }
+
+    /// Generates the witnesses of `jobs`, returned in the same order along with the time spent on
+    /// each of them.
+    ///
+    /// The jobs run in parallel in this process, or in worker processes if
+    /// [SP1Prover::reduce_jobs] is set. Both produce the same outputs.
+    pub fn run_reduce_jobs(
+        &self,
+        jobs: &[ReduceJob],
+    ) -> Result<(Vec<NodeWitnessOutput>, Vec<NodeReport>), ReduceJobError> {
+        let results = match &self.reduce_jobs {
+            None => jobs
+                .par_iter()
+                .map(|job| {
+                    let start = Instant::now();
+                    let output = self.execute_reduce_job(job)?;
+                    let report = NodeReport {
+                        layer: job.layer,
+                        index: job.index,
+                        kind: job.kind,
+                        attempts: 1,
+                        elapsed: start.elapsed(),
+                        out_of_process: false,
+                    };
+                    Ok((output, report))
+                })
+                .collect::<Result<Vec<_>, ReduceJobError>>()?,
+            Some(opts) => run_in_worker_processes(jobs, opts)?,
+        };
+        Ok(results.into_iter().unzip())
+    }
+}
+
+/// Runs `jobs` on a pool of `opts.num_processes` worker processes.
+fn run_in_worker_processes(
+    jobs: &[ReduceJob],
+    opts: &ReduceJobOpts,
+) -> Result<Vec<(NodeWitnessOutput, NodeReport)>, ReduceJobError> {
+    let temp_dir;
+    let dir = match &opts.job_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir)?;
+            dir.clone()
+        }
+        None => {
+            temp_dir = tempfile::tempdir()?;
+            temp_dir.path().to_path_buf()
+        }
+    };
+    let paths = jobs
+        .iter()
+        .map(|job| {
+            let name = format!("layer{}-node{}", job.layer, job.index);
+            (
+                dir.join(format!("{}.job", name)),
+                dir.join(format!("{}.out", name)),
+            )
+        })
+        .collect::<Vec<_>>();
+    for (job, (job_path, _)) in jobs.iter().zip(&paths) {
+        job.save(job_path)?;
+    }
+
+    // Every worker thread drives one process at a time, picking the next job in plan order until
+    // all jobs are taken or one of them failed for good.
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new((0..jobs.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..opts.num_processes.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= jobs.len() || failed.load(Ordering::SeqCst) {
+                    break;
+                }
+                let (job_path, output_path) = &paths[i];
+                let result = run_in_worker_process(&jobs[i], job_path, output_path, opts);
+                if result.is_err() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    let mut outputs = Vec::with_capacity(jobs.len());
+    for result in results.into_inner().unwrap() {
+        // A job is only skipped after another one failed, whose error is returned first.
+        if let Some(result) = result {
+            outputs.push(result?);
+        }
+    }
+    Ok(outputs)
+}
+
+/// Runs `job` in a worker process, retrying it up to `opts.max_retries` times.
+fn run_in_worker_process(
+    job: &ReduceJob,
+    job_path: &Path,
+    output_path: &Path,
+    opts: &ReduceJobOpts,
+) -> Result<(NodeWitnessOutput, NodeReport), ReduceJobError> {
+    let start = Instant::now();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let _ = fs::remove_file(output_path);
+        let status = Command::new(&opts.worker)
+            .args(&opts.worker_args)
+            .arg(job_path)
+            .arg(output_path)
+            .status();
+        let error = match status {
+            Ok(status) if status.success() => match NodeWitnessOutput::load(output_path) {
+                Ok(output) if output.layer == job.layer && output.index == job.index => {
+                    let report = NodeReport {
+                        layer: job.layer,
+                        index: job.index,
+                        kind: job.kind,
+                        attempts,
+                        elapsed: start.elapsed(),
+                        out_of_process: true,
+                    };
+                    return Ok((output, report));
+                }
+                Ok(_) => "the output is not the one of the job".to_string(),
+                Err(err) => err.to_string(),
+            },
+            Ok(status) => format!("the worker exited with {}", status),
+            Err(err) => format!("failed to start the worker: {}", err),
+        };
+        if attempts > opts.max_retries {
+            return Err(ReduceJobError::WorkerFailed {
+                layer: job.layer,
+                index: job.index,
+                attempts,
+                error,
+            });
+        }
+        tracing::warn!(
+            "node {} of layer {} failed (attempt {}), retrying: {}",
+            job.index,
+            job.layer,
+            attempts,
+            error
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use serial_test::serial;
+    use sp1_core::{io::SP1Stdin, utils::setup_logger};
+
+    use super::*;
+    use crate::ReduceNode;
+
+    /// The worker processes of [test_reduce_jobs_out_of_process], which runs this test binary
+    /// again with the paths of a job and of its output as its last arguments.
+    #[test]
+    #[ignore]
+    fn reduce_worker() {
+        let args = env::args().collect::<Vec<_>>();
+        let [.., job, output] = args.as_slice() else {
+            return;
+        };
+        if !job.ends_with(".job") {
+            return;
+        }
+        run_reduce_job(job).unwrap().save(output).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_reduce_jobs_out_of_process() {
+        setup_logger();
+        let elf = include_bytes!("../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+        let mut prover = SP1Prover::new();
+        prover.core_opts.shard_size = 1 << 10;
+        let (pk, vk) = prover.setup(elf);
+        let core_proof = prover.prove_core(&pk, &SP1Stdin::new()).unwrap();
+        let shard_proofs = &core_proof.proof.0;
+
+        // A plan whose leaves form a layer of 4 nodes.
+        let num_shards = shard_proofs.len();
+        assert!(num_shards >= 4);
+        let leaves = (0..4)
+            .map(|i| ReduceNode {
+                kind: ReduceProgramType::Core,
+                inputs: i * num_shards / 4..(i + 1) * num_shards / 4,
+            })
+            .collect();
+        let root = ReduceNode {
+            kind: ReduceProgramType::Reduce,
+            inputs: 0..4,
+        };
+        let plan = ReducePlan {
+            layers: vec![leaves, vec![root]],
+        };
+        plan.validate(num_shards, 0).unwrap();
+        let jobs = prover.first_layer_jobs(&vk, shard_proofs, &[], &plan);
+        assert_eq!(jobs.len(), 4);
+
+        let (in_process, reports) = prover.run_reduce_jobs(&jobs).unwrap();
+        assert!(reports.iter().all(|report| !report.out_of_process));
+
+        let mut worker_opts = ReduceJobOpts::new(env::current_exe().unwrap());
+        worker_opts.worker_args = ["reduce_job::tests::reduce_worker", "--exact", "--ignored"]
+            .map(String::from)
+            .to_vec();
+        worker_opts.num_processes = 2;
+        prover.reduce_jobs = Some(worker_opts);
+        let (out_of_process, reports) = prover.run_reduce_jobs(&jobs).unwrap();
+
+        assert_eq!(reports.len(), 4);
+        for (index, report) in reports.iter().enumerate() {
+            assert_eq!((report.layer, report.index), (0, index));
+            assert!(report.out_of_process);
+            assert_eq!(report.attempts, 1);
+        }
+        for (a, b) in in_process.iter().zip(&out_of_process) {
+            assert_eq!(
+                bincode::serialize(a).unwrap(),
+                bincode::serialize(b).unwrap()
+            );
+        }
+
+        // A missing worker fails every attempt.
+        prover.reduce_jobs = Some(ReduceJobOpts {
+            worker: PathBuf::from("/nonexistent/reduce_worker"),
+            max_retries: 1,
+            ..ReduceJobOpts::new("")
+        });
+        match prover.run_reduce_jobs(&jobs[..1]).unwrap_err() {
+            ReduceJobError::WorkerFailed { attempts, .. } => assert_eq!(attempts, 2),
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+}
@@ -9,9 +9,13 @@ use p3_field::PrimeField;
 use p3_field::{AbstractField, PrimeField32, TwoAdicField};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sp1_core::{
+    air::{PublicValues, Word},
     io::{SP1PublicValues, SP1Stdin},
-    stark::{ShardProof, StarkGenericConfig, StarkProvingKey, StarkVerifyingKey},
-    utils::DIGEST_SIZE,
+    stark::{
+        FingerprintDigest, MachineFingerprint, ShardProof, StarkGenericConfig, StarkProvingKey,
+        StarkVerifyingKey,
+    },
+    utils::{memory, DIGEST_SIZE},
 };
 use sp1_primitives::poseidon2_hash;
 use sp1_recursion_core::{air::RecursionPublicValues, stark::config::BabyBearPoseidon2Outer};
@@ -20,7 +24,7 @@ use thiserror::Error;
 
 use crate::utils::words_to_bytes_be;
 use crate::{utils::babybear_bytes_to_bn254, words_to_bytes};
-use crate::{utils::babybears_to_bn254, CoreSC, InnerSC};
+use crate::{utils::babybears_to_bn254, CoreSC, InnerSC, ReduceJobError, ReducePlanError};
 
 /// The information necessary to generate a proof for a given RISC-V program.
 #[derive(Clone, Serialize, Deserialize)]
@@ -37,6 +41,29 @@ pub struct SP1VerifyingKey {
     pub vk: StarkVerifyingKey<CoreSC>,
 }
 
+/// The digest of the preprocessed traces and the start pc of a program, which its proving and
+/// verifying keys both carry.
+fn program_digest(commit: &[BabyBear; DIGEST_SIZE], pc_start: BabyBear) -> [u8; 32] {
+    let mut inputs = commit.to_vec();
+    inputs.push(pc_start);
+    words_to_bytes_be(&poseidon2_hash(inputs).map(|x| x.as_canonical_u32()))
+}
+
+impl SP1ProvingKey {
+    /// The digest of the program the key was set up for, equal to [SP1VerifyingKey::program_digest]
+    /// for the verifying key of the same program.
+    pub fn program_digest(&self) -> [u8; 32] {
+        program_digest(self.pk.commit.as_ref(), self.pk.pc_start)
+    }
+}
+
+impl SP1VerifyingKey {
+    /// The digest of the program the key was set up for, see [SP1ProvingKey::program_digest].
+    pub fn program_digest(&self) -> [u8; 32] {
+        program_digest(self.vk.commit.as_ref(), self.vk.pc_start)
+    }
+}
+
 /// A trait for keys that can be hashed into a digest.
 pub trait HashableKey {
     /// Hash the key into a digest of BabyBear elements.
@@ -114,6 +141,10 @@ pub struct SP1ProofWithMetadata<P: Clone> {
     pub proof: P,
     pub stdin: SP1Stdin,
     pub public_values: SP1PublicValues,
+    /// Whether the program made unconstrained syscalls, whose effects the proof says nothing
+    /// about, see [sp1_core::utils::SP1CoreOpts::allow_unconstrained].
+    #[serde(default)]
+    pub insecure: bool,
 }
 
 impl<P: Serialize + DeserializeOwned + Clone> SP1ProofWithMetadata<P> {
@@ -132,6 +163,7 @@ impl<P: std::fmt::Debug + Clone> std::fmt::Debug for SP1ProofWithMetadata<P> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SP1ProofWithMetadata")
             .field("proof", &self.proof)
+            .field("insecure", &self.insecure)
             .finish()
     }
 }
@@ -151,6 +183,18 @@ pub type SP1PlonkProof = SP1ProofWithMetadata<SP1PlonkProofData>;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SP1CoreProofData(pub Vec<ShardProof<CoreSC>>);
+impl SP1CoreProofData {
+    /// The bitmap of the features the proof was generated with, see [sp1_core::features]. Zero
+    /// for a proof without shards.
+    pub fn features(&self) -> u32 {
+        self.0.last().map_or(0, |shard_proof| {
+            PublicValues::<Word<BabyBear>, BabyBear>::from_vec(shard_proof.public_values.clone())
+                .features
+                .as_canonical_u32()
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SP1ReducedProofData(pub ShardProof<InnerSC>);
 
@@ -168,6 +212,49 @@ pub struct SP1ReduceProof<SC: StarkGenericConfig> {
     pub proof: ShardProof<SC>,
 }
 
+impl<SC: StarkGenericConfig<Val = BabyBear>> SP1ReduceProof<SC> {
+    /// The digest of the input buffers read by the program, see [SP1Stdin::digest].
+    pub fn sp1_input_digest_bytes(&self) -> [u8; 32] {
+        let pv: &RecursionPublicValues<BabyBear> = self.proof.public_values.as_slice().borrow();
+        words_to_bytes(&pv.input_digest)
+            .into_iter()
+            .map(|b| b.as_canonical_u32() as u8)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    /// The digest of the config the program was run with, see [SP1Stdin::config_digest].
+    pub fn sp1_config_digest_bytes(&self) -> [u8; 32] {
+        let pv: &RecursionPublicValues<BabyBear> = self.proof.public_values.as_slice().borrow();
+        words_to_bytes(&pv.config_digest)
+            .into_iter()
+            .map(|b| b.as_canonical_u32() as u8)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    /// The digest of the verifying key of the program the proof is for, see
+    /// [HashableKey::hash_bytes].
+    pub fn sp1_vkey_digest_bytes(&self) -> [u8; 32] {
+        let pv: &RecursionPublicValues<BabyBear> = self.proof.public_values.as_slice().borrow();
+        words_to_bytes_be(&pv.sp1_vk_digest.map(|x| x.as_canonical_u32()))
+    }
+
+    /// The bitmap of the features the proof was generated with, see [sp1_core::features].
+    pub fn sp1_features(&self) -> u32 {
+        let pv: &RecursionPublicValues<BabyBear> = self.proof.public_values.as_slice().borrow();
+        pv.features.as_canonical_u32()
+    }
+
+    /// The digest of the reduce tree the proof was built with, see [crate::ReducePlan::digest].
+    pub fn reduce_plan_digest(&self) -> [BabyBear; DIGEST_SIZE] {
+        let pv: &RecursionPublicValues<BabyBear> = self.proof.public_values.as_slice().borrow();
+        pv.reduce_plan_digest
+    }
+}
+
 impl SP1ReduceProof<BabyBearPoseidon2Outer> {
     pub fn sp1_vkey_digest_babybear(&self) -> [BabyBear; 8] {
         let proof = &self.proof;
@@ -188,6 +275,26 @@ impl SP1ReduceProof<BabyBearPoseidon2Outer> {
                 .unwrap();
         babybear_bytes_to_bn254(&committed_values_digest_bytes)
     }
+
+    pub fn sp1_input_digest_bn254(&self) -> Bn254Fr {
+        let proof = &self.proof;
+        let pv: &RecursionPublicValues<BabyBear> = proof.public_values.as_slice().borrow();
+        let input_digest_bytes: [BabyBear; 32] =
+            words_to_bytes(&pv.input_digest).try_into().unwrap();
+        babybear_bytes_to_bn254(&input_digest_bytes)
+    }
+
+    /// The committed values digest as bytes, the message signed by the operator when the wrap
+    /// circuit verifies a signature, see [crate::build::build_plonk_bn254_artifacts_with_operator].
+    pub fn sp1_commited_values_digest_bytes(&self) -> [u8; 32] {
+        let pv: &RecursionPublicValues<BabyBear> = self.proof.public_values.as_slice().borrow();
+        words_to_bytes(&pv.committed_value_digest)
+            .iter()
+            .map(|byte| byte.as_canonical_u32() as u8)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
 }
 
 /// A proof that can be reduced along with other proofs into one proof.
@@ -198,4 +305,54 @@ pub enum SP1ReduceProofWrapper {
 }
 
 #[derive(Error, Debug)]
-pub enum SP1RecursionProverError {}
+pub enum SP1RecursionProverError {
+    #[error("invalid reduce plan: {0}")]
+    InvalidReducePlan(#[from] ReducePlanError),
+    #[error("reduce job failed: {0}")]
+    ReduceJob(#[from] ReduceJobError),
+    #[error("the {stage} stage used {peak} bytes of memory, more than its budget of {budget}")]
+    MemoryBudgetExceeded {
+        stage: String,
+        peak: u64,
+        budget: u64,
+    },
+    #[error(
+        "the core machine has shape {core_shape} but the recursion verifier expects shape \
+         {verifier_expected}, the first differing chip is {chip}"
+    )]
+    ShapeMismatch {
+        core_shape: FingerprintDigest,
+        verifier_expected: FingerprintDigest,
+        chip: String,
+    },
+}
+
+impl SP1RecursionProverError {
+    /// Checks the peak memory of `stage` against `budget`, see [sp1_core::utils::MemoryBudgets].
+    pub(crate) fn check_memory_budget(stage: &str, budget: Option<u64>) -> Result<(), Self> {
+        match memory::exceeded_budget(budget) {
+            Some((peak, budget)) => Err(Self::MemoryBudgetExceeded {
+                stage: stage.to_string(),
+                peak,
+                budget,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the fingerprint `core` of the machine of a core verifying key against the one the
+    /// recursion program verifying its proofs was built for, if any.
+    pub(crate) fn check_shape(
+        core: &MachineFingerprint,
+        expected: Option<&MachineFingerprint>,
+    ) -> Result<(), Self> {
+        match expected.and_then(|expected| Some((expected, core.first_difference(expected)?))) {
+            Some((expected, chip)) => Err(Self::ShapeMismatch {
+                core_shape: core.digest(),
+                verifier_expected: expected.digest(),
+                chip,
+            }),
+            None => Ok(()),
+        }
+    }
+}
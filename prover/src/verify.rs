@@ -1,13 +1,14 @@
 use std::{borrow::Borrow, path::Path, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use num_bigint::BigUint;
 use p3_baby_bear::BabyBear;
-use p3_field::{AbstractField, PrimeField};
+use p3_field::{AbstractField, PrimeField, PrimeField32};
 use sp1_core::air::MachineAir;
+use sp1_core::features::{unsupported_features, CONFIG_DIGEST_FEATURE, INPUT_DIGEST_FEATURE};
 use sp1_core::runtime::SubproofVerifier;
 use sp1_core::{
-    air::PublicValues,
+    air::{PublicValues, Word},
     io::SP1PublicValues,
     stark::{MachineProof, MachineVerificationError, StarkGenericConfig},
     utils::BabyBearPoseidon2,
@@ -17,7 +18,8 @@ use sp1_recursion_gnark_ffi::{PlonkBn254Proof, PlonkBn254Prover};
 use thiserror::Error;
 
 use crate::{
-    CoreSC, HashableKey, OuterSC, SP1CoreProofData, SP1Prover, SP1ReduceProof, SP1VerifyingKey,
+    CoreSC, HashableKey, OuterSC, ReducePlan, SP1CoreProofData, SP1Prover, SP1ReduceProof,
+    SP1VerifyingKey,
 };
 
 #[derive(Error, Debug)]
@@ -32,6 +34,114 @@ pub enum PlonkVerificationError {
     InvalidPublicValues,
 }
 
+/// The number of shards above which the shards of a core proof are verified in parallel, see
+/// [sp1_core::stark::StarkMachine::verify_batched].
+pub const BATCHED_VERIFICATION_MIN_SHARDS: usize = 4;
+
+/// Verifies that the `features` committed to by a proof are supported, and that the public values
+/// it doesn't enable are zero. See [sp1_core::features].
+pub fn verify_features<SC: StarkGenericConfig>(
+    features: BabyBear,
+    input_digest: &[Word<BabyBear>],
+    config_digest: &[Word<BabyBear>],
+) -> Result<(), MachineVerificationError<SC>> {
+    let features = features.as_canonical_u32();
+    let bits = unsupported_features(features);
+    if bits != 0 {
+        return Err(MachineVerificationError::UnsupportedFeatures { bits });
+    }
+    let is_zero = |digest: &[Word<BabyBear>]| {
+        digest
+            .iter()
+            .all(|word| word.0.iter().all(|byte| *byte == BabyBear::zero()))
+    };
+    if features & INPUT_DIGEST_FEATURE == 0 && !is_zero(input_digest) {
+        return Err(MachineVerificationError::InvalidPublicValues(
+            "input digest without the input digest feature",
+        ));
+    }
+    if features & CONFIG_DIGEST_FEATURE == 0 && !is_zero(config_digest) {
+        return Err(MachineVerificationError::InvalidPublicValues(
+            "config digest without the config digest feature",
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies that the public values of the shards of a core proof chain into a complete execution.
+///
+/// The shards are numbered from 1, each starting at the pc the previous one stopped at, and
+/// agreeing on the digests, the exit code and the features. The execution halts in the last shard,
+/// and only in it, so no shards can follow the halting one. Halt is signaled with `next_pc == 0`.
+fn verify_shard_transitions(
+    shards: &[PublicValues<Word<BabyBear>, BabyBear>],
+    pc_start: BabyBear,
+) -> Result<(), MachineVerificationError<CoreSC>> {
+    for (i, public_values) in shards.iter().enumerate() {
+        if i == 0 {
+            // If it's the first shard, index should be 1.
+            if public_values.shard != BabyBear::one() {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "first shard not 1",
+                ));
+            }
+            if public_values.start_pc != pc_start {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "wrong pc_start",
+                ));
+            }
+        } else {
+            let prev_public_values = &shards[i - 1];
+            // For non-first shards, the index should be the previous index + 1.
+            if public_values.shard != prev_public_values.shard + BabyBear::one() {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "non incremental shard index",
+                ));
+            }
+            // Start pc should be what the next pc declared in the previous shard was.
+            if public_values.start_pc != prev_public_values.next_pc {
+                return Err(MachineVerificationError::InvalidPublicValues("pc mismatch"));
+            }
+            // Digests and exit code should be the same in all shards.
+            if public_values.committed_value_digest != prev_public_values.committed_value_digest
+                || public_values.input_digest != prev_public_values.input_digest
+                || public_values.config_digest != prev_public_values.config_digest
+                || public_values.deferred_proofs_digest != prev_public_values.deferred_proofs_digest
+                || public_values.exit_code != prev_public_values.exit_code
+            {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "digest or exit code mismatch",
+                ));
+            }
+            if public_values.features != prev_public_values.features {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "features mismatch",
+                ));
+            }
+        }
+        // The last shard should be halted, including when it is also the first one.
+        if i == shards.len() - 1 && public_values.next_pc != BabyBear::zero() {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "last shard isn't halted",
+            ));
+        }
+        // All non-last shards should not be halted.
+        if i != shards.len() - 1 && public_values.next_pc == BabyBear::zero() {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "non-last shard is halted",
+            ));
+        }
+    }
+    if let Some(public_values) = shards.last() {
+        verify_features(
+            public_values.features,
+            &public_values.input_digest,
+            &public_values.config_digest,
+        )?;
+    }
+    Ok(())
+}
+
 impl SP1Prover {
     /// Verify a core proof by verifying the shards, verifying lookup bus, verifying that the
     /// shards are contiguous and complete.
@@ -44,65 +154,22 @@ impl SP1Prover {
         let machine_proof = MachineProof {
             shard_proofs: proof.0.to_vec(),
         };
-        self.core_machine
-            .verify(&vk.vk, &machine_proof, &mut challenger)?;
-
         let num_shards = proof.0.len();
+        if num_shards > BATCHED_VERIFICATION_MIN_SHARDS {
+            self.core_machine
+                .verify_batched(&vk.vk, &machine_proof, &mut challenger)?;
+        } else {
+            self.core_machine
+                .verify(&vk.vk, &machine_proof, &mut challenger)?;
+        }
 
         // Verify shard transitions.
-        for (i, shard_proof) in proof.0.iter().enumerate() {
-            let public_values = PublicValues::from_vec(shard_proof.public_values.clone());
-            // Verify shard transitions
-            if i == 0 {
-                // If it's the first shard, index should be 1.
-                if public_values.shard != BabyBear::one() {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "first shard not 1",
-                    ));
-                }
-                if public_values.start_pc != vk.vk.pc_start {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "wrong pc_start",
-                    ));
-                }
-            } else {
-                let prev_shard_proof = &proof.0[i - 1];
-                let prev_public_values =
-                    PublicValues::from_vec(prev_shard_proof.public_values.clone());
-                // For non-first shards, the index should be the previous index + 1.
-                if public_values.shard != prev_public_values.shard + BabyBear::one() {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "non incremental shard index",
-                    ));
-                }
-                // Start pc should be what the next pc declared in the previous shard was.
-                if public_values.start_pc != prev_public_values.next_pc {
-                    return Err(MachineVerificationError::InvalidPublicValues("pc mismatch"));
-                }
-                // Digests and exit code should be the same in all shards.
-                if public_values.committed_value_digest != prev_public_values.committed_value_digest
-                    || public_values.deferred_proofs_digest
-                        != prev_public_values.deferred_proofs_digest
-                    || public_values.exit_code != prev_public_values.exit_code
-                {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "digest or exit code mismatch",
-                    ));
-                }
-                // The last shard should be halted. Halt is signaled with next_pc == 0.
-                if i == proof.0.len() - 1 && public_values.next_pc != BabyBear::zero() {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "last shard isn't halted",
-                    ));
-                }
-                // All non-last shards should not be halted.
-                if i != proof.0.len() - 1 && public_values.next_pc == BabyBear::zero() {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "non-last shard is halted",
-                    ));
-                }
-            }
-        }
+        let public_values = proof
+            .0
+            .iter()
+            .map(|shard_proof| PublicValues::from_vec(shard_proof.public_values.clone()))
+            .collect::<Vec<_>>();
+        verify_shard_transitions(&public_values, vk.vk.pc_start)?;
 
         // Verify that the number of shards is not too large.
         if proof.0.len() > 1 << 16 {
@@ -173,6 +240,11 @@ impl SP1Prover {
                 "sp1 vk hash mismatch",
             ));
         }
+        verify_features(
+            public_values.features,
+            &public_values.input_digest,
+            &public_values.config_digest,
+        )?;
 
         // Verify that the reduce program is the one we are expecting.
         let recursion_vkey_hash = self.compress_vk.hash_babybear();
@@ -216,6 +288,11 @@ impl SP1Prover {
                 "sp1 vk hash mismatch",
             ));
         }
+        verify_features(
+            public_values.features,
+            &public_values.input_digest,
+            &public_values.config_digest,
+        )?;
 
         Ok(())
     }
@@ -251,6 +328,38 @@ impl SP1Prover {
                 "sp1 vk hash mismatch",
             ));
         }
+        verify_features(
+            public_values.features,
+            &public_values.input_digest,
+            &public_values.config_digest,
+        )?;
+
+        Ok(())
+    }
+
+    /// Verify a wrap bn254 proof and that it was reduced following `plan`.
+    pub fn verify_wrap_bn254_with_plan(
+        &self,
+        proof: &SP1ReduceProof<BabyBearPoseidon2Outer>,
+        vk: &SP1VerifyingKey,
+        plan: &ReducePlan,
+    ) -> Result<(), MachineVerificationError<OuterSC>> {
+        self.verify_wrap_bn254(proof, vk)?;
+        self.verify_reduce_plan(proof, plan)
+    }
+
+    /// Verify that the reduce tree committed to in the public values of a reduced proof is the
+    /// one described by `plan`. The proof itself should be verified separately.
+    pub fn verify_reduce_plan<SC: StarkGenericConfig<Val = BabyBear>>(
+        &self,
+        proof: &SP1ReduceProof<SC>,
+        plan: &ReducePlan,
+    ) -> Result<(), MachineVerificationError<SC>> {
+        if proof.reduce_plan_digest() != plan.digest() {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "reduce plan digest mismatch",
+            ));
+        }
 
         Ok(())
     }
@@ -267,14 +376,51 @@ impl SP1Prover {
 
         let vkey_hash = BigUint::from_str(&proof.public_inputs[0])?;
         let committed_values_digest = BigUint::from_str(&proof.public_inputs[1])?;
+        let input_digest = BigUint::from_str(&proof.public_inputs[2])?;
 
         // Verify the proof with the corresponding public inputs.
-        prover.verify(proof, &vkey_hash, &committed_values_digest, build_dir);
+        prover.verify(
+            proof,
+            &vkey_hash,
+            &committed_values_digest,
+            &input_digest,
+            build_dir,
+        );
 
         verify_plonk_bn254_public_inputs(vk, public_values, &proof.public_inputs)?;
 
         Ok(())
     }
+
+    /// Verifies a batch of PLONK proofs using the circuit artifacts in the build directory.
+    ///
+    /// The proofs are verified independently, see [PlonkBn254Prover::batch_verify]: this saves
+    /// loading the verifying key for each proof, but not the pairing check of each proof. On
+    /// failure, the error names the index of the first invalid proof in the batch.
+    pub fn verify_plonk_bn254_batch(
+        &self,
+        proofs: &[(&PlonkBn254Proof, &SP1VerifyingKey, &SP1PublicValues)],
+        build_dir: &Path,
+    ) -> Result<()> {
+        let mut batch = Vec::with_capacity(proofs.len());
+        for (index, (proof, vk, public_values)) in proofs.iter().enumerate() {
+            verify_plonk_bn254_public_inputs(vk, public_values, &proof.public_inputs)
+                .with_context(|| format!("proof {} has invalid public inputs", index))?;
+            let vkey_hash = BigUint::from_str(&proof.public_inputs[0])?;
+            let committed_values_digest = BigUint::from_str(&proof.public_inputs[1])?;
+            let input_digest = BigUint::from_str(&proof.public_inputs[2])?;
+            batch.push((
+                (*proof).clone(),
+                vkey_hash,
+                committed_values_digest,
+                input_digest,
+            ));
+        }
+
+        PlonkBn254Prover::new().batch_verify(&batch, build_dir)?;
+
+        Ok(())
+    }
 }
 
 /// Verify the vk_hash and public_values_hash in the public inputs of the PlonkBn254Proof match the expected values.
@@ -332,3 +478,167 @@ impl SubproofVerifier for &SP1Prover {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use sp1_core::stark::Challenge;
+    use sp1_recursion_compiler::{asm::AsmBuilder, ir::Felt};
+    use sp1_recursion_core::runtime::Runtime;
+    use sp1_recursion_program::machine::assert_shard_transition;
+
+    use super::*;
+
+    const PC_START: u32 = 0x2000;
+
+    /// Public values of consecutive shards, the last of which halts.
+    fn shards(num_shards: u32) -> Vec<PublicValues<Word<BabyBear>, BabyBear>> {
+        (1..=num_shards)
+            .map(|shard| {
+                let public_values = PublicValues::<u32, u32> {
+                    shard,
+                    start_pc: PC_START + 4 * (shard - 1),
+                    next_pc: if shard == num_shards {
+                        0
+                    } else {
+                        PC_START + 4 * shard
+                    },
+                    ..Default::default()
+                };
+                PublicValues::from_vec(public_values.to_vec())
+            })
+            .collect()
+    }
+
+    fn assert_rejected(shards: &[PublicValues<Word<BabyBear>, BabyBear>], reason: &str) {
+        match verify_shard_transitions(shards, BabyBear::from_canonical_u32(PC_START)) {
+            Err(MachineVerificationError::InvalidPublicValues(err)) => assert_eq!(err, reason),
+            result => panic!("expected `{reason}`, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_verify_shard_transitions() {
+        for num_shards in 1..=3 {
+            verify_shard_transitions(&shards(num_shards), BabyBear::from_canonical_u32(PC_START))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_single_shard_not_halted() {
+        let mut shards = shards(1);
+        shards[0].next_pc = BabyBear::from_canonical_u32(PC_START + 4);
+        assert_rejected(&shards, "last shard isn't halted");
+    }
+
+    #[test]
+    fn test_shard_after_halt() {
+        let mut shards = shards(2);
+        shards[0].next_pc = BabyBear::zero();
+        shards[1].start_pc = BabyBear::zero();
+        assert_rejected(&shards, "non-last shard is halted");
+    }
+
+    /// Whether the shard transition checks of the core recursion program, see
+    /// [assert_shard_transition], accept the public values of consecutive shards.
+    fn recursion_accepts_shard_transitions(
+        shards: &[PublicValues<Word<BabyBear>, BabyBear>],
+    ) -> bool {
+        let mut builder = AsmBuilder::<BabyBear, Challenge<CoreSC>>::default();
+        let current_shard: Felt<_> = builder.eval(shards[0].shard);
+        let current_pc: Felt<_> = builder.eval(shards[0].start_pc);
+        for public_values in shards {
+            let mut felt = |value: BabyBear| -> Felt<_> { builder.eval(value) };
+            let public_values = PublicValues {
+                committed_value_digest: public_values
+                    .committed_value_digest
+                    .map(|word| word.map(&mut felt)),
+                input_digest: public_values.input_digest.map(|word| word.map(&mut felt)),
+                config_digest: public_values.config_digest.map(|word| word.map(&mut felt)),
+                deferred_proofs_digest: public_values.deferred_proofs_digest.map(&mut felt),
+                start_pc: felt(public_values.start_pc),
+                next_pc: felt(public_values.next_pc),
+                exit_code: felt(public_values.exit_code),
+                shard: felt(public_values.shard),
+                features: felt(public_values.features),
+            };
+            assert_shard_transition(&mut builder, current_shard, current_pc, &public_values);
+            builder.assign(current_shard, current_shard + BabyBear::one());
+            builder.assign(current_pc, public_values.next_pc);
+        }
+        builder.halt();
+
+        let program = builder.compile_program();
+        let config = CoreSC::default();
+        let mut runtime =
+            Runtime::<BabyBear, Challenge<CoreSC>, _>::new(&program, config.perm.clone());
+        catch_unwind(AssertUnwindSafe(|| runtime.run())).is_ok()
+    }
+
+    #[test]
+    fn test_shard_after_halt_rejected_by_verifiers() {
+        let valid_shards = shards(3);
+        verify_shard_transitions(&valid_shards, BabyBear::from_canonical_u32(PC_START)).unwrap();
+        assert!(recursion_accepts_shard_transitions(&valid_shards));
+
+        // The second shard halts, and the third one continues from the zero pc it stopped at.
+        let mut shards = shards(3);
+        shards[1].next_pc = BabyBear::zero();
+        shards[2].start_pc = BabyBear::zero();
+        assert_rejected(&shards, "non-last shard is halted");
+        assert!(!recursion_accepts_shard_transitions(&shards));
+    }
+
+    #[test]
+    fn test_unsupported_features() {
+        let mut shards = shards(2);
+        for public_values in shards.iter_mut() {
+            public_values.features = BabyBear::from_canonical_u32(1 << 30 | INPUT_DIGEST_FEATURE);
+        }
+        match verify_shard_transitions(&shards, BabyBear::from_canonical_u32(PC_START)) {
+            Err(MachineVerificationError::UnsupportedFeatures { bits }) => {
+                assert_eq!(bits, 1 << 30)
+            }
+            result => panic!("expected unsupported features, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_features_mismatch() {
+        let mut shards = shards(2);
+        shards[1].features = BabyBear::from_canonical_u32(INPUT_DIGEST_FEATURE);
+        assert_rejected(&shards, "features mismatch");
+    }
+
+    #[test]
+    fn test_input_digest_without_feature() {
+        let mut shards = shards(1);
+        shards[0].input_digest[0] = Word::from(1u32);
+        assert_rejected(&shards, "input digest without the input digest feature");
+
+        shards[0].features = BabyBear::from_canonical_u32(INPUT_DIGEST_FEATURE);
+        verify_shard_transitions(&shards, BabyBear::from_canonical_u32(PC_START)).unwrap();
+    }
+
+    #[test]
+    fn test_config_digest_without_feature() {
+        let mut shards = shards(1);
+        shards[0].config_digest[0] = Word::from(1u32);
+        assert_rejected(&shards, "config digest without the config digest feature");
+
+        shards[0].features = BabyBear::from_canonical_u32(CONFIG_DIGEST_FEATURE);
+        verify_shard_transitions(&shards, BabyBear::from_canonical_u32(PC_START)).unwrap();
+    }
+
+    #[test]
+    fn test_config_digest_mismatch() {
+        let mut shards = shards(2);
+        for public_values in shards.iter_mut() {
+            public_values.features = BabyBear::from_canonical_u32(CONFIG_DIGEST_FEATURE);
+        }
+        shards[1].config_digest[0] = Word::from(1u32);
+        assert_rejected(&shards, "digest or exit code mismatch");
+    }
+}
@@ -157,12 +157,16 @@ mod tests {
     use p3_field::split_32 as split_32_gt;
     use p3_field::AbstractField;
     use p3_symmetric::Hash;
+    use sha2::{Digest, Sha256};
     use sp1_recursion_compiler::config::OuterConfig;
-    use sp1_recursion_compiler::constraints::ConstraintCompiler;
+    use sp1_recursion_compiler::constraints::{evaluate_constraints, ConstraintCompiler};
     use sp1_recursion_compiler::ir::SymbolicExt;
     use sp1_recursion_compiler::ir::{Builder, Witness};
     use sp1_recursion_core::stark::config::{outer_perm, OuterChallenger};
-    use sp1_recursion_gnark_ffi::PlonkBn254Prover;
+    use sp1_recursion_gnark_ffi::ultraplonk::{
+        export_ultraplonk, UltraPlonkCircuit, UltraPlonkExporter, UltraPlonkWitness,
+    };
+    use sp1_recursion_gnark_ffi::JsonConstraintWriter;
 
     use super::reduce_32;
     use super::split_32;
@@ -182,7 +186,7 @@ mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
     #[test]
@@ -199,7 +203,7 @@ mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
     #[test]
@@ -217,11 +221,11 @@ mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
-    #[test]
-    fn test_challenger() {
+    /// A circuit sampling from the challenger and checking the samples against the native ones.
+    fn challenger_circuit() -> Builder<OuterConfig> {
         let perm = outer_perm();
         let mut challenger = OuterChallenger::new(perm).unwrap();
         let a = BabyBear::from_canonical_usize(1);
@@ -252,9 +256,72 @@ mod tests {
         let result2 = challenger.sample(&mut builder);
         builder.assert_felt_eq(gt2, result2);
 
+        builder
+    }
+
+    #[test]
+    fn test_challenger() {
+        let builder = challenger_circuit();
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
+    }
+
+    /// The challenger circuit solved by gnark through the FFI, as a smoke test of the Rust
+    /// evaluation of the constraints the other tests use.
+    #[test]
+    #[cfg(feature = "gnark-ffi-test")]
+    fn test_challenger_gnark_ffi() {
+        let builder = challenger_circuit();
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        sp1_recursion_gnark_ffi::PlonkBn254Prover::test::<OuterConfig>(
+            constraints,
+            Witness::default(),
+        );
+    }
+
+    #[test]
+    fn test_challenger_streamed() {
+        let operations = challenger_circuit().operations;
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(operations.clone());
+        let expected: [u8; 32] = Sha256::digest(serde_json::to_vec(&constraints).unwrap()).into();
+
+        let mut writer = JsonConstraintWriter::new(Sha256::new());
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        backend.emit_into(operations, &mut writer);
+        assert_eq!(writer.len(), constraints.len());
+        let streamed: [u8; 32] = writer.finish().unwrap().finalize().into();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_challenger_ultraplonk() {
+        let operations = challenger_circuit().operations;
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(operations.clone());
+        let (circuit, witness) = export_ultraplonk(&constraints, &Witness::default()).unwrap();
+        circuit.check(&witness).unwrap();
+
+        // The circuit and its witness round-trip through their JSON files.
+        let json = serde_json::to_string(&circuit).unwrap();
+        assert_eq!(
+            serde_json::from_str::<UltraPlonkCircuit>(&json).unwrap(),
+            circuit
+        );
+        let json = serde_json::to_string(&witness).unwrap();
+        assert_eq!(
+            serde_json::from_str::<UltraPlonkWitness>(&json).unwrap(),
+            witness
+        );
+
+        // Exporting the constraints as they are emitted gives the same circuit.
+        let mut exporter = UltraPlonkExporter::new(Witness::default());
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        backend.emit_into(operations, &mut exporter);
+        assert_eq!(exporter.finish().unwrap(), (circuit, witness));
     }
 
     #[test]
@@ -296,6 +363,6 @@ mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 }
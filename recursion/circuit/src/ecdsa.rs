@@ -0,0 +1,384 @@
+//! Verification of secp256k1 ECDSA signatures over BN254.
+//!
+//! The coordinates of points and the scalars are emulated with [crate::nonnative]. The
+//! verification computes `u1·G + u2·Q` with a double-and-add over the bits of both scalars at once,
+//! starting from an offset point whose multiple is subtracted at the end, so that the incomplete
+//! addition formulas never meet the point at infinity. An addition of two points with the same
+//! x-coordinate has no witness, which rejects the signature.
+//!
+//! The signature is read from the witness, see [write_secp256k1_ecdsa_witness].
+
+use num_bigint::{BigInt, BigUint};
+use p3_bn254_fr::Bn254Fr;
+use p3_field::AbstractField;
+use sp1_recursion_compiler::config::OuterConfig;
+use sp1_recursion_compiler::constraints::ConstraintCompiler;
+use sp1_recursion_compiler::ir::{Builder, Config, SymbolicVar, Var, Witness};
+
+use crate::nonnative::{Emulated, EmulatedArithmetic, NativeEmulator, LIMB_BITS, NUM_LIMBS};
+
+/// An affine point of secp256k1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Secp256k1Point {
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+impl Secp256k1Point {
+    pub fn generator() -> Self {
+        Self {
+            x: hex_biguint("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            y: hex_biguint("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+        }
+    }
+
+    /// The point of an uncompressed SEC1 encoding, `0x04 || x || y`.
+    pub fn from_sec1_uncompressed(bytes: &[u8; 65]) -> Option<Self> {
+        let point = Self {
+            x: BigUint::from_bytes_be(&bytes[1..33]),
+            y: BigUint::from_bytes_be(&bytes[33..]),
+        };
+        (bytes[0] == 0x04 && point.is_on_curve()).then_some(point)
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let p = base_modulus();
+        self.x < p && self.y < p && (&self.y * &self.y) % &p == (self.x.pow(3) + 7u32) % &p
+    }
+
+    fn double(&self) -> Self {
+        let p = base_modulus();
+        let lambda = 3u32 * &self.x * &self.x * inverse(&(2u32 * &self.y), &p) % &p;
+        self.with_slope(&lambda, &self.x)
+    }
+
+    /// The sum of `self` and the point with x-coordinate `x` on the line of slope `lambda`.
+    fn with_slope(&self, lambda: &BigUint, x: &BigUint) -> Self {
+        let p = base_modulus();
+        let x3 = (lambda * lambda + 2u32 * &p - &self.x - x) % &p;
+        let y3 = (lambda * (&self.x + &p - &x3) + &p - &self.y) % &p;
+        Self { x: x3, y: y3 }
+    }
+
+    fn negate(&self) -> Self {
+        Self {
+            x: self.x.clone(),
+            y: (base_modulus() - &self.y) % base_modulus(),
+        }
+    }
+}
+
+/// An ECDSA signature `(r, s)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcdsaSignature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+impl EcdsaSignature {
+    /// The signature of a fixed-size encoding, `r || s` in big-endian.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Self {
+        Self {
+            r: BigUint::from_bytes_be(&bytes[..32]),
+            s: BigUint::from_bytes_be(&bytes[32..]),
+        }
+    }
+}
+
+fn hex_biguint(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+/// The modulus of the base field of secp256k1.
+pub fn base_modulus() -> BigUint {
+    hex_biguint("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f")
+}
+
+/// The order of secp256k1, the modulus of its scalar field.
+pub fn scalar_modulus() -> BigUint {
+    hex_biguint("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+}
+
+fn inverse(a: &BigUint, modulus: &BigUint) -> BigUint {
+    a.modpow(&(modulus - 2u32), modulus)
+}
+
+/// The starting point of the double-and-add: the point with the smallest x-coordinate.
+fn offset_point() -> Secp256k1Point {
+    let p = base_modulus();
+    let sqrt_exponent = (&p + 1u32) / 4u32;
+    (1u32..)
+        .map(|x| {
+            let x = BigUint::from(x);
+            let y = (x.pow(3) + 7u32).modpow(&sqrt_exponent, &p);
+            Secp256k1Point { x, y }
+        })
+        .find(Secp256k1Point::is_on_curve)
+        .unwrap()
+}
+
+/// A point with emulated coordinates.
+#[derive(Debug, Clone, Copy)]
+struct PointVariable<V> {
+    x: Emulated<V>,
+    y: Emulated<V>,
+}
+
+fn constant_point<A: EmulatedArithmetic>(
+    arithmetic: &mut A,
+    point: &Secp256k1Point,
+) -> PointVariable<A::Var> {
+    PointVariable {
+        x: arithmetic.constant(&point.x),
+        y: arithmetic.constant(&point.y),
+    }
+}
+
+/// The sum of two points with distinct x-coordinates.
+fn add<A: EmulatedArithmetic>(
+    arithmetic: &mut A,
+    a: &PointVariable<A::Var>,
+    b: &PointVariable<A::Var>,
+) -> PointVariable<A::Var> {
+    let p = base_modulus();
+    let dx = arithmetic.evaluate(&p, &[], &[(&b.x, 1), (&a.x, -1)], 0);
+    let dx_inverse = arithmetic.inverse(&p, &dx);
+    let lambda = arithmetic.evaluate(
+        &p,
+        &[(&b.y, &dx_inverse, 1), (&a.y, &dx_inverse, -1)],
+        &[],
+        0,
+    );
+    with_slope(arithmetic, a, &lambda, &b.x)
+}
+
+fn double<A: EmulatedArithmetic>(
+    arithmetic: &mut A,
+    a: &PointVariable<A::Var>,
+) -> PointVariable<A::Var> {
+    let p = base_modulus();
+    // `2y` is not zero since secp256k1 has no point of order two.
+    let lambda = match (arithmetic.value(&a.x), arithmetic.value(&a.y)) {
+        (Some(x), Some(y)) => Some(3u32 * &x * &x * inverse(&(2u32 * y), &p) % &p),
+        _ => None,
+    };
+    let lambda = arithmetic.hint(lambda);
+    arithmetic.assert_zero(
+        Some(&p),
+        &[(&lambda, &a.y, 2), (&a.x, &a.x, -3)],
+        &[],
+        &BigInt::default(),
+    );
+    with_slope(arithmetic, a, &lambda, &a.x)
+}
+
+/// The sum of `a` and the point with x-coordinate `x` on the line of slope `lambda` through `a`.
+fn with_slope<A: EmulatedArithmetic>(
+    arithmetic: &mut A,
+    a: &PointVariable<A::Var>,
+    lambda: &Emulated<A::Var>,
+    x: &Emulated<A::Var>,
+) -> PointVariable<A::Var> {
+    let p = base_modulus();
+    let x3 = arithmetic.evaluate(&p, &[(lambda, lambda, 1)], &[(&a.x, -1), (x, -1)], 0);
+    let y3 = arithmetic.evaluate(
+        &p,
+        &[(lambda, &a.x, 1), (lambda, &x3, -1)],
+        &[(&a.y, -1)],
+        0,
+    );
+    PointVariable { x: x3, y: y3 }
+}
+
+fn select_point<A: EmulatedArithmetic>(
+    arithmetic: &mut A,
+    bit: A::Var,
+    a: &PointVariable<A::Var>,
+    b: &PointVariable<A::Var>,
+) -> PointVariable<A::Var> {
+    PointVariable {
+        x: arithmetic.select(bit, &a.x, &b.x),
+        y: arithmetic.select(bit, &a.y, &b.y),
+    }
+}
+
+/// Reads a signature from the witness and verifies it against `pubkey` and the big-endian
+/// integer `digest`.
+fn verify<A: EmulatedArithmetic>(
+    arithmetic: &mut A,
+    pubkey: &Secp256k1Point,
+    digest: &Emulated<A::Var>,
+    signature: Option<&EcdsaSignature>,
+) {
+    let p = base_modulus();
+    let n = scalar_modulus();
+
+    let r = arithmetic.hint(signature.map(|signature| signature.r.clone()));
+    let s = arithmetic.hint(signature.map(|signature| signature.s.clone()));
+    arithmetic.assert_canonical(&n, &r);
+    arithmetic.assert_canonical(&n, &s);
+    arithmetic.inverse(&n, &r);
+
+    // The public key is on the curve.
+    let q = constant_point(arithmetic, pubkey);
+    let x_squared = arithmetic.mul(&p, &q.x, &q.x);
+    arithmetic.assert_zero(
+        Some(&p),
+        &[(&q.y, &q.y, 1), (&q.x, &x_squared, -1)],
+        &[],
+        &BigInt::from(-7),
+    );
+
+    let w = arithmetic.inverse(&n, &s);
+    let u1 = arithmetic.mul(&n, digest, &w);
+    let u2 = arithmetic.mul(&n, &r, &w);
+    let u1_bits = arithmetic.bits(&u1);
+    let u2_bits = arithmetic.bits(&u2);
+
+    // `u1·G + u2·Q + 2^256·T`, where `T` is the offset point. The table of the points added for
+    // each pair of bits is `[G, G, Q, G + Q]`, where adding `G` for two zero bits is discarded.
+    let g = constant_point(arithmetic, &Secp256k1Point::generator());
+    let g_plus_q = add(arithmetic, &g, &q);
+    let offset = offset_point();
+    let mut acc = constant_point(arithmetic, &offset);
+    for i in (0..NUM_LIMBS * LIMB_BITS).rev() {
+        acc = double(arithmetic, &acc);
+        let with_q = select_point(arithmetic, u1_bits[i], &g_plus_q, &q);
+        let point = select_point(arithmetic, u2_bits[i], &with_q, &g);
+        let sum = add(arithmetic, &acc, &point);
+        let any = arithmetic.or(u1_bits[i], u2_bits[i]);
+        acc = select_point(arithmetic, any, &sum, &acc);
+    }
+    let correction = (0..NUM_LIMBS * LIMB_BITS)
+        .fold(offset, |point, _| point.double())
+        .negate();
+    let correction = constant_point(arithmetic, &correction);
+    let point = add(arithmetic, &acc, &correction);
+
+    // `r` is the x-coordinate of the point reduced modulo the order.
+    arithmetic.assert_canonical(&p, &point.x);
+    arithmetic.assert_congruent(&n, &point.x, &r);
+}
+
+pub trait EcdsaCircuitBuilder<C: Config> {
+    /// Verifies a secp256k1 ECDSA signature by `pubkey` over `digest`, the 32 big-endian bytes of
+    /// the signed hash, which must be range checked to 8 bits. The signature is read from the
+    /// witness, see [write_secp256k1_ecdsa_witness].
+    fn verify_secp256k1_ecdsa(&mut self, pubkey: &Secp256k1Point, digest: &[Var<C::N>; 32]);
+}
+
+impl<C: Config> EcdsaCircuitBuilder<C> for Builder<C> {
+    fn verify_secp256k1_ecdsa(&mut self, pubkey: &Secp256k1Point, digest: &[Var<C::N>; 32]) {
+        let limbs = core::array::from_fn(|i| {
+            let limb = (0..LIMB_BITS / 8).fold(SymbolicVar::from(C::N::zero()), |limb, j| {
+                limb + digest[31 - 8 * i - j] * C::N::from_canonical_u64(1 << (8 * j))
+            });
+            self.eval(limb)
+        });
+        verify(self, pubkey, &Emulated { limbs }, None);
+    }
+}
+
+/// Writes the values read by [EcdsaCircuitBuilder::verify_secp256k1_ecdsa] to the witness.
+pub fn write_secp256k1_ecdsa_witness<C: Config>(
+    witness: &mut Witness<C>,
+    pubkey: &Secp256k1Point,
+    digest: &[u8; 32],
+    signature: &EcdsaSignature,
+) {
+    let mut emulator = NativeEmulator::<C::N>::default();
+    let digest = Emulated::from_biguint(&BigUint::from_bytes_be(digest));
+    verify(&mut emulator, pubkey, &digest, Some(signature));
+    witness.vars.extend(emulator.hints);
+}
+
+/// The number of constraints of [EcdsaCircuitBuilder::verify_secp256k1_ecdsa].
+pub fn secp256k1_ecdsa_num_constraints() -> usize {
+    let num_constraints = |verify: bool| {
+        let mut builder = Builder::<OuterConfig>::default();
+        let digest = core::array::from_fn(|_| builder.eval(Bn254Fr::zero()));
+        if verify {
+            builder.verify_secp256k1_ecdsa(&Secp256k1Point::generator().double(), &digest);
+        }
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        backend.emit(builder.operations).len()
+    };
+    num_constraints(true) - num_constraints(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use k256::ecdsa::{Signature, SigningKey};
+    use p3_bn254_fr::Bn254Fr;
+    use p3_field::AbstractField;
+    use sp1_recursion_compiler::config::OuterConfig;
+    use sp1_recursion_compiler::constraints::ConstraintCompiler;
+    use sp1_recursion_compiler::ir::{Builder, Var, Witness};
+    use sp1_recursion_gnark_ffi::PlonkBn254Prover;
+
+    use super::*;
+
+    fn operator() -> (SigningKey, Secp256k1Point) {
+        let key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let encoded = key.verifying_key().to_encoded_point(false);
+        let pubkey =
+            Secp256k1Point::from_sec1_uncompressed(encoded.as_bytes().try_into().unwrap()).unwrap();
+        (key, pubkey)
+    }
+
+    fn test_ecdsa(pubkey: &Secp256k1Point, digest: &[u8; 32], signature: &EcdsaSignature) {
+        let mut builder = Builder::<OuterConfig>::default();
+        let digest_vars: [Var<_>; 32] =
+            core::array::from_fn(|i| builder.eval(Bn254Fr::from_canonical_u8(digest[i])));
+        builder.verify_secp256k1_ecdsa(pubkey, &digest_vars);
+
+        let mut witness = Witness::default();
+        write_secp256k1_ecdsa_witness(&mut witness, pubkey, digest, signature);
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        PlonkBn254Prover::test::<OuterConfig>(constraints, witness);
+    }
+
+    fn sign(key: &SigningKey, digest: &[u8; 32]) -> EcdsaSignature {
+        let signature: Signature = key.sign_prehash(digest).unwrap();
+        EcdsaSignature::from_bytes(signature.to_bytes()[..].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_secp256k1_ecdsa() {
+        let (key, pubkey) = operator();
+        let digest = [0xab; 32];
+        test_ecdsa(&pubkey, &digest, &sign(&key, &digest));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_secp256k1_ecdsa_invalid_signature() {
+        let (key, pubkey) = operator();
+        let mut digest = [0xab; 32];
+        let signature = sign(&key, &digest);
+        digest[0] ^= 1;
+        test_ecdsa(&pubkey, &digest, &signature);
+    }
+
+    #[test]
+    fn test_secp256k1_ecdsa_constraints() {
+        println!(
+            "secp256k1 ecdsa verification: {} constraints",
+            secp256k1_ecdsa_num_constraints()
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_point() {
+        let (_, pubkey) = operator();
+        assert!(pubkey.is_on_curve());
+        assert!(Secp256k1Point::generator().is_on_curve());
+        assert!(offset_point().is_on_curve());
+        let g = Secp256k1Point::generator();
+        assert!(g.double().double().is_on_curve());
+        assert!(g.double().negate().is_on_curve());
+    }
+}
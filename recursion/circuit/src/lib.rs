@@ -7,9 +7,12 @@
 pub mod challenger;
 pub mod constraints;
 pub mod domain;
+pub mod ecdsa;
 pub mod fri;
 pub mod mmcs;
+pub mod nonnative;
 pub mod poseidon2;
+pub mod sha256;
 pub mod stark;
 pub mod types;
 pub mod utils;
@@ -131,10 +134,13 @@ mod tests {
         let mut builder = Builder::<OuterConfig>::default();
         let vkey_hash_bn254 = Bn254Fr::from_canonical_u32(1345237507);
         let commited_values_digest_bn254 = Bn254Fr::from_canonical_u32(102);
+        let input_digest_bn254 = Bn254Fr::from_canonical_u32(2024);
         let vkey_hash = builder.eval(vkey_hash_bn254);
         let commited_values_digest = builder.eval(commited_values_digest_bn254);
+        let input_digest = builder.eval(input_digest_bn254);
         builder.commit_vkey_hash_circuit(vkey_hash);
         builder.commit_commited_values_digest_circuit(commited_values_digest);
+        builder.commit_input_digest_circuit(input_digest);
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
@@ -142,6 +148,7 @@ mod tests {
         let mut witness = Witness::default();
         witness.write_vkey_hash(vkey_hash_bn254);
         witness.write_commited_values_digest(commited_values_digest_bn254);
+        witness.write_input_digest(input_digest_bn254);
 
         PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), witness);
     }
@@ -185,4 +192,21 @@ mod tests {
 
         PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), witness);
     }
+
+    #[test]
+    #[should_panic]
+    fn test_commit_input_digest_fail() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let input_digest_bn254 = Bn254Fr::from_canonical_u32(2024);
+        let input_digest = builder.eval(input_digest_bn254);
+        builder.commit_input_digest_circuit(input_digest);
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+
+        let mut witness = Witness::default();
+        witness.write_input_digest(input_digest_bn254 + Bn254Fr::one());
+
+        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), witness);
+    }
 }
@@ -0,0 +1,553 @@
+//! Arithmetic over prime fields larger than BN254, emulated with 64-bit limbs.
+//!
+//! A value is represented by four limbs range checked to 64 bits, so it is less than 2^256 but
+//! not necessarily reduced. Every operation is checked as an integer identity
+//! `Σ s·a·b + Σ k·c + constant = q·p`: the result and the quotient `q` are read from the witness,
+//! and the identity is checked column by column, with witnessed carries between columns, so that
+//! every value stays far below the BN254 modulus and equality in BN254 implies equality over the
+//! integers.
+//!
+//! Operations are written once against [EmulatedArithmetic], which [Builder] implements by
+//! constraining them and [NativeEmulator] by computing the values the circuit reads from the
+//! witness, in the same order.
+
+use num_bigint::{BigInt, BigUint, Sign};
+use p3_field::AbstractField;
+use sp1_recursion_compiler::ir::{Builder, Config, SymbolicVar, Var};
+
+/// The number of limbs of an emulated value.
+pub const NUM_LIMBS: usize = 4;
+
+/// The number of bits of a limb.
+pub const LIMB_BITS: usize = 64;
+
+/// The number of limbs of the quotient of an identity.
+const NUM_QUOTIENT_LIMBS: usize = 5;
+
+/// Carries are witnessed shifted by 2^CARRY_OFFSET_BITS, so that they are non-negative.
+const CARRY_OFFSET_BITS: usize = 79;
+
+/// The number of bits carries are range checked to after the shift.
+const CARRY_BITS: usize = CARRY_OFFSET_BITS + 1;
+
+/// The maximum sum of the absolute values of the coefficients of an identity, for its quotient and
+/// carries to fit their range checks.
+const MAX_COEFFICIENTS: u64 = 64;
+
+/// A value of an emulated field as little-endian 64-bit limbs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Emulated<V> {
+    pub limbs: [V; NUM_LIMBS],
+}
+
+impl Emulated<u64> {
+    /// The limbs of `value`, which must be less than 2^256.
+    pub fn from_biguint(value: &BigUint) -> Self {
+        assert!(value.bits() <= (NUM_LIMBS * LIMB_BITS) as u64);
+        Self {
+            limbs: limbs(value, NUM_LIMBS).try_into().unwrap(),
+        }
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_slice(
+            &self
+                .limbs
+                .iter()
+                .flat_map(|limb| [*limb as u32, (*limb >> 32) as u32])
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// The term `coefficient·a·b` of an identity.
+pub type Product<'a, V> = (&'a Emulated<V>, &'a Emulated<V>, i64);
+
+/// The term `coefficient·a` of an identity.
+pub type Term<'a, V> = (&'a Emulated<V>, i64);
+
+/// Arithmetic over emulated fields, either constrained in a circuit or computed natively.
+pub trait EmulatedArithmetic {
+    /// A limb or a bit.
+    type Var: Copy;
+
+    /// The value of `a`, known outside of a circuit.
+    fn value(&self, a: &Emulated<Self::Var>) -> Option<BigUint>;
+
+    fn constant(&mut self, value: &BigUint) -> Emulated<Self::Var>;
+
+    /// Reads a value from the witness and range checks its limbs. `value` is the value written to
+    /// the witness, known outside of a circuit.
+    fn hint(&mut self, value: Option<BigUint>) -> Emulated<Self::Var>;
+
+    /// Asserts that `Σ products + Σ terms + constant` is a multiple of `modulus`, or zero if there is
+    /// no modulus.
+    fn assert_zero(
+        &mut self,
+        modulus: Option<&BigUint>,
+        products: &[Product<Self::Var>],
+        terms: &[Term<Self::Var>],
+        constant: &BigInt,
+    );
+
+    /// The 256 little-endian bits of `a`.
+    fn bits(&mut self, a: &Emulated<Self::Var>) -> Vec<Self::Var>;
+
+    /// `a` if `bit` is one, `b` if it is zero.
+    fn select(
+        &mut self,
+        bit: Self::Var,
+        a: &Emulated<Self::Var>,
+        b: &Emulated<Self::Var>,
+    ) -> Emulated<Self::Var>;
+
+    fn or(&mut self, a: Self::Var, b: Self::Var) -> Self::Var;
+
+    /// `Σ products + Σ terms + constant` modulo `modulus`.
+    fn evaluate(
+        &mut self,
+        modulus: &BigUint,
+        products: &[Product<Self::Var>],
+        terms: &[Term<Self::Var>],
+        constant: i64,
+    ) -> Emulated<Self::Var> {
+        let value = identity_value(self, products, terms, &constant.into())
+            .map(|value| reduce(&value, modulus));
+        let result = self.hint(value);
+        let mut terms = terms.to_vec();
+        terms.push((&result, -1));
+        self.assert_zero(Some(modulus), products, &terms, &constant.into());
+        result
+    }
+
+    fn mul(
+        &mut self,
+        modulus: &BigUint,
+        a: &Emulated<Self::Var>,
+        b: &Emulated<Self::Var>,
+    ) -> Emulated<Self::Var> {
+        self.evaluate(modulus, &[(a, b, 1)], &[], 0)
+    }
+
+    /// The inverse of `a`, which must not be zero modulo `modulus`.
+    fn inverse(&mut self, modulus: &BigUint, a: &Emulated<Self::Var>) -> Emulated<Self::Var> {
+        let value = self.value(a).map(|a| a.modpow(&(modulus - 2u32), modulus));
+        let inverse = self.hint(value);
+        self.assert_zero(Some(modulus), &[(a, &inverse, 1)], &[], &BigInt::from(-1));
+        inverse
+    }
+
+    /// Asserts that `a` is less than `modulus`.
+    fn assert_canonical(&mut self, modulus: &BigUint, a: &Emulated<Self::Var>) {
+        // `a + d = modulus - 1` for some `d` of 256 bits.
+        let max = modulus - 1u32;
+        let value = self.value(a).map(|a| {
+            if a <= max {
+                &max - a
+            } else {
+                BigUint::default()
+            }
+        });
+        let d = self.hint(value);
+        let constant = -BigInt::from(max);
+        self.assert_zero(None, &[], &[(a, 1), (&d, 1)], &constant);
+    }
+
+    /// Asserts that `a` and `b` are equal modulo `modulus`.
+    fn assert_congruent(
+        &mut self,
+        modulus: &BigUint,
+        a: &Emulated<Self::Var>,
+        b: &Emulated<Self::Var>,
+    ) {
+        self.assert_zero(Some(modulus), &[], &[(a, 1), (b, -1)], &BigInt::default());
+    }
+}
+
+/// The value of `Σ products + Σ terms + constant`, known outside of a circuit.
+fn identity_value<A: EmulatedArithmetic + ?Sized>(
+    arithmetic: &A,
+    products: &[Product<A::Var>],
+    terms: &[Term<A::Var>],
+    constant: &BigInt,
+) -> Option<BigInt> {
+    let mut value = constant.clone();
+    for (a, b, coefficient) in products.iter() {
+        let a = BigInt::from(arithmetic.value(a)?);
+        let b = BigInt::from(arithmetic.value(b)?);
+        value += a * b * coefficient;
+    }
+    for (a, coefficient) in terms.iter() {
+        value += BigInt::from(arithmetic.value(a)?) * coefficient;
+    }
+    Some(value)
+}
+
+/// The non-negative remainder of `value` by `modulus`.
+fn reduce(value: &BigInt, modulus: &BigUint) -> BigUint {
+    let modulus = BigInt::from(modulus.clone());
+    let remainder = ((value % &modulus) + &modulus) % &modulus;
+    remainder.magnitude().clone()
+}
+
+/// The `n` least significant 64-bit limbs of `value`.
+fn limbs(value: &BigUint, n: usize) -> Vec<u64> {
+    let mut limbs = value.to_u64_digits();
+    limbs.resize(n, 0);
+    limbs
+}
+
+/// The constant of an identity, shifted by the multiple of the modulus making the sum of the
+/// identity non-negative, and the number of columns of the identity.
+fn identity_layout(
+    modulus: Option<&BigUint>,
+    product_coefficients: impl Iterator<Item = i64>,
+    term_coefficients: impl Iterator<Item = i64>,
+    constant: &BigInt,
+) -> (BigInt, usize) {
+    let max: BigUint = (BigUint::from(1u32) << (NUM_LIMBS * LIMB_BITS)) - 1u32;
+    let mut negative = BigUint::default();
+    let mut total = 0;
+    let mut num_columns = 1;
+    for coefficient in product_coefficients {
+        total += coefficient.unsigned_abs();
+        if coefficient < 0 {
+            negative += &max * &max * coefficient.unsigned_abs();
+        }
+        num_columns = num_columns.max(2 * NUM_LIMBS - 1);
+    }
+    for coefficient in term_coefficients {
+        total += coefficient.unsigned_abs();
+        if coefficient < 0 {
+            negative += &max * coefficient.unsigned_abs();
+        }
+        num_columns = num_columns.max(NUM_LIMBS);
+    }
+    assert!(
+        total <= MAX_COEFFICIENTS,
+        "the coefficients of an emulated identity are too large"
+    );
+
+    let constant = match modulus {
+        Some(modulus) => {
+            if constant.sign() == Sign::Minus {
+                negative += constant.magnitude();
+            }
+            let multiple = (negative + modulus - 1u32) / modulus;
+            num_columns = num_columns.max(NUM_QUOTIENT_LIMBS + NUM_LIMBS - 1);
+            constant + BigInt::from(multiple * modulus)
+        }
+        None => constant.clone(),
+    };
+    let constant_limbs = constant.magnitude().bits().div_ceil(LIMB_BITS as u64) as usize;
+    (constant, num_columns.max(constant_limbs))
+}
+
+/// The signed limbs of `constant` in `num_columns` columns.
+fn constant_columns(constant: &BigInt, num_columns: usize) -> Vec<BigInt> {
+    limbs(constant.magnitude(), num_columns)
+        .into_iter()
+        .map(|limb| match constant.sign() {
+            Sign::Minus => -BigInt::from(limb),
+            _ => BigInt::from(limb),
+        })
+        .collect()
+}
+
+fn biguint_to_field<N: AbstractField>(value: &BigUint) -> N {
+    let base = N::from_canonical_u64(1 << 32) * N::from_canonical_u64(1 << 32);
+    value
+        .to_u64_digits()
+        .iter()
+        .rev()
+        .fold(N::zero(), |acc, limb| {
+            acc * base.clone() + N::from_canonical_u64(*limb)
+        })
+}
+
+fn bigint_to_field<N: AbstractField>(value: &BigInt) -> N {
+    let magnitude = biguint_to_field::<N>(value.magnitude());
+    match value.sign() {
+        Sign::Minus => -magnitude,
+        _ => magnitude,
+    }
+}
+
+/// Computes the values read from the witness by the emulated arithmetic of a circuit.
+#[derive(Debug, Clone)]
+pub struct NativeEmulator<N> {
+    /// The values to write to the witness variables, in order.
+    pub hints: Vec<N>,
+}
+
+impl<N> Default for NativeEmulator<N> {
+    fn default() -> Self {
+        Self { hints: Vec::new() }
+    }
+}
+
+impl<N: AbstractField> EmulatedArithmetic for NativeEmulator<N> {
+    type Var = u64;
+
+    fn value(&self, a: &Emulated<u64>) -> Option<BigUint> {
+        Some(a.to_biguint())
+    }
+
+    fn constant(&mut self, value: &BigUint) -> Emulated<u64> {
+        Emulated::from_biguint(value)
+    }
+
+    fn hint(&mut self, value: Option<BigUint>) -> Emulated<u64> {
+        let value = Emulated {
+            limbs: limbs(&value.unwrap(), NUM_LIMBS).try_into().unwrap(),
+        };
+        self.hints
+            .extend(value.limbs.iter().map(|limb| N::from_canonical_u64(*limb)));
+        value
+    }
+
+    fn assert_zero(
+        &mut self,
+        modulus: Option<&BigUint>,
+        products: &[Product<u64>],
+        terms: &[Term<u64>],
+        constant: &BigInt,
+    ) {
+        let (constant, num_columns) = identity_layout(
+            modulus,
+            products.iter().map(|product| product.2),
+            terms.iter().map(|term| term.1),
+            constant,
+        );
+
+        let mut columns = constant_columns(&constant, num_columns);
+        for (a, b, coefficient) in products.iter() {
+            for (i, a) in a.limbs.iter().enumerate() {
+                for (j, b) in b.limbs.iter().enumerate() {
+                    columns[i + j] += BigInt::from(*a) * b * coefficient;
+                }
+            }
+        }
+        for (a, coefficient) in terms.iter() {
+            for (i, a) in a.limbs.iter().enumerate() {
+                columns[i] += BigInt::from(*a) * coefficient;
+            }
+        }
+
+        if let Some(modulus) = modulus {
+            // The sum is non-negative by the choice of the constant. It is not a multiple of the
+            // modulus if the identity doesn't hold, and the circuit rejects the witness.
+            let value = identity_value(self, products, terms, &constant).unwrap();
+            let quotient = value.to_biguint().unwrap_or_default() / modulus;
+            let quotient = limbs(&quotient, NUM_QUOTIENT_LIMBS);
+            self.hints
+                .extend(quotient.iter().map(|limb| N::from_canonical_u64(*limb)));
+            for (i, q) in quotient.iter().enumerate() {
+                for (j, p) in limbs(modulus, NUM_LIMBS).iter().enumerate() {
+                    columns[i + j] -= BigInt::from(*q) * p;
+                }
+            }
+        }
+
+        let offset = BigInt::from(1u32) << CARRY_OFFSET_BITS;
+        let mut carry = BigInt::default();
+        for column in columns[..num_columns - 1].iter() {
+            carry = (column + carry) >> LIMB_BITS;
+            self.hints.push(bigint_to_field(&(&carry + &offset)));
+        }
+    }
+
+    fn bits(&mut self, a: &Emulated<u64>) -> Vec<u64> {
+        a.limbs
+            .iter()
+            .flat_map(|limb| (0..LIMB_BITS).map(move |i| (limb >> i) & 1))
+            .collect()
+    }
+
+    fn select(&mut self, bit: u64, a: &Emulated<u64>, b: &Emulated<u64>) -> Emulated<u64> {
+        if bit == 1 {
+            *a
+        } else {
+            *b
+        }
+    }
+
+    fn or(&mut self, a: u64, b: u64) -> u64 {
+        a | b
+    }
+}
+
+/// Emulated arithmetic in a circuit over BN254.
+impl<C: Config> EmulatedArithmetic for Builder<C> {
+    type Var = Var<C::N>;
+
+    fn value(&self, _: &Emulated<Var<C::N>>) -> Option<BigUint> {
+        None
+    }
+
+    fn constant(&mut self, value: &BigUint) -> Emulated<Var<C::N>> {
+        let limbs = limbs(value, NUM_LIMBS);
+        Emulated {
+            limbs: core::array::from_fn(|i| self.eval(C::N::from_canonical_u64(limbs[i]))),
+        }
+    }
+
+    fn hint(&mut self, _: Option<BigUint>) -> Emulated<Var<C::N>> {
+        let limbs = core::array::from_fn(|_| {
+            let limb = self.witness_var();
+            self.num2bits_v_circuit(limb, LIMB_BITS);
+            limb
+        });
+        Emulated { limbs }
+    }
+
+    fn assert_zero(
+        &mut self,
+        modulus: Option<&BigUint>,
+        products: &[Product<Var<C::N>>],
+        terms: &[Term<Var<C::N>>],
+        constant: &BigInt,
+    ) {
+        let (constant, num_columns) = identity_layout(
+            modulus,
+            products.iter().map(|product| product.2),
+            terms.iter().map(|term| term.1),
+            constant,
+        );
+
+        let mut columns: Vec<SymbolicVar<C::N>> = constant_columns(&constant, num_columns)
+            .iter()
+            .map(|limb| bigint_to_field::<C::N>(limb).into())
+            .collect();
+        for (a, b, coefficient) in products.iter() {
+            let coefficient: C::N = bigint_to_field(&BigInt::from(*coefficient));
+            for (i, a) in a.limbs.iter().enumerate() {
+                for (j, b) in b.limbs.iter().enumerate() {
+                    columns[i + j] = columns[i + j].clone() + *a * *b * coefficient.clone();
+                }
+            }
+        }
+        for (a, coefficient) in terms.iter() {
+            let coefficient: C::N = bigint_to_field(&BigInt::from(*coefficient));
+            for (i, a) in a.limbs.iter().enumerate() {
+                columns[i] = columns[i].clone() + *a * coefficient.clone();
+            }
+        }
+
+        if let Some(modulus) = modulus {
+            let modulus = limbs(modulus, NUM_LIMBS);
+            for i in 0..NUM_QUOTIENT_LIMBS {
+                let q = self.witness_var();
+                self.num2bits_v_circuit(q, LIMB_BITS);
+                for (j, p) in modulus.iter().enumerate() {
+                    columns[i + j] = columns[i + j].clone() - q * C::N::from_canonical_u64(*p);
+                }
+            }
+        }
+
+        // Each column and the carry from the previous one are a multiple of 2^64, whose quotient
+        // is carried to the next column. The last column has nothing left to carry.
+        let base: C::N = biguint_to_field(&(BigUint::from(1u32) << LIMB_BITS));
+        let offset: C::N = biguint_to_field(&(BigUint::from(1u32) << CARRY_OFFSET_BITS));
+        let mut carry = SymbolicVar::from(C::N::zero());
+        for column in columns[..num_columns - 1].iter() {
+            let shifted = self.witness_var();
+            self.num2bits_v_circuit(shifted, CARRY_BITS);
+            let next_carry = shifted - offset.clone();
+            self.assert_var_eq(
+                column.clone() + carry - next_carry.clone() * base.clone(),
+                C::N::zero(),
+            );
+            carry = next_carry;
+        }
+        self.assert_var_eq(columns[num_columns - 1].clone() + carry, C::N::zero());
+    }
+
+    fn bits(&mut self, a: &Emulated<Var<C::N>>) -> Vec<Var<C::N>> {
+        a.limbs
+            .iter()
+            .flat_map(|limb| self.num2bits_v_circuit(*limb, LIMB_BITS))
+            .collect()
+    }
+
+    fn select(
+        &mut self,
+        bit: Var<C::N>,
+        a: &Emulated<Var<C::N>>,
+        b: &Emulated<Var<C::N>>,
+    ) -> Emulated<Var<C::N>> {
+        Emulated {
+            limbs: core::array::from_fn(|i| {
+                self.eval(b.limbs[i] + bit * (a.limbs[i] - b.limbs[i]))
+            }),
+        }
+    }
+
+    fn or(&mut self, a: Var<C::N>, b: Var<C::N>) -> Var<C::N> {
+        self.eval(a + b - a * b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use p3_bn254_fr::Bn254Fr;
+    use sp1_recursion_compiler::config::OuterConfig;
+    use sp1_recursion_compiler::constraints::ConstraintCompiler;
+    use sp1_recursion_compiler::ir::{Builder, Witness};
+    use sp1_recursion_gnark_ffi::PlonkBn254Prover;
+
+    use super::{EmulatedArithmetic, NativeEmulator};
+
+    /// The base field of secp256k1.
+    fn modulus() -> BigUint {
+        (BigUint::from(1u32) << 256) - BigUint::from(0x1000003d1u64)
+    }
+
+    /// Checks `(a·b - c)/a` and the canonical form of `a` against `expected`.
+    fn arithmetic<A: EmulatedArithmetic>(arithmetic: &mut A, values: [&BigUint; 4]) {
+        let p = modulus();
+        let [a, b, c, expected] = values.map(|value| arithmetic.constant(value));
+        let ab = arithmetic.mul(&p, &a, &b);
+        let numerator = arithmetic.evaluate(&p, &[], &[(&ab, 1), (&c, -1)], 0);
+        let a_inverse = arithmetic.inverse(&p, &a);
+        let result = arithmetic.mul(&p, &numerator, &a_inverse);
+        arithmetic.assert_canonical(&p, &result);
+        arithmetic.assert_congruent(&p, &result, &expected);
+    }
+
+    fn test_arithmetic(values: [&BigUint; 4]) {
+        let mut builder = Builder::<OuterConfig>::default();
+        arithmetic(&mut builder, values);
+
+        let mut emulator = NativeEmulator::<Bn254Fr>::default();
+        arithmetic(&mut emulator, values);
+        let mut witness = Witness::default();
+        witness.vars.extend(emulator.hints);
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        PlonkBn254Prover::test::<OuterConfig>(constraints, witness);
+    }
+
+    #[test]
+    fn test_emulated_arithmetic() {
+        let p = modulus();
+        let a = &p - 12345u32;
+        let b = (BigUint::from(1u32) << 255) + 77u32;
+        let c = BigUint::from(3u32) << 200;
+        let expected = (&a * &b + &p * 4u32 - &c) % &p * a.modpow(&(&p - 2u32), &p) % &p;
+        test_arithmetic([&a, &b, &c, &expected]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_emulated_arithmetic_wrong_result() {
+        let p = modulus();
+        let a = &p - 12345u32;
+        let b = (BigUint::from(1u32) << 255) + 77u32;
+        let c = BigUint::from(3u32) << 200;
+        let expected = (&a * &b + &p * 4u32 - &c) % &p * a.modpow(&(&p - 2u32), &p) % &p;
+        test_arithmetic([&a, &b, &c, &(expected + 1u32)]);
+    }
+}
@@ -86,10 +86,9 @@ pub mod tests {
     use rand::Rng;
     use sp1_core::utils::{inner_perm, InnerHash};
     use sp1_recursion_compiler::config::OuterConfig;
-    use sp1_recursion_compiler::constraints::ConstraintCompiler;
+    use sp1_recursion_compiler::constraints::{evaluate_constraints, ConstraintCompiler};
     use sp1_recursion_compiler::ir::{Builder, Felt, Var, Witness};
     use sp1_recursion_core::stark::config::{outer_perm, OuterCompress, OuterHash};
-    use sp1_recursion_gnark_ffi::PlonkBn254Prover;
 
     use crate::poseidon2::Poseidon2CircuitBuilder;
     use crate::types::OuterDigestVariable;
@@ -117,7 +116,7 @@ pub mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
     #[test]
@@ -136,7 +135,7 @@ pub mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
     #[test]
@@ -169,7 +168,7 @@ pub mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
     #[test]
@@ -189,7 +188,7 @@ pub mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 
     #[test]
@@ -238,6 +237,6 @@ pub mod tests {
 
         let mut backend = ConstraintCompiler::<OuterConfig>::default();
         let constraints = backend.emit(builder.operations);
-        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
     }
 }
@@ -0,0 +1,275 @@
+//! An implementation of SHA-256 over BN254.
+//!
+//! Words are decomposed into little-endian bits with the num2bits constraint, the message
+//! schedule and the rounds are evaluated over bits, and additions modulo 2^32 are evaluated on the
+//! packed words and decomposed back into bits.
+
+use std::array;
+
+use p3_field::AbstractField;
+use sp1_recursion_compiler::ir::{Builder, Config, SymbolicVar, Var};
+
+/// The initial hash value of SHA-256.
+pub const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants of SHA-256.
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// A 32-bit word as little-endian bits.
+type WordBits<C: Config> = [Var<C::N>; 32];
+
+pub trait Sha256CircuitBuilder<C: Config> {
+    /// Applies the SHA-256 compression function to `state` and a block of 16 message words. The
+    /// words are packed into a single variable each and are range checked to 32 bits.
+    fn sha256_compress(&mut self, state: [Var<C::N>; 8], block: [Var<C::N>; 16]) -> [Var<C::N>; 8];
+
+    /// Hashes `bytes` with SHA-256, returning the 32 bytes of the digest. The bytes are range
+    /// checked to 8 bits, and the padding is fixed by the number of bytes.
+    fn sha256(&mut self, bytes: &[Var<C::N>]) -> [Var<C::N>; 32];
+}
+
+impl<C: Config> Sha256CircuitBuilder<C> for Builder<C> {
+    fn sha256_compress(&mut self, state: [Var<C::N>; 8], block: [Var<C::N>; 16]) -> [Var<C::N>; 8] {
+        let zero: Var<_> = self.eval(C::N::zero());
+
+        // The message schedule.
+        let mut w: Vec<WordBits<C>> = block.iter().map(|word| word_bits(self, *word)).collect();
+        for t in 16..64 {
+            let s0 = xor3(
+                self,
+                &rotr::<C>(&w[t - 15], 7),
+                &rotr::<C>(&w[t - 15], 18),
+                &shr::<C>(&w[t - 15], 3, zero),
+            );
+            let s1 = xor3(
+                self,
+                &rotr::<C>(&w[t - 2], 17),
+                &rotr::<C>(&w[t - 2], 19),
+                &shr::<C>(&w[t - 2], 10, zero),
+            );
+            let word = add_words(self, &[&s1, &w[t - 7], &s0, &w[t - 16]], 0);
+            w.push(word);
+        }
+
+        // The rounds.
+        let initial: [WordBits<C>; 8] = array::from_fn(|i| word_bits(self, state[i]));
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = initial;
+        for t in 0..64 {
+            let s1 = xor3(
+                self,
+                &rotr::<C>(&e, 6),
+                &rotr::<C>(&e, 11),
+                &rotr::<C>(&e, 25),
+            );
+            let ch: WordBits<C> = array::from_fn(|i| self.eval(g[i] + (f[i] - g[i]) * e[i]));
+            let s0 = xor3(
+                self,
+                &rotr::<C>(&a, 2),
+                &rotr::<C>(&a, 13),
+                &rotr::<C>(&a, 22),
+            );
+            let maj: WordBits<C> = array::from_fn(|i| {
+                let ab: Var<_> = self.eval(a[i] * b[i]);
+                self.eval(ab + (a[i] + b[i] - ab * C::N::two()) * c[i])
+            });
+
+            let new_e = add_words(self, &[&d, &h, &s1, &ch, &w[t]], SHA256_K[t]);
+            let new_a = add_words(self, &[&h, &s1, &ch, &w[t], &s0, &maj], SHA256_K[t]);
+            h = g;
+            g = f;
+            f = e;
+            e = new_e;
+            d = c;
+            c = b;
+            b = a;
+            a = new_a;
+        }
+
+        let working = [a, b, c, d, e, f, g, h];
+        array::from_fn(|i| {
+            let word = add_words(self, &[&initial[i], &working[i]], 0);
+            self.bits2num_v_circuit(&word)
+        })
+    }
+
+    fn sha256(&mut self, bytes: &[Var<C::N>]) -> [Var<C::N>; 32] {
+        for byte in bytes.iter() {
+            self.num2bits_v_circuit(*byte, 8);
+        }
+
+        // Pad the message with a one bit, zeros and the length in bits.
+        let mut padded: Vec<SymbolicVar<C::N>> = bytes.iter().map(|byte| (*byte).into()).collect();
+        padded.push(C::N::from_canonical_u32(0x80).into());
+        while padded.len() % 64 != 56 {
+            padded.push(C::N::zero().into());
+        }
+        let bit_len = (bytes.len() as u64) * 8;
+        padded.extend(
+            bit_len
+                .to_be_bytes()
+                .map(|byte| C::N::from_canonical_u8(byte).into()),
+        );
+
+        let mut state: [Var<C::N>; 8] =
+            array::from_fn(|i| self.eval(C::N::from_canonical_u32(SHA256_IV[i])));
+        for chunk in padded.chunks(64) {
+            let block: [Var<C::N>; 16] = array::from_fn(|i| {
+                let word = chunk[4 * i..4 * i + 4]
+                    .iter()
+                    .fold(SymbolicVar::from(C::N::zero()), |word, byte| {
+                        word * C::N::from_canonical_u32(256) + byte.clone()
+                    });
+                self.eval(word)
+            });
+            state = self.sha256_compress(state, block);
+        }
+
+        let mut digest = Vec::with_capacity(32);
+        for word in state {
+            let bits = word_bits(self, word);
+            for i in (0..4).rev() {
+                digest.push(self.bits2num_v_circuit(&bits[8 * i..8 * i + 8]));
+            }
+        }
+        digest.try_into().unwrap()
+    }
+}
+
+/// Decomposes a word into bits, constraining it to 32 bits.
+fn word_bits<C: Config>(builder: &mut Builder<C>, word: Var<C::N>) -> WordBits<C> {
+    builder.num2bits_v_circuit(word, 32).try_into().unwrap()
+}
+
+fn rotr<C: Config>(word: &WordBits<C>, n: usize) -> WordBits<C> {
+    array::from_fn(|i| word[(i + n) % 32])
+}
+
+fn shr<C: Config>(word: &WordBits<C>, n: usize, zero: Var<C::N>) -> WordBits<C> {
+    array::from_fn(|i| if i + n < 32 { word[i + n] } else { zero })
+}
+
+fn xor<C: Config>(builder: &mut Builder<C>, a: Var<C::N>, b: Var<C::N>) -> Var<C::N> {
+    builder.eval(a + b - a * b * C::N::two())
+}
+
+fn xor3<C: Config>(
+    builder: &mut Builder<C>,
+    a: &WordBits<C>,
+    b: &WordBits<C>,
+    c: &WordBits<C>,
+) -> WordBits<C> {
+    array::from_fn(|i| {
+        let ab = xor(builder, a[i], b[i]);
+        xor(builder, ab, c[i])
+    })
+}
+
+/// Adds `words` and `constant` modulo 2^32.
+fn add_words<C: Config>(
+    builder: &mut Builder<C>,
+    words: &[&WordBits<C>],
+    constant: u32,
+) -> WordBits<C> {
+    let mut sum = SymbolicVar::from(C::N::from_canonical_u32(constant));
+    for word in words {
+        for (i, bit) in word.iter().enumerate() {
+            sum = sum + *bit * C::N::from_canonical_u32(1 << i);
+        }
+    }
+    let sum: Var<_> = builder.eval(sum);
+
+    // The sum is less than `words.len() + 1` times 2^32.
+    let carry_bits = (words.len() + 1).next_power_of_two().ilog2() as usize;
+    let bits = builder.num2bits_v_circuit(sum, 32 + carry_bits);
+    bits[..32].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_bn254_fr::Bn254Fr;
+    use p3_field::AbstractField;
+    use sha2::{Digest, Sha256};
+    use sp1_recursion_compiler::config::OuterConfig;
+    use sp1_recursion_compiler::constraints::ConstraintCompiler;
+    use sp1_recursion_compiler::ir::{Builder, Var, Witness};
+    use sp1_recursion_gnark_ffi::PlonkBn254Prover;
+
+    use super::Sha256CircuitBuilder;
+
+    #[test]
+    fn test_sha256() {
+        let preimages: Vec<Vec<u8>> = vec![
+            vec![],
+            b"abc".to_vec(),
+            vec![0xff; 55],
+            vec![0x5a; 56],
+            (0..64).collect(),
+            (0..=255).cycle().take(119).collect(),
+        ];
+
+        let mut builder = Builder::<OuterConfig>::default();
+        for preimage in preimages.iter() {
+            let bytes: Vec<Var<_>> = preimage
+                .iter()
+                .map(|byte| builder.eval(Bn254Fr::from_canonical_u8(*byte)))
+                .collect();
+            let digest = builder.sha256(&bytes);
+
+            let expected = Sha256::digest(preimage);
+            for (byte, expected) in digest.iter().zip(expected) {
+                builder.assert_var_eq(*byte, Bn254Fr::from_canonical_u8(expected));
+            }
+        }
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+    }
+
+    #[test]
+    fn test_sha256_compress_constraints() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let bytes: Vec<Var<_>> = (0..55)
+            .map(|byte| builder.eval(Bn254Fr::from_canonical_u8(byte)))
+            .collect();
+        builder.sha256(&bytes);
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        println!(
+            "sha256 of a single block: {} constraints",
+            constraints.len()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sha256_wrong_digest() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let bytes: Vec<Var<_>> = b"abc"
+            .iter()
+            .map(|byte| builder.eval(Bn254Fr::from_canonical_u8(*byte)))
+            .collect();
+        let digest = builder.sha256(&bytes);
+
+        let expected = Sha256::digest(b"abd");
+        for (byte, expected) in digest.iter().zip(expected) {
+            builder.assert_var_eq(*byte, Bn254Fr::from_canonical_u8(expected));
+        }
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        PlonkBn254Prover::test::<OuterConfig>(constraints.clone(), Witness::default());
+    }
+}
@@ -1,6 +1,7 @@
 use std::borrow::Borrow;
 use std::marker::PhantomData;
 
+use crate::ecdsa::{EcdsaCircuitBuilder, Secp256k1Point};
 use crate::fri::verify_two_adic_pcs;
 use crate::poseidon2::Poseidon2CircuitBuilder;
 use crate::types::OuterDigestVariable;
@@ -17,7 +18,7 @@ use sp1_core::{
     stark::{ShardCommitment, StarkGenericConfig, StarkMachine, StarkVerifyingKey},
 };
 use sp1_recursion_compiler::config::OuterConfig;
-use sp1_recursion_compiler::constraints::{Constraint, ConstraintCompiler};
+use sp1_recursion_compiler::constraints::{Constraint, ConstraintCompiler, ConstraintSink};
 use sp1_recursion_compiler::ir::{Builder, Config, Ext, Felt, Var};
 use sp1_recursion_compiler::ir::{Usize, Witness};
 use sp1_recursion_compiler::prelude::SymbolicVar;
@@ -239,10 +240,40 @@ type OuterSC = BabyBearPoseidon2Outer;
 type OuterF = <BabyBearPoseidon2Outer as StarkGenericConfig>::Val;
 type OuterC = OuterConfig;
 
+/// Options of the circuit wrapping proofs in PLONK.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WrapCircuitOpts {
+    /// Additionally verify a secp256k1 ECDSA signature by this key over the committed values
+    /// digest. The signature is written to the witness after the vkey hash, see
+    /// [crate::ecdsa::write_secp256k1_ecdsa_witness].
+    pub operator_key: Option<Secp256k1Point>,
+}
+
 pub fn build_wrap_circuit(
     wrap_vk: &StarkVerifyingKey<OuterSC>,
     template_proof: ShardProof<OuterSC>,
 ) -> Vec<Constraint> {
+    build_wrap_circuit_with_opts(wrap_vk, template_proof, &WrapCircuitOpts::default())
+}
+
+pub fn build_wrap_circuit_with_opts(
+    wrap_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: ShardProof<OuterSC>,
+    opts: &WrapCircuitOpts,
+) -> Vec<Constraint> {
+    let mut constraints = Vec::new();
+    build_wrap_circuit_into(wrap_vk, template_proof, opts, &mut constraints);
+    constraints
+}
+
+/// Builds the wrap circuit like [build_wrap_circuit_with_opts], emitting its constraints into
+/// `sink` as they are compiled, see [ConstraintCompiler::emit_into].
+pub fn build_wrap_circuit_into<S: ConstraintSink>(
+    wrap_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: ShardProof<OuterSC>,
+    opts: &WrapCircuitOpts,
+    sink: &mut S,
+) {
     let outer_config = OuterSC::new();
     let outer_machine = RecursionAirSkinnyDeg9::<OuterF>::wrap_machine(outer_config);
 
@@ -264,6 +295,8 @@ pub fn build_wrap_circuit(
     builder.commit_commited_values_digest_circuit(commited_values_digest);
     let vkey_hash = Bn254Fr::zero().read(&mut builder);
     builder.commit_vkey_hash_circuit(vkey_hash);
+    let input_digest = Bn254Fr::zero().read(&mut builder);
+    builder.commit_input_digest_circuit(input_digest);
 
     // Validate public values
     let mut pv_elements = Vec::new();
@@ -295,6 +328,23 @@ pub fn build_wrap_circuit(
     // Committed values digest must match the witnessed one that we are committing to.
     builder.assert_var_eq(pv_committed_values_digest, commited_values_digest);
 
+    // Input digest must match the witnessed one that we are committing to.
+    let pv_input_digest_bytes: [Felt<_>; 32] = words_to_bytes(&pv.input_digest).try_into().unwrap();
+    let pv_input_digest: Var<_> = babybear_bytes_to_bn254(&mut builder, &pv_input_digest_bytes);
+    builder.assert_var_eq(pv_input_digest, input_digest);
+
+    // The operator signed the committed values digest.
+    if let Some(operator_key) = &opts.operator_key {
+        let digest_bytes = pv_committed_values_digest_bytes.map(|byte| {
+            let bits = builder.num2bits_f_circuit(byte);
+            for bit in bits[8..].iter() {
+                builder.assert_var_eq(*bit, Bn254Fr::zero());
+            }
+            builder.bits2num_v_circuit(&bits[..8])
+        });
+        builder.verify_secp256k1_ecdsa(operator_key, &digest_bytes);
+    }
+
     let chips = outer_machine
         .shard_chips_ordered(&template_proof.chip_ordering)
         .map(|chip| chip.name())
@@ -358,7 +408,7 @@ pub fn build_wrap_circuit(
     }
 
     let mut backend = ConstraintCompiler::<OuterConfig>::default();
-    backend.emit(builder.operations)
+    backend.emit_into(builder.operations, sink);
 }
 
 #[cfg(test)]
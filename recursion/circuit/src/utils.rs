@@ -26,6 +26,9 @@ pub fn babybears_to_bn254<C: Config>(
     result
 }
 
+/// Packs 32 bytes big-endian into a Bn254 element, dropping the 3 most significant bits of the
+/// first byte. Each felt is constrained to hold a byte, so that no other bytes pack to the same
+/// element.
 pub fn babybear_bytes_to_bn254<C: Config>(
     builder: &mut Builder<C>,
     bytes: &[Felt<C::F>; 32],
@@ -34,7 +37,11 @@ pub fn babybear_bytes_to_bn254<C: Config>(
     let zero_var: Var<_> = builder.constant(C::N::zero());
     let result = builder.constant(C::N::zero());
     for (i, byte) in bytes.iter().enumerate() {
-        let byte_bits = builder.num2bits_f_circuit(*byte);
+        let mut byte_bits = builder.num2bits_f_circuit(*byte);
+        for bit in byte_bits[8..].iter() {
+            builder.assert_var_eq(*bit, C::N::zero());
+        }
+        byte_bits.truncate(8);
         if i == 0 {
             // Since 32 bytes doesn't fit into Bn254, we need to truncate the top 3 bits.
             // For first byte, zero out 3 most significant bits.
@@ -372,6 +372,7 @@ mod tests {
                 exts: vec![OuterChallenge::one(), OuterChallenge::two()],
                 vkey_hash: Bn254Fr::one(),
                 commited_values_digest: Bn254Fr::one(),
+                input_digest: Bn254Fr::one(),
             },
         );
     }
@@ -11,13 +11,17 @@ pub type AsmBuilder<F, EF> = Builder<AsmConfig<F, EF>>;
 impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmBuilder<F, EF> {
     /// Compile to assembly code.
     pub fn compile_asm(self) -> AssemblyCode<F, EF> {
+        self.check_taint();
         let mut compiler = AsmCompiler::new();
         compiler.build(self.operations);
         compiler.code()
     }
 
     /// Compile to a program that can be executed in the recursive zkVM.
+    ///
+    /// The hints of the program are checked with [crate::ir::TaintAnalysis] first.
     pub fn compile_program(self) -> RecursionProgram<F> {
+        self.check_taint();
         let mut compiler = AsmCompiler::new();
         compiler.build(self.operations);
         compiler.compile()
@@ -5,21 +5,23 @@ use core::fmt;
 use core::fmt::Display;
 
 use p3_field::{ExtensionField, PrimeField32};
-use sp1_recursion_core::runtime::RecursionProgram;
+use sp1_recursion_core::runtime::{RecursionProgram, SourceLocation, SourceMap};
 
 use super::AsmInstruction;
 
-/// A basic block of assembly instructions.
+/// A basic block of assembly instructions, with their traces and their source locations keyed by
+/// their index in the block.
 #[derive(Debug, Clone, Default)]
 pub struct BasicBlock<F, EF>(
     pub(crate) Vec<AsmInstruction<F, EF>>,
     pub(crate) Vec<Option<Backtrace>>,
+    pub(crate) BTreeMap<usize, SourceLocation>,
 );
 
 impl<F: PrimeField32, EF: ExtensionField<F>> BasicBlock<F, EF> {
     /// Creates a new basic block.
     pub const fn new() -> Self {
-        Self(Vec::new(), Vec::new())
+        Self(Vec::new(), Vec::new(), BTreeMap::new())
     }
 
     /// Pushes an instruction to a basic block.
@@ -27,7 +29,11 @@ impl<F: PrimeField32, EF: ExtensionField<F>> BasicBlock<F, EF> {
         &mut self,
         instruction: AsmInstruction<F, EF>,
         backtrace: Option<Backtrace>,
+        source: Option<&SourceLocation>,
     ) {
+        if let Some(source) = source {
+            self.2.insert(self.0.len(), source.clone());
+        }
         self.0.push(instruction);
         self.1.push(backtrace);
     }
@@ -65,8 +71,12 @@ impl<F: PrimeField32, EF: ExtensionField<F>> AssemblyCode<F, EF> {
         // Make the second pass to convert the assembly code to machine code.
         let mut machine_code = Vec::new();
         let mut traces = Vec::new();
+        let mut source_map = SourceMap::default();
         let mut pc = 0;
         for block in blocks {
+            for (index, source) in block.2 {
+                source_map.insert(pc + index, source);
+            }
             for (instruction, trace) in block.0.into_iter().zip(block.1) {
                 machine_code.push(instruction.to_machine(pc, &label_to_pc));
                 traces.push(trace);
@@ -77,6 +87,8 @@ impl<F: PrimeField32, EF: ExtensionField<F>> AssemblyCode<F, EF> {
         RecursionProgram {
             instructions: machine_code,
             traces,
+            source_map,
+            verified_fingerprint: None,
         }
     }
 }
@@ -3,12 +3,14 @@ use alloc::vec;
 use backtrace::Backtrace;
 use sp1_recursion_core::runtime::HEAP_PTR;
 use sp1_recursion_core::runtime::HEAP_START_ADDRESS;
+use sp1_recursion_core::runtime::NUM_BITS;
+use sp1_recursion_core::runtime::{SCRATCH_POISON, SCRATCH_SLOTS};
 use std::collections::BTreeSet;
 
 use p3_field::ExtensionField;
 use p3_field::PrimeField32;
 use p3_field::TwoAdicField;
-use sp1_recursion_core::runtime::RecursionProgram;
+use sp1_recursion_core::runtime::{RecursionProgram, SourceLocation};
 
 use super::config::AsmConfig;
 use super::IndexTriple;
@@ -20,13 +22,25 @@ use crate::ir::Usize;
 use crate::ir::{DslIr, Ext, Felt, Ptr, Var};
 use crate::prelude::TracedVec;
 
+// The frame offsets above `-STACK_START_OFFSET` are reserved, and never given to variables:
+//
+// | offset    | use                                                                    |
+// |-----------|------------------------------------------------------------------------|
+// | 0         | `ZERO`, always zero.                                                   |
+// | -4        | `HEAP_PTR`, the address of the next heap allocation.                   |
+// | -8        | `A0`, the link register of jumps, whose return address is discarded.   |
+// | -9 to -15 | `SCRATCH_SLOTS`, handed out by `AsmCompiler::with_scratch`.            |
+//
+// The other offsets are unused. More scratch slots require a larger `STACK_START_OFFSET`, which
+// moves every variable and so changes the compiled programs.
+
 /// The zero address.
 pub(crate) const ZERO: i32 = 0;
 
 /// The offset which the stack starts.
 pub(crate) const STACK_START_OFFSET: i32 = 16;
 
-/// The address of A0.
+/// The address of A0, written by every jump. It must not hold values, use a scratch slot instead.
 pub(crate) const A0: i32 = -8;
 
 /// The assembly compiler.
@@ -38,6 +52,10 @@ pub struct AsmCompiler<F, EF> {
     break_counter: usize,
     contains_break: BTreeSet<F>,
     function_labels: BTreeMap<String, F>,
+    /// The number of scratch slots handed out by [AsmCompiler::with_scratch].
+    scratch_in_use: usize,
+    /// The source location of the operation being built, if the builder tracked it.
+    source: Option<SourceLocation>,
 }
 
 impl<F> Var<F> {
@@ -78,7 +96,37 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
             contains_break: BTreeSet::new(),
             function_labels: BTreeMap::new(),
             break_counter: 0,
+            scratch_in_use: 0,
+            source: None,
+        }
+    }
+
+    /// Hands out `n` distinct scratch slots to `f`, which are released when it returns.
+    ///
+    /// Scratch slots hold the intermediate values of a lowering. The slots of nested calls are
+    /// distinct from those of their callers, so that helpers can't clobber each other's values.
+    /// In debug builds, released slots are poisoned, and the recursion runtime panics if they are
+    /// read before being written again.
+    pub fn with_scratch<R>(&mut self, n: usize, f: impl FnOnce(&[i32], &mut Self) -> R) -> R {
+        let start = self.scratch_in_use;
+        assert!(
+            start + n <= SCRATCH_SLOTS.len(),
+            "out of scratch slots: {} in use, {} requested, {} available",
+            start,
+            n,
+            SCRATCH_SLOTS.len()
+        );
+        let slots = &SCRATCH_SLOTS[start..start + n];
+        self.scratch_in_use = start + n;
+        let result = f(slots, self);
+        self.scratch_in_use = start;
+        if cfg!(debug_assertions) {
+            let poison = F::from_canonical_u32(SCRATCH_POISON);
+            for slot in slots {
+                self.push(AsmInstruction::AddFI(*slot, ZERO, poison), None);
+            }
         }
+        result
     }
 
     /// Creates a new break label.
@@ -98,8 +146,15 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
             self.push(AsmInstruction::AddFI(HEAP_PTR, ZERO, stack_size), None);
         }
 
+        // The instructions of a nested block are located at its own operations, and those
+        // generated around it at the operation containing it.
+        let outer_source = self.source.clone();
+
         // For each operation, generate assembly instructions.
-        for (op, trace) in operations.clone() {
+        for (i, (op, trace)) in operations.clone().into_iter().enumerate() {
+            if let Some(source) = operations.source(i) {
+                self.source = Some(source.clone());
+            }
             match op {
                 DslIr::ImmV(dst, src) => {
                     self.push(AsmInstruction::AddFI(dst.fp(), ZERO, src), trace);
@@ -467,6 +522,15 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
                     }
                     _ => unimplemented!(),
                 },
+                DslIr::CircuitNum2BitsV(value, num_bits, output) => {
+                    assert_eq!(output.len(), num_bits);
+                    let bits = output.iter().map(|bit| bit.fp()).collect::<Vec<_>>();
+                    self.num2bits(value.fp(), &bits, trace);
+                }
+                DslIr::CircuitNum2BitsF(value, output) => {
+                    let bits = output.iter().map(|bit| bit.fp()).collect::<Vec<_>>();
+                    self.num2bits(value.fp(), &bits, trace);
+                }
                 DslIr::Poseidon2PermuteBabyBear(dst, src) => match (dst, src) {
                     (Array::Dyn(dst, _), Array::Dyn(src, _)) => {
                         self.push(AsmInstruction::Poseidon2Permute(dst.fp(), src.fp()), trace)
@@ -533,6 +597,9 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
                 DslIr::CycleTracker(name) => {
                     self.push(AsmInstruction::CycleTracker(name.clone()), trace);
                 }
+                DslIr::AssumeConstrainedV(..)
+                | DslIr::AssumeConstrainedF(..)
+                | DslIr::AssumeConstrainedE(..) => {}
                 DslIr::Halt => {
                     self.push(AsmInstruction::Halt, trace);
                 }
@@ -545,6 +612,7 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
                 _ => unimplemented!(),
             }
         }
+        self.source = outer_source;
     }
 
     pub fn alloc(&mut self, ptr: Ptr<F>, len: Usize<F>, size: usize, backtrace: Option<Backtrace>) {
@@ -567,12 +635,115 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
                     AsmInstruction::AddFI(ptr.fp(), HEAP_PTR, F::zero()),
                     backtrace.clone(),
                 );
-                self.push(AsmInstruction::MulFI(A0, len.fp(), size), backtrace.clone());
-                self.push(AsmInstruction::AddF(HEAP_PTR, HEAP_PTR, A0), backtrace);
+                self.with_scratch(1, |scratch, compiler| {
+                    compiler.push(
+                        AsmInstruction::MulFI(scratch[0], len.fp(), size),
+                        backtrace.clone(),
+                    );
+                    compiler.push(
+                        AsmInstruction::AddF(HEAP_PTR, HEAP_PTR, scratch[0]),
+                        backtrace,
+                    );
+                });
             }
         }
     }
 
+    /// Decomposes the value at `value` into the LE bits at `bits`.
+    ///
+    /// The bits are hinted with `HintBits` into a fresh heap array, loaded, and then constrained
+    /// by [AsmCompiler::constrain_bits]. There can be fewer bits than [NUM_BITS], which range
+    /// checks the value.
+    pub fn num2bits(&mut self, value: i32, bits: &[i32], backtrace: Option<Backtrace>) {
+        assert!(
+            bits.len() <= NUM_BITS,
+            "cannot decompose into {} bits, the field elements have {} bits",
+            bits.len(),
+            NUM_BITS
+        );
+        self.with_scratch(1, |scratch, compiler| {
+            let ptr = scratch[0];
+            compiler.push(
+                AsmInstruction::AddFI(ptr, HEAP_PTR, F::zero()),
+                backtrace.clone(),
+            );
+            compiler.push(
+                AsmInstruction::AddFI(HEAP_PTR, HEAP_PTR, F::from_canonical_usize(NUM_BITS)),
+                backtrace.clone(),
+            );
+            compiler.push(AsmInstruction::HintBits(ptr, value), backtrace.clone());
+            for (i, bit) in bits.iter().enumerate() {
+                compiler.push(
+                    AsmInstruction::LoadFI(
+                        *bit,
+                        ptr,
+                        F::from_canonical_usize(i),
+                        F::zero(),
+                        F::one(),
+                    ),
+                    backtrace.clone(),
+                );
+            }
+        });
+        self.constrain_bits(value, bits, backtrace);
+    }
+
+    /// Traps unless the values at `bits` are the LE bit decomposition of the value at `value`.
+    ///
+    /// Each bit must be boolean, and the bits must recompose to the value. A decomposition into
+    /// [NUM_BITS] bits must also be canonical, that is less than the BabyBear modulus
+    /// `2^31 - 2^27 + 1`: if the 4 most significant bits are all 1, the others must all be 0.
+    pub fn constrain_bits(&mut self, value: i32, bits: &[i32], backtrace: Option<Backtrace>) {
+        self.with_scratch(2, |scratch, compiler| {
+            let (acc, tmp) = (scratch[0], scratch[1]);
+            compiler.push(
+                AsmInstruction::AddFI(acc, ZERO, F::zero()),
+                backtrace.clone(),
+            );
+            for (i, bit) in bits.iter().enumerate() {
+                // Check that `bit * (bit - 1) == 0`.
+                compiler.push(
+                    AsmInstruction::SubFI(tmp, *bit, F::one()),
+                    backtrace.clone(),
+                );
+                compiler.push(AsmInstruction::MulF(tmp, tmp, *bit), backtrace.clone());
+                compiler.assert(
+                    tmp,
+                    ValueOrConst::Const(F::zero()),
+                    false,
+                    backtrace.clone(),
+                );
+                // Add `bit * 2^i` to the recomposition.
+                compiler.push(
+                    AsmInstruction::MulFI(tmp, *bit, F::from_canonical_u32(1 << i)),
+                    backtrace.clone(),
+                );
+                compiler.push(AsmInstruction::AddF(acc, acc, tmp), backtrace.clone());
+            }
+            compiler.assert(acc, ValueOrConst::Val(value), false, backtrace.clone());
+
+            if bits.len() == NUM_BITS {
+                let (low, high) = bits.split_at(NUM_BITS - 4);
+                compiler.push(
+                    AsmInstruction::AddFI(acc, ZERO, F::one()),
+                    backtrace.clone(),
+                );
+                for bit in high {
+                    compiler.push(AsmInstruction::MulF(acc, acc, *bit), backtrace.clone());
+                }
+                compiler.push(
+                    AsmInstruction::AddFI(tmp, ZERO, F::zero()),
+                    backtrace.clone(),
+                );
+                for bit in low {
+                    compiler.push(AsmInstruction::AddF(tmp, tmp, *bit), backtrace.clone());
+                }
+                compiler.push(AsmInstruction::MulF(acc, acc, tmp), backtrace.clone());
+                compiler.assert(acc, ValueOrConst::Const(F::zero()), false, backtrace);
+            }
+        });
+    }
+
     pub fn assert(
         &mut self,
         lhs: i32,
@@ -621,14 +792,14 @@ impl<F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField> AsmCo
         self.basic_blocks
             .get_mut(block_label.as_canonical_u32() as usize)
             .unwrap_or_else(|| panic!("Missing block at label: {:?}", block_label))
-            .push(instruction, backtrace);
+            .push(instruction, backtrace, self.source.as_ref());
     }
 
     fn push(&mut self, instruction: AsmInstruction<F, EF>, backtrace: Option<Backtrace>) {
         self.basic_blocks
             .last_mut()
             .unwrap()
-            .push(instruction, backtrace);
+            .push(instruction, backtrace, self.source.as_ref());
     }
 }
 
@@ -862,3 +1033,154 @@ impl<'a, F: PrimeField32 + TwoAdicField, EF: ExtensionField<F> + TwoAdicField>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_field::{AbstractField, PrimeField32};
+    use sp1_core::stark::StarkGenericConfig;
+    use sp1_core::utils::BabyBearPoseidon2;
+    use sp1_recursion_core::runtime::Runtime;
+    use std::panic::AssertUnwindSafe;
+
+    use super::*;
+    use crate::asm::AsmBuilder;
+
+    type SC = BabyBearPoseidon2;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+
+    #[test]
+    fn test_scratch_slots() {
+        let mut compiler = AsmCompiler::<F, EF>::new();
+        compiler.with_scratch(2, |outer, compiler| {
+            compiler.with_scratch(3, |inner, _| {
+                assert!(inner.iter().all(|slot| !outer.contains(slot)));
+            });
+            // The released slots are handed out again.
+            compiler.with_scratch(3, |inner, _| assert_eq!(inner, &SCRATCH_SLOTS[2..5]));
+        });
+        assert_eq!(compiler.scratch_in_use, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of scratch slots")]
+    fn test_scratch_slots_exhausted() {
+        let mut compiler = AsmCompiler::<F, EF>::new();
+        compiler.with_scratch(SCRATCH_SLOTS.len(), |_, compiler| {
+            compiler.with_scratch(1, |_, _| {});
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "read of released scratch slot")]
+    fn test_released_scratch_slot_read() {
+        let mut compiler = AsmCompiler::<F, EF>::new();
+        let stale = compiler.with_scratch(1, |scratch, compiler| {
+            compiler.push(AsmInstruction::AddFI(scratch[0], ZERO, F::one()), None);
+            scratch[0]
+        });
+        let dst = Var::<F>(0, Default::default());
+        compiler.push(AsmInstruction::AddFI(dst.fp(), stale, F::zero()), None);
+
+        let program = compiler.compile();
+        let config = SC::default();
+        let mut runtime = Runtime::<F, EF, _>::new(&program, config.perm.clone());
+        runtime.run();
+    }
+
+    /// Runs the constraints of [AsmCompiler::constrain_bits] on the given value and bits.
+    fn run_constrain_bits(value: F, bits: &[u32]) {
+        let mut compiler = AsmCompiler::<F, EF>::new();
+        let value_var = Var::<F>(0, Default::default());
+        compiler.push(AsmInstruction::AddFI(value_var.fp(), ZERO, value), None);
+        let bit_vars = (0..bits.len())
+            .map(|i| Var::<F>(i as u32 + 1, Default::default()).fp())
+            .collect::<Vec<_>>();
+        for (bit_var, bit) in bit_vars.iter().zip(bits) {
+            compiler.push(
+                AsmInstruction::AddFI(*bit_var, ZERO, F::from_canonical_u32(*bit)),
+                None,
+            );
+        }
+        compiler.constrain_bits(value_var.fp(), &bit_vars, None);
+
+        let program = compiler.compile();
+        let config = SC::default();
+        Runtime::<F, EF, _>::new(&program, config.perm.clone()).run();
+    }
+
+    fn le_bits(value: u32, num_bits: usize) -> Vec<u32> {
+        (0..num_bits).map(|i| (value >> i) & 1).collect()
+    }
+
+    #[test]
+    fn test_constrain_bits() {
+        for value in [0, 1, (1 << 27) - 1, (15 << 27) - 1, F::ORDER_U32 - 1] {
+            run_constrain_bits(F::from_canonical_u32(value), &le_bits(value, NUM_BITS));
+        }
+        run_constrain_bits(F::from_canonical_u32(255), &le_bits(255, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "TRAP encountered")]
+    fn test_constrain_bits_modulus() {
+        // The bits of p recompose to 0, but aren't canonical.
+        run_constrain_bits(F::zero(), &le_bits(F::ORDER_U32, NUM_BITS));
+    }
+
+    #[test]
+    #[should_panic(expected = "TRAP encountered")]
+    fn test_constrain_bits_modulus_plus_one() {
+        // The bits of p + 1 recompose to 1, but aren't canonical.
+        run_constrain_bits(F::one(), &le_bits(F::ORDER_U32 + 1, NUM_BITS));
+    }
+
+    #[test]
+    #[should_panic(expected = "TRAP encountered")]
+    fn test_constrain_bits_not_boolean() {
+        let mut bits = le_bits(0, NUM_BITS);
+        bits[0] = 2;
+        run_constrain_bits(F::two(), &bits);
+    }
+
+    #[test]
+    #[should_panic(expected = "TRAP encountered")]
+    fn test_constrain_bits_out_of_range() {
+        // 256 doesn't fit in 8 bits, whatever they are.
+        run_constrain_bits(F::from_canonical_u32(256), &le_bits(256, 8));
+    }
+
+    #[test]
+    fn test_trap_source_location() {
+        let build = |track: bool| {
+            let mut builder = AsmBuilder::<F, EF>::default();
+            builder.set_track_sources(track);
+            builder.cycle_tracker("check");
+            let a: Felt<_> = builder.eval(F::one());
+            builder.assert_felt_eq(a, F::two());
+            builder.cycle_tracker("check");
+            builder.halt();
+            builder.compile_program()
+        };
+        let line = line!() - 5;
+
+        // Tracking sources doesn't change the program.
+        let program = build(true);
+        assert_eq!(program.instructions, build(false).instructions);
+        assert!(build(false).source_map.is_empty());
+
+        let config = SC::default();
+        let err = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            Runtime::<F, EF, _>::new(&program, config.perm.clone()).run();
+        }))
+        .unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        assert!(
+            message.contains(&format!("{}:{}", file!(), line)),
+            "{}",
+            message
+        );
+        assert!(message.contains("in region `check`"), "{}", message);
+    }
+}
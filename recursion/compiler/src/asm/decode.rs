@@ -0,0 +1,407 @@
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+use p3_field::{ExtensionField, PrimeField32};
+use sp1_recursion_core::cpu::Instruction;
+use sp1_recursion_core::runtime::{
+    field_to_canonical_i32, Opcode, RecursionProgram, PERMUTATION_WIDTH,
+};
+
+use super::{AsmInstruction, AssemblyCode, BasicBlock};
+
+/// An error decoding machine code back to assembly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The immediate flags of the instruction at `pc` match no assembly instruction.
+    InvalidImmediates { pc: usize, opcode: Opcode },
+    /// The instruction at `pc` is not the encoding of the assembly instruction it decodes to.
+    NotCanonical { pc: usize, opcode: Opcode },
+    /// The branch or jump at `pc` targets `target`, outside of the program.
+    TargetOutOfRange { pc: usize, target: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidImmediates { pc, opcode } => {
+                write!(f, "invalid immediate flags for {:?} at pc {}", opcode, pc)
+            }
+            Self::NotCanonical { pc, opcode } => {
+                write!(f, "non-canonical encoding of {:?} at pc {}", opcode, pc)
+            }
+            Self::TargetOutOfRange { pc, target } => {
+                write!(
+                    f,
+                    "the jump at pc {} targets {}, outside of the program",
+                    pc, target
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl<F: PrimeField32, EF: ExtensionField<F>> AsmInstruction<F, EF> {
+    /// Decodes the machine instruction at `pc`, the inverse of [AsmInstruction::to_machine].
+    ///
+    /// Labels decode to the field element of the pc they point to. Assembly instructions sharing
+    /// an encoding decode to a single one of them: `LoadE`, `StoreE`, `BneE`, `BeqE` and `PrintV`
+    /// to their base field versions, and extension immediates whose upper limbs are zero to the
+    /// base field immediate of a branch.
+    pub fn decode(instruction: &Instruction<F>, pc: usize) -> Result<Self, DecodeError> {
+        let opcode = instruction.opcode;
+        let a = field_to_canonical_i32(instruction.op_a);
+        let b = field_to_canonical_i32(instruction.op_b.0[0]);
+        let c = field_to_canonical_i32(instruction.op_c.0[0]);
+        let b_f = instruction.op_b.0[0];
+        let c_f = instruction.op_c.0[0];
+        let b_ef = EF::from_base_slice(&instruction.op_b.0);
+        let c_ef = EF::from_base_slice(&instruction.op_c.0);
+        let b_is_base = instruction.op_b.0[1..].iter().all(|x| x.is_zero());
+        let (offset, size) = (instruction.offset_imm, instruction.size_imm);
+        // The label of a branch or jump is the pc it points to.
+        let label = |offset: F| F::from_canonical_usize(pc) + offset;
+
+        let decoded = match (opcode, instruction.imm_b, instruction.imm_c) {
+            (Opcode::LOAD, false, false) => AsmInstruction::LoadF(a, b, c, offset, size),
+            (Opcode::LOAD, false, true) => AsmInstruction::LoadFI(a, b, c_f, offset, size),
+            (Opcode::STORE, false, false) => AsmInstruction::StoreF(a, b, c, offset, size),
+            (Opcode::STORE, false, true) => AsmInstruction::StoreFI(a, b, c_f, offset, size),
+            (Opcode::ADD, false, false) => AsmInstruction::AddF(a, b, c),
+            (Opcode::ADD, false, true) => AsmInstruction::AddFI(a, b, c_f),
+            (Opcode::SUB, false, false) => AsmInstruction::SubF(a, b, c),
+            (Opcode::SUB, false, true) => AsmInstruction::SubFI(a, b, c_f),
+            (Opcode::SUB, true, false) => AsmInstruction::SubFIN(a, b_f, c),
+            (Opcode::MUL, false, false) => AsmInstruction::MulF(a, b, c),
+            (Opcode::MUL, false, true) => AsmInstruction::MulFI(a, b, c_f),
+            (Opcode::DIV, false, false) => AsmInstruction::DivF(a, b, c),
+            (Opcode::DIV, false, true) => AsmInstruction::DivFI(a, b, c_f),
+            (Opcode::DIV, true, false) => AsmInstruction::DivFIN(a, b_f, c),
+            (Opcode::EADD, false, false) => AsmInstruction::AddE(a, b, c),
+            (Opcode::EADD, false, true) => AsmInstruction::AddEI(a, b, c_ef),
+            (Opcode::ESUB, false, false) => AsmInstruction::SubE(a, b, c),
+            (Opcode::ESUB, false, true) => AsmInstruction::SubEI(a, b, c_ef),
+            (Opcode::ESUB, true, false) => AsmInstruction::SubEIN(a, b_ef, c),
+            (Opcode::EMUL, false, false) => AsmInstruction::MulE(a, b, c),
+            (Opcode::EMUL, false, true) => AsmInstruction::MulEI(a, b, c_ef),
+            (Opcode::EDIV, false, false) => AsmInstruction::DivE(a, b, c),
+            (Opcode::EDIV, false, true) => AsmInstruction::DivEI(a, b, c_ef),
+            (Opcode::EDIV, true, false) => AsmInstruction::DivEIN(a, b_ef, c),
+            (Opcode::BEQ, false, true) => AsmInstruction::Beq(label(c_f), a, b),
+            (Opcode::BEQ, true, true) if b_is_base => AsmInstruction::BeqI(label(c_f), a, b_f),
+            (Opcode::BEQ, true, true) => AsmInstruction::BeqEI(label(c_f), a, b_ef),
+            (Opcode::BNE, false, true) => AsmInstruction::Bne(label(c_f), a, b),
+            (Opcode::BNE, true, true) if b_is_base => AsmInstruction::BneI(label(c_f), a, b_f),
+            (Opcode::BNE, true, true) => AsmInstruction::BneEI(label(c_f), a, b_ef),
+            (Opcode::BNEINC, false, true) => AsmInstruction::BneInc(label(c_f), a, b),
+            (Opcode::BNEINC, true, true) => AsmInstruction::BneIInc(label(c_f), a, b_f),
+            (Opcode::JAL, true, true) => AsmInstruction::Jal(a, label(b_f), c_f),
+            (Opcode::JALR, false, true) => AsmInstruction::JalR(a, b, c),
+            (Opcode::TRAP, false, false) => AsmInstruction::Trap,
+            (Opcode::HALT, false, false) => AsmInstruction::Halt,
+            (Opcode::HintBits, false, true) => AsmInstruction::HintBits(a, b),
+            (Opcode::Poseidon2Compress, false, false)
+                if offset == F::from_canonical_usize(PERMUTATION_WIDTH / 2) =>
+            {
+                AsmInstruction::Poseidon2Permute(a, b)
+            }
+            (Opcode::Poseidon2Compress, false, false) => AsmInstruction::Poseidon2Compress(a, b, c),
+            (Opcode::PrintF, false, true) => AsmInstruction::PrintF(a),
+            (Opcode::PrintE, false, true) => AsmInstruction::PrintE(a),
+            (Opcode::HintExt2Felt, false, true) => AsmInstruction::HintExt2Felt(a, b),
+            (Opcode::FRIFold, false, true) => AsmInstruction::FriFold(a, b),
+            (Opcode::HintLen, false, true) => AsmInstruction::HintLen(a),
+            (Opcode::Hint, false, true) => AsmInstruction::Hint(a),
+            (Opcode::Commit, false, true) => AsmInstruction::Commit(a, b),
+            (Opcode::RegisterPublicValue, false, true) => AsmInstruction::RegisterPublicValue(a),
+            (Opcode::LessThanF, false, false) => AsmInstruction::LessThan(a, b, c),
+            (Opcode::CycleTracker, true, false) => {
+                AsmInstruction::CycleTracker(instruction.debug.clone())
+            }
+            (Opcode::ExpReverseBitsLen, false, false) => AsmInstruction::ExpReverseBitsLen(a, b, c),
+            _ => return Err(DecodeError::InvalidImmediates { pc, opcode }),
+        };
+
+        // Operands the encoding ignores or duplicates are only checked by encoding again.
+        let mut label_to_pc = BTreeMap::new();
+        if let Some(label) = decoded.label() {
+            label_to_pc.insert(label, label.as_canonical_u32() as usize);
+        }
+        let mut encoded = decoded.clone().to_machine(pc, &label_to_pc);
+        if opcode != Opcode::CycleTracker {
+            encoded.debug.clone_from(&instruction.debug);
+        }
+        if encoded != *instruction {
+            return Err(DecodeError::NotCanonical { pc, opcode });
+        }
+        Ok(decoded)
+    }
+
+    /// The label a branch or jump points to.
+    pub(crate) fn label(&self) -> Option<F> {
+        match self {
+            AsmInstruction::Break(label)
+            | AsmInstruction::Jal(_, label, _)
+            | AsmInstruction::Bne(label, _, _)
+            | AsmInstruction::BneInc(label, _, _)
+            | AsmInstruction::BneI(label, _, _)
+            | AsmInstruction::BneIInc(label, _, _)
+            | AsmInstruction::Beq(label, _, _)
+            | AsmInstruction::BeqI(label, _, _)
+            | AsmInstruction::BneE(label, _, _)
+            | AsmInstruction::BneEI(label, _, _)
+            | AsmInstruction::BeqE(label, _, _)
+            | AsmInstruction::BeqEI(label, _, _) => Some(*label),
+            _ => None,
+        }
+    }
+}
+
+impl<F: PrimeField32, EF: ExtensionField<F>> AssemblyCode<F, EF> {
+    /// Decodes a program back to assembly code, the inverse of [AssemblyCode::machine_code].
+    ///
+    /// Each instruction gets its own basic block, so that the label of a block is the pc of its
+    /// instruction.
+    pub fn disassemble(program: &RecursionProgram<F>) -> Result<Self, DecodeError> {
+        let len = program.instructions.len();
+        let mut blocks = Vec::with_capacity(len + 1);
+        for (pc, instruction) in program.instructions.iter().enumerate() {
+            let instruction = AsmInstruction::decode(instruction, pc)?;
+            if let Some(label) = instruction.label() {
+                let target = label.as_canonical_u32() as usize;
+                if target > len {
+                    return Err(DecodeError::TargetOutOfRange { pc, target });
+                }
+            }
+            let mut block = BasicBlock::new();
+            block.push(
+                instruction,
+                program.traces.get(pc).cloned().flatten(),
+                program.source_map.get(pc),
+            );
+            blocks.push(block);
+        }
+        // A jump past the last instruction targets an empty block.
+        blocks.push(BasicBlock::new());
+        Ok(Self::new(blocks, BTreeMap::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{extension::BinomialExtensionField, AbstractExtensionField, AbstractField};
+    use proptest::prelude::*;
+    use sp1_recursion_core::runtime::canonical_i32_to_field;
+
+    use super::*;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<BabyBear, 4>;
+
+    const P: u32 = BabyBear::ORDER_U32;
+
+    /// Frame pointer offsets, with the boundaries of the range accepted by the encoding.
+    fn fp() -> impl Strategy<Value = i32> {
+        let max = P as i32 - 1;
+        prop_oneof![
+            3 => -max..=max,
+            1 => prop_oneof![Just(0), Just(-1), Just(max), Just(-max)],
+        ]
+    }
+
+    /// Field elements, with the boundaries of the field.
+    fn felt() -> impl Strategy<Value = F> {
+        prop_oneof![
+            3 => 0..P,
+            1 => prop_oneof![Just(0), Just(1), Just(P - 1)],
+        ]
+        .prop_map(F::from_canonical_u32)
+    }
+
+    /// Extension field elements, some of which are base field elements.
+    fn ext() -> impl Strategy<Value = EF> {
+        prop_oneof![
+            3 => proptest::array::uniform4(felt()).prop_map(|limbs| EF::from_base_slice(&limbs)),
+            1 => felt().prop_map(EF::from_base),
+        ]
+    }
+
+    /// Assembly instructions whose labels point to pcs in `targets`.
+    fn instruction(targets: Range<usize>) -> impl Strategy<Value = AsmInstruction<F, EF>> {
+        use AsmInstruction::*;
+        let label = targets.prop_map(F::from_canonical_usize);
+        prop_oneof![
+            (fp(), fp(), fp(), felt(), felt()).prop_map(|(a, b, c, o, s)| LoadF(a, b, c, o, s)),
+            (fp(), fp(), felt(), felt(), felt()).prop_map(|(a, b, c, o, s)| LoadFI(a, b, c, o, s)),
+            (fp(), fp(), fp(), felt(), felt()).prop_map(|(a, b, c, o, s)| StoreF(a, b, c, o, s)),
+            (fp(), fp(), felt(), felt(), felt()).prop_map(|(a, b, c, o, s)| StoreFI(a, b, c, o, s)),
+            (fp(), fp(), fp(), felt(), felt()).prop_map(|(a, b, c, o, s)| LoadE(a, b, c, o, s)),
+            (fp(), fp(), felt(), felt(), felt()).prop_map(|(a, b, c, o, s)| LoadEI(a, b, c, o, s)),
+            (fp(), fp(), fp(), felt(), felt()).prop_map(|(a, b, c, o, s)| StoreE(a, b, c, o, s)),
+            (fp(), fp(), felt(), felt(), felt()).prop_map(|(a, b, c, o, s)| StoreEI(a, b, c, o, s)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| AddF(a, b, c)),
+            (fp(), fp(), felt()).prop_map(|(a, b, c)| AddFI(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| SubF(a, b, c)),
+            (fp(), fp(), felt()).prop_map(|(a, b, c)| SubFI(a, b, c)),
+            (fp(), felt(), fp()).prop_map(|(a, b, c)| SubFIN(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| MulF(a, b, c)),
+            (fp(), fp(), felt()).prop_map(|(a, b, c)| MulFI(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| DivF(a, b, c)),
+            (fp(), fp(), felt()).prop_map(|(a, b, c)| DivFI(a, b, c)),
+            (fp(), felt(), fp()).prop_map(|(a, b, c)| DivFIN(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| AddE(a, b, c)),
+            (fp(), fp(), ext()).prop_map(|(a, b, c)| AddEI(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| SubE(a, b, c)),
+            (fp(), fp(), ext()).prop_map(|(a, b, c)| SubEI(a, b, c)),
+            (fp(), ext(), fp()).prop_map(|(a, b, c)| SubEIN(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| MulE(a, b, c)),
+            (fp(), fp(), ext()).prop_map(|(a, b, c)| MulEI(a, b, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| DivE(a, b, c)),
+            (fp(), fp(), ext()).prop_map(|(a, b, c)| DivEI(a, b, c)),
+            (fp(), ext(), fp()).prop_map(|(a, b, c)| DivEIN(a, b, c)),
+            (fp(), label.clone(), felt()).prop_map(|(a, l, c)| Jal(a, l, c)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| JalR(a, b, c)),
+            (label.clone(), fp(), fp()).prop_map(|(l, a, b)| Bne(l, a, b)),
+            (label.clone(), fp(), fp()).prop_map(|(l, a, b)| BneInc(l, a, b)),
+            (label.clone(), fp(), felt()).prop_map(|(l, a, b)| BneI(l, a, b)),
+            (label.clone(), fp(), felt()).prop_map(|(l, a, b)| BneIInc(l, a, b)),
+            (label.clone(), fp(), fp()).prop_map(|(l, a, b)| Beq(l, a, b)),
+            (label.clone(), fp(), felt()).prop_map(|(l, a, b)| BeqI(l, a, b)),
+            (label.clone(), fp(), fp()).prop_map(|(l, a, b)| BneE(l, a, b)),
+            (label.clone(), fp(), ext()).prop_map(|(l, a, b)| BneEI(l, a, b)),
+            (label.clone(), fp(), fp()).prop_map(|(l, a, b)| BeqE(l, a, b)),
+            (label, fp(), ext()).prop_map(|(l, a, b)| BeqEI(l, a, b)),
+            Just(Trap),
+            Just(Halt),
+            (fp(), fp()).prop_map(|(a, b)| HintBits(a, b)),
+            (fp(), fp()).prop_map(|(a, b)| Poseidon2Permute(a, b)),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| Poseidon2Compress(a, b, c)),
+            fp().prop_map(PrintV),
+            fp().prop_map(PrintF),
+            fp().prop_map(PrintE),
+            (fp(), fp()).prop_map(|(a, b)| HintExt2Felt(a, b)),
+            fp().prop_map(HintLen),
+            fp().prop_map(Hint),
+            (fp(), fp()).prop_map(|(a, b)| FriFold(a, b)),
+            (fp(), fp()).prop_map(|(a, b)| Commit(a, b)),
+            fp().prop_map(RegisterPublicValue),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| LessThan(a, b, c)),
+            "[a-z_]{0,12}".prop_map(CycleTracker),
+            (fp(), fp(), fp()).prop_map(|(a, b, c)| ExpReverseBitsLen(a, b, c)),
+        ]
+    }
+
+    fn encode(instruction: AsmInstruction<F, EF>, pc: usize) -> Instruction<F> {
+        let mut label_to_pc = BTreeMap::new();
+        if let Some(label) = instruction.label() {
+            label_to_pc.insert(label, label.as_canonical_u32() as usize);
+        }
+        instruction.to_machine(pc, &label_to_pc)
+    }
+
+    fn program(instructions: Vec<AsmInstruction<F, EF>>) -> RecursionProgram<F> {
+        let blocks = instructions
+            .into_iter()
+            .map(|instruction| {
+                let mut block = BasicBlock::new();
+                block.push(instruction, None, None);
+                block
+            })
+            .collect();
+        AssemblyCode::new(blocks, BTreeMap::new()).machine_code()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4096))]
+
+        #[test]
+        fn test_decode_round_trip(
+            pc in 0usize..1 << 24,
+            instruction in instruction(0..1 << 24),
+        ) {
+            let encoded = encode(instruction, pc);
+            let decoded = AsmInstruction::decode(&encoded, pc).unwrap();
+            prop_assert_eq!(encode(decoded, pc), encoded);
+        }
+
+        #[test]
+        fn test_disassemble_round_trip(
+            instructions in (1usize..32).prop_flat_map(|len| {
+                proptest::collection::vec(instruction(0..len), len)
+            }),
+        ) {
+            let program = program(instructions);
+            let code = AssemblyCode::<F, EF>::disassemble(&program).unwrap();
+            prop_assert_eq!(code.machine_code().instructions, program.instructions);
+        }
+    }
+
+    #[test]
+    fn test_decode_extreme_ext_immediate() {
+        // Every limb of the immediate survives the round trip, not only the first one.
+        let max = F::from_canonical_u32(P - 1);
+        let imm = EF::from_base_slice(&[F::zero(), max, F::one(), max]);
+        let encoded = encode(AsmInstruction::BneEI(F::zero(), -1, imm), 7);
+        assert_eq!(encoded.op_b.0, [F::zero(), max, F::one(), max]);
+        match AsmInstruction::<F, EF>::decode(&encoded, 7).unwrap() {
+            AsmInstruction::BneEI(label, lhs, rhs) => {
+                assert_eq!((label, lhs, rhs), (F::zero(), -1, imm));
+            }
+            decoded => panic!("unexpected instruction: {:?}", decoded),
+        }
+    }
+
+    #[test]
+    fn test_decode_negative_fp_offsets() {
+        let max = P as i32 - 1;
+        let encoded = encode(AsmInstruction::<F, EF>::AddF(-max, max, -1), 0);
+        assert_eq!(encoded.op_a, F::one());
+        match AsmInstruction::<F, EF>::decode(&encoded, 0).unwrap() {
+            AsmInstruction::AddF(dst, lhs, rhs) => assert_eq!((dst, lhs, rhs), (1, -1, -1)),
+            decoded => panic!("unexpected instruction: {:?}", decoded),
+        }
+        assert_eq!(canonical_i32_to_field::<F>(-max), F::one());
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_encodings() {
+        let mut encoded = encode(AsmInstruction::<F, EF>::AddF(1, 2, 3), 0);
+        encoded.imm_b = true;
+        assert_eq!(
+            AsmInstruction::<F, EF>::decode(&encoded, 0).unwrap_err(),
+            DecodeError::InvalidImmediates {
+                pc: 0,
+                opcode: Opcode::ADD
+            }
+        );
+
+        // The upper limbs of a base field operand must be zero.
+        let mut encoded = encode(AsmInstruction::<F, EF>::AddF(1, 2, 3), 0);
+        encoded.op_c.0[1] = F::one();
+        assert_eq!(
+            AsmInstruction::<F, EF>::decode(&encoded, 0).unwrap_err(),
+            DecodeError::NotCanonical {
+                pc: 0,
+                opcode: Opcode::ADD
+            }
+        );
+
+        let program = program(vec![AsmInstruction::Bne(F::zero(), 1, 2)]);
+        let mut instructions = program.instructions.clone();
+        instructions[0].op_c.0[0] = F::two();
+        let program = RecursionProgram {
+            instructions,
+            traces: program.traces,
+            source_map: program.source_map,
+            verified_fingerprint: None,
+        };
+        assert_eq!(
+            AssemblyCode::<F, EF>::disassemble(&program).unwrap_err(),
+            DecodeError::TargetOutOfRange { pc: 0, target: 2 }
+        );
+    }
+}
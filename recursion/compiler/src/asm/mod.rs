@@ -2,6 +2,7 @@ mod builder;
 mod code;
 mod compiler;
 mod config;
+mod decode;
 mod instruction;
 mod utils;
 
@@ -9,5 +10,6 @@ pub use builder::*;
 pub use code::*;
 pub use compiler::*;
 pub use config::*;
+pub use decode::*;
 pub use instruction::*;
 pub use utils::*;
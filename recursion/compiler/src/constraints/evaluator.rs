@@ -0,0 +1,683 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use p3_baby_bear::BabyBear;
+use p3_bn254_fr::Bn254Fr;
+use p3_field::{AbstractExtensionField, AbstractField, Field, PrimeField, PrimeField32};
+use p3_symmetric::Permutation;
+use sp1_core::utils::{inner_perm, InnerPerm};
+use sp1_recursion_core::runtime::{SourceLocation, SourceMap};
+use sp1_recursion_core::stark::config::{outer_perm, OuterPerm};
+
+use super::opcodes::ConstraintOpcode;
+use super::{Constraint, ConstraintSink};
+use crate::config::OuterConfig;
+use crate::ir::{Config, Witness};
+
+type EF = <OuterConfig as Config>::EF;
+
+/// The error returned by [evaluate_constraints] for the first constraint the witness doesn't
+/// satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint {
+    /// The index of the constraint in the circuit.
+    pub index: usize,
+    pub opcode: ConstraintOpcode,
+    pub failure: ConstraintFailure,
+    /// The builder call the constraint was generated by, see [UnsatisfiedConstraint::locate].
+    pub source: Option<SourceLocation>,
+}
+
+impl UnsatisfiedConstraint {
+    /// Looks up the source location of the constraint in the source map of the circuit, that is
+    /// [super::ConstraintCompiler::source_map] if the builder tracked its sources.
+    pub fn locate(mut self, source_map: &SourceMap) -> Self {
+        self.source = source_map.get(self.index).cloned();
+        self
+    }
+}
+
+impl fmt::Display for UnsatisfiedConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} ({:?}) is not satisfied: {}",
+            self.index, self.opcode, self.failure
+        )?;
+        if let Some(source) = &self.source {
+            write!(f, ", from {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsatisfiedConstraint {}
+
+/// Why a constraint is not satisfied, with the values it was evaluated on, written in decimal and
+/// extension elements as the list of their coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintFailure {
+    /// The two sides of an assertion differ.
+    NotEqual { left: String, right: String },
+    /// A division of `left` by a zero `right`, which an inversion is with `left` one.
+    DivisionByZero { left: String, right: String },
+    /// `left` doesn't fit in `bits` bits, and `output` is the value of its decomposition.
+    TooManyBits {
+        left: String,
+        bits: usize,
+        output: String,
+    },
+    /// The condition of a select is neither zero nor one.
+    NonBooleanCondition { left: String },
+    /// The argument is not defined by an earlier constraint.
+    Undefined(String),
+    /// The arguments don't have the shape of the opcode.
+    Malformed(String),
+    /// The opcode is not implemented by the gnark circuit either.
+    Unsupported,
+}
+
+impl fmt::Display for ConstraintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEqual { left, right } => write!(f, "left {} != right {}", left, right),
+            Self::DivisionByZero { left, right } => {
+                write!(f, "division of left {} by right {}", left, right)
+            }
+            Self::TooManyBits { left, bits, output } => write!(
+                f,
+                "left {} doesn't fit in {} bits, whose output is {}",
+                left, bits, output
+            ),
+            Self::NonBooleanCondition { left } => {
+                write!(f, "the condition {} is not boolean", left)
+            }
+            Self::Undefined(id) => write!(f, "{} is undefined", id),
+            Self::Malformed(message) => write!(f, "malformed arguments, {}", message),
+            Self::Unsupported => write!(f, "the opcode is unsupported"),
+        }
+    }
+}
+
+/// Checks that `witness` satisfies `constraints`, evaluating them in Rust over [Bn254Fr] instead
+/// of solving the gnark circuit through the FFI.
+///
+/// The values of the variables are generated as the gnark circuit assigns them, so that this
+/// accepts the circuits `PlonkBn254Prover::test` accepts, without the Go toolchain.
+pub fn evaluate_constraints(
+    constraints: &[Constraint],
+    witness: &Witness<OuterConfig>,
+) -> Result<(), UnsatisfiedConstraint> {
+    let mut evaluator = ConstraintEvaluator::new(witness.clone());
+    for constraint in constraints {
+        evaluator.eval(constraint)?;
+    }
+    Ok(())
+}
+
+/// The Rust witness generator of the outer circuit, assigning its variables one constraint at a
+/// time as the gnark circuit does and checking its assertions.
+///
+/// As a [ConstraintSink], it evaluates the constraints as they are emitted and keeps the first
+/// failure, returned by [ConstraintEvaluator::finish].
+pub struct ConstraintEvaluator {
+    witness: Witness<OuterConfig>,
+    vars: HashMap<String, Bn254Fr>,
+    felts: HashMap<String, BabyBear>,
+    exts: HashMap<String, EF>,
+    outer_perm: OuterPerm,
+    inner_perm: InnerPerm,
+    len: usize,
+    error: Option<UnsatisfiedConstraint>,
+}
+
+impl ConstraintEvaluator {
+    pub fn new(witness: Witness<OuterConfig>) -> Self {
+        Self {
+            witness,
+            vars: HashMap::new(),
+            felts: HashMap::new(),
+            exts: HashMap::new(),
+            outer_perm: outer_perm(),
+            inner_perm: inner_perm(),
+            len: 0,
+            error: None,
+        }
+    }
+
+    /// The value assigned to the variable `id`.
+    pub fn var(&self, id: &str) -> Option<Bn254Fr> {
+        self.vars.get(id).copied()
+    }
+
+    /// The value assigned to the felt `id`.
+    pub fn felt(&self, id: &str) -> Option<BabyBear> {
+        self.felts.get(id).copied()
+    }
+
+    /// The value assigned to the extension element `id`.
+    pub fn ext(&self, id: &str) -> Option<EF> {
+        self.exts.get(id).copied()
+    }
+
+    /// Evaluates the next constraint of the circuit.
+    pub fn eval(&mut self, constraint: &Constraint) -> Result<(), UnsatisfiedConstraint> {
+        let index = self.len;
+        self.len += 1;
+        self.eval_opcode(&constraint.opcode, &constraint.args)
+            .map_err(|failure| UnsatisfiedConstraint {
+                index,
+                opcode: constraint.opcode.clone(),
+                failure,
+                source: None,
+            })
+    }
+
+    /// Returns the first unsatisfied constraint pushed to the evaluator, if any.
+    pub fn finish(self) -> Result<(), UnsatisfiedConstraint> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn eval_opcode(
+        &mut self,
+        opcode: &ConstraintOpcode,
+        args: &[Vec<String>],
+    ) -> Result<(), ConstraintFailure> {
+        match opcode {
+            ConstraintOpcode::ImmV => {
+                let value = parse(arg(args, 1, 0)?)?;
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::ImmF => {
+                let value = parse(arg(args, 1, 0)?)?;
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::ImmE => {
+                let coordinates = args
+                    .get(1)
+                    .filter(|coordinates| coordinates.len() == 4)
+                    .ok_or_else(|| malformed("an extension element has 4 coordinates"))?;
+                let coordinates = coordinates
+                    .iter()
+                    .map(|x| parse(x))
+                    .collect::<Result<Vec<BabyBear>, _>>()?;
+                self.set_ext(args, 0, EF::from_base_slice(&coordinates))
+            }
+            ConstraintOpcode::AddV => {
+                self.set_var(args, 0, self.var_arg(args, 1)? + self.var_arg(args, 2)?)
+            }
+            ConstraintOpcode::AddF => {
+                self.set_felt(args, 0, self.felt_arg(args, 1)? + self.felt_arg(args, 2)?)
+            }
+            ConstraintOpcode::AddE => {
+                self.set_ext(args, 0, self.ext_arg(args, 1)? + self.ext_arg(args, 2)?)
+            }
+            ConstraintOpcode::AddEF => {
+                self.set_ext(args, 0, self.ext_arg(args, 1)? + self.felt_arg(args, 2)?)
+            }
+            ConstraintOpcode::SubV => {
+                self.set_var(args, 0, self.var_arg(args, 1)? - self.var_arg(args, 2)?)
+            }
+            ConstraintOpcode::SubF => {
+                self.set_felt(args, 0, self.felt_arg(args, 1)? - self.felt_arg(args, 2)?)
+            }
+            ConstraintOpcode::SubE => {
+                self.set_ext(args, 0, self.ext_arg(args, 1)? - self.ext_arg(args, 2)?)
+            }
+            ConstraintOpcode::SubEF => {
+                self.set_ext(args, 0, self.ext_arg(args, 1)? - self.felt_arg(args, 2)?)
+            }
+            ConstraintOpcode::MulV => {
+                self.set_var(args, 0, self.var_arg(args, 1)? * self.var_arg(args, 2)?)
+            }
+            ConstraintOpcode::MulF => {
+                self.set_felt(args, 0, self.felt_arg(args, 1)? * self.felt_arg(args, 2)?)
+            }
+            ConstraintOpcode::MulE => {
+                self.set_ext(args, 0, self.ext_arg(args, 1)? * self.ext_arg(args, 2)?)
+            }
+            ConstraintOpcode::MulEF => {
+                self.set_ext(args, 0, self.ext_arg(args, 1)? * self.felt_arg(args, 2)?)
+            }
+            ConstraintOpcode::DivE => {
+                let (a, b) = (self.ext_arg(args, 1)?, self.ext_arg(args, 2)?);
+                let inverse = b
+                    .try_inverse()
+                    .ok_or_else(|| ConstraintFailure::DivisionByZero {
+                        left: ext_string(a),
+                        right: ext_string(b),
+                    })?;
+                self.set_ext(args, 0, a * inverse)
+            }
+            ConstraintOpcode::NegE => self.set_ext(args, 0, -self.ext_arg(args, 1)?),
+            ConstraintOpcode::InvE => {
+                let a = self.ext_arg(args, 1)?;
+                let inverse = a
+                    .try_inverse()
+                    .ok_or_else(|| ConstraintFailure::DivisionByZero {
+                        left: ext_string(EF::one()),
+                        right: ext_string(a),
+                    })?;
+                self.set_ext(args, 0, inverse)
+            }
+            ConstraintOpcode::Num2BitsV => {
+                let value = self.var_arg(args, 1)?;
+                let bits = arg(args, 2, 0)?
+                    .parse::<usize>()
+                    .map_err(|_| malformed("the number of bits is not an integer"))?;
+                self.num2bits(args, value, bits)
+            }
+            ConstraintOpcode::Num2BitsF => {
+                let value = self.felt_arg(args, 1)?;
+                self.num2bits(
+                    args,
+                    Bn254Fr::from_canonical_u32(value.as_canonical_u32()),
+                    32,
+                )
+            }
+            ConstraintOpcode::Permute => {
+                let mut state = [
+                    self.var_arg(args, 0)?,
+                    self.var_arg(args, 1)?,
+                    self.var_arg(args, 2)?,
+                ];
+                self.outer_perm.permute_mut(&mut state);
+                for (i, value) in state.into_iter().enumerate() {
+                    self.set_var(args, i, value)?;
+                }
+                Ok(())
+            }
+            ConstraintOpcode::PermuteBabyBear => {
+                let mut state = [BabyBear::zero(); 16];
+                for (i, value) in state.iter_mut().enumerate() {
+                    *value = self.felt_arg(args, i)?;
+                }
+                self.inner_perm.permute_mut(&mut state);
+                for (i, value) in state.into_iter().enumerate() {
+                    self.set_felt(args, i, value)?;
+                }
+                Ok(())
+            }
+            ConstraintOpcode::SelectV => {
+                let value = self.select(args, |e, i| e.var_arg(args, i))?;
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::SelectF => {
+                let value = self.select(args, |e, i| e.felt_arg(args, i))?;
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::SelectE => {
+                let value = self.select(args, |e, i| e.ext_arg(args, i))?;
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::Ext2Felt => {
+                let value = self.ext_arg(args, 4)?;
+                for (i, coordinate) in value.as_base_slice().iter().enumerate() {
+                    self.set_felt(args, i, *coordinate)?;
+                }
+                Ok(())
+            }
+            ConstraintOpcode::CircuitFelts2Ext => {
+                let coordinates = (1..5)
+                    .map(|i| self.felt_arg(args, i))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.set_ext(args, 0, EF::from_base_slice(&coordinates))
+            }
+            ConstraintOpcode::AssertEqV => {
+                let (left, right) = (self.var_arg(args, 0)?, self.var_arg(args, 1)?);
+                assert_eq(left, right, var_string)
+            }
+            ConstraintOpcode::AssertEqF => {
+                let (left, right) = (self.felt_arg(args, 0)?, self.felt_arg(args, 1)?);
+                assert_eq(left, right, felt_string)
+            }
+            ConstraintOpcode::AssertEqE => {
+                let (left, right) = (self.ext_arg(args, 0)?, self.ext_arg(args, 1)?);
+                assert_eq(left, right, ext_string)
+            }
+            ConstraintOpcode::PrintV => {
+                println!("{}", var_string(self.var_arg(args, 0)?));
+                Ok(())
+            }
+            ConstraintOpcode::PrintF => {
+                println!("{}", felt_string(self.felt_arg(args, 0)?));
+                Ok(())
+            }
+            ConstraintOpcode::PrintE => {
+                println!("{}", ext_string(self.ext_arg(args, 0)?));
+                Ok(())
+            }
+            ConstraintOpcode::WitnessV => {
+                let value = witness_value(&self.witness.vars, args)?;
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::WitnessF => {
+                let value = witness_value(&self.witness.felts, args)?;
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::WitnessE => {
+                let value = witness_value(&self.witness.exts, args)?;
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::CommitVkeyHash => {
+                assert_eq(self.var_arg(args, 0)?, self.witness.vkey_hash, var_string)
+            }
+            ConstraintOpcode::CommitCommitedValuesDigest => assert_eq(
+                self.var_arg(args, 0)?,
+                self.witness.commited_values_digest,
+                var_string,
+            ),
+            ConstraintOpcode::CommitInputDigest => assert_eq(
+                self.var_arg(args, 0)?,
+                self.witness.input_digest,
+                var_string,
+            ),
+            ConstraintOpcode::DivF
+            | ConstraintOpcode::DivEF
+            | ConstraintOpcode::NegV
+            | ConstraintOpcode::NegF
+            | ConstraintOpcode::InvV
+            | ConstraintOpcode::InvF => Err(ConstraintFailure::Unsupported),
+        }
+    }
+
+    /// Decomposes `value` into the little endian bits assigned to the variables of `args[0]`,
+    /// checking that it fits in `bits` bits.
+    fn num2bits(
+        &mut self,
+        args: &[Vec<String>],
+        value: Bn254Fr,
+        bits: usize,
+    ) -> Result<(), ConstraintFailure> {
+        let outputs = args.first().map_or(0, |outputs| outputs.len());
+        if outputs > bits {
+            return Err(malformed("there are more outputs than bits"));
+        }
+        let canonical = value.as_canonical_biguint();
+        if canonical.bits() > bits as u64 {
+            let output = (0..bits).rev().fold(Bn254Fr::zero(), |acc, i| {
+                acc.double() + Bn254Fr::from_bool(canonical.bit(i as u64))
+            });
+            return Err(ConstraintFailure::TooManyBits {
+                left: var_string(value),
+                bits,
+                output: var_string(output),
+            });
+        }
+        for i in 0..outputs {
+            let bit = Bn254Fr::from_bool(canonical.bit(i as u64));
+            self.vars.insert(args[0][i].clone(), bit);
+        }
+        Ok(())
+    }
+
+    /// The value of `args[2]` if the condition `args[1]` is one, and of `args[3]` if it is zero.
+    fn select<T>(
+        &self,
+        args: &[Vec<String>],
+        value: impl Fn(&Self, usize) -> Result<T, ConstraintFailure>,
+    ) -> Result<T, ConstraintFailure> {
+        let condition = self.var_arg(args, 1)?;
+        if condition == Bn254Fr::one() {
+            value(self, 2)
+        } else if condition == Bn254Fr::zero() {
+            value(self, 3)
+        } else {
+            Err(ConstraintFailure::NonBooleanCondition {
+                left: var_string(condition),
+            })
+        }
+    }
+
+    fn var_arg(&self, args: &[Vec<String>], i: usize) -> Result<Bn254Fr, ConstraintFailure> {
+        let id = arg(args, i, 0)?;
+        self.var(id)
+            .ok_or_else(|| ConstraintFailure::Undefined(id.to_string()))
+    }
+
+    fn felt_arg(&self, args: &[Vec<String>], i: usize) -> Result<BabyBear, ConstraintFailure> {
+        let id = arg(args, i, 0)?;
+        self.felt(id)
+            .ok_or_else(|| ConstraintFailure::Undefined(id.to_string()))
+    }
+
+    fn ext_arg(&self, args: &[Vec<String>], i: usize) -> Result<EF, ConstraintFailure> {
+        let id = arg(args, i, 0)?;
+        self.ext(id)
+            .ok_or_else(|| ConstraintFailure::Undefined(id.to_string()))
+    }
+
+    fn set_var(
+        &mut self,
+        args: &[Vec<String>],
+        i: usize,
+        value: Bn254Fr,
+    ) -> Result<(), ConstraintFailure> {
+        self.vars.insert(arg(args, i, 0)?.to_string(), value);
+        Ok(())
+    }
+
+    fn set_felt(
+        &mut self,
+        args: &[Vec<String>],
+        i: usize,
+        value: BabyBear,
+    ) -> Result<(), ConstraintFailure> {
+        self.felts.insert(arg(args, i, 0)?.to_string(), value);
+        Ok(())
+    }
+
+    fn set_ext(
+        &mut self,
+        args: &[Vec<String>],
+        i: usize,
+        value: EF,
+    ) -> Result<(), ConstraintFailure> {
+        self.exts.insert(arg(args, i, 0)?.to_string(), value);
+        Ok(())
+    }
+}
+
+impl ConstraintSink for ConstraintEvaluator {
+    fn push(&mut self, constraint: Constraint) {
+        if self.error.is_none() {
+            self.error = self.eval(&constraint).err();
+        }
+    }
+}
+
+pub(super) fn malformed(message: &str) -> ConstraintFailure {
+    ConstraintFailure::Malformed(message.to_string())
+}
+
+pub(super) fn arg(args: &[Vec<String>], i: usize, j: usize) -> Result<&str, ConstraintFailure> {
+    args.get(i)
+        .and_then(|arg| arg.get(j))
+        .map(|arg| arg.as_str())
+        .ok_or_else(|| ConstraintFailure::Malformed(format!("missing argument {}", i)))
+}
+
+/// Parses a decimal number into a field element, reduced as gnark reduces constants.
+pub(super) fn parse<F: AbstractField>(value: &str) -> Result<F, ConstraintFailure> {
+    if value.is_empty() {
+        return Err(malformed("an immediate is empty"));
+    }
+    value.chars().try_fold(F::zero(), |acc, c| {
+        let digit = c
+            .to_digit(10)
+            .ok_or_else(|| ConstraintFailure::Malformed(format!("{} is not decimal", value)))?;
+        Ok(acc * F::from_canonical_u32(10) + F::from_canonical_u32(digit))
+    })
+}
+
+pub(super) fn witness_value<T: Copy>(
+    values: &[T],
+    args: &[Vec<String>],
+) -> Result<T, ConstraintFailure> {
+    let index = arg(args, 1, 0)?;
+    index
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| values.get(index).copied())
+        .ok_or_else(|| ConstraintFailure::Malformed(format!("no witness at index {}", index)))
+}
+
+fn assert_eq<T: PartialEq>(
+    left: T,
+    right: T,
+    to_string: fn(T) -> String,
+) -> Result<(), ConstraintFailure> {
+    if left == right {
+        Ok(())
+    } else {
+        Err(ConstraintFailure::NotEqual {
+            left: to_string(left),
+            right: to_string(right),
+        })
+    }
+}
+
+pub(super) fn var_string(value: Bn254Fr) -> String {
+    value.as_canonical_biguint().to_string()
+}
+
+fn felt_string(value: BabyBear) -> String {
+    value.as_canonical_u32().to_string()
+}
+
+fn ext_string(value: EF) -> String {
+    let coordinates = value
+        .as_base_slice()
+        .iter()
+        .map(|x| felt_string(*x))
+        .collect::<Vec<_>>();
+    format!("[{}]", coordinates.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::ConstraintCompiler;
+    use crate::ir::{Builder, Felt, Var};
+
+    #[test]
+    fn test_evaluate_constraints() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let a: Var<_> = builder.eval(Bn254Fr::from_canonical_u32(6));
+        let b: Var<_> = builder.eval(a * Bn254Fr::from_canonical_u32(7));
+        builder.assert_var_eq(b, Bn254Fr::from_canonical_u32(42));
+        let bits = builder.num2bits_v_circuit(b, 6);
+        builder.assert_var_eq(bits[1], Bn254Fr::one());
+        builder.assert_var_eq(bits[2], Bn254Fr::zero());
+        let x: Felt<_> = builder.eval(BabyBear::from_canonical_u32(3));
+        let y: Felt<_> = builder.eval(x * x);
+        builder.assert_felt_eq(y, BabyBear::from_canonical_u32(9));
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations.clone());
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
+
+        // Evaluating the constraints as they are emitted gives the same result.
+        let mut evaluator = ConstraintEvaluator::new(Witness::default());
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        backend.emit_into(builder.operations, &mut evaluator);
+        evaluator.finish().unwrap();
+    }
+
+    #[test]
+    fn test_unsatisfied_constraint() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let a: Var<_> = builder.eval(Bn254Fr::from_canonical_u32(6));
+        builder.assert_var_eq(a, Bn254Fr::from_canonical_u32(7));
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let err = evaluate_constraints(&constraints, &Witness::default()).unwrap_err();
+        let index = constraints
+            .iter()
+            .position(|c| c.opcode == ConstraintOpcode::AssertEqV)
+            .unwrap();
+        assert_eq!(err.index, index);
+        assert_eq!(
+            err.failure,
+            ConstraintFailure::NotEqual {
+                left: "6".to_string(),
+                right: "7".to_string(),
+            }
+        );
+        assert!(err.to_string().contains("left 6 != right 7"));
+    }
+
+    #[test]
+    fn test_unsatisfied_constraint_source() {
+        let mut builder = Builder::<OuterConfig>::default();
+        builder.set_track_sources(true);
+        let a: Var<_> = builder.eval(Bn254Fr::from_canonical_u32(6));
+        builder.assert_var_eq(a, Bn254Fr::from_canonical_u32(7));
+        let line = line!() - 1;
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let err = evaluate_constraints(&constraints, &Witness::default())
+            .unwrap_err()
+            .locate(&backend.source_map);
+        let source = err.source.as_ref().unwrap();
+        assert_eq!(
+            (source.location.file(), source.location.line()),
+            (file!(), line)
+        );
+        assert!(err
+            .to_string()
+            .contains(&format!("from {}:{}", file!(), line)));
+    }
+
+    #[test]
+    fn test_num2bits_overflow() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let a: Var<_> = builder.eval(Bn254Fr::from_canonical_u32(0b10110));
+        builder.num2bits_v_circuit(a, 4);
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let err = evaluate_constraints(&constraints, &Witness::default()).unwrap_err();
+        assert_eq!(err.opcode, ConstraintOpcode::Num2BitsV);
+        assert_eq!(
+            err.failure,
+            ConstraintFailure::TooManyBits {
+                left: "22".to_string(),
+                bits: 4,
+                output: "6".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_witness_and_commitments() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let vkey_hash: Var<_> = builder.witness_var();
+        builder.commit_vkey_hash_circuit(vkey_hash);
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let mut witness = Witness::<OuterConfig> {
+            vars: vec![Bn254Fr::from_canonical_u32(5)],
+            vkey_hash: Bn254Fr::from_canonical_u32(5),
+            ..Default::default()
+        };
+        evaluate_constraints(&constraints, &witness).unwrap();
+
+        witness.vkey_hash = Bn254Fr::from_canonical_u32(6);
+        let err = evaluate_constraints(&constraints, &witness).unwrap_err();
+        assert_eq!(err.opcode, ConstraintOpcode::CommitVkeyHash);
+        assert_eq!(
+            err.failure,
+            ConstraintFailure::NotEqual {
+                left: "5".to_string(),
+                right: "6".to_string(),
+            }
+        );
+    }
+}
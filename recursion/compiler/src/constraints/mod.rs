@@ -1,13 +1,22 @@
+mod evaluator;
 pub mod opcodes;
+mod sink;
+#[cfg(feature = "ultraplonk")]
+pub mod ultraplonk;
 
 use core::fmt::Debug;
 use p3_field::AbstractExtensionField;
 use p3_field::Field;
 use p3_field::PrimeField;
 use serde::{Deserialize, Serialize};
+use sp1_recursion_core::runtime::SourceMap;
 use std::marker::PhantomData;
 
+pub use self::evaluator::{
+    evaluate_constraints, ConstraintEvaluator, ConstraintFailure, UnsatisfiedConstraint,
+};
 use self::opcodes::ConstraintOpcode;
+pub use self::sink::ConstraintSink;
 use crate::ir::Config;
 use crate::ir::DslIr;
 use crate::prelude::TracedVec;
@@ -23,9 +32,26 @@ pub struct Constraint {
 #[derive(Debug, Clone, Default)]
 pub struct ConstraintCompiler<C: Config> {
     pub allocator: usize,
+    /// The source locations of the constraints of the last circuit emitted, keyed by the index of
+    /// the first constraint of each operation. It is empty unless the builder tracked its sources,
+    /// and is never written with the constraints.
+    pub source_map: SourceMap,
     pub phantom: PhantomData<C>,
 }
 
+/// Counts the constraints pushed to a sink, which are the indices of the source map.
+struct CountingSink<'a, S> {
+    sink: &'a mut S,
+    len: usize,
+}
+
+impl<'a, S: ConstraintSink> ConstraintSink for CountingSink<'a, S> {
+    fn push(&mut self, constraint: Constraint) {
+        self.len += 1;
+        self.sink.push(constraint);
+    }
+}
+
 impl<C: Config + Debug> ConstraintCompiler<C> {
     /// Allocate a new variable name in the constraint system.
     pub fn alloc_id(&mut self) -> String {
@@ -35,7 +61,7 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
     }
 
     /// Allocates a variable in the constraint system.
-    pub fn alloc_v(&mut self, constraints: &mut Vec<Constraint>, value: C::N) -> String {
+    pub fn alloc_v(&mut self, constraints: &mut impl ConstraintSink, value: C::N) -> String {
         let tmp_id = self.alloc_id();
         constraints.push(Constraint {
             opcode: ConstraintOpcode::ImmV,
@@ -48,7 +74,7 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
     }
 
     /// Allocate a felt in the constraint system.
-    pub fn alloc_f(&mut self, constraints: &mut Vec<Constraint>, value: C::F) -> String {
+    pub fn alloc_f(&mut self, constraints: &mut impl ConstraintSink, value: C::F) -> String {
         let tmp_id = self.alloc_id();
         constraints.push(Constraint {
             opcode: ConstraintOpcode::ImmF,
@@ -61,7 +87,7 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
     }
 
     /// Allocate an extension element in the constraint system.
-    pub fn alloc_e(&mut self, constraints: &mut Vec<Constraint>, value: C::EF) -> String {
+    pub fn alloc_e(&mut self, constraints: &mut impl ConstraintSink, value: C::EF) -> String {
         let tmp_id = self.alloc_id();
         constraints.push(Constraint {
             opcode: ConstraintOpcode::ImmE,
@@ -80,17 +106,41 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
     /// Emit the constraints from a list of operations in the DSL.
     pub fn emit(&mut self, operations: TracedVec<DslIr<C>>) -> Vec<Constraint> {
         let mut constraints: Vec<Constraint> = Vec::new();
-        for (instruction, _) in operations {
+        self.emit_into(operations, &mut constraints);
+        constraints
+    }
+
+    /// Emit the constraints from a list of operations in the DSL into `sink`, one at a time and in
+    /// the order of [Self::emit].
+    ///
+    /// Unlike [Self::emit], this never holds the constraints of the whole circuit in memory, which
+    /// for the wrap circuit are about as large as its operations: streamed into a file, the peak
+    /// memory of building the circuit is that of the operations alone. The difference can be
+    /// measured by comparing the peak resident memory of
+    /// `sp1_prover::build::build_plonk_bn254_artifacts_with_dummy`, which streams the constraints,
+    /// with that of building the same circuit through [Self::emit].
+    pub fn emit_into<S: ConstraintSink>(
+        &mut self,
+        mut operations: TracedVec<DslIr<C>>,
+        sink: &mut S,
+    ) {
+        self.source_map = SourceMap::default();
+        let sources = operations.sources.take();
+        let sink = &mut CountingSink { sink, len: 0 };
+        for (i, (instruction, _)) in operations.into_iter().enumerate() {
+            if let Some(source) = sources.as_ref().and_then(|s| s.locations.get(&i)) {
+                self.source_map.insert(sink.len, source.clone());
+            }
             match instruction {
-                DslIr::ImmV(a, b) => constraints.push(Constraint {
+                DslIr::ImmV(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::ImmV,
                     args: vec![vec![a.id()], vec![b.as_canonical_biguint().to_string()]],
                 }),
-                DslIr::ImmF(a, b) => constraints.push(Constraint {
+                DslIr::ImmF(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::ImmF,
                     args: vec![vec![a.id()], vec![b.as_canonical_biguint().to_string()]],
                 }),
-                DslIr::ImmE(a, b) => constraints.push(Constraint {
+                DslIr::ImmE(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::ImmE,
                     args: vec![
                         vec![a.id()],
@@ -100,140 +150,140 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
                             .collect(),
                     ],
                 }),
-                DslIr::AddV(a, b, c) => constraints.push(Constraint {
+                DslIr::AddV(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AddV,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::AddVI(a, b, c) => {
-                    let tmp = self.alloc_v(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_v(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AddV,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
-                DslIr::AddF(a, b, c) => constraints.push(Constraint {
+                DslIr::AddF(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AddF,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::AddFI(a, b, c) => {
-                    let tmp = self.alloc_f(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_f(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AddF,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
-                DslIr::AddE(a, b, c) => constraints.push(Constraint {
+                DslIr::AddE(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AddE,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
-                DslIr::AddEF(a, b, c) => constraints.push(Constraint {
+                DslIr::AddEF(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AddEF,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::AddEFI(a, b, c) => {
-                    let tmp = self.alloc_f(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_f(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AddEF,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
                 DslIr::AddEI(a, b, c) => {
-                    let tmp = self.alloc_e(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AddE,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
                 DslIr::AddEFFI(a, b, c) => {
-                    let tmp = self.alloc_e(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AddEF,
                         args: vec![vec![a.id()], vec![tmp], vec![b.id()]],
                     });
                 }
-                DslIr::SubV(a, b, c) => constraints.push(Constraint {
+                DslIr::SubV(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::SubV,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
-                DslIr::SubF(a, b, c) => constraints.push(Constraint {
+                DslIr::SubF(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::SubF,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
-                DslIr::SubE(a, b, c) => constraints.push(Constraint {
+                DslIr::SubE(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::SubE,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
-                DslIr::SubEF(a, b, c) => constraints.push(Constraint {
+                DslIr::SubEF(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::SubEF,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::SubEI(a, b, c) => {
-                    let tmp = self.alloc_e(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::SubE,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
                 DslIr::SubEIN(a, b, c) => {
-                    let tmp = self.alloc_e(&mut constraints, b);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, b);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::SubE,
                         args: vec![vec![a.id()], vec![tmp], vec![c.id()]],
                     });
                 }
-                DslIr::MulV(a, b, c) => constraints.push(Constraint {
+                DslIr::MulV(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::MulV,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::MulVI(a, b, c) => {
-                    let tmp = self.alloc_v(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_v(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::MulV,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
-                DslIr::MulF(a, b, c) => constraints.push(Constraint {
+                DslIr::MulF(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::MulF,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
-                DslIr::MulE(a, b, c) => constraints.push(Constraint {
+                DslIr::MulE(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::MulE,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::MulEI(a, b, c) => {
-                    let tmp = self.alloc_e(&mut constraints, c);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, c);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::MulE,
                         args: vec![vec![a.id()], vec![b.id()], vec![tmp]],
                     });
                 }
-                DslIr::MulEF(a, b, c) => constraints.push(Constraint {
+                DslIr::MulEF(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::MulEF,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::DivFIN(a, b, c) => {
-                    let tmp = self.alloc_f(&mut constraints, b.inverse());
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_f(sink, b.inverse());
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::MulF,
                         args: vec![vec![a.id()], vec![tmp], vec![c.id()]],
                     });
                 }
-                DslIr::DivE(a, b, c) => constraints.push(Constraint {
+                DslIr::DivE(a, b, c) => sink.push(Constraint {
                     opcode: ConstraintOpcode::DivE,
                     args: vec![vec![a.id()], vec![b.id()], vec![c.id()]],
                 }),
                 DslIr::DivEIN(a, b, c) => {
-                    let tmp = self.alloc_e(&mut constraints, b);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, b);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::DivE,
                         args: vec![vec![a.id()], vec![tmp], vec![c.id()]],
                     });
                 }
-                DslIr::NegE(a, b) => constraints.push(Constraint {
+                DslIr::NegE(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::NegE,
                     args: vec![vec![a.id()], vec![b.id()]],
                 }),
-                DslIr::CircuitNum2BitsV(value, bits, output) => constraints.push(Constraint {
+                DslIr::CircuitNum2BitsV(value, bits, output) => sink.push(Constraint {
                     opcode: ConstraintOpcode::Num2BitsV,
                     args: vec![
                         output.iter().map(|x| x.id()).collect(),
@@ -241,38 +291,38 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
                         vec![bits.to_string()],
                     ],
                 }),
-                DslIr::CircuitNum2BitsF(value, output) => constraints.push(Constraint {
+                DslIr::CircuitNum2BitsF(value, output) => sink.push(Constraint {
                     opcode: ConstraintOpcode::Num2BitsF,
                     args: vec![output.iter().map(|x| x.id()).collect(), vec![value.id()]],
                 }),
-                DslIr::CircuitPoseidon2Permute(state) => constraints.push(Constraint {
+                DslIr::CircuitPoseidon2Permute(state) => sink.push(Constraint {
                     opcode: ConstraintOpcode::Permute,
                     args: state.iter().map(|x| vec![x.id()]).collect(),
                 }),
-                DslIr::CircuitPoseidon2PermuteBabyBear(state) => constraints.push(Constraint {
+                DslIr::CircuitPoseidon2PermuteBabyBear(state) => sink.push(Constraint {
                     opcode: ConstraintOpcode::PermuteBabyBear,
                     args: state.iter().map(|x| vec![x.id()]).collect(),
                 }),
                 DslIr::CircuitSelectV(cond, a, b, out) => {
-                    constraints.push(Constraint {
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::SelectV,
                         args: vec![vec![out.id()], vec![cond.id()], vec![a.id()], vec![b.id()]],
                     });
                 }
                 DslIr::CircuitSelectF(cond, a, b, out) => {
-                    constraints.push(Constraint {
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::SelectF,
                         args: vec![vec![out.id()], vec![cond.id()], vec![a.id()], vec![b.id()]],
                     });
                 }
                 DslIr::CircuitSelectE(cond, a, b, out) => {
-                    constraints.push(Constraint {
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::SelectE,
                         args: vec![vec![out.id()], vec![cond.id()], vec![a.id()], vec![b.id()]],
                     });
                 }
                 DslIr::CircuitExt2Felt(a, b) => {
-                    constraints.push(Constraint {
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::Ext2Felt,
                         args: vec![
                             vec![a[0].id()],
@@ -283,72 +333,76 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
                         ],
                     });
                 }
-                DslIr::AssertEqV(a, b) => constraints.push(Constraint {
+                DslIr::AssertEqV(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AssertEqV,
                     args: vec![vec![a.id()], vec![b.id()]],
                 }),
                 DslIr::AssertEqVI(a, b) => {
-                    let tmp = self.alloc_v(&mut constraints, b);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_v(sink, b);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AssertEqV,
                         args: vec![vec![a.id()], vec![tmp]],
                     });
                 }
-                DslIr::AssertEqF(a, b) => constraints.push(Constraint {
+                DslIr::AssertEqF(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AssertEqF,
                     args: vec![vec![a.id()], vec![b.id()]],
                 }),
                 DslIr::AssertEqFI(a, b) => {
-                    let tmp = self.alloc_f(&mut constraints, b);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_f(sink, b);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AssertEqF,
                         args: vec![vec![a.id()], vec![tmp]],
                     });
                 }
-                DslIr::AssertEqE(a, b) => constraints.push(Constraint {
+                DslIr::AssertEqE(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::AssertEqE,
                     args: vec![vec![a.id()], vec![b.id()]],
                 }),
                 DslIr::AssertEqEI(a, b) => {
-                    let tmp = self.alloc_e(&mut constraints, b);
-                    constraints.push(Constraint {
+                    let tmp = self.alloc_e(sink, b);
+                    sink.push(Constraint {
                         opcode: ConstraintOpcode::AssertEqE,
                         args: vec![vec![a.id()], vec![tmp]],
                     });
                 }
-                DslIr::PrintV(a) => constraints.push(Constraint {
+                DslIr::PrintV(a) => sink.push(Constraint {
                     opcode: ConstraintOpcode::PrintV,
                     args: vec![vec![a.id()]],
                 }),
-                DslIr::PrintF(a) => constraints.push(Constraint {
+                DslIr::PrintF(a) => sink.push(Constraint {
                     opcode: ConstraintOpcode::PrintF,
                     args: vec![vec![a.id()]],
                 }),
-                DslIr::PrintE(a) => constraints.push(Constraint {
+                DslIr::PrintE(a) => sink.push(Constraint {
                     opcode: ConstraintOpcode::PrintE,
                     args: vec![vec![a.id()]],
                 }),
-                DslIr::WitnessVar(a, b) => constraints.push(Constraint {
+                DslIr::WitnessVar(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::WitnessV,
                     args: vec![vec![a.id()], vec![b.to_string()]],
                 }),
-                DslIr::WitnessFelt(a, b) => constraints.push(Constraint {
+                DslIr::WitnessFelt(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::WitnessF,
                     args: vec![vec![a.id()], vec![b.to_string()]],
                 }),
-                DslIr::WitnessExt(a, b) => constraints.push(Constraint {
+                DslIr::WitnessExt(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::WitnessE,
                     args: vec![vec![a.id()], vec![b.to_string()]],
                 }),
-                DslIr::CircuitCommitVkeyHash(a) => constraints.push(Constraint {
+                DslIr::CircuitCommitVkeyHash(a) => sink.push(Constraint {
                     opcode: ConstraintOpcode::CommitVkeyHash,
                     args: vec![vec![a.id()]],
                 }),
-                DslIr::CircuitCommitCommitedValuesDigest(a) => constraints.push(Constraint {
+                DslIr::CircuitCommitCommitedValuesDigest(a) => sink.push(Constraint {
                     opcode: ConstraintOpcode::CommitCommitedValuesDigest,
                     args: vec![vec![a.id()]],
                 }),
-                DslIr::CircuitFelts2Ext(a, b) => constraints.push(Constraint {
+                DslIr::CircuitCommitInputDigest(a) => sink.push(Constraint {
+                    opcode: ConstraintOpcode::CommitInputDigest,
+                    args: vec![vec![a.id()]],
+                }),
+                DslIr::CircuitFelts2Ext(a, b) => sink.push(Constraint {
                     opcode: ConstraintOpcode::CircuitFelts2Ext,
                     args: vec![
                         vec![b.id()],
@@ -358,9 +412,11 @@ impl<C: Config + Debug> ConstraintCompiler<C> {
                         vec![a[3].id()],
                     ],
                 }),
+                DslIr::AssumeConstrainedV(..)
+                | DslIr::AssumeConstrainedF(..)
+                | DslIr::AssumeConstrainedE(..) => {}
                 _ => panic!("unsupported {:?}", instruction),
             };
         }
-        constraints
     }
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Operations that can be constrained inside the circuit.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConstraintOpcode {
     ImmV,
     ImmF,
@@ -45,6 +45,7 @@ pub enum ConstraintOpcode {
     WitnessE,
     CommitVkeyHash,
     CommitCommitedValuesDigest,
+    CommitInputDigest,
     CircuitFelts2Ext,
     PermuteBabyBear,
 }
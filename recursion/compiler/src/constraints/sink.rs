@@ -0,0 +1,17 @@
+use super::Constraint;
+
+/// A receiver of the constraints emitted by [super::ConstraintCompiler::emit_into], in order.
+///
+/// A sink that can fail, like a writer, keeps its first error and reports it once the circuit has
+/// been emitted, so that the compiler itself never fails.
+pub trait ConstraintSink {
+    /// Receives the next constraint of the circuit.
+    fn push(&mut self, constraint: Constraint);
+}
+
+/// Collects the constraints in memory, as [super::ConstraintCompiler::emit] does.
+impl ConstraintSink for Vec<Constraint> {
+    fn push(&mut self, constraint: Constraint) {
+        Vec::push(self, constraint);
+    }
+}
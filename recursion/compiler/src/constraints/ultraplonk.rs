@@ -0,0 +1,1004 @@
+//! Export of the outer circuit to the arithmetization of UltraPlonk and Honk provers.
+//!
+//! The constraints emitted by [super::ConstraintCompiler] are lowered to width-3 arithmetic gates
+//!
+//! ```text
+//! q_m·a·b + q_l·a + q_r·b + q_o·c + q_c = 0
+//! ```
+//!
+//! over the variables of the circuit, with range constraints on the variables which hold felts and
+//! the copy constraints of the variables used by several wires. The witness is exported alongside,
+//! with one value per variable in the order of their indices, so that an external prover can prove
+//! the circuit without solving it again.
+//!
+//! The lowering follows the gnark circuit: vars are variables, felts are variables reduced to their
+//! canonical value after every operation, extension elements are four felts and the BN254
+//! permutation is the permutation of the gnark circuit, gate by gate. The opcodes the gnark circuit
+//! doesn't implement, and the BabyBear permutation, are not supported.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use p3_baby_bear::BabyBear;
+use p3_bn254_fr::Bn254Fr;
+use p3_field::{AbstractExtensionField, AbstractField, Field, PrimeField, PrimeField32};
+use serde::{Deserialize, Serialize};
+use sp1_recursion_core::stark::poseidon2::bn254_poseidon2_rc3;
+
+use super::evaluator::{arg, malformed, parse, var_string, witness_value};
+use super::opcodes::ConstraintOpcode;
+use super::{Constraint, ConstraintFailure, ConstraintSink, UnsatisfiedConstraint};
+use crate::config::OuterConfig;
+use crate::ir::{Config, Witness};
+
+type EF = <OuterConfig as Config>::EF;
+
+/// The variable of the constant zero, which fills the unused wires of the gates.
+pub const ZERO_VARIABLE: u32 = 0;
+
+/// The variable of the public input holding the verifying key hash.
+pub const VKEY_HASH_VARIABLE: u32 = 1;
+
+/// The variable of the public input holding the committed values digest.
+pub const COMMITED_VALUES_DIGEST_VARIABLE: u32 = 2;
+
+/// The variable of the public input holding the input digest.
+pub const INPUT_DIGEST_VARIABLE: u32 = 3;
+
+/// The number of bits of a canonical felt, which is checked by the range constraints of the felts
+/// as it is by the gnark circuit.
+const FELT_BITS: u32 = 31;
+
+/// The number of full and partial rounds of the BN254 permutation.
+const ROUNDS_F: usize = 8;
+const ROUNDS_P: usize = 56;
+
+/// The diagonal of the internal linear layer of the BN254 permutation.
+const INTERNAL_DIAG: [u32; 3] = [1, 1, 2];
+
+/// The `W` of the quartic extension of BabyBear, whose elements are polynomials modulo `X^4 - W`.
+const EXT_W: u32 = 11;
+
+/// The gate `q_m·a·b + q_l·a + q_r·b + q_o·c + q_c = 0` on the variables `wires = [a, b, c]`, with
+/// its selectors in decimal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArithmeticGate {
+    pub wires: [u32; 3],
+    pub q_m: String,
+    pub q_l: String,
+    pub q_r: String,
+    pub q_o: String,
+    pub q_c: String,
+}
+
+/// The constraint that `variable` fits in `bits` bits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeConstraint {
+    pub variable: u32,
+    pub bits: u32,
+}
+
+/// The wire in the column `column` (0 for `a`, 1 for `b` and 2 for `c`) of the gate `gate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wire {
+    pub gate: usize,
+    pub column: u8,
+}
+
+/// The circuit description consumed by UltraPlonk and Honk provers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UltraPlonkCircuit {
+    pub num_variables: usize,
+    /// The variables of the public inputs, the verifying key hash, the committed values digest and
+    /// the input digest as in the gnark circuit.
+    pub public_inputs: Vec<u32>,
+    pub gates: Vec<ArithmeticGate>,
+    pub range_constraints: Vec<RangeConstraint>,
+    /// The cycles of the wires holding the same variable, for the variables used by several wires,
+    /// in the order of the variables and of the wires.
+    pub copy_constraints: Vec<Vec<Wire>>,
+}
+
+/// The values of the variables of an [UltraPlonkCircuit], in decimal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UltraPlonkWitness {
+    pub values: Vec<String>,
+}
+
+/// Why a witness doesn't satisfy an [UltraPlonkCircuit], see [UltraPlonkCircuit::check].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UltraPlonkCheckError {
+    /// The witness doesn't have one value per variable.
+    WitnessLength { expected: usize, actual: usize },
+    /// A value of the witness is not a decimal number.
+    Value(String),
+    /// The gate at this index doesn't hold.
+    Gate(usize),
+    /// The variable of the range constraint at this index doesn't fit in its bits.
+    Range(usize),
+    /// The wires of the copy constraint at this index don't hold the same variable.
+    CopyConstraint(usize),
+}
+
+impl fmt::Display for UltraPlonkCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WitnessLength { expected, actual } => write!(
+                f,
+                "the witness has {} values, expected {}",
+                actual, expected
+            ),
+            Self::Value(value) => write!(f, "the value {} is not decimal", value),
+            Self::Gate(index) => write!(f, "gate {} is not satisfied", index),
+            Self::Range(index) => write!(f, "range constraint {} is not satisfied", index),
+            Self::CopyConstraint(index) => {
+                write!(f, "copy constraint {} has different variables", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UltraPlonkCheckError {}
+
+impl UltraPlonkCircuit {
+    /// Checks that `witness` satisfies the gates, range constraints and copy constraints of the
+    /// circuit.
+    pub fn check(&self, witness: &UltraPlonkWitness) -> Result<(), UltraPlonkCheckError> {
+        if witness.values.len() != self.num_variables {
+            return Err(UltraPlonkCheckError::WitnessLength {
+                expected: self.num_variables,
+                actual: witness.values.len(),
+            });
+        }
+        let values = witness
+            .values
+            .iter()
+            .map(|value| parse_selector(value))
+            .collect::<Result<Vec<Bn254Fr>, _>>()?;
+        let value = |variable: u32| values.get(variable as usize).copied();
+
+        for (index, gate) in self.gates.iter().enumerate() {
+            let [Some(a), Some(b), Some(c)] = gate.wires.map(value) else {
+                return Err(UltraPlonkCheckError::Gate(index));
+            };
+            let sum = parse_selector(&gate.q_m)? * a * b
+                + parse_selector(&gate.q_l)? * a
+                + parse_selector(&gate.q_r)? * b
+                + parse_selector(&gate.q_o)? * c
+                + parse_selector(&gate.q_c)?;
+            if sum != Bn254Fr::zero() {
+                return Err(UltraPlonkCheckError::Gate(index));
+            }
+        }
+        for (index, range) in self.range_constraints.iter().enumerate() {
+            let fits = value(range.variable)
+                .is_some_and(|value| value.as_canonical_biguint().bits() <= range.bits as u64);
+            if !fits {
+                return Err(UltraPlonkCheckError::Range(index));
+            }
+        }
+        for (index, cycle) in self.copy_constraints.iter().enumerate() {
+            let variables = cycle
+                .iter()
+                .map(|wire| {
+                    self.gates
+                        .get(wire.gate)
+                        .and_then(|gate| gate.wires.get(wire.column as usize))
+                })
+                .collect::<Vec<_>>();
+            if variables.iter().any(|v| v.is_none() || *v != variables[0]) {
+                return Err(UltraPlonkCheckError::CopyConstraint(index));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Exports `constraints` to an [UltraPlonkCircuit], with the values of its variables for
+/// `witness`.
+///
+/// The values are assigned as the gnark circuit assigns them, and a witness the circuit doesn't
+/// accept gives values which don't satisfy [UltraPlonkCircuit::check].
+pub fn export_ultraplonk(
+    constraints: &[Constraint],
+    witness: &Witness<OuterConfig>,
+) -> Result<(UltraPlonkCircuit, UltraPlonkWitness), UnsatisfiedConstraint> {
+    let mut exporter = UltraPlonkExporter::new(witness.clone());
+    for constraint in constraints {
+        exporter.export(constraint)?;
+    }
+    exporter.finish()
+}
+
+/// The exporter of the outer circuit to an [UltraPlonkCircuit], lowering its constraints one at a
+/// time.
+///
+/// As a [ConstraintSink], it lowers the constraints as they are emitted and keeps the first
+/// failure, returned by [UltraPlonkExporter::finish].
+pub struct UltraPlonkExporter {
+    witness: Witness<OuterConfig>,
+    /// The variables of the vars, felts and extension elements of the constraints, by identifier.
+    vars: HashMap<String, u32>,
+    felts: HashMap<String, u32>,
+    exts: HashMap<String, [u32; 4]>,
+    values: Vec<Bn254Fr>,
+    gates: Vec<ArithmeticGate>,
+    range_constraints: Vec<RangeConstraint>,
+    round_constants: Vec<[Bn254Fr; 3]>,
+    len: usize,
+    error: Option<UnsatisfiedConstraint>,
+}
+
+impl UltraPlonkExporter {
+    pub fn new(witness: Witness<OuterConfig>) -> Self {
+        let values = vec![
+            Bn254Fr::zero(),
+            witness.vkey_hash,
+            witness.commited_values_digest,
+            witness.input_digest,
+        ];
+        let mut exporter = Self {
+            witness,
+            vars: HashMap::new(),
+            felts: HashMap::new(),
+            exts: HashMap::new(),
+            values,
+            gates: Vec::new(),
+            range_constraints: Vec::new(),
+            round_constants: bn254_poseidon2_rc3(),
+            len: 0,
+            error: None,
+        };
+        exporter.assert_constant(ZERO_VARIABLE, Bn254Fr::zero());
+        exporter
+    }
+
+    /// The value of the variable of the var `id`.
+    pub fn var(&self, id: &str) -> Option<Bn254Fr> {
+        self.vars.get(id).map(|&v| self.value(v))
+    }
+
+    /// The value of the variable of the felt `id`.
+    pub fn felt(&self, id: &str) -> Option<BabyBear> {
+        self.felts.get(id).map(|&v| self.felt_value(v))
+    }
+
+    /// The value of the variables of the extension element `id`.
+    pub fn ext(&self, id: &str) -> Option<EF> {
+        self.exts.get(id).map(|&e| self.ext_value(e))
+    }
+
+    /// Lowers the next constraint of the circuit.
+    pub fn export(&mut self, constraint: &Constraint) -> Result<(), UnsatisfiedConstraint> {
+        let index = self.len;
+        self.len += 1;
+        self.export_opcode(&constraint.opcode, &constraint.args)
+            .map_err(|failure| UnsatisfiedConstraint {
+                index,
+                opcode: constraint.opcode.clone(),
+                failure,
+                source: None,
+            })
+    }
+
+    /// Returns the circuit and its witness, or the first constraint pushed to the exporter which
+    /// couldn't be lowered.
+    pub fn finish(self) -> Result<(UltraPlonkCircuit, UltraPlonkWitness), UnsatisfiedConstraint> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let mut wires = vec![Vec::new(); self.values.len()];
+        for (
+            gate,
+            ArithmeticGate {
+                wires: gate_wires, ..
+            },
+        ) in self.gates.iter().enumerate()
+        {
+            for (column, &variable) in gate_wires.iter().enumerate() {
+                wires[variable as usize].push(Wire {
+                    gate,
+                    column: column as u8,
+                });
+            }
+        }
+        let circuit = UltraPlonkCircuit {
+            num_variables: self.values.len(),
+            public_inputs: vec![
+                VKEY_HASH_VARIABLE,
+                COMMITED_VALUES_DIGEST_VARIABLE,
+                INPUT_DIGEST_VARIABLE,
+            ],
+            gates: self.gates,
+            range_constraints: self.range_constraints,
+            copy_constraints: wires.into_iter().filter(|w| w.len() > 1).collect(),
+        };
+        let witness = UltraPlonkWitness {
+            values: self.values.into_iter().map(var_string).collect(),
+        };
+        Ok((circuit, witness))
+    }
+
+    fn export_opcode(
+        &mut self,
+        opcode: &ConstraintOpcode,
+        args: &[Vec<String>],
+    ) -> Result<(), ConstraintFailure> {
+        match opcode {
+            ConstraintOpcode::ImmV => {
+                let value = self.constant(parse(arg(args, 1, 0)?)?);
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::ImmF => {
+                let value = parse::<BabyBear>(arg(args, 1, 0)?)?;
+                let value = self.constant(felt_to_var(value));
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::ImmE => {
+                let coordinates = args
+                    .get(1)
+                    .filter(|coordinates| coordinates.len() == 4)
+                    .ok_or_else(|| malformed("an extension element has 4 coordinates"))?;
+                let mut value = [ZERO_VARIABLE; 4];
+                for (v, coordinate) in value.iter_mut().zip(coordinates) {
+                    *v = self.constant(felt_to_var(parse::<BabyBear>(coordinate)?));
+                }
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::AddV => {
+                let (a, b) = (self.var_arg(args, 1)?, self.var_arg(args, 2)?);
+                let value = self.linear(a, Bn254Fr::one(), b, Bn254Fr::one(), Bn254Fr::zero());
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::AddF => {
+                let value = self.add_felt(self.felt_arg(args, 1)?, self.felt_arg(args, 2)?);
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::AddE => {
+                let (a, b) = (self.ext_arg(args, 1)?, self.ext_arg(args, 2)?);
+                let value = [0, 1, 2, 3].map(|i| self.add_felt(a[i], b[i]));
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::AddEF => {
+                let (mut value, b) = (self.ext_arg(args, 1)?, self.felt_arg(args, 2)?);
+                value[0] = self.add_felt(value[0], b);
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::SubV => {
+                let (a, b) = (self.var_arg(args, 1)?, self.var_arg(args, 2)?);
+                let value = self.linear(a, Bn254Fr::one(), b, -Bn254Fr::one(), Bn254Fr::zero());
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::SubF => {
+                let value = self.sub_felt(self.felt_arg(args, 1)?, self.felt_arg(args, 2)?);
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::SubE => {
+                let (a, b) = (self.ext_arg(args, 1)?, self.ext_arg(args, 2)?);
+                let value = [0, 1, 2, 3].map(|i| self.sub_felt(a[i], b[i]));
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::SubEF => {
+                let (mut value, b) = (self.ext_arg(args, 1)?, self.felt_arg(args, 2)?);
+                value[0] = self.sub_felt(value[0], b);
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::MulV => {
+                let value = self.mul(self.var_arg(args, 1)?, self.var_arg(args, 2)?);
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::MulF => {
+                let value = self.mul_felt(self.felt_arg(args, 1)?, self.felt_arg(args, 2)?);
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::MulE => {
+                let value = self.mul_ext(self.ext_arg(args, 1)?, self.ext_arg(args, 2)?);
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::MulEF => {
+                let (a, b) = (self.ext_arg(args, 1)?, self.felt_arg(args, 2)?);
+                let value = a.map(|a| self.mul_felt(a, b));
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::DivE => {
+                let (a, b) = (self.ext_arg(args, 1)?, self.ext_arg(args, 2)?);
+                let quotient =
+                    self.ext_value(a) * self.ext_value(b).try_inverse().unwrap_or(EF::zero());
+                let quotient = self.ext_witness(quotient);
+                let product = self.mul_ext(b, quotient);
+                for i in 0..4 {
+                    self.assert_equal(product[i], a[i]);
+                }
+                self.set_ext(args, 0, quotient)
+            }
+            ConstraintOpcode::NegE => {
+                let value = self.ext_arg(args, 1)?.map(|a| self.neg_felt(a));
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::InvE => {
+                let a = self.ext_arg(args, 1)?;
+                let inverse = self.ext_value(a).try_inverse().unwrap_or(EF::zero());
+                let inverse = self.ext_witness(inverse);
+                let product = self.mul_ext(a, inverse);
+                for (i, coordinate) in product.into_iter().enumerate() {
+                    self.assert_constant(coordinate, Bn254Fr::from_bool(i == 0));
+                }
+                self.set_ext(args, 0, inverse)
+            }
+            ConstraintOpcode::Num2BitsV => {
+                let value = self.var_arg(args, 1)?;
+                let bits = arg(args, 2, 0)?
+                    .parse::<usize>()
+                    .map_err(|_| malformed("the number of bits is not an integer"))?;
+                self.num2bits(args, value, bits)
+            }
+            ConstraintOpcode::Num2BitsF => {
+                let value = self.felt_arg(args, 1)?;
+                self.num2bits(args, value, 32)
+            }
+            ConstraintOpcode::Permute => {
+                let mut state = [
+                    self.var_arg(args, 0)?,
+                    self.var_arg(args, 1)?,
+                    self.var_arg(args, 2)?,
+                ];
+                self.permute(&mut state);
+                for (i, value) in state.into_iter().enumerate() {
+                    self.set_var(args, i, value)?;
+                }
+                Ok(())
+            }
+            ConstraintOpcode::SelectV => {
+                let (condition, a, b) = self.select_args(args, Self::var_arg)?;
+                let value = self.select(condition, a, b);
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::SelectF => {
+                let (condition, a, b) = self.select_args(args, Self::felt_arg)?;
+                let value = self.select(condition, a, b);
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::SelectE => {
+                let (condition, a, b) = self.select_args(args, Self::ext_arg)?;
+                let value = [0, 1, 2, 3].map(|i| self.select(condition, a[i], b[i]));
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::Ext2Felt => {
+                let value = self.ext_arg(args, 4)?;
+                for (i, coordinate) in value.into_iter().enumerate() {
+                    self.set_felt(args, i, coordinate)?;
+                }
+                Ok(())
+            }
+            ConstraintOpcode::CircuitFelts2Ext => {
+                let mut value = [ZERO_VARIABLE; 4];
+                for (i, coordinate) in value.iter_mut().enumerate() {
+                    *coordinate = self.felt_arg(args, i + 1)?;
+                }
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::AssertEqV => {
+                let (left, right) = (self.var_arg(args, 0)?, self.var_arg(args, 1)?);
+                self.assert_equal(left, right);
+                Ok(())
+            }
+            ConstraintOpcode::AssertEqF => {
+                let (left, right) = (self.felt_arg(args, 0)?, self.felt_arg(args, 1)?);
+                self.assert_equal(left, right);
+                Ok(())
+            }
+            ConstraintOpcode::AssertEqE => {
+                let (left, right) = (self.ext_arg(args, 0)?, self.ext_arg(args, 1)?);
+                for i in 0..4 {
+                    self.assert_equal(left[i], right[i]);
+                }
+                Ok(())
+            }
+            ConstraintOpcode::PrintV => self.var_arg(args, 0).map(|_| ()),
+            ConstraintOpcode::PrintF => self.felt_arg(args, 0).map(|_| ()),
+            ConstraintOpcode::PrintE => self.ext_arg(args, 0).map(|_| ()),
+            ConstraintOpcode::WitnessV => {
+                let value = witness_value(&self.witness.vars, args)?;
+                let value = self.alloc(value);
+                self.set_var(args, 0, value)
+            }
+            ConstraintOpcode::WitnessF => {
+                let value = witness_value(&self.witness.felts, args)?;
+                let value = self.felt_witness(value);
+                self.set_felt(args, 0, value)
+            }
+            ConstraintOpcode::WitnessE => {
+                let value = witness_value(&self.witness.exts, args)?;
+                let value = self.ext_witness(value);
+                self.set_ext(args, 0, value)
+            }
+            ConstraintOpcode::CommitVkeyHash => {
+                let value = self.var_arg(args, 0)?;
+                self.assert_equal(value, VKEY_HASH_VARIABLE);
+                Ok(())
+            }
+            ConstraintOpcode::CommitCommitedValuesDigest => {
+                let value = self.var_arg(args, 0)?;
+                self.assert_equal(value, COMMITED_VALUES_DIGEST_VARIABLE);
+                Ok(())
+            }
+            ConstraintOpcode::CommitInputDigest => {
+                let value = self.var_arg(args, 0)?;
+                self.assert_equal(value, INPUT_DIGEST_VARIABLE);
+                Ok(())
+            }
+            ConstraintOpcode::PermuteBabyBear
+            | ConstraintOpcode::DivF
+            | ConstraintOpcode::DivEF
+            | ConstraintOpcode::NegV
+            | ConstraintOpcode::NegF
+            | ConstraintOpcode::InvV
+            | ConstraintOpcode::InvF => Err(ConstraintFailure::Unsupported),
+        }
+    }
+
+    fn value(&self, variable: u32) -> Bn254Fr {
+        self.values[variable as usize]
+    }
+
+    fn felt_value(&self, variable: u32) -> BabyBear {
+        let value = to_u128(self.value(variable)).unwrap_or_default();
+        BabyBear::from_wrapped_u32(value as u32)
+    }
+
+    fn ext_value(&self, variables: [u32; 4]) -> EF {
+        EF::from_base_slice(&variables.map(|v| self.felt_value(v)))
+    }
+
+    /// Allocates a variable of value `value`, unconstrained.
+    fn alloc(&mut self, value: Bn254Fr) -> u32 {
+        self.values.push(value);
+        (self.values.len() - 1) as u32
+    }
+
+    fn gate(
+        &mut self,
+        wires: [u32; 3],
+        q_m: Bn254Fr,
+        q_l: Bn254Fr,
+        q_r: Bn254Fr,
+        q_o: Bn254Fr,
+        q_c: Bn254Fr,
+    ) {
+        self.gates.push(ArithmeticGate {
+            wires,
+            q_m: var_string(q_m),
+            q_l: var_string(q_l),
+            q_r: var_string(q_r),
+            q_o: var_string(q_o),
+            q_c: var_string(q_c),
+        });
+    }
+
+    /// The variable `q_a·a + q_b·b + c`.
+    fn linear(&mut self, a: u32, q_a: Bn254Fr, b: u32, q_b: Bn254Fr, c: Bn254Fr) -> u32 {
+        let out = self.alloc(q_a * self.value(a) + q_b * self.value(b) + c);
+        let zero = Bn254Fr::zero();
+        self.gate([a, b, out], zero, q_a, q_b, -Bn254Fr::one(), c);
+        out
+    }
+
+    /// The variable `a·b`.
+    fn mul(&mut self, a: u32, b: u32) -> u32 {
+        let out = self.alloc(self.value(a) * self.value(b));
+        let zero = Bn254Fr::zero();
+        self.gate(
+            [a, b, out],
+            Bn254Fr::one(),
+            zero,
+            zero,
+            -Bn254Fr::one(),
+            zero,
+        );
+        out
+    }
+
+    /// The variable of the constant `c`.
+    fn constant(&mut self, c: Bn254Fr) -> u32 {
+        let zero = Bn254Fr::zero();
+        self.linear(ZERO_VARIABLE, zero, ZERO_VARIABLE, zero, c)
+    }
+
+    fn assert_equal(&mut self, a: u32, b: u32) {
+        let zero = Bn254Fr::zero();
+        let wires = [a, b, ZERO_VARIABLE];
+        self.gate(wires, zero, Bn254Fr::one(), -Bn254Fr::one(), zero, zero);
+    }
+
+    fn assert_constant(&mut self, a: u32, c: Bn254Fr) {
+        let zero = Bn254Fr::zero();
+        let wires = [a, ZERO_VARIABLE, ZERO_VARIABLE];
+        self.gate(wires, zero, Bn254Fr::one(), zero, zero, -c);
+    }
+
+    fn assert_boolean(&mut self, a: u32) {
+        let zero = Bn254Fr::zero();
+        let wires = [a, a, ZERO_VARIABLE];
+        self.gate(wires, Bn254Fr::one(), -Bn254Fr::one(), zero, zero, zero);
+    }
+
+    fn range(&mut self, variable: u32, bits: u32) {
+        self.range_constraints
+            .push(RangeConstraint { variable, bits });
+    }
+
+    /// A felt of the witness, checked to be canonical.
+    fn felt_witness(&mut self, value: BabyBear) -> u32 {
+        let variable = self.alloc(felt_to_var(value));
+        self.range(variable, FELT_BITS);
+        variable
+    }
+
+    fn ext_witness(&mut self, value: EF) -> [u32; 4] {
+        let coordinates = value.as_base_slice();
+        [0, 1, 2, 3].map(|i| self.felt_witness(coordinates[i]))
+    }
+
+    /// The canonical felt of `x`, of at most `bits` bits, written `x = q·p + r` with the quotient
+    /// `q` and the remainder `r` range checked.
+    fn reduce(&mut self, x: u32, bits: u32) -> u32 {
+        let modulus = BabyBear::ORDER_U32 as u128;
+        let (q, r) = to_u128(self.value(x))
+            .map(|x| (x / modulus, x % modulus))
+            .unwrap_or_default();
+        let q = self.alloc(from_u128(q));
+        let r = self.alloc(from_u128(r));
+        self.range(r, FELT_BITS);
+        self.range(q, bits - FELT_BITS);
+        let zero = Bn254Fr::zero();
+        let modulus = Bn254Fr::from_canonical_u32(BabyBear::ORDER_U32);
+        self.gate(
+            [q, r, x],
+            zero,
+            modulus,
+            Bn254Fr::one(),
+            -Bn254Fr::one(),
+            zero,
+        );
+        r
+    }
+
+    fn add_felt(&mut self, a: u32, b: u32) -> u32 {
+        let sum = self.linear(a, Bn254Fr::one(), b, Bn254Fr::one(), Bn254Fr::zero());
+        self.reduce(sum, FELT_BITS + 1)
+    }
+
+    /// The difference `a - b`, offset by twice the modulus so that it is never negative.
+    fn sub_felt(&mut self, a: u32, b: u32) -> u32 {
+        let difference = self.linear(a, Bn254Fr::one(), b, -Bn254Fr::one(), two_modulus());
+        self.reduce(difference, FELT_BITS + 2)
+    }
+
+    /// The negation `-a`, offset by twice the modulus like [Self::sub_felt].
+    fn neg_felt(&mut self, a: u32) -> u32 {
+        let zero = Bn254Fr::zero();
+        let negation = self.linear(a, -Bn254Fr::one(), ZERO_VARIABLE, zero, two_modulus());
+        self.reduce(negation, FELT_BITS + 2)
+    }
+
+    fn mul_felt(&mut self, a: u32, b: u32) -> u32 {
+        let product = self.mul(a, b);
+        self.reduce(product, 2 * FELT_BITS)
+    }
+
+    /// The product of extension elements, whose coordinates are reduced once their products are
+    /// summed.
+    fn mul_ext(&mut self, a: [u32; 4], b: [u32; 4]) -> [u32; 4] {
+        let mut products = [[ZERO_VARIABLE; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                products[i][j] = self.mul(a[i], b[j]);
+            }
+        }
+        [0, 1, 2, 3].map(|k| {
+            // The products of degree k and k + 4, which is k modulo X^4 - W.
+            let terms = (0..4)
+                .map(|i| {
+                    let j = (k + 4 - i) % 4;
+                    let weight = if i + j >= 4 { EXT_W } else { 1 };
+                    (products[i][j], Bn254Fr::from_canonical_u32(weight))
+                })
+                .collect::<Vec<_>>();
+            let zero = Bn254Fr::zero();
+            let mut sum = self.linear(terms[0].0, terms[0].1, terms[1].0, terms[1].1, zero);
+            for &(product, weight) in &terms[2..] {
+                sum = self.linear(sum, Bn254Fr::one(), product, weight, zero);
+            }
+            // The sum of 4 products of canonical felts, 3 of them weighted by W, fits in 68 bits.
+            self.reduce(sum, 2 * FELT_BITS + 6)
+        })
+    }
+
+    /// Decomposes `value` into `bits` little endian bits, the first of which are assigned to the
+    /// variables of `args[0]`.
+    fn num2bits(
+        &mut self,
+        args: &[Vec<String>],
+        value: u32,
+        bits: usize,
+    ) -> Result<(), ConstraintFailure> {
+        let outputs = args.first().map_or(0, |outputs| outputs.len());
+        if outputs > bits {
+            return Err(malformed("there are more outputs than bits"));
+        }
+        let canonical = self.value(value).as_canonical_biguint();
+        let bits = (0..bits)
+            .map(|i| {
+                let bit = self.alloc(Bn254Fr::from_bool(canonical.bit(i as u64)));
+                self.assert_boolean(bit);
+                bit
+            })
+            .collect::<Vec<_>>();
+        let mut sum = ZERO_VARIABLE;
+        let mut power = Bn254Fr::one();
+        for &bit in &bits {
+            sum = self.linear(sum, Bn254Fr::one(), bit, power, Bn254Fr::zero());
+            power = power.double();
+        }
+        self.assert_equal(sum, value);
+        for (i, bit) in bits.into_iter().take(outputs).enumerate() {
+            self.vars.insert(args[0][i].clone(), bit);
+        }
+        Ok(())
+    }
+
+    /// The condition `args[1]`, checked to be boolean, and the values `args[2]` and `args[3]` it
+    /// selects between.
+    fn select_args<T>(
+        &mut self,
+        args: &[Vec<String>],
+        value: impl Fn(&Self, &[Vec<String>], usize) -> Result<T, ConstraintFailure>,
+    ) -> Result<(u32, T, T), ConstraintFailure> {
+        let condition = self.var_arg(args, 1)?;
+        let (a, b) = (value(self, args, 2)?, value(self, args, 3)?);
+        self.assert_boolean(condition);
+        Ok((condition, a, b))
+    }
+
+    /// The variable `condition·(a - b) + b`, that is `a` if the condition is one and `b` if it is
+    /// zero.
+    fn select(&mut self, condition: u32, a: u32, b: u32) -> u32 {
+        let (one, zero) = (Bn254Fr::one(), Bn254Fr::zero());
+        let difference = self.linear(a, one, b, -one, zero);
+        let product = self.mul(condition, difference);
+        self.linear(product, one, b, one, zero)
+    }
+
+    /// The BN254 permutation of the gnark circuit.
+    fn permute(&mut self, state: &mut [u32; 3]) {
+        self.external_linear_layer(state);
+        let internal_rounds = ROUNDS_F / 2..ROUNDS_F / 2 + ROUNDS_P;
+        for r in 0..ROUNDS_F + ROUNDS_P {
+            let round_constants = self.round_constants[r];
+            if internal_rounds.contains(&r) {
+                state[0] = self.sbox(state[0], round_constants[0]);
+                self.internal_linear_layer(state);
+            } else {
+                for (x, rc) in state.iter_mut().zip(round_constants) {
+                    *x = self.sbox(*x, rc);
+                }
+                self.external_linear_layer(state);
+            }
+        }
+    }
+
+    /// The variable `(x + rc)^5`, in three gates.
+    fn sbox(&mut self, x: u32, rc: Bn254Fr) -> u32 {
+        let (one, zero) = (Bn254Fr::one(), Bn254Fr::zero());
+        let shifted = self.value(x) + rc;
+        // (x + rc)^2 = x·x + 2·rc·x + rc^2.
+        let squared = self.alloc(shifted.square());
+        self.gate([x, x, squared], one, rc.double(), zero, -one, rc.square());
+        let quartic = self.mul(squared, squared);
+        // (x + rc)^5 = quartic·x + rc·quartic.
+        let out = self.alloc(self.value(quartic) * shifted);
+        self.gate([quartic, x, out], one, rc, zero, -one, zero);
+        out
+    }
+
+    /// The variable of the sum of the state.
+    fn state_sum(&mut self, state: &[u32; 3]) -> u32 {
+        let (one, zero) = (Bn254Fr::one(), Bn254Fr::zero());
+        let sum = self.linear(state[0], one, state[1], one, zero);
+        self.linear(sum, one, state[2], one, zero)
+    }
+
+    fn external_linear_layer(&mut self, state: &mut [u32; 3]) {
+        let sum = self.state_sum(state);
+        let (one, zero) = (Bn254Fr::one(), Bn254Fr::zero());
+        for x in state.iter_mut() {
+            *x = self.linear(*x, one, sum, one, zero);
+        }
+    }
+
+    fn internal_linear_layer(&mut self, state: &mut [u32; 3]) {
+        let sum = self.state_sum(state);
+        let (one, zero) = (Bn254Fr::one(), Bn254Fr::zero());
+        for (x, diag) in state.iter_mut().zip(INTERNAL_DIAG) {
+            *x = self.linear(*x, Bn254Fr::from_canonical_u32(diag), sum, one, zero);
+        }
+    }
+
+    fn var_arg(&self, args: &[Vec<String>], i: usize) -> Result<u32, ConstraintFailure> {
+        let id = arg(args, i, 0)?;
+        self.vars
+            .get(id)
+            .copied()
+            .ok_or_else(|| ConstraintFailure::Undefined(id.to_string()))
+    }
+
+    fn felt_arg(&self, args: &[Vec<String>], i: usize) -> Result<u32, ConstraintFailure> {
+        let id = arg(args, i, 0)?;
+        self.felts
+            .get(id)
+            .copied()
+            .ok_or_else(|| ConstraintFailure::Undefined(id.to_string()))
+    }
+
+    fn ext_arg(&self, args: &[Vec<String>], i: usize) -> Result<[u32; 4], ConstraintFailure> {
+        let id = arg(args, i, 0)?;
+        self.exts
+            .get(id)
+            .copied()
+            .ok_or_else(|| ConstraintFailure::Undefined(id.to_string()))
+    }
+
+    fn set_var(
+        &mut self,
+        args: &[Vec<String>],
+        i: usize,
+        variable: u32,
+    ) -> Result<(), ConstraintFailure> {
+        self.vars.insert(arg(args, i, 0)?.to_string(), variable);
+        Ok(())
+    }
+
+    fn set_felt(
+        &mut self,
+        args: &[Vec<String>],
+        i: usize,
+        variable: u32,
+    ) -> Result<(), ConstraintFailure> {
+        self.felts.insert(arg(args, i, 0)?.to_string(), variable);
+        Ok(())
+    }
+
+    fn set_ext(
+        &mut self,
+        args: &[Vec<String>],
+        i: usize,
+        variables: [u32; 4],
+    ) -> Result<(), ConstraintFailure> {
+        self.exts.insert(arg(args, i, 0)?.to_string(), variables);
+        Ok(())
+    }
+}
+
+impl ConstraintSink for UltraPlonkExporter {
+    fn push(&mut self, constraint: Constraint) {
+        if self.error.is_none() {
+            self.error = self.export(&constraint).err();
+        }
+    }
+}
+
+fn parse_selector(value: &str) -> Result<Bn254Fr, UltraPlonkCheckError> {
+    parse(value).map_err(|_| UltraPlonkCheckError::Value(value.to_string()))
+}
+
+fn felt_to_var(value: BabyBear) -> Bn254Fr {
+    Bn254Fr::from_canonical_u32(value.as_canonical_u32())
+}
+
+fn two_modulus() -> Bn254Fr {
+    Bn254Fr::from_canonical_u32(BabyBear::ORDER_U32).double()
+}
+
+/// The value of `x` if it fits in 128 bits, which the unreduced felts always do.
+fn to_u128(x: Bn254Fr) -> Option<u128> {
+    let digits = x.as_canonical_biguint().to_u64_digits();
+    match digits[..] {
+        [] => Some(0),
+        [low] => Some(low as u128),
+        [low, high] => Some(((high as u128) << 64) | low as u128),
+        _ => None,
+    }
+}
+
+fn from_u128(x: u128) -> Bn254Fr {
+    let shift = Bn254Fr::from_canonical_u64(1 << 32).square();
+    Bn254Fr::from_canonical_u64((x >> 64) as u64) * shift + Bn254Fr::from_canonical_u64(x as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_symmetric::Permutation;
+    use sp1_recursion_core::stark::config::outer_perm;
+
+    use super::*;
+    use crate::constraints::{evaluate_constraints, ConstraintCompiler};
+    use crate::ir::{Builder, DslIr, Ext, Felt, Var};
+
+    fn export(builder: Builder<OuterConfig>) -> (UltraPlonkCircuit, UltraPlonkWitness) {
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        evaluate_constraints(&constraints, &Witness::default()).unwrap();
+        export_ultraplonk(&constraints, &Witness::default()).unwrap()
+    }
+
+    #[test]
+    fn test_export_ultraplonk() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let a: Var<_> = builder.eval(Bn254Fr::from_canonical_u32(6));
+        let b: Var<_> = builder.eval(a * Bn254Fr::from_canonical_u32(7));
+        builder.assert_var_eq(b, Bn254Fr::from_canonical_u32(42));
+        let bits = builder.num2bits_v_circuit(b, 6);
+        builder.assert_var_eq(bits[1], Bn254Fr::one());
+        let x: Felt<_> = builder.eval(BabyBear::from_canonical_u32(3));
+        let y: Felt<_> = builder.eval(x * x - BabyBear::from_canonical_u32(10));
+        builder.assert_felt_eq(y, -BabyBear::one());
+        let e: Ext<_, _> = builder.eval(EF::from_base_slice(&[
+            BabyBear::from_canonical_u32(1),
+            BabyBear::from_canonical_u32(2),
+            BabyBear::from_canonical_u32(3),
+            BabyBear::from_canonical_u32(4),
+        ]));
+        let inverse: Ext<_, _> = builder.eval(EF::one() / e);
+        let product: Ext<_, _> = builder.eval(e * inverse);
+        builder.assert_ext_eq(product, EF::one());
+
+        let (circuit, witness) = export(builder);
+        circuit.check(&witness).unwrap();
+
+        // Any change of a value is caught.
+        for index in [3, witness.values.len() - 1] {
+            let mut tampered = witness.clone();
+            tampered.values[index].push('1');
+            assert!(circuit.check(&tampered).is_err());
+        }
+    }
+
+    #[test]
+    fn test_export_permutation() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let state: [Var<_>; 3] = [1, 2, 3].map(|x| builder.eval(Bn254Fr::from_canonical_u32(x)));
+        builder.push(DslIr::CircuitPoseidon2Permute(state));
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let mut exporter = UltraPlonkExporter::new(Witness::default());
+        for constraint in &constraints {
+            exporter.export(constraint).unwrap();
+        }
+        let permute = constraints
+            .iter()
+            .find(|c| c.opcode == ConstraintOpcode::Permute)
+            .unwrap();
+        let mut expected = [1, 2, 3].map(Bn254Fr::from_canonical_u32);
+        outer_perm().permute_mut(&mut expected);
+        for (i, expected) in expected.into_iter().enumerate() {
+            assert_eq!(exporter.var(&permute.args[i][0]), Some(expected));
+        }
+
+        let (circuit, witness) = exporter.finish().unwrap();
+        circuit.check(&witness).unwrap();
+    }
+
+    #[test]
+    fn test_export_unsupported() {
+        let mut builder = Builder::<OuterConfig>::default();
+        let state: [Felt<_>; 16] = core::array::from_fn(|_| builder.eval(BabyBear::one()));
+        builder.push(DslIr::CircuitPoseidon2PermuteBabyBear(state));
+
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let err = export_ultraplonk(&constraints, &Witness::default()).unwrap_err();
+        assert_eq!(err.opcode, ConstraintOpcode::PermuteBabyBear);
+        assert_eq!(err.failure, ConstraintFailure::Unsupported);
+    }
+}
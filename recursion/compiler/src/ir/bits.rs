@@ -35,6 +35,15 @@ impl<C: Config> Builder<C> {
         });
     }
 
+    /// Range checks a felt to a certain number of bits.
+    pub fn range_check_f(&mut self, num: Felt<C::F>, num_bits: usize) {
+        let bits = self.num2bits_f(num);
+        self.range(num_bits, bits.len()).for_each(|i, builder| {
+            let bit = builder.get(&bits, i);
+            builder.assert_var_eq(bit, C::N::zero());
+        });
+    }
+
     /// Converts a variable to bits inside a circuit.
     pub fn num2bits_v_circuit(&mut self, num: Var<C::N>, bits: usize) -> Vec<Var<C::N>> {
         let mut output = Vec::new();
@@ -1,7 +1,8 @@
-use std::{iter::Zip, vec::IntoIter};
+use std::{collections::BTreeMap, iter::Zip, panic::Location, vec::IntoIter};
 
 use backtrace::Backtrace;
 use p3_field::AbstractField;
+use sp1_recursion_core::runtime::SourceLocation;
 
 use super::{
     Array, Config, DslIr, Ext, Felt, FromConstant, SymbolicExt, SymbolicFelt, SymbolicUsize,
@@ -14,6 +15,45 @@ use super::{
 pub struct TracedVec<T> {
     pub vec: Vec<T>,
     pub traces: Vec<Option<Backtrace>>,
+    /// The source locations of the elements, recorded only when source tracking is enabled, see
+    /// [Builder::set_track_sources].
+    pub sources: Option<SourceTable>,
+}
+
+/// The source locations of the operations of a builder, keyed by their index.
+#[derive(Debug, Clone, Default)]
+pub struct SourceTable {
+    pub locations: BTreeMap<usize, SourceLocation>,
+    /// The cycle tracker regions open at the end of the operations, innermost last.
+    regions: Vec<String>,
+}
+
+impl SourceTable {
+    /// An empty table for a nested block, which is in the regions open in this one.
+    fn nested(&self) -> Self {
+        Self {
+            locations: BTreeMap::new(),
+            regions: self.regions.clone(),
+        }
+    }
+
+    fn record(&mut self, index: usize, location: &'static Location<'static>) {
+        let source = SourceLocation {
+            location,
+            region: self.regions.last().cloned(),
+        };
+        self.locations.insert(index, source);
+    }
+
+    /// Enters the region `name`, or exits it if it is open, as cycle trackers do.
+    fn toggle_region(&mut self, name: &str) {
+        match self.regions.iter().rposition(|region| region == name) {
+            Some(position) => {
+                self.regions.remove(position);
+            }
+            None => self.regions.push(name.to_string()),
+        }
+    }
 }
 
 impl<T> Default for TracedVec<T> {
@@ -28,6 +68,7 @@ impl<T> From<Vec<T>> for TracedVec<T> {
         Self {
             vec,
             traces: vec![None; len],
+            sources: None,
         }
     }
 }
@@ -37,16 +78,21 @@ impl<T> TracedVec<T> {
         Self {
             vec: Vec::new(),
             traces: Vec::new(),
+            sources: None,
         }
     }
 
+    #[track_caller]
     pub fn push(&mut self, value: T) {
+        self.record_source();
         self.vec.push(value);
         self.traces.push(None);
     }
 
     /// Pushes a value to the vector and records a backtrace if SP1_DEBUG is enabled
+    #[track_caller]
     pub fn trace_push(&mut self, value: T) {
+        self.record_source();
         self.vec.push(value);
         match std::env::var("SP1_DEBUG")
             .unwrap_or("false".to_string())
@@ -73,9 +119,33 @@ impl<T> TracedVec<T> {
         }
     }
 
+    /// Appends the elements of `other`, with their traces and source locations.
+    pub fn append(&mut self, other: TracedVec<T>) {
+        if let (Some(sources), Some(other_sources)) = (&mut self.sources, &other.sources) {
+            let offset = self.vec.len();
+            for (index, source) in other_sources.locations.iter() {
+                sources.locations.insert(offset + index, source.clone());
+            }
+        }
+        self.extend(other);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.vec.is_empty()
     }
+
+    /// The source location of the element at `index`, if it was recorded.
+    pub fn source(&self, index: usize) -> Option<&SourceLocation> {
+        self.sources.as_ref()?.locations.get(&index)
+    }
+
+    /// Records the caller as the source location of the next element, if sources are tracked.
+    #[track_caller]
+    fn record_source(&mut self) {
+        if let Some(sources) = &mut self.sources {
+            sources.record(self.vec.len(), Location::caller());
+        }
+    }
 }
 
 impl<T> IntoIterator for TracedVec<T> {
@@ -102,6 +172,8 @@ pub struct Builder<C: Config> {
     pub(crate) witness_ext_count: u32,
     pub(crate) debug: bool,
     pub(crate) is_sub_builder: bool,
+    /// Whether compilation fails if an unconstrained hint reaches a sink, see [super::TaintAnalysis].
+    pub(crate) strict_taint: bool,
 }
 
 impl<C: Config> Builder<C> {
@@ -126,15 +198,41 @@ impl<C: Config> Builder<C> {
             nb_public_values,
             debug,
             is_sub_builder: true,
+            strict_taint: false,
         }
     }
 
+    /// Records the source location of each operation pushed from now on, with the innermost
+    /// [Builder::cycle_tracker] region it is pushed in.
+    ///
+    /// The locations are those of the outermost `#[track_caller]` builder method called, and are
+    /// carried to the compiled program and circuit, where the runtime traps and the unsatisfied
+    /// constraints report them. They are a debugging aid: disabled, no location is captured.
+    pub fn set_track_sources(&mut self, track: bool) {
+        self.operations.sources = track.then(SourceTable::default);
+    }
+
+    /// Creates a builder for a nested block, tracking sources if this builder does.
+    fn sub_builder(&self) -> Self {
+        let mut builder = Self::new_sub_builder(
+            self.var_count,
+            self.felt_count,
+            self.ext_count,
+            self.nb_public_values,
+            self.debug,
+        );
+        builder.operations.sources = self.operations.sources.as_ref().map(SourceTable::nested);
+        builder
+    }
+
     /// Pushes an operation to the builder.
+    #[track_caller]
     pub fn push(&mut self, op: DslIr<C>) {
         self.operations.push(op);
     }
 
     /// Pushes an operation to the builder and records a trace if SP1_DEBUG.
+    #[track_caller]
     pub fn trace_push(&mut self, op: DslIr<C>) {
         self.operations.trace_push(op);
     }
@@ -145,6 +243,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Evaluates an expression and returns a variable.
+    #[track_caller]
     pub fn eval<V: Variable<C>, E: Into<V::Expression>>(&mut self, expr: E) -> V {
         let dst = V::uninit(self);
         dst.assign(expr.into(), self);
@@ -157,11 +256,13 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assigns an expression to a variable.
+    #[track_caller]
     pub fn assign<V: Variable<C>, E: Into<V::Expression>>(&mut self, dst: V, expr: E) {
         dst.assign(expr.into(), self);
     }
 
     /// Asserts that two expressions are equal.
+    #[track_caller]
     pub fn assert_eq<V: Variable<C>>(
         &mut self,
         lhs: impl Into<V::Expression>,
@@ -171,6 +272,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Asserts that two expressions are not equal.
+    #[track_caller]
     pub fn assert_ne<V: Variable<C>>(
         &mut self,
         lhs: impl Into<V::Expression>,
@@ -180,6 +282,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two vars are equal.
+    #[track_caller]
     pub fn assert_var_eq<LhsExpr: Into<SymbolicVar<C::N>>, RhsExpr: Into<SymbolicVar<C::N>>>(
         &mut self,
         lhs: LhsExpr,
@@ -189,6 +292,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two vars are not equal.
+    #[track_caller]
     pub fn assert_var_ne<LhsExpr: Into<SymbolicVar<C::N>>, RhsExpr: Into<SymbolicVar<C::N>>>(
         &mut self,
         lhs: LhsExpr,
@@ -198,6 +302,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two felts are equal.
+    #[track_caller]
     pub fn assert_felt_eq<LhsExpr: Into<SymbolicFelt<C::F>>, RhsExpr: Into<SymbolicFelt<C::F>>>(
         &mut self,
         lhs: LhsExpr,
@@ -207,6 +312,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two felts are not equal.
+    #[track_caller]
     pub fn assert_felt_ne<LhsExpr: Into<SymbolicFelt<C::F>>, RhsExpr: Into<SymbolicFelt<C::F>>>(
         &mut self,
         lhs: LhsExpr,
@@ -216,6 +322,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two usizes are equal.
+    #[track_caller]
     pub fn assert_usize_eq<
         LhsExpr: Into<SymbolicUsize<C::N>>,
         RhsExpr: Into<SymbolicUsize<C::N>>,
@@ -228,6 +335,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two usizes are not equal.
+    #[track_caller]
     pub fn assert_usize_ne(
         &mut self,
         lhs: impl Into<SymbolicUsize<C::N>>,
@@ -237,6 +345,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two exts are equal.
+    #[track_caller]
     pub fn assert_ext_eq<
         LhsExpr: Into<SymbolicExt<C::F, C::EF>>,
         RhsExpr: Into<SymbolicExt<C::F, C::EF>>,
@@ -249,6 +358,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Assert that two exts are not equal.
+    #[track_caller]
     pub fn assert_ext_ne<
         LhsExpr: Into<SymbolicExt<C::F, C::EF>>,
         RhsExpr: Into<SymbolicExt<C::F, C::EF>>,
@@ -260,6 +370,7 @@ impl<C: Config> Builder<C> {
         self.assert_ne::<Ext<C::F, C::EF>>(lhs, rhs);
     }
 
+    #[track_caller]
     pub fn lt(&mut self, lhs: Var<C::N>, rhs: Var<C::N>) -> Var<C::N> {
         let result = self.uninit();
         self.operations.push(DslIr::LessThan(result, lhs, rhs));
@@ -309,31 +420,37 @@ impl<C: Config> Builder<C> {
     }
 
     /// Break out of a loop.
+    #[track_caller]
     pub fn break_loop(&mut self) {
         self.operations.push(DslIr::Break);
     }
 
+    #[track_caller]
     pub fn print_debug(&mut self, val: usize) {
         let constant = self.eval(C::N::from_canonical_usize(val));
         self.print_v(constant);
     }
 
     /// Print a variable.
+    #[track_caller]
     pub fn print_v(&mut self, dst: Var<C::N>) {
         self.operations.push(DslIr::PrintV(dst));
     }
 
     /// Print a felt.
+    #[track_caller]
     pub fn print_f(&mut self, dst: Felt<C::F>) {
         self.operations.push(DslIr::PrintF(dst));
     }
 
     /// Print an ext.
+    #[track_caller]
     pub fn print_e(&mut self, dst: Ext<C::F, C::EF>) {
         self.operations.push(DslIr::PrintE(dst));
     }
 
     /// Hint the length of the next vector of variables.
+    #[track_caller]
     pub fn hint_len(&mut self) -> Var<C::N> {
         let len = self.uninit();
         self.operations.push(DslIr::HintLen(len));
@@ -341,6 +458,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Hint a single variable.
+    #[track_caller]
     pub fn hint_var(&mut self) -> Var<C::N> {
         let len = self.hint_len();
         let arr = self.dyn_array(len);
@@ -349,6 +467,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Hint a single felt.
+    #[track_caller]
     pub fn hint_felt(&mut self) -> Felt<C::F> {
         let len = self.hint_len();
         let arr = self.dyn_array(len);
@@ -357,6 +476,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Hint a single ext.
+    #[track_caller]
     pub fn hint_ext(&mut self) -> Ext<C::F, C::EF> {
         let len = self.hint_len();
         let arr = self.dyn_array(len);
@@ -365,6 +485,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Hint a vector of variables.
+    #[track_caller]
     pub fn hint_vars(&mut self) -> Array<C, Var<C::N>> {
         let len = self.hint_len();
         let arr = self.dyn_array(len);
@@ -373,6 +494,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Hint a vector of felts.
+    #[track_caller]
     pub fn hint_felts(&mut self) -> Array<C, Felt<C::F>> {
         let len = self.hint_len();
         let arr = self.dyn_array(len);
@@ -381,6 +503,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Hint a vector of exts.
+    #[track_caller]
     pub fn hint_exts(&mut self) -> Array<C, Ext<C::F, C::EF>> {
         let len = self.hint_len();
         let arr = self.dyn_array(len);
@@ -388,6 +511,7 @@ impl<C: Config> Builder<C> {
         arr
     }
 
+    #[track_caller]
     pub fn witness_var(&mut self) -> Var<C::N> {
         assert!(
             !self.is_sub_builder,
@@ -400,6 +524,7 @@ impl<C: Config> Builder<C> {
         witness
     }
 
+    #[track_caller]
     pub fn witness_felt(&mut self) -> Felt<C::F> {
         assert!(
             !self.is_sub_builder,
@@ -412,6 +537,7 @@ impl<C: Config> Builder<C> {
         witness
     }
 
+    #[track_caller]
     pub fn witness_ext(&mut self) -> Ext<C::F, C::EF> {
         assert!(
             !self.is_sub_builder,
@@ -425,11 +551,13 @@ impl<C: Config> Builder<C> {
     }
 
     /// Throws an error.
+    #[track_caller]
     pub fn error(&mut self) {
         self.operations.trace_push(DslIr::Error());
     }
 
     /// Materializes a usize into a variable.
+    #[track_caller]
     pub fn materialize(&mut self, num: Usize<C::N>) -> Var<C::N> {
         match num {
             Usize::Const(num) => self.eval(C::N::from_canonical_usize(num)),
@@ -438,11 +566,13 @@ impl<C: Config> Builder<C> {
     }
 
     /// Register a felt as public value.  This is append to the proof's public values buffer.
+    #[track_caller]
     pub fn register_public_value(&mut self, val: Felt<C::F>) {
         self.operations.push(DslIr::RegisterPublicValue(val));
     }
 
     /// Register and commits a felt as public value.  This value will be constrained when verified.
+    #[track_caller]
     pub fn commit_public_value(&mut self, val: Felt<C::F>) {
         assert!(
             !self.is_sub_builder,
@@ -458,6 +588,7 @@ impl<C: Config> Builder<C> {
     }
 
     /// Commits an array of felts in public values.
+    #[track_caller]
     pub fn commit_public_values(&mut self, vals: &Array<C, Felt<C::F>>) {
         assert!(
             !self.is_sub_builder,
@@ -470,19 +601,31 @@ impl<C: Config> Builder<C> {
         });
     }
 
+    #[track_caller]
     pub fn commit_vkey_hash_circuit(&mut self, var: Var<C::N>) {
         self.operations.push(DslIr::CircuitCommitVkeyHash(var));
     }
 
+    #[track_caller]
     pub fn commit_commited_values_digest_circuit(&mut self, var: Var<C::N>) {
         self.operations
             .push(DslIr::CircuitCommitCommitedValuesDigest(var));
     }
 
+    #[track_caller]
+    pub fn commit_input_digest_circuit(&mut self, var: Var<C::N>) {
+        self.operations.push(DslIr::CircuitCommitInputDigest(var));
+    }
+
+    #[track_caller]
     pub fn cycle_tracker(&mut self, name: &str) {
+        if let Some(sources) = &mut self.operations.sources {
+            sources.toggle_region(name);
+        }
         self.operations.push(DslIr::CycleTracker(name.to_string()));
     }
 
+    #[track_caller]
     pub fn halt(&mut self) {
         self.operations.push(DslIr::Halt);
     }
@@ -507,18 +650,13 @@ enum IfCondition<N> {
 }
 
 impl<'a, C: Config> IfBuilder<'a, C> {
+    #[track_caller]
     pub fn then(mut self, mut f: impl FnMut(&mut Builder<C>)) {
         // Get the condition reduced from the expressions for lhs and rhs.
         let condition = self.condition();
 
         // Execute the `then` block and collect the instructions.
-        let mut f_builder = Builder::<C>::new_sub_builder(
-            self.builder.var_count,
-            self.builder.felt_count,
-            self.builder.ext_count,
-            self.builder.nb_public_values,
-            self.builder.debug,
-        );
+        let mut f_builder = self.builder.sub_builder();
         f(&mut f_builder);
         let then_instructions = f_builder.operations;
 
@@ -526,12 +664,12 @@ impl<'a, C: Config> IfBuilder<'a, C> {
         match condition {
             IfCondition::EqConst(lhs, rhs) => {
                 if lhs == rhs {
-                    self.builder.operations.extend(then_instructions);
+                    self.builder.operations.append(then_instructions);
                 }
             }
             IfCondition::NeConst(lhs, rhs) => {
                 if lhs != rhs {
-                    self.builder.operations.extend(then_instructions);
+                    self.builder.operations.append(then_instructions);
                 }
             }
             IfCondition::Eq(lhs, rhs) => {
@@ -553,6 +691,7 @@ impl<'a, C: Config> IfBuilder<'a, C> {
         }
     }
 
+    #[track_caller]
     pub fn then_or_else(
         mut self,
         mut then_f: impl FnMut(&mut Builder<C>),
@@ -560,25 +699,13 @@ impl<'a, C: Config> IfBuilder<'a, C> {
     ) {
         // Get the condition reduced from the expressions for lhs and rhs.
         let condition = self.condition();
-        let mut then_builder = Builder::<C>::new_sub_builder(
-            self.builder.var_count,
-            self.builder.felt_count,
-            self.builder.ext_count,
-            self.builder.nb_public_values,
-            self.builder.debug,
-        );
+        let mut then_builder = self.builder.sub_builder();
 
         // Execute the `then` and `else_then` blocks and collect the instructions.
         then_f(&mut then_builder);
         let then_instructions = then_builder.operations;
 
-        let mut else_builder = Builder::<C>::new_sub_builder(
-            self.builder.var_count,
-            self.builder.felt_count,
-            self.builder.ext_count,
-            self.builder.nb_public_values,
-            self.builder.debug,
-        );
+        let mut else_builder = self.builder.sub_builder();
         else_f(&mut else_builder);
         let else_instructions = else_builder.operations;
 
@@ -586,16 +713,16 @@ impl<'a, C: Config> IfBuilder<'a, C> {
         match condition {
             IfCondition::EqConst(lhs, rhs) => {
                 if lhs == rhs {
-                    self.builder.operations.extend(then_instructions);
+                    self.builder.operations.append(then_instructions);
                 } else {
-                    self.builder.operations.extend(else_instructions);
+                    self.builder.operations.append(else_instructions);
                 }
             }
             IfCondition::NeConst(lhs, rhs) => {
                 if lhs != rhs {
-                    self.builder.operations.extend(then_instructions);
+                    self.builder.operations.append(then_instructions);
                 } else {
-                    self.builder.operations.extend(else_instructions);
+                    self.builder.operations.append(else_instructions);
                 }
             }
             IfCondition::Eq(lhs, rhs) => {
@@ -703,16 +830,11 @@ impl<'a, C: Config> RangeBuilder<'a, C> {
         self
     }
 
+    #[track_caller]
     pub fn for_each(self, mut f: impl FnMut(Var<C::N>, &mut Builder<C>)) {
         let step_size = C::N::from_canonical_usize(self.step_size);
         let loop_variable: Var<C::N> = self.builder.uninit();
-        let mut loop_body_builder = Builder::<C>::new_sub_builder(
-            self.builder.var_count,
-            self.builder.felt_count,
-            self.builder.ext_count,
-            self.builder.nb_public_values,
-            self.builder.debug,
-        );
+        let mut loop_body_builder = self.builder.sub_builder();
 
         f(loop_variable, &mut loop_body_builder);
 
@@ -1,7 +1,7 @@
 use itertools::Itertools;
 use p3_field::AbstractField;
 
-use super::{Builder, Config, FromConstant, MemIndex, MemVariable, Ptr, Usize, Var, Variable};
+use super::{Builder, Config, Ext, FromConstant, MemIndex, MemVariable, Ptr, Usize, Var, Variable};
 
 /// An array that is either of static or dynamic size.
 #[derive(Debug, Clone)]
@@ -86,14 +86,7 @@ impl<C: Config, V: MemVariable<C>> Array<C, V> {
                 }
 
                 let slice_len: Usize<_> = builder.eval(end - start);
-                let mut slice = builder.dyn_array(slice_len);
-                builder.range(0, slice_len).for_each(|i, builder| {
-                    let idx: Usize<_> = builder.eval(start + i);
-                    let value = builder.get(self, idx);
-                    builder.set(&mut slice, i, value);
-                });
-
-                slice
+                builder.array_slice(self, start, slice_len)
             }
         }
     }
@@ -237,6 +230,167 @@ impl<C: Config> Builder<C> {
             }
         }
     }
+
+    /// Returns a new array holding the `len` elements of `src` starting at `start`.
+    ///
+    /// Fixed arrays are sliced at compile time, dynamic arrays are copied with
+    /// [Builder::array_copy].
+    pub fn array_slice<V: MemVariable<C>>(
+        &mut self,
+        src: &Array<C, V>,
+        start: impl Into<Usize<C::N>>,
+        len: impl Into<Usize<C::N>>,
+    ) -> Array<C, V> {
+        let (start, len) = (start.into(), len.into());
+        match src {
+            Array::Fixed(vec) => {
+                if let (Usize::Const(start), Usize::Const(len)) = (start, len) {
+                    Array::Fixed(vec[start..start + len].to_vec())
+                } else {
+                    panic!("Cannot slice a fixed array with a variable start or length");
+                }
+            }
+            Array::Dyn(_, _) => {
+                let mut slice = self.dyn_array(len);
+                self.array_copy(&mut slice, 0, src, start, len);
+                slice
+            }
+        }
+    }
+
+    /// Copies the `len` elements of `src` starting at `src_offset` to `dst` starting at
+    /// `dst_offset`.
+    ///
+    /// Fixed arrays are copied by re-binding their variables, which adds no instructions or
+    /// constraints. Dynamic arrays are copied by a loop moving one memory block per load and
+    /// store, indexed by the loop variable alone. The ranges may overlap if `src` and `dst` are
+    /// the same array, otherwise they must not overlap.
+    pub fn array_copy<V: MemVariable<C>>(
+        &mut self,
+        dst: &mut Array<C, V>,
+        dst_offset: impl Into<Usize<C::N>>,
+        src: &Array<C, V>,
+        src_offset: impl Into<Usize<C::N>>,
+        len: impl Into<Usize<C::N>>,
+    ) {
+        let (dst_offset, src_offset, len) = (dst_offset.into(), src_offset.into(), len.into());
+        match (dst, src) {
+            (Array::Fixed(dst), Array::Fixed(src)) => {
+                if let (Usize::Const(dst_offset), Usize::Const(src_offset), Usize::Const(len)) =
+                    (dst_offset, src_offset, len)
+                {
+                    dst[dst_offset..dst_offset + len]
+                        .clone_from_slice(&src[src_offset..src_offset + len]);
+                } else {
+                    panic!("Cannot copy between fixed arrays with a variable offset or length");
+                }
+            }
+            (Array::Dyn(dst_ptr, dst_len), Array::Dyn(src_ptr, src_len)) => {
+                if self.debug {
+                    self.assert_range_in_bounds(dst_offset, len, *dst_len);
+                    self.assert_range_in_bounds(src_offset, len, *src_len);
+                }
+
+                // A forward copy within the same array only reads blocks before overwriting them
+                // if the destination comes first, otherwise copy through a scratch array.
+                let forward_is_safe = match (dst_offset, src_offset) {
+                    (Usize::Const(dst_offset), Usize::Const(src_offset)) => {
+                        dst_offset <= src_offset
+                    }
+                    _ => false,
+                };
+                if dst_ptr.address == src_ptr.address && !forward_is_safe {
+                    let scratch = self.array_slice(src, src_offset, len);
+                    let Array::Dyn(scratch_ptr, _) = scratch else {
+                        unreachable!()
+                    };
+                    self.copy_blocks(
+                        *dst_ptr,
+                        dst_offset,
+                        scratch_ptr,
+                        Usize::Const(0),
+                        len,
+                        V::size_of(),
+                    );
+                } else {
+                    self.copy_blocks(
+                        *dst_ptr,
+                        dst_offset,
+                        *src_ptr,
+                        src_offset,
+                        len,
+                        V::size_of(),
+                    );
+                }
+            }
+            _ => panic!("Cannot copy between fixed and dynamic arrays"),
+        }
+    }
+
+    /// Asserts that `offset + len <= array_len`.
+    fn assert_range_in_bounds(
+        &mut self,
+        offset: Usize<C::N>,
+        len: Usize<C::N>,
+        array_len: Usize<C::N>,
+    ) {
+        let end: Usize<_> = self.eval(offset + len);
+        let end = end.materialize(self);
+        let array_len = array_len.materialize(self);
+        let array_len_plus_1: Var<_> = self.eval(array_len + C::N::one());
+        let valid = self.lt(end, array_len_plus_1);
+        self.assert_var_eq(valid, C::N::one());
+    }
+
+    /// Moves the `len` elements of `size` blocks each from `src` to `dst`, starting at the given
+    /// element offsets.
+    fn copy_blocks(
+        &mut self,
+        dst: Ptr<C::N>,
+        dst_offset: Usize<C::N>,
+        src: Ptr<C::N>,
+        src_offset: Usize<C::N>,
+        len: Usize<C::N>,
+        size: usize,
+    ) {
+        let (dst, dst_offset) = self.offset_ptr(dst, dst_offset, size);
+        let (src, src_offset) = self.offset_ptr(src, src_offset, size);
+        // Loads and stores move whole blocks, so an extension element can carry any variable.
+        let block: Ext<C::F, C::EF> = self.uninit();
+        self.range(0, len).for_each(|i, builder| {
+            for j in 0..size {
+                let src_index = MemIndex {
+                    index: i.into(),
+                    offset: src_offset + j,
+                    size,
+                };
+                builder.load(block, src, src_index);
+                let dst_index = MemIndex {
+                    index: i.into(),
+                    offset: dst_offset + j,
+                    size,
+                };
+                builder.store(dst, dst_index, block);
+            }
+        });
+    }
+
+    /// Splits the address of the element at `offset` into a pointer and a constant block offset,
+    /// only materializing a new pointer if `offset` is a variable.
+    fn offset_ptr(
+        &mut self,
+        ptr: Ptr<C::N>,
+        offset: Usize<C::N>,
+        size: usize,
+    ) -> (Ptr<C::N>, usize) {
+        match offset {
+            Usize::Const(offset) => (ptr, offset * size),
+            Usize::Var(offset) => {
+                let address = self.eval(ptr.address + offset * C::N::from_canonical_usize(size));
+                (Ptr { address }, 0)
+            }
+        }
+    }
 }
 
 impl<C: Config, T: MemVariable<C>> Variable<C> for Array<C, T> {
@@ -246,6 +400,7 @@ impl<C: Config, T: MemVariable<C>> Variable<C> for Array<C, T> {
         Array::Dyn(builder.uninit(), builder.uninit())
     }
 
+    #[track_caller]
     fn assign(&self, src: Self::Expression, builder: &mut Builder<C>) {
         match (self, src.clone()) {
             (Array::Dyn(lhs_ptr, lhs_len), Array::Dyn(rhs_ptr, rhs_len)) => {
@@ -256,6 +411,7 @@ impl<C: Config, T: MemVariable<C>> Variable<C> for Array<C, T> {
         }
     }
 
+    #[track_caller]
     fn assert_eq(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -291,6 +447,7 @@ impl<C: Config, T: MemVariable<C>> Variable<C> for Array<C, T> {
         }
     }
 
+    #[track_caller]
     fn assert_ne(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -366,3 +523,152 @@ impl<C: Config, V: FromConstant<C> + MemVariable<C>> FromConstant<C> for Array<C
         array
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_field::{AbstractExtensionField, AbstractField};
+    use sp1_core::{stark::StarkGenericConfig, utils::BabyBearPoseidon2};
+    use sp1_recursion_core::runtime::Runtime;
+
+    use crate::{
+        asm::{AsmBuilder, AsmConfig},
+        ir::{Array, Ext, ExtConst, Felt, Var},
+    };
+
+    type SC = BabyBearPoseidon2;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+
+    /// Runs the program and returns the number of instructions it executed.
+    fn run(builder: AsmBuilder<F, EF>) -> usize {
+        let program = builder.compile_program();
+        let config = SC::default();
+        let mut runtime = Runtime::<F, EF, _>::new(&program, config.perm.clone());
+        runtime.run();
+        runtime.timestamp
+    }
+
+    /// Returns a dynamic array holding `0, 1, ..., len - 1`.
+    fn iota(builder: &mut AsmBuilder<F, EF>, len: usize) -> Array<AsmConfig<F, EF>, Felt<F>> {
+        let mut array = builder.dyn_array(len);
+        for i in 0..len {
+            builder.set(&mut array, i, F::from_canonical_usize(i));
+        }
+        array
+    }
+
+    fn assert_contents(
+        builder: &mut AsmBuilder<F, EF>,
+        array: &Array<AsmConfig<F, EF>, Felt<F>>,
+        expected: &[usize],
+    ) {
+        builder.assert_usize_eq(array.len(), expected.len());
+        for (i, value) in expected.iter().enumerate() {
+            let element = builder.get(array, i);
+            builder.assert_felt_eq(element, F::from_canonical_usize(*value));
+        }
+    }
+
+    #[test]
+    fn test_array_copy() {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let src = iota(&mut builder, 8);
+
+        // Disjoint arrays, with constant and variable offsets.
+        let mut dst = iota(&mut builder, 6);
+        let src_offset: Var<_> = builder.eval(F::two());
+        builder.array_copy(&mut dst, 1, &src, src_offset, 4);
+        assert_contents(&mut builder, &dst, &[0, 2, 3, 4, 5, 5]);
+        let slice = builder.array_slice(&src, 3, 5);
+        assert_contents(&mut builder, &slice, &[3, 4, 5, 6, 7]);
+
+        // Overlapping ranges of the same array, in both directions.
+        let mut forward = iota(&mut builder, 8);
+        let alias = forward.clone();
+        builder.array_copy(&mut forward, 0, &alias, 2, 5);
+        assert_contents(&mut builder, &forward, &[2, 3, 4, 5, 6, 5, 6, 7]);
+        let mut backward = iota(&mut builder, 8);
+        let alias = backward.clone();
+        builder.array_copy(&mut backward, 2, &alias, 0, 5);
+        assert_contents(&mut builder, &backward, &[0, 1, 0, 1, 2, 3, 4, 7]);
+        let mut backward = iota(&mut builder, 8);
+        let alias = backward.clone();
+        let dst_offset: Var<_> = builder.eval(F::two());
+        builder.array_copy(&mut backward, dst_offset, &alias, 0, 5);
+        assert_contents(&mut builder, &backward, &[0, 1, 0, 1, 2, 3, 4, 7]);
+
+        // Extension elements and elements spanning several blocks are copied whole.
+        let mut exts = builder.dyn_array::<Ext<_, _>>(3);
+        for i in 0..3 {
+            let value = EF::from_canonical_usize(i) + EF::from_base_slice(&[F::zero(), F::one()]);
+            builder.set(&mut exts, i, value.cons());
+        }
+        let ext_slice = builder.array_slice(&exts, 1, 2);
+        for i in 0..2 {
+            let expected = builder.get(&exts, i + 1);
+            let element = builder.get(&ext_slice, i);
+            builder.assert_ext_eq(element, expected);
+        }
+        let mut arrays = builder.dyn_array::<Array<_, Felt<_>>>(2);
+        builder.set_value(&mut arrays, 0, src.clone());
+        builder.set_value(&mut arrays, 1, slice.clone());
+        let arrays_slice = builder.array_slice(&arrays, 1, 1);
+        let element = builder.get(&arrays_slice, 0);
+        builder.assert_eq::<Array<_, _>>(element, slice);
+
+        // Fixed arrays are re-bound without emitting any instruction.
+        let felts: Vec<Felt<_>> = (0..4)
+            .map(|i| builder.eval(F::from_canonical_usize(i)))
+            .collect();
+        let fixed_src = builder.vec(felts.clone());
+        let mut fixed_dst = builder.vec(felts.clone());
+        let num_operations = builder.operations.vec.len();
+        builder.array_copy(&mut fixed_dst, 0, &fixed_src, 1, 3);
+        let fixed_slice = builder.array_slice(&fixed_src, 2, 2);
+        assert_eq!(builder.operations.vec.len(), num_operations);
+        assert_eq!(fixed_dst.vec(), [felts[1], felts[2], felts[3], felts[3]]);
+        assert_eq!(fixed_slice.vec(), felts[2..4]);
+
+        run(builder);
+    }
+
+    #[test]
+    fn test_array_copy_cycles() {
+        let len = 64;
+
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let src = iota(&mut builder, len);
+        let mut dst = builder.dyn_array::<Felt<_>>(len);
+        builder.range(0, len).for_each(|i, builder| {
+            let value = builder.get(&src, i);
+            builder.set(&mut dst, i, value);
+        });
+        let naive_cycles = run(builder);
+
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let src = iota(&mut builder, len);
+        let mut dst = builder.dyn_array::<Felt<_>>(len);
+        builder.array_copy(&mut dst, 0, &src, 0, len);
+        let copy_cycles = run(builder);
+
+        // The naive loop moves each element with a load, a copy and a store, the copy loop with a
+        // load and a store.
+        assert!(
+            copy_cycles + len <= naive_cycles,
+            "copy took {} cycles, naive loop took {}",
+            copy_cycles,
+            naive_cycles
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "TRAP encountered")]
+    fn test_array_copy_out_of_bounds() {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        builder.debug = true;
+        let src = iota(&mut builder, 4);
+        let mut dst = builder.dyn_array::<Felt<_>>(4);
+        builder.array_copy(&mut dst, 2, &src, 0, 3);
+        run(builder);
+    }
+}
@@ -220,6 +220,16 @@ pub enum DslIr<C: Config> {
     /// Throws an error.
     Error(),
 
+    // Taint analysis.
+    /// Marks the hints a variable is derived from as constrained outside of the program, with a
+    /// reason. Only used by the [super::TaintAnalysis] and compiles to nothing.
+    AssumeConstrainedV(Var<C::N>, String),
+    /// Marks the hints a field element is derived from as constrained outside of the program.
+    AssumeConstrainedF(Felt<C::F>, String),
+    /// Marks the hints an extension field element is derived from as constrained outside of the
+    /// program.
+    AssumeConstrainedE(Ext<C::F, C::EF>, String),
+
     /// Converts an ext to a slice of felts.  
     HintExt2Felt(Array<C, Felt<C::F>>, Ext<C::F, C::EF>),
     /// Hint the length of the next array.  
@@ -250,6 +260,9 @@ pub enum DslIr<C: Config> {
     /// Asserts that the inputted var is equal the circuit's commited values digest public input. Should
     /// only be used when target is a gnark circuit.
     CircuitCommitCommitedValuesDigest(Var<C::N>),
+    /// Asserts that the inputted var is equal the circuit's input digest public input. Should only
+    /// be used when target is a gnark circuit.
+    CircuitCommitInputDigest(Var<C::N>),
 
     // FRI specific instructions.
     /// Executes a FRI fold operation. 1st field is the size of the fri fold input array.  2nd field
@@ -8,6 +8,7 @@ mod instructions;
 mod poseidon;
 mod ptr;
 mod symbolic;
+mod taint;
 mod types;
 mod utils;
 mod var;
@@ -18,6 +19,7 @@ pub use fold::*;
 pub use instructions::*;
 pub use ptr::*;
 pub use symbolic::*;
+pub use taint::*;
 pub use types::*;
 pub use var::*;
 
@@ -42,10 +42,12 @@ impl<C: Config> Variable<C> for Ptr<C::N> {
         }
     }
 
+    #[track_caller]
     fn assign(&self, src: Self::Expression, builder: &mut Builder<C>) {
         self.address.assign(src.address, builder);
     }
 
+    #[track_caller]
     fn assert_eq(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -54,6 +56,7 @@ impl<C: Config> Variable<C> for Ptr<C::N> {
         Var::assert_eq(lhs.into().address, rhs.into().address, builder);
     }
 
+    #[track_caller]
     fn assert_ne(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
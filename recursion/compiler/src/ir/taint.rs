@@ -0,0 +1,800 @@
+//! Accounting of which hinted values end up constrained.
+//!
+//! Hints (`Hint*`, `HintBits*`, `HintLen`, `HintExt2Felt` and the `Witness*` operations of
+//! circuits) inject values chosen by the prover. [TaintAnalysis] marks every such value as tainted
+//! by its hint, propagates taint through arithmetic, memory, hashing and the conditions and
+//! bounds of the blocks a value is assigned in, and counts a hint as
+//! constrained once an assertion is made on any value derived from it. The report lists the
+//! hints that flow into a public value, the address of a memory write, or a branch condition
+//! without ever being constrained.
+//!
+//! The analysis is flow-insensitive: a variable is tainted by every hint that is ever assigned to
+//! it, and memory is tracked per allocation site. It is meant to catch hints that are never
+//! checked, not hints that are checked insufficiently. Values that are checked outside of the
+//! program, such as public values verified by the next layer of recursion, can be marked with
+//! [Builder::assume_var_constrained] and its variants.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use super::{Array, Builder, Config, DslIr, Ext, Felt, MemIndex, Ptr, TracedVec, Usize, Var};
+
+/// The position of an operation: its index in the program, followed by the index of the block
+/// and the index in the block for each nested loop or branch.
+pub type OpPath = Vec<usize>;
+
+/// Where a hinted value was used without being constrained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaintSink {
+    /// A committed or registered public value, or a public input of a circuit.
+    PublicValue,
+    /// The address of a memory write.
+    MemoryWrite,
+    /// The condition of a branch.
+    BranchCondition,
+}
+
+/// An operation introducing prover-chosen values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintSite {
+    pub path: OpPath,
+    pub kind: &'static str,
+}
+
+/// A sink reached by hints that are never constrained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintFinding {
+    pub sink: TaintSink,
+    pub path: OpPath,
+    /// Indices of the unconstrained hints in [TaintReport::hints].
+    pub hints: Vec<usize>,
+}
+
+/// The result of a [TaintAnalysis].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaintReport {
+    /// Every hint of the program, in the order they were found.
+    pub hints: Vec<HintSite>,
+    /// Values assumed to be constrained outside of the program, with the reason given.
+    pub assumptions: Vec<(OpPath, String)>,
+    pub findings: Vec<TaintFinding>,
+}
+
+impl TaintReport {
+    /// Whether no unconstrained hint reaches a sink.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+fn fmt_path(path: &OpPath) -> String {
+    path.iter()
+        .map(|index| index.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+impl fmt::Display for TaintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} sinks reached by unconstrained hints:",
+            self.findings.len()
+        )?;
+        for finding in self.findings.iter() {
+            let hints = finding
+                .hints
+                .iter()
+                .map(|&hint| {
+                    let site = &self.hints[hint];
+                    format!("{} at {}", site.kind, fmt_path(&site.path))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                f,
+                "  {:?} at {}: {}",
+                finding.sink,
+                fmt_path(&finding.path),
+                hints
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A value of the DSL, identified by its kind and index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Value {
+    Var(u32),
+    Felt(u32),
+    Ext(u32),
+}
+
+trait Tracked {
+    fn value(&self) -> Value;
+}
+
+impl<N> Tracked for Var<N> {
+    fn value(&self) -> Value {
+        Value::Var(self.0)
+    }
+}
+
+impl<F> Tracked for Felt<F> {
+    fn value(&self) -> Value {
+        Value::Felt(self.0)
+    }
+}
+
+impl<F, EF> Tracked for Ext<F, EF> {
+    fn value(&self) -> Value {
+        Value::Ext(self.0)
+    }
+}
+
+fn usize_value<N>(value: &Usize<N>) -> Option<Value> {
+    match value {
+        Usize::Const(_) => None,
+        Usize::Var(var) => Some(var.value()),
+    }
+}
+
+/// A set of hint indices.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct HintSet(Vec<u64>);
+
+impl HintSet {
+    fn single(hint: usize) -> Self {
+        let mut set = Self::default();
+        set.0.resize(hint / 64 + 1, 0);
+        set.0[hint / 64] |= 1 << (hint % 64);
+        set
+    }
+
+    fn union_with(&mut self, other: &Self) -> bool {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        let mut changed = false;
+        for (word, other) in self.0.iter_mut().zip(other.0.iter()) {
+            changed |= *other & !*word != 0;
+            *word |= *other;
+        }
+        changed
+    }
+
+    fn difference(&self, other: &Self) -> Vec<usize> {
+        let mut hints = Vec::new();
+        for (i, word) in self.0.iter().enumerate() {
+            let mut word = word & !other.0.get(i).copied().unwrap_or_default();
+            while word != 0 {
+                hints.push(i * 64 + word.trailing_zeros() as usize);
+                word &= word - 1;
+            }
+        }
+        hints
+    }
+}
+
+/// What is known about a value or the contents of an allocation.
+#[derive(Debug, Clone, Default)]
+struct Facts {
+    hints: HintSet,
+    /// The allocation sites the value may point to.
+    sites: BTreeSet<usize>,
+}
+
+impl Facts {
+    fn union_with(&mut self, other: &Self) -> bool {
+        let mut changed = self.hints.union_with(&other.hints);
+        for site in other.sites.iter() {
+            changed |= self.sites.insert(*site);
+        }
+        changed
+    }
+}
+
+/// A flow-insensitive taint analysis of a program, see the [module documentation](self).
+#[derive(Debug, Default)]
+pub struct TaintAnalysis {
+    values: HashMap<Value, Facts>,
+    /// The contents of each allocation site.
+    memory: HashMap<usize, Facts>,
+    hint_ids: BTreeMap<OpPath, usize>,
+    hints: Vec<HintSite>,
+    site_ids: BTreeMap<OpPath, usize>,
+    assumptions: BTreeMap<OpPath, String>,
+    sinks: BTreeMap<(OpPath, TaintSink), HintSet>,
+    constrained: HintSet,
+    /// The taint of the conditions and loop bounds of the blocks being visited.
+    context: HintSet,
+    changed: bool,
+}
+
+impl TaintAnalysis {
+    /// Analyzes `operations` until the taint of every value is stable.
+    pub fn analyze<C: Config>(operations: &TracedVec<DslIr<C>>) -> TaintReport {
+        let mut analysis = Self::default();
+        loop {
+            analysis.changed = false;
+            analysis.visit_block(operations, &mut Vec::new());
+            if !analysis.changed {
+                break;
+            }
+        }
+        analysis.report()
+    }
+
+    fn report(self) -> TaintReport {
+        let findings = self
+            .sinks
+            .into_iter()
+            .filter_map(|((path, sink), hints)| {
+                let hints = hints.difference(&self.constrained);
+                (!hints.is_empty()).then_some(TaintFinding { sink, path, hints })
+            })
+            .collect();
+        TaintReport {
+            hints: self.hints,
+            assumptions: self.assumptions.into_iter().collect(),
+            findings,
+        }
+    }
+
+    fn visit_block<C: Config>(&mut self, operations: &TracedVec<DslIr<C>>, path: &mut OpPath) {
+        for (i, op) in operations.vec.iter().enumerate() {
+            path.push(i);
+            self.visit(op, path);
+            path.pop();
+        }
+    }
+
+    fn visit_nested<C: Config>(
+        &mut self,
+        block: usize,
+        operations: &TracedVec<DslIr<C>>,
+        path: &mut OpPath,
+    ) {
+        path.push(block);
+        self.visit_block(operations, path);
+        path.pop();
+    }
+
+    /// Enters a block whose execution depends on `values`, returning the previous context.
+    fn enter(&mut self, values: &[Value]) -> HintSet {
+        let mut context = self.context.clone();
+        for value in values {
+            if let Some(facts) = self.values.get(value) {
+                context.union_with(&facts.hints);
+            }
+        }
+        std::mem::replace(&mut self.context, context)
+    }
+
+    fn facts(&self, value: Value) -> Facts {
+        self.values.get(&value).cloned().unwrap_or_default()
+    }
+
+    fn flow_facts(&mut self, dst: Value, facts: &Facts) {
+        let entry = self.values.entry(dst).or_default();
+        let changed = entry.union_with(facts) | entry.hints.union_with(&self.context);
+        self.changed |= changed;
+    }
+
+    fn flow(&mut self, dst: Value, srcs: &[Value]) {
+        let mut facts = Facts::default();
+        for src in srcs {
+            if let Some(src) = self.values.get(src) {
+                facts.hints.union_with(&src.hints);
+                facts.sites.extend(src.sites.iter().copied());
+            }
+        }
+        self.flow_facts(dst, &facts);
+    }
+
+    fn flow_all(&mut self, values: &[Value]) {
+        for dst in values {
+            self.flow(*dst, values);
+        }
+    }
+
+    fn constrain(&mut self, values: &[Value]) {
+        for value in values {
+            if let Some(facts) = self.values.get(value) {
+                self.constrained.union_with(&facts.hints);
+            }
+        }
+    }
+
+    fn sink(&mut self, kind: TaintSink, path: &OpPath, values: &[Value]) {
+        let mut hints = HintSet::default();
+        for value in values {
+            if let Some(facts) = self.values.get(value) {
+                hints.union_with(&facts.hints);
+            }
+        }
+        self.sinks
+            .entry((path.clone(), kind))
+            .or_default()
+            .union_with(&hints);
+    }
+
+    /// The facts of a new hint at `path`.
+    fn hint(&mut self, path: &OpPath, kind: &'static str) -> Facts {
+        let hints = &mut self.hints;
+        let id = *self.hint_ids.entry(path.clone()).or_insert_with(|| {
+            hints.push(HintSite {
+                path: path.clone(),
+                kind,
+            });
+            hints.len() - 1
+        });
+        Facts {
+            hints: HintSet::single(id),
+            sites: BTreeSet::new(),
+        }
+    }
+
+    fn alloc<N>(&mut self, ptr: &Ptr<N>, path: &OpPath) {
+        let next = self.site_ids.len();
+        let site = *self.site_ids.entry(path.clone()).or_insert(next);
+        let facts = Facts {
+            hints: HintSet::default(),
+            sites: BTreeSet::from([site]),
+        };
+        self.flow_facts(ptr.address.value(), &facts);
+    }
+
+    fn sites(&self, ptr: Value) -> BTreeSet<usize> {
+        self.values
+            .get(&ptr)
+            .map(|facts| facts.sites.clone())
+            .unwrap_or_default()
+    }
+
+    /// The contents of the sites `ptr` may point to, together with the taint of `ptr` itself.
+    fn read(&self, ptr: Value) -> Facts {
+        let mut facts = Facts::default();
+        if let Some(ptr) = self.values.get(&ptr) {
+            facts.hints.union_with(&ptr.hints);
+            for site in ptr.sites.iter() {
+                if let Some(contents) = self.memory.get(site) {
+                    facts.union_with(contents);
+                }
+            }
+        }
+        facts
+    }
+
+    fn write(&mut self, ptr: Value, facts: &Facts) {
+        for site in self.sites(ptr) {
+            let entry = self.memory.entry(site).or_default();
+            let changed = entry.union_with(facts) | entry.hints.union_with(&self.context);
+            self.changed |= changed;
+        }
+    }
+
+    fn load<N>(&mut self, dst: Value, ptr: &Ptr<N>, index: &MemIndex<N>) {
+        let mut facts = self.read(ptr.address.value());
+        if let Some(index) = usize_value(&index.index) {
+            facts.hints.union_with(&self.facts(index).hints);
+        }
+        // Only variables hold pointers to other allocations.
+        if !matches!(dst, Value::Var(_)) {
+            facts.sites.clear();
+        }
+        self.flow_facts(dst, &facts);
+    }
+
+    fn store<N>(&mut self, src: Value, ptr: &Ptr<N>, index: &MemIndex<N>, path: &OpPath) {
+        let facts = self.facts(src);
+        self.write(ptr.address.value(), &facts);
+        let mut address = vec![ptr.address.value()];
+        address.extend(usize_value(&index.index));
+        self.sink(TaintSink::MemoryWrite, path, &address);
+    }
+
+    fn read_array<C: Config, T: Tracked>(&self, array: &Array<C, T>) -> Facts {
+        match array {
+            Array::Fixed(values) => {
+                let mut facts = Facts::default();
+                for value in values {
+                    facts.union_with(&self.facts(value.value()));
+                }
+                facts
+            }
+            Array::Dyn(ptr, _) => self.read(ptr.address.value()),
+        }
+    }
+
+    fn write_array<C: Config, T: Tracked>(&mut self, array: &Array<C, T>, facts: &Facts) {
+        match array {
+            Array::Fixed(values) => {
+                for value in values {
+                    self.flow_facts(value.value(), facts);
+                }
+            }
+            Array::Dyn(ptr, _) => self.write(ptr.address.value(), facts),
+        }
+    }
+
+    fn hint_array<C: Config, T: Tracked>(
+        &mut self,
+        array: &Array<C, T>,
+        path: &OpPath,
+        kind: &'static str,
+    ) {
+        let facts = self.hint(path, kind);
+        self.write_array(array, &facts);
+    }
+
+    fn assume(&mut self, value: Value, reason: &str, path: &OpPath) {
+        self.assumptions.insert(path.clone(), reason.to_string());
+        self.constrain(&[value]);
+    }
+
+    fn visit<C: Config>(&mut self, op: &DslIr<C>, path: &mut OpPath) {
+        match op {
+            DslIr::ImmV(..) | DslIr::ImmF(..) | DslIr::ImmE(..) => {}
+
+            DslIr::AddV(a, b, c) | DslIr::SubV(a, b, c) | DslIr::MulV(a, b, c) => {
+                self.flow(a.value(), &[b.value(), c.value()])
+            }
+            DslIr::AddF(a, b, c)
+            | DslIr::SubF(a, b, c)
+            | DslIr::MulF(a, b, c)
+            | DslIr::DivF(a, b, c) => self.flow(a.value(), &[b.value(), c.value()]),
+            DslIr::AddE(a, b, c)
+            | DslIr::SubE(a, b, c)
+            | DslIr::MulE(a, b, c)
+            | DslIr::DivE(a, b, c) => self.flow(a.value(), &[b.value(), c.value()]),
+            DslIr::AddEF(a, b, c)
+            | DslIr::SubEF(a, b, c)
+            | DslIr::MulEF(a, b, c)
+            | DslIr::DivEF(a, b, c) => self.flow(a.value(), &[b.value(), c.value()]),
+            DslIr::AddVI(a, b, _)
+            | DslIr::SubVI(a, b, _)
+            | DslIr::SubVIN(a, _, b)
+            | DslIr::MulVI(a, b, _)
+            | DslIr::NegV(a, b)
+            | DslIr::InvV(a, b) => self.flow(a.value(), &[b.value()]),
+            DslIr::AddFI(a, b, _)
+            | DslIr::SubFI(a, b, _)
+            | DslIr::SubFIN(a, _, b)
+            | DslIr::MulFI(a, b, _)
+            | DslIr::DivFI(a, b, _)
+            | DslIr::DivFIN(a, _, b)
+            | DslIr::NegF(a, b)
+            | DslIr::InvF(a, b) => self.flow(a.value(), &[b.value()]),
+            DslIr::AddEI(a, b, _)
+            | DslIr::AddEFI(a, b, _)
+            | DslIr::SubEI(a, b, _)
+            | DslIr::SubEIN(a, _, b)
+            | DslIr::SubEFI(a, b, _)
+            | DslIr::MulEI(a, b, _)
+            | DslIr::MulEFI(a, b, _)
+            | DslIr::DivEI(a, b, _)
+            | DslIr::DivEIN(a, _, b)
+            | DslIr::DivEFI(a, b, _)
+            | DslIr::DivEFIN(a, _, b)
+            | DslIr::NegE(a, b)
+            | DslIr::InvE(a, b) => self.flow(a.value(), &[b.value()]),
+            DslIr::AddEFFI(a, b, _) => self.flow(a.value(), &[b.value()]),
+            DslIr::LessThan(a, b, c) => self.flow(a.value(), &[b.value(), c.value()]),
+
+            // The bounds of a loop taint the values assigned in its body, but are not a sink
+            // since the lengths of hinted arrays bound most loops. The loop variable only ranges
+            // over the indices of the array and is not tainted.
+            DslIr::For(start, end, _, _, body) => {
+                let bounds = [usize_value(start), usize_value(end)];
+                let outer = self.enter(&bounds.into_iter().flatten().collect::<Vec<_>>());
+                self.visit_nested(0, body, path);
+                self.context = outer;
+            }
+            DslIr::IfEq(lhs, rhs, then_block, else_block)
+            | DslIr::IfNe(lhs, rhs, then_block, else_block) => {
+                let condition = [lhs.value(), rhs.value()];
+                self.sink(TaintSink::BranchCondition, path, &condition);
+                let outer = self.enter(&condition);
+                self.visit_nested(0, then_block, path);
+                self.visit_nested(1, else_block, path);
+                self.context = outer;
+            }
+            DslIr::IfEqI(lhs, _, then_block, else_block)
+            | DslIr::IfNeI(lhs, _, then_block, else_block) => {
+                self.sink(TaintSink::BranchCondition, path, &[lhs.value()]);
+                let outer = self.enter(&[lhs.value()]);
+                self.visit_nested(0, then_block, path);
+                self.visit_nested(1, else_block, path);
+                self.context = outer;
+            }
+            DslIr::Break => {}
+
+            DslIr::AssertEqV(a, b) | DslIr::AssertNeV(a, b) => {
+                self.constrain(&[a.value(), b.value()])
+            }
+            DslIr::AssertEqF(a, b) | DslIr::AssertNeF(a, b) => {
+                self.constrain(&[a.value(), b.value()])
+            }
+            DslIr::AssertEqE(a, b) | DslIr::AssertNeE(a, b) => {
+                self.constrain(&[a.value(), b.value()])
+            }
+            DslIr::AssertEqVI(a, _) | DslIr::AssertNeVI(a, _) => self.constrain(&[a.value()]),
+            DslIr::AssertEqFI(a, _) | DslIr::AssertNeFI(a, _) => self.constrain(&[a.value()]),
+            DslIr::AssertEqEI(a, _) | DslIr::AssertNeEI(a, _) => self.constrain(&[a.value()]),
+            DslIr::AssumeConstrainedV(a, reason) => self.assume(a.value(), reason, path),
+            DslIr::AssumeConstrainedF(a, reason) => self.assume(a.value(), reason, path),
+            DslIr::AssumeConstrainedE(a, reason) => self.assume(a.value(), reason, path),
+
+            DslIr::Alloc(ptr, _, _) => self.alloc(ptr, path),
+            DslIr::LoadV(dst, ptr, index) => self.load(dst.value(), ptr, index),
+            DslIr::LoadF(dst, ptr, index) => self.load(dst.value(), ptr, index),
+            DslIr::LoadE(dst, ptr, index) => self.load(dst.value(), ptr, index),
+            DslIr::StoreV(src, ptr, index) => self.store(src.value(), ptr, index, path),
+            DslIr::StoreF(src, ptr, index) => self.store(src.value(), ptr, index, path),
+            DslIr::StoreE(src, ptr, index) => self.store(src.value(), ptr, index, path),
+
+            DslIr::CircuitNum2BitsV(num, _, bits) => {
+                for bit in bits {
+                    self.flow(bit.value(), &[num.value()]);
+                }
+            }
+            DslIr::CircuitNum2BitsF(num, bits) => {
+                for bit in bits {
+                    self.flow(bit.value(), &[num.value()]);
+                }
+            }
+
+            DslIr::Poseidon2PermuteBabyBear(output, input) => {
+                let facts = self.read_array(input);
+                self.write_array(output, &facts);
+            }
+            DslIr::Poseidon2CompressBabyBear(output, left, right) => {
+                let mut facts = self.read_array(left);
+                facts.union_with(&self.read_array(right));
+                self.write_array(output, &facts);
+            }
+            DslIr::CircuitPoseidon2Permute(state) => {
+                self.flow_all(&state.map(|var| var.value()));
+            }
+            DslIr::CircuitPoseidon2PermuteBabyBear(state) => {
+                self.flow_all(&state.map(|felt| felt.value()));
+            }
+
+            DslIr::HintBitsU(bits, _) => self.hint_array(bits, path, "HintBitsU"),
+            DslIr::HintBitsV(bits, _) => self.hint_array(bits, path, "HintBitsV"),
+            DslIr::HintBitsF(bits, _) => self.hint_array(bits, path, "HintBitsF"),
+            DslIr::HintExt2Felt(felts, _) => self.hint_array(felts, path, "HintExt2Felt"),
+            DslIr::HintVars(array) => self.hint_array(array, path, "HintVars"),
+            DslIr::HintFelts(array) => self.hint_array(array, path, "HintFelts"),
+            DslIr::HintExts(array) => self.hint_array(array, path, "HintExts"),
+            DslIr::HintLen(len) => {
+                let facts = self.hint(path, "HintLen");
+                self.flow_facts(len.value(), &facts);
+            }
+            DslIr::WitnessVar(var, _) => {
+                let facts = self.hint(path, "WitnessVar");
+                self.flow_facts(var.value(), &facts);
+            }
+            DslIr::WitnessFelt(felt, _) => {
+                let facts = self.hint(path, "WitnessFelt");
+                self.flow_facts(felt.value(), &facts);
+            }
+            DslIr::WitnessExt(ext, _) => {
+                let facts = self.hint(path, "WitnessExt");
+                self.flow_facts(ext.value(), &facts);
+            }
+
+            DslIr::Commit(value, _) | DslIr::RegisterPublicValue(value) => {
+                self.sink(TaintSink::PublicValue, path, &[value.value()])
+            }
+            DslIr::CircuitCommitVkeyHash(value)
+            | DslIr::CircuitCommitCommitedValuesDigest(value)
+            | DslIr::CircuitCommitInputDigest(value) => {
+                self.sink(TaintSink::PublicValue, path, &[value.value()])
+            }
+
+            // The fold reads and writes through the pointers stored in its inputs, so every
+            // allocation reachable from them is both read and written.
+            DslIr::FriFold(m, input) => {
+                let mut sites = match input {
+                    Array::Dyn(ptr, _) => self.sites(ptr.address.value()),
+                    Array::Fixed(_) => BTreeSet::new(),
+                };
+                let mut pending = sites.iter().copied().collect::<Vec<_>>();
+                while let Some(site) = pending.pop() {
+                    if let Some(contents) = self.memory.get(&site) {
+                        for next in contents.sites.iter() {
+                            if sites.insert(*next) {
+                                pending.push(*next);
+                            }
+                        }
+                    }
+                }
+                let mut facts = self.facts(m.value());
+                facts.hints.union_with(&self.context);
+                for site in sites.iter() {
+                    if let Some(contents) = self.memory.get(site) {
+                        facts.union_with(contents);
+                    }
+                }
+                for site in sites {
+                    let changed = self.memory.entry(site).or_default().union_with(&facts);
+                    self.changed |= changed;
+                }
+            }
+            DslIr::ExpReverseBitsLen(ptr, bits, len) => {
+                let mut facts = self.read(ptr.address.value());
+                facts.union_with(&self.read(bits.value()));
+                facts.hints.union_with(&self.facts(len.value()).hints);
+                self.write(ptr.address.value(), &facts);
+            }
+
+            DslIr::CircuitSelectV(cond, a, b, out) => {
+                self.flow(out.value(), &[cond.value(), a.value(), b.value()])
+            }
+            DslIr::CircuitSelectF(cond, a, b, out) => {
+                self.flow(out.value(), &[cond.value(), a.value(), b.value()])
+            }
+            DslIr::CircuitSelectE(cond, a, b, out) => {
+                self.flow(out.value(), &[cond.value(), a.value(), b.value()])
+            }
+            DslIr::CircuitExt2Felt(felts, ext) => {
+                for felt in felts {
+                    self.flow(felt.value(), &[ext.value()]);
+                }
+            }
+            DslIr::CircuitFelts2Ext(felts, ext) => {
+                self.flow(ext.value(), &felts.map(|felt| felt.value()));
+            }
+
+            DslIr::PrintV(_)
+            | DslIr::PrintF(_)
+            | DslIr::PrintE(_)
+            | DslIr::Error()
+            | DslIr::Halt
+            | DslIr::CycleTracker(_) => {}
+        }
+    }
+}
+
+impl<C: Config> Builder<C> {
+    /// Marks the hints `var` is derived from as constrained, for values that are checked outside
+    /// of the program. See [TaintAnalysis].
+    pub fn assume_var_constrained(&mut self, var: Var<C::N>, reason: &str) {
+        self.operations
+            .push(DslIr::AssumeConstrainedV(var, reason.to_string()));
+    }
+
+    /// Marks the hints `felt` is derived from as constrained, see
+    /// [Builder::assume_var_constrained].
+    pub fn assume_felt_constrained(&mut self, felt: Felt<C::F>, reason: &str) {
+        self.operations
+            .push(DslIr::AssumeConstrainedF(felt, reason.to_string()));
+    }
+
+    /// Marks the hints `ext` is derived from as constrained, see
+    /// [Builder::assume_var_constrained].
+    pub fn assume_ext_constrained(&mut self, ext: Ext<C::F, C::EF>, reason: &str) {
+        self.operations
+            .push(DslIr::AssumeConstrainedE(ext, reason.to_string()));
+    }
+
+    /// Makes compilation fail if an unconstrained hint reaches a sink, instead of logging the
+    /// report.
+    pub fn set_strict_taint(&mut self, strict: bool) {
+        self.strict_taint = strict;
+    }
+
+    /// Runs the [TaintAnalysis] on the operations of the builder.
+    pub fn taint_report(&self) -> TaintReport {
+        TaintAnalysis::analyze(&self.operations)
+    }
+
+    /// Reports the unconstrained hints of the program, panicking in strict mode.
+    pub(crate) fn check_taint(&self) {
+        let report = self.taint_report();
+        if report.is_clean() {
+            return;
+        }
+        if self.strict_taint {
+            panic!("unconstrained hints in strict mode: {}", report);
+        }
+        tracing::warn!("{}", report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+
+    use crate::asm::AsmBuilder;
+    use crate::ir::{Felt, Var};
+
+    use super::*;
+
+    type F = BabyBear;
+    type EF = p3_field::extension::BinomialExtensionField<BabyBear, 4>;
+
+    #[test]
+    fn test_hint_flowing_into_public_value() {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let hinted = builder.hint_felt();
+        let doubled: Felt<_> = builder.eval(hinted + hinted);
+        builder.commit_public_value(doubled);
+
+        let report = builder.taint_report();
+        assert_eq!(report.findings.len(), 1);
+        let finding = &report.findings[0];
+        assert_eq!(finding.sink, TaintSink::PublicValue);
+        let kinds = finding
+            .hints
+            .iter()
+            .map(|&hint| report.hints[hint].kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["HintFelts"]);
+
+        // Constraining any value derived from the hint clears the finding.
+        builder.assert_felt_eq(doubled, F::two());
+        assert!(builder.taint_report().is_clean());
+    }
+
+    #[test]
+    #[should_panic(expected = "unconstrained hints in strict mode")]
+    fn test_strict_mode_rejects_unconstrained_public_value() {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        builder.set_strict_taint(true);
+        let hinted = builder.hint_felt();
+        builder.commit_public_value(hinted);
+        builder.halt();
+        builder.compile_program();
+    }
+
+    #[test]
+    fn test_branch_and_memory_sinks() {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let condition = builder.hint_var();
+        builder.if_eq(condition, F::one()).then(|builder| {
+            builder.print_v(condition);
+        });
+
+        let index = builder.hint_var();
+        let mut array = builder.dyn_array::<Var<_>>(4);
+        let value: Var<_> = builder.eval(F::zero());
+        builder.set(&mut array, index, value);
+
+        let report = builder.taint_report();
+        let sinks = report
+            .findings
+            .iter()
+            .map(|finding| finding.sink)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sinks,
+            vec![TaintSink::BranchCondition, TaintSink::MemoryWrite]
+        );
+
+        builder.assume_var_constrained(condition, "checked by the caller");
+        builder.assert_var_ne(index, F::from_canonical_u32(4));
+        let report = builder.taint_report();
+        assert!(report.is_clean(), "{}", report);
+        assert_eq!(report.assumptions.len(), 1);
+    }
+
+    #[test]
+    fn test_taint_through_memory_and_hashing() {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let hinted = builder.hint_felts();
+        let digest = builder.poseidon2_hash(&hinted);
+        let element = builder.get(&digest, 0);
+        builder.commit_public_value(element);
+        assert!(!builder.taint_report().is_clean());
+
+        // Asserting on the digest constrains the hashed values.
+        let expected: Felt<_> = builder.eval(F::zero());
+        builder.assert_felt_eq(element, expected);
+        assert!(builder.taint_report().is_clean());
+    }
+}
@@ -49,11 +49,12 @@ pub struct Witness<C: Config> {
     pub exts: Vec<C::EF>,
     pub vkey_hash: C::N,
     pub commited_values_digest: C::N,
+    pub input_digest: C::N,
 }
 
 impl<C: Config> Witness<C> {
     pub fn size(&self) -> usize {
-        self.vars.len() + self.felts.len() + self.exts.len() + 2
+        self.vars.len() + self.felts.len() + self.exts.len() + 3
     }
 
     pub fn write_vkey_hash(&mut self, vkey_hash: C::N) {
@@ -65,6 +66,11 @@ impl<C: Config> Witness<C> {
         self.vars.push(commited_values_digest);
         self.commited_values_digest = commited_values_digest
     }
+
+    pub fn write_input_digest(&mut self, input_digest: C::N) {
+        self.vars.push(input_digest);
+        self.input_digest = input_digest;
+    }
 }
 
 impl<N: Field> Usize<N> {
@@ -159,6 +165,7 @@ impl<C: Config> Variable<C> for Usize<C::N> {
         builder.uninit::<Var<C::N>>().into()
     }
 
+    #[track_caller]
     fn assign(&self, src: Self::Expression, builder: &mut Builder<C>) {
         match self {
             Usize::Const(_) => {
@@ -175,6 +182,7 @@ impl<C: Config> Variable<C> for Usize<C::N> {
         }
     }
 
+    #[track_caller]
     fn assert_eq(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -197,6 +205,7 @@ impl<C: Config> Variable<C> for Usize<C::N> {
         }
     }
 
+    #[track_caller]
     fn assert_ne(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -223,6 +232,7 @@ impl<C: Config> Variable<C> for Usize<C::N> {
 }
 
 impl<N: Field> Var<N> {
+    #[track_caller]
     fn assign_with_cache<C: Config<N = N>>(
         &self,
         src: SymbolicVar<N>,
@@ -412,10 +422,12 @@ impl<C: Config> Variable<C> for Var<C::N> {
         var
     }
 
+    #[track_caller]
     fn assign(&self, src: Self::Expression, builder: &mut Builder<C>) {
         self.assign_with_cache(src, builder, &mut HashMap::new());
     }
 
+    #[track_caller]
     fn assert_eq(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -457,6 +469,7 @@ impl<C: Config> Variable<C> for Var<C::N> {
         }
     }
 
+    #[track_caller]
     fn assert_ne(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -514,6 +527,7 @@ impl<C: Config> MemVariable<C> for Var<C::N> {
 }
 
 impl<F: Field> Felt<F> {
+    #[track_caller]
     fn assign_with_cache<C: Config<F = F>>(
         &self,
         src: SymbolicFelt<F>,
@@ -755,10 +769,12 @@ impl<C: Config> Variable<C> for Felt<C::F> {
         felt
     }
 
+    #[track_caller]
     fn assign(&self, src: Self::Expression, builder: &mut Builder<C>) {
         self.assign_with_cache(src, builder, &mut HashMap::new());
     }
 
+    #[track_caller]
     fn assert_eq(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -800,6 +816,7 @@ impl<C: Config> Variable<C> for Felt<C::F> {
         }
     }
 
+    #[track_caller]
     fn assert_ne(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -857,6 +874,7 @@ impl<C: Config> MemVariable<C> for Felt<C::F> {
 }
 
 impl<F: Field, EF: ExtensionField<F>> Ext<F, EF> {
+    #[track_caller]
     fn assign_with_caches<C: Config<F = F, EF = EF>>(
         &self,
         src: SymbolicExt<F, EF>,
@@ -1149,10 +1167,12 @@ impl<C: Config> Variable<C> for Ext<C::F, C::EF> {
         ext
     }
 
+    #[track_caller]
     fn assign(&self, src: Self::Expression, builder: &mut Builder<C>) {
         self.assign_with_caches(src, builder, &mut HashMap::new(), &mut HashMap::new());
     }
 
+    #[track_caller]
     fn assert_eq(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
@@ -1194,6 +1214,7 @@ impl<C: Config> Variable<C> for Ext<C::F, C::EF> {
         }
     }
 
+    #[track_caller]
     fn assert_ne(
         lhs: impl Into<Self::Expression>,
         rhs: impl Into<Self::Expression>,
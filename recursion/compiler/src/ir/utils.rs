@@ -328,6 +328,82 @@ mod tests {
         runtime.run();
     }
 
+    #[test]
+    fn test_num2bits_edge_values() {
+        type SC = BabyBearPoseidon2;
+        type F = <SC as StarkGenericConfig>::Val;
+        type EF = <SC as StarkGenericConfig>::Challenge;
+
+        let config = SC::default();
+        let mut builder = AsmBuilder::<F, EF>::default();
+
+        // The extremes of the canonical range, and the values around the 4 most significant bits
+        // the canonical check of the decomposition looks at.
+        let values = [
+            0,
+            1,
+            (1 << 27) - 1,
+            1 << 27,
+            (15 << 27) - 1,
+            F::ORDER_U32 - 1,
+        ];
+        for value in values {
+            let num_val = F::from_canonical_u32(value);
+            let num: Var<_> = builder.eval(num_val);
+            let num_felt: Felt<_> = builder.eval(num_val);
+            let bits = builder.num2bits_v(num);
+            let bits_felt = builder.num2bits_f(num_felt);
+            for i in 0..NUM_BITS {
+                let expected_bit = F::from_canonical_u32((value >> i) & 1);
+                let bit = builder.get(&bits, i);
+                builder.assert_var_eq(bit, expected_bit);
+                let bit_felt = builder.get(&bits_felt, i);
+                builder.assert_var_eq(bit_felt, expected_bit);
+            }
+        }
+
+        let program = builder.compile_program();
+
+        let mut runtime = Runtime::<F, EF, _>::new(&program, config.perm.clone());
+        runtime.run();
+    }
+
+    #[test]
+    fn test_num2bits_circuit() {
+        type SC = BabyBearPoseidon2;
+        type F = <SC as StarkGenericConfig>::Val;
+        type EF = <SC as StarkGenericConfig>::Challenge;
+
+        let config = SC::default();
+        let mut builder = AsmBuilder::<F, EF>::default();
+
+        let values = [0, 1, (1 << 27) - 1, (15 << 27) - 1, F::ORDER_U32 - 1];
+        for value in values {
+            let num_val = F::from_canonical_u32(value);
+            let num: Var<_> = builder.eval(num_val);
+            let num_felt: Felt<_> = builder.eval(num_val);
+            let bits = builder.num2bits_v_circuit(num, NUM_BITS);
+            let bits_felt = builder.num2bits_f_circuit(num_felt);
+            for (i, (bit, bit_felt)) in bits.into_iter().zip(bits_felt).enumerate() {
+                let expected_bit = F::from_canonical_u32((value >> i) & 1);
+                builder.assert_var_eq(bit, expected_bit);
+                builder.assert_var_eq(bit_felt, expected_bit);
+            }
+        }
+
+        // Fewer bits range check the value.
+        let num: Var<_> = builder.eval(F::from_canonical_u32(0b1011));
+        let bits = builder.num2bits_v_circuit(num, 4);
+        for (bit, expected_bit) in bits.into_iter().zip([1, 1, 0, 1]) {
+            builder.assert_var_eq(bit, F::from_canonical_u32(expected_bit));
+        }
+
+        let program = builder.compile_program();
+
+        let mut runtime = Runtime::<F, EF, _>::new(&program, config.perm.clone());
+        runtime.run();
+    }
+
     #[test]
     fn test_reverse_bits_len() {
         type SC = BabyBearPoseidon2;
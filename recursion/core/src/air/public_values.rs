@@ -68,6 +68,12 @@ pub struct RecursionPublicValues<T> {
     /// The hash of all the bytes that the program has written to public values.
     pub committed_value_digest: [Word<T>; PV_DIGEST_NUM_WORDS],
 
+    /// The hash of the input buffers that the program has read.
+    pub input_digest: [Word<T>; PV_DIGEST_NUM_WORDS],
+
+    /// The hash of the config that the program was run with.
+    pub config_digest: [Word<T>; PV_DIGEST_NUM_WORDS],
+
     /// The hash of all deferred proofs that have been witnessed in the VM.
     pub deferred_proofs_digest: [T; POSEIDON_NUM_WORDS],
 
@@ -113,6 +119,13 @@ pub struct RecursionPublicValues<T> {
     /// Total number of core shards in the program execution.
     pub total_core_shards: T,
 
+    /// The bitmap of the features the program execution was proven with, see
+    /// [sp1_core::features].
+    pub features: T,
+
+    /// The digest of the reduce tree topology whose leaves and nodes produced this proof.
+    pub reduce_plan_digest: [T; DIGEST_SIZE],
+
     /// The digest of all the previous public values elements.
     pub digest: [T; DIGEST_SIZE],
 
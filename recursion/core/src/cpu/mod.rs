@@ -2,12 +2,14 @@ pub mod air;
 pub mod columns;
 mod trace;
 
+use serde::{Deserialize, Serialize};
+
 use crate::air::Block;
 pub use crate::{memory::MemoryRecord, runtime::Instruction};
 
 pub use columns::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CpuEvent<F> {
     pub clk: F,
     pub pc: F,
@@ -11,6 +11,7 @@ use p3_field::PrimeField32;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::reverse_bits_len;
+use serde::{Deserialize, Serialize};
 use sp1_core::air::{BaseAirBuilder, ExtensionAirBuilder, MachineAir, SP1AirBuilder};
 use sp1_core::utils::pad_rows_fixed;
 use sp1_derive::AlignedBorrow;
@@ -29,7 +30,7 @@ pub struct ExpReverseBitsLenChip<const DEGREE: usize> {
     pub pad: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExpReverseBitsLenEvent<F> {
     /// The clk cycle for the event.
     pub clk: F,
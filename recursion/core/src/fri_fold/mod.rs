@@ -1,7 +1,7 @@
 #![allow(clippy::needless_range_loop)]
 
-use crate::air::RecursionMemoryAirBuilder;
-use crate::memory::{MemoryReadCols, MemoryReadSingleCols, MemoryReadWriteCols};
+use crate::air::{Block, BlockBuilder, RecursionMemoryAirBuilder};
+use crate::memory::{MemoryReadCols, MemoryReadTimestampCols, MemoryReadWriteCols};
 use crate::runtime::Opcode;
 use core::borrow::Borrow;
 use itertools::Itertools;
@@ -10,6 +10,7 @@ use p3_field::AbstractField;
 use p3_field::PrimeField32;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
+use serde::{Deserialize, Serialize};
 use sp1_core::air::{BaseAirBuilder, BinomialExtension, ExtensionAirBuilder, MachineAir};
 use sp1_core::utils::pad_rows_fixed;
 use sp1_derive::AlignedBorrow;
@@ -28,7 +29,7 @@ pub struct FriFoldChip<const DEGREE: usize> {
     pub pad: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FriFoldEvent<F> {
     pub clk: F,
     pub m: F,
@@ -63,17 +64,33 @@ pub struct FriFoldCols<T: Copy> {
     /// At the last iteraction of a FRI_FOLD invocation.
     pub is_last_iteration: T,
 
-    /// The inputs stored in memory.  All the values are just read from memory.
-    pub z: MemoryReadCols<T>,
-    pub alpha: MemoryReadCols<T>,
-    pub x: MemoryReadSingleCols<T>,
-
-    pub log_height: MemoryReadSingleCols<T>,
-    pub mat_opening_ptr: MemoryReadSingleCols<T>,
-    pub ps_at_z_ptr: MemoryReadSingleCols<T>,
-    pub alpha_pow_ptr: MemoryReadSingleCols<T>,
-    pub ro_ptr: MemoryReadSingleCols<T>,
-
+    /// At the first iteration of a FRI_FOLD invocation.
+    pub is_first_iteration: T,
+
+    /// The inputs stored in memory that are the same for every iteration of an invocation.  They
+    /// are only read from memory on the first iteration and copied over from the previous row
+    /// otherwise.
+    pub z: Block<T>,
+    pub alpha: Block<T>,
+    pub x: T,
+
+    pub log_height: T,
+    pub mat_opening_ptr: T,
+    pub ps_at_z_ptr: T,
+    pub alpha_pow_ptr: T,
+    pub ro_ptr: T,
+
+    /// The timestamps of the first iteration's reads of the inputs above.
+    pub z_read: MemoryReadTimestampCols<T>,
+    pub alpha_read: MemoryReadTimestampCols<T>,
+    pub x_read: MemoryReadTimestampCols<T>,
+    pub log_height_read: MemoryReadTimestampCols<T>,
+    pub mat_opening_ptr_read: MemoryReadTimestampCols<T>,
+    pub ps_at_z_ptr_read: MemoryReadTimestampCols<T>,
+    pub alpha_pow_ptr_read: MemoryReadTimestampCols<T>,
+    pub ro_ptr_read: MemoryReadTimestampCols<T>,
+
+    /// The inputs read from memory on every iteration.
     pub p_at_x: MemoryReadCols<T>,
     pub p_at_z: MemoryReadCols<T>,
 
@@ -121,16 +138,28 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for FriFoldChip<DEGREE>
                 cols.m = event.m;
                 cols.input_ptr = event.input_ptr;
                 cols.is_last_iteration = event.is_last_iteration;
+                cols.is_first_iteration = F::from_bool(event.m.is_zero());
                 cols.is_real = F::one();
 
-                cols.z.populate(&event.z);
-                cols.alpha.populate(&event.alpha);
-                cols.x.populate(&event.x);
-                cols.log_height.populate(&event.log_height);
-                cols.mat_opening_ptr.populate(&event.mat_opening_ptr);
-                cols.ps_at_z_ptr.populate(&event.ps_at_z_ptr);
-                cols.alpha_pow_ptr.populate(&event.alpha_pow_ptr);
-                cols.ro_ptr.populate(&event.ro_ptr);
+                cols.z = event.z.value;
+                cols.alpha = event.alpha.value;
+                cols.x = event.x.value[0];
+                cols.log_height = event.log_height.value[0];
+                cols.mat_opening_ptr = event.mat_opening_ptr.value[0];
+                cols.ps_at_z_ptr = event.ps_at_z_ptr.value[0];
+                cols.alpha_pow_ptr = event.alpha_pow_ptr.value[0];
+                cols.ro_ptr = event.ro_ptr.value[0];
+
+                if event.m.is_zero() {
+                    cols.z_read.populate(&event.z);
+                    cols.alpha_read.populate(&event.alpha);
+                    cols.x_read.populate(&event.x);
+                    cols.log_height_read.populate(&event.log_height);
+                    cols.mat_opening_ptr_read.populate(&event.mat_opening_ptr);
+                    cols.ps_at_z_ptr_read.populate(&event.ps_at_z_ptr);
+                    cols.alpha_pow_ptr_read.populate(&event.alpha_pow_ptr);
+                    cols.ro_ptr_read.populate(&event.ro_ptr);
+                }
 
                 cols.p_at_x.populate(&event.p_at_x);
                 cols.p_at_z.populate(&event.p_at_z);
@@ -177,6 +206,7 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         local: &FriFoldCols<AB::Var>,
         next: &FriFoldCols<AB::Var>,
         receive_table: AB::Var,
+        first_iteration_memory_access: AB::Var,
         memory_access: AB::Var,
     ) {
         // Dummy constraints to normalize to DEGREE when DEGREE > 3.
@@ -205,6 +235,20 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         );
 
         builder.assert_bool(local.is_last_iteration);
+        builder.assert_bool(local.is_first_iteration);
+
+        // Ensure that the first iteration flag is set exactly on the first row of each invocation.
+        builder
+            .when_first_row()
+            .assert_eq(local.is_first_iteration, local.is_real);
+        builder
+            .when_transition()
+            .when(next.is_real)
+            .assert_eq(next.is_first_iteration, local.is_last_iteration);
+        builder
+            .when(local.is_first_iteration)
+            .assert_one(local.is_real);
+        builder.when(local.is_first_iteration).assert_zero(local.m);
 
         // Ensure that all first iteration rows has a m value of 0.
         builder.when_first_row().assert_zero(local.m);
@@ -231,74 +275,93 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
             .when(next.is_real)
             .assert_eq(local.clk + AB::Expr::one(), next.clk);
 
+        // Ensure that the inputs which are only read on the first iteration are carried over to
+        // the rest of the invocation.
+        {
+            let mut builder = builder
+                .when_transition()
+                .when_not(local.is_last_iteration)
+                .when(next.is_real);
+            builder.assert_block_eq(local.z, next.z);
+            builder.assert_block_eq(local.alpha, next.alpha);
+            builder.assert_eq(local.x, next.x);
+            builder.assert_eq(local.log_height, next.log_height);
+            builder.assert_eq(local.mat_opening_ptr, next.mat_opening_ptr);
+            builder.assert_eq(local.ps_at_z_ptr, next.ps_at_z_ptr);
+            builder.assert_eq(local.alpha_pow_ptr, next.alpha_pow_ptr);
+            builder.assert_eq(local.ro_ptr, next.ro_ptr);
+        }
+
         // Constrain read for `z` at `input_ptr`
         builder.recursion_eval_memory_access(
             local.clk,
             local.input_ptr + AB::Expr::zero(),
-            &local.z,
-            memory_access,
+            &local.z_read.read(local.z),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `alpha`
         builder.recursion_eval_memory_access(
             local.clk,
             local.input_ptr + AB::Expr::one(),
-            &local.alpha,
-            memory_access,
+            &local.alpha_read.read(local.alpha),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `x`
         builder.recursion_eval_memory_access_single(
             local.clk,
             local.input_ptr + AB::Expr::from_canonical_u32(2),
-            &local.x,
-            memory_access,
+            &local.x_read.read_single(local.x),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `log_height`
         builder.recursion_eval_memory_access_single(
             local.clk,
             local.input_ptr + AB::Expr::from_canonical_u32(3),
-            &local.log_height,
-            memory_access,
+            &local.log_height_read.read_single(local.log_height),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `mat_opening_ptr`
         builder.recursion_eval_memory_access_single(
             local.clk,
             local.input_ptr + AB::Expr::from_canonical_u32(4),
-            &local.mat_opening_ptr,
-            memory_access,
+            &local
+                .mat_opening_ptr_read
+                .read_single(local.mat_opening_ptr),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `ps_at_z_ptr`
         builder.recursion_eval_memory_access_single(
             local.clk,
             local.input_ptr + AB::Expr::from_canonical_u32(6),
-            &local.ps_at_z_ptr,
-            memory_access,
+            &local.ps_at_z_ptr_read.read_single(local.ps_at_z_ptr),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `alpha_pow_ptr`
         builder.recursion_eval_memory_access_single(
             local.clk,
             local.input_ptr + AB::Expr::from_canonical_u32(8),
-            &local.alpha_pow_ptr,
-            memory_access,
+            &local.alpha_pow_ptr_read.read_single(local.alpha_pow_ptr),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `ro_ptr`
         builder.recursion_eval_memory_access_single(
             local.clk,
             local.input_ptr + AB::Expr::from_canonical_u32(10),
-            &local.ro_ptr,
-            memory_access,
+            &local.ro_ptr_read.read_single(local.ro_ptr),
+            first_iteration_memory_access,
         );
 
         // Constrain read for `p_at_x`
         builder.recursion_eval_memory_access(
             local.clk,
-            local.mat_opening_ptr.access.value.into() + local.m.into(),
+            local.mat_opening_ptr.into() + local.m.into(),
             &local.p_at_x,
             memory_access,
         );
@@ -306,7 +369,7 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         // Constrain read for `p_at_z`
         builder.recursion_eval_memory_access(
             local.clk,
-            local.ps_at_z_ptr.access.value.into() + local.m.into(),
+            local.ps_at_z_ptr.into() + local.m.into(),
             &local.p_at_z,
             memory_access,
         );
@@ -315,13 +378,13 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         // 1. Constrain old and new value against memory
         builder.recursion_eval_memory_access(
             local.clk,
-            local.alpha_pow_ptr.access.value.into() + local.log_height.access.value.into(),
+            local.alpha_pow_ptr.into() + local.log_height.into(),
             &local.alpha_pow_at_log_height,
             memory_access,
         );
 
         // 2. Constrain new_value = old_value * alpha.
-        let alpha = local.alpha.access.value.as_extension::<AB>();
+        let alpha = local.alpha.as_extension::<AB>();
         let alpha_pow_at_log_height = local
             .alpha_pow_at_log_height
             .prev_value
@@ -341,7 +404,7 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         // 1. Constrain old and new value against memory.
         builder.recursion_eval_memory_access(
             local.clk,
-            local.ro_ptr.access.value.into() + local.log_height.access.value.into(),
+            local.ro_ptr.into() + local.log_height.into(),
             &local.ro_at_log_height,
             memory_access,
         );
@@ -351,8 +414,8 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         // <=> (new_value - old_value) * (z - x) = old_alpha_pow_at_log_height * (p_at_x - p_at_z)
         let p_at_z = local.p_at_z.access.value.as_extension::<AB>();
         let p_at_x = local.p_at_x.access.value.as_extension::<AB>();
-        let z = local.z.access.value.as_extension::<AB>();
-        let x = local.x.access.value.into();
+        let z = local.z.as_extension::<AB>();
+        let x = local.x.into();
 
         let ro_at_log_height = local.ro_at_log_height.prev_value.as_extension::<AB>();
         let new_ro_at_log_height = local.ro_at_log_height.access.value.as_extension::<AB>();
@@ -366,6 +429,10 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
         local.is_last_iteration
     }
 
+    pub const fn do_first_iteration_memory_access<T: Copy>(local: &FriFoldCols<T>) -> T {
+        local.is_first_iteration
+    }
+
     pub const fn do_memory_access<T: Copy>(local: &FriFoldCols<T>) -> T {
         local.is_real
     }
@@ -385,7 +452,189 @@ where
             local,
             next,
             Self::do_receive_table::<AB::Var>(local),
+            Self::do_first_iteration_memory_access::<AB::Var>(local),
             Self::do_memory_access::<AB::Var>(local),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractExtensionField, AbstractField, Field, PrimeField32};
+    use p3_matrix::dense::RowMajorMatrix;
+    use sp1_core::lookup::{Interaction, InteractionKind};
+    use sp1_core::stark::{Chip, StarkGenericConfig};
+    use sp1_core::{
+        air::MachineAir,
+        utils::{uni_stark_prove, uni_stark_verify, BabyBearPoseidon2},
+    };
+
+    use super::{FriFoldChip, FriFoldCols, FriFoldEvent, NUM_FRI_FOLD_COLS};
+    use crate::air::Block;
+    use crate::memory::MemoryRecord;
+    use crate::runtime::ExecutionRecord;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<BabyBear, 4>;
+
+    /// Generates the events of one FRI_FOLD invocation with `num_iterations` iterations, mirroring
+    /// what the runtime records.
+    fn fri_fold_events(clk: u32, input_ptr: u32, num_iterations: u32) -> Vec<FriFoldEvent<F>> {
+        let clk = F::from_canonical_u32(clk);
+        let input_ptr = F::from_canonical_u32(input_ptr);
+        let read = |offset: u32, value: Block<F>| {
+            MemoryRecord::new_read(
+                input_ptr + F::from_canonical_u32(offset),
+                value,
+                clk,
+                F::zero(),
+            )
+        };
+        let single = |v: u32| Block::from(F::from_canonical_u32(v));
+
+        let z = EF::from_canonical_u32(7);
+        let alpha = EF::from_canonical_u32(3);
+        let x = F::from_canonical_u32(11);
+        let (mat_opening_ptr, ps_at_z_ptr, alpha_pow_ptr, ro_ptr) = (1000, 2000, 3000, 4000);
+        let log_height = 2;
+
+        let z_record = read(0, Block::from(z.as_base_slice()));
+        let alpha_record = read(1, Block::from(alpha.as_base_slice()));
+        let x_record = read(2, Block::from(x));
+        let log_height_record = read(3, single(log_height));
+        let mat_opening_ptr_record = read(4, single(mat_opening_ptr));
+        let ps_at_z_ptr_record = read(6, single(ps_at_z_ptr));
+        let alpha_pow_ptr_record = read(8, single(alpha_pow_ptr));
+        let ro_ptr_record = read(10, single(ro_ptr));
+
+        let mut alpha_pow = EF::one();
+        let mut ro = EF::zero();
+        let mut prev_timestamp = F::zero();
+        (0..num_iterations)
+            .map(|m| {
+                let timestamp = clk + F::from_canonical_u32(m);
+                let p_at_x = EF::from_canonical_u32(m + 5);
+                let p_at_z = EF::from_canonical_u32(m + 1);
+                let new_ro = ro + alpha_pow * (p_at_x - p_at_z) / (EF::from_base(x) - z);
+                let new_alpha_pow = alpha_pow * alpha;
+
+                let event = FriFoldEvent {
+                    clk: timestamp,
+                    m: F::from_canonical_u32(m),
+                    input_ptr,
+                    is_last_iteration: F::from_bool(m == num_iterations - 1),
+                    z: z_record,
+                    alpha: alpha_record,
+                    x: x_record,
+                    log_height: log_height_record,
+                    mat_opening_ptr: mat_opening_ptr_record,
+                    ps_at_z_ptr: ps_at_z_ptr_record,
+                    alpha_pow_ptr: alpha_pow_ptr_record,
+                    ro_ptr: ro_ptr_record,
+                    p_at_x: MemoryRecord::new_read(
+                        F::from_canonical_u32(mat_opening_ptr + m),
+                        Block::from(p_at_x.as_base_slice()),
+                        timestamp,
+                        F::zero(),
+                    ),
+                    p_at_z: MemoryRecord::new_read(
+                        F::from_canonical_u32(ps_at_z_ptr + m),
+                        Block::from(p_at_z.as_base_slice()),
+                        timestamp,
+                        F::zero(),
+                    ),
+                    alpha_pow_at_log_height: MemoryRecord::new_write(
+                        F::from_canonical_u32(alpha_pow_ptr + log_height),
+                        Block::from(new_alpha_pow.as_base_slice()),
+                        timestamp,
+                        Block::from(alpha_pow.as_base_slice()),
+                        prev_timestamp,
+                    ),
+                    ro_at_log_height: MemoryRecord::new_write(
+                        F::from_canonical_u32(ro_ptr + log_height),
+                        Block::from(new_ro.as_base_slice()),
+                        timestamp,
+                        Block::from(ro.as_base_slice()),
+                        prev_timestamp,
+                    ),
+                };
+                alpha_pow = new_alpha_pow;
+                ro = new_ro;
+                prev_timestamp = timestamp;
+                event
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prove_multiple_invocations() {
+        let config = BabyBearPoseidon2::compressed();
+        let mut challenger = config.challenger();
+
+        let chip = FriFoldChip::<3> {
+            fixed_log2_rows: None,
+            pad: true,
+        };
+
+        let mut input_exec = ExecutionRecord::<F>::default();
+        input_exec
+            .fri_fold_events
+            .extend(fri_fold_events(10, 100, 3));
+        input_exec
+            .fri_fold_events
+            .extend(fri_fold_events(20, 200, 2));
+        input_exec
+            .fri_fold_events
+            .extend(fri_fold_events(30, 300, 1));
+        let trace: RowMajorMatrix<F> =
+            chip.generate_trace(&input_exec, &mut ExecutionRecord::<F>::default());
+
+        let (num_invocations, num_rows) =
+            trace
+                .values
+                .chunks_exact(NUM_FRI_FOLD_COLS)
+                .fold((0, 0), |(first, real), row| {
+                    let cols: &FriFoldCols<F> = row.borrow();
+                    (
+                        first + cols.is_first_iteration.as_canonical_u32(),
+                        real + cols.is_real.as_canonical_u32(),
+                    )
+                });
+        assert_eq!(num_invocations, 3);
+        assert_eq!(num_rows, 6);
+
+        // Count the memory interactions the chip emits over the trace: the invariant inputs are
+        // read once per invocation rather than once per row.
+        let air = Chip::<F, _>::new(FriFoldChip::<3>::default());
+        let num_memory_interactions = |interactions: &[Interaction<F>]| {
+            trace
+                .values
+                .chunks_exact(NUM_FRI_FOLD_COLS)
+                .map(|row| {
+                    interactions
+                        .iter()
+                        .filter(|interaction| interaction.kind == InteractionKind::Memory)
+                        .filter(|interaction| {
+                            !interaction.multiplicity.apply::<F, F>(&[], row).is_zero()
+                        })
+                        .count()
+                })
+                .sum::<usize>()
+        };
+        let num_memory_accesses = 8 * num_invocations as usize + 4 * num_rows as usize;
+        assert_eq!(num_memory_accesses, 48);
+        assert_eq!(num_memory_interactions(air.sends()), num_memory_accesses);
+        assert_eq!(num_memory_interactions(air.receives()), num_memory_accesses);
+        assert!(num_memory_accesses < 12 * num_rows as usize);
+
+        let proof = uni_stark_prove(&config, &chip, &mut challenger, trace);
+
+        let mut challenger = config.challenger();
+        uni_stark_verify(&config, &chip, &mut challenger, &proof)
+            .expect("expected proof to be valid");
+    }
+}
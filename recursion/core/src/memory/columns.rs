@@ -46,6 +46,16 @@ pub struct MemoryReadWriteSingleCols<T> {
     pub access: MemoryAccessCols<T, T>,
 }
 
+/// The timestamp columns of a memory read whose value is kept in separate columns, e.g. because it
+/// is shared with other rows and only read from memory on one of them.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct MemoryReadTimestampCols<T> {
+    pub prev_timestamp: T,
+    pub diff_16bit_limb: T,
+    pub diff_12bit_limb: T,
+}
+
 #[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
 #[repr(C)]
 pub struct MemoryAccessCols<T, TValue> {
@@ -66,6 +76,31 @@ pub struct MemoryAccessCols<T, TValue> {
     pub diff_12bit_limb: T,
 }
 
+impl<T: Clone> MemoryReadTimestampCols<T> {
+    /// The read access of `value` with these timestamp columns.
+    pub fn read(&self, value: Block<T>) -> MemoryReadCols<T> {
+        MemoryReadCols {
+            access: self.access(value),
+        }
+    }
+
+    /// The read access of the single field element `value` with these timestamp columns.
+    pub fn read_single(&self, value: T) -> MemoryReadSingleCols<T> {
+        MemoryReadSingleCols {
+            access: self.access(value),
+        }
+    }
+
+    fn access<TValue>(&self, value: TValue) -> MemoryAccessCols<T, TValue> {
+        MemoryAccessCols {
+            value,
+            prev_timestamp: self.prev_timestamp.clone(),
+            diff_16bit_limb: self.diff_16bit_limb.clone(),
+            diff_12bit_limb: self.diff_12bit_limb.clone(),
+        }
+    }
+}
+
 /// The common columns for all memory access types.
 pub trait MemoryCols<T, TValue> {
     fn access(&self) -> &MemoryAccessCols<T, TValue>;
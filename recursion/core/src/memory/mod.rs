@@ -2,12 +2,13 @@ mod air;
 mod columns;
 
 use p3_field::PrimeField32;
+use serde::{Deserialize, Serialize};
 
 use crate::air::Block;
 pub use columns::*;
 
 #[allow(clippy::manual_non_exhaustive)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct MemoryRecord<F> {
     pub addr: F,
     pub value: Block<F>,
@@ -98,6 +99,14 @@ impl<T: PrimeField32> MemoryReadSingleCols<T> {
     }
 }
 
+impl<T: PrimeField32> MemoryReadTimestampCols<T> {
+    pub fn populate(&mut self, record: &MemoryRecord<T>) {
+        self.prev_timestamp = record.prev_timestamp;
+        self.diff_16bit_limb = record.diff_16bit_limb;
+        self.diff_12bit_limb = record.diff_12bit_limb;
+    }
+}
+
 impl<F: PrimeField32, TValue> MemoryAccessCols<F, TValue> {
     /// Populate the memory access columns.
     pub fn populate(&mut self, value: TValue, record: &MemoryRecord<F>) {
@@ -28,6 +28,7 @@ pub struct MultiCols<T: Copy> {
 
     pub is_fri_fold: T,
     pub fri_fold_receive_table: T,
+    pub fri_fold_first_iteration_memory_access: T,
     pub fri_fold_memory_access: T,
 
     pub is_poseidon2: T,
@@ -92,6 +93,8 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for MultiChip<DEGREE> {
                     let fri_fold_cols = *cols.fri_fold();
                     cols.fri_fold_receive_table =
                         FriFoldChip::<3>::do_receive_table(&fri_fold_cols);
+                    cols.fri_fold_first_iteration_memory_access =
+                        FriFoldChip::<3>::do_first_iteration_memory_access(&fri_fold_cols);
                     cols.fri_fold_memory_access =
                         FriFoldChip::<3>::do_memory_access(&fri_fold_cols);
                 } else {
@@ -171,6 +174,11 @@ where
             local.is_fri_fold * FriFoldChip::<3>::do_memory_access::<AB::Var>(fri_columns_local),
             local.fri_fold_memory_access,
         );
+        sub_builder.assert_eq(
+            local.is_fri_fold
+                * FriFoldChip::<3>::do_first_iteration_memory_access::<AB::Var>(fri_columns_local),
+            local.fri_fold_first_iteration_memory_access,
+        );
         sub_builder.assert_eq(
             local.is_fri_fold * FriFoldChip::<3>::do_receive_table::<AB::Var>(fri_columns_local),
             local.fri_fold_receive_table,
@@ -182,6 +190,7 @@ where
             local.fri_fold(),
             next.fri_fold(),
             local.fri_fold_receive_table,
+            local.fri_fold_first_iteration_memory_access,
             local.fri_fold_memory_access,
         );
 
@@ -7,11 +7,12 @@ mod trace;
 use crate::air::Block;
 use crate::memory::MemoryRecord;
 use p3_field::PrimeField32;
+use serde::{Deserialize, Serialize};
 
 pub use columns::Poseidon2Cols;
 pub use external::Poseidon2Chip;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Poseidon2Event<F> {
     pub clk: F,
     pub dst: F,   // from a_val
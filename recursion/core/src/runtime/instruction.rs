@@ -5,7 +5,7 @@ use crate::air::Block;
 
 use super::{Opcode, D};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Instruction<F> {
     /// Which operation to execute.
     pub opcode: Opcode,
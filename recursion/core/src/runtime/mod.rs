@@ -3,8 +3,8 @@ mod opcode;
 mod program;
 mod record;
 mod utils;
+mod witness;
 
-use std::collections::VecDeque;
 use std::process::exit;
 use std::{marker::PhantomData, sync::Arc};
 
@@ -19,6 +19,7 @@ use p3_symmetric::Permutation;
 pub use program::*;
 pub use record::*;
 pub use utils::*;
+pub use witness::*;
 
 use crate::air::{Block, RECURSION_PUBLIC_VALUES_COL_MAP, RECURSIVE_PROOF_NUM_PV_ELTS};
 use crate::cpu::CpuEvent;
@@ -33,6 +34,13 @@ use sp1_core::runtime::MemoryAccessPosition;
 
 /// The heap pointer address.
 pub const HEAP_PTR: i32 = -4;
+
+/// The frame offsets of the scratch slots the compiler hands out to its lowerings.
+pub const SCRATCH_SLOTS: [i32; 7] = [-9, -10, -11, -12, -13, -14, -15];
+
+/// The value written to the scratch slots released by the compiler in debug builds. Reading it back
+/// from a scratch slot panics, as the slot was read after its release.
+pub const SCRATCH_POISON: u32 = 0x5ca7_c4ed;
 pub const HEAP_START_ADDRESS: usize = STACK_SIZE + 4;
 
 pub const STACK_SIZE: usize = 1 << 24;
@@ -116,7 +124,7 @@ pub struct Runtime<F: PrimeField32, EF: ExtensionField<F>, Diffusion> {
     /// The access record for this cycle.
     pub access: CpuRecord<F>,
 
-    pub witness_stream: VecDeque<Vec<Block<F>>>,
+    pub witness_stream: WitnessStream<F>,
 
     pub cycle_tracker: HashMap<String, CycleTrackerEntry>,
 
@@ -177,7 +185,7 @@ where
             record,
             perm: Some(perm),
             access: CpuRecord::default(),
-            witness_stream: VecDeque::new(),
+            witness_stream: WitnessStream::new(),
             cycle_tracker: HashMap::new(),
             _marker: PhantomData,
         }
@@ -207,12 +215,18 @@ where
             record,
             perm: None,
             access: CpuRecord::default(),
-            witness_stream: VecDeque::new(),
+            witness_stream: WitnessStream::new(),
             cycle_tracker: HashMap::new(),
             _marker: PhantomData,
         }
     }
 
+    /// Appends a shared hint source to the witness stream, see [WitnessStream::with_source].
+    pub fn with_witness_source(mut self, source: WitnessSource<F>) -> Self {
+        self.witness_stream.push_source(source);
+        self
+    }
+
     pub fn print_stats(&self) {
         tracing::debug!("Total Cycles: {}", self.timestamp);
         tracing::debug!("Poseidon Operations: {}", self.nb_poseidons);
@@ -327,7 +341,9 @@ where
 
     // When we read the "a" position, it is never an immediate value, so we always read from memory.
     fn get_a(&mut self, instruction: &Instruction<F>) -> Block<F> {
-        self.mr_cpu(self.fp + instruction.op_a, MemoryAccessPosition::A)
+        let value = self.mr_cpu(self.fp + instruction.op_a, MemoryAccessPosition::A);
+        self.check_scratch_read(instruction.op_a, &value);
+        value
     }
 
     // Useful to peek at the value of the "a" position without updating the access record.
@@ -347,7 +363,9 @@ where
         if instruction.imm_b {
             instruction.op_b
         } else {
-            self.mr_cpu(self.fp + instruction.op_b[0], MemoryAccessPosition::B)
+            let value = self.mr_cpu(self.fp + instruction.op_b[0], MemoryAccessPosition::B);
+            self.check_scratch_read(instruction.op_b[0], &value);
+            value
         }
     }
 
@@ -355,7 +373,23 @@ where
         if instruction.imm_c {
             instruction.op_c
         } else {
-            self.mr_cpu(self.fp + instruction.op_c[0], MemoryAccessPosition::C)
+            let value = self.mr_cpu(self.fp + instruction.op_c[0], MemoryAccessPosition::C);
+            self.check_scratch_read(instruction.op_c[0], &value);
+            value
+        }
+    }
+
+    /// Panics in debug builds if a scratch slot at frame offset `offset` is read after the
+    /// compiler released it, which poisoned it with [SCRATCH_POISON].
+    fn check_scratch_read(&self, offset: F, value: &Block<F>) {
+        if !cfg!(debug_assertions) || value.0[0] != F::from_canonical_u32(SCRATCH_POISON) {
+            return;
+        }
+        let slot = SCRATCH_SLOTS
+            .iter()
+            .find(|slot| offset == -F::from_canonical_u32(slot.unsigned_abs()));
+        if let Some(slot) = slot {
+            panic!("read of released scratch slot {} at pc {}", slot, self.pc);
         }
     }
 
@@ -590,6 +624,9 @@ where
                     self.record.public_values[RECURSION_PUBLIC_VALUES_COL_MAP.exit_code] = F::one();
 
                     let trap_pc = self.pc.as_canonical_u32() as usize;
+                    if let Some(source) = self.program.source_map.get(trap_pc) {
+                        panic!("TRAP encountered at pc={}, from {}", trap_pc, source);
+                    }
                     let trace = self.program.traces[trap_pc].clone();
                     if let Some(mut trace) = trace {
                         trace.resolve();
@@ -707,14 +744,14 @@ where
                 Opcode::HintLen => {
                     let (a_ptr, b_val, c_val) = self.alu_rr(&instruction);
                     let a_val: Block<F> =
-                        F::from_canonical_usize(self.witness_stream[0].len()).into();
+                        F::from_canonical_usize(self.witness_stream.peek().unwrap().len()).into();
                     self.mw_cpu(a_ptr, a_val, MemoryAccessPosition::A);
                     (a, b, c) = (a_val, b_val, c_val);
                 }
                 Opcode::Hint => {
                     let (a_val, b_val, c_val) = self.all_rr(&instruction);
                     let dst = a_val[0].as_canonical_u32() as usize;
-                    let blocks = self.witness_stream.pop_front().unwrap();
+                    let blocks = self.witness_stream.next_hint().unwrap().to_vec();
                     for (i, block) in blocks.into_iter().enumerate() {
                         self.mw_uninitialized(dst + i, block);
                     }
@@ -730,33 +767,35 @@ where
 
                     let mut timestamp = self.clk;
 
-                    // Read the input values.
+                    // Read the input values. These are the same for every iteration, so they are
+                    // only read once at the timestamp of the first iteration.
+                    let mut ptr = input_ptr;
+                    let (z_record, z) = self.mr(ptr, timestamp);
+                    let z: EF = z.ext();
+                    ptr += F::one();
+                    let (alpha_record, alpha) = self.mr(ptr, timestamp);
+                    let alpha: EF = alpha.ext();
+                    ptr += F::one();
+                    let (x_record, x) = self.mr(ptr, timestamp);
+                    let x = x[0];
+                    ptr += F::one();
+                    let (log_height_record, log_height) = self.mr(ptr, timestamp);
+                    let log_height = log_height[0];
+                    ptr += F::one();
+                    let (mat_opening_ptr_record, mat_opening_ptr) = self.mr(ptr, timestamp);
+                    let mat_opening_ptr = mat_opening_ptr[0];
+                    ptr += F::two();
+                    let (ps_at_z_ptr_record, ps_at_z_ptr) = self.mr(ptr, timestamp);
+                    let ps_at_z_ptr = ps_at_z_ptr[0];
+                    ptr += F::two();
+                    let (alpha_pow_ptr_record, alpha_pow_ptr) = self.mr(ptr, timestamp);
+                    let alpha_pow_ptr = alpha_pow_ptr[0];
+                    ptr += F::two();
+                    let (ro_ptr_record, ro_ptr) = self.mr(ptr, timestamp);
+                    let ro_ptr = ro_ptr[0];
+
                     for m in 0..ps_at_z_len.as_canonical_u32() {
                         let m = F::from_canonical_u32(m);
-                        let mut ptr = input_ptr;
-                        let (z_record, z) = self.mr(ptr, timestamp);
-                        let z: EF = z.ext();
-                        ptr += F::one();
-                        let (alpha_record, alpha) = self.mr(ptr, timestamp);
-                        let alpha: EF = alpha.ext();
-                        ptr += F::one();
-                        let (x_record, x) = self.mr(ptr, timestamp);
-                        let x = x[0];
-                        ptr += F::one();
-                        let (log_height_record, log_height) = self.mr(ptr, timestamp);
-                        let log_height = log_height[0];
-                        ptr += F::one();
-                        let (mat_opening_ptr_record, mat_opening_ptr) = self.mr(ptr, timestamp);
-                        let mat_opening_ptr = mat_opening_ptr[0];
-                        ptr += F::two();
-                        let (ps_at_z_ptr_record, ps_at_z_ptr) = self.mr(ptr, timestamp);
-                        let ps_at_z_ptr = ps_at_z_ptr[0];
-                        ptr += F::two();
-                        let (alpha_pow_ptr_record, alpha_pow_ptr) = self.mr(ptr, timestamp);
-                        let alpha_pow_ptr = alpha_pow_ptr[0];
-                        ptr += F::two();
-                        let (ro_ptr_record, ro_ptr) = self.mr(ptr, timestamp);
-                        let ro_ptr = ro_ptr[0];
 
                         // Get the opening values.
                         let (p_at_x_record, p_at_x) = self.mr(mat_opening_ptr + m, timestamp);
@@ -952,7 +991,10 @@ mod tests {
         utils::BabyBearPoseidon2,
     };
 
-    use super::{Instruction, Opcode, RecursionProgram, Runtime};
+    use std::sync::Arc;
+
+    use super::{Instruction, Opcode, RecursionProgram, Runtime, WitnessSource};
+    use crate::air::Block;
 
     type SC = BabyBearPoseidon2;
     type F = <SC as StarkGenericConfig>::Val;
@@ -965,6 +1007,8 @@ mod tests {
         let zero_block = [F::zero(); 4];
         let program = RecursionProgram {
             traces: vec![],
+            source_map: SourceMap::default(),
+            verified_fingerprint: None,
             instructions: vec![
                 Instruction::new(
                     Opcode::HintLen,
@@ -996,4 +1040,60 @@ mod tests {
             vec![vec![F::two().into(), F::two().into(), F::two().into()]].into();
         runtime.run();
     }
+
+    #[test]
+    fn test_shared_witness_stream() {
+        let zero = F::zero();
+        let zero_block = [F::zero(); 4];
+        // Each hint is written to the address given by its length.
+        let instructions = [0, 1]
+            .into_iter()
+            .flat_map(|slot| {
+                [Opcode::HintLen, Opcode::Hint].map(|opcode| {
+                    Instruction::new(
+                        opcode,
+                        F::from_canonical_u32(slot),
+                        zero_block,
+                        zero_block,
+                        zero,
+                        zero,
+                        false,
+                        false,
+                        "".to_string(),
+                    )
+                })
+            })
+            .collect();
+        let program = RecursionProgram {
+            traces: vec![],
+            source_map: SourceMap::default(),
+            verified_fingerprint: None,
+            instructions,
+        };
+        let hint = |values: &[u32]| -> Vec<Block<F>> {
+            values
+                .iter()
+                .map(|v| F::from_canonical_u32(*v).into())
+                .collect()
+        };
+
+        // Two reduce nodes share the first hint and each read their own second hint.
+        let shared: WitnessSource<F> = vec![hint(&[1, 2])].into();
+        let node_hints = [hint(&[3, 4, 5, 6, 7]), hint(&[8, 9, 10, 11, 12])];
+        let perm = A::machine(SC::default()).config().perm.clone();
+        for node_hint in node_hints {
+            let mut runtime = Runtime::<F, EF, _>::new(&program, perm.clone())
+                .with_witness_source(shared.clone())
+                .with_witness_source(vec![node_hint.clone()].into());
+            assert_eq!(Arc::strong_count(&shared), 2);
+            runtime.run();
+
+            let mut expected = Runtime::<F, EF, _>::new(&program, perm.clone());
+            expected.witness_stream = vec![hint(&[1, 2]), node_hint].into();
+            expected.run();
+            assert_eq!(runtime.uninitialized_memory, expected.uninitialized_memory);
+            assert_eq!(runtime.uninitialized_memory.len(), 7);
+        }
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
 }
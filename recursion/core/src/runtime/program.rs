@@ -1,14 +1,25 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::panic::Location;
+
 use super::Instruction;
 use backtrace::Backtrace;
 use p3_field::Field;
 use serde::{Deserialize, Serialize};
 use sp1_core::air::MachineProgram;
+use sp1_core::stark::MachineFingerprint;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RecursionProgram<F> {
     pub instructions: Vec<Instruction<F>>,
     #[serde(skip)]
     pub traces: Vec<Option<Backtrace>>,
+    /// The builder calls the instructions were generated by, if the builder tracked them. Like the
+    /// traces, it is a debugging aid that is not part of the serialized program.
+    #[serde(skip)]
+    pub source_map: SourceMap,
+    /// The fingerprint of the machine whose proofs the program verifies, if it verifies proofs.
+    pub verified_fingerprint: Option<MachineFingerprint>,
 }
 
 impl<F: Field> MachineProgram<F> for RecursionProgram<F> {
@@ -16,3 +27,42 @@ impl<F: Field> MachineProgram<F> for RecursionProgram<F> {
         F::zero()
     }
 }
+
+/// The builder call an operation was pushed by, and the innermost cycle tracker region it was
+/// pushed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub location: &'static Location<'static>,
+    pub region: Option<String>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.location)?;
+        if let Some(region) = &self.region {
+            write!(f, " in region `{}`", region)?;
+        }
+        Ok(())
+    }
+}
+
+/// The source locations of the instructions of a program, or of the constraints of a circuit,
+/// keyed by the index of the first instruction or constraint generated from each operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap(BTreeMap<usize, SourceLocation>);
+
+impl SourceMap {
+    pub fn insert(&mut self, index: usize, source: SourceLocation) {
+        self.0.insert(index, source);
+    }
+
+    /// The location of the operation that generated the instruction or constraint at `index`,
+    /// which is the nearest location recorded at or before it.
+    pub fn get(&self, index: usize) -> Option<&SourceLocation> {
+        self.0.range(..=index).next_back().map(|(_, source)| source)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
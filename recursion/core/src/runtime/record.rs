@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use p3_field::{AbstractField, PrimeField32};
+use serde::{Deserialize, Serialize};
 use sp1_core::stark::{MachineRecord, PROOF_MAX_NUM_PVS};
 use std::collections::HashMap;
 
@@ -14,8 +15,10 @@ use crate::fri_fold::FriFoldEvent;
 use crate::poseidon2::Poseidon2Event;
 use crate::range_check::RangeCheckEvent;
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionRecord<F: Default> {
+    /// The program is not serialized, it is set again by the caller when the record is loaded.
+    #[serde(skip)]
     pub program: Arc<RecursionProgram<F>>,
     pub cpu_events: Vec<CpuEvent<F>>,
     pub poseidon2_events: Vec<Poseidon2Event<F>>,
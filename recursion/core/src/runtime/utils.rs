@@ -4,9 +4,16 @@ use crate::range_check::{RangeCheckEvent, RangeCheckOpcode};
 
 use super::{Instruction, Opcode, HEAP_PTR, HEAP_START_ADDRESS};
 
+/// Converts `x` to a field element, reducing negative values modulo the order of the field.
+///
+/// `x` must be in the open interval `(-p, p)`, where `p` is the order of the field.
 pub fn canonical_i32_to_field<F: PrimeField32>(x: i32) -> F {
     let modulus = F::ORDER_U32;
-    assert!(x < modulus as i32 && x >= -(modulus as i32));
+    assert!(
+        x < modulus as i32 && x > -(modulus as i32),
+        "{} is out of the range of canonical field offsets",
+        x
+    );
     if x < 0 {
         -F::from_canonical_u32((-x) as u32)
     } else {
@@ -14,6 +21,18 @@ pub fn canonical_i32_to_field<F: PrimeField32>(x: i32) -> F {
     }
 }
 
+/// The inverse of [canonical_i32_to_field], mapping `x` to its representative in the symmetric
+/// interval `(-p / 2, p / 2]`.
+pub fn field_to_canonical_i32<F: PrimeField32>(x: F) -> i32 {
+    let modulus = F::ORDER_U32;
+    let x = x.as_canonical_u32();
+    if x > modulus / 2 {
+        -((modulus - x) as i32)
+    } else {
+        x as i32
+    }
+}
+
 pub fn get_heap_size_range_check_events<F: PrimeField32>(
     end_heap_address: F,
 ) -> (RangeCheckEvent, RangeCheckEvent) {
@@ -31,3 +50,35 @@ pub fn get_heap_size_range_check_events<F: PrimeField32>(
 pub fn instruction_is_heap_expand<F: PrimeField32>(instruction: &Instruction<F>) -> bool {
     instruction.opcode == Opcode::ADD && instruction.op_a == canonical_i32_to_field(HEAP_PTR)
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32};
+
+    use super::*;
+
+    #[test]
+    fn test_canonical_i32_round_trip() {
+        let p = BabyBear::ORDER_U32 as i32;
+        for x in [0, 1, -1, p / 2, -(p / 2), p - 1, -(p - 1)] {
+            let f = canonical_i32_to_field::<BabyBear>(x);
+            assert_eq!(
+                canonical_i32_to_field::<BabyBear>(field_to_canonical_i32(f)),
+                f
+            );
+        }
+        assert_eq!(field_to_canonical_i32(BabyBear::neg_one()), -1);
+        assert_eq!(
+            field_to_canonical_i32(canonical_i32_to_field::<BabyBear>(p - 1)),
+            -1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the range of canonical field offsets")]
+    fn test_canonical_i32_rejects_negative_modulus() {
+        // -p used to pass the range check and produce the non-canonical value p.
+        canonical_i32_to_field::<BabyBear>(-(BabyBear::ORDER_U32 as i32));
+    }
+}
@@ -0,0 +1,146 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use crate::air::Block;
+
+/// A shared, immutable sequence of hints.
+pub type WitnessSource<F> = Arc<[Vec<Block<F>>]>;
+
+/// The hints consumed by the `HintLen` and `Hint` instructions.
+///
+/// The stream is a read cursor over one or more shared [WitnessSource]s, which are read back to
+/// back in the order they were added. Cloning a stream or building several streams from the same
+/// source only copies the `Arc`s, so runtimes executing reduce nodes over the same witness share
+/// its data instead of each holding a copy.
+///
+/// Each hinted element is a [Block] of four field elements (16 bytes for BabyBear), plus a
+/// `Vec` header per hint. With a shared source, `k` runtimes over a witness of `n` elements hold
+/// `16 * n` bytes in total instead of `16 * n * k`, and each stream only adds a few words for its
+/// cursor and one `Arc` per source.
+#[derive(Debug, Clone)]
+pub struct WitnessStream<F> {
+    sources: Vec<WitnessSource<F>>,
+    /// The index of the source holding the next hint.
+    source: usize,
+    /// The index of the next hint within the current source.
+    position: usize,
+}
+
+impl<F> Default for WitnessStream<F> {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            source: 0,
+            position: 0,
+        }
+    }
+}
+
+impl<F> WitnessStream<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `source` to the stream, to be read after all previously added sources.
+    pub fn with_source(mut self, source: WitnessSource<F>) -> Self {
+        self.push_source(source);
+        self
+    }
+
+    /// Appends `source` to the stream, to be read after all previously added sources.
+    pub fn push_source(&mut self, source: WitnessSource<F>) {
+        self.sources.push(source);
+        self.skip_exhausted();
+    }
+
+    /// Returns the next hint without consuming it.
+    pub fn peek(&self) -> Option<&[Block<F>]> {
+        self.sources
+            .get(self.source)
+            .map(|source| source[self.position].as_slice())
+    }
+
+    /// Consumes and returns the next hint.
+    pub fn next_hint(&mut self) -> Option<&[Block<F>]> {
+        let (source, position) = (self.source, self.position);
+        if source == self.sources.len() {
+            return None;
+        }
+        self.position += 1;
+        self.skip_exhausted();
+        Some(self.sources[source][position].as_slice())
+    }
+
+    /// The number of hints left to read.
+    pub fn len(&self) -> usize {
+        self.sources[self.source.min(self.sources.len())..]
+            .iter()
+            .map(|source| source.len())
+            .sum::<usize>()
+            - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves the cursor past exhausted sources so that it always points at a hint, or past the
+    /// last source.
+    fn skip_exhausted(&mut self) {
+        while self.source < self.sources.len() && self.position == self.sources[self.source].len() {
+            self.source += 1;
+            self.position = 0;
+        }
+    }
+}
+
+impl<F> From<Vec<Vec<Block<F>>>> for WitnessStream<F> {
+    fn from(hints: Vec<Vec<Block<F>>>) -> Self {
+        Self::new().with_source(hints.into())
+    }
+}
+
+impl<F> From<VecDeque<Vec<Block<F>>>> for WitnessStream<F> {
+    fn from(hints: VecDeque<Vec<Block<F>>>) -> Self {
+        Vec::from(hints).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+
+    use super::*;
+
+    fn hint(values: &[u32]) -> Vec<Block<BabyBear>> {
+        values
+            .iter()
+            .map(|v| BabyBear::from_canonical_u32(*v).into())
+            .collect()
+    }
+
+    #[test]
+    fn test_composed_sources_read_in_order() {
+        let first: WitnessSource<BabyBear> = vec![hint(&[1, 2]), hint(&[])].into();
+        let empty: WitnessSource<BabyBear> = Vec::new().into();
+        let second: WitnessSource<BabyBear> = vec![hint(&[3])].into();
+        let mut stream = WitnessStream::new()
+            .with_source(first.clone())
+            .with_source(empty)
+            .with_source(second.clone());
+
+        let mut expected: WitnessStream<BabyBear> =
+            vec![hint(&[1, 2]), hint(&[]), hint(&[3])].into();
+        assert_eq!(stream.len(), 3);
+        while let Some(next) = expected.next_hint() {
+            assert_eq!(stream.peek(), Some(next));
+            assert_eq!(stream.next_hint(), Some(next));
+        }
+        assert!(stream.is_empty());
+        assert_eq!(stream.next_hint(), None);
+
+        // The stream only holds references to the sources.
+        assert_eq!(Arc::strong_count(&first), 2);
+        assert_eq!(Arc::strong_count(&second), 2);
+    }
+}
@@ -16,6 +16,7 @@ use p3_symmetric::{MultiField32PaddingFreeSponge, TruncatedPermutation};
 use serde::Deserialize;
 use serde::Serialize;
 use sp1_core::stark::StarkGenericConfig;
+use sp1_core::utils::FirstWitnessChallenger;
 
 use super::poseidon2::bn254_poseidon2_rc3;
 use super::utils;
@@ -137,14 +138,14 @@ impl StarkGenericConfig for BabyBearPoseidon2Outer {
     type Domain = <OuterPcs as p3_commit::Pcs<OuterChallenge, OuterChallenger>>::Domain;
     type Pcs = OuterPcs;
     type Challenge = OuterChallenge;
-    type Challenger = OuterChallenger;
+    type Challenger = FirstWitnessChallenger<OuterChallenger>;
 
     fn pcs(&self) -> &Self::Pcs {
         &self.pcs
     }
 
     fn challenger(&self) -> Self::Challenger {
-        OuterChallenger::new(self.perm.clone()).unwrap()
+        FirstWitnessChallenger(OuterChallenger::new(self.perm.clone()).unwrap())
     }
 }
 
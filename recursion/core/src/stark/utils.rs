@@ -35,7 +35,7 @@ pub fn run_test_recursion(
 
     let mut runtime = Runtime::<Val, Challenge, _>::new(&program, config.perm.clone());
     if witness.is_some() {
-        runtime.witness_stream = witness.unwrap();
+        runtime.witness_stream = witness.unwrap().into();
     }
     runtime.run();
     println!(
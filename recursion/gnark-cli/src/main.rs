@@ -29,6 +29,12 @@ enum Command {
 #[derive(Debug, Args)]
 struct BuildArgs {
     data_dir: String,
+    /// Build with a local, insecure, setup.
+    #[arg(long)]
+    dev: bool,
+    /// The hex encoded seed of the development setup.
+    #[arg(long)]
+    dev_seed: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -44,6 +50,7 @@ struct VerifyArgs {
     proof_path: String,
     vkey_hash: String,
     committed_values_digest: String,
+    input_digest: String,
     output_path: String,
 }
 
@@ -54,7 +61,7 @@ struct TestArgs {
 }
 
 fn run_build(args: BuildArgs) {
-    build_plonk_bn254(&args.data_dir);
+    build_plonk_bn254(&args.data_dir, args.dev, args.dev_seed.as_deref());
 }
 
 fn run_prove(args: ProveArgs) {
@@ -72,6 +79,7 @@ fn run_verify(args: VerifyArgs) {
         proof.trim(),
         &args.vkey_hash,
         &args.committed_values_digest,
+        &args.input_digest,
     );
     let output = match result {
         Ok(_) => "OK".to_string(),
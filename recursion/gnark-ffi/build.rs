@@ -61,10 +61,20 @@ fn main() {
             println!("cargo:rustc-link-search=native={}", dest_path.display());
             println!("cargo:rustc-link-lib=static={}", lib_name);
 
-            // Static linking doesn't really work on macos, so we need to link some system libs
-            if cfg!(target_os = "macos") {
-                println!("cargo:rustc-link-lib=framework=CoreFoundation");
-                println!("cargo:rustc-link-lib=framework=Security");
+            // The Go runtime needs some system libraries that aren't part of the archive. Build
+            // scripts run on the host, so the target is read from the environment.
+            match env::var("CARGO_CFG_TARGET_OS").unwrap().as_str() {
+                // Static linking doesn't really work on macos, so we need to link some system libs
+                "macos" => {
+                    println!("cargo:rustc-link-lib=framework=CoreFoundation");
+                    println!("cargo:rustc-link-lib=framework=Security");
+                }
+                "windows" => {
+                    for lib in ["ws2_32", "winmm", "ntdll", "userenv", "bcrypt"] {
+                        println!("cargo:rustc-link-lib=dylib={}", lib);
+                    }
+                }
+                _ => {}
             }
         }
     }
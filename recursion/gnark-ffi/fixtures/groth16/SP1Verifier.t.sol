@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+import {SP1Verifier} from "../src/SP1Verifier.sol";
+
+/// @title SP1 Verifier Test
+/// @author Succinct Labs
+/// @notice Checks the generated verifier against a proof of its verifying key. Run with
+/// `forge test`.
+contract SP1VerifierTest {
+    bytes internal constant PROOF = hex"285a893a88e5ceb2b7685b4f5be7d7441f0d36e6257b28ae39ee0e21878e70a00910b3e8115baa66a5271b1ca793d5b5f37a4886f3149a70814748e89d6f2fa52597aad7fb2e95709316a75cc93d24ab20ae53119d65f4f595b6fa3983e8fc321efa1f92450ea52c8798d099f283f24efd5497342f7182adde42c82f7677d0ff28e62ff8d5474cb174bfcfc1c07d56c36a30ebccf992170ad5b1da35d10da490086ef23d9d5dea01cd3bb7adc5765cbcda7b5d9ec252405036625ab7d6ba358624b5e79472ae4ba66a9759f8ef0b8b93fad85631c3887914d9d4b768afcb90ca00e483b63a49cf1697e4fe117006732dfa5d0cf5651082525e2aa67e1094df84";
+    bytes32 internal constant VKEY_DIGEST = 0x1a80d3bae62af06baac8444df6029c383a89b6400360c3eba7d30fb7ee325c3a;
+    bytes internal constant PUBLIC_VALUES = hex"14000000551000006d1a0000";
+    bytes32 internal constant INPUT_DIGEST = 0x2f6d8fe3ac68f51bcb99099f2d3008dcf2e5f286d2edfee7c54b057cdf5bb71f;
+
+    SP1Verifier internal verifier;
+
+    function setUp() public {
+        verifier = new SP1Verifier();
+    }
+
+    function test_VerifyProof() public view {
+        verifier.verifyProof(PROOF, VKEY_DIGEST, PUBLIC_VALUES, INPUT_DIGEST);
+    }
+
+    function test_RevertWhen_PublicValuesChanged() public view {
+        bytes memory publicValues = abi.encodePacked(PUBLIC_VALUES, bytes1(0x01));
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProof.selector,
+                PROOF,
+                VKEY_DIGEST,
+                publicValues,
+                INPUT_DIGEST
+            ),
+            SP1Verifier.ProofInvalid.selector
+        );
+    }
+
+    function test_RevertWhen_InputDigestChanged() public view {
+        bytes32 inputDigest = bytes32(uint256(INPUT_DIGEST) ^ 1);
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProof.selector,
+                PROOF,
+                VKEY_DIGEST,
+                PUBLIC_VALUES,
+                inputDigest
+            ),
+            SP1Verifier.ProofInvalid.selector
+        );
+    }
+
+    function test_RevertWhen_VkeyDigestChanged() public view {
+        bytes32 vkeyDigest = bytes32(uint256(VKEY_DIGEST) ^ 1);
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProof.selector,
+                PROOF,
+                vkeyDigest,
+                PUBLIC_VALUES,
+                INPUT_DIGEST
+            ),
+            SP1Verifier.ProofInvalid.selector
+        );
+    }
+
+    function test_RevertWhen_VkeyDigestNotInField() public view {
+        bytes32 vkeyDigest = bytes32(type(uint256).max);
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProof.selector,
+                PROOF,
+                vkeyDigest,
+                PUBLIC_VALUES,
+                INPUT_DIGEST
+            ),
+            SP1Verifier.InvalidVkeyDigest.selector
+        );
+    }
+
+    function test_RevertWhen_ProofTruncated() public view {
+        bytes memory proof = new bytes(PROOF.length - 32);
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProof.selector,
+                proof,
+                VKEY_DIGEST,
+                PUBLIC_VALUES,
+                INPUT_DIGEST
+            ),
+            SP1Verifier.InvalidProofLength.selector
+        );
+    }
+
+    function test_VerifyProofInRoot() public view {
+        verifier.verifyProofInRoot(PROOF, VKEY_DIGEST, PUBLIC_VALUES, INPUT_DIGEST, vkeyProof());
+    }
+
+    function test_RevertWhen_VkeyDigestNotInRoot() public view {
+        bytes32 vkeyDigest = bytes32(uint256(VKEY_DIGEST) ^ 1);
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProofInRoot.selector,
+                PROOF,
+                vkeyDigest,
+                PUBLIC_VALUES,
+                INPUT_DIGEST,
+                vkeyProof()
+            ),
+            SP1Verifier.VkeyDigestMismatch.selector
+        );
+    }
+
+    function vkeyProof() internal pure returns (bytes32[] memory siblings) {
+        siblings = new bytes32[](1);
+        siblings[0] = 0x0b26e9b37be593f9f8448b414a75a19f64ee92f96f8153e630fa8eeec89c7ac1;
+    }
+
+    /// @dev Checks that calling the verifier with `data` reverts with the error `selector`.
+    function expectRevert(bytes memory data, bytes4 selector) internal view {
+        (bool success, bytes memory reason) = address(verifier).staticcall(data);
+        require(!success, "the call did not revert");
+        require(
+            reason.length == 4 && bytes4(reason) == selector,
+            "the call reverted with another error"
+        );
+    }
+}
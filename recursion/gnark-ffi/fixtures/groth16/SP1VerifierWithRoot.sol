@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+/// @title SP1 Verifier
+/// @author Succinct Labs
+/// @notice This contract verifies the Groth16 BN254 proofs of SP1. It is generated for one
+/// verifying key of the wrapper circuit, whose public inputs are the verifying key digest of the
+/// program, the digest of its public values and the digest of its input, in that order.
+contract SP1Verifier {
+    /// @notice The proof is not the 256 bytes of its eight words.
+    error InvalidProofLength();
+    /// @notice The verifying key digest is not an element of the scalar field.
+    error InvalidVkeyDigest();
+    /// @notice The verifying key digest is not in the tree of `VKEY_ROOT`.
+    error VkeyDigestMismatch();
+    /// @notice The pairing check failed, or the proof is not made of curve points.
+    error ProofInvalid();
+
+    /// @dev The order of the scalar field of BN254.
+    uint256 internal constant R =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    /// @dev The order of the base field of BN254.
+    uint256 internal constant P =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    // The verifying key, with the G2 points in the order of the pairing precompile.
+    uint256 internal constant ALPHA_X = 0x127fe3089ef73e3199d089c7e89efe29b18c0f9a2559d74814d246b86cbdd262;
+    uint256 internal constant ALPHA_Y = 0x10f36e810e907b9c660a2adff14895f130c47fb7a2907eaebdb7cee1f2363905;
+    uint256 internal constant BETA_X_C1 = 0x088fff12add07ffdc08f767652994c159e75beaf21548078fa6221391770e760;
+    uint256 internal constant BETA_X_C0 = 0x2d335f25572eb14b83cd2b25e07d6dcdd14d8d4f32245807e8f4fa91af9f9f89;
+    uint256 internal constant BETA_Y_C1 = 0x04216951c0119e69f248c9db584f1c8ccac18f011daba9291af5166b0e6ee476;
+    uint256 internal constant BETA_Y_C0 = 0x18c4666e7ecdf1437c5e98c12a595516a93061649c43f8ec5352e52d689e392e;
+    uint256 internal constant GAMMA_X_C1 = 0x29a55c038b227e1ebafe148fe5f7c619e664d5e9fb119d588c44424e5c8157f4;
+    uint256 internal constant GAMMA_X_C0 = 0x2aeef259e6668c75ef054dee67d87052fcbe4ad7f4f64469f135283c02a3cf2a;
+    uint256 internal constant GAMMA_Y_C1 = 0x01ee25baf24c95f427a149207061dfe4b5047737b10abe29a64dd970ce4425e8;
+    uint256 internal constant GAMMA_Y_C0 = 0x280041422628a4803443b6b98bf607ad77142f7d893890da3dc3589ce98c0915;
+    uint256 internal constant DELTA_X_C1 = 0x25bc773799151e3cbdca469fadffeb0fc869754c0493c555f98e34ec7ba44287;
+    uint256 internal constant DELTA_X_C0 = 0x1216aaccbd737e836179f760206f16b2df5344f2c2c4be1c30db8b9e9b99a5ff;
+    uint256 internal constant DELTA_Y_C1 = 0x25ad989d3e89d178ce3e51c26d0664aca85f89ded35d6a9620a9ff5e7c1537a8;
+    uint256 internal constant DELTA_Y_C0 = 0x29ffa4481c2a43ac3c83a65859b6d1e383fff80b21b469bf45fcf0de559b87d6;
+    uint256 internal constant IC0_X = 0x176e7f69ddd21916cc4beb8a1f8b16de95aa888c1eb80c9f8074349ef0e78f6c;
+    uint256 internal constant IC0_Y = 0x1b74ba94dd25a974f162f4b350ea68ea1b8c08cf0cc11843f06390d572c8749e;
+    uint256 internal constant IC1_X = 0x15fea6a66197878c26c63929225643a598126f7691337bf429053c773b0be3d9;
+    uint256 internal constant IC1_Y = 0x302f731d050f0960b2cb316505823d6820cccd69e43889e7b427e722672f0b60;
+    uint256 internal constant IC2_X = 0x2349cc697d504c35d2a49403f738c9af2c571d964d8111754181447a407a54c3;
+    uint256 internal constant IC2_Y = 0x03d10efd713f04b6db40a88e1cf886511164edc804b9953a0aa1ab8a93d77a42;
+    uint256 internal constant IC3_X = 0x28eaa72b89de9306a34be4110dd7aa2a2cedfc36f89bfc5649352a346fcebd95;
+    uint256 internal constant IC3_Y = 0x07f3e364d018700e582795d0a4f2fab713b5bca81e78c1f5e17f5dfbfcc7fab7;
+
+    function VERSION() external pure returns (string memory) {
+        return "v1.0.9-testnet";
+    }
+
+    /// @notice The root of the Merkle tree over the verifying key digests of the programs accepted
+    /// by `verifyProofInRoot`, whose nodes are the SHA-256 of their two children in increasing
+    /// order.
+    function VKEY_ROOT() public pure returns (bytes32) {
+        return 0xd82610302ff93a41041e819e28dd181c6da0b9fa9438bac61e570e1f124c758f;
+    }
+
+    /// @notice Hashes the public values to a field element of BN254.
+    /// @param publicValues The public values.
+    function hashPublicValues(
+        bytes calldata publicValues
+    ) public pure returns (bytes32) {
+        return sha256(publicValues) & bytes32(uint256((1 << 253) - 1));
+    }
+
+    /// @notice The public inputs of the circuit for a program, its public values and its input.
+    /// @param vkeyDigest The verifying key digest of the program.
+    /// @param publicValues The public values.
+    /// @param inputDigest The SHA-256 digest of the input buffers committed by the program.
+    function publicInputs(
+        bytes32 vkeyDigest,
+        bytes calldata publicValues,
+        bytes32 inputDigest
+    ) public pure returns (uint256[3] memory inputs) {
+        if (uint256(vkeyDigest) >= R) {
+            revert InvalidVkeyDigest();
+        }
+        inputs[0] = uint256(vkeyDigest);
+        inputs[1] = uint256(hashPublicValues(publicValues));
+        inputs[2] = uint256(inputDigest & bytes32(uint256((1 << 253) - 1)));
+    }
+
+    /// @notice Verifies a proof of the program of verifying key digest `vkeyDigest` with the
+    /// given public values and input.
+    /// @param proof The proof, the ABI encoding of its `uint256[8]`.
+    /// @param vkeyDigest The verifying key digest of the program.
+    /// @param publicValues The public values.
+    /// @param inputDigest The SHA-256 digest of the input buffers committed by the program, zero
+    /// if it does not commit to its input.
+    function verifyProof(
+        bytes calldata proof,
+        bytes32 vkeyDigest,
+        bytes calldata publicValues,
+        bytes32 inputDigest
+    ) public view {
+        verifyPairing(proof, publicInputs(vkeyDigest, publicValues, inputDigest));
+    }
+
+    /// @notice Verifies a proof like `verifyProof`, for a program whose verifying key digest is in
+    /// the tree of `VKEY_ROOT`.
+    /// @param vkeyProof The siblings of `vkeyDigest` in the tree, from the leaf to the root.
+    function verifyProofInRoot(
+        bytes calldata proof,
+        bytes32 vkeyDigest,
+        bytes calldata publicValues,
+        bytes32 inputDigest,
+        bytes32[] calldata vkeyProof
+    ) external view {
+        bytes32 node = vkeyDigest;
+        for (uint256 i = 0; i < vkeyProof.length; i++) {
+            bytes32 sibling = vkeyProof[i];
+            node = node <= sibling
+                ? sha256(abi.encodePacked(node, sibling))
+                : sha256(abi.encodePacked(sibling, node));
+        }
+        if (node != VKEY_ROOT()) {
+            revert VkeyDigestMismatch();
+        }
+        verifyProof(proof, vkeyDigest, publicValues, inputDigest);
+    }
+
+    /// @dev Checks `e(-a, b) * e(alpha, beta) * e(vk_x, gamma) * e(c, delta) = 1`, where `vk_x`
+    /// is `ic_0 + inputs_0 * ic_1 + inputs_1 * ic_2 + inputs_2 * ic_3`.
+    function verifyPairing(
+        bytes calldata proof,
+        uint256[3] memory inputs
+    ) internal view {
+        if (proof.length != 256) {
+            revert InvalidProofLength();
+        }
+        uint256[8] memory p = abi.decode(proof, (uint256[8]));
+
+        (uint256 x, uint256 y) = mulAdd(IC0_X, IC0_Y, IC1_X, IC1_Y, inputs[0]);
+        (x, y) = mulAdd(x, y, IC2_X, IC2_Y, inputs[1]);
+        (x, y) = mulAdd(x, y, IC3_X, IC3_Y, inputs[2]);
+
+        uint256[24] memory pairing;
+        pairing[0] = p[0];
+        pairing[1] = (P - (p[1] % P)) % P;
+        pairing[2] = p[2];
+        pairing[3] = p[3];
+        pairing[4] = p[4];
+        pairing[5] = p[5];
+        pairing[6] = ALPHA_X;
+        pairing[7] = ALPHA_Y;
+        pairing[8] = BETA_X_C1;
+        pairing[9] = BETA_X_C0;
+        pairing[10] = BETA_Y_C1;
+        pairing[11] = BETA_Y_C0;
+        pairing[12] = x;
+        pairing[13] = y;
+        pairing[14] = GAMMA_X_C1;
+        pairing[15] = GAMMA_X_C0;
+        pairing[16] = GAMMA_Y_C1;
+        pairing[17] = GAMMA_Y_C0;
+        pairing[18] = p[6];
+        pairing[19] = p[7];
+        pairing[20] = DELTA_X_C1;
+        pairing[21] = DELTA_X_C0;
+        pairing[22] = DELTA_Y_C1;
+        pairing[23] = DELTA_Y_C0;
+        (bool success, bytes memory result) = address(0x08).staticcall(
+            abi.encode(pairing)
+        );
+        if (!success || result.length != 32 || abi.decode(result, (uint256)) != 1) {
+            revert ProofInvalid();
+        }
+    }
+
+    /// @dev Computes `(ax, ay) + s * (bx, by)` with the addition and multiplication precompiles.
+    function mulAdd(
+        uint256 ax,
+        uint256 ay,
+        uint256 bx,
+        uint256 by,
+        uint256 s
+    ) internal view returns (uint256, uint256) {
+        (bool success, bytes memory result) = address(0x07).staticcall(
+            abi.encode(bx, by, s)
+        );
+        if (!success || result.length != 64) {
+            revert ProofInvalid();
+        }
+        (uint256 mx, uint256 my) = abi.decode(result, (uint256, uint256));
+        (success, result) = address(0x06).staticcall(abi.encode(ax, ay, mx, my));
+        if (!success || result.length != 64) {
+            revert ProofInvalid();
+        }
+        return abi.decode(result, (uint256, uint256));
+    }
+}
@@ -4,13 +4,14 @@ package main
 #include "./babybear.h"
 
 typedef struct {
-	char *PublicInputs[2];
+	char *PublicInputs[3];
 	char *EncodedProof;
 	char *RawProof;
 } C_PlonkBn254Proof;
 */
 import "C"
 import (
+	"encoding/hex"
 	"encoding/json"
 	"fmt"
 	"os"
@@ -45,28 +46,57 @@ func ProvePlonkBn254(dataDir *C.char, witnessPath *C.char) *C.C_PlonkBn254Proof
 	structPtr := (*C.C_PlonkBn254Proof)(ms)
 	structPtr.PublicInputs[0] = C.CString(sp1PlonkBn254Proof.PublicInputs[0])
 	structPtr.PublicInputs[1] = C.CString(sp1PlonkBn254Proof.PublicInputs[1])
+	structPtr.PublicInputs[2] = C.CString(sp1PlonkBn254Proof.PublicInputs[2])
 	structPtr.EncodedProof = C.CString(sp1PlonkBn254Proof.EncodedProof)
 	structPtr.RawProof = C.CString(sp1PlonkBn254Proof.RawProof)
 	return structPtr
 }
 
 //export BuildPlonkBn254
-func BuildPlonkBn254(dataDir *C.char) {
+func BuildPlonkBn254(dataDir *C.char, dev C.int, devSeed *C.char) {
 	// Sanity check the required arguments have been provided.
 	dataDirString := C.GoString(dataDir)
+	devSeedBytes, err := hex.DecodeString(C.GoString(devSeed))
+	if err != nil {
+		panic(err)
+	}
 
-	sp1.Build(dataDirString)
+	sp1.Build(dataDirString, dev != 0, devSeedBytes)
 }
 
 //export VerifyPlonkBn254
-func VerifyPlonkBn254(dataDir *C.char, proof *C.char, vkeyHash *C.char, commitedValuesDigest *C.char) *C.char {
+func VerifyPlonkBn254(dataDir *C.char, proof *C.char, vkeyHash *C.char, commitedValuesDigest *C.char, inputDigest *C.char) *C.char {
 	dataDirString := C.GoString(dataDir)
 	proofString := C.GoString(proof)
 	vkeyHashString := C.GoString(vkeyHash)
 	commitedValuesDigestString := C.GoString(commitedValuesDigest)
+	inputDigestString := C.GoString(inputDigest)
+
+	err := sp1.Verify(dataDirString, proofString, vkeyHashString, commitedValuesDigestString, inputDigestString)
+	if err != nil {
+		return C.CString(err.Error())
+	}
+	return nil
+}
+
+//export VerifyPlonkBn254Batch
+func VerifyPlonkBn254Batch(dataDir *C.char, proofsPath *C.char, failedIndex *C.int) *C.char {
+	dataDirString := C.GoString(dataDir)
+	proofsPathString := C.GoString(proofsPath)
+	*failedIndex = -1
+
+	data, err := os.ReadFile(proofsPathString)
+	if err != nil {
+		return C.CString(err.Error())
+	}
+	var proofs []sp1.BatchProof
+	if err := json.Unmarshal(data, &proofs); err != nil {
+		return C.CString(err.Error())
+	}
 
-	err := sp1.Verify(dataDirString, proofString, vkeyHashString, commitedValuesDigestString)
+	index, err := sp1.VerifyBatch(dataDirString, proofs)
 	if err != nil {
+		*failedIndex = C.int(index)
 		return C.CString(err.Error())
 	}
 	return nil
@@ -5,7 +5,7 @@ import (
 	"fmt"
 	"log"
 	"os"
-	"strings"
+	"path/filepath"
 
 	"github.com/consensys/gnark-crypto/ecc"
 	"github.com/consensys/gnark-crypto/kzg"
@@ -16,15 +16,24 @@ import (
 	"github.com/succinctlabs/sp1-recursion-gnark/sp1/trusted_setup"
 )
 
-func Build(dataDir string) {
+// Build compiles the circuit and generates its proving and verifying keys in dataDir.
+//
+// In development mode, the SRS is generated locally instead of coming from the Aztec Ignition
+// ceremony, with a toxic waste derived from devSeed if it is not empty, so that the keys are the
+// same across builds. Anyone knowing the seed can forge proofs, so such keys are INSECURE.
+func Build(dataDir string, dev bool, devSeed []byte) {
+	if len(devSeed) > 0 && !dev {
+		panic("refusing to build with a dev seed outside of development mode")
+	}
+
 	// Set the enviroment variable for the constraints file.
 	//
 	// TODO: There might be some non-determinism if a single process is running this command
 	// multiple times.
-	os.Setenv("CONSTRAINTS_JSON", dataDir+"/"+CONSTRAINTS_JSON_FILE)
+	os.Setenv("CONSTRAINTS_JSON", filepath.Join(dataDir, CONSTRAINTS_JSON_FILE))
 
 	// Read the file.
-	witnessInputPath := dataDir + "/witness.json"
+	witnessInputPath := filepath.Join(dataDir, "witness.json")
 	data, err := os.ReadFile(witnessInputPath)
 	if err != nil {
 		panic(err)
@@ -49,8 +58,8 @@ func Build(dataDir string) {
 	// Download the trusted setup.
 	var srs kzg.SRS = kzg.NewSRS(ecc.BN254)
 	var srsLagrange kzg.SRS = kzg.NewSRS(ecc.BN254)
-	srsFileName := dataDir + "/" + SRS_FILE
-	srsLagrangeFileName := dataDir + "/" + SRS_LAGRANGE_FILE
+	srsFileName := filepath.Join(dataDir, SRS_FILE)
+	srsLagrangeFileName := filepath.Join(dataDir, SRS_LAGRANGE_FILE)
 
 	srsLagrangeFile, err := os.Create(srsLagrangeFileName)
 	if err != nil {
@@ -59,7 +68,7 @@ func Build(dataDir string) {
 	}
 	defer srsLagrangeFile.Close()
 
-	if !strings.Contains(dataDir, "dev") {
+	if !dev {
 		if _, err := os.Stat(srsFileName); os.IsNotExist(err) {
 			fmt.Println("downloading aztec ignition srs")
 			trusted_setup.DownloadAndSaveAztecIgnitionSrs(174, srsFileName)
@@ -107,7 +116,11 @@ func Build(dataDir string) {
 			}
 		}
 	} else {
-		srs, srsLagrange, err = unsafekzg.NewSRS(scs)
+		var options []unsafekzg.Option
+		if len(devSeed) > 0 {
+			options = append(options, unsafekzg.WithToxicSeed(devSeed))
+		}
+		srs, srsLagrange, err = unsafekzg.NewSRS(scs, options...)
 		if err != nil {
 			panic(err)
 		}
@@ -160,14 +173,14 @@ func Build(dataDir string) {
 	os.MkdirAll(dataDir, 0755)
 
 	// Write the solidity verifier.
-	solidityVerifierFile, err := os.Create(dataDir + "/" + VERIFIER_CONTRACT_PATH)
+	solidityVerifierFile, err := os.Create(filepath.Join(dataDir, VERIFIER_CONTRACT_PATH))
 	if err != nil {
 		panic(err)
 	}
 	vk.ExportSolidity(solidityVerifierFile)
 
 	// Write the R1CS.
-	scsFile, err := os.Create(dataDir + "/" + CIRCUIT_PATH)
+	scsFile, err := os.Create(filepath.Join(dataDir, CIRCUIT_PATH))
 	if err != nil {
 		panic(err)
 	}
@@ -178,7 +191,7 @@ func Build(dataDir string) {
 	}
 
 	// Write the verifier key.
-	vkFile, err := os.Create(dataDir + "/" + VK_PATH)
+	vkFile, err := os.Create(filepath.Join(dataDir, VK_PATH))
 	if err != nil {
 		panic(err)
 	}
@@ -189,7 +202,7 @@ func Build(dataDir string) {
 	}
 
 	// Write the proving key.
-	pkFile, err := os.Create(dataDir + "/" + PK_PATH)
+	pkFile, err := os.Create(filepath.Join(dataDir, PK_PATH))
 	if err != nil {
 		panic(err)
 	}
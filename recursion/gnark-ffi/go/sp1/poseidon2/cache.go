@@ -0,0 +1,78 @@
+package poseidon2
+
+import (
+	"math/big"
+	"sync"
+
+	"github.com/consensys/gnark/frontend"
+	"github.com/succinctlabs/sp1-recursion-gnark/sp1/babybear"
+)
+
+// The round constants of each width, parsed once from the strings of constants.go.
+//
+// The builder parses a constant given as a string every time it is used, which a circuit with
+// hundreds of permutations would otherwise do for every round of every permutation.
+var (
+	roundConstantsMu    sync.Mutex
+	roundConstantsCache = make(map[int]any)
+)
+
+// The tables of round constants of the BN254 and BabyBear permutations.
+type (
+	rc3Table  = [len(RC3)][WIDTH]frontend.Variable
+	rc16Table = [len(RC16)][BABYBEAR_WIDTH]babybear.Variable
+)
+
+// cachedRoundConstants returns the round constants of the given width, loading them on first use.
+func cachedRoundConstants[T any](width int, load func() T) T {
+	roundConstantsMu.Lock()
+	defer roundConstantsMu.Unlock()
+	if rc, ok := roundConstantsCache[width]; ok {
+		return rc.(T)
+	}
+	rc := load()
+	roundConstantsCache[width] = rc
+	return rc
+}
+
+// parseConstant parses a constant given as a string, the same way the builder would.
+func parseConstant(v frontend.Variable) frontend.Variable {
+	s, ok := v.(string)
+	if !ok {
+		return v
+	}
+	n, ok := new(big.Int).SetString(s, 0)
+	if !ok {
+		panic("invalid round constant " + s)
+	}
+	return n
+}
+
+// roundConstants3 returns the parsed round constants of the BN254 permutation.
+func roundConstants3() *rc3Table {
+	return cachedRoundConstants(WIDTH, func() *rc3Table {
+		rc := new(rc3Table)
+		for r := range RC3 {
+			for i := range RC3[r] {
+				rc[r][i] = parseConstant(RC3[r][i])
+			}
+		}
+		return rc
+	})
+}
+
+// roundConstants16 returns the parsed round constants of the BabyBear permutation.
+func roundConstants16() *rc16Table {
+	return cachedRoundConstants(BABYBEAR_WIDTH, func() *rc16Table {
+		rc := new(rc16Table)
+		for r := range RC16 {
+			for i := range RC16[r] {
+				rc[r][i] = babybear.Variable{
+					Value:  parseConstant(RC16[r][i].Value),
+					NbBits: RC16[r][i].NbBits,
+				}
+			}
+		}
+		return rc
+	})
+}
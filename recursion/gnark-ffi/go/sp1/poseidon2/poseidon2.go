@@ -10,50 +10,75 @@ const NUM_INTERNAL_ROUNDS = 56
 const DEGREE = 5
 
 type Poseidon2Chip struct {
-	api                   frontend.API
-	internal_linear_layer [WIDTH]frontend.Variable
-	zero, one             frontend.Variable
+	api frontend.API
+	// The round constants, shared by every chip.
+	rc *rc3Table
+	// The linear layers, built once per chip and applied by every permutation.
+	externalLinearLayer func(state *[WIDTH]frontend.Variable)
+	internalLinearLayer func(state *[WIDTH]frontend.Variable)
 }
 
 func NewChip(api frontend.API) *Poseidon2Chip {
+	return newChip(api, roundConstants3())
+}
+
+func newChip(api frontend.API, rc *rc3Table) *Poseidon2Chip {
+	zero := frontend.Variable(0)
+	internalDiag := [WIDTH]frontend.Variable{
+		frontend.Variable(1),
+		frontend.Variable(1),
+		frontend.Variable(2),
+	}
 	return &Poseidon2Chip{
 		api: api,
-		internal_linear_layer: [WIDTH]frontend.Variable{
-			frontend.Variable(1),
-			frontend.Variable(1),
-			frontend.Variable(2),
+		rc:  rc,
+		externalLinearLayer: func(state *[WIDTH]frontend.Variable) {
+			sum := api.Add(state[0], state[1])
+			sum = api.Add(sum, state[2])
+			state[0] = api.Add(state[0], sum)
+			state[1] = api.Add(state[1], sum)
+			state[2] = api.Add(state[2], sum)
+		},
+		internalLinearLayer: func(state *[WIDTH]frontend.Variable) {
+			sum := zero
+			for i := 0; i < WIDTH; i++ {
+				sum = api.Add(sum, state[i])
+			}
+
+			for i := 0; i < WIDTH; i++ {
+				state[i] = api.Mul(state[i], internalDiag[i])
+				state[i] = api.Add(state[i], sum)
+			}
 		},
-		zero: frontend.Variable(0),
-		one:  frontend.Variable(1),
 	}
 }
 
 func (p *Poseidon2Chip) PermuteMut(state *[WIDTH]frontend.Variable) {
 	// The initial linear layer.
-	p.MatrixPermuteMut(state)
+	p.externalLinearLayer(state)
 
 	// The first half of the external rounds.
 	rounds := NUM_EXTERNAL_ROUNDS + NUM_INTERNAL_ROUNDS
 	rounds_f_beginning := NUM_EXTERNAL_ROUNDS / 2
 	for r := 0; r < rounds_f_beginning; r++ {
-		p.AddRc(state, RC3[r])
+		p.AddRc(state, p.rc[r])
 		p.Sbox(state)
-		p.MatrixPermuteMut(state)
+		p.externalLinearLayer(state)
 	}
 
 	// The internal rounds.
 	p_end := rounds_f_beginning + NUM_INTERNAL_ROUNDS
 	for r := rounds_f_beginning; r < p_end; r++ {
-		state[0] = p.api.Add(state[0], RC3[r][0])
+		state[0] = p.api.Add(state[0], p.rc[r][0])
 		state[0] = p.SboxP(state[0])
-		p.DiffusionPermuteMut(state)
+		p.internalLinearLayer(state)
 	}
 
 	// The second half of the external rounds.
 	for r := p_end; r < rounds; r++ {
-		p.AddRc(state, RC3[r])
+		p.AddRc(state, p.rc[r])
 		p.Sbox(state)
-		p.MatrixPermuteMut(state)
+		p.externalLinearLayer(state)
 	}
 }
 
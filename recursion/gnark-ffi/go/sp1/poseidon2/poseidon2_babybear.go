@@ -13,12 +13,37 @@ const BABYBEAR_DEGREE = 7
 type Poseidon2BabyBearChip struct {
 	api      frontend.API
 	fieldApi *babybear.Chip
+	// The round constants, shared by every chip.
+	rc *rc16Table
+	// The constants of the internal linear layer, built once per chip.
+	matInternalDiagM1 [BABYBEAR_WIDTH]babybear.Variable
+	montyInverse      babybear.Variable
 }
 
 func NewBabyBearChip(api frontend.API) *Poseidon2BabyBearChip {
 	return &Poseidon2BabyBearChip{
 		api:      api,
 		fieldApi: babybear.NewChip(api),
+		rc:       roundConstants16(),
+		matInternalDiagM1: [BABYBEAR_WIDTH]babybear.Variable{
+			babybear.NewF("2013265919"),
+			babybear.NewF("1"),
+			babybear.NewF("2"),
+			babybear.NewF("4"),
+			babybear.NewF("8"),
+			babybear.NewF("16"),
+			babybear.NewF("32"),
+			babybear.NewF("64"),
+			babybear.NewF("128"),
+			babybear.NewF("256"),
+			babybear.NewF("512"),
+			babybear.NewF("1024"),
+			babybear.NewF("2048"),
+			babybear.NewF("4096"),
+			babybear.NewF("8192"),
+			babybear.NewF("32768"),
+		},
+		montyInverse: babybear.NewF("943718400"),
 	}
 }
 
@@ -30,7 +55,7 @@ func (p *Poseidon2BabyBearChip) PermuteMut(state *[BABYBEAR_WIDTH]babybear.Varia
 	rounds := BABYBEAR_NUM_EXTERNAL_ROUNDS + BABYBEAR_NUM_INTERNAL_ROUNDS
 	roundsFBeggining := BABYBEAR_NUM_EXTERNAL_ROUNDS / 2
 	for r := 0; r < roundsFBeggining; r++ {
-		p.addRc(state, RC16[r])
+		p.addRc(state, p.rc[r])
 		p.sbox(state)
 		p.externalLinearLayer(state)
 	}
@@ -38,14 +63,14 @@ func (p *Poseidon2BabyBearChip) PermuteMut(state *[BABYBEAR_WIDTH]babybear.Varia
 	// The internal rounds.
 	p_end := roundsFBeggining + BABYBEAR_NUM_INTERNAL_ROUNDS
 	for r := roundsFBeggining; r < p_end; r++ {
-		state[0] = p.fieldApi.AddF(state[0], RC16[r][0])
+		state[0] = p.fieldApi.AddF(state[0], p.rc[r][0])
 		state[0] = p.sboxP(state[0])
 		p.diffusionPermuteMut(state)
 	}
 
 	// The second half of the external rounds.
 	for r := p_end; r < rounds; r++ {
-		p.addRc(state, RC16[r])
+		p.addRc(state, p.rc[r])
 		p.sbox(state)
 		p.externalLinearLayer(state)
 	}
@@ -115,30 +140,10 @@ func (p *Poseidon2BabyBearChip) externalLinearLayer(state *[BABYBEAR_WIDTH]babyb
 }
 
 func (p *Poseidon2BabyBearChip) diffusionPermuteMut(state *[BABYBEAR_WIDTH]babybear.Variable) {
-	matInternalDiagM1 := [BABYBEAR_WIDTH]babybear.Variable{
-		babybear.NewF("2013265919"),
-		babybear.NewF("1"),
-		babybear.NewF("2"),
-		babybear.NewF("4"),
-		babybear.NewF("8"),
-		babybear.NewF("16"),
-		babybear.NewF("32"),
-		babybear.NewF("64"),
-		babybear.NewF("128"),
-		babybear.NewF("256"),
-		babybear.NewF("512"),
-		babybear.NewF("1024"),
-		babybear.NewF("2048"),
-		babybear.NewF("4096"),
-		babybear.NewF("8192"),
-		babybear.NewF("32768"),
-	}
-	montyInverse := babybear.NewF("943718400")
-	p.matmulInternal(state, &matInternalDiagM1)
+	p.matmulInternal(state, &p.matInternalDiagM1)
 	for i := 0; i < BABYBEAR_WIDTH; i++ {
-		state[i] = p.fieldApi.MulF(state[i], montyInverse)
+		state[i] = p.fieldApi.MulF(state[i], p.montyInverse)
 	}
-
 }
 
 func (p *Poseidon2BabyBearChip) matmulInternal(
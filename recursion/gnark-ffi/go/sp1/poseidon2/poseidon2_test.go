@@ -2,10 +2,14 @@ package poseidon2
 
 import (
 	"testing"
+	"time"
 
 	"github.com/consensys/gnark-crypto/ecc"
+	"github.com/consensys/gnark-crypto/ecc/bn254/fr"
 	"github.com/consensys/gnark/backend"
+	"github.com/consensys/gnark/constraint"
 	"github.com/consensys/gnark/frontend"
+	"github.com/consensys/gnark/frontend/cs/scs"
 	"github.com/consensys/gnark/test"
 )
 
@@ -50,3 +54,143 @@ func TestPoseidon2(t *testing.T) {
 	witness = TestPoseidon2Circuit{Input: input, ExpectedOutput: expected_output}
 	assert.ProverSucceeded(&circuit, &witness, test.WithCurves(ecc.BN254), test.WithBackends(backend.PLONK))
 }
+
+// The number of permutations of the transcript circuits.
+const NUM_TRANSCRIPT_PERMUTATIONS = 200
+
+// A transcript of permutations sharing one chip, and so its round constants and linear layers.
+type TestPoseidon2TranscriptCircuit struct {
+	Input, ExpectedOutput [WIDTH]frontend.Variable `gnark:",public"`
+}
+
+func (circuit *TestPoseidon2TranscriptCircuit) Define(api frontend.API) error {
+	poseidon2Chip := NewChip(api)
+	state := circuit.Input
+	for i := 0; i < NUM_TRANSCRIPT_PERMUTATIONS; i++ {
+		poseidon2Chip.PermuteMut(&state)
+	}
+	for i := 0; i < WIDTH; i++ {
+		api.AssertIsEqual(circuit.ExpectedOutput[i], state[i])
+	}
+	return nil
+}
+
+// The same transcript, building a chip with unparsed round constants for every permutation.
+type TestPoseidon2UncachedTranscriptCircuit struct {
+	Input, ExpectedOutput [WIDTH]frontend.Variable `gnark:",public"`
+}
+
+func (circuit *TestPoseidon2UncachedTranscriptCircuit) Define(api frontend.API) error {
+	state := circuit.Input
+	for i := 0; i < NUM_TRANSCRIPT_PERMUTATIONS; i++ {
+		newChip(api, &RC3).PermuteMut(&state)
+	}
+	for i := 0; i < WIDTH; i++ {
+		api.AssertIsEqual(circuit.ExpectedOutput[i], state[i])
+	}
+	return nil
+}
+
+// permuteNative computes the permutation outside of a circuit.
+func permuteNative(state *[WIDTH]fr.Element) {
+	externalLinearLayer := func() {
+		var sum fr.Element
+		sum.Add(&state[0], &state[1]).Add(&sum, &state[2])
+		for i := 0; i < WIDTH; i++ {
+			state[i].Add(&state[i], &sum)
+		}
+	}
+	addRc := func(r, i int) {
+		var rc fr.Element
+		if _, err := rc.SetString(RC3[r][i].(string)); err != nil {
+			panic(err)
+		}
+		state[i].Add(&state[i], &rc)
+	}
+	sbox := func(i int) {
+		var x2, x4 fr.Element
+		x2.Square(&state[i])
+		x4.Square(&x2)
+		state[i].Mul(&x4, &state[i])
+	}
+
+	externalLinearLayer()
+	rounds_f_beginning := NUM_EXTERNAL_ROUNDS / 2
+	p_end := rounds_f_beginning + NUM_INTERNAL_ROUNDS
+	for r := 0; r < NUM_EXTERNAL_ROUNDS+NUM_INTERNAL_ROUNDS; r++ {
+		if r < rounds_f_beginning || r >= p_end {
+			for i := 0; i < WIDTH; i++ {
+				addRc(r, i)
+				sbox(i)
+			}
+			externalLinearLayer()
+			continue
+		}
+		addRc(r, 0)
+		sbox(0)
+		var sum fr.Element
+		sum.Add(&state[0], &state[1]).Add(&sum, &state[2])
+		state[2].Double(&state[2])
+		for i := 0; i < WIDTH; i++ {
+			state[i].Add(&state[i], &sum)
+		}
+	}
+}
+
+func TestPoseidon2Transcript(t *testing.T) {
+	assert := test.NewAssert(t)
+
+	var input, output [WIDTH]fr.Element
+	for i := 0; i < WIDTH; i++ {
+		input[i].SetUint64(uint64(i))
+	}
+	output = input
+	for i := 0; i < NUM_TRANSCRIPT_PERMUTATIONS; i++ {
+		permuteNative(&output)
+	}
+	var inputVars, outputVars [WIDTH]frontend.Variable
+	for i := 0; i < WIDTH; i++ {
+		inputVars[i] = input[i].String()
+		outputVars[i] = output[i].String()
+	}
+
+	// The fastest of a few compilations, to smooth out the noise of the scheduler.
+	compile := func(circuit frontend.Circuit) (constraint.ConstraintSystem, time.Duration) {
+		var ccs constraint.ConstraintSystem
+		best := time.Duration(1<<63 - 1)
+		for i := 0; i < 3; i++ {
+			start := time.Now()
+			var err error
+			ccs, err = frontend.Compile(ecc.BN254.ScalarField(), scs.NewBuilder, circuit)
+			assert.NoError(err)
+			best = min(best, time.Since(start))
+		}
+		return ccs, best
+	}
+	cached, cachedTime := compile(&TestPoseidon2TranscriptCircuit{})
+	uncached, uncachedTime := compile(&TestPoseidon2UncachedTranscriptCircuit{})
+	t.Logf(
+		"cached: %v, %d constraints, %d internal variables",
+		cachedTime, cached.GetNbConstraints(), cached.GetNbInternalVariables(),
+	)
+	t.Logf(
+		"uncached: %v, %d constraints, %d internal variables",
+		uncachedTime, uncached.GetNbConstraints(), uncached.GetNbInternalVariables(),
+	)
+
+	// Sharing the constants emits the same constraints, only faster.
+	assert.Equal(uncached.GetNbConstraints(), cached.GetNbConstraints())
+	assert.Equal(uncached.GetNbInternalVariables(), cached.GetNbInternalVariables())
+	assert.Less(cachedTime, uncachedTime)
+
+	assert.NoError(test.IsSolved(
+		&TestPoseidon2TranscriptCircuit{},
+		&TestPoseidon2TranscriptCircuit{Input: inputVars, ExpectedOutput: outputVars},
+		ecc.BN254.ScalarField(),
+	))
+	assert.NoError(test.IsSolved(
+		&TestPoseidon2UncachedTranscriptCircuit{},
+		&TestPoseidon2UncachedTranscriptCircuit{Input: inputVars, ExpectedOutput: outputVars},
+		ecc.BN254.ScalarField(),
+	))
+}
@@ -4,6 +4,7 @@ import (
 	"bufio"
 	"encoding/json"
 	"os"
+	"path/filepath"
 
 	"github.com/consensys/gnark-crypto/ecc"
 	"github.com/consensys/gnark/backend/plonk"
@@ -15,10 +16,10 @@ func Prove(dataDir string, witnessPath string) Proof {
 	if dataDir == "" {
 		panic("dataDirStr is required")
 	}
-	os.Setenv("CONSTRAINTS_JSON", dataDir+"/"+CONSTRAINTS_JSON_FILE)
+	os.Setenv("CONSTRAINTS_JSON", filepath.Join(dataDir, CONSTRAINTS_JSON_FILE))
 
 	// Read the R1CS.
-	scsFile, err := os.Open(dataDir + "/" + CIRCUIT_PATH)
+	scsFile, err := os.Open(filepath.Join(dataDir, CIRCUIT_PATH))
 	if err != nil {
 		panic(err)
 	}
@@ -26,7 +27,7 @@ func Prove(dataDir string, witnessPath string) Proof {
 	scs.ReadFrom(scsFile)
 
 	// Read the proving key.
-	pkFile, err := os.Open(dataDir + "/" + PK_PATH)
+	pkFile, err := os.Open(filepath.Join(dataDir, PK_PATH))
 	if err != nil {
 		panic(err)
 	}
@@ -35,7 +36,7 @@ func Prove(dataDir string, witnessPath string) Proof {
 	pk.UnsafeReadFrom(bufReader)
 
 	// Read the verifier key.
-	vkFile, err := os.Open(dataDir + "/" + VK_PATH)
+	vkFile, err := os.Open(filepath.Join(dataDir, VK_PATH))
 	if err != nil {
 		panic(err)
 	}
@@ -1,6 +1,7 @@
 package sp1
 
 import (
+	"bufio"
 	"encoding/json"
 	"fmt"
 	"os"
@@ -23,6 +24,7 @@ var PK_PATH string = "pk.bin"
 type Circuit struct {
 	VkeyHash             frontend.Variable `gnark:",public"`
 	CommitedValuesDigest frontend.Variable `gnark:",public"`
+	InputDigest          frontend.Variable `gnark:",public"`
 	Vars                 []frontend.Variable
 	Felts                []babybear.Variable
 	Exts                 []babybear.ExtensionVariable
@@ -39,10 +41,11 @@ type WitnessInput struct {
 	Exts                 [][]string `json:"exts"`
 	VkeyHash             string     `json:"vkey_hash"`
 	CommitedValuesDigest string     `json:"commited_values_digest"`
+	InputDigest          string     `json:"input_digest"`
 }
 
 type Proof struct {
-	PublicInputs [2]string `json:"public_inputs"`
+	PublicInputs [3]string `json:"public_inputs"`
 	EncodedProof string    `json:"encoded_proof"`
 	RawProof     string    `json:"raw_proof"`
 }
@@ -54,16 +57,15 @@ func (circuit *Circuit) Define(api frontend.API) error {
 		fileName = "constraints.json"
 	}
 
-	// Read the file.
-	data, err := os.ReadFile(fileName)
+	// Open the file, whose constraints are decoded one at a time so that the circuit is built
+	// without holding all of them in memory.
+	file, err := os.Open(fileName)
 	if err != nil {
 		return fmt.Errorf("failed to read file: %w", err)
 	}
-
-	// Deserialize the JSON data into a slice of Instruction structs.
-	var constraints []Constraint
-	err = json.Unmarshal(data, &constraints)
-	if err != nil {
+	defer file.Close()
+	decoder := json.NewDecoder(bufio.NewReader(file))
+	if _, err := decoder.Token(); err != nil {
 		return fmt.Errorf("error deserializing JSON: %v", err)
 	}
 
@@ -75,7 +77,11 @@ func (circuit *Circuit) Define(api frontend.API) error {
 	exts := make(map[string]babybear.ExtensionVariable)
 
 	// Iterate through the instructions and handle each opcode.
-	for _, cs := range constraints {
+	for decoder.More() {
+		var cs Constraint
+		if err := decoder.Decode(&cs); err != nil {
+			return fmt.Errorf("error deserializing JSON: %v", err)
+		}
 		switch cs.Opcode {
 		case "ImmV":
 			vars[cs.Args[0][0]] = frontend.Variable(cs.Args[1][0])
@@ -194,6 +200,9 @@ func (circuit *Circuit) Define(api frontend.API) error {
 		case "CommitCommitedValuesDigest":
 			element := vars[cs.Args[0][0]]
 			api.AssertIsEqual(circuit.CommitedValuesDigest, element)
+		case "CommitInputDigest":
+			element := vars[cs.Args[0][0]]
+			api.AssertIsEqual(circuit.InputDigest, element)
 		case "CircuitFelts2Ext":
 			exts[cs.Args[0][0]] = babybear.Felts2Ext(felts[cs.Args[1][0]], felts[cs.Args[2][0]], felts[cs.Args[3][0]], felts[cs.Args[4][0]])
 		default:
@@ -15,9 +15,10 @@ func NewSP1PlonkBn254Proof(proof *plonk.Proof, witnessInput WitnessInput) Proof
 	(*proof).WriteRawTo(&buf)
 	proofBytes := buf.Bytes()
 
-	var publicInputs [2]string
+	var publicInputs [3]string
 	publicInputs[0] = witnessInput.VkeyHash
 	publicInputs[1] = witnessInput.CommitedValuesDigest
+	publicInputs[2] = witnessInput.InputDigest
 
 	// Cast plonk proof into plonk_bn254 proof so we can call MarshalSolidity.
 	p := (*proof).(*plonk_bn254.Proof)
@@ -47,6 +48,7 @@ func NewCircuit(witnessInput WitnessInput) Circuit {
 	return Circuit{
 		VkeyHash:             witnessInput.VkeyHash,
 		CommitedValuesDigest: witnessInput.CommitedValuesDigest,
+		InputDigest:          witnessInput.InputDigest,
 		Vars:                 vars,
 		Felts:                felts,
 		Exts:                 exts,
@@ -4,6 +4,8 @@ import (
 	"bytes"
 	"encoding/hex"
 	"os"
+	"path/filepath"
+	"sync"
 
 	"github.com/consensys/gnark-crypto/ecc"
 	"github.com/consensys/gnark/backend/plonk"
@@ -11,7 +13,15 @@ import (
 	"github.com/succinctlabs/sp1-recursion-gnark/sp1/babybear"
 )
 
-func Verify(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkeyHash string, verifyCmdCommitedValuesDigest string) error {
+// A proof and its public inputs, as passed to VerifyBatch.
+type BatchProof struct {
+	Proof                string `json:"proof"`
+	VkeyHash             string `json:"vkey_hash"`
+	CommitedValuesDigest string `json:"commited_values_digest"`
+	InputDigest          string `json:"input_digest"`
+}
+
+func Verify(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkeyHash string, verifyCmdCommitedValuesDigest string, verifyCmdInputDigest string) error {
 	// Sanity check the required arguments have been provided.
 	if verifyCmdDataDir == "" {
 		panic("--data is required")
@@ -27,32 +37,78 @@ func Verify(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkeyHash st
 		panic(err)
 	}
 
-	// Read the verifier key.
-	vkFile, err := os.Open(verifyCmdDataDir + "/" + VK_PATH)
+	return verifyWithKey(readVerifyingKey(verifyCmdDataDir), proof, verifyCmdVkeyHash, verifyCmdCommitedValuesDigest, verifyCmdInputDigest)
+}
+
+// VerifyBatch verifies the proofs concurrently against a verifying key that is only read once.
+//
+// It returns the index of the first proof that fails to verify together with its error, or -1 if
+// all proofs are valid. Proofs that cannot be decoded are reported as failures instead of panicking.
+func VerifyBatch(dataDir string, proofs []BatchProof) (int, error) {
+	if dataDir == "" {
+		panic("--data is required")
+	}
+	vk := readVerifyingKey(dataDir)
+
+	errs := make([]error, len(proofs))
+	var wg sync.WaitGroup
+	for i := range proofs {
+		wg.Add(1)
+		go func(i int) {
+			defer wg.Done()
+			proofDecodedBytes, err := hex.DecodeString(proofs[i].Proof)
+			if err != nil {
+				errs[i] = err
+				return
+			}
+			proof := plonk.NewProof(ecc.BN254)
+			if _, err := proof.ReadFrom(bytes.NewReader(proofDecodedBytes)); err != nil {
+				errs[i] = err
+				return
+			}
+			errs[i] = verifyWithKey(vk, proof, proofs[i].VkeyHash, proofs[i].CommitedValuesDigest, proofs[i].InputDigest)
+		}(i)
+	}
+	wg.Wait()
+
+	for i, err := range errs {
+		if err != nil {
+			return i, err
+		}
+	}
+	return -1, nil
+}
+
+func readVerifyingKey(dataDir string) plonk.VerifyingKey {
+	vkFile, err := os.Open(filepath.Join(dataDir, VK_PATH))
 	if err != nil {
 		panic(err)
 	}
+	defer vkFile.Close()
 	vk := plonk.NewVerifyingKey(ecc.BN254)
 	vk.ReadFrom(vkFile)
+	return vk
+}
 
+func verifyWithKey(vk plonk.VerifyingKey, proof plonk.Proof, vkeyHash string, commitedValuesDigest string, inputDigest string) error {
 	// Compute the public witness.
 	circuit := Circuit{
 		Vars:                 []frontend.Variable{},
 		Felts:                []babybear.Variable{},
 		Exts:                 []babybear.ExtensionVariable{},
-		VkeyHash:             verifyCmdVkeyHash,
-		CommitedValuesDigest: verifyCmdCommitedValuesDigest,
+		VkeyHash:             vkeyHash,
+		CommitedValuesDigest: commitedValuesDigest,
+		InputDigest:          inputDigest,
 	}
 	witness, err := frontend.NewWitness(&circuit, ecc.BN254.ScalarField())
 	if err != nil {
-		panic(err)
+		return err
 	}
 	publicWitness, err := witness.Public()
 	if err != nil {
-		panic(err)
+		return err
 	}
 
 	// Verify proof.
-	err = plonk.Verify(proof, vk, publicWitness)
-	return err
+	return plonk.Verify(proof, vk, publicWitness)
 }
@@ -0,0 +1,90 @@
+use std::io::{self, Write};
+
+use sp1_recursion_compiler::constraints::{Constraint, ConstraintSink};
+
+/// A [ConstraintSink] writing the constraints to `W` as they are emitted, in the JSON format read
+/// by the gnark circuit. The output is byte for byte the serialization of the vector of the same
+/// constraints, which gnark decodes one constraint at a time.
+///
+/// The first write error is kept, and the constraints after it are dropped: it is returned by
+/// [JsonConstraintWriter::finish].
+pub struct JsonConstraintWriter<W: Write> {
+    writer: W,
+    len: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonConstraintWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            len: 0,
+            error: None,
+        }
+    }
+
+    /// The number of constraints received.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Closes the JSON array and flushes the writer, returning it.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let end: &[u8] = if self.len == 0 { b"[]" } else { b"]" };
+        self.writer.write_all(end)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write(&mut self, constraint: &Constraint) -> io::Result<()> {
+        let separator: &[u8] = if self.len == 0 { b"[" } else { b"," };
+        self.writer.write_all(separator)?;
+        serde_json::to_writer(&mut self.writer, constraint)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> ConstraintSink for JsonConstraintWriter<W> {
+    fn push(&mut self, constraint: Constraint) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.write(&constraint) {
+            Ok(()) => self.len += 1,
+            Err(err) => self.error = Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sp1_recursion_compiler::constraints::{opcodes::ConstraintOpcode, Constraint};
+
+    use super::*;
+
+    #[test]
+    fn test_json_constraint_writer() {
+        let constraints = (0..3)
+            .map(|i| Constraint {
+                opcode: ConstraintOpcode::ImmV,
+                args: vec![vec![format!("backend{}", i)], vec![i.to_string()]],
+            })
+            .collect::<Vec<_>>();
+        for n in 0..=constraints.len() {
+            let mut writer = JsonConstraintWriter::new(Vec::new());
+            for constraint in constraints[..n].iter() {
+                writer.push(constraint.clone());
+            }
+            assert_eq!(writer.len(), n);
+            let bytes = writer.finish().unwrap();
+            assert_eq!(bytes, serde_json::to_vec(&constraints[..n]).unwrap());
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use sp1_core::SP1_CIRCUIT_VERSION;
+use sp1_helper::docker_volume;
 
 use crate::PlonkBn254Proof;
 use std::io::Write;
@@ -28,7 +29,7 @@ fn call_docker(args: &[&str], mounts: &[(&str, &str)]) -> anyhow::Result<()> {
     let mut cmd = Command::new("docker");
     cmd.args(["run", "--rm"]);
     for (src, dest) in mounts {
-        cmd.arg("-v").arg(format!("{}:{}", src, dest));
+        cmd.arg("-v").arg(docker_volume(src, dest));
     }
     cmd.arg(get_docker_image());
     cmd.args(args);
@@ -52,15 +53,22 @@ pub fn prove_plonk_bn254(data_dir: &str, witness_path: &str) -> PlonkBn254Proof
     bincode::deserialize_from(&output_file).expect("failed to deserialize result")
 }
 
-pub fn build_plonk_bn254(data_dir: &str) {
+pub fn build_plonk_bn254(data_dir: &str, dev: bool, dev_seed: Option<&str>) {
     let circuit_dir = if data_dir.ends_with("dev") {
         "/circuit_dev"
     } else {
         "/circuit"
     };
     let mounts = [(data_dir, circuit_dir)];
+    let mut args = vec!["build-plonk", circuit_dir];
+    if dev {
+        args.push("--dev");
+    }
+    if let Some(dev_seed) = dev_seed {
+        args.extend(["--dev-seed", dev_seed]);
+    }
     assert_docker();
-    call_docker(&["build-plonk", circuit_dir], &mounts).expect("failed to build with docker");
+    call_docker(&args, &mounts).expect("failed to build with docker");
 }
 
 pub fn verify_plonk_bn254(
@@ -68,6 +76,7 @@ pub fn verify_plonk_bn254(
     proof: &str,
     vkey_hash: &str,
     committed_values_digest: &str,
+    input_digest: &str,
 ) -> Result<(), String> {
     // Write proof string to a file since it can be large.
     let mut proof_file = tempfile::NamedTempFile::new().unwrap();
@@ -86,6 +95,7 @@ pub fn verify_plonk_bn254(
             "/proof",
             vkey_hash,
             committed_values_digest,
+            input_digest,
             "/output",
         ],
         &mounts,
@@ -99,6 +109,29 @@ pub fn verify_plonk_bn254(
     }
 }
 
+/// Verifies `(proof, vkey_hash, committed_values_digest, input_digest)` tuples against the circuit
+/// in `data_dir`, returning the index of the first invalid proof and its error.
+///
+/// The docker image has no batch entrypoint, so the proofs are verified one by one.
+pub fn verify_plonk_bn254_batch(
+    data_dir: &str,
+    proofs: &[(&str, &str, &str, &str)],
+) -> Result<(), (usize, String)> {
+    for (index, (proof, vkey_hash, committed_values_digest, input_digest)) in
+        proofs.iter().enumerate()
+    {
+        verify_plonk_bn254(
+            data_dir,
+            proof,
+            vkey_hash,
+            committed_values_digest,
+            input_digest,
+        )
+        .map_err(|err| (index, err))?;
+    }
+    Ok(())
+}
+
 pub fn test_plonk_bn254(witness_json: &str, constraints_json: &str) {
     let mounts = [
         (constraints_json, "/constraints"),
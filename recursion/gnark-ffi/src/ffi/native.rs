@@ -32,11 +32,18 @@ pub fn prove_plonk_bn254(data_dir: &str, witness_path: &str) -> PlonkBn254Proof
     proof.into_rust()
 }
 
-pub fn build_plonk_bn254(data_dir: &str) {
+/// Builds the circuit in `data_dir`, with a local setup if `dev` is set, derived from the hex
+/// encoded `dev_seed` if any.
+pub fn build_plonk_bn254(data_dir: &str, dev: bool, dev_seed: Option<&str>) {
     let data_dir = CString::new(data_dir).expect("CString::new failed");
+    let dev_seed = CString::new(dev_seed.unwrap_or_default()).expect("CString::new failed");
 
     unsafe {
-        bind::BuildPlonkBn254(data_dir.as_ptr() as *mut c_char);
+        bind::BuildPlonkBn254(
+            data_dir.as_ptr() as *mut c_char,
+            dev as i32,
+            dev_seed.as_ptr() as *mut c_char,
+        );
     }
 }
 
@@ -45,12 +52,14 @@ pub fn verify_plonk_bn254(
     proof: &str,
     vkey_hash: &str,
     committed_values_digest: &str,
+    input_digest: &str,
 ) -> Result<(), String> {
     let data_dir = CString::new(data_dir).expect("CString::new failed");
     let proof = CString::new(proof).expect("CString::new failed");
     let vkey_hash = CString::new(vkey_hash).expect("CString::new failed");
     let committed_values_digest =
         CString::new(committed_values_digest).expect("CString::new failed");
+    let input_digest = CString::new(input_digest).expect("CString::new failed");
 
     let err_ptr = unsafe {
         bind::VerifyPlonkBn254(
@@ -58,6 +67,7 @@ pub fn verify_plonk_bn254(
             proof.as_ptr() as *mut c_char,
             vkey_hash.as_ptr() as *mut c_char,
             committed_values_digest.as_ptr() as *mut c_char,
+            input_digest.as_ptr() as *mut c_char,
         )
     };
     if err_ptr.is_null() {
@@ -69,6 +79,52 @@ pub fn verify_plonk_bn254(
     }
 }
 
+/// Verifies `(proof, vkey_hash, committed_values_digest, input_digest)` tuples against the circuit
+/// in `data_dir`, returning the index of the first invalid proof and its error.
+pub fn verify_plonk_bn254_batch(
+    data_dir: &str,
+    proofs: &[(&str, &str, &str, &str)],
+) -> Result<(), (usize, String)> {
+    // Write the proofs to a file since they can be large.
+    let proofs = proofs
+        .iter()
+        .map(
+            |(proof, vkey_hash, committed_values_digest, input_digest)| {
+                serde_json::json!({
+                    "proof": proof,
+                    "vkey_hash": vkey_hash,
+                    "commited_values_digest": committed_values_digest,
+                    "input_digest": input_digest,
+                })
+            },
+        )
+        .collect::<Vec<_>>();
+    let proofs_file = tempfile::NamedTempFile::new().unwrap();
+    serde_json::to_writer(&proofs_file, &proofs).unwrap();
+
+    let data_dir = CString::new(data_dir).expect("CString::new failed");
+    let proofs_path =
+        CString::new(proofs_file.path().to_str().unwrap()).expect("CString::new failed");
+    let mut failed_index: std::os::raw::c_int = -1;
+
+    let err_ptr = unsafe {
+        bind::VerifyPlonkBn254Batch(
+            data_dir.as_ptr() as *mut c_char,
+            proofs_path.as_ptr() as *mut c_char,
+            &mut failed_index,
+        )
+    };
+    if err_ptr.is_null() {
+        return Ok(());
+    }
+    // Safety: The error message is returned from the go code and is guaranteed to be valid.
+    let err = unsafe { CString::from_raw(err_ptr) }.into_string().unwrap();
+    match usize::try_from(failed_index) {
+        Ok(index) => Err((index, err)),
+        Err(_) => panic!("VerifyPlonkBn254Batch failed: {}", err),
+    }
+}
+
 pub fn test_plonk_bn254(witness_json: &str, constraints_json: &str) {
     unsafe {
         let witness_json = CString::new(witness_json).expect("CString::new failed");
@@ -118,6 +174,7 @@ impl C_PlonkBn254Proof {
                 public_inputs: [
                     c_char_ptr_to_string(self.PublicInputs[0]),
                     c_char_ptr_to_string(self.PublicInputs[1]),
+                    c_char_ptr_to_string(self.PublicInputs[2]),
                 ],
                 encoded_proof: c_char_ptr_to_string(self.EncodedProof),
                 raw_proof: c_char_ptr_to_string(self.RawProof),
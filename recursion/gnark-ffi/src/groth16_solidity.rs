@@ -0,0 +1,515 @@
+//! Generation of the Solidity verifier of the Groth16 BN254 proofs of SP1.
+//!
+//! The generic verifier exported by gnark takes the public inputs of the circuit as field
+//! elements. [Groth16SolidityVerifier] emits a complete `SP1Verifier.sol` instead, whose
+//! `verifyProof(bytes proof, bytes32 vkeyDigest, bytes publicValues, bytes32 inputDigest)` computes
+//! the public inputs from the raw public values and input digest like the wrapper circuit does, see
+//! [groth16_public_inputs]. It
+//! reverts with `InvalidVkeyDigest` or `VkeyDigestMismatch` when the verifying key digest is
+//! rejected, and with `ProofInvalid` when the pairing check fails.
+//!
+//! Given a [Groth16Fixture], [Groth16SolidityVerifier::export] also writes a foundry project
+//! testing the contract against the proof of the fixture, run with `forge test`.
+
+use std::{fmt, fs, io, path::Path};
+
+use sha2::{Digest, Sha256};
+use sp1_core::SP1_CIRCUIT_VERSION;
+
+/// The size of an encoded field element, a big-endian `uint256`.
+const WORD_LEN: usize = 32;
+
+/// The size of an encoded proof, the ABI encoding of its `uint256[8]`.
+pub const GROTH16_PROOF_LEN: usize = 8 * WORD_LEN;
+
+/// The number of public inputs of the wrapper circuit: the verifying key digest of the program, the
+/// digest of its public values and the digest of its input.
+const NUM_PUBLIC_INPUTS: usize = 3;
+
+/// The order of the scalar field of BN254, big-endian.
+const SCALAR_FIELD_MODULUS: [u8; WORD_LEN] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// The contract, filled in by [Groth16SolidityVerifier::contract].
+const CONTRACT_TEMPLATE: &str = include_str!("../assets/SP1Groth16Verifier.txt");
+
+/// The foundry test of the contract, filled in by [Groth16SolidityVerifier::test_contract].
+const TEST_TEMPLATE: &str = include_str!("../assets/SP1Groth16VerifierTest.txt");
+
+/// The foundry configuration written by [Groth16SolidityVerifier::export].
+const FOUNDRY_TOML: &str = "[profile.default]\nsrc = \"src\"\ntest = \"test\"\nout = \"out\"\n";
+
+const VKEY_ROOT_ERROR: &str = "
+    /// @notice The verifying key digest is not in the tree of `VKEY_ROOT`.
+    error VkeyDigestMismatch();";
+
+const VKEY_ROOT_GETTER: &str = "
+
+    /// @notice The root of the Merkle tree over the verifying key digests of the programs accepted
+    /// by `verifyProofInRoot`, whose nodes are the SHA-256 of their two children in increasing
+    /// order.
+    function VKEY_ROOT() public pure returns (bytes32) {
+        return {VKEY_ROOT};
+    }";
+
+const VKEY_ROOT_FUNCTION: &str = "
+
+    /// @notice Verifies a proof like `verifyProof`, for a program whose verifying key digest is in
+    /// the tree of `VKEY_ROOT`.
+    /// @param vkeyProof The siblings of `vkeyDigest` in the tree, from the leaf to the root.
+    function verifyProofInRoot(
+        bytes calldata proof,
+        bytes32 vkeyDigest,
+        bytes calldata publicValues,
+        bytes32 inputDigest,
+        bytes32[] calldata vkeyProof
+    ) external view {
+        bytes32 node = vkeyDigest;
+        for (uint256 i = 0; i < vkeyProof.length; i++) {
+            bytes32 sibling = vkeyProof[i];
+            node = node <= sibling
+                ? sha256(abi.encodePacked(node, sibling))
+                : sha256(abi.encodePacked(sibling, node));
+        }
+        if (node != VKEY_ROOT()) {
+            revert VkeyDigestMismatch();
+        }
+        verifyProof(proof, vkeyDigest, publicValues, inputDigest);
+    }";
+
+const VKEY_ROOT_TESTS: &str = "
+
+    function test_VerifyProofInRoot() public view {
+        verifier.verifyProofInRoot(PROOF, VKEY_DIGEST, PUBLIC_VALUES, INPUT_DIGEST, vkeyProof());
+    }
+
+    function test_RevertWhen_VkeyDigestNotInRoot() public view {
+        bytes32 vkeyDigest = bytes32(uint256(VKEY_DIGEST) ^ 1);
+        expectRevert(
+            abi.encodeWithSelector(
+                SP1Verifier.verifyProofInRoot.selector,
+                PROOF,
+                vkeyDigest,
+                PUBLIC_VALUES,
+                INPUT_DIGEST,
+                vkeyProof()
+            ),
+            SP1Verifier.VkeyDigestMismatch.selector
+        );
+    }
+
+    function vkeyProof() internal pure returns (bytes32[] memory siblings) {
+        siblings = new bytes32[]({VKEY_PROOF_LEN});{VKEY_PROOF}
+    }";
+
+/// An error generating a Solidity verifier or its test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Groth16SolidityError {
+    /// The verifying key is not a G1 point, three G2 points, then G1 points.
+    InvalidVerifyingKeyLength(usize),
+    /// The verifying key is not the one of a circuit with the public inputs of SP1.
+    PublicInputsMismatch { expected: usize, actual: usize },
+    /// The proof is not [GROTH16_PROOF_LEN] bytes long.
+    InvalidProofLength(usize),
+    /// The verifying key digest is not an element of the scalar field.
+    InvalidVkeyDigest,
+}
+
+impl fmt::Display for Groth16SolidityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidVerifyingKeyLength(len) => {
+                write!(f, "invalid verifying key length {}", len)
+            }
+            Self::PublicInputsMismatch { expected, actual } => write!(
+                f,
+                "expected a circuit with {} public inputs, got {}",
+                expected, actual
+            ),
+            Self::InvalidProofLength(len) => write!(
+                f,
+                "expected {} bytes for the proof, got {}",
+                GROTH16_PROOF_LEN, len
+            ),
+            Self::InvalidVkeyDigest => {
+                write!(f, "the verifying key digest is not in the scalar field")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Groth16SolidityError {}
+
+type Word = [u8; WORD_LEN];
+
+/// A Groth16 verifying key over BN254, as the big-endian words of its coordinates, with the G2
+/// points in the order of the EVM pairing precompile: `x.c1`, `x.c0`, `y.c1` then `y.c0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Groth16VerifyingKey {
+    pub alpha: [Word; 2],
+    pub beta: [Word; 4],
+    pub gamma: [Word; 4],
+    pub delta: [Word; 4],
+    /// The commitments to the constant input, then to each public input.
+    pub ic: Vec<[Word; 2]>,
+}
+
+impl Groth16VerifyingKey {
+    /// Decodes a verifying key from `alpha`, `beta`, `gamma`, `delta`, then the input commitments,
+    /// which is the encoding of `sp1_verifier_wasm::Groth16VerifyingKey::to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Groth16SolidityError> {
+        let header_len = 14 * WORD_LEN;
+        if bytes.len() < header_len || (bytes.len() - header_len) % (2 * WORD_LEN) != 0 {
+            return Err(Groth16SolidityError::InvalidVerifyingKeyLength(bytes.len()));
+        }
+        let words = bytes
+            .chunks(WORD_LEN)
+            .map(|word| word.try_into().unwrap())
+            .collect::<Vec<Word>>();
+        Ok(Self {
+            alpha: [words[0], words[1]],
+            beta: words[2..6].try_into().unwrap(),
+            gamma: words[6..10].try_into().unwrap(),
+            delta: words[10..14].try_into().unwrap(),
+            ic: words[14..]
+                .chunks(2)
+                .map(|point| [point[0], point[1]])
+                .collect(),
+        })
+    }
+
+    /// The number of public inputs of the circuit.
+    pub fn num_public_inputs(&self) -> usize {
+        self.ic.len().saturating_sub(1)
+    }
+}
+
+/// The public inputs of the wrapper circuit for the program of verifying key digest `vkey_digest`,
+/// its public values and the digest of its input: the verifying key digest itself, then the
+/// SHA-256 of the public values and the input digest, both with their top three bits cleared. This
+/// is what the `publicInputs` function of the contract computes.
+pub fn groth16_public_inputs(
+    vkey_digest: &[u8; 32],
+    public_values: &[u8],
+    input_digest: &[u8; 32],
+) -> Result<[[u8; 32]; 3], Groth16SolidityError> {
+    if *vkey_digest >= SCALAR_FIELD_MODULUS {
+        return Err(Groth16SolidityError::InvalidVkeyDigest);
+    }
+    let mut public_values_digest: [u8; 32] = Sha256::digest(public_values).into();
+    public_values_digest[0] &= 0b00011111;
+    let mut input_digest = *input_digest;
+    input_digest[0] &= 0b00011111;
+    Ok([*vkey_digest, public_values_digest, input_digest])
+}
+
+/// A proof of a program, which the generated foundry test checks the contract with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Groth16Fixture {
+    proof: Vec<u8>,
+    vkey_digest: [u8; 32],
+    public_values: Vec<u8>,
+    input_digest: [u8; 32],
+    vkey_proof: Vec<[u8; 32]>,
+}
+
+impl Groth16Fixture {
+    /// The fixture of `proof`, encoded as its `uint256[8]`, of the program of verifying key digest
+    /// `vkey_digest` with the public values `public_values` and the input digest `input_digest`.
+    pub fn new(
+        proof: Vec<u8>,
+        vkey_digest: [u8; 32],
+        public_values: Vec<u8>,
+        input_digest: [u8; 32],
+    ) -> Result<Self, Groth16SolidityError> {
+        if proof.len() != GROTH16_PROOF_LEN {
+            return Err(Groth16SolidityError::InvalidProofLength(proof.len()));
+        }
+        groth16_public_inputs(&vkey_digest, &public_values, &input_digest)?;
+        Ok(Self {
+            proof,
+            vkey_digest,
+            public_values,
+            input_digest,
+            vkey_proof: Vec::new(),
+        })
+    }
+
+    /// Sets the siblings of the verifying key digest in the tree of the root given to
+    /// [Groth16SolidityVerifier::with_vkey_root], from the leaf to the root.
+    pub fn with_vkey_proof(mut self, vkey_proof: Vec<[u8; 32]>) -> Self {
+        self.vkey_proof = vkey_proof;
+        self
+    }
+}
+
+/// The generator of the Solidity verifier of a Groth16 verifying key, see the module
+/// documentation.
+#[derive(Debug, Clone)]
+pub struct Groth16SolidityVerifier {
+    vk: Groth16VerifyingKey,
+    vkey_root: Option<[u8; 32]>,
+}
+
+impl Groth16SolidityVerifier {
+    /// The generator of the verifier of `vk`, which must have the public inputs of the wrapper
+    /// circuit.
+    pub fn new(vk: Groth16VerifyingKey) -> Result<Self, Groth16SolidityError> {
+        if vk.num_public_inputs() != NUM_PUBLIC_INPUTS {
+            return Err(Groth16SolidityError::PublicInputsMismatch {
+                expected: NUM_PUBLIC_INPUTS,
+                actual: vk.num_public_inputs(),
+            });
+        }
+        Ok(Self {
+            vk,
+            vkey_root: None,
+        })
+    }
+
+    /// Adds a `verifyProofInRoot` function to the contract, which also checks that the verifying
+    /// key digest is in the Merkle tree of root `vkey_root`, built like `sp1_sdk::bundle_root`
+    /// over the verifying key digests passed to the contract.
+    pub fn with_vkey_root(mut self, vkey_root: [u8; 32]) -> Self {
+        self.vkey_root = Some(vkey_root);
+        self
+    }
+
+    /// The source of `SP1Verifier.sol`.
+    pub fn contract(&self) -> String {
+        let vk = &self.vk;
+        let g2_points = [("BETA", vk.beta), ("GAMMA", vk.gamma), ("DELTA", vk.delta)];
+        let mut constants = vec![
+            ("ALPHA_X".to_string(), vk.alpha[0]),
+            ("ALPHA_Y".to_string(), vk.alpha[1]),
+        ];
+        for (name, point) in g2_points {
+            for (coordinate, word) in ["X_C1", "X_C0", "Y_C1", "Y_C0"].iter().zip(point) {
+                constants.push((format!("{}_{}", name, coordinate), word));
+            }
+        }
+        for (i, point) in vk.ic.iter().enumerate() {
+            constants.push((format!("IC{}_X", i), point[0]));
+            constants.push((format!("IC{}_Y", i), point[1]));
+        }
+
+        let (error, getter, function) = match self.vkey_root {
+            Some(root) => (
+                VKEY_ROOT_ERROR.to_string(),
+                VKEY_ROOT_GETTER.replace("{VKEY_ROOT}", &bytes32(&root)),
+                VKEY_ROOT_FUNCTION.to_string(),
+            ),
+            None => Default::default(),
+        };
+        let contract = CONTRACT_TEMPLATE
+            .replace("{SP1_CIRCUIT_VERSION}", SP1_CIRCUIT_VERSION)
+            .replace("{VKEY_ROOT_ERROR}", &error)
+            .replace("{VKEY_ROOT_GETTER}", &getter)
+            .replace("{VKEY_ROOT_FUNCTION}", &function);
+        constants
+            .into_iter()
+            .fold(contract, |contract, (name, word)| {
+                contract.replace(&format!("{{{}}}", name), &bytes32(&word))
+            })
+    }
+
+    /// The source of the foundry test `SP1Verifier.t.sol`, checking the contract against the proof
+    /// of `fixture`, and the verifying key digest against the root if there is one.
+    pub fn test_contract(&self, fixture: &Groth16Fixture) -> String {
+        let root_tests = match self.vkey_root {
+            Some(_) => {
+                let siblings = fixture
+                    .vkey_proof
+                    .iter()
+                    .enumerate()
+                    .map(|(i, sibling)| {
+                        format!("\n        siblings[{}] = {};", i, bytes32(sibling))
+                    })
+                    .collect::<String>();
+                VKEY_ROOT_TESTS
+                    .replace("{VKEY_PROOF_LEN}", &fixture.vkey_proof.len().to_string())
+                    .replace("{VKEY_PROOF}", &siblings)
+            }
+            None => String::new(),
+        };
+        TEST_TEMPLATE
+            .replace("{PROOF}", &hex::encode(&fixture.proof))
+            .replace("{VKEY_DIGEST}", &bytes32(&fixture.vkey_digest))
+            .replace("{PUBLIC_VALUES}", &hex::encode(&fixture.public_values))
+            .replace("{INPUT_DIGEST}", &bytes32(&fixture.input_digest))
+            .replace("{VKEY_ROOT_TESTS}", &root_tests)
+    }
+
+    /// Writes the contract to `src/SP1Verifier.sol` in `dir`. With a fixture, also writes its test
+    /// to `test/SP1Verifier.t.sol` and a `foundry.toml` if there is none, so that `forge test`
+    /// runs in `dir`.
+    pub fn export(&self, dir: &Path, fixture: Option<&Groth16Fixture>) -> io::Result<()> {
+        write_file(&dir.join("src").join("SP1Verifier.sol"), &self.contract())?;
+        if let Some(fixture) = fixture {
+            write_file(
+                &dir.join("test").join("SP1Verifier.t.sol"),
+                &self.test_contract(fixture),
+            )?;
+            let foundry_toml = dir.join("foundry.toml");
+            if !foundry_toml.exists() {
+                write_file(&foundry_toml, FOUNDRY_TOML)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A word as a Solidity hex literal.
+fn bytes32(word: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(word))
+}
+
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    /// The fixtures of `fixtures/groth16`, see the README there.
+    const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/groth16");
+
+    fn fixture_bytes(name: &str) -> Vec<u8> {
+        let path = Path::new(FIXTURES_DIR).join(name);
+        hex::decode(fs::read_to_string(path).unwrap().trim()).unwrap()
+    }
+
+    fn fixture_word(name: &str) -> [u8; 32] {
+        fixture_bytes(name).try_into().unwrap()
+    }
+
+    fn verifier() -> Groth16SolidityVerifier {
+        let vk = Groth16VerifyingKey::from_bytes(&fixture_bytes("vk.hex")).unwrap();
+        Groth16SolidityVerifier::new(vk).unwrap()
+    }
+
+    fn fixture() -> Groth16Fixture {
+        Groth16Fixture::new(
+            fixture_bytes("proof.hex"),
+            fixture_word("vkey_digest.hex"),
+            fixture_bytes("public_values.hex"),
+            fixture_word("input_digest.hex"),
+        )
+        .unwrap()
+        .with_vkey_proof(vec![fixture_word("vkey_proof.hex")])
+    }
+
+    /// Checks that `generated` matches the golden file `name`, or overwrites the golden file with
+    /// it if `SP1_UPDATE_GOLDEN` is set.
+    fn assert_golden(name: &str, generated: &str) {
+        let path = Path::new(FIXTURES_DIR).join(name);
+        if std::env::var("SP1_UPDATE_GOLDEN").is_ok() {
+            fs::write(&path, generated).unwrap();
+        }
+        let golden = fs::read_to_string(&path).unwrap();
+        assert!(
+            golden == generated,
+            "{} changed, regenerate it with SP1_UPDATE_GOLDEN=1 if the change is intended",
+            name
+        );
+    }
+
+    #[test]
+    fn test_golden_contracts() {
+        let verifier = verifier();
+        assert_golden("SP1Verifier.sol", &verifier.contract());
+        let verifier = verifier.with_vkey_root(fixture_word("vkey_root.hex"));
+        assert_golden("SP1VerifierWithRoot.sol", &verifier.contract());
+        assert_golden("SP1Verifier.t.sol", &verifier.test_contract(&fixture()));
+    }
+
+    #[test]
+    fn test_fixture_proof() {
+        let fixture = fixture();
+        let inputs = groth16_public_inputs(
+            &fixture.vkey_digest,
+            &fixture.public_values,
+            &fixture.input_digest,
+        )
+        .unwrap();
+        let vk = fixture_bytes("vk.hex");
+        sp1_verifier_wasm::try_verify_groth16(&fixture.proof, &inputs.concat(), &vk).unwrap();
+
+        let mut public_values = fixture.public_values.clone();
+        public_values.push(1);
+        let inputs =
+            groth16_public_inputs(&fixture.vkey_digest, &public_values, &fixture.input_digest)
+                .unwrap();
+        assert!(!sp1_verifier_wasm::verify_groth16(
+            &fixture.proof,
+            &inputs.concat(),
+            &vk
+        ));
+
+        let mut input_digest = fixture.input_digest;
+        input_digest[31] ^= 1;
+        let inputs =
+            groth16_public_inputs(&fixture.vkey_digest, &fixture.public_values, &input_digest)
+                .unwrap();
+        assert!(!sp1_verifier_wasm::verify_groth16(
+            &fixture.proof,
+            &inputs.concat(),
+            &vk
+        ));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let vk = fixture_bytes("vk.hex");
+        assert_eq!(
+            Groth16VerifyingKey::from_bytes(&vk[1..]).unwrap_err(),
+            Groth16SolidityError::InvalidVerifyingKeyLength(vk.len() - 1)
+        );
+        let two_inputs = Groth16VerifyingKey::from_bytes(&vk[..vk.len() - 2 * WORD_LEN]).unwrap();
+        assert_eq!(
+            Groth16SolidityVerifier::new(two_inputs).unwrap_err(),
+            Groth16SolidityError::PublicInputsMismatch {
+                expected: 3,
+                actual: 2
+            }
+        );
+
+        assert_eq!(
+            groth16_public_inputs(&SCALAR_FIELD_MODULUS, &[], &[0; 32]),
+            Err(Groth16SolidityError::InvalidVkeyDigest)
+        );
+        let proof = fixture_bytes("proof.hex");
+        assert_eq!(
+            Groth16Fixture::new(proof[1..].to_vec(), [0; 32], vec![], [0; 32]),
+            Err(Groth16SolidityError::InvalidProofLength(
+                GROTH16_PROOF_LEN - 1
+            ))
+        );
+    }
+
+    /// Runs the generated foundry test, when `forge` is installed.
+    #[test]
+    fn test_forge_fixture() {
+        if Command::new("forge").arg("--version").output().is_err() {
+            println!("forge is not installed, skipping the foundry test");
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let verifier = verifier().with_vkey_root(fixture_word("vkey_root.hex"));
+        verifier.export(dir.path(), Some(&fixture())).unwrap();
+        let status = Command::new("forge")
+            .arg("test")
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
@@ -1,9 +1,15 @@
 mod babybear;
 
+pub mod constraints;
 pub mod ffi;
 
+pub mod groth16_solidity;
 pub mod plonk_bn254;
+#[cfg(feature = "ultraplonk")]
+pub mod ultraplonk;
 pub mod witness;
 
+pub use constraints::JsonConstraintWriter;
+pub use groth16_solidity::*;
 pub use plonk_bn254::*;
 pub use witness::*;
@@ -1,11 +1,15 @@
 use std::{
     fs::File,
-    io::Write,
+    io::{self, BufWriter, Write},
     path::{Path, PathBuf},
 };
 
-use crate::ffi::{build_plonk_bn254, prove_plonk_bn254, test_plonk_bn254, verify_plonk_bn254};
+use crate::ffi::{
+    build_plonk_bn254, prove_plonk_bn254, test_plonk_bn254, verify_plonk_bn254,
+    verify_plonk_bn254_batch,
+};
 use crate::witness::GnarkWitness;
+use crate::JsonConstraintWriter;
 
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
@@ -13,7 +17,7 @@ use sha2::Digest;
 use sha2::Sha256;
 use sp1_core::SP1_CIRCUIT_VERSION;
 use sp1_recursion_compiler::{
-    constraints::Constraint,
+    constraints::{Constraint, ConstraintSink},
     ir::{Config, Witness},
 };
 
@@ -21,10 +25,101 @@ use sp1_recursion_compiler::{
 #[derive(Debug, Clone)]
 pub struct PlonkBn254Prover;
 
+/// The error returned by [PlonkBn254Prover::batch_verify], naming the first invalid proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlonkBn254BatchError {
+    /// The index of the proof in the batch.
+    pub index: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for PlonkBn254BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "proof {} failed to verify: {}", self.index, self.message)
+    }
+}
+
+impl std::error::Error for PlonkBn254BatchError {}
+
+/// The file of a build directory holding the constraints of the circuit.
+pub const CONSTRAINTS_FILE: &str = "constraints.json";
+
+/// The file recording how the artifacts of a build directory were built.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The options of [PlonkBn254Prover::build_with_opts].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildOpts {
+    /// Whether to generate the setup locally instead of using the one of the Aztec Ignition
+    /// ceremony. Development artifacts are INSECURE, and are refused by
+    /// [PlonkBn254Prover::check_production_artifacts].
+    pub dev: bool,
+    /// The seed the toxic waste of the development setup is derived from, so that builds with the
+    /// same seed produce the same keys and the proofs, calldata and verifier contracts made with
+    /// them can be checked in as test fixtures. Anyone knowing the seed can forge proofs, so it is
+    /// only allowed with `dev`.
+    pub dev_seed: Option<[u8; 32]>,
+}
+
+impl BuildOpts {
+    /// The options of a development build with a fresh setup.
+    pub fn dev() -> Self {
+        Self {
+            dev: true,
+            dev_seed: None,
+        }
+    }
+
+    /// The options of a reproducible development build, with a setup derived from `seed`.
+    pub fn dev_seeded(seed: [u8; 32]) -> Self {
+        Self {
+            dev: true,
+            dev_seed: Some(seed),
+        }
+    }
+}
+
+/// The manifest of a build directory, written to [MANIFEST_FILE] by
+/// [PlonkBn254Prover::build_with_opts].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub circuit_version: String,
+    /// Whether the setup was generated locally, see [BuildOpts::dev].
+    pub dev: bool,
+    /// The hex encoded seed of the setup, see [BuildOpts::dev_seed].
+    pub dev_seed: Option<String>,
+}
+
+/// The error returned when building or loading PLONK artifacts with the wrong [BuildOpts].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlonkBn254ArtifactsError {
+    /// A seeded setup was requested outside of development mode.
+    SeedWithoutDev,
+    /// The artifacts of the directory were built in development mode.
+    DevArtifacts(PathBuf),
+}
+
+impl std::fmt::Display for PlonkBn254ArtifactsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SeedWithoutDev => {
+                write!(f, "a seeded setup is insecure and requires a dev build")
+            }
+            Self::DevArtifacts(build_dir) => write!(
+                f,
+                "the artifacts in {} were built in development mode and are insecure",
+                build_dir.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlonkBn254ArtifactsError {}
+
 /// A zero-knowledge proof generated by the PLONK protocol with a Base64 encoded gnark PLONK proof.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PlonkBn254Proof {
-    pub public_inputs: [String; 2],
+    pub public_inputs: [String; 3],
     pub encoded_proof: String,
     pub raw_proof: String,
     pub plonk_vkey_hash: [u8; 32],
@@ -62,14 +157,64 @@ impl PlonkBn254Prover {
         );
     }
 
-    /// Builds the PLONK circuit locally.
+    /// Builds the PLONK circuit locally, in development mode if the directory name contains
+    /// `dev`.
     pub fn build<C: Config>(constraints: Vec<Constraint>, witness: Witness<C>, build_dir: PathBuf) {
-        let serialized = serde_json::to_string(&constraints).unwrap();
+        let opts = BuildOpts {
+            dev: build_dir.to_str().unwrap().contains("dev"),
+            dev_seed: None,
+        };
+        Self::build_with_opts(constraints, witness, build_dir, opts).unwrap();
+    }
+
+    /// Builds the PLONK circuit locally with the given options, and records them in the
+    /// [BuildManifest] of the directory.
+    pub fn build_with_opts<C: Config>(
+        constraints: Vec<Constraint>,
+        witness: Witness<C>,
+        build_dir: PathBuf,
+        opts: BuildOpts,
+    ) -> Result<(), PlonkBn254ArtifactsError> {
+        if opts.dev_seed.is_some() && !opts.dev {
+            return Err(PlonkBn254ArtifactsError::SeedWithoutDev);
+        }
 
         // Write constraints.
-        let constraints_path = build_dir.join("constraints.json");
-        let mut file = File::create(constraints_path).unwrap();
-        file.write_all(serialized.as_bytes()).unwrap();
+        let mut writer = Self::constraints_writer(&build_dir).unwrap();
+        for constraint in constraints {
+            writer.push(constraint);
+        }
+        writer.finish().unwrap();
+
+        Self::build_with_written_constraints(witness, build_dir, opts)
+    }
+
+    /// Creates the constraints file of a build directory, for the constraints of the circuit to be
+    /// streamed into with [sp1_recursion_compiler::constraints::ConstraintCompiler::emit_into]
+    /// before calling [PlonkBn254Prover::build_with_written_constraints].
+    pub fn constraints_writer(
+        build_dir: &Path,
+    ) -> io::Result<JsonConstraintWriter<BufWriter<File>>> {
+        let file = File::create(build_dir.join(CONSTRAINTS_FILE))?;
+        Ok(JsonConstraintWriter::new(BufWriter::new(file)))
+    }
+
+    /// Builds the PLONK circuit locally like [PlonkBn254Prover::build_with_opts], from the
+    /// constraints written with [PlonkBn254Prover::constraints_writer].
+    pub fn build_with_written_constraints<C: Config>(
+        witness: Witness<C>,
+        build_dir: PathBuf,
+        opts: BuildOpts,
+    ) -> Result<(), PlonkBn254ArtifactsError> {
+        if opts.dev_seed.is_some() && !opts.dev {
+            return Err(PlonkBn254ArtifactsError::SeedWithoutDev);
+        }
+        if let Some(seed) = opts.dev_seed {
+            log::warn!(
+                "building plonk bn254 artifacts with the seeded setup {}, do not use them in production",
+                hex::encode(seed)
+            );
+        }
 
         // Write witness.
         let witness_path = build_dir.join("witness.json");
@@ -78,7 +223,17 @@ impl PlonkBn254Prover {
         let serialized = serde_json::to_string(&gnark_witness).unwrap();
         file.write_all(serialized.as_bytes()).unwrap();
 
-        build_plonk_bn254(build_dir.to_str().unwrap());
+        let dev_seed = opts.dev_seed.map(hex::encode);
+        build_plonk_bn254(build_dir.to_str().unwrap(), opts.dev, dev_seed.as_deref());
+
+        // Write the manifest.
+        let manifest = BuildManifest {
+            circuit_version: SP1_CIRCUIT_VERSION.to_string(),
+            dev: opts.dev,
+            dev_seed,
+        };
+        let file = File::create(build_dir.join(MANIFEST_FILE)).unwrap();
+        serde_json::to_writer_pretty(file, &manifest).unwrap();
 
         // Write the corresponding asset files to the build dir.
         let sp1_mock_verifier_path = build_dir.join("SP1MockVerifier.sol");
@@ -107,6 +262,26 @@ impl PlonkBn254Prover {
         interface_sp1_verifier_file
             .write_all(interface_sp1_verifier_str.as_bytes())
             .unwrap();
+
+        Ok(())
+    }
+
+    /// Reads the [BuildManifest] of a build directory, if it has one. Artifacts installed from a
+    /// release have none, and are always production artifacts.
+    pub fn manifest(build_dir: &Path) -> Option<BuildManifest> {
+        let file = File::open(build_dir.join(MANIFEST_FILE)).ok()?;
+        Some(serde_json::from_reader(file).expect("invalid build manifest"))
+    }
+
+    /// Checks that the artifacts of a build directory were not built in development mode, which
+    /// production code must check before proving with them.
+    pub fn check_production_artifacts(build_dir: &Path) -> Result<(), PlonkBn254ArtifactsError> {
+        match Self::manifest(build_dir) {
+            Some(manifest) if manifest.dev => Err(PlonkBn254ArtifactsError::DevArtifacts(
+                build_dir.to_path_buf(),
+            )),
+            _ => Ok(()),
+        }
     }
 
     /// Generates a PLONK proof given a witness.
@@ -125,12 +300,14 @@ impl PlonkBn254Prover {
         proof
     }
 
-    /// Verify a PLONK proof and verify that the supplied vkey_hash and committed_values_digest match.
+    /// Verify a PLONK proof and verify that the supplied vkey_hash, committed_values_digest and
+    /// input_digest match.
     pub fn verify(
         &self,
         proof: &PlonkBn254Proof,
         vkey_hash: &BigUint,
         committed_values_digest: &BigUint,
+        input_digest: &BigUint,
         build_dir: &Path,
     ) {
         if proof.plonk_vkey_hash != Self::get_vkey_hash(build_dir) {
@@ -141,9 +318,60 @@ impl PlonkBn254Prover {
             &proof.raw_proof,
             &vkey_hash.to_string(),
             &committed_values_digest.to_string(),
+            &input_digest.to_string(),
         )
         .expect("failed to verify proof")
     }
+
+    /// Verifies a batch of PLONK proofs, each with its vkey hash, committed values digest and input
+    /// digest.
+    ///
+    /// With the `native` feature, the circuit verifying key is loaded once and the proofs are
+    /// verified concurrently. Each proof still gets its own pairing check, since gnark does not
+    /// expose the KZG openings that an aggregated check would combine.
+    pub fn batch_verify(
+        &self,
+        proofs: &[(PlonkBn254Proof, BigUint, BigUint, BigUint)],
+        build_dir: &Path,
+    ) -> Result<(), PlonkBn254BatchError> {
+        let circuit_vkey_hash = Self::get_vkey_hash(build_dir);
+        if let Some(index) = proofs
+            .iter()
+            .position(|(proof, _, _, _)| proof.plonk_vkey_hash != circuit_vkey_hash)
+        {
+            return Err(PlonkBn254BatchError {
+                index,
+                message: "proof vkey hash does not match circuit vkey hash".to_string(),
+            });
+        }
+
+        let public_inputs = proofs
+            .iter()
+            .map(|(_, vkey_hash, committed_values_digest, input_digest)| {
+                (
+                    vkey_hash.to_string(),
+                    committed_values_digest.to_string(),
+                    input_digest.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let batch = proofs
+            .iter()
+            .zip(public_inputs.iter())
+            .map(
+                |((proof, _, _, _), (vkey_hash, committed_values_digest, input_digest))| {
+                    (
+                        proof.raw_proof.as_str(),
+                        vkey_hash.as_str(),
+                        committed_values_digest.as_str(),
+                        input_digest.as_str(),
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+        verify_plonk_bn254_batch(build_dir.to_str().unwrap(), &batch)
+            .map_err(|(index, message)| PlonkBn254BatchError { index, message })
+    }
 }
 
 impl Default for PlonkBn254Prover {
@@ -151,3 +379,87 @@ impl Default for PlonkBn254Prover {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_field::AbstractField;
+    use sp1_recursion_compiler::{
+        config::OuterConfig,
+        constraints::ConstraintCompiler,
+        ir::{Builder, Config, Witness},
+    };
+
+    use super::*;
+
+    type N = <OuterConfig as Config>::N;
+
+    /// A circuit checking that its witness is one.
+    fn circuit() -> (Vec<Constraint>, Witness<OuterConfig>) {
+        let mut builder = Builder::<OuterConfig>::default();
+        let a = builder.witness_var();
+        builder.assert_var_eq(a, N::one());
+        let mut backend = ConstraintCompiler::<OuterConfig>::default();
+        let constraints = backend.emit(builder.operations);
+        let witness = Witness {
+            vars: vec![N::one()],
+            ..Default::default()
+        };
+        (constraints, witness)
+    }
+
+    #[test]
+    fn test_build_dev_seeded() {
+        let seed = [7; 32];
+        let mut vks = Vec::new();
+        for _ in 0..2 {
+            let build_dir = tempfile::tempdir().unwrap();
+            let (constraints, witness) = circuit();
+            PlonkBn254Prover::build_with_opts(
+                constraints,
+                witness,
+                build_dir.path().to_path_buf(),
+                BuildOpts::dev_seeded(seed),
+            )
+            .unwrap();
+            vks.push(std::fs::read(build_dir.path().join("vk.bin")).unwrap());
+
+            assert_eq!(
+                PlonkBn254Prover::manifest(build_dir.path()),
+                Some(BuildManifest {
+                    circuit_version: SP1_CIRCUIT_VERSION.to_string(),
+                    dev: true,
+                    dev_seed: Some(hex::encode(seed)),
+                })
+            );
+            assert_eq!(
+                PlonkBn254Prover::check_production_artifacts(build_dir.path()),
+                Err(PlonkBn254ArtifactsError::DevArtifacts(
+                    build_dir.path().to_path_buf()
+                ))
+            );
+        }
+        assert_eq!(vks[0], vks[1]);
+    }
+
+    #[test]
+    fn test_build_seed_requires_dev() {
+        let build_dir = tempfile::tempdir().unwrap();
+        let (constraints, witness) = circuit();
+        let opts = BuildOpts {
+            dev: false,
+            dev_seed: Some([7; 32]),
+        };
+        assert_eq!(
+            PlonkBn254Prover::build_with_opts(
+                constraints,
+                witness,
+                build_dir.path().to_path_buf(),
+                opts
+            ),
+            Err(PlonkBn254ArtifactsError::SeedWithoutDev)
+        );
+        assert!(PlonkBn254Prover::manifest(build_dir.path()).is_none());
+        // Directories without a manifest hold released artifacts.
+        assert!(PlonkBn254Prover::check_production_artifacts(build_dir.path()).is_ok());
+    }
+}
@@ -0,0 +1,98 @@
+//! The export of the circuit to UltraPlonk and Honk provers, an alternative to the gnark PLONK
+//! circuit for the verifiers of these proof systems, see
+//! [sp1_recursion_compiler::constraints::ultraplonk].
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use sp1_recursion_compiler::config::OuterConfig;
+use sp1_recursion_compiler::constraints::Constraint;
+use sp1_recursion_compiler::ir::Witness;
+
+pub use sp1_recursion_compiler::constraints::ultraplonk::*;
+
+/// The file of the [UltraPlonkCircuit] in a build directory.
+pub const ULTRAPLONK_CIRCUIT_FILE: &str = "ultraplonk_circuit.json";
+
+/// The file of the [UltraPlonkWitness] in a build directory.
+pub const ULTRAPLONK_WITNESS_FILE: &str = "ultraplonk_witness.json";
+
+/// Exports the circuit of `constraints` and its witness for `witness` to the files
+/// [ULTRAPLONK_CIRCUIT_FILE] and [ULTRAPLONK_WITNESS_FILE] of `build_dir`.
+pub fn write_ultraplonk(
+    constraints: &[Constraint],
+    witness: &Witness<OuterConfig>,
+    build_dir: &Path,
+) -> anyhow::Result<()> {
+    let (circuit, witness) = export_ultraplonk(constraints, witness)?;
+    write_json(&build_dir.join(ULTRAPLONK_CIRCUIT_FILE), &circuit)?;
+    write_json(&build_dir.join(ULTRAPLONK_WITNESS_FILE), &witness)
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, value)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use p3_field::AbstractField;
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+
+    /// The fixtures of `fixtures/ultraplonk`, see the README there.
+    const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/ultraplonk");
+
+    fn fixture<T: DeserializeOwned>(name: &str) -> T {
+        let path = Path::new(FIXTURES_DIR).join(name);
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn reference_witness() -> Witness<OuterConfig> {
+        Witness {
+            vars: vec![AbstractField::from_canonical_u32(5)],
+            vkey_hash: AbstractField::from_canonical_u32(28),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reference_circuit() {
+        let constraints: Vec<Constraint> = fixture("reference_constraints.json");
+        let (circuit, witness) = export_ultraplonk(&constraints, &reference_witness()).unwrap();
+        circuit.check(&witness).unwrap();
+
+        let expected_circuit: UltraPlonkCircuit = fixture("reference_circuit.json");
+        let expected_witness: UltraPlonkWitness = fixture("reference_witness.json");
+        assert_eq!(circuit, expected_circuit);
+        assert_eq!(witness, expected_witness);
+    }
+
+    #[test]
+    fn test_write_ultraplonk() {
+        let constraints: Vec<Constraint> = fixture("reference_constraints.json");
+        let dir = tempfile::tempdir().unwrap();
+        write_ultraplonk(&constraints, &reference_witness(), dir.path()).unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        let circuit: UltraPlonkCircuit =
+            serde_json::from_str(&read(ULTRAPLONK_CIRCUIT_FILE)).unwrap();
+        let witness: UltraPlonkWitness =
+            serde_json::from_str(&read(ULTRAPLONK_WITNESS_FILE)).unwrap();
+        assert_eq!(circuit, fixture("reference_circuit.json"));
+        assert_eq!(witness, fixture("reference_witness.json"));
+
+        // A witness the circuit doesn't accept gives values which don't satisfy it.
+        let mut witness = reference_witness();
+        witness.vkey_hash = AbstractField::from_canonical_u32(29);
+        let (circuit, witness) = export_ultraplonk(&constraints, &witness).unwrap();
+        assert_eq!(circuit.check(&witness), Err(UltraPlonkCheckError::Gate(4)));
+    }
+}
@@ -17,6 +17,7 @@ pub struct GnarkWitness {
     pub exts: Vec<Vec<String>>,
     pub vkey_hash: String,
     pub commited_values_digest: String,
+    pub input_digest: String,
 }
 
 impl GnarkWitness {
@@ -51,6 +52,7 @@ impl GnarkWitness {
                 .commited_values_digest
                 .as_canonical_biguint()
                 .to_string(),
+            input_digest: witness.input_digest.as_canonical_biguint().to_string(),
         }
     }
 
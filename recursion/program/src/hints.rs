@@ -9,8 +9,8 @@ use sp1_core::stark::{
     AirOpenedValues, ChipOpenedValues, Com, RiscvAir, ShardCommitment, ShardOpenedValues,
 };
 use sp1_core::utils::{
-    BabyBearPoseidon2, InnerChallenge, InnerDigest, InnerDigestHash, InnerPcsProof, InnerPerm,
-    InnerVal,
+    BabyBearPoseidon2, FirstWitnessChallenger, InnerChallenge, InnerDigest, InnerDigestHash,
+    InnerPcsProof, InnerPerm, InnerVal,
 };
 use sp1_recursion_compiler::{
     config::InnerConfig,
@@ -24,7 +24,7 @@ use crate::fri::TwoAdicMultiplicativeCosetVariable;
 use crate::machine::*;
 use crate::stark::{ShardProofHint, VerifyingKeyHint};
 use crate::types::{
-    AirOpenedValuesVariable, ChipOpenedValuesVariable, Sha256DigestVariable,
+    AirOpenedValuesVariable, ChipOpenedValuesVariable, MachineShapeVariable, Sha256DigestVariable,
     ShardCommitmentVariable, ShardOpenedValuesVariable, ShardProofVariable, VerifyingKeyVariable,
 };
 use crate::types::{QuotientData, QuotientDataValues};
@@ -419,6 +419,18 @@ impl Hintable<C> for DuplexChallenger<InnerVal, InnerPerm, 16, 8> {
     }
 }
 
+impl Hintable<C> for FirstWitnessChallenger<DuplexChallenger<InnerVal, InnerPerm, 16, 8>> {
+    type HintVariable = DuplexChallengerVariable<C>;
+
+    fn read(builder: &mut Builder<C>) -> Self::HintVariable {
+        DuplexChallenger::<InnerVal, InnerPerm, 16, 8>::read(builder)
+    }
+
+    fn write(&self) -> Vec<Vec<Block<<C as Config>::F>>> {
+        self.0.write()
+    }
+}
+
 impl<
         'a,
         SC: StarkGenericConfig<
@@ -551,6 +563,51 @@ impl<'a, A: MachineAir<BabyBear>> Hintable<C>
     }
 }
 
+impl Hintable<C> for MachineShape {
+    type HintVariable = MachineShapeVariable<C>;
+
+    fn read(builder: &mut Builder<C>) -> Self::HintVariable {
+        let chip_mask = Vec::<usize>::read(builder);
+        let max_log_degrees = Vec::<usize>::read(builder);
+        MachineShapeVariable {
+            chip_mask,
+            max_log_degrees,
+        }
+    }
+
+    fn write(&self) -> Vec<Vec<Block<<C as Config>::F>>> {
+        let chip_mask = self
+            .chip_mask
+            .iter()
+            .map(|&included| included as usize)
+            .collect::<Vec<_>>();
+
+        let mut stream = Vec::new();
+        stream.extend(chip_mask.write());
+        stream.extend(self.max_log_degrees.write());
+        stream
+    }
+}
+
+impl<'a, A: MachineAir<BabyBear>> Hintable<C>
+    for SP1UniversalMemoryLayout<'a, BabyBearPoseidon2, A>
+{
+    type HintVariable = SP1UniversalMemoryLayoutVariable<C>;
+
+    fn read(builder: &mut Builder<C>) -> Self::HintVariable {
+        let core = SP1RecursionMemoryLayout::<'a, BabyBearPoseidon2, A>::read(builder);
+        let shape = MachineShape::read(builder);
+        SP1UniversalMemoryLayoutVariable { core, shape }
+    }
+
+    fn write(&self) -> Vec<Vec<Block<<C as Config>::F>>> {
+        let mut stream = Vec::new();
+        stream.extend(self.core.write());
+        stream.extend(self.shape.write());
+        stream
+    }
+}
+
 impl<'a, A: MachineAir<BabyBear>> Hintable<C> for SP1ReduceMemoryLayout<'a, BabyBearPoseidon2, A> {
     type HintVariable = SP1ReduceMemoryLayoutVariable<C>;
 
@@ -631,11 +688,14 @@ impl<'a, A: MachineAir<BabyBear>> Hintable<C>
 
         let sp1_vk = VerifyingKeyHint::<'a, BabyBearPoseidon2, RiscvAir<_>>::read(builder);
         let committed_value_digest = Vec::<Vec<InnerVal>>::read(builder);
+        let input_digest = Vec::<Vec<InnerVal>>::read(builder);
+        let config_digest = Vec::<Vec<InnerVal>>::read(builder);
         let deferred_proofs_digest = Vec::<InnerVal>::read(builder);
         let leaf_challenger = DuplexChallenger::<InnerVal, InnerPerm, 16, 8>::read(builder);
         let end_pc = InnerVal::read(builder);
         let end_shard = InnerVal::read(builder);
         let total_core_shards = builder.hint_var();
+        let features = InnerVal::read(builder);
 
         SP1DeferredMemoryLayoutVariable {
             compress_vk,
@@ -644,11 +704,14 @@ impl<'a, A: MachineAir<BabyBear>> Hintable<C>
             is_complete,
             sp1_vk,
             committed_value_digest,
+            input_digest,
+            config_digest,
             deferred_proofs_digest,
             leaf_challenger,
             end_pc,
             end_shard,
             total_core_shards,
+            features,
         }
     }
 
@@ -672,6 +735,16 @@ impl<'a, A: MachineAir<BabyBear>> Hintable<C>
             .iter()
             .map(|w| w.0.to_vec())
             .collect::<Vec<_>>();
+        let input_digest = self
+            .input_digest
+            .iter()
+            .map(|w| w.0.to_vec())
+            .collect::<Vec<_>>();
+        let config_digest = self
+            .config_digest
+            .iter()
+            .map(|w| w.0.to_vec())
+            .collect::<Vec<_>>();
 
         stream.extend(compress_vk_hint.write());
         stream.extend(proof_hints.write());
@@ -680,11 +753,14 @@ impl<'a, A: MachineAir<BabyBear>> Hintable<C>
 
         stream.extend(sp1_vk_hint.write());
         stream.extend(committed_value_digest.write());
+        stream.extend(input_digest.write());
+        stream.extend(config_digest.write());
         stream.extend(self.deferred_proofs_digest.write());
         stream.extend(self.leaf_challenger.write());
         stream.extend(self.end_pc.write());
         stream.extend(self.end_shard.write());
         stream.extend(self.total_core_shards.write());
+        stream.extend(self.features.write());
 
         stream
     }
@@ -17,7 +17,9 @@ use sp1_core::utils::BabyBearPoseidon2;
 use sp1_recursion_compiler::config::InnerConfig;
 use sp1_recursion_compiler::ir::{Array, Builder, Config, Felt, Var};
 use sp1_recursion_compiler::prelude::DslVariable;
-use sp1_recursion_core::air::{RecursionPublicValues, RECURSIVE_PROOF_NUM_PV_ELTS};
+use sp1_recursion_core::air::{
+    ChallengerPublicValues, RecursionPublicValues, RECURSIVE_PROOF_NUM_PV_ELTS,
+};
 use sp1_recursion_core::runtime::{RecursionProgram, D, DIGEST_SIZE};
 
 use sp1_recursion_compiler::prelude::*;
@@ -33,7 +35,9 @@ use crate::utils::{
     get_challenger_public_values, hash_vkey, var2felt,
 };
 
-use super::utils::{commit_public_values, proof_data_from_vk, verify_public_values_hash};
+use super::utils::{
+    commit_public_values, proof_data_from_vk, range_check_public_values, verify_public_values_hash,
+};
 
 /// A program to verify a batch of recursive proofs and aggregate their public values.
 #[derive(Debug, Clone, Copy)]
@@ -42,7 +46,7 @@ pub struct SP1CompressVerifier<C: Config, SC: StarkGenericConfig, A> {
 }
 
 /// The different types of programs that can be verified by the `SP1ReduceVerifier`.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ReduceProgramType {
     /// A batch of proofs that are all SP1 Core proofs.
     Core = 0,
@@ -100,7 +104,9 @@ where
 
         builder.halt();
 
-        builder.compile_program()
+        let mut program = builder.compile_program();
+        program.verified_fingerprint = Some(machine.fingerprint());
+        program
     }
 }
 
@@ -156,8 +162,9 @@ where
         // Compute the digest of compress_vk and input the value to the public values.
         let compress_vk_digest = hash_vkey(builder, &compress_vk);
 
-        reduce_public_values.compress_vk_digest =
+        let compress_vk_digest_values: [Felt<_>; DIGEST_SIZE] =
             array::from_fn(|i| builder.get(&compress_vk_digest, i));
+        reduce_public_values.compress_vk_digest = compress_vk_digest_values;
 
         // Assert that there is at least one proof.
         builder.assert_usize_ne(shard_proofs.len(), 0);
@@ -173,8 +180,13 @@ where
         let mut leaf_challenger = DuplexChallengerVariable::new(builder);
         let committed_value_digest: [Word<Felt<_>>; PV_DIGEST_NUM_WORDS] =
             array::from_fn(|_| Word(array::from_fn(|_| builder.uninit())));
+        let input_digest: [Word<Felt<_>>; PV_DIGEST_NUM_WORDS] =
+            array::from_fn(|_| Word(array::from_fn(|_| builder.uninit())));
+        let config_digest: [Word<Felt<_>>; PV_DIGEST_NUM_WORDS] =
+            array::from_fn(|_| Word(array::from_fn(|_| builder.uninit())));
         let deferred_proofs_digest: [Felt<_>; POSEIDON_NUM_WORDS] =
             array::from_fn(|_| builder.uninit());
+        let features: Felt<_> = builder.uninit();
         let reconstruct_deferred_digest: [Felt<_>; POSEIDON_NUM_WORDS] =
             core::array::from_fn(|_| builder.uninit());
         let cumulative_sum: [Felt<_>; D] = core::array::from_fn(|_| builder.eval(C::F::zero()));
@@ -183,11 +195,30 @@ where
         let recursive_vk_variable = proof_data_from_vk(builder, recursive_vk, machine);
         let deferred_vk_variable = proof_data_from_vk(builder, deferred_vk, machine);
 
+        // The challenger of a proof starts by observing the verifying key of its kind, so observe
+        // each key once for the node and start the challengers of the proofs from the result.
+        let recursive_vk_challenger = vk_challenger_values(builder, &recursive_vk_variable);
+        let deferred_vk_challenger = vk_challenger_values(builder, &deferred_vk_variable);
+        let compress_vk_challenger = vk_challenger_values(builder, &compress_vk);
+
         // Get field values for the proof kind.
         let core_kind = C::N::from_canonical_u32(ReduceProgramType::Core as u32);
         let deferred_kind = C::N::from_canonical_u32(ReduceProgramType::Deferred as u32);
         let reduce_kind = C::N::from_canonical_u32(ReduceProgramType::Reduce as u32);
 
+        // Initialize the inputs of the reduce plan digest: `kind || arity || child digests`.
+        let header_slots: Var<_> = builder.constant(C::N::two());
+        let digest_size: Var<_> = builder.constant(C::N::from_canonical_usize(DIGEST_SIZE));
+        let digest_slots: Var<_> = builder.eval(shard_proofs.len() * DIGEST_SIZE);
+        let plan_inputs_len: Var<_> = builder.eval(header_slots + digest_slots);
+        let mut reduce_plan_inputs = builder.dyn_array(plan_inputs_len);
+        let reduce_kind_felt: Felt<_> =
+            builder.eval(C::F::from_canonical_u32(ReduceProgramType::Reduce as u32));
+        builder.set(&mut reduce_plan_inputs, 0, reduce_kind_felt);
+        let num_proofs = shard_proofs.len().materialize(builder);
+        let num_proofs_felt = var2felt(builder, num_proofs);
+        builder.set(&mut reduce_plan_inputs, 1, num_proofs_felt);
+
         // Verify the shard proofs and connect the values.
         builder.range(0, shard_proofs.len()).for_each(|i, builder| {
             // Load the proof.
@@ -197,23 +228,40 @@ where
 
             // Verify the shard proof.
 
-            // Initialize values for verifying key and proof data.
+            // Initialize values for verifying key, proof data and the challenger having observed
+            // the verifying key.
             let vk: VerifyingKeyVariable<_> = builder.uninit();
+            let mut challenger = DuplexChallengerVariable::new(builder);
             // Set the correct value given the value of kind, and assert it must be one of the
             // valid values. We can do that by nested `if-else` statements.
             builder.if_eq(kind, core_kind).then_or_else(
                 |builder| {
                     builder.assign(vk.clone(), recursive_vk_variable.clone());
+                    assign_challenger_from_pv(
+                        builder,
+                        &mut challenger.clone(),
+                        recursive_vk_challenger,
+                    );
                 },
                 |builder| {
                     builder.if_eq(kind, deferred_kind).then_or_else(
                         |builder| {
                             builder.assign(vk.clone(), deferred_vk_variable.clone());
+                            assign_challenger_from_pv(
+                                builder,
+                                &mut challenger.clone(),
+                                deferred_vk_challenger,
+                            );
                         },
                         |builder| {
                             builder.if_eq(kind, reduce_kind).then_or_else(
                                 |builder| {
                                     builder.assign(vk.clone(), compress_vk.clone());
+                                    assign_challenger_from_pv(
+                                        builder,
+                                        &mut challenger.clone(),
+                                        compress_vk_challenger,
+                                    );
                                 },
                                 |builder| {
                                     // If the kind is not one of the valid values, raise
@@ -228,11 +276,6 @@ where
 
             // Verify the shard proof given the correct data.
 
-            // Prepare a challenger.
-            let mut challenger = DuplexChallengerVariable::new(builder);
-            // Observe the vk and start pc.
-            challenger.observe(builder, vk.commitment.clone());
-            challenger.observe(builder, vk.pc_start);
             // Observe the main commitment and public values.
             challenger.observe(builder, proof.commitment.main_commit.clone());
             for j in 0..machine.num_pv_elts() {
@@ -262,6 +305,24 @@ where
             // Check that the public values digest is correct.
             verify_public_values_hash(builder, current_public_values);
 
+            // Deferred and reduce proofs verified their own proofs with the compress key, which
+            // must be the key of this node, so that a single key digest is propagated upward.
+            builder.if_ne(kind, core_kind).then(|builder| {
+                for (digest, current) in compress_vk_digest_values
+                    .iter()
+                    .zip_eq(current_public_values.compress_vk_digest.iter())
+                {
+                    builder.assert_felt_eq(*digest, *current);
+                }
+            });
+
+            // Add the reduce plan digest of the proof to the inputs of this node's digest.
+            let plan_offset: Var<_> = builder.eval(header_slots + i * digest_size);
+            for (j, element) in current_public_values.reduce_plan_digest.iter().enumerate() {
+                let index: Var<_> = builder.eval(plan_offset + C::N::from_canonical_usize(j));
+                builder.set(&mut reduce_plan_inputs, index, *element);
+            }
+
             // If the proof is the first proof, initialize the values.
             builder.if_eq(i, C::N::zero()).then(|builder| {
                 // Initialize global and accumulated values.
@@ -320,7 +381,10 @@ where
                     current_public_values.start_reconstruct_challenger,
                 );
 
-                // Assign the commited values and deferred proof digests.
+                // Assign the commited values, input and deferred proof digests. The digests of the
+                // other proofs are asserted equal to these, so only the first ones are range
+                // checked.
+                range_check_public_values(builder, current_public_values);
                 for (word, current_word) in committed_value_digest
                     .iter()
                     .zip_eq(current_public_values.committed_value_digest.iter())
@@ -330,6 +394,24 @@ where
                     }
                 }
 
+                for (word, current_word) in input_digest
+                    .iter()
+                    .zip_eq(current_public_values.input_digest.iter())
+                {
+                    for (byte, current_byte) in word.0.iter().zip_eq(current_word.0.iter()) {
+                        builder.assign(*byte, *current_byte);
+                    }
+                }
+
+                for (word, current_word) in config_digest
+                    .iter()
+                    .zip_eq(current_public_values.config_digest.iter())
+                {
+                    for (byte, current_byte) in word.0.iter().zip_eq(current_word.0.iter()) {
+                        builder.assign(*byte, *current_byte);
+                    }
+                }
+
                 for (digest, current_digest) in deferred_proofs_digest
                     .iter()
                     .zip_eq(current_public_values.deferred_proofs_digest.iter())
@@ -337,6 +419,8 @@ where
                     builder.assign(*digest, *current_digest);
                 }
 
+                builder.assign(features, current_public_values.features);
+
                 // Initialize the start reconstruct deferred digest.
                 for (digest, first_digest, global_digest) in izip!(
                     reconstruct_deferred_digest.iter(),
@@ -401,6 +485,26 @@ where
                 }
             }
 
+            // Assert that the input digests are the same.
+            for (word, current_word) in input_digest
+                .iter()
+                .zip_eq(current_public_values.input_digest.iter())
+            {
+                for (byte, current_byte) in word.0.iter().zip_eq(current_word.0.iter()) {
+                    builder.assert_felt_eq(*byte, *current_byte);
+                }
+            }
+
+            // Assert that the config digests are the same.
+            for (word, current_word) in config_digest
+                .iter()
+                .zip_eq(current_public_values.config_digest.iter())
+            {
+                for (byte, current_byte) in word.0.iter().zip_eq(current_word.0.iter()) {
+                    builder.assert_felt_eq(*byte, *current_byte);
+                }
+            }
+
             // Assert that the deferred proof digests are the same.
             for (digest, current_digest) in deferred_proofs_digest
                 .iter()
@@ -415,6 +519,9 @@ where
                 current_public_values.total_core_shards,
             );
 
+            // Assert that the features are the same.
+            builder.assert_felt_eq(features, current_public_values.features);
+
             // Update the accumulated values.
 
             // Update the deferred proof digest.
@@ -469,10 +576,21 @@ where
         reduce_public_values.deferred_proofs_digest = deferred_proofs_digest;
         // Assign the committed value digests.
         reduce_public_values.committed_value_digest = committed_value_digest;
+        // Assign the input digests.
+        reduce_public_values.input_digest = input_digest;
+        // Assign the config digests.
+        reduce_public_values.config_digest = config_digest;
         // Assign the cumulative sum.
         reduce_public_values.cumulative_sum = cumulative_sum;
         // Assign the total number of shards.
         reduce_public_values.total_core_shards = total_core_shards_felt;
+        // Assign the features.
+        reduce_public_values.features = features;
+        // Assign the digest of the reduce plan, binding the kinds and arities of all the nodes
+        // below this one.
+        let reduce_plan_digest = builder.poseidon2_hash(&reduce_plan_inputs);
+        reduce_public_values.reduce_plan_digest =
+            array::from_fn(|i| builder.get(&reduce_plan_digest, i));
 
         // If the proof is complete, make completeness assertions and set the flag. Otherwise, check
         // the flag is zero and set the public value to zero.
@@ -490,3 +608,87 @@ where
         commit_public_values(builder, reduce_public_values);
     }
 }
+
+/// The state of a fresh challenger after observing `vk`, which only depends on the key.
+fn vk_challenger_values<C: Config>(
+    builder: &mut Builder<C>,
+    vk: &VerifyingKeyVariable<C>,
+) -> ChallengerPublicValues<Felt<C::F>> {
+    let mut challenger = DuplexChallengerVariable::new(builder);
+    challenger.observe(builder, vk.clone());
+    get_challenger_public_values(builder, &challenger)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_challenger::{CanObserve, CanSample};
+    use sp1_core::stark::{Challenge, StarkGenericConfig};
+    use sp1_recursion_core::runtime::Runtime;
+
+    use crate::challenger::CanSampleVariable;
+
+    use super::*;
+
+    /// The cycles taken to set up the challengers of `num_proofs` proofs, observing their
+    /// verifying key either once for all of them or once for each of them.
+    fn challenger_setup_cycles(num_proofs: usize, observe_vk_once: bool) -> usize {
+        type SC = BabyBearPoseidon2;
+        type F = BabyBear;
+        type EF = Challenge<SC>;
+
+        let config = SC::default();
+        let commit: [F; DIGEST_SIZE] = array::from_fn(|i| F::from_canonical_usize(i + 1));
+        let pc_start = F::from_canonical_u32(0x200000);
+        let value = F::from_canonical_u32(7);
+        let mut challenger = config.challenger();
+        commit
+            .iter()
+            .for_each(|element| challenger.observe(*element));
+        challenger.observe(pc_start);
+        challenger.observe(value);
+        let expected: F = challenger.sample();
+
+        let mut builder = Builder::<InnerConfig>::default();
+        let mut commitment: Array<_, Felt<_>> = builder.dyn_array(DIGEST_SIZE);
+        for (i, element) in commit.iter().enumerate() {
+            builder.set(&mut commitment, i, *element);
+        }
+        let vk = VerifyingKeyVariable {
+            commitment,
+            pc_start: builder.eval(pc_start),
+            preprocessed_sorted_idxs: builder.dyn_array(0),
+            prep_domains: builder.dyn_array(0),
+        };
+        let vk_challenger = observe_vk_once.then(|| vk_challenger_values(&mut builder, &vk));
+        let expected: Felt<_> = builder.eval(expected);
+        builder.range(0, num_proofs).for_each(|_, builder| {
+            let mut challenger = DuplexChallengerVariable::new(builder);
+            match vk_challenger {
+                Some(values) => assign_challenger_from_pv(builder, &mut challenger, values),
+                None => challenger.observe(builder, vk.clone()),
+            }
+            let value: Felt<_> = builder.eval(value);
+            challenger.observe(builder, value);
+            let sample = challenger.sample(builder);
+            builder.assert_felt_eq(sample, expected);
+        });
+        builder.halt();
+
+        let program = builder.compile_program();
+        let mut runtime = Runtime::<F, EF, _>::new(&program, config.perm.clone());
+        runtime.run();
+        runtime.timestamp
+    }
+
+    #[test]
+    fn test_observe_vk_once_per_node() {
+        // The challengers of the proofs are the same either way, which the program asserts, but
+        // observing the key once costs less for every proof of the node.
+        let per_proof_cycles = |observe_vk_once| {
+            challenger_setup_cycles(5, observe_vk_once)
+                - challenger_setup_cycles(1, observe_vk_once)
+        };
+        assert!(per_proof_cycles(true) < per_proof_cycles(false));
+        assert!(challenger_setup_cycles(2, true) < challenger_setup_cycles(2, false));
+    }
+}
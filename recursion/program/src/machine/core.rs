@@ -24,10 +24,17 @@ use crate::fri::TwoAdicFriPcsVariable;
 use crate::hints::Hintable;
 use crate::stark::StarkVerifier;
 use crate::types::ShardProofVariable;
-use crate::types::VerifyingKeyVariable;
-use crate::utils::{const_fri_config, felt2var, get_challenger_public_values, hash_vkey, var2felt};
-
-use super::utils::{assert_complete, commit_public_values};
+use crate::types::{MachineShapeVariable, VerifyingKeyVariable};
+use crate::utils::{
+    const_fri_config, felt2var, get_challenger_public_values, hash_runtime_vkey, hash_vkey,
+    var2felt,
+};
+
+use super::utils::{
+    assert_complete, assert_shard_transition, commit_public_values, range_check_words,
+    reduce_plan_leaf_digest,
+};
+use super::ReduceProgramType;
 
 /// A program for recursively verifying a batch of SP1 proofs.
 #[derive(Debug, Clone, Copy)]
@@ -64,6 +71,15 @@ impl SP1RecursiveVerifier<InnerConfig, BabyBearPoseidon2> {
     pub fn build(
         machine: &StarkMachine<BabyBearPoseidon2, RiscvAir<BabyBear>>,
     ) -> RecursionProgram<BabyBear> {
+        let mut program = Self::builder(machine).compile_program();
+        program.verified_fingerprint = Some(machine.fingerprint());
+        program
+    }
+
+    /// The operations of the program, before compilation.
+    pub(crate) fn builder(
+        machine: &StarkMachine<BabyBearPoseidon2, RiscvAir<BabyBear>>,
+    ) -> Builder<InnerConfig> {
         let mut builder = Builder::<InnerConfig>::default();
 
         let input: SP1RecursionMemoryLayoutVariable<_> = builder.uninit();
@@ -76,7 +92,7 @@ impl SP1RecursiveVerifier<InnerConfig, BabyBearPoseidon2> {
 
         builder.halt();
 
-        builder.compile_program()
+        builder
     }
 }
 
@@ -126,6 +142,21 @@ where
         pcs: &TwoAdicFriPcsVariable<C>,
         machine: &StarkMachine<SC, RiscvAir<SC::Val>>,
         input: SP1RecursionMemoryLayoutVariable<C>,
+    ) {
+        Self::verify_with_shape(builder, pcs, machine, input, None)
+    }
+
+    /// Verify a batch of SP1 shard proofs of a machine described by a witnessed `shape`, see
+    /// [StarkVerifier::verify_shard_with_shape].
+    ///
+    /// With a shape, the committed `sp1_vk_digest` is the digest of the verifying key and the
+    /// shape computed by [hash_runtime_vkey], which tells proofs of different shapes apart.
+    pub fn verify_with_shape(
+        builder: &mut Builder<C>,
+        pcs: &TwoAdicFriPcsVariable<C>,
+        machine: &StarkMachine<SC, RiscvAir<SC::Val>>,
+        input: SP1RecursionMemoryLayoutVariable<C>,
+        shape: Option<&MachineShapeVariable<C>>,
     ) {
         // Read input.
         let SP1RecursionMemoryLayoutVariable {
@@ -145,10 +176,14 @@ where
         // Start and end shard indices.
         let initial_shard: Felt<_> = builder.uninit();
 
-        // The commited values digest and deferred proof digest. These will be checked to be the
-        // same for all proofs.
+        // The commited values digest, input digest and deferred proof digest. These will be
+        // checked to be the same for all proofs.
         let committed_value_digest: [Word<Felt<_>>; PV_DIGEST_NUM_WORDS] =
             array::from_fn(|_| Word(array::from_fn(|_| builder.uninit())));
+        let input_digest: [Word<Felt<_>>; PV_DIGEST_NUM_WORDS] =
+            array::from_fn(|_| Word(array::from_fn(|_| builder.uninit())));
+        let config_digest: [Word<Felt<_>>; PV_DIGEST_NUM_WORDS] =
+            array::from_fn(|_| Word(array::from_fn(|_| builder.uninit())));
         let deferred_proofs_digest: [Felt<_>; POSEIDON_NUM_WORDS] =
             array::from_fn(|_| builder.uninit());
 
@@ -164,6 +199,7 @@ where
         let cumulative_sum: Ext<_, _> = builder.eval(C::EF::zero().cons());
         let current_pc: Felt<_> = builder.uninit();
         let exit_code: Felt<_> = builder.uninit();
+        let features: Felt<_> = builder.uninit();
 
         // Range check that the number of proofs is sufficiently small.
         let num_shard_proofs: Var<_> = shard_proofs.len().materialize(builder);
@@ -176,9 +212,10 @@ where
 
             // Verify the shard proof.
             let mut challenger = leaf_challenger.copy(builder);
-            StarkVerifier::<C, SC>::verify_shard(
+            StarkVerifier::<C, SC>::verify_shard_with_shape(
                 builder,
                 &vk,
+                shape,
                 pcs,
                 machine,
                 &mut challenger,
@@ -196,7 +233,11 @@ where
 
             // If this is the first proof in the batch, verify the initial conditions.
             builder.if_eq(i, C::N::zero()).then(|builder| {
-                // Initialize the values of accumulated variables.
+                // Initialize the values of accumulated variables. The digests of the other proofs
+                // are asserted equal to these, so only the first ones are range checked.
+                range_check_words(builder, &public_values.committed_value_digest);
+                range_check_words(builder, &public_values.input_digest);
+                range_check_words(builder, &public_values.config_digest);
 
                 // Shard.
                 builder.assign(initial_shard, public_values.shard);
@@ -216,6 +257,26 @@ where
                     }
                 }
 
+                // Input digests.
+                for (word, first_word) in input_digest
+                    .iter()
+                    .zip_eq(public_values.input_digest.iter())
+                {
+                    for (byte, first_byte) in word.0.iter().zip_eq(first_word.0.iter()) {
+                        builder.assign(*byte, *first_byte);
+                    }
+                }
+
+                // Config digests.
+                for (word, first_word) in config_digest
+                    .iter()
+                    .zip_eq(public_values.config_digest.iter())
+                {
+                    for (byte, first_byte) in word.0.iter().zip_eq(first_word.0.iter()) {
+                        builder.assign(*byte, *first_byte);
+                    }
+                }
+
                 // Deferred proofs digests.
                 for (digest, first_digest) in deferred_proofs_digest
                     .iter()
@@ -226,6 +287,9 @@ where
 
                 // Exit code.
                 builder.assign(exit_code, public_values.exit_code);
+
+                // Features.
+                builder.assign(features, public_values.features);
             });
 
             // If it's first shard, verify the global initial conditions hold on challenger and pc.
@@ -261,14 +325,28 @@ where
                 }
             }
 
-            // Assert that the start_pc of the proof is equal to the current pc.
-            builder.assert_felt_eq(current_pc, public_values.start_pc);
-            // Assert that the start_pc is not zero (this means program has halted in a non-last
-            // shard).
-            builder.assert_felt_ne(public_values.start_pc, C::F::zero());
+            // Assert that the input digests are all the same.
+            for (word, current_word) in input_digest
+                .iter()
+                .zip_eq(public_values.input_digest.iter())
+            {
+                for (byte, current_byte) in word.0.iter().zip_eq(current_word.0.iter()) {
+                    builder.assert_felt_eq(*byte, *current_byte);
+                }
+            }
+
+            // Assert that the config digests are all the same.
+            for (word, current_word) in config_digest
+                .iter()
+                .zip_eq(public_values.config_digest.iter())
+            {
+                for (byte, current_byte) in word.0.iter().zip_eq(current_word.0.iter()) {
+                    builder.assert_felt_eq(*byte, *current_byte);
+                }
+            }
 
-            // Assert that the shard of the proof is equal to the current shard.
-            builder.assert_felt_eq(current_shard, public_values.shard);
+            // Assert that the proof continues the execution at the current shard and pc.
+            assert_shard_transition(builder, current_shard, current_pc, &public_values);
 
             // Assert that exit code is the same for all proofs.
             builder.assert_felt_eq(exit_code, public_values.exit_code);
@@ -276,6 +354,9 @@ where
             // Assert that the exit code is zero (success) for all proofs.
             builder.assert_felt_eq(exit_code, C::F::zero());
 
+            // Assert that the features are the same for all proofs.
+            builder.assert_felt_eq(features, public_values.features);
+
             // Assert that the deferred proof digest is the same for all proofs.
             for (digest, current_digest) in deferred_proofs_digest
                 .iter()
@@ -314,7 +395,10 @@ where
         // Write all values to the public values struct and commit to them.
 
         // Compute vk digest.
-        let vk_digest = hash_vkey(builder, &vk);
+        let vk_digest = match shape {
+            Some(shape) => hash_runtime_vkey(builder, &vk, shape),
+            None => hash_vkey(builder, &vk),
+        };
         let vk_digest: [Felt<_>; DIGEST_SIZE] = array::from_fn(|i| builder.get(&vk_digest, i));
 
         // Collect values for challenges.
@@ -341,6 +425,8 @@ where
         let total_core_shards_felt = var2felt(builder, total_core_shards);
 
         recursion_public_values.committed_value_digest = committed_value_digest;
+        recursion_public_values.input_digest = input_digest;
+        recursion_public_values.config_digest = config_digest;
         recursion_public_values.deferred_proofs_digest = deferred_proofs_digest;
         recursion_public_values.start_pc = start_pc;
         recursion_public_values.next_pc = current_pc;
@@ -355,6 +441,9 @@ where
         recursion_public_values.end_reconstruct_deferred_digest = end_deferred_digest;
         recursion_public_values.is_complete = is_complete_felt;
         recursion_public_values.total_core_shards = total_core_shards_felt;
+        recursion_public_values.features = features;
+        recursion_public_values.reduce_plan_digest =
+            reduce_plan_leaf_digest(builder, ReduceProgramType::Core, shard_proofs.len());
 
         // If the proof represents a complete proof, make completeness assertions.
         //
@@ -27,7 +27,8 @@ use crate::types::ShardProofVariable;
 use crate::types::VerifyingKeyVariable;
 use crate::utils::{const_fri_config, get_challenger_public_values, hash_vkey, var2felt};
 
-use super::utils::{commit_public_values, verify_public_values_hash};
+use super::utils::{commit_public_values, reduce_plan_leaf_digest, verify_public_values_hash};
+use super::ReduceProgramType;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SP1DeferredVerifier<C: Config, SC: StarkGenericConfig, A> {
@@ -50,11 +51,14 @@ where
     pub sp1_vk: &'a StarkVerifyingKey<SC>,
     pub sp1_machine: &'a StarkMachine<SC, RiscvAir<SC::Val>>,
     pub committed_value_digest: Vec<Word<SC::Val>>,
+    pub input_digest: Vec<Word<SC::Val>>,
+    pub config_digest: Vec<Word<SC::Val>>,
     pub deferred_proofs_digest: Vec<SC::Val>,
     pub leaf_challenger: SC::Challenger,
     pub end_pc: SC::Val,
     pub end_shard: SC::Val,
     pub total_core_shards: usize,
+    pub features: SC::Val,
 }
 
 /// A variable version of the [SP1DeferredMemoryLayout] struct.
@@ -70,11 +74,14 @@ pub struct SP1DeferredMemoryLayoutVariable<C: Config> {
 
     pub sp1_vk: VerifyingKeyVariable<C>,
     pub committed_value_digest: Array<C, Array<C, Felt<C::F>>>,
+    pub input_digest: Array<C, Array<C, Felt<C::F>>>,
+    pub config_digest: Array<C, Array<C, Felt<C::F>>>,
     pub deferred_proofs_digest: Array<C, Felt<C::F>>,
     pub leaf_challenger: DuplexChallengerVariable<C>,
     pub end_pc: Felt<C::F>,
     pub end_shard: Felt<C::F>,
     pub total_core_shards: Var<C::N>,
+    pub features: Felt<C::F>,
 }
 
 impl<A> SP1DeferredVerifier<InnerConfig, BabyBearPoseidon2, A>
@@ -95,7 +102,9 @@ where
 
         builder.halt();
 
-        builder.compile_program()
+        let mut program = builder.compile_program();
+        program.verified_fingerprint = Some(machine.fingerprint());
+        program
     }
 }
 
@@ -134,10 +143,13 @@ where
             total_core_shards,
             sp1_vk,
             committed_value_digest,
+            input_digest,
+            config_digest,
             deferred_proofs_digest,
             leaf_challenger,
             end_pc,
             end_shard,
+            features,
         } = input;
 
         // Initialize the values for the aggregated public output as all zeros.
@@ -278,6 +290,18 @@ where
             public_word.0 = array::from_fn(|j| builder.get(&hinted_word, j));
         }
 
+        // Set the input digest to be the hitned value.
+        for (i, public_word) in deferred_public_values.input_digest.iter_mut().enumerate() {
+            let hinted_word = builder.get(&input_digest, i);
+            public_word.0 = array::from_fn(|j| builder.get(&hinted_word, j));
+        }
+
+        // Set the config digest to be the hinted value.
+        for (i, public_word) in deferred_public_values.config_digest.iter_mut().enumerate() {
+            let hinted_word = builder.get(&config_digest, i);
+            public_word.0 = array::from_fn(|j| builder.get(&hinted_word, j));
+        }
+
         // Set the deferred proof digest to be the hitned value.
         deferred_public_values.deferred_proofs_digest =
             core::array::from_fn(|i| builder.get(&deferred_proofs_digest, i));
@@ -295,6 +319,43 @@ where
         // Set the is_complete flag.
         deferred_public_values.is_complete = var2felt(builder, is_complete);
         deferred_public_values.total_core_shards = var2felt(builder, total_core_shards);
+        deferred_public_values.features = features;
+        deferred_public_values.reduce_plan_digest =
+            reduce_plan_leaf_digest(builder, ReduceProgramType::Deferred, proofs.len());
+
+        // The hinted values passed through to the public values are connected to the public
+        // values of the neighbouring proofs by the compress program.
+        let passed_through = [end_pc, end_shard, features]
+            .into_iter()
+            .chain(deferred_public_values.sp1_vk_digest)
+            .chain(
+                deferred_public_values
+                    .committed_value_digest
+                    .iter()
+                    .flat_map(|word| word.0),
+            )
+            .chain(
+                deferred_public_values
+                    .input_digest
+                    .iter()
+                    .flat_map(|word| word.0),
+            )
+            .chain(
+                deferred_public_values
+                    .config_digest
+                    .iter()
+                    .flat_map(|word| word.0),
+            )
+            .chain(deferred_public_values.deferred_proofs_digest)
+            .chain(deferred_public_values.start_reconstruct_deferred_digest)
+            .chain(values.sponge_state)
+            .chain(values.input_buffer)
+            .chain(values.output_buffer)
+            .chain([values.num_inputs, values.num_outputs])
+            .collect::<Vec<_>>();
+        for felt in passed_through {
+            builder.assume_felt_constrained(felt, "checked by the compress program");
+        }
 
         commit_public_values(builder, deferred_public_values);
     }
@@ -2,12 +2,14 @@ mod compress;
 mod core;
 mod deferred;
 mod root;
+mod universal;
 mod utils;
 
 pub use compress::*;
 pub use core::*;
 pub use deferred::*;
 pub use root::*;
+pub use universal::*;
 pub use utils::*;
 
 #[cfg(test)]
@@ -373,6 +375,14 @@ mod tests {
         tracing::info!("Wrapping successful");
     }
 
+    #[test]
+    fn test_sp1_recursive_program_hints_are_constrained() {
+        let machine = RiscvAir::machine(BabyBearPoseidon2::default());
+        let report = SP1RecursiveVerifier::<InnerConfig, BabyBearPoseidon2>::builder(&machine)
+            .taint_report();
+        assert!(report.is_clean(), "{}", report);
+    }
+
     #[test]
     fn test_sp1_recursive_machine_verify_fibonacci() {
         let elf = include_bytes!("../../../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
@@ -24,7 +24,7 @@ use crate::stark::{RecursiveVerifierConstraintFolder, ShardProofHint, StarkVerif
 use crate::types::ShardProofVariable;
 use crate::utils::{const_fri_config, hash_vkey};
 
-use super::utils::{commit_public_values, verify_public_values_hash};
+use super::utils::{commit_public_values, range_check_public_values, verify_public_values_hash};
 
 /// The program that gets a final verifier at the root of the tree.
 #[derive(Debug, Clone, Copy)]
@@ -64,7 +64,9 @@ where
 
         SP1RootVerifier::verify(&mut builder, &pcs, machine, vk, &proof, is_compress);
 
-        builder.compile_program()
+        let mut program = builder.compile_program();
+        program.verified_fingerprint = Some(machine.fingerprint());
+        program
     }
 }
 
@@ -127,6 +129,7 @@ where
 
         // Check that the public values digest is correct.
         verify_public_values_hash(builder, public_values);
+        range_check_public_values(builder, public_values);
 
         // Assert that the proof is complete.
         //
@@ -0,0 +1,294 @@
+use std::marker::PhantomData;
+
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use serde::{Deserialize, Serialize};
+use sp1_core::air::MachineAir;
+use sp1_core::stark::{RiscvAir, StarkGenericConfig, StarkMachine};
+use sp1_core::utils::BabyBearPoseidon2;
+use sp1_primitives::poseidon2_hash;
+use sp1_recursion_compiler::config::InnerConfig;
+use sp1_recursion_compiler::ir::{Builder, Config};
+use sp1_recursion_compiler::prelude::DslVariable;
+use sp1_recursion_core::runtime::{RecursionProgram, DIGEST_SIZE};
+
+use sp1_recursion_compiler::prelude::*;
+
+use crate::fri::TwoAdicFriPcsVariable;
+use crate::hints::Hintable;
+use crate::types::MachineShapeVariable;
+use crate::utils::const_fri_config;
+
+use super::{SP1RecursionMemoryLayout, SP1RecursionMemoryLayoutVariable, SP1RecursiveVerifier};
+
+/// The chips of a core machine, relative to the chips of the machine a [SP1UniversalVerifier] is
+/// built for, and the largest trace each of them may have.
+///
+/// The shape is witnessed along with the verifying key, so that one recursion program verifies
+/// proofs of every machine whose chips are a subset of its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineShape {
+    /// Whether each chip of the universal machine is a chip of the shape.
+    pub chip_mask: Vec<bool>,
+    /// The largest log degree of the trace of each chip of the universal machine.
+    pub max_log_degrees: Vec<usize>,
+}
+
+impl MachineShape {
+    /// The shape of `machine` within `universal`, with traces of at most `2^max_log_degree` rows.
+    ///
+    /// Panics if `machine` has a chip that `universal` does not have.
+    pub fn new<SC: StarkGenericConfig, A: MachineAir<SC::Val>>(
+        universal: &StarkMachine<SC, A>,
+        machine: &StarkMachine<SC, A>,
+        max_log_degree: usize,
+    ) -> Self {
+        let names = universal
+            .chips()
+            .iter()
+            .map(|chip| chip.name())
+            .collect::<Vec<_>>();
+        for chip in machine.chips() {
+            assert!(
+                names.contains(&chip.name()),
+                "chip {} is not a chip of the universal machine",
+                chip.name()
+            );
+        }
+        let chip_mask = names
+            .iter()
+            .map(|name| machine.chips().iter().any(|chip| &chip.name() == name))
+            .collect::<Vec<_>>();
+        let max_log_degrees = vec![max_log_degree; chip_mask.len()];
+        Self {
+            chip_mask,
+            max_log_degrees,
+        }
+    }
+
+    /// The digest of a verifying key for this shape, committed by the [SP1UniversalVerifier] as
+    /// the `sp1_vk_digest`, given the digest `vk_digest` of the key itself.
+    ///
+    /// Reference: [crate::utils::hash_runtime_vkey]
+    pub fn vk_digest(&self, vk_digest: [BabyBear; DIGEST_SIZE]) -> [BabyBear; DIGEST_SIZE] {
+        let mut inputs = vk_digest.to_vec();
+        inputs.extend(
+            self.chip_mask
+                .iter()
+                .map(|&included| BabyBear::from_bool(included)),
+        );
+        inputs.extend(
+            self.max_log_degrees
+                .iter()
+                .map(|&log_degree| BabyBear::from_canonical_usize(log_degree)),
+        );
+        poseidon2_hash(inputs)
+    }
+}
+
+/// A program verifying a batch of SP1 shard proofs of any machine shape, see [MachineShape].
+#[derive(Debug, Clone, Copy)]
+pub struct SP1UniversalVerifier<C: Config, SC: StarkGenericConfig> {
+    _phantom: PhantomData<(C, SC)>,
+}
+
+/// The witness of a [SP1UniversalVerifier]. The `machine` of the core layout must be the machine
+/// the program is built for, even if the proofs are of a smaller machine.
+pub struct SP1UniversalMemoryLayout<'a, SC: StarkGenericConfig, A: MachineAir<SC::Val>> {
+    pub core: SP1RecursionMemoryLayout<'a, SC, A>,
+    pub shape: &'a MachineShape,
+}
+
+#[derive(DslVariable, Clone)]
+pub struct SP1UniversalMemoryLayoutVariable<C: Config> {
+    pub core: SP1RecursionMemoryLayoutVariable<C>,
+    pub shape: MachineShapeVariable<C>,
+}
+
+impl SP1UniversalVerifier<InnerConfig, BabyBearPoseidon2> {
+    /// Create a new instance of the program for the [BabyBearPoseidon2] config, verifying proofs
+    /// of `machine` and of any machine with a subset of its chips.
+    pub fn build(
+        machine: &StarkMachine<BabyBearPoseidon2, RiscvAir<BabyBear>>,
+    ) -> RecursionProgram<BabyBear> {
+        let mut builder = Builder::<InnerConfig>::default();
+
+        let input: SP1UniversalMemoryLayoutVariable<_> = builder.uninit();
+        SP1UniversalMemoryLayout::<BabyBearPoseidon2, RiscvAir<_>>::witness(&input, &mut builder);
+
+        let pcs = TwoAdicFriPcsVariable {
+            config: const_fri_config(&mut builder, machine.config().pcs().fri_config()),
+        };
+        SP1RecursiveVerifier::verify_with_shape(
+            &mut builder,
+            &pcs,
+            machine,
+            input.core,
+            Some(&input.shape),
+        );
+
+        builder.halt();
+
+        builder.compile_program()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use p3_challenger::CanObserve;
+    use sp1_core::air::SP1_PROOF_NUM_PV_ELTS;
+    use sp1_core::runtime::{Program, Runtime as CoreRuntime};
+    use sp1_core::stark::{Chip, LocalProver};
+    use sp1_core::utils::{setup_logger, SP1CoreOpts};
+    use sp1_recursion_core::air::RecursionPublicValues;
+    use sp1_recursion_core::runtime::Runtime;
+
+    use super::*;
+
+    type SC = BabyBearPoseidon2;
+    type F = BabyBear;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+
+    /// The RISC-V machine without any precompile.
+    fn lite_machine() -> StarkMachine<SC, RiscvAir<F>> {
+        let chips = RiscvAir::get_all()
+            .into_iter()
+            .filter(|air| {
+                matches!(
+                    air,
+                    RiscvAir::Program(_)
+                        | RiscvAir::Cpu(_)
+                        | RiscvAir::Add(_)
+                        | RiscvAir::Bitwise(_)
+                        | RiscvAir::Mul(_)
+                        | RiscvAir::DivRem(_)
+                        | RiscvAir::Lt(_)
+                        | RiscvAir::Shift(_)
+                        | RiscvAir::ByteLookup(_)
+                        | RiscvAir::MemoryInit(_)
+                        | RiscvAir::MemoryFinal(_)
+                        | RiscvAir::ProgramMemory(_)
+                )
+            })
+            .map(Chip::new)
+            .collect();
+        StarkMachine::new(SC::default(), chips, SP1_PROOF_NUM_PV_ELTS)
+    }
+
+    /// Proves `program` with `machine`, verifies the proof with the universal verifier of
+    /// `universal` witnessing `shape`, and returns the committed vkey digest.
+    fn verify_with_shape(
+        recursion_program: &RecursionProgram<F>,
+        universal: &StarkMachine<SC, RiscvAir<F>>,
+        machine: &StarkMachine<SC, RiscvAir<F>>,
+        shape: &MachineShape,
+        program: Program,
+    ) -> [F; DIGEST_SIZE] {
+        let (pk, vk) = machine.setup(&program);
+        let mut runtime = CoreRuntime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let mut challenger = machine.config().challenger();
+        let proof = machine.prove::<LocalProver<_, _>>(
+            &pk,
+            runtime.record,
+            &mut challenger,
+            SP1CoreOpts::default(),
+        );
+
+        let mut leaf_challenger = machine.config().challenger();
+        vk.observe_into(&mut leaf_challenger);
+        for shard_proof in proof.shard_proofs.iter() {
+            leaf_challenger.observe(shard_proof.commitment.main_commit);
+            leaf_challenger.observe_slice(&shard_proof.public_values[0..machine.num_pv_elts()]);
+        }
+        let mut initial_reconstruct_challenger = machine.config().challenger();
+        vk.observe_into(&mut initial_reconstruct_challenger);
+
+        let total_core_shards = proof.shard_proofs.len();
+        let layout = SP1UniversalMemoryLayout {
+            core: SP1RecursionMemoryLayout {
+                vk: &vk,
+                machine: universal,
+                shard_proofs: proof.shard_proofs,
+                leaf_challenger: &leaf_challenger,
+                initial_reconstruct_challenger,
+                is_complete: total_core_shards == 1,
+                total_core_shards,
+            },
+            shape,
+        };
+
+        let mut runtime =
+            Runtime::<F, EF, _>::new(recursion_program, universal.config().perm.clone());
+        runtime.witness_stream = layout.write().into();
+        runtime.run();
+
+        let public_values: &RecursionPublicValues<F> =
+            runtime.record.public_values.as_slice().borrow();
+        public_values.sp1_vk_digest
+    }
+
+    #[test]
+    fn test_universal_verifier_two_shapes() {
+        setup_logger();
+        let elf = include_bytes!("../../../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+        let universal = RiscvAir::machine(SC::default());
+        let lite = lite_machine();
+        let recursion_program = SP1UniversalVerifier::<InnerConfig, SC>::build(&universal);
+
+        let full_shape = MachineShape::new(&universal, &universal, 22);
+        let lite_shape = MachineShape::new(&universal, &lite, 22);
+        assert!(lite_shape.chip_mask.iter().any(|included| !included));
+
+        let full_digest = verify_with_shape(
+            &recursion_program,
+            &universal,
+            &universal,
+            &full_shape,
+            Program::from(elf),
+        );
+        let lite_digest = verify_with_shape(
+            &recursion_program,
+            &universal,
+            &lite,
+            &lite_shape,
+            Program::from(elf),
+        );
+
+        // Both machines have the same verifying key for the program, only the shape tells their
+        // proofs apart.
+        let commit = |machine: &StarkMachine<SC, RiscvAir<F>>| -> [F; DIGEST_SIZE] {
+            machine.setup(&Program::from(elf)).1.commit.into()
+        };
+        assert_eq!(commit(&universal), commit(&lite));
+        assert_ne!(full_digest, lite_digest);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_universal_verifier_trace_too_large() {
+        setup_logger();
+        let elf = include_bytes!("../../../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+        let universal = RiscvAir::machine(SC::default());
+        let lite = lite_machine();
+        let recursion_program = SP1UniversalVerifier::<InnerConfig, SC>::build(&universal);
+
+        // The CPU trace of the program has more than 2^4 rows.
+        let mut shape = MachineShape::new(&universal, &lite, 22);
+        let cpu = universal
+            .chips()
+            .iter()
+            .position(|chip| chip.name() == "CPU")
+            .unwrap();
+        shape.max_log_degrees[cpu] = 4;
+        verify_with_shape(
+            &recursion_program,
+            &universal,
+            &lite,
+            &shape,
+            Program::from(elf),
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::array;
 use std::mem::transmute;
 
 use itertools::Itertools;
@@ -5,10 +6,10 @@ use p3_commit::TwoAdicMultiplicativeCoset;
 use p3_field::AbstractField;
 
 use sp1_core::{
-    air::MachineAir,
+    air::{MachineAir, PublicValues, Word},
     stark::{Com, StarkGenericConfig, StarkMachine, StarkVerifyingKey},
 };
-use sp1_recursion_compiler::ir::{Array, Builder, Config, Felt, Var};
+use sp1_recursion_compiler::ir::{Array, Builder, Config, Felt, Usize, Var};
 use sp1_recursion_core::{
     air::{RecursionPublicValues, NUM_PV_ELMS_TO_HASH, RECURSIVE_PROOF_NUM_PV_ELTS},
     runtime::DIGEST_SIZE,
@@ -18,9 +19,11 @@ use crate::{
     challenger::DuplexChallengerVariable,
     fri::TwoAdicMultiplicativeCosetVariable,
     types::VerifyingKeyVariable,
-    utils::{assert_challenger_eq_pv, felt2var, get_preprocessed_data},
+    utils::{assert_challenger_eq_pv, felt2var, get_preprocessed_data, var2felt},
 };
 
+use super::ReduceProgramType;
+
 /// Assertions on the public values describing a complete recursive proof state.
 ///
 /// See [SP1Prover::verify] for the verification algorithm of a complete SP1 proof.
@@ -128,6 +131,24 @@ where
     }
 }
 
+/// Calculates the reduce plan digest of a leaf verifying `num_proofs` proofs of the given kind.
+///
+/// The digest is `poseidon2(kind || num_proofs)`, matching `sp1_prover::ReducePlan::digest`.
+pub(crate) fn reduce_plan_leaf_digest<C: Config>(
+    builder: &mut Builder<C>,
+    kind: ReduceProgramType,
+    num_proofs: Usize<C::N>,
+) -> [Felt<C::F>; DIGEST_SIZE] {
+    let mut poseidon_inputs = builder.array(2);
+    let kind: Felt<_> = builder.eval(C::F::from_canonical_u32(kind as u32));
+    builder.set(&mut poseidon_inputs, 0, kind);
+    let num_proofs = num_proofs.materialize(builder);
+    let num_proofs = var2felt(builder, num_proofs);
+    builder.set(&mut poseidon_inputs, 1, num_proofs);
+    let digest = builder.poseidon2_hash(&poseidon_inputs);
+    array::from_fn(|i| builder.get(&digest, i))
+}
+
 /// Calculates the digest of the recursion public values.
 pub(crate) fn calculate_public_values_digest<C: Config>(
     builder: &mut Builder<C>,
@@ -159,6 +180,50 @@ pub(crate) fn verify_public_values_hash<C: Config>(
     });
 }
 
+/// Range checks the bytes of public value words as they are loaded from a proof.
+///
+/// The digests of the public values are made of [Word]s, whose four little-endian bytes are each
+/// stored in a felt. The wrap circuit packs the 32 bytes of the committed value digest big-endian
+/// into a single Bn254 element, dropping the 3 most significant bits of the first byte, so a felt
+/// holding 256 or more would alias another digest. Each felt is checked to hold a byte.
+pub(crate) fn range_check_words<C: Config>(builder: &mut Builder<C>, words: &[Word<Felt<C::F>>]) {
+    for word in words {
+        for byte in word.0 {
+            builder.range_check_f(byte, 8);
+        }
+    }
+}
+
+/// Asserts that the shard with the given public values continues the execution at `current_shard`
+/// and `current_pc`, which also rejects the shards following one which halted, as their start pc
+/// is zero.
+pub fn assert_shard_transition<C: Config>(
+    builder: &mut Builder<C>,
+    current_shard: Felt<C::F>,
+    current_pc: Felt<C::F>,
+    public_values: &PublicValues<Word<Felt<C::F>>, Felt<C::F>>,
+) {
+    // Assert that the start_pc of the proof is equal to the current pc.
+    builder.assert_felt_eq(current_pc, public_values.start_pc);
+    // Assert that the start_pc is not zero (this means program has halted in a non-last
+    // shard).
+    builder.assert_felt_ne(public_values.start_pc, C::F::zero());
+
+    // Assert that the shard of the proof is equal to the current shard.
+    builder.assert_felt_eq(current_shard, public_values.shard);
+}
+
+/// Range checks the digests of recursion public values loaded from a proof, see
+/// [range_check_words].
+pub(crate) fn range_check_public_values<C: Config>(
+    builder: &mut Builder<C>,
+    public_values: &RecursionPublicValues<Felt<C::F>>,
+) {
+    range_check_words(builder, &public_values.committed_value_digest);
+    range_check_words(builder, &public_values.input_digest);
+    range_check_words(builder, &public_values.config_digest);
+}
+
 /// Register and commits the recursion public values.
 pub fn commit_public_values<C: Config>(
     builder: &mut Builder<C>,
@@ -178,3 +243,49 @@ pub fn commit_public_values<C: Config>(
         builder.commit_public_value(digest_element);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::array;
+
+    use p3_field::AbstractField;
+    use sp1_core::{air::Word, stark::StarkGenericConfig, utils::BabyBearPoseidon2};
+    use sp1_recursion_compiler::asm::AsmBuilder;
+    use sp1_recursion_core::runtime::Runtime;
+
+    use super::range_check_words;
+
+    type SC = BabyBearPoseidon2;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+
+    /// Runs a program range checking a word of hinted bytes.
+    fn run_range_check(bytes: [u32; 4]) {
+        let mut builder = AsmBuilder::<F, EF>::default();
+        let word = Word(array::from_fn(|_| builder.hint_felt()));
+        range_check_words(&mut builder, &[word]);
+        builder.halt();
+
+        let program = builder.compile_program();
+        let config = SC::default();
+        let mut runtime = Runtime::<F, EF, _>::new(&program, config.perm.clone());
+        runtime.witness_stream = bytes
+            .iter()
+            .map(|byte| vec![F::from_canonical_u32(*byte).into()])
+            .collect::<Vec<_>>()
+            .into();
+        runtime.run();
+    }
+
+    #[test]
+    fn test_range_check_words() {
+        run_range_check([0x12, 0x34, 0x56, 0xff]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_check_words_rejects_overflowing_byte() {
+        // 0x100 in the second byte packs like a carry into the third one.
+        run_range_check([0x12, 0x100, 0x55, 0xff]);
+    }
+}
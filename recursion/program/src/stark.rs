@@ -29,14 +29,20 @@ use crate::commit::PolynomialSpaceVariable;
 use crate::fri::types::TwoAdicPcsMatsVariable;
 use crate::fri::types::TwoAdicPcsRoundVariable;
 use crate::fri::TwoAdicMultiplicativeCosetVariable;
+use crate::types::MachineShapeVariable;
 use crate::types::ShardCommitmentVariable;
 use crate::types::VerifyingKeyVariable;
 use crate::{commit::PcsVariable, fri::TwoAdicFriPcsVariable, types::ShardProofVariable};
 
 use crate::types::QuotientData;
+use crate::utils::num_preprocessed_traces;
 
 pub const EMPTY: usize = 0x_1111_1111;
 
+/// The number of bits a log degree is range checked to, enough for any two-adic subgroup of the
+/// field.
+const LOG_DEGREE_BITS: usize = 5;
+
 pub trait StarkRecursiveVerifier<C: Config> {
     fn verify_shard(
         &self,
@@ -126,6 +132,42 @@ where
         C::F: TwoAdicField,
         C::EF: TwoAdicField,
         Com<SC>: Into<[SC::Val; DIGEST_SIZE]>,
+    {
+        Self::verify_shard_with_shape(
+            builder,
+            vk,
+            None,
+            pcs,
+            machine,
+            challenger,
+            proof,
+            total_shards,
+        )
+    }
+
+    /// Verifies a shard proof of a machine whose chips are a subset of the chips of `machine`,
+    /// described by the witnessed `shape`.
+    ///
+    /// The shape is bounded by `machine`: its arrays have one entry per chip of `machine`, and its
+    /// log degrees are at most the two-adicity of the field. A chip left out of the shape must not
+    /// be in the proof, and the trace of a chip in the proof must not be larger than the shape
+    /// allows. The CPU and the preprocessed chips with a preprocessed trace in `vk` are part of
+    /// every shape. Without a shape, every chip of `machine` may be in the proof.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_shard_with_shape<A>(
+        builder: &mut Builder<C>,
+        vk: &VerifyingKeyVariable<C>,
+        shape: Option<&MachineShapeVariable<C>>,
+        pcs: &TwoAdicFriPcsVariable<C>,
+        machine: &StarkMachine<SC, A>,
+        challenger: &mut DuplexChallengerVariable<C>,
+        proof: &ShardProofVariable<C>,
+        total_shards: Var<C::N>,
+    ) where
+        A: MachineAir<C::F> + for<'a> Air<RecursiveVerifierConstraintFolder<'a, C>>,
+        C::F: TwoAdicField,
+        C::EF: TwoAdicField,
+        Com<SC>: Into<[SC::Val; DIGEST_SIZE]>,
     {
         builder.cycle_tracker("stage-c-verify-shard-setup");
         let ShardProofVariable {
@@ -168,10 +210,11 @@ where
         let mut quotient_domains =
             builder.dyn_array::<TwoAdicMultiplicativeCosetVariable<_>>(num_shard_chips);
 
-        let num_preprocessed_chips = machine.preprocessed_chip_ids().len();
+        // The preprocessed chips without a preprocessed trace in the key, such as the unused
+        // program banks, are left out of the preprocessed round.
+        let num_prep_traces = num_preprocessed_traces(builder, vk);
 
-        let mut prep_mats: Array<_, TwoAdicPcsMatsVariable<_>> =
-            builder.dyn_array(num_preprocessed_chips);
+        let mut prep_mats: Array<_, TwoAdicPcsMatsVariable<_>> = builder.dyn_array(num_prep_traces);
         let mut main_mats: Array<_, TwoAdicPcsMatsVariable<_>> = builder.dyn_array(num_shard_chips);
         let mut perm_mats: Array<_, TwoAdicPcsMatsVariable<_>> = builder.dyn_array(num_shard_chips);
 
@@ -191,29 +234,34 @@ where
         for (preprocessed_id, chip_id) in machine.preprocessed_chip_ids().into_iter().enumerate() {
             // Get index within sorted preprocessed chips.
             let preprocessed_sorted_id = builder.get(&vk.preprocessed_sorted_idxs, preprocessed_id);
-            // Get domain from witnessed domains. Array is ordered by machine.chips ordering.
-            let domain = builder.get(&vk.prep_domains, preprocessed_id);
-
-            // Get index within all sorted chips.
-            let chip_sorted_id = builder.get(&proof.sorted_idxs, chip_id);
-            // Get opening from proof.
-            let opening = builder.get(&opened_values.chips, chip_sorted_id);
-
-            let mut trace_points = builder.dyn_array::<Ext<_, _>>(2);
-            let zeta_next = domain.next_point(builder, zeta);
-
-            builder.set_value(&mut trace_points, 0, zeta);
-            builder.set_value(&mut trace_points, 1, zeta_next);
-
-            let mut prep_values = builder.dyn_array::<Array<C, _>>(2);
-            builder.set_value(&mut prep_values, 0, opening.preprocessed.local);
-            builder.set_value(&mut prep_values, 1, opening.preprocessed.next);
-            let main_mat = TwoAdicPcsMatsVariable::<C> {
-                domain: domain.clone(),
-                values: prep_values,
-                points: trace_points.clone(),
-            };
-            builder.set_value(&mut prep_mats, preprocessed_sorted_id, main_mat);
+            builder
+                .if_ne(preprocessed_sorted_id, C::N::from_canonical_usize(EMPTY))
+                .then(|builder| {
+                    // Get domain from witnessed domains. Array is ordered by machine.chips
+                    // ordering.
+                    let domain = builder.get(&vk.prep_domains, preprocessed_id);
+
+                    // Get index within all sorted chips.
+                    let chip_sorted_id = builder.get(&proof.sorted_idxs, chip_id);
+                    // Get opening from proof.
+                    let opening = builder.get(&opened_values.chips, chip_sorted_id);
+
+                    let mut trace_points = builder.dyn_array::<Ext<_, _>>(2);
+                    let zeta_next = domain.next_point(builder, zeta);
+
+                    builder.set_value(&mut trace_points, 0, zeta);
+                    builder.set_value(&mut trace_points, 1, zeta_next);
+
+                    let mut prep_values = builder.dyn_array::<Array<C, _>>(2);
+                    builder.set_value(&mut prep_values, 0, opening.preprocessed.local);
+                    builder.set_value(&mut prep_values, 1, opening.preprocessed.next);
+                    let main_mat = TwoAdicPcsMatsVariable::<C> {
+                        domain: domain.clone(),
+                        values: prep_values,
+                        points: trace_points.clone(),
+                    };
+                    builder.set_value(&mut prep_mats, preprocessed_sorted_id, main_mat);
+                });
         }
 
         let qc_index: Var<_> = builder.eval(C::N::zero());
@@ -315,16 +363,60 @@ where
 
         let shard_bits = builder.num2bits_f(public_values.shard);
         let shard = builder.bits2num_v(&shard_bits);
+        if let Some(shape) = shape {
+            builder.assert_usize_eq(shape.chip_mask.len(), machine.chips().len());
+            builder.assert_usize_eq(shape.max_log_degrees.len(), machine.chips().len());
+        }
+        let preprocessed_chip_ids = machine.preprocessed_chip_ids();
         for (i, chip) in machine.chips().iter().enumerate() {
             tracing::debug!("verifying constraints for chip: {}", chip.name());
             let index = builder.get(&proof.sorted_idxs, i);
 
+            // A preprocessed chip is in the shard exactly when the key has a preprocessed trace
+            // for it.
+            let prep_sorted_idx = preprocessed_chip_ids
+                .iter()
+                .position(|&chip_id| chip_id == i)
+                .map(|preprocessed_id| builder.get(&vk.preprocessed_sorted_idxs, preprocessed_id));
+
+            if let Some(shape) = shape {
+                let included = builder.get(&shape.chip_mask, i);
+                builder.assert_var_eq(included * (included - C::N::one()), C::N::zero());
+                if chip.name() == "CPU" {
+                    builder.assert_var_eq(included, C::N::one());
+                }
+                if let Some(prep_sorted_idx) = prep_sorted_idx {
+                    builder
+                        .if_ne(prep_sorted_idx, C::N::from_canonical_usize(EMPTY))
+                        .then(|builder| {
+                            builder.assert_var_eq(included, C::N::one());
+                        });
+                }
+                builder.if_eq(included, C::N::zero()).then(|builder| {
+                    builder.assert_var_eq(index, C::N::from_canonical_usize(EMPTY));
+                });
+
+                let max_log_degree = builder.get(&shape.max_log_degrees, i);
+                let slack: Var<_> =
+                    builder.eval(C::N::from_canonical_usize(C::F::TWO_ADICITY) - max_log_degree);
+                builder.range_check_v(slack, LOG_DEGREE_BITS);
+            }
+
             if chip.name() == "CPU" {
                 builder.assert_var_ne(index, C::N::from_canonical_usize(EMPTY));
             }
 
-            if chip.preprocessed_width() > 0 {
-                builder.assert_var_ne(index, C::N::from_canonical_usize(EMPTY));
+            if let Some(prep_sorted_idx) = prep_sorted_idx {
+                builder
+                    .if_eq(prep_sorted_idx, C::N::from_canonical_usize(EMPTY))
+                    .then_or_else(
+                        |builder| {
+                            builder.assert_var_eq(index, C::N::from_canonical_usize(EMPTY));
+                        },
+                        |builder| {
+                            builder.assert_var_ne(index, C::N::from_canonical_usize(EMPTY));
+                        },
+                    );
             }
 
             if chip.name() == "MemoryInit" {
@@ -353,6 +445,11 @@ where
                 .if_ne(index, C::N::from_canonical_usize(EMPTY))
                 .then(|builder| {
                     let values = builder.get(&opened_values.chips, index);
+                    if let Some(shape) = shape {
+                        let max_log_degree = builder.get(&shape.max_log_degrees, i);
+                        let slack: Var<_> = builder.eval(max_log_degree - values.log_degree);
+                        builder.range_check_v(slack, LOG_DEGREE_BITS);
+                    }
                     let trace_domain = builder.get(&trace_domains, index);
                     let quotient_domain: TwoAdicMultiplicativeCosetVariable<_> =
                         builder.get(&quotient_domains, index);
@@ -42,6 +42,13 @@ pub struct VerifyingKeyVariable<C: Config> {
     pub prep_domains: Array<C, TwoAdicMultiplicativeCosetVariable<C>>,
 }
 
+/// The chips of a machine shape, see [crate::machine::MachineShape].
+#[derive(DslVariable, Clone)]
+pub struct MachineShapeVariable<C: Config> {
+    pub chip_mask: Array<C, Var<C::N>>,
+    pub max_log_degrees: Array<C, Var<C::N>>,
+}
+
 /// Reference: [sp1_core::stark::ShardCommitment]
 #[derive(DslVariable, Clone)]
 pub struct ShardCommitmentVariable<C: Config> {
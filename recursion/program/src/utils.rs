@@ -1,5 +1,5 @@
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
-use p3_commit::{ExtensionMmcs, TwoAdicMultiplicativeCoset};
+use p3_commit::{ExtensionMmcs, Pcs, TwoAdicMultiplicativeCoset};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field, TwoAdicField};
 use p3_fri::FriConfig;
@@ -18,7 +18,7 @@ use crate::challenger::DuplexChallengerVariable;
 use crate::fri::types::FriConfigVariable;
 use crate::fri::TwoAdicMultiplicativeCosetVariable;
 use crate::stark::EMPTY;
-use crate::types::{QuotientDataValues, VerifyingKeyVariable};
+use crate::types::{MachineShapeVariable, QuotientDataValues, VerifyingKeyVariable};
 
 type SC = BabyBearPoseidon2;
 type F = <SC as StarkGenericConfig>::Val;
@@ -168,11 +168,15 @@ pub fn get_challenger_public_values<C: Config>(
 
 /// Hash the verifying key + prep domains into a single digest.
 /// poseidon2( commit[0..8] || pc_start || prep_domains[N].{log_n, .size, .shift, .g})
+///
+/// Only the domains of the chips with a preprocessed trace in the key are hashed, in the order of
+/// their traces.
 pub fn hash_vkey<C: Config>(
     builder: &mut Builder<C>,
     vk: &VerifyingKeyVariable<C>,
 ) -> Array<C, Felt<C::F>> {
-    let domain_slots: Var<_> = builder.eval(vk.prep_domains.len() * 4);
+    let num_prep_traces = num_preprocessed_traces(builder, vk);
+    let domain_slots: Var<_> = builder.eval(num_prep_traces * C::N::from_canonical_usize(4));
     let vkey_slots: Var<_> = builder.constant(C::N::from_canonical_usize(DIGEST_SIZE + 1));
     let total_slots: Var<_> = builder.eval(vkey_slots + domain_slots);
     let mut inputs = builder.dyn_array(total_slots);
@@ -187,21 +191,76 @@ pub fn hash_vkey<C: Config>(
         .range(0, vk.prep_domains.len())
         .for_each(|i, builder| {
             let sorted_index = builder.get(&vk.preprocessed_sorted_idxs, i);
-            let domain = builder.get(&vk.prep_domains, i);
-            let log_n_index: Var<_> = builder.eval(vkey_slots + sorted_index * four);
-            let size_index: Var<_> = builder.eval(log_n_index + one);
-            let shift_index: Var<_> = builder.eval(size_index + one);
-            let g_index: Var<_> = builder.eval(shift_index + one);
-            let log_n_felt = var2felt(builder, domain.log_n);
-            let size_felt = var2felt(builder, domain.size);
-            builder.set(&mut inputs, log_n_index, log_n_felt);
-            builder.set(&mut inputs, size_index, size_felt);
-            builder.set(&mut inputs, shift_index, domain.shift);
-            builder.set(&mut inputs, g_index, domain.g);
+            builder
+                .if_ne(sorted_index, C::N::from_canonical_usize(EMPTY))
+                .then(|builder| {
+                    let domain = builder.get(&vk.prep_domains, i);
+                    let log_n_index: Var<_> = builder.eval(vkey_slots + sorted_index * four);
+                    let size_index: Var<_> = builder.eval(log_n_index + one);
+                    let shift_index: Var<_> = builder.eval(size_index + one);
+                    let g_index: Var<_> = builder.eval(shift_index + one);
+                    let log_n_felt = var2felt(builder, domain.log_n);
+                    let size_felt = var2felt(builder, domain.size);
+                    builder.set(&mut inputs, log_n_index, log_n_felt);
+                    builder.set(&mut inputs, size_index, size_felt);
+                    builder.set(&mut inputs, shift_index, domain.shift);
+                    builder.set(&mut inputs, g_index, domain.g);
+                });
         });
     builder.poseidon2_hash(&inputs)
 }
 
+/// The number of preprocessed traces of the key: its preprocessed chips whose index is not
+/// [EMPTY].
+pub fn num_preprocessed_traces<C: Config>(
+    builder: &mut Builder<C>,
+    vk: &VerifyingKeyVariable<C>,
+) -> Var<C::N> {
+    let num_prep_traces: Var<_> = builder.eval(C::N::zero());
+    builder
+        .range(0, vk.preprocessed_sorted_idxs.len())
+        .for_each(|i, builder| {
+            let sorted_index = builder.get(&vk.preprocessed_sorted_idxs, i);
+            builder
+                .if_ne(sorted_index, C::N::from_canonical_usize(EMPTY))
+                .then(|builder| {
+                    builder.assign(num_prep_traces, num_prep_traces + C::N::one());
+                });
+        });
+    num_prep_traces
+}
+
+/// Hash the verifying key together with the shape of the machine it is for, so that keys of the
+/// same program for different shapes have different digests.
+/// poseidon2( hash_vkey(vk)[0..8] || chip_mask[N] || max_log_degrees[N] )
+pub fn hash_runtime_vkey<C: Config>(
+    builder: &mut Builder<C>,
+    vk: &VerifyingKeyVariable<C>,
+    shape: &MachineShapeVariable<C>,
+) -> Array<C, Felt<C::F>> {
+    let vk_digest = hash_vkey(builder, vk);
+    let num_chips: Var<_> = shape.chip_mask.len().materialize(builder);
+    let total_slots: Var<_> =
+        builder.eval(num_chips * C::N::two() + C::N::from_canonical_usize(DIGEST_SIZE));
+    let mut inputs = builder.dyn_array(total_slots);
+    builder.range(0, DIGEST_SIZE).for_each(|i, builder| {
+        let element = builder.get(&vk_digest, i);
+        builder.set(&mut inputs, i, element);
+    });
+    let digest_slots: Var<_> = builder.constant(C::N::from_canonical_usize(DIGEST_SIZE));
+    builder.range(0, num_chips).for_each(|i, builder| {
+        let included = builder.get(&shape.chip_mask, i);
+        let max_log_degree = builder.get(&shape.max_log_degrees, i);
+        let mask_index: Var<_> = builder.eval(digest_slots + i);
+        let degree_index: Var<_> = builder.eval(digest_slots + num_chips + i);
+        let included_felt = var2felt(builder, included);
+        let max_log_degree_felt = var2felt(builder, max_log_degree);
+        builder.set(&mut inputs, mask_index, included_felt);
+        builder.set(&mut inputs, degree_index, max_log_degree_felt);
+    });
+    builder.poseidon2_hash(&inputs)
+}
+
 pub(crate) fn get_sorted_indices<SC: StarkGenericConfig, A: MachineAir<SC::Val>>(
     machine: &StarkMachine<SC, A>,
     proof: &ShardProof<SC>,
@@ -216,18 +275,24 @@ pub(crate) fn get_sorted_indices<SC: StarkGenericConfig, A: MachineAir<SC::Val>>
         .collect()
 }
 
+/// The index of the preprocessed trace of each preprocessed chip of `machine` in `vk`, and its
+/// domain. A chip without a preprocessed trace in `vk` has the index [EMPTY] and a placeholder
+/// domain.
 pub(crate) fn get_preprocessed_data<SC: StarkGenericConfig, A: MachineAir<SC::Val>>(
     machine: &StarkMachine<SC, A>,
     vk: &StarkVerifyingKey<SC>,
 ) -> (Vec<usize>, Vec<Dom<SC>>) {
     let chips = machine.chips();
+    let placeholder = machine.config().pcs().natural_domain_for_degree(1);
     let (prep_sorted_indices, prep_domains) = machine
         .preprocessed_chip_ids()
         .into_iter()
         .map(|chip_idx| {
             let name = chips[chip_idx].name().clone();
-            let prep_sorted_idx = vk.chip_ordering[&name];
-            (prep_sorted_idx, vk.chip_information[prep_sorted_idx].1)
+            match vk.chip_ordering.get(&name) {
+                Some(&prep_sorted_idx) => (prep_sorted_idx, vk.chip_information[prep_sorted_idx].1),
+                None => (EMPTY, placeholder),
+            }
         })
         .unzip();
     (prep_sorted_indices, prep_domains)
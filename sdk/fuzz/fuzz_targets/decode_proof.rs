@@ -0,0 +1,33 @@
+//! Decodes untrusted bytes as every kind of proof, which must fail with a `ProofDecodeError`
+//! instead of panicking or exhausting memory.
+//!
+//! Run with `cargo fuzz run decode_proof corpus/decode_proof` from `sdk/fuzz`.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use sp1_sdk::{ProofDecodeLimits, SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof};
+
+struct Limits {
+    core: ProofDecodeLimits,
+    compressed: ProofDecodeLimits,
+    plonk: ProofDecodeLimits,
+}
+
+fn limits() -> &'static Limits {
+    static LIMITS: OnceLock<Limits> = OnceLock::new();
+    LIMITS.get_or_init(|| Limits {
+        core: SP1Proof::default_limits(),
+        compressed: SP1CompressedProof::default_limits(),
+        plonk: SP1PlonkBn254Proof::default_limits(),
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let limits = limits();
+    let _ = SP1Proof::decode(data, &limits.core);
+    let _ = SP1CompressedProof::decode(data, &limits.compressed);
+    let _ = SP1PlonkBn254Proof::decode(data, &limits.plonk);
+});
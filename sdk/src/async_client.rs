@@ -0,0 +1,407 @@
+//! An async facade over [ProverClient] for services running on tokio.
+//!
+//! Each call of an [AsyncProverClient] is sent to a pool of threads owned by the client, not to
+//! the blocking pool of tokio, and runs the method of the same name of the wrapped [ProverClient].
+//! The returned [ProofJob] is a future resolving to its result, streaming [ProgressEvent]s as the
+//! proving stages run.
+//!
+//! Dropping a [ProofJob] cancels it. Cancellation is cooperative: a job still queued never starts,
+//! and a running job stops before its next proving stage or retry, the current stage running to
+//! completion in the background.
+//!
+//! ```no_run
+//! use sp1_sdk::{AsyncProverClient, ProverClient, SP1Stdin};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+//! let client = AsyncProverClient::new(ProverClient::new());
+//! let (pk, vk) = client.setup(elf).await?;
+//! let mut stdin = SP1Stdin::new();
+//! stdin.write(&10usize);
+//!
+//! let mut job = client.prove(&pk, stdin);
+//! let mut events = job.take_events().unwrap();
+//! tokio::spawn(async move {
+//!     while let Some(event) = events.recv().await {
+//!         println!("{:?}", event);
+//!     }
+//! });
+//! let proof = job.await?;
+//! client.verify(proof, vk).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex},
+    task::{Context, Poll},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use sp1_core::runtime::ExecutionReport;
+use sp1_prover::{SP1ProvingKey, SP1PublicValues, SP1Stdin, SP1VerifyingKey};
+use tokio::sync::{mpsc as async_mpsc, oneshot};
+
+use crate::{
+    retry::CancellationToken, ProverClient, SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof,
+};
+
+/// What a [ProofJob] is doing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A worker thread picked up the job.
+    Started,
+    /// An attempt of a proving stage started, see [crate::StageReport].
+    StageStarted { stage: String, attempt: u32 },
+    /// A proving stage finished, after all its attempts.
+    StageFinished {
+        stage: String,
+        succeeded: bool,
+        elapsed: Duration,
+    },
+    /// The job stopped because it was cancelled.
+    Cancelled,
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// The threads running the jobs of an [AsyncProverClient], in submission order.
+struct WorkerPool {
+    tasks: Mutex<mpsc::Sender<Task>>,
+}
+
+impl WorkerPool {
+    fn new(num_workers: usize) -> Self {
+        let (tasks, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..num_workers.max(1) {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("sp1-async-prover-{}", i))
+                .spawn(move || loop {
+                    // The lock is released before running the task.
+                    let task = receiver.lock().unwrap().recv();
+                    match task {
+                        Ok(task) => task(),
+                        // The pool was dropped.
+                        Err(_) => break,
+                    }
+                })
+                .expect("failed to spawn a prover thread");
+        }
+        Self {
+            tasks: Mutex::new(tasks),
+        }
+    }
+
+    fn execute(&self, task: Task) {
+        // The workers only stop once the sender is dropped, with the pool.
+        self.tasks.lock().unwrap().send(task).unwrap();
+    }
+}
+
+/// The job run by the current thread, read by the proving stages.
+struct JobContext {
+    token: CancellationToken,
+    events: async_mpsc::UnboundedSender<ProgressEvent>,
+}
+
+thread_local! {
+    static JOB: RefCell<Option<JobContext>> = const { RefCell::new(None) };
+}
+
+fn emit(event: ProgressEvent) {
+    JOB.with(|job| {
+        if let Some(job) = job.borrow().as_ref() {
+            // Nobody may be listening.
+            let _ = job.events.send(event);
+        }
+    });
+}
+
+/// Fails if the job of the current thread was cancelled, which is checked before each attempt of
+/// a proving stage. Always succeeds outside of a job.
+pub(crate) fn check_cancelled(stage: &str) -> Result<()> {
+    let cancelled = JOB.with(|job| {
+        job.borrow()
+            .as_ref()
+            .map_or(false, |job| job.token.is_cancelled())
+    });
+    if cancelled {
+        return Err(anyhow!("the proof was cancelled before stage {}", stage));
+    }
+    Ok(())
+}
+
+/// Reports an attempt of a proving stage. A no-op outside of a job.
+pub(crate) fn stage_started(stage: &str, attempt: u32) {
+    emit(ProgressEvent::StageStarted {
+        stage: stage.to_string(),
+        attempt,
+    });
+}
+
+/// Reports a proving stage which finished after all its attempts. A no-op outside of a job.
+pub(crate) fn stage_finished(stage: &str, succeeded: bool, elapsed: Duration) {
+    emit(ProgressEvent::StageFinished {
+        stage: stage.to_string(),
+        succeeded,
+        elapsed,
+    });
+}
+
+/// A call of an [AsyncProverClient], resolving to its result. Dropping it cancels the call, see
+/// the module documentation.
+#[must_use = "a job is cancelled when dropped"]
+pub struct ProofJob<T> {
+    result: oneshot::Receiver<Result<T>>,
+    token: CancellationToken,
+    events: Option<async_mpsc::UnboundedReceiver<ProgressEvent>>,
+}
+
+impl<T> ProofJob<T> {
+    /// The progress of the job. The stream ends when the job does, and is only returned once.
+    pub fn take_events(&mut self) -> Option<async_mpsc::UnboundedReceiver<ProgressEvent>> {
+        self.events.take()
+    }
+
+    /// Cancels the job, which then resolves to an error unless it already completed.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+}
+
+impl<T> Future for ProofJob<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.result)
+            .poll(cx)
+            .map(|result| result.unwrap_or_else(|_| Err(anyhow!("the prover thread stopped"))))
+    }
+}
+
+impl<T> Drop for ProofJob<T> {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// A [ProverClient] whose calls run on dedicated threads, see the module documentation.
+pub struct AsyncProverClient {
+    client: Arc<ProverClient>,
+    pool: WorkerPool,
+}
+
+impl AsyncProverClient {
+    /// Wraps `client`, running one call at a time. Proving already uses every core, so further
+    /// calls wait for the previous ones.
+    pub fn new(client: ProverClient) -> Self {
+        Self::with_workers(client, 1)
+    }
+
+    /// Wraps `client`, running up to `num_workers` calls at a time.
+    pub fn with_workers(client: ProverClient, num_workers: usize) -> Self {
+        Self {
+            client: Arc::new(client),
+            pool: WorkerPool::new(num_workers),
+        }
+    }
+
+    /// The wrapped client.
+    pub fn client(&self) -> &ProverClient {
+        &self.client
+    }
+
+    /// Runs `f` on the client in the pool.
+    fn spawn<T, F>(&self, f: F) -> ProofJob<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ProverClient) -> Result<T> + Send + 'static,
+    {
+        let token = CancellationToken::default();
+        let (events, events_receiver) = async_mpsc::unbounded_channel();
+        let (result_sender, result) = oneshot::channel();
+        let client = self.client.clone();
+        let job = JobContext {
+            token: token.clone(),
+            events,
+        };
+        self.pool.execute(Box::new(move || {
+            let (events, cancelled) = (job.events.clone(), job.token.clone());
+            let result = if job.token.is_cancelled() {
+                Err(anyhow!("the proof was cancelled before it started"))
+            } else {
+                let _ = events.send(ProgressEvent::Started);
+                JOB.with(|current| *current.borrow_mut() = Some(job));
+                let result = catch_unwind(AssertUnwindSafe(|| f(&client)))
+                    .unwrap_or_else(|_| Err(anyhow!("the prover panicked")));
+                JOB.with(|current| current.borrow_mut().take());
+                result
+            };
+            if result.is_err() && cancelled.is_cancelled() {
+                let _ = events.send(ProgressEvent::Cancelled);
+            }
+            // Dropping the last sender closes the progress stream.
+            drop(events);
+            // The job may have been dropped.
+            let _ = result_sender.send(result);
+        }));
+        ProofJob {
+            result,
+            token,
+            events: Some(events_receiver),
+        }
+    }
+
+    /// See [ProverClient::setup].
+    pub fn setup(&self, elf: &[u8]) -> ProofJob<(SP1ProvingKey, SP1VerifyingKey)> {
+        let elf = elf.to_vec();
+        self.spawn(move |client| Ok(client.setup(&elf)))
+    }
+
+    /// See [ProverClient::execute].
+    pub fn execute(
+        &self,
+        elf: &[u8],
+        stdin: SP1Stdin,
+    ) -> ProofJob<(SP1PublicValues, ExecutionReport)> {
+        let elf = elf.to_vec();
+        self.spawn(move |client| client.execute(&elf, stdin))
+    }
+
+    /// See [ProverClient::prove].
+    pub fn prove(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> ProofJob<SP1Proof> {
+        let pk = pk.clone();
+        self.spawn(move |client| client.prove(&pk, stdin))
+    }
+
+    /// See [ProverClient::prove_compressed].
+    pub fn prove_compressed(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: SP1Stdin,
+    ) -> ProofJob<SP1CompressedProof> {
+        let pk = pk.clone();
+        self.spawn(move |client| client.prove_compressed(&pk, stdin))
+    }
+
+    /// See [ProverClient::prove_plonk].
+    pub fn prove_plonk(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> ProofJob<SP1PlonkBn254Proof> {
+        let pk = pk.clone();
+        self.spawn(move |client| client.prove_plonk(&pk, stdin))
+    }
+
+    /// See [ProverClient::verify]. The error is a [crate::SP1VerificationError].
+    pub fn verify(&self, proof: SP1Proof, vk: SP1VerifyingKey) -> ProofJob<()> {
+        self.spawn(move |client| Ok(client.verify(&proof, &vk)?))
+    }
+
+    /// See [ProverClient::verify_compressed]. The error is a [crate::SP1VerificationError].
+    pub fn verify_compressed(
+        &self,
+        proof: SP1CompressedProof,
+        vk: SP1VerifyingKey,
+    ) -> ProofJob<()> {
+        self.spawn(move |client| Ok(client.verify_compressed(&proof, &vk)?))
+    }
+
+    /// See [ProverClient::verify_plonk]. The error is a [crate::SP1VerificationError].
+    pub fn verify_plonk(&self, proof: SP1PlonkBn254Proof, vk: SP1VerifyingKey) -> ProofJob<()> {
+        self.spawn(move |client| Ok(client.verify_plonk(&proof, &vk)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::utils;
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+
+    fn stdin() -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        stdin
+    }
+
+    #[tokio::test]
+    async fn test_prove_does_not_block_the_runtime() {
+        utils::setup_logger();
+        let client = AsyncProverClient::new(ProverClient::local());
+        let ticks = Arc::new(AtomicU64::new(0));
+        let ticker = tokio::spawn({
+            let ticks = ticks.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    ticks.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        let (pk, vk) = client.setup(FIBONACCI_ELF).await.unwrap();
+        let mut job = client.prove(&pk, stdin());
+        let mut events = job.take_events().unwrap();
+        let proof = job.await.unwrap();
+        client.verify(proof, vk).await.unwrap();
+
+        // The runtime has a single thread, which kept ticking while the proof was generated.
+        assert!(ticks.load(Ordering::SeqCst) > 10);
+        ticker.abort();
+
+        assert_eq!(events.recv().await, Some(ProgressEvent::Started));
+        assert!(matches!(
+            events.recv().await,
+            Some(ProgressEvent::StageStarted { stage, attempt: 1 }) if stage == "core"
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(ProgressEvent::StageFinished { stage, succeeded: true, .. }) if stage == "core"
+        ));
+        assert_eq!(events.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_drop_cancels_proof() {
+        utils::setup_logger();
+        let client = AsyncProverClient::new(ProverClient::local());
+        let (pk, _) = client.setup(FIBONACCI_ELF).await.unwrap();
+
+        // Drop the job while its core stage runs: the compress stage never starts.
+        let mut job = client.prove_compressed(&pk, stdin());
+        let mut events = job.take_events().unwrap();
+        assert_eq!(events.recv().await, Some(ProgressEvent::Started));
+        assert!(matches!(
+            events.recv().await,
+            Some(ProgressEvent::StageStarted { stage, .. }) if stage == "core"
+        ));
+        drop(job);
+
+        let mut rest = Vec::new();
+        while let Some(event) = events.recv().await {
+            rest.push(event);
+        }
+        assert_eq!(rest.len(), 2);
+        assert!(matches!(&rest[0], ProgressEvent::StageFinished { stage, .. } if stage == "core"));
+        assert_eq!(rest[1], ProgressEvent::Cancelled);
+
+        // A job cancelled while queued never starts.
+        let blocker = client.prove(&pk, stdin());
+        let mut queued = client.prove(&pk, stdin());
+        queued.cancel();
+        let mut events = queued.take_events().unwrap();
+        assert!(queued.await.is_err());
+        assert_eq!(events.recv().await, Some(ProgressEvent::Cancelled));
+        blocker.await.unwrap();
+    }
+}
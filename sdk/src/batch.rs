@@ -0,0 +1,399 @@
+//! Proving many inputs of a program and aggregating the proofs into one.
+//!
+//! A [BatchProver] proves each [SP1Stdin] of a batch (for example one block of a block range) to a
+//! compressed proof, running up to [BatchProver::with_concurrency] proofs at a time, and then
+//! proves an aggregation program that verifies all of them. Completed items can be recorded in a
+//! [BatchManifest] so that a batch that failed part way can be resumed without proving them again.
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sp1_core::runtime::ExecutionReport;
+use thiserror::Error;
+
+use crate::{
+    HashableKey, ProverClient, SP1CompressedProof, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+};
+
+/// Proves a batch of inputs of a program and aggregates the proofs.
+///
+/// The aggregation program reads the inputs written by [BatchProver::aggregation_stdin]: the
+/// verifying key digests of the items as a `Vec<[u32; 8]>`, then their public values as a
+/// `Vec<Vec<u8>>`, both in the order of the batch, and verifies each proof with
+/// `sp1_zkvm::precompiles::verify::verify_sp1_proof`. See `examples/aggregation/program` for such a
+/// program.
+pub struct BatchProver<'a> {
+    client: &'a ProverClient,
+    concurrency: usize,
+    manifest: Option<PathBuf>,
+}
+
+/// The result of [BatchProver::prove].
+#[derive(Debug, Clone)]
+pub struct BatchProof {
+    /// The compressed proof of the aggregation program.
+    pub aggregate: SP1CompressedProof,
+    /// The compressed proofs of the items, in the order of the batch.
+    pub proofs: Vec<SP1CompressedProof>,
+    /// The execution reports of the items, in the order of the batch.
+    pub reports: Vec<ExecutionReport>,
+}
+
+/// A failed item of a batch.
+#[derive(Error, Debug)]
+#[error("batch item {index} failed: {error:#}")]
+pub struct BatchItemError {
+    pub index: usize,
+    pub error: anyhow::Error,
+}
+
+#[derive(Error, Debug)]
+pub enum BatchError {
+    /// Some items failed. The proofs of the other items are kept, and are recorded in the manifest
+    /// if there is one.
+    #[error("{}", items_failed_message(.failures, .completed.len()))]
+    Items {
+        failures: Vec<BatchItemError>,
+        completed: Vec<Option<SP1CompressedProof>>,
+    },
+    #[error("failed to aggregate the batch: {0:#}")]
+    Aggregation(anyhow::Error),
+    #[error("failed to access the batch manifest: {0:#}")]
+    Manifest(anyhow::Error),
+}
+
+fn items_failed_message(failures: &[BatchItemError], total: usize) -> String {
+    let indices = failures
+        .iter()
+        .map(|failure| failure.index.to_string())
+        .collect::<Vec<_>>();
+    format!(
+        "{} of {} batch items failed (indices {}), first error: {}",
+        failures.len(),
+        total,
+        indices.join(", "),
+        failures[0]
+    )
+}
+
+/// The items of a batch that have been proven, stored as a JSON file next to their proofs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchManifest {
+    /// The digest of the verifying key of the program, see [HashableKey::bytes32].
+    pub vkey: String,
+    pub items: Vec<Option<BatchManifestItem>>,
+}
+
+/// A proven item of a [BatchManifest].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchManifestItem {
+    /// The hex encoded [SP1Stdin::digest] of the input the proof was generated with.
+    pub input_digest: String,
+    /// The path of the proof, relative to the manifest.
+    pub proof: PathBuf,
+}
+
+impl BatchManifest {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path.as_ref())
+            .with_context(|| format!("failed to open {}", path.as_ref().display()))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        // Write to a temporary file first so that an interrupted batch never leaves a truncated
+        // manifest behind.
+        let path = path.as_ref();
+        let tmp = path.with_extension("json.tmp");
+        serde_json::to_writer_pretty(File::create(&tmp)?, self)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// The proof of the item at `index`, if it was proven with the same program and input.
+    fn proof(
+        &self,
+        dir: &Path,
+        vkey: &str,
+        index: usize,
+        stdin: &SP1Stdin,
+    ) -> Option<SP1CompressedProof> {
+        let item = self.items.get(index)?.as_ref()?;
+        if self.vkey != vkey || item.input_digest != hex::encode(stdin.digest()) {
+            return None;
+        }
+        let path = dir.join(&item.proof);
+        if !path.exists() {
+            return None;
+        }
+        SP1CompressedProof::load(path).ok()
+    }
+}
+
+impl<'a> BatchProver<'a> {
+    /// Creates a [BatchProver] proving one item at a time with `client`.
+    pub fn new(client: &'a ProverClient) -> Self {
+        Self {
+            client,
+            concurrency: 1,
+            manifest: None,
+        }
+    }
+
+    /// Sets the number of items proven at the same time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "concurrency must be positive");
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Records the proven items in the manifest at `path`, and reuses the items already recorded
+    /// there. The proofs are saved in the directory of the manifest.
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest = Some(path.into());
+        self
+    }
+
+    /// Proves each input of `inputs` with the program `elf`, then aggregates the proofs with the
+    /// program `aggregation_elf`.
+    pub fn prove(
+        &self,
+        elf: &[u8],
+        aggregation_elf: &[u8],
+        inputs: Vec<SP1Stdin>,
+    ) -> Result<BatchProof, BatchError> {
+        assert!(!inputs.is_empty(), "cannot prove an empty batch");
+        let (pk, vk) = self.client.setup(elf);
+        let vkey = vk.bytes32();
+
+        let previous = match &self.manifest {
+            Some(path) if path.exists() => {
+                Some(BatchManifest::load(path).map_err(BatchError::Manifest)?)
+            }
+            _ => None,
+        };
+        // Keep the items of the previous manifest until they are checked against the inputs, so
+        // that interrupting the batch again doesn't forget them.
+        let mut items = previous
+            .as_ref()
+            .filter(|previous| previous.vkey == vkey)
+            .map(|previous| previous.items.clone())
+            .unwrap_or_default();
+        items.resize(inputs.len(), None);
+        let manifest = Mutex::new(BatchManifest {
+            vkey: vkey.clone(),
+            items,
+        });
+        let dir = self
+            .manifest
+            .as_ref()
+            .map(|path| path.parent().unwrap_or(Path::new(".")).to_path_buf());
+
+        // Prove the items, each worker taking the next item that hasn't been taken yet.
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(
+            (0..inputs.len())
+                .map(|_| None)
+                .collect::<Vec<Option<Result<(SP1CompressedProof, ExecutionReport)>>>>(),
+        );
+        thread::scope(|s| {
+            for _ in 0..self.concurrency.min(inputs.len()) {
+                s.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(stdin) = inputs.get(index) else {
+                        break;
+                    };
+                    let reused = previous
+                        .as_ref()
+                        .zip(dir.as_ref())
+                        .and_then(|(m, dir)| m.proof(dir, &vkey, index, stdin));
+                    let is_reused = reused.is_some();
+                    let result = self.prove_item(&pk, elf, stdin, reused).and_then(|item| {
+                        let proof = (!is_reused).then_some(&item.0);
+                        self.record(&manifest, dir.as_deref(), index, stdin, proof)?;
+                        Ok(item)
+                    });
+                    if let Err(err) = &result {
+                        tracing::warn!("batch item {} failed: {:#}", index, err);
+                    }
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+
+        let mut proofs = Vec::with_capacity(inputs.len());
+        let mut reports = Vec::with_capacity(inputs.len());
+        let mut failures = Vec::new();
+        for (index, result) in results.into_inner().unwrap().into_iter().enumerate() {
+            match result.expect("every item is proven") {
+                Ok((proof, report)) => {
+                    proofs.push(Some(proof));
+                    reports.push(report);
+                }
+                Err(error) => {
+                    proofs.push(None);
+                    failures.push(BatchItemError { index, error });
+                }
+            }
+        }
+        if !failures.is_empty() {
+            return Err(BatchError::Items {
+                failures,
+                completed: proofs,
+            });
+        }
+        let proofs = proofs.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+        let (aggregation_pk, _) = self.client.setup(aggregation_elf);
+        let stdin = Self::aggregation_stdin(&vk, &proofs);
+        let aggregate = self
+            .client
+            .prove_compressed(&aggregation_pk, stdin)
+            .map_err(BatchError::Aggregation)?;
+
+        Ok(BatchProof {
+            aggregate,
+            proofs,
+            reports,
+        })
+    }
+
+    /// The input of the aggregation program for the proofs of a batch of the program of `vk`.
+    pub fn aggregation_stdin(vk: &SP1VerifyingKey, proofs: &[SP1CompressedProof]) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write::<Vec<[u32; 8]>>(&vec![vk.hash_u32(); proofs.len()]);
+        let public_values = proofs
+            .iter()
+            .map(|proof| proof.public_values.to_vec())
+            .collect::<Vec<_>>();
+        stdin.write::<Vec<Vec<u8>>>(&public_values);
+        for proof in proofs {
+            stdin.write_proof(proof.proof.clone(), vk.vk.clone());
+        }
+        stdin
+    }
+
+    /// Executes an item for its report, and proves it unless a proof was already recorded.
+    fn prove_item(
+        &self,
+        pk: &SP1ProvingKey,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+        reused: Option<SP1CompressedProof>,
+    ) -> Result<(SP1CompressedProof, ExecutionReport)> {
+        let (_, report) = self.client.execute(elf, stdin.clone())?;
+        let proof = match reused {
+            Some(proof) => proof,
+            None => self.client.prove_compressed(pk, stdin.clone())?,
+        };
+        Ok((proof, report))
+    }
+
+    /// Records the item at `index` in the manifest, saving its proof unless it is reused from the
+    /// manifest.
+    fn record(
+        &self,
+        manifest: &Mutex<BatchManifest>,
+        dir: Option<&Path>,
+        index: usize,
+        stdin: &SP1Stdin,
+        proof: Option<&SP1CompressedProof>,
+    ) -> Result<()> {
+        let (Some(path), Some(dir)) = (&self.manifest, dir) else {
+            return Ok(());
+        };
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "batch".to_string());
+        let proof_path = PathBuf::from(format!("{}-{}.bin", stem, index));
+        if let Some(proof) = proof {
+            proof.save(dir.join(&proof_path))?;
+        }
+
+        let mut manifest = manifest.lock().unwrap();
+        manifest.items[index] = Some(BatchManifestItem {
+            input_digest: hex::encode(stdin.digest()),
+            proof: proof_path,
+        });
+        manifest.save(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn test_e2e_batch_prove() {
+        utils::setup_logger();
+        let client = ProverClient::local();
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let aggregation_elf =
+            include_bytes!("../../examples/aggregation/program/elf/riscv32im-succinct-zkvm-elf");
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("batch.json");
+
+        let blocks = [10u32, 20, 30];
+        let inputs = blocks
+            .iter()
+            .map(|n| {
+                let mut stdin = SP1Stdin::new();
+                stdin.write(n);
+                stdin
+            })
+            .collect::<Vec<_>>();
+        let batch = BatchProver::new(&client)
+            .with_concurrency(2)
+            .with_manifest(&manifest_path)
+            .prove(elf, aggregation_elf, inputs.clone())
+            .unwrap();
+
+        // The items are kept in the order of the batch.
+        assert_eq!(batch.reports.len(), blocks.len());
+        for (proof, n) in batch.proofs.iter().zip(blocks) {
+            assert_eq!(proof.public_values.clone().read::<u32>(), n);
+        }
+
+        // The aggregation program commits to the public values of the items in order.
+        let (_, vk) = client.setup(elf);
+        let mut expected = (blocks.len() as u32).to_be_bytes().to_vec();
+        for _ in blocks {
+            for word in vk.hash_u32() {
+                expected.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        for proof in batch.proofs.iter() {
+            let public_values = proof.public_values.to_vec();
+            expected.extend_from_slice(&(public_values.len() as u32).to_be_bytes());
+            expected.extend_from_slice(&public_values);
+        }
+        assert_eq!(batch.aggregate.public_values.to_vec(), expected);
+        let (_, aggregation_vk) = client.setup(aggregation_elf);
+        client
+            .verify_compressed(&batch.aggregate, &aggregation_vk)
+            .unwrap();
+
+        // Every item is recorded in the manifest, and can be reused by the next run.
+        let manifest = BatchManifest::load(&manifest_path).unwrap();
+        assert_eq!(manifest.vkey, vk.bytes32());
+        for (index, stdin) in inputs.iter().enumerate() {
+            let proof = manifest.proof(dir.path(), &vk.bytes32(), index, stdin);
+            assert_eq!(
+                proof.unwrap().public_values.to_vec(),
+                batch.proofs[index].public_values.to_vec()
+            );
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! The programs built from one guest crate, proven by name and identified together.
+//!
+//! A guest crate with several binaries, for example one per feature set, is built into one ELF per
+//! binary with `sp1_helper::BuildArgs::binaries`. A [ProgramBundle] sets up each of them under a
+//! name, dispatches the proofs by name, and commits to the verifying keys of all of them with
+//! [ProgramBundle::digest]: the root of a Merkle tree over their digests, so that a contract storing
+//! the root accepts the proofs of any program of the bundle given its [ProgramBundle::merkle_proof].
+//!
+//! ```no_run
+//! use sp1_sdk::{ProgramBundle, ProverClient, SP1Stdin};
+//!
+//! let client = ProverClient::new();
+//! let bundle = ProgramBundle::new(&client)
+//!     .with_program(
+//!         "fibonacci",
+//!         include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf"),
+//!     )
+//!     .unwrap()
+//!     .with_program(
+//!         "is-prime",
+//!         include_bytes!("../../examples/is-prime/program/elf/riscv32im-succinct-zkvm-elf"),
+//!     )
+//!     .unwrap();
+//! let mut stdin = SP1Stdin::new();
+//! stdin.write(&10u32);
+//! let proof = bundle.prove("fibonacci", stdin).unwrap();
+//! bundle.verify("fibonacci", &proof).unwrap();
+//! let root = bundle.digest();
+//! ```
+
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+use sp1_core::runtime::ExecutionReport;
+use sp1_prover::{HashableKey, SP1ProvingKey, SP1PublicValues, SP1Stdin, SP1VerifyingKey};
+use thiserror::Error;
+
+use crate::{ProverClient, SP1CompressedProof, SP1Proof, SP1VerificationError};
+
+/// An error of a [ProgramBundle] operation.
+#[derive(Error, Debug)]
+pub enum BundleError {
+    #[error("the bundle has no program named `{0}`")]
+    UnknownProgram(String),
+    #[error("the bundle already has a program named `{0}`")]
+    DuplicateProgram(String),
+    #[error(transparent)]
+    Prover(#[from] anyhow::Error),
+    #[error(transparent)]
+    Verification(#[from] SP1VerificationError),
+}
+
+/// A program of a [ProgramBundle], with its keys.
+pub struct BundledProgram {
+    pub elf: Vec<u8>,
+    pub pk: SP1ProvingKey,
+    pub vk: SP1VerifyingKey,
+}
+
+/// Named programs proven with the same client, see the module documentation.
+pub struct ProgramBundle<'a> {
+    client: &'a ProverClient,
+    programs: BTreeMap<String, BundledProgram>,
+}
+
+impl<'a> ProgramBundle<'a> {
+    pub fn new(client: &'a ProverClient) -> Self {
+        Self {
+            client,
+            programs: BTreeMap::new(),
+        }
+    }
+
+    /// Sets up `elf` and adds it to the bundle under `name`.
+    pub fn with_program(
+        mut self,
+        name: impl Into<String>,
+        elf: &[u8],
+    ) -> Result<Self, BundleError> {
+        let name = name.into();
+        if self.programs.contains_key(&name) {
+            return Err(BundleError::DuplicateProgram(name));
+        }
+        let (pk, vk) = self.client.setup(elf);
+        let program = BundledProgram {
+            elf: elf.to_vec(),
+            pk,
+            vk,
+        };
+        self.programs.insert(name, program);
+        Ok(self)
+    }
+
+    /// The names of the programs, in lexicographic order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.programs.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&BundledProgram, BundleError> {
+        self.programs
+            .get(name)
+            .ok_or_else(|| BundleError::UnknownProgram(name.to_string()))
+    }
+
+    /// The verifying key of the program `name`.
+    pub fn vk(&self, name: &str) -> Result<&SP1VerifyingKey, BundleError> {
+        Ok(&self.get(name)?.vk)
+    }
+
+    /// Executes the program `name`, see [ProverClient::execute].
+    pub fn execute(
+        &self,
+        name: &str,
+        stdin: SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport), BundleError> {
+        Ok(self.client.execute(&self.get(name)?.elf, stdin)?)
+    }
+
+    /// Proves the program `name`, see [ProverClient::prove].
+    pub fn prove(&self, name: &str, stdin: SP1Stdin) -> Result<SP1Proof, BundleError> {
+        Ok(self.client.prove(&self.get(name)?.pk, stdin)?)
+    }
+
+    /// Proves the program `name` in the compressed mode, see [ProverClient::prove_compressed].
+    pub fn prove_compressed(
+        &self,
+        name: &str,
+        stdin: SP1Stdin,
+    ) -> Result<SP1CompressedProof, BundleError> {
+        Ok(self.client.prove_compressed(&self.get(name)?.pk, stdin)?)
+    }
+
+    /// Verifies a proof of the program `name`.
+    pub fn verify(&self, name: &str, proof: &SP1Proof) -> Result<(), BundleError> {
+        Ok(self.client.verify(proof, self.vk(name)?)?)
+    }
+
+    /// Verifies a compressed proof of the program `name`.
+    pub fn verify_compressed(
+        &self,
+        name: &str,
+        proof: &SP1CompressedProof,
+    ) -> Result<(), BundleError> {
+        Ok(self.client.verify_compressed(proof, self.vk(name)?)?)
+    }
+
+    /// The root of the Merkle tree over the verifying key digests of the programs, see
+    /// [bundle_root]. It only depends on the set of programs, not on their names nor on the order
+    /// they were added in.
+    pub fn digest(&self) -> [u8; 32] {
+        bundle_root(&self.vkey_digests())
+    }
+
+    /// The sibling nodes proving that the program `name` is in the bundle, from the leaf to the
+    /// root, checked by [verify_bundle_membership].
+    pub fn merkle_proof(&self, name: &str) -> Result<Vec<[u8; 32]>, BundleError> {
+        let leaf = self.vk(name)?.hash_bytes();
+        Ok(bundle_merkle_proof(&self.vkey_digests(), leaf))
+    }
+
+    fn vkey_digests(&self) -> Vec<[u8; 32]> {
+        self.programs
+            .values()
+            .map(|program| program.vk.hash_bytes())
+            .collect()
+    }
+}
+
+/// Hashes two nodes in their lexicographic order, so that a proof doesn't need to say which side
+/// each sibling is on.
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    Sha256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// The layers of the Merkle tree over the sorted `leaves`, from the leaves to the root. A node
+/// without a sibling is moved up to the next layer.
+fn merkle_layers(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    let mut leaves = leaves.to_vec();
+    leaves.sort_unstable();
+    let mut layers = vec![leaves];
+    while layers.last().unwrap().len() > 1 {
+        let next = layers
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [node] => *node,
+                _ => unreachable!(),
+            })
+            .collect();
+        layers.push(next);
+    }
+    layers
+}
+
+/// The root of the SHA-256 Merkle tree over the verifying key digests `leaves`, see
+/// [HashableKey::hash_bytes], which are sorted first. The root of a single key is its digest, and
+/// the root of no key is zero.
+pub fn bundle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    merkle_layers(leaves)
+        .last()
+        .and_then(|root| root.first().copied())
+        .unwrap_or_default()
+}
+
+/// The sibling nodes of `leaf` in the tree of [bundle_root], from the leaf to the root.
+fn bundle_merkle_proof(leaves: &[[u8; 32]], leaf: [u8; 32]) -> Vec<[u8; 32]> {
+    let layers = merkle_layers(leaves);
+    let mut index = layers[0].binary_search(&leaf).unwrap();
+    let mut proof = Vec::new();
+    for layer in &layers[..layers.len() - 1] {
+        if let Some(sibling) = layer.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+/// Checks that the verifying key digest `leaf` is in the bundle of digest `root`, given its
+/// [ProgramBundle::merkle_proof]. This is the check a contract storing the root makes.
+pub fn verify_bundle_membership(root: [u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling))
+        == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<[u8; 32]> {
+        (0..n).map(|i| [i.wrapping_mul(37); 32]).collect()
+    }
+
+    #[test]
+    fn test_bundle_root() {
+        assert_eq!(bundle_root(&[]), [0; 32]);
+        assert_eq!(bundle_root(&leaves(1)), leaves(1)[0]);
+
+        for n in 2..=7 {
+            let leaves = leaves(n);
+            let root = bundle_root(&leaves);
+            let mut reversed = leaves.clone();
+            reversed.reverse();
+            assert_eq!(bundle_root(&reversed), root);
+
+            for leaf in &leaves {
+                let proof = bundle_merkle_proof(&leaves, *leaf);
+                assert!(verify_bundle_membership(root, *leaf, &proof));
+                assert!(!verify_bundle_membership(root, [0xff; 32], &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn test_program_bundle() {
+        let client = ProverClient::local();
+        let fibonacci =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let is_prime =
+            include_bytes!("../../examples/is-prime/program/elf/riscv32im-succinct-zkvm-elf");
+        let bundle = ProgramBundle::new(&client)
+            .with_program("fibonacci", fibonacci)
+            .unwrap()
+            .with_program("is-prime", is_prime)
+            .unwrap();
+        assert_eq!(
+            bundle.names().collect::<Vec<_>>(),
+            vec!["fibonacci", "is-prime"]
+        );
+
+        // The digest only depends on the programs.
+        let reordered = ProgramBundle::new(&client)
+            .with_program("b", is_prime)
+            .unwrap()
+            .with_program("a", fibonacci)
+            .unwrap();
+        assert_eq!(reordered.digest(), bundle.digest());
+        for name in bundle.names() {
+            let leaf = bundle.vk(name).unwrap().hash_bytes();
+            let proof = bundle.merkle_proof(name).unwrap();
+            assert!(verify_bundle_membership(bundle.digest(), leaf, &proof));
+        }
+
+        assert!(matches!(
+            bundle.prove("missing", SP1Stdin::new()),
+            Err(BundleError::UnknownProgram(_))
+        ));
+        assert!(matches!(
+            ProgramBundle::new(&client)
+                .with_program("fibonacci", fibonacci)
+                .unwrap()
+                .with_program("fibonacci", is_prime),
+            Err(BundleError::DuplicateProgram(_))
+        ));
+    }
+}
@@ -0,0 +1,406 @@
+//! Estimates of the cost of proving a program from its execution report.
+//!
+//! A [CostModel] prices each stage of a proof from the size of the execution: the core stage from
+//! the rows of the core traces and the bytes they commit to, the compress stage from the number of
+//! recursion proofs in the reduce tree, and the shrink and wrap stages at a fixed cost. The
+//! coefficients can be fitted to the stage timings of actual proofs with [CostModel::fit] and
+//! saved to or loaded from a JSON calibration file.
+
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sp1_core::runtime::{ExecutionReport, ShardingConfig};
+use sp1_prover::ReducePlan;
+
+use crate::ProvingReport;
+
+/// The stages of a [ProvingReport] priced by [CostModel::wrap_seconds].
+const WRAP_STAGES: [&str; 3] = ["shrink", "wrap_bn254", "wrap_plonk_bn254"];
+
+/// The coefficients used to estimate the cost of a proof.
+///
+/// The defaults are rough figures for a 32-core machine, fit a model to your own hardware with
+/// [CostModel::fit] for accurate estimates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CostModel {
+    /// The seconds spent on each row of the core traces.
+    pub core_seconds_per_row: f64,
+    /// The seconds spent on each byte of a column of the padded core traces. The width of the
+    /// traces is folded into the coefficient.
+    pub core_seconds_per_committed_byte: f64,
+    /// The seconds spent on each proof of the reduce tree.
+    pub recursion_seconds_per_proof: f64,
+    /// The seconds spent shrinking and wrapping the compressed proof.
+    pub wrap_seconds: f64,
+    /// The price of a second of the machine.
+    pub dollars_per_second: f64,
+    /// The relative error of the estimates, used as their confidence bounds.
+    pub relative_error: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            core_seconds_per_row: 4e-6,
+            core_seconds_per_committed_byte: 2e-7,
+            recursion_seconds_per_proof: 5.0,
+            wrap_seconds: 90.0,
+            dollars_per_second: 3.0 / 3600.0,
+            relative_error: 0.5,
+        }
+    }
+}
+
+/// The estimated seconds of a stage, with the bounds given by [CostModel::relative_error].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StageEstimate {
+    pub seconds: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// The estimated cost of proving an execution, broken down by stage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub num_shards: u64,
+    pub trace_rows: u64,
+    pub committed_bytes: u64,
+    pub recursion_proofs: u64,
+    pub core: StageEstimate,
+    /// The compress stage, reducing the shard proofs to a single proof.
+    pub recursion: StageEstimate,
+    /// The shrink and wrap stages, only run for PLONK proofs.
+    pub wrap: StageEstimate,
+    pub total: StageEstimate,
+    pub dollars: StageEstimate,
+}
+
+/// The size of an execution, as priced by a [CostModel].
+#[derive(Debug, Clone, Copy)]
+struct Workload {
+    num_shards: u64,
+    trace_rows: u64,
+    committed_bytes: u64,
+    recursion_proofs: u64,
+}
+
+impl Workload {
+    fn new(report: &ExecutionReport, shard_size: usize) -> Self {
+        let cycles = report.total_instruction_count();
+        let num_shards = cycles.div_ceil(shard_size as u64).max(1);
+        let trace_rows = cycles + report.total_precompile_rows();
+        // A BabyBear element per row of each column of the padded traces.
+        let committed_bytes = num_shards * shard_size.next_power_of_two() as u64 * 4;
+        let plan = ReducePlan::default_for(num_shards as usize, 0);
+        let recursion_proofs = plan.layers.iter().map(Vec::len).sum::<usize>() as u64;
+        Self {
+            num_shards,
+            trace_rows,
+            committed_bytes,
+            recursion_proofs,
+        }
+    }
+}
+
+/// The execution of a program together with the stages run to prove it, used to fit a
+/// [CostModel].
+#[derive(Debug, Clone)]
+pub struct CalibrationSample {
+    pub report: ExecutionReport,
+    /// The shard size the program was proven with.
+    pub shard_size: usize,
+    pub proving_report: ProvingReport,
+}
+
+impl CostModel {
+    /// Loads a model from a JSON calibration file.
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Saves the model to a JSON calibration file.
+    pub fn to_json_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Estimates the cost of proving the execution of `report` with the shards of `sharding`.
+    pub fn estimate(&self, report: &ExecutionReport, sharding: &ShardingConfig) -> CostEstimate {
+        let workload = Workload::new(report, sharding.shard_size());
+        let core = self.stage(self.core_seconds(&workload));
+        let recursion = self.stage(self.recursion_seconds(&workload));
+        let wrap = self.stage(self.wrap_seconds);
+        let total = self.stage(core.seconds + recursion.seconds + wrap.seconds);
+        let dollars = self.stage(total.seconds * self.dollars_per_second);
+        CostEstimate {
+            num_shards: workload.num_shards,
+            trace_rows: workload.trace_rows,
+            committed_bytes: workload.committed_bytes,
+            recursion_proofs: workload.recursion_proofs,
+            core,
+            recursion,
+            wrap,
+            total,
+            dollars,
+        }
+    }
+
+    /// Fits the model to the stage timings of `samples`.
+    ///
+    /// The coefficients of the core and compress stages are scaled by the least squares factor
+    /// between their estimates and the timings, keeping the ratio between the row and byte
+    /// coefficients of the core stage. The wrap cost is the mean time of the wrap stages, if any
+    /// sample ran them. The relative error is the largest one of the fitted model on the samples.
+    pub fn fit(&self, samples: &[CalibrationSample]) -> Self {
+        let mut model = *self;
+        let workloads = samples
+            .iter()
+            .map(|sample| Workload::new(&sample.report, sample.shard_size))
+            .collect::<Vec<_>>();
+
+        let core = samples
+            .iter()
+            .zip(workloads.iter())
+            .filter_map(|(sample, workload)| {
+                Some((
+                    self.core_seconds(workload),
+                    stage_seconds(sample, &["core"])?,
+                ))
+            });
+        if let Some(scale) = least_squares_scale(core) {
+            model.core_seconds_per_row *= scale;
+            model.core_seconds_per_committed_byte *= scale;
+        }
+
+        let recursion = samples
+            .iter()
+            .zip(workloads.iter())
+            .filter_map(|(sample, workload)| {
+                Some((
+                    self.recursion_seconds(workload),
+                    stage_seconds(sample, &["compress"])?,
+                ))
+            });
+        if let Some(scale) = least_squares_scale(recursion) {
+            model.recursion_seconds_per_proof *= scale;
+        }
+
+        let wrap = samples
+            .iter()
+            .filter_map(|sample| stage_seconds(sample, &WRAP_STAGES))
+            .collect::<Vec<_>>();
+        if !wrap.is_empty() {
+            model.wrap_seconds = wrap.iter().sum::<f64>() / wrap.len() as f64;
+        }
+
+        model.relative_error = model.max_relative_error(samples);
+        model
+    }
+
+    /// The largest relative error of the estimated stages of `samples`, ignoring the stages that
+    /// were not run.
+    pub fn max_relative_error(&self, samples: &[CalibrationSample]) -> f64 {
+        let mut max_error: f64 = 0.0;
+        for sample in samples {
+            let workload = Workload::new(&sample.report, sample.shard_size);
+            let stages = [
+                (
+                    self.core_seconds(&workload),
+                    stage_seconds(sample, &["core"]),
+                ),
+                (
+                    self.recursion_seconds(&workload),
+                    stage_seconds(sample, &["compress"]),
+                ),
+                (self.wrap_seconds, stage_seconds(sample, &WRAP_STAGES)),
+            ];
+            for (estimate, actual) in stages {
+                if let Some(actual) = actual.filter(|actual| *actual > 0.0) {
+                    max_error = max_error.max((estimate - actual).abs() / actual);
+                }
+            }
+        }
+        max_error
+    }
+
+    fn core_seconds(&self, workload: &Workload) -> f64 {
+        workload.trace_rows as f64 * self.core_seconds_per_row
+            + workload.committed_bytes as f64 * self.core_seconds_per_committed_byte
+    }
+
+    fn recursion_seconds(&self, workload: &Workload) -> f64 {
+        workload.recursion_proofs as f64 * self.recursion_seconds_per_proof
+    }
+
+    fn stage(&self, seconds: f64) -> StageEstimate {
+        StageEstimate {
+            seconds,
+            low: seconds * (1.0 - self.relative_error).max(0.0),
+            high: seconds * (1.0 + self.relative_error),
+        }
+    }
+}
+
+/// The total seconds spent in `stages` by the proof of `sample`, if it ran any of them.
+fn stage_seconds(sample: &CalibrationSample, stages: &[&str]) -> Option<f64> {
+    let reports = stages
+        .iter()
+        .filter_map(|stage| sample.proving_report.stage(stage))
+        .collect::<Vec<_>>();
+    if reports.is_empty() {
+        return None;
+    }
+    Some(
+        reports
+            .iter()
+            .map(|report| report.elapsed.as_secs_f64())
+            .sum(),
+    )
+}
+
+/// The factor minimizing the squared error between the scaled estimates and the actual values of
+/// `points`, given as `(estimate, actual)` pairs.
+fn least_squares_scale(points: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+    let (estimate_actual, estimate_squared) =
+        points.fold((0.0, 0.0), |(xy, xx), (x, y)| (xy + x * y, xx + x * x));
+    (estimate_squared > 0.0).then(|| estimate_actual / estimate_squared)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sp1_core::runtime::SyscallCode;
+
+    use super::*;
+    use crate::{utils, ProverClient, SP1Stdin, StageReport};
+
+    fn stage(stage: &str, seconds: f64) -> StageReport {
+        StageReport {
+            stage: stage.to_string(),
+            attempts: 1,
+            timeouts: 0,
+            elapsed: Duration::from_secs_f64(seconds),
+            succeeded: true,
+            peak_memory: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_breakdown() {
+        let client = ProverClient::mock();
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        let (_, report) = client.execute(elf, stdin).unwrap();
+
+        let model = CostModel::default();
+        let cycles = report.total_instruction_count();
+        let small = model.estimate(&report, &ShardingConfig::new(1 << 10));
+        let large = model.estimate(&report, &ShardingConfig::new(1 << 22));
+        assert_eq!(small.num_shards, cycles.div_ceil(1 << 10));
+        assert_eq!(large.num_shards, 1);
+        assert_eq!(large.recursion_proofs, 2);
+        assert!(small.recursion.seconds > large.recursion.seconds);
+        assert_eq!(small.trace_rows, large.trace_rows);
+
+        let total = small.core.seconds + small.recursion.seconds + small.wrap.seconds;
+        assert!((small.total.seconds - total).abs() < 1e-9);
+        assert!(small.total.low < small.total.seconds && small.total.seconds < small.total.high);
+        assert!((small.dollars.seconds - total * model.dollars_per_second).abs() < 1e-9);
+
+        // Keccak permutes take 24 rows each.
+        let mut keccak = report.clone();
+        keccak
+            .syscall_counts
+            .insert(SyscallCode::KECCAK_PERMUTE, 1000);
+        let estimate = model.estimate(&keccak, &ShardingConfig::new(1 << 22));
+        assert_eq!(estimate.trace_rows, large.trace_rows + 24_000);
+    }
+
+    #[test]
+    fn test_fit_synthetic_timings() {
+        let mut report = ExecutionReport::default();
+        report
+            .opcode_counts
+            .insert(sp1_core::runtime::Opcode::ADD, 1 << 20);
+
+        // A machine twice as slow as the default model on the core and compress stages.
+        let default = CostModel::default();
+        let samples = [1 << 16, 1 << 18]
+            .into_iter()
+            .map(|shard_size| {
+                let workload = Workload::new(&report, shard_size);
+                CalibrationSample {
+                    report: report.clone(),
+                    shard_size,
+                    proving_report: ProvingReport {
+                        stages: vec![
+                            stage("core", 2.0 * default.core_seconds(&workload)),
+                            stage("compress", 2.0 * default.recursion_seconds(&workload)),
+                        ],
+                        ..Default::default()
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let model = default.fit(&samples);
+        assert!((model.core_seconds_per_row / default.core_seconds_per_row - 2.0).abs() < 1e-6);
+        assert!(
+            (model.recursion_seconds_per_proof / default.recursion_seconds_per_proof - 2.0).abs()
+                < 1e-6
+        );
+        assert_eq!(model.wrap_seconds, default.wrap_seconds);
+        assert!(model.relative_error < 1e-6);
+
+        let path = tempfile::NamedTempFile::new().unwrap();
+        model.to_json_file(path.path()).unwrap();
+        assert_eq!(CostModel::from_json_file(path.path()).unwrap(), model);
+    }
+
+    /// Proves two small programs, fits the model to their stage timings and checks that it
+    /// predicts them within the tolerance.
+    #[test]
+    fn test_calibrate_compressed_proofs() {
+        utils::setup_logger();
+        let shard_size = 1 << 14;
+        let client = ProverClient::local().with_shard_size(shard_size);
+        let mut fibonacci_stdin = SP1Stdin::new();
+        fibonacci_stdin.write(&1000u32);
+        let mut is_prime_stdin = SP1Stdin::new();
+        is_prime_stdin.write(&29u64);
+        let programs: [(&[u8], SP1Stdin); 2] = [
+            (
+                include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf"),
+                fibonacci_stdin,
+            ),
+            (
+                include_bytes!("../../examples/is-prime/program/elf/riscv32im-succinct-zkvm-elf"),
+                is_prime_stdin,
+            ),
+        ];
+
+        let mut samples = Vec::new();
+        for (elf, stdin) in programs {
+            let (_, report) = client.execute(elf, stdin.clone()).unwrap();
+            let (pk, _) = client.setup(elf);
+            client.prove_compressed(&pk, stdin).unwrap();
+            samples.push(CalibrationSample {
+                report,
+                shard_size,
+                proving_report: client.proving_report().unwrap(),
+            });
+        }
+
+        let model = CostModel::default().fit(&samples);
+        tracing::info!("calibrated cost model: {:?}", model);
+        assert!(
+            model.relative_error < 0.5,
+            "the cost model is off by {:.0}%",
+            model.relative_error * 100.0
+        );
+    }
+}
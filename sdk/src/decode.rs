@@ -0,0 +1,465 @@
+//! Decoding of proofs from untrusted bytes.
+//!
+//! A proof is decoded with a size limit equal to the length of its encoding, so that a length
+//! prefix can't make the decoder allocate more memory than the input it came with, and the input
+//! itself is bounded by [ProofDecodeLimits::max_bytes]. The structure of the decoded proof is then
+//! checked against the largest legitimate proof of the machine before it is handed to a verifier.
+
+use std::io::{ErrorKind, Read};
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use sp1_core::{
+    stark::{RiscvAir, ShardProof, StarkGenericConfig, PROOF_MAX_NUM_PVS},
+    utils::baby_bear_poseidon2::default_fri_config,
+};
+use sp1_prover::{CoreSC, InnerSC, PlonkBn254Proof, ReduceAir};
+use thiserror::Error;
+
+use crate::SP1ProofWithPublicValues;
+
+/// The log2 of the largest domain of the BabyBear field, which bounds the degree of every trace and
+/// the height of every Merkle tree.
+const MAX_LOG_DEGREE: usize = 27;
+
+/// The largest number of decimal digits of a BN254 scalar field element.
+const MAX_BN254_DIGITS: usize = 78;
+
+/// The largest length of the hex encoded PLONK proofs.
+const MAX_PLONK_PROOF_HEX_LEN: usize = 1 << 16;
+
+/// An error decoding a proof, naming the offending field.
+#[derive(Error, Debug)]
+pub enum ProofDecodeError {
+    #[error("{field} has length {len}, more than the limit of {limit}")]
+    TooLarge {
+        field: String,
+        len: usize,
+        limit: usize,
+    },
+    #[error("{field} is invalid: {reason}")]
+    Invalid { field: String, reason: String },
+    #[error("the input ends before {field}, or a length prefix of {field} exceeds the input")]
+    Truncated { field: String },
+    #[error("failed to decode {field}: {message}")]
+    Malformed { field: String, message: String },
+    #[error("failed to read the proof: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The limits on the shape of a decoded proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofDecodeLimits {
+    /// The largest encoding of a proof, including its stdin, in bytes.
+    pub max_bytes: usize,
+    /// The largest number of shard proofs.
+    pub max_shards: usize,
+    /// The number of chips of the machine, which bounds the chips of a shard.
+    pub max_chips: usize,
+    /// The number of FRI queries of a shard proof.
+    pub num_queries: usize,
+}
+
+/// A proof that can be decoded from untrusted bytes, see [SP1ProofWithPublicValues::decode].
+pub trait ProofShape: Sized {
+    /// The limits of the largest legitimate proof of this kind.
+    fn default_limits() -> ProofDecodeLimits;
+
+    /// Checks the structural invariants of a decoded proof, where `field` is the path of the proof.
+    fn check_shape(&self, limits: &ProofDecodeLimits, field: &str) -> Result<(), ProofDecodeError>;
+}
+
+impl ProofShape for Vec<ShardProof<CoreSC>> {
+    fn default_limits() -> ProofDecodeLimits {
+        ProofDecodeLimits {
+            max_bytes: 1 << 30,
+            max_shards: 1 << 16,
+            max_chips: RiscvAir::<<CoreSC as StarkGenericConfig>::Val>::get_all().len(),
+            num_queries: default_fri_config().num_queries,
+        }
+    }
+
+    fn check_shape(&self, limits: &ProofDecodeLimits, field: &str) -> Result<(), ProofDecodeError> {
+        check_len(field, self.len(), limits.max_shards)?;
+        for (i, shard_proof) in self.iter().enumerate() {
+            check_shard_proof(shard_proof, limits, &format!("{}[{}]", field, i))?;
+        }
+        Ok(())
+    }
+}
+
+impl ProofShape for ShardProof<InnerSC> {
+    fn default_limits() -> ProofDecodeLimits {
+        ProofDecodeLimits {
+            max_bytes: 1 << 28,
+            max_shards: 1,
+            max_chips: ReduceAir::<<InnerSC as StarkGenericConfig>::Val>::get_all().len(),
+            num_queries: default_fri_config().num_queries,
+        }
+    }
+
+    fn check_shape(&self, limits: &ProofDecodeLimits, field: &str) -> Result<(), ProofDecodeError> {
+        check_shard_proof(self, limits, field)
+    }
+}
+
+impl ProofShape for PlonkBn254Proof {
+    fn default_limits() -> ProofDecodeLimits {
+        ProofDecodeLimits {
+            max_bytes: 1 << 28,
+            max_shards: 0,
+            max_chips: 0,
+            num_queries: 0,
+        }
+    }
+
+    fn check_shape(
+        &self,
+        _limits: &ProofDecodeLimits,
+        field: &str,
+    ) -> Result<(), ProofDecodeError> {
+        for (i, input) in self.public_inputs.iter().enumerate() {
+            let field = format!("{}.public_inputs[{}]", field, i);
+            check_len(&field, input.len(), MAX_BN254_DIGITS)?;
+            if !input.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(invalid(&field, "not a decimal number"));
+            }
+        }
+        for (name, hex) in [
+            ("encoded_proof", &self.encoded_proof),
+            ("raw_proof", &self.raw_proof),
+        ] {
+            let field = format!("{}.{}", field, name);
+            check_len(&field, hex.len(), MAX_PLONK_PROOF_HEX_LEN)?;
+            if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                return Err(invalid(&field, "not a hex string"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the number of chips and queries of a shard proof, and the lengths of its openings.
+fn check_shard_proof(
+    proof: &ShardProof<CoreSC>,
+    limits: &ProofDecodeLimits,
+    field: &str,
+) -> Result<(), ProofDecodeError> {
+    let chips = &proof.opened_values.chips;
+    check_len(
+        &format!("{}.opened_values.chips", field),
+        chips.len(),
+        limits.max_chips,
+    )?;
+    if proof.chip_ordering.len() != chips.len()
+        || proof.chip_ordering.values().any(|&i| i >= chips.len())
+    {
+        return Err(invalid(
+            &format!("{}.chip_ordering", field),
+            "does not index the opened chips",
+        ));
+    }
+    for (i, chip) in chips.iter().enumerate() {
+        let field = format!("{}.opened_values.chips[{}]", field, i);
+        if chip.log_degree > MAX_LOG_DEGREE {
+            return Err(invalid(
+                &format!("{}.log_degree", field),
+                &format!("{} is more than {}", chip.log_degree, MAX_LOG_DEGREE),
+            ));
+        }
+        for (name, values) in [
+            ("preprocessed", &chip.preprocessed),
+            ("main", &chip.main),
+            ("permutation", &chip.permutation),
+        ] {
+            if values.local.len() != values.next.len() {
+                return Err(invalid(
+                    &format!("{}.{}", field, name),
+                    "the local and next rows have different widths",
+                ));
+            }
+        }
+    }
+    if proof.public_values.len() != PROOF_MAX_NUM_PVS {
+        return Err(invalid(
+            &format!("{}.public_values", field),
+            &format!("expected {} values", PROOF_MAX_NUM_PVS),
+        ));
+    }
+
+    // The queries of the FRI proof, and the heights of their Merkle paths.
+    let field = format!("{}.opening_proof", field);
+    let fri_proof = &proof.opening_proof.fri_proof;
+    let query_proofs = &fri_proof.query_proofs;
+    let query_openings = &proof.opening_proof.query_openings;
+    for (name, len) in [
+        ("fri_proof.query_proofs", query_proofs.len()),
+        ("query_openings", query_openings.len()),
+    ] {
+        if len != limits.num_queries {
+            return Err(invalid(
+                &format!("{}.{}", field, name),
+                &format!("expected {} queries, got {}", limits.num_queries, len),
+            ));
+        }
+    }
+    check_len(
+        &format!("{}.fri_proof.commit_phase_commits", field),
+        fri_proof.commit_phase_commits.len(),
+        MAX_LOG_DEGREE,
+    )?;
+    for (i, query) in query_proofs.iter().enumerate() {
+        let field = format!("{}.fri_proof.query_proofs[{}]", field, i);
+        check_len(
+            &format!("{}.commit_phase_openings", field),
+            query.commit_phase_openings.len(),
+            fri_proof.commit_phase_commits.len(),
+        )?;
+        for (j, step) in query.commit_phase_openings.iter().enumerate() {
+            check_len(
+                &format!("{}.commit_phase_openings[{}].opening_proof", field, j),
+                step.opening_proof.len(),
+                MAX_LOG_DEGREE,
+            )?;
+        }
+    }
+    for (i, openings) in query_openings.iter().enumerate() {
+        for (j, opening) in openings.iter().enumerate() {
+            check_len(
+                &format!("{}.query_openings[{}][{}].opening_proof", field, i, j),
+                opening.opening_proof.len(),
+                MAX_LOG_DEGREE,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn check_len(field: &str, len: usize, limit: usize) -> Result<(), ProofDecodeError> {
+    if len > limit {
+        return Err(ProofDecodeError::TooLarge {
+            field: field.to_string(),
+            len,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+fn invalid(field: &str, reason: &str) -> ProofDecodeError {
+    ProofDecodeError::Invalid {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+impl<P: ProofShape + DeserializeOwned> SP1ProofWithPublicValues<P> {
+    /// The limits of the largest legitimate proof of this kind.
+    pub fn default_limits() -> ProofDecodeLimits {
+        P::default_limits()
+    }
+
+    /// Decodes a proof saved with [SP1ProofWithPublicValues::save] from untrusted bytes, checking
+    /// it against `limits` before it is verified.
+    pub fn decode(bytes: &[u8], limits: &ProofDecodeLimits) -> Result<Self, ProofDecodeError> {
+        check_len("proof bytes", bytes.len(), limits.max_bytes)?;
+
+        // The same encoding as `bincode::serialize`, limited to the input.
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(bytes.len() as u64);
+        let mut deserializer = bincode::Deserializer::from_slice(bytes, options);
+        let proof: Self = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|err| decode_error(err, None))?;
+
+        proof.proof.check_shape(limits, "proof")?;
+        Ok(proof)
+    }
+
+    /// Decodes a proof in the encoding of [SP1ProofWithPublicValues::decode] from an untrusted
+    /// stream, reading at most [ProofDecodeLimits::max_bytes] bytes of it.
+    pub fn decode_from(
+        reader: impl Read,
+        limits: &ProofDecodeLimits,
+    ) -> Result<Self, ProofDecodeError> {
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(limits.max_bytes as u64);
+        let mut deserializer = bincode::Deserializer::with_reader(reader, options);
+        let proof: Self = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|err| decode_error(err, Some(limits.max_bytes)))?;
+
+        proof.proof.check_shape(limits, "proof")?;
+        Ok(proof)
+    }
+}
+
+/// Converts a deserialization error, where `stream_limit` is the size limit of a proof decoded
+/// from a stream. A proof decoded from bytes is limited to their length, so that it only hits the
+/// limit when it is truncated.
+fn decode_error(
+    err: serde_path_to_error::Error<bincode::Error>,
+    stream_limit: Option<usize>,
+) -> ProofDecodeError {
+    let field = err.path().to_string();
+    match *err.into_inner() {
+        bincode::ErrorKind::SizeLimit => match stream_limit {
+            Some(limit) => ProofDecodeError::Invalid {
+                field,
+                reason: format!(
+                    "the proof decodes to more than the limit of {} bytes",
+                    limit
+                ),
+            },
+            None => ProofDecodeError::Truncated { field },
+        },
+        bincode::ErrorKind::Io(err) if err.kind() == ErrorKind::UnexpectedEof => {
+            ProofDecodeError::Truncated { field }
+        }
+        err => ProofDecodeError::Malformed {
+            field,
+            message: err.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sp1_core::SP1_CIRCUIT_VERSION;
+    use sp1_prover::{SP1PublicValues, SP1Stdin};
+
+    use super::*;
+    use crate::{SP1PlonkBn254Proof, SP1Proof};
+
+    fn core_proof_bytes() -> Vec<u8> {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&[1u8; 8]);
+        let proof = SP1Proof {
+            proof: vec![],
+            stdin,
+            public_values: SP1PublicValues::new(),
+            insecure: false,
+            sp1_version: SP1_CIRCUIT_VERSION.to_string(),
+            vkey_digest: None,
+            profile_path: None,
+            features: 0,
+        };
+        bincode::serialize(&proof).unwrap()
+    }
+
+    fn plonk_proof(encoded_proof: &str) -> SP1PlonkBn254Proof {
+        SP1PlonkBn254Proof {
+            proof: PlonkBn254Proof {
+                public_inputs: ["1".to_string(), "2".to_string(), "3".to_string()],
+                encoded_proof: encoded_proof.to_string(),
+                raw_proof: "00".to_string(),
+                plonk_vkey_hash: [0; 32],
+            },
+            stdin: SP1Stdin::new(),
+            public_values: SP1PublicValues::new(),
+            insecure: false,
+            sp1_version: SP1_CIRCUIT_VERSION.to_string(),
+            vkey_digest: None,
+            profile_path: None,
+            features: 0,
+        }
+    }
+
+    #[test]
+    fn test_decode_proof() {
+        let limits = SP1Proof::default_limits();
+        let bytes = core_proof_bytes();
+        let proof = SP1Proof::decode(&bytes, &limits).unwrap();
+        assert_eq!(proof.sp1_version, SP1_CIRCUIT_VERSION);
+
+        // Every truncation of the proof is reported.
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                SP1Proof::decode(&bytes[..len], &limits),
+                Err(ProofDecodeError::Truncated { .. })
+            ));
+        }
+
+        // The input is rejected before being decoded if it is too large.
+        let small = ProofDecodeLimits {
+            max_bytes: 16,
+            ..limits
+        };
+        assert!(matches!(
+            SP1Proof::decode(&bytes, &small),
+            Err(ProofDecodeError::TooLarge { field, .. }) if field == "proof bytes"
+        ));
+    }
+
+    #[test]
+    fn test_decode_proof_from_stream() {
+        let limits = SP1Proof::default_limits();
+        let bytes = core_proof_bytes();
+        let proof = SP1Proof::decode_from(&bytes[..], &limits).unwrap();
+        assert_eq!(
+            proof.stdin.buffer,
+            vec![bincode::serialize(&[1u8; 8]).unwrap()]
+        );
+        assert!(matches!(
+            SP1Proof::decode_from(&bytes[..bytes.len() - 1], &limits),
+            Err(ProofDecodeError::Truncated { field }) if field == "sp1_version"
+        ));
+
+        // A stream is read up to the limit, however long it is.
+        let small = ProofDecodeLimits {
+            max_bytes: 16,
+            ..limits
+        };
+        assert!(matches!(
+            SP1Proof::decode_from(std::io::repeat(0), &small),
+            Err(ProofDecodeError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_length_inflated_proof() {
+        let limits = SP1Proof::default_limits();
+
+        // The number of shard proofs, and the length of the first stdin buffer, which would
+        // allocate terabytes if they were trusted.
+        for (offset, field) in [(0, "proof"), (16, "stdin.buffer")] {
+            let mut bytes = core_proof_bytes();
+            bytes[offset..offset + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+            match SP1Proof::decode(&bytes, &limits) {
+                Err(ProofDecodeError::Truncated { field: path }) => {
+                    assert!(path.starts_with(field), "{}", path)
+                }
+                result => panic!("unexpected result {:?}", result.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_type_confused_proof() {
+        let limits = SP1Proof::default_limits();
+
+        // A version that is not UTF-8.
+        let mut bytes = core_proof_bytes();
+        let len = bytes.len();
+        bytes[len - 1] = 0xff;
+        assert!(matches!(
+            SP1Proof::decode(&bytes, &limits),
+            Err(ProofDecodeError::Malformed { field, .. }) if field == "sp1_version"
+        ));
+
+        // A PLONK proof decoded as a core proof.
+        let bytes = bincode::serialize(&plonk_proof("00")).unwrap();
+        assert!(SP1Proof::decode(&bytes, &limits).is_err());
+
+        // A PLONK proof whose proof is not hex.
+        let limits = SP1PlonkBn254Proof::default_limits();
+        assert!(SP1PlonkBn254Proof::decode(&bytes, &limits).is_ok());
+        let bytes = bincode::serialize(&plonk_proof("0x")).unwrap();
+        assert!(matches!(
+            SP1PlonkBn254Proof::decode(&bytes, &limits),
+            Err(ProofDecodeError::Invalid { field, .. }) if field == "proof.encoded_proof"
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! Consistency checks between the program artifacts passed to the SDK.
+//!
+//! Proving and verifying keys carry the digest of their program, see
+//! [SP1VerifyingKey::program_digest], and proofs carry the digest of the verifying key they were
+//! generated for, see [crate::SP1ProofWithPublicValues::vkey_digest]. The [crate::ProverClient]
+//! entry points compare them up front, so that mispaired artifacts fail with a
+//! [SP1Error::ProgramMismatch] naming the two that disagree instead of a panic in the prover or a
+//! generic verification failure.
+
+use std::{fmt, str::FromStr};
+
+use num_bigint::BigUint;
+use p3_field::PrimeField;
+use sp1_prover::{HashableKey, SP1ProvingKey, SP1ReduceProof, SP1Stdin, SP1VerifyingKey};
+use thiserror::Error;
+
+use crate::{SP1CompressedProof, SP1PlonkBn254Proof, SP1ProofWithPublicValues};
+
+/// The two artifacts compared by a failed consistency check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchContext {
+    /// The proving key and the verifying key it embeds.
+    ProvingKeyAndVerifyingKey,
+    /// A proof and the verifying key it is verified against.
+    ProofAndVerifyingKey,
+    /// The verifying key digest committed to in the public values of a proof and the verifying
+    /// key it is verified against.
+    PublicValuesAndVerifyingKey,
+    /// The deferred proof at the given index of the stdin and the verifying key it is paired with.
+    DeferredProofAndVerifyingKey(usize),
+    /// A receipt and the verifying key it is verified against.
+    ReceiptAndVerifyingKey,
+}
+
+impl fmt::Display for MismatchContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProvingKeyAndVerifyingKey => write!(f, "proving key and its verifying key"),
+            Self::ProofAndVerifyingKey => write!(f, "proof and verifying key"),
+            Self::PublicValuesAndVerifyingKey => {
+                write!(f, "proof public values and verifying key")
+            }
+            Self::DeferredProofAndVerifyingKey(index) => {
+                write!(f, "deferred proof {} and its verifying key", index)
+            }
+            Self::ReceiptAndVerifyingKey => write!(f, "receipt and verifying key"),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SP1Error {
+    /// Two artifacts were generated for different programs. `expected` is the digest of the
+    /// verifying key side and `found` the digest of the other artifact.
+    #[error("program mismatch between the {context}: expected {expected}, found {found}")]
+    ProgramMismatch {
+        expected: String,
+        found: String,
+        context: MismatchContext,
+    },
+}
+
+fn check_digest(
+    expected: [u8; 32],
+    found: [u8; 32],
+    context: MismatchContext,
+) -> Result<(), SP1Error> {
+    if expected == found {
+        return Ok(());
+    }
+    Err(SP1Error::ProgramMismatch {
+        expected: format!("0x{}", hex::encode(expected)),
+        found: format!("0x{}", hex::encode(found)),
+        context,
+    })
+}
+
+/// Checks that the verifying key embedded in `pk` is the one of its program.
+pub(crate) fn check_proving_key(pk: &SP1ProvingKey) -> Result<(), SP1Error> {
+    check_digest(
+        pk.vk.program_digest(),
+        pk.program_digest(),
+        MismatchContext::ProvingKeyAndVerifyingKey,
+    )
+}
+
+/// Checks that the deferred proofs of `stdin` were generated for the verifying keys they are
+/// paired with.
+pub(crate) fn check_deferred_proofs(stdin: &SP1Stdin) -> Result<(), SP1Error> {
+    for (index, (proof, vk)) in stdin.proofs.iter().enumerate() {
+        let proof = SP1ReduceProof {
+            proof: proof.clone(),
+        };
+        check_digest(
+            vk.hash_bytes(),
+            proof.sp1_vkey_digest_bytes(),
+            MismatchContext::DeferredProofAndVerifyingKey(index),
+        )?;
+    }
+    Ok(())
+}
+
+/// Checks the verifying key digest carried by `proof`, if any, against `vk`.
+pub(crate) fn check_proof<P>(
+    proof: &SP1ProofWithPublicValues<P>,
+    vk: &SP1VerifyingKey,
+) -> Result<(), SP1Error> {
+    match proof.vkey_digest {
+        Some(digest) => check_digest(
+            vk.hash_bytes(),
+            digest,
+            MismatchContext::ProofAndVerifyingKey,
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Checks `proof` and the verifying key digest committed to in its public values against `vk`.
+pub(crate) fn check_compressed_proof(
+    proof: &SP1CompressedProof,
+    vk: &SP1VerifyingKey,
+) -> Result<(), SP1Error> {
+    check_proof(proof, vk)?;
+    let reduce_proof = SP1ReduceProof {
+        proof: proof.proof.clone(),
+    };
+    check_digest(
+        vk.hash_bytes(),
+        reduce_proof.sp1_vkey_digest_bytes(),
+        MismatchContext::PublicValuesAndVerifyingKey,
+    )
+}
+
+/// Checks `proof` and the verifying key digest of its public inputs against `vk`.
+pub(crate) fn check_plonk_proof(
+    proof: &SP1PlonkBn254Proof,
+    vk: &SP1VerifyingKey,
+) -> Result<(), SP1Error> {
+    check_proof(proof, vk)?;
+    let found = &proof.proof.public_inputs[0];
+    if BigUint::from_str(found).ok() == Some(vk.hash_bn254().as_canonical_biguint()) {
+        return Ok(());
+    }
+    let found = match BigUint::from_str(found) {
+        Ok(found) => format!("0x{:0>64}", found.to_str_radix(16)),
+        Err(_) => found.clone(),
+    };
+    Err(SP1Error::ProgramMismatch {
+        expected: vk.bytes32(),
+        found,
+        context: MismatchContext::PublicValuesAndVerifyingKey,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, ProverClient, SP1Receipt, SP1VerificationError};
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    const IS_PRIME_ELF: &[u8] =
+        include_bytes!("../../examples/is-prime/program/elf/riscv32im-succinct-zkvm-elf");
+
+    fn stdin() -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        stdin
+    }
+
+    fn context(err: &anyhow::Error) -> MismatchContext {
+        match err.downcast_ref::<SP1Error>() {
+            Some(SP1Error::ProgramMismatch { context, .. }) => *context,
+            None => panic!("unexpected error: {}", err),
+        }
+    }
+
+    fn verification_context(result: Result<(), SP1VerificationError>) -> MismatchContext {
+        match result {
+            Err(SP1VerificationError::Program(SP1Error::ProgramMismatch { context, .. })) => {
+                context
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_program_mismatch_prove() {
+        utils::setup_logger();
+        let client = ProverClient::mock();
+        let (mut pk, vk) = client.setup(FIBONACCI_ELF);
+        let (_, other_vk) = client.setup(IS_PRIME_ELF);
+        assert_eq!(pk.program_digest(), vk.program_digest());
+        assert_ne!(vk.program_digest(), other_vk.program_digest());
+
+        pk.vk = other_vk.clone();
+        let err = client.prove(&pk, stdin()).unwrap_err();
+        assert_eq!(context(&err), MismatchContext::ProvingKeyAndVerifyingKey);
+        let expected = format!("0x{}", hex::encode(other_vk.program_digest()));
+        assert_eq!(
+            err.downcast_ref::<SP1Error>(),
+            Some(&SP1Error::ProgramMismatch {
+                expected,
+                found: format!("0x{}", hex::encode(vk.program_digest())),
+                context: MismatchContext::ProvingKeyAndVerifyingKey,
+            })
+        );
+        let err = client.prove_compressed(&pk, stdin()).unwrap_err();
+        assert_eq!(context(&err), MismatchContext::ProvingKeyAndVerifyingKey);
+        let err = client.prove_plonk(&pk, stdin()).unwrap_err();
+        assert_eq!(context(&err), MismatchContext::ProvingKeyAndVerifyingKey);
+    }
+
+    #[test]
+    fn test_program_mismatch_verify() {
+        utils::setup_logger();
+        let client = ProverClient::mock();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let (_, other_vk) = client.setup(IS_PRIME_ELF);
+
+        let proof = client.prove(&pk, stdin()).unwrap();
+        assert_eq!(proof.vkey_digest, Some(vk.hash_bytes()));
+        client.verify(&proof, &vk).unwrap();
+        assert_eq!(
+            verification_context(client.verify(&proof, &other_vk)),
+            MismatchContext::ProofAndVerifyingKey
+        );
+
+        let mut proof = client.prove_plonk(&pk, stdin()).unwrap();
+        client.verify_plonk(&proof, &vk).unwrap();
+        assert_eq!(
+            verification_context(client.verify_plonk(&proof, &other_vk)),
+            MismatchContext::ProofAndVerifyingKey
+        );
+        // Without the digest carried by the proof, its public inputs still disagree.
+        proof.vkey_digest = None;
+        assert_eq!(
+            verification_context(client.verify_plonk(&proof, &other_vk)),
+            MismatchContext::PublicValuesAndVerifyingKey
+        );
+
+        let proof = client.prove(&pk, stdin()).unwrap();
+        let receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        client.verify_receipt(&receipt, &vk).unwrap();
+        assert_eq!(
+            verification_context(client.verify_receipt(&receipt, &other_vk)),
+            MismatchContext::ReceiptAndVerifyingKey
+        );
+    }
+
+    #[test]
+    fn test_program_mismatch_compressed() {
+        utils::setup_logger();
+        let client = ProverClient::local();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let (other_pk, other_vk) = client.setup(IS_PRIME_ELF);
+
+        let mut proof = client.prove_compressed(&pk, stdin()).unwrap();
+        client.verify_compressed(&proof, &vk).unwrap();
+        assert_eq!(
+            verification_context(client.verify_compressed(&proof, &other_vk)),
+            MismatchContext::ProofAndVerifyingKey
+        );
+        proof.vkey_digest = None;
+        assert_eq!(
+            verification_context(client.verify_compressed(&proof, &other_vk)),
+            MismatchContext::PublicValuesAndVerifyingKey
+        );
+
+        // A deferred proof paired with the verifying key of another program.
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10u64);
+        stdin.write_proof(proof.proof.clone(), vk.vk.clone());
+        stdin.write_proof(proof.proof, other_vk.vk.clone());
+        let err = client.prove_compressed(&other_pk, stdin).unwrap_err();
+        assert_eq!(
+            context(&err),
+            MismatchContext::DeferredProofAndVerifyingKey(1)
+        );
+    }
+}
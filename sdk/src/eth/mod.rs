@@ -0,0 +1,579 @@
+//! Building the inputs of programs executing Ethereum blocks from an RPC endpoint.
+//!
+//! An [EthInputBuilder] fetches a block, its transactions, the headers of its ancestors and the
+//! state proofs of the accounts it touches at its parent block, with concurrent requests retried
+//! on failure. The assembled [EthBlockInput] is validated to re-derive the hash of the block and of
+//! its ancestors before it is written to an [SP1Stdin], and cached on disk under the hash of the
+//! block.
+//!
+//! Requests can be recorded to, and replayed from, a JSON fixture with a [FixtureTransport], to
+//! build inputs offline.
+
+mod transport;
+
+pub use transport::*;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ethers::{
+    types::{Address, Bloom, Bytes, H256, H64, U256, U64},
+    utils::{keccak256, rlp::RlpStream},
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{RetryPolicy, SP1Stdin};
+
+/// The number of requests an [EthInputBuilder] sends concurrently by default.
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+/// An error building an [EthBlockInput].
+#[derive(Debug, Error)]
+pub enum EthInputError {
+    #[error("transport error: {0}")]
+    Transport(String),
+    #[error("{method} failed with error {code}: {message}")]
+    Rpc {
+        method: String,
+        code: i64,
+        message: String,
+    },
+    #[error("failed to decode the result of {method}: {source}")]
+    Decode {
+        method: String,
+        source: serde_json::Error,
+    },
+    #[error("{method} with params {params} is not in the fixture")]
+    FixtureMiss { method: String, params: Value },
+    #[error("block {0} not found")]
+    BlockNotFound(u64),
+    #[error("the header of block {number} hashes to {derived:?} instead of {expected:?}")]
+    BlockHashMismatch {
+        number: u64,
+        expected: H256,
+        derived: H256,
+    },
+    #[error("block {0} is not the parent of the next block of the input")]
+    BrokenAncestorChain(u64),
+    #[error("expected {expected} transactions, got {actual}")]
+    TransactionCountMismatch { expected: usize, actual: usize },
+    #[error("transaction {0:?} does not hash to its hash")]
+    TransactionHashMismatch(H256),
+    #[error("the state proof of {0:?} does not start at the state root of the parent block")]
+    StateProofMismatch(Address),
+    #[error("the code of {0:?} does not hash to its code hash")]
+    CodeHashMismatch(Address),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The header of a block, as returned by `eth_getBlockByNumber`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub hash: H256,
+    pub parent_hash: H256,
+    #[serde(rename = "sha3Uncles")]
+    pub uncles_hash: H256,
+    pub miner: Address,
+    pub state_root: H256,
+    pub transactions_root: H256,
+    pub receipts_root: H256,
+    pub logs_bloom: Bloom,
+    pub difficulty: U256,
+    pub number: U64,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    pub mix_hash: H256,
+    pub nonce: H64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+}
+
+impl BlockHeader {
+    /// The hash of the header: the keccak256 digest of its RLP encoding, with the fields added by
+    /// the London, Shanghai and Cancun forks if present.
+    pub fn derive_hash(&self) -> H256 {
+        let mut rlp = RlpStream::new();
+        rlp.begin_unbounded_list();
+        rlp.append(&self.parent_hash)
+            .append(&self.uncles_hash)
+            .append(&self.miner)
+            .append(&self.state_root)
+            .append(&self.transactions_root)
+            .append(&self.receipts_root)
+            .append(&self.logs_bloom)
+            .append(&self.difficulty)
+            .append(&self.number.as_u64())
+            .append(&self.gas_limit)
+            .append(&self.gas_used)
+            .append(&self.timestamp)
+            .append(&self.extra_data.to_vec())
+            .append(&self.mix_hash)
+            .append(&self.nonce);
+        if let Some(base_fee_per_gas) = &self.base_fee_per_gas {
+            rlp.append(base_fee_per_gas);
+        }
+        if let Some(withdrawals_root) = &self.withdrawals_root {
+            rlp.append(withdrawals_root);
+        }
+        if let Some(blob_gas_used) = &self.blob_gas_used {
+            rlp.append(blob_gas_used);
+        }
+        if let Some(excess_blob_gas) = &self.excess_blob_gas {
+            rlp.append(excess_blob_gas);
+        }
+        if let Some(parent_beacon_block_root) = &self.parent_beacon_block_root {
+            rlp.append(parent_beacon_block_root);
+        }
+        rlp.finalize_unbounded_list();
+        H256(keccak256(rlp.out()))
+    }
+}
+
+/// The proof of an account and of some of its storage slots, as returned by `eth_getProof`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: Address,
+    pub balance: U256,
+    pub code_hash: H256,
+    pub nonce: U64,
+    pub storage_hash: H256,
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// The proof of a storage slot of an [AccountProof].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub key: U256,
+    pub value: U256,
+    pub proof: Vec<Bytes>,
+}
+
+/// The input of a program executing an Ethereum block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthBlockInput {
+    /// The header of the block.
+    pub header: BlockHeader,
+    /// The signed transactions of the block, RLP encoded.
+    pub transactions: Vec<Bytes>,
+    /// The headers of the ancestors of the block, starting with its parent.
+    pub ancestors: Vec<BlockHeader>,
+    /// The proofs of the accounts touched by the block against the state root of its parent.
+    pub accounts: Vec<AccountProof>,
+    /// The code of the accounts, in the order of [EthBlockInput::accounts].
+    pub codes: Vec<Bytes>,
+}
+
+impl EthBlockInput {
+    /// Checks that the block and its ancestors re-derive their hashes and chain, that the
+    /// transactions and the codes hash to their hashes, and that the account proofs start at the
+    /// state root of the parent block.
+    ///
+    /// The transactions are checked against the hashes the node returned for the block. The nodes
+    /// of the state proofs past their root aren't checked, which the program does when it
+    /// executes the block.
+    pub fn validate(&self, transaction_hashes: &[H256]) -> Result<(), EthInputError> {
+        let headers = std::iter::once(&self.header)
+            .chain(self.ancestors.iter())
+            .collect::<Vec<_>>();
+        for header in headers.iter() {
+            let derived = header.derive_hash();
+            if derived != header.hash {
+                return Err(EthInputError::BlockHashMismatch {
+                    number: header.number.as_u64(),
+                    expected: header.hash,
+                    derived,
+                });
+            }
+        }
+        for pair in headers.windows(2) {
+            if pair[0].parent_hash != pair[1].hash {
+                return Err(EthInputError::BrokenAncestorChain(pair[1].number.as_u64()));
+            }
+        }
+
+        if transaction_hashes.len() != self.transactions.len() {
+            return Err(EthInputError::TransactionCountMismatch {
+                expected: transaction_hashes.len(),
+                actual: self.transactions.len(),
+            });
+        }
+        for (transaction, hash) in self.transactions.iter().zip(transaction_hashes) {
+            if H256(keccak256(transaction)) != *hash {
+                return Err(EthInputError::TransactionHashMismatch(*hash));
+            }
+        }
+
+        let parent_state_root = self.ancestors.first().map(|parent| parent.state_root);
+        for (account, code) in self.accounts.iter().zip(self.codes.iter()) {
+            let root = account
+                .account_proof
+                .first()
+                .map(|node| H256(keccak256(node)));
+            if root.is_none() || root != parent_state_root {
+                return Err(EthInputError::StateProofMismatch(account.address));
+            }
+            if H256(keccak256(code)) != account.code_hash {
+                return Err(EthInputError::CodeHashMismatch(account.address));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the input holds the proofs of all the storage slots of `accounts`.
+    fn covers(&self, accounts: &BTreeMap<Address, BTreeSet<U256>>) -> bool {
+        accounts.iter().all(|(address, keys)| {
+            self.accounts.iter().any(|account| {
+                account.address == *address
+                    && keys
+                        .iter()
+                        .all(|key| account.storage_proof.iter().any(|proof| proof.key == *key))
+            })
+        })
+    }
+}
+
+/// A transaction of a block, as returned by `eth_getBlockByNumber` with full transactions.
+#[derive(Deserialize)]
+struct RpcTransaction {
+    hash: H256,
+    from: Address,
+    to: Option<Address>,
+}
+
+/// A block with its header, and its full transactions or their hashes.
+#[derive(Deserialize)]
+struct RpcBlock<T> {
+    #[serde(flatten)]
+    header: BlockHeader,
+    transactions: Vec<T>,
+}
+
+/// The input of a cached block, with the hashes of its transactions.
+#[derive(Serialize, Deserialize)]
+struct CachedInput {
+    input: EthBlockInput,
+    transaction_hashes: Vec<H256>,
+}
+
+/// Builds the [EthBlockInput] of blocks from an RPC endpoint.
+///
+/// The accounts touched by a block are its miner, and the senders and recipients of its
+/// transactions. The accounts and storage slots the block reads through the contracts it calls
+/// can be added with [EthInputBuilder::account].
+pub struct EthInputBuilder<T> {
+    transport: T,
+    cache_dir: Option<PathBuf>,
+    ancestors: usize,
+    concurrency: usize,
+    accounts: BTreeMap<Address, BTreeSet<U256>>,
+}
+
+impl EthInputBuilder<RetryingTransport<HttpTransport>> {
+    /// Builds inputs from the HTTP endpoint `rpc_url`, retrying failed requests with the backoff
+    /// of `retry_policy`.
+    pub fn new(rpc_url: &str, retry_policy: RetryPolicy) -> Self {
+        Self::with_transport(RetryingTransport::new(
+            HttpTransport::new(rpc_url),
+            retry_policy,
+        ))
+    }
+}
+
+impl<T: RpcTransport> EthInputBuilder<T> {
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            transport,
+            cache_dir: None,
+            ancestors: 1,
+            concurrency: DEFAULT_CONCURRENCY,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// Caches the inputs in `cache_dir`, in a JSON file named after the hash of their block.
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Sets the number of ancestors of the block whose headers are fetched, at least the parent.
+    /// The `BLOCKHASH` opcode reads up to the last 256 of them.
+    pub fn ancestors(mut self, ancestors: usize) -> Self {
+        self.ancestors = ancestors.max(1);
+        self
+    }
+
+    /// Sets the number of requests sent concurrently.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Adds the proofs of `address` and of its `storage_keys` to the inputs.
+    pub fn account(mut self, address: Address, storage_keys: &[U256]) -> Self {
+        self.accounts
+            .entry(address)
+            .or_default()
+            .extend(storage_keys.iter().copied());
+        self
+    }
+
+    /// Builds and validates the input of the block `number`.
+    pub async fn build(&self, number: u64) -> Result<EthBlockInput, EthInputError> {
+        let block: RpcBlock<RpcTransaction> = self.get_block(number, true).await?;
+        let transaction_hashes = block
+            .transactions
+            .iter()
+            .map(|transaction| transaction.hash)
+            .collect::<Vec<_>>();
+
+        let mut accounts = self.accounts.clone();
+        accounts.entry(block.header.miner).or_default();
+        for transaction in block.transactions.iter() {
+            accounts.entry(transaction.from).or_default();
+            if let Some(to) = transaction.to {
+                accounts.entry(to).or_default();
+            }
+        }
+
+        let cache_path = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:?}.json", block.header.hash)));
+        if let Some(cached) = cache_path.as_ref().and_then(|path| Self::load(path)) {
+            if cached.input.header == block.header
+                && cached.input.ancestors.len() >= self.ancestors
+                && cached.input.covers(&accounts)
+                && cached.input.validate(&cached.transaction_hashes).is_ok()
+            {
+                tracing::debug!("loaded the input of block {} from the cache", number);
+                return Ok(cached.input);
+            }
+        }
+
+        let transactions = stream::iter(transaction_hashes.iter())
+            .map(|hash| self.request::<Bytes>("eth_getRawTransactionByHash", json!([hash])))
+            .buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let first_ancestor = number.saturating_sub(self.ancestors as u64);
+        let ancestors = stream::iter((first_ancestor..number).rev())
+            .map(|number| async move {
+                let block: RpcBlock<H256> = self.get_block(number, false).await?;
+                Ok::<_, EthInputError>(block.header)
+            })
+            .buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        // The block is executed on the state of its parent.
+        let parent = json!(format!("{:#x}", number.saturating_sub(1)));
+        let accounts = stream::iter(accounts.iter())
+            .map(|(address, keys)| {
+                let parent = parent.clone();
+                let keys = keys
+                    .iter()
+                    .map(|key| {
+                        let mut bytes = [0u8; 32];
+                        key.to_big_endian(&mut bytes);
+                        H256(bytes)
+                    })
+                    .collect::<Vec<_>>();
+                async move {
+                    let proof = self
+                        .request::<AccountProof>("eth_getProof", json!([address, keys, parent]))
+                        .await?;
+                    let code = self
+                        .request::<Bytes>("eth_getCode", json!([address, parent]))
+                        .await?;
+                    Ok::<_, EthInputError>((proof, code))
+                }
+            })
+            .buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let (accounts, codes) = accounts.into_iter().unzip();
+
+        let input = EthBlockInput {
+            header: block.header,
+            transactions,
+            ancestors,
+            accounts,
+            codes,
+        };
+        input.validate(&transaction_hashes)?;
+
+        if let Some(path) = cache_path {
+            let cached = CachedInput {
+                input,
+                transaction_hashes,
+            };
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(
+                &path,
+                serde_json::to_vec(&cached).expect("failed to serialize the input"),
+            )?;
+            return Ok(cached.input);
+        }
+        Ok(input)
+    }
+
+    /// Builds and validates the input of the block `number`, and writes it to `stdin`.
+    pub async fn write_stdin(
+        &self,
+        number: u64,
+        stdin: &mut SP1Stdin,
+    ) -> Result<EthBlockInput, EthInputError> {
+        let input = self.build(number).await?;
+        stdin.write(&input);
+        Ok(input)
+    }
+
+    async fn get_block<B: DeserializeOwned>(
+        &self,
+        number: u64,
+        full_transactions: bool,
+    ) -> Result<B, EthInputError> {
+        let block: Option<B> = self
+            .request(
+                "eth_getBlockByNumber",
+                json!([format!("{:#x}", number), full_transactions]),
+            )
+            .await?;
+        block.ok_or(EthInputError::BlockNotFound(number))
+    }
+
+    async fn request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<R, EthInputError> {
+        let result = self.transport.request(method, params).await?;
+        serde_json::from_value(result).map_err(|source| EthInputError::Decode {
+            method: method.to_string(),
+            source,
+        })
+    }
+
+    fn load(path: &Path) -> Option<CachedInput> {
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fixture recording the requests building the input of a synthetic block 3 of a chain
+    /// with one legacy transaction, whose hashes are derived as on mainnet.
+    fn fixture() -> FixtureTransport<HttpTransport> {
+        FixtureTransport::replay(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("src/eth/fixtures/block-3.json"),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_build_from_fixture() {
+        let builder = EthInputBuilder::with_transport(fixture()).ancestors(2);
+        let input = builder.build(3).await.unwrap();
+        assert_eq!(input.header.number.as_u64(), 3);
+        assert_eq!(input.transactions.len(), 1);
+        assert_eq!(
+            input
+                .ancestors
+                .iter()
+                .map(|header| header.number.as_u64())
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(input.accounts.len(), 3);
+
+        let mut stdin = SP1Stdin::new();
+        builder.write_stdin(3, &mut stdin).await.unwrap();
+        assert_eq!(stdin.read::<EthBlockInput>(), input);
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let builder = EthInputBuilder::with_transport(fixture()).cache_dir(cache_dir.path());
+        let input = builder.build(3).await.unwrap();
+        let cached = cache_dir
+            .path()
+            .join(format!("{:?}.json", input.header.hash));
+        assert!(cached.is_file());
+
+        // Only the block is fetched when the input is cached.
+        let block_only = FixtureTransport::record(fixture());
+        let builder = EthInputBuilder::with_transport(block_only).cache_dir(cache_dir.path());
+        assert_eq!(builder.build(3).await.unwrap(), input);
+        assert_eq!(builder.transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_validate() {
+        let input = EthInputBuilder::with_transport(fixture())
+            .build(3)
+            .await
+            .unwrap();
+        let block: RpcBlock<RpcTransaction> = EthInputBuilder::with_transport(fixture())
+            .get_block(3, true)
+            .await
+            .unwrap();
+        let hashes = block
+            .transactions
+            .iter()
+            .map(|transaction| transaction.hash)
+            .collect::<Vec<_>>();
+
+        let mut tampered = input.clone();
+        tampered.header.gas_used += U256::one();
+        assert!(matches!(
+            tampered.validate(&hashes),
+            Err(EthInputError::BlockHashMismatch { number: 3, .. })
+        ));
+
+        let mut tampered = input.clone();
+        tampered.ancestors[0] = tampered.header.clone();
+        assert!(matches!(
+            tampered.validate(&hashes),
+            Err(EthInputError::BrokenAncestorChain(3))
+        ));
+
+        let mut tampered = input.clone();
+        tampered.transactions[0] = Bytes::from(vec![0xc0]);
+        assert!(matches!(
+            tampered.validate(&hashes),
+            Err(EthInputError::TransactionHashMismatch(_))
+        ));
+
+        let mut tampered = input;
+        tampered.codes[0] = Bytes::from(vec![0x00]);
+        assert!(matches!(
+            tampered.validate(&hashes),
+            Err(EthInputError::CodeHashMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+//! JSON-RPC transports for the [super::EthInputBuilder].
+
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::EthInputError;
+use crate::RetryPolicy;
+
+/// A transport for Ethereum JSON-RPC requests.
+#[async_trait]
+pub trait RpcTransport: Send + Sync {
+    /// Sends the request `method` with `params`, returning its result.
+    async fn request(&self, method: &str, params: Value) -> Result<Value, EthInputError>;
+}
+
+/// A transport sending the requests to an HTTP endpoint.
+pub struct HttpTransport {
+    url: String,
+    client: reqwest::Client,
+    next_id: AtomicU64,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+/// A JSON-RPC response.
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcResponseError>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponseError {
+    code: i64,
+    message: String,
+}
+
+#[async_trait]
+impl RpcTransport for HttpTransport {
+    async fn request(&self, method: &str, params: Value) -> Result<Value, EthInputError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| EthInputError::Transport(err.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(EthInputError::Transport(format!(
+                "{} returned HTTP {}",
+                method, status
+            )));
+        }
+        let response: RpcResponse = response
+            .json()
+            .await
+            .map_err(|err| EthInputError::Transport(err.to_string()))?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(EthInputError::Rpc {
+                method: method.to_string(),
+                code: error.code,
+                message: error.message,
+            }),
+            (result, None) => Ok(result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// A transport retrying the failed requests of another one with the backoff of a [RetryPolicy].
+///
+/// Transport errors, and the JSON-RPC errors providers return when they are rate limited, are
+/// retried. Each attempt is bounded by [RetryPolicy::per_stage_timeout] if set.
+pub struct RetryingTransport<T> {
+    inner: T,
+    policy: RetryPolicy,
+}
+
+impl<T> RetryingTransport<T> {
+    pub fn new(inner: T, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+/// The JSON-RPC error code of a request over the rate limit of the provider.
+const LIMIT_EXCEEDED: i64 = -32005;
+
+impl EthInputError {
+    /// Whether the request that failed with this error may succeed if retried.
+    fn is_retriable(&self) -> bool {
+        match self {
+            EthInputError::Transport(_) => true,
+            EthInputError::Rpc { code, message, .. } => {
+                *code == LIMIT_EXCEEDED || message.to_lowercase().contains("rate limit")
+            }
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: RpcTransport> RpcTransport for RetryingTransport<T> {
+    async fn request(&self, method: &str, params: Value) -> Result<Value, EthInputError> {
+        let mut attempt = 0;
+        loop {
+            let request = self.inner.request(method, params.clone());
+            let result = match self.policy.per_stage_timeout {
+                Some(timeout) => {
+                    tokio::time::timeout(timeout, request)
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(EthInputError::Transport(format!("{} timed out", method)))
+                        })
+                }
+                None => request.await,
+            };
+            match result {
+                Err(err) if err.is_retriable() && attempt < self.policy.max_retries => {
+                    attempt += 1;
+                    tracing::warn!("{} failed, retrying: {}", method, err);
+                    tokio::time::sleep(self.policy.backoff_for(attempt)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A request and its result, as stored in a fixture.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub params: Value,
+    pub result: Value,
+}
+
+/// A transport replaying the requests recorded in a JSON fixture, or recording the requests sent
+/// to another transport into one.
+///
+/// The fixture is a JSON array of [RecordedRequest]s. Replaying it doesn't touch the network, so
+/// the tests can build inputs offline.
+pub struct FixtureTransport<T> {
+    inner: Option<T>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl FixtureTransport<HttpTransport> {
+    /// Replays the requests of the fixture at `path`. A request missing from it fails with
+    /// [EthInputError::FixtureMiss].
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, EthInputError> {
+        let requests =
+            serde_json::from_slice(&fs::read(path)?).map_err(|source| EthInputError::Decode {
+                method: "fixture".to_string(),
+                source,
+            })?;
+        Ok(Self {
+            inner: None,
+            requests: Mutex::new(requests),
+        })
+    }
+}
+
+impl<T> FixtureTransport<T> {
+    /// Sends the requests to `inner`, recording them to be written with [FixtureTransport::save].
+    pub fn record(inner: T) -> Self {
+        Self {
+            inner: Some(inner),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The requests recorded or replayed.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Writes the recorded requests to the fixture at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), EthInputError> {
+        let requests = self.requests();
+        fs::write(
+            path.as_ref(),
+            serde_json::to_vec_pretty(&requests).expect("failed to serialize the requests"),
+        )?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T: RpcTransport> RpcTransport for FixtureTransport<T> {
+    async fn request(&self, method: &str, params: Value) -> Result<Value, EthInputError> {
+        let recorded = self
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|request| request.method == method && request.params == params)
+            .map(|request| request.result.clone());
+        if let Some(result) = recorded {
+            return Ok(result);
+        }
+        let Some(inner) = &self.inner else {
+            return Err(EthInputError::FixtureMiss {
+                method: method.to_string(),
+                params,
+            });
+        };
+        let result = inner.request(method, params.clone()).await?;
+        self.requests.lock().unwrap().push(RecordedRequest {
+            method: method.to_string(),
+            params,
+            result: result.clone(),
+        });
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+    use std::time::Duration;
+
+    use super::*;
+
+    /// A transport failing its first `failures` requests.
+    struct FlakyTransport {
+        failures: u32,
+        attempts: AtomicU32,
+    }
+
+    #[async_trait]
+    impl RpcTransport for FlakyTransport {
+        async fn request(&self, method: &str, _: Value) -> Result<Value, EthInputError> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.failures {
+                return Err(EthInputError::Rpc {
+                    method: method.to_string(),
+                    code: LIMIT_EXCEEDED,
+                    message: "limit exceeded".to_string(),
+                });
+            }
+            Ok(json!(attempt))
+        }
+    }
+
+    fn policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            backoff: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retrying_transport() {
+        let flaky = FlakyTransport {
+            failures: 2,
+            attempts: AtomicU32::new(0),
+        };
+        let transport = RetryingTransport::new(flaky, policy(2));
+        assert_eq!(
+            transport
+                .request("eth_blockNumber", json!([]))
+                .await
+                .unwrap(),
+            json!(2)
+        );
+
+        let flaky = FlakyTransport {
+            failures: 2,
+            attempts: AtomicU32::new(0),
+        };
+        let transport = RetryingTransport::new(flaky, policy(1));
+        assert!(matches!(
+            transport.request("eth_blockNumber", json!([])).await,
+            Err(EthInputError::Rpc { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fixture_transport() {
+        let flaky = FlakyTransport {
+            failures: 0,
+            attempts: AtomicU32::new(0),
+        };
+        let recording = FixtureTransport::record(flaky);
+        recording.request("eth_chainId", json!([])).await.unwrap();
+        // A recorded request is answered from the fixture.
+        recording.request("eth_chainId", json!([])).await.unwrap();
+        assert_eq!(
+            recording.requests(),
+            vec![RecordedRequest {
+                method: "eth_chainId".to_string(),
+                params: json!([]),
+                result: json!(0),
+            }]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.json");
+        recording.save(&path).unwrap();
+        let replay = FixtureTransport::replay(&path).unwrap();
+        assert_eq!(
+            replay.request("eth_chainId", json!([])).await.unwrap(),
+            json!(0)
+        );
+        assert!(matches!(
+            replay.request("eth_blockNumber", json!([])).await,
+            Err(EthInputError::FixtureMiss { .. })
+        ));
+    }
+}
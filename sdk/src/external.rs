@@ -0,0 +1,93 @@
+//! Proofs of other zkVMs, written to the input of the SP1 programs verifying them.
+//!
+//! A program verifying a RISC Zero receipt, like the one of the `risc0-receipt` example, reads the
+//! image id of the program the receipt proves, then the receipt as serialized by RISC Zero with
+//! bincode. [Risc0Receipt::write_to] writes both in this order.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use sp1_prover::SP1Stdin;
+
+/// A RISC Zero receipt with the image id of the program it proves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Risc0Receipt {
+    /// The `risc0_zkvm::Receipt`, serialized with bincode as RISC Zero writes receipts to files.
+    pub receipt: Vec<u8>,
+    /// The image id, as the words of a `risc0_zkvm::sha::Digest`.
+    pub image_id: [u32; 8],
+}
+
+impl Risc0Receipt {
+    pub fn new(receipt: Vec<u8>, image_id: [u32; 8]) -> Self {
+        Self { receipt, image_id }
+    }
+
+    /// Reads a receipt from a file.
+    pub fn read(path: impl AsRef<Path>, image_id: [u32; 8]) -> Result<Self> {
+        let path = path.as_ref();
+        let receipt = fs::read(path)
+            .with_context(|| format!("failed to read the receipt at {}", path.display()))?;
+        Ok(Self::new(receipt, image_id))
+    }
+
+    /// Downloads a receipt, for instance from the receipt URL of a Bonsai session.
+    pub async fn fetch(url: &str, image_id: [u32; 8]) -> Result<Self> {
+        let response = Client::new()
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("failed to fetch the receipt at {}", url))?;
+        let receipt = response.bytes().await?.to_vec();
+        Ok(Self::new(receipt, image_id))
+    }
+
+    /// Parses an image id from its hex encoding, as a `risc0_zkvm::sha::Digest` displays it: the
+    /// little endian bytes of its words.
+    pub fn parse_image_id(hex: &str) -> Result<[u32; 8]> {
+        let hex = hex.trim().trim_start_matches("0x");
+        let bytes: [u8; 32] = hex::decode(hex)?
+            .try_into()
+            .map_err(|bytes: Vec<u8>| anyhow!("an image id has 32 bytes, not {}", bytes.len()))?;
+        let mut image_id = [0u32; 8];
+        for (word, bytes) in image_id.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(image_id)
+    }
+
+    /// Writes the image id, then the receipt, to `stdin`.
+    pub fn write_to(&self, stdin: &mut SP1Stdin) {
+        stdin.write(&self.image_id);
+        stdin.write_slice(&self.receipt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_image_id() {
+        let hex = "0100000002000000030000000400000005000000060000000700000008000000";
+        let image_id = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(Risc0Receipt::parse_image_id(hex).unwrap(), image_id);
+        assert_eq!(
+            Risc0Receipt::parse_image_id(&format!("0x{}\n", hex)).unwrap(),
+            image_id
+        );
+        assert!(Risc0Receipt::parse_image_id(&hex[2..]).is_err());
+        assert!(Risc0Receipt::parse_image_id("zz").is_err());
+    }
+
+    #[test]
+    fn test_write_to() {
+        let receipt = Risc0Receipt::new(vec![1, 2, 3], [7; 8]);
+        let mut stdin = SP1Stdin::new();
+        receipt.write_to(&mut stdin);
+        assert_eq!(stdin.read::<[u32; 8]>(), [7; 8]);
+        assert_eq!(stdin.buffer[1], vec![1, 2, 3]);
+    }
+}
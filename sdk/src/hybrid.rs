@@ -0,0 +1,420 @@
+//! Fast pre-confirmations from an attested executor, backed later by the SP1 proof of the same
+//! execution.
+//!
+//! An [ExecutorBackend] executes programs. [LocalExecutor] runs them with the SP1 runtime, and
+//! [RemoteExecutor] submits the digest of the ELF and the stdin to an executor service running in
+//! a trusted execution environment (TEE), which returns the public values along with an
+//! [Attestation] of the execution. The attestation is a pre-confirmation to act upon before the
+//! proof is ready. A [HybridProof] bundles it with the SP1 proof once generated, and
+//! [HybridProof::verify] checks both and that they commit to the same public values.
+//!
+//! The attestation document is specific to the TEE platform and is checked by an
+//! [AttestationVerifier], which must check that the document binds the
+//! [Attestation::claim_digest].
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp1_core::{
+    runtime::{ExecutionReport, Opcode, SyscallCode},
+    utils::SP1CoreOpts,
+};
+use sp1_prover::{utils::block_on, SP1Prover, SP1PublicValues, SP1Stdin, SP1VerifyingKey};
+use thiserror::Error;
+
+use crate::{execution_error, Prover, SP1Receipt, SP1VerificationError};
+
+/// The sha256 digest of an ELF, which identifies it to a [RemoteExecutor].
+pub fn elf_digest(elf: &[u8]) -> [u8; 32] {
+    Sha256::digest(elf).into()
+}
+
+/// The public values and report of an execution by an [ExecutorBackend], with the attestation
+/// of the executor if it is attested.
+#[derive(Debug, Clone)]
+pub struct ExecutorOutput {
+    pub public_values: SP1PublicValues,
+    pub report: ExecutionReport,
+    pub attestation: Option<Attestation>,
+}
+
+/// A backend executing programs.
+pub trait ExecutorBackend: Send + Sync {
+    /// Executes `elf` on `stdin`.
+    fn execute(&self, elf: &[u8], stdin: &SP1Stdin) -> Result<ExecutorOutput>;
+}
+
+/// Executes programs locally with the SP1 runtime, without attestation.
+#[derive(Debug, Clone, Default)]
+pub struct LocalExecutor {
+    pub opts: SP1CoreOpts,
+}
+
+impl ExecutorBackend for LocalExecutor {
+    fn execute(&self, elf: &[u8], stdin: &SP1Stdin) -> Result<ExecutorOutput> {
+        let (public_values, report) = SP1Prover::execute_with_opts(elf, stdin, self.opts)
+            .map_err(|err| execution_error(err, stdin))?;
+        Ok(ExecutorOutput {
+            public_values,
+            report,
+            attestation: None,
+        })
+    }
+}
+
+/// The claim of an attested executor that a program committed some public values when executed
+/// on some input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attestation {
+    /// The [elf_digest] of the program.
+    pub elf_digest: [u8; 32],
+    /// The [SP1Stdin::digest] of the input.
+    pub stdin_digest: [u8; 32],
+    pub public_values: SP1PublicValues,
+    /// The attestation document of the TEE, binding the [Attestation::claim_digest].
+    pub document: Vec<u8>,
+}
+
+impl Attestation {
+    /// The sha256 digest of the ELF digest, the stdin digest and the public values, which the
+    /// attestation document binds.
+    pub fn claim_digest(&self) -> [u8; 32] {
+        Sha256::new()
+            .chain_update(self.elf_digest)
+            .chain_update(self.stdin_digest)
+            .chain_update(self.public_values.as_slice())
+            .finalize()
+            .into()
+    }
+}
+
+/// Checks attestation documents of a TEE platform.
+pub trait AttestationVerifier: Send + Sync {
+    /// Checks that `attestation.document` is a valid document of a trusted executor binding
+    /// [Attestation::claim_digest].
+    fn verify(&self, attestation: &Attestation) -> Result<()>;
+}
+
+/// The body of a request to the executor service.
+#[derive(Serialize, Deserialize)]
+struct ExecuteRequest {
+    /// The hex encoded [elf_digest] of the program, which the service must know.
+    elf_digest: String,
+    /// The hex encoded stdin, serialized with bincode.
+    stdin: String,
+}
+
+/// The body of a response of the executor service.
+#[derive(Serialize, Deserialize)]
+struct ExecuteResponse {
+    /// The hex encoded public values.
+    public_values: String,
+    opcode_counts: HashMap<Opcode, u64>,
+    syscall_counts: HashMap<SyscallCode, u64>,
+    gas_used: u64,
+    /// The hex encoded attestation document.
+    document: String,
+}
+
+/// Executes programs with an attested executor service, which runs the programs it knows by
+/// [elf_digest] at `POST {url}/execute`.
+///
+/// The report of an execution only has the opcode and syscall counts and the gas used.
+#[derive(Debug, Clone)]
+pub struct RemoteExecutor {
+    url: String,
+    client: Client,
+}
+
+impl RemoteExecutor {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// Executes `elf` on `stdin` with the executor service, see [ExecutorBackend::execute].
+    pub async fn execute_async(&self, elf: &[u8], stdin: &SP1Stdin) -> Result<ExecutorOutput> {
+        let elf_digest = elf_digest(elf);
+        let request = ExecuteRequest {
+            elf_digest: hex::encode(elf_digest),
+            stdin: hex::encode(bincode::serialize(stdin)?),
+        };
+        let response: ExecuteResponse = self
+            .client
+            .post(format!("{}/execute", self.url))
+            .json(&request)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("failed to execute the program at {}", self.url))?
+            .json()
+            .await
+            .context("invalid response of the executor service")?;
+
+        let public_values = SP1PublicValues::from(&hex::decode(response.public_values)?);
+        let mut report = ExecutionReport::default();
+        report.opcode_counts = response.opcode_counts;
+        report.syscall_counts = response.syscall_counts;
+        report.gas_used = response.gas_used;
+        let attestation = Attestation {
+            elf_digest,
+            stdin_digest: stdin.digest(),
+            public_values: public_values.clone(),
+            document: hex::decode(response.document)?,
+        };
+        Ok(ExecutorOutput {
+            public_values,
+            report,
+            attestation: Some(attestation),
+        })
+    }
+}
+
+impl ExecutorBackend for RemoteExecutor {
+    fn execute(&self, elf: &[u8], stdin: &SP1Stdin) -> Result<ExecutorOutput> {
+        block_on(self.execute_async(elf, stdin))
+    }
+}
+
+/// An error of [HybridProof::verify].
+#[derive(Error, Debug)]
+pub enum HybridError {
+    #[error("invalid attestation: {0}")]
+    Attestation(anyhow::Error),
+    #[error("the attestation is for the ELF 0x{found}, expected 0x{expected}")]
+    ElfMismatch { expected: String, found: String },
+    #[error("the SP1 proof is not attached yet")]
+    ProofPending,
+    #[error(transparent)]
+    Proof(#[from] SP1VerificationError),
+    #[error("the attested public values differ from the public values of the proof")]
+    PublicValuesMismatch,
+}
+
+/// An attestation of an execution, with the SP1 proof of the same execution once generated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridProof {
+    pub attestation: Attestation,
+    pub proof: Option<SP1Receipt>,
+}
+
+impl HybridProof {
+    pub fn new(attestation: Attestation) -> Self {
+        Self {
+            attestation,
+            proof: None,
+        }
+    }
+
+    /// Attaches the SP1 proof of the execution.
+    pub fn with_proof(mut self, proof: SP1Receipt) -> Self {
+        self.proof = Some(proof);
+        self
+    }
+
+    /// Checks the attestation alone, for the program of digest `elf_digest`. This is the check of
+    /// a pre-confirmation.
+    pub fn verify_attestation(
+        &self,
+        elf_digest: [u8; 32],
+        verifier: &dyn AttestationVerifier,
+    ) -> Result<(), HybridError> {
+        if self.attestation.elf_digest != elf_digest {
+            return Err(HybridError::ElfMismatch {
+                expected: hex::encode(elf_digest),
+                found: hex::encode(self.attestation.elf_digest),
+            });
+        }
+        verifier
+            .verify(&self.attestation)
+            .map_err(HybridError::Attestation)
+    }
+
+    /// Checks the attestation and the SP1 proof of the program of digest `elf_digest` and
+    /// verifying key `vkey`, and that they commit to the same public values.
+    pub fn verify(
+        &self,
+        prover: &dyn Prover,
+        vkey: &SP1VerifyingKey,
+        elf_digest: [u8; 32],
+        verifier: &dyn AttestationVerifier,
+    ) -> Result<(), HybridError> {
+        self.verify_attestation(elf_digest, verifier)?;
+        let proof = self.proof.as_ref().ok_or(HybridError::ProofPending)?;
+        proof.verify(prover, vkey)?;
+        if proof.public_values.as_slice() != self.attestation.public_values.as_slice() {
+            return Err(HybridError::PublicValuesMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::ensure;
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+
+    use super::*;
+    use crate::{utils, ProverClient};
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+
+    /// The document of the mock TEE: the digest of its key and the claim.
+    fn mock_document(claim_digest: [u8; 32]) -> Vec<u8> {
+        Sha256::new()
+            .chain_update(b"mock-tee")
+            .chain_update(claim_digest)
+            .finalize()
+            .to_vec()
+    }
+
+    struct MockAttestationVerifier;
+
+    impl AttestationVerifier for MockAttestationVerifier {
+        fn verify(&self, attestation: &Attestation) -> Result<()> {
+            ensure!(
+                attestation.document == mock_document(attestation.claim_digest()),
+                "the document does not bind the claim"
+            );
+            Ok(())
+        }
+    }
+
+    /// An executor service running the programs it knows and attesting with the mock TEE.
+    async fn mock_execute(
+        State(elfs): State<Arc<HashMap<String, Vec<u8>>>>,
+        Json(request): Json<ExecuteRequest>,
+    ) -> Result<Json<ExecuteResponse>, StatusCode> {
+        let elf = elfs.get(&request.elf_digest).ok_or(StatusCode::NOT_FOUND)?;
+        let stdin: SP1Stdin = hex::decode(&request.stdin)
+            .ok()
+            .and_then(|bytes| bincode::deserialize(&bytes).ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let (public_values, report) = LocalExecutor::default()
+            .execute(elf, &stdin)
+            .map(|output| (output.public_values, output.report))
+            .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+        let attestation = Attestation {
+            elf_digest: elf_digest(elf),
+            stdin_digest: stdin.digest(),
+            public_values: public_values.clone(),
+            document: vec![],
+        };
+        Ok(Json(ExecuteResponse {
+            public_values: hex::encode(public_values.as_slice()),
+            opcode_counts: report.opcode_counts,
+            syscall_counts: report.syscall_counts,
+            gas_used: report.gas_used,
+            document: hex::encode(mock_document(attestation.claim_digest())),
+        }))
+    }
+
+    /// Serves [mock_execute] for the fibonacci program on `runtime`, returning its URL.
+    fn spawn_mock_executor(runtime: &tokio::runtime::Runtime) -> String {
+        let elfs = HashMap::from([(
+            hex::encode(elf_digest(FIBONACCI_ELF)),
+            FIBONACCI_ELF.to_vec(),
+        )]);
+        let app = Router::new()
+            .route("/execute", post(mock_execute))
+            .with_state(Arc::new(elfs));
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        runtime.spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    fn stdin(n: u32) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&n);
+        stdin
+    }
+
+    #[test]
+    fn test_hybrid_proof() {
+        utils::setup_logger();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let executor = RemoteExecutor::new(&spawn_mock_executor(&runtime));
+        let digest = elf_digest(FIBONACCI_ELF);
+
+        // The remote execution matches the local one and is attested.
+        let output = executor.execute(FIBONACCI_ELF, &stdin(10)).unwrap();
+        let local = LocalExecutor::default()
+            .execute(FIBONACCI_ELF, &stdin(10))
+            .unwrap();
+        assert_eq!(
+            output.public_values.as_slice(),
+            local.public_values.as_slice()
+        );
+        assert_eq!(output.report.opcode_counts, local.report.opcode_counts);
+        assert!(local.attestation.is_none());
+        let hybrid = HybridProof::new(output.attestation.unwrap());
+        hybrid
+            .verify_attestation(digest, &MockAttestationVerifier)
+            .unwrap();
+        assert!(matches!(
+            hybrid.verify_attestation([0; 32], &MockAttestationVerifier),
+            Err(HybridError::ElfMismatch { .. })
+        ));
+
+        let client = ProverClient::mock();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let verify = |hybrid: &HybridProof| {
+            hybrid.verify(
+                client.prover.as_ref(),
+                &vk,
+                digest,
+                &MockAttestationVerifier,
+            )
+        };
+        assert!(matches!(verify(&hybrid), Err(HybridError::ProofPending)));
+
+        let proof = client.prove(&pk, stdin(10)).unwrap();
+        let receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        let hybrid = hybrid.with_proof(receipt);
+        verify(&hybrid).unwrap();
+
+        // A doctored attestation is rejected.
+        let mut doctored = hybrid.clone();
+        doctored.attestation.public_values = SP1PublicValues::from(&[0u8; 4]);
+        assert!(matches!(
+            verify(&doctored),
+            Err(HybridError::Attestation(_))
+        ));
+    }
+
+    #[test]
+    fn test_hybrid_proof_public_values_mismatch() {
+        utils::setup_logger();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let executor = RemoteExecutor::new(&spawn_mock_executor(&runtime));
+        let output = executor.execute(FIBONACCI_ELF, &stdin(10)).unwrap();
+
+        // The proof of another input commits to other public values.
+        let client = ProverClient::mock();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let proof = client.prove(&pk, stdin(11)).unwrap();
+        let receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        let hybrid = HybridProof::new(output.attestation.unwrap()).with_proof(receipt);
+        let result = hybrid.verify(
+            client.prover.as_ref(),
+            &vk,
+            elf_digest(FIBONACCI_ELF),
+            &MockAttestationVerifier,
+        );
+        assert!(matches!(result, Err(HybridError::PublicValuesMismatch)));
+
+        // An executor service which doesn't know the program fails.
+        assert!(executor.execute(b"unknown", &stdin(10)).is_err());
+    }
+}
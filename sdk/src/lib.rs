@@ -10,39 +10,113 @@ pub mod proto {
     pub mod network;
 }
 pub mod artifacts;
+pub mod async_client;
+pub mod batch;
+pub mod builder;
+pub mod bundle;
+pub mod cost;
+pub mod decode;
+pub mod error;
+#[cfg(feature = "eth")]
+pub mod eth;
+pub mod external;
+pub mod hybrid;
 #[cfg(feature = "network")]
 pub mod network;
 #[cfg(feature = "network")]
 pub use crate::network::prover::NetworkProver;
 
+pub mod profile;
 pub mod provers;
+pub mod receipt;
+pub mod retry;
+pub mod segments;
+pub mod session;
+pub mod store;
+pub mod tenancy;
 pub mod utils {
     pub use sp1_core::utils::setup_logger;
 }
 
 use cfg_if::cfg_if;
 pub use provers::SP1VerificationError;
-use std::{env, fmt::Debug, fs::File, path::Path};
+use std::{
+    env,
+    fmt::Debug,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{Ok, Result};
 
-pub use provers::{LocalProver, MockProver, Prover};
+pub use async_client::{AsyncProverClient, ProgressEvent, ProofJob};
+pub use batch::{BatchError, BatchProof, BatchProver};
+pub use builder::{ConfigError, ConfigIssue, ProverClientBuilder, ProverClientConfig};
+pub use bundle::{
+    bundle_root, verify_bundle_membership, BundleError, BundledProgram, ProgramBundle,
+};
+pub use cost::{CalibrationSample, CostEstimate, CostModel, StageEstimate};
+pub use decode::{ProofDecodeError, ProofDecodeLimits, ProofShape};
+pub use error::{MismatchContext, SP1Error};
+pub use external::Risc0Receipt;
+pub use hybrid::{
+    Attestation, AttestationVerifier, ExecutorBackend, ExecutorOutput, HybridError, HybridProof,
+    LocalExecutor, RemoteExecutor,
+};
+pub use profile::{ChipTrace, MemoryProfile, ProfileReport, StageTiming, TraceReport};
+pub use provers::{LocalProver, MockProver, PlonkArtifacts, Prover};
+pub use receipt::{SP1Receipt, SP1ReceiptProof};
+pub use retry::{ProvingReport, RetryPolicy, StageReport};
+pub use segments::{SegmentChain, SegmentError, SegmentProver};
+pub use session::{KeyCache, ProverSession};
+pub use sp1_core::features::{supported_features, FeatureInfo, SUPPORTED_FEATURES};
+pub use sp1_core::io::{
+    ChunkedCommitError, FromPublicValues, KvCommitError, KvCommitment, OutputChunk,
+    PublicValuesLengthError, SegmentChainError, SegmentDigests, SpanCycles,
+};
+pub use sp1_core::runtime::{
+    GasSchedule, GasScheduleDigest, OpcodeClass, ProfilerOpts, UnconstrainedSyscallContext,
+    UnconstrainedSyscalls,
+};
+pub use sp1_core::utils::{
+    guest_test_main, GuestTestError, GuestTestOutcome, GuestTestRunner, MemoryBudgets,
+};
+pub use store::{ContentId, ProofStore, ProofStoreError};
+pub use tenancy::{ProgramId, TenancyError, TenantClient, TenantId, TenantRegistry};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sp1_core::{
-    runtime::ExecutionReport,
+    air::PublicValues,
+    runtime::{CoverageReport, ExecutionError, ExecutionReport},
     stark::{MachineVerificationError, ShardProof},
     SP1_CIRCUIT_VERSION,
 };
 pub use sp1_prover::{
-    CoreSC, HashableKey, InnerSC, OuterSC, PlonkBn254Proof, SP1Prover, SP1ProvingKey,
-    SP1PublicValues, SP1Stdin, SP1VerifyingKey,
+    CoreSC, HashableKey, InnerSC, NodeReport, OuterSC, PlonkBn254Proof, ReduceJobOpts, SP1Prover,
+    SP1ProvingKey, SP1PublicValues, SP1ReduceProof, SP1Stdin, SP1VerifyingKey,
 };
 
+/// Converts an execution error, pointing a failed `sp1_zkvm::io::read` back to the `SP1Stdin`
+/// write call that produced the input.
+fn execution_error(err: ExecutionError, stdin: &SP1Stdin) -> anyhow::Error {
+    let description = match &err {
+        ExecutionError::GuestPanic { message, .. } => stdin.describe_read_error(message),
+        _ => None,
+    };
+    match description {
+        Some(description) => anyhow::Error::new(err).context(description),
+        None => err.into(),
+    }
+}
+
 /// A client for interacting with SP1.
 pub struct ProverClient {
     /// The underlying prover implementation.
     pub prover: Box<dyn Prover>,
+    /// The programs and caches of the tenants served by the client, see [tenancy].
+    tenants: TenantRegistry,
+    /// The keys of the programs set up by the client, see [session].
+    keys: KeyCache,
 }
 
 /// A proof generated with SP1.
@@ -53,7 +127,26 @@ pub struct SP1ProofWithPublicValues<P> {
     pub proof: P,
     pub stdin: SP1Stdin,
     pub public_values: SP1PublicValues,
+    /// Whether the program made unconstrained syscalls, whose effects the proof says nothing
+    /// about. Such proofs are only generated with
+    /// [sp1_core::utils::SP1CoreOpts::allow_unconstrained] set.
+    #[serde(default)]
+    pub insecure: bool,
+    /// The digest of the verifying key the proof was generated for, see
+    /// [HashableKey::hash_bytes]. `None` for proofs that do not record it, which are only checked
+    /// against the verifying key by the verifier itself.
+    #[serde(default)]
+    pub vkey_digest: Option<[u8; 32]>,
+    /// The HTML [ProfileReport] written for the proof, if the client was built with
+    /// [ProverClient::with_profile].
+    #[serde(default)]
+    pub profile_path: Option<PathBuf>,
     pub sp1_version: String,
+    /// The bitmap of the features the proof was generated with, see [supported_features]. The
+    /// verifiers reject the features they don't support, and the proofs committing to other
+    /// features than these.
+    #[serde(default)]
+    pub features: u32,
 }
 
 /// A [SP1ProofWithPublicValues] generated with [ProverClient::prove].
@@ -89,18 +182,12 @@ impl ProverClient {
             .to_lowercase()
             .as_str()
         {
-            "mock" => Self {
-                prover: Box::new(MockProver::new()),
-            },
-            "local" => Self {
-                prover: Box::new(LocalProver::new()),
-            },
+            "mock" => Self::with_prover(Box::new(MockProver::new())),
+            "local" => Self::with_prover(Box::new(LocalProver::new())),
             "network" => {
                 cfg_if! {
                     if #[cfg(feature = "network")] {
-                        Self {
-                            prover: Box::new(NetworkProver::new()),
-                        }
+                        Self::with_prover(Box::new(NetworkProver::new()))
                     } else {
                         panic!("network feature is not enabled")
                     }
@@ -112,6 +199,29 @@ impl ProverClient {
         }
     }
 
+    /// A [ProverClientBuilder] validating the configuration from code and environment variables
+    /// before creating the [ProverClient].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::ProverClient;
+    ///
+    /// let client = ProverClient::builder().shard_size(1 << 20).build().unwrap();
+    /// ```
+    pub fn builder() -> ProverClientBuilder {
+        ProverClientBuilder::new()
+    }
+
+    /// Creates a new [ProverClient] with the given prover.
+    pub(crate) fn with_prover(prover: Box<dyn Prover>) -> Self {
+        Self {
+            prover,
+            tenants: TenantRegistry::default(),
+            keys: KeyCache::default(),
+        }
+    }
+
     /// Creates a new [ProverClient] with the mock prover.
     ///
     /// Recommended for testing and development. You can also use [ProverClient::new] to set the
@@ -125,9 +235,7 @@ impl ProverClient {
     /// let client = ProverClient::mock();
     /// ```
     pub fn mock() -> Self {
-        Self {
-            prover: Box::new(MockProver::new()),
-        }
+        Self::with_prover(Box::new(MockProver::new()))
     }
 
     /// Creates a new [ProverClient] with the local prover.
@@ -143,9 +251,7 @@ impl ProverClient {
     /// let client = ProverClient::local();
     /// ```
     pub fn local() -> Self {
-        Self {
-            prover: Box::new(LocalProver::new()),
-        }
+        Self::with_prover(Box::new(LocalProver::new()))
     }
 
     /// Creates a new [ProverClient] with the network prover.
@@ -163,15 +269,237 @@ impl ProverClient {
     pub fn network() -> Self {
         cfg_if! {
             if #[cfg(feature = "network")] {
-                Self {
-                    prover: Box::new(NetworkProver::new()),
-                }
+                Self::with_prover(Box::new(NetworkProver::new()))
             } else {
                 panic!("network feature is not enabled")
             }
         }
     }
 
+    /// Runs every proving stage under the given [RetryPolicy].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sp1_sdk::{ProverClient, RetryPolicy};
+    ///
+    /// let client = ProverClient::local().with_retry_policy(RetryPolicy {
+    ///     per_stage_timeout: Some(Duration::from_secs(3600)),
+    ///     max_retries: 2,
+    ///     backoff: Duration::from_secs(10),
+    /// });
+    /// ```
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.prover.set_retry_policy(policy);
+        self
+    }
+
+    /// Limits the memory footprint of the guest to `limit` bytes when executing or proving locally.
+    /// The footprint counts the distinct words touched, wherever they are in the address space. A
+    /// guest touching more memory than that fails with `ExecutionError::MemoryLimitExceeded`.
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::ProverClient;
+    ///
+    /// let client = ProverClient::local().with_max_memory(256 << 20);
+    /// ```
+    pub fn with_max_memory(mut self, limit: u64) -> Self {
+        self.prover.set_max_memory(limit);
+        self
+    }
+
+    /// Handles the unconstrained syscalls of the programs with `syscalls` when executing or proving
+    /// locally, see [UnconstrainedSyscalls].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, UnconstrainedSyscalls};
+    ///
+    /// let mut syscalls = UnconstrainedSyscalls::new();
+    /// syscalls.register(0xC0, |ctx, x, y| {
+    ///     let sum = ctx.read_word(x).wrapping_add(ctx.read_word(y));
+    ///     ctx.write_word(x, sum);
+    /// });
+    /// let client = ProverClient::local().with_unconstrained_syscalls(syscalls);
+    /// ```
+    pub fn with_unconstrained_syscalls(mut self, syscalls: UnconstrainedSyscalls) -> Self {
+        self.prover.set_unconstrained_syscalls(syscalls);
+        self
+    }
+
+    /// Makes the local proofs reproducible: proving the same program on the same input with the
+    /// same options again gives the same bytes, up to the wrapped proof. The PLONK proofs are
+    /// still blinded with fresh randomness. See [sp1_core::utils::determinism_seed].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::ProverClient;
+    ///
+    /// let client = ProverClient::local().with_deterministic(true);
+    /// ```
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.prover.set_deterministic(deterministic);
+        self
+    }
+
+    /// Bounds the peak heap usage of each local proving stage. A stage going over its budget stops
+    /// at the next point where it can stop cleanly with a `MemoryBudgetExceeded` error instead of
+    /// running out of memory. The peak of every stage is recorded in the [ProvingReport].
+    ///
+    /// Requires the `memory-stats` feature, without which the budgets are ignored.
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{MemoryBudgets, ProverClient};
+    ///
+    /// let client = ProverClient::local().with_memory_budgets(MemoryBudgets {
+    ///     core: Some(16 << 30),
+    ///     compress: Some(32 << 30),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn with_memory_budgets(mut self, budgets: MemoryBudgets) -> Self {
+        self.prover.set_memory_budgets(budgets);
+        self
+    }
+
+    /// Writes a [ProfileReport] of each local proof to [profile::profile_dir], `sp1-profile`
+    /// unless set by the `SP1_PROFILE_DIR` environment variable, and records the path of its HTML
+    /// page in [SP1ProofWithPublicValues::profile_path]. The report is written next to a JSON copy
+    /// and to the call stacks of the program, sampled by executing it once more with the profiler.
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::local().with_profile(true);
+    /// let (pk, _) = client.setup(elf);
+    /// let proof = client.prove(&pk, SP1Stdin::new()).unwrap();
+    /// println!("profile: {}", proof.profile_path.unwrap().display());
+    /// ```
+    pub fn with_profile(mut self, profile: bool) -> Self {
+        self.prover.set_profile(profile);
+        self
+    }
+
+    /// Proves programs locally with core shards of `shard_size` cycles.
+    ///
+    /// By default, the shard size is picked for each program by executing it once and keeping the
+    /// traces of its densest precompiles under the maximum height, unless the `SHARD_SIZE`
+    /// environment variable is set.
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::ProverClient;
+    ///
+    /// let client = ProverClient::local().with_shard_size(1 << 20);
+    /// ```
+    pub fn with_shard_size(mut self, shard_size: usize) -> Self {
+        self.prover.set_shard_size(shard_size);
+        self
+    }
+
+    /// Generates the witnesses of the reduce nodes of local proofs in worker processes, which are
+    /// scheduled and retried independently. The time spent on each node is recorded in the
+    /// [ProvingReport].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, ReduceJobOpts};
+    ///
+    /// let client = ProverClient::local().with_reduce_jobs(ReduceJobOpts::new("reduce_worker"));
+    /// ```
+    pub fn with_reduce_jobs(mut self, opts: ReduceJobOpts) -> Self {
+        self.prover.set_reduce_jobs(opts);
+        self
+    }
+
+    /// Shares `registry` with other clients, or keeps the caches of the tenants in another
+    /// directory than `~/.sp1/tenants`.
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, TenantRegistry};
+    ///
+    /// let client = ProverClient::local().with_tenant_registry(TenantRegistry::new("/var/sp1"));
+    /// ```
+    pub fn with_tenant_registry(mut self, registry: TenantRegistry) -> Self {
+        self.tenants = registry;
+        self
+    }
+
+    /// The programs and caches of the tenants served by the client.
+    pub fn tenants(&self) -> &TenantRegistry {
+        &self.tenants
+    }
+
+    /// Persists the keys of the programs set up by the client to `dir`, where they are looked up
+    /// before setting up a program, see [KeyCache::with_dir].
+    pub fn with_key_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.keys = KeyCache::with_dir(dir);
+        self
+    }
+
+    /// A [ProverSession] proving the program `elf` on many inputs, which only does the
+    /// program-dependent work of its proofs once, see [session].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let session = client.prover_session(elf);
+    /// let mut stdin = SP1Stdin::new();
+    /// stdin.write(&10usize);
+    /// let proof = session.prove(stdin).unwrap();
+    /// session.verify(&proof).unwrap();
+    /// ```
+    pub fn prover_session(&self, elf: &[u8]) -> ProverSession<'_> {
+        ProverSession::new(self, elf)
+    }
+
+    /// A [TenantClient] proving and verifying the programs registered by the tenant `tenant_id`
+    /// only, see [tenancy].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let tenant = client.for_tenant("acme").unwrap();
+    /// let program = tenant.register_program(elf);
+    /// let proof = tenant.prove(&program, SP1Stdin::new()).unwrap();
+    /// ```
+    pub fn for_tenant(&self, tenant_id: &str) -> Result<TenantClient<'_>, TenancyError> {
+        Ok(TenantClient::new(self, TenantId::new(tenant_id)?))
+    }
+
+    /// Registers the program `elf` for `tenant`, see [TenantRegistry::register_program].
+    pub fn register_program(&self, tenant: &TenantId, elf: &[u8]) -> ProgramId {
+        self.tenants.register_program(tenant, elf, self)
+    }
+
+    /// Returns the attempts and timeouts of each stage of the last proof, if the prover records
+    /// them.
+    pub fn proving_report(&self) -> Option<ProvingReport> {
+        self.prover.proving_report()
+    }
+
     /// Gets the current version of the SP1 zkVM.
     ///
     /// Note: This is not the same as the version of the SP1 SDK.
@@ -206,7 +534,157 @@ impl ProverClient {
         elf: &[u8],
         stdin: SP1Stdin,
     ) -> Result<(SP1PublicValues, ExecutionReport)> {
-        Ok(SP1Prover::execute(elf, &stdin)?)
+        SP1Prover::execute_with_unconstrained_syscalls(
+            elf,
+            &stdin,
+            self.prover.core_opts(),
+            &self.prover.unconstrained_syscalls(),
+        )
+        .map_err(|err| execution_error(err, &stdin))
+    }
+
+    /// Executes the given program like [ProverClient::execute], metering its gas with `schedule`
+    /// instead of the default [GasSchedule]. The gas is reported as [ExecutionReport::gas_used],
+    /// along with the digest of the schedule.
+    ///
+    /// A program reading its gas with `sp1_zkvm::io::gas_used` reads the gas metered with the
+    /// default schedule when proven.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use sp1_sdk::{GasSchedule, ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let mut stdin = SP1Stdin::new();
+    /// stdin.write(&10usize);
+    /// let schedule = GasSchedule::from_json(r#"{"opcode_weights": {"DivRem": 40}}"#).unwrap();
+    /// let (_, report) = client.execute_with_gas_schedule(elf, stdin, &schedule).unwrap();
+    /// println!("gas used: {}", report.gas_used);
+    /// ```
+    pub fn execute_with_gas_schedule(
+        &self,
+        elf: &[u8],
+        stdin: SP1Stdin,
+        schedule: &GasSchedule,
+    ) -> Result<(SP1PublicValues, ExecutionReport)> {
+        SP1Prover::execute_with_gas_schedule(elf, &stdin, self.prover.core_opts(), schedule)
+            .map_err(|err| execution_error(err, &stdin))
+    }
+
+    /// Executes the given program like [ProverClient::execute], also returning a [CoverageReport]
+    /// of the instructions and functions that were executed.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let mut stdin = SP1Stdin::new();
+    /// stdin.write(&10usize);
+    /// let (public_values, report, coverage) = client.execute_with_coverage(elf, stdin).unwrap();
+    /// std::fs::write("coverage.json", coverage.to_json().unwrap()).unwrap();
+    /// ```
+    pub fn execute_with_coverage(
+        &self,
+        elf: &[u8],
+        stdin: SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport, CoverageReport)> {
+        SP1Prover::execute_with_coverage(elf, &stdin).map_err(|err| execution_error(err, &stdin))
+    }
+
+    /// Executes the given program like [ProverClient::execute], also sampling its call stack every
+    /// `profiler.interval` cycles. The samples are written to `profiler.output` in the folded
+    /// format of inferno and `flamegraph.pl`, whose path is set in the returned report.
+    ///
+    /// Call stacks are only recovered if the program is built with frame pointers, by adding
+    /// `-C force-frame-pointers=yes` to its rustflags. Otherwise, each sample is attributed to the
+    /// function being executed alone.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use sp1_sdk::{ProfilerOpts, ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let mut stdin = SP1Stdin::new();
+    /// stdin.write(&10usize);
+    /// let profiler = ProfilerOpts {
+    ///     interval: 100,
+    ///     output: "fibonacci.folded".into(),
+    /// };
+    /// let (public_values, report) = client.execute_with_profiler(elf, stdin, profiler).unwrap();
+    /// ```
+    pub fn execute_with_profiler(
+        &self,
+        elf: &[u8],
+        stdin: SP1Stdin,
+        profiler: ProfilerOpts,
+    ) -> Result<(SP1PublicValues, ExecutionReport)> {
+        let (public_values, mut report, samples) = SP1Prover::execute_with_profiler(
+            elf,
+            &stdin,
+            self.prover.core_opts(),
+            profiler.interval,
+        )
+        .map_err(|err| execution_error(err, &stdin))?;
+        let mut file = std::io::BufWriter::new(File::create(&profiler.output)?);
+        samples.write_folded(elf, &mut file)?;
+        std::io::Write::flush(&mut file)?;
+        report.profile_path = Some(profiler.output);
+        Ok((public_values, report))
+    }
+
+    /// Executes the given program like [ProverClient::execute], also returning the chunks it
+    /// committed with `sp1_zkvm::io::commit_chunk`. The execution is deterministic, so these are
+    /// the chunks committed in a proof of the program on the same input, which are checked with
+    /// [SP1ProofWithPublicValues::output_chunks].
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let (pk, vk) = client.setup(elf);
+    /// let stdin = SP1Stdin::new();
+    /// let (_, chunks, _) = client.execute_with_output_chunks(elf, stdin.clone()).unwrap();
+    /// let proof = client.prove(&pk, stdin).unwrap();
+    /// client.verify(&proof, &vk).unwrap();
+    /// let output_chunks = proof.output_chunks(&chunks).unwrap();
+    /// ```
+    pub fn execute_with_output_chunks(
+        &self,
+        elf: &[u8],
+        stdin: SP1Stdin,
+    ) -> Result<(SP1PublicValues, Vec<Vec<u8>>, ExecutionReport)> {
+        SP1Prover::execute_with_output_chunks(elf, &stdin, self.prover.core_opts())
+            .map_err(|err| execution_error(err, &stdin))
+    }
+
+    /// Executes the given program like [ProverClient::execute], also returning the entries it
+    /// committed with `sp1_zkvm::io::commit_kv`, which are checked against the root committed in
+    /// the public values with [KvCommitment::verify_entries].
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use sp1_sdk::{ProverClient, SP1Stdin};
+    ///
+    /// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    /// let client = ProverClient::new();
+    /// let (mut public_values, entries, _) =
+    ///     client.execute_with_kv_entries(elf, SP1Stdin::new()).unwrap();
+    /// let commitment = public_values.read_kv_commitment();
+    /// let entries = commitment.verify_entries(&entries).unwrap();
+    /// ```
+    pub fn execute_with_kv_entries(
+        &self,
+        elf: &[u8],
+        stdin: SP1Stdin,
+    ) -> Result<(SP1PublicValues, Vec<Vec<u8>>, ExecutionReport)> {
+        SP1Prover::execute_with_kv_entries(elf, &stdin, self.prover.core_opts())
+            .map_err(|err| execution_error(err, &stdin))
     }
 
     /// Setup a program to be proven and verified by the SP1 RISC-V zkVM by computing the proving
@@ -226,7 +704,8 @@ impl ProverClient {
     /// let (pk, vk) = client.setup(elf);
     /// ```
     pub fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
-        self.prover.setup(elf)
+        let (pk, vk) = self.keys.setup(elf, self.prover.as_ref());
+        (pk.as_ref().clone(), vk.as_ref().clone())
     }
 
     /// Proves the execution of the given program with the given input in the default mode.
@@ -256,6 +735,8 @@ impl ProverClient {
     /// let proof = client.prove(&pk, stdin).unwrap();
     /// ```
     pub fn prove(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1Proof> {
+        error::check_proving_key(pk)?;
+        error::check_deferred_proofs(&stdin)?;
         self.prover.prove(pk, stdin)
     }
 
@@ -289,6 +770,8 @@ impl ProverClient {
         pk: &SP1ProvingKey,
         stdin: SP1Stdin,
     ) -> Result<SP1CompressedProof> {
+        error::check_proving_key(pk)?;
+        error::check_deferred_proofs(&stdin)?;
         self.prover.prove_compressed(pk, stdin)
     }
 
@@ -319,6 +802,8 @@ impl ProverClient {
     /// ```
     /// Generates a plonk bn254 proof, verifiable onchain, of the given elf and stdin.
     pub fn prove_plonk(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1PlonkBn254Proof> {
+        error::check_proving_key(pk)?;
+        error::check_deferred_proofs(&stdin)?;
         self.prover.prove_plonk(pk, stdin)
     }
 
@@ -410,6 +895,15 @@ impl ProverClient {
     ) -> Result<(), SP1VerificationError> {
         self.prover.verify_plonk(proof, vkey)
     }
+
+    /// Verifies a [SP1Receipt] with the verifier matching the stage of its proof.
+    pub fn verify_receipt(
+        &self,
+        receipt: &SP1Receipt,
+        vkey: &SP1VerifyingKey,
+    ) -> Result<(), SP1VerificationError> {
+        receipt.verify(self.prover.as_ref(), vkey)
+    }
 }
 
 impl Default for ProverClient {
@@ -425,10 +919,86 @@ impl<P: Debug + Clone + Serialize + DeserializeOwned> SP1ProofWithPublicValues<P
             .map_err(Into::into)
     }
 
-    /// Loads a proof from a path.
+    /// Saves the proof to a path, compressed with zstd behind a checksum, see [store].
+    pub fn save_compressed(&self, path: impl AsRef<Path>) -> Result<()> {
+        store::write_compressed(self, File::create(path)?).map_err(Into::into)
+    }
+}
+
+impl<P> SP1ProofWithPublicValues<P> {
+    /// Checks the chunks captured by [ProverClient::execute_with_output_chunks] against the
+    /// chunked commitment of the public values, returning the digest of each chunk. The proof
+    /// itself must be verified separately.
+    pub fn output_chunks(
+        &self,
+        chunks: &[Vec<u8>],
+    ) -> Result<Vec<OutputChunk>, ChunkedCommitError> {
+        self.public_values.output_chunks(chunks)
+    }
+}
+
+impl<P: ProofShape + Debug + Clone + Serialize + DeserializeOwned> SP1ProofWithPublicValues<P> {
+    /// Loads a proof from a path, checking it against the default limits of its kind, see
+    /// [SP1ProofWithPublicValues::decode].
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
-        bincode::deserialize_from(File::open(path).expect("failed to open file"))
-            .map_err(Into::into)
+        let limits = Self::default_limits();
+        let len = std::fs::metadata(&path)?.len();
+        if len > limits.max_bytes as u64 {
+            return Err(ProofDecodeError::TooLarge {
+                field: "proof bytes".to_string(),
+                len: len as usize,
+                limit: limits.max_bytes,
+            }
+            .into());
+        }
+        let bytes = std::fs::read(path)?;
+        Self::decode(&bytes, &limits).map_err(Into::into)
+    }
+
+    /// Loads a proof saved with [SP1ProofWithPublicValues::save_compressed], checking its checksum
+    /// before decompressing it and the decompressed proof against the default limits of its kind.
+    pub fn load_compressed(path: impl AsRef<Path>) -> Result<Self> {
+        store::read_compressed(path.as_ref()).map_err(Into::into)
+    }
+}
+
+impl SP1Proof {
+    /// The digest of the input buffers read by the program, committed to by the guest when built
+    /// with the `commit-input` feature of `sp1-zkvm`. Compare it with [SP1Stdin::digest].
+    pub fn input_digest(&self) -> [u8; 32] {
+        let last_proof = self.proof.last().expect("proof has no shards");
+        PublicValues::from_vec(last_proof.public_values.clone())
+            .input_digest_bytes()
+            .try_into()
+            .unwrap()
+    }
+
+    /// The digest of the config the program was run with. Compare it with
+    /// [SP1Stdin::config_digest].
+    pub fn config_digest(&self) -> [u8; 32] {
+        let last_proof = self.proof.last().expect("proof has no shards");
+        PublicValues::from_vec(last_proof.public_values.clone())
+            .config_digest_bytes()
+            .try_into()
+            .unwrap()
+    }
+}
+
+impl SP1CompressedProof {
+    /// The digest of the input buffers read by the program, see [SP1Proof::input_digest].
+    pub fn input_digest(&self) -> [u8; 32] {
+        SP1ReduceProof {
+            proof: self.proof.clone(),
+        }
+        .sp1_input_digest_bytes()
+    }
+
+    /// The digest of the config the program was run with, see [SP1Proof::config_digest].
+    pub fn config_digest(&self) -> [u8; 32] {
+        SP1ReduceProof {
+            proof: self.proof.clone(),
+        }
+        .sp1_config_digest_bytes()
     }
 }
 
@@ -483,6 +1053,65 @@ mod tests {
         client.verify_plonk(&proof, &vk).unwrap();
     }
 
+    #[test]
+    fn test_prove_with_profile() {
+        utils::setup_logger();
+        let client = ProverClient::local().with_profile(true);
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        let (_, report) = client.execute(elf, stdin.clone()).unwrap();
+        let proof = client.prove(&pk, stdin).unwrap();
+        client.verify(&proof, &vk).unwrap();
+
+        let path = proof.profile_path.unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.contains(&report.total_instruction_count().to_string()));
+        assert!(html.contains("CPU"));
+        assert!(html.contains("core"));
+        for extension in ["html", "json", "folded"] {
+            std::fs::remove_file(path.with_extension(extension)).unwrap();
+        }
+    }
+
+    #[cfg(feature = "memory-stats")]
+    #[test]
+    fn test_proving_report_peak_memory() {
+        use crate::MemoryBudgets;
+        use sp1_core::utils::SP1CoreProverError;
+
+        utils::setup_logger();
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+
+        let client = ProverClient::local();
+        let (pk, _) = client.setup(elf);
+        client.prove_compressed(&pk, stdin.clone()).unwrap();
+        let report = client.proving_report().unwrap();
+        for stage in ["core", "compress"] {
+            let peak = report.stage(stage).unwrap().peak_memory;
+            assert!(peak.unwrap() > 0, "no peak memory for stage {}", stage);
+        }
+
+        let client = ProverClient::local()
+            .with_shard_size(1 << 14)
+            .with_memory_budgets(MemoryBudgets {
+                core: Some(1 << 20),
+                ..Default::default()
+            });
+        let err = client.prove(&pk, stdin).unwrap_err();
+        match err.downcast_ref::<SP1CoreProverError>() {
+            Some(SP1CoreProverError::MemoryBudgetExceeded { stage, budget, .. }) => {
+                assert_eq!((stage.as_str(), *budget), ("core", 1 << 20));
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+    }
+
     #[test]
     fn test_e2e_prove_plonk_mock() {
         utils::setup_logger();
@@ -32,6 +32,8 @@ const TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 pub struct NetworkClient {
     pub rpc: TwirpClient,
+    /// The endpoint `rpc` sends requests to.
+    pub rpc_url: String,
     pub http: HttpClientWithMiddleware,
     pub auth: NetworkAuth,
 }
@@ -43,6 +45,11 @@ impl NetworkClient {
 
     // Create a new NetworkClient with the given private key for authentication.
     pub fn new(private_key: &str) -> Self {
+        Self::with_rpc_url(private_key, &Self::rpc_url())
+    }
+
+    /// Create a new NetworkClient with the given private key, sending requests to `rpc_url`.
+    pub fn with_rpc_url(private_key: &str, rpc_url: &str) -> Self {
         let auth = NetworkAuth::new(private_key);
 
         let twirp_http_client = HttpClient::builder()
@@ -51,9 +58,8 @@ impl NetworkClient {
             .build()
             .unwrap();
 
-        let rpc_url = Self::rpc_url();
         let rpc =
-            TwirpClient::new(Url::parse(&rpc_url).unwrap(), twirp_http_client, vec![]).unwrap();
+            TwirpClient::new(Url::parse(rpc_url).unwrap(), twirp_http_client, vec![]).unwrap();
 
         let http_client = HttpClient::builder()
             .pool_max_idle_per_host(0)
@@ -64,6 +70,7 @@ impl NetworkClient {
         Self {
             auth,
             rpc,
+            rpc_url: rpc_url.to_string(),
             http: http_client.into(),
         }
     }
@@ -1,14 +1,18 @@
-use std::{env, time::Duration};
+use std::{collections::HashMap, env, sync::Mutex, time::Duration};
 
 use crate::proto::network::ProofMode;
+use crate::retry::{run_stage_async, ProvingReport, RetryPolicy};
 use crate::{
     network::client::{NetworkClient, DEFAULT_PROVER_NETWORK_RPC},
     proto::network::ProofStatus,
-    Prover,
+    PlonkArtifacts, Prover,
 };
 use crate::{SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof, SP1ProvingKey, SP1VerifyingKey};
 use anyhow::Result;
 use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use sp1_core::runtime::UnconstrainedSyscalls;
+use sp1_core::utils::{MemoryBudgets, SP1CoreOpts};
 use sp1_prover::utils::block_on;
 use sp1_prover::{SP1Prover, SP1Stdin, SP1_CIRCUIT_VERSION};
 use tokio::time::sleep;
@@ -19,6 +23,11 @@ use crate::provers::{LocalProver, ProverType};
 pub struct NetworkProver {
     client: NetworkClient,
     local_prover: LocalProver,
+    retry_policy: RetryPolicy,
+    report: Mutex<ProvingReport>,
+    /// The proof IDs of the requests that have been created but not yet fulfilled, by idempotency
+    /// key.
+    pending_requests: Mutex<HashMap<[u8; 32], String>>,
 }
 
 impl NetworkProver {
@@ -31,16 +40,56 @@ impl NetworkProver {
 
     /// Creates a new [NetworkProver] with the given private key.
     pub fn new_from_key(private_key: &str) -> Self {
+        Self::new_from_key_and_rpc(private_key, &NetworkClient::rpc_url())
+    }
+
+    /// Creates a new [NetworkProver] with the given private key, sending requests to `rpc_url`.
+    pub fn new_from_key_and_rpc(private_key: &str, rpc_url: &str) -> Self {
         let version = SP1_CIRCUIT_VERSION;
         log::info!("Client circuit version: {}", version);
 
         let local_prover = LocalProver::new();
         Self {
-            client: NetworkClient::new(private_key),
+            client: NetworkClient::with_rpc_url(private_key, rpc_url),
             local_prover,
+            retry_policy: RetryPolicy::default(),
+            report: Mutex::new(ProvingReport::default()),
+            pending_requests: Mutex::new(HashMap::new()),
         }
     }
 
+    /// The idempotency key of a proof request: requests for the same program, input and mode
+    /// share a key.
+    pub fn idempotency_key(elf: &[u8], stdin: &SP1Stdin, mode: ProofMode) -> Result<[u8; 32]> {
+        let mut hasher = Sha256::new();
+        hasher.update((elf.len() as u64).to_le_bytes());
+        hasher.update(elf);
+        hasher.update(bincode::serialize(stdin)?);
+        hasher.update((mode as i32).to_le_bytes());
+        Ok(hasher.finalize().into())
+    }
+
+    /// Requests a proof like [NetworkProver::request_proof], unless a request with the same
+    /// idempotency key is still pending, in which case its proof ID is returned instead.
+    async fn request_proof_once(
+        &self,
+        key: [u8; 32],
+        elf: &[u8],
+        stdin: SP1Stdin,
+        mode: ProofMode,
+    ) -> Result<String> {
+        if let Some(proof_id) = self.pending_requests.lock().unwrap().get(&key) {
+            log::info!("Reusing pending request {}", proof_id);
+            return Ok(proof_id.clone());
+        }
+        let proof_id = self.request_proof(elf, stdin, mode).await?;
+        self.pending_requests
+            .lock()
+            .unwrap()
+            .insert(key, proof_id.clone());
+        Ok(proof_id)
+    }
+
     /// Requests a proof from the prover network, returning the proof ID.
     pub async fn request_proof(
         &self,
@@ -68,7 +117,7 @@ impl NetworkProver {
         let proof_id = client.create_proof(elf, &stdin, mode, version).await?;
         log::info!("Created {}", proof_id);
 
-        if NetworkClient::rpc_url() == DEFAULT_PROVER_NETWORK_RPC {
+        if client.rpc_url == DEFAULT_PROVER_NETWORK_RPC {
             log::info!(
                 "View in explorer: https://explorer.succinct.xyz/{}",
                 proof_id
@@ -107,9 +156,29 @@ impl NetworkProver {
     }
 
     /// Requests a proof from the prover network and waits for it to be generated.
+    ///
+    /// Both the request and the wait are retried according to the prover's [RetryPolicy]. A retried
+    /// request does not create a second proof request once the first one has been created: the
+    /// request is keyed by [NetworkProver::idempotency_key] until its proof has been received.
     pub async fn prove<P: ProofType>(&self, elf: &[u8], stdin: SP1Stdin) -> Result<P> {
-        let proof_id = self.request_proof(elf, stdin, P::PROOF_MODE).await?;
-        self.wait_proof(&proof_id).await
+        let key = Self::idempotency_key(elf, &stdin, P::PROOF_MODE)?;
+        let mut report = ProvingReport::default();
+        let result = async {
+            let proof_id = run_stage_async(&self.retry_policy, &mut report, "request", || {
+                self.request_proof_once(key, elf, stdin.clone(), P::PROOF_MODE)
+            })
+            .await?;
+            run_stage_async(&self.retry_policy, &mut report, "wait", || {
+                self.wait_proof(&proof_id)
+            })
+            .await
+        }
+        .await;
+        if result.is_ok() {
+            self.pending_requests.lock().unwrap().remove(&key);
+        }
+        *self.report.lock().unwrap() = report;
+        result
     }
 }
 
@@ -126,6 +195,51 @@ impl Prover for NetworkProver {
         self.local_prover.sp1_prover()
     }
 
+    fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+        self.local_prover.set_retry_policy(policy);
+    }
+
+    fn set_max_memory(&mut self, limit: u64) {
+        self.local_prover.set_max_memory(limit);
+    }
+
+    fn set_unconstrained_syscalls(&mut self, syscalls: UnconstrainedSyscalls) {
+        self.local_prover.set_unconstrained_syscalls(syscalls);
+    }
+
+    fn set_memory_budgets(&mut self, budgets: MemoryBudgets) {
+        self.local_prover.set_memory_budgets(budgets);
+    }
+
+    fn set_shard_size(&mut self, shard_size: usize) {
+        self.local_prover.set_shard_size(shard_size);
+    }
+
+    fn set_shard_batch_size(&mut self, shard_batch_size: usize) {
+        self.local_prover.set_shard_batch_size(shard_batch_size);
+    }
+
+    fn set_plonk_artifacts(&mut self, artifacts: PlonkArtifacts) {
+        self.local_prover.set_plonk_artifacts(artifacts);
+    }
+
+    fn plonk_artifacts(&self) -> PlonkArtifacts {
+        self.local_prover.plonk_artifacts()
+    }
+
+    fn core_opts(&self) -> SP1CoreOpts {
+        self.local_prover.core_opts()
+    }
+
+    fn unconstrained_syscalls(&self) -> UnconstrainedSyscalls {
+        self.local_prover.unconstrained_syscalls()
+    }
+
+    fn proving_report(&self) -> Option<ProvingReport> {
+        Some(self.report.lock().unwrap().clone())
+    }
+
     fn prove(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1Proof> {
         block_on(self.prove(&pk.elf, stdin))
     }
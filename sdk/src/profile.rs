@@ -0,0 +1,606 @@
+//! A single report of an execution, its traces and its proof, rendered as a static HTML page.
+//!
+//! [ProfileReport::collect] gathers the [ExecutionReport] of a program, the [TraceReport] of its
+//! shard proofs, the [ProvingReport] of the stages that proved it and its [MemoryProfile], any of
+//! which may be missing. [ProfileReport::to_html] renders them from the `profile.html` template as
+//! a self-contained page, with tables and inline SVG charts but no external assets, and
+//! [ProfileReport::to_json] serializes them for tools.
+//!
+//! A [crate::ProverClient] built with [crate::ProverClient::with_profile] collects the report of
+//! each local proof and records the path of its page in
+//! [crate::SP1ProofWithPublicValues::profile_path].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fmt::{Display, Write},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sp1_core::{
+    runtime::ExecutionReport,
+    stark::{ShardProof, StarkGenericConfig},
+};
+
+use crate::ProvingReport;
+
+/// The page the report is rendered in, whose `{{section}}` placeholders are filled by
+/// [ProfileReport::render_html].
+const TEMPLATE: &str = include_str!("profile.html");
+
+/// The directory the reports of [crate::ProverClient::with_profile] are written to, unless set by
+/// the `SP1_PROFILE_DIR` environment variable.
+pub const DEFAULT_PROFILE_DIR: &str = "sp1-profile";
+
+/// The width of the charts in pixels.
+const CHART_WIDTH: f64 = 1000.0;
+
+/// The width of the labels on the left of the bars of a chart.
+const LABEL_WIDTH: f64 = 260.0;
+
+/// The width of the values on the right of the bars of a chart.
+const VALUE_WIDTH: f64 = 120.0;
+
+/// The height of a bar of a chart and of a frame of the flamegraph.
+const ROW_HEIGHT: f64 = 18.0;
+
+/// The largest number of bars of a chart, the smallest values being left out.
+const MAX_BARS: usize = 40;
+
+/// The directory [crate::ProverClient::with_profile] writes the reports to.
+pub fn profile_dir() -> PathBuf {
+    env::var("SP1_PROFILE_DIR")
+        .unwrap_or_else(|_| DEFAULT_PROFILE_DIR.to_string())
+        .into()
+}
+
+/// The traces of a chip, summed over the shards it is in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChipTrace {
+    pub name: String,
+    /// The number of shards with a trace of the chip.
+    pub shards: u64,
+    /// The rows of the padded traces.
+    pub rows: u64,
+    /// The number of main columns.
+    pub width: u64,
+    /// The cells of the padded main traces.
+    pub area: u64,
+}
+
+/// The traces committed to by a proof, per chip.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceReport {
+    /// The chips, by decreasing area.
+    pub chips: Vec<ChipTrace>,
+}
+
+impl TraceReport {
+    /// The traces of `proofs`, read from the degree and the opened main columns of each chip.
+    pub fn from_shard_proofs<SC: StarkGenericConfig>(proofs: &[ShardProof<SC>]) -> Self {
+        let mut chips = BTreeMap::<String, ChipTrace>::new();
+        for proof in proofs {
+            for (name, index) in proof.chip_ordering.iter() {
+                let opened = &proof.opened_values.chips[*index];
+                let rows = 1u64 << opened.log_degree;
+                let width = opened.main.local.len() as u64;
+                let chip = chips.entry(name.clone()).or_insert_with(|| ChipTrace {
+                    name: name.clone(),
+                    shards: 0,
+                    rows: 0,
+                    width,
+                    area: 0,
+                });
+                chip.shards += 1;
+                chip.rows += rows;
+                chip.area += rows * width;
+            }
+        }
+        let mut chips = chips.into_values().collect::<Vec<_>>();
+        chips.sort_by(|a, b| b.area.cmp(&a.area).then_with(|| a.name.cmp(&b.name)));
+        Self { chips }
+    }
+
+    pub fn total_area(&self) -> u64 {
+        self.chips.iter().map(|chip| chip.area).sum()
+    }
+}
+
+/// The peak heap usage of the prover in each proving stage, in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryProfile {
+    pub stages: Vec<(String, u64)>,
+}
+
+impl MemoryProfile {
+    /// The peaks recorded in `report`, which are only measured with the `memory-stats` feature.
+    pub fn from_proving_report(report: &ProvingReport) -> Self {
+        let stages = report
+            .stages
+            .iter()
+            .filter_map(|stage| Some((stage.stage.clone(), stage.peak_memory?)))
+            .collect();
+        Self { stages }
+    }
+
+    pub fn peak(&self) -> Option<u64> {
+        self.stages.iter().map(|(_, peak)| *peak).max()
+    }
+}
+
+/// The time spent in a proving stage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub seconds: f64,
+    pub attempts: u32,
+    pub succeeded: bool,
+}
+
+/// The metrics of an execution and of its proof, see the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileReport {
+    pub cycles: u64,
+    pub syscalls: u64,
+    pub gas_used: u64,
+    /// The number of times each opcode was executed, by decreasing count.
+    pub opcode_counts: Vec<(String, u64)>,
+    /// The number of times each syscall was made, by decreasing count.
+    pub syscall_counts: Vec<(String, u64)>,
+    /// The cycles of each `cycle-tracker` span, by decreasing count.
+    pub span_cycles: Vec<(String, u64)>,
+    pub chips: Vec<ChipTrace>,
+    pub stages: Vec<StageTiming>,
+    /// The peak heap usage of each proving stage in bytes.
+    pub memory: Vec<(String, u64)>,
+    /// The folded call stacks sampled by the profiler with their number of samples, if the
+    /// execution was profiled.
+    pub flamegraph: Vec<(String, u64)>,
+}
+
+impl ProfileReport {
+    /// Gathers the reports of a run. The flamegraph is read from
+    /// [ExecutionReport::profile_path], and left out if the file can't be read.
+    pub fn collect(
+        execution_report: &ExecutionReport,
+        trace_report: Option<&TraceReport>,
+        proving_report: Option<&ProvingReport>,
+        memory_profile: Option<&MemoryProfile>,
+    ) -> Self {
+        let stages = proving_report
+            .map(|report| {
+                report
+                    .stages
+                    .iter()
+                    .map(|stage| StageTiming {
+                        stage: stage.stage.clone(),
+                        seconds: stage.elapsed.as_secs_f64(),
+                        attempts: stage.attempts,
+                        succeeded: stage.succeeded,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let flamegraph = execution_report
+            .profile_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|folded| parse_folded(&folded))
+            .unwrap_or_default();
+        Self {
+            cycles: execution_report.total_instruction_count(),
+            syscalls: execution_report.total_syscall_count(),
+            gas_used: execution_report.gas_used,
+            opcode_counts: sorted_counts(&execution_report.opcode_counts),
+            syscall_counts: sorted_counts(&execution_report.syscall_counts),
+            span_cycles: sorted_counts(&execution_report.span_cycles),
+            chips: trace_report
+                .map(|report| report.chips.clone())
+                .unwrap_or_default(),
+            stages,
+            memory: memory_profile
+                .map(|profile| profile.stages.clone())
+                .unwrap_or_default(),
+            flamegraph,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Writes the page of [Self::render_html] to `path`.
+    pub fn to_html(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.render_html())?;
+        Ok(())
+    }
+
+    /// Renders the report as a self-contained HTML page.
+    pub fn render_html(&self) -> String {
+        let sections = [
+            ("summary", self.summary_section()),
+            ("chips", self.chips_section()),
+            ("stages", self.stages_section()),
+            ("memory", self.memory_section()),
+            ("spans", counts_section(&self.span_cycles, "span", "cycles")),
+            (
+                "opcodes",
+                counts_section(&self.opcode_counts, "opcode", "count"),
+            ),
+            ("flamegraph", flamegraph(&self.flamegraph)),
+        ];
+        sections
+            .iter()
+            .fold(TEMPLATE.to_string(), |page, (name, section)| {
+                page.replace(&format!("{{{{{}}}}}", name), section)
+            })
+    }
+
+    fn summary_section(&self) -> String {
+        let mut rows = vec![
+            vec!["cycles".to_string(), self.cycles.to_string()],
+            vec!["syscalls".to_string(), self.syscalls.to_string()],
+            vec!["gas used".to_string(), self.gas_used.to_string()],
+        ];
+        if !self.chips.is_empty() {
+            let area = self.chips.iter().map(|chip| chip.area).sum::<u64>();
+            rows.push(vec!["trace area".to_string(), area.to_string()]);
+        }
+        if !self.stages.is_empty() {
+            let seconds = self.stages.iter().map(|stage| stage.seconds).sum::<f64>();
+            rows.push(vec!["proving time".to_string(), format_seconds(seconds)]);
+        }
+        if let Some(peak) = self.memory.iter().map(|(_, peak)| *peak).max() {
+            rows.push(vec!["peak memory".to_string(), format_bytes(peak)]);
+        }
+        table(&["metric", "value"], rows)
+    }
+
+    fn chips_section(&self) -> String {
+        if self.chips.is_empty() {
+            return not_recorded();
+        }
+        let bars = self
+            .chips
+            .iter()
+            .map(|chip| (chip.name.clone(), chip.area as f64, chip.area.to_string()))
+            .collect::<Vec<_>>();
+        let rows = self
+            .chips
+            .iter()
+            .map(|chip| {
+                vec![
+                    chip.name.clone(),
+                    chip.shards.to_string(),
+                    chip.rows.to_string(),
+                    chip.width.to_string(),
+                    chip.area.to_string(),
+                ]
+            })
+            .collect();
+        bar_chart(&bars) + &table(&["chip", "shards", "rows", "width", "area"], rows)
+    }
+
+    fn stages_section(&self) -> String {
+        if self.stages.is_empty() {
+            return not_recorded();
+        }
+        let bars = self
+            .stages
+            .iter()
+            .map(|stage| {
+                let seconds = format_seconds(stage.seconds);
+                (stage.stage.clone(), stage.seconds, seconds)
+            })
+            .collect::<Vec<_>>();
+        let rows = self
+            .stages
+            .iter()
+            .map(|stage| {
+                vec![
+                    stage.stage.clone(),
+                    format_seconds(stage.seconds),
+                    stage.attempts.to_string(),
+                    stage.succeeded.to_string(),
+                ]
+            })
+            .collect();
+        bar_chart(&bars) + &table(&["stage", "time", "attempts", "succeeded"], rows)
+    }
+
+    fn memory_section(&self) -> String {
+        if self.memory.is_empty() {
+            return not_recorded();
+        }
+        let bars = self
+            .memory
+            .iter()
+            .map(|(stage, peak)| (stage.clone(), *peak as f64, format_bytes(*peak)))
+            .collect::<Vec<_>>();
+        let rows = self
+            .memory
+            .iter()
+            .map(|(stage, peak)| vec![stage.clone(), peak.to_string()])
+            .collect();
+        bar_chart(&bars) + &table(&["stage", "peak bytes"], rows)
+    }
+}
+
+/// The entries of `counts` by decreasing count, then by name.
+fn sorted_counts<K: Display>(counts: &HashMap<K, u64>) -> Vec<(String, u64)> {
+    let mut counts = counts
+        .iter()
+        .map(|(key, count)| (key.to_string(), *count))
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Parses the folded call stacks written by [sp1_core::runtime::Profiler::write_folded],
+/// skipping malformed lines.
+fn parse_folded(folded: &str) -> Vec<(String, u64)> {
+    folded
+        .lines()
+        .filter_map(|line| {
+            let (stack, samples) = line.trim().rsplit_once(' ')?;
+            Some((stack.to_string(), samples.parse().ok()?))
+        })
+        .collect()
+}
+
+fn counts_section(counts: &[(String, u64)], label: &str, value: &str) -> String {
+    if counts.is_empty() {
+        return not_recorded();
+    }
+    let bars = counts
+        .iter()
+        .map(|(name, count)| (name.clone(), *count as f64, count.to_string()))
+        .collect::<Vec<_>>();
+    let rows = counts
+        .iter()
+        .map(|(name, count)| vec![name.clone(), count.to_string()])
+        .collect();
+    bar_chart(&bars) + &table(&[label, value], rows)
+}
+
+fn not_recorded() -> String {
+    r#"<p class="empty">Not recorded.</p>"#.to_string()
+}
+
+fn format_seconds(seconds: f64) -> String {
+    format!("{:.3} s", seconds)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1 << 20) as f64)
+}
+
+/// Escapes the characters of `text` that are special in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for header in headers {
+        write!(html, "<th>{}</th>", escape(header)).unwrap();
+    }
+    html.push_str("</tr>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            write!(html, "<td>{}</td>", escape(&cell)).unwrap();
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// A horizontal bar chart of `(label, value, formatted value)` bars, scaled to the largest value.
+fn bar_chart(bars: &[(String, f64, String)]) -> String {
+    let bars = &bars[..bars.len().min(MAX_BARS)];
+    let max = bars.iter().map(|(_, value, _)| *value).fold(0.0, f64::max);
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+        CHART_WIDTH,
+        bars.len() as f64 * ROW_HEIGHT
+    );
+    for (i, (label, value, formatted)) in bars.iter().enumerate() {
+        let y = i as f64 * ROW_HEIGHT;
+        let width = if max > 0.0 {
+            (CHART_WIDTH - LABEL_WIDTH - VALUE_WIDTH) * value / max
+        } else {
+            0.0
+        };
+        write!(
+            svg,
+            r##"<text x="0" y="{text_y}">{label}</text><rect x="{LABEL_WIDTH}" y="{y}" width="{width:.1}" height="{height}" fill="#4c78a8"/><text x="{value_x:.1}" y="{text_y}">{formatted}</text>"##,
+            text_y = y + ROW_HEIGHT - 5.0,
+            label = escape(label),
+            height = ROW_HEIGHT - 2.0,
+            value_x = LABEL_WIDTH + width + 4.0,
+            formatted = escape(formatted),
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// A frame of the flamegraph, with the samples of the stacks going through it.
+#[derive(Default)]
+struct Frame {
+    samples: u64,
+    callees: BTreeMap<String, Frame>,
+}
+
+/// The flamegraph of the folded call stacks `stacks`, drawn top-down from the outermost frames.
+fn flamegraph(stacks: &[(String, u64)]) -> String {
+    let mut root = Frame::default();
+    for (stack, samples) in stacks {
+        root.samples += samples;
+        let mut frame = &mut root;
+        for name in stack.split(';') {
+            frame = frame.callees.entry(name.to_string()).or_default();
+            frame.samples += samples;
+        }
+    }
+    if root.samples == 0 {
+        return not_recorded();
+    }
+
+    let mut rects = String::new();
+    let rows = draw_callees(&root, 0.0, 0, root.samples, &mut rects);
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">{}</svg>\n",
+        CHART_WIDTH,
+        rows as f64 * ROW_HEIGHT,
+        rects
+    )
+}
+
+/// Draws the callees of `frame` from `x` at `depth`, returning the number of rows of the frames
+/// drawn. Frames narrower than half a pixel are left out.
+fn draw_callees(frame: &Frame, mut x: f64, depth: usize, total: u64, svg: &mut String) -> usize {
+    const COLORS: [&str; 4] = ["#e45756", "#f58518", "#eeca3b", "#f2a65a"];
+    let mut rows = 0;
+    for (name, callee) in frame.callees.iter() {
+        let width = CHART_WIDTH * callee.samples as f64 / total as f64;
+        if width >= 0.5 {
+            let y = depth as f64 * ROW_HEIGHT;
+            let label = if width > 40.0 {
+                // Monospace characters are about 7 pixels wide.
+                name.chars().take((width / 7.0) as usize - 1).collect()
+            } else {
+                String::new()
+            };
+            write!(
+                svg,
+                r#"<g><title>{name} ({samples} samples)</title><rect x="{x:.1}" y="{y}" width="{width:.1}" height="{height}" fill="{color}"/><text x="{text_x:.1}" y="{text_y}">{label}</text></g>"#,
+                name = escape(name),
+                samples = callee.samples,
+                height = ROW_HEIGHT - 1.0,
+                color = COLORS[depth % COLORS.len()],
+                text_x = x + 2.0,
+                text_y = y + ROW_HEIGHT - 5.0,
+                label = escape(&label),
+            )
+            .unwrap();
+            rows = rows
+                .max(depth + 1)
+                .max(draw_callees(callee, x, depth + 1, total, svg));
+        }
+        x += width;
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sp1_core::runtime::{Opcode, SyscallCode};
+
+    use super::*;
+    use crate::StageReport;
+
+    fn report() -> ProfileReport {
+        let mut execution_report = ExecutionReport::default();
+        execution_report.opcode_counts.insert(Opcode::ADD, 123457);
+        execution_report.opcode_counts.insert(Opcode::LW, 2048);
+        execution_report
+            .syscall_counts
+            .insert(SyscallCode::SHA_COMPRESS, 17);
+        execution_report
+            .span_cycles
+            .insert("verify<signature>".to_string(), 98765);
+
+        let folded = tempfile::NamedTempFile::new().unwrap();
+        fs::write(folded.path(), "main;verify 30\nmain;hash 10\nmain 2\n").unwrap();
+        execution_report.profile_path = Some(folded.path().to_path_buf());
+
+        let trace_report = TraceReport {
+            chips: vec![ChipTrace {
+                name: "CPU".to_string(),
+                shards: 2,
+                rows: 1 << 17,
+                width: 100,
+                area: 100 << 17,
+            }],
+        };
+        let proving_report = ProvingReport {
+            stages: vec![StageReport {
+                stage: "core".to_string(),
+                attempts: 1,
+                timeouts: 0,
+                elapsed: Duration::from_millis(4250),
+                succeeded: true,
+                peak_memory: Some(3 << 30),
+            }],
+            nodes: vec![],
+        };
+        let memory_profile = MemoryProfile::from_proving_report(&proving_report);
+        ProfileReport::collect(
+            &execution_report,
+            Some(&trace_report),
+            Some(&proving_report),
+            Some(&memory_profile),
+        )
+    }
+
+    #[test]
+    fn test_collect() {
+        let report = report();
+        assert_eq!(report.cycles, 125505);
+        assert_eq!(report.opcode_counts[0], ("add".to_string(), 123457));
+        assert_eq!(report.memory, vec![("core".to_string(), 3 << 30)]);
+        assert_eq!(report.flamegraph.len(), 3);
+        assert_eq!(
+            ProfileReport::from_json(&report.to_json().unwrap()).unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = report().render_html();
+        assert!(!html.contains("{{"));
+        assert!(!html.contains("<link") && !html.contains("<script"));
+        for expected in [
+            "125505",
+            "123457",
+            "13107200",
+            "4.250 s",
+            "3072.0 MiB",
+            "98765",
+            "verify&lt;signature&gt;",
+            "main (42 samples)",
+            "verify (30 samples)",
+        ] {
+            assert!(html.contains(expected), "missing {}", expected);
+        }
+
+        // Sections without data say so instead of drawing an empty chart.
+        let empty = ProfileReport::collect(&ExecutionReport::default(), None, None, None);
+        assert_eq!(empty.render_html().matches("Not recorded.").count(), 6);
+    }
+}
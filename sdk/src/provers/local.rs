@@ -1,23 +1,215 @@
-use anyhow::Result;
-use sp1_prover::{SP1Prover, SP1Stdin};
+use std::{
+    env, fs,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use sp1_core::{
+    runtime::UnconstrainedSyscalls,
+    stark::ShardProof,
+    utils::{MemoryBudgets, SP1CoreOpts},
+};
+use sp1_prover::{
+    HashableKey, InnerSC, ReduceJobOpts, SP1CoreProof, SP1Prover, SP1ReduceProof, SP1Stdin,
+};
 
 use crate::{
-    Prover, SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof, SP1ProofWithPublicValues,
-    SP1ProvingKey, SP1VerifyingKey,
+    profile::{profile_dir, MemoryProfile, ProfileReport, TraceReport},
+    retry::{run_stage, ProvingReport, RetryPolicy},
+    PlonkArtifacts, Prover, SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof,
+    SP1ProofWithPublicValues, SP1ProvingKey, SP1VerifyingKey,
 };
 
 use super::ProverType;
 
+/// The number of cycles between two samples of the call stack taken for a profile.
+const PROFILE_INTERVAL: u64 = 100;
+
 /// An implementation of [crate::ProverClient] that can generate end-to-end proofs locally.
 pub struct LocalProver {
-    prover: SP1Prover,
+    prover: Arc<SP1Prover>,
+    retry_policy: RetryPolicy,
+    report: Mutex<ProvingReport>,
+    /// Whether the shard size is picked for each program, see [SP1Prover::auto_sharded_opts].
+    auto_shard_size: bool,
+    plonk_artifacts: PlonkArtifacts,
+    /// The directory of the PLONK artifacts, resolved by the first PLONK proof.
+    plonk_artifacts_dir: Arc<OnceLock<PathBuf>>,
+    /// Whether each stage is run twice, failing if the two proofs differ.
+    check_determinism: bool,
+    /// Whether a [ProfileReport] is written for each proof.
+    profile: bool,
 }
 
 impl LocalProver {
     /// Creates a new [LocalProver].
     pub fn new() -> Self {
         let prover = SP1Prover::new();
-        Self { prover }
+        Self {
+            prover: Arc::new(prover),
+            retry_policy: RetryPolicy::default(),
+            report: Mutex::new(ProvingReport::default()),
+            auto_shard_size: env::var("SHARD_SIZE").is_err(),
+            plonk_artifacts: PlonkArtifacts::from_env(),
+            plonk_artifacts_dir: Default::default(),
+            check_determinism: false,
+            profile: false,
+        }
+    }
+
+    /// Creates a new [LocalProver] running each proving stage under `policy`.
+    pub fn with_retry_policy(policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy: policy,
+            ..Self::new()
+        }
+    }
+
+    /// The options used to prove `pk` on `stdin`.
+    fn core_opts_for(&self, pk: &SP1ProvingKey, stdin: &SP1Stdin) -> Result<SP1CoreOpts> {
+        if self.auto_shard_size {
+            let program = self.prover.program(pk).as_ref().clone();
+            Ok(self.prover.auto_sharded_opts_for_program(program, stdin)?)
+        } else {
+            Ok(self.prover.core_opts)
+        }
+    }
+
+    /// Writes the [ProfileReport] of the last proof, of `elf` on `stdin` with the traces `trace`,
+    /// to the profile directory, returning the path of its page. The program is executed once
+    /// more to sample its call stacks.
+    fn write_profile(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        trace: &TraceReport,
+    ) -> Result<PathBuf> {
+        let dir = profile_dir();
+        fs::create_dir_all(&dir)?;
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let name = format!("{}-{}", hex::encode(&pk.vk.hash_bytes()[..4]), created_at);
+
+        let (_, mut execution_report, samples) = SP1Prover::execute_with_profiler(
+            &pk.elf,
+            stdin,
+            self.prover.core_opts,
+            PROFILE_INTERVAL,
+        )?;
+        let folded = dir.join(format!("{}.folded", name));
+        let mut file = BufWriter::new(File::create(&folded)?);
+        samples.write_folded(&pk.elf, &mut file)?;
+        file.flush()?;
+        execution_report.profile_path = Some(folded);
+
+        let proving_report = self.report.lock().unwrap().clone();
+        let report = ProfileReport::collect(
+            &execution_report,
+            Some(trace),
+            Some(&proving_report),
+            Some(&MemoryProfile::from_proving_report(&proving_report)),
+        );
+        fs::write(dir.join(format!("{}.json", name)), report.to_json()?)?;
+        let path = dir.join(format!("{}.html", name));
+        report.to_html(&path)?;
+        Ok(path)
+    }
+
+    /// Runs the stages of a proof in order, recording them in the proving report.
+    fn run_stages<T>(&self, stages: impl FnOnce(&mut Stages) -> Result<T>) -> Result<T> {
+        let mut stages = Stages {
+            prover: self.prover.clone(),
+            policy: self.retry_policy,
+            report: ProvingReport::default(),
+            check_determinism: self.check_determinism,
+        };
+        let result = stages(&mut stages);
+        *self.report.lock().unwrap() = stages.report;
+        result
+    }
+}
+
+/// The proving stages of a [LocalProver]. Each stage is idempotent and is retried according to
+/// the prover's [RetryPolicy].
+struct Stages {
+    prover: Arc<SP1Prover>,
+    policy: RetryPolicy,
+    report: ProvingReport,
+    check_determinism: bool,
+}
+
+impl Stages {
+    fn run<T, F>(&mut self, stage: &str, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(&SP1Prover) -> Result<T> + Send + Sync + 'static,
+    {
+        let prover = self.prover.clone();
+        run_stage(&self.policy, &mut self.report, stage, move |_| f(&prover))
+    }
+
+    /// Runs a stage like [Self::run]. When checking determinism, the stage is run a second time,
+    /// recorded with a `_check` suffix, and the part of its output picked by `proof` must
+    /// serialize to the same bytes both times.
+    fn run_reproducible<T, P, F>(&mut self, stage: &str, f: F, proof: fn(&T) -> &P) -> Result<T>
+    where
+        T: Send + 'static,
+        P: Serialize,
+        F: Fn(&SP1Prover) -> Result<T> + Clone + Send + Sync + 'static,
+    {
+        let output = self.run(stage, f.clone())?;
+        if self.check_determinism {
+            let again = self.run(&format!("{}_check", stage), f)?;
+            if bincode::serialize(proof(&output))? != bincode::serialize(proof(&again))? {
+                return Err(anyhow!(
+                    "stage {} is not deterministic: proving it twice made different proofs",
+                    stage
+                ));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Runs the core stage.
+    fn core(
+        &mut self,
+        pk: SP1ProvingKey,
+        stdin: SP1Stdin,
+        opts: SP1CoreOpts,
+    ) -> Result<SP1CoreProof> {
+        self.run_reproducible(
+            "core",
+            move |prover| Ok(prover.prove_core_with_opts(&pk, &stdin, opts)?),
+            |proof| proof,
+        )
+    }
+
+    /// Runs the compress stage, recording the time spent on each reduce node.
+    fn compress(
+        &mut self,
+        vk: SP1VerifyingKey,
+        proof: SP1CoreProof,
+        deferred_proofs: Vec<ShardProof<InnerSC>>,
+    ) -> Result<SP1ReduceProof<InnerSC>> {
+        let (reduce_proof, nodes) = self.run_reproducible(
+            "compress",
+            move |prover| {
+                let (reduce_proof, _, nodes) = prover.compress_with_report(
+                    &vk,
+                    proof.clone(),
+                    deferred_proofs.clone(),
+                    None,
+                )?;
+                Ok((reduce_proof, nodes))
+            },
+            |(reduce_proof, _)| reduce_proof,
+        )?;
+        self.report.nodes = nodes;
+        Ok(reduce_proof)
     }
 }
 
@@ -30,57 +222,206 @@ impl Prover for LocalProver {
         self.prover.setup(elf)
     }
 
+    fn prepare(&self, pk: &SP1ProvingKey) {
+        self.prover.program(pk);
+    }
+
     fn sp1_prover(&self) -> &SP1Prover {
         &self.prover
     }
 
+    fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    fn set_deterministic(&mut self, deterministic: bool) {
+        Arc::get_mut(&mut self.prover)
+            .expect("the prover is in use")
+            .core_opts
+            .deterministic = deterministic;
+    }
+
+    fn set_check_determinism(&mut self, check: bool) {
+        self.check_determinism = check;
+    }
+
+    fn set_max_memory(&mut self, limit: u64) {
+        Arc::get_mut(&mut self.prover)
+            .expect("the prover is in use")
+            .core_opts
+            .max_memory = limit;
+    }
+
+    fn set_unconstrained_syscalls(&mut self, syscalls: UnconstrainedSyscalls) {
+        Arc::get_mut(&mut self.prover)
+            .expect("the prover is in use")
+            .unconstrained_syscalls = syscalls;
+    }
+
+    fn set_memory_budgets(&mut self, budgets: MemoryBudgets) {
+        let prover = Arc::get_mut(&mut self.prover).expect("the prover is in use");
+        prover.core_opts.memory_budgets = budgets;
+        prover.recursion_opts.memory_budgets = budgets;
+    }
+
+    fn set_shard_size(&mut self, shard_size: usize) {
+        Arc::get_mut(&mut self.prover)
+            .expect("the prover is in use")
+            .core_opts
+            .shard_size = shard_size;
+        self.auto_shard_size = false;
+    }
+
+    fn set_shard_batch_size(&mut self, shard_batch_size: usize) {
+        Arc::get_mut(&mut self.prover)
+            .expect("the prover is in use")
+            .core_opts
+            .shard_batch_size = shard_batch_size;
+    }
+
+    fn set_reduce_jobs(&mut self, opts: ReduceJobOpts) {
+        Arc::get_mut(&mut self.prover)
+            .expect("the prover is in use")
+            .reduce_jobs = Some(opts);
+    }
+
+    fn set_profile(&mut self, profile: bool) {
+        self.profile = profile;
+    }
+
+    fn set_plonk_artifacts(&mut self, artifacts: PlonkArtifacts) {
+        self.plonk_artifacts = artifacts;
+        self.plonk_artifacts_dir = Default::default();
+    }
+
+    fn plonk_artifacts(&self) -> PlonkArtifacts {
+        self.plonk_artifacts.clone()
+    }
+
+    fn core_opts(&self) -> SP1CoreOpts {
+        self.prover.core_opts
+    }
+
+    fn unconstrained_syscalls(&self) -> UnconstrainedSyscalls {
+        self.prover.unconstrained_syscalls.clone()
+    }
+
+    fn proving_report(&self) -> Option<ProvingReport> {
+        Some(self.report.lock().unwrap().clone())
+    }
+
     fn prove(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1Proof> {
-        let proof = self.prover.prove_core(pk, &stdin)?;
+        let opts = self.core_opts_for(pk, &stdin)?;
+        let vkey_digest = Some(pk.vk.hash_bytes());
+        let (core_pk, core_stdin) = (pk.clone(), stdin.clone());
+        let proof = self.run_stages(|stages| stages.core(core_pk, core_stdin, opts))?;
+        let features = proof.proof.features();
+        let profile_path = if self.profile {
+            let trace = TraceReport::from_shard_proofs(&proof.proof.0);
+            Some(self.write_profile(pk, &stdin, &trace)?)
+        } else {
+            None
+        };
         Ok(SP1ProofWithPublicValues {
             proof: proof.proof.0,
             stdin: proof.stdin,
             public_values: proof.public_values,
+            insecure: proof.insecure,
             sp1_version: self.version().to_string(),
+            vkey_digest,
+            profile_path,
+            features,
         })
     }
 
     fn prove_compressed(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1CompressedProof> {
-        let proof = self.prover.prove_core(pk, &stdin)?;
-        let deferred_proofs = stdin.proofs.iter().map(|p| p.0.clone()).collect();
-        let public_values = proof.public_values.clone();
-        let reduce_proof = self.prover.compress(&pk.vk, proof, deferred_proofs)?;
+        let opts = self.core_opts_for(pk, &stdin)?;
+        let vkey_digest = Some(pk.vk.hash_bytes());
+        let (core_pk, core_stdin) = (pk.clone(), stdin.clone());
+        let deferred_proofs: Vec<_> = stdin.proofs.iter().map(|p| p.0.clone()).collect();
+        let (public_values, insecure, features, trace, reduce_proof) =
+            self.run_stages(|stages| {
+                let vk = core_pk.vk.clone();
+                let proof = stages.core(core_pk, core_stdin, opts)?;
+                let (public_values, insecure) = (proof.public_values.clone(), proof.insecure);
+                let features = proof.proof.features();
+                let trace = self
+                    .profile
+                    .then(|| TraceReport::from_shard_proofs(&proof.proof.0));
+                let reduce_proof = stages.compress(vk, proof, deferred_proofs)?;
+                Ok((public_values, insecure, features, trace, reduce_proof))
+            })?;
+        let profile_path = match trace {
+            Some(trace) => Some(self.write_profile(pk, &stdin, &trace)?),
+            None => None,
+        };
         Ok(SP1CompressedProof {
             proof: reduce_proof.proof,
             stdin,
             public_values,
+            insecure,
             sp1_version: self.version().to_string(),
+            vkey_digest,
+            profile_path,
+            features,
         })
     }
 
     fn prove_plonk(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1PlonkBn254Proof> {
-        let proof = self.prover.prove_core(pk, &stdin)?;
-        let deferred_proofs = stdin.proofs.iter().map(|p| p.0.clone()).collect();
-        let public_values = proof.public_values.clone();
-        let reduce_proof = self.prover.compress(&pk.vk, proof, deferred_proofs)?;
-        let compress_proof = self.prover.shrink(reduce_proof)?;
-        let outer_proof = self.prover.wrap_bn254(compress_proof)?;
-
-        let plonk_bn254_aritfacts = if sp1_prover::build::sp1_dev_mode() {
-            sp1_prover::build::try_build_plonk_bn254_artifacts_dev(
-                &self.prover.wrap_vk,
-                &outer_proof.proof,
-            )
-        } else {
-            sp1_prover::build::try_install_plonk_bn254_artifacts()
+        let opts = self.core_opts_for(pk, &stdin)?;
+        let vkey_digest = Some(pk.vk.hash_bytes());
+        let (core_pk, core_stdin) = (pk.clone(), stdin.clone());
+        let deferred_proofs: Vec<_> = stdin.proofs.iter().map(|p| p.0.clone()).collect();
+        let artifacts = self.plonk_artifacts.clone();
+        let artifacts_dir = self.plonk_artifacts_dir.clone();
+        let (public_values, insecure, features, trace, proof) = self.run_stages(|stages| {
+            let vk = core_pk.vk.clone();
+            let proof = stages.core(core_pk, core_stdin, opts)?;
+            let (public_values, insecure) = (proof.public_values.clone(), proof.insecure);
+            let features = proof.proof.features();
+            let trace = self
+                .profile
+                .then(|| TraceReport::from_shard_proofs(&proof.proof.0));
+            let reduce_proof = stages.compress(vk, proof, deferred_proofs)?;
+            let compress_proof = stages.run_reproducible(
+                "shrink",
+                move |prover| Ok(prover.shrink(reduce_proof.clone())?),
+                |proof| proof,
+            )?;
+            let outer_proof = stages.run_reproducible(
+                "wrap_bn254",
+                move |prover| Ok(prover.wrap_bn254(compress_proof.clone())?),
+                |proof| proof,
+            )?;
+            let proof = stages.run("wrap_plonk_bn254", move |prover| {
+                // The artifacts don't depend on the program, so they are only looked up once.
+                let plonk_bn254_aritfacts = artifacts_dir.get_or_init(|| match &artifacts {
+                    PlonkArtifacts::Installed => {
+                        sp1_prover::build::try_install_plonk_bn254_artifacts()
+                    }
+                    PlonkArtifacts::Dev => sp1_prover::build::try_build_plonk_bn254_artifacts_dev(
+                        &prover.wrap_vk,
+                        &outer_proof.proof,
+                    ),
+                    PlonkArtifacts::Dir(dir) => dir.clone(),
+                });
+                Ok(prover.wrap_plonk_bn254(outer_proof.clone(), plonk_bn254_aritfacts))
+            })?;
+            Ok((public_values, insecure, features, trace, proof))
+        })?;
+        let profile_path = match trace {
+            Some(trace) => Some(self.write_profile(pk, &stdin, &trace)?),
+            None => None,
         };
-        let proof = self
-            .prover
-            .wrap_plonk_bn254(outer_proof, &plonk_bn254_aritfacts);
         Ok(SP1ProofWithPublicValues {
             proof,
             stdin,
             public_values,
+            insecure,
             sp1_version: self.version().to_string(),
+            vkey_digest,
+            profile_path,
+            features,
         })
     }
 }
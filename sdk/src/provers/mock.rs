@@ -1,15 +1,20 @@
 #![allow(unused_variables)]
 use crate::{
+    error::{check_plonk_proof, check_proof},
     Prover, SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof, SP1ProofWithPublicValues,
     SP1ProvingKey, SP1VerificationError, SP1VerifyingKey,
 };
 use anyhow::Result;
+use num_bigint::BigUint;
 use p3_field::PrimeField;
+use sp1_core::{
+    features::INPUT_DIGEST_FEATURE, runtime::UnconstrainedSyscalls, utils::SP1CoreOpts,
+};
 use sp1_prover::{
     verify::verify_plonk_bn254_public_inputs, HashableKey, PlonkBn254Proof, SP1Prover, SP1Stdin,
 };
 
-use super::ProverType;
+use super::{check_features, ProverType};
 
 /// An implementation of [crate::ProverClient] that can generate mock proofs.
 pub struct MockProver {
@@ -37,13 +42,38 @@ impl Prover for MockProver {
         unimplemented!("MockProver does not support SP1Prover")
     }
 
+    fn set_max_memory(&mut self, limit: u64) {
+        self.prover.core_opts.max_memory = limit;
+    }
+
+    fn set_unconstrained_syscalls(&mut self, syscalls: UnconstrainedSyscalls) {
+        self.prover.unconstrained_syscalls = syscalls;
+    }
+
+    fn core_opts(&self) -> SP1CoreOpts {
+        self.prover.core_opts
+    }
+
+    fn unconstrained_syscalls(&self) -> UnconstrainedSyscalls {
+        self.prover.unconstrained_syscalls.clone()
+    }
+
     fn prove(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1Proof> {
-        let (public_values, _) = SP1Prover::execute(&pk.elf, &stdin)?;
+        let (public_values, report) = SP1Prover::execute_with_unconstrained_syscalls(
+            &pk.elf,
+            &stdin,
+            self.prover.core_opts,
+            &self.prover.unconstrained_syscalls,
+        )?;
         Ok(SP1ProofWithPublicValues {
             proof: vec![],
             stdin,
             public_values,
+            insecure: false,
             sp1_version: self.version().to_string(),
+            vkey_digest: Some(pk.vk.hash_bytes()),
+            profile_path: None,
+            features: report.features(),
         })
     }
 
@@ -56,12 +86,24 @@ impl Prover for MockProver {
     }
 
     fn prove_plonk(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1PlonkBn254Proof> {
-        let (public_values, _) = SP1Prover::execute(&pk.elf, &stdin)?;
+        let (public_values, report) = SP1Prover::execute_with_unconstrained_syscalls(
+            &pk.elf,
+            &stdin,
+            self.prover.core_opts,
+            &self.prover.unconstrained_syscalls,
+        )?;
+        // The input digest of a guest reading every buffer, masked like the public values hash.
+        let mut input_digest = [0; 32];
+        if report.features() & INPUT_DIGEST_FEATURE != 0 {
+            input_digest = stdin.digest();
+            input_digest[0] &= 0b00011111;
+        }
         Ok(SP1PlonkBn254Proof {
             proof: PlonkBn254Proof {
                 public_inputs: [
                     pk.vk.hash_bn254().as_canonical_biguint().to_string(),
                     public_values.hash().to_string(),
+                    BigUint::from_bytes_be(&input_digest).to_string(),
                 ],
                 encoded_proof: "".to_string(),
                 raw_proof: "".to_string(),
@@ -69,23 +111,27 @@ impl Prover for MockProver {
             },
             stdin,
             public_values,
+            insecure: false,
             sp1_version: self.version().to_string(),
+            vkey_digest: Some(pk.vk.hash_bytes()),
+            profile_path: None,
+            features: report.features(),
         })
     }
 
-    fn verify(
-        &self,
-        _proof: &SP1Proof,
-        _vkey: &SP1VerifyingKey,
-    ) -> Result<(), SP1VerificationError> {
+    fn verify(&self, proof: &SP1Proof, vkey: &SP1VerifyingKey) -> Result<(), SP1VerificationError> {
+        check_proof(proof, vkey)?;
+        check_features(proof.features, None)?;
         Ok(())
     }
 
     fn verify_compressed(
         &self,
-        _proof: &SP1CompressedProof,
-        _vkey: &SP1VerifyingKey,
+        proof: &SP1CompressedProof,
+        vkey: &SP1VerifyingKey,
     ) -> Result<(), SP1VerificationError> {
+        check_proof(proof, vkey)?;
+        check_features(proof.features, None)?;
         Ok(())
     }
 
@@ -94,6 +140,8 @@ impl Prover for MockProver {
         proof: &SP1PlonkBn254Proof,
         vkey: &SP1VerifyingKey,
     ) -> Result<(), SP1VerificationError> {
+        check_plonk_proof(proof, vkey)?;
+        check_features(proof.features, None)?;
         verify_plonk_bn254_public_inputs(vkey, &proof.public_values, &proof.proof.public_inputs)
             .map_err(SP1VerificationError::Plonk)?;
         Ok(())
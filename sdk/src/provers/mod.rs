@@ -1,39 +1,106 @@
 mod local;
 mod mock;
 
+use crate::error::{check_compressed_proof, check_plonk_proof, check_proof};
+use crate::retry::{ProvingReport, RetryPolicy};
+use crate::SP1Error;
 use crate::{SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof};
 use anyhow::Result;
 pub use local::LocalProver;
 pub use mock::MockProver;
+use serde::{Deserialize, Serialize};
+use sp1_core::features::unsupported_features;
+use sp1_core::runtime::UnconstrainedSyscalls;
 use sp1_core::stark::MachineVerificationError;
+use sp1_core::utils::{MemoryBudgets, SP1CoreOpts};
 use sp1_core::SP1_CIRCUIT_VERSION;
 use sp1_prover::CoreSC;
 use sp1_prover::InnerSC;
+use sp1_prover::ReduceJobOpts;
 use sp1_prover::SP1CoreProofData;
 use sp1_prover::SP1Prover;
 use sp1_prover::SP1ReduceProof;
 use sp1_prover::{SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+use std::path::PathBuf;
 use strum_macros::EnumString;
 use thiserror::Error;
 
 /// The type of prover.
-#[derive(Debug, PartialEq, EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Serialize, Deserialize)]
 pub enum ProverType {
     Local,
     Mock,
     Network,
 }
 
+/// Where the circuit artifacts used to wrap proofs in PLONK are taken from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlonkArtifacts {
+    /// The release artifacts, downloaded to `~/.sp1/circuits` on first use.
+    Installed,
+    /// Development artifacts built on first use, which must not be used in production.
+    Dev,
+    /// Artifacts already present in a directory.
+    Dir(PathBuf),
+}
+
+impl PlonkArtifacts {
+    /// [PlonkArtifacts::Dev] if the `SP1_DEV` environment variable is set, and
+    /// [PlonkArtifacts::Installed] otherwise.
+    pub fn from_env() -> Self {
+        if sp1_prover::build::sp1_dev_mode() {
+            Self::Dev
+        } else {
+            Self::Installed
+        }
+    }
+
+    /// The directory of the artifacts used to verify proofs, downloading them if needed.
+    pub fn verifier_dir(&self) -> PathBuf {
+        match self {
+            Self::Installed => sp1_prover::build::try_install_plonk_bn254_artifacts(),
+            Self::Dev => sp1_prover::build::plonk_bn254_artifacts_dev_dir(),
+            Self::Dir(dir) => dir.clone(),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum SP1VerificationError {
     #[error("Version mismatch")]
     VersionMismatch(String),
+    #[error("Verifying key mismatch")]
+    VkeyMismatch,
     #[error("Core machine verification error: {0}")]
     Core(MachineVerificationError<CoreSC>),
     #[error("Recursion verification error: {0}")]
     Recursion(MachineVerificationError<InnerSC>),
     #[error("Plonk verification error: {0}")]
     Plonk(anyhow::Error),
+    #[error("Unsupported features: {bits:#x}")]
+    UnsupportedFeatures { bits: u32 },
+    #[error("Features mismatch: the proof is for {claimed:#x}, but commits to {proven:#x}")]
+    FeaturesMismatch { claimed: u32, proven: u32 },
+    #[error(transparent)]
+    Program(#[from] SP1Error),
+}
+
+/// Checks that the `claimed` features of a proof are supported, and that they are the features
+/// `proven` by the proof if it commits to them.
+pub(crate) fn check_features(
+    claimed: u32,
+    proven: Option<u32>,
+) -> Result<(), SP1VerificationError> {
+    let bits = unsupported_features(claimed);
+    if bits != 0 {
+        return Err(SP1VerificationError::UnsupportedFeatures { bits });
+    }
+    match proven {
+        Some(proven) if proven != claimed => {
+            Err(SP1VerificationError::FeaturesMismatch { claimed, proven })
+        }
+        _ => Ok(()),
+    }
 }
 
 /// An implementation of [crate::ProverClient].
@@ -48,6 +115,73 @@ pub trait Prover: Send + Sync {
 
     fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey);
 
+    /// Does the program-dependent work of proving with `pk` ahead of its first proof, see
+    /// [crate::ProverSession]. Provers without such work ignore it.
+    fn prepare(&self, _pk: &SP1ProvingKey) {}
+
+    /// Sets the timeouts and retries applied to the proving stages. Provers without stages ignore
+    /// the policy.
+    fn set_retry_policy(&mut self, _policy: RetryPolicy) {}
+
+    /// Sets the guest memory limit in bytes, see [SP1CoreOpts::max_memory]. Provers that don't
+    /// execute programs locally ignore the limit.
+    fn set_max_memory(&mut self, _limit: u64) {}
+
+    /// Sets the handlers of the unconstrained syscalls made by the programs, see
+    /// [UnconstrainedSyscalls]. Provers that don't execute programs locally ignore them.
+    fn set_unconstrained_syscalls(&mut self, _syscalls: UnconstrainedSyscalls) {}
+
+    /// Sets whether the proofs are reproducible, see [SP1CoreOpts::deterministic]. Provers that
+    /// don't prove locally ignore it.
+    fn set_deterministic(&mut self, _deterministic: bool) {}
+
+    /// Sets whether each proving stage runs twice, failing if the two proofs differ. Provers
+    /// without stages ignore it.
+    fn set_check_determinism(&mut self, _check: bool) {}
+
+    /// Sets the peak heap usage allowed for each proving stage. Provers that don't prove locally
+    /// ignore the budgets.
+    fn set_memory_budgets(&mut self, _budgets: MemoryBudgets) {}
+
+    /// Sets the number of cycles of a core shard, instead of picking it for each program with
+    /// [sp1_core::runtime::ShardingConfig::auto]. Provers that don't prove locally ignore it.
+    fn set_shard_size(&mut self, _shard_size: usize) {}
+
+    /// Sets the number of core shards proven at once, see [SP1CoreOpts::shard_batch_size].
+    /// Provers that don't prove locally ignore it.
+    fn set_shard_batch_size(&mut self, _shard_batch_size: usize) {}
+
+    /// Sets the worker processes generating the witnesses of the reduce nodes. Provers that don't
+    /// prove locally ignore them.
+    fn set_reduce_jobs(&mut self, _opts: ReduceJobOpts) {}
+
+    /// Sets whether a [crate::ProfileReport] is written for each proof, see
+    /// [crate::ProverClient::with_profile]. Provers that don't prove locally ignore it.
+    fn set_profile(&mut self, _profile: bool) {}
+
+    /// Sets where the artifacts used to wrap and verify PLONK proofs are taken from.
+    fn set_plonk_artifacts(&mut self, _artifacts: PlonkArtifacts) {}
+
+    /// Where the artifacts used to wrap and verify PLONK proofs are taken from.
+    fn plonk_artifacts(&self) -> PlonkArtifacts {
+        PlonkArtifacts::from_env()
+    }
+
+    /// The options used to execute programs locally.
+    fn core_opts(&self) -> SP1CoreOpts {
+        SP1CoreOpts::default()
+    }
+
+    /// The handlers of the unconstrained syscalls used to execute programs locally.
+    fn unconstrained_syscalls(&self) -> UnconstrainedSyscalls {
+        UnconstrainedSyscalls::default()
+    }
+
+    /// The report of the stages run for the last proof, if the prover records one.
+    fn proving_report(&self) -> Option<ProvingReport> {
+        None
+    }
+
     /// Prove the execution of a RISCV ELF with the given inputs.
     fn prove(&self, pk: &SP1ProvingKey, stdin: SP1Stdin) -> Result<SP1Proof>;
 
@@ -64,8 +198,11 @@ pub trait Prover: Send + Sync {
                 proof.sp1_version.clone(),
             ));
         }
+        check_proof(proof, vkey)?;
+        let proof_data = SP1CoreProofData(proof.proof.clone());
+        check_features(proof.features, Some(proof_data.features()))?;
         self.sp1_prover()
-            .verify(&SP1CoreProofData(proof.proof.clone()), vkey)
+            .verify(&proof_data, vkey)
             .map_err(SP1VerificationError::Core)
     }
 
@@ -80,13 +217,13 @@ pub trait Prover: Send + Sync {
                 proof.sp1_version.clone(),
             ));
         }
+        check_compressed_proof(proof, vkey)?;
+        let reduce_proof = SP1ReduceProof {
+            proof: proof.proof.clone(),
+        };
+        check_features(proof.features, Some(reduce_proof.sp1_features()))?;
         self.sp1_prover()
-            .verify_compressed(
-                &SP1ReduceProof {
-                    proof: proof.proof.clone(),
-                },
-                vkey,
-            )
+            .verify_compressed(&reduce_proof, vkey)
             .map_err(SP1VerificationError::Recursion)
     }
 
@@ -102,13 +239,12 @@ pub trait Prover: Send + Sync {
                 proof.sp1_version.clone(),
             ));
         }
+        check_plonk_proof(proof, vkey)?;
+        // The public inputs of the PLONK proofs don't include the features.
+        check_features(proof.features, None)?;
         let sp1_prover = self.sp1_prover();
 
-        let plonk_bn254_aritfacts = if sp1_prover::build::sp1_dev_mode() {
-            sp1_prover::build::plonk_bn254_artifacts_dev_dir()
-        } else {
-            sp1_prover::build::try_install_plonk_bn254_artifacts()
-        };
+        let plonk_bn254_aritfacts = self.plonk_artifacts().verifier_dir();
         sp1_prover
             .verify_plonk_bn254(
                 &proof.proof,
@@ -121,3 +257,90 @@ pub trait Prover: Send + Sync {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sp1_core::features::INPUT_DIGEST_FEATURE;
+
+    use super::*;
+    use crate::{utils, ProverClient, SP1Receipt};
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+
+    /// A bit no version of SP1 supports yet.
+    const FUTURE_FEATURE: u32 = 1 << 31;
+
+    fn stdin() -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        stdin
+    }
+
+    fn assert_unsupported(result: Result<(), SP1VerificationError>) {
+        match result {
+            Err(SP1VerificationError::UnsupportedFeatures { bits }) => {
+                assert_eq!(bits, FUTURE_FEATURE)
+            }
+            result => panic!("expected unsupported features, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_check_features() {
+        check_features(0, Some(0)).unwrap();
+        check_features(INPUT_DIGEST_FEATURE, None).unwrap();
+        assert!(matches!(
+            check_features(INPUT_DIGEST_FEATURE, Some(0)),
+            Err(SP1VerificationError::FeaturesMismatch {
+                claimed: INPUT_DIGEST_FEATURE,
+                proven: 0
+            })
+        ));
+        assert_unsupported(check_features(
+            FUTURE_FEATURE | INPUT_DIGEST_FEATURE,
+            Some(FUTURE_FEATURE | INPUT_DIGEST_FEATURE),
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_features_mock() {
+        utils::setup_logger();
+        let client = ProverClient::mock();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+
+        let mut proof = client.prove(&pk, stdin()).unwrap();
+        client.verify(&proof, &vk).unwrap();
+        proof.features |= FUTURE_FEATURE;
+        assert_unsupported(client.verify(&proof, &vk));
+        let receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        assert_unsupported(client.verify_receipt(&receipt, &vk));
+
+        let mut proof = client.prove_plonk(&pk, stdin()).unwrap();
+        client.verify_plonk(&proof, &vk).unwrap();
+        proof.features |= FUTURE_FEATURE;
+        assert_unsupported(client.verify_plonk(&proof, &vk));
+    }
+
+    #[test]
+    fn test_unsupported_features_core() {
+        utils::setup_logger();
+        let client = ProverClient::local();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+
+        let mut proof = client.prove(&pk, stdin()).unwrap();
+        assert_eq!(
+            proof.features,
+            SP1CoreProofData(proof.proof.clone()).features()
+        );
+        client.verify(&proof, &vk).unwrap();
+
+        proof.features |= FUTURE_FEATURE;
+        assert_unsupported(client.verify(&proof, &vk));
+        proof.features = INPUT_DIGEST_FEATURE;
+        assert!(matches!(
+            client.verify(&proof, &vk),
+            Err(SP1VerificationError::FeaturesMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+//! Self-describing receipts for proofs generated with SP1.
+//!
+//! A [SP1Receipt] bundles a proof of any stage with everything a third party needs to check it:
+//! the digest of the verifying key it was generated for, the committed public values, the SP1
+//! version that produced it and how it was produced.
+
+use std::{
+    fs::File,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sp1_core::stark::ShardProof;
+use sp1_prover::{
+    CoreSC, HashableKey, InnerSC, PlonkBn254Proof, SP1PublicValues, SP1Stdin, SP1VerifyingKey,
+};
+
+use crate::{
+    provers::ProverType, MismatchContext, Prover, SP1Error, SP1ProofWithPublicValues,
+    SP1VerificationError,
+};
+
+/// The proof contained in a [SP1Receipt].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SP1ReceiptProof {
+    /// The shard proofs generated with [crate::ProverClient::prove].
+    Core(Vec<ShardProof<CoreSC>>),
+    /// The proof generated with [crate::ProverClient::prove_compressed].
+    Compressed(ShardProof<InnerSC>),
+    /// The proof generated with [crate::ProverClient::prove_plonk].
+    Plonk(PlonkBn254Proof),
+}
+
+impl From<Vec<ShardProof<CoreSC>>> for SP1ReceiptProof {
+    fn from(proof: Vec<ShardProof<CoreSC>>) -> Self {
+        Self::Core(proof)
+    }
+}
+
+impl From<ShardProof<InnerSC>> for SP1ReceiptProof {
+    fn from(proof: ShardProof<InnerSC>) -> Self {
+        Self::Compressed(proof)
+    }
+}
+
+impl From<PlonkBn254Proof> for SP1ReceiptProof {
+    fn from(proof: PlonkBn254Proof) -> Self {
+        Self::Plonk(proof)
+    }
+}
+
+/// A machine-checkable receipt for the execution of a program.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SP1Receipt {
+    pub proof: SP1ReceiptProof,
+    /// The digest of the verifying key the proof was generated for, see [HashableKey::hash_bytes].
+    pub vkey_digest: [u8; 32],
+    pub public_values: SP1PublicValues,
+    /// Whether the proof is insecure, see [SP1ProofWithPublicValues::insecure].
+    #[serde(default)]
+    pub insecure: bool,
+    pub sp1_version: String,
+    /// The features of the proof, see [SP1ProofWithPublicValues::features].
+    #[serde(default)]
+    pub features: u32,
+    pub prover_mode: ProverType,
+    /// The creation time of the receipt, in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+impl SP1Receipt {
+    /// Creates a receipt for a proof generated by a prover of type `prover_mode`.
+    pub fn new<P: Into<SP1ReceiptProof>>(
+        proof: SP1ProofWithPublicValues<P>,
+        vkey: &SP1VerifyingKey,
+        prover_mode: ProverType,
+    ) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Self {
+            proof: proof.proof.into(),
+            vkey_digest: vkey.hash_bytes(),
+            public_values: proof.public_values,
+            insecure: proof.insecure,
+            sp1_version: proof.sp1_version,
+            features: proof.features,
+            prover_mode,
+            created_at,
+        }
+    }
+
+    /// Verifies the receipt against `vkey` with the stage verifier matching its proof.
+    ///
+    /// Receipts produced by a different SP1 version or for a different verifying key are rejected
+    /// before the proof itself is checked.
+    pub fn verify(
+        &self,
+        prover: &dyn Prover,
+        vkey: &SP1VerifyingKey,
+    ) -> Result<(), SP1VerificationError> {
+        if self.sp1_version != prover.version() {
+            return Err(SP1VerificationError::VersionMismatch(
+                self.sp1_version.clone(),
+            ));
+        }
+        if self.vkey_digest != vkey.hash_bytes() {
+            return Err(SP1Error::ProgramMismatch {
+                expected: format!("0x{}", hex::encode(vkey.hash_bytes())),
+                found: format!("0x{}", hex::encode(self.vkey_digest)),
+                context: MismatchContext::ReceiptAndVerifyingKey,
+            }
+            .into());
+        }
+        match &self.proof {
+            SP1ReceiptProof::Core(proof) => prover.verify(&self.with_public_values(proof), vkey),
+            SP1ReceiptProof::Compressed(proof) => {
+                prover.verify_compressed(&self.with_public_values(proof), vkey)
+            }
+            SP1ReceiptProof::Plonk(proof) => {
+                prover.verify_plonk(&self.with_public_values(proof), vkey)
+            }
+        }
+    }
+
+    /// Rebuilds the proof in the form expected by the [Prover] verifiers. The stdin is not part
+    /// of the receipt and is not needed for verification.
+    fn with_public_values<P: Clone>(&self, proof: &P) -> SP1ProofWithPublicValues<P> {
+        SP1ProofWithPublicValues {
+            proof: proof.clone(),
+            stdin: SP1Stdin::new(),
+            public_values: self.public_values.clone(),
+            insecure: self.insecure,
+            sp1_version: self.sp1_version.clone(),
+            vkey_digest: Some(self.vkey_digest),
+            profile_path: None,
+            features: self.features,
+        }
+    }
+
+    /// Saves the receipt to a path using bincode.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        bincode::serialize_into(File::create(path)?, self).map_err(Into::into)
+    }
+
+    /// Loads a receipt saved with [SP1Receipt::save].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        bincode::deserialize_from(File::open(path)?).map_err(Into::into)
+    }
+
+    /// Saves the receipt to a path as JSON.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        serde_json::to_writer(File::create(path)?, self).map_err(Into::into)
+    }
+
+    /// Loads a receipt saved with [SP1Receipt::save_json].
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self> {
+        serde_json::from_reader(File::open(path)?).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, ProverClient};
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+
+    fn stdin() -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        stdin
+    }
+
+    /// Checks that the receipt survives both encodings and still verifies.
+    fn assert_round_trip(client: &ProverClient, receipt: &SP1Receipt, vk: &SP1VerifyingKey) {
+        let dir = tempfile::tempdir().unwrap();
+
+        let path = dir.path().join("receipt.bin");
+        receipt.save(&path).unwrap();
+        let loaded = SP1Receipt::load(&path).unwrap();
+        assert_eq!(loaded.vkey_digest, receipt.vkey_digest);
+        assert_eq!(loaded.created_at, receipt.created_at);
+        loaded.verify(client.prover.as_ref(), vk).unwrap();
+
+        let path = dir.path().join("receipt.json");
+        receipt.save_json(&path).unwrap();
+        let loaded = SP1Receipt::load_json(&path).unwrap();
+        assert_eq!(
+            loaded.public_values.as_slice(),
+            receipt.public_values.as_slice()
+        );
+        loaded.verify(client.prover.as_ref(), vk).unwrap();
+    }
+
+    #[test]
+    fn test_receipt_round_trip_core() {
+        utils::setup_logger();
+        let client = ProverClient::local();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let proof = client.prove(&pk, stdin()).unwrap();
+        let receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        assert!(matches!(receipt.proof, SP1ReceiptProof::Core(_)));
+        assert_round_trip(&client, &receipt, &vk);
+    }
+
+    #[test]
+    fn test_receipt_round_trip_compressed() {
+        utils::setup_logger();
+        let client = ProverClient::local();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let proof = client.prove_compressed(&pk, stdin()).unwrap();
+        let receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        assert!(matches!(receipt.proof, SP1ReceiptProof::Compressed(_)));
+        assert_round_trip(&client, &receipt, &vk);
+    }
+
+    #[test]
+    fn test_receipt_rejects_doctored_version() {
+        utils::setup_logger();
+        let client = ProverClient::mock();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let proof = client.prove(&pk, stdin()).unwrap();
+        let mut receipt = SP1Receipt::new(proof, &vk, client.prover.id());
+        receipt.verify(client.prover.as_ref(), &vk).unwrap();
+
+        receipt.sp1_version = "v0.0.0-doctored".to_string();
+        assert!(matches!(
+            receipt.verify(client.prover.as_ref(), &vk),
+            Err(SP1VerificationError::VersionMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,367 @@
+//! Timeouts and retries for proving stages.
+//!
+//! A [RetryPolicy] bounds how long each stage of a proof (core proving, compression, shrinking,
+//! wrapping, or a request to the prover network) may take and how often it is retried. What
+//! happened to each stage is recorded in a [ProvingReport], available from
+//! [crate::ProverClient::proving_report] after a proof completes or fails.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sp1_core::utils::memory;
+use sp1_prover::NodeReport;
+
+use crate::async_client;
+
+/// How long proving stages may run and how often they are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// The maximum time a single attempt of a stage may take. `None` disables the watchdog.
+    pub per_stage_timeout: Option<Duration>,
+    /// The number of times a failed or timed out stage is retried before giving up.
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled after every further retry.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            per_stage_timeout: None,
+            max_retries: 0,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retrying after `attempt` failed attempts.
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+    }
+}
+
+/// A flag set by the watchdog when an attempt runs out of time, or when a call of an
+/// [crate::AsyncProverClient] is dropped.
+///
+/// Cancellation is cooperative: a stage that polls the token can stop early, otherwise its worker
+/// thread keeps running in the background until it finishes and its result is discarded.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The attempts made for a single stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageReport {
+    pub stage: String,
+    pub attempts: u32,
+    /// The number of attempts stopped by the watchdog.
+    pub timeouts: u32,
+    /// The total time spent in the stage, including backoff.
+    pub elapsed: Duration,
+    pub succeeded: bool,
+    /// The peak heap usage of the prover during the stage in bytes, recorded with the
+    /// `memory-stats` feature for the stages proven locally.
+    #[serde(default)]
+    pub peak_memory: Option<u64>,
+}
+
+impl StageReport {
+    /// The number of attempts after the first one.
+    pub fn retries(&self) -> u32 {
+        self.attempts.saturating_sub(1)
+    }
+}
+
+/// The stages run for a proof, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvingReport {
+    pub stages: Vec<StageReport>,
+    /// The reduce nodes of the compress stage, in plan order.
+    #[serde(default)]
+    pub nodes: Vec<NodeReport>,
+}
+
+impl ProvingReport {
+    /// Returns the report of the stage with the given name.
+    pub fn stage(&self, stage: &str) -> Option<&StageReport> {
+        self.stages.iter().find(|report| report.stage == stage)
+    }
+
+    pub fn total_retries(&self) -> u32 {
+        self.stages.iter().map(StageReport::retries).sum()
+    }
+
+    pub fn total_timeouts(&self) -> u32 {
+        self.stages.iter().map(|report| report.timeouts).sum()
+    }
+}
+
+/// Runs an idempotent stage under `policy`, recording its attempts in `report`.
+///
+/// With a timeout, every attempt runs on a worker thread watched by the caller, which cancels the
+/// attempt's [CancellationToken] and moves on to the next attempt when it runs out of time. Without
+/// one, attempts run on the calling thread. The error of the last attempt is returned once all
+/// retries are exhausted.
+pub(crate) fn run_stage<T, F>(
+    policy: &RetryPolicy,
+    report: &mut ProvingReport,
+    stage: &str,
+    f: F,
+) -> Result<T>
+where
+    T: Send + 'static,
+    F: Fn(CancellationToken) -> Result<T> + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    let start = Instant::now();
+    memory::reset_peak();
+    let mut stage_report = StageReport {
+        stage: stage.to_string(),
+        attempts: 0,
+        timeouts: 0,
+        elapsed: Duration::ZERO,
+        succeeded: false,
+        peak_memory: None,
+    };
+    let result = loop {
+        if let Err(err) = async_client::check_cancelled(stage) {
+            break Err(err);
+        }
+        stage_report.attempts += 1;
+        async_client::stage_started(stage, stage_report.attempts);
+        let token = CancellationToken::default();
+        let result = match policy.per_stage_timeout {
+            None => f(token),
+            Some(timeout) => {
+                let (tx, rx) = mpsc::channel();
+                let f = f.clone();
+                let worker_token = token.clone();
+                thread::Builder::new()
+                    .name(format!("sp1-{}", stage))
+                    .spawn(move || {
+                        // The receiver is gone if the attempt already timed out.
+                        let _ = tx.send(f(worker_token));
+                    })?;
+                match rx.recv_timeout(timeout) {
+                    Ok(result) => result,
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        token.cancel();
+                        stage_report.timeouts += 1;
+                        Err(anyhow!("stage {} timed out after {:?}", stage, timeout))
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        Err(anyhow!("stage {} panicked", stage))
+                    }
+                }
+            }
+        };
+        match result {
+            Ok(value) => break Ok(value),
+            Err(err) if stage_report.attempts > policy.max_retries => break Err(err),
+            Err(err) => {
+                let backoff = policy.backoff_for(stage_report.attempts);
+                log::warn!(
+                    "stage {} failed (attempt {}), retrying in {:?}: {}",
+                    stage,
+                    stage_report.attempts,
+                    backoff,
+                    err
+                );
+                thread::sleep(backoff);
+            }
+        }
+    };
+    // A stage cancelled before its first attempt didn't run.
+    if stage_report.attempts == 0 {
+        return result;
+    }
+    stage_report.succeeded = result.is_ok();
+    stage_report.elapsed = start.elapsed();
+    stage_report.peak_memory = memory::peak();
+    report.stages.push(stage_report);
+    async_client::stage_finished(stage, stage_report.succeeded, stage_report.elapsed);
+    result
+}
+
+/// Runs an asynchronous stage under `policy`, recording its attempts in `report`.
+///
+/// A timed out attempt is cancelled by dropping its future, so `f` must be safe to call again:
+/// external requests made by it should be keyed so that a retry does not submit them twice.
+#[cfg(feature = "network")]
+pub(crate) async fn run_stage_async<T, F, Fut>(
+    policy: &RetryPolicy,
+    report: &mut ProvingReport,
+    stage: &str,
+    mut f: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let start = Instant::now();
+    let mut stage_report = StageReport {
+        stage: stage.to_string(),
+        attempts: 0,
+        timeouts: 0,
+        elapsed: Duration::ZERO,
+        succeeded: false,
+        peak_memory: None,
+    };
+    let result = loop {
+        if let Err(err) = async_client::check_cancelled(stage) {
+            break Err(err);
+        }
+        stage_report.attempts += 1;
+        async_client::stage_started(stage, stage_report.attempts);
+        let result = match policy.per_stage_timeout {
+            None => f().await,
+            Some(timeout) => match tokio::time::timeout(timeout, f()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    stage_report.timeouts += 1;
+                    Err(anyhow!("stage {} timed out after {:?}", stage, timeout))
+                }
+            },
+        };
+        match result {
+            Ok(value) => break Ok(value),
+            Err(err) if stage_report.attempts > policy.max_retries => break Err(err),
+            Err(err) => {
+                let backoff = policy.backoff_for(stage_report.attempts);
+                log::warn!(
+                    "stage {} failed (attempt {}), retrying in {:?}: {}",
+                    stage,
+                    stage_report.attempts,
+                    backoff,
+                    err
+                );
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    };
+    // A stage cancelled before its first attempt didn't run.
+    if stage_report.attempts == 0 {
+        return result;
+    }
+    stage_report.succeeded = result.is_ok();
+    stage_report.elapsed = start.elapsed();
+    report.stages.push(stage_report);
+    async_client::stage_finished(stage, stage_report.succeeded, stage_report.elapsed);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            per_stage_timeout: Some(Duration::from_millis(200)),
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    /// A stage that stalls on its first attempt until the watchdog cancels it.
+    fn stalling_stage(attempts: Arc<AtomicU32>) -> impl Fn(CancellationToken) -> Result<u32> {
+        move |token| {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                while !token.is_cancelled() {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                return Err(anyhow!("cancelled"));
+            }
+            Ok(42)
+        }
+    }
+
+    #[test]
+    fn test_stalled_stage_is_retried() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let mut report = ProvingReport::default();
+        let result = run_stage(
+            &policy(),
+            &mut report,
+            "shrink",
+            stalling_stage(attempts.clone()),
+        );
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        let stage = report.stage("shrink").unwrap();
+        assert_eq!(stage.attempts, 2);
+        assert_eq!(stage.timeouts, 1);
+        assert!(stage.succeeded);
+        assert_eq!(report.total_retries(), 1);
+    }
+
+    #[test]
+    fn test_permanent_failure_returns_last_error() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let mut report = ProvingReport::default();
+        let result: Result<()> = run_stage(&policy(), &mut report, "wrap", move |_| {
+            Err(anyhow!(
+                "attempt {} failed",
+                counter.fetch_add(1, Ordering::SeqCst)
+            ))
+        });
+
+        assert_eq!(result.unwrap_err().to_string(), "attempt 2 failed");
+        let stage = report.stage("wrap").unwrap();
+        assert_eq!(stage.attempts, 3);
+        assert_eq!(stage.timeouts, 0);
+        assert!(!stage.succeeded);
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_async_stage_times_out() {
+        let attempts = AtomicU32::new(0);
+        let mut report = ProvingReport::default();
+        let result =
+            sp1_prover::utils::block_on(run_stage_async(&policy(), &mut report, "request", || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 3 {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                    }
+                    Ok(attempt)
+                }
+            }));
+
+        assert!(result.unwrap_err().to_string().contains("timed out"));
+        assert_eq!(report.total_timeouts(), 3);
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy {
+            backoff: Duration::from_millis(10),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(10));
+        assert_eq!(policy.backoff_for(3), Duration::from_millis(40));
+    }
+}
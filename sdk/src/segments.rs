@@ -0,0 +1,242 @@
+//! Proving a long computation as a chain of segments proven independently.
+//!
+//! A segment program resumes the state of the computation with `sp1_zkvm::io::resume_state`,
+//! advances it, and suspends it with `sp1_zkvm::io::suspend_state`, which commits the digests of
+//! both states as the last public values of the segment, see [SegmentDigests]. A [SegmentProver]
+//! runs the segments one after the other, feeding the state suspended by each one to the next, and
+//! proves each of them. The chain is then checked natively with [SegmentProver::verify], or
+//! aggregated into one proof with [SegmentProver::aggregate], and in both cases only the digests
+//! of the first state and of the last one are exposed.
+//!
+//! ```no_run
+//! use sp1_sdk::{ProverClient, SP1Stdin, SegmentProver};
+//!
+//! let elf = std::fs::read("tests/segment-counter/elf/riscv32im-succinct-zkvm-elf").unwrap();
+//! let client = ProverClient::new();
+//! let (pk, vk) = client.setup(&elf);
+//! let inputs = (0..3)
+//!     .map(|_| {
+//!         let mut stdin = SP1Stdin::new();
+//!         stdin.write(&10u64);
+//!         stdin
+//!     })
+//!     .collect();
+//! let prover = SegmentProver::new(&client);
+//! let chain = prover.prove(&pk, &0u64, inputs).unwrap();
+//! assert_eq!(prover.verify(&chain, &vk).unwrap(), chain.digests());
+//! assert_eq!(chain.decode_final_state::<u64>().unwrap(), 30);
+//! ```
+
+use serde::{de::DeserializeOwned, Serialize};
+use sp1_core::{
+    io::{segment_state_digest, verify_segment_chain, SegmentChainError, SegmentDigests},
+    runtime::ExecutionReport,
+};
+use thiserror::Error;
+
+use crate::{
+    execution_error, HashableKey, ProverClient, SP1CompressedProof, SP1Prover, SP1ProvingKey,
+    SP1Stdin, SP1VerificationError, SP1VerifyingKey,
+};
+
+/// An error proving or verifying a chain of segments.
+#[derive(Error, Debug)]
+pub enum SegmentError {
+    #[error("segment {0} halted without suspending its state")]
+    NotSuspended(usize),
+    #[error("failed to prove segment {index}: {error:#}")]
+    Segment { index: usize, error: anyhow::Error },
+    #[error("segment {index} failed to verify: {error}")]
+    Verification {
+        index: usize,
+        error: SP1VerificationError,
+    },
+    #[error(transparent)]
+    Chain(#[from] SegmentChainError),
+    #[error("failed to aggregate the segments: {0:#}")]
+    Aggregation(anyhow::Error),
+}
+
+/// The result of [SegmentProver::prove].
+#[derive(Debug, Clone)]
+pub struct SegmentChain {
+    /// The compressed proofs of the segments, in order.
+    pub proofs: Vec<SP1CompressedProof>,
+    /// The execution reports of the segments, in order.
+    pub reports: Vec<ExecutionReport>,
+    /// The state the first segment resumed from, serialized.
+    pub initial_state: Vec<u8>,
+    /// The state the last segment suspended, serialized.
+    pub final_state: Vec<u8>,
+}
+
+impl SegmentChain {
+    /// Deserializes the state the last segment suspended.
+    pub fn decode_final_state<S: DeserializeOwned>(&self) -> bincode::Result<S> {
+        bincode::deserialize(&self.final_state)
+    }
+
+    /// The digests of the state the first segment resumed from and of the state the last one
+    /// suspended, which are the digests exposed by the verification of the chain.
+    pub fn digests(&self) -> SegmentDigests {
+        SegmentDigests {
+            input: segment_state_digest(&self.initial_state),
+            output: segment_state_digest(&self.final_state),
+        }
+    }
+}
+
+/// Proves the segments of a computation, see the module documentation.
+pub struct SegmentProver<'a> {
+    client: &'a ProverClient,
+}
+
+impl<'a> SegmentProver<'a> {
+    pub fn new(client: &'a ProverClient) -> Self {
+        Self { client }
+    }
+
+    /// Runs and proves a segment of the program of `pk` for each input of `inputs`, in order. The
+    /// first segment resumes from `initial_state`, and each next one from the state the previous
+    /// one suspended.
+    pub fn prove<S: Serialize>(
+        &self,
+        pk: &SP1ProvingKey,
+        initial_state: &S,
+        inputs: Vec<SP1Stdin>,
+    ) -> Result<SegmentChain, SegmentError> {
+        assert!(!inputs.is_empty(), "cannot prove an empty segment chain");
+        let initial_state = bincode::serialize(initial_state).expect("serialization failed");
+        let mut state = initial_state.clone();
+        let mut proofs = Vec::with_capacity(inputs.len());
+        let mut reports = Vec::with_capacity(inputs.len());
+        for (index, input) in inputs.iter().enumerate() {
+            let stdin = Self::segment_stdin(&state, input);
+            let (_, suspended, report) =
+                SP1Prover::execute_segment(&pk.elf, &stdin, self.client.prover.core_opts())
+                    .map_err(|err| SegmentError::Segment {
+                        index,
+                        error: execution_error(err, &stdin),
+                    })?;
+            let suspended = suspended.ok_or(SegmentError::NotSuspended(index))?;
+            tracing::info!(
+                "proving segment {} of {} ({} cycles)",
+                index + 1,
+                inputs.len(),
+                report.total_instruction_count()
+            );
+            let proof = self
+                .client
+                .prove_compressed(pk, stdin)
+                .map_err(|error| SegmentError::Segment { index, error })?;
+            proofs.push(proof);
+            reports.push(report);
+            state = suspended;
+        }
+        Ok(SegmentChain {
+            proofs,
+            reports,
+            initial_state,
+            final_state: state,
+        })
+    }
+
+    /// The input of a segment resuming from the serialized `state`: the state as the first buffer,
+    /// read by `sp1_zkvm::io::resume_state`, then the buffers, proofs, config and entropy of
+    /// `input`.
+    pub fn segment_stdin(state: &[u8], input: &SP1Stdin) -> SP1Stdin {
+        let mut stdin = input.clone();
+        stdin.buffer.insert(0, state.to_vec());
+        stdin
+    }
+
+    /// Verifies the proof of each segment of `chain` against `vk`, then checks that the segments
+    /// link, and returns the digests of the chain.
+    pub fn verify(
+        &self,
+        chain: &SegmentChain,
+        vk: &SP1VerifyingKey,
+    ) -> Result<SegmentDigests, SegmentError> {
+        for (index, proof) in chain.proofs.iter().enumerate() {
+            self.client
+                .verify_compressed(proof, vk)
+                .map_err(|error| SegmentError::Verification { index, error })?;
+        }
+        let public_values = chain
+            .proofs
+            .iter()
+            .map(|proof| proof.public_values.clone())
+            .collect::<Vec<_>>();
+        Ok(verify_segment_chain(&public_values)?)
+    }
+
+    /// Proves the program `aggregation_elf` verifying the proofs of `chain` in the recursion, with
+    /// `sp1_zkvm::precompiles::verify::verify_segment_chain`. Its public values are the
+    /// [SegmentDigests] of the chain, so the aggregate is itself a segment of the computation.
+    pub fn aggregate(
+        &self,
+        chain: &SegmentChain,
+        vk: &SP1VerifyingKey,
+        aggregation_elf: &[u8],
+    ) -> Result<SP1CompressedProof, SegmentError> {
+        let (aggregation_pk, _) = self.client.setup(aggregation_elf);
+        let stdin = Self::aggregation_stdin(vk, &chain.proofs);
+        self.client
+            .prove_compressed(&aggregation_pk, stdin)
+            .map_err(SegmentError::Aggregation)
+    }
+
+    /// The input of the aggregation program for the proofs of the segments of the program of `vk`:
+    /// the verifying key digest as a `[u32; 8]`, then the public values of the segments as a
+    /// `Vec<Vec<u8>>`, and the proofs themselves.
+    pub fn aggregation_stdin(vk: &SP1VerifyingKey, proofs: &[SP1CompressedProof]) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write::<[u32; 8]>(&vk.hash_u32());
+        let public_values = proofs
+            .iter()
+            .map(|proof| proof.public_values.to_vec())
+            .collect::<Vec<_>>();
+        stdin.write::<Vec<Vec<u8>>>(&public_values);
+        for proof in proofs {
+            stdin.write_proof(proof.proof.clone(), vk.vk.clone());
+        }
+        stdin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_stdin() {
+        let mut input = SP1Stdin::new();
+        input.write(&10u64);
+        input.write_config("mode", b"fast");
+        let state = bincode::serialize(&20u64).unwrap();
+
+        let mut stdin = SegmentProver::segment_stdin(&state, &input);
+        assert_eq!(stdin.buffer.len(), 2);
+        assert_eq!(stdin.read::<u64>(), 20);
+        assert_eq!(stdin.read::<u64>(), 10);
+        assert_eq!(stdin.config, input.config);
+    }
+
+    #[test]
+    fn test_segment_chain_digests() {
+        let chain = SegmentChain {
+            proofs: Vec::new(),
+            reports: Vec::new(),
+            initial_state: bincode::serialize(&0u64).unwrap(),
+            final_state: bincode::serialize(&30u64).unwrap(),
+        };
+        assert_eq!(chain.decode_final_state::<u64>().unwrap(), 30);
+        let digests = chain.digests();
+        assert_eq!(digests.input, segment_state_digest(&chain.initial_state));
+        assert_eq!(
+            SegmentDigests::decode(&digests.encode()),
+            Some(digests),
+            "the digests are committed with the same encoding"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Proving the same program on many inputs.
+//!
+//! Setting up a program computes its proving and verifying keys, which only depend on its ELF. A
+//! [KeyCache] keeps the keys of the programs set up by a [ProverClient], by SHA-256 digest of their
+//! ELF, and optionally persists them to a directory, so that a program is set up once per client,
+//! or once per cache directory.
+//!
+//! A [ProverSession], obtained with [ProverClient::prover_session], proves a single program with
+//! the keys of the cache. The program is set up and decoded by the first proof of the session; the
+//! following proofs only execute the program on their input and prove its execution.
+//!
+//! ```no_run
+//! use sp1_sdk::{ProverClient, SP1Stdin};
+//!
+//! let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+//! let client = ProverClient::new().with_key_cache("keys");
+//! let session = client.prover_session(elf);
+//! for n in [10usize, 20, 30] {
+//!     let mut stdin = SP1Stdin::new();
+//!     stdin.write(&n);
+//!     let proof = session.prove(stdin).unwrap();
+//!     session.verify(&proof).unwrap();
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use sp1_core::SP1_CIRCUIT_VERSION;
+use sp1_prover::{SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+
+use crate::{
+    Prover, ProverClient, SP1CompressedProof, SP1PlonkBn254Proof, SP1Proof, SP1VerificationError,
+};
+
+/// The proving and verifying keys of a program.
+type Keys = (Arc<SP1ProvingKey>, Arc<SP1VerifyingKey>);
+
+/// The keys of the programs set up by a client. Clones share the same cache.
+#[derive(Clone, Default)]
+pub struct KeyCache {
+    /// The directory the keys are persisted to, if any.
+    dir: Option<PathBuf>,
+    keys: Arc<RwLock<HashMap<[u8; 32], Keys>>>,
+}
+
+impl KeyCache {
+    /// A cache keeping the keys in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cache also persisting the keys to `dir`, where they are looked up before setting up a
+    /// program. The directory is created when the first keys are written.
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            keys: Default::default(),
+        }
+    }
+
+    /// The directory the keys are persisted to, if any.
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// The keys of the program `elf`, set up with `prover` unless they are in the cache.
+    pub fn setup(&self, elf: &[u8], prover: &dyn Prover) -> Keys {
+        let elf_digest: [u8; 32] = Sha256::digest(elf).into();
+        if let Some(keys) = self.keys.read().unwrap().get(&elf_digest) {
+            return keys.clone();
+        }
+
+        let keys = match self.load(&elf_digest, elf) {
+            Some(keys) => keys,
+            None => {
+                // The setup runs outside of the lock, so that it doesn't hold up the other
+                // programs.
+                let (pk, vk) = prover.setup(elf);
+                if let Err(err) = self.store(&elf_digest, &pk) {
+                    tracing::warn!("failed to persist the keys of the program: {}", err);
+                }
+                (Arc::new(pk), Arc::new(vk))
+            }
+        };
+        self.keys
+            .write()
+            .unwrap()
+            .entry(elf_digest)
+            .or_insert(keys)
+            .clone()
+    }
+
+    /// The path of the persisted keys of the program with the ELF digest `elf_digest`. The keys
+    /// are only reused by the same circuit version.
+    fn path(&self, elf_digest: &[u8; 32]) -> Option<PathBuf> {
+        let name = format!("{}-{}.bin", hex::encode(elf_digest), SP1_CIRCUIT_VERSION);
+        self.dir.as_ref().map(|dir| dir.join(name))
+    }
+
+    /// The persisted keys of `elf`, if they can be read.
+    fn load(&self, elf_digest: &[u8; 32], elf: &[u8]) -> Option<Keys> {
+        let path = self.path(elf_digest)?;
+        let bytes = fs::read(&path).ok()?;
+        match bincode::deserialize::<SP1ProvingKey>(&bytes) {
+            Ok(pk) if pk.elf == elf => {
+                let vk = pk.vk.clone();
+                Some((Arc::new(pk), Arc::new(vk)))
+            }
+            Ok(_) => {
+                tracing::warn!("ignoring the keys of another program in {}", path.display());
+                None
+            }
+            Err(err) => {
+                tracing::warn!("ignoring the unreadable keys {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    /// Persists `pk`, which also holds the verifying key, if the cache has a directory.
+    fn store(&self, elf_digest: &[u8; 32], pk: &SP1ProvingKey) -> io::Result<()> {
+        let Some(path) = self.path(elf_digest) else {
+            return Ok(());
+        };
+        fs::create_dir_all(path.parent().unwrap())?;
+        let bytes = bincode::serialize(pk).map_err(io::Error::other)?;
+        // Written next to its final path and renamed, so that a reader never sees partial keys.
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &path)
+    }
+}
+
+/// A [ProverClient] proving a single program on many inputs, see [crate::session].
+pub struct ProverSession<'a> {
+    client: &'a ProverClient,
+    elf: Vec<u8>,
+    keys: OnceLock<Keys>,
+}
+
+impl<'a> ProverSession<'a> {
+    pub(crate) fn new(client: &'a ProverClient, elf: &[u8]) -> Self {
+        Self {
+            client,
+            elf: elf.to_vec(),
+            keys: OnceLock::new(),
+        }
+    }
+
+    /// The proving key of the program, set up on first use.
+    pub fn pk(&self) -> &SP1ProvingKey {
+        &self.keys().0
+    }
+
+    /// The verifying key of the program, set up on first use.
+    pub fn vk(&self) -> &SP1VerifyingKey {
+        &self.keys().1
+    }
+
+    /// Proves the program on `stdin`, see [ProverClient::prove].
+    pub fn prove(&self, stdin: SP1Stdin) -> Result<SP1Proof> {
+        self.client.prove(self.pk(), stdin)
+    }
+
+    /// Proves the program on `stdin` in the compressed mode, see [ProverClient::prove_compressed].
+    pub fn prove_compressed(&self, stdin: SP1Stdin) -> Result<SP1CompressedProof> {
+        self.client.prove_compressed(self.pk(), stdin)
+    }
+
+    /// Proves the program on `stdin` in the plonk bn254 mode, see [ProverClient::prove_plonk].
+    pub fn prove_plonk(&self, stdin: SP1Stdin) -> Result<SP1PlonkBn254Proof> {
+        self.client.prove_plonk(self.pk(), stdin)
+    }
+
+    /// Verifies a proof of the program, see [ProverClient::verify].
+    pub fn verify(&self, proof: &SP1Proof) -> Result<(), SP1VerificationError> {
+        self.client.verify(proof, self.vk())
+    }
+
+    /// Verifies a compressed proof of the program, see [ProverClient::verify_compressed].
+    pub fn verify_compressed(
+        &self,
+        proof: &SP1CompressedProof,
+    ) -> Result<(), SP1VerificationError> {
+        self.client.verify_compressed(proof, self.vk())
+    }
+
+    /// Verifies a plonk bn254 proof of the program, see [ProverClient::verify_plonk].
+    pub fn verify_plonk(&self, proof: &SP1PlonkBn254Proof) -> Result<(), SP1VerificationError> {
+        self.client.verify_plonk(proof, self.vk())
+    }
+
+    /// The keys of the program, from the cache of the client, with the program decoded by the
+    /// prover so that its proofs skip every program-dependent step.
+    fn keys(&self) -> &Keys {
+        self.keys.get_or_init(|| {
+            let keys = self
+                .client
+                .keys
+                .setup(&self.elf, self.client.prover.as_ref());
+            self.client.prover.prepare(&keys.0);
+            keys
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use sp1_prover::HashableKey;
+
+    use crate::{utils, ProverClient, SP1Stdin};
+
+    #[test]
+    fn test_prover_session_reuses_setup() {
+        utils::setup_logger();
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let stdin = |n: usize| {
+            let mut stdin = SP1Stdin::new();
+            stdin.write(&n);
+            stdin
+        };
+
+        let client = ProverClient::local().with_deterministic(true);
+        let session = client.prover_session(elf);
+        let setups = || client.prover.sp1_prover().num_program_setups();
+
+        let start = Instant::now();
+        let first = session.prove(stdin(10)).unwrap();
+        let first_time = start.elapsed();
+        assert_eq!(
+            setups(),
+            2,
+            "the first proof sets up and decodes the program"
+        );
+
+        let start = Instant::now();
+        let second = session.prove(stdin(20)).unwrap();
+        let second_time = start.elapsed();
+        assert_eq!(setups(), 2, "the second proof did program-dependent work");
+        assert!(
+            second_time < first_time,
+            "the second proof took {:?}, the first {:?}",
+            second_time,
+            first_time
+        );
+        session.verify(&first).unwrap();
+        session.verify(&second).unwrap();
+
+        // A fresh client makes the same proof.
+        let fresh = ProverClient::local().with_deterministic(true);
+        let (pk, vk) = fresh.setup(elf);
+        assert_eq!(vk.hash_bytes(), session.vk().hash_bytes());
+        let expected = fresh.prove(&pk, stdin(20)).unwrap();
+        assert_eq!(
+            bincode::serialize(&second.proof).unwrap(),
+            bincode::serialize(&expected.proof).unwrap()
+        );
+        assert_eq!(second.public_values, expected.public_values);
+    }
+
+    #[test]
+    fn test_key_cache_dir() {
+        utils::setup_logger();
+        let elf =
+            include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+        let dir = tempfile::tempdir().unwrap();
+
+        let client = ProverClient::local().with_key_cache(dir.path());
+        let (_, vk) = client.setup(elf);
+        client.setup(elf);
+        assert_eq!(client.prover.sp1_prover().num_program_setups(), 1);
+
+        // Another client reads the keys from the directory, and only decodes the program.
+        let client = ProverClient::local().with_key_cache(dir.path());
+        let session = client.prover_session(elf);
+        assert_eq!(session.vk().hash_bytes(), vk.hash_bytes());
+        assert_eq!(client.prover.sp1_prover().num_program_setups(), 1);
+    }
+}
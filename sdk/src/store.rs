@@ -0,0 +1,434 @@
+//! Compressed proof artifacts and a content-addressable store for them.
+//!
+//! A compressed proof is the encoding of [SP1ProofWithPublicValues::save], compressed with zstd,
+//! behind a header holding the length and the SHA-256 digest of the compressed payload. Both are
+//! checked before any of the payload is decompressed, so that a corrupted file is reported as such
+//! instead of as a malformed proof. The proof is compressed and decompressed as a stream, without
+//! holding its encoding in memory.
+//!
+//! A [ProofStore] keeps compressed proofs in a directory, named after the [ContentId] of their
+//! encoding, so that storing the same proof twice only writes it once.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{ProofDecodeError, ProofShape, SP1ProofWithPublicValues};
+
+/// The zstd compression level of the proofs, which trades a slightly lower ratio than the
+/// highest levels for a much faster compression of multi-GB proofs.
+pub const COMPRESSION_LEVEL: i32 = 9;
+
+/// The magic bytes at the start of a compressed proof.
+const MAGIC: [u8; 4] = *b"SP1Z";
+
+/// The version of the compressed proof format.
+const FORMAT_VERSION: u32 = 1;
+
+/// The length of the header: the magic bytes, the format version, the length of the payload and
+/// its digest.
+const HEADER_LEN: usize = 4 + 4 + 8 + 32;
+
+/// An error saving or loading a compressed proof.
+#[derive(Error, Debug)]
+pub enum ProofStoreError {
+    #[error("{0} is not a compressed proof")]
+    NotCompressed(PathBuf),
+    #[error("{path} has format version {version}, expected {FORMAT_VERSION}")]
+    UnsupportedVersion { path: PathBuf, version: u32 },
+    #[error("{path} is corrupted: {reason}")]
+    Corrupted { path: PathBuf, reason: String },
+    #[error("no proof with id {0} in the store")]
+    NotFound(ContentId),
+    #[error("failed to encode the proof: {0}")]
+    Encoding(#[from] bincode::Error),
+    #[error(transparent)]
+    Decode(#[from] ProofDecodeError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The identifier of a proof in a [ProofStore]: the SHA-256 digest of its encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentId(pub [u8; 32]);
+
+impl ContentId {
+    /// The identifier of `proof`, computed without holding its encoding in memory.
+    pub fn of<P>(proof: &SP1ProofWithPublicValues<P>) -> Result<Self, ProofStoreError>
+    where
+        SP1ProofWithPublicValues<P>: Serialize,
+    {
+        let mut hasher = Sha256::new();
+        bincode::serialize_into(&mut hasher, proof)?;
+        Ok(Self(hasher.finalize().into()))
+    }
+}
+
+impl fmt::Display for ContentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl FromStr for ContentId {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut id = [0; 32];
+        hex::decode_to_slice(s, &mut id)?;
+        Ok(Self(id))
+    }
+}
+
+/// A writer hashing and counting the bytes written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes `proof` compressed to `file`, which is truncated first.
+pub(crate) fn write_compressed<P>(
+    proof: &SP1ProofWithPublicValues<P>,
+    mut file: File,
+) -> Result<(), ProofStoreError>
+where
+    SP1ProofWithPublicValues<P>: Serialize,
+{
+    file.set_len(0)?;
+    file.write_all(&[0; HEADER_LEN])?;
+    let writer = HashingWriter {
+        inner: BufWriter::new(&file),
+        hasher: Sha256::new(),
+        len: 0,
+    };
+    let mut encoder = zstd::Encoder::new(writer, COMPRESSION_LEVEL)?;
+    bincode::serialize_into(&mut encoder, proof)?;
+    let mut writer = encoder.finish()?;
+    writer.flush()?;
+    let HashingWriter { inner, hasher, len } = writer;
+    drop(inner);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(&MAGIC);
+    header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header.extend_from_slice(&len.to_le_bytes());
+    header.extend_from_slice(&hasher.finalize());
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Reads a proof written by [write_compressed] from `path`, checking the payload against the
+/// header before decompressing it.
+pub(crate) fn read_compressed<P>(
+    path: &Path,
+) -> Result<SP1ProofWithPublicValues<P>, ProofStoreError>
+where
+    P: ProofShape + fmt::Debug + Clone + DeserializeOwned,
+{
+    let corrupted = |reason: String| ProofStoreError::Corrupted {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let mut file = File::open(path)?;
+
+    let mut header = [0; HEADER_LEN];
+    match file.read_exact(&mut header) {
+        Ok(()) if header[..4] == MAGIC => {}
+        Ok(()) => return Err(ProofStoreError::NotCompressed(path.to_path_buf())),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            return Err(ProofStoreError::NotCompressed(path.to_path_buf()))
+        }
+        Err(err) => return Err(err.into()),
+    }
+    let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    if version != FORMAT_VERSION {
+        return Err(ProofStoreError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version,
+        });
+    }
+    let len = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    let digest = &header[16..];
+
+    let mut hasher = Sha256::new();
+    let read = io::copy(&mut BufReader::new(&file), &mut hasher)?;
+    if read != len {
+        return Err(corrupted(format!(
+            "the payload has {} bytes, expected {}",
+            read, len
+        )));
+    }
+    if hasher.finalize()[..] != *digest {
+        return Err(corrupted(
+            "the payload does not match its checksum".to_string(),
+        ));
+    }
+
+    file.seek(SeekFrom::Start(HEADER_LEN as u64))?;
+    let decoder = zstd::Decoder::new(file)?;
+    let limits = SP1ProofWithPublicValues::<P>::default_limits();
+    SP1ProofWithPublicValues::decode_from(decoder, &limits).map_err(Into::into)
+}
+
+/// A content-addressable store of compressed proofs in a directory.
+///
+/// The proof of id `abcd…` is stored at `<root>/ab/cd…`. Proofs are written to a temporary file
+/// that is then renamed, so that a concurrent reader never sees a partially written proof.
+///
+/// ```no_run
+/// use sp1_sdk::{ProofStore, ProverClient, SP1Proof, SP1Stdin};
+///
+/// let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+/// let client = ProverClient::new();
+/// let (pk, _) = client.setup(elf);
+/// let mut stdin = SP1Stdin::new();
+/// stdin.write(&10usize);
+/// let proof = client.prove(&pk, stdin).unwrap();
+///
+/// let store = ProofStore::new("proofs").unwrap();
+/// let id = store.put(&proof).unwrap();
+/// let loaded: SP1Proof = store.get(&id).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProofStore {
+    root: PathBuf,
+}
+
+impl ProofStore {
+    /// Opens the store at `root`, creating the directory if needed.
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, ProofStoreError> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// The directory of the store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The path of the proof with the given id, whether or not it is in the store.
+    pub fn path(&self, id: &ContentId) -> PathBuf {
+        let id = id.to_string();
+        self.root.join(&id[..2]).join(&id[2..])
+    }
+
+    /// Whether the proof with the given id is in the store.
+    pub fn contains(&self, id: &ContentId) -> bool {
+        self.path(id).is_file()
+    }
+
+    /// Stores `proof`, unless it is already in the store, and returns its id.
+    pub fn put<P>(&self, proof: &SP1ProofWithPublicValues<P>) -> Result<ContentId, ProofStoreError>
+    where
+        SP1ProofWithPublicValues<P>: Serialize,
+    {
+        let id = ContentId::of(proof)?;
+        let path = self.path(&id);
+        if path.is_file() {
+            return Ok(id);
+        }
+        let dir = path.parent().unwrap();
+        std::fs::create_dir_all(dir)?;
+        let file = tempfile::NamedTempFile::new_in(dir)?;
+        write_compressed(proof, file.reopen()?)?;
+        file.persist(&path).map_err(|err| err.error)?;
+        Ok(id)
+    }
+
+    /// Loads the proof with the given id.
+    pub fn get<P>(&self, id: &ContentId) -> Result<SP1ProofWithPublicValues<P>, ProofStoreError>
+    where
+        P: ProofShape + fmt::Debug + Clone + DeserializeOwned,
+    {
+        let path = self.path(id);
+        if !path.is_file() {
+            return Err(ProofStoreError::NotFound(*id));
+        }
+        read_compressed(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp1_core::stark::ShardProof;
+
+    use crate::{utils, CoreSC, ProverClient, SP1Proof, SP1Stdin};
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+
+    fn mock_proof(n: usize) -> SP1Proof {
+        let client = ProverClient::mock();
+        let (pk, _) = client.setup(FIBONACCI_ELF);
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&n);
+        client.prove(&pk, stdin).unwrap()
+    }
+
+    #[test]
+    fn test_compressed_proof_round_trip() {
+        utils::setup_logger();
+        let client = ProverClient::local();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        let proof = client.prove(&pk, stdin).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let raw_path = dir.path().join("proof.bin");
+        let compressed_path = dir.path().join("proof.bin.zst");
+        proof.save(&raw_path).unwrap();
+        proof.save_compressed(&compressed_path).unwrap();
+
+        let loaded = SP1Proof::load_compressed(&compressed_path).unwrap();
+        assert_eq!(
+            bincode::serialize(&loaded).unwrap(),
+            bincode::serialize(&proof).unwrap()
+        );
+        client.verify(&loaded, &vk).unwrap();
+
+        // The trace openings of a real proof are mostly padding, which compresses well.
+        let raw_len = std::fs::metadata(&raw_path).unwrap().len();
+        let compressed_len = std::fs::metadata(&compressed_path).unwrap().len();
+        assert!(
+            compressed_len * 5 < raw_len * 4,
+            "compressed {} bytes to {} bytes",
+            raw_len,
+            compressed_len
+        );
+
+        // An uncompressed proof is not mistaken for a compressed one.
+        assert!(matches!(
+            read_compressed::<Vec<ShardProof<CoreSC>>>(&raw_path),
+            Err(ProofStoreError::NotCompressed(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_store_dedup() {
+        utils::setup_logger();
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProofStore::new(dir.path().join("store")).unwrap();
+        let proof = mock_proof(10);
+
+        let id = store.put(&proof).unwrap();
+        assert_eq!(id, ContentId::of(&proof).unwrap());
+        assert_eq!(id.to_string().parse::<ContentId>().unwrap(), id);
+        assert!(store.contains(&id));
+        let modified = std::fs::metadata(store.path(&id))
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        // Storing the same proof again leaves the stored file alone.
+        assert_eq!(store.put(&proof.clone()).unwrap(), id);
+        assert_eq!(
+            std::fs::metadata(store.path(&id))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            modified
+        );
+
+        let other = store.put(&mock_proof(20)).unwrap();
+        assert_ne!(other, id);
+        let loaded: SP1Proof = store.get(&id).unwrap();
+        assert_eq!(loaded.stdin.buffer, proof.stdin.buffer);
+        let files = walk(store.root());
+        assert_eq!(files.len(), 2, "{:?}", files);
+
+        let missing = ContentId([0; 32]);
+        assert!(matches!(
+            get(&store, &missing),
+            Err(ProofStoreError::NotFound(id)) if id == missing
+        ));
+    }
+
+    #[test]
+    fn test_proof_store_detects_corruption() {
+        utils::setup_logger();
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProofStore::new(dir.path()).unwrap();
+        let id = store.put(&mock_proof(10)).unwrap();
+        let path = store.path(&id);
+        let bytes = std::fs::read(&path).unwrap();
+
+        // A flipped bit anywhere in the payload.
+        for offset in [HEADER_LEN, (HEADER_LEN + bytes.len()) / 2, bytes.len() - 1] {
+            let mut corrupted = bytes.clone();
+            corrupted[offset] ^= 1;
+            std::fs::write(&path, &corrupted).unwrap();
+            assert!(matches!(
+                get(&store, &id),
+                Err(ProofStoreError::Corrupted { .. })
+            ));
+        }
+
+        // A truncated payload, and one with trailing bytes.
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            get(&store, &id),
+            Err(ProofStoreError::Corrupted { .. })
+        ));
+        std::fs::write(&path, [&bytes[..], &[0]].concat()).unwrap();
+        assert!(matches!(
+            get(&store, &id),
+            Err(ProofStoreError::Corrupted { .. })
+        ));
+
+        // A header of another version.
+        let mut other_version = bytes.clone();
+        other_version[4] = 2;
+        std::fs::write(&path, &other_version).unwrap();
+        assert!(matches!(
+            get(&store, &id),
+            Err(ProofStoreError::UnsupportedVersion { version: 2, .. })
+        ));
+
+        std::fs::write(&path, &bytes).unwrap();
+        get(&store, &id).unwrap();
+    }
+
+    fn get(store: &ProofStore, id: &ContentId) -> Result<SP1Proof, ProofStoreError> {
+        store.get(id)
+    }
+
+    fn walk(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(walk(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+}
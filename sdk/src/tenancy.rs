@@ -0,0 +1,530 @@
+//! Key isolation between the tenants of a shared proving service.
+//!
+//! A [TenantRegistry] keeps the programs registered by each tenant, with their proving and
+//! verifying keys, in a namespace of their own: a tenant only ever proves and verifies with the
+//! keys of the programs it registered, identified by their [ProgramId]. The registry also gives
+//! each tenant its own directory for on-disk caches, and optionally its own PLONK artifacts.
+//!
+//! A [TenantClient], obtained with [crate::ProverClient::for_tenant], proves and verifies on behalf
+//! of a tenant. Every operation is logged as a `tracing` event with the target
+//! [AUDIT_TARGET], naming the tenant, the program and the outcome.
+//!
+//! ```no_run
+//! use sp1_sdk::{ProverClient, SP1Stdin};
+//!
+//! let elf = include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+//! let client = ProverClient::new();
+//! let tenant = client.for_tenant("acme").unwrap();
+//! let program = tenant.register_program(elf);
+//! let mut stdin = SP1Stdin::new();
+//! stdin.write(&10usize);
+//! let proof = tenant.prove(&program, stdin).unwrap();
+//! tenant.verify(&proof, &program).unwrap();
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use sha2::{Digest, Sha256};
+use sp1_prover::{HashableKey, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+use thiserror::Error;
+
+use crate::{
+    error::{check_compressed_proof, check_plonk_proof, check_proof},
+    PlonkArtifacts, ProofStore, ProofStoreError, ProverClient, SP1CompressedProof, SP1Error,
+    SP1PlonkBn254Proof, SP1Proof, SP1VerificationError,
+};
+
+/// The `tracing` target of the audit events of the tenants.
+pub const AUDIT_TARGET: &str = "sp1_sdk::audit";
+
+/// The maximum length of a [TenantId].
+pub const MAX_TENANT_ID_LEN: usize = 64;
+
+/// An error of a tenant operation.
+#[derive(Error, Debug)]
+pub enum TenancyError {
+    #[error(
+        "invalid tenant id {0:?}: expected 1 to {MAX_TENANT_ID_LEN} ASCII letters, digits, `-` or `_`"
+    )]
+    InvalidTenantId(String),
+    #[error("program {program} is not registered for tenant {tenant}")]
+    UnregisteredProgram {
+        tenant: TenantId,
+        program: ProgramId,
+    },
+    #[error(
+        "tenant {tenant} uses the PLONK artifacts {expected:?}, but the prover uses {found:?}"
+    )]
+    PlonkArtifactsMismatch {
+        tenant: TenantId,
+        expected: PlonkArtifacts,
+        found: PlonkArtifacts,
+    },
+    #[error(
+        "the proof returned to tenant {tenant} was not generated for program {program}: {source}"
+    )]
+    ForeignProof {
+        tenant: TenantId,
+        program: ProgramId,
+        #[source]
+        source: SP1Error,
+    },
+    #[error(transparent)]
+    Prover(#[from] anyhow::Error),
+    #[error(transparent)]
+    Verification(#[from] SP1VerificationError),
+    #[error(transparent)]
+    Store(#[from] ProofStoreError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The identifier of a tenant, which names its cache directory and so is restricted to ASCII
+/// letters, digits, `-` and `_`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Checks that `id` is a valid tenant identifier.
+    pub fn new(id: impl Into<String>) -> Result<Self, TenancyError> {
+        let id = id.into();
+        let valid = !id.is_empty()
+            && id.len() <= MAX_TENANT_ID_LEN
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        if !valid {
+            return Err(TenancyError::InvalidTenantId(id));
+        }
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The identifier of a registered program: the digest of its verifying key, see
+/// [HashableKey::hash_bytes], which proofs of the program carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProgramId(pub [u8; 32]);
+
+impl ProgramId {
+    /// The identifier of the program of `vk`.
+    pub fn of(vk: &SP1VerifyingKey) -> Self {
+        Self(vk.hash_bytes())
+    }
+}
+
+impl Display for ProgramId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+/// The keys of a registered program.
+struct ProgramKeys {
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+}
+
+/// The namespace of a tenant.
+#[derive(Default)]
+struct TenantState {
+    /// The programs of the tenant, by identifier.
+    programs: BTreeMap<ProgramId, Arc<ProgramKeys>>,
+    /// The identifiers of the programs of the tenant, by SHA-256 digest of their ELF, so that
+    /// registering a program twice only sets it up once.
+    elfs: HashMap<[u8; 32], ProgramId>,
+    /// The PLONK artifacts of the tenant, if it doesn't use the ones of the prover.
+    plonk_artifacts: Option<PlonkArtifacts>,
+}
+
+/// The programs and caches of the tenants of a proving service. Clones share the same registry.
+#[derive(Clone)]
+pub struct TenantRegistry {
+    /// The directory holding the cache directories of the tenants.
+    cache_root: PathBuf,
+    tenants: Arc<RwLock<HashMap<TenantId, TenantState>>>,
+}
+
+impl TenantRegistry {
+    /// An empty registry keeping the caches of the tenants in subdirectories of `cache_root`.
+    pub fn new(cache_root: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_root: cache_root.into(),
+            tenants: Default::default(),
+        }
+    }
+
+    /// Registers the program `elf` for `tenant`, setting up its keys with `prover` unless the
+    /// tenant already registered it.
+    pub fn register_program(
+        &self,
+        tenant: &TenantId,
+        elf: &[u8],
+        prover: &ProverClient,
+    ) -> ProgramId {
+        let elf_digest: [u8; 32] = Sha256::digest(elf).into();
+        let cached = self
+            .tenants
+            .read()
+            .unwrap()
+            .get(tenant)
+            .and_then(|state| state.elfs.get(&elf_digest).copied());
+        if let Some(program) = cached {
+            tracing::debug!(target: AUDIT_TARGET, %tenant, %program, "setup cache hit");
+            return program;
+        }
+
+        // The setup runs outside of the lock, so that it doesn't hold up the other tenants.
+        let (pk, vk) = prover.setup(elf);
+        let program = ProgramId::of(&vk);
+        let mut tenants = self.tenants.write().unwrap();
+        let state = tenants.entry(tenant.clone()).or_default();
+        state.elfs.insert(elf_digest, program);
+        state
+            .programs
+            .entry(program)
+            .or_insert_with(|| Arc::new(ProgramKeys { pk, vk }));
+        tracing::info!(target: AUDIT_TARGET, %tenant, %program, operation = "register");
+        program
+    }
+
+    /// The programs registered by `tenant`.
+    pub fn programs(&self, tenant: &TenantId) -> Vec<ProgramId> {
+        self.tenants
+            .read()
+            .unwrap()
+            .get(tenant)
+            .map(|state| state.programs.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The verifying key of the program `program` of `tenant`.
+    pub fn verifying_key(
+        &self,
+        tenant: &TenantId,
+        program: &ProgramId,
+    ) -> Result<SP1VerifyingKey, TenancyError> {
+        Ok(self.keys(tenant, program)?.vk.clone())
+    }
+
+    /// Uses `artifacts` to wrap and verify the PLONK proofs of `tenant`, instead of the artifacts
+    /// of the prover.
+    pub fn set_plonk_artifacts(&self, tenant: &TenantId, artifacts: PlonkArtifacts) {
+        let mut tenants = self.tenants.write().unwrap();
+        tenants.entry(tenant.clone()).or_default().plonk_artifacts = Some(artifacts);
+    }
+
+    /// The PLONK artifacts of `tenant`, if it doesn't use the ones of the prover.
+    pub fn plonk_artifacts(&self, tenant: &TenantId) -> Option<PlonkArtifacts> {
+        self.tenants
+            .read()
+            .unwrap()
+            .get(tenant)
+            .and_then(|state| state.plonk_artifacts.clone())
+    }
+
+    /// The directory of the on-disk caches of `tenant`, which is not created.
+    pub fn cache_dir(&self, tenant: &TenantId) -> PathBuf {
+        self.cache_root.join(tenant.as_str())
+    }
+
+    fn keys(
+        &self,
+        tenant: &TenantId,
+        program: &ProgramId,
+    ) -> Result<Arc<ProgramKeys>, TenancyError> {
+        self.tenants
+            .read()
+            .unwrap()
+            .get(tenant)
+            .and_then(|state| state.programs.get(program).cloned())
+            .ok_or_else(|| TenancyError::UnregisteredProgram {
+                tenant: tenant.clone(),
+                program: *program,
+            })
+    }
+}
+
+impl Default for TenantRegistry {
+    /// A registry keeping the caches of the tenants in `~/.sp1/tenants`.
+    fn default() -> Self {
+        let home = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+        Self::new(home.join(".sp1").join("tenants"))
+    }
+}
+
+/// A [ProverClient] proving and verifying the programs of a single tenant, see [crate::tenancy].
+pub struct TenantClient<'a> {
+    client: &'a ProverClient,
+    tenant: TenantId,
+}
+
+impl<'a> TenantClient<'a> {
+    pub(crate) fn new(client: &'a ProverClient, tenant: TenantId) -> Self {
+        Self { client, tenant }
+    }
+
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    /// Registers the program `elf` for the tenant, see [TenantRegistry::register_program].
+    pub fn register_program(&self, elf: &[u8]) -> ProgramId {
+        self.client
+            .tenants()
+            .register_program(&self.tenant, elf, self.client)
+    }
+
+    /// The programs registered by the tenant.
+    pub fn programs(&self) -> Vec<ProgramId> {
+        self.client.tenants().programs(&self.tenant)
+    }
+
+    /// The verifying key of the program `program` of the tenant.
+    pub fn verifying_key(&self, program: &ProgramId) -> Result<SP1VerifyingKey, TenancyError> {
+        self.client.tenants().verifying_key(&self.tenant, program)
+    }
+
+    /// The directory of the on-disk caches of the tenant, created if needed.
+    pub fn cache_dir(&self) -> Result<PathBuf, TenancyError> {
+        let dir = self.client.tenants().cache_dir(&self.tenant);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// A [ProofStore] in the cache directory of the tenant.
+    pub fn proof_store(&self) -> Result<ProofStore, TenancyError> {
+        Ok(ProofStore::new(self.cache_dir()?.join("proofs"))?)
+    }
+
+    /// Proves the program `program` of the tenant, see [ProverClient::prove].
+    pub fn prove(&self, program: &ProgramId, stdin: SP1Stdin) -> Result<SP1Proof, TenancyError> {
+        self.audited("prove", program, || {
+            let keys = self.keys(program)?;
+            let proof = self.client.prove(&keys.pk, stdin)?;
+            self.check_returned(program, check_proof(&proof, &keys.vk))?;
+            Ok(proof)
+        })
+    }
+
+    /// Proves the program `program` of the tenant in the compressed mode, see
+    /// [ProverClient::prove_compressed].
+    pub fn prove_compressed(
+        &self,
+        program: &ProgramId,
+        stdin: SP1Stdin,
+    ) -> Result<SP1CompressedProof, TenancyError> {
+        self.audited("prove_compressed", program, || {
+            let keys = self.keys(program)?;
+            let proof = self.client.prove_compressed(&keys.pk, stdin)?;
+            self.check_returned(program, check_compressed_proof(&proof, &keys.vk))?;
+            Ok(proof)
+        })
+    }
+
+    /// Proves the program `program` of the tenant in the plonk bn254 mode, see
+    /// [ProverClient::prove_plonk].
+    pub fn prove_plonk(
+        &self,
+        program: &ProgramId,
+        stdin: SP1Stdin,
+    ) -> Result<SP1PlonkBn254Proof, TenancyError> {
+        self.audited("prove_plonk", program, || {
+            let keys = self.keys(program)?;
+            self.check_plonk_artifacts()?;
+            let proof = self.client.prove_plonk(&keys.pk, stdin)?;
+            self.check_returned(program, check_plonk_proof(&proof, &keys.vk))?;
+            Ok(proof)
+        })
+    }
+
+    /// Verifies a proof of the program `program` of the tenant, see [ProverClient::verify].
+    pub fn verify(&self, proof: &SP1Proof, program: &ProgramId) -> Result<(), TenancyError> {
+        self.audited("verify", program, || {
+            let keys = self.keys(program)?;
+            Ok(self.client.verify(proof, &keys.vk)?)
+        })
+    }
+
+    /// Verifies a compressed proof of the program `program` of the tenant, see
+    /// [ProverClient::verify_compressed].
+    pub fn verify_compressed(
+        &self,
+        proof: &SP1CompressedProof,
+        program: &ProgramId,
+    ) -> Result<(), TenancyError> {
+        self.audited("verify_compressed", program, || {
+            let keys = self.keys(program)?;
+            Ok(self.client.verify_compressed(proof, &keys.vk)?)
+        })
+    }
+
+    /// Verifies a plonk bn254 proof of the program `program` of the tenant, see
+    /// [ProverClient::verify_plonk].
+    pub fn verify_plonk(
+        &self,
+        proof: &SP1PlonkBn254Proof,
+        program: &ProgramId,
+    ) -> Result<(), TenancyError> {
+        self.audited("verify_plonk", program, || {
+            let keys = self.keys(program)?;
+            self.check_plonk_artifacts()?;
+            Ok(self.client.verify_plonk(proof, &keys.vk)?)
+        })
+    }
+
+    fn keys(&self, program: &ProgramId) -> Result<Arc<ProgramKeys>, TenancyError> {
+        self.client.tenants().keys(&self.tenant, program)
+    }
+
+    /// Checks that the prover uses the PLONK artifacts of the tenant, if it has its own.
+    fn check_plonk_artifacts(&self) -> Result<(), TenancyError> {
+        let Some(expected) = self.client.tenants().plonk_artifacts(&self.tenant) else {
+            return Ok(());
+        };
+        let found = self.client.prover.plonk_artifacts();
+        if expected != found {
+            return Err(TenancyError::PlonkArtifactsMismatch {
+                tenant: self.tenant.clone(),
+                expected,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_returned(
+        &self,
+        program: &ProgramId,
+        check: Result<(), SP1Error>,
+    ) -> Result<(), TenancyError> {
+        check.map_err(|source| TenancyError::ForeignProof {
+            tenant: self.tenant.clone(),
+            program: *program,
+            source,
+        })
+    }
+
+    /// Runs `f`, logging an audit event with its outcome.
+    fn audited<T>(
+        &self,
+        operation: &str,
+        program: &ProgramId,
+        f: impl FnOnce() -> Result<T, TenancyError>,
+    ) -> Result<T, TenancyError> {
+        let tenant = &self.tenant;
+        let result = f();
+        match &result {
+            Ok(_) => tracing::info!(target: AUDIT_TARGET, %tenant, %program, operation),
+            Err(err) => {
+                tracing::warn!(target: AUDIT_TARGET, %tenant, %program, operation, error = %err)
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TenancyError, TenantRegistry};
+    use crate::{utils, PlonkArtifacts, ProverClient, SP1Stdin};
+
+    const FIBONACCI_ELF: &[u8] =
+        include_bytes!("../../examples/fibonacci/program/elf/riscv32im-succinct-zkvm-elf");
+    const IS_PRIME_ELF: &[u8] =
+        include_bytes!("../../examples/is-prime/program/elf/riscv32im-succinct-zkvm-elf");
+
+    #[test]
+    fn test_tenant_isolation() {
+        utils::setup_logger();
+        let root = tempfile::tempdir().unwrap();
+        let client = ProverClient::mock().with_tenant_registry(TenantRegistry::new(root.path()));
+        let alice = client.for_tenant("alice").unwrap();
+        let bob = client.for_tenant("bob").unwrap();
+
+        let fibonacci = alice.register_program(FIBONACCI_ELF);
+        let is_prime = bob.register_program(IS_PRIME_ELF);
+        assert_eq!(alice.register_program(FIBONACCI_ELF), fibonacci);
+        assert_eq!(alice.programs(), vec![fibonacci]);
+        assert_eq!(bob.programs(), vec![is_prime]);
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&10usize);
+        let proof = alice.prove(&fibonacci, stdin.clone()).unwrap();
+        alice.verify(&proof, &fibonacci).unwrap();
+
+        // Bob can't use the keys of Alice, until he registers the program himself.
+        match bob.prove(&fibonacci, stdin.clone()) {
+            Err(TenancyError::UnregisteredProgram { tenant, program }) => {
+                assert_eq!((tenant.as_str(), program), ("bob", fibonacci));
+            }
+            result => panic!("expected an unregistered program, got {:?}", result.err()),
+        }
+        assert!(matches!(
+            bob.verify(&proof, &fibonacci),
+            Err(TenancyError::UnregisteredProgram { .. })
+        ));
+        assert_eq!(bob.register_program(FIBONACCI_ELF), fibonacci);
+        bob.verify(&proof, &fibonacci).unwrap();
+        assert_eq!(alice.programs(), vec![fibonacci]);
+
+        // A proof of another program is rejected.
+        assert!(matches!(
+            bob.verify(&proof, &is_prime),
+            Err(TenancyError::Verification(_))
+        ));
+
+        let alice_dir = alice.cache_dir().unwrap();
+        let bob_dir = bob.cache_dir().unwrap();
+        assert_ne!(alice_dir, bob_dir);
+        assert!(alice_dir.starts_with(root.path()) && bob_dir.starts_with(root.path()));
+        assert!(alice.proof_store().unwrap().root().starts_with(&alice_dir));
+    }
+
+    #[test]
+    fn test_tenant_plonk_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        let client = ProverClient::mock().with_tenant_registry(TenantRegistry::new(root.path()));
+        let alice = client.for_tenant("alice").unwrap();
+        let program = alice.register_program(FIBONACCI_ELF);
+
+        let artifacts = PlonkArtifacts::Dir(root.path().join("alice-circuits"));
+        client
+            .tenants()
+            .set_plonk_artifacts(alice.tenant(), artifacts.clone());
+        match alice.prove_plonk(&program, SP1Stdin::new()) {
+            Err(TenancyError::PlonkArtifactsMismatch { expected, .. }) => {
+                assert_eq!(expected, artifacts)
+            }
+            result => panic!("expected an artifacts mismatch, got {:?}", result.err()),
+        }
+    }
+
+    #[test]
+    fn test_invalid_tenant_id() {
+        let client = ProverClient::mock();
+        let long = "a".repeat(65);
+        for id in ["", "../bob", "alice/bob", long.as_str()] {
+            assert!(matches!(
+                client.for_tenant(id),
+                Err(TenancyError::InvalidTenantId(_))
+            ));
+        }
+        assert!(client.for_tenant("tenant_01-eu").is_ok());
+    }
+}
@@ -0,0 +1,15 @@
+//! Aggregates the proofs of consecutive segments of a computation, reading the inputs written by
+//! `SegmentProver::aggregation_stdin`, and commits only the digests of the first state and of the
+//! last one.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::precompiles::verify::verify_segment_chain;
+
+pub fn main() {
+    let vkey = sp1_zkvm::io::read::<[u32; 8]>();
+    let public_values = sp1_zkvm::io::read::<Vec<Vec<u8>>>();
+    let chain = verify_segment_chain(&vkey, &public_values);
+    sp1_zkvm::io::commit_slice(&chain.encode());
+}
@@ -0,0 +1,16 @@
+//! A segment of a counter split across several proofs: it resumes the count, adds the number of
+//! steps read from the input, commits the new count and suspends it for the next segment.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    let count = sp1_zkvm::io::resume_state::<u64>();
+    let steps = sp1_zkvm::io::read::<u64>();
+    let mut next = count;
+    for _ in 0..steps {
+        next += 1;
+    }
+    sp1_zkvm::io::commit(&next);
+    sp1_zkvm::io::suspend_state(&next);
+}
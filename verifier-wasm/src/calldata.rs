@@ -0,0 +1,70 @@
+use bn::Fr;
+
+use crate::{decode_public_inputs, Groth16Error, Groth16Proof, PROOF_LEN, WORD_LEN};
+
+/// The size of a function selector.
+const SELECTOR_LEN: usize = 4;
+
+/// Decodes the proof and the public inputs from the calldata of the Solidity verifier's
+/// `verifyProof(uint256[8] proof, uint256[N] input)`, so that the bytes sent on-chain can be
+/// checked in the browser first.
+///
+/// The calldata may or may not start with the function selector, which is not checked since it
+/// is the contract that dispatches on it.
+pub fn decode_calldata(calldata: &[u8]) -> Result<(Groth16Proof, Vec<Fr>), Groth16Error> {
+    let args = match calldata.len() % WORD_LEN {
+        0 => calldata,
+        SELECTOR_LEN => &calldata[SELECTOR_LEN..],
+        _ => return Err(Groth16Error::InvalidCalldataLength(calldata.len())),
+    };
+    if args.len() < PROOF_LEN {
+        return Err(Groth16Error::InvalidCalldataLength(calldata.len()));
+    }
+    let (proof, public_inputs) = args.split_at(PROOF_LEN);
+    Ok((
+        Groth16Proof::from_bytes(proof)?,
+        decode_public_inputs(public_inputs)?,
+    ))
+}
+
+/// Encodes the arguments of `verifyProof(uint256[8] proof, uint256[N] input)`, without the
+/// function selector.
+pub fn encode_calldata(proof: &Groth16Proof, public_inputs: &[Fr]) -> Vec<u8> {
+    let mut calldata = proof.to_bytes();
+    calldata.extend(crate::encode_public_inputs(public_inputs));
+    calldata
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::groth16::tests::simulate;
+
+    #[test]
+    fn test_decode_calldata() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let public_inputs = [Fr::random(&mut rng), Fr::random(&mut rng)];
+        let (vk, proof) = simulate(&mut rng, &public_inputs);
+
+        let args = encode_calldata(&proof, &public_inputs);
+        assert_eq!(args.len(), PROOF_LEN + 2 * WORD_LEN);
+        let (decoded_proof, decoded_inputs) = decode_calldata(&args).unwrap();
+        assert_eq!(decoded_proof.to_bytes(), proof.to_bytes());
+        assert_eq!(decoded_inputs, public_inputs);
+        let mut calldata = vec![0x12, 0x34, 0x56, 0x78];
+        calldata.extend(&args);
+        let (decoded_proof, decoded_inputs) = decode_calldata(&calldata).unwrap();
+        vk.verify(&decoded_proof, &decoded_inputs).unwrap();
+
+        assert_eq!(
+            decode_calldata(&calldata[1..]).unwrap_err(),
+            Groth16Error::InvalidCalldataLength(calldata.len() - 1)
+        );
+        assert_eq!(
+            decode_calldata(&calldata[..SELECTOR_LEN + PROOF_LEN - WORD_LEN]).unwrap_err(),
+            Groth16Error::InvalidCalldataLength(SELECTOR_LEN + PROOF_LEN - WORD_LEN)
+        );
+    }
+}
@@ -0,0 +1,549 @@
+use bn::{arith::U256, pairing_batch, AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+use sha2::{Digest, Sha512};
+use thiserror::Error;
+
+/// The size of an encoded field element, a big-endian `uint256`.
+pub const WORD_LEN: usize = 32;
+
+/// The size of an encoded G1 point, `x` then `y`.
+pub const G1_LEN: usize = 2 * WORD_LEN;
+
+/// The size of an encoded G2 point, `x.c1`, `x.c0`, `y.c1` then `y.c0`, in the order of the EVM
+/// pairing precompile (EIP-197).
+pub const G2_LEN: usize = 4 * WORD_LEN;
+
+/// The size of an encoded proof, `a`, `b` then `c`, which is the ABI encoding of the `uint256[8]`
+/// taken by the Solidity verifier.
+pub const PROOF_LEN: usize = 2 * G1_LEN + G2_LEN;
+
+/// The size of an encoded verifying key without its input commitments: `alpha`, `beta`, `gamma`
+/// then `delta`.
+const VK_HEADER_LEN: usize = G1_LEN + 3 * G2_LEN;
+
+/// An error returned when decoding or verifying a Groth16 proof.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Groth16Error {
+    #[error("expected {expected} bytes for the proof, got {actual}")]
+    InvalidProofLength { expected: usize, actual: usize },
+    #[error("invalid verifying key length {0}")]
+    InvalidVerifyingKeyLength(usize),
+    #[error("the public inputs length {0} is not a multiple of {WORD_LEN}")]
+    InvalidPublicInputsLength(usize),
+    #[error("the calldata length {0} is not a selector followed by words")]
+    InvalidCalldataLength(usize),
+    #[error("{0} is not a point of the curve")]
+    InvalidPoint(&'static str),
+    #[error("public input {0} is not in the scalar field")]
+    InvalidPublicInput(usize),
+    #[error("expected {expected} public inputs, got {actual}")]
+    PublicInputsMismatch { expected: usize, actual: usize },
+    #[error("the pairing check failed")]
+    VerificationFailed,
+}
+
+/// A Groth16 proof over BN254.
+#[derive(Debug, Clone, Copy)]
+pub struct Groth16Proof {
+    pub a: G1,
+    pub b: G2,
+    pub c: G1,
+}
+
+/// A Groth16 verifying key over BN254.
+#[derive(Debug, Clone)]
+pub struct Groth16VerifyingKey {
+    pub alpha: G1,
+    pub beta: G2,
+    pub gamma: G2,
+    pub delta: G2,
+    /// The commitments to the constant input, then to each public input.
+    pub ic: Vec<G1>,
+}
+
+impl Groth16Proof {
+    /// Decodes a proof from its [PROOF_LEN] bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Groth16Error> {
+        if bytes.len() != PROOF_LEN {
+            return Err(Groth16Error::InvalidProofLength {
+                expected: PROOF_LEN,
+                actual: bytes.len(),
+            });
+        }
+        Ok(Self {
+            a: decode_g1(&bytes[..G1_LEN], "proof.a")?,
+            b: decode_g2(&bytes[G1_LEN..G1_LEN + G2_LEN], "proof.b")?,
+            c: decode_g1(&bytes[G1_LEN + G2_LEN..], "proof.c")?,
+        })
+    }
+
+    /// Encodes the proof into its [PROOF_LEN] bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PROOF_LEN);
+        encode_g1(&self.a, &mut bytes);
+        encode_g2(&self.b, &mut bytes);
+        encode_g1(&self.c, &mut bytes);
+        bytes
+    }
+}
+
+impl Groth16VerifyingKey {
+    /// Decodes a verifying key from `alpha`, `beta`, `gamma`, `delta`, then the input commitments,
+    /// whose number is given by the length of the bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Groth16Error> {
+        if bytes.len() < VK_HEADER_LEN + G1_LEN || (bytes.len() - VK_HEADER_LEN) % G1_LEN != 0 {
+            return Err(Groth16Error::InvalidVerifyingKeyLength(bytes.len()));
+        }
+        let (header, ic) = bytes.split_at(VK_HEADER_LEN);
+        Ok(Self {
+            alpha: decode_g1(&header[..G1_LEN], "vk.alpha")?,
+            beta: decode_g2(&header[G1_LEN..G1_LEN + G2_LEN], "vk.beta")?,
+            gamma: decode_g2(&header[G1_LEN + G2_LEN..G1_LEN + 2 * G2_LEN], "vk.gamma")?,
+            delta: decode_g2(&header[G1_LEN + 2 * G2_LEN..], "vk.delta")?,
+            ic: ic
+                .chunks(G1_LEN)
+                .map(|point| decode_g1(point, "vk.ic"))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Encodes the verifying key into the bytes read by [Groth16VerifyingKey::from_bytes].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(VK_HEADER_LEN + self.ic.len() * G1_LEN);
+        encode_g1(&self.alpha, &mut bytes);
+        encode_g2(&self.beta, &mut bytes);
+        encode_g2(&self.gamma, &mut bytes);
+        encode_g2(&self.delta, &mut bytes);
+        self.ic
+            .iter()
+            .for_each(|point| encode_g1(point, &mut bytes));
+        bytes
+    }
+
+    /// The number of public inputs of the circuit.
+    pub fn num_public_inputs(&self) -> usize {
+        self.ic.len() - 1
+    }
+
+    /// Verifies `proof` for `public_inputs`, checking that
+    /// `e(a, b) = e(alpha, beta) * e(ic_0 + sum_i input_i * ic_{i+1}, gamma) * e(c, delta)`.
+    pub fn verify(&self, proof: &Groth16Proof, public_inputs: &[Fr]) -> Result<(), Groth16Error> {
+        let inputs = self.prepare_inputs(public_inputs)?;
+        pairing_check(&[
+            (-proof.a, proof.b),
+            (self.alpha, self.beta),
+            (inputs, self.gamma),
+            (proof.c, self.delta),
+        ])
+    }
+
+    /// Verifies several proofs with a single pairing check on a random linear combination of
+    /// their equations, `sum_j r_j * e(a_j, b_j) = e(sum_j r_j * alpha, beta) * ...` written
+    /// additively. The coefficients `r_j` are derived from a transcript of the verifying key and of
+    /// all the proofs and public inputs, see [Groth16VerifyingKey::batch_coefficients], so that
+    /// invalid proofs cannot be crafted to cancel each other out. The batch fails as a whole if any
+    /// proof is invalid, without telling which one.
+    pub fn verify_batch(&self, batch: &[(Groth16Proof, Vec<Fr>)]) -> Result<(), Groth16Error> {
+        let inputs = batch
+            .iter()
+            .map(|(_, public_inputs)| self.prepare_inputs(public_inputs))
+            .collect::<Result<Vec<_>, _>>()?;
+        let coefficients = self.batch_coefficients(batch);
+
+        let mut pairs = Vec::with_capacity(batch.len() + 3);
+        let (mut alpha, mut combined_inputs, mut c) = (Fr::zero(), G1::zero(), G1::zero());
+        for (((proof, _), inputs), r) in batch.iter().zip(inputs).zip(coefficients) {
+            pairs.push((-proof.a * r, proof.b));
+            alpha = alpha + r;
+            combined_inputs = combined_inputs + inputs * r;
+            c = c + proof.c * r;
+        }
+        pairs.extend([
+            (self.alpha * alpha, self.beta),
+            (combined_inputs, self.gamma),
+            (c, self.delta),
+        ]);
+        pairing_check(&pairs)
+    }
+
+    /// The random coefficients of [Groth16VerifyingKey::verify_batch], one per proof: the SHA-512
+    /// hash of a seed and of the index of the proof, reduced into the scalar field. The seed hashes
+    /// the encodings of the verifying key, then of each proof followed by its public inputs.
+    pub fn batch_coefficients(&self, batch: &[(Groth16Proof, Vec<Fr>)]) -> Vec<Fr> {
+        let mut transcript = Sha512::new();
+        transcript.update(self.to_bytes());
+        for (proof, public_inputs) in batch {
+            transcript.update(proof.to_bytes());
+            transcript.update(encode_public_inputs(public_inputs));
+        }
+        let seed = transcript.finalize();
+        (0..batch.len() as u64)
+            .map(|i| {
+                let digest = Sha512::new()
+                    .chain_update(seed)
+                    .chain_update(i.to_be_bytes())
+                    .finalize();
+                Fr::interpret(&digest.into())
+            })
+            .collect()
+    }
+
+    /// The commitment to the public inputs, `ic_0 + sum_i input_i * ic_{i+1}`.
+    fn prepare_inputs(&self, public_inputs: &[Fr]) -> Result<G1, Groth16Error> {
+        if public_inputs.len() != self.num_public_inputs() {
+            return Err(Groth16Error::PublicInputsMismatch {
+                expected: self.num_public_inputs(),
+                actual: public_inputs.len(),
+            });
+        }
+        Ok(public_inputs
+            .iter()
+            .zip(&self.ic[1..])
+            .fold(self.ic[0], |acc, (input, ic)| acc + *ic * *input))
+    }
+}
+
+/// Checks that the product of the pairings of `pairs` is one.
+fn pairing_check(pairs: &[(G1, G2)]) -> Result<(), Groth16Error> {
+    if pairing_batch(pairs) == Gt::one() {
+        Ok(())
+    } else {
+        Err(Groth16Error::VerificationFailed)
+    }
+}
+
+/// Decodes public inputs from consecutive big-endian words, which is the ABI encoding of the
+/// `uint256[]` taken by the Solidity verifier. Each input must be in the scalar field.
+pub fn decode_public_inputs(bytes: &[u8]) -> Result<Vec<Fr>, Groth16Error> {
+    if bytes.len() % WORD_LEN != 0 {
+        return Err(Groth16Error::InvalidPublicInputsLength(bytes.len()));
+    }
+    bytes
+        .chunks(WORD_LEN)
+        .enumerate()
+        .map(|(i, word)| {
+            U256::from_slice(word)
+                .ok()
+                .and_then(Fr::new)
+                .ok_or(Groth16Error::InvalidPublicInput(i))
+        })
+        .collect()
+}
+
+/// Encodes public inputs into the bytes read by [decode_public_inputs].
+pub fn encode_public_inputs(public_inputs: &[Fr]) -> Vec<u8> {
+    let mut bytes = vec![0; public_inputs.len() * WORD_LEN];
+    for (input, word) in public_inputs.iter().zip(bytes.chunks_mut(WORD_LEN)) {
+        input.into_u256().to_big_endian(word).unwrap();
+    }
+    bytes
+}
+
+fn decode_fq(bytes: &[u8], name: &'static str) -> Result<Fq, Groth16Error> {
+    Fq::from_slice(bytes).map_err(|_| Groth16Error::InvalidPoint(name))
+}
+
+/// Decodes a G1 point, where `(0, 0)` is the point at infinity as in the EVM precompiles.
+fn decode_g1(bytes: &[u8], name: &'static str) -> Result<G1, Groth16Error> {
+    let x = decode_fq(&bytes[..WORD_LEN], name)?;
+    let y = decode_fq(&bytes[WORD_LEN..], name)?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1::zero());
+    }
+    AffineG1::new(x, y)
+        .map(Into::into)
+        .map_err(|_| Groth16Error::InvalidPoint(name))
+}
+
+/// Decodes a G2 point, which is also checked to be in the prime order subgroup.
+fn decode_g2(bytes: &[u8], name: &'static str) -> Result<G2, Groth16Error> {
+    let word = |i: usize| decode_fq(&bytes[i * WORD_LEN..(i + 1) * WORD_LEN], name);
+    let x = Fq2::new(word(1)?, word(0)?);
+    let y = Fq2::new(word(3)?, word(2)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2::zero());
+    }
+    AffineG2::new(x, y)
+        .map(Into::into)
+        .map_err(|_| Groth16Error::InvalidPoint(name))
+}
+
+fn encode_fq(element: Fq, bytes: &mut Vec<u8>) {
+    let mut word = [0; WORD_LEN];
+    element.to_big_endian(&mut word).unwrap();
+    bytes.extend_from_slice(&word);
+}
+
+fn encode_g1(point: &G1, bytes: &mut Vec<u8>) {
+    match AffineG1::from_jacobian(*point) {
+        Some(point) => {
+            encode_fq(point.x(), bytes);
+            encode_fq(point.y(), bytes);
+        }
+        None => bytes.extend_from_slice(&[0; G1_LEN]),
+    }
+}
+
+fn encode_g2(point: &G2, bytes: &mut Vec<u8>) {
+    match AffineG2::from_jacobian(*point) {
+        Some(point) => {
+            encode_fq(point.x().imaginary(), bytes);
+            encode_fq(point.x().real(), bytes);
+            encode_fq(point.y().imaginary(), bytes);
+            encode_fq(point.y().real(), bytes);
+        }
+        None => bytes.extend_from_slice(&[0; G2_LEN]),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// A verifying key with `num_public_inputs` inputs and a valid proof for `public_inputs`, made
+    /// with the trapdoor of the key instead of a circuit.
+    pub(crate) fn simulate(
+        rng: &mut StdRng,
+        public_inputs: &[Fr],
+    ) -> (Groth16VerifyingKey, Groth16Proof) {
+        let [alpha, beta, gamma, delta, a, b] = [(); 6].map(|_| Fr::random(rng));
+        let trapdoor = Trapdoor {
+            alpha,
+            beta,
+            gamma,
+            delta,
+            ic: (0..=public_inputs.len()).map(|_| Fr::random(rng)).collect(),
+        };
+        (trapdoor.vk(), trapdoor.prove(a, b, public_inputs))
+    }
+
+    /// Like [simulate], with a proof for each of the public inputs under the same verifying key.
+    fn simulate_batch(
+        rng: &mut StdRng,
+        public_inputs: &[Vec<Fr>],
+    ) -> (Groth16VerifyingKey, Vec<(Groth16Proof, Vec<Fr>)>) {
+        let [alpha, beta, gamma, delta] = [(); 4].map(|_| Fr::random(rng));
+        let trapdoor = Trapdoor {
+            alpha,
+            beta,
+            gamma,
+            delta,
+            ic: (0..=public_inputs[0].len())
+                .map(|_| Fr::random(rng))
+                .collect(),
+        };
+        let batch = public_inputs
+            .iter()
+            .map(|inputs| {
+                let [a, b] = [(); 2].map(|_| Fr::random(rng));
+                (trapdoor.prove(a, b, inputs), inputs.clone())
+            })
+            .collect();
+        (trapdoor.vk(), batch)
+    }
+
+    /// The discrete logarithms of the points of a verifying key.
+    struct Trapdoor {
+        alpha: Fr,
+        beta: Fr,
+        gamma: Fr,
+        delta: Fr,
+        ic: Vec<Fr>,
+    }
+
+    impl Trapdoor {
+        fn vk(&self) -> Groth16VerifyingKey {
+            Groth16VerifyingKey {
+                alpha: G1::one() * self.alpha,
+                beta: G2::one() * self.beta,
+                gamma: G2::one() * self.gamma,
+                delta: G2::one() * self.delta,
+                ic: self.ic.iter().map(|ic| G1::one() * *ic).collect(),
+            }
+        }
+
+        /// The proof with the given `a` and `b`, solving the verification equation for `c`.
+        fn prove(&self, a: Fr, b: Fr, public_inputs: &[Fr]) -> Groth16Proof {
+            let inputs = public_inputs
+                .iter()
+                .zip(&self.ic[1..])
+                .fold(self.ic[0], |acc, (input, ic)| acc + *input * *ic);
+            let c = (a * b - self.alpha * self.beta - inputs * self.gamma)
+                * self.delta.inverse().unwrap();
+            Groth16Proof {
+                a: G1::one() * a,
+                b: G2::one() * b,
+                c: G1::one() * c,
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let public_inputs = [Fr::random(&mut rng), Fr::random(&mut rng)];
+        let (vk, proof) = simulate(&mut rng, &public_inputs);
+        vk.verify(&proof, &public_inputs).unwrap();
+
+        let other_inputs = [public_inputs[0], public_inputs[1] + Fr::one()];
+        assert_eq!(
+            vk.verify(&proof, &other_inputs),
+            Err(Groth16Error::VerificationFailed)
+        );
+        assert_eq!(
+            vk.verify(&proof, &public_inputs[..1]),
+            Err(Groth16Error::PublicInputsMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+        let swapped = Groth16Proof {
+            a: proof.c,
+            c: proof.a,
+            ..proof
+        };
+        assert_eq!(
+            vk.verify(&swapped, &public_inputs),
+            Err(Groth16Error::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let public_inputs = (0..3)
+            .map(|_| vec![Fr::random(&mut rng), Fr::random(&mut rng)])
+            .collect::<Vec<_>>();
+        let (vk, batch) = simulate_batch(&mut rng, &public_inputs);
+        vk.verify_batch(&batch).unwrap();
+        vk.verify_batch(&[]).unwrap();
+
+        // A single invalid proof makes the whole batch fail, although the others are valid.
+        let mut bad_batch = batch.clone();
+        bad_batch[1].1[0] = bad_batch[1].1[0] + Fr::one();
+        for (i, (proof, public_inputs)) in bad_batch.iter().enumerate() {
+            assert_eq!(vk.verify(proof, public_inputs).is_ok(), i != 1);
+        }
+        assert_eq!(
+            vk.verify_batch(&bad_batch),
+            Err(Groth16Error::VerificationFailed)
+        );
+
+        // Errors which cancel out in the plain sum of the equations are caught by the coefficients.
+        let mut cancelling_batch = batch.clone();
+        let offset = G1::one() * Fr::random(&mut rng);
+        cancelling_batch[0].0.c = cancelling_batch[0].0.c + offset;
+        cancelling_batch[2].0.c = cancelling_batch[2].0.c - offset;
+        assert_eq!(
+            vk.verify_batch(&cancelling_batch),
+            Err(Groth16Error::VerificationFailed)
+        );
+
+        let mut short_batch = batch.clone();
+        short_batch[2].1.pop();
+        assert_eq!(
+            vk.verify_batch(&short_batch),
+            Err(Groth16Error::PublicInputsMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_batch_coefficients_bind_the_batch() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let public_inputs = vec![vec![Fr::one()], vec![Fr::zero()]];
+        let (vk, batch) = simulate_batch(&mut rng, &public_inputs);
+        let coefficients = vk.batch_coefficients(&batch);
+        assert_eq!(coefficients.len(), 2);
+        assert_ne!(coefficients[0], coefficients[1]);
+
+        // Changing any public input or reordering the proofs changes all the coefficients.
+        let mut other_inputs = batch.clone();
+        other_inputs[1].1[0] = Fr::one();
+        let other_coefficients = vk.batch_coefficients(&other_inputs);
+        assert!(other_coefficients.iter().all(|r| !coefficients.contains(r)));
+        let reversed = batch.iter().rev().cloned().collect::<Vec<_>>();
+        let reversed_coefficients = vk.batch_coefficients(&reversed);
+        assert!(reversed_coefficients
+            .iter()
+            .all(|r| !coefficients.contains(r)));
+    }
+
+    #[test]
+    fn test_encoding_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let public_inputs = [Fr::random(&mut rng)];
+        let (vk, proof) = simulate(&mut rng, &public_inputs);
+
+        let proof_bytes = proof.to_bytes();
+        assert_eq!(proof_bytes.len(), PROOF_LEN);
+        let decoded = Groth16Proof::from_bytes(&proof_bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), proof_bytes);
+        let vk_bytes = vk.to_bytes();
+        assert_eq!(vk_bytes.len(), VK_HEADER_LEN + 2 * G1_LEN);
+        let decoded = Groth16VerifyingKey::from_bytes(&vk_bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), vk_bytes);
+        let input_bytes = encode_public_inputs(&public_inputs);
+        assert_eq!(decode_public_inputs(&input_bytes).unwrap(), public_inputs);
+
+        // The point at infinity is encoded as zeros.
+        let mut bytes = Vec::new();
+        encode_g1(&G1::zero(), &mut bytes);
+        assert_eq!(bytes, [0; G1_LEN]);
+        assert!(decode_g1(&bytes, "zero").unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_invalid_encodings() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let (vk, proof) = simulate(&mut rng, &[Fr::one()]);
+        let proof_bytes = proof.to_bytes();
+
+        assert_eq!(
+            Groth16Proof::from_bytes(&proof_bytes[1..]).unwrap_err(),
+            (Groth16Error::InvalidProofLength {
+                expected: PROOF_LEN,
+                actual: PROOF_LEN - 1
+            })
+        );
+        let mut off_curve = proof_bytes.clone();
+        off_curve[G1_LEN - 1] ^= 1;
+        assert_eq!(
+            Groth16Proof::from_bytes(&off_curve).unwrap_err(),
+            Groth16Error::InvalidPoint("proof.a")
+        );
+        // Swapping the coordinates of `b` gives a point off the twist.
+        let mut swapped = proof_bytes.clone();
+        swapped[G1_LEN..G1_LEN + G2_LEN].rotate_left(WORD_LEN);
+        assert_eq!(
+            Groth16Proof::from_bytes(&swapped).unwrap_err(),
+            Groth16Error::InvalidPoint("proof.b")
+        );
+
+        let vk_bytes = vk.to_bytes();
+        assert_eq!(
+            Groth16VerifyingKey::from_bytes(&vk_bytes[..VK_HEADER_LEN]).unwrap_err(),
+            Groth16Error::InvalidVerifyingKeyLength(VK_HEADER_LEN)
+        );
+        assert_eq!(
+            Groth16VerifyingKey::from_bytes(&vk_bytes[..vk_bytes.len() - 1]).unwrap_err(),
+            Groth16Error::InvalidVerifyingKeyLength(vk_bytes.len() - 1)
+        );
+
+        // The scalar field modulus is not a valid input.
+        let modulus = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+        let mut input_bytes = encode_public_inputs(&[Fr::one()]);
+        input_bytes.extend(hex::decode(modulus).unwrap());
+        assert_eq!(
+            decode_public_inputs(&input_bytes),
+            Err(Groth16Error::InvalidPublicInput(1))
+        );
+        assert_eq!(
+            decode_public_inputs(&input_bytes[1..]),
+            Err(Groth16Error::InvalidPublicInputsLength(2 * WORD_LEN - 1))
+        );
+    }
+}
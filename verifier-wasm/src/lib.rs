@@ -0,0 +1,50 @@
+//! Verification of SP1 Groth16 BN254 proofs that compiles standalone for `wasm32-unknown-unknown`,
+//! so that they can be checked in the browser before being submitted on-chain.
+//!
+//! The pairing is computed in pure Rust, without the gnark FFI or the prover. Proofs, public
+//! inputs and verifying keys use the encodings of the Solidity verifier: big-endian `uint256`
+//! words, with G2 points in the order of the EVM pairing precompile. The same calldata can thus be
+//! sent to the contract and checked with [verify_groth16_calldata]. Several proofs for the same
+//! key can be checked with a single pairing with [Groth16VerifyingKey::verify_batch].
+//!
+//! Build the bindings with `wasm-pack build verifier-wasm --target web`, and run the tests with
+//! `wasm-pack test verifier-wasm --node`.
+
+mod calldata;
+mod groth16;
+
+pub use bn::Fr;
+pub use calldata::*;
+pub use groth16::*;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Verifies a Groth16 proof, encoded as the `uint256[8]` of the Solidity verifier, for the public
+/// inputs encoded as consecutive `uint256`, with the verifying key encoded as in
+/// [Groth16VerifyingKey::from_bytes]. Returns false for malformed inputs as well.
+#[wasm_bindgen]
+pub fn verify_groth16(proof_bytes: &[u8], public_inputs: &[u8], vk_bytes: &[u8]) -> bool {
+    try_verify_groth16(proof_bytes, public_inputs, vk_bytes).is_ok()
+}
+
+/// Verifies a Groth16 proof given the calldata of the Solidity verifier's `verifyProof`, with or
+/// without its function selector.
+#[wasm_bindgen]
+pub fn verify_groth16_calldata(calldata: &[u8], vk_bytes: &[u8]) -> bool {
+    decode_calldata(calldata)
+        .and_then(|(proof, public_inputs)| {
+            Groth16VerifyingKey::from_bytes(vk_bytes)?.verify(&proof, &public_inputs)
+        })
+        .is_ok()
+}
+
+/// Like [verify_groth16], but returns the reason the proof was rejected.
+pub fn try_verify_groth16(
+    proof_bytes: &[u8],
+    public_inputs: &[u8],
+    vk_bytes: &[u8],
+) -> Result<(), Groth16Error> {
+    let proof = Groth16Proof::from_bytes(proof_bytes)?;
+    let public_inputs = decode_public_inputs(public_inputs)?;
+    Groth16VerifyingKey::from_bytes(vk_bytes)?.verify(&proof, &public_inputs)
+}
@@ -0,0 +1,73 @@
+//! Checks the bindings against the fixtures. Run with `wasm-pack test --node`, or with
+//! `cargo test` natively.
+
+use sp1_verifier_wasm::{
+    try_verify_groth16, verify_groth16, verify_groth16_calldata, Groth16Error, PROOF_LEN, WORD_LEN,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn fixture(hex: &str) -> Vec<u8> {
+    hex::decode(hex.trim()).unwrap()
+}
+
+fn vk() -> Vec<u8> {
+    fixture(include_str!("../fixtures/vk.hex"))
+}
+
+fn proof() -> Vec<u8> {
+    fixture(include_str!("../fixtures/proof.hex"))
+}
+
+fn public_inputs() -> Vec<u8> {
+    fixture(include_str!("../fixtures/public_inputs.hex"))
+}
+
+fn calldata() -> Vec<u8> {
+    fixture(include_str!("../fixtures/calldata.hex"))
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_verify_fixture() {
+    assert!(verify_groth16(&proof(), &public_inputs(), &vk()));
+    assert!(verify_groth16_calldata(&calldata(), &vk()));
+    // Without the selector.
+    assert!(verify_groth16_calldata(&calldata()[4..], &vk()));
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_reject_wrong_public_inputs() {
+    let mut public_inputs = public_inputs();
+    public_inputs[2 * WORD_LEN - 1] ^= 1;
+    assert!(!verify_groth16(&proof(), &public_inputs, &vk()));
+    assert_eq!(
+        try_verify_groth16(&proof(), &public_inputs, &vk()),
+        Err(Groth16Error::VerificationFailed)
+    );
+
+    let mut calldata = calldata();
+    let len = calldata.len();
+    calldata[len - 1] ^= 1;
+    assert!(!verify_groth16_calldata(&calldata, &vk()));
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_reject_tampered_proof() {
+    // Swapping `a` and `c` keeps both points on the curve.
+    let mut proof = proof();
+    let (a, rest) = proof.split_at_mut(2 * WORD_LEN);
+    a.swap_with_slice(&mut rest[PROOF_LEN - 4 * WORD_LEN..]);
+    assert_eq!(
+        try_verify_groth16(&proof, &public_inputs(), &vk()),
+        Err(Groth16Error::VerificationFailed)
+    );
+
+    let mut proof = self::proof();
+    proof[0] ^= 1;
+    assert!(!verify_groth16(&proof, &public_inputs(), &vk()));
+    assert!(!verify_groth16(&[], &public_inputs(), &vk()));
+    assert!(!verify_groth16(
+        &self::proof(),
+        &public_inputs(),
+        &vk()[1..]
+    ));
+}
@@ -0,0 +1,196 @@
+//! Command line arguments and environment variables of the program.
+//!
+//! The host writes them with `SP1Stdin::write_args` or `SP1Stdin::write_args_with_env` as the
+//! first input buffer, and a program declared with `entrypoint!(main, args)` or
+//! `entrypoint!(main, args, env)` receives them as the parameters of its `main`. Since they are an
+//! input buffer like any other, they are part of the input digest committed with the
+//! `commit-input` feature.
+//!
+//! The encoding is the number of arguments followed by each argument as `len || bytes`, then the
+//! number of environment variables followed by each of them in key order as
+//! `key_len || key || value_len || value`, where all lengths are little-endian `u32`s.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The largest encoding of the arguments and environment. Must match `MAX_ARGS_LEN` in
+/// `core/src/io.rs`.
+pub const MAX_ARGS_LEN: usize = 1 << 16;
+
+/// An error decoding the arguments and environment of the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgsError {
+    /// The encoding is longer than [MAX_ARGS_LEN].
+    TooLarge { len: usize },
+    /// The encoding ends before `what`.
+    Truncated { what: String },
+    /// `what` is not valid UTF-8.
+    InvalidUtf8 { what: String },
+    /// The environment variable `key` is out of order or repeated.
+    UnsortedEnv { key: String },
+    /// The encoding has bytes after the environment.
+    TrailingBytes { len: usize },
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge { len } => write!(
+                f,
+                "the arguments have {} bytes, more than the limit of {}",
+                len, MAX_ARGS_LEN
+            ),
+            Self::Truncated { what } => write!(f, "the arguments end before {}", what),
+            Self::InvalidUtf8 { what } => write!(f, "{} is not valid UTF-8", what),
+            Self::UnsortedEnv { key } => {
+                write!(
+                    f,
+                    "environment variable {} is out of order or repeated",
+                    key
+                )
+            }
+            Self::TrailingBytes { len } => {
+                write!(f, "{} trailing bytes after the environment", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// Decodes the arguments and environment written by the host.
+pub fn decode_args(bytes: &[u8]) -> Result<(Vec<String>, BTreeMap<String, String>), ArgsError> {
+    if bytes.len() > MAX_ARGS_LEN {
+        return Err(ArgsError::TooLarge { len: bytes.len() });
+    }
+    let mut reader = Reader { rest: bytes };
+
+    let argc = reader.len(|| "the number of arguments".to_string())?;
+    let mut args = Vec::new();
+    for i in 0..argc {
+        args.push(reader.string(|| format!("argument {}", i))?);
+    }
+
+    let envc = reader.len(|| "the number of environment variables".to_string())?;
+    let mut env = BTreeMap::new();
+    for i in 0..envc {
+        let key = reader.string(|| format!("the key of environment variable {}", i))?;
+        let value = reader.string(|| format!("environment variable {}", key))?;
+        if env.last_key_value().is_some_and(|(last, _)| *last >= key) {
+            return Err(ArgsError::UnsortedEnv { key });
+        }
+        env.insert(key, value);
+    }
+
+    if !reader.rest.is_empty() {
+        return Err(ArgsError::TrailingBytes {
+            len: reader.rest.len(),
+        });
+    }
+    Ok((args, env))
+}
+
+/// A reader of the length-prefixed fields of the encoding, where `what` names the field read.
+struct Reader<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize, what: impl Fn() -> String) -> Result<&'a [u8], ArgsError> {
+        if self.rest.len() < len {
+            return Err(ArgsError::Truncated { what: what() });
+        }
+        let (bytes, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(bytes)
+    }
+
+    fn len(&mut self, what: impl Fn() -> String) -> Result<usize, ArgsError> {
+        let bytes = self.bytes(4, what)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    fn string(&mut self, what: impl Fn() -> String) -> Result<String, ArgsError> {
+        let len = self.len(&what)?;
+        let bytes = self.bytes(len, &what)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ArgsError::InvalidUtf8 { what: what() })
+    }
+}
+
+/// Reads the arguments and environment from the first input buffer, panicking with the
+/// [ArgsError] if they are invalid. Called by the entrypoint before `main`.
+pub fn read_args() -> (Vec<String>, BTreeMap<String, String>) {
+    let bytes = crate::io::read_vec();
+    decode_args(&bytes).unwrap_or_else(|err| panic!("invalid program arguments: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(args: &[&str], env: &[(&str, &str)]) -> Vec<u8> {
+        fn push(bytes: &mut Vec<u8>, data: &str) {
+            bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(data.as_bytes());
+        }
+        let mut bytes = (args.len() as u32).to_le_bytes().to_vec();
+        for arg in args {
+            push(&mut bytes, arg);
+        }
+        bytes.extend_from_slice(&(env.len() as u32).to_le_bytes());
+        for (key, value) in env {
+            push(&mut bytes, key);
+            push(&mut bytes, value);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_args() {
+        let (args, env) = decode_args(&encode(&["tool", "--verbose", ""], &[])).unwrap();
+        assert_eq!(args, ["tool", "--verbose", ""]);
+        assert!(env.is_empty());
+
+        let (args, env) = decode_args(&encode(&[], &[("HOME", "/"), ("LANG", "C")])).unwrap();
+        assert!(args.is_empty());
+        assert_eq!(env["HOME"], "/");
+        assert_eq!(env["LANG"], "C");
+    }
+
+    #[test]
+    fn test_decode_invalid_args() {
+        // The last argument is followed by the number of environment variables.
+        let mut bytes = encode(&["ok", "??"], &[]);
+        let len = bytes.len();
+        bytes[len - 6..len - 4].copy_from_slice(&[0xff, 0xfe]);
+        assert_eq!(
+            decode_args(&bytes),
+            Err(ArgsError::InvalidUtf8 {
+                what: "argument 1".to_string()
+            })
+        );
+        assert_eq!(
+            decode_args(&encode(&[], &[("B", ""), ("A", "")])),
+            Err(ArgsError::UnsortedEnv {
+                key: "A".to_string()
+            })
+        );
+        let bytes = encode(&["tool"], &[]);
+        assert_eq!(
+            decode_args(&bytes[..6]),
+            Err(ArgsError::Truncated {
+                what: "argument 0".to_string()
+            })
+        );
+        assert_eq!(
+            decode_args(&[&bytes[..], &[0]].concat()),
+            Err(ArgsError::TrailingBytes { len: 1 })
+        );
+        assert_eq!(
+            decode_args(&vec![0; MAX_ARGS_LEN + 1]),
+            Err(ArgsError::TooLarge {
+                len: MAX_ARGS_LEN + 1
+            })
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! The atomic intrinsics of the target, as plain loads and stores.
+//!
+//! The target has no atomic instructions, so the compiler lowers the operations of
+//! `core::sync::atomic` to calls to the `__atomic_*` functions of libatomic. A program runs on a
+//! single thread without interrupts, so an operation can't be observed halfway through: reading,
+//! computing and writing back is atomic, whatever the memory ordering.
+
+macro_rules! atomics {
+    ($ty:ty, $load:ident, $store:ident, $exchange:ident, $compare_exchange:ident, $(($fetch:ident, $op:expr)),* $(,)?) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn $load(ptr: *const $ty, _ordering: i32) -> $ty {
+            core::ptr::read_volatile(ptr)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $store(ptr: *mut $ty, value: $ty, _ordering: i32) {
+            core::ptr::write_volatile(ptr, value)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $exchange(ptr: *mut $ty, value: $ty, _ordering: i32) -> $ty {
+            let previous = core::ptr::read_volatile(ptr);
+            core::ptr::write_volatile(ptr, value);
+            previous
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $compare_exchange(
+            ptr: *mut $ty,
+            expected: *mut $ty,
+            desired: $ty,
+            _success: i32,
+            _failure: i32,
+        ) -> bool {
+            let current = core::ptr::read_volatile(ptr);
+            if current == *expected {
+                core::ptr::write_volatile(ptr, desired);
+                true
+            } else {
+                *expected = current;
+                false
+            }
+        }
+
+        $(
+            #[no_mangle]
+            pub unsafe extern "C" fn $fetch(ptr: *mut $ty, value: $ty, _ordering: i32) -> $ty {
+                let op: fn($ty, $ty) -> $ty = $op;
+                let previous = core::ptr::read_volatile(ptr);
+                core::ptr::write_volatile(ptr, op(previous, value));
+                previous
+            }
+        )*
+    };
+}
+
+atomics!(
+    u8,
+    __atomic_load_1,
+    __atomic_store_1,
+    __atomic_exchange_1,
+    __atomic_compare_exchange_1,
+    (__atomic_fetch_add_1, |a, b| a.wrapping_add(b)),
+    (__atomic_fetch_sub_1, |a, b| a.wrapping_sub(b)),
+    (__atomic_fetch_and_1, |a, b| a & b),
+    (__atomic_fetch_or_1, |a, b| a | b),
+    (__atomic_fetch_xor_1, |a, b| a ^ b),
+    (__atomic_fetch_nand_1, |a, b| !(a & b)),
+);
+
+atomics!(
+    u16,
+    __atomic_load_2,
+    __atomic_store_2,
+    __atomic_exchange_2,
+    __atomic_compare_exchange_2,
+    (__atomic_fetch_add_2, |a, b| a.wrapping_add(b)),
+    (__atomic_fetch_sub_2, |a, b| a.wrapping_sub(b)),
+    (__atomic_fetch_and_2, |a, b| a & b),
+    (__atomic_fetch_or_2, |a, b| a | b),
+    (__atomic_fetch_xor_2, |a, b| a ^ b),
+    (__atomic_fetch_nand_2, |a, b| !(a & b)),
+);
+
+atomics!(
+    u32,
+    __atomic_load_4,
+    __atomic_store_4,
+    __atomic_exchange_4,
+    __atomic_compare_exchange_4,
+    (__atomic_fetch_add_4, |a, b| a.wrapping_add(b)),
+    (__atomic_fetch_sub_4, |a, b| a.wrapping_sub(b)),
+    (__atomic_fetch_and_4, |a, b| a & b),
+    (__atomic_fetch_or_4, |a, b| a | b),
+    (__atomic_fetch_xor_4, |a, b| a ^ b),
+    (__atomic_fetch_nand_4, |a, b| !(a & b)),
+);
+
+atomics!(
+    u64,
+    __atomic_load_8,
+    __atomic_store_8,
+    __atomic_exchange_8,
+    __atomic_compare_exchange_8,
+    (__atomic_fetch_add_8, |a, b| a.wrapping_add(b)),
+    (__atomic_fetch_sub_8, |a, b| a.wrapping_sub(b)),
+    (__atomic_fetch_and_8, |a, b| a & b),
+    (__atomic_fetch_or_8, |a, b| a | b),
+    (__atomic_fetch_xor_8, |a, b| a ^ b),
+    (__atomic_fetch_nand_8, |a, b| !(a & b)),
+);
+
+/// A fence orders nothing on a single thread.
+#[no_mangle]
+pub extern "C" fn __atomic_thread_fence(_ordering: i32) {}
+
+#[no_mangle]
+pub extern "C" fn __atomic_signal_fence(_ordering: i32) {}
+
+/// Every size is lock-free, see the module documentation.
+#[no_mangle]
+pub extern "C" fn __atomic_is_lock_free(_size: usize, _ptr: *const u8) -> bool {
+    true
+}
@@ -0,0 +1,473 @@
+//! A read-only in-memory filesystem, for crates that load their inputs from files.
+//!
+//! The host writes the files with `SP1Stdin::write_file_tree`, and the functions of this module
+//! mirror the ones of `std::fs` with the same names, which are unsupported in the zkVM. The files
+//! are stored in the config, so the program commits to their contents through the config digest,
+//! and they are only deserialized the first time the program accesses the filesystem.
+//!
+//! Paths are normalized like on the host: the program has no current directory, so relative
+//! paths are relative to the root, and `.`, `..` and repeated separators are resolved lexically.
+//! Missing files and directories are reported with the same error kinds and messages as `std`.
+//!
+//! With the `fs-syscalls` feature, files opened with `open_fd` can also be read through the
+//! `sys_read` syscall used by the standard library for raw file descriptors.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The config key holding the file tree. Must match `FILE_TREE_CONFIG_KEY` in `core/src/io.rs`.
+pub const FILE_TREE_CONFIG_KEY: &str = "sp1.fs";
+
+static FILE_TREE: OnceLock<FileTree> = OnceLock::new();
+
+/// The files of the filesystem, indexed by their normalized path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileTree {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+/// Metadata about a file or a directory, see [metadata].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    is_dir: bool,
+    len: u64,
+}
+
+/// An entry of a directory, see [read_dir].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    path: PathBuf,
+    metadata: Metadata,
+}
+
+/// An iterator over the entries of a directory, in a deterministic order.
+#[derive(Debug)]
+pub struct ReadDir {
+    entries: std::vec::IntoIter<DirEntry>,
+}
+
+/// An open file of the filesystem, see [File::open].
+#[derive(Debug, Clone)]
+pub struct File {
+    data: &'static [u8],
+    pos: u64,
+}
+
+/// An entry of the file tree, as found by [FileTree::lookup].
+enum Entry<'a> {
+    File(&'a [u8]),
+    Dir(String),
+}
+
+fn not_found() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "No such file or directory (os error 2)",
+    )
+}
+
+fn not_a_directory() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "Not a directory (os error 20)")
+}
+
+fn is_a_directory() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "Is a directory (os error 21)")
+}
+
+/// Normalizes a path to an absolute path without `.`, `..` or repeated separators. Must match
+/// `normalize_file_path` in `core/src/io.rs`.
+fn normalize(path: &str) -> String {
+    let mut components = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            name => components.push(name),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
+
+impl FileTree {
+    /// Decodes the file tree written by `SP1Stdin::write_file_tree`.
+    pub fn decode(bytes: &[u8]) -> bincode::Result<Self> {
+        let files = bincode::deserialize(bytes)?;
+        Ok(Self { files })
+    }
+
+    /// The file tree written by the host, decoded on first use. It is empty if the host did not
+    /// write one.
+    pub fn global() -> &'static FileTree {
+        FILE_TREE.get_or_init(|| match crate::io::config(FILE_TREE_CONFIG_KEY) {
+            Some(bytes) => Self::decode(&bytes).expect("invalid file tree"),
+            None => Self::default(),
+        })
+    }
+
+    fn lookup(&self, path: &Path) -> io::Result<Entry<'_>> {
+        let path = path.to_str().ok_or_else(not_found)?;
+        let normalized = normalize(path);
+        if let Some(data) = self.files.get(&normalized) {
+            // Like `std`, a trailing separator requires the path to be a directory.
+            if path.ends_with('/') {
+                return Err(not_a_directory());
+            }
+            return Ok(Entry::File(data));
+        }
+        let prefix = Self::dir_prefix(&normalized);
+        let has_children = self
+            .files
+            .range(prefix.clone()..)
+            .next()
+            .is_some_and(|(child, _)| child.starts_with(&prefix));
+        if normalized == "/" || has_children {
+            return Ok(Entry::Dir(normalized));
+        }
+
+        // Like `std`, a path below a file is not a directory rather than missing.
+        let below_file = normalized
+            .match_indices('/')
+            .any(|(i, _)| self.files.contains_key(&normalized[..i]));
+        if below_file {
+            Err(not_a_directory())
+        } else {
+            Err(not_found())
+        }
+    }
+
+    /// The prefix of the paths of the files below the directory `dir`.
+    fn dir_prefix(dir: &str) -> String {
+        if dir == "/" {
+            dir.to_string()
+        } else {
+            format!("{}/", dir)
+        }
+    }
+
+    /// The contents of the file at `path`.
+    pub fn read(&self, path: impl AsRef<Path>) -> io::Result<&[u8]> {
+        match self.lookup(path.as_ref())? {
+            Entry::File(data) => Ok(data),
+            Entry::Dir(_) => Err(is_a_directory()),
+        }
+    }
+
+    /// The metadata of the file or directory at `path`.
+    pub fn metadata(&self, path: impl AsRef<Path>) -> io::Result<Metadata> {
+        Ok(match self.lookup(path.as_ref())? {
+            Entry::File(data) => Metadata {
+                is_dir: false,
+                len: data.len() as u64,
+            },
+            Entry::Dir(_) => Metadata {
+                is_dir: true,
+                len: 0,
+            },
+        })
+    }
+
+    /// The entries of the directory at `path`.
+    pub fn read_dir(&self, path: impl AsRef<Path>) -> io::Result<ReadDir> {
+        let dir = match self.lookup(path.as_ref())? {
+            Entry::File(_) => return Err(not_a_directory()),
+            Entry::Dir(dir) => dir,
+        };
+        let prefix = Self::dir_prefix(&dir);
+        let mut entries: Vec<DirEntry> = Vec::new();
+        for (path, data) in self.files.range(prefix.clone()..) {
+            let Some(rest) = path.strip_prefix(&prefix) else {
+                break;
+            };
+            // Files in subdirectories are listed once as their topmost subdirectory.
+            let (name, metadata) = match rest.split_once('/') {
+                Some((name, _)) => (
+                    name,
+                    Metadata {
+                        is_dir: true,
+                        len: 0,
+                    },
+                ),
+                None => (
+                    rest,
+                    Metadata {
+                        is_dir: false,
+                        len: data.len() as u64,
+                    },
+                ),
+            };
+            let path = PathBuf::from(format!("{}{}", prefix, name));
+            if entries.last().map(|entry| &entry.path) != Some(&path) {
+                entries.push(DirEntry { path, metadata });
+            }
+        }
+        Ok(ReadDir {
+            entries: entries.into_iter(),
+        })
+    }
+}
+
+impl Metadata {
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    pub fn is_file(&self) -> bool {
+        !self.is_dir
+    }
+
+    /// The size of the file in bytes, zero for a directory.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl DirEntry {
+    /// The full path of the entry.
+    pub fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    pub fn file_name(&self) -> OsString {
+        self.path.file_name().unwrap_or_default().to_os_string()
+    }
+
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        Ok(self.metadata)
+    }
+}
+
+impl Iterator for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(Ok)
+    }
+}
+
+impl File {
+    /// Opens the file at `path` for reading.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        let data = FileTree::global().read(path)?;
+        Ok(File { data, pos: 0 })
+    }
+
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        Ok(Metadata {
+            is_dir: false,
+            len: self.data.len() as u64,
+        })
+    }
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = self.pos.min(self.data.len() as u64) as usize;
+        let len = buf.len().min(self.data.len() - start);
+        buf[..len].copy_from_slice(&self.data[start..start + len]);
+        self.pos += len as u64;
+        Ok(len)
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => (0, offset as i64),
+            SeekFrom::End(offset) => (self.data.len() as u64, offset),
+            SeekFrom::Current(offset) => (self.pos, offset),
+        };
+        match base.checked_add_signed(offset) {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+/// Reads the contents of a file, like `std::fs::read`.
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    FileTree::global().read(path).map(<[u8]>::to_vec)
+}
+
+/// Reads the contents of a file into a string, like `std::fs::read_to_string`.
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let data = FileTree::global().read(path)?;
+    String::from_utf8(data.to_vec()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+    })
+}
+
+/// The metadata of a file or a directory, like `std::fs::metadata`.
+pub fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    FileTree::global().metadata(path)
+}
+
+/// The entries of a directory, like `std::fs::read_dir`.
+pub fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
+    FileTree::global().read_dir(path)
+}
+
+/// Whether a file or a directory exists at `path`, like `Path::exists`.
+pub fn exists(path: impl AsRef<Path>) -> bool {
+    metadata(path).is_ok()
+}
+
+#[cfg(feature = "fs-syscalls")]
+mod fds {
+    use std::collections::BTreeMap;
+    use std::io::{self, Read};
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    use super::File;
+
+    /// The first file descriptor handed out by [open_fd], above the ones of the runtime hooks.
+    const FIRST_FD: u32 = 1 << 16;
+
+    static FILES: Mutex<BTreeMap<u32, File>> = Mutex::new(BTreeMap::new());
+
+    /// Opens the file at `path` and returns a raw file descriptor that can be read with the
+    /// `sys_read` syscall until it is closed with [close_fd].
+    pub fn open_fd(path: impl AsRef<Path>) -> io::Result<u32> {
+        let file = File::open(path)?;
+        let mut files = FILES.lock().unwrap();
+        let fd = files.keys().next_back().map_or(FIRST_FD, |fd| fd + 1);
+        files.insert(fd, file);
+        Ok(fd)
+    }
+
+    /// Closes a file descriptor returned by [open_fd].
+    pub fn close_fd(fd: u32) {
+        FILES.lock().unwrap().remove(&fd);
+    }
+
+    /// Reads up to `nrequested` bytes of the file opened as `fd` into `recv_buf`, returning the
+    /// number of bytes read. Standard input is always empty, and unknown file descriptors panic.
+    ///
+    /// # Safety
+    ///
+    /// Make sure that `recv_buf` has at least `nrequested` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn sys_read(fd: u32, recv_buf: *mut u8, nrequested: usize) -> usize {
+        if fd == 0 {
+            return 0;
+        }
+        let mut files = FILES.lock().unwrap();
+        let file = files
+            .get_mut(&fd)
+            .unwrap_or_else(|| panic!("read from unknown file descriptor {}", fd));
+        let buf = core::slice::from_raw_parts_mut(recv_buf, nrequested);
+        file.read(buf).unwrap()
+    }
+}
+
+#[cfg(feature = "fs-syscalls")]
+pub use fds::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> FileTree {
+        let files = [
+            ("/etc/app/config.toml", "name = \"app\"\n"),
+            ("/etc/app/rules/a.toml", ""),
+            ("/etc/app/rules/b.toml", "b = 2"),
+            ("/etc/hosts", "localhost"),
+        ];
+        let files = files
+            .iter()
+            .map(|(path, data)| (path.to_string(), data.as_bytes().to_vec()))
+            .collect::<BTreeMap<_, _>>();
+        FileTree::decode(&bincode::serialize(&files).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_read_normalized_paths() {
+        let tree = tree();
+        for path in [
+            "/etc/hosts",
+            "etc/hosts",
+            "//etc/./hosts",
+            "/etc/app/../hosts",
+            "/../etc/hosts",
+        ] {
+            assert_eq!(tree.read(path).unwrap(), b"localhost", "{}", path);
+        }
+        assert_eq!(tree.metadata("/etc/app/rules/b.toml").unwrap().len(), 5);
+        assert!(tree.metadata("/etc/app/rules/").unwrap().is_dir());
+        assert!(tree.metadata("/").unwrap().is_dir());
+    }
+
+    #[test]
+    fn test_errors_mirror_std() {
+        let tree = tree();
+        let err = tree.read("/etc/missing.toml").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(err.to_string(), "No such file or directory (os error 2)");
+        assert_eq!(
+            tree.metadata("/etc/hosts/nested").unwrap_err().to_string(),
+            "Not a directory (os error 20)"
+        );
+        assert_eq!(
+            tree.read("/etc/app").unwrap_err().to_string(),
+            "Is a directory (os error 21)"
+        );
+        assert_eq!(
+            tree.read("/etc/hosts/").unwrap_err().to_string(),
+            "Not a directory (os error 20)"
+        );
+        assert!(tree.read_dir("/etc/hosts").is_err());
+    }
+
+    #[test]
+    fn test_read_dir() {
+        let tree = tree();
+        let entries = tree
+            .read_dir("/etc")
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.file_name(), entry.metadata().unwrap().is_dir())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (OsString::from("app"), true),
+                (OsString::from("hosts"), false)
+            ]
+        );
+
+        let paths = tree
+            .read_dir("etc/app/rules")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/etc/app/rules/a.toml"),
+                PathBuf::from("/etc/app/rules/b.toml")
+            ]
+        );
+        assert_eq!(tree.read_dir("/").unwrap().count(), 1);
+    }
+}
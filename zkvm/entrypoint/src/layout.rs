@@ -0,0 +1,17 @@
+//! The memory layout of the guest, mirrored by `sp1_core::runtime::MemoryLayout` on the host.
+
+/// The address the stack grows down from.
+pub const STACK_TOP: u32 = 0x0020_0400;
+
+/// The number of bytes below [STACK_TOP] reserved for the stack.
+pub const STACK_MAX_SIZE: u32 = 0x0010_0000;
+
+/// The address the heap ends at.
+pub const HEAP_END: u32 = 0x0C00_0000;
+
+/// The end of the memory reserved for the buffers of the syscalls, which starts at [HEAP_END].
+pub const SYSCALL_BUFFERS_END: u32 = 0x0D00_0000;
+
+/// The end of the memory reserved for the data placed by the host, which starts at
+/// [SYSCALL_BUFFERS_END].
+pub const INPUT_REGION_END: u32 = 0x1000_0000;
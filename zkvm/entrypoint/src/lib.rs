@@ -1,5 +1,12 @@
+pub mod args;
+pub mod fs;
 pub mod heap;
+pub mod layout;
+pub mod public_values;
 pub mod syscalls;
+pub mod testing;
+pub mod thread;
+pub mod time;
 pub mod io {
     pub use sp1_precompiles::io::*;
 }
@@ -9,11 +16,40 @@ pub mod precompiles {
 
 extern crate alloc;
 
+/// Declares the entrypoint of the program.
+///
+/// - `entrypoint!(main)` calls `fn main()`.
+/// - `entrypoint!(main, args)` calls `fn main(args: Vec<String>)` with the arguments written on
+///   the host with `SP1Stdin::write_args`.
+/// - `entrypoint!(main, args, env)` calls
+///   `fn main(args: Vec<String>, env: BTreeMap<String, String>)` with the arguments and the
+///   environment written on the host with `SP1Stdin::write_args_with_env`.
+///
+/// The arguments are read from the first input buffer before `main` is called, see [args].
 #[macro_export]
 macro_rules! entrypoint {
     ($path:path) => {
         const ZKVM_ENTRY: fn() = $path;
 
+        $crate::entrypoint!(@main ZKVM_ENTRY());
+    };
+    ($path:path, args) => {
+        const ZKVM_ENTRY: fn(::std::vec::Vec<::std::string::String>) = $path;
+
+        $crate::entrypoint!(@main ZKVM_ENTRY($crate::args::read_args().0));
+    };
+    ($path:path, args, env) => {
+        const ZKVM_ENTRY: fn(
+            ::std::vec::Vec<::std::string::String>,
+            ::std::collections::BTreeMap<::std::string::String, ::std::string::String>,
+        ) = $path;
+
+        $crate::entrypoint!(@main {
+            let (args, env) = $crate::args::read_args();
+            ZKVM_ENTRY(args, env)
+        });
+    };
+    (@main $call:expr) => {
         use $crate::heap::SimpleAlloc;
 
         #[global_allocator]
@@ -23,12 +59,16 @@ macro_rules! entrypoint {
 
             #[no_mangle]
             fn main() {
-                super::ZKVM_ENTRY()
+                use super::ZKVM_ENTRY;
+                $call
             }
         }
     };
 }
 
+#[cfg(target_os = "zkvm")]
+mod atomics;
+
 #[cfg(all(target_os = "zkvm", feature = "libm"))]
 mod libm;
 
@@ -55,16 +95,46 @@ mod zkvm {
 
     pub static mut PUBLIC_VALUES_HASHER: Option<Sha256> = None;
 
+    /// The digest of the config provided by the host, committed on halt, see
+    /// `SP1Stdin::config_digest` on the host.
+    pub static mut CONFIG_DIGEST: [u8; 32] = [0; 32];
+
+    /// Hashes the input buffers as they are read, see `SP1Stdin::digest` on the host.
+    #[cfg(feature = "commit-input")]
+    pub static mut INPUT_HASHER: Option<Sha256> = None;
+
+    /// Hashes the entropy consumed by `getrandom`, created on the first read. Folded into the
+    /// input digest on halt, see `SP1Stdin::digest_with_entropy` on the host.
+    #[cfg(feature = "commit-input")]
+    pub static mut ENTROPY_HASHER: Option<Sha256> = None;
+
+    /// Whether `getrandom` has read from the entropy pool, after which it never falls back to
+    /// `sys_rand`.
+    static mut ENTROPY_USED: bool = false;
+
     #[cfg(not(feature = "interface"))]
     #[no_mangle]
     unsafe extern "C" fn __start() {
         {
             PUBLIC_VALUES_HASHER = Some(Sha256::new());
+            #[cfg(feature = "commit-input")]
+            {
+                INPUT_HASHER = Some(Sha256::new());
+            }
             #[cfg(feature = "verify")]
             {
                 DEFERRED_PROOFS_DIGEST = Some([BabyBear::zero(); 8]);
             }
 
+            // Hash the config provided by the host, or the empty config if there is none. The
+            // digest is committed on halt so that verifiers can check the exact configuration the
+            // program was run with.
+            let config = sp1_precompiles::io::init_config().unwrap_or_default();
+            let mut hasher = Sha256::new();
+            hasher.update(sp1_precompiles::io::CONFIG_DIGEST_DOMAIN);
+            hasher.update(&config);
+            CONFIG_DIGEST = hasher.finalize().into();
+
             extern "C" {
                 fn main();
             }
@@ -74,7 +144,7 @@ mod zkvm {
         syscall_halt(0);
     }
 
-    static STACK_TOP: u32 = 0x0020_0400;
+    static STACK_TOP: u32 = crate::layout::STACK_TOP;
 
     core::arch::global_asm!(include_str!("memset.s"));
     core::arch::global_asm!(include_str!("memcpy.s"));
@@ -95,9 +165,39 @@ mod zkvm {
         sym STACK_TOP
     );
 
+    /// Serves `getrandom` from the entropy pool written with `SP1Stdin::write_entropy`.
+    ///
+    /// Requesting more bytes than the pool has left is an error, including when the host provided
+    /// no entropy at all. With the `insecure-rand` feature, programs run without any entropy fall
+    /// back to the predictable fixed-seed `sys_rand` instead.
     fn zkvm_getrandom(s: &mut [u8]) -> Result<(), Error> {
         unsafe {
-            crate::syscalls::sys_rand(s.as_mut_ptr(), s.len());
+            let remaining = crate::syscalls::syscall_entropy_len();
+            if remaining == 0 && !ENTROPY_USED {
+                if cfg!(feature = "insecure-rand") {
+                    crate::syscalls::sys_rand(s.as_mut_ptr(), s.len());
+                    return Ok(());
+                }
+                panic!(
+                    "getrandom requested {} bytes of entropy, but the host provided none: write \
+                     some with `SP1Stdin::write_entropy`, or enable the `insecure-rand` feature \
+                     of sp1-zkvm to use a fixed seed",
+                    s.len()
+                );
+            }
+            if s.len() > remaining {
+                panic!(
+                    "getrandom requested {} bytes of entropy, but only {} are left in the pool",
+                    s.len(),
+                    remaining
+                );
+            }
+            // The runtime writes the entropy to memory that has never been written to, so read
+            // it into a fresh word-aligned allocation before copying it out.
+            let buf = crate::syscalls::sys_alloc_aligned(s.len(), 4);
+            crate::syscalls::syscall_entropy_read(buf, s.len());
+            s.copy_from_slice(core::slice::from_raw_parts(buf, s.len()));
+            ENTROPY_USED = true;
         }
 
         Ok(())
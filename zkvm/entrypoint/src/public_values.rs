@@ -0,0 +1,191 @@
+//! Typed public values, declared with [sp1_public_values!](crate::sp1_public_values).
+//!
+//! The declaration is embedded in the [PUBLIC_VALUES_SECTION] section of the ELF, from which
+//! `sp1_helper::generate_public_values_bindings` generates the host-side struct and its parser, so
+//! that the host cannot read the public values with another layout than the one the guest commits.
+//!
+//! The declaration is a versioned header line, the name of the struct, and a line per field with
+//! its name and type as written in the macro:
+//!
+//! ```text
+//! sp1-public-values v1
+//! struct Outputs
+//! n u32
+//! digest [u8; 32]
+//! ```
+
+/// The ELF section holding the public values declaration. Must match `PUBLIC_VALUES_SECTION` in
+/// `helper/src/public_values.rs`.
+pub const PUBLIC_VALUES_SECTION: &str = ".sp1_public_values";
+
+/// A type that can be declared as a public value. Values are committed as their little-endian
+/// bytes, which is also how `bincode` serializes them.
+pub trait PublicValue: Copy {
+    /// Appends the encoding of the value to `bytes`.
+    fn encode(&self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! impl_public_value_int {
+    ($($ty:ty),*) => {
+        $(
+            impl PublicValue for $ty {
+                fn encode(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_public_value_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl PublicValue for bool {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self as u8);
+    }
+}
+
+impl<const N: usize> PublicValue for [u8; N] {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self);
+    }
+}
+
+/// The 64-bit FNV-1a digest of a declaration, embedded in front of it. Must match
+/// `declaration_digest` in `helper/src/public_values.rs`.
+pub const fn declaration_digest(declaration: &[u8]) -> u64 {
+    let mut digest = 0xcbf2_9ce4_8422_2325u64;
+    let mut i = 0;
+    while i < declaration.len() {
+        digest ^= declaration[i] as u64;
+        digest = digest.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    digest
+}
+
+/// The contents of the [PUBLIC_VALUES_SECTION]: the little-endian digest of the declaration,
+/// followed by the declaration itself.
+pub const fn embed_declaration<const N: usize>(declaration: &str) -> [u8; N] {
+    let declaration = declaration.as_bytes();
+    assert!(N == declaration.len() + 8);
+    let mut section = [0u8; N];
+    let digest = declaration_digest(declaration).to_le_bytes();
+    let mut i = 0;
+    while i < 8 {
+        section[i] = digest[i];
+        i += 1;
+    }
+    while i < N {
+        section[i] = declaration[i - 8];
+        i += 1;
+    }
+    section
+}
+
+/// Declares the public values of the program as a struct of fields of the types implementing
+/// [PublicValue](crate::public_values::PublicValue), which it commits in declaration order with its
+/// `commit` method.
+///
+/// ```ignore
+/// sp1_zkvm::sp1_public_values! {
+///     pub struct Outputs {
+///         n: u32,
+///         fib: u64,
+///         digest: [u8; 32],
+///     }
+/// }
+///
+/// Outputs { n, fib, digest }.commit();
+/// ```
+///
+/// The declaration is embedded in the ELF, and the host generates its bindings from it with
+/// `sp1_helper::generate_public_values_bindings`. A program may only declare its public values
+/// once, and should not commit anything else.
+#[macro_export]
+macro_rules! sp1_public_values {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $vis struct $name {
+            $(pub $field: $ty,)*
+        }
+
+        impl $name {
+            /// Commits the fields to the public values, in declaration order.
+            pub fn commit(&self) {
+                const DECLARATION: &str = concat!(
+                    "sp1-public-values v1\n",
+                    "struct ", stringify!($name), "\n",
+                    $(stringify!($field), " ", stringify!($ty), "\n",)*
+                );
+
+                #[used]
+                #[link_section = ".sp1_public_values"]
+                static SECTION: [u8; DECLARATION.len() + 8] =
+                    $crate::public_values::embed_declaration(DECLARATION);
+
+                // Referencing the section keeps the linker from discarding it.
+                ::core::hint::black_box(&SECTION);
+
+                let mut bytes = ::std::vec::Vec::new();
+                $($crate::public_values::PublicValue::encode(&self.$field, &mut bytes);)*
+                $crate::io::commit_slice(&bytes);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::sp1_public_values! {
+        struct Outputs {
+            n: u32,
+            negative: i16,
+            done: bool,
+            digest: [u8; 4],
+        }
+    }
+
+    #[test]
+    fn test_embed_declaration() {
+        let declaration = "sp1-public-values v1\nstruct Outputs\nn u32\n";
+        let section: [u8; 45] = embed_declaration(declaration);
+        assert_eq!(
+            u64::from_le_bytes(section[..8].try_into().unwrap()),
+            declaration_digest(declaration.as_bytes())
+        );
+        assert_eq!(&section[8..], declaration.as_bytes());
+        // The FNV-1a test vectors.
+        assert_eq!(declaration_digest(b""), 0xcbf29ce484222325);
+        assert_eq!(declaration_digest(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_encode_public_values() {
+        let outputs = Outputs {
+            n: 7,
+            negative: -2,
+            done: true,
+            digest: [1, 2, 3, 4],
+        };
+        let mut bytes = Vec::new();
+        outputs.n.encode(&mut bytes);
+        outputs.negative.encode(&mut bytes);
+        outputs.done.encode(&mut bytes);
+        outputs.digest.encode(&mut bytes);
+        assert_eq!(bytes, [7, 0, 0, 0, 0xfe, 0xff, 1, 1, 2, 3, 4]);
+        assert_eq!(
+            bytes,
+            bincode::serialize(&(outputs.n, outputs.negative, outputs.done, outputs.digest))
+                .unwrap()
+        );
+    }
+}
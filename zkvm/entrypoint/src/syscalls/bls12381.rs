@@ -4,6 +4,9 @@ use core::arch::asm;
 /// Adds two Bls12381 points.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_add].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_bls12381_add(p: *mut u32, q: *const u32) {
@@ -24,6 +27,9 @@ pub extern "C" fn syscall_bls12381_add(p: *mut u32, q: *const u32) {
 /// Double a Bls12381 point.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_double].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_bls12381_double(p: *mut u32) {
@@ -42,6 +48,9 @@ pub extern "C" fn syscall_bls12381_double(p: *mut u32) {
 ///
 /// The first half of the input array should contain the X coordinate.
 /// The second half of the input array will be overwritten with the Y coordinate.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_decompress].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_bls12381_decompress(point: &mut [u8; 96], is_odd: bool) {
@@ -64,3 +73,96 @@ pub extern "C" fn syscall_bls12381_decompress(point: &mut [u8; 96], is_odd: bool
     #[cfg(not(target_os = "zkvm"))]
     unreachable!()
 }
+
+/// Adds two elements of the BLS12-381 base field, given as 12 little endian words.
+///
+/// Both elements must be reduced. The result is stored in the first element.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_fp_add].
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_bls12381_fp_add(x: *mut u32, y: *const u32) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::BLS12381_FP_ADD,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Subtracts the second element from the first of the BLS12-381 base field, given as 12 little endian words.
+///
+/// Both elements must be reduced. The result is stored in the first element.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_fp_sub].
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_bls12381_fp_sub(x: *mut u32, y: *const u32) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::BLS12381_FP_SUB,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Multiplies two elements of the BLS12-381 base field, given as 12 little endian words.
+///
+/// Both elements must be reduced. The result is stored in the first element.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_fp_mul].
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_bls12381_fp_mul(x: *mut u32, y: *const u32) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::BLS12381_FP_MUL,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Divides two elements of the BLS12-381 base field, given as 12 little endian words.
+///
+/// Both elements must be reduced, and the second one must not be zero. The result is stored in the
+/// first element.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bls12381_fp_div].
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_bls12381_fp_div(x: *mut u32, y: *const u32) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::BLS12381_FP_DIV,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
@@ -4,6 +4,9 @@ use core::arch::asm;
 /// Adds two Bn254 points.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bn254_add].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_bn254_add(p: *mut u32, q: *const u32) {
@@ -24,6 +27,9 @@ pub extern "C" fn syscall_bn254_add(p: *mut u32, q: *const u32) {
 /// Double a Bn254 point.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::bn254_double].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_bn254_double(p: *mut u32) {
@@ -4,6 +4,9 @@ use core::arch::asm;
 /// Adds two Edwards points.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::ed_add].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_ed_add(p: *mut u32, q: *mut u32) {
@@ -26,6 +29,9 @@ pub extern "C" fn syscall_ed_add(p: *mut u32, q: *mut u32) {
 /// The second half of the input array should contain the compressed Y point with the final bit as
 /// the sign bit. The first half of the input array will be overwritten with the decompressed point,
 /// and the sign bit will be removed.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::ed_decompress].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_ed_decompress(point: &mut [u8; 64]) {
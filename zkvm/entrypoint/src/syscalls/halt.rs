@@ -20,6 +20,9 @@ cfg_if! {
 pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
     #[cfg(target_os = "zkvm")]
     unsafe {
+        // Commit the hash chain of the chunked outputs, if the program committed any.
+        sp1_precompiles::io::finalize_chunked_commit();
+
         // When we halt, we retrieve the public values finalized digest.  This is the hash of all
         // the bytes written to the public values fd.
         let pv_digest_bytes = core::mem::take(&mut zkvm::PUBLIC_VALUES_HASHER)
@@ -43,6 +46,36 @@ pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
             asm!("ecall", in("t0") crate::syscalls::COMMIT, in("a0") i, in("a1") pv_digest_words[i]);
         }
 
+        // Commit the digest of the input buffers read by the program, or zero if the program does
+        // not commit its input.
+        cfg_if! {
+            if #[cfg(feature = "commit-input")] {
+                let mut input_digest_bytes = core::mem::take(&mut zkvm::INPUT_HASHER)
+                    .unwrap()
+                    .finalize();
+                if let Some(entropy_hasher) = core::mem::take(&mut zkvm::ENTROPY_HASHER) {
+                    let mut hasher = sha2::Sha256::new();
+                    hasher.update(input_digest_bytes);
+                    hasher.update(entropy_hasher.finalize());
+                    input_digest_bytes = hasher.finalize();
+                }
+                for (i, chunk) in input_digest_bytes.chunks_exact(4).enumerate() {
+                    let word = u32::from_le_bytes(chunk.try_into().unwrap());
+                    asm!("ecall", in("t0") crate::syscalls::COMMIT_INPUT, in("a0") i, in("a1") word);
+                }
+            } else {
+                for i in 0..PV_DIGEST_NUM_WORDS {
+                    asm!("ecall", in("t0") crate::syscalls::COMMIT_INPUT, in("a0") i, in("a1") 0);
+                }
+            }
+        }
+
+        // Commit the digest of the config the program was run with.
+        for (i, chunk) in zkvm::CONFIG_DIGEST.chunks_exact(4).enumerate() {
+            let word = u32::from_le_bytes(chunk.try_into().unwrap());
+            asm!("ecall", in("t0") crate::syscalls::COMMIT_CONFIG, in("a0") i, in("a1") word);
+        }
+
         cfg_if! {
             if #[cfg(feature = "verify")] {
                 let deferred_proofs_digest = zkvm::DEFERRED_PROOFS_DIGEST.as_mut().unwrap();
@@ -65,6 +65,152 @@ pub extern "C" fn syscall_hint_read(ptr: *mut u8, len: usize) {
             in("a0") ptr,
             in("a1") len,
         );
+
+        // Absorb the buffer into the input digest, which is committed on halt.
+        #[cfg(feature = "commit-input")]
+        {
+            let hasher = zkvm::INPUT_HASHER.as_mut().unwrap();
+            hasher.update(&(len as u32).to_le_bytes());
+            hasher.update(core::slice::from_raw_parts(ptr, len));
+        }
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_config_len() -> usize {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let len;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::CONFIG_LEN,
+            lateout("t0") len,
+        );
+        len
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_config_read(ptr: *mut u8, len: usize) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::CONFIG_READ,
+            in("a0") ptr,
+            in("a1") len,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Returns the number of bytes left in the entropy pool provided by the host.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_entropy_len() -> usize {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let len;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::ENTROPY_LEN,
+            lateout("t0") len,
+        );
+        len
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Reads the next `len` bytes of the entropy pool into `ptr`, which must be word aligned and not
+/// yet written to.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_entropy_read(ptr: *mut u8, len: usize) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::ENTROPY_READ,
+            in("a0") ptr,
+            in("a1") len,
+        );
+
+        // Absorb the entropy into its own digest, which is folded into the input digest on halt.
+        #[cfg(feature = "commit-input")]
+        if len > 0 {
+            zkvm::ENTROPY_HASHER
+                .get_or_insert_with(|| {
+                    sha2::Sha256::default().chain(sp1_precompiles::io::ENTROPY_DIGEST_DOMAIN)
+                })
+                .update(core::slice::from_raw_parts(ptr, len));
+        }
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Returns the number of cycles executed so far, modulo 2^32.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_cycle_count() -> u32 {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let cycles;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::CYCLE_COUNT,
+            lateout("t0") cycles,
+        );
+        cycles
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Returns the low word of the gas used so far if `half` is 0, and its high word otherwise.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_gas_used(half: u32) -> u32 {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let word;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::GAS_USED,
+            in("a0") half,
+            lateout("t0") word,
+        );
+        word
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Hints the runtime that the current cycle is a good place to end the shard.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_hint_shard_boundary() {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::HINT_SHARD_BOUNDARY,
+        );
     }
 
     #[cfg(not(target_os = "zkvm"))]
@@ -2,6 +2,9 @@
 use core::arch::asm;
 
 /// Executes the Keccak256 permutation on the given state.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::keccak_permute].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_keccak_permute(state: *mut u64) {
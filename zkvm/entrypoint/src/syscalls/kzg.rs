@@ -0,0 +1,18 @@
+/// Verifies an EIP-4844 KZG point evaluation: that the versioned hash is the one of the
+/// commitment, and that the proof opens the commitment to `y` at `z`.
+///
+/// There is no single `ecall` for this: the pairings are computed with the bls12381_fp_*
+/// precompiles by [sp1_precompiles::kzg::verify_kzg_proof], so every base field operation is
+/// constrained by the `Bls12381FpOp` chip. Returns `false` if one of the inputs is invalid. Prefer
+/// [sp1_precompiles::kzg::point_evaluation], which mirrors the ABI of the EVM precompile.
+#[no_mangle]
+pub extern "C" fn syscall_kzg_point_evaluation(
+    versioned_hash: &[u8; 32],
+    z: &[u8; 32],
+    y: &[u8; 32],
+    commitment: &[u8; 48],
+    proof: &[u8; 48],
+) -> bool {
+    sp1_precompiles::kzg::kzg_to_versioned_hash(commitment) == *versioned_hash
+        && sp1_precompiles::kzg::verify_kzg_proof(commitment, z, y, proof) == Ok(true)
+}
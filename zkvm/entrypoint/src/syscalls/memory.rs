@@ -12,7 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-const SYSTEM_START: usize = 0x0C00_0000;
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// Returns the guest memory footprint limit configured on the host, in bytes.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_memory_limit() -> usize {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let limit;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::MEMORY_LIMIT,
+            lateout("t0") limit,
+        );
+        limit
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
 
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
@@ -26,11 +46,18 @@ pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u
     // initialized.
     static mut HEAP_POS: usize = 0;
 
+    // The end of the heap, or 0 if the heap has not yet been initialized.
+    static mut HEAP_END: usize = 0;
+
     // SAFETY: Single threaded, so nothing else can touch this while we're working.
     let mut heap_pos = unsafe { HEAP_POS };
 
     if heap_pos == 0 {
         heap_pos = unsafe { (&_end) as *const u8 as usize };
+        // The heap can't grow past the host's memory footprint limit, so stop there and panic in
+        // the guest instead of being stopped by the host.
+        let limit = heap_pos.saturating_add(syscall_memory_limit());
+        unsafe { HEAP_END = (crate::layout::HEAP_END as usize).min(limit) };
     }
 
     let offset = heap_pos & (align - 1);
@@ -39,10 +66,10 @@ pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u
     }
 
     let ptr = heap_pos as *mut u8;
-    heap_pos += bytes;
+    heap_pos = heap_pos.saturating_add(bytes);
 
-    // Check to make sure heap doesn't collide with SYSTEM memory.
-    if SYSTEM_START < heap_pos {
+    // Check to make sure heap doesn't collide with the syscall buffers or exceed the memory limit.
+    if unsafe { HEAP_END } < heap_pos {
         panic!();
     }
 
@@ -5,8 +5,10 @@ mod ed25519;
 mod halt;
 mod io;
 mod keccak_permute;
+mod kzg;
 mod memory;
 mod secp256k1;
+mod secp256r1;
 mod sha_compress;
 mod sha_extend;
 mod sys;
@@ -21,8 +23,10 @@ pub use ed25519::*;
 pub use halt::*;
 pub use io::*;
 pub use keccak_permute::*;
+pub use kzg::*;
 pub use memory::*;
 pub use secp256k1::*;
+pub use secp256r1::*;
 pub use sha_compress::*;
 pub use sha_extend::*;
 pub use sys::*;
@@ -79,6 +83,12 @@ pub const BN254_DOUBLE: u32 = 0x00_00_01_0F;
 /// Executes the `COMMIT` precompile.
 pub const COMMIT: u32 = 0x00_00_00_10;
 
+/// Executes the `COMMIT_INPUT` precompile.
+pub const COMMIT_INPUT: u32 = 0x00_00_00_11;
+
+/// Executes the `COMMIT_CONFIG` precompile.
+pub const COMMIT_CONFIG: u32 = 0x00_00_00_12;
+
 /// Executes the `COMMIT_DEFERRED_PROOFS` precompile.
 pub const COMMIT_DEFERRED_PROOFS: u32 = 0x00_00_00_1A;
 
@@ -91,6 +101,36 @@ pub const HINT_LEN: u32 = 0x00_00_00_F0;
 /// Executes `HINT_READ`.
 pub const HINT_READ: u32 = 0x00_00_00_F1;
 
+/// Executes `CONFIG_LEN`.
+pub const CONFIG_LEN: u32 = 0x00_00_00_F2;
+
+/// Executes `CONFIG_READ`.
+pub const CONFIG_READ: u32 = 0x00_00_00_F3;
+
+/// Executes `MEMORY_LIMIT`.
+pub const MEMORY_LIMIT: u32 = 0x00_00_00_F4;
+
+/// Executes `CYCLE_COUNT`.
+pub const CYCLE_COUNT: u32 = 0x00_00_00_F5;
+
+/// Executes `HINT_SHARD_BOUNDARY`.
+pub const HINT_SHARD_BOUNDARY: u32 = 0x00_00_00_F6;
+
+/// Executes `ENTROPY_LEN`.
+pub const ENTROPY_LEN: u32 = 0x00_00_00_F7;
+
+/// Executes `ENTROPY_READ`.
+pub const ENTROPY_READ: u32 = 0x00_00_00_F8;
+
+/// Executes `GAS_USED`.
+pub const GAS_USED: u32 = 0x00_00_00_F9;
+
+/// The first id reserved for unconstrained syscalls, which are handled by the host and not proven.
+pub const UNCONSTRAINED_SYSCALL_START: u32 = 0x00_00_00_C0;
+
+/// The last id reserved for unconstrained syscalls.
+pub const UNCONSTRAINED_SYSCALL_END: u32 = 0x00_00_00_DF;
+
 /// Executes `BLS12381_DECOMPRESS`.
 pub const BLS12381_DECOMPRESS: u32 = 0x00_00_01_1C;
 
@@ -102,3 +142,21 @@ pub const BLS12381_ADD: u32 = 0x00_01_01_1E;
 
 /// Executes the `BLS12381_DOUBLE` precompile.
 pub const BLS12381_DOUBLE: u32 = 0x00_00_01_1F;
+
+/// Executes the `BLS12381_FP_ADD` precompile.
+pub const BLS12381_FP_ADD: u32 = 0x00_00_01_20;
+
+/// Executes the `BLS12381_FP_SUB` precompile.
+pub const BLS12381_FP_SUB: u32 = 0x00_00_01_21;
+
+/// Executes the `BLS12381_FP_MUL` precompile.
+pub const BLS12381_FP_MUL: u32 = 0x00_00_01_22;
+
+/// Executes the `SECP256R1_ADD` precompile.
+pub const SECP256R1_ADD: u32 = 0x00_01_01_23;
+
+/// Executes the `SECP256R1_DOUBLE` precompile.
+pub const SECP256R1_DOUBLE: u32 = 0x00_00_01_24;
+
+/// Executes the `BLS12381_FP_DIV` precompile.
+pub const BLS12381_FP_DIV: u32 = 0x00_00_01_25;
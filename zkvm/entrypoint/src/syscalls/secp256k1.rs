@@ -4,6 +4,9 @@ use core::arch::asm;
 /// Adds two Secp256k1 points.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::secp256k1_add].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_secp256k1_add(p: *mut u32, q: *mut u32) {
@@ -24,6 +27,9 @@ pub extern "C" fn syscall_secp256k1_add(p: *mut u32, q: *mut u32) {
 /// Double a Secp256k1 point.
 ///
 /// The result is stored in the first point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::secp256k1_double].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_secp256k1_double(p: *mut u32) {
@@ -45,6 +51,9 @@ pub extern "C" fn syscall_secp256k1_double(p: *mut u32) {
 ///
 /// The input array should be 32 bytes long, with the first 16 bytes containing the X coordinate in
 /// big-endian format. The second half of the input will be overwritten with the decompressed point.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::secp256k1_decompress].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_secp256k1_decompress(point: &mut [u8; 64], is_odd: bool) {
@@ -1,6 +1,10 @@
 #[cfg(target_os = "zkvm")]
 use core::arch::asm;
 
+/// Compresses the 64 words of the SHA-256 message schedule `w` into the 8 words of `state`.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::sha256_compress].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_sha256_compress(w: *mut u32, state: *mut u32) {
@@ -1,6 +1,10 @@
 #[cfg(target_os = "zkvm")]
 use core::arch::asm;
 
+/// Extends the first 16 words of the SHA-256 message schedule `w` to its 64 words.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::sha256_extend].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_sha256_extend(w: *mut u32) {
@@ -4,6 +4,9 @@ use core::arch::asm;
 /// Uint256 multiplication operation.
 ///
 /// The result is written over the first input.
+///
+/// This is the raw syscall, which does not check the length and alignment of its arguments: a
+/// misuse is only caught when proving. Prefer [sp1_precompiles::checked::uint256_mulmod].
 #[allow(unused_variables)]
 #[no_mangle]
 pub extern "C" fn syscall_uint256_mulmod(x: *mut u32, y: *const u32) {
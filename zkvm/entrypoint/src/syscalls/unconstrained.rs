@@ -37,3 +37,32 @@ pub fn syscall_exit_unconstrained() {
     #[cfg(not(target_os = "zkvm"))]
     println!("Exiting unconstrained execution block");
 }
+
+/// Executes the unconstrained syscall `id`, which the host handles with the handler it registered
+/// for that id, typically to prototype a precompile before writing its chip.
+///
+/// The effects of the syscall are not proven, so a program making one can only be executed, or
+/// proven with an insecure proof.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_unconstrained(id: u32, arg1: u32, arg2: u32) {
+    assert!(
+        (crate::syscalls::UNCONSTRAINED_SYSCALL_START..=crate::syscalls::UNCONSTRAINED_SYSCALL_END)
+            .contains(&id),
+        "syscall id {:#x} is not reserved for unconstrained syscalls",
+        id
+    );
+
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") id,
+            in("a0") arg1,
+            in("a1") arg2,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
@@ -0,0 +1,118 @@
+//! Runs the tests of a program generated by `#[sp1_derive::sp1_test]`.
+//!
+//! The host writes a [TestPlan] to the first input buffer, and the program reports the tests it
+//! runs to the [FD_TEST_REPORTS] file descriptor. A failing test halts the program, so the host
+//! runs it again with the tests that are left, see `sp1_core::utils::GuestTestRunner`.
+
+use std::panic::PanicInfo;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::io::{cycle_count, FD_TEST_REPORTS};
+
+/// A test collected by `#[sp1_derive::sp1_test]`.
+pub struct TestCase {
+    pub name: &'static str,
+    pub test: fn(),
+}
+
+/// What the host asks the program to do. Must match `GuestTestPlan` in
+/// `core/src/utils/guest_tests.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestPlan {
+    /// Report the names of all the tests.
+    List,
+    /// Run the tests with these names, in order.
+    Run(Vec<String>),
+}
+
+/// What the program tells the host. Must match `GuestTestReport` in
+/// `core/src/utils/guest_tests.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum TestReport<'a> {
+    Listed {
+        names: Vec<&'a str>,
+    },
+    Started {
+        name: &'a str,
+    },
+    Passed {
+        name: &'a str,
+        cycles: u64,
+    },
+    Failed {
+        name: &'a str,
+        message: String,
+        cycles: u64,
+    },
+}
+
+/// The test being run and the cycle count when it started, for the panic hook to report it.
+static CURRENT: Mutex<Option<(&'static str, u32)>> = Mutex::new(None);
+
+fn report(report: &TestReport) {
+    crate::io::write(FD_TEST_REPORTS, &bincode::serialize(report).unwrap());
+}
+
+fn cycles_since(start: u32) -> u64 {
+    cycle_count().wrapping_sub(start) as u64
+}
+
+/// The message a test panicked with.
+fn panic_message(info: &PanicInfo) -> String {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    match info.location() {
+        Some(location) => format!("{} at {}", message, location),
+        None => message.to_string(),
+    }
+}
+
+/// Runs `tests` as planned by the host, reporting each of them to [FD_TEST_REPORTS].
+pub fn run_tests(tests: &'static [TestCase]) {
+    let names = match crate::io::read::<TestPlan>() {
+        TestPlan::List => {
+            report(&TestReport::Listed {
+                names: tests.iter().map(|test| test.name).collect(),
+            });
+            return;
+        }
+        TestPlan::Run(names) => names,
+    };
+
+    // Report the failing test before the default hook writes the message to stderr and the
+    // program halts.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let current = CURRENT.lock().map(|current| *current).unwrap_or(None);
+        if let Some((name, start)) = current {
+            report(&TestReport::Failed {
+                name,
+                message: panic_message(info),
+                cycles: cycles_since(start),
+            });
+        }
+        default_hook(info);
+    }));
+
+    for name in names {
+        let test = tests
+            .iter()
+            .find(|test| test.name == name)
+            .unwrap_or_else(|| panic!("no test named {}", name));
+        report(&TestReport::Started { name: test.name });
+        let start = cycle_count();
+        *CURRENT.lock().unwrap() = Some((test.name, start));
+        (test.test)();
+        *CURRENT.lock().unwrap() = None;
+        report(&TestReport::Passed {
+            name: test.name,
+            cycles: cycles_since(start),
+        });
+    }
+}
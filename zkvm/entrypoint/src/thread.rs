@@ -0,0 +1,142 @@
+//! Threads, in place of `std::thread`.
+//!
+//! A program runs on a single thread, and `std::thread::spawn` fails in the zkVM. The functions
+//! of this module have the signatures of the ones of `std::thread`. With the `inline-threads`
+//! feature, [spawn] and [Scope::spawn] run the closure to completion before returning, and
+//! otherwise they panic, naming the feature. Running a closure inline only works for closures
+//! that don't wait on the thread spawning them, such as workers draining a bounded channel filled
+//! beforehand.
+//!
+//! [sleep] advances the clock of [crate::time] without waiting.
+
+use core::marker::PhantomData;
+use core::num::NonZeroUsize;
+use core::time::Duration;
+
+/// The message of the panic of a spawn without the `inline-threads` feature.
+const SPAWN_UNSUPPORTED: &str =
+    "the zkVM runs a single thread: enable the `inline-threads` feature of sp1-zkvm to run \
+     spawned closures inline";
+
+/// Runs `f` inline with the `inline-threads` feature, and panics otherwise.
+fn run_inline<F: FnOnce() -> T, T>(f: F) -> T {
+    if cfg!(feature = "inline-threads") {
+        f()
+    } else {
+        panic!("{}", SPAWN_UNSUPPORTED)
+    }
+}
+
+/// A handle to a spawned closure, which has already run.
+#[derive(Debug)]
+pub struct JoinHandle<T>(T);
+
+impl<T> JoinHandle<T> {
+    /// The result of the closure.
+    pub fn join(self) -> std::thread::Result<T> {
+        Ok(self.0)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        true
+    }
+}
+
+/// Runs `f`, see the module documentation.
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    JoinHandle(run_inline(f))
+}
+
+/// A scope to spawn closures borrowing from the stack, see [scope].
+pub struct Scope<'scope, 'env: 'scope> {
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// A handle to a closure spawned in a [Scope], which has already run.
+#[derive(Debug)]
+pub struct ScopedJoinHandle<'scope, T>(T, PhantomData<&'scope ()>);
+
+impl<'scope, T> ScopedJoinHandle<'scope, T> {
+    /// The result of the closure.
+    pub fn join(self) -> std::thread::Result<T> {
+        Ok(self.0)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        true
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Runs `f`, see the module documentation.
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        ScopedJoinHandle(run_inline(f), PhantomData)
+    }
+}
+
+/// Calls `f` with a [Scope] to spawn closures borrowing from the stack.
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    f(&Scope {
+        scope: PhantomData,
+        env: PhantomData,
+    })
+}
+
+/// Advances the clock of [crate::time] by `duration`, without waiting.
+pub fn sleep(duration: Duration) {
+    crate::time::advance(duration);
+}
+
+/// Does nothing, there being no other thread to yield to.
+pub fn yield_now() {}
+
+/// One, the number of threads of a program.
+pub fn available_parallelism() -> std::io::Result<NonZeroUsize> {
+    Ok(NonZeroUsize::MIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "inline-threads")]
+    fn test_spawn_inline() {
+        let handle = spawn(|| 1 + 2);
+        assert!(handle.is_finished());
+        assert_eq!(handle.join().unwrap(), 3);
+
+        // The closures run before the scope returns.
+        let mut values = vec![];
+        scope(|s| {
+            s.spawn(|| values.push(1));
+        });
+        let sum = scope(|s| s.spawn(|| values.iter().sum::<u32>()).join().unwrap());
+        assert_eq!(sum, 1);
+        assert_eq!(values, vec![1]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "inline-threads"))]
+    #[should_panic(expected = "enable the `inline-threads` feature")]
+    fn test_spawn_unsupported() {
+        spawn(|| ());
+    }
+
+    #[test]
+    fn test_available_parallelism() {
+        assert_eq!(available_parallelism().unwrap().get(), 1);
+    }
+}
@@ -0,0 +1,201 @@
+//! Deterministic clocks, in place of `std::time`.
+//!
+//! A program has no clock to read: `std::time::Instant::now` and `std::time::SystemTime::now`
+//! panic in the zkVM, and a clock read from the host would make the execution depend on when it
+//! runs. The [Instant] and [SystemTime] of this module read a counter instead, which advances by
+//! [TICK] at every read and by the duration of every [crate::thread::sleep]. The same program on
+//! the same input then reads the same times, inside and outside of the zkVM.
+//!
+//! [SystemTime] starts at [UNIX_EPOCH], or at the time given to [set_system_time], for instance
+//! read from the input by a program checking the validity period of a certificate.
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::time::Duration;
+use std::sync::Mutex;
+
+/// How much the clock advances at every read.
+pub const TICK: Duration = Duration::from_micros(1);
+
+/// The time elapsed since the start of the program.
+static CLOCK: Mutex<Duration> = Mutex::new(Duration::ZERO);
+
+/// The time between [UNIX_EPOCH] and the start of the program.
+static SYSTEM_TIME_START: Mutex<Duration> = Mutex::new(Duration::ZERO);
+
+/// Advances the clock by `duration`, returning the time before.
+pub(crate) fn advance(duration: Duration) -> Duration {
+    let mut clock = CLOCK.lock().unwrap();
+    let before = *clock;
+    *clock += duration;
+    before
+}
+
+/// Sets the [SystemTime] of the start of the program to `since_epoch` after [UNIX_EPOCH].
+pub fn set_system_time(since_epoch: Duration) {
+    *SYSTEM_TIME_START.lock().unwrap() = since_epoch;
+}
+
+/// A point of the clock, like `std::time::Instant`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+impl Instant {
+    /// Reads the clock, advancing it by [TICK].
+    pub fn now() -> Self {
+        Self(advance(TICK))
+    }
+
+    /// The time elapsed since `earlier`, or zero if it is later than `self`.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// The time elapsed since `self`, which reads the clock.
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration).map(Self)
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_sub(duration).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        Self(self.0 + duration)
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        self.0 += duration;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        Self(self.0 - duration)
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        self.0 -= duration;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// A date, like `std::time::SystemTime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTime(Duration);
+
+/// The date of the Unix epoch, 1970-01-01 00:00:00 UTC.
+pub const UNIX_EPOCH: SystemTime = SystemTime(Duration::ZERO);
+
+/// The error of [SystemTime::duration_since] when the other date is later, holding how much later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTimeError(pub Duration);
+
+impl core::fmt::Display for SystemTimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "second time provided was later than self by {:?}",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for SystemTimeError {}
+
+impl SystemTime {
+    pub const UNIX_EPOCH: SystemTime = UNIX_EPOCH;
+
+    /// Reads the clock, advancing it by [TICK].
+    pub fn now() -> Self {
+        let start = *SYSTEM_TIME_START.lock().unwrap();
+        Self(start + advance(TICK))
+    }
+
+    pub fn duration_since(&self, earlier: SystemTime) -> Result<Duration, SystemTimeError> {
+        self.0
+            .checked_sub(earlier.0)
+            .ok_or_else(|| SystemTimeError(earlier.0 - self.0))
+    }
+
+    /// The time elapsed since `self`, which reads the clock.
+    pub fn elapsed(&self) -> Result<Duration, SystemTimeError> {
+        Self::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<SystemTime> {
+        self.0.checked_add(duration).map(Self)
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<SystemTime> {
+        self.0.checked_sub(duration).map(Self)
+    }
+}
+
+impl Add<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn add(self, duration: Duration) -> SystemTime {
+        Self(self.0 + duration)
+    }
+}
+
+impl Sub<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn sub(self, duration: Duration) -> SystemTime {
+        Self(self.0 - duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The only test reading the clock, which is shared by the tests running in parallel.
+    #[test]
+    fn test_deterministic_clock() {
+        // Every read advances the clock by a tick.
+        let start = Instant::now();
+        let next = Instant::now();
+        assert_eq!(next - start, TICK);
+        assert_eq!(start.elapsed(), 2 * TICK);
+
+        // Sleeping advances it by the duration, without waiting.
+        crate::thread::sleep(Duration::from_secs(5));
+        assert_eq!(start.elapsed(), Duration::from_secs(5) + 3 * TICK);
+        assert_eq!(next.duration_since(Instant::now()), Duration::ZERO);
+
+        // The system time reads the same clock, from the time it is set to.
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(since_epoch + TICK, Instant::now().0);
+        set_system_time(Duration::from_secs(1_700_000_000));
+        let now = SystemTime::now();
+        assert_eq!(
+            now.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_secs(1_700_000_000) + since_epoch + 2 * TICK
+        );
+        assert_eq!(UNIX_EPOCH.duration_since(now), Err(SystemTimeError(now.0)));
+        assert_eq!(now.elapsed().unwrap(), TICK);
+    }
+}
@@ -1,6 +1,10 @@
 #![allow(unused_imports)]
 use crate::utils::CurveOperations;
-use crate::{syscall_bls12381_add, syscall_bls12381_decompress, syscall_bls12381_double};
+use crate::{
+    syscall_bls12381_add, syscall_bls12381_decompress, syscall_bls12381_double,
+    syscall_bls12381_fp_add, syscall_bls12381_fp_div, syscall_bls12381_fp_mul,
+    syscall_bls12381_fp_sub,
+};
 
 use amcl::bls381::bls381::utils::deserialize_g1;
 use anyhow::Result;
@@ -10,6 +14,9 @@ pub struct Bls12381;
 
 const NUM_WORDS: usize = 24;
 
+/// The number of words of an element of the BLS12-381 base field.
+const FP_NUM_WORDS: usize = 12;
+
 impl CurveOperations<NUM_WORDS> for Bls12381 {
     // The generator has been taken from py_ecc python library by Ethereum Foundation.
     // https://github.com/ethereum/py_ecc/blob/7b9e1b3/py_ecc/bls12_381/bls12_381_curve.py#L38-L45
@@ -59,3 +66,39 @@ pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96]> {
         }
     }
 }
+
+/// Sets `x` to `x + y` in the BLS12-381 base field, using the bls12381_fp_add precompile.
+///
+/// The elements are little endian words and must be reduced.
+pub fn fp_add(x: &mut [u32; FP_NUM_WORDS], y: &[u32; FP_NUM_WORDS]) {
+    unsafe {
+        syscall_bls12381_fp_add(x.as_mut_ptr(), y.as_ptr());
+    }
+}
+
+/// Sets `x` to `x - y` in the BLS12-381 base field, using the bls12381_fp_sub precompile.
+///
+/// The elements are little endian words and must be reduced.
+pub fn fp_sub(x: &mut [u32; FP_NUM_WORDS], y: &[u32; FP_NUM_WORDS]) {
+    unsafe {
+        syscall_bls12381_fp_sub(x.as_mut_ptr(), y.as_ptr());
+    }
+}
+
+/// Sets `x` to `x * y` in the BLS12-381 base field, using the bls12381_fp_mul precompile.
+///
+/// The elements are little endian words and must be reduced.
+pub fn fp_mul(x: &mut [u32; FP_NUM_WORDS], y: &[u32; FP_NUM_WORDS]) {
+    unsafe {
+        syscall_bls12381_fp_mul(x.as_mut_ptr(), y.as_ptr());
+    }
+}
+
+/// Sets `x` to `x / y` in the BLS12-381 base field, using the bls12381_fp_div precompile.
+///
+/// The elements are little endian words and must be reduced, and `y` must not be zero.
+pub fn fp_div(x: &mut [u32; FP_NUM_WORDS], y: &[u32; FP_NUM_WORDS]) {
+    unsafe {
+        syscall_bls12381_fp_div(x.as_mut_ptr(), y.as_ptr());
+    }
+}
@@ -0,0 +1,273 @@
+//! Checked wrappers of the precompile syscalls.
+//!
+//! The raw syscalls take pointers and trust the program to pass buffers of the right length,
+//! aligned to a word: a misuse is only caught when proving, as a constraint failure. These wrappers
+//! take slices and check their lengths and alignment before the `ecall`, panicking with a message
+//! naming the precompile and the faulty argument. The checks are a few comparisons, and the
+//! wrappers are inlined.
+//!
+//! Byte buffers must be aligned to a word, which a byte array is not in general: wrap it in
+//! [Aligned] to make it so.
+//!
+//! ```ignore
+//! use sp1_zkvm::precompiles::checked::{self, Aligned};
+//!
+//! let mut point = Aligned([0u8; 64]);
+//! point.0[32..].copy_from_slice(&compressed);
+//! checked::ed_decompress(&mut point.0);
+//! ```
+
+use crate::{
+    syscall_bls12381_add, syscall_bls12381_decompress, syscall_bls12381_double,
+    syscall_bls12381_fp_add, syscall_bls12381_fp_div, syscall_bls12381_fp_mul,
+    syscall_bls12381_fp_sub, syscall_bn254_add, syscall_bn254_double, syscall_ed_add,
+    syscall_ed_decompress, syscall_keccak_permute, syscall_secp256k1_add,
+    syscall_secp256k1_decompress, syscall_secp256k1_double, syscall_secp256r1_add,
+    syscall_secp256r1_double, syscall_sha256_compress, syscall_sha256_extend,
+    syscall_uint256_mulmod, BIGINT_WIDTH_WORDS,
+};
+
+/// The number of words of the message schedule of SHA-256.
+pub const SHA256_W_WORDS: usize = 64;
+
+/// The number of words of the state of SHA-256.
+pub const SHA256_STATE_WORDS: usize = 8;
+
+/// The number of lanes of the state of Keccak.
+pub const KECCAK_STATE_LANES: usize = 25;
+
+/// The number of words of an ed25519, secp256k1, secp256r1 or bn254 point.
+pub const POINT_256_WORDS: usize = 16;
+
+/// The number of words of a BLS12-381 point.
+pub const BLS12381_POINT_WORDS: usize = 24;
+
+/// The number of words of an element of the BLS12-381 base field.
+pub const BLS12381_FP_WORDS: usize = 12;
+
+/// A value aligned to a word, for the byte buffers passed to the precompiles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub struct Aligned<T>(pub T);
+
+/// Panics unless `slice` has `len` elements.
+#[inline]
+#[track_caller]
+fn check_len<T>(precompile: &str, arg: &str, slice: &[T], len: usize) {
+    if slice.len() != len {
+        panic!(
+            "{}: `{}` must have {} elements, got {}",
+            precompile,
+            arg,
+            len,
+            slice.len()
+        );
+    }
+}
+
+/// Panics unless `bytes` starts at a word aligned address.
+#[inline]
+#[track_caller]
+fn check_aligned(precompile: &str, arg: &str, bytes: &[u8]) {
+    let addr = bytes.as_ptr() as usize;
+    if addr % 4 != 0 {
+        panic!(
+            "{}: `{}` must be aligned to 4 bytes, got address {:#x}",
+            precompile, arg, addr
+        );
+    }
+}
+
+/// Extends the first 16 words of the SHA-256 message schedule `w` to its 64 words.
+#[inline]
+#[track_caller]
+pub fn sha256_extend(w: &mut [u32]) {
+    check_len("sha256_extend", "w", w, SHA256_W_WORDS);
+    unsafe { syscall_sha256_extend(w.as_mut_ptr()) }
+}
+
+/// Compresses the 64 words of the SHA-256 message schedule `w` into the 8 words of `state`.
+#[inline]
+#[track_caller]
+pub fn sha256_compress(w: &[u32], state: &mut [u32]) {
+    check_len("sha256_compress", "w", w, SHA256_W_WORDS);
+    check_len("sha256_compress", "state", state, SHA256_STATE_WORDS);
+    // The precompile only reads `w`.
+    unsafe { syscall_sha256_compress(w.as_ptr() as *mut u32, state.as_mut_ptr()) }
+}
+
+/// Applies the Keccak-f[1600] permutation to the 25 lanes of `state`.
+#[inline]
+#[track_caller]
+pub fn keccak_permute(state: &mut [u64]) {
+    check_len("keccak_permute", "state", state, KECCAK_STATE_LANES);
+    unsafe { syscall_keccak_permute(state.as_mut_ptr()) }
+}
+
+/// Sets the ed25519 point `p` to `p + q`, both given as 16 little endian words.
+#[inline]
+#[track_caller]
+pub fn ed_add(p: &mut [u32], q: &[u32]) {
+    check_len("ed_add", "p", p, POINT_256_WORDS);
+    check_len("ed_add", "q", q, POINT_256_WORDS);
+    // The precompile only reads `q`.
+    unsafe { syscall_ed_add(p.as_mut_ptr(), q.as_ptr() as *mut u32) }
+}
+
+/// Decompresses the ed25519 point whose compressed encoding is the second half of the 64 bytes of
+/// `point`, writing its X coordinate to the first half and clearing the sign bit.
+#[inline]
+#[track_caller]
+pub fn ed_decompress(point: &mut [u8]) {
+    check_len("ed_decompress", "point", point, 64);
+    check_aligned("ed_decompress", "point", point);
+    unsafe { syscall_ed_decompress(point.try_into().unwrap()) }
+}
+
+/// Sets the secp256k1 point `p` to `p + q`, both given as 16 little endian words.
+///
+/// The points must be distinct and not opposite, see [secp256k1_double].
+#[inline]
+#[track_caller]
+pub fn secp256k1_add(p: &mut [u32], q: &[u32]) {
+    check_len("secp256k1_add", "p", p, POINT_256_WORDS);
+    check_len("secp256k1_add", "q", q, POINT_256_WORDS);
+    unsafe { syscall_secp256k1_add(p.as_mut_ptr(), q.as_ptr()) }
+}
+
+/// Sets the secp256k1 point `p`, given as 16 little endian words, to `2 * p`.
+#[inline]
+#[track_caller]
+pub fn secp256k1_double(p: &mut [u32]) {
+    check_len("secp256k1_double", "p", p, POINT_256_WORDS);
+    unsafe { syscall_secp256k1_double(p.as_mut_ptr()) }
+}
+
+/// Decompresses the secp256k1 point whose big endian X coordinate is the first half of the 64
+/// bytes of `point`, writing its Y coordinate of parity `is_odd` to the second half.
+#[inline]
+#[track_caller]
+pub fn secp256k1_decompress(point: &mut [u8], is_odd: bool) {
+    check_len("secp256k1_decompress", "point", point, 64);
+    check_aligned("secp256k1_decompress", "point", point);
+    unsafe { syscall_secp256k1_decompress(point.try_into().unwrap(), is_odd) }
+}
+
+/// Sets the secp256r1 point `p` to `p + q`, both given as 16 little endian words.
+///
+/// The points must be distinct and not opposite, see [secp256r1_double].
+#[inline]
+#[track_caller]
+pub fn secp256r1_add(p: &mut [u32], q: &[u32]) {
+    check_len("secp256r1_add", "p", p, POINT_256_WORDS);
+    check_len("secp256r1_add", "q", q, POINT_256_WORDS);
+    unsafe { syscall_secp256r1_add(p.as_mut_ptr(), q.as_ptr()) }
+}
+
+/// Sets the secp256r1 point `p`, given as 16 little endian words, to `2 * p`.
+#[inline]
+#[track_caller]
+pub fn secp256r1_double(p: &mut [u32]) {
+    check_len("secp256r1_double", "p", p, POINT_256_WORDS);
+    unsafe { syscall_secp256r1_double(p.as_mut_ptr()) }
+}
+
+/// Sets the bn254 point `p` to `p + q`, both given as 16 little endian words.
+///
+/// The points must be distinct and not opposite, see [bn254_double].
+#[inline]
+#[track_caller]
+pub fn bn254_add(p: &mut [u32], q: &[u32]) {
+    check_len("bn254_add", "p", p, POINT_256_WORDS);
+    check_len("bn254_add", "q", q, POINT_256_WORDS);
+    unsafe { syscall_bn254_add(p.as_mut_ptr(), q.as_ptr()) }
+}
+
+/// Sets the bn254 point `p`, given as 16 little endian words, to `2 * p`.
+#[inline]
+#[track_caller]
+pub fn bn254_double(p: &mut [u32]) {
+    check_len("bn254_double", "p", p, POINT_256_WORDS);
+    unsafe { syscall_bn254_double(p.as_mut_ptr()) }
+}
+
+/// Sets the BLS12-381 point `p` to `p + q`, both given as 24 little endian words.
+///
+/// The points must be distinct and not opposite, see [bls12381_double].
+#[inline]
+#[track_caller]
+pub fn bls12381_add(p: &mut [u32], q: &[u32]) {
+    check_len("bls12381_add", "p", p, BLS12381_POINT_WORDS);
+    check_len("bls12381_add", "q", q, BLS12381_POINT_WORDS);
+    unsafe { syscall_bls12381_add(p.as_mut_ptr(), q.as_ptr()) }
+}
+
+/// Sets the BLS12-381 point `p`, given as 24 little endian words, to `2 * p`.
+#[inline]
+#[track_caller]
+pub fn bls12381_double(p: &mut [u32]) {
+    check_len("bls12381_double", "p", p, BLS12381_POINT_WORDS);
+    unsafe { syscall_bls12381_double(p.as_mut_ptr()) }
+}
+
+/// Decompresses the BLS12-381 point whose big endian X coordinate is the first half of the 96
+/// bytes of `point`, writing its Y coordinate to the second half.
+#[inline]
+#[track_caller]
+pub fn bls12381_decompress(point: &mut [u8], is_odd: bool) {
+    check_len("bls12381_decompress", "point", point, 96);
+    check_aligned("bls12381_decompress", "point", point);
+    unsafe { syscall_bls12381_decompress(point.try_into().unwrap(), is_odd) }
+}
+
+/// Sets `x` to `x + y` in the BLS12-381 base field, both given as 12 little endian words.
+#[inline]
+#[track_caller]
+pub fn bls12381_fp_add(x: &mut [u32], y: &[u32]) {
+    check_len("bls12381_fp_add", "x", x, BLS12381_FP_WORDS);
+    check_len("bls12381_fp_add", "y", y, BLS12381_FP_WORDS);
+    unsafe { syscall_bls12381_fp_add(x.as_mut_ptr(), y.as_ptr()) }
+}
+
+/// Sets `x` to `x - y` in the BLS12-381 base field, both given as 12 little endian words.
+#[inline]
+#[track_caller]
+pub fn bls12381_fp_sub(x: &mut [u32], y: &[u32]) {
+    check_len("bls12381_fp_sub", "x", x, BLS12381_FP_WORDS);
+    check_len("bls12381_fp_sub", "y", y, BLS12381_FP_WORDS);
+    unsafe { syscall_bls12381_fp_sub(x.as_mut_ptr(), y.as_ptr()) }
+}
+
+/// Sets `x` to `x * y` in the BLS12-381 base field, both given as 12 little endian words.
+#[inline]
+#[track_caller]
+pub fn bls12381_fp_mul(x: &mut [u32], y: &[u32]) {
+    check_len("bls12381_fp_mul", "x", x, BLS12381_FP_WORDS);
+    check_len("bls12381_fp_mul", "y", y, BLS12381_FP_WORDS);
+    unsafe { syscall_bls12381_fp_mul(x.as_mut_ptr(), y.as_ptr()) }
+}
+
+/// Sets `x` to `x / y` in the BLS12-381 base field, both given as 12 little endian words. `y` must
+/// not be zero.
+#[inline]
+#[track_caller]
+pub fn bls12381_fp_div(x: &mut [u32], y: &[u32]) {
+    check_len("bls12381_fp_div", "x", x, BLS12381_FP_WORDS);
+    check_len("bls12381_fp_div", "y", y, BLS12381_FP_WORDS);
+    unsafe { syscall_bls12381_fp_div(x.as_mut_ptr(), y.as_ptr()) }
+}
+
+/// Sets `x` to `x * y % modulus`, all given as 8 little endian words. A zero modulus stands for
+/// `2^256`.
+#[inline]
+#[track_caller]
+pub fn uint256_mulmod(x: &mut [u32], y: &[u32], modulus: &[u32]) {
+    check_len("uint256_mulmod", "x", x, BIGINT_WIDTH_WORDS);
+    check_len("uint256_mulmod", "y", y, BIGINT_WIDTH_WORDS);
+    check_len("uint256_mulmod", "modulus", modulus, BIGINT_WIDTH_WORDS);
+    // The precompile reads the modulus right after `y`.
+    let mut y_and_modulus = [0u32; 2 * BIGINT_WIDTH_WORDS];
+    y_and_modulus[..BIGINT_WIDTH_WORDS].copy_from_slice(y);
+    y_and_modulus[BIGINT_WIDTH_WORDS..].copy_from_slice(modulus);
+    unsafe { syscall_uint256_mulmod(x.as_mut_ptr(), y_and_modulus.as_ptr()) }
+}
@@ -1,16 +1,44 @@
 #![allow(unused_unsafe)]
 use crate::syscall_write;
-use crate::{syscall_hint_len, syscall_hint_read};
+use crate::{
+    syscall_config_len, syscall_config_read, syscall_cycle_count, syscall_gas_used,
+    syscall_hint_len, syscall_hint_read, syscall_hint_shard_boundary,
+};
+use k256::sha2::{Digest, Sha256};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::alloc::Layout;
+use std::collections::BTreeMap;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
 
 const FD_HINT: u32 = 4;
 pub const FD_PUBLIC_VALUES: u32 = 3;
 // Runtime hook file descriptors. Make sure these match the FDs in the HookRegistry.
 // The default hooks can be found in `core/src/runtime/hooks.rs`.
 pub const FD_ECRECOVER_HOOK: u32 = 5;
+/// The file descriptor the chunks committed with [commit_chunk] are written to, for the host to
+/// capture them. Must match `FD_OUTPUT_CHUNKS` in `core/src/io.rs`.
+pub const FD_OUTPUT_CHUNKS: u32 = 6;
+/// The file descriptor the reports of the tests run with `sp1_zkvm::testing::run_tests` are
+/// written to. Must match `FD_TEST_REPORTS` in `core/src/io.rs`.
+pub const FD_TEST_REPORTS: u32 = 7;
+/// The file descriptor the state suspended with [suspend_state] is written to, for the host to
+/// feed it to the next segment. Must match `FD_SEGMENT_STATE` in `core/src/io.rs`.
+pub const FD_SEGMENT_STATE: u32 = 8;
+/// The file descriptor the entries committed with [commit_kv] are written to, for the host to
+/// capture them. Must match `FD_KV_ENTRIES` in `core/src/io.rs`.
+pub const FD_KV_ENTRIES: u32 = 9;
+
+/// Domain separator for the config digest. Must match `CONFIG_DIGEST_DOMAIN` in `core/src/io.rs`.
+pub const CONFIG_DIGEST_DOMAIN: &[u8] = b"SP1_CONFIG_V1";
+
+/// Domain separator for the digest of the consumed entropy. Must match `ENTROPY_DIGEST_DOMAIN` in
+/// `core/src/io.rs`.
+pub const ENTROPY_DIGEST_DOMAIN: &[u8] = b"SP1_ENTROPY_V1";
+
+static CONFIG: OnceLock<BTreeMap<String, Vec<u8>>> = OnceLock::new();
 
 pub struct SyscallWriter {
     fd: u32,
@@ -31,8 +59,65 @@ impl std::io::Write for SyscallWriter {
     }
 }
 
+/// The number of buffers read from the input stream so far.
+static mut READ_INDEX: usize = 0;
+
+/// The number of input bytes shown in a [ReadError].
+const READ_ERROR_PREFIX_LEN: usize = 16;
+
+/// An error deserializing a value read from the input stream.
+///
+/// The read index is the number of buffers read before this one, so it is also the index of the
+/// `SP1Stdin::write` call on the host that wrote the buffer. The message is formatted like the
+/// host's `ReadError` in `core/src/io.rs`, make sure they match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadError {
+    pub index: usize,
+    pub type_name: &'static str,
+    /// The number of bytes consumed before deserialization failed.
+    pub consumed: usize,
+    /// The length of the buffer.
+    pub available: usize,
+    /// The first bytes of the buffer, hex encoded.
+    pub prefix: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to deserialize `{}` at read index {}: {} (consumed {} of {} bytes, input starts with 0x{})",
+            self.type_name, self.index, self.message, self.consumed, self.available, self.prefix
+        )
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+/// Deserialize the buffer at read index `index` as a `T`.
+fn deserialize_input<T: DeserializeOwned>(index: usize, bytes: &[u8]) -> Result<T, ReadError> {
+    let mut cursor = std::io::Cursor::new(bytes);
+    bincode::deserialize_from(&mut cursor).map_err(|err| ReadError {
+        index,
+        type_name: std::any::type_name::<T>(),
+        consumed: cursor.position() as usize,
+        available: bytes.len(),
+        prefix: hex::encode(&bytes[..bytes.len().min(READ_ERROR_PREFIX_LEN)]),
+        message: err.to_string(),
+    })
+}
+
 pub fn read_vec() -> Vec<u8> {
+    unsafe {
+        READ_INDEX += 1;
+    }
     let len = unsafe { syscall_hint_len() };
+    read_into_fresh_vec(len, |ptr| unsafe { syscall_hint_read(ptr, len) })
+}
+
+/// Allocate a new word-aligned vec of `len` bytes and fill it with `read`.
+fn read_into_fresh_vec(len: usize, read: impl FnOnce(*mut u8)) -> Vec<u8> {
     // Round up to the nearest multiple of 4 so that the memory allocated is in whole words
     let capacity = (len + 3) / 4 * 4;
 
@@ -48,19 +133,86 @@ pub fn read_vec() -> Vec<u8> {
     let mut vec = unsafe { Vec::from_raw_parts(ptr, 0, capacity) };
     // Read the vec into uninitialized memory. The syscall assumes the memory is uninitialized,
     // which should be true because the allocator does not dealloc, so a new alloc should be fresh.
+    read(ptr);
     unsafe {
-        syscall_hint_read(ptr, len);
         vec.set_len(len);
     }
     vec
 }
 
+/// Read a value from the input stream, panicking with the [ReadError] if it cannot be
+/// deserialized. The panic message is reported to the host on stderr.
 pub fn read<T: DeserializeOwned>() -> T {
+    try_read().unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Read a value from the input stream, returning a [ReadError] if it cannot be deserialized as a
+/// `T`. The buffer is consumed either way.
+pub fn try_read<T: DeserializeOwned>() -> Result<T, ReadError> {
+    let index = unsafe { READ_INDEX };
     let vec = read_vec();
-    bincode::deserialize(&vec).expect("deserialization failed")
+    deserialize_input(index, &vec)
+}
+
+/// Read the config map provided by the host, returning its canonical encoding if it is non-empty.
+///
+/// This is called by the entrypoint before `main`, which hashes the returned bytes, or no bytes if
+/// the config is empty, and commits the digest on halt.
+pub fn init_config() -> Option<Vec<u8>> {
+    let len = unsafe { syscall_config_len() };
+    if len == 0 {
+        CONFIG
+            .set(BTreeMap::new())
+            .expect("config already initialized");
+        return None;
+    }
+    let bytes = read_into_fresh_vec(len, |ptr| unsafe { syscall_config_read(ptr, len) });
+    CONFIG
+        .set(decode_config(&bytes))
+        .expect("config already initialized");
+    Some(bytes)
+}
+
+/// Look up a config value written on the host with `SP1Stdin::write_config`.
+pub fn config(key: &str) -> Option<Vec<u8>> {
+    CONFIG
+        .get()
+        .expect("config not initialized")
+        .get(key)
+        .cloned()
+}
+
+/// Decode the canonical config encoding: an entry count followed by `key_len || key ||
+/// value_len || value` for each entry, with keys strictly increasing.
+fn decode_config(bytes: &[u8]) -> BTreeMap<String, Vec<u8>> {
+    let mut offset = 0;
+    let mut next = |n: usize| {
+        let slice = &bytes[offset..offset + n];
+        offset += n;
+        slice
+    };
+    let read_u32 = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap()) as usize;
+
+    let count = read_u32(next(4));
+    let mut config = BTreeMap::new();
+    let mut last_key: Option<String> = None;
+    for _ in 0..count {
+        let key_len = read_u32(next(4));
+        let key = String::from_utf8(next(key_len).to_vec()).expect("config key is not utf-8");
+        let value_len = read_u32(next(4));
+        let value = next(value_len).to_vec();
+        if let Some(last_key) = &last_key {
+            assert!(*last_key < key, "config keys are not sorted and unique");
+        }
+        last_key = Some(key.clone());
+        config.insert(key, value);
+    }
+    assert_eq!(offset, bytes.len(), "trailing bytes in config");
+    config
 }
 
 pub fn commit<T: Serialize>(value: &T) {
+    start_legacy_commit();
     let writer = SyscallWriter {
         fd: FD_PUBLIC_VALUES,
     };
@@ -68,12 +220,384 @@ pub fn commit<T: Serialize>(value: &T) {
 }
 
 pub fn commit_slice(buf: &[u8]) {
+    start_legacy_commit();
     let mut my_writer = SyscallWriter {
         fd: FD_PUBLIC_VALUES,
     };
     my_writer.write_all(buf).unwrap();
 }
 
+/// Tag of a chunked commitment, see [start_chunked_commit]. Must match `CHUNKED_COMMIT_TAG` in
+/// `core/src/io.rs`.
+pub const CHUNKED_COMMIT_TAG: &[u8; 8] = b"SP1CHNK\x01";
+
+/// The hash chain of the chunks committed with [commit_chunk].
+///
+/// The chain starts at `sha256(CHUNKED_COMMIT_TAG || chunk_size)`, and each chunk is folded into
+/// it as `chain = sha256(chain || sha256(chunk))`. Every chunk has `chunk_size` bytes, except the
+/// last one which may be shorter, but not empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkChain {
+    pub chunk_size: u32,
+    pub num_chunks: u64,
+    pub chain: [u8; 32],
+    /// Whether a chunk shorter than `chunk_size` was pushed, which ends the chain.
+    partial: bool,
+}
+
+impl ChunkChain {
+    pub fn new(chunk_size: u32) -> Self {
+        assert!(chunk_size > 0, "the chunk size must be positive");
+        let mut hasher = Sha256::new();
+        hasher.update(CHUNKED_COMMIT_TAG);
+        hasher.update(chunk_size.to_le_bytes());
+        Self {
+            chunk_size,
+            num_chunks: 0,
+            chain: hasher.finalize().into(),
+            partial: false,
+        }
+    }
+
+    /// Folds `chunk` into the chain, returning its digest.
+    pub fn push(&mut self, chunk: &[u8]) -> [u8; 32] {
+        assert!(
+            !self.partial,
+            "only the last chunk may be shorter than the chunk size"
+        );
+        assert!(
+            !chunk.is_empty() && chunk.len() <= self.chunk_size as usize,
+            "chunk of {} bytes for a chunk size of {}",
+            chunk.len(),
+            self.chunk_size
+        );
+        self.partial = chunk.len() < self.chunk_size as usize;
+        let digest: [u8; 32] = Sha256::digest(chunk).into();
+        let mut hasher = Sha256::new();
+        hasher.update(self.chain);
+        hasher.update(digest);
+        self.chain = hasher.finalize().into();
+        self.num_chunks += 1;
+        digest
+    }
+
+    /// The public values of the chunked commitment:
+    ///
+    /// CHUNKED_COMMIT_TAG || chunk_size || num_chunks || chain
+    ///
+    /// where `chunk_size` is a little-endian `u32` and `num_chunks` a little-endian `u64`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CHUNKED_COMMIT_TAG.len() + 4 + 8 + 32);
+        bytes.extend_from_slice(CHUNKED_COMMIT_TAG);
+        bytes.extend_from_slice(&self.chunk_size.to_le_bytes());
+        bytes.extend_from_slice(&self.num_chunks.to_le_bytes());
+        bytes.extend_from_slice(&self.chain);
+        bytes
+    }
+}
+
+static CHUNK_CHAIN: Mutex<Option<ChunkChain>> = Mutex::new(None);
+
+static LEGACY_COMMIT: AtomicBool = AtomicBool::new(false);
+
+/// Records that the program commits its public values with [commit], [commit_slice] or
+/// [commit_kv].
+fn start_legacy_commit() {
+    assert!(
+        CHUNK_CHAIN.lock().unwrap().is_none(),
+        "cannot commit public values after starting a chunked commit"
+    );
+    assert!(
+        !SEGMENT_SUSPENDED.load(Ordering::Relaxed),
+        "cannot commit public values after suspending the segment state"
+    );
+    LEGACY_COMMIT.store(true, Ordering::Relaxed);
+}
+
+/// Commit the outputs of the program in chunks of `chunk_size` bytes with [commit_chunk], for
+/// outputs too large to be buffered, or to be carried in the public values of the proof.
+///
+/// The public values are then only the hash chain of the chunks (see [ChunkChain]), committed
+/// when the program halts, while the chunks themselves are captured by the host, which checks
+/// them against the chain with `SP1PublicValues::output_chunks`. A program committing chunks
+/// cannot use [commit] or [commit_slice].
+pub fn start_chunked_commit(chunk_size: usize) {
+    assert!(
+        !LEGACY_COMMIT.load(Ordering::Relaxed),
+        "cannot start a chunked commit after committing public values"
+    );
+    let mut chain = CHUNK_CHAIN.lock().unwrap();
+    assert!(chain.is_none(), "the chunked commit was already started");
+    let chunk_size = u32::try_from(chunk_size).expect("the chunk size must fit in a u32");
+    *chain = Some(ChunkChain::new(chunk_size));
+}
+
+/// Commit a chunk of the outputs of the program, see [start_chunked_commit]. Every chunk must have
+/// exactly the chunk size, except the last one which may be shorter.
+pub fn commit_chunk(chunk: &[u8]) {
+    let mut chain = CHUNK_CHAIN.lock().unwrap();
+    chain
+        .as_mut()
+        .expect("commit_chunk requires start_chunked_commit")
+        .push(chunk);
+    write(FD_OUTPUT_CHUNKS, chunk);
+}
+
+/// Commit the hash chain of the chunked commit, if the program started one. This is called by the
+/// entrypoint when the program halts.
+pub fn finalize_chunked_commit() {
+    // The lock is poisoned if the program panicked while committing a chunk, and halts anyway.
+    let mut chain = CHUNK_CHAIN.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(chain) = chain.take() {
+        write(FD_PUBLIC_VALUES, &chain.encode());
+    }
+}
+
+/// Tag of the state digests of a segment, see [suspend_state]. Must match `SEGMENT_DIGESTS_TAG` in
+/// `core/src/io.rs`.
+pub const SEGMENT_DIGESTS_TAG: &[u8; 8] = b"SP1SEGM\x01";
+
+/// Domain separator for the digest of a segment state. Must match `SEGMENT_STATE_DOMAIN` in
+/// `core/src/io.rs`.
+pub const SEGMENT_STATE_DOMAIN: &[u8] = b"SP1_SEGMENT_STATE_V1";
+
+/// The digest of the serialized state of a segment, `sha256(SEGMENT_STATE_DOMAIN || state)`.
+pub fn segment_state_digest(state: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SEGMENT_STATE_DOMAIN);
+    hasher.update(state);
+    hasher.finalize().into()
+}
+
+/// The digests of the state a segment resumed from and of the state it suspended, committed as
+/// the last public values of the segment:
+///
+/// SEGMENT_DIGESTS_TAG || input || output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentDigests {
+    pub input: [u8; 32],
+    pub output: [u8; 32],
+}
+
+impl SegmentDigests {
+    /// The length of the encoding of the digests.
+    pub const LEN: usize = 8 + 32 + 32;
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(SEGMENT_DIGESTS_TAG);
+        bytes[8..40].copy_from_slice(&self.input);
+        bytes[40..].copy_from_slice(&self.output);
+        bytes
+    }
+
+    /// Decodes the digests from the end of the public values of a segment, returning `None` if
+    /// they do not end with them.
+    pub fn decode(public_values: &[u8]) -> Option<Self> {
+        let start = public_values.len().checked_sub(Self::LEN)?;
+        let bytes = public_values[start..].strip_prefix(SEGMENT_DIGESTS_TAG.as_slice())?;
+        Some(Self {
+            input: bytes[..32].try_into().unwrap(),
+            output: bytes[32..].try_into().unwrap(),
+        })
+    }
+}
+
+/// The digest of the state read by [resume_state].
+static SEGMENT_INPUT: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+
+static SEGMENT_SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Resume a segment of a computation split across several proofs, reading its state from the next
+/// buffer of the input stream.
+///
+/// The digest of the state is committed by [suspend_state], next to the digest of the state the
+/// segment ends with, so that the proofs of consecutive segments can be chained by checking that
+/// each one resumes from the state the previous one suspended. Panics with the [ReadError] if the
+/// buffer cannot be deserialized as an `S`, and if the state was already resumed.
+pub fn resume_state<S: DeserializeOwned>() -> S {
+    let mut input = SEGMENT_INPUT.lock().unwrap();
+    assert!(input.is_none(), "the segment state was already resumed");
+    let index = unsafe { READ_INDEX };
+    let bytes = read_vec();
+    *input = Some(segment_state_digest(&bytes));
+    deserialize_input(index, &bytes).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Suspend the segment resumed with [resume_state], ending it with the state `state`.
+///
+/// The serialized state is written to the host, which gives it to the next segment, and the
+/// [SegmentDigests] of the segment are committed as its last public values: nothing can be
+/// committed after this call, and a segment cannot use a chunked commit.
+pub fn suspend_state<S: Serialize>(state: &S) {
+    let input = SEGMENT_INPUT
+        .lock()
+        .unwrap()
+        .expect("suspend_state requires resume_state");
+    start_legacy_commit();
+    SEGMENT_SUSPENDED.store(true, Ordering::Relaxed);
+
+    let bytes = bincode::serialize(state).expect("serialization failed");
+    write(FD_SEGMENT_STATE, &bytes);
+    let digests = SegmentDigests {
+        input,
+        output: segment_state_digest(&bytes),
+    };
+    write(FD_PUBLIC_VALUES, &digests.encode());
+}
+
+/// Tag of a key-value commitment, see [commit_kv]. Must match `KV_COMMIT_TAG` in
+/// `core/src/io.rs`.
+pub const KV_COMMIT_TAG: &[u8; 8] = b"SP1KVCM\x01";
+
+/// The canonical encoding of an entry of a key-value commitment:
+///
+/// key_len || key || value_len || value
+///
+/// where `key_len` and `value_len` are little-endian `u32`s.
+pub fn encode_kv_entry(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let key_len = u32::try_from(key.len()).expect("the key must be shorter than 2^32 bytes");
+    let value_len = u32::try_from(value.len()).expect("the value must be shorter than 2^32 bytes");
+    let mut entry = Vec::with_capacity(4 + key.len() + 4 + value.len());
+    entry.extend_from_slice(&key_len.to_le_bytes());
+    entry.extend_from_slice(key);
+    entry.extend_from_slice(&value_len.to_le_bytes());
+    entry.extend_from_slice(value);
+    entry
+}
+
+/// The root of the entries committed with [commit_kv].
+///
+/// The root starts at `keccak256(KV_COMMIT_TAG)`, and each entry is folded into it as
+/// `root = keccak256(root || keccak256(entry))`, where `entry` is its [encode_kv_entry]. The keys
+/// must be strictly increasing in lexicographic order, so that a set of entries has a single root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvAccumulator {
+    pub num_entries: u64,
+    pub root: [u8; 32],
+    /// The key of the last entry, which the next key must be greater than.
+    last_key: Option<Vec<u8>>,
+}
+
+impl Default for KvAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KvAccumulator {
+    pub fn new() -> Self {
+        Self {
+            num_entries: 0,
+            root: crate::sha3::keccak256(KV_COMMIT_TAG),
+            last_key: None,
+        }
+    }
+
+    /// Folds the entry of `key` and `value` into the root, returning its encoding.
+    ///
+    /// Panics if `key` is not greater than the key of the previous entry.
+    pub fn push(&mut self, key: &[u8], value: &[u8]) -> Vec<u8> {
+        if let Some(last_key) = &self.last_key {
+            assert!(
+                last_key.as_slice() < key,
+                "the keys of a key-value commitment must be strictly increasing"
+            );
+        }
+        self.last_key = Some(key.to_vec());
+        let entry = encode_kv_entry(key, value);
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.root);
+        bytes[32..].copy_from_slice(&crate::sha3::keccak256(&entry));
+        self.root = crate::sha3::keccak256(&bytes);
+        self.num_entries += 1;
+        entry
+    }
+
+    /// The public values of the key-value commitment:
+    ///
+    /// KV_COMMIT_TAG || num_entries || root
+    ///
+    /// where `num_entries` is a little-endian `u64`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(KV_COMMIT_TAG.len() + 8 + 32);
+        bytes.extend_from_slice(KV_COMMIT_TAG);
+        bytes.extend_from_slice(&self.num_entries.to_le_bytes());
+        bytes.extend_from_slice(&self.root);
+        bytes
+    }
+}
+
+/// Commit key-value entries, such as the changes of a state, by their root only.
+///
+/// The entries are folded into a [KvAccumulator] with the keccak precompile, and only its root and
+/// number of entries are committed to the public values, while the entries themselves are written
+/// to the host, which checks them against the root with `KvCommitment::verify_entries`. The keys
+/// must be given in strictly increasing order, and the program panics otherwise.
+pub fn commit_kv<K: AsRef<[u8]>, V: AsRef<[u8]>>(entries: impl IntoIterator<Item = (K, V)>) {
+    start_legacy_commit();
+    let mut accumulator = KvAccumulator::new();
+    for (key, value) in entries {
+        let entry = accumulator.push(key.as_ref(), value.as_ref());
+        write(FD_KV_ENTRIES, &entry);
+    }
+    write(FD_PUBLIC_VALUES, &accumulator.encode());
+}
+
+/// Tag of a span committed with [commit_span_cycles]. Must match `SPAN_CYCLES_TAG` in
+/// `core/src/io.rs`.
+pub const SPAN_CYCLES_TAG: &[u8; 8] = b"SP1SPAN\x01";
+
+/// The number of cycles executed so far, modulo 2^32.
+///
+/// The value is given by the prover and is not constrained, so a span measured with it is only
+/// trusted as much as the prover is.
+pub fn cycle_count() -> u32 {
+    unsafe { syscall_cycle_count() }
+}
+
+/// The gas used so far, metered with the gas schedule of the runtime executing the program.
+///
+/// Like [cycle_count], the value is given by the prover and is not constrained, so a budget
+/// enforced with it is only trusted as much as the prover is.
+pub fn gas_used() -> u64 {
+    // The halves are read by two syscalls, which use gas in between: read the high word again
+    // until it is the same on both sides of the low one.
+    loop {
+        let high = unsafe { syscall_gas_used(1) };
+        let low = unsafe { syscall_gas_used(0) };
+        if unsafe { syscall_gas_used(1) } == high {
+            return ((high as u64) << 32) | low as u64;
+        }
+    }
+}
+
+/// Hint that the current point of the program is a good place to end the shard, for example
+/// between two iterations of a loop whose state is small.
+///
+/// The runtime ends the shard at a hint once the shard is at least a fraction of the shard size
+/// long, see `SP1CoreOpts::shard_boundary_hint_fraction`, and ignores it otherwise. This only moves
+/// the shard boundaries, so the hint has no effect on the program nor on the validity of its proof.
+pub fn hint_shard_boundary() {
+    unsafe { syscall_hint_shard_boundary() }
+}
+
+/// Commit the number of cycles of a span, measured with [cycle_count].
+///
+/// The span is committed as `SPAN_CYCLES_TAG || name_len || name || cycles`, where `name_len` is a
+/// little-endian `u32` and `cycles` a little-endian `u64`, and can be read back on the host with
+/// `SP1PublicValues::read_span_cycles`. If the span is also delimited by `cycle-tracker-start` and
+/// `cycle-tracker-end` markers, and `cycles` is the difference between the first and the last
+/// [cycle_count] read between them, the host checks it against its own measurement when executing.
+pub fn commit_span_cycles(name: &str, cycles: u64) {
+    let mut bytes = Vec::with_capacity(SPAN_CYCLES_TAG.len() + 4 + name.len() + 8);
+    bytes.extend_from_slice(SPAN_CYCLES_TAG);
+    bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.extend_from_slice(&cycles.to_le_bytes());
+    commit_slice(&bytes);
+}
+
 pub fn hint<T: Serialize>(value: &T) {
     let writer = SyscallWriter { fd: FD_HINT };
     bincode::serialize_into(writer, value).expect("serialization failed");
@@ -88,3 +612,96 @@ pub fn hint_slice(buf: &[u8]) {
 pub fn write(fd: u32, buf: &[u8]) {
     SyscallWriter { fd }.write_all(buf).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Written {
+        a: u32,
+        b: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Expected {
+        a: u32,
+        b: u64,
+    }
+
+    #[test]
+    fn test_chunk_chain() {
+        let mut chain = ChunkChain::new(4);
+        let initial = chain.chain;
+        assert_eq!(
+            chain.push(b"abcd"),
+            <[u8; 32]>::from(Sha256::digest(b"abcd"))
+        );
+        assert_ne!(chain.chain, initial);
+        chain.push(b"ef");
+        assert_eq!(chain.num_chunks, 2);
+
+        let bytes = chain.encode();
+        assert_eq!(bytes.len(), 52);
+        assert_eq!(&bytes[..8], CHUNKED_COMMIT_TAG);
+        assert_eq!(&bytes[8..12], &4u32.to_le_bytes());
+        assert_eq!(&bytes[12..20], &2u64.to_le_bytes());
+        assert_eq!(&bytes[20..], &chain.chain);
+
+        // The chain depends on the chunk size.
+        assert_ne!(ChunkChain::new(5).chain, initial);
+    }
+
+    #[test]
+    #[should_panic(expected = "only the last chunk may be shorter than the chunk size")]
+    fn test_chunk_chain_partial_not_last() {
+        let mut chain = ChunkChain::new(4);
+        chain.push(b"ab");
+        chain.push(b"cdef");
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk of 5 bytes for a chunk size of 4")]
+    fn test_chunk_chain_chunk_too_large() {
+        ChunkChain::new(4).push(b"abcde");
+    }
+
+    #[test]
+    fn test_segment_digests() {
+        let digests = SegmentDigests {
+            input: segment_state_digest(&bincode::serialize(&1u64).unwrap()),
+            output: segment_state_digest(&bincode::serialize(&2u64).unwrap()),
+        };
+        assert_ne!(digests.input, digests.output);
+
+        // The digests are decoded from the end of the public values.
+        let mut public_values = vec![7; 5];
+        public_values.extend_from_slice(&digests.encode());
+        assert_eq!(SegmentDigests::decode(&public_values), Some(digests));
+        assert_eq!(
+            SegmentDigests::decode(&public_values[..public_values.len() - 1]),
+            None
+        );
+        assert_eq!(SegmentDigests::decode(&public_values[5..]), Some(digests));
+        assert_eq!(SegmentDigests::decode(&public_values[6..]), None);
+    }
+
+    #[test]
+    fn test_read_error() {
+        let bytes = bincode::serialize(&Written { a: 1, b: 2 }).unwrap();
+        let err = deserialize_input::<Expected>(2, &bytes).unwrap_err();
+        assert_eq!(err.index, 2);
+        assert_eq!(err.available, 8);
+        assert_eq!(err.prefix, "0100000002000000");
+
+        let message = err.to_string();
+        assert!(message.contains("read index 2"), "{}", message);
+        assert!(message.contains("io::tests::Expected"), "{}", message);
+
+        let value: u64 = deserialize_input(0, &bytes).unwrap();
+        assert_eq!(value, 2 << 32 | 1);
+    }
+}
@@ -0,0 +1,268 @@
+//! The groups G1 and G2 of BLS12-381, as points of y^2 = x^3 + b over Fp and Fp2 in Jacobian
+//! coordinates.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::fp::{Fp, Fp2};
+
+/// The field operations the group law needs, implemented by [Fp] and [Fp2].
+pub(crate) trait Field:
+    Copy + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn is_zero(&self) -> bool;
+
+    fn square(&self) -> Self;
+
+    fn double(&self) -> Self;
+
+    fn invert(&self) -> Option<Self>;
+}
+
+impl Field for Fp {
+    const ZERO: Self = Fp::ZERO;
+    const ONE: Self = Fp::ONE;
+
+    fn is_zero(&self) -> bool {
+        Fp::is_zero(self)
+    }
+
+    fn square(&self) -> Self {
+        Fp::square(self)
+    }
+
+    fn double(&self) -> Self {
+        Fp::double(self)
+    }
+
+    fn invert(&self) -> Option<Self> {
+        Fp::invert(self)
+    }
+}
+
+impl Field for Fp2 {
+    const ZERO: Self = Fp2::ZERO;
+    const ONE: Self = Fp2::ONE;
+
+    fn is_zero(&self) -> bool {
+        Fp2::is_zero(self)
+    }
+
+    fn square(&self) -> Self {
+        Fp2::square(self)
+    }
+
+    fn double(&self) -> Self {
+        Fp2::double(self)
+    }
+
+    fn invert(&self) -> Option<Self> {
+        Fp2::invert(self)
+    }
+}
+
+/// An affine point, which is never the point at infinity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Affine<F> {
+    pub(crate) x: F,
+    pub(crate) y: F,
+}
+
+/// A point `(x / z^2, y / z^3)` in Jacobian coordinates, the point at infinity having `z = 0`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Jacobian<F> {
+    pub(crate) x: F,
+    pub(crate) y: F,
+    pub(crate) z: F,
+}
+
+pub(crate) type G1 = Jacobian<Fp>;
+
+pub(crate) type G2 = Jacobian<Fp2>;
+
+/// The generator of G1.
+pub(crate) const G1_GENERATOR: Affine<Fp> = Affine {
+    x: Fp([
+        0xdb22c6bb, 0xfb3af00a, 0xf97a1aef, 0x6c55e83f, 0x171bac58, 0xa14e3a3f, 0x9774b905,
+        0xc3688c4f, 0x4fa9ac0f, 0x2695638c, 0x3197d794, 0x17f1d3a7,
+    ]),
+    y: Fp([
+        0x46c5e7e1, 0x0caa2329, 0xa2888ae4, 0xd03cc744, 0x2c04b3ed, 0x00db18cb, 0xd5d00af6,
+        0xfcf5e095, 0x741d8ae4, 0xa09e30ed, 0xe3aaa0f1, 0x08b3f481,
+    ]),
+};
+
+/// The generator of G2.
+pub(crate) const G2_GENERATOR: Affine<Fp2> = Affine {
+    x: Fp2 {
+        c0: Fp([
+            0xc121bdb8, 0xd48056c8, 0xa805bbef, 0x0bac0326, 0x7ae3d177, 0xb4510b64, 0xfa403b02,
+            0xc6e47ad4, 0x2dc51051, 0x26080527, 0xf08f0a91, 0x024aa2b2,
+        ]),
+        c1: Fp([
+            0x5d042b7e, 0xe5ac7d05, 0x13945d57, 0x334cf112, 0xdc7f5049, 0xb5da61bb, 0x9920b61a,
+            0x596bd0d0, 0x88274f65, 0x7dacd3a0, 0x52719f60, 0x13e02b60,
+        ]),
+    },
+    y: Fp2 {
+        c0: Fp([
+            0x08b82801, 0xe1935486, 0x3baca289, 0x923ac9cc, 0x5160d12c, 0x6d429a69, 0x8cbdd3a7,
+            0xadfd9baa, 0xda2e351a, 0x8cc9cdc6, 0x727d6e11, 0x0ce5d527,
+        ]),
+        c1: Fp([
+            0xf05f79be, 0xaaa9075f, 0x5cec1da1, 0x3f370d27, 0x572e99ab, 0x267492ab, 0x85a763af,
+            0xcb3e287e, 0x2bc28b99, 0x32acd2b0, 0x2ea734cc, 0x0606c4a0,
+        ]),
+    },
+};
+
+/// [tau]G2 of the Ethereum KZG ceremony, the second G2 point of the mainnet trusted setup.
+pub(crate) const TAU_G2: Affine<Fp2> = Affine {
+    x: Fp2 {
+        c0: Fp([
+            0x20c1def2, 0xc98edada, 0x621000ed, 0x087041de, 0x7ba4c60b, 0xa3685147, 0xcceceac9,
+            0x3926c911, 0xb38608e2, 0x734429b7, 0x53492714, 0x185cbfee,
+        ]),
+        c1: Fp([
+            0xf3499f72, 0xafaaab24, 0x0cb452d2, 0x2914e587, 0x615ac53d, 0x1009a2ce, 0xcbfbefa8,
+            0x26187075, 0x230af389, 0x843bc287, 0x8cdeb128, 0x15bfd7dd,
+        ]),
+    },
+    y: Fp2 {
+        c0: Fp([
+            0xbb832a99, 0xee689bfb, 0x5941f383, 0x4ce26d10, 0x96a9c979, 0xe82451a4, 0x0e28de18,
+            0x13156949, 0x99d1fca2, 0xd7d5ee85, 0xb96b626d, 0x014353bd,
+        ]),
+        c1: Fp([
+            0x0d0a154f, 0x23048ef3, 0x3d7ac9cd, 0x9495346f, 0x9bfa0789, 0xda5ed1ba, 0xfc63671f,
+            0xef79de09, 0xe0181b4b, 0x03432fca, 0x0a325295, 0x1666c54b,
+        ]),
+    },
+};
+
+/// The order r of G1 and G2, as big endian bytes.
+pub(crate) const GROUP_ORDER: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+impl<F: Field> From<Affine<F>> for Jacobian<F> {
+    fn from(p: Affine<F>) -> Self {
+        Jacobian {
+            x: p.x,
+            y: p.y,
+            z: F::ONE,
+        }
+    }
+}
+
+impl<F: Field> Jacobian<F> {
+    pub(crate) const IDENTITY: Self = Jacobian {
+        x: F::ONE,
+        y: F::ONE,
+        z: F::ZERO,
+    };
+
+    pub(crate) fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Returns the affine coordinates of the point, or `None` for the point at infinity.
+    pub(crate) fn to_affine(self) -> Option<Affine<F>> {
+        self.z.invert().map(|z_inv| {
+            let z_inv_squared = z_inv.square();
+            Affine {
+                x: self.x * z_inv_squared,
+                y: self.y * z_inv_squared * z_inv,
+            }
+        })
+    }
+
+    pub(crate) fn double(&self) -> Self {
+        // dbl-2009-l, for a = 0.
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = b.square();
+        let d = ((self.x + b).square() - a - c).double();
+        let e = a.double() + a;
+        let f = e.square();
+        let x = f - d.double();
+        let y = e * (d - x) - c.double().double().double();
+        let z = (self.y * self.z).double();
+        Jacobian { x, y, z }
+    }
+
+    /// Multiplies the point by a big endian scalar.
+    pub(crate) fn mul_by_scalar(&self, scalar: &[u8]) -> Self {
+        let mut result = Self::IDENTITY;
+        for byte in scalar {
+            for i in (0..8).rev() {
+                if !result.is_identity() {
+                    result = result.double();
+                }
+                if (byte >> i) & 1 == 1 {
+                    result = result + *self;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<F: Field> Add for Jacobian<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        // add-2007-bl, falling back to doubling for equal points.
+        if self.is_identity() {
+            return rhs;
+        }
+        if rhs.is_identity() {
+            return self;
+        }
+        let z1_squared = self.z.square();
+        let z2_squared = rhs.z.square();
+        let u1 = self.x * z2_squared;
+        let u2 = rhs.x * z1_squared;
+        let s1 = self.y * rhs.z * z2_squared;
+        let s2 = rhs.y * self.z * z1_squared;
+        let h = u2 - u1;
+        if h.is_zero() {
+            return if s1 == s2 {
+                self.double()
+            } else {
+                Self::IDENTITY
+            };
+        }
+        let i = h.double().square();
+        let j = h * i;
+        let r = (s2 - s1).double();
+        let v = u1 * i;
+        let x = r.square() - j - v.double();
+        let y = r * (v - x) - (s1 * j).double();
+        let z = ((self.z + rhs.z).square() - z1_squared - z2_squared) * h;
+        Jacobian { x, y, z }
+    }
+}
+
+impl<F: Field> Neg for Jacobian<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Jacobian {
+            x: self.x,
+            y: -self.y,
+            z: self.z,
+        }
+    }
+}
+
+impl<F: Field> Sub for Jacobian<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
@@ -0,0 +1,308 @@
+//! The base field of BLS12-381 and its quadratic extension.
+//!
+//! Inside the zkVM, every operation on [Fp] is one call to the bls12381_fp_* precompiles, so the
+//! whole pairing is proven by the `Bls12381FpOp` chip. On the host, the same operations are done
+//! with big integers, which keeps the code path identical for testing.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// The number of words of an element of the BLS12-381 base field.
+const FP_NUM_WORDS: usize = 12;
+
+/// The modulus p of the base field, as little endian words.
+const MODULUS: [u32; FP_NUM_WORDS] = [
+    0xffffaaab, 0xb9feffff, 0xb153ffff, 0x1eabfffe, 0xf6b0f624, 0x6730d2a0, 0xf38512bf, 0x64774b84,
+    0x434bacd7, 0x4b1ba7b6, 0x397fe69a, 0x1a0111ea,
+];
+
+/// (p - 1) / 2, the largest element that is not lexicographically largest.
+const MODULUS_MINUS_ONE_DIV_TWO: [u32; FP_NUM_WORDS] = [
+    0xffffd555, 0xdcff7fff, 0x58a9ffff, 0x0f55ffff, 0x7b587b12, 0xb3986950, 0x79c2895f, 0xb23ba5c2,
+    0x21a5d66b, 0x258dd3db, 0x1cbff34d, 0x0d0088f5,
+];
+
+/// (p + 1) / 4, the exponent of a square root since p = 3 mod 4.
+const SQRT_EXPONENT: [u32; FP_NUM_WORDS] = [
+    0xffffeaab, 0xee7fbfff, 0xac54ffff, 0x07aaffff, 0x3dac3d89, 0xd9cc34a8, 0x3ce144af, 0xd91dd2e1,
+    0x90d2eb35, 0x92c6e9ed, 0x8e5ff9a6, 0x0680447a,
+];
+
+/// An operation of the bls12381_fp_* precompiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FpOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of times each [FpOp] was applied on this thread, in the order of the variants.
+    pub(crate) static FP_OP_COUNTS: std::cell::Cell<[usize; 4]> =
+        const { std::cell::Cell::new([0; 4]) };
+}
+
+/// Sets `x` to `x op y`. Both operands must be reduced, and `y` must not be zero for a division.
+fn apply(op: FpOp, x: &mut [u32; FP_NUM_WORDS], y: &[u32; FP_NUM_WORDS]) {
+    #[cfg(test)]
+    FP_OP_COUNTS.with(|counts| {
+        let mut c = counts.get();
+        c[op as usize] += 1;
+        counts.set(c);
+    });
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            use crate::bls12381::{fp_add, fp_div, fp_mul, fp_sub};
+            match op {
+                FpOp::Add => fp_add(x, y),
+                FpOp::Sub => fp_sub(x, y),
+                FpOp::Mul => fp_mul(x, y),
+                FpOp::Div => fp_div(x, y),
+            }
+        } else {
+            use num::BigUint;
+
+            let p = BigUint::from_slice(&MODULUS);
+            let a = BigUint::from_slice(x);
+            let b = BigUint::from_slice(y);
+            let result = match op {
+                FpOp::Add => (a + b) % &p,
+                FpOp::Sub => (&p + a - b) % &p,
+                FpOp::Mul => (a * b) % &p,
+                FpOp::Div => (a * b.modpow(&(&p - 2u32), &p)) % &p,
+            };
+            *x = [0; FP_NUM_WORDS];
+            for (word, digit) in x.iter_mut().zip(result.to_u32_digits()) {
+                *word = digit;
+            }
+        }
+    }
+}
+
+/// Compares two little endian words representations.
+fn cmp_words(x: &[u32; FP_NUM_WORDS], y: &[u32; FP_NUM_WORDS]) -> std::cmp::Ordering {
+    x.iter().rev().cmp(y.iter().rev())
+}
+
+/// An element of the BLS12-381 base field, as reduced little endian words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fp(pub(crate) [u32; FP_NUM_WORDS]);
+
+impl Fp {
+    pub(crate) const ZERO: Fp = Fp([0; FP_NUM_WORDS]);
+
+    pub(crate) const ONE: Fp = Fp([1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Decodes a big endian element, or returns `None` if it is not reduced.
+    pub(crate) fn from_be_bytes(bytes: &[u8; 48]) -> Option<Fp> {
+        let mut words = [0u32; FP_NUM_WORDS];
+        for (word, chunk) in words.iter_mut().rev().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        (cmp_words(&words, &MODULUS).is_lt()).then_some(Fp(words))
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        *self == Fp::ZERO
+    }
+
+    /// Whether the element is larger than its negation, as in the ZCash serialization format.
+    pub(crate) fn lexicographically_largest(&self) -> bool {
+        cmp_words(&self.0, &MODULUS_MINUS_ONE_DIV_TWO).is_gt()
+    }
+
+    pub(crate) fn square(&self) -> Fp {
+        *self * *self
+    }
+
+    pub(crate) fn double(&self) -> Fp {
+        *self + *self
+    }
+
+    pub(crate) fn invert(&self) -> Option<Fp> {
+        (!self.is_zero()).then(|| Fp::ONE / *self)
+    }
+
+    /// Raises the element to a little endian words exponent.
+    fn pow_vartime(&self, exponent: &[u32; FP_NUM_WORDS]) -> Fp {
+        let mut result = Fp::ONE;
+        for word in exponent.iter().rev() {
+            for i in (0..32).rev() {
+                result = result.square();
+                if (word >> i) & 1 == 1 {
+                    result = result * *self;
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns a square root of the element, or `None` if it is not a square.
+    pub(crate) fn sqrt(&self) -> Option<Fp> {
+        let root = self.pow_vartime(&SQRT_EXPONENT);
+        (root.square() == *self).then_some(root)
+    }
+}
+
+impl Add for Fp {
+    type Output = Fp;
+
+    fn add(mut self, rhs: Fp) -> Fp {
+        apply(FpOp::Add, &mut self.0, &rhs.0);
+        self
+    }
+}
+
+impl Sub for Fp {
+    type Output = Fp;
+
+    fn sub(mut self, rhs: Fp) -> Fp {
+        apply(FpOp::Sub, &mut self.0, &rhs.0);
+        self
+    }
+}
+
+impl Mul for Fp {
+    type Output = Fp;
+
+    fn mul(mut self, rhs: Fp) -> Fp {
+        apply(FpOp::Mul, &mut self.0, &rhs.0);
+        self
+    }
+}
+
+impl Div for Fp {
+    type Output = Fp;
+
+    fn div(mut self, rhs: Fp) -> Fp {
+        apply(FpOp::Div, &mut self.0, &rhs.0);
+        self
+    }
+}
+
+impl Neg for Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Fp {
+        if self.is_zero() {
+            self
+        } else {
+            Fp::ZERO - self
+        }
+    }
+}
+
+/// An element `c0 + c1 * u` of Fp2 = Fp[u] / (u^2 + 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fp2 {
+    pub(crate) c0: Fp,
+    pub(crate) c1: Fp,
+}
+
+impl Fp2 {
+    pub(crate) const ZERO: Fp2 = Fp2 {
+        c0: Fp::ZERO,
+        c1: Fp::ZERO,
+    };
+
+    pub(crate) const ONE: Fp2 = Fp2 {
+        c0: Fp::ONE,
+        c1: Fp::ZERO,
+    };
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    pub(crate) fn square(&self) -> Fp2 {
+        // (a + bu)^2 = (a + b)(a - b) + 2abu.
+        let ab = self.c0 * self.c1;
+        Fp2 {
+            c0: (self.c0 + self.c1) * (self.c0 - self.c1),
+            c1: ab.double(),
+        }
+    }
+
+    pub(crate) fn double(&self) -> Fp2 {
+        *self + *self
+    }
+
+    /// Multiplies by the nonresidue u + 1 used to build Fp6.
+    pub(crate) fn mul_by_nonresidue(&self) -> Fp2 {
+        Fp2 {
+            c0: self.c0 - self.c1,
+            c1: self.c0 + self.c1,
+        }
+    }
+
+    pub(crate) fn mul_by_fp(&self, rhs: Fp) -> Fp2 {
+        Fp2 {
+            c0: self.c0 * rhs,
+            c1: self.c1 * rhs,
+        }
+    }
+
+    /// The p-power Frobenius endomorphism, which is the conjugation.
+    pub(crate) fn frobenius_map(&self) -> Fp2 {
+        Fp2 {
+            c0: self.c0,
+            c1: -self.c1,
+        }
+    }
+
+    pub(crate) fn invert(&self) -> Option<Fp2> {
+        // 1 / (a + bu) = (a - bu) / (a^2 + b^2).
+        (self.c0.square() + self.c1.square()).invert().map(|t| Fp2 {
+            c0: self.c0 * t,
+            c1: -(self.c1 * t),
+        })
+    }
+}
+
+impl Add for Fp2 {
+    type Output = Fp2;
+
+    fn add(self, rhs: Fp2) -> Fp2 {
+        Fp2 {
+            c0: self.c0 + rhs.c0,
+            c1: self.c1 + rhs.c1,
+        }
+    }
+}
+
+impl Sub for Fp2 {
+    type Output = Fp2;
+
+    fn sub(self, rhs: Fp2) -> Fp2 {
+        Fp2 {
+            c0: self.c0 - rhs.c0,
+            c1: self.c1 - rhs.c1,
+        }
+    }
+}
+
+impl Mul for Fp2 {
+    type Output = Fp2;
+
+    fn mul(self, rhs: Fp2) -> Fp2 {
+        // Karatsuba, with u^2 = -1.
+        let aa = self.c0 * rhs.c0;
+        let bb = self.c1 * rhs.c1;
+        Fp2 {
+            c0: aa - bb,
+            c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - aa - bb,
+        }
+    }
+}
+
+impl Neg for Fp2 {
+    type Output = Fp2;
+
+    fn neg(self) -> Fp2 {
+        Fp2 {
+            c0: -self.c0,
+            c1: -self.c1,
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! The KZG point evaluation of EIP-4844, for guests verifying blob data.
+//!
+//! The two pairings of the check are computed in guest code on top of the bls12381_fp_*
+//! precompiles, so every base field operation is a row of the `Bls12381FpOp` chip and the whole
+//! verification is constrained. [point_evaluation] mirrors the ABI of the EVM precompile at
+//! address 0x0a, and [verify_kzg_proof] the function of the same name of c-kzg.
+
+mod curve;
+mod fp;
+mod pairing;
+mod tower;
+
+use std::fmt;
+
+use k256::sha2::{Digest, Sha256};
+
+use curve::{Jacobian, G1, G1_GENERATOR, G2, G2_GENERATOR, GROUP_ORDER, TAU_G2};
+use fp::Fp;
+
+/// The version byte of the versioned hash of a KZG commitment.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The number of field elements of a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: u64 = 4096;
+
+/// The modulus of the scalar field of BLS12-381, as big endian bytes.
+pub const BLS_MODULUS: [u8; 32] = GROUP_ORDER;
+
+/// The length of the input of [point_evaluation].
+pub const POINT_EVALUATION_INPUT_LEN: usize = 192;
+
+/// The output of a successful [point_evaluation]: [FIELD_ELEMENTS_PER_BLOB] and [BLS_MODULUS] as
+/// 32-byte big endian integers.
+pub const POINT_EVALUATION_OUTPUT: [u8; 64] = {
+    let mut output = [0u8; 64];
+    let field_elements = FIELD_ELEMENTS_PER_BLOB.to_be_bytes();
+    let mut i = 0;
+    while i < 8 {
+        output[24 + i] = field_elements[i];
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 32 {
+        output[32 + i] = BLS_MODULUS[i];
+        i += 1;
+    }
+    output
+};
+
+/// Why a compressed G1 point was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointError {
+    /// The flags are invalid, or the x coordinate is not reduced.
+    InvalidEncoding,
+    /// There is no point with this x coordinate.
+    NotOnCurve,
+    /// The point is not in the subgroup of order r.
+    NotInSubgroup,
+}
+
+impl fmt::Display for PointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointError::InvalidEncoding => write!(f, "invalid encoding"),
+            PointError::NotOnCurve => write!(f, "not on the curve"),
+            PointError::NotInSubgroup => write!(f, "not in the G1 subgroup"),
+        }
+    }
+}
+
+/// An error of a KZG point evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KzgError {
+    /// The input of [point_evaluation] is not [POINT_EVALUATION_INPUT_LEN] bytes long.
+    InvalidInputLength(usize),
+    /// The versioned hash does not match the commitment.
+    MismatchedVersionedHash,
+    /// The evaluation point or the claimed value is not reduced modulo [BLS_MODULUS].
+    InvalidFieldElement,
+    InvalidCommitment(PointError),
+    InvalidProof(PointError),
+    /// The proof does not open the commitment to the claimed value.
+    VerificationFailed,
+}
+
+impl fmt::Display for KzgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KzgError::InvalidInputLength(len) => write!(
+                f,
+                "invalid input length {len}, expected {POINT_EVALUATION_INPUT_LEN}"
+            ),
+            KzgError::MismatchedVersionedHash => {
+                write!(f, "versioned hash does not match the commitment")
+            }
+            KzgError::InvalidFieldElement => write!(f, "field element is not reduced"),
+            KzgError::InvalidCommitment(err) => write!(f, "invalid commitment: {err}"),
+            KzgError::InvalidProof(err) => write!(f, "invalid proof: {err}"),
+            KzgError::VerificationFailed => write!(f, "KZG proof verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for KzgError {}
+
+/// Returns the versioned hash of a commitment: its SHA-256 hash with the first byte replaced by
+/// [VERSIONED_HASH_VERSION_KZG].
+pub fn kzg_to_versioned_hash(commitment: &[u8; 48]) -> [u8; 32] {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Decodes a compressed G1 point in the ZCash format, checking that it is in the subgroup.
+fn decompress_g1(bytes: &[u8; 48]) -> Result<G1, PointError> {
+    let compressed = bytes[0] & 0x80 != 0;
+    let infinity = bytes[0] & 0x40 != 0;
+    let sign = bytes[0] & 0x20 != 0;
+    if !compressed {
+        return Err(PointError::InvalidEncoding);
+    }
+    if infinity {
+        // The point at infinity has no other bit set, including the sign.
+        return if bytes[0] & 0x3f == 0 && bytes[1..].iter().all(|b| *b == 0) {
+            Ok(G1::IDENTITY)
+        } else {
+            Err(PointError::InvalidEncoding)
+        };
+    }
+
+    let mut x_bytes = *bytes;
+    x_bytes[0] &= 0x1f;
+    let x = Fp::from_be_bytes(&x_bytes).ok_or(PointError::InvalidEncoding)?;
+    let y = (x.square() * x + Fp([4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]))
+        .sqrt()
+        .ok_or(PointError::NotOnCurve)?;
+    let y = if y.lexicographically_largest() == sign {
+        y
+    } else {
+        -y
+    };
+
+    let point = Jacobian { x, y, z: Fp::ONE };
+    if point.mul_by_scalar(&GROUP_ORDER).is_identity() {
+        Ok(point)
+    } else {
+        Err(PointError::NotInSubgroup)
+    }
+}
+
+/// Checks that a big endian scalar is reduced modulo [BLS_MODULUS].
+fn check_field_element(bytes: &[u8; 32]) -> Result<(), KzgError> {
+    if bytes < &BLS_MODULUS {
+        Ok(())
+    } else {
+        Err(KzgError::InvalidFieldElement)
+    }
+}
+
+/// Verifies that `proof` opens `commitment` to `y` at `z`, that is
+/// e(commitment - [y]G1, G2) = e(proof, [tau]G2 - [z]G2).
+///
+/// Returns an error if one of the inputs can't be decoded, and `Ok(false)` if the proof is
+/// wrong.
+pub fn verify_kzg_proof(
+    commitment: &[u8; 48],
+    z: &[u8; 32],
+    y: &[u8; 32],
+    proof: &[u8; 48],
+) -> Result<bool, KzgError> {
+    let commitment = decompress_g1(commitment).map_err(KzgError::InvalidCommitment)?;
+    check_field_element(z)?;
+    check_field_element(y)?;
+    let proof = decompress_g1(proof).map_err(KzgError::InvalidProof)?;
+
+    let p_minus_y = commitment - G1::from(G1_GENERATOR).mul_by_scalar(y);
+    let x_minus_z = G2::from(TAU_G2) - G2::from(G2_GENERATOR).mul_by_scalar(z);
+
+    // e(P - y, -G2) * e(proof, X - z) = 1, skipping the pairings with the point at infinity.
+    let terms = [(p_minus_y, -G2::from(G2_GENERATOR)), (proof, x_minus_z)]
+        .iter()
+        .filter_map(|(p, q)| Some((p.to_affine()?, q.to_affine()?)))
+        .collect::<Vec<_>>();
+    Ok(pairing::pairing_check(&terms))
+}
+
+/// The EIP-4844 point evaluation precompile.
+///
+/// The input is the versioned hash, z, y, the commitment and the proof, and the output is
+/// [POINT_EVALUATION_OUTPUT] if the versioned hash matches the commitment and the proof is valid.
+pub fn point_evaluation(input: &[u8]) -> Result<[u8; 64], KzgError> {
+    if input.len() != POINT_EVALUATION_INPUT_LEN {
+        return Err(KzgError::InvalidInputLength(input.len()));
+    }
+    let versioned_hash: &[u8; 32] = input[..32].try_into().unwrap();
+    let z: &[u8; 32] = input[32..64].try_into().unwrap();
+    let y: &[u8; 32] = input[64..96].try_into().unwrap();
+    let commitment: &[u8; 48] = input[96..144].try_into().unwrap();
+    let proof: &[u8; 48] = input[144..].try_into().unwrap();
+
+    if kzg_to_versioned_hash(commitment) != *versioned_hash {
+        return Err(KzgError::MismatchedVersionedHash);
+    }
+    if !verify_kzg_proof(commitment, z, y, proof)? {
+        return Err(KzgError::VerificationFailed);
+    }
+    Ok(POINT_EVALUATION_OUTPUT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    /// A case of the c-kzg vectors of `fixtures/kzg`, see the README there.
+    struct Case {
+        name: String,
+        commitment: [u8; 48],
+        z: [u8; 32],
+        y: [u8; 32],
+        proof: [u8; 48],
+        /// `None` if an input is invalid.
+        output: Option<bool>,
+    }
+
+    fn cases() -> Vec<Case> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/kzg/verify_kzg_proof");
+        let mut cases = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let data = fs::read_to_string(path.join("data.yaml")).unwrap();
+                let field = |key: &str| {
+                    data.lines()
+                        .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix(": "))
+                        .unwrap()
+                };
+                let bytes = |key: &str| {
+                    hex::decode(field(key).trim_matches('\'').trim_start_matches("0x")).unwrap()
+                };
+                Case {
+                    name: path.file_name().unwrap().to_str().unwrap().to_string(),
+                    commitment: bytes("commitment").try_into().unwrap(),
+                    z: bytes("z").try_into().unwrap(),
+                    y: bytes("y").try_into().unwrap(),
+                    proof: bytes("proof").try_into().unwrap(),
+                    output: match field("output") {
+                        "true" => Some(true),
+                        "false" => Some(false),
+                        _ => None,
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        cases.sort_by(|a, b| a.name.cmp(&b.name));
+        cases
+    }
+
+    /// The error of an invalid case, from the input and the defect its name describes.
+    fn expected_error(name: &str) -> KzgError {
+        let point_error = |defect: &str| match defect {
+            "not_on_curve" => PointError::NotOnCurve,
+            "not_in_subgroup" => PointError::NotInSubgroup,
+            _ => PointError::InvalidEncoding,
+        };
+        if let Some(defect) = name.strip_prefix("invalid_commitment_") {
+            KzgError::InvalidCommitment(point_error(defect))
+        } else if let Some(defect) = name.strip_prefix("invalid_proof_") {
+            KzgError::InvalidProof(point_error(defect))
+        } else {
+            KzgError::InvalidFieldElement
+        }
+    }
+
+    /// The input of [point_evaluation] for a case.
+    fn point_evaluation_input(case: &Case) -> Vec<u8> {
+        let mut input = kzg_to_versioned_hash(&case.commitment).to_vec();
+        input.extend_from_slice(&case.z);
+        input.extend_from_slice(&case.y);
+        input.extend_from_slice(&case.commitment);
+        input.extend_from_slice(&case.proof);
+        input
+    }
+
+    #[test]
+    fn test_verify_kzg_proof_vectors() {
+        let cases = cases();
+        assert_eq!(cases.len(), 26);
+        for case in cases {
+            let result = verify_kzg_proof(&case.commitment, &case.z, &case.y, &case.proof);
+            match case.output {
+                Some(output) => assert_eq!(result, Ok(output), "{}", case.name),
+                None => assert_eq!(result, Err(expected_error(&case.name)), "{}", case.name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_point_evaluation() {
+        let cases = cases();
+        let case = |name: &str| cases.iter().find(|case| case.name == name).unwrap();
+
+        // The output of revm for its vector.
+        let input = point_evaluation_input(case("correct_proof_revm"));
+        assert_eq!(
+            point_evaluation(&input).map(hex::encode),
+            Ok(
+                "0000000000000000000000000000000000000000000000000000000000001000\
+                73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+                    .to_string()
+            )
+        );
+
+        assert_eq!(
+            point_evaluation(&input[1..]),
+            Err(KzgError::InvalidInputLength(191))
+        );
+        let mut mismatched = input.clone();
+        mismatched[0] = 0;
+        assert_eq!(
+            point_evaluation(&mismatched),
+            Err(KzgError::MismatchedVersionedHash)
+        );
+
+        let input = point_evaluation_input(case("incorrect_proof_wrong_y"));
+        assert_eq!(point_evaluation(&input), Err(KzgError::VerificationFailed));
+        let input = point_evaluation_input(case("invalid_commitment_not_in_subgroup"));
+        assert_eq!(
+            point_evaluation(&input),
+            Err(KzgError::InvalidCommitment(PointError::NotInSubgroup))
+        );
+    }
+
+    /// The number of calls to the bls12381_fp_* precompiles of a point evaluation, which the cost
+    /// documented in `core/src/runtime/gas.rs` and `core/src/runtime/report.rs` is derived from.
+    #[test]
+    fn test_point_evaluation_fp_ops() {
+        let fp_ops = |f: &dyn Fn()| {
+            fp::FP_OP_COUNTS.with(|counts| counts.set([0; 4]));
+            f();
+            fp::FP_OP_COUNTS.with(|counts| counts.get())
+        };
+        let cases = cases();
+        let case = |name: &str| cases.iter().find(|case| case.name == name).unwrap();
+
+        // The adds, subs, muls and divs of the vector of revm.
+        let input = point_evaluation_input(case("correct_proof_revm"));
+        let counts = fp_ops(&|| {
+            point_evaluation(&input).unwrap();
+        });
+        assert_eq!(counts, [67903, 53666, 42658, 5]);
+
+        // The scalar multiplications by z and y depend on their bits, so the largest count is for
+        // the 255-bit scalar below the modulus with the most bits set.
+        let mut heaviest = [0xff; 32];
+        heaviest[0] = 0x6f;
+        let case = case("correct_proof_z_random_0");
+        let counts = fp_ops(&|| {
+            verify_kzg_proof(&case.commitment, &heaviest, &heaviest, &case.proof).unwrap();
+        });
+        assert_eq!(counts.iter().sum::<usize>(), 184_687);
+    }
+}
@@ -0,0 +1,200 @@
+//! The optimal ate pairing of BLS12-381, ported from the zkcrypto `bls12_381` crate.
+
+use super::curve::Affine;
+use super::fp::{Fp, Fp2};
+use super::tower::{Fp12, Fp6};
+
+/// The absolute value of the BLS parameter x, which is negative.
+const BLS_X: u64 = 0xd201_0000_0001_0000;
+
+/// A point of G2 in the coordinates the line functions of the Miller loop work with.
+struct G2Projective {
+    x: Fp2,
+    y: Fp2,
+    z: Fp2,
+}
+
+/// Doubles `r` and returns the coefficients of the tangent line, Algorithm 26 of
+/// https://eprint.iacr.org/2010/354.pdf.
+fn doubling_step(r: &mut G2Projective) -> (Fp2, Fp2, Fp2) {
+    let tmp0 = r.x.square();
+    let tmp1 = r.y.square();
+    let tmp2 = tmp1.square();
+    let tmp3 = ((tmp1 + r.x).square() - tmp0 - tmp2).double();
+    let tmp4 = tmp0.double() + tmp0;
+    let tmp6 = r.x + tmp4;
+    let tmp5 = tmp4.square();
+    let z_squared = r.z.square();
+    r.x = tmp5 - tmp3 - tmp3;
+    r.z = (r.z + r.y).square() - tmp1 - z_squared;
+    r.y = (tmp3 - r.x) * tmp4 - tmp2.double().double().double();
+    let tmp3 = -(tmp4 * z_squared).double();
+    let tmp6 = tmp6.square() - tmp0 - tmp5 - tmp1.double().double();
+    let tmp0 = (r.z * z_squared).double();
+    (tmp0, tmp3, tmp6)
+}
+
+/// Adds `q` to `r` and returns the coefficients of the line through them, Algorithm 27 of
+/// https://eprint.iacr.org/2010/354.pdf.
+fn addition_step(r: &mut G2Projective, q: &Affine<Fp2>) -> (Fp2, Fp2, Fp2) {
+    let z_squared = r.z.square();
+    let y_squared = q.y.square();
+    let t0 = z_squared * q.x;
+    let t1 = ((q.y + r.z).square() - y_squared - z_squared) * z_squared;
+    let t2 = t0 - r.x;
+    let t3 = t2.square();
+    let t4 = t3.double().double();
+    let t5 = t4 * t2;
+    let t6 = t1 - r.y - r.y;
+    let t9 = t6 * q.x;
+    let t7 = t4 * r.x;
+    r.x = t6.square() - t5 - t7 - t7;
+    r.z = (r.z + t2).square() - z_squared - t3;
+    let t10 = q.y + r.z;
+    let t8 = (t7 - r.x) * t6;
+    let t0 = (r.y * t5).double();
+    r.y = t8 - t0;
+    let t10 = t10.square() - y_squared - r.z.square();
+    let t9 = t9.double() - t10;
+    let t10 = r.z.double();
+    let t1 = (-t6).double();
+    (t10, t1, t9)
+}
+
+/// Multiplies `f` by the line with coefficients `coeffs` evaluated at `p`.
+fn ell(f: Fp12, coeffs: (Fp2, Fp2, Fp2), p: &Affine<Fp>) -> Fp12 {
+    let c0 = coeffs.0.mul_by_fp(p.y);
+    let c1 = coeffs.1.mul_by_fp(p.x);
+    f.mul_by_014(coeffs.2, c1, c0)
+}
+
+/// Computes the product of the Miller loops of `terms`, sharing the squarings between them.
+fn multi_miller_loop(terms: &[(Affine<Fp>, Affine<Fp2>)]) -> Fp12 {
+    let mut currents = terms
+        .iter()
+        .map(|(_, q)| G2Projective {
+            x: q.x,
+            y: q.y,
+            z: Fp2::ONE,
+        })
+        .collect::<Vec<_>>();
+
+    let mut f = Fp12::ONE;
+    let mut found_one = false;
+    for bit in (0..64).rev().map(|b| ((BLS_X >> 1) >> b) & 1 == 1) {
+        if !found_one {
+            found_one = bit;
+            continue;
+        }
+        for ((p, _), r) in terms.iter().zip(currents.iter_mut()) {
+            f = ell(f, doubling_step(r), p);
+        }
+        if bit {
+            for ((p, q), r) in terms.iter().zip(currents.iter_mut()) {
+                f = ell(f, addition_step(r, q), p);
+            }
+        }
+        f = f.square();
+    }
+    for ((p, _), r) in terms.iter().zip(currents.iter_mut()) {
+        f = ell(f, doubling_step(r), p);
+    }
+
+    // The parameter x is negative.
+    f.conjugate()
+}
+
+fn fp4_square(a: Fp2, b: Fp2) -> (Fp2, Fp2) {
+    let t0 = a.square();
+    let t1 = b.square();
+    let c0 = t1.mul_by_nonresidue() + t0;
+    let c1 = (a + b).square() - t0 - t1;
+    (c0, c1)
+}
+
+/// Squares an element of the cyclotomic subgroup, Algorithm 5.5.4 of "Guide to Pairing-Based
+/// Cryptography", from https://eprint.iacr.org/2009/565.pdf.
+fn cyclotomic_square(f: Fp12) -> Fp12 {
+    let (z0, z4, z3) = (f.c0.c0, f.c0.c1, f.c0.c2);
+    let (z2, z1, z5) = (f.c1.c0, f.c1.c1, f.c1.c2);
+
+    let (t0, t1) = fp4_square(z0, z1);
+    let z0 = (t0 - z0).double() + t0;
+    let z1 = (t1 + z1).double() + t1;
+
+    let (t0, t1) = fp4_square(z2, z3);
+    let (t2, t3) = fp4_square(z4, z5);
+    let z4 = (t0 - z4).double() + t0;
+    let z5 = (t1 + z5).double() + t1;
+
+    let t0 = t3.mul_by_nonresidue();
+    let z2 = (t0 + z2).double() + t0;
+    let z3 = (t2 - z3).double() + t2;
+
+    Fp12 {
+        c0: Fp6 {
+            c0: z0,
+            c1: z4,
+            c2: z3,
+        },
+        c1: Fp6 {
+            c0: z2,
+            c1: z1,
+            c2: z5,
+        },
+    }
+}
+
+/// Raises an element of the cyclotomic subgroup to the power x.
+fn cyclotomic_exp(f: Fp12) -> Fp12 {
+    let mut tmp = Fp12::ONE;
+    let mut found_one = false;
+    for bit in (0..64).rev().map(|b| (BLS_X >> b) & 1 == 1) {
+        if found_one {
+            tmp = cyclotomic_square(tmp);
+        } else {
+            found_one = bit;
+        }
+        if bit {
+            tmp = tmp * f;
+        }
+    }
+    tmp.conjugate()
+}
+
+/// Raises the output of a Miller loop to the power (p^12 - 1) / r, or returns `None` if it is
+/// zero.
+fn final_exponentiation(f: Fp12) -> Option<Fp12> {
+    let t0 = f
+        .frobenius_map()
+        .frobenius_map()
+        .frobenius_map()
+        .frobenius_map()
+        .frobenius_map()
+        .frobenius_map();
+    f.invert().map(|t1| {
+        let t2 = t0 * t1;
+        let t1 = t2;
+        let t2 = t2.frobenius_map().frobenius_map() * t1;
+        let t1 = cyclotomic_square(t2).conjugate();
+        let t3 = cyclotomic_exp(t2);
+        let t4 = cyclotomic_square(t3);
+        let t5 = t1 * t3;
+        let t1 = cyclotomic_exp(t5);
+        let t0 = cyclotomic_exp(t1);
+        let t6 = cyclotomic_exp(t0) * t4;
+        let t4 = cyclotomic_exp(t6);
+        let t5 = t5.conjugate();
+        let t4 = t4 * (t5 * t2);
+        let t5 = t2.conjugate();
+        let t1 = (t1 * t2).frobenius_map().frobenius_map().frobenius_map();
+        let t6 = (t6 * t5).frobenius_map();
+        let t3 = (t3 * t0).frobenius_map().frobenius_map() * t1 * t6;
+        t3 * t4
+    })
+}
+
+/// Checks that the product of the pairings of `terms` is the identity of the target group.
+pub(crate) fn pairing_check(terms: &[(Affine<Fp>, Affine<Fp2>)]) -> bool {
+    final_exponentiation(multi_miller_loop(terms)) == Some(Fp12::ONE)
+}
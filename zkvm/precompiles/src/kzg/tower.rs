@@ -0,0 +1,248 @@
+//! The extensions Fp6 = Fp2[v] / (v^3 - (u + 1)) and Fp12 = Fp6[w] / (w^2 - v) the pairing lands
+//! in, following the tower of the zkcrypto `bls12_381` crate.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::fp::{Fp, Fp2};
+
+/// (u + 1)^((p - 1) / 3), which only has a `c1` component.
+const FROBENIUS_COEFF_FP6_C1: Fp2 = Fp2 {
+    c0: Fp::ZERO,
+    c1: Fp([
+        0x0000aaac, 0x8bfd0000, 0x4f49fffd, 0x409427eb, 0x0fb85f9b, 0x897d2965, 0x89759ad4,
+        0xaa0d857d, 0x63d4de85, 0xec024086, 0x397fe699, 0x1a0111ea,
+    ]),
+};
+
+/// (u + 1)^((2p - 2) / 3), which only has a `c0` component.
+const FROBENIUS_COEFF_FP6_C2: Fp2 = Fp2 {
+    c0: Fp([
+        0x0000aaad, 0x8bfd0000, 0x4f49fffd, 0x409427eb, 0x0fb85f9b, 0x897d2965, 0x89759ad4,
+        0xaa0d857d, 0x63d4de85, 0xec024086, 0x397fe699, 0x1a0111ea,
+    ]),
+    c1: Fp::ZERO,
+};
+
+/// (u + 1)^((p - 1) / 6).
+const FROBENIUS_COEFF_FP12_C1: Fp2 = Fp2 {
+    c0: Fp([
+        0x92235fb8, 0x8d0775ed, 0x63e7813d, 0xf67ea53d, 0x84bab9c4, 0x7b2443d7, 0x3cbd5f4f,
+        0x0fd603fd, 0x202c0d1f, 0xc231beb4, 0x02bb0667, 0x1904d3bf,
+    ]),
+    c1: Fp([
+        0x6ddc4af3, 0x2cf78a12, 0x4d6c7ec2, 0x282d5ac1, 0x71f63c5f, 0xec0c8ec9, 0xb6c7b36f,
+        0x54a14787, 0x231f9fb8, 0x88e9e902, 0x36c4e032, 0x00fc3e2b,
+    ]),
+};
+
+/// An element `c0 + c1 * v + c2 * v^2` of Fp6.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fp6 {
+    pub(crate) c0: Fp2,
+    pub(crate) c1: Fp2,
+    pub(crate) c2: Fp2,
+}
+
+impl Fp6 {
+    pub(crate) const ZERO: Fp6 = Fp6 {
+        c0: Fp2::ZERO,
+        c1: Fp2::ZERO,
+        c2: Fp2::ZERO,
+    };
+
+    pub(crate) const ONE: Fp6 = Fp6 {
+        c0: Fp2::ONE,
+        c1: Fp2::ZERO,
+        c2: Fp2::ZERO,
+    };
+
+    /// Multiplies by `c1 * v`.
+    pub(crate) fn mul_by_1(&self, c1: Fp2) -> Fp6 {
+        Fp6 {
+            c0: (self.c2 * c1).mul_by_nonresidue(),
+            c1: self.c0 * c1,
+            c2: self.c1 * c1,
+        }
+    }
+
+    /// Multiplies by `c0 + c1 * v`.
+    pub(crate) fn mul_by_01(&self, c0: Fp2, c1: Fp2) -> Fp6 {
+        let a_a = self.c0 * c0;
+        let b_b = self.c1 * c1;
+        Fp6 {
+            c0: (self.c2 * c1).mul_by_nonresidue() + a_a,
+            c1: (c0 + c1) * (self.c0 + self.c1) - a_a - b_b,
+            c2: self.c2 * c0 + b_b,
+        }
+    }
+
+    /// Multiplies by the nonresidue v used to build Fp12.
+    pub(crate) fn mul_by_nonresidue(&self) -> Fp6 {
+        Fp6 {
+            c0: self.c2.mul_by_nonresidue(),
+            c1: self.c0,
+            c2: self.c1,
+        }
+    }
+
+    pub(crate) fn mul_by_fp2(&self, rhs: Fp2) -> Fp6 {
+        Fp6 {
+            c0: self.c0 * rhs,
+            c1: self.c1 * rhs,
+            c2: self.c2 * rhs,
+        }
+    }
+
+    pub(crate) fn frobenius_map(&self) -> Fp6 {
+        Fp6 {
+            c0: self.c0.frobenius_map(),
+            c1: self.c1.frobenius_map() * FROBENIUS_COEFF_FP6_C1,
+            c2: self.c2.frobenius_map() * FROBENIUS_COEFF_FP6_C2,
+        }
+    }
+
+    pub(crate) fn square(&self) -> Fp6 {
+        let s0 = self.c0.square();
+        let s1 = (self.c0 * self.c1).double();
+        let s2 = (self.c0 - self.c1 + self.c2).square();
+        let s3 = (self.c1 * self.c2).double();
+        let s4 = self.c2.square();
+        Fp6 {
+            c0: s3.mul_by_nonresidue() + s0,
+            c1: s4.mul_by_nonresidue() + s1,
+            c2: s1 + s2 + s3 - s0 - s4,
+        }
+    }
+
+    pub(crate) fn invert(&self) -> Option<Fp6> {
+        let c0 = self.c0.square() - (self.c1 * self.c2).mul_by_nonresidue();
+        let c1 = self.c2.square().mul_by_nonresidue() - self.c0 * self.c1;
+        let c2 = self.c1.square() - self.c0 * self.c2;
+        let t = (self.c1 * c2 + self.c2 * c1).mul_by_nonresidue() + self.c0 * c0;
+        t.invert().map(|t| Fp6 {
+            c0: c0 * t,
+            c1: c1 * t,
+            c2: c2 * t,
+        })
+    }
+}
+
+impl Add for Fp6 {
+    type Output = Fp6;
+
+    fn add(self, rhs: Fp6) -> Fp6 {
+        Fp6 {
+            c0: self.c0 + rhs.c0,
+            c1: self.c1 + rhs.c1,
+            c2: self.c2 + rhs.c2,
+        }
+    }
+}
+
+impl Sub for Fp6 {
+    type Output = Fp6;
+
+    fn sub(self, rhs: Fp6) -> Fp6 {
+        Fp6 {
+            c0: self.c0 - rhs.c0,
+            c1: self.c1 - rhs.c1,
+            c2: self.c2 - rhs.c2,
+        }
+    }
+}
+
+impl Mul for Fp6 {
+    type Output = Fp6;
+
+    fn mul(self, rhs: Fp6) -> Fp6 {
+        // Karatsuba over Fp2, with v^3 = u + 1.
+        let a_a = self.c0 * rhs.c0;
+        let b_b = self.c1 * rhs.c1;
+        let c_c = self.c2 * rhs.c2;
+        Fp6 {
+            c0: ((self.c1 + self.c2) * (rhs.c1 + rhs.c2) - b_b - c_c).mul_by_nonresidue() + a_a,
+            c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - a_a - b_b + c_c.mul_by_nonresidue(),
+            c2: (self.c0 + self.c2) * (rhs.c0 + rhs.c2) - a_a + b_b - c_c,
+        }
+    }
+}
+
+impl Neg for Fp6 {
+    type Output = Fp6;
+
+    fn neg(self) -> Fp6 {
+        Fp6 {
+            c0: -self.c0,
+            c1: -self.c1,
+            c2: -self.c2,
+        }
+    }
+}
+
+/// An element `c0 + c1 * w` of Fp12.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fp12 {
+    pub(crate) c0: Fp6,
+    pub(crate) c1: Fp6,
+}
+
+impl Fp12 {
+    pub(crate) const ONE: Fp12 = Fp12 {
+        c0: Fp6::ONE,
+        c1: Fp6::ZERO,
+    };
+
+    /// Multiplies by the sparse element `c0 + c1 * v + c4 * v * w` a line evaluates to.
+    pub(crate) fn mul_by_014(&self, c0: Fp2, c1: Fp2, c4: Fp2) -> Fp12 {
+        let aa = self.c0.mul_by_01(c0, c1);
+        let bb = self.c1.mul_by_1(c4);
+        Fp12 {
+            c0: bb.mul_by_nonresidue() + aa,
+            c1: (self.c1 + self.c0).mul_by_01(c0, c1 + c4) - aa - bb,
+        }
+    }
+
+    pub(crate) fn conjugate(&self) -> Fp12 {
+        Fp12 {
+            c0: self.c0,
+            c1: -self.c1,
+        }
+    }
+
+    pub(crate) fn frobenius_map(&self) -> Fp12 {
+        Fp12 {
+            c0: self.c0.frobenius_map(),
+            c1: self.c1.frobenius_map().mul_by_fp2(FROBENIUS_COEFF_FP12_C1),
+        }
+    }
+
+    pub(crate) fn square(&self) -> Fp12 {
+        let ab = self.c0 * self.c1;
+        let c0 = (self.c1.mul_by_nonresidue() + self.c0) * (self.c0 + self.c1)
+            - ab
+            - ab.mul_by_nonresidue();
+        Fp12 { c0, c1: ab + ab }
+    }
+
+    pub(crate) fn invert(&self) -> Option<Fp12> {
+        (self.c0.square() - self.c1.square().mul_by_nonresidue())
+            .invert()
+            .map(|t| Fp12 {
+                c0: self.c0 * t,
+                c1: -(self.c1 * t),
+            })
+    }
+}
+
+impl Mul for Fp12 {
+    type Output = Fp12;
+
+    fn mul(self, rhs: Fp12) -> Fp12 {
+        let aa = self.c0 * rhs.c0;
+        let bb = self.c1 * rhs.c1;
+        Fp12 {
+            c0: bb.mul_by_nonresidue() + aa,
+            c1: (self.c1 + self.c0) * (rhs.c0 + rhs.c1) - aa - bb,
+        }
+    }
+}
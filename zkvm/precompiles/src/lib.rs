@@ -7,8 +7,12 @@
 
 pub mod bls12381;
 pub mod bn254;
+pub mod checked;
 pub mod io;
+pub mod kzg;
 pub mod secp256k1;
+pub mod secp256r1;
+pub mod sha3;
 pub mod unconstrained;
 pub mod utils;
 #[cfg(feature = "verify")]
@@ -27,17 +31,38 @@ extern "C" {
     pub fn syscall_secp256k1_add(p: *mut u32, q: *const u32);
     pub fn syscall_secp256k1_double(p: *mut u32);
     pub fn syscall_secp256k1_decompress(point: &mut [u8; 64], is_odd: bool);
+    pub fn syscall_secp256r1_add(p: *mut u32, q: *const u32);
+    pub fn syscall_secp256r1_double(p: *mut u32);
     pub fn syscall_bn254_add(p: *mut u32, q: *const u32);
     pub fn syscall_bn254_double(p: *mut u32);
     pub fn syscall_bls12381_add(p: *mut u32, q: *const u32);
     pub fn syscall_bls12381_double(p: *mut u32);
+    pub fn syscall_bls12381_fp_add(x: *mut u32, y: *const u32);
+    pub fn syscall_bls12381_fp_sub(x: *mut u32, y: *const u32);
+    pub fn syscall_bls12381_fp_mul(x: *mut u32, y: *const u32);
+    pub fn syscall_bls12381_fp_div(x: *mut u32, y: *const u32);
     pub fn syscall_keccak_permute(state: *mut u64);
+    pub fn syscall_kzg_point_evaluation(
+        versioned_hash: &[u8; 32],
+        z: &[u8; 32],
+        y: &[u8; 32],
+        commitment: &[u8; 48],
+        proof: &[u8; 48],
+    ) -> bool;
     pub fn syscall_uint256_mulmod(x: *mut u32, y: *const u32);
     pub fn syscall_enter_unconstrained() -> bool;
     pub fn syscall_exit_unconstrained();
     pub fn syscall_verify_sp1_proof(vkey: &[u32; 8], pv_digest: &[u8; 32]);
     pub fn syscall_hint_len() -> usize;
     pub fn syscall_hint_read(ptr: *mut u8, len: usize);
+    pub fn syscall_config_len() -> usize;
+    pub fn syscall_config_read(ptr: *mut u8, len: usize);
+    pub fn syscall_entropy_len() -> usize;
+    pub fn syscall_entropy_read(ptr: *mut u8, len: usize);
+    pub fn syscall_cycle_count() -> u32;
+    pub fn syscall_gas_used(half: u32) -> u32;
+    pub fn syscall_hint_shard_boundary();
+    pub fn syscall_unconstrained(id: u32, arg1: u32, arg2: u32);
     pub fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8;
     pub fn syscall_bls12381_decompress(point: &mut [u8; 96], is_odd: bool);
     pub fn sys_bigint(
@@ -0,0 +1,144 @@
+#![allow(unused)]
+
+use crate::utils::{AffinePoint, CurveOperations};
+use crate::{syscall_secp256r1_add, syscall_secp256r1_double};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::elliptic_curve::ff::PrimeFieldBits;
+use p256::elliptic_curve::ops::{Invert, Reduce};
+use p256::{FieldBytes, Scalar, U256};
+
+const NUM_WORDS: usize = 16;
+
+#[derive(Copy, Clone)]
+pub struct Secp256r1Operations;
+
+impl CurveOperations<NUM_WORDS> for Secp256r1Operations {
+    // The values are taken from https://neuromancer.sk/std/secg/secp256r1.
+    const GENERATOR: [u32; NUM_WORDS] = [
+        3633889942, 4104206661, 770388896, 1996717441, 1671708914, 4173129445, 3777774151,
+        1796723186, 935285237, 3417718888, 1798397646, 734933847, 2081398294, 2397563722,
+        4263149467, 1340293858,
+    ];
+
+    fn add_assign(limbs: &mut [u32; NUM_WORDS], other: &[u32; NUM_WORDS]) {
+        unsafe {
+            syscall_secp256r1_add(limbs.as_mut_ptr(), other.as_ptr());
+        }
+    }
+
+    fn double(limbs: &mut [u32; NUM_WORDS]) {
+        unsafe {
+            syscall_secp256r1_double(limbs.as_mut_ptr());
+        }
+    }
+}
+
+/// Multiplies `point` by `scalar` with the secp256r1 precompiles. Returns `None` for the point at
+/// infinity.
+///
+/// Like the precompiles, the additions must not be of equal or opposite points, which holds for
+/// a point of prime order `n` and a scalar in `[1, n)`.
+pub fn scalar_mul(
+    point: &AffinePoint<Secp256r1Operations, NUM_WORDS>,
+    scalar: &Scalar,
+) -> Option<AffinePoint<Secp256r1Operations, NUM_WORDS>> {
+    let mut res: Option<AffinePoint<Secp256r1Operations, NUM_WORDS>> = None;
+    let mut temp = *point;
+    for bit in scalar.to_le_bits() {
+        if bit {
+            match res.as_mut() {
+                Some(res) => res.add_assign(&temp),
+                None => res = Some(temp),
+            };
+        }
+        temp.double();
+    }
+    res
+}
+
+/// Verifies a secp256r1 (P-256) ECDSA signature of the message hash `msg_hash` by the uncompressed
+/// SEC1 public key `pubkey`.
+///
+/// Warning: this function does not check if the key is actually on the curve.
+pub fn verify_signature(pubkey: &[u8; 65], msg_hash: &[u8; 32], signature: &Signature) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            if pubkey[0] != 4 {
+                return false;
+            }
+            let mut pubkey_x_le_bytes = [0u8; 32];
+            pubkey_x_le_bytes.copy_from_slice(&pubkey[1..33]);
+            pubkey_x_le_bytes.reverse();
+            let mut pubkey_y_le_bytes = [0u8; 32];
+            pubkey_y_le_bytes.copy_from_slice(&pubkey[33..]);
+            pubkey_y_le_bytes.reverse();
+            let affine = AffinePoint::<Secp256r1Operations, NUM_WORDS>::from(
+                &pubkey_x_le_bytes,
+                &pubkey_y_le_bytes,
+            );
+
+            const GENERATOR: AffinePoint<Secp256r1Operations, NUM_WORDS> =
+                AffinePoint::<Secp256r1Operations, NUM_WORDS>::generator_in_affine();
+
+            let z = <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::from_slice(msg_hash));
+            let (r, s) = signature.split_scalars();
+            let s_inv = *s.invert();
+
+            let u1 = z * s_inv;
+            let u2 = *r * s_inv;
+
+            let res = match double_and_add_base(&u1, &GENERATOR, &u2, &affine) {
+                Some(res) => res,
+                None => return false,
+            };
+            let mut x_bytes_be = [0u8; 32];
+            for i in 0..8 {
+                x_bytes_be[i * 4..(i * 4) + 4].copy_from_slice(&res.limbs[i].to_le_bytes());
+            }
+            x_bytes_be.reverse();
+
+            *r == <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::from_slice(&x_bytes_be))
+        } else {
+            match VerifyingKey::from_sec1_bytes(pubkey) {
+                Ok(verify_key) => verify_key.verify_prehash(msg_hash, signature).is_ok(),
+                Err(_) => false,
+            }
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+fn double_and_add_base(
+    a: &Scalar,
+    A: &AffinePoint<Secp256r1Operations, NUM_WORDS>,
+    b: &Scalar,
+    B: &AffinePoint<Secp256r1Operations, NUM_WORDS>,
+) -> Option<AffinePoint<Secp256r1Operations, NUM_WORDS>> {
+    let mut res: Option<AffinePoint<Secp256r1Operations, NUM_WORDS>> = None;
+    let mut temp_A = *A;
+    let mut temp_B = *B;
+
+    let a_bits = a.to_le_bits();
+    let b_bits = b.to_le_bits();
+    for (a_bit, b_bit) in a_bits.iter().zip(b_bits) {
+        if *a_bit {
+            match res.as_mut() {
+                Some(res) => res.add_assign(&temp_A),
+                None => res = Some(temp_A),
+            };
+        }
+
+        if b_bit {
+            match res.as_mut() {
+                Some(res) => res.add_assign(&temp_B),
+                None => res = Some(temp_B),
+            };
+        }
+
+        temp_A.double();
+        temp_B.double();
+    }
+
+    res
+}
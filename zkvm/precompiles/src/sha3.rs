@@ -0,0 +1,343 @@
+//! SHA-3, SHAKE and Keccak-256 built on the `KECCAK_PERMUTE` precompile.
+//!
+//! All functions share one sponge implementation and only differ in their rate (the number of
+//! bytes absorbed per permutation) and in the domain separation byte used for padding:
+//!
+//! | Function    | Rate (bytes) | Padding byte |
+//! |-------------|--------------|--------------|
+//! | `keccak256` | 136          | `0x01`       |
+//! | `sha3_256`  | 136          | `0x06`       |
+//! | `sha3_512`  | 72           | `0x06`       |
+//! | `shake128`  | 168          | `0x1f`       |
+//! | `shake256`  | 136          | `0x1f`       |
+
+use digest::{
+    consts::{U32, U64},
+    ExtendableOutput, ExtendableOutputReset, FixedOutput, FixedOutputReset, HashMarker, Output,
+    OutputSizeUser, Reset, Update, XofReader,
+};
+
+/// The number of 64-bit lanes in the Keccak-f[1600] state.
+const STATE_LANES: usize = 25;
+
+const KECCAK_PADDING: u8 = 0x01;
+const SHA3_PADDING: u8 = 0x06;
+const SHAKE_PADDING: u8 = 0x1f;
+
+/// Applies Keccak-f[1600] to `state`, using the precompile inside the zkVM.
+fn keccak_permute(state: &mut [u64; STATE_LANES]) {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "zkvm")] {
+            unsafe {
+                crate::syscall_keccak_permute(state.as_mut_ptr());
+            }
+        } else {
+            keccak::f1600(state);
+        }
+    }
+}
+
+/// A Keccak sponge with a rate of `RATE` bytes.
+#[derive(Clone)]
+struct Sponge<const RATE: usize> {
+    state: [u64; STATE_LANES],
+    /// The position of the next byte to absorb or squeeze within the rate.
+    offset: usize,
+}
+
+impl<const RATE: usize> Default for Sponge<RATE> {
+    fn default() -> Self {
+        Self {
+            state: [0; STATE_LANES],
+            offset: 0,
+        }
+    }
+}
+
+impl<const RATE: usize> Sponge<RATE> {
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+    }
+
+    fn byte(&self, index: usize) -> u8 {
+        (self.state[index / 8] >> (8 * (index % 8))) as u8
+    }
+
+    fn absorb(&mut self, data: &[u8]) {
+        for byte in data {
+            self.xor_byte(self.offset, *byte);
+            self.offset += 1;
+            if self.offset == RATE {
+                keccak_permute(&mut self.state);
+                self.offset = 0;
+            }
+        }
+    }
+
+    /// Applies the `padding || 0* || 0x80` padding and switches the sponge to squeezing.
+    fn pad(&mut self, padding: u8) {
+        self.xor_byte(self.offset, padding);
+        self.xor_byte(RATE - 1, 0x80);
+        keccak_permute(&mut self.state);
+        self.offset = 0;
+    }
+
+    fn squeeze(&mut self, out: &mut [u8]) {
+        for byte in out {
+            if self.offset == RATE {
+                keccak_permute(&mut self.state);
+                self.offset = 0;
+            }
+            *byte = self.byte(self.offset);
+            self.offset += 1;
+        }
+    }
+}
+
+macro_rules! fixed_output_hasher {
+    ($name:ident, $rate:expr, $padding:expr, $output_size:ty, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone, Default)]
+        pub struct $name {
+            sponge: Sponge<$rate>,
+        }
+
+        impl HashMarker for $name {}
+
+        impl OutputSizeUser for $name {
+            type OutputSize = $output_size;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+        }
+
+        impl FixedOutput for $name {
+            fn finalize_into(mut self, out: &mut Output<Self>) {
+                self.sponge.pad($padding);
+                self.sponge.squeeze(out);
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = Self::default();
+            }
+        }
+
+        impl FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                self.clone().finalize_into(out);
+                self.reset();
+            }
+        }
+    };
+}
+
+fixed_output_hasher!(
+    Keccak256,
+    136,
+    KECCAK_PADDING,
+    U32,
+    "An incremental Keccak-256 hasher (the pre-standard padding used by Ethereum)."
+);
+fixed_output_hasher!(
+    Sha3_256,
+    136,
+    SHA3_PADDING,
+    U32,
+    "An incremental SHA3-256 hasher."
+);
+fixed_output_hasher!(
+    Sha3_512,
+    72,
+    SHA3_PADDING,
+    U64,
+    "An incremental SHA3-512 hasher."
+);
+
+/// Reads the output of a SHAKE hasher.
+#[derive(Clone)]
+pub struct ShakeReader<const RATE: usize> {
+    sponge: Sponge<RATE>,
+}
+
+impl<const RATE: usize> XofReader for ShakeReader<RATE> {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.sponge.squeeze(buffer);
+    }
+}
+
+macro_rules! shake_hasher {
+    ($name:ident, $rate:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone, Default)]
+        pub struct $name {
+            sponge: Sponge<$rate>,
+        }
+
+        impl HashMarker for $name {}
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+        }
+
+        impl ExtendableOutput for $name {
+            type Reader = ShakeReader<$rate>;
+
+            fn finalize_xof(mut self) -> Self::Reader {
+                self.sponge.pad(SHAKE_PADDING);
+                ShakeReader {
+                    sponge: self.sponge,
+                }
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = Self::default();
+            }
+        }
+
+        impl ExtendableOutputReset for $name {
+            fn finalize_xof_reset(&mut self) -> Self::Reader {
+                let reader = self.clone().finalize_xof();
+                self.reset();
+                reader
+            }
+        }
+    };
+}
+
+shake_hasher!(Shake128, 168, "An incremental SHAKE128 hasher.");
+shake_hasher!(Shake256, 136, "An incremental SHAKE256 hasher.");
+
+/// Computes the Keccak-256 hash of `data`.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::default();
+    hasher.update(data);
+    hasher.finalize_fixed().into()
+}
+
+/// Computes the SHA3-256 hash of `data`.
+pub fn sha3_256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::default();
+    hasher.update(data);
+    hasher.finalize_fixed().into()
+}
+
+/// Computes the SHA3-512 hash of `data`.
+pub fn sha3_512(data: &[u8]) -> [u8; 64] {
+    let mut hasher = Sha3_512::default();
+    hasher.update(data);
+    hasher.finalize_fixed().into()
+}
+
+/// Computes `output_len` bytes of SHAKE128 output for `data`.
+pub fn shake128(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = Shake128::default();
+    hasher.update(data);
+    let mut output = vec![0u8; output_len];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+/// Computes `output_len` bytes of SHAKE256 output for `data`.
+pub fn shake256(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = Shake256::default();
+    hasher.update(data);
+    let mut output = vec![0u8; output_len];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use digest::Digest;
+
+    use super::*;
+
+    /// Input lengths around the rate boundaries of every variant.
+    const LENGTHS: [usize; 15] = [
+        0, 1, 71, 72, 73, 135, 136, 137, 167, 168, 169, 271, 272, 273, 1000,
+    ];
+
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + 3) as u8).collect()
+    }
+
+    #[test]
+    fn test_nist_vectors() {
+        assert_eq!(
+            hex::encode(sha3_256(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            hex::encode(sha3_512(b"abc")),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(shake128(b"", 32)),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            hex::encode(shake256(b"", 32)),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f"
+        );
+    }
+
+    #[test]
+    fn test_matches_sha3_crate() {
+        for len in LENGTHS {
+            let data = input(len);
+            assert_eq!(
+                keccak256(&data),
+                <[u8; 32]>::from(sha3::Keccak256::digest(&data))
+            );
+            assert_eq!(
+                sha3_256(&data),
+                <[u8; 32]>::from(sha3::Sha3_256::digest(&data))
+            );
+            assert_eq!(
+                sha3_512(&data),
+                <[u8; 64]>::from(sha3::Sha3_512::digest(&data))
+            );
+
+            // Squeeze across several blocks as well.
+            for output_len in [1, 32, 168, 169, 500] {
+                let mut expected = vec![0u8; output_len];
+                sha3::Shake128::digest_xof(&data, &mut expected);
+                assert_eq!(shake128(&data, output_len), expected);
+                sha3::Shake256::digest_xof(&data, &mut expected);
+                assert_eq!(shake256(&data, output_len), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let data = input(1000);
+        for split in [0, 1, 135, 136, 137, 999] {
+            let mut hasher = Sha3_256::new();
+            Digest::update(&mut hasher, &data[..split]);
+            Digest::update(&mut hasher, &data[split..]);
+            assert_eq!(<[u8; 32]>::from(hasher.finalize()), sha3_256(&data));
+
+            let mut hasher = Shake128::default();
+            hasher.update(&data[..split]);
+            hasher.update(&data[split..]);
+            let mut output = [0u8; 64];
+            hasher.finalize_xof().read(&mut output);
+            assert_eq!(output.to_vec(), shake128(&data, 64));
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use crate::io::SegmentDigests;
 use crate::syscall_verify_sp1_proof;
+use k256::sha2::{Digest, Sha256};
 
 /// Verifies the next proof in the proof input stream given a pkey digest and public values digest.
 ///
@@ -8,3 +10,37 @@ pub fn verify_sp1_proof(pkey_digest: &[u32; 8], pv_digest: &[u8; 32]) {
         syscall_verify_sp1_proof(pkey_digest, pv_digest);
     }
 }
+
+/// Verifies the next proofs in the proof input stream as consecutive segments of the program of
+/// `vkey`, given their public values, and returns the digests of the chain: the state the first
+/// segment resumed from and the state the last one suspended.
+///
+/// Panics if a segment does not commit its [SegmentDigests], or does not resume from the state the
+/// previous one suspended. An aggregation program commits the returned digests with
+/// `SegmentDigests::encode`, so that its own proof is a segment of the whole computation.
+pub fn verify_segment_chain(vkey: &[u32; 8], public_values: &[Vec<u8>]) -> SegmentDigests {
+    assert!(!public_values.is_empty(), "the segment chain is empty");
+    let mut chain: Option<SegmentDigests> = None;
+    for (index, public_values) in public_values.iter().enumerate() {
+        verify_sp1_proof(vkey, &Sha256::digest(public_values).into());
+        let digests = SegmentDigests::decode(public_values)
+            .unwrap_or_else(|| panic!("segment {} does not commit its state digests", index));
+        chain = Some(match chain {
+            None => digests,
+            Some(chain) => {
+                assert_eq!(
+                    chain.output,
+                    digests.input,
+                    "segment {} does not resume from the state of segment {}",
+                    index,
+                    index - 1
+                );
+                SegmentDigests {
+                    input: chain.input,
+                    output: digests.output,
+                }
+            }
+        });
+    }
+    chain.unwrap()
+}